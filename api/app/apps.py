@@ -20,5 +20,7 @@ class AppConfig(AppConfig):
         import app.signals.alert_runtime_sync_signals  # noqa: F401
         # Import alert cache signal handlers (alerts:address:* indexes)
         import app.signals.alert_cache_signals  # noqa: F401
+        # Import admin audit trail signal handlers
+        import app.signals.admin_audit_signals  # noqa: F401
 
         # NLP compilation runs in background tasks; no web-worker startup initialization required.
@@ -748,3 +748,10 @@ class UserWalletGroupImportSerializer(serializers.Serializer):
     payload = serializers.CharField()
     merge_mode = serializers.ChoiceField(choices=[('append', 'append'), ('replace', 'replace')], default='append')
     dedupe = serializers.BooleanField(default=True, required=False)
+
+
+class WatchlistImportSerializer(serializers.Serializer):
+    """Serializer for asynchronous bulk import into a wallet GenericGroup."""
+
+    format = serializers.ChoiceField(choices=[('csv', 'CSV'), ('json', 'JSON')])
+    payload = serializers.CharField()
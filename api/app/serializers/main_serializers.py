@@ -7,6 +7,7 @@ from django.contrib.auth import get_user_model
 from ..models.alerts import (
     AlertInstance, AlertChangeLog, AlertExecution, DefaultNetworkAlert
 )
+from ..services.alert_escalation import EscalationPolicyError, normalize_escalation_policy
 
 User = get_user_model()
 
@@ -227,6 +228,7 @@ class AlertInstanceCreateRequestSerializer(serializers.Serializer):
     target_selector = serializers.DictField()
     variable_values = serializers.DictField(required=False, default=dict)
     notification_overrides = serializers.DictField(required=False, default=dict)
+    escalation_policy = serializers.DictField(required=False, default=dict)
 
     def validate(self, data):
         from app.models.alert_templates import AlertTemplate, AlertTemplateVersion
@@ -314,6 +316,13 @@ class AlertInstanceCreateRequestSerializer(serializers.Serializer):
             if cleaned_overrides:
                 data["_notification_overrides"] = cleaned_overrides
 
+        try:
+            escalation_policy = normalize_escalation_policy(data.get("escalation_policy") or {})
+        except EscalationPolicyError as exc:
+            raise serializers.ValidationError({"escalation_policy": str(exc)}) from exc
+        if escalation_policy:
+            data["_escalation_policy"] = escalation_policy
+
         raw_kind = str(template.target_kind or "wallet").strip().lower()
         valid_alert_types = {choice[0] for choice in AlertType.choices}
         alert_type = raw_kind if raw_kind in valid_alert_types else AlertType.WALLET
@@ -0,0 +1,366 @@
+"""
+Admin audit trail.
+
+Every admin mutation of rules, watchlists, chains and config produces one
+signed record: who (actor, IP, request), what (action, resource, before/after
+snapshots) and when. Records are appended to the `admin:audit` Redis stream
+and written to the `admin_audit` DuckLake table; neither sink is ever updated
+or trimmed.
+
+The signature is an HMAC-SHA256 over the canonical JSON of every field except
+`signature` itself, so a record edited after the fact no longer verifies.
+
+Sinks:
+- Redis stream: ADMIN_AUDIT_STREAM (default `admin:audit`), recent records
+- DuckLake: ducklake.admin_audit.ekko.default.write, long-term compliance store
+"""
+
+import asyncio
+import contextvars
+import hashlib
+import hmac
+import json
+import logging
+import uuid
+from datetime import date, datetime, timezone as dt_timezone
+from typing import Any, Dict, Iterable, List, Optional
+
+from asgiref.sync import async_to_sync
+from django.conf import settings
+from django.core.serializers.json import DjangoJSONEncoder
+from django.utils import timezone
+
+logger = logging.getLogger(__name__)
+
+ADMIN_AUDIT_TABLE = "admin_audit"
+ADMIN_AUDIT_WRITE_SUBJECT = "ducklake.admin_audit.ekko.default.write"
+
+# Audited models (app label → resource_type recorded on the trail)
+AUDITED_MODELS: Dict[str, str] = {
+    "app.AlertInstance": "rule",
+    "app.AlertTemplate": "rule_template",
+    "app.GenericGroup": "watchlist",
+    "blockchain.Chain": "chain",
+    "app.BlockchainNode": "chain_node",
+    "app.NotificationChannelEndpoint": "notification_channel",
+}
+
+# Bookkeeping fields that change on every save and are not a change by themselves
+IGNORED_DIFF_FIELDS = {"updated_at", "last_modified", "modified_at"}
+
+# Field names containing any of these are redacted from snapshots
+REDACTED_FIELD_MARKERS = ("password", "secret", "token", "api_key", "key_hash")
+REDACTED = "[redacted]"
+
+SIGNED_FIELDS = (
+    "audit_id",
+    "audit_date",
+    "occurred_at",
+    "actor_id",
+    "actor_email",
+    "source_ip",
+    "request_method",
+    "request_path",
+    "action",
+    "resource_type",
+    "resource_id",
+    "changed_fields",
+    "before_json",
+    "after_json",
+    "signature_key_id",
+)
+
+_current_request: contextvars.ContextVar = contextvars.ContextVar(
+    "admin_audit_request", default=None
+)
+
+
+class AuditContextMiddleware:
+    """
+    Expose the current request to audit signal handlers.
+
+    The user is read when a record is built, not here: DRF authenticates
+    inside the view and then sets the user on the underlying Django request.
+    """
+
+    def __init__(self, get_response):
+        self.get_response = get_response
+
+    def __call__(self, request):
+        token = _current_request.set(request)
+        try:
+            return self.get_response(request)
+        finally:
+            _current_request.reset(token)
+
+
+def _format_timestamp(value: datetime) -> str:
+    """UTC timestamp with microseconds, as stored by DuckLake (naive, UTC)."""
+    if timezone.is_aware(value):
+        value = value.astimezone(dt_timezone.utc).replace(tzinfo=None)
+    return value.strftime("%Y-%m-%d %H:%M:%S.%f")
+
+
+def _source_ip(request) -> Optional[str]:
+    """Client address, read from X-Forwarded-For only behind trusted proxies.
+
+    Each of the TRUSTED_PROXY_COUNT proxies appends the address it received
+    the request from, so the client is that many entries from the right;
+    anything further left was supplied by the client and is not trusted.
+    """
+    trusted = getattr(settings, "TRUSTED_PROXY_COUNT", 0)
+    if trusted > 0:
+        forwarded = [
+            entry.strip()
+            for entry in request.META.get("HTTP_X_FORWARDED_FOR", "").split(",")
+            if entry.strip()
+        ]
+        if len(forwarded) >= trusted:
+            return forwarded[-trusted]
+    return request.META.get("REMOTE_ADDR") or None
+
+
+def _current_actor() -> Dict[str, Optional[str]]:
+    request = _current_request.get()
+    if request is None:
+        return {
+            "actor_id": None,
+            "actor_email": None,
+            "source_ip": None,
+            "request_method": None,
+            "request_path": None,
+        }
+
+    user = getattr(request, "user", None)
+    authenticated = bool(user is not None and getattr(user, "is_authenticated", False))
+    return {
+        "actor_id": str(user.pk) if authenticated else None,
+        "actor_email": getattr(user, "email", None) if authenticated else None,
+        "source_ip": _source_ip(request),
+        "request_method": request.method,
+        "request_path": request.path,
+    }
+
+
+def model_snapshot(instance) -> Dict[str, Any]:
+    """JSON-safe snapshot of a model's concrete fields, secrets redacted."""
+    data = {}
+    for field in instance._meta.concrete_fields:
+        name = field.attname
+        if any(marker in name.lower() for marker in REDACTED_FIELD_MARKERS):
+            data[name] = REDACTED
+        else:
+            data[name] = getattr(instance, name)
+    return json.loads(json.dumps(data, cls=DjangoJSONEncoder))
+
+
+def changed_fields(before: Optional[Dict[str, Any]], after: Optional[Dict[str, Any]]) -> List[str]:
+    before = before or {}
+    after = after or {}
+    return sorted(
+        key
+        for key in set(before) | set(after)
+        if key not in IGNORED_DIFF_FIELDS and before.get(key) != after.get(key)
+    )
+
+
+def _canonical_json(value: Any) -> str:
+    return json.dumps(value, sort_keys=True, separators=(",", ":"), cls=DjangoJSONEncoder)
+
+
+def _signing_payload(record: Dict[str, Any]) -> bytes:
+    payload = {}
+    for key in SIGNED_FIELDS:
+        value = record.get(key)
+        # DuckLake returns typed values; sign over the text form written
+        if isinstance(value, datetime):
+            value = _format_timestamp(value)
+        elif isinstance(value, date):
+            value = value.isoformat()
+        payload[key] = value
+    return _canonical_json(payload).encode("utf-8")
+
+
+def sign_record(record: Dict[str, Any]) -> str:
+    key = getattr(settings, "ADMIN_AUDIT_SIGNING_KEY", settings.SECRET_KEY)
+    return hmac.new(key.encode("utf-8"), _signing_payload(record), hashlib.sha256).hexdigest()
+
+
+def verify_record(record: Dict[str, Any]) -> bool:
+    """True when the record was signed with the current key and is unmodified."""
+    key_id = getattr(settings, "ADMIN_AUDIT_SIGNING_KEY_ID", "default")
+    signature = record.get("signature")
+    if not signature or record.get("signature_key_id") != key_id:
+        return False
+    return hmac.compare_digest(str(signature), sign_record(record))
+
+
+def build_audit_record(
+    *,
+    action: str,
+    resource_type: str,
+    resource_id: str,
+    before: Optional[Dict[str, Any]],
+    after: Optional[Dict[str, Any]],
+    occurred_at: Optional[datetime] = None,
+) -> Dict[str, Any]:
+    """Build and sign one audit record for the current actor."""
+    occurred_at = occurred_at or timezone.now()
+    record: Dict[str, Any] = {
+        "audit_id": str(uuid.uuid4()),
+        "audit_date": occurred_at.date().isoformat(),
+        "occurred_at": _format_timestamp(occurred_at),
+        **_current_actor(),
+        "action": action,
+        "resource_type": resource_type,
+        "resource_id": str(resource_id),
+        "changed_fields": _canonical_json(changed_fields(before, after)),
+        "before_json": _canonical_json(before) if before is not None else None,
+        "after_json": _canonical_json(after) if after is not None else None,
+        "signature_key_id": getattr(settings, "ADMIN_AUDIT_SIGNING_KEY_ID", "default"),
+    }
+    record["signature"] = sign_record(record)
+    return record
+
+
+def _redis_client():
+    import redis
+
+    return redis.from_url(getattr(settings, "REDIS_URL", "redis://localhost:6379"), decode_responses=True)
+
+
+def _stream_name() -> str:
+    return getattr(settings, "ADMIN_AUDIT_STREAM", "admin:audit")
+
+
+def append_audit_record(record: Dict[str, Any]) -> None:
+    """
+    Append a record to both sinks.
+
+    Failures are logged and never raised: the admin change has already been
+    committed by the time this runs.
+    """
+    if not getattr(settings, "ADMIN_AUDIT_ENABLED", True):
+        return
+
+    try:
+        fields = {key: "" if value is None else str(value) for key, value in record.items()}
+        _redis_client().xadd(_stream_name(), fields)
+    except Exception as exc:
+        logger.error("Failed to append audit record %s to Redis stream: %s", record["audit_id"], exc)
+
+    row = dict(record, ingested_at=_format_timestamp(timezone.now()))
+
+    async def _publish():
+        from app.services.nats_service import NATSService
+
+        # Fresh client per call: asyncio.run creates a new loop each time
+        stub_mode = not getattr(settings, "NATS_ENABLED", True)
+        service = NATSService(stub_mode=stub_mode)
+        try:
+            await service.connect()
+            result = await service.publish(ADMIN_AUDIT_WRITE_SUBJECT, row)
+            return result.get("success", False)
+        finally:
+            await service.disconnect()
+
+    try:
+        if not asyncio.run(_publish()):
+            logger.error("Failed to write audit record %s to DuckLake", record["audit_id"])
+    except Exception as exc:
+        logger.error("Failed to write audit record %s to DuckLake: %s", record["audit_id"], exc)
+
+
+def _escape_sql_literal(value: str) -> str:
+    return value.replace("'", "''")
+
+
+def _matches(record: Dict[str, Any], filters: Dict[str, Optional[str]]) -> bool:
+    return all(record.get(key) == value for key, value in filters.items() if value)
+
+
+def _with_verification(records: Iterable[Dict[str, Any]]) -> List[Dict[str, Any]]:
+    results = []
+    for record in records:
+        record = dict(record)
+        record["signature_valid"] = verify_record(record)
+        for key in ("occurred_at", "ingested_at"):
+            if isinstance(record.get(key), datetime):
+                record[key] = _format_timestamp(record[key])
+        if isinstance(record.get("audit_date"), date):
+            record["audit_date"] = record["audit_date"].isoformat()
+        results.append(record)
+    return results
+
+
+def query_audit_records(
+    *,
+    resource_type: Optional[str] = None,
+    resource_id: Optional[str] = None,
+    actor_id: Optional[str] = None,
+    action: Optional[str] = None,
+    start: Optional[datetime] = None,
+    end: Optional[datetime] = None,
+    limit: int = 100,
+    offset: int = 0,
+    source: str = "ducklake",
+) -> List[Dict[str, Any]]:
+    """
+    Query the audit trail, newest first, with `signature_valid` on each record.
+
+    `source="stream"` reads the Redis stream instead, which includes records
+    DuckLake has not flushed yet.
+    """
+    limit = min(max(1, limit), 1000)
+    offset = max(0, offset)
+    filters = {
+        "resource_type": resource_type,
+        "resource_id": resource_id,
+        "actor_id": actor_id,
+        "action": action,
+    }
+
+    if source == "stream":
+        start_text = _format_timestamp(start) if start else None
+        end_text = _format_timestamp(end) if end else None
+        matched = []
+        for _, fields in _redis_client().xrevrange(_stream_name(), count=10_000):
+            record = {key: (value if value != "" else None) for key, value in fields.items()}
+            if not _matches(record, filters):
+                continue
+            if start_text and record["occurred_at"] < start_text:
+                continue
+            if end_text and record["occurred_at"] > end_text:
+                continue
+            matched.append(record)
+        return _with_verification(matched[offset: offset + limit])
+
+    where_clauses = [
+        f"{column} = '{_escape_sql_literal(value)}'" for column, value in filters.items() if value
+    ]
+    if start:
+        where_clauses.append(f"audit_date >= CAST('{start.date().isoformat()}' AS DATE)")
+        where_clauses.append(f"occurred_at >= '{_format_timestamp(start)}'")
+    if end:
+        where_clauses.append(f"audit_date <= CAST('{end.date().isoformat()}' AS DATE)")
+        where_clauses.append(f"occurred_at <= '{_format_timestamp(end)}'")
+    where_sql = f"WHERE {' AND '.join(where_clauses)}" if where_clauses else ""
+
+    query = f"""
+        SELECT *
+        FROM {ADMIN_AUDIT_TABLE}
+        {where_sql}
+        ORDER BY occurred_at DESC
+        LIMIT {limit} OFFSET {offset}
+    """
+
+    async def _run():
+        from app.services.ducklake_client import DuckLakeClient
+
+        client = DuckLakeClient()
+        try:
+            return await client.query_rows(query=query, table=ADMIN_AUDIT_TABLE)
+        finally:
+            await client.close()
+
+    return _with_verification(async_to_sync(_run)())
@@ -0,0 +1,151 @@
+"""
+Alert escalation policies and notification acknowledgement.
+
+A policy is stored per alert instance under the reserved
+`__escalation_policy` template param and projected into the
+`alerts:instance:{id}` snapshot. The notification-router arms one timer per
+step through the alert scheduler; each step re-sends the notification to its
+channels unless the notification was acknowledged first.
+
+Acknowledgements are published to `alerts.ack`, where the scheduler records
+them and suppresses any pending escalation timers.
+"""
+
+import asyncio
+import json
+import logging
+from typing import Any, Optional
+
+from django.conf import settings
+from django.utils import timezone
+
+from app.services.alert_runtime_projection import _redis_client
+from app.services.nats_service import NATSService
+
+logger = logging.getLogger(__name__)
+
+
+ALERT_ACK_SUBJECT = "alerts.ack"
+PENDING_ESCALATION_PREFIX = "alerts:escalation:"
+
+ESCALATION_CHANNELS = ("webhook", "websocket", "telegram")
+MAX_ESCALATION_STEPS = 5
+MIN_STEP_DELAY_SECS = 60
+# Pending escalations expire after 7 days (retention-policy ALERTS_ESCALATION)
+MAX_STEP_DELAY_SECS = 7 * 24 * 3600
+
+
+class EscalationPolicyError(ValueError):
+    pass
+
+
+def normalize_escalation_policy(raw: Any) -> dict:
+    """
+    Validate an escalation policy and return its canonical form.
+
+    Shape: {"steps": [{"after_secs": 600, "channels": ["telegram"]}, ...]}.
+    `after_secs` is measured from the original delivery, so steps must be
+    strictly increasing. An empty dict (or no steps) disables escalation.
+    """
+    if raw is None:
+        return {}
+    if not isinstance(raw, dict):
+        raise EscalationPolicyError("escalation_policy must be an object")
+
+    steps = raw.get("steps") or []
+    if not isinstance(steps, list):
+        raise EscalationPolicyError("steps must be a list")
+    if len(steps) > MAX_ESCALATION_STEPS:
+        raise EscalationPolicyError(f"at most {MAX_ESCALATION_STEPS} escalation steps are allowed")
+
+    cleaned_steps = []
+    previous_after = 0
+    for index, step in enumerate(steps):
+        if not isinstance(step, dict):
+            raise EscalationPolicyError(f"steps[{index}] must be an object")
+
+        after_secs = step.get("after_secs")
+        if isinstance(after_secs, bool) or not isinstance(after_secs, int):
+            raise EscalationPolicyError(f"steps[{index}].after_secs must be an integer")
+        if after_secs < MIN_STEP_DELAY_SECS or after_secs > MAX_STEP_DELAY_SECS:
+            raise EscalationPolicyError(
+                f"steps[{index}].after_secs must be between {MIN_STEP_DELAY_SECS} and {MAX_STEP_DELAY_SECS}"
+            )
+        if after_secs <= previous_after:
+            raise EscalationPolicyError(f"steps[{index}].after_secs must be greater than the previous step")
+        previous_after = after_secs
+
+        channels = step.get("channels")
+        if not isinstance(channels, list) or not channels:
+            raise EscalationPolicyError(f"steps[{index}].channels must be a non-empty list")
+        cleaned_channels: list[str] = []
+        for channel in channels:
+            name = str(channel or "").strip().lower()
+            if name not in ESCALATION_CHANNELS:
+                raise EscalationPolicyError(
+                    f"steps[{index}].channels: unsupported channel {channel!r} "
+                    f"(expected one of {', '.join(ESCALATION_CHANNELS)})"
+                )
+            if name not in cleaned_channels:
+                cleaned_channels.append(name)
+
+        cleaned_steps.append({"after_secs": after_secs, "channels": cleaned_channels})
+
+    return {"steps": cleaned_steps} if cleaned_steps else {}
+
+
+def get_pending_escalation(notification_id: str) -> Optional[dict]:
+    """Return the router's pending escalation record for a notification, if any."""
+    try:
+        raw = _redis_client().get(f"{PENDING_ESCALATION_PREFIX}{notification_id}")
+    except Exception as e:
+        logger.error(f"Failed to read pending escalation {notification_id}: {e}")
+        return None
+    if not raw:
+        return None
+    try:
+        parsed = json.loads(raw)
+    except json.JSONDecodeError:
+        return None
+    return parsed if isinstance(parsed, dict) else None
+
+
+def build_ack_message(notification_id: str, user_id: str) -> dict:
+    return {
+        "schema_version": "alert_ack_v1",
+        "notification_id": str(notification_id),
+        "user_id": str(user_id),
+        "acked_at": timezone.now().isoformat(),
+        "source": "api",
+    }
+
+
+def publish_alert_ack_sync(notification_id: str, user_id: str) -> Optional[bool]:
+    """
+    Publish an acknowledgement to `alerts.ack` and wait for the scheduler's reply.
+
+    Returns True when this call recorded the acknowledgement, False when the
+    notification was already acknowledged, and None when the scheduler could
+    not be reached.
+    """
+    message = build_ack_message(notification_id, user_id)
+
+    async def _request():
+        stub_mode = not getattr(settings, 'NATS_ENABLED', True)
+        service = NATSService(stub_mode=stub_mode)
+        try:
+            await service.connect()
+            result = await service.request(ALERT_ACK_SUBJECT, message)
+            if not result.get('success'):
+                return None
+            if result.get('stub'):
+                return True
+            return bool(result.get('reply', {}).get('acknowledged'))
+        finally:
+            await service.disconnect()
+
+    try:
+        return asyncio.run(_request())
+    except Exception as e:
+        logger.error(f"Failed to publish alert ack for {notification_id}: {e}")
+        return None
@@ -16,6 +16,8 @@ INSTANCE_KEY_PREFIX = "alerts:instance:"
 TEMPLATE_KEY_PREFIX = "alerts:template:"
 EXECUTABLE_KEY_PREFIX = "alerts:executable:"
 NOTIFICATION_OVERRIDE_KEY = "__notification_overrides"
+ESCALATION_POLICY_KEY = "__escalation_policy"
+RESERVED_TEMPLATE_PARAM_KEYS = (NOTIFICATION_OVERRIDE_KEY, ESCALATION_POLICY_KEY)
 
 SCHEDULE_PERIODIC_ZSET = "alerts:schedule:periodic"
 SCHEDULE_ONE_TIME_ZSET = "alerts:schedule:one_time"
@@ -276,8 +278,10 @@ class AlertRuntimeProjection:
             action = {}
 
         variable_values = template_params if isinstance(template_params, dict) else {}
-        if isinstance(template_params, dict) and NOTIFICATION_OVERRIDE_KEY in template_params:
-            variable_values = {k: v for k, v in template_params.items() if k != NOTIFICATION_OVERRIDE_KEY}
+        if any(key in variable_values for key in RESERVED_TEMPLATE_PARAM_KEYS):
+            variable_values = {
+                k: v for k, v in variable_values.items() if k not in RESERVED_TEMPLATE_PARAM_KEYS
+            }
 
         snapshot = {
             "instance_id": instance_id,
@@ -295,6 +299,9 @@ class AlertRuntimeProjection:
             "notification_template": notification_template,
             "action": action,
         }
+        escalation_policy = template_params.get(ESCALATION_POLICY_KEY) if isinstance(template_params, dict) else None
+        if isinstance(escalation_policy, dict) and escalation_policy.get("steps"):
+            snapshot["escalation_policy"] = escalation_policy
 
         with self._redis.pipeline() as pipe:
             self._apply_instance_index_updates(
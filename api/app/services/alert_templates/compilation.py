@@ -603,6 +603,7 @@ def compile_template_to_executable(
     method_selectors = evm_hints.get("method_selector_any_of")
     topic0s = evm_hints.get("event_topic0_any_of")
     addrs = evm_hints.get("contract_addresses_any_of")
+    exclude_contexts = evm_hints.get("exclude_category_contexts")
 
     trigger_pruning = {
         "evm": {
@@ -620,6 +621,8 @@ def compile_template_to_executable(
                 "name_any_of": [],
                 "required": bool(topic0s),
             },
+            # e.g. ["internal_exchange_ops"] to skip cold/hot wallet moves of labeled entities
+            "exclude_category_contexts": [c for c in (exclude_contexts or []) if isinstance(c, str)],
         }
     }
 
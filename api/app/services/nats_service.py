@@ -155,6 +155,33 @@ class NATSService:
             logger.error(f"Failed to publish to {subject}: {e}")
             return {'success': False, 'error': str(e)}
 
+    async def request(self, subject: str, data: Dict[str, Any], timeout: float = 5.0) -> Dict[str, Any]:
+        """
+        Send a request and wait for a JSON reply.
+
+        Returns:
+            Result with success status and the decoded `reply` payload
+        """
+        payload = json.dumps(data).encode('utf-8')
+
+        if self._stub_mode:
+            logger.info(f"[STUB] NATS request to {subject}: {json.dumps(data, indent=2)}")
+            return {'success': True, 'stub': True, 'reply': {}}
+
+        if not self.is_connected:
+            await self.connect()
+
+        if not self.is_connected:
+            logger.error(f"Cannot request {subject}: not connected to NATS")
+            return {'success': False, 'error': 'Not connected'}
+
+        try:
+            msg = await self._nc.request(subject, payload, timeout=timeout)
+            return {'success': True, 'reply': json.loads(msg.data.decode('utf-8') or '{}')}
+        except Exception as e:
+            logger.error(f"NATS request to {subject} failed: {e}")
+            return {'success': False, 'error': str(e)}
+
     async def subscribe(self, subject: str, callback: Callable) -> bool:
         """
         Subscribe to NATS subject.
@@ -8,6 +8,9 @@ Redis Structure (written by provider-status-common):
     provider:status:{provider_id}       - JSON: ProviderStatus
     provider:subscription:{id}:{chain}  - JSON: SubscriptionStatus
     provider:errors:{provider_id}       - List: ErrorRecord JSON
+
+Block checkpoints (written by eth_raw_transactions via block-checkpoint):
+    checkpoint:block:{network}-{subnet} - JSON: BlockCheckpointV1
 """
 import json
 import logging
@@ -28,6 +31,7 @@ class ProviderStatusService:
 
     KEY_REGISTRY = "provider:registry"
     KEY_PREFIX_STATUS = "provider:status"
+    KEY_PREFIX_CHECKPOINT = "checkpoint:block"
 
     def __init__(self):
         """Initialize Redis connection using Django cache settings."""
@@ -79,3 +83,20 @@ class ProviderStatusService:
             if status:
                 statuses.append(status)
         return statuses
+
+    def get_block_checkpoint(self, chain_id: str) -> Optional[dict]:
+        """Get the last fully processed block for a chain.
+
+        Args:
+            chain_id: Chain identifier in node key form ("ethereum-mainnet")
+
+        Returns:
+            BlockCheckpointV1 dict or None if the chain has no checkpoint yet
+        """
+        try:
+            key = f"{self.KEY_PREFIX_CHECKPOINT}:{chain_id}"
+            data = self.redis_client.get(key)
+            return json.loads(data) if data else None
+        except Exception as e:
+            logger.warning(f"Failed to get block checkpoint for {chain_id}: {e}")
+            return None
@@ -0,0 +1,237 @@
+"""
+Bulk watchlist import/export for wallet groups.
+
+Imports accept CSV (header with `member_key` or `network,subnet,address`, plus
+optional `label` and `tags`) or JSON (a list, or an object with a `wallets` /
+`members` list). Every row is validated on its own:
+- key format (`{NETWORK}:{subnet}:{address}`)
+- EIP-55 checksum for mixed-case EVM addresses
+- duplicates within the file and against the group's current members
+- chain support: the network/subnet must be an enabled Chain / SubChain
+
+Imports run as a background task (`app.tasks.watchlist_tasks.import_watchlist`).
+Job state is cached under `watchlist:import:{user_id}:{job_id}` and progress is
+pushed over NATS ws.events:
+- watchlist.import.status   - {group_id, total, processed, added, duplicates, invalid}
+- watchlist.import.complete - {result: job state}
+- watchlist.import.error    - {code, message}
+"""
+
+import csv
+import io
+import json
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterable, List, Optional, Set, Tuple
+
+from django.conf import settings
+from django.core.cache import cache
+
+from app.models.groups import normalize_network_subnet_address_key
+from app.services.group_service import AlertValidationService
+from app.utils.evm_address import has_valid_checksum, is_evm_address
+
+MAX_IMPORT_ROWS = 50_000
+IMPORT_CHUNK_SIZE = 500
+# Row errors kept in the job state; the full count is always reported
+MAX_REPORTED_ERRORS = 1_000
+
+EXPORT_COLUMNS = ['member_key', 'network', 'subnet', 'address', 'label', 'tags', 'added_at']
+
+
+@dataclass
+class WatchlistRow:
+    row_number: int
+    member_key: str
+    label: str = ''
+    tags: List[str] = field(default_factory=list)
+
+
+@dataclass
+class WatchlistValidation:
+    valid: List[WatchlistRow] = field(default_factory=list)
+    duplicates: List[str] = field(default_factory=list)
+    errors: List[Dict[str, Any]] = field(default_factory=list)
+
+
+def _cell(value) -> str:
+    # csv.DictReader uses None for missing columns; treat it as empty.
+    if value is None:
+        return ''
+    return str(value).strip()
+
+
+def _tags(value) -> List[str]:
+    if isinstance(value, list):
+        return [str(tag).strip() for tag in value if str(tag).strip()]
+    return [tag.strip() for tag in _cell(value).replace('|', ';').split(';') if tag.strip()]
+
+
+def _row_from_mapping(row_number: int, item: Dict[str, Any]) -> WatchlistRow:
+    member_key = _cell(item.get('member_key'))
+    if not member_key:
+        network = _cell(item.get('network'))
+        subnet = _cell(item.get('subnet'))
+        address = _cell(item.get('address'))
+        if network and subnet and address:
+            member_key = f"{network}:{subnet}:{address}"
+    return WatchlistRow(
+        row_number=row_number,
+        member_key=member_key,
+        label=_cell(item.get('label')),
+        tags=_tags(item.get('tags')),
+    )
+
+
+def parse_watchlist_payload(fmt: str, payload: str) -> List[WatchlistRow]:
+    """
+    Parse an import file into rows.
+
+    Raises:
+        ValueError: If the file itself is malformed or exceeds MAX_IMPORT_ROWS
+    """
+    rows: List[WatchlistRow] = []
+
+    if fmt == 'json':
+        try:
+            parsed = json.loads(payload)
+        except json.JSONDecodeError as exc:
+            raise ValueError(f"Invalid JSON payload: {exc}") from exc
+
+        if isinstance(parsed, dict):
+            parsed = parsed.get('wallets', parsed.get('members', []))
+        if not isinstance(parsed, list):
+            raise ValueError("JSON payload must be a list or an object with a 'wallets' list")
+
+        for idx, item in enumerate(parsed, start=1):
+            if isinstance(item, str):
+                rows.append(WatchlistRow(row_number=idx, member_key=item.strip()))
+            elif isinstance(item, dict):
+                rows.append(_row_from_mapping(idx, item))
+            else:
+                rows.append(WatchlistRow(row_number=idx, member_key=''))
+    else:
+        reader = csv.DictReader(io.StringIO(payload))
+        if not reader.fieldnames:
+            raise ValueError("CSV payload must include a header row")
+        for idx, item in enumerate(reader, start=1):
+            rows.append(_row_from_mapping(idx, item))
+
+    if len(rows) > MAX_IMPORT_ROWS:
+        raise ValueError(f"Import is limited to {MAX_IMPORT_ROWS} rows (got {len(rows)})")
+    return rows
+
+
+def supported_chain_keys() -> Set[str]:
+    """`{NETWORK}:{subnet}` pairs of enabled chains, keyed by native token symbol."""
+    from blockchain.models import SubChain
+
+    keys = set()
+    subchains = SubChain.objects.filter(enabled=True, chain__enabled=True).select_related('chain')
+    for sub_chain in subchains:
+        network = str(sub_chain.chain.native_token or '').upper()
+        subnet = str(sub_chain.name or '').lower()
+        if network and subnet:
+            keys.add(f"{network}:{subnet}")
+    return keys
+
+
+def _row_error(row: WatchlistRow, code: str, message: str) -> Dict[str, Any]:
+    return {'row_number': row.row_number, 'member_key': row.member_key, 'code': code, 'error': message}
+
+
+def validate_watchlist_rows(
+    rows: Iterable[WatchlistRow],
+    *,
+    existing_keys: Iterable[str],
+    supported_chains: Set[str],
+) -> WatchlistValidation:
+    """Split rows into valid (normalized) rows, duplicates and per-row errors."""
+    result = WatchlistValidation()
+    existing = set(existing_keys)
+    seen: Set[str] = set()
+
+    for row in rows:
+        if not row.member_key:
+            result.errors.append(_row_error(row, 'missing_key', 'Missing wallet key'))
+            continue
+
+        normalized = normalize_network_subnet_address_key(row.member_key)
+        try:
+            AlertValidationService.validate_targets('wallet', [normalized])
+        except Exception:
+            result.errors.append(_row_error(row, 'invalid_format', 'Expected {NETWORK}:{subnet}:{address}'))
+            continue
+
+        network, subnet, _ = normalized.split(':', 2)
+        raw_address = row.member_key.strip().split(':', 2)[2].strip()
+        if raw_address.lower().startswith('0x'):
+            if not is_evm_address(raw_address):
+                result.errors.append(_row_error(row, 'invalid_address', 'EVM addresses must be 0x + 40 hex characters'))
+                continue
+            if not has_valid_checksum(raw_address):
+                result.errors.append(_row_error(row, 'bad_checksum', 'EIP-55 checksum does not match'))
+                continue
+
+        if f"{network}:{subnet}" not in supported_chains:
+            result.errors.append(_row_error(row, 'unsupported_chain', f"Chain {network}:{subnet} is not supported"))
+            continue
+
+        if normalized in seen or normalized in existing:
+            result.duplicates.append(normalized)
+            continue
+        seen.add(normalized)
+
+        result.valid.append(
+            WatchlistRow(row_number=row.row_number, member_key=normalized, label=row.label, tags=row.tags)
+        )
+
+    return result
+
+
+def export_watchlist(group, fmt: str) -> Tuple[str, str, str]:
+    """
+    Render a wallet group's members.
+
+    Returns:
+        (body, content_type, filename)
+    """
+    members = (group.member_data or {}).get('members', {}) or {}
+    records = []
+    for member_key in sorted(members):
+        meta = members[member_key] or {}
+        parts = member_key.split(':', 2)
+        network, subnet, address = parts if len(parts) == 3 else ('', '', member_key)
+        records.append({
+            'member_key': member_key,
+            'network': network,
+            'subnet': subnet,
+            'address': address,
+            'label': meta.get('label', '') or '',
+            'tags': list(meta.get('tags', []) or []),
+            'added_at': meta.get('added_at', '') or '',
+        })
+
+    filename = f"watchlist_{group.id}.{fmt}"
+    if fmt == 'json':
+        body = json.dumps({'group_id': str(group.id), 'name': group.name, 'wallets': records}, indent=2)
+        return body, 'application/json', filename
+
+    buffer = io.StringIO()
+    writer = csv.DictWriter(buffer, fieldnames=EXPORT_COLUMNS)
+    writer.writeheader()
+    for record in records:
+        writer.writerow({**record, 'tags': ';'.join(record['tags'])})
+    return buffer.getvalue(), 'text/csv', filename
+
+
+def import_job_cache_key(user_id: str, job_id: str) -> str:
+    return f"watchlist:import:{user_id}:{job_id}"
+
+
+def save_import_job(user_id: str, job_id: str, state: Dict[str, Any]) -> None:
+    ttl_secs = int(getattr(settings, 'WATCHLIST_IMPORT_JOB_TTL_SECS', 86400))
+    cache.set(import_job_cache_key(user_id, job_id), state, timeout=ttl_secs)
+
+
+def get_import_job(user_id: str, job_id: str) -> Optional[Dict[str, Any]]:
+    return cache.get(import_job_cache_key(user_id, job_id))
@@ -0,0 +1,92 @@
+"""
+Django signals for the admin audit trail.
+
+Every create, update and delete of an audited model (see
+`app.services.admin_audit.AUDITED_MODELS`) appends a signed audit record once
+the surrounding transaction commits.
+
+Behavior:
+- pre_save: snapshot the stored row so the record carries the before state
+- post_save: record `create`, or `update` when a non-bookkeeping field changed
+- post_delete: record `delete` with the last stored state
+"""
+
+import logging
+from functools import partial
+
+from django.conf import settings
+from django.db import transaction
+from django.db.models.signals import post_delete, post_save, pre_save
+
+from app.services.admin_audit import (
+    AUDITED_MODELS,
+    append_audit_record,
+    build_audit_record,
+    changed_fields,
+    model_snapshot,
+)
+
+logger = logging.getLogger(__name__)
+
+
+def _enabled():
+    return getattr(settings, "ADMIN_AUDIT_ENABLED", True)
+
+
+def _record(resource_type, instance, action, before, after):
+    try:
+        record = build_audit_record(
+            action=action,
+            resource_type=resource_type,
+            resource_id=str(instance.pk),
+            before=before,
+            after=after,
+        )
+        transaction.on_commit(partial(append_audit_record, record))
+    except Exception as e:
+        # Signal handlers should NOT raise exceptions that break model operations
+        logger.error(f"Error auditing {action} of {resource_type} {instance.pk}: {e}")
+
+
+def capture_before_state(sender, instance, raw=False, **kwargs):
+    if raw or instance.pk is None or not _enabled():
+        return
+    try:
+        stored = sender._default_manager.filter(pk=instance.pk).first()
+        instance._admin_audit_before = model_snapshot(stored) if stored else None
+    except Exception as e:
+        logger.error(f"Error snapshotting {sender.__name__} {instance.pk} for audit: {e}")
+
+
+def audit_save(sender, instance, created, raw=False, resource_type=None, **kwargs):
+    if raw or not _enabled():
+        return
+    before = None if created else getattr(instance, "_admin_audit_before", None)
+    after = model_snapshot(instance)
+    if not created and before is not None and not changed_fields(before, after):
+        return
+    _record(resource_type, instance, "create" if created else "update", before, after)
+
+
+def audit_delete(sender, instance, resource_type=None, **kwargs):
+    if not _enabled():
+        return
+    _record(resource_type, instance, "delete", model_snapshot(instance), None)
+
+
+for _label, _resource_type in AUDITED_MODELS.items():
+    pre_save.connect(
+        capture_before_state, sender=_label, dispatch_uid=f"admin_audit_pre_save:{_label}"
+    )
+    post_save.connect(
+        partial(audit_save, resource_type=_resource_type),
+        sender=_label,
+        weak=False,
+        dispatch_uid=f"admin_audit_post_save:{_label}",
+    )
+    post_delete.connect(
+        partial(audit_delete, resource_type=_resource_type),
+        sender=_label,
+        weak=False,
+        dispatch_uid=f"admin_audit_post_delete:{_label}",
+    )
@@ -2,8 +2,10 @@
 Django 6.0 Tasks for async background processing.
 
 Phase 1: Async NLP parse pipeline.
+Watchlist bulk import.
 """
 
 from .nlp_tasks import parse_nl_description
+from .watchlist_tasks import import_watchlist
 
-__all__ = ['parse_nl_description']
+__all__ = ['parse_nl_description', 'import_watchlist']
@@ -0,0 +1,141 @@
+"""
+Watchlist Import Tasks
+
+Bulk wallet-group import pipeline:
+1. POST /api/groups/{id}/import/ → 202 + job_id (file parsed, row count checked)
+2. Task validates every row (format, checksum, chain support, duplicates)
+3. Valid rows are added in chunks of IMPORT_CHUNK_SIZE, each chunk synced to Redis
+4. Progress events published to NATS ws.events; job state cached for polling via
+   GET /api/groups/{id}/import/{job_id}/
+"""
+
+import logging
+from typing import Any, Dict
+
+from django.utils import timezone
+
+from app.tasks.tasking import task
+from app.services.watchlist_bulk import (
+    IMPORT_CHUNK_SIZE,
+    MAX_REPORTED_ERRORS,
+    parse_watchlist_payload,
+    save_import_job,
+    supported_chain_keys,
+    validate_watchlist_rows,
+)
+
+logger = logging.getLogger(__name__)
+
+
+def publish_progress(user_id: str, event_type: str, job_id: str, payload: Dict[str, Any]) -> bool:
+    """Publish progress event to NATS ws.events."""
+    from app.services.nats_service import publish_ws_event_sync
+
+    return publish_ws_event_sync(
+        user_id=user_id,
+        event_type=event_type,
+        payload=payload,
+        job_id=job_id,
+    )
+
+
+@task(queue_name="default")
+def import_watchlist(
+    user_id: str,
+    group_id: str,
+    job_id: str,
+    fmt: str,
+    payload: str,
+) -> Dict[str, Any]:
+    """
+    Import wallets into a wallet group.
+
+    Events Published:
+        - watchlist.import.status: Progress (total, processed, added, duplicates, invalid)
+        - watchlist.import.complete: Import finished (result: job state)
+        - watchlist.import.error: Import failed (code, message)
+    """
+    from app.models.groups import GenericGroup
+    from app.services.group_service import GroupService
+
+    state: Dict[str, Any] = {
+        'job_id': job_id,
+        'group_id': group_id,
+        'status': 'running',
+        'total': 0,
+        'processed': 0,
+        'added': 0,
+        'duplicates': 0,
+        'invalid': 0,
+        'errors': [],
+        'started_at': timezone.now().isoformat(),
+        'completed_at': None,
+    }
+    save_import_job(user_id, job_id, state)
+
+    def progress() -> None:
+        save_import_job(user_id, job_id, state)
+        publish_progress(user_id, "watchlist.import.status", job_id, {
+            key: state[key]
+            for key in ('group_id', 'total', 'processed', 'added', 'duplicates', 'invalid')
+        })
+
+    try:
+        rows = parse_watchlist_payload(fmt, payload)
+        group = GenericGroup.objects.get(id=group_id)
+        state['total'] = len(rows)
+
+        validation = validate_watchlist_rows(
+            rows,
+            existing_keys=(group.member_data or {}).get('members', {}).keys(),
+            supported_chains=supported_chain_keys(),
+        )
+        state['duplicates'] = len(validation.duplicates)
+        state['invalid'] = len(validation.errors)
+        state['errors'] = validation.errors[:MAX_REPORTED_ERRORS]
+        # Rejected rows count as processed up front; valid rows as their chunk lands
+        state['processed'] = state['duplicates'] + state['invalid']
+        progress()
+
+        service = GroupService()
+        for start in range(0, len(validation.valid), IMPORT_CHUNK_SIZE):
+            chunk = validation.valid[start:start + IMPORT_CHUNK_SIZE]
+            state['added'] += service.add_members(
+                group.id,
+                [
+                    {
+                        'key': row.member_key,
+                        'label': row.label,
+                        'tags': row.tags,
+                        'added_by': user_id,
+                        'metadata': {'source': 'import', 'import_job_id': job_id},
+                    }
+                    for row in chunk
+                ],
+                sync_redis=True,
+            )
+            state['processed'] += len(chunk)
+            progress()
+
+        state['status'] = 'completed'
+        state['completed_at'] = timezone.now().isoformat()
+        save_import_job(user_id, job_id, state)
+        publish_progress(user_id, "watchlist.import.complete", job_id, {'result': state})
+
+        logger.info(
+            f"Watchlist import completed: job_id={job_id}, group_id={group_id}, "
+            f"added={state['added']}, invalid={state['invalid']}, duplicates={state['duplicates']}"
+        )
+        return state
+
+    except Exception as e:
+        logger.error(f"Watchlist import failed: job_id={job_id}, error={e}")
+        state['status'] = 'failed'
+        state['error'] = str(e)
+        state['completed_at'] = timezone.now().isoformat()
+        save_import_job(user_id, job_id, state)
+        publish_progress(user_id, "watchlist.import.error", job_id, {
+            'code': 'import_failed',
+            'message': str(e),
+        })
+        raise
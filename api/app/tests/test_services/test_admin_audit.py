@@ -0,0 +1,83 @@
+import json
+from unittest.mock import patch
+
+from django.test import RequestFactory, TestCase, override_settings
+
+from app.services.admin_audit import (
+    AuditContextMiddleware,
+    _current_actor,
+    build_audit_record,
+    verify_record,
+)
+from blockchain.models import Chain
+
+
+@override_settings(ADMIN_AUDIT_ENABLED=True, ADMIN_AUDIT_SIGNING_KEY="audit-test-key")
+class TestAdminAuditTrail(TestCase):
+    def test_chain_mutations_append_signed_records(self):
+        with patch("app.signals.admin_audit_signals.append_audit_record") as append:
+            with self.captureOnCommitCallbacks(execute=True):
+                chain = Chain.objects.create(name="ethereum", display_name="Ethereum", chain_id=1)
+            with self.captureOnCommitCallbacks(execute=True):
+                chain.save()  # no field changed: not audited
+            with self.captureOnCommitCallbacks(execute=True):
+                chain.enabled = False
+                chain.save()
+            with self.captureOnCommitCallbacks(execute=True):
+                chain.delete()
+
+        records = [call.args[0] for call in append.call_args_list]
+        assert [r["action"] for r in records] == ["create", "update", "delete"]
+        assert all(r["resource_type"] == "chain" for r in records)
+
+        update = records[1]
+        assert json.loads(update["changed_fields"]) == ["enabled"]
+        assert json.loads(update["before_json"])["enabled"] is True
+        assert json.loads(update["after_json"])["enabled"] is False
+        assert records[0]["before_json"] is None
+        assert records[2]["after_json"] is None
+        assert all(verify_record(r) for r in records)
+
+    def test_signature_detects_tampering_and_records_actor(self):
+        user = type("User", (), {"pk": 7, "email": "ops@example.com", "is_authenticated": True})()
+        request = RequestFactory().patch("/api/chains/1/", REMOTE_ADDR="10.0.0.5")
+        request.user = user
+
+        def view(_request):
+            return build_audit_record(
+                action="update",
+                resource_type="chain",
+                resource_id="1",
+                before={"rpc_url": "https://a"},
+                after={"rpc_url": "https://b"},
+            )
+
+        record = AuditContextMiddleware(view)(request)
+        assert record["actor_id"] == "7"
+        assert record["actor_email"] == "ops@example.com"
+        assert record["source_ip"] == "10.0.0.5"
+        assert record["request_path"] == "/api/chains/1/"
+        assert verify_record(record)
+
+        tampered = dict(record, after_json=json.dumps({"rpc_url": "https://evil"}))
+        assert not verify_record(tampered)
+        with override_settings(ADMIN_AUDIT_SIGNING_KEY_ID="rotated"):
+            assert not verify_record(record)
+
+    def test_source_ip_trusts_forwarded_for_only_behind_configured_proxies(self):
+        def source_ip():
+            request = RequestFactory().get(
+                "/api/chains/",
+                REMOTE_ADDR="10.0.0.2",
+                HTTP_X_FORWARDED_FOR="6.6.6.6, 203.0.113.9, 10.0.0.1",
+            )
+            return AuditContextMiddleware(lambda _request: _current_actor())(request)["source_ip"]
+
+        with override_settings(TRUSTED_PROXY_COUNT=0):
+            assert source_ip() == "10.0.0.2"
+        with override_settings(TRUSTED_PROXY_COUNT=1):
+            assert source_ip() == "10.0.0.1"
+        with override_settings(TRUSTED_PROXY_COUNT=2):
+            assert source_ip() == "203.0.113.9"
+        with override_settings(TRUSTED_PROXY_COUNT=4):
+            assert source_ip() == "10.0.0.2"
@@ -0,0 +1,37 @@
+import pytest
+
+from app.services.alert_escalation import EscalationPolicyError, normalize_escalation_policy
+
+
+def test_normalize_escalation_policy_canonicalizes_channels():
+    policy = normalize_escalation_policy({
+        "steps": [
+            {"after_secs": 600, "channels": ["Telegram", "telegram"]},
+            {"after_secs": 1800, "channels": ["webhook", "websocket"]},
+        ]
+    })
+
+    assert policy == {
+        "steps": [
+            {"after_secs": 600, "channels": ["telegram"]},
+            {"after_secs": 1800, "channels": ["webhook", "websocket"]},
+        ]
+    }
+    assert normalize_escalation_policy({"steps": []}) == {}
+
+
+@pytest.mark.parametrize(
+    "raw",
+    [
+        {"steps": [{"after_secs": 10, "channels": ["telegram"]}]},
+        {"steps": [{"after_secs": 600, "channels": []}]},
+        {"steps": [{"after_secs": 600, "channels": ["sms"]}]},
+        {"steps": [
+            {"after_secs": 600, "channels": ["telegram"]},
+            {"after_secs": 600, "channels": ["webhook"]},
+        ]},
+    ],
+)
+def test_normalize_escalation_policy_rejects_invalid_steps(raw):
+    with pytest.raises(EscalationPolicyError):
+        normalize_escalation_policy(raw)
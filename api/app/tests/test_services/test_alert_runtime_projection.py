@@ -8,6 +8,7 @@ import pytest
 
 from app.services.alert_runtime_projection import (
     AlertRuntimeProjection,
+    ESCALATION_POLICY_KEY,
     EVENT_IDX_GROUP_INSTANCES_PREFIX,
     EVENT_IDX_TARGET_INSTANCES_PREFIX,
     EXECUTABLE_KEY_PREFIX,
@@ -251,3 +252,42 @@ def test_project_instance_applies_notification_overrides(redis_mock):
     snapshot = json.loads(set_cmd[2])
     assert snapshot["notification_template"]["title"] == "Custom title"
     assert snapshot["notification_template"]["body"] == "Custom body"
+
+
+def test_project_instance_includes_escalation_policy(redis_mock):
+    template_id = str(uuid4())
+    instance_id = str(uuid4())
+    template_version = 1
+    policy = {"steps": [{"after_secs": 600, "channels": ["telegram"]}]}
+
+    executable_spec = {
+        "schema_version": "alert_executable_v1",
+        "notification_template": {"title": "Default title", "body": "Default body"},
+        "action": {},
+    }
+    instance = _make_instance(
+        instance_id=instance_id,
+        enabled=True,
+        user_id=123,
+        template_id=template_id,
+        template_version=template_version,
+        template_params={"threshold": 5, ESCALATION_POLICY_KEY: policy},
+        target_keys=["ETH:mainnet:0xabc"],
+    )
+
+    def redis_get_side_effect(key: str):
+        if key == f"{EXECUTABLE_KEY_PREFIX}{template_id}:{template_version}":
+            return json.dumps(executable_spec)
+        return None
+
+    redis_mock.get.side_effect = redis_get_side_effect
+
+    with patch("app.services.alert_runtime_projection._redis_client", return_value=redis_mock):
+        proj = AlertRuntimeProjection()
+        proj.project_instance(instance)
+
+    pipeline: MockPipeline = redis_mock.pipeline.return_value
+    set_cmd = next(cmd for cmd in pipeline.commands if cmd[0] == "set" and cmd[1] == f"{INSTANCE_KEY_PREFIX}{instance_id}")
+    snapshot = json.loads(set_cmd[2])
+    assert snapshot["escalation_policy"] == policy
+    assert snapshot["variable_values"] == {"threshold": 5}
@@ -42,7 +42,9 @@ def test_compile_allows_tx_only_template_without_datasources() -> None:
             "condition_ast": {"op": "gt", "left": "$.tx.value_native", "right": "{{threshold}}"},
             "cron_cadence_seconds": 0,
             "dedupe": {"cooldown_seconds": 60, "key_template": "{{instance_id}}:{{target.key}}"},
-            "pruning_hints": {"evm": {"tx_type": "native_transfer"}},
+            "pruning_hints": {
+                "evm": {"tx_type": "native_transfer", "exclude_category_contexts": ["internal_exchange_ops"]}
+            },
         },
         "notification": {
             "title_template": "Incoming transfer: {{target.short}}",
@@ -57,6 +59,7 @@ def test_compile_allows_tx_only_template_without_datasources() -> None:
     assert exe["schema_version"] == "alert_executable_v1"
     assert exe["datasources"] == []
     assert exe["conditions"]["all"][0]["left"] == "$.tx.value_native"
+    assert exe["trigger_pruning"]["evm"]["exclude_category_contexts"] == ["internal_exchange_ops"]
 
 
 def test_compile_maps_derivation_names_into_condition() -> None:
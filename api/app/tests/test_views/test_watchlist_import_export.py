@@ -0,0 +1,157 @@
+import csv
+import io
+import json
+from unittest.mock import patch
+
+import pytest
+
+from app.models.groups import GenericGroup, GroupType
+from app.utils.evm_address import has_valid_checksum, to_checksum_address
+from blockchain.models import Chain, SubChain
+
+
+pytestmark = pytest.mark.django_db
+
+CHECKSUMMED = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+BAD_CHECKSUM = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"
+LOWER = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359"
+
+
+@pytest.fixture
+def ethereum_mainnet():
+    chain = Chain.objects.create(
+        name="ethereum",
+        display_name="Ethereum",
+        chain_id=1,
+        native_token="ETH",
+        enabled=True,
+    )
+    return SubChain.objects.create(chain=chain, name="mainnet", display_name="Mainnet", enabled=True)
+
+
+@pytest.fixture
+def watchlist(user):
+    return GenericGroup.objects.create(
+        group_type=GroupType.WALLET,
+        name="Watchlist",
+        owner=user,
+        settings={"visibility": "private"},
+        member_data={"members": {
+            f"ETH:mainnet:{LOWER}": {"added_at": "2025-01-01T00:00:00Z", "label": "Existing", "tags": ["ops"]},
+        }},
+    )
+
+
+@pytest.fixture(autouse=True)
+def no_side_effects():
+    with patch("app.services.group_service.GroupService._sync_members_to_redis"), \
+            patch("app.tasks.watchlist_tasks.publish_progress") as publish:
+        yield publish
+
+
+class TestEvmChecksum:
+    def test_eip55_vectors(self):
+        assert to_checksum_address(CHECKSUMMED.lower()) == CHECKSUMMED
+        assert has_valid_checksum(CHECKSUMMED)
+        assert has_valid_checksum(CHECKSUMMED.lower())
+        assert not has_valid_checksum(BAD_CHECKSUM)
+        assert not has_valid_checksum("0x1234")
+
+
+class TestWatchlistImport:
+    def test_import_validates_rows_and_adds_valid_wallets(
+        self, api_client, user, watchlist, ethereum_mainnet, no_side_effects
+    ):
+        api_client.force_authenticate(user=user)
+        payload = "\n".join([
+            "network,subnet,address,label,tags",
+            f"ETH,mainnet,{CHECKSUMMED},Treasury,defi;core",
+            f"ETH,mainnet,{BAD_CHECKSUM},Typo,",
+            "SOL,mainnet,5yBbxyz,Unsupported,",
+            f"ETH,mainnet,{CHECKSUMMED.lower()},Again,",
+            f"ETH,mainnet,{LOWER},Existing,",
+            "not,a,",
+        ])
+
+        resp = api_client.post(
+            f"/api/groups/{watchlist.id}/import/",
+            data={"format": "csv", "payload": payload},
+            format="json",
+        )
+        assert resp.status_code == 202
+        body = resp.json()
+        assert body["total_rows"] == 6
+
+        status_resp = api_client.get(f"/api/groups/{watchlist.id}/import/{body['job_id']}/")
+        assert status_resp.status_code == 200
+        state = status_resp.json()
+        assert state["status"] == "completed"
+        assert state["total"] == 6
+        assert state["processed"] == 6
+        assert state["added"] == 1
+        assert state["duplicates"] == 2
+        assert state["invalid"] == 3
+        codes = {error["row_number"]: error["code"] for error in state["errors"]}
+        assert codes == {2: "bad_checksum", 3: "unsupported_chain", 6: "missing_key"}
+
+        watchlist.refresh_from_db()
+        member = watchlist.member_data["members"][f"ETH:mainnet:{CHECKSUMMED.lower()}"]
+        assert member["label"] == "Treasury"
+        assert member["tags"] == ["defi", "core"]
+
+        event_types = [call.args[1] for call in no_side_effects.call_args_list]
+        assert event_types[-1] == "watchlist.import.complete"
+        assert "watchlist.import.status" in event_types
+
+    def test_import_rejects_malformed_payload_and_non_owner(
+        self, api_client, user, admin_user, watchlist, ethereum_mainnet
+    ):
+        api_client.force_authenticate(user=user)
+        resp = api_client.post(
+            f"/api/groups/{watchlist.id}/import/",
+            data={"format": "json", "payload": "{not json"},
+            format="json",
+        )
+        assert resp.status_code == 400
+
+        watchlist.settings = {"visibility": "public"}
+        watchlist.save(update_fields=["settings"])
+        api_client.force_authenticate(user=admin_user)
+        resp = api_client.post(
+            f"/api/groups/{watchlist.id}/import/",
+            data={"format": "json", "payload": json.dumps([f"ETH:mainnet:{CHECKSUMMED}"])},
+            format="json",
+        )
+        assert resp.status_code == 403
+
+    def test_unknown_job_returns_404(self, api_client, user, watchlist):
+        api_client.force_authenticate(user=user)
+        resp = api_client.get(f"/api/groups/{watchlist.id}/import/missing/")
+        assert resp.status_code == 404
+
+
+class TestWatchlistExport:
+    def test_export_csv_and_json(self, api_client, user, watchlist):
+        api_client.force_authenticate(user=user)
+
+        resp = api_client.get(f"/api/groups/{watchlist.id}/export/")
+        assert resp.status_code == 200
+        assert resp["Content-Type"].startswith("text/csv")
+        assert "attachment" in resp["Content-Disposition"]
+        rows = list(csv.DictReader(io.StringIO(resp.content.decode())))
+        assert rows == [{
+            "member_key": f"ETH:mainnet:{LOWER}",
+            "network": "ETH",
+            "subnet": "mainnet",
+            "address": LOWER,
+            "label": "Existing",
+            "tags": "ops",
+            "added_at": "2025-01-01T00:00:00Z",
+        }]
+
+        resp = api_client.get(f"/api/groups/{watchlist.id}/export/?file_format=json")
+        assert resp.status_code == 200
+        exported = json.loads(resp.content)
+        assert exported["wallets"][0]["tags"] == ["ops"]
+
+        assert api_client.get(f"/api/groups/{watchlist.id}/export/?file_format=xml").status_code == 400
@@ -19,7 +19,7 @@ from .views.notification_views import (
     NotificationDeliveryViewSet, NotificationTemplateViewSet,
     BulkNotificationAPIView, NotificationCacheAPIView, NotificationHealthAPIView,
     PlatformHealthMetricsAPIView, ChannelHealthMetricsAPIView,
-    UserNotificationHistoryAPIView
+    UserNotificationHistoryAPIView, NotificationAckAPIView
 )
 from .views.alert_job_views import (
     GetActiveAlertsByTriggerTypeView,
@@ -49,6 +49,7 @@ from .views.team_views import (
     TeamListView, TeamMembersView, TeamInviteView,
     TeamMemberDetailView, TeamMemberResendInviteView
 )
+from .views.admin_audit_views import admin_audit_log
 from .views.analytics_views import (
     analytics_health, analytics_snapshots, analytics_tables,
     analytics_table_schema, wallet_transactions, wallet_token_transfers,
@@ -93,6 +94,7 @@ urlpatterns = [
     path('notifications/platform-metrics/', PlatformHealthMetricsAPIView.as_view(), name='platform-health-metrics'),
     path('notifications/channel-metrics/<str:channel_id>/', ChannelHealthMetricsAPIView.as_view(), name='channel-health-metrics'),
     path('notifications/history/', UserNotificationHistoryAPIView.as_view(), name='notification-history'),
+    path('notifications/<str:notification_id>/ack/', NotificationAckAPIView.as_view(), name='notification-ack'),
 
     # Profile API (v1)
     path('v1/profile/', ProfileView.as_view(), name='profile'),
@@ -150,6 +152,9 @@ urlpatterns = [
 
     # Newsfeed API (v1) - Transaction feed for monitored wallets
     path('v1/analytics/newsfeed/transactions/', newsfeed_transactions, name='analytics-newsfeed-transactions'),
+
+    # Admin audit trail (v1) - compliance review and export
+    path('v1/admin/audit/', admin_audit_log, name='admin-audit-log'),
 ]
 
 # Available endpoints:
@@ -178,6 +183,9 @@ urlpatterns = [
 # GET    /alerts/{id}/executions/     - Get execution history
 # POST   /alerts/{id}/enable/         - Enable alert
 # POST   /alerts/{id}/disable/        - Disable alert
+# GET    /alerts/{id}/escalation-policy/ - Get escalation policy
+# PUT    /alerts/{id}/escalation-policy/ - Replace escalation policy
+# DELETE /alerts/{id}/escalation-policy/ - Remove escalation policy
 #
 # NOTIFICATION SETTINGS:
 # GET    /notification-settings/      - Get user's notification settings
@@ -248,6 +256,7 @@ urlpatterns = [
 #        ?alert_id=<uuid>             - Filter by specific alert
 #        ?start_date=<ISO8601>        - Filter by date range start
 #        ?end_date=<ISO8601>          - Filter by date range end
+# POST   /notifications/{id}/ack/     - Acknowledge a notification (stops pending escalations)
 #
 # GROUPS (GenericGroup unified model):
 # GET    /groups/                      - List user's groups
@@ -259,6 +268,9 @@ urlpatterns = [
 # POST   /groups/{id}/add_members/     - Add members to group (bulk)
 # POST   /groups/{id}/remove_members/  - Remove members from group (bulk)
 # GET    /groups/{id}/members/         - List all members with metadata
+# POST   /groups/{id}/import/          - Bulk import wallets from CSV/JSON (async, 202 + job_id)
+# GET    /groups/{id}/import/{job_id}/ - Bulk import job status
+# GET    /groups/{id}/export/          - Export wallets (?file_format=csv|json)
 # GET    /groups/by_type/?type=wallet  - Filter groups by type
 # GET    /groups/summary/              - Get summary of groups by type
 #
@@ -272,6 +284,11 @@ urlpatterns = [
 # GET    /subscriptions/by_alert_group/?alert_group_id=X - Filter by alert group
 # GET    /subscriptions/by_target_group/?target_group_id=X - Filter by target group
 #
+# ADMIN AUDIT (staff only):
+# GET    /v1/admin/audit/              - Query signed audit records (?resource_type=&resource_id=&actor_id=&action=&start=&end=)
+# GET    /v1/admin/audit/?export=jsonl - Download records as JSON Lines
+# GET    /v1/admin/audit/?source=stream - Read the Redis stream (includes unflushed records)
+#
 # Query parameters:
 # ?latest_only=true/false             - Show only latest versions (default: true)
 # ?chain=ethereum-mainnet             - Filter by chain name
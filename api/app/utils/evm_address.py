@@ -0,0 +1,101 @@
+"""
+EVM address helpers (EIP-55 checksums).
+
+Keccak-256 is implemented here because hashlib only ships the NIST SHA3
+variant, which pads differently and yields different digests.
+"""
+
+import re
+
+_EVM_ADDRESS_RE = re.compile(r"^0x[0-9a-fA-F]{40}$")
+
+_ROUND_CONSTANTS = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+]
+
+# Rotation offsets indexed [x][y]
+_ROTATIONS = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+]
+
+_MASK = (1 << 64) - 1
+_RATE = 136
+
+
+def _rotl(value: int, shift: int) -> int:
+    return ((value << shift) | (value >> (64 - shift))) & _MASK if shift else value
+
+
+def _keccak_f(state: list[int]) -> list[int]:
+    for rc in _ROUND_CONSTANTS:
+        columns = [state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20] for x in range(5)]
+        deltas = [columns[(x - 1) % 5] ^ _rotl(columns[(x + 1) % 5], 1) for x in range(5)]
+        state = [lane ^ deltas[i % 5] for i, lane in enumerate(state)]
+
+        moved = [0] * 25
+        for x in range(5):
+            for y in range(5):
+                moved[y + 5 * ((2 * x + 3 * y) % 5)] = _rotl(state[x + 5 * y], _ROTATIONS[x][y])
+
+        state = [
+            moved[i] ^ (~moved[(i % 5 + 1) % 5 + 5 * (i // 5)] & _MASK & moved[(i % 5 + 2) % 5 + 5 * (i // 5)])
+            for i in range(25)
+        ]
+        state[0] ^= rc
+    return state
+
+
+def keccak256(data: bytes) -> bytes:
+    """Keccak-256 digest as used by Ethereum."""
+    padded = bytearray(data)
+    padded.append(0x01)
+    padded.extend(b"\x00" * (-len(padded) % _RATE))
+    padded[-1] |= 0x80
+
+    state = [0] * 25
+    for offset in range(0, len(padded), _RATE):
+        block = padded[offset:offset + _RATE]
+        for i in range(_RATE // 8):
+            state[i] ^= int.from_bytes(block[i * 8:(i + 1) * 8], "little")
+        state = _keccak_f(state)
+
+    return b"".join(lane.to_bytes(8, "little") for lane in state[:4])
+
+
+def is_evm_address(address: str) -> bool:
+    return bool(_EVM_ADDRESS_RE.match(address or ""))
+
+
+def to_checksum_address(address: str) -> str:
+    """EIP-55 mixed-case form of a 20-byte hex address."""
+    if not is_evm_address(address):
+        raise ValueError(f"Not an EVM address: {address}")
+    lowered = address[2:].lower()
+    digest = keccak256(lowered.encode("ascii")).hex()
+    return "0x" + "".join(
+        char.upper() if char.isalpha() and int(digest[i], 16) >= 8 else char
+        for i, char in enumerate(lowered)
+    )
+
+
+def has_valid_checksum(address: str) -> bool:
+    """
+    True unless `address` is mixed-case with a wrong EIP-55 checksum.
+
+    All-lowercase and all-uppercase addresses carry no checksum and are accepted.
+    """
+    if not is_evm_address(address):
+        return False
+    body = address[2:]
+    if body == body.lower() or body == body.upper():
+        return True
+    return to_checksum_address(address) == address
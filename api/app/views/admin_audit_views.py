@@ -0,0 +1,95 @@
+"""Admin audit trail query endpoint for compliance review."""
+
+import json
+import logging
+
+from django.http import HttpResponse
+from django.utils.dateparse import parse_datetime
+from rest_framework import status
+from rest_framework.decorators import api_view, permission_classes
+from rest_framework.permissions import IsAdminUser
+from rest_framework.request import Request
+from rest_framework.response import Response
+
+from app.services.admin_audit import query_audit_records
+
+logger = logging.getLogger(__name__)
+
+
+@api_view(['GET'])
+@permission_classes([IsAdminUser])
+def admin_audit_log(request: Request) -> Response:
+    """
+    Query the admin audit trail, newest first.
+
+    Query params:
+        resource_type, resource_id, actor_id, action: exact-match filters
+        start, end: ISO-8601 datetimes bounding occurred_at
+        limit (default 100, max 1000), offset
+        source: `ducklake` (default) or `stream` for not-yet-flushed records
+        export: `jsonl` to download the records as a JSON Lines file
+
+    Every record carries `signature_valid`; false means the record was
+    altered after signing or was signed with a retired key.
+    """
+    params = request.query_params
+
+    bounds = {}
+    for name in ('start', 'end'):
+        raw = params.get(name)
+        if raw:
+            parsed = parse_datetime(raw)
+            if parsed is None:
+                return Response(
+                    {'error': f'Invalid {name} datetime: {raw}'},
+                    status=status.HTTP_400_BAD_REQUEST,
+                )
+            bounds[name] = parsed
+
+    source = params.get('source', 'ducklake')
+    if source not in ('ducklake', 'stream'):
+        return Response(
+            {'error': "source must be 'ducklake' or 'stream'"},
+            status=status.HTTP_400_BAD_REQUEST,
+        )
+
+    try:
+        limit = int(params.get('limit', 100))
+        offset = int(params.get('offset', 0))
+    except ValueError:
+        return Response(
+            {'error': 'limit and offset must be integers'},
+            status=status.HTTP_400_BAD_REQUEST,
+        )
+
+    try:
+        records = query_audit_records(
+            resource_type=params.get('resource_type'),
+            resource_id=params.get('resource_id'),
+            actor_id=params.get('actor_id'),
+            action=params.get('action'),
+            limit=limit,
+            offset=offset,
+            source=source,
+            **bounds,
+        )
+    except Exception as e:
+        logger.error(f"Failed to query admin audit trail: {e}")
+        return Response(
+            {'error': 'Failed to query admin audit trail'},
+            status=status.HTTP_500_INTERNAL_SERVER_ERROR,
+        )
+
+    if params.get('export') == 'jsonl':
+        body = ''.join(json.dumps(record, default=str) + '\n' for record in records)
+        response = HttpResponse(body, content_type='application/x-ndjson')
+        response['Content-Disposition'] = 'attachment; filename="admin_audit.jsonl"'
+        return response
+
+    return Response({
+        'records': records,
+        'count': len(records),
+        'limit': limit,
+        'offset': offset,
+        'source': source,
+    })
@@ -13,6 +13,7 @@ from rest_framework.response import Response
 from django.db.models import Count, Q
 from django.utils import timezone
 from datetime import timedelta
+from typing import Optional
 
 from ..models.alerts import AlertInstance, AlertExecution
 from ..models.groups import GenericGroup, GroupType, SYSTEM_GROUP_ACCOUNTS, GroupSubscription
@@ -329,7 +330,7 @@ class DashboardNetworkStatusView(APIView):
         for provider in provider_statuses:
             for chain_id, sub in provider.get('subscriptions', {}).items():
                 networks.append(self._transform_subscription(
-                    provider, chain_id, sub
+                    provider, chain_id, sub, service.get_block_checkpoint(chain_id)
                 ))
 
         # Return empty list if no providers registered (not mock data)
@@ -339,11 +340,26 @@ class DashboardNetworkStatusView(APIView):
             'timestamp': timezone.now().isoformat()
         })
 
-    def _transform_subscription(self, provider: dict, chain_id: str, sub: dict) -> dict:
-        """Transform subscription status to network status format."""
+    def _transform_subscription(
+        self, provider: dict, chain_id: str, sub: dict, checkpoint: Optional[dict] = None
+    ) -> dict:
+        """Transform subscription status to network status format.
+
+        The block checkpoint, when present, reports how far processing has
+        got behind the head the provider last received.
+        """
         state = sub.get('state', 'unknown')
         last_block = sub.get('last_block')
         metrics = sub.get('metrics', {})
+        block_height = last_block.get('number') if last_block else None
+        checkpoint_block = checkpoint.get('block_number') if checkpoint else None
+        processing_lag = None
+        if block_height is not None and checkpoint_block is not None:
+            processing_lag = max(0, block_height - checkpoint_block)
+        pending_gap_blocks = sum(
+            gap['to'] - gap['from'] + 1
+            for gap in (checkpoint or {}).get('pending_gaps', [])
+        )
 
         # Map subscription state to status
         status_map = {
@@ -360,11 +376,14 @@ class DashboardNetworkStatusView(APIView):
             'status': status_map.get(state, 'unknown'),
             'provider_id': provider.get('provider_id'),
             'provider_type': provider.get('provider_type'),
-            'block_height': last_block.get('number') if last_block else None,
+            'block_height': block_height,
             'last_block_time': last_block.get('received_at') if last_block else None,
             'avg_latency_ms': metrics.get('avg_latency_ms'),
             'blocks_received': metrics.get('blocks_received', 0),
             'connection_errors': metrics.get('connection_errors', 0),
+            'checkpoint_block': checkpoint_block,
+            'processing_lag': processing_lag,
+            'pending_gap_blocks': pending_gap_blocks,
             'health_score': self._calculate_health_score(sub),
         }
 
@@ -11,6 +11,7 @@ Provides API endpoints for:
 import csv
 import io
 import json
+import uuid
 from typing import List, Tuple
 
 from rest_framework import viewsets, status, permissions
@@ -19,6 +20,7 @@ from rest_framework.response import Response
 from rest_framework.filters import SearchFilter, OrderingFilter
 from rest_framework.exceptions import PermissionDenied, ValidationError
 from django_filters.rest_framework import DjangoFilterBackend
+from django.http import HttpResponse
 from django.utils import timezone
 from django.db.models import Q
 from django.shortcuts import get_object_or_404
@@ -48,6 +50,7 @@ from ..serializers.group_serializers import (
     UserWalletGroupUpdateSerializer,
     UserWalletGroupWalletKeysSerializer,
     UserWalletGroupImportSerializer,
+    WatchlistImportSerializer,
 )
 
 
@@ -63,6 +66,9 @@ class GenericGroupViewSet(viewsets.ModelViewSet):
     - DELETE /api/groups/{id}/               - Delete group
     - POST   /api/groups/{id}/add_members/   - Add members to group
     - POST   /api/groups/{id}/remove_members/ - Remove members from group
+    - POST   /api/groups/{id}/import/        - Bulk import wallets (async, returns job_id)
+    - GET    /api/groups/{id}/import/{job_id}/ - Bulk import job status
+    - GET    /api/groups/{id}/export/        - Export wallets as CSV/JSON
     - GET    /api/groups/by_type/            - List groups by type
     """
 
@@ -896,6 +902,111 @@ class GenericGroupViewSet(viewsets.ModelViewSet):
             'members': members
         })
 
+    @action(detail=True, methods=['post'], url_path='import')
+    def import_wallets(self, request, pk=None):
+        """
+        Bulk import wallets into a wallet group (asynchronous).
+
+        Request body:
+            {"format": "csv" | "json", "payload": "<file contents>"}
+
+        The file is parsed and its row count checked up front; per-row
+        validation (format, EIP-55 checksum, chain support, duplicates) and
+        member writes run in a background task. Progress is pushed as
+        `watchlist.import.*` WebSocket events and can be polled via
+        GET /api/groups/{id}/import/{job_id}/.
+        """
+        from app.services.watchlist_bulk import parse_watchlist_payload
+        from app.tasks.watchlist_tasks import import_watchlist
+
+        group = self.get_object()
+        self._require_owner(group)
+        if group.group_type != GroupType.WALLET:
+            return Response(
+                {'detail': 'Only wallet groups support import'},
+                status=status.HTTP_400_BAD_REQUEST,
+            )
+
+        serializer = WatchlistImportSerializer(data=request.data)
+        serializer.is_valid(raise_exception=True)
+        fmt = serializer.validated_data['format']
+        payload = serializer.validated_data['payload']
+
+        try:
+            rows = parse_watchlist_payload(fmt, payload)
+        except ValueError as exc:
+            return Response({'error': str(exc)}, status=status.HTTP_400_BAD_REQUEST)
+
+        user_id = str(request.user.id)
+        job_id = str(uuid.uuid4())
+        try:
+            import_watchlist.enqueue(
+                user_id=user_id,
+                group_id=str(group.id),
+                job_id=job_id,
+                fmt=fmt,
+                payload=payload,
+            )
+        except Exception as exc:
+            # The task records its own failure in the job state
+            return Response(
+                {'error': f'Failed to import watchlist: {exc}', 'job_id': job_id},
+                status=status.HTTP_500_INTERNAL_SERVER_ERROR,
+            )
+
+        return Response(
+            {
+                'job_id': job_id,
+                'group_id': str(group.id),
+                'status': 'queued',
+                'total_rows': len(rows),
+            },
+            status=status.HTTP_202_ACCEPTED,
+        )
+
+    @action(detail=True, methods=['get'], url_path=r'import/(?P<job_id>[^/.]+)')
+    def import_status(self, request, pk=None, job_id=None):
+        """Get the state of a bulk import job started by the current user."""
+        from app.services.watchlist_bulk import get_import_job
+
+        group = self.get_object()
+        self._require_owner(group)
+
+        state = get_import_job(str(request.user.id), str(job_id))
+        if not state or state.get('group_id') != str(group.id):
+            return Response({'detail': 'Import job not found'}, status=status.HTTP_404_NOT_FOUND)
+        return Response(state, status=status.HTTP_200_OK)
+
+    @action(detail=True, methods=['get'], url_path='export')
+    def export_wallets(self, request, pk=None):
+        """
+        Export a wallet group's members as a file attachment.
+
+        Query params:
+            file_format: csv (default) | json
+        """
+        from app.services.watchlist_bulk import export_watchlist
+
+        group = self.get_object()
+        self._require_owner(group)
+        if group.group_type != GroupType.WALLET:
+            return Response(
+                {'detail': 'Only wallet groups support export'},
+                status=status.HTTP_400_BAD_REQUEST,
+            )
+
+        fmt = (request.query_params.get('file_format') or 'csv').lower()
+        if fmt not in {'csv', 'json'}:
+            return Response(
+                {'error': "file_format must be 'csv' or 'json'"},
+                status=status.HTTP_400_BAD_REQUEST,
+            )
+
+        body, content_type, filename = export_watchlist(group, fmt)
+        response = HttpResponse(body, content_type=content_type)
+        response['Content-Disposition'] = f'attachment; filename="{filename}"'
+        return response
+
     @action(detail=True, methods=['get'], url_path='templates')
     def templates(self, request, pk=None):
         """
@@ -23,7 +23,8 @@ from ..services.nats_service import (
     publish_alert_created_sync, publish_alert_updated_sync,
     publish_alert_enabled_sync, publish_alert_disabled_sync,
 )
-from ..services.alert_runtime_projection import NOTIFICATION_OVERRIDE_KEY
+from ..services.alert_runtime_projection import ESCALATION_POLICY_KEY, NOTIFICATION_OVERRIDE_KEY
+from ..services.alert_escalation import EscalationPolicyError, normalize_escalation_policy
 from blockchain.models import Chain, SubChain
 
 
@@ -131,6 +132,7 @@ class AlertInstanceViewSet(viewsets.ModelViewSet):
 
         resolved_variables = data["_resolved_variable_values"]
         notification_overrides = data.get("_notification_overrides") or {}
+        escalation_policy = data.get("_escalation_policy") or {}
         target_group = data.get("_target_group")
         target_keys = data.get("_target_keys") or []
         alert_type = data.get("_alert_type") or "wallet"
@@ -159,6 +161,8 @@ class AlertInstanceViewSet(viewsets.ModelViewSet):
         template_params = dict(resolved_variables)
         if notification_overrides:
             template_params[NOTIFICATION_OVERRIDE_KEY] = notification_overrides
+        if escalation_policy:
+            template_params[ESCALATION_POLICY_KEY] = escalation_policy
 
         alert = AlertInstance.objects.create(
             name=instance_name,
@@ -347,6 +351,61 @@ class AlertInstanceViewSet(viewsets.ModelViewSet):
 
         return Response({'message': 'Alert instance disabled successfully'})
 
+    @action(detail=True, methods=['get', 'put', 'delete'], url_path='escalation-policy')
+    def escalation_policy(self, request, pk=None):
+        """
+        Get, replace or remove the instance's escalation policy.
+
+        Body (PUT): {"steps": [{"after_secs": 600, "channels": ["telegram"]}]}
+        Each step re-sends an unacknowledged notification to its channels
+        `after_secs` after the original delivery.
+        """
+        alert_instance = self.get_object()
+        template_params = dict(alert_instance.template_params or {})
+        current = template_params.get(ESCALATION_POLICY_KEY) or {}
+
+        if request.method == 'GET':
+            return Response({'escalation_policy': current})
+
+        if request.method == 'DELETE':
+            new_policy = {}
+        else:
+            try:
+                new_policy = normalize_escalation_policy(request.data)
+            except EscalationPolicyError as e:
+                return Response({'error': str(e)}, status=status.HTTP_400_BAD_REQUEST)
+
+        if new_policy == current:
+            return Response({'escalation_policy': current})
+
+        if new_policy:
+            template_params[ESCALATION_POLICY_KEY] = new_policy
+        else:
+            template_params.pop(ESCALATION_POLICY_KEY, None)
+
+        AlertChangeLog.objects.create(
+            alert_instance=alert_instance,
+            from_version=alert_instance.version,
+            to_version=alert_instance.version,
+            change_type='params_updated',
+            changed_fields=['escalation_policy'],
+            old_values={'escalation_policy': current},
+            new_values={'escalation_policy': new_policy},
+            changed_by=request.user
+        )
+
+        alert_instance.template_params = template_params
+        alert_instance.save(update_fields=['template_params'])
+
+        try:
+            publish_alert_updated_sync(alert_instance)
+        except Exception as e:
+            import logging
+            logger = logging.getLogger(__name__)
+            logger.error(f"Failed to publish alert updated message: {e}")
+
+        return Response({'escalation_policy': new_policy})
+
     @action(detail=False, methods=['post'])
     def parse(self, request):
         """Parse natural language description asynchronously.
@@ -1940,3 +1940,40 @@ class ChannelHealthMetricsAPIView(APIView):
                 {'error': f'Failed to retrieve metrics: {str(e)}'},
                 status=status.HTTP_500_INTERNAL_SERVER_ERROR
             )
+
+
+class NotificationAckAPIView(APIView):
+    """
+    Acknowledge a delivered notification.
+
+    Acknowledging stops any escalation steps still pending for the
+    notification. The ack is published on `alerts.ack` and recorded by the
+    alert scheduler; acknowledging twice is harmless.
+    """
+
+    permission_classes = [IsAuthenticated]
+
+    def post(self, request, notification_id):
+        from app.services.alert_escalation import get_pending_escalation, publish_alert_ack_sync
+
+        user_id = str(request.user.id)
+        pending = get_pending_escalation(notification_id)
+        if pending is not None and str(pending.get('user_id')) != user_id:
+            return Response(
+                {'error': 'Notification not found'},
+                status=status.HTTP_404_NOT_FOUND
+            )
+
+        acknowledged = publish_alert_ack_sync(notification_id, user_id)
+        if acknowledged is None:
+            return Response(
+                {'error': 'Failed to record acknowledgement'},
+                status=status.HTTP_503_SERVICE_UNAVAILABLE
+            )
+
+        logger.info(f"Notification {notification_id} acknowledged by user {user_id}")
+        return Response({
+            'notification_id': str(notification_id),
+            'acknowledged': True,
+            'already_acknowledged': not acknowledged,
+        })
@@ -98,6 +98,7 @@ MIDDLEWARE = [
     'django.middleware.common.CommonMiddleware',
     'django.middleware.csrf.CsrfViewMiddleware',
     'django.contrib.auth.middleware.AuthenticationMiddleware',
+    'app.services.admin_audit.AuditContextMiddleware',
     'django_otp.middleware.OTPMiddleware',
     'allauth.account.middleware.AccountMiddleware',
     'django.contrib.messages.middleware.MessageMiddleware',
@@ -488,6 +489,17 @@ DUCKLAKE_CATALOG_NAME = env('DUCKLAKE_CATALOG_NAME', default='ekko_lake')
 DUCKLAKE_THREADS = env.int('DUCKLAKE_THREADS', default=4)
 DUCKLAKE_MEMORY_LIMIT = env('DUCKLAKE_MEMORY_LIMIT', default='2GB')
 
+# Admin audit trail (Redis stream + ducklake.admin_audit)
+ADMIN_AUDIT_ENABLED = env.bool('ADMIN_AUDIT_ENABLED', default=True)
+ADMIN_AUDIT_STREAM = env('ADMIN_AUDIT_STREAM', default='admin:audit')
+# HMAC key for record signatures; rotate by changing both key and key id
+ADMIN_AUDIT_SIGNING_KEY = env('ADMIN_AUDIT_SIGNING_KEY', default=SECRET_KEY)
+ADMIN_AUDIT_SIGNING_KEY_ID = env('ADMIN_AUDIT_SIGNING_KEY_ID', default='default')
+# Reverse proxies in front of the API that append to X-Forwarded-For; the
+# client address is the entry this many places from the right. 0 trusts no
+# forwarded header and records REMOTE_ADDR.
+TRUSTED_PROXY_COUNT = env.int('TRUSTED_PROXY_COUNT', default=0)
+
 # Firebase Configuration (for email delivery and optional features)
 FIREBASE_PROJECT_ID = env("FIREBASE_PROJECT_ID", default="")
 FIREBASE_API_KEY = env("FIREBASE_API_KEY", default="")
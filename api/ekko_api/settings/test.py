@@ -95,6 +95,9 @@ NATS_ENABLED = os.environ.get("EKKO_TEST_USE_NATS", "").strip() == "1"
 # wasmCloud runtime Redis projection is out-of-scope for unit tests; disable by default.
 ALERT_RUNTIME_REDIS_SYNC_ENABLED = os.environ.get("EKKO_TEST_RUNTIME_REDIS_SYNC", "").strip() == "1"
 
+# Admin audit sinks (Redis stream, DuckLake) are disabled unless explicitly requested.
+ADMIN_AUDIT_ENABLED = os.environ.get("EKKO_TEST_ADMIN_AUDIT", "").strip() == "1"
+
 # Security settings for testing
 SECRET_KEY = "test-secret-key-for-testing-only-not-for-production"
 ALLOWED_HOSTS = (
@@ -160,6 +163,7 @@ MIDDLEWARE = [
     "django.middleware.common.CommonMiddleware",
     "django.middleware.csrf.CsrfViewMiddleware",
     "django.contrib.auth.middleware.AuthenticationMiddleware",
+    "app.services.admin_audit.AuditContextMiddleware",
     "django.contrib.messages.middleware.MessageMiddleware",
     "allauth.account.middleware.AccountMiddleware",
 ]
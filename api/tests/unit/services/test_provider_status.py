@@ -215,3 +215,60 @@ class TestDashboardNetworkStatusView:
         assert result['blocks_received'] == 12847
         assert result['connection_errors'] == 2
         assert result['health_score'] == 96  # 100 - 2*2 = 96
+        assert result['checkpoint_block'] is None
+        assert result['processing_lag'] is None
+
+    def test_transform_subscription_with_checkpoint(self):
+        """Test processing lag and pending gaps come from the block checkpoint."""
+        from app.views.dashboard_views import DashboardNetworkStatusView
+
+        view = DashboardNetworkStatusView()
+        provider = {'provider_id': 'newheads-evm-pod1', 'provider_type': 'evm'}
+        sub = {
+            'chain_name': 'Ethereum Mainnet',
+            'state': 'active',
+            'last_block': {'number': 19283746, 'received_at': '2024-01-15T10:30:45Z'},
+            'metrics': {},
+        }
+        checkpoint = {
+            'block_number': 19283740,
+            'pending_gaps': [{'from': 19283700, 'to': 19283709}],
+        }
+
+        result = view._transform_subscription(provider, 'ethereum-mainnet', sub, checkpoint)
+
+        assert result['checkpoint_block'] == 19283740
+        assert result['processing_lag'] == 6
+        assert result['pending_gap_blocks'] == 10
+
+
+class TestBlockCheckpoint:
+    """Tests for reading block checkpoints."""
+
+    @pytest.fixture
+    def mock_redis(self):
+        """Create a mock Redis client."""
+        with patch('app.services.provider_status.redis') as mock_redis_module:
+            mock_client = MagicMock()
+            mock_redis_module.from_url.return_value = mock_client
+            yield mock_client
+
+    def test_get_block_checkpoint(self, mock_redis):
+        """Test reading a chain's checkpoint by node key."""
+        from app.services.provider_status import ProviderStatusService
+        checkpoint = {'block_number': 42, 'block_hash': '0xabc', 'pending_gaps': []}
+        mock_redis.get.return_value = json.dumps(checkpoint)
+
+        service = ProviderStatusService()
+
+        assert service.get_block_checkpoint('ethereum-mainnet') == checkpoint
+        mock_redis.get.assert_called_once_with('checkpoint:block:ethereum-mainnet')
+
+    def test_get_block_checkpoint_redis_error(self, mock_redis):
+        """Test graceful handling of Redis errors when reading checkpoints."""
+        from app.services.provider_status import ProviderStatusService
+        mock_redis.get.side_effect = Exception("Connection refused")
+
+        service = ProviderStatusService()
+
+        assert service.get_block_checkpoint('ethereum-mainnet') is None
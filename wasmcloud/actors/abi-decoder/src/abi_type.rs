@@ -0,0 +1,251 @@
+//! Parsed ABI types
+//!
+//! JSON ABIs spell tuples as `tuple` with `components` (`tuple[]` for an array
+//! of them), text signatures spell them inline (`(address,uint256)[]`). Both
+//! are parsed into one [`AbiType`] tree, which gives the canonical type string
+//! selectors are hashed over and drives the recursive decoder.
+//!
+//! Encoding (Solidity ABI spec): a tuple is a sequence of heads followed by
+//! tails. Static types sit in their head; dynamic types (`bytes`, `string`,
+//! `T[]`, and tuples or fixed arrays containing one) leave a 32-byte offset in
+//! their head, counted from the start of the enclosing tuple, pointing at the
+//! tail. `T[k]` is encoded as a tuple of `k` `T`s and `T[]` as its length
+//! followed by such a tuple.
+
+use crate::AbiParam;
+
+/// One ABI type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiType {
+    Address,
+    Bool,
+    /// `uintN`, bit width kept for the canonical signature
+    Uint(usize),
+    Int(usize),
+    /// `bytesN`
+    FixedBytes(usize),
+    Bytes,
+    String,
+    /// `T[]`
+    Array(Box<AbiType>),
+    /// `T[k]`
+    FixedArray(Box<AbiType>, usize),
+    Tuple(Vec<AbiType>),
+}
+
+impl AbiType {
+    /// Type of a JSON ABI parameter, tuples taken from `components`
+    pub fn from_param(param: &AbiParam) -> Result<Self, String> {
+        let Some(suffix) = param.param_type.strip_prefix("tuple") else {
+            return Self::parse(&param.param_type);
+        };
+        let components = param
+            .components
+            .as_ref()
+            .ok_or_else(|| format!("Tuple parameter {} without components", param.name))?;
+        let tuple = AbiType::Tuple(
+            components
+                .iter()
+                .map(Self::from_param)
+                .collect::<Result<_, _>>()?,
+        );
+        Self::with_array_suffix(tuple, suffix)
+    }
+
+    /// Type of a signature type string, e.g. `(address,uint256)[]`
+    pub fn parse(type_str: &str) -> Result<Self, String> {
+        let type_str = type_str.trim();
+        // Array suffixes bind last: `uint256[2][]` is an array of `uint256[2]`
+        if type_str.ends_with(']') {
+            let open = type_str
+                .rfind('[')
+                .ok_or_else(|| format!("Malformed array type: {}", type_str))?;
+            let inner = Self::parse(&type_str[..open])?;
+            return Self::with_array_suffix(inner, &type_str[open..]);
+        }
+        if let Some(inner) = type_str
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Ok(AbiType::Tuple(
+                split_top_level(inner)
+                    .into_iter()
+                    .map(Self::parse)
+                    .collect::<Result<_, _>>()?,
+            ));
+        }
+
+        match type_str {
+            "address" => Ok(AbiType::Address),
+            "bool" => Ok(AbiType::Bool),
+            "string" => Ok(AbiType::String),
+            "bytes" => Ok(AbiType::Bytes),
+            "uint" => Ok(AbiType::Uint(256)),
+            "int" => Ok(AbiType::Int(256)),
+            t if t.starts_with("uint") => Ok(AbiType::Uint(bit_width(t, &t[4..])?)),
+            t if t.starts_with("int") => Ok(AbiType::Int(bit_width(t, &t[3..])?)),
+            t if t.starts_with("bytes") => {
+                let size: usize = t[5..]
+                    .parse()
+                    .map_err(|_| format!("Invalid bytes size: {}", t))?;
+                if !(1..=32).contains(&size) {
+                    return Err(format!("Invalid bytes size: {}", t));
+                }
+                Ok(AbiType::FixedBytes(size))
+            }
+            _ => Err(format!("Unsupported type: {}", type_str)),
+        }
+    }
+
+    /// Apply `[]` / `[k]` suffixes, left to right, to `inner`
+    fn with_array_suffix(inner: AbiType, suffix: &str) -> Result<Self, String> {
+        let mut ty = inner;
+        let mut rest = suffix;
+        while let Some(dims) = rest.strip_prefix('[') {
+            let close = dims
+                .find(']')
+                .ok_or_else(|| format!("Malformed array suffix: {}", suffix))?;
+            ty = match &dims[..close] {
+                "" => AbiType::Array(Box::new(ty)),
+                size => AbiType::FixedArray(
+                    Box::new(ty),
+                    size.parse()
+                        .map_err(|_| format!("Invalid array size: {}", suffix))?,
+                ),
+            };
+            rest = &dims[close + 1..];
+        }
+        if !rest.is_empty() {
+            return Err(format!("Malformed array suffix: {}", suffix));
+        }
+        Ok(ty)
+    }
+
+    /// Type string as hashed into selectors and topics
+    pub fn canonical(&self) -> String {
+        match self {
+            AbiType::Address => "address".to_string(),
+            AbiType::Bool => "bool".to_string(),
+            AbiType::Uint(bits) => format!("uint{}", bits),
+            AbiType::Int(bits) => format!("int{}", bits),
+            AbiType::FixedBytes(size) => format!("bytes{}", size),
+            AbiType::Bytes => "bytes".to_string(),
+            AbiType::String => "string".to_string(),
+            AbiType::Array(inner) => format!("{}[]", inner.canonical()),
+            AbiType::FixedArray(inner, size) => format!("{}[{}]", inner.canonical(), size),
+            AbiType::Tuple(members) => {
+                let members: Vec<String> = members.iter().map(AbiType::canonical).collect();
+                format!("({})", members.join(","))
+            }
+        }
+    }
+
+    /// Whether the value lives in the tail, behind an offset
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            AbiType::Bytes | AbiType::String | AbiType::Array(_) => true,
+            AbiType::FixedArray(inner, _) => inner.is_dynamic(),
+            AbiType::Tuple(members) => members.iter().any(AbiType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Bytes the value takes in its enclosing tuple's head
+    pub fn head_size(&self) -> usize {
+        if self.is_dynamic() {
+            return 32;
+        }
+        match self {
+            AbiType::FixedArray(inner, size) => inner.head_size() * size,
+            AbiType::Tuple(members) => members.iter().map(AbiType::head_size).sum(),
+            _ => 32,
+        }
+    }
+}
+
+fn bit_width(type_str: &str, digits: &str) -> Result<usize, String> {
+    let bits: usize = digits
+        .parse()
+        .map_err(|_| format!("Invalid integer type: {}", type_str))?;
+    if bits == 0 || bits > 256 || bits % 8 != 0 {
+        return Err(format!("Invalid integer type: {}", type_str));
+    }
+    Ok(bits)
+}
+
+/// Split a tuple's members on the commas outside nested parentheses
+pub fn split_top_level(types: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in types.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                members.push(&types[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !types[start..].trim().is_empty() {
+        members.push(&types[start..]);
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, param_type: &str, components: Option<Vec<AbiParam>>) -> AbiParam {
+        AbiParam {
+            name: name.to_string(),
+            param_type: param_type.to_string(),
+            indexed: false,
+            components,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_canonical_forms() {
+        let ty = AbiType::parse("(address,uint256)[]").unwrap();
+        assert_eq!(
+            ty,
+            AbiType::Array(Box::new(AbiType::Tuple(vec![
+                AbiType::Address,
+                AbiType::Uint(256)
+            ])))
+        );
+        assert!(ty.is_dynamic());
+
+        let nested = AbiType::parse("uint8[2][]").unwrap();
+        assert_eq!(
+            nested,
+            AbiType::Array(Box::new(AbiType::FixedArray(Box::new(AbiType::Uint(8)), 2)))
+        );
+        assert_eq!(nested.canonical(), "uint8[2][]");
+        assert_eq!(AbiType::parse("uint").unwrap().canonical(), "uint256");
+        assert!(AbiType::parse("uint7").is_err());
+        assert!(AbiType::parse("bytes33").is_err());
+
+        let static_pair = AbiType::parse("(address,uint256)[3]").unwrap();
+        assert!(!static_pair.is_dynamic());
+        assert_eq!(static_pair.head_size(), 192);
+
+        let params = param(
+            "params",
+            "tuple[]",
+            Some(vec![
+                param("path", "bytes", None),
+                param("amounts", "uint256[2]", None),
+            ]),
+        );
+        assert_eq!(
+            AbiType::from_param(&params).unwrap().canonical(),
+            "(bytes,uint256[2])[]"
+        );
+        assert!(AbiType::from_param(&param("p", "tuple", None)).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Batch decode limits and chunking
+//!
+//! `abi.decode.batch` requests are bounded and split before decoding:
+//! - a batch with more than `max_batch_size` requests is rejected as a whole,
+//!   with a result naming the limit, so the sender can split it
+//! - a batch with more than `chunk_size` requests is republished on
+//!   `abi.decode.batch` as one request per chunk, tagged with its
+//!   [`BatchChunk`], so the chunks are spread over actor instances and each
+//!   publishes its own result under the batch's `batch_id`
+//! - a request that fails to decode is answered with `DecodingFailed` instead
+//!   of failing its chunk, and requests decoding slower than
+//!   `request_timeout_ms` are counted as `slow_requests`
+//! - once a chunk has been decoding for `chunk_timeout_ms`, its remaining
+//!   requests are answered `Skipped` (retryable) so the chunk's result is
+//!   still published
+//!
+//! The limits are read from `abi_decoder:batch_config` (JSON, every field
+//! optional).
+
+use serde::{Deserialize, Serialize};
+
+use crate::DecodeRequest;
+
+/// Batch limits, stored in `abi_decoder:batch_config`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchConfig {
+    pub max_batch_size: usize,
+    pub chunk_size: usize,
+    pub request_timeout_ms: u64,
+    pub chunk_timeout_ms: u64,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 1_000,
+            chunk_size: 50,
+            request_timeout_ms: 2_000,
+            chunk_timeout_ms: 30_000,
+        }
+    }
+}
+
+/// Position of a chunk in the batch it was split from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchChunk {
+    /// 0-based
+    pub index: u32,
+    pub count: u32,
+}
+
+impl BatchConfig {
+    /// Why a batch of `size` requests is refused, if it is
+    pub fn oversize_error(&self, size: usize) -> Option<String> {
+        (size > self.max_batch_size).then(|| {
+            format!(
+                "Batch of {} requests exceeds the limit of {}",
+                size, self.max_batch_size
+            )
+        })
+    }
+
+    /// Whether a batch of `size` requests is decoded in chunks
+    pub fn needs_chunking(&self, size: usize) -> bool {
+        size > self.chunk_size.max(1)
+    }
+}
+
+/// Split `requests` into chunks of at most `chunk_size`, in order
+pub fn split(
+    requests: Vec<DecodeRequest>,
+    chunk_size: usize,
+) -> Vec<(BatchChunk, Vec<DecodeRequest>)> {
+    let chunk_size = chunk_size.max(1);
+    let count = requests.len().div_ceil(chunk_size) as u32;
+    let mut requests = requests.into_iter().peekable();
+    let mut chunks = Vec::new();
+    while requests.peek().is_some() {
+        let chunk = BatchChunk {
+            index: chunks.len() as u32,
+            count,
+        };
+        chunks.push((chunk, requests.by_ref().take(chunk_size).collect()));
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(i: usize) -> DecodeRequest {
+        DecodeRequest {
+            to_address: "0x00000000000000000000000000000000000000aa".to_string(),
+            input_data: "0xa9059cbb".to_string(),
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            transaction_hash: format!("0x{:064x}", i),
+            request_id: format!("req-{}", i),
+        }
+    }
+
+    #[test]
+    fn test_batch_limits_and_chunks() {
+        let config: BatchConfig = serde_json::from_str(r#"{"chunk_size": 2}"#).unwrap();
+        assert_eq!(config.max_batch_size, 1_000);
+        assert!(config.oversize_error(1_000).is_none());
+        assert_eq!(
+            config.oversize_error(1_001).as_deref(),
+            Some("Batch of 1001 requests exceeds the limit of 1000")
+        );
+        assert!(!config.needs_chunking(2));
+        assert!(config.needs_chunking(3));
+
+        let chunks = split((0..5).map(request).collect(), config.chunk_size);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].0, BatchChunk { index: 2, count: 3 });
+        assert_eq!(chunks[2].1.len(), 1);
+        assert_eq!(chunks[1].1[0].request_id, "req-2");
+        assert!(split(Vec::new(), 0).is_empty());
+    }
+}
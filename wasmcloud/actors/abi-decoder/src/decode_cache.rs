@@ -0,0 +1,109 @@
+//! Decoded output cache
+//!
+//! Airdrop claims and mints send thousands of identical calls to one contract
+//! within minutes. The decode of a call only depends on the contract and its
+//! calldata, so it is cached in `abi_decoded:{network}:{address}:{selector}:{hash}`,
+//! `hash` being the keccak256 of the calldata, and reused for [`TTL_SECS`].
+//!
+//! Entries record the ABI registry version (`cache:registry_version:abi`) they
+//! were decoded under, so an ABI upload or deletion makes them stale at once.
+//! Proxy upgrades and newly fetched ABIs show up once the TTL runs out.
+
+use serde::{Deserialize, Serialize};
+
+use crate::escalation::DecodeOutcome;
+use crate::multicall::SubCall;
+use crate::permit::Permit;
+use crate::Component;
+
+/// How long a decode is reused
+pub const TTL_SECS: u64 = 600;
+
+/// Everything decoded from one call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallDecode {
+    pub outcome: DecodeOutcome,
+    pub sub_calls: Vec<SubCall>,
+    pub permit: Option<Permit>,
+}
+
+/// Cached [`CallDecode`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedDecode {
+    pub decode: CallDecode,
+    /// Unix seconds
+    pub cached_at: u64,
+    /// ABI registry version the decode was made under
+    pub abi_version: u64,
+}
+
+impl CachedDecode {
+    /// Whether the entry can still be served
+    pub fn is_fresh(&self, now_secs: u64, abi_version: u64) -> bool {
+        self.abi_version == abi_version && now_secs < self.cached_at.saturating_add(TTL_SECS)
+    }
+}
+
+/// Cache key of a call; `None` when the calldata is not hex
+pub fn cache_key(network: &str, address: &str, selector: &str, input_data: &str) -> Option<String> {
+    let calldata = hex::decode(input_data.trim_start_matches("0x")).ok()?;
+    let hash = Component::keccak256(&calldata);
+    Some(retention_policy::DECODE_RESULT_CACHE.key(&format!(
+        "{}:{}:{}:{}",
+        network,
+        address.to_lowercase(),
+        selector.to_lowercase(),
+        hex::encode(hash)
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escalation::DecodeLevel;
+
+    #[test]
+    fn test_cache_key_and_freshness() {
+        let key = cache_key(
+            "ethereum",
+            "0x00000000000000000000000000000000000000AA",
+            "0x4E71D92D",
+            "0x4e71d92d",
+        )
+        .unwrap();
+        assert!(key.starts_with(
+            "abi_decoded:ethereum:0x00000000000000000000000000000000000000aa:0x4e71d92d:"
+        ));
+        // Same call, any hex case: same key
+        assert_eq!(
+            cache_key(
+                "ethereum",
+                "0x00000000000000000000000000000000000000aa",
+                "0x4e71d92d",
+                "0x4E71D92D"
+            ),
+            Some(key)
+        );
+        assert_eq!(cache_key("ethereum", "0xaa", "0x4e71d92d", "0xzz"), None);
+
+        let entry = CachedDecode {
+            decode: CallDecode {
+                outcome: DecodeOutcome {
+                    level: DecodeLevel::Full,
+                    decoded_function: None,
+                    attempts: Vec::new(),
+                    abi_source: None,
+                    abi_missing: false,
+                    implementation: None,
+                },
+                sub_calls: Vec::new(),
+                permit: None,
+            },
+            cached_at: 1_000,
+            abi_version: 7,
+        };
+        assert!(entry.is_fresh(1_000 + TTL_SECS - 1, 7));
+        assert!(!entry.is_fresh(1_000 + TTL_SECS, 7));
+        assert!(!entry.is_fresh(1_001, 8));
+    }
+}
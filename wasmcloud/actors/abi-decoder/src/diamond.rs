@@ -0,0 +1,149 @@
+//! EIP-2535 diamond facet resolution
+//!
+//! A diamond routes every selector to its own facet contract, so there is no
+//! single implementation to decode against: the ABI to use depends on the
+//! function called. The diamond's loupe `facets()` lists each facet with the
+//! selectors it serves; the map is read through the http-rpc provider and
+//! cached in `diamond:facets:{network}:{address}` for a day, an empty map
+//! recording a contract that is not a diamond. Calls are then decoded with
+//! the ABI of the facet serving their selector.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::proxy::{ChainReader, ProxyImplementation, ProxyKind};
+use crate::{AbiParam, AbiValue, Component};
+
+/// Loupe `facets()`, returning `(address facetAddress, bytes4[] functionSelectors)[]`
+const FACETS_SELECTOR: &str = "0x7a0ed627";
+
+/// Facet of every selector a diamond serves
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetMap {
+    /// `0x`-prefixed selector to lowercase facet address
+    pub facets: BTreeMap<String, String>,
+}
+
+impl FacetMap {
+    /// Facet serving `selector`
+    pub fn facet_of(&self, selector: &str) -> Option<ProxyImplementation> {
+        self.facets
+            .get(&selector.to_lowercase())
+            .map(|facet| ProxyImplementation::new(facet, ProxyKind::Eip2535Facet))
+    }
+}
+
+/// Read the facets of the diamond at `address`; empty when the contract has
+/// no loupe or the call fails
+pub fn read_facets(chain: &impl ChainReader, address: &str) -> FacetMap {
+    let Some(output) = chain
+        .rpc(
+            "eth_call",
+            json!([{"to": address, "data": FACETS_SELECTOR}, "latest"]),
+        )
+        .and_then(|output| output.as_str().map(str::to_string))
+    else {
+        return FacetMap::default();
+    };
+    parse_facets(&output).unwrap_or_default()
+}
+
+fn parse_facets(output: &str) -> Option<FacetMap> {
+    let data = hex::decode(output.trim_start_matches("0x")).ok()?;
+    let facets_param = AbiParam {
+        name: "facets".to_string(),
+        param_type: "(address,bytes4[])[]".to_string(),
+        indexed: false,
+        components: None,
+    };
+    let values = Component::decode_abi_params(&[facets_param], &data).ok()?;
+    let Some(AbiValue::Array(facets)) = values.first() else {
+        return None;
+    };
+
+    let mut map = FacetMap::default();
+    for facet in facets {
+        let AbiValue::Tuple(members) = facet else {
+            return None;
+        };
+        let (Some(AbiValue::Address(facet)), Some(AbiValue::Array(selectors))) =
+            (members.first(), members.get(1))
+        else {
+            return None;
+        };
+        for selector in selectors {
+            if let AbiValue::FixedBytes(selector) = selector {
+                map.facets
+                    .entry(format!("0x{}", hex::encode(selector)))
+                    .or_insert_with(|| format!("0x{}", hex::encode(facet)));
+            }
+        }
+    }
+    Some(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const DIAMOND: &str = "0x00000000000000000000000000000000000000dd";
+
+    struct Loupe(Option<String>);
+
+    impl ChainReader for Loupe {
+        fn rpc(&self, method: &str, params: Value) -> Option<Value> {
+            assert_eq!(method, "eth_call");
+            assert_eq!(params[0]["to"], DIAMOND);
+            assert_eq!(params[0]["data"], FACETS_SELECTOR);
+            self.0.clone().map(Value::from)
+        }
+    }
+
+    #[test]
+    fn test_facet_map_from_loupe() {
+        // facets(): [(0x..f1, [transfer, approve]), (0x..f2, [balanceOf])]
+        let output = format!(
+            "0x{}",
+            [
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "00000000000000000000000000000000000000000000000000000000000000f1",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "a9059cbb00000000000000000000000000000000000000000000000000000000",
+                "095ea7b300000000000000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000f2",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "70a0823100000000000000000000000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+        let map = read_facets(&Loupe(Some(output)), DIAMOND);
+        assert_eq!(map.facets.len(), 3);
+        assert_eq!(
+            map.facet_of("0xA9059CBB"),
+            Some(ProxyImplementation::new(
+                "0x00000000000000000000000000000000000000f1",
+                ProxyKind::Eip2535Facet
+            ))
+        );
+        assert_eq!(
+            map.facet_of("0x70a08231").unwrap().address,
+            "0x00000000000000000000000000000000000000f2"
+        );
+        assert_eq!(map.facet_of("0x23b872dd"), None);
+
+        // No loupe: not a diamond
+        assert_eq!(read_facets(&Loupe(None), DIAMOND), FacetMap::default());
+        assert_eq!(
+            read_facets(&Loupe(Some("0x".to_string())), DIAMOND),
+            FacetMap::default()
+        );
+    }
+}
@@ -0,0 +1,555 @@
+//! Decode escalation ladder
+//!
+//! A single cached ABI is often not enough: proxies are cached with the proxy's
+//! own ABI, and uploaded ABIs can be partial. When an attempt fails the decoder
+//! escalates through progressively weaker sources:
+//!
+//! 1. cached ABI for the called address
+//! 2. implementation ABI, when the address resolves to a proxy implementation
+//!    (see [`crate::proxy`])
+//! 3. 4byte text signature for the selector (types only, no parameter names),
+//!    from the `abi_signature:{selector}` directory the abi-decoder provider
+//!    seeds, else a short built-in list (which includes the multicall entry
+//!    points, see [`crate::multicall`])
+//! 4. heuristic decode of the raw 32-byte argument words
+//!
+//! Every attempt is recorded with its source; the final level is the one the
+//! first successful attempt reached.
+
+use serde::{Deserialize, Serialize};
+
+use crate::abi_type::split_top_level;
+use crate::proxy::ProxyImplementation;
+use crate::{AbiInfo, AbiParam, Component, DecodedFunction, DecodedParameter};
+
+/// Well-known selectors used when the signature registry has no entry
+const KNOWN_SIGNATURES: &[(&str, &str)] = &[
+    ("0xa9059cbb", "transfer(address,uint256)"),
+    ("0x095ea7b3", "approve(address,uint256)"),
+    ("0x23b872dd", "transferFrom(address,address,uint256)"),
+    ("0x42842e0e", "safeTransferFrom(address,address,uint256)"),
+    ("0xa22cb465", "setApprovalForAll(address,bool)"),
+    ("0xd0e30db0", "deposit()"),
+    ("0x2e1a7d4d", "withdraw(uint256)"),
+    ("0x40c10f19", "mint(address,uint256)"),
+    ("0x42966c68", "burn(uint256)"),
+];
+
+/// Where a decode attempt took its ABI from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodeSource {
+    CachedAbi,
+    ImplementationAbi,
+    FourByte,
+    Heuristic,
+}
+
+impl DecodeSource {
+    /// Level a successful attempt from this source reaches
+    pub fn level(self) -> DecodeLevel {
+        match self {
+            DecodeSource::CachedAbi | DecodeSource::ImplementationAbi => DecodeLevel::Full,
+            DecodeSource::FourByte => DecodeLevel::Partial,
+            DecodeSource::Heuristic => DecodeLevel::Heuristic,
+        }
+    }
+}
+
+/// How much of the call could be decoded, weakest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodeLevel {
+    #[default]
+    Failed,
+    /// Argument words classified by shape only
+    Heuristic,
+    /// Types from a text signature, no parameter names
+    Partial,
+    /// Full ABI entry with names
+    Full,
+}
+
+/// One rung of the ladder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodeAttempt {
+    pub source: DecodeSource,
+    pub succeeded: bool,
+    /// ABI source / signature on success, error on failure
+    pub detail: String,
+}
+
+/// Result of walking the ladder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeOutcome {
+    pub level: DecodeLevel,
+    pub decoded_function: Option<DecodedFunction>,
+    pub attempts: Vec<DecodeAttempt>,
+    /// Source of the ABI the result came from, if any
+    pub abi_source: Option<String>,
+    /// True when neither the address nor its implementation had a cached ABI
+    pub abi_missing: bool,
+    /// Implementation behind the called address when it is a proxy
+    pub implementation: Option<ProxyImplementation>,
+}
+
+impl DecodeOutcome {
+    /// Pipeline `decoding_status` string for the achieved level
+    pub fn status(&self) -> String {
+        match self.level {
+            DecodeLevel::Full => "Success".to_string(),
+            DecodeLevel::Partial => "PartialDecoded".to_string(),
+            DecodeLevel::Heuristic => "HeuristicDecoded".to_string(),
+            DecodeLevel::Failed if self.abi_missing => "AbiNotFound".to_string(),
+            DecodeLevel::Failed => format!("DecodingFailed: {}", self.last_abi_error()),
+        }
+    }
+
+    /// Error of the last full-ABI attempt, the most useful failure to surface
+    pub fn last_abi_error(&self) -> &str {
+        self.attempts
+            .iter()
+            .rev()
+            .find(|a| a.source.level() == DecodeLevel::Full)
+            .map(|a| a.detail.as_str())
+            .unwrap_or_default()
+    }
+}
+
+/// ABI material the ladder draws from
+pub trait AbiLookup {
+    fn cached_abi(&self, address: &str, network: &str) -> Option<AbiInfo>;
+    /// Implementation behind `address` if it is a proxy
+    fn implementation_of(
+        &self,
+        address: &str,
+        network: &str,
+        subnet: &str,
+        cached: Option<&AbiInfo>,
+    ) -> Option<ProxyImplementation>;
+    /// Text signature for a `0x`-prefixed selector, e.g. `transfer(address,uint256)`
+    fn signature(&self, selector: &str) -> Option<String>;
+    /// Facet serving `selector` if `address` is an EIP-2535 diamond
+    fn facet_of(
+        &self,
+        _address: &str,
+        _network: &str,
+        _subnet: &str,
+        _selector: &str,
+    ) -> Option<ProxyImplementation> {
+        None
+    }
+}
+
+/// Walk the ladder until an attempt succeeds
+pub fn decode_with_escalation(
+    lookup: &impl AbiLookup,
+    address: &str,
+    network: &str,
+    subnet: &str,
+    selector: &str,
+    input_data: &str,
+) -> DecodeOutcome {
+    let mut attempts = Vec::new();
+
+    let cached = lookup.cached_abi(address, network);
+    match &cached {
+        Some(abi) => match try_abi(abi, selector, input_data) {
+            Ok(decoded) => {
+                attempts.push(succeeded(DecodeSource::CachedAbi, &abi.source));
+                return finish(DecodeSource::CachedAbi, attempts, decoded, false, None);
+            }
+            Err(e) => attempts.push(failed(DecodeSource::CachedAbi, e)),
+        },
+        None => attempts.push(failed(
+            DecodeSource::CachedAbi,
+            "ABI not cached".to_string(),
+        )),
+    }
+
+    let mut implementation_cached = false;
+    let implementation = lookup
+        .implementation_of(address, network, subnet, cached.as_ref())
+        .or_else(|| lookup.facet_of(address, network, subnet, selector))
+        .filter(|implementation| !implementation.address.eq_ignore_ascii_case(address));
+    match &implementation {
+        Some(proxy) => match lookup.cached_abi(&proxy.address, network) {
+            Some(abi) => {
+                implementation_cached = true;
+                match try_abi(&abi, selector, input_data) {
+                    Ok(decoded) => {
+                        let detail = format!("{} ({})", proxy.address, abi.source);
+                        attempts.push(succeeded(DecodeSource::ImplementationAbi, &detail));
+                        return finish(
+                            DecodeSource::ImplementationAbi,
+                            attempts,
+                            decoded,
+                            false,
+                            implementation.clone(),
+                        );
+                    }
+                    Err(e) => attempts.push(failed(DecodeSource::ImplementationAbi, e)),
+                }
+            }
+            None => attempts.push(failed(
+                DecodeSource::ImplementationAbi,
+                format!("ABI not cached for implementation {}", proxy.address),
+            )),
+        },
+        None => attempts.push(failed(
+            DecodeSource::ImplementationAbi,
+            "No proxy implementation resolved".to_string(),
+        )),
+    }
+    let abi_missing = cached.is_none() && !implementation_cached;
+
+    let signature = lookup
+        .signature(selector)
+        .or_else(|| known_signature(selector).map(str::to_string));
+    match signature {
+        Some(signature) => match decode_with_signature(&signature, selector, input_data) {
+            Ok(decoded) => {
+                attempts.push(succeeded(DecodeSource::FourByte, &signature));
+                return finish(
+                    DecodeSource::FourByte,
+                    attempts,
+                    decoded,
+                    abi_missing,
+                    implementation,
+                );
+            }
+            Err(e) => attempts.push(failed(DecodeSource::FourByte, e)),
+        },
+        None => attempts.push(failed(
+            DecodeSource::FourByte,
+            format!("No signature known for {}", selector),
+        )),
+    }
+
+    match decode_heuristic(selector, input_data) {
+        Ok(decoded) => {
+            attempts.push(succeeded(DecodeSource::Heuristic, &decoded.signature));
+            finish(
+                DecodeSource::Heuristic,
+                attempts,
+                decoded,
+                abi_missing,
+                implementation,
+            )
+        }
+        Err(e) => {
+            attempts.push(failed(DecodeSource::Heuristic, e));
+            DecodeOutcome {
+                level: DecodeLevel::Failed,
+                decoded_function: None,
+                attempts,
+                abi_source: cached.map(|abi| abi.source),
+                abi_missing,
+                implementation,
+            }
+        }
+    }
+}
+
+fn finish(
+    source: DecodeSource,
+    attempts: Vec<DecodeAttempt>,
+    decoded: DecodedFunction,
+    abi_missing: bool,
+    implementation: Option<ProxyImplementation>,
+) -> DecodeOutcome {
+    DecodeOutcome {
+        level: source.level(),
+        abi_source: Some(decoded.abi_source.clone()),
+        decoded_function: Some(decoded),
+        attempts,
+        abi_missing,
+        implementation,
+    }
+}
+
+fn succeeded(source: DecodeSource, detail: &str) -> DecodeAttempt {
+    DecodeAttempt {
+        source,
+        succeeded: true,
+        detail: detail.to_string(),
+    }
+}
+
+fn failed(source: DecodeSource, error: String) -> DecodeAttempt {
+    DecodeAttempt {
+        source,
+        succeeded: false,
+        detail: error,
+    }
+}
+
+fn try_abi(abi: &AbiInfo, selector: &str, input_data: &str) -> Result<DecodedFunction, String> {
+    Component::decode_with_alloy(abi, selector, input_data).map_err(|e| e.to_string())
+}
+
+fn known_signature(selector: &str) -> Option<&'static str> {
+    KNOWN_SIGNATURES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(selector))
+        .map(|(_, signature)| *signature)
+        .or_else(|| crate::multicall::multicall_signature(selector))
+        .or_else(|| crate::permit::permit_signature(selector))
+}
+
+/// Split `name(type,type)` into the name and its top-level parameter types
+pub fn parse_signature(signature: &str) -> Result<(String, Vec<AbiParam>), String> {
+    let (name, rest) = signature
+        .split_once('(')
+        .ok_or_else(|| format!("Malformed signature: {}", signature))?;
+    let types = rest
+        .strip_suffix(')')
+        .ok_or_else(|| format!("Malformed signature: {}", signature))?;
+
+    let inputs = split_top_level(types)
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| AbiParam {
+            name: format!("arg{}", i),
+            param_type: t.trim().to_string(),
+            indexed: false,
+            components: None,
+        })
+        .collect();
+    Ok((name.to_string(), inputs))
+}
+
+fn decode_with_signature(
+    signature: &str,
+    selector: &str,
+    input_data: &str,
+) -> Result<DecodedFunction, String> {
+    let (name, inputs) = parse_signature(signature)?;
+    let data = argument_bytes(input_data)?;
+    let values = Component::decode_abi_params(&inputs, &data)?;
+
+    Ok(DecodedFunction {
+        signature: Component::build_signature(&name, &inputs),
+        name,
+        selector: selector.to_string(),
+        parameters: inputs
+            .iter()
+            .zip(&values)
+            .map(|(param, value)| DecodedParameter {
+                name: param.name.clone(),
+                param_type: param.param_type.clone(),
+                value: Component::format_abi_value(value),
+                indexed: false,
+            })
+            .collect(),
+        abi_source: "4byte".to_string(),
+    })
+}
+
+/// Classify each argument word: a value with 12 zero bytes followed by a
+/// non-zero high byte range is taken as an address, anything else as uint256
+fn decode_heuristic(selector: &str, input_data: &str) -> Result<DecodedFunction, String> {
+    let data = argument_bytes(input_data)?;
+    if data.is_empty() {
+        return Err("No arguments to infer".to_string());
+    }
+    if data.len() % 32 != 0 {
+        return Err(format!("Calldata not word-aligned ({} bytes)", data.len()));
+    }
+
+    let parameters: Vec<DecodedParameter> = data
+        .chunks(32)
+        .enumerate()
+        .map(|(i, word)| {
+            let looks_like_address =
+                word[..12].iter().all(|b| *b == 0) && word[12..16].iter().any(|b| *b != 0);
+            let (param_type, value) = if looks_like_address {
+                ("address", format!("0x{}", hex::encode(&word[12..])))
+            } else {
+                let mut val = [0u8; 32];
+                val.copy_from_slice(word);
+                ("uint256", Component::u256_to_decimal(&val))
+            };
+            DecodedParameter {
+                name: format!("word{}", i),
+                param_type: param_type.to_string(),
+                value,
+                indexed: false,
+            }
+        })
+        .collect();
+    let types: Vec<&str> = parameters.iter().map(|p| p.param_type.as_str()).collect();
+
+    Ok(DecodedFunction {
+        name: "unknown".to_string(),
+        selector: selector.to_string(),
+        signature: format!("unknown({})", types.join(",")),
+        parameters,
+        abi_source: "heuristic".to_string(),
+    })
+}
+
+pub fn argument_bytes(input_data: &str) -> Result<Vec<u8>, String> {
+    let args = input_data
+        .get(10..)
+        .ok_or_else(|| "Input shorter than a selector".to_string())?;
+    hex::decode(args).map_err(|e| format!("Invalid calldata hex: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::ProxyKind;
+    use std::collections::HashMap;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const IMPLEMENTATION: &str = "0x00000000000000000000000000000000000000bb";
+    // transfer(0x1111..., 1000)
+    const TRANSFER_INPUT: &str = "0xa9059cbb\
+        0000000000000000000000001111111111111111111111111111111111111111\
+        00000000000000000000000000000000000000000000000000000000000003e8";
+
+    #[derive(Default)]
+    struct MockLookup {
+        abis: HashMap<String, AbiInfo>,
+        implementations: HashMap<String, ProxyImplementation>,
+    }
+
+    impl MockLookup {
+        fn with_abi(mut self, address: &str, abi_json: &str) -> Self {
+            self.abis.insert(
+                address.to_string(),
+                AbiInfo {
+                    address: address.to_string(),
+                    network: "ethereum".to_string(),
+                    abi_json: abi_json.to_string(),
+                    source: format!("etherscan:{}", address),
+                    verified: true,
+                    cached_at: String::new(),
+                    implementation_address: None,
+                    uploaded_by: None,
+                    expires_at: None,
+                    selector_index: Default::default(),
+                },
+            );
+            self
+        }
+    }
+
+    impl AbiLookup for MockLookup {
+        fn cached_abi(&self, address: &str, _network: &str) -> Option<AbiInfo> {
+            self.abis.get(address).cloned()
+        }
+
+        fn implementation_of(
+            &self,
+            address: &str,
+            _network: &str,
+            _subnet: &str,
+            _cached: Option<&AbiInfo>,
+        ) -> Option<ProxyImplementation> {
+            self.implementations.get(address).cloned()
+        }
+
+        fn signature(&self, _selector: &str) -> Option<String> {
+            None
+        }
+    }
+
+    const PROXY_ABI: &str =
+        r#"[{"type":"function","name":"upgradeTo","inputs":[{"name":"impl","type":"address"}]}]"#;
+    const TOKEN_ABI: &str = r#"[{"type":"function","name":"transfer","inputs":[
+        {"name":"to","type":"address"},{"name":"amount","type":"uint256"}]}]"#;
+
+    #[test]
+    fn test_proxy_escalates_to_implementation_abi() {
+        let mut lookup = MockLookup::default()
+            .with_abi(TOKEN, PROXY_ABI)
+            .with_abi(IMPLEMENTATION, TOKEN_ABI);
+        lookup.implementations.insert(
+            TOKEN.to_string(),
+            ProxyImplementation::new(IMPLEMENTATION, ProxyKind::Eip1967),
+        );
+
+        let outcome = decode_with_escalation(
+            &lookup,
+            TOKEN,
+            "ethereum",
+            "mainnet",
+            "0xa9059cbb",
+            TRANSFER_INPUT,
+        );
+
+        assert_eq!(outcome.level, DecodeLevel::Full);
+        let implementation = outcome.implementation.as_ref().unwrap();
+        assert_eq!(implementation.address, IMPLEMENTATION);
+        assert_eq!(implementation.kind, ProxyKind::Eip1967);
+        assert_eq!(outcome.status(), "Success");
+        let sources: Vec<(DecodeSource, bool)> = outcome
+            .attempts
+            .iter()
+            .map(|a| (a.source, a.succeeded))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                (DecodeSource::CachedAbi, false),
+                (DecodeSource::ImplementationAbi, true)
+            ]
+        );
+        let decoded = outcome.decoded_function.unwrap();
+        assert_eq!(decoded.parameters[0].name, "to");
+        assert_eq!(decoded.parameters[1].value, "1000");
+    }
+
+    #[test]
+    fn test_missing_abi_falls_back_to_4byte_then_heuristic() {
+        let lookup = MockLookup::default();
+
+        let outcome = decode_with_escalation(
+            &lookup,
+            TOKEN,
+            "ethereum",
+            "mainnet",
+            "0xa9059cbb",
+            TRANSFER_INPUT,
+        );
+        assert_eq!(outcome.level, DecodeLevel::Partial);
+        assert_eq!(outcome.status(), "PartialDecoded");
+        let decoded = outcome.decoded_function.unwrap();
+        assert_eq!(decoded.signature, "transfer(address,uint256)");
+        assert_eq!(decoded.parameters[0].name, "arg0");
+        assert_eq!(
+            decoded.parameters[0].value,
+            "0x1111111111111111111111111111111111111111"
+        );
+
+        let unknown = TRANSFER_INPUT.replacen("a9059cbb", "deadbeef", 1);
+        let outcome = decode_with_escalation(
+            &lookup,
+            TOKEN,
+            "ethereum",
+            "mainnet",
+            "0xdeadbeef",
+            &unknown,
+        );
+        assert_eq!(outcome.level, DecodeLevel::Heuristic);
+        assert_eq!(outcome.attempts.len(), 4);
+        assert_eq!(
+            outcome.decoded_function.unwrap().signature,
+            "unknown(address,uint256)"
+        );
+
+        let outcome = decode_with_escalation(
+            &lookup,
+            TOKEN,
+            "ethereum",
+            "mainnet",
+            "0xdeadbeef",
+            "0xdeadbeef",
+        );
+        assert_eq!(outcome.level, DecodeLevel::Failed);
+        assert_eq!(outcome.status(), "AbiNotFound");
+        assert!(outcome.attempts.iter().all(|a| !a.succeeded));
+    }
+}
@@ -0,0 +1,457 @@
+//! Event log decoding
+//!
+//! Receipt logs are matched against the `event` entries of the emitting
+//! contract's cached ABI: topic0 is the keccak256 of the event signature, and
+//! the remaining topics hold the indexed parameters in declaration order.
+//! ERC-20 and ERC-721 `Transfer` share a signature and differ only in which
+//! parameters are indexed, so an event is only taken when its indexed count
+//! matches the log's topics.
+//!
+//! Indexed static parameters are decoded from their topic word. Indexed
+//! dynamic parameters (`string`, `bytes`, arrays, tuples) are stored as the
+//! keccak256 of their value, which cannot be reversed; their value is the topic
+//! hash. Non-indexed parameters are ABI-decoded from the log data.
+//!
+//! Logs emitted by a proxy are decoded with the implementation's ABI when the
+//! proxy's own ABI has no matching event. Anonymous events have no topic0 and
+//! are not matched.
+
+use serde::{Deserialize, Serialize};
+
+use crate::escalation::AbiLookup;
+use crate::proxy::ProxyImplementation;
+use crate::{AbiEntry, AbiInfo, AbiParam, Component, DecodedParameter};
+
+/// Receipt logs of one transaction to decode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogDecodeRequest {
+    /// Network (e.g., "ethereum", "polygon")
+    pub network: String,
+    /// Subnet (e.g., "mainnet", "sepolia")
+    pub subnet: String,
+    /// Logs as found in the transaction receipt
+    pub logs: Vec<ReceiptLog>,
+}
+
+/// Log as found in a transaction receipt (quantities stay hex strings)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptLog {
+    pub address: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub data: String,
+    #[serde(default)]
+    pub block_number: Option<String>,
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    #[serde(default)]
+    pub log_index: Option<String>,
+}
+
+/// Decoded event information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedEvent {
+    /// Event name
+    pub name: String,
+    /// Event signature, e.g. `Transfer(address,address,uint256)`
+    pub signature: String,
+    /// Decoded parameters in declaration order
+    pub parameters: Vec<DecodedParameter>,
+    /// ABI source
+    pub abi_source: String,
+}
+
+/// Receipt log enriched with its decoded event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedLog {
+    pub network: String,
+    pub subnet: String,
+    pub address: String,
+    /// Implementation whose ABI decoded the log, when `address` is a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<ProxyImplementation>,
+    pub transaction_hash: Option<String>,
+    pub block_number: u64,
+    pub log_index: Option<u64>,
+    pub topics: Vec<String>,
+    pub data: String,
+    /// Decode status: `Success`, `AbiNotFound`, `EventNotFound` or `DecodingError`
+    pub decoding_status: String,
+    /// Decoded event (if successful)
+    pub decoded_event: Option<DecodedEvent>,
+    /// Decode error (if any)
+    pub error: Option<String>,
+    /// Processing timestamp
+    pub processed_at: String,
+    /// Processor ID
+    pub processor_id: String,
+}
+
+/// Decode every log of `request`, in receipt order
+pub fn decode_logs(
+    lookup: &impl AbiLookup,
+    request: &LogDecodeRequest,
+    processed_at: &str,
+) -> Vec<DecodedLog> {
+    request
+        .logs
+        .iter()
+        .map(|log| {
+            let mut implementation = None;
+            let (decoding_status, decoded_event, error) =
+                match decode_log(lookup, &request.network, &request.subnet, log) {
+                    Ok((event, via)) => {
+                        implementation = via;
+                        ("Success", Some(event), None)
+                    }
+                    Err(LogError::AbiNotFound) => (
+                        "AbiNotFound",
+                        None,
+                        Some(format!("No ABI cached for {}", log.address)),
+                    ),
+                    Err(LogError::EventNotFound) => (
+                        "EventNotFound",
+                        None,
+                        Some("No ABI event matches topic0".to_string()),
+                    ),
+                    Err(LogError::Decoding(error)) => ("DecodingError", None, Some(error)),
+                };
+            DecodedLog {
+                network: request.network.clone(),
+                subnet: request.subnet.clone(),
+                address: log.address.clone(),
+                implementation,
+                transaction_hash: log.transaction_hash.clone(),
+                block_number: log
+                    .block_number
+                    .as_deref()
+                    .map(Component::parse_hex_u64)
+                    .unwrap_or(0),
+                log_index: log.log_index.as_deref().map(Component::parse_hex_u64),
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+                decoding_status: decoding_status.to_string(),
+                decoded_event,
+                error,
+                processed_at: processed_at.to_string(),
+                processor_id: "abi-decoder-actor".to_string(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+enum LogError {
+    /// Neither the address nor its implementation has a cached ABI
+    AbiNotFound,
+    /// ABIs were cached but none has a matching event
+    EventNotFound,
+    Decoding(String),
+}
+
+/// Decode one log with the emitter's ABI, then its implementation's; the
+/// implementation is returned when its ABI decoded the log
+fn decode_log(
+    lookup: &impl AbiLookup,
+    network: &str,
+    subnet: &str,
+    log: &ReceiptLog,
+) -> Result<(DecodedEvent, Option<ProxyImplementation>), LogError> {
+    let cached = lookup.cached_abi(&log.address, network);
+    let mut result = Err(LogError::AbiNotFound);
+    if let Some(abi) = &cached {
+        result = decode_with_abi(abi, log).map(|event| (event, None));
+        if !matches!(result, Err(LogError::EventNotFound)) {
+            return result;
+        }
+    }
+
+    let Some(implementation) = lookup
+        .implementation_of(&log.address, network, subnet, cached.as_ref())
+        .filter(|implementation| !implementation.address.eq_ignore_ascii_case(&log.address))
+    else {
+        return result;
+    };
+    match lookup.cached_abi(&implementation.address, network) {
+        Some(abi) => decode_with_abi(&abi, log).map(|event| (event, Some(implementation))),
+        None => result,
+    }
+}
+
+/// Decode `log` with the event of `abi` its topic0 names
+fn decode_with_abi(abi: &AbiInfo, log: &ReceiptLog) -> Result<DecodedEvent, LogError> {
+    let entries: Vec<AbiEntry> = serde_json::from_str(&abi.abi_json)
+        .map_err(|e| LogError::Decoding(format!("Invalid ABI JSON: {}", e)))?;
+    let topic0 = log
+        .topics
+        .first()
+        .ok_or(LogError::EventNotFound)?
+        .to_lowercase();
+
+    let event = entries
+        .iter()
+        .filter(|entry| entry.entry_type == "event" && !entry.anonymous)
+        .find(|event| {
+            let indexed = event.inputs.iter().filter(|input| input.indexed).count();
+            event_topic(event) == topic0 && indexed + 1 == log.topics.len()
+        })
+        .ok_or(LogError::EventNotFound)?;
+
+    decode_event(event, log, &abi.source).map_err(LogError::Decoding)
+}
+
+/// `0x`-prefixed topic0 of an event
+pub fn event_topic(event: &AbiEntry) -> String {
+    let signature = Component::build_signature(&event.name, &event.inputs);
+    format!(
+        "0x{}",
+        hex::encode(Component::keccak256(signature.as_bytes()))
+    )
+}
+
+fn decode_event(event: &AbiEntry, log: &ReceiptLog, source: &str) -> Result<DecodedEvent, String> {
+    let data = hex::decode(log.data.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid log data: {}", e))?;
+    let non_indexed: Vec<AbiParam> = event
+        .inputs
+        .iter()
+        .filter(|input| !input.indexed)
+        .cloned()
+        .collect();
+    let mut data_values = Component::decode_abi_params(&non_indexed, &data)?.into_iter();
+    let mut topics = log.topics.iter().skip(1);
+
+    let mut parameters = Vec::with_capacity(event.inputs.len());
+    for input in &event.inputs {
+        let value = if input.indexed {
+            let topic = topics.next().ok_or("Missing topic for indexed parameter")?;
+            indexed_value(&input.param_type, topic)?
+        } else {
+            let value = data_values
+                .next()
+                .ok_or("Missing data for non-indexed parameter")?;
+            Component::format_abi_value(&value)
+        };
+        parameters.push(DecodedParameter {
+            name: input.name.clone(),
+            param_type: input.param_type.clone(),
+            value,
+            indexed: input.indexed,
+        });
+    }
+
+    Ok(DecodedEvent {
+        name: event.name.clone(),
+        signature: Component::build_signature(&event.name, &event.inputs),
+        parameters,
+        abi_source: source.to_string(),
+    })
+}
+
+/// Value of an indexed parameter; dynamic types only left their hash
+fn indexed_value(param_type: &str, topic: &str) -> Result<String, String> {
+    if is_hashed_in_topic(param_type) {
+        return Ok(topic.to_lowercase());
+    }
+    let word =
+        hex::decode(topic.trim_start_matches("0x")).map_err(|e| format!("Invalid topic: {}", e))?;
+    let (value, _) = Component::decode_single_param(param_type, &word, 0)?;
+    Ok(Component::format_abi_value(&value))
+}
+
+fn is_hashed_in_topic(param_type: &str) -> bool {
+    param_type == "string"
+        || param_type == "bytes"
+        || param_type.starts_with("tuple")
+        || param_type.ends_with(']')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::ProxyKind;
+    use std::collections::HashMap;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const NFT: &str = "0x00000000000000000000000000000000000000cc";
+    const TRANSFER_TOPIC: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    const FROM_TOPIC: &str = "0x0000000000000000000000001111111111111111111111111111111111111111";
+    const TO_TOPIC: &str = "0x0000000000000000000000002222222222222222222222222222222222222222";
+    const ERC20_ABI: &str = r#"[
+        {"type":"function","name":"transfer","inputs":[
+            {"name":"to","type":"address"},{"name":"amount","type":"uint256"}]},
+        {"type":"event","name":"Transfer","anonymous":false,"inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"value","type":"uint256","indexed":false}]}]"#;
+    const ERC721_ABI: &str = r#"[
+        {"type":"event","name":"Transfer","inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"tokenId","type":"uint256","indexed":true}]},
+        {"type":"event","name":"Named","inputs":[
+            {"name":"label","type":"string","indexed":true},
+            {"name":"note","type":"string","indexed":false}]}]"#;
+
+    #[derive(Default)]
+    struct MockLookup {
+        abis: HashMap<String, AbiInfo>,
+        implementations: HashMap<String, ProxyImplementation>,
+    }
+
+    impl MockLookup {
+        fn with_abi(mut self, address: &str, abi_json: &str) -> Self {
+            self.abis.insert(
+                address.to_string(),
+                AbiInfo {
+                    address: address.to_string(),
+                    network: "ethereum".to_string(),
+                    abi_json: abi_json.to_string(),
+                    source: format!("etherscan:{}", address),
+                    verified: true,
+                    cached_at: String::new(),
+                    implementation_address: None,
+                    uploaded_by: None,
+                    expires_at: None,
+                    selector_index: Default::default(),
+                },
+            );
+            self
+        }
+    }
+
+    impl AbiLookup for MockLookup {
+        fn cached_abi(&self, address: &str, _network: &str) -> Option<AbiInfo> {
+            self.abis.get(address).cloned()
+        }
+
+        fn implementation_of(
+            &self,
+            address: &str,
+            _network: &str,
+            _subnet: &str,
+            _cached: Option<&AbiInfo>,
+        ) -> Option<ProxyImplementation> {
+            self.implementations.get(address).cloned()
+        }
+
+        fn signature(&self, _selector: &str) -> Option<String> {
+            None
+        }
+    }
+
+    fn log(address: &str, topics: &[&str], data: &str) -> ReceiptLog {
+        ReceiptLog {
+            address: address.to_string(),
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+            data: data.to_string(),
+            block_number: Some("0x10".to_string()),
+            transaction_hash: Some("0xabc".to_string()),
+            log_index: Some("0x2".to_string()),
+        }
+    }
+
+    fn request(logs: Vec<ReceiptLog>) -> LogDecodeRequest {
+        LogDecodeRequest {
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            logs,
+        }
+    }
+
+    #[test]
+    fn test_indexed_count_tells_erc20_from_erc721_transfer() {
+        let lookup = MockLookup::default()
+            .with_abi(TOKEN, ERC20_ABI)
+            .with_abi(NFT, ERC721_ABI);
+        let amount = format!("0x{:064x}", 1000);
+        let token_id = format!("0x{:064x}", 7);
+        let decoded = decode_logs(
+            &lookup,
+            &request(vec![
+                log(TOKEN, &[TRANSFER_TOPIC, FROM_TOPIC, TO_TOPIC], &amount),
+                log(
+                    NFT,
+                    &[TRANSFER_TOPIC, FROM_TOPIC, TO_TOPIC, &token_id],
+                    "0x",
+                ),
+                // Three topics do not fit the ERC-721 Transfer
+                log(NFT, &[TRANSFER_TOPIC, FROM_TOPIC, TO_TOPIC], &amount),
+            ]),
+            "2026-01-01T00:00:00Z",
+        );
+
+        assert_eq!(decoded[0].decoding_status, "Success");
+        assert_eq!(decoded[0].block_number, 16);
+        assert_eq!(decoded[0].log_index, Some(2));
+        let event = decoded[0].decoded_event.as_ref().unwrap();
+        assert_eq!(event.signature, "Transfer(address,address,uint256)");
+        assert_eq!(
+            event.parameters[0].value,
+            "0x1111111111111111111111111111111111111111"
+        );
+        assert!(event.parameters[1].indexed);
+        assert_eq!(event.parameters[2].value, "1000");
+        assert!(!event.parameters[2].indexed);
+
+        let event = decoded[1].decoded_event.as_ref().unwrap();
+        assert_eq!(event.parameters[2].name, "tokenId");
+        assert_eq!(event.parameters[2].value, "7");
+
+        assert_eq!(decoded[2].decoding_status, "EventNotFound");
+    }
+
+    #[test]
+    fn test_indexed_dynamic_params_keep_topic_hash() {
+        let lookup = MockLookup::default().with_abi(NFT, ERC721_ABI);
+        let entries: Vec<AbiEntry> = serde_json::from_str(ERC721_ABI).unwrap();
+        let named = event_topic(&entries[1]);
+        let label_hash = format!("0x{}", "ab".repeat(32));
+        // The non-indexed "hi": offset, length, padded bytes
+        let data = format!("0x{:064x}{:064x}{:0<64}", 32, 2, hex::encode("hi"));
+        let decoded = decode_logs(
+            &lookup,
+            &request(vec![log(NFT, &[&named, &label_hash], &data)]),
+            "2026-01-01T00:00:00Z",
+        );
+
+        let event = decoded[0].decoded_event.as_ref().unwrap();
+        assert_eq!(event.name, "Named");
+        assert_eq!(event.parameters[0].value, label_hash);
+        assert_eq!(event.parameters[1].value, "hi");
+    }
+
+    #[test]
+    fn test_proxy_logs_use_implementation_abi() {
+        let implementation = "0x00000000000000000000000000000000000000bb";
+        let proxy_abi = r#"[{"type":"event","name":"Upgraded","inputs":[
+            {"name":"implementation","type":"address","indexed":true}]}]"#;
+        let mut lookup = MockLookup::default()
+            .with_abi(TOKEN, proxy_abi)
+            .with_abi(implementation, ERC20_ABI);
+        let amount = format!("0x{:064x}", 5);
+        let transfer = log(TOKEN, &[TRANSFER_TOPIC, FROM_TOPIC, TO_TOPIC], &amount);
+
+        let decoded = decode_logs(&lookup, &request(vec![transfer.clone()]), "");
+        assert_eq!(decoded[0].decoding_status, "EventNotFound");
+
+        lookup.implementations.insert(
+            TOKEN.to_string(),
+            ProxyImplementation::new(implementation, ProxyKind::Registry),
+        );
+        let decoded = decode_logs(&lookup, &request(vec![transfer.clone()]), "");
+        let event = decoded[0].decoded_event.as_ref().unwrap();
+        assert_eq!(event.abi_source, format!("etherscan:{}", implementation));
+        assert_eq!(
+            decoded[0].implementation.as_ref().unwrap().address,
+            implementation
+        );
+
+        let other = log(NFT, &[TRANSFER_TOPIC], "0x");
+        let decoded = decode_logs(&lookup, &request(vec![other]), "");
+        assert_eq!(decoded[0].decoding_status, "AbiNotFound");
+    }
+}
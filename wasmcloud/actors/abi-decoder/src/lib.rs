@@ -5,20 +5,71 @@
 //!
 //! ## Message Subjects
 //! - `contract-transactions.{network}.{subnet}.*.raw` - Contract transactions from pipeline
-//! - `abi.decode.request` - Direct ABI decode requests
-//! - `abi.decode.batch` - Batch decode requests
+//! - `abi.decode.dispatch` - Decode requests from the decode-queue provider
+//!   (answered on the reply subject so the queue can acknowledge them); direct
+//!   `abi.decode.request` messages are consumed by the queue, not by this actor
+//! - `abi.decode.batch` - Batch decode requests, bounded and split into chunks
+//!   (see [`batch`])
+//! - `abi.decode.logs` - Receipt logs of a transaction to decode against the
+//!   emitting contracts' cached ABIs (see [`events`])
+//! - `abi.decode.output` - Return or revert data of a call (from traces or
+//!   `eth_call`) to decode against the function's outputs (see [`outputs`]);
+//!   answered on the reply subject
+//! - `abi.registry.put` / `abi.registry.get` / `abi.registry.delete` - Manage
+//!   cached ABIs, e.g. uploads for unverified contracts (see [`registry`])
 //!
 //! ## Output Subjects
 //! - `blockchain.{network}.{subnet}.contracts.decoded` - Successfully decoded contract transactions
+//! - `blockchain.{network}.{subnet}.logs.decoded` - Receipt logs enriched with their decoded event
+//! - `blockchain.{network}.{subnet}.userops.decoded` - ERC-4337 UserOperations of
+//!   `handleOps` bundles, one message each (see [`user_ops`])
 //! - `abi.decode.result` - Single decode results
-//! - `abi.decode.batch.result` - Batch decode results
+//! - `abi.decode.batch.result` - Batch decode results, one per chunk
+//! - `abi.decode.output.result` - Decoded outputs of requests without a reply subject
+//! - `ducklake.transactions.{network}.{subnet}.upsert` - Decode outcome of a direct
+//!   request, replacing the row's `Pending` status
 //!
 //! NOTE: HTTP capability temporarily disabled due to WASI 0.2.3 incompatibility.
 //! ABIs must be pre-populated in Redis cache using key format: abi:{network}:{contract_address}
+//! The contract creation processor seeds this key from Solidity metadata on IPFS
+//! for newly deployed contracts; other contracts can be uploaded through
+//! `abi.registry.put`.
+//!
+//! When the cached ABI cannot decode a call, the decoder escalates to the proxy
+//! implementation's ABI, then a 4byte signature, then a heuristic word decode
+//! (see [`escalation`]). Every attempt is reported in `decode_attempts`.
+//! Proxy implementations are resolved from the upgrade registry or read from
+//! the chain through the http-rpc provider (see [`proxy`]); outputs name the
+//! implementation next to the called proxy address. Calls to EIP-2535 diamonds
+//! are decoded with the ABI of the facet serving their selector (see
+//! [`diamond`]).
+//!
+//! Multicall3 and Uniswap `multicall` batches and Safe `execTransaction`s are
+//! expanded into `sub_calls`, each inner call decoded against its own target
+//! (see [`multicall`]). EIP-2612, DAI and Permit2 `permit` calls carry the
+//! approval they grant and a shape check of its signature in `permit` (see
+//! [`permit`]).
+//!
+//! Decodes are cached for ten minutes per contract and calldata hash, so bursts
+//! of identical calls (airdrop claims, mints) are decoded once (see
+//! [`decode_cache`]). ABI uploads and deletions invalidate the cache.
 
 // mod abi_fetcher; // Disabled - HTTP capability causes WASI 0.2.3 dependency
+mod abi_type;
+mod batch;
+mod decode_cache;
+mod diamond;
+mod escalation;
+mod events;
+mod multicall;
+mod outputs;
+mod permit;
+mod proxy;
+mod registry;
+mod user_ops;
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // Generate WIT bindings for the abi-decoder world
 wit_bindgen::generate!({ generate_all });
@@ -41,6 +92,9 @@ pub struct AbiEntry {
     /// State mutability
     #[serde(rename = "stateMutability", default)]
     pub state_mutability: Option<String>,
+    /// Anonymous events have no topic0
+    #[serde(default)]
+    pub anonymous: bool,
 }
 
 /// ABI parameter for parsing JSON ABI
@@ -56,7 +110,7 @@ pub struct AbiParam {
     #[serde(default)]
     pub indexed: bool,
     /// Components for tuple types
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub components: Option<Vec<AbiParam>>,
 }
 
@@ -74,7 +128,11 @@ pub enum AbiValue {
     Tuple(Vec<AbiValue>),
 }
 
+use abi_type::AbiType;
+use escalation::{AbiLookup, DecodeAttempt, DecodeLevel};
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+use multicall::SubCall;
+use proxy::{ChainReader, ProxyImplementation, ProxyKind};
 use subject_registry::blockchain;
 use wasmcloud::messaging::{consumer, types};
 
@@ -192,6 +250,21 @@ pub struct DecodedTransaction {
     pub input_data: String,
     /// ABI source
     pub abi_source: Option<String>,
+    /// Implementation behind `to_address` when it is a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<ProxyImplementation>,
+    /// Inner calls when the call is a multicall batch or Safe transaction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_calls: Vec<SubCall>,
+    /// Off-chain signed approval granted by the call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permit: Option<permit::Permit>,
+    /// Best decode level reached
+    #[serde(default)]
+    pub decode_level: DecodeLevel,
+    /// Every escalation attempt, in order
+    #[serde(default)]
+    pub decode_attempts: Vec<DecodeAttempt>,
     /// Processing timestamp
     pub processed_at: String,
     /// Processor ID
@@ -207,6 +280,18 @@ pub struct DecodeResult {
     pub status: DecodeStatus,
     /// Decoded function (if successful)
     pub decoded_function: Option<DecodedFunction>,
+    /// Implementation behind `to_address` when it is a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<ProxyImplementation>,
+    /// Inner calls when the call is a multicall batch or Safe transaction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_calls: Vec<SubCall>,
+    /// Off-chain signed approval granted by the call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permit: Option<permit::Permit>,
+    /// Every escalation attempt, in order
+    #[serde(default)]
+    pub attempts: Vec<DecodeAttempt>,
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
     /// Processed timestamp
@@ -215,18 +300,99 @@ pub struct DecodeResult {
     pub processor_id: String,
 }
 
+impl DecodeResult {
+    /// Result of a request that was not attempted
+    fn skipped(request: DecodeRequest, reason: String, processed_at: String) -> Self {
+        Self::without_decode(request, DecodeStatus::Skipped { reason }, 0, processed_at)
+    }
+
+    /// Result of a request whose decode returned an error
+    fn failed(
+        request: DecodeRequest,
+        error: String,
+        processing_time_ms: u64,
+        processed_at: String,
+    ) -> Self {
+        Self::without_decode(
+            request,
+            DecodeStatus::DecodingFailed { error },
+            processing_time_ms,
+            processed_at,
+        )
+    }
+
+    fn without_decode(
+        request: DecodeRequest,
+        status: DecodeStatus,
+        processing_time_ms: u64,
+        processed_at: String,
+    ) -> Self {
+        Self {
+            request,
+            status,
+            decoded_function: None,
+            implementation: None,
+            sub_calls: Vec::new(),
+            permit: None,
+            attempts: Vec::new(),
+            processing_time_ms,
+            processed_at,
+            processor_id: "abi-decoder-actor".to_string(),
+        }
+    }
+}
+
 /// Decode status
+///
+/// `PartiallyDecoded` comes from a 4byte signature (types only, no parameter
+/// names); `HeuristicDecoded` classifies argument words by shape only.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "details")]
 pub enum DecodeStatus {
     Success,
     NativeTransfer,
     ContractCreation,
-    AbiNotFound { message: String },
-    AbiAutoFetched { source: String },
-    DecodingFailed { error: String },
-    InvalidInput { error: String },
-    RateLimited { message: String },
+    AbiNotFound {
+        message: String,
+    },
+    AbiAutoFetched {
+        source: String,
+    },
+    PartiallyDecoded {
+        signature: String,
+    },
+    HeuristicDecoded,
+    DecodingFailed {
+        error: String,
+    },
+    InvalidInput {
+        error: String,
+    },
+    RateLimited {
+        message: String,
+    },
+    /// Not attempted, e.g. past a batch chunk's deadline
+    Skipped {
+        reason: String,
+    },
+}
+
+impl DecodeStatus {
+    /// Lake `decoding_status` for a final outcome; `None` when the request
+    /// should be retried
+    pub fn pipeline_status(&self) -> Option<&'static str> {
+        match self {
+            DecodeStatus::Success | DecodeStatus::AbiAutoFetched { .. } => Some("Success"),
+            DecodeStatus::NativeTransfer => Some("NativeTransfer"),
+            DecodeStatus::ContractCreation => Some("ContractCreation"),
+            DecodeStatus::AbiNotFound { .. } => Some("AbiNotFound"),
+            DecodeStatus::PartiallyDecoded { .. } => Some("PartialDecoded"),
+            DecodeStatus::HeuristicDecoded => Some("HeuristicDecoded"),
+            DecodeStatus::DecodingFailed { .. } => Some("DecodingError"),
+            DecodeStatus::InvalidInput { .. } => Some("InvalidInput"),
+            DecodeStatus::RateLimited { .. } | DecodeStatus::Skipped { .. } => None,
+        }
+    }
 }
 
 /// Decoded function information
@@ -268,6 +434,9 @@ pub struct BatchDecodeRequest {
     pub priority: String,
     /// Submitted timestamp
     pub submitted_at: String,
+    /// Set on the chunks an oversized batch is split into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<batch::BatchChunk>,
 }
 
 /// Batch decode result
@@ -279,12 +448,36 @@ pub struct BatchDecodeResult {
     pub results: Vec<DecodeResult>,
     /// Processing summary
     pub summary: BatchSummary,
+    /// Chunk of the batch these results cover; one result per chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<batch::BatchChunk>,
+    /// Why the batch was refused without decoding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
     /// Processed timestamp
     pub processed_at: String,
 }
 
+impl BatchDecodeResult {
+    /// Answer to a batch refused without decoding
+    fn rejected(batch_request: BatchDecodeRequest, error: String, processed_at: String) -> Self {
+        Self {
+            batch_id: batch_request.batch_id,
+            results: Vec::new(),
+            summary: BatchSummary {
+                total_requests: batch_request.requests.len() as u32,
+                skipped_requests: batch_request.requests.len() as u32,
+                ..Default::default()
+            },
+            chunk: batch_request.chunk,
+            error: Some(error),
+            processed_at,
+        }
+    }
+}
+
 /// Batch processing summary
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BatchSummary {
     /// Total requests
     pub total_requests: u32,
@@ -294,6 +487,12 @@ pub struct BatchSummary {
     pub failed_decodes: u32,
     /// Native transfers
     pub native_transfers: u32,
+    /// Requests left undecoded past the chunk deadline
+    #[serde(default)]
+    pub skipped_requests: u32,
+    /// Requests that took longer than the per-request timeout
+    #[serde(default)]
+    pub slow_requests: u32,
     /// Total processing time in milliseconds
     pub total_processing_time_ms: u64,
 }
@@ -313,6 +512,19 @@ pub struct AbiInfo {
     pub verified: bool,
     /// Cached timestamp
     pub cached_at: String,
+    /// Implementation contract when this address is a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation_address: Option<String>,
+    /// Operator or dashboard user who uploaded the ABI through `abi.registry.put`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploaded_by: Option<String>,
+    /// End of an uploaded ABI's TTL; the entry is treated as missing afterwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// Function selector → position of the function in `abi_json`, so decodes
+    /// skip hashing every signature. Built on first read when a writer left it out.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub selector_index: BTreeMap<String, usize>,
 }
 
 /// Main ABI Decoder Actor Component
@@ -324,29 +536,101 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
-        let subject = &msg.subject;
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+        let subject = subject_registry::unprefixed(&msg.subject).ok_or_else(|| {
+            format!(
+                "Subject {} is outside environment prefix {}",
+                msg.subject,
+                subject_registry::active_prefix()
+            )
+        })?;
         eprintln!("[ABI-DECODER] Received message on subject: {}", subject);
 
         // Handle pipeline contract transactions
         if subject.starts_with("contract-transactions.") {
-            return Self::handle_contract_transaction(&msg);
+            return Self::handle_contract_transaction(subject, &msg);
         }
 
-        // Handle direct decode requests
-        match subject.as_str() {
-            "abi.decode.request" => {
+        // Handle decode requests
+        match subject {
+            "abi.decode.dispatch" => {
                 let request: DecodeRequest = serde_json::from_slice(&msg.body)
                     .map_err(|e| format!("Failed to parse decode request: {}", e))?;
 
                 let result = Self::decode_transaction(request)?;
+                Self::publish_lake_update(&result)?;
+                if let Some(reply_to) = &msg.reply_to {
+                    Self::reply_result(reply_to, &result)?;
+                }
                 Self::publish_result(result)?;
             }
             "abi.decode.batch" => {
                 let batch_request: BatchDecodeRequest = serde_json::from_slice(&msg.body)
                     .map_err(|e| format!("Failed to parse batch request: {}", e))?;
 
-                let result = Self::decode_batch(batch_request)?;
-                Self::publish_batch_result(result)?;
+                let config = Self::batch_config();
+                let size = batch_request.requests.len();
+                if let Some(error) = config.oversize_error(size) {
+                    eprintln!(
+                        "[ABI-DECODER] Rejected batch {}: {}",
+                        batch_request.batch_id, error
+                    );
+                    Self::publish_batch_result(BatchDecodeResult::rejected(
+                        batch_request,
+                        error,
+                        Self::get_timestamp(),
+                    ))?;
+                } else if batch_request.chunk.is_none() && config.needs_chunking(size) {
+                    Self::dispatch_batch_chunks(batch_request, &config)?;
+                } else {
+                    let result = Self::decode_batch(batch_request, &config);
+                    Self::publish_batch_result(result)?;
+                }
+            }
+            "abi.decode.logs" => {
+                let request: events::LogDecodeRequest = serde_json::from_slice(&msg.body)
+                    .map_err(|e| format!("Failed to parse log decode request: {}", e))?;
+
+                let decoded_logs =
+                    events::decode_logs(&Component, &request, &Self::get_timestamp());
+                eprintln!(
+                    "[ABI-DECODER] Decoded {} of {} log(s)",
+                    decoded_logs
+                        .iter()
+                        .filter(|log| log.decoded_event.is_some())
+                        .count(),
+                    decoded_logs.len()
+                );
+                for decoded_log in &decoded_logs {
+                    Self::publish_decoded_log(decoded_log)?;
+                }
+            }
+            "abi.decode.output" => {
+                let request: outputs::OutputDecodeRequest = serde_json::from_slice(&msg.body)
+                    .map_err(|e| format!("Failed to parse output decode request: {}", e))?;
+
+                let decoded = outputs::decode_output(&Component, &request, &Self::get_timestamp());
+                eprintln!(
+                    "[ABI-DECODER] Output of {:?} on {}: {}",
+                    decoded.selector, decoded.address, decoded.decoding_status
+                );
+                Self::publish_decoded_output(msg.reply_to.as_deref(), &decoded)?;
+            }
+            "abi.registry.put" | "abi.registry.get" | "abi.registry.delete" => {
+                let response = Self::handle_registry(subject, &msg.body);
+                if let Some(error) = &response.error {
+                    eprintln!("[ABI-DECODER] {} rejected: {}", subject, error);
+                }
+                match &msg.reply_to {
+                    Some(reply_to) => Self::reply_registry(reply_to, &response)?,
+                    None => eprintln!("[ABI-DECODER] {} without reply subject", subject),
+                }
             }
             _ => {
                 // Unknown subject, ignore
@@ -360,7 +644,10 @@ impl MessageHandler for Component {
 
 impl Component {
     /// Handle a contract transaction from the pipeline
-    fn handle_contract_transaction(msg: &types::BrokerMessage) -> Result<(), String> {
+    fn handle_contract_transaction(
+        subject: &str,
+        msg: &types::BrokerMessage,
+    ) -> Result<(), String> {
         eprintln!("[ABI-DECODER] Processing contract transaction");
 
         let tx = match serde_json::from_slice::<ContractTransaction>(&msg.body) {
@@ -368,7 +655,7 @@ impl Component {
             Err(_) => {
                 let raw_tx: RawContractTransaction = serde_json::from_slice(&msg.body)
                     .map_err(|e| format!("Failed to parse raw contract transaction: {}", e))?;
-                let (network, subnet, vm_type) = Self::parse_contract_subject(&msg.subject)?;
+                let (network, subnet, vm_type) = Self::parse_contract_subject(subject)?;
                 Self::contract_tx_from_raw(raw_tx, network, subnet, vm_type)
             }
         };
@@ -407,6 +694,11 @@ impl Component {
                     decoded_function: None,
                     input_data: tx.input_data,
                     abi_source: None,
+                    implementation: None,
+                    sub_calls: Vec::new(),
+                    permit: None,
+                    decode_level: DecodeLevel::Failed,
+                    decode_attempts: Vec::new(),
                     processed_at: processed_at.clone(),
                     processor_id: "abi-decoder-actor".to_string(),
                 };
@@ -415,94 +707,63 @@ impl Component {
             }
         };
 
-        // Try to get ABI from cache
-        let abi_info = match Self::get_abi_from_cache(&tx.to_address, &tx.network) {
-            Some(abi) => {
-                eprintln!("[ABI-DECODER] Found ABI in cache from {}", abi.source);
-                abi
-            }
-            None => {
-                // Try to auto-fetch ABI
-                eprintln!("[ABI-DECODER] ABI not in cache, attempting to fetch...");
-                match Self::fetch_and_cache_abi(&tx.to_address, &tx.network, &tx.subnet) {
-                    Ok(abi) => {
-                        eprintln!(
-                            "[ABI-DECODER] Successfully fetched and cached ABI from {}",
-                            abi.source
-                        );
-                        abi
-                    }
-                    Err(e) => {
-                        eprintln!("[ABI-DECODER] Failed to fetch ABI: {}", e);
-                        // Publish transaction without decoding
-                        let decoded_tx = DecodedTransaction {
-                            transaction_hash: tx.transaction_hash,
-                            block_number: tx.block_number,
-                            from_address: tx.from_address,
-                            to_address: tx.to_address,
-                            network: tx.network.clone(),
-                            subnet: tx.subnet.clone(),
-                            value: tx.value,
-                            decoding_status: "AbiNotFound".to_string(),
-                            decoded_function: None,
-                            input_data: tx.input_data,
-                            abi_source: None,
-                            processed_at: processed_at.clone(),
-                            processor_id: "abi-decoder-actor".to_string(),
-                        };
-                        Self::publish_decoded_transaction(&decoded_tx)?;
-                        return Ok(());
-                    }
-                }
-            }
-        };
-
-        // Decode the transaction
-        let decoded_tx = match Self::decode_with_alloy(&abi_info, &selector, &tx.input_data) {
-            Ok(decoded_function) => {
-                eprintln!(
-                    "[ABI-DECODER] Successfully decoded function: {}",
-                    decoded_function.name
-                );
-                DecodedTransaction {
-                    transaction_hash: tx.transaction_hash,
-                    block_number: tx.block_number,
-                    from_address: tx.from_address,
-                    to_address: tx.to_address,
-                    network: tx.network.clone(),
-                    subnet: tx.subnet.clone(),
-                    value: tx.value,
-                    decoding_status: "Success".to_string(),
-                    decoded_function: Some(decoded_function),
-                    input_data: tx.input_data,
-                    abi_source: Some(abi_info.source),
-                    processed_at: processed_at.clone(),
-                    processor_id: "abi-decoder-actor".to_string(),
-                }
-            }
-            Err(e) => {
-                eprintln!("[ABI-DECODER] Decoding failed: {}", e);
-                DecodedTransaction {
-                    transaction_hash: tx.transaction_hash,
-                    block_number: tx.block_number,
-                    from_address: tx.from_address,
-                    to_address: tx.to_address,
-                    network: tx.network.clone(),
-                    subnet: tx.subnet.clone(),
-                    value: tx.value,
-                    decoding_status: format!("DecodingFailed: {}", e),
-                    decoded_function: None,
-                    input_data: tx.input_data,
-                    abi_source: Some(abi_info.source),
-                    processed_at: processed_at.clone(),
-                    processor_id: "abi-decoder-actor".to_string(),
-                }
-            }
+        // Decode the transaction, escalating through weaker ABI sources on failure
+        let decode_cache::CallDecode {
+            outcome,
+            sub_calls,
+            permit,
+        } = Self::decode_call(
+            &tx.to_address,
+            &tx.network,
+            &tx.subnet,
+            &selector,
+            &tx.input_data,
+        );
+        match &outcome.decoded_function {
+            Some(decoded_function) => eprintln!(
+                "[ABI-DECODER] Decoded function {} at level {:?} after {} attempt(s)",
+                decoded_function.name,
+                outcome.level,
+                outcome.attempts.len()
+            ),
+            None => eprintln!("[ABI-DECODER] Decoding failed: {}", outcome.status()),
+        }
+        let user_operations = user_ops::decode_bundle(&Component, &tx, &processed_at);
+        let decoded_tx = DecodedTransaction {
+            transaction_hash: tx.transaction_hash,
+            block_number: tx.block_number,
+            from_address: tx.from_address,
+            to_address: tx.to_address,
+            network: tx.network.clone(),
+            subnet: tx.subnet.clone(),
+            value: tx.value,
+            decoding_status: outcome.status(),
+            decoded_function: outcome.decoded_function,
+            input_data: tx.input_data,
+            abi_source: outcome.abi_source,
+            implementation: outcome.implementation,
+            sub_calls,
+            permit,
+            decode_level: outcome.level,
+            decode_attempts: outcome.attempts,
+            processed_at: processed_at.clone(),
+            processor_id: "abi-decoder-actor".to_string(),
         };
 
         // Publish to blockchain.{network}.{subnet}.contracts.decoded subject
         Self::publish_decoded_transaction(&decoded_tx)?;
 
+        if !user_operations.is_empty() {
+            eprintln!(
+                "[ABI-DECODER] Decoded {} UserOperation(s) in bundle {}",
+                user_operations.len(),
+                decoded_tx.transaction_hash
+            );
+        }
+        for user_operation in &user_operations {
+            Self::publish_user_operation(user_operation)?;
+        }
+
         Ok(())
     }
 
@@ -556,11 +817,8 @@ impl Component {
 
     /// Fetch ABI from external sources and cache it
     /// NOTE: HTTP capability disabled - ABIs must be pre-populated in Redis
-    fn fetch_and_cache_abi(
-        contract_address: &str,
-        network: &str,
-        _subnet: &str,
-    ) -> Result<AbiInfo, String> {
+    /// (new deployments are seeded by eth_contract_creation_processor)
+    fn fetch_and_cache_abi(contract_address: &str, network: &str) -> Result<AbiInfo, String> {
         // HTTP capability is disabled due to WASI 0.2.3 incompatibility
         // ABIs must be pre-populated in Redis using key format: abi:{network}:{contract_address}
         eprintln!(
@@ -586,6 +844,10 @@ impl Component {
                 request,
                 status: DecodeStatus::NativeTransfer,
                 decoded_function: None,
+                implementation: None,
+                sub_calls: Vec::new(),
+                permit: None,
+                attempts: Vec::new(),
                 processing_time_ms: processing_time,
                 processed_at: processed_at.clone(),
                 processor_id: "abi-decoder-actor".to_string(),
@@ -598,6 +860,10 @@ impl Component {
                 request,
                 status: DecodeStatus::ContractCreation,
                 decoded_function: None,
+                implementation: None,
+                sub_calls: Vec::new(),
+                permit: None,
+                attempts: Vec::new(),
                 processing_time_ms: processing_time,
                 processed_at: processed_at.clone(),
                 processor_id: "abi-decoder-actor".to_string(),
@@ -615,6 +881,10 @@ impl Component {
                         error: "Invalid input data format".to_string(),
                     },
                     decoded_function: None,
+                    implementation: None,
+                    sub_calls: Vec::new(),
+                    permit: None,
+                    attempts: Vec::new(),
                     processing_time_ms: processing_time,
                     processed_at: processed_at.clone(),
                     processor_id: "abi-decoder-actor".to_string(),
@@ -622,121 +892,342 @@ impl Component {
             }
         };
 
-        // Try to get ABI from cache, then fetch if not found
-        let (abi_info, auto_fetched) =
-            match Self::get_abi_from_cache(&request.to_address, &request.network) {
-                Some(abi) => (abi, false),
-                None => {
-                    // Try to auto-fetch
-                    match Self::fetch_and_cache_abi(
-                        &request.to_address,
-                        &request.network,
-                        &request.subnet,
-                    ) {
-                        Ok(abi) => (abi, true),
-                        Err(e) => {
-                            let processing_time = start_time.elapsed().as_millis() as u64;
-                            return Ok(DecodeResult {
-                                request,
-                                status: DecodeStatus::AbiNotFound { message: e },
-                                decoded_function: None,
-                                processing_time_ms: processing_time,
-                                processed_at: processed_at.clone(),
-                                processor_id: "abi-decoder-actor".to_string(),
-                            });
-                        }
-                    }
-                }
-            };
+        let decode_cache::CallDecode {
+            outcome,
+            sub_calls,
+            permit,
+        } = Self::decode_call(
+            &request.to_address,
+            &request.network,
+            &request.subnet,
+            &selector,
+            &request.input_data,
+        );
+        let status = match (outcome.level, &outcome.decoded_function) {
+            (DecodeLevel::Full, _) => DecodeStatus::Success,
+            (DecodeLevel::Partial, Some(decoded_function)) => DecodeStatus::PartiallyDecoded {
+                signature: decoded_function.signature.clone(),
+            },
+            (DecodeLevel::Heuristic, _) => DecodeStatus::HeuristicDecoded,
+            _ if outcome.abi_missing => DecodeStatus::AbiNotFound {
+                message: format!(
+                    "No ABI cached. Pre-populate ABI in Redis with key: abi:{}:{}",
+                    request.network,
+                    request.to_address.to_lowercase()
+                ),
+            },
+            _ => DecodeStatus::DecodingFailed {
+                error: outcome.last_abi_error().to_string(),
+            },
+        };
 
-        // Decode the transaction using Alloy
-        match Self::decode_with_alloy(&abi_info, &selector, &request.input_data) {
-            Ok(decoded_function) => {
-                let processing_time = start_time.elapsed().as_millis() as u64;
-                let status = if auto_fetched {
-                    DecodeStatus::AbiAutoFetched {
-                        source: abi_info.source.clone(),
+        let processing_time = start_time.elapsed().as_millis() as u64;
+        Ok(DecodeResult {
+            request,
+            status,
+            decoded_function: outcome.decoded_function,
+            implementation: outcome.implementation,
+            sub_calls,
+            permit,
+            attempts: outcome.attempts,
+            processing_time_ms: processing_time,
+            processed_at: processed_at.clone(),
+            processor_id: "abi-decoder-actor".to_string(),
+        })
+    }
+
+    /// Decode one call with its sub-calls and permit, reusing a recent decode
+    /// of the same calldata to the same contract (see [`decode_cache`])
+    fn decode_call(
+        address: &str,
+        network: &str,
+        subnet: &str,
+        selector: &str,
+        input_data: &str,
+    ) -> decode_cache::CallDecode {
+        let cache_key = decode_cache::cache_key(network, address, selector, input_data);
+        let abi_version = Self::abi_registry_version();
+        let now = Self::now_secs();
+        if let Some(cached) = cache_key
+            .as_deref()
+            .and_then(Self::get_from_redis)
+            .and_then(|cached| serde_json::from_str::<decode_cache::CachedDecode>(&cached).ok())
+            .filter(|cached| cached.is_fresh(now, abi_version))
+        {
+            eprintln!(
+                "[ABI-DECODER] Reusing cached decode of {} on {}",
+                selector, address
+            );
+            return cached.decode;
+        }
+
+        let decode = decode_cache::CallDecode {
+            outcome: escalation::decode_with_escalation(
+                &Component, address, network, subnet, selector, input_data,
+            ),
+            sub_calls: multicall::expand(
+                &Component, address, network, subnet, selector, input_data,
+            ),
+            permit: permit::decode_permit(address, input_data),
+        };
+        if let Some(cache_key) = cache_key {
+            let entry = decode_cache::CachedDecode {
+                decode,
+                cached_at: now,
+                abi_version,
+            };
+            match serde_json::to_string(&entry) {
+                Ok(json) => {
+                    if let Err(e) = Self::set_in_redis(&cache_key, &json) {
+                        eprintln!("[ABI-DECODER] Failed to cache decode: {}", e);
                     }
-                } else {
-                    DecodeStatus::Success
-                };
-                Ok(DecodeResult {
-                    request,
-                    status,
-                    decoded_function: Some(decoded_function),
-                    processing_time_ms: processing_time,
-                    processed_at: processed_at.clone(),
-                    processor_id: "abi-decoder-actor".to_string(),
-                })
-            }
-            Err(e) => {
-                let processing_time = start_time.elapsed().as_millis() as u64;
-                Ok(DecodeResult {
-                    request,
-                    status: DecodeStatus::DecodingFailed {
-                        error: e.to_string(),
-                    },
-                    decoded_function: None,
-                    processing_time_ms: processing_time,
-                    processed_at: processed_at.clone(),
-                    processor_id: "abi-decoder-actor".to_string(),
-                })
+                }
+                Err(e) => eprintln!("[ABI-DECODER] Failed to serialize decode: {}", e),
             }
+            return entry.decode;
         }
+        decode
     }
 
-    /// Decode multiple transactions in batch
-    fn decode_batch(batch_request: BatchDecodeRequest) -> Result<BatchDecodeResult, String> {
+    /// Current ABI registry version, bumped on every upload and deletion
+    fn abi_registry_version() -> u64 {
+        let kind = cache_invalidation::InvalidationKind::Abi;
+        wasi::keyvalue::store::open("default")
+            .ok()
+            .and_then(|bucket| {
+                wasi::keyvalue::atomics::increment(&bucket, &kind.version_key(), 0).ok()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Decode a batch or one chunk of it
+    ///
+    /// A failing request is answered with its error rather than failing the
+    /// chunk, and requests still waiting at the chunk deadline are skipped.
+    fn decode_batch(
+        batch_request: BatchDecodeRequest,
+        config: &batch::BatchConfig,
+    ) -> BatchDecodeResult {
         let start_time = std::time::Instant::now();
         let mut results = Vec::new();
-        let mut successful = 0;
-        let mut failed = 0;
-        let mut native_transfers = 0;
+        let mut summary = BatchSummary::default();
 
         for request in batch_request.requests {
-            let result = Self::decode_transaction(request)?;
+            if start_time.elapsed().as_millis() as u64 >= config.chunk_timeout_ms {
+                results.push(DecodeResult::skipped(
+                    request,
+                    format!("Chunk deadline of {}ms exceeded", config.chunk_timeout_ms),
+                    Self::get_timestamp(),
+                ));
+                summary.skipped_requests += 1;
+                continue;
+            }
+
+            let request_id = request.request_id.clone();
+            let started = std::time::Instant::now();
+            let result = Self::decode_transaction(request.clone()).unwrap_or_else(|e| {
+                eprintln!("[ABI-DECODER] Request {} failed: {}", request_id, e);
+                DecodeResult::failed(
+                    request,
+                    e,
+                    started.elapsed().as_millis() as u64,
+                    Self::get_timestamp(),
+                )
+            });
+            if result.processing_time_ms > config.request_timeout_ms {
+                eprintln!(
+                    "[ABI-DECODER] Request {} took {}ms (timeout {}ms)",
+                    request_id, result.processing_time_ms, config.request_timeout_ms
+                );
+                summary.slow_requests += 1;
+            }
 
             match &result.status {
-                DecodeStatus::Success | DecodeStatus::AbiAutoFetched { .. } => successful += 1,
-                DecodeStatus::NativeTransfer => native_transfers += 1,
-                DecodeStatus::ContractCreation => native_transfers += 1,
-                _ => failed += 1,
+                DecodeStatus::Success
+                | DecodeStatus::AbiAutoFetched { .. }
+                | DecodeStatus::PartiallyDecoded { .. } => summary.successful_decodes += 1,
+                DecodeStatus::NativeTransfer => summary.native_transfers += 1,
+                DecodeStatus::ContractCreation => summary.native_transfers += 1,
+                _ => summary.failed_decodes += 1,
             }
 
             results.push(result);
         }
 
-        let total_processing_time = start_time.elapsed().as_millis() as u64;
-        let processed_at = Self::get_timestamp();
-
-        let total_requests = results.len() as u32;
-        Ok(BatchDecodeResult {
+        summary.total_requests = results.len() as u32;
+        summary.total_processing_time_ms = start_time.elapsed().as_millis() as u64;
+        BatchDecodeResult {
             batch_id: batch_request.batch_id,
             results,
-            summary: BatchSummary {
-                total_requests,
-                successful_decodes: successful,
-                failed_decodes: failed,
-                native_transfers,
-                total_processing_time_ms: total_processing_time,
-            },
-            processed_at,
-        })
+            summary,
+            chunk: batch_request.chunk,
+            error: None,
+            processed_at: Self::get_timestamp(),
+        }
+    }
+
+    /// Republish an oversized batch as one `abi.decode.batch` request per
+    /// chunk, so instances decode the chunks side by side
+    fn dispatch_batch_chunks(
+        batch_request: BatchDecodeRequest,
+        config: &batch::BatchConfig,
+    ) -> Result<(), String> {
+        let chunks = batch::split(batch_request.requests, config.chunk_size);
+        eprintln!(
+            "[ABI-DECODER] Splitting batch {} into {} chunk(s)",
+            batch_request.batch_id,
+            chunks.len()
+        );
+        for (chunk, requests) in chunks {
+            let chunk_request = BatchDecodeRequest {
+                requests,
+                batch_id: batch_request.batch_id.clone(),
+                priority: batch_request.priority.clone(),
+                submitted_at: batch_request.submitted_at.clone(),
+                chunk: Some(chunk),
+            };
+            let payload = serde_json::to_vec(&chunk_request)
+                .map_err(|e| format!("Failed to serialize batch chunk: {}", e))?;
+            consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed("abi.decode.batch"),
+                body: payload,
+                reply_to: None,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Batch limits, defaults for anything not configured
+    fn batch_config() -> batch::BatchConfig {
+        Self::get_from_redis(&retention_policy::ABI_DECODER_BATCH_CONFIG.key(""))
+            .and_then(|config| serde_json::from_str(&config).ok())
+            .unwrap_or_default()
     }
 
     /// Get ABI from cache (Redis)
     fn get_abi_from_cache(contract_address: &str, network: &str) -> Option<AbiInfo> {
-        let cache_key = format!("abi:{}:{}", network, contract_address.to_lowercase());
+        let cache_key = retention_policy::ABI_CACHE.key(&format!(
+            "{}:{}",
+            network,
+            contract_address.to_lowercase()
+        ));
 
         match Self::get_from_redis(&cache_key) {
             Some(abi_json) => match serde_json::from_str::<AbiInfo>(&abi_json) {
-                Ok(abi_info) => Some(abi_info),
-                Err(_) => None,
+                Ok(abi_info) if !registry::is_expired(&abi_info, &Self::get_timestamp()) => {
+                    Some(Self::ensure_selector_index(&cache_key, abi_info))
+                }
+                _ => None,
             },
             None => None,
         }
     }
 
+    /// Index a cached ABI written without a selector index and store it back,
+    /// so only the first decode of a contract pays for hashing its signatures
+    fn ensure_selector_index(cache_key: &str, mut abi_info: AbiInfo) -> AbiInfo {
+        if !abi_info.selector_index.is_empty() {
+            return abi_info;
+        }
+        let Ok(entries) = serde_json::from_str::<Vec<AbiEntry>>(&abi_info.abi_json) else {
+            return abi_info;
+        };
+        abi_info.selector_index = Self::selector_index(&entries);
+        if abi_info.selector_index.is_empty() {
+            return abi_info;
+        }
+        match serde_json::to_string(&abi_info) {
+            Ok(payload) => {
+                if let Err(e) = Self::set_in_redis(cache_key, &payload) {
+                    eprintln!("[ABI-DECODER] Failed to store selector index: {}", e);
+                }
+            }
+            Err(e) => eprintln!("[ABI-DECODER] Failed to serialize selector index: {}", e),
+        }
+        abi_info
+    }
+
+    /// Serve an `abi.registry.*` request
+    fn handle_registry(subject: &str, body: &[u8]) -> registry::AbiRegistryResponse {
+        let handled = match subject {
+            "abi.registry.put" => serde_json::from_slice::<registry::AbiPutRequest>(body)
+                .map_err(|e| format!("Failed to parse ABI upload: {}", e))
+                .and_then(|request| Self::put_registry_abi(&request)),
+            _ => serde_json::from_slice::<registry::AbiKeyRequest>(body)
+                .map_err(|e| format!("Failed to parse ABI registry request: {}", e))
+                .and_then(|request| request.normalize())
+                .and_then(|(network, address)| {
+                    if subject == "abi.registry.get" {
+                        Self::get_abi_from_cache(&address, &network)
+                            .map(registry::AbiRegistryResponse::found)
+                            .ok_or_else(|| format!("No ABI cached for {}:{}", network, address))
+                    } else {
+                        Self::delete_registry_abi(&network, &address)
+                    }
+                }),
+        };
+        handled.unwrap_or_else(registry::AbiRegistryResponse::error)
+    }
+
+    fn put_registry_abi(
+        request: &registry::AbiPutRequest,
+    ) -> Result<registry::AbiRegistryResponse, String> {
+        let abi_info = registry::normalize_put(request, Self::now_secs())?;
+        let cache_key =
+            retention_policy::ABI_CACHE.key(&format!("{}:{}", abi_info.network, abi_info.address));
+        let payload = serde_json::to_string(&abi_info)
+            .map_err(|e| format!("Failed to serialize ABI: {}", e))?;
+        Self::set_in_redis(&cache_key, &payload)?;
+        Self::publish_abi_invalidation(&abi_info.network, &abi_info.address)?;
+
+        eprintln!(
+            "[ABI-DECODER] Stored {} ABI for {}:{}",
+            abi_info.source, abi_info.network, abi_info.address
+        );
+        Ok(registry::AbiRegistryResponse::found(abi_info))
+    }
+
+    fn delete_registry_abi(
+        network: &str,
+        address: &str,
+    ) -> Result<registry::AbiRegistryResponse, String> {
+        let cache_key = retention_policy::ABI_CACHE.key(&format!("{}:{}", network, address));
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        bucket
+            .delete(&cache_key)
+            .map_err(|e| format!("Failed to delete key: {:?}", e))?;
+        Self::publish_abi_invalidation(network, address)?;
+
+        eprintln!("[ABI-DECODER] Deleted ABI for {}:{}", network, address);
+        Ok(registry::AbiRegistryResponse::deleted())
+    }
+
+    /// Tell in-memory ABI caches (abi-decoder provider) to drop the entry
+    fn publish_abi_invalidation(network: &str, address: &str) -> Result<(), String> {
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        let kind = cache_invalidation::InvalidationKind::Abi;
+        let version = wasi::keyvalue::atomics::increment(&bucket, &kind.version_key(), 1)
+            .map_err(|e| format!("Failed to bump ABI registry version: {:?}", e))?;
+
+        let event = cache_invalidation::InvalidationEvent::new(
+            kind,
+            vec![format!("{}:{}", network, address)],
+            version,
+            "abi-decoder-actor",
+            &Self::get_timestamp(),
+        );
+        let payload = serde_json::to_vec(&event)
+            .map_err(|e| format!("Failed to serialize invalidation: {}", e))?;
+        consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(&kind.subject()),
+            body: payload,
+            reply_to: None,
+        })?;
+        Ok(())
+    }
+
     /// Get value from Redis
     fn get_from_redis(key: &str) -> Option<String> {
         match wasi::keyvalue::store::open("default") {
@@ -789,17 +1280,23 @@ impl Component {
             .try_into()
             .map_err(|_| "Invalid selector length")?;
 
-        // Find function by selector
-        let function = abi
-            .iter()
-            .filter(|e| e.entry_type == "function")
-            .find(|f| {
-                // Calculate selector from function signature
-                let sig = Self::build_signature(&f.name, &f.inputs);
-                let hash = Self::keccak256(sig.as_bytes());
-                hash[..4] == selector_array
-            })
-            .ok_or("Function not found in ABI")?;
+        // Find function by selector, through the index when the ABI has one
+        let indexed = abi_info
+            .selector_index
+            .get(&selector.to_lowercase())
+            .and_then(|&position| abi.get(position))
+            .filter(|e| e.entry_type == "function");
+        let function = match indexed {
+            Some(function) => function,
+            None if !abi_info.selector_index.is_empty() => {
+                return Err("Function not found in ABI".into())
+            }
+            None => abi
+                .iter()
+                .filter(|e| e.entry_type == "function")
+                .find(|f| Self::function_selector(f) == selector_array)
+                .ok_or("Function not found in ABI")?,
+        };
 
         // Extract and decode parameters (skip the 4-byte selector)
         let input_bytes = hex::decode(&input_data[10..])?;
@@ -829,9 +1326,36 @@ impl Component {
         })
     }
 
+    /// Selector of a function entry
+    fn function_selector(function: &AbiEntry) -> [u8; 4] {
+        let sig = Self::build_signature(&function.name, &function.inputs);
+        let hash = Self::keccak256(sig.as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// `0x`-prefixed selector → position of every function in `entries`; the
+    /// first of colliding functions wins, as in a linear scan
+    fn selector_index(entries: &[AbiEntry]) -> BTreeMap<String, usize> {
+        let mut index = BTreeMap::new();
+        for (position, entry) in entries.iter().enumerate() {
+            if entry.entry_type == "function" {
+                let selector = format!("0x{}", hex::encode(Self::function_selector(entry)));
+                index.entry(selector).or_insert(position);
+            }
+        }
+        index
+    }
+
     /// Build function signature from name and inputs
     fn build_signature(name: &str, inputs: &[AbiParam]) -> String {
-        let params: Vec<String> = inputs.iter().map(|p| p.param_type.clone()).collect();
+        let params: Vec<String> = inputs
+            .iter()
+            .map(|p| {
+                AbiType::from_param(p)
+                    .map(|t| t.canonical())
+                    .unwrap_or_else(|_| p.param_type.clone())
+            })
+            .collect();
         format!("{}({})", name, params.join(","))
     }
 
@@ -847,112 +1371,119 @@ impl Component {
 
     /// Decode ABI-encoded parameters
     fn decode_abi_params(params: &[AbiParam], data: &[u8]) -> Result<Vec<AbiValue>, String> {
-        let mut results = Vec::new();
-        let mut offset = 0;
-
-        for param in params {
-            let (value, consumed) = Self::decode_single_param(&param.param_type, data, offset)?;
-            results.push(value);
-            offset += consumed;
-        }
-
-        Ok(results)
+        let types = params
+            .iter()
+            .map(AbiType::from_param)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::decode_heads(types.iter(), data)
     }
 
-    /// Decode a single ABI parameter
+    /// Decode a single ABI parameter whose head starts at `offset`
     fn decode_single_param(
         type_str: &str,
         data: &[u8],
         offset: usize,
     ) -> Result<(AbiValue, usize), String> {
-        // Handle basic types (all are 32 bytes padded)
-        match type_str {
-            "address" => {
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for address".to_string());
-                }
+        let ty = AbiType::parse(type_str)?;
+        let value = Self::decode_head(&ty, data, offset)?;
+        Ok((value, ty.head_size()))
+    }
+
+    /// Decode consecutive heads of a tuple (or array body) starting at `data[0]`;
+    /// tail offsets are relative to `data`
+    fn decode_heads<'a>(
+        types: impl Iterator<Item = &'a AbiType>,
+        data: &[u8],
+    ) -> Result<Vec<AbiValue>, String> {
+        let mut values = Vec::new();
+        let mut offset = 0;
+        for ty in types {
+            values.push(Self::decode_head(ty, data, offset)?);
+            offset += ty.head_size();
+        }
+        Ok(values)
+    }
+
+    /// Decode the value whose head is at `data[offset]`, following the tail
+    /// offset of dynamic types
+    fn decode_head(ty: &AbiType, data: &[u8], offset: usize) -> Result<AbiValue, String> {
+        let head = data
+            .get(offset..)
+            .ok_or_else(|| format!("Not enough data for {}", ty.canonical()))?;
+        if !ty.is_dynamic() {
+            return Self::decode_value(ty, head);
+        }
+        let tail_offset = Self::read_u256_as_usize(head)?;
+        let tail = data
+            .get(tail_offset..)
+            .ok_or_else(|| format!("Invalid {} offset", ty.canonical()))?;
+        Self::decode_value(ty, tail)
+    }
+
+    /// Decode the encoding of `ty` starting at `data[0]`
+    fn decode_value(ty: &AbiType, data: &[u8]) -> Result<AbiValue, String> {
+        let word = || {
+            data.get(..32)
+                .ok_or_else(|| format!("Not enough data for {}", ty.canonical()))
+        };
+        match ty {
+            AbiType::Address => {
                 let mut addr = [0u8; 20];
-                addr.copy_from_slice(&data[offset + 12..offset + 32]);
-                Ok((AbiValue::Address(addr), 32))
+                addr.copy_from_slice(&word()?[12..32]);
+                Ok(AbiValue::Address(addr))
             }
-            "bool" => {
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for bool".to_string());
+            AbiType::Bool => Ok(AbiValue::Bool(word()?[31] != 0)),
+            AbiType::Uint(_) => Ok(AbiValue::Uint256(word()?.try_into().unwrap())),
+            AbiType::Int(_) => Ok(AbiValue::Int256(word()?.try_into().unwrap())),
+            AbiType::FixedBytes(size) => Ok(AbiValue::FixedBytes(word()?[..*size].to_vec())),
+            AbiType::Bytes | AbiType::String => {
+                let len = Self::read_u256_as_usize(word()?)?;
+                let bytes = data
+                    .get(32..)
+                    .and_then(|rest| rest.get(..len))
+                    .ok_or_else(|| format!("{} data out of bounds", ty.canonical()))?
+                    .to_vec();
+                if *ty == AbiType::Bytes {
+                    return Ok(AbiValue::Bytes(bytes));
                 }
-                let val = data[offset + 31] != 0;
-                Ok((AbiValue::Bool(val), 32))
+                let s = String::from_utf8(bytes).map_err(|_| "Invalid UTF-8 string")?;
+                Ok(AbiValue::String(s))
             }
-            "string" => {
-                // Dynamic type - first 32 bytes is offset, then length + data
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for string offset".to_string());
-                }
-                let data_offset = Self::read_u256_as_usize(&data[offset..offset + 32])?;
-                if data_offset + 32 > data.len() {
-                    return Err("Invalid string offset".to_string());
-                }
-                let str_len = Self::read_u256_as_usize(&data[data_offset..data_offset + 32])?;
-                if data_offset + 32 + str_len > data.len() {
-                    return Err("String data out of bounds".to_string());
-                }
-                let str_data = &data[data_offset + 32..data_offset + 32 + str_len];
-                let s = String::from_utf8(str_data.to_vec()).map_err(|_| "Invalid UTF-8 string")?;
-                Ok((AbiValue::String(s), 32))
+            AbiType::Array(inner) => {
+                let len = Self::read_u256_as_usize(word()?)?;
+                Self::decode_array(inner, len, &data[32..])
             }
-            "bytes" => {
-                // Dynamic type
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for bytes offset".to_string());
-                }
-                let data_offset = Self::read_u256_as_usize(&data[offset..offset + 32])?;
-                if data_offset + 32 > data.len() {
-                    return Err("Invalid bytes offset".to_string());
-                }
-                let bytes_len = Self::read_u256_as_usize(&data[data_offset..data_offset + 32])?;
-                if data_offset + 32 + bytes_len > data.len() {
-                    return Err("Bytes data out of bounds".to_string());
-                }
-                let bytes_data = data[data_offset + 32..data_offset + 32 + bytes_len].to_vec();
-                Ok((AbiValue::Bytes(bytes_data), 32))
+            AbiType::FixedArray(inner, len) => Self::decode_array(inner, *len, data),
+            AbiType::Tuple(members) => {
+                Ok(AbiValue::Tuple(Self::decode_heads(members.iter(), data)?))
             }
-            t if t.starts_with("uint") => {
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for uint".to_string());
-                }
-                let mut val = [0u8; 32];
-                val.copy_from_slice(&data[offset..offset + 32]);
-                Ok((AbiValue::Uint256(val), 32))
-            }
-            t if t.starts_with("int") => {
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for int".to_string());
-                }
-                let mut val = [0u8; 32];
-                val.copy_from_slice(&data[offset..offset + 32]);
-                Ok((AbiValue::Int256(val), 32))
-            }
-            t if t.starts_with("bytes") && t.len() > 5 => {
-                // Fixed bytes (bytes1 to bytes32)
-                let size: usize = t[5..].parse().map_err(|_| "Invalid bytes size")?;
-                if offset + 32 > data.len() {
-                    return Err("Not enough data for fixed bytes".to_string());
-                }
-                let val = data[offset..offset + size].to_vec();
-                Ok((AbiValue::FixedBytes(val), 32))
-            }
-            _ => Err(format!("Unsupported type: {}", type_str)),
         }
     }
 
+    /// Decode `len` elements of `inner`, laid out as a tuple at `data[0]`
+    fn decode_array(inner: &AbiType, len: usize, data: &[u8]) -> Result<AbiValue, String> {
+        // Every element takes at least one head word, so a length beyond the
+        // remaining data is malformed rather than worth iterating over
+        if len > data.len() / 32 {
+            return Err(format!("Array length {} out of bounds", len));
+        }
+        let values = Self::decode_heads(std::iter::repeat(inner).take(len), data)?;
+        Ok(AbiValue::Array(values))
+    }
+
     /// Read u256 as usize (for offsets and lengths)
     fn read_u256_as_usize(data: &[u8]) -> Result<usize, String> {
         if data.len() < 32 {
             return Err("Not enough data for u256".to_string());
         }
-        // Only read last 8 bytes as usize (enough for any practical offset)
+        // Offsets and lengths beyond 2^64 can only come from malformed data
+        if data[..24].iter().any(|&b| b != 0) {
+            return Err("Offset or length out of range".to_string());
+        }
         let mut bytes = [0u8; 8];
         bytes.copy_from_slice(&data[24..32]);
-        Ok(u64::from_be_bytes(bytes) as usize)
+        usize::try_from(u64::from_be_bytes(bytes))
+            .map_err(|_| "Offset or length out of range".to_string())
     }
 
     /// Format ABI value for display
@@ -1025,19 +1556,12 @@ impl Component {
 
     /// Get current timestamp as ISO 8601 string
     fn get_timestamp() -> String {
-        #[cfg(target_arch = "wasm32")]
-        {
-            let now = wasi::clocks::wall_clock::now();
-            rfc3339_from_unix_secs(now.seconds)
-        }
+        rfc3339_from_unix_secs(Self::now_secs())
+    }
 
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default();
-            rfc3339_from_unix_secs(now.as_secs())
-        }
+    /// Current Unix time in seconds, frozen in deterministic replays
+    fn now_secs() -> u64 {
+        replay_clock::now().timestamp().max(0) as u64
     }
 
     /// Publish decoded transaction to pipeline
@@ -1048,6 +1572,59 @@ impl Component {
 
         eprintln!("[ABI-DECODER] Publishing to {}", subject);
 
+        consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(&subject),
+            body: payload,
+            reply_to: None,
+        })?;
+
+        Ok(())
+    }
+
+    /// Publish a receipt log enriched with its decoded event
+    fn publish_decoded_log(decoded_log: &events::DecodedLog) -> Result<(), String> {
+        let subject = blockchain::logs_decoded(&decoded_log.network, &decoded_log.subnet);
+        let payload = serde_json::to_vec(decoded_log)
+            .map_err(|e| format!("Failed to serialize decoded log: {}", e))?;
+
+        consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(&subject),
+            body: payload,
+            reply_to: None,
+        })?;
+
+        Ok(())
+    }
+
+    /// Publish one UserOperation of a `handleOps` bundle
+    fn publish_user_operation(
+        user_operation: &user_ops::DecodedUserOperation,
+    ) -> Result<(), String> {
+        let subject = blockchain::user_ops_decoded(&user_operation.network, &user_operation.subnet);
+        let payload = serde_json::to_vec(user_operation)
+            .map_err(|e| format!("Failed to serialize UserOperation: {}", e))?;
+
+        consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(&subject),
+            body: payload,
+            reply_to: None,
+        })?;
+
+        Ok(())
+    }
+
+    /// Answer an output decode request on its reply subject, or publish it
+    fn publish_decoded_output(
+        reply_to: Option<&str>,
+        decoded: &outputs::DecodedOutput,
+    ) -> Result<(), String> {
+        let payload = serde_json::to_vec(decoded)
+            .map_err(|e| format!("Failed to serialize decoded output: {}", e))?;
+        let subject = match reply_to {
+            Some(reply_to) => reply_to.to_string(),
+            None => subject_registry::prefixed("abi.decode.output.result"),
+        };
+
         consumer::publish(&types::BrokerMessage {
             subject,
             body: payload,
@@ -1063,7 +1640,57 @@ impl Component {
             .map_err(|e| format!("Failed to serialize result: {}", e))?;
 
         consumer::publish(&types::BrokerMessage {
-            subject: "abi.decode.result".to_string(),
+            subject: subject_registry::prefixed("abi.decode.result"),
+            body: payload,
+            reply_to: None,
+        })?;
+
+        Ok(())
+    }
+
+    fn reply_registry(
+        reply_to: &str,
+        response: &registry::AbiRegistryResponse,
+    ) -> Result<(), String> {
+        let payload = serde_json::to_vec(response)
+            .map_err(|e| format!("Failed to serialize registry response: {}", e))?;
+
+        consumer::publish(&types::BrokerMessage {
+            subject: reply_to.to_string(),
+            body: payload,
+            reply_to: None,
+        })?;
+
+        Ok(())
+    }
+
+    /// Answer a queued request so the decode queue can acknowledge it
+    fn reply_result(reply_to: &str, result: &DecodeResult) -> Result<(), String> {
+        let payload =
+            serde_json::to_vec(result).map_err(|e| format!("Failed to serialize result: {}", e))?;
+
+        consumer::publish(&types::BrokerMessage {
+            subject: reply_to.to_string(),
+            body: payload,
+            reply_to: None,
+        })?;
+
+        Ok(())
+    }
+
+    /// Write a final decode outcome back to the transaction's lake row
+    fn publish_lake_update(result: &DecodeResult) -> Result<(), String> {
+        let Some(record) = Self::lake_update_record(result) else {
+            return Ok(());
+        };
+        let payload = serde_json::to_vec(&record)
+            .map_err(|e| format!("Failed to serialize lake update: {}", e))?;
+
+        consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(&format!(
+                "ducklake.transactions.{}.{}.upsert",
+                result.request.network, result.request.subnet
+            )),
             body: payload,
             reply_to: None,
         })?;
@@ -1071,13 +1698,38 @@ impl Component {
         Ok(())
     }
 
+    /// Partial `transactions` row keyed by (chain_id, transaction_hash)
+    fn lake_update_record(result: &DecodeResult) -> Option<serde_json::Value> {
+        let status = result.status.pipeline_status()?;
+        if result.request.transaction_hash.is_empty() {
+            return None;
+        }
+
+        let mut record = serde_json::json!({
+            "chain_id": format!("{}_{}", result.request.network, result.request.subnet),
+            "transaction_hash": result.request.transaction_hash,
+            "decoding_status": status,
+            "decoding_time_ms": result.processing_time_ms,
+        });
+        if let Some(function) = &result.decoded_function {
+            record["decoded_function_name"] = function.name.clone().into();
+            record["decoded_function_signature"] = function.signature.clone().into();
+            record["decoded_function_selector"] = function.selector.clone().into();
+            record["decoded_parameters"] = serde_json::to_string(&function.parameters)
+                .unwrap_or_default()
+                .into();
+            record["abi_source"] = function.abi_source.clone().into();
+        }
+        Some(record)
+    }
+
     /// Publish batch decode result to NATS
     fn publish_batch_result(result: BatchDecodeResult) -> Result<(), String> {
         let payload = serde_json::to_vec(&result)
             .map_err(|e| format!("Failed to serialize batch result: {}", e))?;
 
         consumer::publish(&types::BrokerMessage {
-            subject: "abi.decode.batch.result".to_string(),
+            subject: subject_registry::prefixed("abi.decode.batch.result"),
             body: payload,
             reply_to: None,
         })?;
@@ -1086,6 +1738,126 @@ impl Component {
     }
 }
 
+impl AbiLookup for Component {
+    fn cached_abi(&self, address: &str, network: &str) -> Option<AbiInfo> {
+        Self::get_abi_from_cache(address, network).or_else(|| {
+            eprintln!(
+                "[ABI-DECODER] ABI for {} not in cache, attempting to fetch...",
+                address
+            );
+            Self::fetch_and_cache_abi(address, network).ok()
+        })
+    }
+
+    /// Prefer the implementation recorded on the proxy's ABI entry, then the
+    /// `proxy:implementation:{network}:{address}` registry, then the chain
+    fn implementation_of(
+        &self,
+        address: &str,
+        network: &str,
+        subnet: &str,
+        cached: Option<&AbiInfo>,
+    ) -> Option<ProxyImplementation> {
+        let contract = format!("{}:{}", network, address.to_lowercase());
+        if let Some(implementation) = cached
+            .and_then(|abi| abi.implementation_address.clone())
+            .or_else(|| {
+                Self::get_from_redis(&retention_policy::PROXY_IMPLEMENTATION.key(&contract))
+            })
+        {
+            return Some(ProxyImplementation::new(
+                &implementation,
+                ProxyKind::Registry,
+            ));
+        }
+
+        // Chain reads are cached, an empty value meaning "not a proxy"
+        let resolved_key = retention_policy::PROXY_RESOLVED.key(&contract);
+        if let Some(resolved) = Self::get_from_redis(&resolved_key) {
+            return serde_json::from_str(&resolved).ok();
+        }
+        let chain = RpcChain { network, subnet };
+        let resolved = proxy::resolve_on_chain(&chain, address);
+        let value = resolved
+            .as_ref()
+            .and_then(|implementation| serde_json::to_string(implementation).ok())
+            .unwrap_or_default();
+        if let Err(e) = Self::set_in_redis(&resolved_key, &value) {
+            eprintln!("[ABI-DECODER] Failed to cache proxy resolution: {}", e);
+        }
+        resolved
+    }
+
+    fn signature(&self, selector: &str) -> Option<String> {
+        Self::get_from_redis(&retention_policy::ABI_SIGNATURE.key(&selector.to_lowercase()))
+    }
+
+    /// Facet maps are read once per diamond and cached, an empty map meaning
+    /// "not a diamond"
+    fn facet_of(
+        &self,
+        address: &str,
+        network: &str,
+        subnet: &str,
+        selector: &str,
+    ) -> Option<ProxyImplementation> {
+        let facets_key = retention_policy::DIAMOND_FACETS.key(&format!(
+            "{}:{}",
+            network,
+            address.to_lowercase()
+        ));
+        let facets = match Self::get_from_redis(&facets_key)
+            .and_then(|facets| serde_json::from_str::<diamond::FacetMap>(&facets).ok())
+        {
+            Some(facets) => facets,
+            None => {
+                let facets = diamond::read_facets(&RpcChain { network, subnet }, address);
+                let value = serde_json::to_string(&facets).unwrap_or_default();
+                if let Err(e) = Self::set_in_redis(&facets_key, &value) {
+                    eprintln!("[ABI-DECODER] Failed to cache diamond facets: {}", e);
+                }
+                facets
+            }
+        };
+        facets.facet_of(selector)
+    }
+}
+
+/// Timeout of one JSON-RPC request to the http-rpc provider
+const RPC_TIMEOUT_MS: u32 = 5_000;
+
+/// One chain, read through the http-rpc provider's `rpc.request.{network}.{subnet}`
+struct RpcChain<'a> {
+    network: &'a str,
+    subnet: &'a str,
+}
+
+impl ChainReader for RpcChain<'_> {
+    fn rpc(&self, method: &str, params: serde_json::Value) -> Option<serde_json::Value> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .ok()?;
+        let subject = format!("rpc.request.{}.{}", self.network, self.subnet);
+        let reply =
+            match consumer::request(&subject_registry::prefixed(&subject), &body, RPC_TIMEOUT_MS) {
+                Ok(reply) => reply,
+                Err(e) => {
+                    eprintln!("[ABI-DECODER] {} on {} failed: {}", method, subject, e);
+                    return None;
+                }
+            };
+        let response: serde_json::Value = serde_json::from_slice(&reply.body).ok()?;
+        response
+            .get("result")
+            .filter(|result| !result.is_null())
+            .cloned()
+    }
+}
+
 fn rfc3339_from_unix_secs(total_seconds: u64) -> String {
     let days_since_epoch = total_seconds / 86400;
     let time_of_day = total_seconds % 86400;
@@ -1215,6 +1987,237 @@ mod tests {
         assert_eq!(resolved, "2026-01-01T00:00:00Z");
     }
 
+    #[test]
+    fn test_resolve_processed_at_uses_replay_clock() {
+        replay_clock::configure(
+            serde_json::from_value(serde_json::json!({
+                "enabled": true,
+                "frozen_at": "2026-03-01T12:30:00Z",
+            }))
+            .unwrap(),
+        );
+        let tx = ContractTransaction {
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            vm_type: "evm".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            block_number: 1,
+            transaction_index: 0,
+            from_address: "0xfrom".to_string(),
+            to_address: "0xto".to_string(),
+            value: "0x0".to_string(),
+            gas_limit: 21_000,
+            gas_price: "0x1".to_string(),
+            input_data: "0x".to_string(),
+            nonce: 0,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            transaction_type: None,
+            processed_at: String::new(),
+            processor_id: "test".to_string(),
+        };
+
+        assert_eq!(Component::resolve_processed_at(&tx), "2026-03-01T12:30:00Z");
+        assert_eq!(Component::get_timestamp(), "2026-03-01T12:30:00Z");
+    }
+
+    #[test]
+    fn test_lake_update_record() {
+        let mut result = DecodeResult {
+            request: DecodeRequest {
+                to_address: "0xto".to_string(),
+                input_data: "0xa9059cbb".to_string(),
+                network: "ethereum".to_string(),
+                subnet: "mainnet".to_string(),
+                transaction_hash: "0xabc".to_string(),
+                request_id: "req-1".to_string(),
+            },
+            status: DecodeStatus::PartiallyDecoded {
+                signature: "transfer(address,uint256)".to_string(),
+            },
+            decoded_function: Some(DecodedFunction {
+                name: "transfer".to_string(),
+                selector: "0xa9059cbb".to_string(),
+                signature: "transfer(address,uint256)".to_string(),
+                parameters: Vec::new(),
+                abi_source: "4byte".to_string(),
+            }),
+            implementation: None,
+            sub_calls: Vec::new(),
+            permit: None,
+            attempts: Vec::new(),
+            processing_time_ms: 3,
+            processed_at: "2026-01-01T00:00:00Z".to_string(),
+            processor_id: "test".to_string(),
+        };
+
+        let record = Component::lake_update_record(&result).unwrap();
+        assert_eq!(record["chain_id"], "ethereum_mainnet");
+        assert_eq!(record["transaction_hash"], "0xabc");
+        assert_eq!(record["decoding_status"], "PartialDecoded");
+        assert_eq!(record["decoded_function_name"], "transfer");
+        assert_eq!(record["decoded_parameters"], "[]");
+
+        // Rate-limited requests stay Pending so the queue retries them
+        result.status = DecodeStatus::RateLimited {
+            message: "slow down".to_string(),
+        };
+        assert!(Component::lake_update_record(&result).is_none());
+    }
+
+    fn words(words: &[&str]) -> Vec<u8> {
+        hex::decode(words.concat()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_uniswap_v3_exact_input() {
+        let abi_json = r#"[{
+            "type": "function",
+            "name": "exactInput",
+            "stateMutability": "payable",
+            "inputs": [{
+                "name": "params",
+                "type": "tuple",
+                "internalType": "struct ISwapRouter.ExactInputParams",
+                "components": [
+                    {"name": "path", "type": "bytes"},
+                    {"name": "recipient", "type": "address"},
+                    {"name": "deadline", "type": "uint256"},
+                    {"name": "amountIn", "type": "uint256"},
+                    {"name": "amountOutMinimum", "type": "uint256"}
+                ]
+            }],
+            "outputs": [{"name": "amountOut", "type": "uint256"}]
+        }]"#;
+        let abi_info = AbiInfo {
+            address: "0xe592427a0aece92de3edee1f18e0157c05861564".to_string(),
+            network: "ethereum".to_string(),
+            abi_json: abi_json.to_string(),
+            source: "etherscan".to_string(),
+            verified: true,
+            cached_at: String::new(),
+            implementation_address: None,
+            uploaded_by: None,
+            expires_at: None,
+            selector_index: BTreeMap::new(),
+        };
+        // USDC -> 0.05% pool -> WETH, 1000 USDC in
+        let input_data = format!(
+            "0xc04b8d59{}",
+            [
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000001f9090aae28b8a3dceadf281b0f12828e676c326",
+                "000000000000000000000000000000000000000000000000000000006553f100",
+                "000000000000000000000000000000000000000000000000000000003b9aca00",
+                "00000000000000000000000000000000000000000000000006f05b59d3b20000",
+                "000000000000000000000000000000000000000000000000000000000000002b",
+                "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb480001f4c02aaa39b223fe8d0a",
+                "0e5c4f27ead9083c756cc2000000000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+
+        let decoded = Component::decode_with_alloy(&abi_info, "0xc04b8d59", &input_data).unwrap();
+        assert_eq!(
+            decoded.signature,
+            "exactInput((bytes,address,uint256,uint256,uint256))"
+        );
+        assert_eq!(decoded.parameters.len(), 1);
+        assert_eq!(decoded.parameters[0].param_type, "tuple");
+        assert_eq!(
+            decoded.parameters[0].value,
+            "(0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb480001f4c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2, \
+             0x1f9090aae28b8a3dceadf281b0f12828e676c326, 1700000000, 1000000000, 500000000000000000)"
+        );
+    }
+
+    #[test]
+    fn test_selector_index_lookup() {
+        let abi_json = r#"[
+            {"type": "event", "name": "Transfer", "inputs": []},
+            {"type": "function", "name": "approve", "inputs": [
+                {"name": "spender", "type": "address"}, {"name": "amount", "type": "uint256"}]},
+            {"type": "function", "name": "transfer", "inputs": [
+                {"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}]}
+        ]"#;
+        let entries: Vec<AbiEntry> = serde_json::from_str(abi_json).unwrap();
+        let index = Component::selector_index(&entries);
+        assert_eq!(
+            index,
+            BTreeMap::from([("0x095ea7b3".to_string(), 1), ("0xa9059cbb".to_string(), 2)])
+        );
+
+        let mut abi_info = AbiInfo {
+            address: "0x00000000000000000000000000000000000000aa".to_string(),
+            network: "ethereum".to_string(),
+            abi_json: abi_json.to_string(),
+            source: "etherscan".to_string(),
+            verified: true,
+            cached_at: String::new(),
+            implementation_address: None,
+            uploaded_by: None,
+            expires_at: None,
+            selector_index: BTreeMap::new(),
+        };
+        let input_data = format!(
+            "0xA9059CBB{}{}",
+            "0000000000000000000000001111111111111111111111111111111111111111",
+            "00000000000000000000000000000000000000000000000000000000000003e8"
+        );
+        let scanned = Component::decode_with_alloy(&abi_info, "0xA9059CBB", &input_data).unwrap();
+
+        abi_info.selector_index = index;
+        let indexed = Component::decode_with_alloy(&abi_info, "0xA9059CBB", &input_data).unwrap();
+        assert_eq!(indexed.signature, "transfer(address,uint256)");
+        assert_eq!(indexed.parameters[1].value, scanned.parameters[1].value);
+
+        // An indexed ABI is trusted: selectors it lacks are not scanned for
+        abi_info.selector_index.remove("0xa9059cbb");
+        assert!(Component::decode_with_alloy(&abi_info, "0xa9059cbb", &input_data).is_err());
+    }
+
+    #[test]
+    fn test_decode_nested_dynamic_arrays() {
+        let transfers = words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "00000000000000000000000000000000000000000000000000000000000000aa",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "00000000000000000000000000000000000000000000000000000000000000bb",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+        ]);
+        let (value, consumed) =
+            Component::decode_single_param("(address,uint256)[]", &transfers, 0).unwrap();
+        assert_eq!(consumed, 32);
+        assert_eq!(
+            Component::format_abi_value(&value),
+            "[(0x00000000000000000000000000000000000000aa, 1), \
+             (0x00000000000000000000000000000000000000bb, 2)]"
+        );
+
+        // [[7], [8, 9]]: inner offsets count from just after the outer length
+        let matrix = words(&[
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000007",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000008",
+            "0000000000000000000000000000000000000000000000000000000000000009",
+        ]);
+        let (value, _) = Component::decode_single_param("uint256[][]", &matrix, 0).unwrap();
+        assert_eq!(Component::format_abi_value(&value), "[[7], [8, 9]]");
+
+        // A length the data cannot hold is rejected instead of iterated
+        let mut truncated = matrix.clone();
+        truncated[63] = 0xff;
+        assert!(Component::decode_single_param("uint256[][]", &truncated, 0).is_err());
+        assert!(Component::decode_single_param("uint256[][]", &matrix[..64], 0).is_err());
+    }
+
     #[test]
     fn test_rfc3339_from_unix_secs_epoch() {
         let ts = rfc3339_from_unix_secs(0);
@@ -0,0 +1,552 @@
+//! Multicall and Safe transaction expansion
+//!
+//! Batched and relayed calls decode to opaque `bytes`, so the calls a user
+//! actually made stay hidden. When the selector is one of the wrapping entry
+//! points below, every inner call is decoded on its own through the escalation
+//! ladder against its target's ABI and returned as a [`SubCall`]:
+//! - Multicall3 `aggregate`, `tryAggregate`, `aggregate3`, `aggregate3Value`:
+//!   each call names its target, plus `allowFailure` / `value` for the
+//!   `aggregate3` variants
+//! - Uniswap `multicall(bytes[])` and its deadline / previous-block-hash
+//!   variants: the router delegatecalls itself, so the target is the called
+//!   address
+//! - Safe `execTransaction`: the one wrapped `to` / `value` / `data`, with the
+//!   Safe's `operation` (a plain call, or a delegatecall such as to MultiSend)
+//! - smart account `execute(address,uint256,bytes)` and
+//!   `executeBatch((address,uint256,bytes)[])`, the usual ERC-4337 account
+//!   entry points (see [`crate::user_ops`])
+//!
+//! Inner calls that wrap calls themselves (a Safe executing a Multicall3
+//! batch) are expanded in turn, down to [`MAX_DEPTH`] levels.
+
+use serde::{Deserialize, Serialize};
+
+use crate::escalation::{
+    argument_bytes, decode_with_escalation, parse_signature, AbiLookup, DecodeLevel,
+};
+use crate::permit::{decode_permit, Permit};
+use crate::proxy::ProxyImplementation;
+use crate::{AbiValue, Component, DecodedFunction};
+
+/// How a wrapping entry point carries its inner calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wrapper {
+    /// Array of calls as the last argument
+    Batch,
+    /// Safe `execTransaction(to, value, data, operation, ...)`
+    SafeTransaction,
+    /// Smart account `execute(to, value, data)`
+    AccountExecute,
+}
+
+/// Wrapping entry points, by selector
+const WRAPPER_SIGNATURES: &[(&str, &str, Wrapper)] = &[
+    ("0x252dba42", "aggregate((address,bytes)[])", Wrapper::Batch),
+    (
+        "0xbce38bd7",
+        "tryAggregate(bool,(address,bytes)[])",
+        Wrapper::Batch,
+    ),
+    (
+        "0x82ad56cb",
+        "aggregate3((address,bool,bytes)[])",
+        Wrapper::Batch,
+    ),
+    (
+        "0x174dea71",
+        "aggregate3Value((address,bool,uint256,bytes)[])",
+        Wrapper::Batch,
+    ),
+    ("0xac9650d8", "multicall(bytes[])", Wrapper::Batch),
+    ("0x5ae401dc", "multicall(uint256,bytes[])", Wrapper::Batch),
+    ("0x1f0464d1", "multicall(bytes32,bytes[])", Wrapper::Batch),
+    (
+        "0x6a761202",
+        "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+        Wrapper::SafeTransaction,
+    ),
+    (
+        "0xb61d27f6",
+        "execute(address,uint256,bytes)",
+        Wrapper::AccountExecute,
+    ),
+    (
+        "0x34fcd5be",
+        "executeBatch((address,uint256,bytes)[])",
+        Wrapper::Batch,
+    ),
+];
+
+/// How a Safe executes its wrapped call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallOperation {
+    Call,
+    /// The target's code runs in the Safe's context
+    DelegateCall,
+}
+
+/// Nesting levels expanded below the transaction's own call
+pub const MAX_DEPTH: usize = 3;
+
+/// One call inside a batch or Safe transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubCall {
+    /// Position in the batch; always 0 for a Safe transaction or `execute`
+    pub index: usize,
+    /// Lowercase address the call is made to
+    pub target: String,
+    pub input_data: String,
+    /// `aggregate3` / `aggregate3Value`: whether the batch tolerates this call reverting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_failure: Option<bool>,
+    /// `aggregate3Value`, Safe transactions and account calls: wei sent with the call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Safe transactions: call or delegatecall
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation: Option<CallOperation>,
+    /// Pipeline `decoding_status` string, as for transactions
+    pub decoding_status: String,
+    #[serde(default)]
+    pub decode_level: DecodeLevel,
+    pub decoded_function: Option<DecodedFunction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<ProxyImplementation>,
+    /// Off-chain signed approval granted by the call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permit: Option<Permit>,
+    /// Calls of a nested batch
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<SubCall>,
+}
+
+/// Call extracted from a wrapper's arguments
+#[derive(Debug, Clone, Default, PartialEq)]
+struct InnerCall {
+    target: String,
+    input_data: String,
+    allow_failure: Option<bool>,
+    value: Option<String>,
+    operation: Option<CallOperation>,
+}
+
+/// Decoded inner calls of `input_data` if `selector` is a wrapping entry point;
+/// empty otherwise
+pub fn expand(
+    lookup: &impl AbiLookup,
+    address: &str,
+    network: &str,
+    subnet: &str,
+    selector: &str,
+    input_data: &str,
+) -> Vec<SubCall> {
+    expand_at(lookup, address, network, subnet, selector, input_data, 0)
+}
+
+fn expand_at(
+    lookup: &impl AbiLookup,
+    address: &str,
+    network: &str,
+    subnet: &str,
+    selector: &str,
+    input_data: &str,
+    depth: usize,
+) -> Vec<SubCall> {
+    if depth >= MAX_DEPTH {
+        return Vec::new();
+    }
+    let Some((signature, wrapper)) = wrapper_of(selector) else {
+        return Vec::new();
+    };
+    let calls = match inner_calls(signature, wrapper, address, input_data) {
+        Ok(calls) => calls,
+        Err(e) => {
+            eprintln!("[ABI-DECODER] Failed to expand {}: {}", signature, e);
+            return Vec::new();
+        }
+    };
+
+    calls
+        .into_iter()
+        .enumerate()
+        .map(|(index, call)| decode_sub_call(lookup, network, subnet, index, call, depth))
+        .collect()
+}
+
+/// Decode one call to `target`, expanding it when it wraps further calls
+pub fn decode_call(
+    lookup: &impl AbiLookup,
+    network: &str,
+    subnet: &str,
+    target: &str,
+    input_data: &str,
+) -> SubCall {
+    let call = InnerCall {
+        target: target.to_lowercase(),
+        input_data: input_data.to_string(),
+        ..Default::default()
+    };
+    decode_sub_call(lookup, network, subnet, 0, call, 0)
+}
+
+fn decode_sub_call(
+    lookup: &impl AbiLookup,
+    network: &str,
+    subnet: &str,
+    index: usize,
+    call: InnerCall,
+    depth: usize,
+) -> SubCall {
+    let mut sub_call = SubCall {
+        index,
+        target: call.target,
+        input_data: call.input_data,
+        allow_failure: call.allow_failure,
+        value: call.value,
+        operation: call.operation,
+        decoding_status: "InvalidInput".to_string(),
+        decode_level: DecodeLevel::Failed,
+        decoded_function: None,
+        implementation: None,
+        permit: None,
+        calls: Vec::new(),
+    };
+    let Some(selector) = sub_call.input_data.get(..10).map(str::to_string) else {
+        return sub_call;
+    };
+
+    let outcome = decode_with_escalation(
+        lookup,
+        &sub_call.target,
+        network,
+        subnet,
+        &selector,
+        &sub_call.input_data,
+    );
+    sub_call.decoding_status = outcome.status();
+    sub_call.decode_level = outcome.level;
+    sub_call.decoded_function = outcome.decoded_function;
+    sub_call.implementation = outcome.implementation;
+    sub_call.permit = decode_permit(&sub_call.target, &sub_call.input_data);
+    sub_call.calls = expand_at(
+        lookup,
+        &sub_call.target,
+        network,
+        subnet,
+        &selector,
+        &sub_call.input_data,
+        depth + 1,
+    );
+    sub_call
+}
+
+/// Text signature of a wrapping entry point
+pub fn multicall_signature(selector: &str) -> Option<&'static str> {
+    wrapper_of(selector).map(|(signature, _)| signature)
+}
+
+fn wrapper_of(selector: &str) -> Option<(&'static str, Wrapper)> {
+    WRAPPER_SIGNATURES
+        .iter()
+        .find(|(known, _, _)| known.eq_ignore_ascii_case(selector))
+        .map(|(_, signature, wrapper)| (*signature, *wrapper))
+}
+
+/// Inner calls of a wrapper's arguments
+fn inner_calls(
+    signature: &str,
+    wrapper: Wrapper,
+    address: &str,
+    input_data: &str,
+) -> Result<Vec<InnerCall>, String> {
+    let (_, inputs) = parse_signature(signature)?;
+    let data = argument_bytes(input_data)?;
+    let values = Component::decode_abi_params(&inputs, &data)?;
+    match wrapper {
+        Wrapper::SafeTransaction => return wrapped_call(&values, true).map(|call| vec![call]),
+        Wrapper::AccountExecute => return wrapped_call(&values, false).map(|call| vec![call]),
+        Wrapper::Batch => {}
+    }
+
+    // Batches always take the calls as their last argument
+    let Some(AbiValue::Array(calls)) = values.last() else {
+        return Err(format!("{} has no call array", signature));
+    };
+
+    calls
+        .iter()
+        .map(|call| match call {
+            // multicall(bytes[]): the router calls itself
+            AbiValue::Bytes(input) => Ok(InnerCall {
+                target: address.to_lowercase(),
+                input_data: format!("0x{}", hex::encode(input)),
+                ..Default::default()
+            }),
+            // Multicall3 (target, [allowFailure], [value], callData)
+            AbiValue::Tuple(members) => match (members.first(), members.last()) {
+                (Some(AbiValue::Address(target)), Some(AbiValue::Bytes(input))) => Ok(InnerCall {
+                    target: format!("0x{}", hex::encode(target)),
+                    input_data: format!("0x{}", hex::encode(input)),
+                    allow_failure: members.iter().find_map(|member| match member {
+                        AbiValue::Bool(allow_failure) => Some(*allow_failure),
+                        _ => None,
+                    }),
+                    value: members.iter().find_map(|member| match member {
+                        AbiValue::Uint256(value) => Some(Component::u256_to_decimal(value)),
+                        _ => None,
+                    }),
+                    operation: None,
+                }),
+                _ => Err(format!("Unexpected call shape in {}", signature)),
+            },
+            _ => Err(format!("Unexpected call shape in {}", signature)),
+        })
+        .collect()
+}
+
+/// Wrapped call of `(to, value, data, [operation], ...)` arguments; the
+/// operation is only read for Safe transactions
+fn wrapped_call(values: &[AbiValue], with_operation: bool) -> Result<InnerCall, String> {
+    let (Some(AbiValue::Address(to)), Some(AbiValue::Uint256(value)), Some(AbiValue::Bytes(data))) =
+        (values.first(), values.get(1), values.get(2))
+    else {
+        return Err("Unexpected wrapped call arguments".to_string());
+    };
+    let operation = match values.get(3) {
+        Some(AbiValue::Uint256(operation)) if with_operation => {
+            match Component::u256_to_decimal(operation).as_str() {
+                "0" => Some(CallOperation::Call),
+                "1" => Some(CallOperation::DelegateCall),
+                other => return Err(format!("Unknown Safe operation {}", other)),
+            }
+        }
+        _ if with_operation => return Err("Missing Safe operation".to_string()),
+        _ => None,
+    };
+    Ok(InnerCall {
+        target: format!("0x{}", hex::encode(to)),
+        input_data: format!("0x{}", hex::encode(data)),
+        allow_failure: None,
+        value: Some(Component::u256_to_decimal(value)),
+        operation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbiInfo;
+    use std::collections::HashMap;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const OTHER: &str = "0x00000000000000000000000000000000000000cc";
+    const ROUTER: &str = "0xe592427a0aece92de3edee1f18e0157c05861564";
+    const TOKEN_ABI: &str = r#"[{"type":"function","name":"transfer","inputs":[
+        {"name":"to","type":"address"},{"name":"amount","type":"uint256"}]}]"#;
+    const ROUTER_ABI: &str = r#"[{"type":"function","name":"refundETH","inputs":[]}]"#;
+
+    #[derive(Default)]
+    struct MockLookup {
+        abis: HashMap<&'static str, &'static str>,
+    }
+
+    impl AbiLookup for MockLookup {
+        fn cached_abi(&self, address: &str, network: &str) -> Option<AbiInfo> {
+            self.abis.get(address).map(|abi_json| AbiInfo {
+                address: address.to_string(),
+                network: network.to_string(),
+                abi_json: abi_json.to_string(),
+                source: "etherscan".to_string(),
+                verified: true,
+                cached_at: String::new(),
+                implementation_address: None,
+                uploaded_by: None,
+                expires_at: None,
+                selector_index: Default::default(),
+            })
+        }
+
+        fn implementation_of(
+            &self,
+            _address: &str,
+            _network: &str,
+            _subnet: &str,
+            _cached: Option<&AbiInfo>,
+        ) -> Option<ProxyImplementation> {
+            None
+        }
+
+        fn signature(&self, _selector: &str) -> Option<String> {
+            None
+        }
+    }
+
+    fn calldata(selector: &str, words: &[&str]) -> String {
+        format!("{}{}", selector, words.concat())
+    }
+
+    #[test]
+    fn test_aggregate3_decodes_each_target() {
+        // aggregate3([(TOKEN, false, transfer(0x1111..., 1000)), (OTHER, true, 0x12345678)])
+        let input_data = calldata(
+            "0x82ad56cb",
+            &[
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000120",
+                "00000000000000000000000000000000000000000000000000000000000000aa",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "0000000000000000000000000000000000000000000000000000000000000044",
+                "a9059cbb00000000000000000000000011111111111111111111111111111111",
+                "1111111100000000000000000000000000000000000000000000000000000000",
+                "000003e800000000000000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000cc",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "1234567800000000000000000000000000000000000000000000000000000000",
+            ],
+        );
+        let lookup = MockLookup {
+            abis: HashMap::from([(TOKEN, TOKEN_ABI)]),
+        };
+
+        let calls = expand(
+            &lookup,
+            "0xca11bde05977b3631167028862be2a173976ca11",
+            "ethereum",
+            "mainnet",
+            "0x82ad56cb",
+            &input_data,
+        );
+        assert_eq!(calls.len(), 2);
+
+        assert_eq!(calls[0].target, TOKEN);
+        assert_eq!(calls[0].allow_failure, Some(false));
+        assert_eq!(calls[0].decode_level, DecodeLevel::Full);
+        let transfer = calls[0].decoded_function.as_ref().unwrap();
+        assert_eq!(transfer.name, "transfer");
+        assert_eq!(transfer.parameters[1].value, "1000");
+        assert!(calls[0].calls.is_empty());
+
+        // No ABI and no known selector: nothing decodes, the call is still listed
+        assert_eq!(calls[1].target, OTHER);
+        assert_eq!(calls[1].allow_failure, Some(true));
+        assert_eq!(calls[1].input_data, "0x12345678");
+        assert_eq!(calls[1].decoding_status, "AbiNotFound");
+    }
+
+    #[test]
+    fn test_safe_exec_transaction_decodes_wrapped_call() {
+        // execTransaction(TOKEN, 0, transfer(0x1111..., 1000), CALL, 0, 0, 0, 0x0, 0x0, signature)
+        let mut words = [
+            "00000000000000000000000000000000000000000000000000000000000000aa",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000140",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000000000000000000000000000000001c0",
+            "0000000000000000000000000000000000000000000000000000000000000044",
+            "a9059cbb00000000000000000000000011111111111111111111111111111111",
+            "1111111100000000000000000000000000000000000000000000000000000000",
+            "000003e800000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000041",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "1100000000000000000000000000000000000000000000000000000000000000",
+        ];
+        let input_data = calldata("0x6a761202", &words);
+        let lookup = MockLookup {
+            abis: HashMap::from([(TOKEN, TOKEN_ABI)]),
+        };
+
+        let calls = expand(
+            &lookup,
+            "0x00000000000000000000000000000000000000dd",
+            "ethereum",
+            "mainnet",
+            "0x6a761202",
+            &input_data,
+        );
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, TOKEN);
+        assert_eq!(calls[0].value.as_deref(), Some("0"));
+        assert_eq!(calls[0].operation, Some(CallOperation::Call));
+        assert_eq!(calls[0].decode_level, DecodeLevel::Full);
+        assert_eq!(
+            calls[0].decoded_function.as_ref().unwrap().signature,
+            "transfer(address,uint256)"
+        );
+
+        // operation 2 does not exist
+        words[3] = "0000000000000000000000000000000000000000000000000000000000000002";
+        let bad_operation = calldata("0x6a761202", &words);
+        assert!(expand(
+            &lookup,
+            "0x00000000000000000000000000000000000000dd",
+            "ethereum",
+            "mainnet",
+            "0x6a761202",
+            &bad_operation,
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_uniswap_multicall_targets_the_router() {
+        // multicall([transfer(0x1111..., 1000), refundETH()])
+        let input_data = calldata(
+            "0xac9650d8",
+            &[
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "00000000000000000000000000000000000000000000000000000000000000c0",
+                "0000000000000000000000000000000000000000000000000000000000000044",
+                "a9059cbb00000000000000000000000011111111111111111111111111111111",
+                "1111111100000000000000000000000000000000000000000000000000000000",
+                "000003e800000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "12210e8a00000000000000000000000000000000000000000000000000000000",
+            ],
+        );
+        let lookup = MockLookup {
+            abis: HashMap::from([(ROUTER, ROUTER_ABI)]),
+        };
+
+        let calls = expand(
+            &lookup,
+            ROUTER,
+            "ethereum",
+            "mainnet",
+            "0xac9650d8",
+            &input_data,
+        );
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|call| call.target == ROUTER));
+        assert_eq!(calls[0].allow_failure, None);
+        // Not in the router's ABI: decoded from the built-in signature list
+        assert_eq!(calls[0].decode_level, DecodeLevel::Partial);
+        assert_eq!(
+            calls[1].decoded_function.as_ref().unwrap().signature,
+            "refundETH()"
+        );
+
+        assert!(expand(
+            &lookup,
+            ROUTER,
+            "ethereum",
+            "mainnet",
+            "0xa9059cbb",
+            &input_data
+        )
+        .is_empty());
+    }
+}
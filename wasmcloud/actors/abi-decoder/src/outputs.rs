@@ -0,0 +1,493 @@
+//! Return data decoding
+//!
+//! Traces and `eth_call` simulations give the bytes a call returned, which
+//! only the called function's `outputs` explain. An `abi.decode.output`
+//! request names the contract, the call (its calldata or just the selector)
+//! and the return data; the function is found by selector in the contract's
+//! cached ABI, then its proxy implementation's, and the data decoded against
+//! its outputs.
+//!
+//! Reverted calls return an error instead: `Error(string)` from `require`,
+//! `Panic(uint256)` from failed assertions and arithmetic, or a custom error
+//! declared in the ABI. These are decoded when the request is `reverted`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::escalation::AbiLookup;
+use crate::proxy::ProxyImplementation;
+use crate::{AbiEntry, AbiInfo, AbiParam, Component, DecodedParameter};
+
+/// `Error(string)`
+const ERROR_SELECTOR: &str = "0x08c379a0";
+/// `Panic(uint256)`
+const PANIC_SELECTOR: &str = "0x4e487b71";
+
+/// Return data of one call to decode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputDecodeRequest {
+    pub network: String,
+    pub subnet: String,
+    /// Called contract
+    pub address: String,
+    /// Calldata of the call; only its selector is read
+    #[serde(default)]
+    pub input_data: Option<String>,
+    /// Selector of the call, when the calldata is not at hand
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// Return data (hex)
+    pub output: String,
+    /// The call reverted, so `output` is revert data
+    #[serde(default)]
+    pub reverted: bool,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+}
+
+impl OutputDecodeRequest {
+    /// Lowercase `0x`-prefixed selector of the call
+    pub fn call_selector(&self) -> Option<String> {
+        self.selector
+            .as_deref()
+            .or_else(|| self.input_data.as_deref().and_then(|input| input.get(..10)))
+            .filter(|selector| selector.len() == 10 && selector.starts_with("0x"))
+            .map(str::to_lowercase)
+    }
+}
+
+/// Decoded return value or revert error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedReturn {
+    /// Function name, or the error name for reverts (`Error`, `Panic`, custom)
+    pub name: String,
+    pub signature: String,
+    /// Output (or error) parameters in declaration order
+    pub values: Vec<DecodedParameter>,
+    /// Revert message: the `require` message or the panic's meaning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// ABI source; `None` for the built-in `Error` and `Panic`
+    pub abi_source: Option<String>,
+}
+
+/// Answer to an `abi.decode.output` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedOutput {
+    pub network: String,
+    pub subnet: String,
+    pub address: String,
+    /// Implementation whose ABI decoded the output, when `address` is a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<ProxyImplementation>,
+    pub request_id: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub selector: Option<String>,
+    pub output: String,
+    pub reverted: bool,
+    /// Decode status: `Success`, `AbiNotFound`, `FunctionNotFound`,
+    /// `ErrorNotFound` or `DecodingError`
+    pub decoding_status: String,
+    pub decoded_output: Option<DecodedReturn>,
+    pub error: Option<String>,
+    pub processed_at: String,
+    pub processor_id: String,
+}
+
+#[derive(Debug)]
+enum OutputError {
+    /// Neither the address nor its implementation has a cached ABI
+    AbiNotFound,
+    /// ABIs were cached but none declares the function or error
+    NotFound,
+    Decoding(String),
+}
+
+/// Decode the return (or revert) data of `request`
+pub fn decode_output(
+    lookup: &impl AbiLookup,
+    request: &OutputDecodeRequest,
+    processed_at: &str,
+) -> DecodedOutput {
+    let selector = request.call_selector();
+    let result = match hex::decode(request.output.trim_start_matches("0x")) {
+        Err(e) => Err(OutputError::Decoding(format!("Invalid output data: {}", e))),
+        Ok(data) if request.reverted => decode_revert(lookup, request, &data),
+        Ok(data) => match &selector {
+            Some(selector) => {
+                with_contract_abi(lookup, request, |abi| decode_return(abi, selector, &data))
+            }
+            None => Err(OutputError::Decoding(
+                "Missing selector or input_data".to_string(),
+            )),
+        },
+    };
+
+    let mut implementation = None;
+    let (decoding_status, decoded_output, error) = match result {
+        Ok((decoded, via)) => {
+            implementation = via;
+            ("Success", Some(decoded), None)
+        }
+        Err(OutputError::AbiNotFound) => (
+            "AbiNotFound",
+            None,
+            Some(format!("No ABI cached for {}", request.address)),
+        ),
+        Err(OutputError::NotFound) if request.reverted => (
+            "ErrorNotFound",
+            None,
+            Some("No ABI error matches the revert data".to_string()),
+        ),
+        Err(OutputError::NotFound) => (
+            "FunctionNotFound",
+            None,
+            Some("No ABI function matches the selector".to_string()),
+        ),
+        Err(OutputError::Decoding(error)) => ("DecodingError", None, Some(error)),
+    };
+    DecodedOutput {
+        network: request.network.clone(),
+        subnet: request.subnet.clone(),
+        address: request.address.to_lowercase(),
+        implementation,
+        request_id: request.request_id.clone(),
+        transaction_hash: request.transaction_hash.clone(),
+        selector,
+        output: request.output.clone(),
+        reverted: request.reverted,
+        decoding_status: decoding_status.to_string(),
+        decoded_output,
+        error,
+        processed_at: processed_at.to_string(),
+        processor_id: "abi-decoder-actor".to_string(),
+    }
+}
+
+/// Decode with the contract's ABI, then its implementation's; the
+/// implementation is returned when its ABI decoded the data
+fn with_contract_abi<T>(
+    lookup: &impl AbiLookup,
+    request: &OutputDecodeRequest,
+    decode: impl Fn(&AbiInfo) -> Result<T, OutputError>,
+) -> Result<(T, Option<ProxyImplementation>), OutputError> {
+    let cached = lookup.cached_abi(&request.address, &request.network);
+    let mut result = Err(OutputError::AbiNotFound);
+    if let Some(abi) = &cached {
+        result = decode(abi).map(|decoded| (decoded, None));
+        if !matches!(result, Err(OutputError::NotFound)) {
+            return result;
+        }
+    }
+
+    let Some(implementation) = lookup
+        .implementation_of(
+            &request.address,
+            &request.network,
+            &request.subnet,
+            cached.as_ref(),
+        )
+        .or_else(|| {
+            let selector = request.call_selector()?;
+            lookup.facet_of(
+                &request.address,
+                &request.network,
+                &request.subnet,
+                &selector,
+            )
+        })
+        .filter(|implementation| {
+            !implementation
+                .address
+                .eq_ignore_ascii_case(&request.address)
+        })
+    else {
+        return result;
+    };
+    match lookup.cached_abi(&implementation.address, &request.network) {
+        Some(abi) => decode(&abi).map(|decoded| (decoded, Some(implementation))),
+        None => result,
+    }
+}
+
+fn decode_return(abi: &AbiInfo, selector: &str, data: &[u8]) -> Result<DecodedReturn, OutputError> {
+    let entries = parse_entries(abi)?;
+    let function = abi
+        .selector_index
+        .get(selector)
+        .and_then(|&position| entries.get(position))
+        .filter(|entry| entry.entry_type == "function")
+        .or_else(|| {
+            entries
+                .iter()
+                .filter(|entry| entry.entry_type == "function")
+                .find(|function| entry_selector(function) == selector)
+        })
+        .ok_or(OutputError::NotFound)?;
+
+    Ok(DecodedReturn {
+        name: function.name.clone(),
+        signature: Component::build_signature(&function.name, &function.inputs),
+        values: decode_values(&function.outputs, data)?,
+        reason: None,
+        abi_source: Some(abi.source.clone()),
+    })
+}
+
+/// Decode revert data: the built-in `Error` / `Panic`, or a custom error
+/// declared in the contract's ABI
+fn decode_revert(
+    lookup: &impl AbiLookup,
+    request: &OutputDecodeRequest,
+    data: &[u8],
+) -> Result<(DecodedReturn, Option<ProxyImplementation>), OutputError> {
+    if data.len() < 4 {
+        return Err(OutputError::Decoding(
+            "Revert data has no error selector".to_string(),
+        ));
+    }
+    let error_selector = format!("0x{}", hex::encode(&data[..4]));
+    let builtin = match error_selector.as_str() {
+        ERROR_SELECTOR => Some(("Error", "string", "message")),
+        PANIC_SELECTOR => Some(("Panic", "uint256", "code")),
+        _ => None,
+    };
+    if let Some((name, param_type, param_name)) = builtin {
+        let params = [AbiParam {
+            name: param_name.to_string(),
+            param_type: param_type.to_string(),
+            indexed: false,
+            components: None,
+        }];
+        let values = decode_values(&params, &data[4..])?;
+        let reason = match name {
+            "Error" => values.first().map(|value| value.value.clone()),
+            _ => values.first().map(|value| panic_reason(&value.value)),
+        };
+        let decoded = DecodedReturn {
+            name: name.to_string(),
+            signature: format!("{}({})", name, param_type),
+            values,
+            reason,
+            abi_source: None,
+        };
+        return Ok((decoded, None));
+    }
+
+    with_contract_abi(lookup, request, |abi| {
+        let entries = parse_entries(abi)?;
+        let error = entries
+            .iter()
+            .filter(|entry| entry.entry_type == "error")
+            .find(|error| entry_selector(error) == error_selector)
+            .ok_or(OutputError::NotFound)?;
+        Ok(DecodedReturn {
+            name: error.name.clone(),
+            signature: Component::build_signature(&error.name, &error.inputs),
+            values: decode_values(&error.inputs, &data[4..])?,
+            reason: None,
+            abi_source: Some(abi.source.clone()),
+        })
+    })
+}
+
+fn parse_entries(abi: &AbiInfo) -> Result<Vec<AbiEntry>, OutputError> {
+    serde_json::from_str(&abi.abi_json)
+        .map_err(|e| OutputError::Decoding(format!("Invalid ABI JSON: {}", e)))
+}
+
+fn entry_selector(entry: &AbiEntry) -> String {
+    format!("0x{}", hex::encode(Component::function_selector(entry)))
+}
+
+fn decode_values(params: &[AbiParam], data: &[u8]) -> Result<Vec<DecodedParameter>, OutputError> {
+    let values = Component::decode_abi_params(params, data).map_err(OutputError::Decoding)?;
+    Ok(params
+        .iter()
+        .zip(&values)
+        .map(|(param, value)| DecodedParameter {
+            name: param.name.clone(),
+            param_type: param.param_type.clone(),
+            value: Component::format_abi_value(value),
+            indexed: false,
+        })
+        .collect())
+}
+
+/// Meaning of a Solidity panic code
+fn panic_reason(code: &str) -> String {
+    let reason = match code {
+        "1" => "Assertion failed",
+        "17" => "Arithmetic overflow or underflow",
+        "18" => "Division or modulo by zero",
+        "33" => "Invalid enum value",
+        "34" => "Invalid storage byte array encoding",
+        "49" => "pop() on an empty array",
+        "50" => "Array index out of bounds",
+        "65" => "Out of memory",
+        "81" => "Call to an uninitialized function",
+        _ => return format!("Panic code {}", code),
+    };
+    reason.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIR: &str = "0x00000000000000000000000000000000000000aa";
+    const PAIR_ABI: &str = r#"[
+        {"type":"function","name":"getReserves","inputs":[],"outputs":[
+            {"name":"reserve0","type":"uint112"},
+            {"name":"reserve1","type":"uint112"},
+            {"name":"blockTimestampLast","type":"uint32"}]},
+        {"type":"error","name":"InsufficientBalance","inputs":[
+            {"name":"available","type":"uint256"},
+            {"name":"required","type":"uint256"}]}]"#;
+
+    struct PairOnly;
+
+    impl AbiLookup for PairOnly {
+        fn cached_abi(&self, address: &str, network: &str) -> Option<AbiInfo> {
+            (address == PAIR).then(|| AbiInfo {
+                address: address.to_string(),
+                network: network.to_string(),
+                abi_json: PAIR_ABI.to_string(),
+                source: "etherscan".to_string(),
+                verified: true,
+                cached_at: String::new(),
+                implementation_address: None,
+                uploaded_by: None,
+                expires_at: None,
+                selector_index: Default::default(),
+            })
+        }
+
+        fn implementation_of(
+            &self,
+            _address: &str,
+            _network: &str,
+            _subnet: &str,
+            _cached: Option<&AbiInfo>,
+        ) -> Option<ProxyImplementation> {
+            None
+        }
+
+        fn signature(&self, _selector: &str) -> Option<String> {
+            None
+        }
+    }
+
+    fn request(output: &[&str], reverted: bool) -> OutputDecodeRequest {
+        OutputDecodeRequest {
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            address: PAIR.to_string(),
+            input_data: Some("0x0902f1ac".to_string()),
+            selector: None,
+            output: format!("0x{}", output.concat()),
+            reverted,
+            request_id: Some("call-1".to_string()),
+            transaction_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_decode_return_data() {
+        let output = [
+            "00000000000000000000000000000000000000000000000000000000000003e8",
+            "00000000000000000000000000000000000000000000000000000000000007d0",
+            "0000000000000000000000000000000000000000000000000000000065920080",
+        ];
+        let decoded = decode_output(&PairOnly, &request(&output, false), "now");
+        assert_eq!(decoded.decoding_status, "Success");
+        let returned = decoded.decoded_output.unwrap();
+        assert_eq!(returned.signature, "getReserves()");
+        let values: Vec<(&str, &str)> = returned
+            .values
+            .iter()
+            .map(|value| (value.name.as_str(), value.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("reserve0", "1000"),
+                ("reserve1", "2000"),
+                ("blockTimestampLast", "1704067200")
+            ]
+        );
+
+        let mut unknown = request(&output, false);
+        unknown.input_data = None;
+        unknown.selector = Some("0x12345678".to_string());
+        assert_eq!(
+            decode_output(&PairOnly, &unknown, "now").decoding_status,
+            "FunctionNotFound"
+        );
+        unknown.address = "0x00000000000000000000000000000000000000bb".to_string();
+        assert_eq!(
+            decode_output(&PairOnly, &unknown, "now").decoding_status,
+            "AbiNotFound"
+        );
+    }
+
+    #[test]
+    fn test_decode_revert_data() {
+        let require = decode_output(
+            &PairOnly,
+            &request(
+                &[
+                    "08c379a0",
+                    "0000000000000000000000000000000000000000000000000000000000000020",
+                    "0000000000000000000000000000000000000000000000000000000000000020",
+                    "4f776e61626c653a2063616c6c6572206973206e6f7420746865206f776e6572",
+                ],
+                true,
+            ),
+            "now",
+        );
+        let error = require.decoded_output.unwrap();
+        assert_eq!(error.name, "Error");
+        assert_eq!(
+            error.reason.as_deref(),
+            Some("Ownable: caller is not the owner")
+        );
+
+        let panic = decode_output(
+            &PairOnly,
+            &request(
+                &[
+                    "4e487b71",
+                    "0000000000000000000000000000000000000000000000000000000000000011",
+                ],
+                true,
+            ),
+            "now",
+        );
+        assert_eq!(
+            panic.decoded_output.unwrap().reason.as_deref(),
+            Some("Arithmetic overflow or underflow")
+        );
+
+        let custom = decode_output(
+            &PairOnly,
+            &request(
+                &[
+                    "cf479181",
+                    "0000000000000000000000000000000000000000000000000000000000000001",
+                    "0000000000000000000000000000000000000000000000000000000000000002",
+                ],
+                true,
+            ),
+            "now",
+        );
+        let error = custom.decoded_output.unwrap();
+        assert_eq!(error.signature, "InsufficientBalance(uint256,uint256)");
+        assert_eq!(error.values[1].value, "2");
+        assert_eq!(error.abi_source.as_deref(), Some("etherscan"));
+
+        let unknown = decode_output(&PairOnly, &request(&["deadbeef"], true), "now");
+        assert_eq!(unknown.decoding_status, "ErrorNotFound");
+    }
+}
@@ -0,0 +1,394 @@
+//! Off-chain signed approvals
+//!
+//! A `permit` call sets an allowance from the owner's signature, submitted by
+//! anyone, so the approval never appears as an `approve` from the owner.
+//! Calls to the permit entry points below are decoded into a [`Permit`] that
+//! is attached to the decoded output, for approval monitoring to alert on:
+//! - EIP-2612 `permit(owner, spender, value, deadline, v, r, s)` on the token
+//! - DAI-style `permit(holder, spender, nonce, expiry, allowed, v, r, s)`,
+//!   granting an unlimited allowance or revoking it
+//! - Uniswap Permit2 `permit(owner, PermitSingle, signature)` and
+//!   `permit(owner, PermitBatch, signature)`, one allowance per token
+//!
+//! The signature is only checked for shape, not recovered: `v` must be 27 or
+//! 28, `r` and `s` non-zero and below the curve order, and `s` in the lower
+//! half (EIP-2). Permit2 takes the signature as bytes: 65 bytes are `r, s, v`,
+//! 64 bytes an EIP-2098 compact signature; any other non-empty signature is
+//! left to the owner contract (EIP-1271).
+
+use serde::{Deserialize, Serialize};
+
+use crate::escalation::{argument_bytes, parse_signature};
+use crate::{AbiValue, Component};
+
+/// secp256k1 group order
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+/// Largest `s` accepted by EIP-2, `CURVE_ORDER / 2`
+const HALF_CURVE_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// Permit entry point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermitKind {
+    Eip2612,
+    Dai,
+    Permit2,
+    Permit2Batch,
+}
+
+/// Permit entry points, by selector
+const PERMIT_SIGNATURES: &[(&str, &str, PermitKind)] = &[
+    (
+        "0xd505accf",
+        "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+        PermitKind::Eip2612,
+    ),
+    (
+        "0x8fcbaf0c",
+        "permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)",
+        PermitKind::Dai,
+    ),
+    (
+        "0x2b67b570",
+        "permit(address,((address,uint160,uint48,uint48),address,uint256),bytes)",
+        PermitKind::Permit2,
+    ),
+    (
+        "0x2a2d80d1",
+        "permit(address,((address,uint160,uint48,uint48)[],address,uint256),bytes)",
+        PermitKind::Permit2Batch,
+    ),
+];
+
+/// How the signature was encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureFormat {
+    /// Separate `v`, `r`, `s` arguments
+    Vrs,
+    /// 65 bytes `r, s, v`
+    Packed,
+    /// EIP-2098 64 bytes `r, vs`
+    Compact,
+    /// Validated by the owner contract (EIP-1271)
+    Contract,
+}
+
+/// Signature embedded in a permit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermitSignature {
+    pub format: SignatureFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub s: Option<String>,
+    /// Whether the signature is well-formed
+    pub valid: bool,
+    /// Why it is not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One allowance granted by a permit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermitAllowance {
+    /// Lowercase token address
+    pub token: String,
+    /// Allowance in base units, as a decimal string
+    pub amount: String,
+    /// Amount is the type's maximum
+    pub unlimited: bool,
+    /// Permit2: when the allowance lapses (unix seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<String>,
+}
+
+/// Decoded off-chain signed approval
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permit {
+    pub kind: PermitKind,
+    pub owner: String,
+    pub spender: String,
+    pub allowances: Vec<PermitAllowance>,
+    /// Last second the signature can be submitted (unix seconds; DAI `0` never expires)
+    pub deadline: String,
+    pub signature: PermitSignature,
+}
+
+/// Text signature of a permit entry point
+pub fn permit_signature(selector: &str) -> Option<&'static str> {
+    PERMIT_SIGNATURES
+        .iter()
+        .find(|(known, _, _)| known.eq_ignore_ascii_case(selector))
+        .map(|(_, signature, _)| *signature)
+}
+
+/// Permit granted by a call to `address`, if the call is one
+pub fn decode_permit(address: &str, input_data: &str) -> Option<Permit> {
+    let selector = input_data.get(..10)?;
+    let (_, signature, kind) = PERMIT_SIGNATURES
+        .iter()
+        .find(|(known, _, _)| known.eq_ignore_ascii_case(selector))?;
+    let (_, inputs) = parse_signature(signature).ok()?;
+    let data = argument_bytes(input_data).ok()?;
+    let values = Component::decode_abi_params(&inputs, &data).ok()?;
+
+    match kind {
+        PermitKind::Eip2612 | PermitKind::Dai => token_permit(*kind, address, &values),
+        PermitKind::Permit2 | PermitKind::Permit2Batch => permit2(*kind, &values),
+    }
+}
+
+/// EIP-2612 and DAI permits, signed as separate `v`, `r`, `s`
+fn token_permit(kind: PermitKind, token: &str, values: &[AbiValue]) -> Option<Permit> {
+    // DAI takes a nonce where EIP-2612 takes the value, and `allowed` before `v`
+    let v_at = if kind == PermitKind::Dai { 5 } else { 4 };
+    let (Some(AbiValue::Uint256(v)), Some(AbiValue::FixedBytes(r)), Some(AbiValue::FixedBytes(s))) =
+        (values.get(v_at), values.get(v_at + 1), values.get(v_at + 2))
+    else {
+        return None;
+    };
+    let (amount, unlimited) = match (kind, values.get(2), values.get(4)) {
+        (PermitKind::Dai, _, Some(AbiValue::Bool(true))) => {
+            (Component::u256_to_decimal(&[0xff; 32]), true)
+        }
+        (PermitKind::Dai, _, Some(AbiValue::Bool(false))) => ("0".to_string(), false),
+        (PermitKind::Eip2612, Some(AbiValue::Uint256(value)), _) => (
+            Component::u256_to_decimal(value),
+            value.iter().all(|b| *b == 0xff),
+        ),
+        _ => return None,
+    };
+
+    Some(Permit {
+        kind,
+        owner: address_at(values, 0)?,
+        spender: address_at(values, 1)?,
+        allowances: vec![PermitAllowance {
+            token: token.to_lowercase(),
+            amount,
+            unlimited,
+            expiration: None,
+        }],
+        deadline: uint_at(values, 3)?,
+        signature: vrs_signature(SignatureFormat::Vrs, v[31], r, s),
+    })
+}
+
+/// Permit2 `permit(owner, (details, spender, sigDeadline), signature)`
+fn permit2(kind: PermitKind, values: &[AbiValue]) -> Option<Permit> {
+    let (Some(AbiValue::Tuple(permit)), Some(AbiValue::Bytes(signature))) =
+        (values.get(1), values.get(2))
+    else {
+        return None;
+    };
+    let details = match permit.first()? {
+        AbiValue::Array(details) => details.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+
+    Some(Permit {
+        kind,
+        owner: address_at(values, 0)?,
+        spender: address_at(permit, 1)?,
+        allowances: details
+            .iter()
+            .map(permit2_allowance)
+            .collect::<Option<_>>()?,
+        deadline: uint_at(permit, 2)?,
+        signature: bytes_signature(signature),
+    })
+}
+
+/// `(token, amount, expiration, nonce)` of a Permit2 `PermitDetails`
+fn permit2_allowance(details: &AbiValue) -> Option<PermitAllowance> {
+    let AbiValue::Tuple(members) = details else {
+        return None;
+    };
+    let Some(AbiValue::Uint256(amount)) = members.get(1) else {
+        return None;
+    };
+    Some(PermitAllowance {
+        token: address_at(members, 0)?,
+        amount: Component::u256_to_decimal(amount),
+        // uint160 maximum
+        unlimited: amount[12..].iter().all(|b| *b == 0xff),
+        expiration: Some(uint_at(members, 2)?),
+    })
+}
+
+/// Shape check of a Permit2 bytes signature
+fn bytes_signature(signature: &[u8]) -> PermitSignature {
+    match signature.len() {
+        65 => vrs_signature(
+            SignatureFormat::Packed,
+            signature[64],
+            &signature[..32],
+            &signature[32..64],
+        ),
+        64 => {
+            let mut s = signature[32..].to_vec();
+            let v = 27 + (s[0] >> 7);
+            s[0] &= 0x7f;
+            vrs_signature(SignatureFormat::Compact, v, &signature[..32], &s)
+        }
+        0 => PermitSignature {
+            format: SignatureFormat::Contract,
+            v: None,
+            r: None,
+            s: None,
+            valid: false,
+            error: Some("Empty signature".to_string()),
+        },
+        _ => PermitSignature {
+            format: SignatureFormat::Contract,
+            v: None,
+            r: None,
+            s: None,
+            valid: true,
+            error: None,
+        },
+    }
+}
+
+fn vrs_signature(format: SignatureFormat, v: u8, r: &[u8], s: &[u8]) -> PermitSignature {
+    let is_zero = |word: &[u8]| word.iter().all(|b| *b == 0);
+    let error = if v != 27 && v != 28 {
+        Some(format!("Invalid recovery id {}", v))
+    } else if is_zero(r) || r >= CURVE_ORDER.as_slice() {
+        Some("r out of range".to_string())
+    } else if is_zero(s) || s >= CURVE_ORDER.as_slice() {
+        Some("s out of range".to_string())
+    } else if s > HALF_CURVE_ORDER.as_slice() {
+        Some("Malleable signature: s in the upper half of the curve order".to_string())
+    } else {
+        None
+    };
+    PermitSignature {
+        format,
+        v: Some(v),
+        r: Some(format!("0x{}", hex::encode(r))),
+        s: Some(format!("0x{}", hex::encode(s))),
+        valid: error.is_none(),
+        error,
+    }
+}
+
+fn address_at(values: &[AbiValue], index: usize) -> Option<String> {
+    match values.get(index)? {
+        AbiValue::Address(address) => Some(format!("0x{}", hex::encode(address))),
+        _ => None,
+    }
+}
+
+fn uint_at(values: &[AbiValue], index: usize) -> Option<String> {
+    match values.get(index)? {
+        AbiValue::Uint256(value) => Some(Component::u256_to_decimal(value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000C1";
+    const OWNER: &str = "0x00000000000000000000000000000000000000a1";
+    const SPENDER: &str = "0x00000000000000000000000000000000000000b2";
+
+    fn calldata(selector: &str, words: &[&str]) -> String {
+        format!("{}{}", selector, words.concat())
+    }
+
+    #[test]
+    fn test_eip2612_permit() {
+        let mut words = [
+            "00000000000000000000000000000000000000000000000000000000000000a1",
+            "00000000000000000000000000000000000000000000000000000000000000b2",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "000000000000000000000000000000000000000000000000000000006553f100",
+            "000000000000000000000000000000000000000000000000000000000000001b",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+        ];
+        let permit = decode_permit(TOKEN, &calldata("0xd505accf", &words)).unwrap();
+        assert_eq!(permit.kind, PermitKind::Eip2612);
+        assert_eq!(permit.owner, OWNER);
+        assert_eq!(permit.spender, SPENDER);
+        assert_eq!(permit.deadline, "1700000000");
+        assert_eq!(permit.allowances.len(), 1);
+        assert_eq!(permit.allowances[0].token, TOKEN.to_lowercase());
+        assert!(permit.allowances[0].unlimited);
+        assert_eq!(permit.signature.format, SignatureFormat::Vrs);
+        assert_eq!(permit.signature.v, Some(27));
+        assert!(permit.signature.valid);
+
+        // High-s signatures are malleable
+        words[6] = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140";
+        let permit = decode_permit(TOKEN, &calldata("0xd505accf", &words)).unwrap();
+        assert!(!permit.signature.valid);
+        assert!(permit.signature.error.unwrap().starts_with("Malleable"));
+
+        words[4] = "0000000000000000000000000000000000000000000000000000000000000001";
+        let permit = decode_permit(TOKEN, &calldata("0xd505accf", &words)).unwrap();
+        assert_eq!(
+            permit.signature.error.as_deref(),
+            Some("Invalid recovery id 1")
+        );
+
+        assert_eq!(decode_permit(TOKEN, &calldata("0xa9059cbb", &words)), None);
+    }
+
+    #[test]
+    fn test_permit2_single() {
+        let words = [
+            "00000000000000000000000000000000000000000000000000000000000000a1",
+            "00000000000000000000000000000000000000000000000000000000000000c1",
+            "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+            "000000000000000000000000000000000000000000000000000000006553ff10",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000000000000000000000000000000000b2",
+            "000000000000000000000000000000000000000000000000000000006553f100",
+            "0000000000000000000000000000000000000000000000000000000000000100",
+            "0000000000000000000000000000000000000000000000000000000000000041",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+            "1c00000000000000000000000000000000000000000000000000000000000000",
+        ];
+        let permit2 = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+        let permit = decode_permit(permit2, &calldata("0x2b67b570", &words)).unwrap();
+        assert_eq!(permit.kind, PermitKind::Permit2);
+        assert_eq!(permit.owner, OWNER);
+        assert_eq!(permit.spender, SPENDER);
+        assert_eq!(
+            permit.allowances,
+            vec![PermitAllowance {
+                token: TOKEN.to_lowercase(),
+                amount: "1000000000000000000".to_string(),
+                unlimited: false,
+                expiration: Some("1700003600".to_string()),
+            }]
+        );
+        assert_eq!(permit.signature.format, SignatureFormat::Packed);
+        assert_eq!(permit.signature.v, Some(28));
+        assert!(permit.signature.valid);
+
+        // Compact signatures carry v in the top bit of s
+        let mut compact = [0x11u8; 64];
+        compact[32] = 0xa2;
+        let signature = bytes_signature(&compact);
+        assert_eq!(signature.format, SignatureFormat::Compact);
+        assert_eq!(signature.v, Some(28));
+        assert!(signature.s.unwrap().starts_with("0x2211"));
+        assert!(bytes_signature(&[0u8; 96]).valid);
+        assert!(!bytes_signature(&[]).valid);
+    }
+}
@@ -0,0 +1,231 @@
+//! Proxy implementation resolution
+//!
+//! A call to a proxy carries the implementation's selectors, so the proxy's own
+//! ABI (often empty) cannot decode it. The implementation is taken from, in
+//! order:
+//! 1. the proxy's cached ABI entry (`implementation_address`)
+//! 2. the `proxy:implementation:{network}:{address}` registry kept current by
+//!    evm-logs-ingestion from `Upgraded` logs
+//! 3. the chain, read through the http-rpc provider (`rpc.request.{network}.{subnet}`):
+//!    - EIP-1167 minimal proxies carry the implementation in their bytecode
+//!    - EIP-1967 proxies store it in the implementation slot
+//!    - EIP-1967 beacon proxies store a beacon whose `implementation()` names it
+//!
+//! Chain lookups are cached in `proxy:resolved:{network}:{address}` for a day,
+//! an empty value recording a contract that is not a proxy.
+//!
+//! EIP-2535 diamonds have one implementation per selector instead; see
+//! [`crate::diamond`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// `keccak256("eip1967.proxy.implementation") - 1`
+pub const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// `keccak256("eip1967.proxy.beacon") - 1`
+pub const EIP1967_BEACON_SLOT: &str =
+    "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// `implementation()` of an EIP-1967 beacon
+const BEACON_IMPLEMENTATION_SELECTOR: &str = "0x5c60da1b";
+
+/// EIP-1167 runtime code around the 20-byte implementation address
+const MINIMAL_PROXY_PREFIX: &str = "363d3d373d3d3d363d73";
+const MINIMAL_PROXY_SUFFIX: &str = "5af43d82803e903d91602b57fd5bf3";
+
+/// How a proxy's implementation was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyKind {
+    /// Cached ABI entry or the upgrade registry
+    Registry,
+    Eip1967,
+    Eip1967Beacon,
+    Eip1167,
+    /// Facet of an EIP-2535 diamond serving the call's selector
+    Eip2535Facet,
+}
+
+/// Implementation behind a proxy address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyImplementation {
+    /// Lowercase implementation address
+    pub address: String,
+    pub kind: ProxyKind,
+}
+
+impl ProxyImplementation {
+    pub fn new(address: &str, kind: ProxyKind) -> Self {
+        Self {
+            address: address.trim().to_lowercase(),
+            kind,
+        }
+    }
+}
+
+/// JSON-RPC access to the chain the proxy lives on
+pub trait ChainReader {
+    /// `result` of `method`, `None` on any failure
+    fn rpc(&self, method: &str, params: Value) -> Option<Value>;
+}
+
+/// Read the implementation of `address` from the chain
+pub fn resolve_on_chain(chain: &impl ChainReader, address: &str) -> Option<ProxyImplementation> {
+    let code = chain.rpc("eth_getCode", json!([address, "latest"]))?;
+    let code = code.as_str()?;
+    if code.trim_start_matches("0x").is_empty() {
+        return None;
+    }
+    if let Some(implementation) = minimal_proxy_target(code) {
+        return Some(ProxyImplementation::new(
+            &implementation,
+            ProxyKind::Eip1167,
+        ));
+    }
+
+    let slot = |slot: &str| {
+        chain
+            .rpc("eth_getStorageAt", json!([address, slot, "latest"]))
+            .and_then(|word| word.as_str().and_then(address_from_word))
+    };
+    if let Some(implementation) = slot(EIP1967_IMPLEMENTATION_SLOT) {
+        return Some(ProxyImplementation::new(
+            &implementation,
+            ProxyKind::Eip1967,
+        ));
+    }
+    let beacon = slot(EIP1967_BEACON_SLOT)?;
+    let implementation = chain
+        .rpc(
+            "eth_call",
+            json!([{"to": beacon, "data": BEACON_IMPLEMENTATION_SELECTOR}, "latest"]),
+        )
+        .and_then(|word| word.as_str().and_then(address_from_word))?;
+    Some(ProxyImplementation::new(
+        &implementation,
+        ProxyKind::Eip1967Beacon,
+    ))
+}
+
+/// Implementation address of EIP-1167 runtime code
+pub fn minimal_proxy_target(code: &str) -> Option<String> {
+    let code = code.trim_start_matches("0x").to_lowercase();
+    let target = code
+        .strip_prefix(MINIMAL_PROXY_PREFIX)?
+        .strip_suffix(MINIMAL_PROXY_SUFFIX)?;
+    (target.len() == 40 && target.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("0x{}", target))
+}
+
+/// Address in the low 20 bytes of a 32-byte word; `None` for zero or for a
+/// word that is not an address
+pub fn address_from_word(word: &str) -> Option<String> {
+    let digits = word.trim_start_matches("0x");
+    if digits.len() != 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let (padding, address) = digits.split_at(24);
+    if padding.chars().any(|c| c != '0') || address.chars().all(|c| c == '0') {
+        return None;
+    }
+    Some(format!("0x{}", address.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const PROXY: &str = "0x00000000000000000000000000000000000000aa";
+    const BEACON: &str = "0x00000000000000000000000000000000000000bb";
+    const IMPLEMENTATION: &str = "0x00000000000000000000000000000000000000cc";
+
+    #[derive(Default)]
+    struct MockChain {
+        code: String,
+        storage: HashMap<&'static str, String>,
+        beacon_answer: Option<String>,
+    }
+
+    impl ChainReader for MockChain {
+        fn rpc(&self, method: &str, params: Value) -> Option<Value> {
+            match method {
+                "eth_getCode" => Some(json!(self.code)),
+                "eth_getStorageAt" => {
+                    let slot = params[1].as_str()?;
+                    let word = self
+                        .storage
+                        .get(slot)
+                        .cloned()
+                        .unwrap_or_else(|| format!("0x{}", "0".repeat(64)));
+                    Some(json!(word))
+                }
+                "eth_call" => {
+                    assert_eq!(params[0]["to"], BEACON);
+                    self.beacon_answer.clone().map(Value::from)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    fn word(address: &str) -> String {
+        format!("0x{:0>64}", address.trim_start_matches("0x"))
+    }
+
+    #[test]
+    fn test_minimal_proxy_bytecode() {
+        let code = format!(
+            "0x{}{}{}",
+            MINIMAL_PROXY_PREFIX,
+            IMPLEMENTATION.trim_start_matches("0x"),
+            MINIMAL_PROXY_SUFFIX
+        );
+        let chain = MockChain {
+            code,
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_on_chain(&chain, PROXY),
+            Some(ProxyImplementation::new(IMPLEMENTATION, ProxyKind::Eip1167))
+        );
+        assert_eq!(minimal_proxy_target("0x363d3d373d3d3d363d73"), None);
+    }
+
+    #[test]
+    fn test_eip1967_slots_and_beacon() {
+        let mut chain = MockChain {
+            code: "0x6080604052".to_string(),
+            ..Default::default()
+        };
+        // Plain contract: both slots empty
+        assert_eq!(resolve_on_chain(&chain, PROXY), None);
+
+        chain.storage.insert(EIP1967_BEACON_SLOT, word(BEACON));
+        chain.beacon_answer = Some(word(IMPLEMENTATION));
+        assert_eq!(
+            resolve_on_chain(&chain, PROXY),
+            Some(ProxyImplementation::new(
+                IMPLEMENTATION,
+                ProxyKind::Eip1967Beacon
+            ))
+        );
+
+        chain.storage.insert(
+            EIP1967_IMPLEMENTATION_SLOT,
+            word("0x00000000000000000000000000000000000000Dd"),
+        );
+        assert_eq!(
+            resolve_on_chain(&chain, PROXY),
+            Some(ProxyImplementation::new(
+                "0x00000000000000000000000000000000000000dd",
+                ProxyKind::Eip1967
+            ))
+        );
+
+        // No code at all: not deployed, nothing to resolve
+        chain.code = "0x".to_string();
+        assert_eq!(resolve_on_chain(&chain, PROXY), None);
+        assert_eq!(address_from_word(&format!("0x{}", "f".repeat(64))), None);
+    }
+}
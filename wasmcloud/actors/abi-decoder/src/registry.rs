@@ -0,0 +1,314 @@
+//! ABI registry management
+//!
+//! Operators and the dashboard manage cached ABIs over request/reply:
+//! - `abi.registry.put` uploads an ABI, e.g. for an unverified contract or one
+//!   deployed from private source, with its source metadata and an optional TTL
+//! - `abi.registry.get` returns the cached entry of `{network, address}`
+//! - `abi.registry.delete` removes it
+//!
+//! Every request is answered with an [`AbiRegistryResponse`]. Uploads are
+//! validated before anything is written: the address must be 20 bytes of hex,
+//! the ABI must be a JSON entry array (also accepted as a string or inside a
+//! build artifact's `abi` field) with at least one function or event, and every
+//! parameter type must parse. The stored ABI is re-serialized from the parsed
+//! entries, so `abi_json` always has the shape the decoder reads.
+//!
+//! wasi:keyvalue cannot expire keys, so an upload's TTL is recorded as
+//! `expires_at` and the decoder treats the entry as missing past it. The
+//! redis-janitor expires `abi:*` keys after the `ABI_CACHE` TTL, which is
+//! therefore also the longest TTL an upload can have.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::abi_type::AbiType;
+use crate::{rfc3339_from_unix_secs, AbiEntry, AbiInfo, AbiParam, Component};
+
+/// Source recorded when an upload names none
+const DEFAULT_SOURCE: &str = "custom";
+
+/// Entry types of a JSON ABI
+const ENTRY_TYPES: [&str; 6] = [
+    "function",
+    "event",
+    "constructor",
+    "fallback",
+    "receive",
+    "error",
+];
+
+/// `abi.registry.put` body
+#[derive(Debug, Clone, Deserialize)]
+pub struct AbiPutRequest {
+    pub network: String,
+    pub address: String,
+    /// JSON ABI: an entry array, the array as a string, or an object with an `abi` field
+    pub abi: Value,
+    /// Where the ABI came from; defaults to `custom`
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub verified: bool,
+    /// Operator or dashboard user who uploaded the ABI
+    #[serde(default)]
+    pub uploaded_by: Option<String>,
+    /// Implementation contract when the address is a proxy
+    #[serde(default)]
+    pub implementation_address: Option<String>,
+    /// Seconds until the entry expires; capped at the `ABI_CACHE` TTL
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// `abi.registry.get` and `abi.registry.delete` body
+#[derive(Debug, Clone, Deserialize)]
+pub struct AbiKeyRequest {
+    pub network: String,
+    pub address: String,
+}
+
+impl AbiKeyRequest {
+    /// Normalized `(network, address)`
+    pub fn normalize(&self) -> Result<(String, String), String> {
+        Ok((
+            normalize_network(&self.network)?,
+            normalize_address(&self.address)
+                .ok_or_else(|| format!("Invalid contract address: {}", self.address))?,
+        ))
+    }
+}
+
+/// Reply to every registry request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AbiRegistryResponse {
+    pub ok: bool,
+    /// Stored entry for `put` and `get`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abi: Option<AbiInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AbiRegistryResponse {
+    pub fn found(abi: AbiInfo) -> Self {
+        Self {
+            ok: true,
+            abi: Some(abi),
+            error: None,
+        }
+    }
+
+    pub fn deleted() -> Self {
+        Self {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            abi: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Validate an upload and build the entry to store
+pub fn normalize_put(request: &AbiPutRequest, now_secs: u64) -> Result<AbiInfo, String> {
+    let network = normalize_network(&request.network)?;
+    let address = normalize_address(&request.address)
+        .ok_or_else(|| format!("Invalid contract address: {}", request.address))?;
+    let implementation_address = request
+        .implementation_address
+        .as_deref()
+        .map(|implementation| {
+            normalize_address(implementation)
+                .ok_or_else(|| format!("Invalid implementation address: {}", implementation))
+        })
+        .transpose()?;
+
+    let entries = parse_entries(&request.abi)?;
+    let abi_json =
+        serde_json::to_string(&entries).map_err(|e| format!("Failed to serialize ABI: {}", e))?;
+
+    let max_ttl = retention_policy::ABI_CACHE.ttl_secs.unwrap_or(u64::MAX);
+    let expires_at = match request.ttl_secs {
+        Some(0) => return Err("ttl_secs must be positive".to_string()),
+        Some(ttl) => Some(rfc3339_from_unix_secs(
+            now_secs.saturating_add(ttl.min(max_ttl)),
+        )),
+        None => None,
+    };
+    let source = request
+        .source
+        .as_deref()
+        .map(str::trim)
+        .filter(|source| !source.is_empty())
+        .unwrap_or(DEFAULT_SOURCE)
+        .to_string();
+
+    Ok(AbiInfo {
+        address,
+        network,
+        abi_json,
+        source,
+        verified: request.verified,
+        cached_at: rfc3339_from_unix_secs(now_secs),
+        implementation_address,
+        uploaded_by: request.uploaded_by.clone(),
+        expires_at,
+        selector_index: Component::selector_index(&entries),
+    })
+}
+
+/// Whether an entry's TTL has run out; `now` is RFC 3339 UTC like `expires_at`
+pub fn is_expired(abi: &AbiInfo, now: &str) -> bool {
+    abi.expires_at
+        .as_deref()
+        .is_some_and(|expires_at| expires_at <= now)
+}
+
+/// Parsed and validated entries of an uploaded ABI
+fn parse_entries(abi: &Value) -> Result<Vec<AbiEntry>, String> {
+    let parsed;
+    let abi = match abi {
+        Value::String(text) => {
+            parsed = serde_json::from_str::<Value>(text)
+                .map_err(|e| format!("ABI is not valid JSON: {}", e))?;
+            &parsed
+        }
+        other => other,
+    };
+    // Hardhat/Foundry artifacts and explorer responses wrap the entries
+    let abi = abi.get("abi").unwrap_or(abi);
+    let entries: Vec<AbiEntry> = serde_json::from_value(abi.clone())
+        .map_err(|e| format!("ABI is not a JSON entry array: {}", e))?;
+
+    for entry in &entries {
+        if !ENTRY_TYPES.contains(&entry.entry_type.as_str()) {
+            return Err(format!("Unknown ABI entry type: {}", entry.entry_type));
+        }
+        let named = matches!(entry.entry_type.as_str(), "function" | "event" | "error");
+        if named && entry.name.is_empty() {
+            return Err(format!("Unnamed {} entry", entry.entry_type));
+        }
+        for param in entry.inputs.iter().chain(&entry.outputs) {
+            validate_param(&entry.name, param)?;
+        }
+    }
+    if !entries
+        .iter()
+        .any(|entry| entry.entry_type == "function" || entry.entry_type == "event")
+    {
+        return Err("ABI has no functions or events".to_string());
+    }
+    Ok(entries)
+}
+
+fn validate_param(entry: &str, param: &AbiParam) -> Result<(), String> {
+    AbiType::from_param(param)
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", entry, e))
+}
+
+fn normalize_network(network: &str) -> Result<String, String> {
+    let network = network.trim().to_lowercase();
+    if network.is_empty() || network.contains(':') {
+        return Err(format!("Invalid network: {}", network));
+    }
+    Ok(network)
+}
+
+/// `0x`-prefixed lowercase address, or `None` if not 20 bytes of hex
+fn normalize_address(address: &str) -> Option<String> {
+    let digits = address.trim().strip_prefix("0x")?;
+    (digits.len() == 40 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("0x{}", digits.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn put(abi: Value) -> AbiPutRequest {
+        serde_json::from_value(json!({
+            "network": "Ethereum",
+            "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "abi": abi,
+            "uploaded_by": "ops@ekko.zone",
+            "ttl_secs": 3600
+        }))
+        .unwrap()
+    }
+
+    fn transfer_abi() -> Value {
+        json!([{
+            "type": "function",
+            "name": "transfer",
+            "inputs": [
+                {"name": "to", "type": "address", "internalType": "address"},
+                {"name": "amount", "type": "uint256", "internalType": "uint256"}
+            ],
+            "outputs": [{"name": "", "type": "bool"}],
+            "stateMutability": "nonpayable"
+        }])
+    }
+
+    #[test]
+    fn test_normalize_put() {
+        let abi = normalize_put(&put(transfer_abi()), 0).unwrap();
+        assert_eq!(abi.network, "ethereum");
+        assert_eq!(abi.address, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        assert_eq!(abi.source, "custom");
+        assert!(!abi.verified);
+        assert_eq!(abi.uploaded_by.as_deref(), Some("ops@ekko.zone"));
+        assert_eq!(abi.cached_at, "1970-01-01T00:00:00Z");
+        assert_eq!(abi.expires_at.as_deref(), Some("1970-01-01T01:00:00Z"));
+
+        let entries: Vec<AbiEntry> = serde_json::from_str(&abi.abi_json).unwrap();
+        assert_eq!(entries[0].name, "transfer");
+        assert!(!abi.abi_json.contains("internalType"));
+
+        // Stringified ABIs and build artifacts are unwrapped
+        let text = normalize_put(&put(json!(transfer_abi().to_string())), 0).unwrap();
+        let artifact = normalize_put(&put(json!({"abi": transfer_abi()})), 0).unwrap();
+        assert_eq!(text.abi_json, abi.abi_json);
+        assert_eq!(artifact.abi_json, abi.abi_json);
+
+        assert!(is_expired(&abi, "1970-01-01T01:00:00Z"));
+        assert!(!is_expired(&abi, "1970-01-01T00:59:59Z"));
+    }
+
+    #[test]
+    fn test_normalize_put_rejects_invalid_uploads() {
+        let mut request = put(transfer_abi());
+        request.address = "0xa0b8".to_string();
+        assert!(normalize_put(&request, 0).is_err());
+
+        let bad_type = json!([{
+            "type": "function",
+            "name": "f",
+            "inputs": [{"name": "x", "type": "uint7"}]
+        }]);
+        assert_eq!(
+            normalize_put(&put(bad_type), 0).unwrap_err(),
+            "f: Invalid integer type: uint7"
+        );
+        assert!(normalize_put(&put(json!([{"type": "constructor", "inputs": []}])), 0).is_err());
+        assert!(normalize_put(&put(json!("not json")), 0).is_err());
+
+        let mut request = put(transfer_abi());
+        request.ttl_secs = Some(0);
+        assert!(normalize_put(&request, 0).is_err());
+
+        // TTLs beyond the janitor's are capped to it
+        request.ttl_secs = Some(u64::MAX);
+        let max_ttl = retention_policy::ABI_CACHE.ttl_secs.unwrap();
+        assert_eq!(
+            normalize_put(&request, 0).unwrap().expires_at,
+            Some(rfc3339_from_unix_secs(max_ttl))
+        );
+    }
+}
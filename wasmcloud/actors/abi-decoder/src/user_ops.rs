@@ -0,0 +1,349 @@
+//! ERC-4337 UserOperation decoding
+//!
+//! Account-abstraction transactions are bundles: a bundler calls
+//! `handleOps(ops, beneficiary)` on an EntryPoint, and the EntryPoint calls
+//! every op's `sender` (the smart account) with the op's `callData`. Decoding
+//! the bundle transaction alone only shows `handleOps`, so for calls to a
+//! known EntryPoint every UserOperation is decoded into a
+//! [`DecodedUserOperation`]: sender, nonce, the factory deploying the account
+//! (from `initCode`), the paymaster (from `paymasterAndData`) and the account
+//! call, decoded against the account's ABI and expanded through
+//! `execute` / `executeBatch` and any batches below (see [`crate::multicall`]).
+//!
+//! EntryPoint v0.6 takes the full `UserOperation` struct; v0.7 and v0.8 take
+//! `PackedUserOperation`, with the gas fields packed into `bytes32`s.
+
+use serde::{Deserialize, Serialize};
+
+use crate::escalation::{argument_bytes, parse_signature, AbiLookup};
+use crate::multicall::{self, SubCall};
+use crate::{AbiValue, Component, ContractTransaction};
+
+/// EntryPoint release, as deployed at the same address on every chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryPointVersion {
+    #[serde(rename = "v0.6")]
+    V06,
+    #[serde(rename = "v0.7")]
+    V07,
+    #[serde(rename = "v0.8")]
+    V08,
+}
+
+/// Canonical EntryPoint deployments
+const ENTRY_POINTS: &[(&str, EntryPointVersion)] = &[
+    (
+        "0x5ff137d4b0fdcd49dca30c7cf57e578a026d2789",
+        EntryPointVersion::V06,
+    ),
+    (
+        "0x0000000071727de22e5e9d8baf0edac6f37da032",
+        EntryPointVersion::V07,
+    ),
+    (
+        "0x4337084d9e255ff0702461cf8895ce9e3b5ff108",
+        EntryPointVersion::V08,
+    ),
+];
+
+const HANDLE_OPS_V06: &str =
+    "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)";
+const HANDLE_OPS_PACKED: &str =
+    "handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)";
+
+impl EntryPointVersion {
+    /// Version of the EntryPoint at `address`, if it is one
+    pub fn of(address: &str) -> Option<Self> {
+        ENTRY_POINTS
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(address))
+            .map(|(_, version)| *version)
+    }
+
+    /// `(selector, signature)` of this version's `handleOps`
+    fn handle_ops(self) -> (&'static str, &'static str) {
+        match self {
+            EntryPointVersion::V06 => ("0x1fad948c", HANDLE_OPS_V06),
+            EntryPointVersion::V07 | EntryPointVersion::V08 => ("0x765e827f", HANDLE_OPS_PACKED),
+        }
+    }
+
+    /// Position of `paymasterAndData` in the op struct
+    fn paymaster_field(self) -> usize {
+        match self {
+            EntryPointVersion::V06 => 9,
+            EntryPointVersion::V07 | EntryPointVersion::V08 => 7,
+        }
+    }
+}
+
+/// One UserOperation of a bundle, published for account-abstraction analytics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedUserOperation {
+    pub network: String,
+    pub subnet: String,
+    /// Bundle transaction
+    pub transaction_hash: String,
+    pub block_number: u64,
+    /// Account that submitted the bundle
+    pub bundler: String,
+    pub entry_point: String,
+    pub entry_point_version: EntryPointVersion,
+    /// Receiver of the bundle's gas compensation
+    pub beneficiary: String,
+    /// Position in the bundle
+    pub index: usize,
+    /// Smart account executing the operation
+    pub sender: String,
+    pub nonce: String,
+    /// Factory deploying the account in this operation, from `initCode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<String>,
+    /// Paymaster sponsoring the gas, from `paymasterAndData`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<String>,
+    /// The account's `callData`, decoded with the calls it wraps
+    pub call: SubCall,
+    pub processed_at: String,
+    pub processor_id: String,
+}
+
+/// UserOperations of `tx` if it is a `handleOps` call to a known EntryPoint;
+/// empty otherwise
+pub fn decode_bundle(
+    lookup: &impl AbiLookup,
+    tx: &ContractTransaction,
+    processed_at: &str,
+) -> Vec<DecodedUserOperation> {
+    let Some(version) = EntryPointVersion::of(&tx.to_address) else {
+        return Vec::new();
+    };
+    let (selector, signature) = version.handle_ops();
+    if !tx
+        .input_data
+        .get(..10)
+        .is_some_and(|called| called.eq_ignore_ascii_case(selector))
+    {
+        return Vec::new();
+    }
+
+    let (ops, beneficiary) = match handle_ops_arguments(signature, &tx.input_data) {
+        Ok(arguments) => arguments,
+        Err(e) => {
+            eprintln!(
+                "[ABI-DECODER] Failed to decode handleOps in {}: {}",
+                tx.transaction_hash, e
+            );
+            return Vec::new();
+        }
+    };
+
+    ops.iter()
+        .enumerate()
+        .filter_map(|(index, op)| {
+            let AbiValue::Tuple(fields) = op else {
+                return None;
+            };
+            let (Some(AbiValue::Address(sender)), Some(AbiValue::Uint256(nonce))) =
+                (fields.first(), fields.get(1))
+            else {
+                return None;
+            };
+            let sender = format!("0x{}", hex::encode(sender));
+            let call_data = match fields.get(3) {
+                Some(AbiValue::Bytes(call_data)) => format!("0x{}", hex::encode(call_data)),
+                _ => return None,
+            };
+
+            Some(DecodedUserOperation {
+                network: tx.network.clone(),
+                subnet: tx.subnet.clone(),
+                transaction_hash: tx.transaction_hash.clone(),
+                block_number: tx.block_number,
+                bundler: tx.from_address.to_lowercase(),
+                entry_point: tx.to_address.to_lowercase(),
+                entry_point_version: version,
+                beneficiary: beneficiary.clone(),
+                index,
+                nonce: Component::u256_to_decimal(nonce),
+                factory: leading_address(fields.get(2)),
+                paymaster: leading_address(fields.get(version.paymaster_field())),
+                call: multicall::decode_call(lookup, &tx.network, &tx.subnet, &sender, &call_data),
+                sender,
+                processed_at: processed_at.to_string(),
+                processor_id: "abi-decoder-actor".to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `(ops, beneficiary)` of a `handleOps` call
+fn handle_ops_arguments(
+    signature: &str,
+    input_data: &str,
+) -> Result<(Vec<AbiValue>, String), String> {
+    let (_, inputs) = parse_signature(signature)?;
+    let data = argument_bytes(input_data)?;
+    match Component::decode_abi_params(&inputs, &data)?.as_slice() {
+        [AbiValue::Array(ops), AbiValue::Address(beneficiary)] => {
+            Ok((ops.clone(), format!("0x{}", hex::encode(beneficiary))))
+        }
+        _ => Err("Unexpected handleOps arguments".to_string()),
+    }
+}
+
+/// Address in the first 20 bytes of `initCode` / `paymasterAndData`
+fn leading_address(field: Option<&AbiValue>) -> Option<String> {
+    match field {
+        Some(AbiValue::Bytes(bytes)) if bytes.len() >= 20 => {
+            Some(format!("0x{}", hex::encode(&bytes[..20])))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escalation::DecodeLevel;
+    use crate::proxy::ProxyImplementation;
+    use crate::AbiInfo;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const TOKEN_ABI: &str = r#"[{"type":"function","name":"transfer","inputs":[
+        {"name":"to","type":"address"},{"name":"amount","type":"uint256"}]}]"#;
+
+    struct TokenOnly;
+
+    impl AbiLookup for TokenOnly {
+        fn cached_abi(&self, address: &str, network: &str) -> Option<AbiInfo> {
+            (address == TOKEN).then(|| AbiInfo {
+                address: address.to_string(),
+                network: network.to_string(),
+                abi_json: TOKEN_ABI.to_string(),
+                source: "etherscan".to_string(),
+                verified: true,
+                cached_at: String::new(),
+                implementation_address: None,
+                uploaded_by: None,
+                expires_at: None,
+                selector_index: Default::default(),
+            })
+        }
+
+        fn implementation_of(
+            &self,
+            _address: &str,
+            _network: &str,
+            _subnet: &str,
+            _cached: Option<&AbiInfo>,
+        ) -> Option<ProxyImplementation> {
+            None
+        }
+
+        fn signature(&self, _selector: &str) -> Option<String> {
+            None
+        }
+    }
+
+    fn bundle(to_address: &str, input_data: String) -> ContractTransaction {
+        ContractTransaction {
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            vm_type: "evm".to_string(),
+            transaction_hash: "0xbundle".to_string(),
+            block_number: 19_000_000,
+            transaction_index: 0,
+            from_address: "0x00000000000000000000000000000000000000B0".to_string(),
+            to_address: to_address.to_string(),
+            value: "0x0".to_string(),
+            gas_limit: 0,
+            gas_price: "0x0".to_string(),
+            input_data,
+            nonce: 0,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            transaction_type: None,
+            processed_at: String::new(),
+            processor_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_packed_user_operation_bundle() {
+        // handleOps([op], 0x..be): op from account 0x..a1 with nonce 5, sponsored by
+        // paymaster 0x..fa, calling execute(TOKEN, 0, transfer(0x1111..., 1000))
+        let input_data = format!(
+            "0x765e827f{}",
+            [
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "00000000000000000000000000000000000000000000000000000000000000be",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "00000000000000000000000000000000000000000000000000000000000000a1",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "0000000000000000000000000000000000000000000000000000000000000120",
+                "0000000000000000000000000000000000000000000000000000000000000140",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000260",
+                "00000000000000000000000000000000000000000000000000000000000002c0",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000e4",
+                "b61d27f600000000000000000000000000000000000000000000000000000000",
+                "000000aa00000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000006000000000000000000000000000000000000000000000000000000000",
+                "00000044a9059cbb000000000000000000000000111111111111111111111111",
+                "1111111111111111000000000000000000000000000000000000000000000000",
+                "00000000000003e8000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000034",
+                "00000000000000000000000000000000000000fa000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000041",
+                "2222222222222222222222222222222222222222222222222222222222222222",
+                "2222222222222222222222222222222222222222222222222222222222222222",
+                "2200000000000000000000000000000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+        let tx = bundle("0x0000000071727De22E5E9d8BAf0edAc6f37da032", input_data);
+
+        let ops = decode_bundle(&TokenOnly, &tx, "2024-01-01T00:00:00Z");
+        assert_eq!(ops.len(), 1);
+        let op = &ops[0];
+        assert_eq!(op.entry_point_version, EntryPointVersion::V07);
+        assert_eq!(op.entry_point, "0x0000000071727de22e5e9d8baf0edac6f37da032");
+        assert_eq!(op.bundler, "0x00000000000000000000000000000000000000b0");
+        assert_eq!(op.beneficiary, "0x00000000000000000000000000000000000000be");
+        assert_eq!(op.sender, "0x00000000000000000000000000000000000000a1");
+        assert_eq!(op.nonce, "5");
+        assert_eq!(op.factory, None);
+        assert_eq!(
+            op.paymaster.as_deref(),
+            Some("0x00000000000000000000000000000000000000fa")
+        );
+
+        // The account has no cached ABI: execute comes from the wrapper list,
+        // the token call inside it from the token's ABI
+        assert_eq!(op.call.target, op.sender);
+        assert_eq!(op.call.decode_level, DecodeLevel::Partial);
+        assert_eq!(op.call.calls.len(), 1);
+        assert_eq!(op.call.calls[0].target, TOKEN);
+        assert_eq!(op.call.calls[0].decode_level, DecodeLevel::Full);
+        assert_eq!(
+            op.call.calls[0].decoded_function.as_ref().unwrap().name,
+            "transfer"
+        );
+
+        // Same calldata to an address that is not an EntryPoint: not a bundle
+        let other = bundle(TOKEN, tx.input_data.clone());
+        assert!(decode_bundle(&TokenOnly, &other, "2024-01-01T00:00:00Z").is_empty());
+        assert_eq!(
+            EntryPointVersion::of("0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789"),
+            Some(EntryPointVersion::V06)
+        );
+    }
+}
@@ -76,6 +76,16 @@ pub fn build_tx_columns(tx: Option<&EvaluationTxV1>, rows: usize) -> Vec<(Field,
     let topic3 = tx.and_then(|t| t.topic3.clone());
     let data = tx.and_then(|t| t.data.clone());
 
+    // Perpetuals position events; USD amounts as floats for threshold conditions
+    let perp = tx.and_then(|t| t.perp.as_ref());
+    let perp_usd = |value: Option<&String>| value.and_then(|v| v.parse::<f64>().ok());
+    let perp_event_type = perp.map(|p| p.event_type.clone());
+    let perp_account = perp.map(|p| p.account.clone());
+    let perp_market = perp.map(|p| p.market.clone());
+    let perp_size_delta_usd = perp.and_then(|p| perp_usd(p.size_delta_usd.as_ref()));
+    let perp_realized_pnl_usd = perp.and_then(|p| perp_usd(p.realized_pnl_usd.as_ref()));
+    let perp_funding_payment_usd = perp.and_then(|p| perp_usd(p.funding_payment_usd.as_ref()));
+
     let block_number = tx.map(|t| t.block_number);
     let block_timestamp = tx.map(|t| t.block_timestamp.to_rfc3339());
 
@@ -94,6 +104,16 @@ pub fn build_tx_columns(tx: Option<&EvaluationTxV1>, rows: usize) -> Vec<(Field,
         string_column("tx__topic2", topic2.as_deref(), rows),
         string_column("tx__topic3", topic3.as_deref(), rows),
         string_column("tx__data", data.as_deref(), rows),
+        string_column("tx__perp_event_type", perp_event_type.as_deref(), rows),
+        string_column("tx__perp_account", perp_account.as_deref(), rows),
+        string_column("tx__perp_market", perp_market.as_deref(), rows),
+        float_column("tx__perp_size_delta_usd", perp_size_delta_usd, rows),
+        float_column("tx__perp_realized_pnl_usd", perp_realized_pnl_usd, rows),
+        float_column(
+            "tx__perp_funding_payment_usd",
+            perp_funding_payment_usd,
+            rows,
+        ),
         int64_column("tx__block_number", block_number, rows),
         string_column("tx__block_timestamp", block_timestamp.as_deref(), rows),
     ]
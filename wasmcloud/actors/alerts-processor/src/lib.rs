@@ -5,7 +5,8 @@
 //! - executing runtime DatasourceCatalog SQL via DuckLake Read (NATS request/reply)
 //! - building a pre-joined Arrow IPC frame (1 row per target_key)
 //! - calling Polars Eval provider (NATS request/reply)
-//! - publishing `alert_triggered_batch_v1` match batches (`alerts.triggered.*`)
+//! - publishing `alert_triggered_batch_v1` match batches (`alerts.triggered.*`),
+//!   sealed for tenants that enabled payload encryption
 
 mod arrow_frame;
 mod catalog;
@@ -25,6 +26,9 @@ use wasmcloud::messaging::types as nats_types;
 #[cfg(target_arch = "wasm32")]
 use wasi::keyvalue::store;
 
+#[cfg(target_arch = "wasm32")]
+subject_acl::component!("alerts-processor");
+
 #[cfg(target_arch = "wasm32")]
 struct Component;
 
@@ -51,14 +55,26 @@ impl RuntimeIO for WasmRuntime {
         body: Vec<u8>,
         timeout_ms: u32,
     ) -> Result<Vec<u8>, ProcessorError> {
-        let resp = wasmcloud::messaging::consumer::request(subject, &body, timeout_ms)
-            .map_err(|e| ProcessorError::nats(format!("nats request failed: {:?}", e)))?;
+        let resp = wasmcloud::messaging::consumer::request(
+            &subject_registry::prefixed(subject),
+            &body,
+            timeout_ms,
+        )
+        .map_err(|e| ProcessorError::nats(format!("nats request failed: {:?}", e)))?;
         Ok(resp.body)
     }
 
     fn nats_publish(&self, subject: &str, body: Vec<u8>) -> Result<(), ProcessorError> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(ProcessorError::nats(violation.to_string()));
+        }
         let msg = nats_types::BrokerMessage {
-            subject: subject.to_string(),
+            subject: subject_registry::prefixed(subject),
             body,
             reply_to: None,
         };
@@ -67,6 +83,25 @@ impl RuntimeIO for WasmRuntime {
         Ok(())
     }
 
+    fn secret_get(&self, name: &str) -> Result<Option<Vec<u8>>, ProcessorError> {
+        use wasmcloud::secrets::store::{SecretValue, SecretsError};
+
+        let secret = match wasmcloud::secrets::store::get(name) {
+            Ok(secret) => secret,
+            Err(SecretsError::NotFound) => return Ok(None),
+            Err(e) => {
+                return Err(ProcessorError::encryption(format!(
+                    "secret {} unavailable: {:?}",
+                    name, e
+                )))
+            }
+        };
+        Ok(Some(match wasmcloud::secrets::reveal::reveal(&secret) {
+            SecretValue::String(s) => s.into_bytes(),
+            SecretValue::Bytes(b) => b,
+        }))
+    }
+
     fn now(&self) -> chrono::DateTime<chrono::Utc> {
         chrono::Utc::now()
     }
@@ -75,7 +110,14 @@ impl RuntimeIO for WasmRuntime {
 #[cfg(target_arch = "wasm32")]
 impl MessageHandler for Component {
     fn handle_message(msg: nats_types::BrokerMessage) -> std::result::Result<(), String> {
+        let subject = subject_registry::unprefixed(&msg.subject).ok_or_else(|| {
+            format!(
+                "Subject {} is outside environment prefix {}",
+                msg.subject,
+                subject_registry::active_prefix()
+            )
+        })?;
         let io = WasmRuntime;
-        runtime::handle_nats_message(&io, &msg.subject, &msg.body).map_err(|e| e.to_string())
+        runtime::handle_nats_message(&io, subject, &msg.body).map_err(|e| e.to_string())
     }
 }
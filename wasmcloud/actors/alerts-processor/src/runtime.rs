@@ -15,6 +15,9 @@ use alert_runtime_common::{
     OutputFieldV1, PolarsEvalRequestV1, PolarsEvalRequestV2, PolarsEvalResponseV1,
 };
 use ducklake_common::types::QueryRequest;
+use payload_encryption::{
+    policy_key, seal, TenantEncryptionPolicy, TenantKeyring, MASTER_KEY_SECRET,
+};
 
 use crate::arrow_frame::{
     align_datasource_columns, build_joined_record_batch, concat_batches, decode_ipc_stream,
@@ -58,6 +61,13 @@ impl ProcessorError {
             message,
         }
     }
+
+    pub fn encryption(message: String) -> Self {
+        Self {
+            code: "encryption_error",
+            message,
+        }
+    }
 }
 
 impl std::fmt::Display for ProcessorError {
@@ -77,6 +87,7 @@ pub trait RuntimeIO {
         timeout_ms: u32,
     ) -> Result<Vec<u8>, ProcessorError>;
     fn nats_publish(&self, subject: &str, body: Vec<u8>) -> Result<(), ProcessorError>;
+    fn secret_get(&self, name: &str) -> Result<Option<Vec<u8>>, ProcessorError>;
     fn now(&self) -> DateTime<Utc>;
 }
 
@@ -536,12 +547,52 @@ fn publish_triggered(
 
         let bytes = serde_json::to_vec(&batch)
             .map_err(|e| ProcessorError::json(format!("triggered: {e}")))?;
+        let bytes = seal_for_tenant(io, &job.evaluation_context.instance.user_id, bytes)?;
         io.nats_publish(&subject, bytes)?;
     }
 
     Ok(())
 }
 
+/// Seal a triggered batch when the instance owner has opted in to payload encryption.
+///
+/// The batch is sealed to the tenant's platform key, derived from the master
+/// key for the policy's `key_id`, so only the notification router can open it;
+/// the policy's stored `public_key` is not trusted here. Fails closed: an
+/// enabled policy without a usable master key is an error rather than a
+/// plaintext publish.
+fn seal_for_tenant(
+    io: &dyn RuntimeIO,
+    user_id: &serde_json::Value,
+    bytes: Vec<u8>,
+) -> Result<Vec<u8>, ProcessorError> {
+    let tenant_id = match user_id {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return Ok(bytes),
+    };
+    let Some(raw) = io.kv_get(&policy_key(&tenant_id))? else {
+        return Ok(bytes);
+    };
+    let policy: TenantEncryptionPolicy = serde_json::from_slice(&raw)
+        .map_err(|e| ProcessorError::json(format!("encryption policy: {e}")))?;
+    if !policy.enabled {
+        return Ok(bytes);
+    }
+
+    let Some(master_key) = io.secret_get(MASTER_KEY_SECRET)? else {
+        return Err(ProcessorError::encryption(format!(
+            "tenant {tenant_id} enabled encryption but secret {MASTER_KEY_SECRET} is not provisioned"
+        )));
+    };
+    let keyring = TenantKeyring::new(master_key)
+        .map_err(|e| ProcessorError::encryption(format!("{MASTER_KEY_SECRET}: {e}")))?;
+    let public_key = keyring.public_key(&tenant_id, &policy.key_id);
+    let envelope = seal(&bytes, &tenant_id, &policy.key_id, &public_key)
+        .map_err(|e| ProcessorError::encryption(format!("tenant {tenant_id}: {e}")))?;
+    serde_json::to_vec(&envelope).map_err(|e| ProcessorError::json(format!("envelope: {e}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -552,10 +603,12 @@ mod tests {
         JobPriorityV1, PartitionV1, TargetModeV1, TargetsV1, TriggerTypeV1,
     };
     use chrono::Utc;
+    use payload_encryption::EncryptedPayloadV1;
     use std::collections::BTreeMap;
 
     struct MockIO {
         kv: BTreeMap<String, Vec<u8>>,
+        secrets: BTreeMap<String, Vec<u8>>,
     }
 
     impl RuntimeIO for MockIO {
@@ -578,6 +631,10 @@ mod tests {
             Ok(())
         }
 
+        fn secret_get(&self, name: &str) -> Result<Option<Vec<u8>>, ProcessorError> {
+            Ok(self.secrets.get(name).cloned())
+        }
+
         fn now(&self) -> DateTime<Utc> {
             Utc::now()
         }
@@ -626,7 +683,10 @@ mod tests {
             serde_json::to_vec(&catalog_entry).unwrap(),
         );
 
-        let io = MockIO { kv };
+        let io = MockIO {
+            kv,
+            secrets: BTreeMap::new(),
+        };
 
         let fields = compute_output_fields(&tpl, &io).unwrap();
         assert_eq!(fields.len(), 1);
@@ -790,4 +850,34 @@ mod tests {
         assert!(v.get("template").is_some());
         assert!(v.get("executable").is_none());
     }
+
+    #[test]
+    fn triggered_batch_sealed_when_tenant_opted_in() {
+        let keyring = TenantKeyring::new(vec![5u8; 32]).unwrap();
+        let mut policy = keyring.policy("u1", "k1");
+        // A stored key other than the derived one must not receive the batch
+        policy.public_key = TenantKeyring::new(vec![6u8; 32])
+            .unwrap()
+            .policy("u1", "k1")
+            .public_key;
+        let kv = BTreeMap::from([(policy_key("u1"), serde_json::to_vec(&policy).unwrap())]);
+        let mut io = MockIO {
+            kv,
+            secrets: BTreeMap::new(),
+        };
+        let batch = br#"{"instance_id":"inst_1"}"#.to_vec();
+
+        let err = seal_for_tenant(&io, &serde_json::json!("u1"), batch.clone()).unwrap_err();
+        assert_eq!(err.code, "encryption_error");
+
+        io.secrets
+            .insert(MASTER_KEY_SECRET.to_string(), vec![5u8; 32]);
+        let sealed = seal_for_tenant(&io, &serde_json::json!("u1"), batch.clone()).unwrap();
+        let envelope = EncryptedPayloadV1::detect(&sealed).expect("sealed envelope");
+        assert_eq!(envelope.tenant_id, "u1");
+        assert_eq!(keyring.open(&envelope).unwrap(), batch);
+
+        let plain = seal_for_tenant(&io, &serde_json::json!("u2"), batch.clone()).unwrap();
+        assert_eq!(plain, batch);
+    }
 }
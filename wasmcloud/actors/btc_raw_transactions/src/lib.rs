@@ -114,14 +114,25 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages containing blockchain newheads
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+
         // Only process newheads messages for UTXO chains
-        if !msg.subject.starts_with("newheads.") || !msg.subject.ends_with(".utxo") {
-            return Ok(());
-        }
+        let subject = match subject_registry::unprefixed(&msg.subject) {
+            Some(subject) if subject.starts_with("newheads.") && subject.ends_with(".utxo") => {
+                subject
+            }
+            _ => return Ok(()),
+        };
 
         eprintln!(
             "BTC Raw Transactions received message on subject: {}",
-            msg.subject
+            subject
         );
 
         // Parse the block header from the message
@@ -461,7 +472,7 @@ impl Component {
             inputs,
             outputs,
             fee: None, // Will be calculated if needed
-            processed_at: chrono::Utc::now().to_rfc3339(),
+            processed_at: replay_clock::now().to_rfc3339(),
             processor_id: "btc-raw-transactions-actor".to_string(),
         })
     }
@@ -472,7 +483,7 @@ impl Component {
             .map_err(|e| format!("Failed to serialize transaction message: {}", e))?;
 
         let msg = types::BrokerMessage {
-            subject: "transactions.raw.utxo".to_string(),
+            subject: subject_registry::prefixed("transactions.raw.utxo"),
             body: tx_payload,
             reply_to: None,
         };
@@ -0,0 +1,244 @@
+//! Chain Onboarding Actor
+//!
+//! Onboards a new EVM chain from its chain id and RPC URLs:
+//! - `admin.chains.onboard` probes every endpoint (chain id, head, latency),
+//!   measures the block time and base fee, resolves the native symbol from the
+//!   built-in registry or name heuristics, generates the node config, gas
+//!   thresholds, chain health block time and knowledge-pack entries, and runs
+//!   a canary ingestion of a few recent blocks
+//! - `admin.chains.onboard.approve` writes the staged configuration to Redis,
+//!   enables the node and announces it on `admin.chains.activated`, which
+//!   newheads-evm follows to start streaming the chain
+//!
+//! Both replies are a `ChainOnboardResultV1` carrying the staged
+//! `ChainOnboardingPlanV1`.
+
+mod onboard;
+
+pub use onboard::{
+    activated_schema_version, approve, gas_alert_config, handle_approve_message,
+    handle_onboard_message, native_symbol, plan_key, plan_schema_version, result_schema_version,
+    run_canary, run_onboarding, BlockchainNodeV1, CanaryBlockV1, CanaryReportV1, ChainActivatedV1,
+    ChainApproveRequestV1, ChainConfigSetV1, ChainOnboardRequestV1, ChainOnboardResultV1,
+    ChainOnboardingPlanV1, ChainProbeV1, EndpointProbeV1, KnowledgePackEntryV1, OnboardIO,
+    OnboardingStatusV1, SymbolSourceV1, ACTIVATED_SUBJECT, APPROVE_SUBJECT, ONBOARD_SUBJECT,
+};
+
+#[cfg(target_arch = "wasm32")]
+wit_bindgen::generate!({ generate_all });
+
+#[cfg(target_arch = "wasm32")]
+use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+
+#[cfg(target_arch = "wasm32")]
+use wasmcloud::messaging::types as nats_types;
+
+#[cfg(target_arch = "wasm32")]
+use wasi::keyvalue::store;
+
+#[cfg(target_arch = "wasm32")]
+subject_acl::component!("chain-onboarding");
+
+#[cfg(target_arch = "wasm32")]
+struct Component;
+
+#[cfg(target_arch = "wasm32")]
+export!(Component);
+
+#[cfg(target_arch = "wasm32")]
+struct WasmRuntime;
+
+#[cfg(target_arch = "wasm32")]
+impl WasmRuntime {
+    fn bucket(&self) -> Result<store::Bucket, String> {
+        store::open("default").map_err(|e| format!("failed to open keyvalue bucket: {:?}", e))
+    }
+
+    fn parse_url(url: &str) -> Result<(wasi::http::types::Scheme, String, String), String> {
+        let (scheme_str, rest) = url
+            .split_once("://")
+            .ok_or_else(|| format!("invalid URL format: {}", url))?;
+
+        let scheme = match scheme_str {
+            "http" => wasi::http::types::Scheme::Http,
+            "https" => wasi::http::types::Scheme::Https,
+            _ => return Err(format!("unsupported scheme: {}", scheme_str)),
+        };
+
+        let (authority, path) = match rest.split_once('/') {
+            Some((authority, path)) => (authority.to_string(), format!("/{}", path)),
+            None => (rest.to_string(), "/".to_string()),
+        };
+
+        Ok((scheme, authority, path))
+    }
+
+    /// POST a JSON body and return the body of a 2xx response
+    fn http_post(url: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+        let (scheme, authority, path) = Self::parse_url(url)?;
+
+        let headers = wasi::http::types::Fields::new();
+        headers
+            .set(
+                &"content-type".to_string(),
+                &vec!["application/json".as_bytes().to_vec()],
+            )
+            .map_err(|e| format!("failed to set content-type header: {:?}", e))?;
+        headers
+            .set(
+                &"content-length".to_string(),
+                &vec![body.len().to_string().as_bytes().to_vec()],
+            )
+            .map_err(|e| format!("failed to set content-length header: {:?}", e))?;
+        let request = wasi::http::types::OutgoingRequest::new(headers);
+        request
+            .set_method(&wasi::http::types::Method::Post)
+            .map_err(|e| format!("failed to set method: {:?}", e))?;
+        request
+            .set_scheme(Some(&scheme))
+            .map_err(|e| format!("failed to set scheme: {:?}", e))?;
+        request
+            .set_authority(Some(&authority))
+            .map_err(|e| format!("failed to set authority: {:?}", e))?;
+        request
+            .set_path_with_query(Some(&path))
+            .map_err(|e| format!("failed to set path: {:?}", e))?;
+
+        let outgoing_body = request
+            .body()
+            .map_err(|_| "failed to get request body".to_string())?;
+        {
+            let output_stream = outgoing_body
+                .write()
+                .map_err(|_| "failed to get body output stream".to_string())?;
+            output_stream
+                .blocking_write_and_flush(body)
+                .map_err(|e| format!("failed to write request body: {:?}", e))?;
+        }
+        wasi::http::types::OutgoingBody::finish(outgoing_body, None)
+            .map_err(|_| "failed to finish request body".to_string())?;
+
+        let future_response = wasi::http::outgoing_handler::handle(request, None)
+            .map_err(|e| format!("RPC request failed: {:?}", e))?;
+        let pollable = future_response.subscribe();
+        wasi::io::poll::poll(&[&pollable]);
+
+        let response = future_response
+            .get()
+            .ok_or_else(|| "RPC response not ready".to_string())?
+            .map_err(|e| format!("RPC request failed (outer): {:?}", e))?
+            .map_err(|e| format!("RPC request failed (inner): {:?}", e))?;
+
+        let status = response.status();
+        if !(200..300).contains(&status) {
+            return Err(format!("RPC endpoint returned status {}", status));
+        }
+
+        let response_body = response
+            .consume()
+            .map_err(|_| "failed to consume RPC response".to_string())?;
+        let stream = response_body
+            .stream()
+            .map_err(|_| "failed to get RPC response stream".to_string())?;
+        let mut bytes = Vec::new();
+        loop {
+            match stream.blocking_read(64 * 1024) {
+                Ok(chunk) if chunk.is_empty() => break,
+                Ok(chunk) => bytes.extend_from_slice(&chunk),
+                Err(wasi::io::streams::StreamError::Closed) => break,
+                Err(e) => return Err(format!("failed to read RPC response: {:?}", e)),
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl OnboardIO for WasmRuntime {
+    fn kv_get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        self.bucket()?
+            .get(key)
+            .map_err(|e| format!("keyvalue get failed: {:?}", e))
+    }
+
+    fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        self.bucket()?
+            .set(key, value)
+            .map_err(|e| format!("keyvalue set failed: {:?}", e))
+    }
+
+    fn rpc(
+        &self,
+        rpc_url: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        }))
+        .map_err(|e| format!("failed to serialize RPC request: {}", e))?;
+        let response: serde_json::Value = serde_json::from_slice(&Self::http_post(rpc_url, &body)?)
+            .map_err(|e| format!("failed to parse RPC response: {}", e))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} error: {}", method, error));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| format!("{} returned no result", method))
+    }
+
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
+        wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+            subject: subject_registry::prefixed(subject),
+            body,
+            reply_to: None,
+        })
+        .map_err(|e| format!("nats publish failed: {:?}", e))
+    }
+
+    fn clock_ms(&self) -> u64 {
+        wasi::clocks::monotonic_clock::now() / 1_000_000
+    }
+
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl MessageHandler for Component {
+    fn handle_message(msg: nats_types::BrokerMessage) -> Result<(), String> {
+        let io = WasmRuntime;
+        let result = match subject_registry::unprefixed(&msg.subject) {
+            Some(ONBOARD_SUBJECT) => handle_onboard_message(&io, &msg.body),
+            Some(APPROVE_SUBJECT) => handle_approve_message(&io, &msg.body),
+            _ => return Ok(()),
+        };
+        if let Some(error) = &result.error {
+            eprintln!("[CHAIN-ONBOARDING] ❌ {}: {}", msg.subject, error);
+        }
+        if let Some(reply_to) = msg.reply_to {
+            let body = serde_json::to_vec(&result)
+                .map_err(|e| format!("failed to serialize onboarding result: {}", e))?;
+            wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: reply_to,
+                body,
+                reply_to: None,
+            })
+            .map_err(|e| format!("failed to send onboarding reply: {:?}", e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1209 @@
+//! Onboarding plan and runner for `admin.chains.onboard`
+//!
+//! A new EVM chain needs a Django node config (`blockchain:nodes:*`), gas
+//! alert thresholds, an expected block time for chain health and knowledge-pack
+//! entries. An onboarding request probes every RPC URL (`eth_chainId`,
+//! `eth_blockNumber`), measures the block time and base fee, resolves the
+//! native symbol, generates that configuration set and runs a canary
+//! ingestion of a few recent blocks through the same RPC calls the ingestion
+//! actors make. The result is staged as a [`ChainOnboardingPlanV1`]; nothing
+//! is written outside the plan until an `admin.chains.onboard.approve`
+//! request activates it.
+
+use alert_runtime_common::{ChainHealthConfigV1, GasAlertConfigV1};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const ONBOARD_SUBJECT: &str = "admin.chains.onboard";
+pub const APPROVE_SUBJECT: &str = "admin.chains.onboard.approve";
+/// Published after activation; newheads-evm starts streaming the chain
+pub const ACTIVATED_SUBJECT: &str = "admin.chains.activated";
+
+pub const DEFAULT_CANARY_BLOCKS: u32 = 3;
+pub const MAX_CANARY_BLOCKS: u32 = 10;
+/// Canary blocks stay this far behind the head, clear of short reorgs
+pub const CANARY_CONFIRMATIONS: u64 = 2;
+/// Blocks between the two timestamps the block time is measured over
+pub const BLOCK_TIME_SAMPLE: u64 = 100;
+
+/// Gas alerts fire at this multiple of the base fee seen while probing
+const BASE_FEE_THRESHOLD_MULTIPLIER: f64 = 10.0;
+const MIN_BASE_FEE_THRESHOLD_GWEI: f64 = 0.001;
+const WEI_PER_GWEI: f64 = 1_000_000_000.0;
+
+/// Known chains: (chain id, network, subnet, name, native symbol)
+///
+/// Network names follow the processors' partitions (`bsc`, not `binance`).
+#[rustfmt::skip]
+const KNOWN_CHAINS: &[(u64, &str, &str, &str, &str)] = &[
+    (1, "ethereum", "mainnet", "Ethereum Mainnet", "ETH"),
+    (11155111, "ethereum", "sepolia", "Ethereum Sepolia", "ETH"),
+    (17000, "ethereum", "holesky", "Ethereum Holesky", "ETH"),
+    (10, "optimism", "mainnet", "OP Mainnet", "ETH"),
+    (11155420, "optimism", "sepolia", "OP Sepolia", "ETH"),
+    (8453, "base", "mainnet", "Base", "ETH"),
+    (84532, "base", "sepolia", "Base Sepolia", "ETH"),
+    (42161, "arbitrum", "mainnet", "Arbitrum One", "ETH"),
+    (421614, "arbitrum", "sepolia", "Arbitrum Sepolia", "ETH"),
+    (137, "polygon", "mainnet", "Polygon PoS", "MATIC"),
+    (80002, "polygon", "amoy", "Polygon Amoy", "MATIC"),
+    (56, "bsc", "mainnet", "BNB Smart Chain", "BNB"),
+    (97, "bsc", "testnet", "BNB Smart Chain Testnet", "BNB"),
+    (43114, "avalanche", "mainnet", "Avalanche C-Chain", "AVAX"),
+    (43113, "avalanche", "fuji", "Avalanche Fuji", "AVAX"),
+    (250, "fantom", "mainnet", "Fantom Opera", "FTM"),
+    (100, "gnosis", "mainnet", "Gnosis", "XDAI"),
+    (42220, "celo", "mainnet", "Celo", "CELO"),
+    (5000, "mantle", "mainnet", "Mantle", "MNT"),
+    (324, "zksync", "mainnet", "zkSync Era", "ETH"),
+    (59144, "linea", "mainnet", "Linea", "ETH"),
+    (534352, "scroll", "mainnet", "Scroll", "ETH"),
+    (81457, "blast", "mainnet", "Blast", "ETH"),
+];
+
+/// Name fragments of rollups that pay gas in bridged ETH
+const ETH_ROLLUP_HINTS: &[&str] = &[
+    "rollup", "l2", "zk", "op", "arbitrum", "optimism", "base", "linea", "scroll", "blast",
+];
+
+/// Reference copy of the `*` entries in the contract processor's dApp table:
+/// contracts deployed at the same address on every EVM network
+#[rustfmt::skip]
+const CROSS_CHAIN_DAPPS: &[(&str, &str)] = &[
+    ("0xba12222222228d8ba445958a75a0704d566bf2c8", "Balancer"),
+    ("0x000000000022d473030f116ddee9f6b43ac78ba3", "Uniswap"),
+    ("0x00000000000000adc04c56bf30ac9d3c0aaf14dc", "OpenSea"),
+];
+
+pub trait OnboardIO {
+    fn kv_get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String>;
+    /// JSON-RPC call against `rpc_url`; returns the `result` member
+    fn rpc(&self, rpc_url: &str, method: &str, params: Value) -> Result<Value, String>;
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String>;
+    /// Monotonic milliseconds, for endpoint latency
+    fn clock_ms(&self) -> u64;
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// `admin.chains.onboard` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainOnboardRequestV1 {
+    /// Numeric EVM chain id the endpoints must report
+    pub chain_id: u64,
+    pub rpc_urls: Vec<String>,
+    /// Derived from the fastest RPC URL (`https` -> `wss`) when absent
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// Required for chains missing from the built-in registry
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub subnet: Option<String>,
+    #[serde(default)]
+    pub chain_name: Option<String>,
+    #[serde(default)]
+    pub native_symbol: Option<String>,
+    /// Recent blocks run through the canary ingestion
+    #[serde(default)]
+    pub canary_blocks: Option<u32>,
+}
+
+impl ChainOnboardRequestV1 {
+    fn canary_blocks(&self) -> u32 {
+        self.canary_blocks
+            .unwrap_or(DEFAULT_CANARY_BLOCKS)
+            .clamp(1, MAX_CANARY_BLOCKS)
+    }
+}
+
+/// `admin.chains.onboard.approve` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainApproveRequestV1 {
+    pub chain_id: u64,
+    pub approved_by: String,
+}
+
+/// Where the native symbol came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolSourceV1 {
+    /// `native_symbol` in the request
+    Request,
+    /// Built-in registry entry for the chain id
+    Registry,
+    /// Registry entry of another chain on the same network
+    Network,
+    /// Rollup name hint
+    Heuristic,
+    /// Nothing matched; assumed ETH
+    Default,
+}
+
+/// One RPC URL as seen by the probe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointProbeV1 {
+    pub url: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<u64>,
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What the probe learned about the chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainProbeV1 {
+    pub endpoints: Vec<EndpointProbeV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_time_secs: Option<f64>,
+    /// Base fee of the head block; absent before EIP-1559
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_gwei: Option<f64>,
+    pub native_symbol: String,
+    pub symbol_source: SymbolSourceV1,
+}
+
+/// `blockchain:nodes:{network}-{subnet}` value, in Django's BlockchainNode shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockchainNodeV1 {
+    /// `{network}-{subnet}`
+    pub chain_id: String,
+    pub chain_name: String,
+    pub network: String,
+    pub subnet: String,
+    pub vm_type: String,
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub enabled: bool,
+    pub is_primary: bool,
+    pub priority: i32,
+    pub latency_ms: Option<i32>,
+    pub success_rate: Option<f64>,
+    pub last_health_check: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Knowledge-pack entry written under `dapp:contract:{network}:{address}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnowledgePackEntryV1 {
+    pub address: String,
+    pub dapp_name: String,
+}
+
+/// Everything written to Redis when the chain is activated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainConfigSetV1 {
+    pub node: BlockchainNodeV1,
+    pub native_symbol: String,
+    /// `gas:alerts:config:{network}:{subnet}`
+    pub gas_alerts: GasAlertConfigV1,
+    /// Merged into `chain:health:config` for the network unless already set
+    pub block_time_secs: f64,
+    pub knowledge_pack: Vec<KnowledgePackEntryV1>,
+}
+
+impl ChainConfigSetV1 {
+    fn network(&self) -> &str {
+        &self.node.network
+    }
+
+    fn subnet(&self) -> &str {
+        &self.node.subnet
+    }
+}
+
+/// One block run through the canary ingestion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryBlockV1 {
+    pub number: u64,
+    pub hash: String,
+    pub transactions: usize,
+    pub logs: usize,
+    /// `eth_getBlockReceipts` results; absent when the node lacks the method
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipts: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanaryReportV1 {
+    pub passed: bool,
+    pub blocks: Vec<CanaryBlockV1>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStatusV1 {
+    /// Probe and canary passed; waiting for `admin.chains.onboard.approve`
+    AwaitingApproval,
+    /// Probe or canary failed; kept for inspection
+    Failed,
+    Active,
+}
+
+/// Staged onboarding, stored under `chain_onboarding:plan:{chain_id}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainOnboardingPlanV1 {
+    pub schema_version: String,
+    pub onboarding_id: String,
+    pub chain_id: u64,
+    pub status: OnboardingStatusV1,
+    pub probe: ChainProbeV1,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<ChainConfigSetV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canary: Option<CanaryReportV1>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activated_at: Option<DateTime<Utc>>,
+}
+
+/// Reply to `admin.chains.onboard` and `admin.chains.onboard.approve`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainOnboardResultV1 {
+    pub schema_version: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<ChainOnboardingPlanV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ChainOnboardResultV1 {
+    fn failed(error: String) -> Self {
+        Self {
+            schema_version: result_schema_version(),
+            success: false,
+            plan: None,
+            error: Some(error),
+        }
+    }
+
+    fn for_plan(plan: ChainOnboardingPlanV1) -> Self {
+        Self {
+            schema_version: result_schema_version(),
+            success: plan.status != OnboardingStatusV1::Failed,
+            error: plan.error.clone(),
+            plan: Some(plan),
+        }
+    }
+}
+
+/// Published on [`ACTIVATED_SUBJECT`] once the configuration is written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainActivatedV1 {
+    pub schema_version: String,
+    pub chain_id: u64,
+    /// `blockchain:nodes:*` suffix, `{network}-{subnet}`
+    pub node_id: String,
+    pub network: String,
+    pub subnet: String,
+    pub approved_by: String,
+    pub activated_at: DateTime<Utc>,
+}
+
+/// Redis key of the staged plan for `chain_id`
+pub fn plan_key(chain_id: u64) -> String {
+    retention_policy::CHAIN_ONBOARDING_PLAN.key(&chain_id.to_string())
+}
+
+pub fn handle_onboard_message(io: &dyn OnboardIO, body: &[u8]) -> ChainOnboardResultV1 {
+    match serde_json::from_slice::<ChainOnboardRequestV1>(body) {
+        Ok(request) => run_onboarding(io, &request),
+        Err(e) => ChainOnboardResultV1::failed(format!("Invalid onboarding request: {}", e)),
+    }
+}
+
+pub fn handle_approve_message(io: &dyn OnboardIO, body: &[u8]) -> ChainOnboardResultV1 {
+    match serde_json::from_slice::<ChainApproveRequestV1>(body) {
+        Ok(request) => approve(io, &request),
+        Err(e) => ChainOnboardResultV1::failed(format!("Invalid approval request: {}", e)),
+    }
+}
+
+/// Probe the chain, generate its configuration, run the canary and stage the plan
+pub fn run_onboarding(io: &dyn OnboardIO, request: &ChainOnboardRequestV1) -> ChainOnboardResultV1 {
+    if request.rpc_urls.is_empty() {
+        return ChainOnboardResultV1::failed("rpc_urls is empty".to_string());
+    }
+    let Some((network, subnet, chain_name)) = identify(request) else {
+        return ChainOnboardResultV1::failed(format!(
+            "Chain {} is not in the registry; set network and subnet",
+            request.chain_id
+        ));
+    };
+    match load_plan(io, request.chain_id) {
+        Ok(Some(plan)) if plan.status == OnboardingStatusV1::Active => {
+            return ChainOnboardResultV1::failed(format!(
+                "Chain {} is already active",
+                request.chain_id
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => return ChainOnboardResultV1::failed(e),
+    }
+    let node_key = retention_policy::BLOCKCHAIN_NODE.key(&node_id(&network, &subnet));
+    match io.kv_get(&node_key) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return ChainOnboardResultV1::failed(format!("{} is already configured", node_key));
+        }
+        Err(e) => return ChainOnboardResultV1::failed(e),
+    }
+
+    let now = io.now();
+    let (native_symbol, symbol_source) = native_symbol(request, &network, &chain_name);
+    let mut plan = ChainOnboardingPlanV1 {
+        schema_version: plan_schema_version(),
+        onboarding_id: format!("{}-{}", request.chain_id, now.timestamp_millis()),
+        chain_id: request.chain_id,
+        status: OnboardingStatusV1::Failed,
+        probe: ChainProbeV1 {
+            endpoints: request
+                .rpc_urls
+                .iter()
+                .map(|url| probe_endpoint(io, url, request.chain_id))
+                .collect(),
+            head: None,
+            block_time_secs: None,
+            base_fee_gwei: None,
+            native_symbol,
+            symbol_source,
+        },
+        config: None,
+        canary: None,
+        warnings: Vec::new(),
+        error: None,
+        created_at: now,
+        approved_by: None,
+        activated_at: None,
+    };
+    if symbol_source == SymbolSourceV1::Default {
+        plan.warnings.push(format!(
+            "Native symbol defaulted to {}; set native_symbol to override",
+            plan.probe.native_symbol
+        ));
+    }
+
+    if let Err(e) = build_plan(io, request, &network, &subnet, &chain_name, &mut plan) {
+        plan.error = Some(e);
+    }
+    if plan.error.is_none() {
+        plan.status = OnboardingStatusV1::AwaitingApproval;
+    }
+    if let Err(e) = store_plan(io, &plan) {
+        return ChainOnboardResultV1::failed(e);
+    }
+    ChainOnboardResultV1::for_plan(plan)
+}
+
+fn build_plan(
+    io: &dyn OnboardIO,
+    request: &ChainOnboardRequestV1,
+    network: &str,
+    subnet: &str,
+    chain_name: &str,
+    plan: &mut ChainOnboardingPlanV1,
+) -> Result<(), String> {
+    let primary = plan
+        .probe
+        .endpoints
+        .iter()
+        .filter(|endpoint| endpoint.ok)
+        .min_by_key(|endpoint| endpoint.latency_ms)
+        .cloned()
+        .ok_or_else(|| "No RPC URL answered for the requested chain id".to_string())?;
+    for endpoint in plan.probe.endpoints.iter().filter(|endpoint| !endpoint.ok) {
+        plan.warnings.push(format!(
+            "{} skipped: {}",
+            endpoint.url,
+            endpoint.error.as_deref().unwrap_or("unreachable")
+        ));
+    }
+    let head = primary.head.unwrap_or_default();
+    plan.probe.head = Some(head);
+
+    let (block_time_secs, base_fee_gwei) = measure_block_time(io, &primary.url, head)?;
+    plan.probe.block_time_secs = Some(block_time_secs);
+    plan.probe.base_fee_gwei = base_fee_gwei;
+
+    let ws_url = match &request.ws_url {
+        Some(ws_url) => ws_url.clone(),
+        None => {
+            let ws_url = derive_ws_url(&primary.url)
+                .ok_or_else(|| format!("Cannot derive a ws_url from {}", primary.url))?;
+            plan.warnings.push(format!("ws_url derived as {}", ws_url));
+            ws_url
+        }
+    };
+    plan.warnings
+        .push("WebSocket endpoint is not probed; check newheads after activation".to_string());
+
+    let knowledge_pack = knowledge_pack(io, &primary.url, &mut plan.warnings);
+    let timestamp = plan.created_at.to_rfc3339();
+    plan.config = Some(ChainConfigSetV1 {
+        node: BlockchainNodeV1 {
+            chain_id: node_id(network, subnet),
+            chain_name: chain_name.to_string(),
+            network: network.to_string(),
+            subnet: subnet.to_string(),
+            vm_type: "EVM".to_string(),
+            rpc_url: primary.url.clone(),
+            ws_url,
+            enabled: false,
+            is_primary: true,
+            priority: 0,
+            latency_ms: i32::try_from(primary.latency_ms).ok(),
+            success_rate: None,
+            last_health_check: Some(timestamp.clone()),
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+        },
+        native_symbol: plan.probe.native_symbol.clone(),
+        gas_alerts: gas_alert_config(base_fee_gwei),
+        block_time_secs,
+        knowledge_pack,
+    });
+
+    let canary = run_canary(
+        io,
+        &primary.url,
+        request.chain_id,
+        head,
+        request.canary_blocks(),
+    );
+    let failed = !canary.passed;
+    plan.canary = Some(canary);
+    if failed {
+        return Err("Canary ingestion failed".to_string());
+    }
+    Ok(())
+}
+
+/// Write the staged configuration, mark the plan active and announce it
+pub fn approve(io: &dyn OnboardIO, request: &ChainApproveRequestV1) -> ChainOnboardResultV1 {
+    let mut plan = match load_plan(io, request.chain_id) {
+        Ok(Some(plan)) => plan,
+        Ok(None) => {
+            return ChainOnboardResultV1::failed(format!(
+                "No onboarding plan for chain {}",
+                request.chain_id
+            ));
+        }
+        Err(e) => return ChainOnboardResultV1::failed(e),
+    };
+    if plan.status != OnboardingStatusV1::AwaitingApproval {
+        return ChainOnboardResultV1::failed(format!(
+            "Plan {} is {:?}, not awaiting approval",
+            plan.onboarding_id, plan.status
+        ));
+    }
+    if request.approved_by.trim().is_empty() {
+        return ChainOnboardResultV1::failed("approved_by is required".to_string());
+    }
+    let Some(config) = plan.config.clone() else {
+        return ChainOnboardResultV1::failed(format!(
+            "Plan {} has no configuration",
+            plan.onboarding_id
+        ));
+    };
+
+    let now = io.now();
+    if let Err(e) = write_config(io, &config, now) {
+        return ChainOnboardResultV1::failed(e);
+    }
+
+    plan.status = OnboardingStatusV1::Active;
+    plan.approved_by = Some(request.approved_by.clone());
+    plan.activated_at = Some(now);
+    if let Err(e) = store_plan(io, &plan) {
+        return ChainOnboardResultV1::failed(e);
+    }
+
+    let event = ChainActivatedV1 {
+        schema_version: activated_schema_version(),
+        chain_id: plan.chain_id,
+        node_id: config.node.chain_id.clone(),
+        network: config.network().to_string(),
+        subnet: config.subnet().to_string(),
+        approved_by: request.approved_by.clone(),
+        activated_at: now,
+    };
+    let published = serde_json::to_vec(&event)
+        .map_err(|e| format!("failed to serialize activation: {}", e))
+        .and_then(|body| io.publish(ACTIVATED_SUBJECT, body));
+    if let Err(e) = published {
+        // The config is live; newheads-evm picks the chain up on its next reload
+        plan.warnings
+            .push(format!("Activation event not sent: {}", e));
+    }
+    ChainOnboardResultV1::for_plan(plan)
+}
+
+fn write_config(
+    io: &dyn OnboardIO,
+    config: &ChainConfigSetV1,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let chain = format!("{}:{}", config.network(), config.subnet());
+    let gas_key = retention_policy::GAS_ALERT_CONFIG.key(&chain);
+    if io.kv_get(&gas_key)?.is_none() {
+        io.kv_set(&gas_key, &to_json(&config.gas_alerts)?)?;
+    }
+
+    let health_key = retention_policy::CHAIN_HEALTH_CONFIG.key("");
+    let mut health = io
+        .kv_get(&health_key)?
+        .and_then(|bytes| serde_json::from_slice::<ChainHealthConfigV1>(&bytes).ok())
+        .unwrap_or_default();
+    if !health.block_time_secs.contains_key(config.network()) {
+        health
+            .block_time_secs
+            .insert(config.network().to_string(), config.block_time_secs);
+        io.kv_set(&health_key, &to_json(&health)?)?;
+    }
+
+    for entry in &config.knowledge_pack {
+        let key =
+            retention_policy::DAPP_CONTRACT.key(&format!("{}:{}", config.network(), entry.address));
+        if io.kv_get(&key)?.is_none() {
+            io.kv_set(&key, entry.dapp_name.as_bytes())?;
+        }
+    }
+
+    // The node goes last: once it is enabled, ingestion can start
+    let mut node = config.node.clone();
+    node.enabled = true;
+    node.updated_at = now.to_rfc3339();
+    let node_key = retention_policy::BLOCKCHAIN_NODE.key(&node.chain_id);
+    io.kv_set(&node_key, &to_json(&node)?)
+}
+
+/// Network, subnet and display name of the requested chain
+fn identify(request: &ChainOnboardRequestV1) -> Option<(String, String, String)> {
+    let known = KNOWN_CHAINS
+        .iter()
+        .find(|(chain_id, ..)| *chain_id == request.chain_id);
+    let network = request
+        .network
+        .clone()
+        .or_else(|| known.map(|(_, network, ..)| network.to_string()))?
+        .trim()
+        .to_lowercase();
+    let subnet = request
+        .subnet
+        .clone()
+        .or_else(|| known.map(|(_, _, subnet, ..)| subnet.to_string()))?
+        .trim()
+        .to_lowercase();
+    if network.is_empty() || subnet.is_empty() {
+        return None;
+    }
+    let chain_name = request
+        .chain_name
+        .clone()
+        .or_else(|| known.map(|(_, _, _, name, _)| name.to_string()))
+        .unwrap_or_else(|| format!("{} {}", network, subnet));
+    Some((network, subnet, chain_name))
+}
+
+/// Native currency symbol: the request, the registry by chain id, then by
+/// network, then rollup name hints
+pub fn native_symbol(
+    request: &ChainOnboardRequestV1,
+    network: &str,
+    chain_name: &str,
+) -> (String, SymbolSourceV1) {
+    if let Some(symbol) = request
+        .native_symbol
+        .as_deref()
+        .map(str::trim)
+        .filter(|symbol| !symbol.is_empty())
+    {
+        return (symbol.to_uppercase(), SymbolSourceV1::Request);
+    }
+    if let Some((.., symbol)) = KNOWN_CHAINS
+        .iter()
+        .find(|(chain_id, ..)| *chain_id == request.chain_id)
+    {
+        return (symbol.to_string(), SymbolSourceV1::Registry);
+    }
+    if let Some((.., symbol)) = KNOWN_CHAINS.iter().find(|(_, known, ..)| *known == network) {
+        return (symbol.to_string(), SymbolSourceV1::Network);
+    }
+    let name = format!("{} {}", network, chain_name).to_lowercase();
+    let is_rollup = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| {
+            ETH_ROLLUP_HINTS
+                .iter()
+                .any(|hint| word == *hint || (*hint != "op" && word.starts_with(hint)))
+        });
+    if is_rollup {
+        return ("ETH".to_string(), SymbolSourceV1::Heuristic);
+    }
+    ("ETH".to_string(), SymbolSourceV1::Default)
+}
+
+fn probe_endpoint(io: &dyn OnboardIO, url: &str, expected_chain_id: u64) -> EndpointProbeV1 {
+    let started = io.clock_ms();
+    let mut probe = EndpointProbeV1 {
+        url: url.trim().to_string(),
+        ok: false,
+        chain_id: None,
+        head: None,
+        latency_ms: 0,
+        error: None,
+    };
+    let result = (|| -> Result<(), String> {
+        let chain_id = hex_u64(&io.rpc(&probe.url, "eth_chainId", json!([]))?)
+            .ok_or_else(|| "eth_chainId returned no number".to_string())?;
+        probe.chain_id = Some(chain_id);
+        let head = hex_u64(&io.rpc(&probe.url, "eth_blockNumber", json!([]))?)
+            .ok_or_else(|| "eth_blockNumber returned no number".to_string())?;
+        probe.head = Some(head);
+        if chain_id != expected_chain_id {
+            return Err(format!(
+                "reports chain id {}, expected {}",
+                chain_id, expected_chain_id
+            ));
+        }
+        Ok(())
+    })();
+    probe.latency_ms = io.clock_ms().saturating_sub(started);
+    match result {
+        Ok(()) => probe.ok = true,
+        Err(e) => probe.error = Some(e),
+    }
+    probe
+}
+
+/// Average seconds per block over the last [`BLOCK_TIME_SAMPLE`] blocks, and
+/// the head block's base fee in gwei
+fn measure_block_time(
+    io: &dyn OnboardIO,
+    rpc_url: &str,
+    head: u64,
+) -> Result<(f64, Option<f64>), String> {
+    let span = BLOCK_TIME_SAMPLE.min(head);
+    if span == 0 {
+        return Err("Chain has no blocks past genesis".to_string());
+    }
+    let latest = get_block(io, rpc_url, head, false)?;
+    let earlier = get_block(io, rpc_url, head - span, false)?;
+    let (Some(latest_ts), Some(earlier_ts)) = (
+        latest.get("timestamp").and_then(hex_u64),
+        earlier.get("timestamp").and_then(hex_u64),
+    ) else {
+        return Err("Blocks have no timestamp".to_string());
+    };
+    let block_time = latest_ts.saturating_sub(earlier_ts) as f64 / span as f64;
+    let base_fee_gwei = latest
+        .get("baseFeePerGas")
+        .and_then(hex_u128)
+        .map(|wei| wei as f64 / WEI_PER_GWEI);
+    Ok(((block_time * 100.0).round() / 100.0, base_fee_gwei))
+}
+
+/// Gas thresholds scaled to the fees seen while probing; disabled on chains
+/// without a base fee
+pub fn gas_alert_config(base_fee_gwei: Option<f64>) -> GasAlertConfigV1 {
+    let defaults = GasAlertConfigV1::default();
+    match base_fee_gwei {
+        Some(base_fee) => GasAlertConfigV1 {
+            base_fee_threshold_gwei: round_significant(
+                (base_fee * BASE_FEE_THRESHOLD_MULTIPLIER).max(MIN_BASE_FEE_THRESHOLD_GWEI),
+                2,
+            ),
+            min_spike_priority_fee_gwei: defaults.min_spike_priority_fee_gwei.min(
+                round_significant(base_fee.max(MIN_BASE_FEE_THRESHOLD_GWEI), 2),
+            ),
+            ..defaults
+        },
+        None => GasAlertConfigV1 {
+            enabled: false,
+            ..defaults
+        },
+    }
+}
+
+/// Cross-chain dApp contracts that have code on the new chain
+fn knowledge_pack(
+    io: &dyn OnboardIO,
+    rpc_url: &str,
+    warnings: &mut Vec<String>,
+) -> Vec<KnowledgePackEntryV1> {
+    let mut entries = Vec::new();
+    for (address, dapp_name) in CROSS_CHAIN_DAPPS {
+        match io.rpc(rpc_url, "eth_getCode", json!([address, "latest"])) {
+            Ok(code) if code.as_str().is_some_and(|code| code.len() > 2) => {
+                entries.push(KnowledgePackEntryV1 {
+                    address: address.to_string(),
+                    dapp_name: dapp_name.to_string(),
+                });
+            }
+            Ok(_) => {}
+            Err(e) => warnings.push(format!("eth_getCode {} failed: {}", address, e)),
+        }
+    }
+    entries
+}
+
+/// Fetch recent blocks through the calls eth-raw-transactions and
+/// evm-logs-ingestion make and check that they chain together and decode
+pub fn run_canary(
+    io: &dyn OnboardIO,
+    rpc_url: &str,
+    chain_id: u64,
+    head: u64,
+    blocks: u32,
+) -> CanaryReportV1 {
+    let mut report = CanaryReportV1::default();
+    let last = head.saturating_sub(CANARY_CONFIRMATIONS);
+    let first = last.saturating_sub(u64::from(blocks.max(1)) - 1);
+    let mut previous: Option<(String, u64)> = None;
+
+    for number in first..=last {
+        match canary_block(io, rpc_url, chain_id, number, previous.as_ref()) {
+            Ok((block, timestamp)) => {
+                previous = Some((block.hash.clone(), timestamp));
+                report.blocks.push(block);
+            }
+            Err(e) => {
+                report.failures.push(format!("block {}: {}", number, e));
+                previous = None;
+            }
+        }
+    }
+    report.passed = report.failures.is_empty() && !report.blocks.is_empty();
+    report
+}
+
+fn canary_block(
+    io: &dyn OnboardIO,
+    rpc_url: &str,
+    chain_id: u64,
+    number: u64,
+    previous: Option<&(String, u64)>,
+) -> Result<(CanaryBlockV1, u64), String> {
+    let block = get_block(io, rpc_url, number, true)?;
+    if block.get("number").and_then(hex_u64) != Some(number) {
+        return Err("eth_getBlockByNumber returned another block".to_string());
+    }
+    let hash = block
+        .get("hash")
+        .and_then(Value::as_str)
+        .ok_or("block has no hash")?
+        .to_lowercase();
+    let timestamp = block
+        .get("timestamp")
+        .and_then(hex_u64)
+        .ok_or("block has no timestamp")?;
+    if let Some((previous_hash, previous_ts)) = previous {
+        let parent = block.get("parentHash").and_then(Value::as_str);
+        if parent.map(str::to_lowercase).as_deref() != Some(previous_hash.as_str()) {
+            return Err("parentHash does not match the previous block".to_string());
+        }
+        if timestamp < *previous_ts {
+            return Err("timestamp is earlier than the previous block".to_string());
+        }
+    }
+
+    let transactions = block
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or("block has no transactions array")?;
+    for tx in transactions {
+        if tx.get("hash").and_then(Value::as_str).is_none() {
+            return Err("transaction without hash".to_string());
+        }
+        if let Some(tx_chain_id) = tx.get("chainId").and_then(hex_u64) {
+            if tx_chain_id != chain_id {
+                return Err(format!("transaction on chain {}", tx_chain_id));
+            }
+        }
+    }
+
+    // eth-raw-transactions reads blocks by hash
+    let by_hash = io.rpc(rpc_url, "eth_getBlockByHash", json!([hash, true]))?;
+    if by_hash.get("number").and_then(hex_u64) != Some(number) {
+        return Err("eth_getBlockByHash disagrees with eth_getBlockByNumber".to_string());
+    }
+
+    let block_hex = format!("0x{:x}", number);
+    let logs = io.rpc(
+        rpc_url,
+        "eth_getLogs",
+        json!([{ "fromBlock": block_hex, "toBlock": block_hex }]),
+    )?;
+    let logs = logs.as_array().ok_or("eth_getLogs returned no array")?;
+    if logs
+        .iter()
+        .any(|log| log.get("blockNumber").and_then(hex_u64) != Some(number))
+    {
+        return Err("eth_getLogs returned logs of another block".to_string());
+    }
+
+    // Best effort, as in eth-raw-transactions: nodes without the method still ingest
+    let receipts = match io.rpc(rpc_url, "eth_getBlockReceipts", json!([hash])) {
+        Ok(receipts) => {
+            let count = receipts
+                .as_array()
+                .map(Vec::len)
+                .ok_or("eth_getBlockReceipts returned no array")?;
+            if count != transactions.len() {
+                return Err(format!(
+                    "{} receipts for {} transactions",
+                    count,
+                    transactions.len()
+                ));
+            }
+            Some(count)
+        }
+        Err(_) => None,
+    };
+
+    Ok((
+        CanaryBlockV1 {
+            number,
+            hash,
+            transactions: transactions.len(),
+            logs: logs.len(),
+            receipts,
+        },
+        timestamp,
+    ))
+}
+
+fn get_block(io: &dyn OnboardIO, rpc_url: &str, number: u64, full: bool) -> Result<Value, String> {
+    let block = io.rpc(
+        rpc_url,
+        "eth_getBlockByNumber",
+        json!([format!("0x{:x}", number), full]),
+    )?;
+    if block.is_null() {
+        return Err(format!("block {} not found", number));
+    }
+    Ok(block)
+}
+
+fn load_plan(io: &dyn OnboardIO, chain_id: u64) -> Result<Option<ChainOnboardingPlanV1>, String> {
+    io.kv_get(&plan_key(chain_id))?
+        .map(|bytes| {
+            serde_json::from_slice(&bytes).map_err(|e| format!("invalid stored plan: {}", e))
+        })
+        .transpose()
+}
+
+fn store_plan(io: &dyn OnboardIO, plan: &ChainOnboardingPlanV1) -> Result<(), String> {
+    io.kv_set(&plan_key(plan.chain_id), &to_json(plan)?)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(value).map_err(|e| format!("failed to serialize: {}", e))
+}
+
+fn node_id(network: &str, subnet: &str) -> String {
+    format!("{}-{}", network, subnet)
+}
+
+fn derive_ws_url(rpc_url: &str) -> Option<String> {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        return Some(format!("wss://{}", rest));
+    }
+    rpc_url
+        .strip_prefix("http://")
+        .map(|rest| format!("ws://{}", rest))
+}
+
+fn hex_u64(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+fn hex_u128(value: &Value) -> Option<u128> {
+    u128::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+fn round_significant(value: f64, digits: i32) -> f64 {
+    if value <= 0.0 {
+        return value;
+    }
+    // Scale by exact powers of ten so round values stay exact
+    let exponent = value.log10().floor() as i32 - (digits - 1);
+    if exponent >= 0 {
+        let scale = 10f64.powi(exponent);
+        (value / scale).round() * scale
+    } else {
+        let scale = 10f64.powi(-exponent);
+        (value * scale).round() / scale
+    }
+}
+
+pub fn plan_schema_version() -> String {
+    "chain_onboarding_plan_v1".to_string()
+}
+
+pub fn result_schema_version() -> String {
+    "chain_onboard_result_v1".to_string()
+}
+
+pub fn activated_schema_version() -> String {
+    "chain_activated_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+
+    const RPC: &str = "https://rpc.example.org";
+    const HEAD: u64 = 1_000;
+
+    /// A chain with one block every 2s and a 0.05 gwei base fee
+    struct FakeIO {
+        kv: RefCell<HashMap<String, Vec<u8>>>,
+        published: RefCell<Vec<(String, Value)>>,
+        chain_id: u64,
+        broken_parent_at: Option<u64>,
+        clock: Cell<u64>,
+    }
+
+    impl FakeIO {
+        fn new(chain_id: u64) -> Self {
+            Self {
+                kv: RefCell::new(HashMap::new()),
+                published: RefCell::new(Vec::new()),
+                chain_id,
+                broken_parent_at: None,
+                clock: Cell::new(0),
+            }
+        }
+
+        fn block(&self, number: u64, full: bool) -> Value {
+            let parent = if Some(number) == self.broken_parent_at {
+                "0xdead".to_string()
+            } else {
+                format!("0x{:x}", number.wrapping_sub(1))
+            };
+            let tx = if full {
+                json!({"hash": format!("0x{:x}01", number), "chainId": format!("0x{:x}", self.chain_id)})
+            } else {
+                json!(format!("0x{:x}01", number))
+            };
+            json!({
+                "number": format!("0x{:x}", number),
+                "hash": format!("0x{:x}", number),
+                "parentHash": parent,
+                "timestamp": format!("0x{:x}", 1_700_000_000 + number * 2),
+                "baseFeePerGas": "0x2faf080",
+                "transactions": [tx],
+            })
+        }
+    }
+
+    impl OnboardIO for FakeIO {
+        fn kv_get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.kv.borrow().get(key).cloned())
+        }
+
+        fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+            self.kv.borrow_mut().insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+
+        fn rpc(&self, rpc_url: &str, method: &str, params: Value) -> Result<Value, String> {
+            self.clock.set(self.clock.get() + 10);
+            if rpc_url != RPC {
+                return Err("connection refused".to_string());
+            }
+            let number = |value: &Value| hex_u64(value).unwrap();
+            Ok(match method {
+                "eth_chainId" => json!(format!("0x{:x}", self.chain_id)),
+                "eth_blockNumber" => json!(format!("0x{:x}", HEAD)),
+                "eth_getBlockByNumber" => self.block(number(&params[0]), params[1] == json!(true)),
+                "eth_getBlockByHash" => self.block(number(&params[0]), true),
+                "eth_getLogs" => json!([{"blockNumber": params[0]["fromBlock"]}]),
+                "eth_getBlockReceipts" => json!([{}]),
+                "eth_getCode" if params[0] == "0xba12222222228d8ba445958a75a0704d566bf2c8" => {
+                    json!("0x6080")
+                }
+                "eth_getCode" => json!("0x"),
+                _ => return Err(format!("unexpected {}", method)),
+            })
+        }
+
+        fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+            self.published
+                .borrow_mut()
+                .push((subject.to_string(), serde_json::from_slice(&body).unwrap()));
+            Ok(())
+        }
+
+        fn clock_ms(&self) -> u64 {
+            self.clock.get()
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            DateTime::from_timestamp(1_704_067_200, 0).unwrap()
+        }
+    }
+
+    fn request(chain_id: u64) -> ChainOnboardRequestV1 {
+        serde_json::from_value(json!({
+            "chain_id": chain_id,
+            "rpc_urls": ["https://down.example.org", RPC],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_onboarding_stages_plan_without_writing_config() {
+        let io = FakeIO::new(8453);
+        let result = run_onboarding(&io, &request(8453));
+        assert!(result.success, "{:?}", result.error);
+
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.status, OnboardingStatusV1::AwaitingApproval);
+        assert_eq!(plan.probe.head, Some(HEAD));
+        assert_eq!(plan.probe.block_time_secs, Some(2.0));
+        assert_eq!(plan.probe.native_symbol, "ETH");
+        assert_eq!(plan.probe.symbol_source, SymbolSourceV1::Registry);
+        assert!(!plan.probe.endpoints[0].ok);
+        assert!(plan.probe.endpoints[1].ok);
+
+        let config = plan.config.unwrap();
+        assert_eq!(config.node.chain_id, "base-mainnet");
+        assert_eq!(config.node.rpc_url, RPC);
+        assert_eq!(config.node.ws_url, "wss://rpc.example.org");
+        assert!(!config.node.enabled);
+        assert_eq!(config.gas_alerts.base_fee_threshold_gwei, 0.5);
+        assert_eq!(config.knowledge_pack.len(), 1);
+        assert_eq!(config.knowledge_pack[0].dapp_name, "Balancer");
+
+        let canary = plan.canary.unwrap();
+        assert!(canary.passed);
+        assert_eq!(
+            canary.blocks.iter().map(|b| b.number).collect::<Vec<_>>(),
+            vec![996, 997, 998]
+        );
+
+        let kv = io.kv.borrow();
+        assert!(kv.contains_key("chain_onboarding:plan:8453"));
+        assert!(!kv.contains_key("blockchain:nodes:base-mainnet"));
+    }
+
+    #[test]
+    fn test_onboarding_fails_on_wrong_chain_or_broken_canary() {
+        let io = FakeIO::new(1);
+        let result = run_onboarding(&io, &request(8453));
+        assert!(!result.success);
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.status, OnboardingStatusV1::Failed);
+        assert_eq!(
+            plan.probe.endpoints[1].error.as_deref(),
+            Some("reports chain id 1, expected 8453")
+        );
+
+        let mut io = FakeIO::new(8453);
+        io.broken_parent_at = Some(997);
+        let result = run_onboarding(&io, &request(8453));
+        let canary = result.plan.unwrap().canary.unwrap();
+        assert!(!canary.passed);
+        assert_eq!(
+            canary.failures,
+            vec!["block 997: parentHash does not match the previous block"]
+        );
+
+        let unknown: ChainOnboardRequestV1 =
+            serde_json::from_value(json!({"chain_id": 999_999, "rpc_urls": [RPC]})).unwrap();
+        assert!(run_onboarding(&io, &unknown)
+            .error
+            .unwrap()
+            .contains("set network and subnet"));
+    }
+
+    #[test]
+    fn test_approval_writes_config_and_announces_chain() {
+        let io = FakeIO::new(100);
+        run_onboarding(&io, &request(100));
+        io.kv_set(
+            "gas:alerts:config:gnosis:mainnet",
+            br#"{"base_fee_threshold_gwei": 1.0}"#,
+        )
+        .unwrap();
+
+        let result = handle_approve_message(&io, br#"{"chain_id": 100, "approved_by": "ops"}"#);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.plan.unwrap().status, OnboardingStatusV1::Active);
+
+        let kv = io.kv.borrow();
+        let node: BlockchainNodeV1 =
+            serde_json::from_slice(&kv["blockchain:nodes:gnosis-mainnet"]).unwrap();
+        assert!(node.enabled);
+        // Operator thresholds are kept
+        assert_eq!(
+            kv["gas:alerts:config:gnosis:mainnet"],
+            br#"{"base_fee_threshold_gwei": 1.0}"#.to_vec()
+        );
+        assert_eq!(
+            kv["dapp:contract:gnosis:0xba12222222228d8ba445958a75a0704d566bf2c8"],
+            b"Balancer".to_vec()
+        );
+        let health: ChainHealthConfigV1 =
+            serde_json::from_slice(&kv["chain:health:config"]).unwrap();
+        assert_eq!(health.block_time_secs.get("gnosis"), Some(&2.0));
+        assert_eq!(health.block_time_secs.get("ethereum"), Some(&12.0));
+        drop(kv);
+
+        let published = io.published.borrow();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, ACTIVATED_SUBJECT);
+        assert_eq!(published[0].1["node_id"], "gnosis-mainnet");
+
+        let again = handle_approve_message(&io, br#"{"chain_id": 100, "approved_by": "ops"}"#);
+        assert!(!again.success);
+    }
+
+    #[test]
+    fn test_native_symbol_and_gas_heuristics() {
+        let mut req = request(7_777_777);
+        assert_eq!(
+            native_symbol(&req, "polygon", "Polygon zkEVM Cardona"),
+            ("MATIC".to_string(), SymbolSourceV1::Network)
+        );
+        assert_eq!(
+            native_symbol(&req, "unichain", "Unichain OP Stack L2"),
+            ("ETH".to_string(), SymbolSourceV1::Heuristic)
+        );
+        assert_eq!(
+            native_symbol(&req, "sei", "Sei"),
+            ("ETH".to_string(), SymbolSourceV1::Default)
+        );
+        req.native_symbol = Some("sei".to_string());
+        assert_eq!(
+            native_symbol(&req, "sei", "Sei"),
+            ("SEI".to_string(), SymbolSourceV1::Request)
+        );
+
+        assert!(!gas_alert_config(None).enabled);
+        let mainnet = gas_alert_config(Some(12.34));
+        assert_eq!(mainnet.base_fee_threshold_gwei, 120.0);
+        assert_eq!(mainnet.min_spike_priority_fee_gwei, 2.0);
+    }
+}
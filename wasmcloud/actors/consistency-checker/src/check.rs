@@ -0,0 +1,551 @@
+//! Sampled consistency check for `admin.consistency.check`
+//!
+//! A random sample of recent EVM `transactions` rows is read back from
+//! DuckLake and the derived columns are recomputed from the raw inputs
+//! stored alongside them. Each [`CheckKindV1`] compares one stored field with
+//! its re-derived value; the mismatch counts are published as data-quality
+//! metrics so drift between processors, the lake writer and the knowledge
+//! packs shows up without a full re-index.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use arrow::array::Array;
+use arrow::ipc::reader::StreamReader;
+use arrow::util::display::array_value_to_string;
+use chrono::{DateTime, Utc};
+use ducklake_common::types::{QueryRequest, SqlParam};
+use serde::{Deserialize, Serialize};
+
+pub const CHECK_SUBJECT: &str = "admin.consistency.check";
+pub const METRICS_SUBJECT: &str = "metrics.data_quality.consistency";
+
+pub const DEFAULT_SAMPLE_SIZE: u32 = 500;
+pub const MAX_SAMPLE_SIZE: u32 = 5_000;
+pub const DEFAULT_LOOKBACK_HOURS: u32 = 24;
+pub const MAX_EXAMPLES: usize = 20;
+pub const QUERY_TIMEOUT_MS: u32 = 30_000;
+
+/// Relative difference tolerated between `amount_native` (a float) and `value`
+const VALUE_TOLERANCE: f64 = 1e-9;
+
+const WEI_PER_NATIVE: f64 = 1_000_000_000_000_000_000.0;
+
+const SAMPLE_SQL: &str = "SELECT t.transaction_hash, t.transaction_type, t.transaction_subtype, \
+     t.to_address, t.input_data, t.method_signature, t.decoded_function_selector, \
+     t.gas_used, CAST(t.effective_gas_price AS VARCHAR) AS effective_gas_price, \
+     CAST(t.transaction_fee AS VARCHAR) AS transaction_fee, \
+     CAST(t.value AS VARCHAR) AS value, t.amount_native, c.dapp_name \
+     FROM transactions t \
+     LEFT JOIN contract_calls c \
+     ON c.chain_id = t.chain_id AND c.transaction_hash = t.transaction_hash AND c.call_index = 0 \
+     WHERE t.chain_id = ? AND t.vm_type = 'evm' \
+     AND t.block_timestamp >= now() - to_hours(CAST(? AS BIGINT)) \
+     ORDER BY random() LIMIT ?";
+
+/// One DuckLake result row; NULL columns are absent
+pub type Row = HashMap<String, String>;
+
+/// Decode a DuckLake Arrow IPC response into rows of display strings
+pub fn decode_rows(bytes: &[u8]) -> Result<Vec<Row>, String> {
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)
+        .map_err(|e| format!("failed to decode arrow stream: {}", e))?;
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(|e| format!("arrow decode error: {}", e))?;
+        let schema = batch.schema();
+        for index in 0..batch.num_rows() {
+            let mut row = Row::new();
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                if column.is_null(index) {
+                    continue;
+                }
+                let value = array_value_to_string(column, index)
+                    .map_err(|e| format!("arrow value error: {}", e))?;
+                row.insert(field.name().clone(), value);
+            }
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+pub trait CheckIO {
+    /// Read a knowledge-pack entry; `None` when the key is missing
+    fn kv_get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    /// Run a DuckLake query and return its rows
+    fn query(&self, subject: &str, request: &QueryRequest) -> Result<Vec<Row>, String>;
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String>;
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckKindV1 {
+    /// `transaction_fee` against `gas_used * effective_gas_price`
+    Fee,
+    /// `amount_native` against `value` in wei
+    Value,
+    /// `method_signature` / `decoded_function_selector` against `input_data`
+    Selector,
+    /// `transaction_subtype` against the selector's function category
+    Category,
+    /// `contract_calls.dapp_name` against the knowledge-pack entry for `to_address`
+    Dapp,
+}
+
+impl CheckKindV1 {
+    pub const ALL: [Self; 5] = [
+        Self::Fee,
+        Self::Value,
+        Self::Selector,
+        Self::Category,
+        Self::Dapp,
+    ];
+}
+
+/// `admin.consistency.check` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyCheckRequestV1 {
+    pub network: String,
+    pub subnet: String,
+    /// Rows sampled from the lookback window
+    #[serde(default)]
+    pub sample_size: Option<u32>,
+    #[serde(default)]
+    pub lookback_hours: Option<u32>,
+}
+
+impl ConsistencyCheckRequestV1 {
+    /// DuckLake `chain_id` partition value, e.g. `ethereum_mainnet`
+    pub fn chain_key(&self) -> String {
+        format!("{}_{}", self.network, self.subnet)
+    }
+
+    fn sample_size(&self) -> u32 {
+        self.sample_size
+            .unwrap_or(DEFAULT_SAMPLE_SIZE)
+            .clamp(1, MAX_SAMPLE_SIZE)
+    }
+
+    fn lookback_hours(&self) -> u32 {
+        self.lookback_hours.unwrap_or(DEFAULT_LOOKBACK_HOURS).max(1)
+    }
+}
+
+/// Outcome of one check over the sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckSummaryV1 {
+    pub check: CheckKindV1,
+    /// Rows the check applied to
+    pub compared: u64,
+    pub mismatches: u64,
+    pub mismatch_rate: f64,
+}
+
+/// A row whose stored field disagrees with the re-derived value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MismatchV1 {
+    pub check: CheckKindV1,
+    pub transaction_hash: String,
+    pub stored: String,
+    pub derived: String,
+}
+
+/// Published on [`METRICS_SUBJECT`] and sent as the reply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyReportV1 {
+    pub schema_version: String,
+    pub check_id: String,
+    pub network: String,
+    pub subnet: String,
+    pub sampled: u64,
+    pub checks: Vec<CheckSummaryV1>,
+    /// First [`MAX_EXAMPLES`] mismatches across all checks
+    pub examples: Vec<MismatchV1>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn handle_check_message(io: &dyn CheckIO, body: &[u8]) -> ConsistencyReportV1 {
+    match serde_json::from_slice::<ConsistencyCheckRequestV1>(body) {
+        Ok(request) => run_check(io, &request),
+        Err(e) => {
+            let now = io.now();
+            ConsistencyReportV1 {
+                schema_version: report_schema_version(),
+                check_id: String::new(),
+                network: String::new(),
+                subnet: String::new(),
+                sampled: 0,
+                checks: Vec::new(),
+                examples: Vec::new(),
+                started_at: now,
+                completed_at: now,
+                error: Some(format!("Invalid consistency check request: {}", e)),
+            }
+        }
+    }
+}
+
+pub fn run_check(io: &dyn CheckIO, request: &ConsistencyCheckRequestV1) -> ConsistencyReportV1 {
+    let started_at = io.now();
+    let mut report = ConsistencyReportV1 {
+        schema_version: report_schema_version(),
+        check_id: format!("{}-{}", request.chain_key(), started_at.timestamp_millis()),
+        network: request.network.clone(),
+        subnet: request.subnet.clone(),
+        sampled: 0,
+        checks: Vec::new(),
+        examples: Vec::new(),
+        started_at,
+        completed_at: started_at,
+        error: None,
+    };
+
+    let subject = format!(
+        "ducklake.transactions.{}.{}.query",
+        request.network, request.subnet
+    );
+    let query = QueryRequest::new(SAMPLE_SQL)
+        .with_timeout(QUERY_TIMEOUT_MS / 1000)
+        .with_parameters(vec![
+            SqlParam::String(request.chain_key()),
+            SqlParam::Int64(request.lookback_hours() as i64),
+            SqlParam::Int64(request.sample_size() as i64),
+        ]);
+    let rows = match io.query(&subject, &query) {
+        Ok(rows) => rows,
+        Err(e) => {
+            report.error = Some(e);
+            report.completed_at = io.now();
+            return report;
+        }
+    };
+
+    let mut counts: HashMap<CheckKindV1, (u64, u64)> = HashMap::new();
+    for row in &rows {
+        for check in CheckKindV1::ALL {
+            let Some((stored, derived, matches)) = compare(io, request, check, row) else {
+                continue;
+            };
+            let entry = counts.entry(check).or_default();
+            entry.0 += 1;
+            if matches {
+                continue;
+            }
+            entry.1 += 1;
+            if report.examples.len() < MAX_EXAMPLES {
+                report.examples.push(MismatchV1 {
+                    check,
+                    transaction_hash: row.get("transaction_hash").cloned().unwrap_or_default(),
+                    stored,
+                    derived,
+                });
+            }
+        }
+    }
+
+    report.sampled = rows.len() as u64;
+    report.checks = CheckKindV1::ALL
+        .iter()
+        .map(|check| {
+            let (compared, mismatches) = counts.get(check).copied().unwrap_or_default();
+            CheckSummaryV1 {
+                check: *check,
+                compared,
+                mismatches,
+                mismatch_rate: if compared == 0 {
+                    0.0
+                } else {
+                    mismatches as f64 / compared as f64
+                },
+            }
+        })
+        .collect();
+    report.completed_at = io.now();
+
+    match serde_json::to_vec(&report) {
+        Ok(body) => {
+            if let Err(e) = io.publish(METRICS_SUBJECT, body) {
+                report.error = Some(e);
+            }
+        }
+        Err(e) => report.error = Some(format!("failed to serialize report: {}", e)),
+    }
+    report
+}
+
+/// `(stored, derived, matches)` for one check, or `None` when it does not apply
+fn compare(
+    io: &dyn CheckIO,
+    request: &ConsistencyCheckRequestV1,
+    check: CheckKindV1,
+    row: &Row,
+) -> Option<(String, String, bool)> {
+    let contract_call = row.get("transaction_type").map(String::as_str) == Some("contract_call");
+    match check {
+        CheckKindV1::Fee => {
+            let stored = integer_part(row.get("transaction_fee")?)?;
+            let gas_used: u128 = row.get("gas_used")?.parse().ok()?;
+            let gas_price = integer_part(row.get("effective_gas_price")?)?;
+            let derived = gas_used.checked_mul(gas_price)?;
+            Some((stored.to_string(), derived.to_string(), stored == derived))
+        }
+        CheckKindV1::Value => {
+            let stored: f64 = row.get("amount_native")?.parse().ok()?;
+            let derived = integer_part(row.get("value")?)? as f64 / WEI_PER_NATIVE;
+            let scale = stored.abs().max(derived.abs()).max(f64::MIN_POSITIVE);
+            let matches = (stored - derived).abs() / scale <= VALUE_TOLERANCE;
+            Some((stored.to_string(), derived.to_string(), matches))
+        }
+        CheckKindV1::Selector if contract_call => {
+            let derived = selector(row.get("input_data")?)?;
+            let stored = row.get("method_signature")?.to_lowercase();
+            let decoded_matches = row
+                .get("decoded_function_selector")
+                .map(|decoded| decoded.to_lowercase() == derived)
+                .unwrap_or(true);
+            Some((
+                stored.clone(),
+                derived.clone(),
+                stored == derived && decoded_matches,
+            ))
+        }
+        CheckKindV1::Category if contract_call => {
+            let derived = subtype_for_selector(&selector(row.get("input_data")?)?);
+            let stored = row.get("transaction_subtype")?.clone();
+            Some((stored.clone(), derived.to_string(), stored == derived))
+        }
+        CheckKindV1::Dapp if contract_call => {
+            let key = retention_policy::DAPP_CONTRACT.key(&format!(
+                "{}:{}",
+                request.network.to_lowercase(),
+                row.get("to_address")?.to_lowercase()
+            ));
+            let derived = io
+                .kv_get(&key)
+                .ok()
+                .flatten()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())?;
+            let stored = row.get("dapp_name").cloned().unwrap_or_default();
+            Some((stored.clone(), derived.clone(), stored == derived))
+        }
+        _ => None,
+    }
+}
+
+/// Integer part of a decimal rendered by DuckDB (`21000.000000000000000000`)
+fn integer_part(value: &str) -> Option<u128> {
+    value.split('.').next()?.trim().parse().ok()
+}
+
+/// `0x` plus the first four bytes of call data, lowercased
+fn selector(input_data: &str) -> Option<String> {
+    let input = input_data.trim().to_lowercase();
+    if input.len() < 10 || !input.starts_with("0x") {
+        return None;
+    }
+    Some(input[..10].to_string())
+}
+
+/// Reference mapping of eth_contract_transaction_processor's function
+/// categories, kept separate so a change on either side is reported
+pub fn subtype_for_selector(selector: &str) -> &'static str {
+    match selector {
+        "0xa9059cbb" | "0x23b872dd" => "transfer",
+        "0x095ea7b3" => "approve",
+        "0x38ed1739" | "0x7ff36ab5" | "0x18cbafe5" => "swap",
+        "0xa694fc3a" | "0xb6b55f25" => "stake",
+        "0x2e1a7d4d" => "unstake",
+        "0xc5ebeaec" => "borrow",
+        "0x573ade81" => "repay",
+        "0x00a718a9" => "liquidate",
+        "0xda95691a" | "0x15373e3d" => "governance",
+        "0xf2ae372f" | "0x5b88e8c6" | "0xb7ddc992" | "0xb6b1b6c3" => "perp_open",
+        "0x7be7d141" | "0x90205d8c" | "0x00aa9a89" => "perp_close",
+        "0xde2ea948" | "0x65d461bd" | "0x86b9d81f" => "perp_liquidation",
+        _ => "unknown",
+    }
+}
+
+pub fn report_schema_version() -> String {
+    "consistency_report_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeIO {
+        kv: HashMap<String, Vec<u8>>,
+        rows: Vec<Row>,
+        queries: RefCell<Vec<(String, Vec<SqlParam>)>>,
+        published: RefCell<Vec<ConsistencyReportV1>>,
+    }
+
+    impl CheckIO for FakeIO {
+        fn kv_get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.kv.get(key).cloned())
+        }
+
+        fn query(&self, subject: &str, request: &QueryRequest) -> Result<Vec<Row>, String> {
+            self.queries.borrow_mut().push((
+                subject.to_string(),
+                request.parameters.clone().unwrap_or_default(),
+            ));
+            Ok(self.rows.clone())
+        }
+
+        fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+            assert_eq!(subject, METRICS_SUBJECT);
+            self.published
+                .borrow_mut()
+                .push(serde_json::from_slice(&body).unwrap());
+            Ok(())
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            DateTime::from_timestamp(1_704_067_200, 0).unwrap()
+        }
+    }
+
+    fn row(pairs: &[(&str, &str)]) -> Row {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn contract_call(hash: &str, subtype: &str, fee: &str) -> Row {
+        row(&[
+            ("transaction_hash", hash),
+            ("transaction_type", "contract_call"),
+            ("transaction_subtype", subtype),
+            ("to_address", "0x7A250D5630B4CF539739DF2C5DACB4C659F2488D"),
+            ("input_data", "0xa9059cbb000000000000000000000000"),
+            ("method_signature", "0xa9059cbb"),
+            ("decoded_function_selector", "0xa9059cbb"),
+            ("gas_used", "21000"),
+            ("effective_gas_price", "20000000000.000000000000000000"),
+            ("transaction_fee", fee),
+            ("value", "1500000000000000000.000000000000000000"),
+            ("amount_native", "1.5"),
+            ("dapp_name", "Uniswap"),
+        ])
+    }
+
+    fn request() -> ConsistencyCheckRequestV1 {
+        ConsistencyCheckRequestV1 {
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            sample_size: Some(50_000),
+            lookback_hours: None,
+        }
+    }
+
+    #[test]
+    fn test_consistent_rows_report_no_mismatches() {
+        let io = FakeIO {
+            kv: HashMap::from([(
+                "dapp:contract:ethereum:0x7a250d5630b4cf539739df2c5dacb4c659f2488d".to_string(),
+                b"Uniswap".to_vec(),
+            )]),
+            rows: vec![contract_call(
+                "0x1",
+                "transfer",
+                "420000000000000.000000000000000000",
+            )],
+            ..Default::default()
+        };
+
+        let report = run_check(&io, &request());
+        assert!(report.error.is_none());
+        assert_eq!(report.sampled, 1);
+        assert!(report.examples.is_empty());
+        assert!(report
+            .checks
+            .iter()
+            .all(|summary| summary.compared == 1 && summary.mismatches == 0));
+
+        let queries = io.queries.borrow();
+        assert_eq!(queries[0].0, "ducklake.transactions.ethereum.mainnet.query");
+        let [SqlParam::String(chain), SqlParam::Int64(hours), SqlParam::Int64(limit)] =
+            queries[0].1.as_slice()
+        else {
+            panic!("unexpected params {:?}", queries[0].1);
+        };
+        assert_eq!(chain, "ethereum_mainnet");
+        assert_eq!(*hours, 24);
+        assert_eq!(*limit, MAX_SAMPLE_SIZE as i64);
+        assert_eq!(io.published.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_drifted_fields_are_reported() {
+        let mut native = row(&[
+            ("transaction_hash", "0x3"),
+            ("transaction_type", "transfer"),
+            ("gas_used", "21000"),
+            ("effective_gas_price", "1.000000000000000000"),
+            ("transaction_fee", "21000.000000000000000000"),
+            ("value", "1000000000000000000.000000000000000000"),
+            ("amount_native", "0.5"),
+        ]);
+        native.insert("transaction_subtype".to_string(), "native".to_string());
+        let io = FakeIO {
+            rows: vec![
+                contract_call("0x1", "swap", "420000000000000.000000000000000000"),
+                contract_call("0x2", "transfer", "1.000000000000000000"),
+                native,
+            ],
+            ..Default::default()
+        };
+
+        let report = run_check(&io, &request());
+        let summary = |check| {
+            report
+                .checks
+                .iter()
+                .find(|summary| summary.check == check)
+                .cloned()
+                .unwrap()
+        };
+
+        assert_eq!(summary(CheckKindV1::Fee).compared, 3);
+        assert_eq!(summary(CheckKindV1::Fee).mismatches, 1);
+        assert_eq!(summary(CheckKindV1::Value).mismatches, 1);
+        assert_eq!(summary(CheckKindV1::Category).compared, 2);
+        assert_eq!(summary(CheckKindV1::Category).mismatches, 1);
+        assert_eq!(summary(CheckKindV1::Category).mismatch_rate, 0.5);
+        // No knowledge-pack entry, so the dApp check has nothing to compare
+        assert_eq!(summary(CheckKindV1::Dapp).compared, 0);
+
+        let category = report
+            .examples
+            .iter()
+            .find(|example| example.check == CheckKindV1::Category)
+            .unwrap();
+        assert_eq!(category.transaction_hash, "0x1");
+        assert_eq!(category.stored, "swap");
+        assert_eq!(category.derived, "transfer");
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let io = FakeIO::default();
+        let report = handle_check_message(&io, b"{}");
+        assert!(report
+            .error
+            .unwrap()
+            .contains("Invalid consistency check request"));
+        assert!(io.queries.borrow().is_empty());
+        assert!(io.published.borrow().is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Consistency Checker Actor
+//!
+//! Handles `admin.consistency.check` requests by sampling processed DuckLake
+//! `transactions` rows and re-deriving key fields from the raw inputs stored
+//! with them:
+//! - the fee from `gas_used` and `effective_gas_price`
+//! - `amount_native` from the wei `value`
+//! - the selector from `input_data`
+//! - the transaction subtype from the selector's function category
+//! - the dApp from the knowledge-pack entry (`dapp:contract:*`) when one exists
+//!
+//! Mismatch counts are published as a `ConsistencyReportV1` on
+//! `metrics.data_quality.consistency` and sent to the request's reply subject.
+//! Scheduling a periodic request turns this into a drift monitor.
+
+mod check;
+
+pub use check::{
+    decode_rows, handle_check_message, run_check, subtype_for_selector, CheckIO, CheckKindV1,
+    CheckSummaryV1, ConsistencyCheckRequestV1, ConsistencyReportV1, MismatchV1, Row, CHECK_SUBJECT,
+    METRICS_SUBJECT,
+};
+
+#[cfg(target_arch = "wasm32")]
+wit_bindgen::generate!({ generate_all });
+
+#[cfg(target_arch = "wasm32")]
+use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+
+#[cfg(target_arch = "wasm32")]
+use wasmcloud::messaging::types as nats_types;
+
+#[cfg(target_arch = "wasm32")]
+use wasi::keyvalue::store;
+
+#[cfg(target_arch = "wasm32")]
+subject_acl::component!("consistency-checker");
+
+#[cfg(target_arch = "wasm32")]
+struct Component;
+
+#[cfg(target_arch = "wasm32")]
+export!(Component);
+
+#[cfg(target_arch = "wasm32")]
+struct WasmRuntime;
+
+#[cfg(target_arch = "wasm32")]
+impl CheckIO for WasmRuntime {
+    fn kv_get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        store::open("default")
+            .map_err(|e| format!("failed to open keyvalue bucket: {:?}", e))?
+            .get(key)
+            .map_err(|e| format!("keyvalue get failed: {:?}", e))
+    }
+
+    fn query(
+        &self,
+        subject: &str,
+        request: &ducklake_common::types::QueryRequest,
+    ) -> Result<Vec<Row>, String> {
+        let body =
+            serde_json::to_vec(request).map_err(|e| format!("failed to serialize query: {}", e))?;
+        let resp = wasmcloud::messaging::consumer::request(
+            &subject_registry::prefixed(subject),
+            &body,
+            check::QUERY_TIMEOUT_MS,
+        )
+        .map_err(|e| format!("ducklake query failed: {:?}", e))?;
+        decode_rows(&resp.body)
+    }
+
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
+        wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+            subject: subject_registry::prefixed(subject),
+            body,
+            reply_to: None,
+        })
+        .map_err(|e| format!("nats publish failed: {:?}", e))
+    }
+
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl MessageHandler for Component {
+    fn handle_message(msg: nats_types::BrokerMessage) -> Result<(), String> {
+        if subject_registry::unprefixed(&msg.subject) != Some(CHECK_SUBJECT) {
+            return Ok(());
+        }
+
+        let io = WasmRuntime;
+        let report = handle_check_message(&io, &msg.body);
+        if let Some(error) = &report.error {
+            eprintln!("[CONSISTENCY] ❌ {}: {}", report.check_id, error);
+        }
+        if let Some(reply_to) = msg.reply_to {
+            let body = serde_json::to_vec(&report)
+                .map_err(|e| format!("failed to serialize consistency report: {}", e))?;
+            wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: reply_to,
+                body,
+                reply_to: None,
+            })
+            .map_err(|e| format!("failed to send consistency reply: {:?}", e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,493 @@
+//! # Entity Activity Aggregator Actor
+//!
+//! WasmCloud actor that merges address activity across chains for addresses
+//! linked to the same entity by the clustering/labels system. Every chain
+//! processor already writes `address_transactions` rows; this actor listens to
+//! those writes, attributes each row to an entity and maintains a rolling 24h
+//! cross-chain window per entity.
+//!
+//! ## Architecture
+//! - **wasmCloud Actor**: Uses proper WIT interfaces
+//! - **Messaging**: Subscribes to `ducklake.address_transactions.*.*.write`
+//! - **State**: Redis for entity links, native prices and rolling windows
+//!
+//! ## Redis Keys
+//! - `entity:address:{chain_id}:{address}` - Chain-specific entity link (takes precedence)
+//! - `entity:address:{address}` - Entity link for all chains (same EVM address on every chain)
+//! - `price:native:{network}` - Native asset USD price used for `value_usd`
+//! - `entity_activity:config` - `{"large_movement_usd": 1000000}` alert threshold
+//! - `entity_activity:window:{entity_id}` - Rolling 24h window state (2 day TTL,
+//!   applied by the redis-janitor per `retention_policy::ENTITY_ACTIVITY_WINDOW`)
+//!
+//! Entity links are JSON `{"entity_id": "...", "label": "..."}` and are written by
+//! the clustering/labels system; addresses without a link are ignored.
+//!
+//! ## Subscription Pattern
+//! - Subscribes to: `ducklake.address_transactions.*.*.write`
+//! - Publishes to:
+//!   - `ducklake.entity_activity.{network}.{subnet}.write` - DuckLake persistence
+//!   - `entities.activity.updated` - Window snapshot after each event
+//!   - `entities.activity.large_movement` - Outflow crossed the threshold in 24h
+//!
+//! Window updates are read-modify-write, so this component runs as a single replica.
+
+mod window;
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use window::{EntityWindow, WindowEvent, WindowTotals};
+
+// Generate WIT bindings for the aggregator world
+wit_bindgen::generate!({ generate_all });
+
+use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+use wasmcloud::messaging::{consumer, types};
+
+subject_acl::component!("entity-activity-aggregator");
+
+/// Default outflow threshold for `entities.activity.large_movement`
+const DEFAULT_LARGE_MOVEMENT_USD: f64 = 1_000_000.0;
+
+/// Token subtypes whose `value` is in token units rather than the native asset
+const TOKEN_SUBTYPES: [&str; 6] = ["erc20", "erc721", "erc1155", "trc10", "trc20", "spl"];
+
+/// address_transactions row as written by the chain processors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressTransactionRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub address: String,
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub is_sender: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_subtype: Option<String>,
+}
+
+/// Address to entity link maintained by the clustering/labels system
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityLink {
+    pub entity_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Aggregator configuration stored in Redis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatorConfig {
+    #[serde(default = "default_large_movement_usd")]
+    pub large_movement_usd: f64,
+}
+
+fn default_large_movement_usd() -> f64 {
+    DEFAULT_LARGE_MOVEMENT_USD
+}
+
+impl Default for AggregatorConfig {
+    fn default() -> Self {
+        Self {
+            large_movement_usd: DEFAULT_LARGE_MOVEMENT_USD,
+        }
+    }
+}
+
+/// DuckLake entity_activity record aligned to schema requirements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeEntityActivityRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub entity_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_label: Option<String>,
+    pub address: String,
+    pub network: String,
+    pub subnet: String,
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub is_sender: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterparty_address: Option<String>,
+    pub is_internal: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_subtype: Option<String>,
+    pub window_inflow_usd_24h: f64,
+    pub window_outflow_usd_24h: f64,
+    pub window_tx_count_24h: u64,
+    pub window_chain_count_24h: u32,
+}
+
+/// Entity window snapshot published for alert evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityActivityEvent {
+    pub entity_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_label: Option<String>,
+    pub chain_id: String,
+    pub address: String,
+    pub transaction_hash: String,
+    pub block_timestamp: u64,
+    pub window: WindowTotals,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_usd: Option<f64>,
+}
+
+/// Main Entity Activity Aggregator Actor
+pub struct Component;
+
+// Export Component for WasmCloud
+export!(Component);
+
+impl MessageHandler for Component {
+    /// Handle address_transactions writes from any chain
+    fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        let subject = match subject_registry::unprefixed(&msg.subject) {
+            Some(subject)
+                if subject.starts_with("ducklake.address_transactions.")
+                    && subject.ends_with(".write") =>
+            {
+                subject
+            }
+            _ => return Ok(()),
+        };
+
+        let (network, subnet) = Self::parse_subject_context(subject)?;
+
+        let record: AddressTransactionRecord = serde_json::from_slice(&msg.body)
+            .map_err(|e| format!("Failed to parse address transaction record: {}", e))?;
+
+        Self::process_record(record, network, subnet)
+    }
+}
+
+impl Component {
+    /// Parse network context from NATS subject
+    fn parse_subject_context(subject: &str) -> Result<(String, String), String> {
+        // ducklake.address_transactions.ethereum.mainnet.write
+        let parts: Vec<&str> = subject.split('.').collect();
+        if parts.len() == 5 {
+            Ok((parts[2].to_string(), parts[3].to_string()))
+        } else {
+            Err(format!("Invalid subject pattern: {}", subject))
+        }
+    }
+
+    /// Attribute a record to its entity, update the window and publish results
+    fn process_record(
+        record: AddressTransactionRecord,
+        network: String,
+        subnet: String,
+    ) -> Result<(), String> {
+        let Some(link) = Self::lookup_entity(&record.chain_id, &record.address) else {
+            return Ok(());
+        };
+
+        let is_internal = record
+            .counterparty_address
+            .as_deref()
+            .and_then(|counterparty| Self::lookup_entity(&record.chain_id, counterparty))
+            .is_some_and(|other| other.entity_id == link.entity_id);
+
+        let value_usd = Self::native_value(&network, &record)
+            .zip(Self::native_price_usd(&network))
+            .map(|(amount, price)| amount * price);
+
+        let window_key = retention_policy::ENTITY_ACTIVITY_WINDOW.key(&link.entity_id);
+        let mut window: EntityWindow = Self::get_json(&window_key).unwrap_or_default();
+
+        let accepted = window.record(&WindowEvent {
+            block_timestamp: record.block_timestamp,
+            chain_id: &record.chain_id,
+            is_sender: record.is_sender,
+            is_internal,
+            value_usd,
+        });
+        if !accepted {
+            eprintln!(
+                "[ENTITY-ACTIVITY] ⚠️ Event {} for entity {} is older than the 24h window",
+                record.transaction_hash, link.entity_id
+            );
+        }
+
+        let config: AggregatorConfig = Self::get_json("entity_activity:config").unwrap_or_default();
+        let large_movement =
+            window.check_large_movement(config.large_movement_usd, record.block_timestamp / 3600);
+        let totals = window.totals();
+
+        Self::set_json(&window_key, &window)?;
+
+        let ducklake_record = Self::build_ducklake_record(
+            &record,
+            &link,
+            &network,
+            &subnet,
+            is_internal,
+            value_usd,
+            &totals,
+        );
+        let ducklake_subject = format!("ducklake.entity_activity.{}.{}.write", network, subnet);
+        Self::publish_json(&ducklake_subject, &ducklake_record)?;
+
+        let mut event = EntityActivityEvent {
+            entity_id: link.entity_id.clone(),
+            entity_label: link.label.clone(),
+            chain_id: record.chain_id.clone(),
+            address: record.address.clone(),
+            transaction_hash: record.transaction_hash.clone(),
+            block_timestamp: record.block_timestamp,
+            window: totals,
+            threshold_usd: None,
+        };
+        Self::publish_json("entities.activity.updated", &event)?;
+
+        if large_movement {
+            event.threshold_usd = Some(config.large_movement_usd);
+            Self::publish_json("entities.activity.large_movement", &event)?;
+            eprintln!(
+                "[ENTITY-ACTIVITY] 🚨 Entity {} moved ${:.2} across {} chain(s) in 24h",
+                link.entity_id, event.window.outflow_usd, event.window.chain_count
+            );
+        }
+
+        Ok(())
+    }
+
+    fn build_ducklake_record(
+        record: &AddressTransactionRecord,
+        link: &EntityLink,
+        network: &str,
+        subnet: &str,
+        is_internal: bool,
+        value_usd: Option<f64>,
+        totals: &WindowTotals,
+    ) -> DuckLakeEntityActivityRecord {
+        let block_date = Utc
+            .timestamp_opt(record.block_timestamp as i64, 0)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| record.block_date.clone());
+
+        DuckLakeEntityActivityRecord {
+            chain_id: record.chain_id.clone(),
+            block_date,
+            entity_id: link.entity_id.clone(),
+            entity_label: link.label.clone(),
+            address: record.address.clone(),
+            network: network.to_string(),
+            subnet: subnet.to_string(),
+            transaction_hash: record.transaction_hash.clone(),
+            block_number: record.block_number,
+            block_timestamp: record.block_timestamp,
+            is_sender: record.is_sender,
+            counterparty_address: record.counterparty_address.clone(),
+            is_internal,
+            value: record.value.clone(),
+            value_usd,
+            transaction_type: record.transaction_type.clone(),
+            transaction_subtype: record.transaction_subtype.clone(),
+            window_inflow_usd_24h: totals.inflow_usd,
+            window_outflow_usd_24h: totals.outflow_usd,
+            window_tx_count_24h: totals.tx_count,
+            window_chain_count_24h: totals.chain_count,
+        }
+    }
+
+    /// Resolve an address to its entity, preferring a chain-specific link
+    fn lookup_entity(chain_id: &str, address: &str) -> Option<EntityLink> {
+        let address = Self::normalize_address(address);
+        Self::get_json(&format!("entity:address:{}:{}", chain_id, address))
+            .or_else(|| Self::get_json(&format!("entity:address:{}", address)))
+    }
+
+    /// Lowercase hex addresses; base58 addresses (Tron, Solana) are case-sensitive
+    fn normalize_address(address: &str) -> String {
+        if address.starts_with("0x") || address.starts_with("0X") {
+            address.to_lowercase()
+        } else {
+            address.to_string()
+        }
+    }
+
+    /// Native asset amount moved by the record, if its value is in native units
+    fn native_value(network: &str, record: &AddressTransactionRecord) -> Option<f64> {
+        if let Some(subtype) = record.transaction_subtype.as_deref() {
+            if TOKEN_SUBTYPES.contains(&subtype.to_lowercase().as_str()) {
+                return None;
+            }
+        }
+
+        let base_units = record.value.as_deref()?.trim().parse::<u128>().ok()?;
+        Some(base_units as f64 / 10f64.powi(Self::native_decimals(network) as i32))
+    }
+
+    /// Decimals of the chain's native asset base unit
+    fn native_decimals(network: &str) -> u32 {
+        match network.to_lowercase().as_str() {
+            "bitcoin" | "btc" => 8,
+            "solana" | "sol" => 9,
+            "tron" | "trx" => 6,
+            _ => 18,
+        }
+    }
+
+    fn native_price_usd(network: &str) -> Option<f64> {
+        Self::get_from_redis(&format!("price:native:{}", network.to_lowercase()))?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|price| *price > 0.0)
+    }
+
+    /// Get value from Redis
+    fn get_from_redis(key: &str) -> Option<String> {
+        let bucket = wasi::keyvalue::store::open("default").ok()?;
+        let bytes = bucket.get(key).ok()??;
+        String::from_utf8(bytes).ok()
+    }
+
+    fn get_json<T: serde::de::DeserializeOwned>(key: &str) -> Option<T> {
+        serde_json::from_str(&Self::get_from_redis(key)?).ok()
+    }
+
+    fn set_json<T: Serialize>(key: &str, value: &T) -> Result<(), String> {
+        let payload =
+            serde_json::to_vec(value).map_err(|e| format!("Failed to serialize {}: {}", key, e))?;
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        bucket
+            .set(key, &payload)
+            .map_err(|e| format!("Failed to set key {}: {:?}", key, e))
+    }
+
+    fn publish_json<T: Serialize>(subject: &str, value: &T) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
+        let body = serde_json::to_vec(value)
+            .map_err(|e| format!("Failed to serialize payload for {}: {}", subject, e))?;
+        let msg = types::BrokerMessage {
+            subject: subject_registry::prefixed(subject),
+            body,
+            reply_to: None,
+        };
+
+        consumer::publish(&msg)
+            .map_err(|e| format!("Failed to publish to {}: {:?}", subject, e))?;
+
+        eprintln!("[ENTITY-ACTIVITY] ✅ Published to: {}", subject);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(subtype: Option<&str>, value: &str) -> AddressTransactionRecord {
+        AddressTransactionRecord {
+            chain_id: "ethereum_mainnet".to_string(),
+            block_date: "2024-01-01".to_string(),
+            address: "0xabc".to_string(),
+            transaction_hash: "0xhash".to_string(),
+            block_number: 19_000_000,
+            block_timestamp: 1_704_067_200,
+            is_sender: true,
+            counterparty_address: Some("0xdef".to_string()),
+            value: Some(value.to_string()),
+            transaction_type: Some("TRANSFER".to_string()),
+            transaction_subtype: subtype.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_subject_context() {
+        let (network, subnet) =
+            Component::parse_subject_context("ducklake.address_transactions.polygon.mainnet.write")
+                .unwrap();
+        assert_eq!(network, "polygon");
+        assert_eq!(subnet, "mainnet");
+        assert!(Component::parse_subject_context("ducklake.address_transactions.write").is_err());
+    }
+
+    #[test]
+    fn test_native_value_uses_chain_decimals() {
+        let eth = record(Some("native"), "1500000000000000000");
+        assert_eq!(Component::native_value("ethereum", &eth), Some(1.5));
+
+        let trx = record(None, "2500000");
+        assert_eq!(Component::native_value("tron", &trx), Some(2.5));
+
+        let token = record(Some("ERC20"), "1000");
+        assert_eq!(Component::native_value("ethereum", &token), None);
+    }
+
+    #[test]
+    fn test_normalize_address_keeps_base58_case() {
+        assert_eq!(Component::normalize_address("0xABCdef"), "0xabcdef");
+        assert_eq!(
+            Component::normalize_address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"),
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+        );
+    }
+
+    #[test]
+    fn test_build_ducklake_record_carries_window() {
+        let record = record(Some("native"), "1000000000000000000");
+        let link = EntityLink {
+            entity_id: "entity-1".to_string(),
+            label: Some("Exchange Hot Wallets".to_string()),
+        };
+        let totals = WindowTotals {
+            inflow_usd: 10.0,
+            outflow_usd: 3_000.0,
+            tx_count: 4,
+            chain_count: 2,
+        };
+
+        let ducklake = Component::build_ducklake_record(
+            &record,
+            &link,
+            "ethereum",
+            "mainnet",
+            false,
+            Some(3_000.0),
+            &totals,
+        );
+
+        assert_eq!(ducklake.entity_id, "entity-1");
+        assert_eq!(ducklake.block_date, "2024-01-01");
+        assert_eq!(ducklake.window_outflow_usd_24h, 3_000.0);
+        assert_eq!(ducklake.window_chain_count_24h, 2);
+
+        let json = serde_json::to_value(&ducklake).unwrap();
+        assert_eq!(json["entity_label"], "Exchange Hot Wallets");
+        assert_eq!(json["value_usd"], 3_000.0);
+    }
+
+    #[test]
+    fn test_entity_link_label_optional() {
+        let link: EntityLink = serde_json::from_str(r#"{"entity_id":"e1"}"#).unwrap();
+        assert_eq!(link.label, None);
+
+        let config: AggregatorConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.large_movement_usd, DEFAULT_LARGE_MOVEMENT_USD);
+    }
+}
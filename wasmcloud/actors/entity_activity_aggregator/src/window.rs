@@ -0,0 +1,209 @@
+//! Rolling 24h activity window per entity
+//!
+//! Activity is bucketed by hour so the window can be persisted as a small JSON
+//! document in Redis and pruned cheaply. The window slides forward with the
+//! newest block timestamp seen for the entity, not wall clock, so replays and
+//! backfills produce the same totals as live ingestion.
+
+use serde::{Deserialize, Serialize};
+
+/// Window length in hourly buckets
+pub const WINDOW_HOURS: u64 = 24;
+
+/// Activity aggregated over one hour across all chains
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HourBucket {
+    pub hour: u64,
+    pub inflow_usd: f64,
+    pub outflow_usd: f64,
+    pub tx_count: u64,
+    pub chains: Vec<String>,
+}
+
+/// Persisted window state for one entity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityWindow {
+    pub buckets: Vec<HourBucket>,
+    /// Hour of the last large-movement alert, to fire once per window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_alert_hour: Option<u64>,
+}
+
+/// One address_transactions event attributed to an entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowEvent<'a> {
+    pub block_timestamp: u64,
+    pub chain_id: &'a str,
+    pub is_sender: bool,
+    /// Both sides belong to the entity; moves no value in or out
+    pub is_internal: bool,
+    pub value_usd: Option<f64>,
+}
+
+/// Cross-chain totals over the window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowTotals {
+    pub inflow_usd: f64,
+    pub outflow_usd: f64,
+    pub tx_count: u64,
+    pub chain_count: u32,
+}
+
+impl EntityWindow {
+    /// Add an event and drop buckets that fell out of the window
+    ///
+    /// Returns false when the event is older than the window and was ignored.
+    pub fn record(&mut self, event: &WindowEvent) -> bool {
+        let hour = event.block_timestamp / 3600;
+        let newest = self
+            .buckets
+            .iter()
+            .map(|b| b.hour)
+            .max()
+            .unwrap_or(hour)
+            .max(hour);
+
+        if hour + WINDOW_HOURS <= newest {
+            return false;
+        }
+        self.buckets.retain(|b| b.hour + WINDOW_HOURS > newest);
+
+        let bucket = match self.buckets.iter().position(|b| b.hour == hour) {
+            Some(index) => &mut self.buckets[index],
+            None => {
+                self.buckets.push(HourBucket {
+                    hour,
+                    ..Default::default()
+                });
+                self.buckets.sort_by_key(|b| b.hour);
+                let index = self.buckets.iter().position(|b| b.hour == hour).unwrap();
+                &mut self.buckets[index]
+            }
+        };
+
+        // Internal moves show up once per side; count the transaction only on the sender row
+        if !event.is_internal || event.is_sender {
+            bucket.tx_count += 1;
+        }
+        if !event.is_internal {
+            let usd = event.value_usd.unwrap_or(0.0);
+            if event.is_sender {
+                bucket.outflow_usd += usd;
+            } else {
+                bucket.inflow_usd += usd;
+            }
+        }
+        if !bucket.chains.iter().any(|c| c == event.chain_id) {
+            bucket.chains.push(event.chain_id.to_string());
+        }
+
+        true
+    }
+
+    /// Totals across all buckets in the window
+    pub fn totals(&self) -> WindowTotals {
+        let mut chains: Vec<&str> = Vec::new();
+        let mut totals = WindowTotals::default();
+
+        for bucket in &self.buckets {
+            totals.inflow_usd += bucket.inflow_usd;
+            totals.outflow_usd += bucket.outflow_usd;
+            totals.tx_count += bucket.tx_count;
+            for chain in &bucket.chains {
+                if !chains.contains(&chain.as_str()) {
+                    chains.push(chain);
+                }
+            }
+        }
+        totals.chain_count = chains.len() as u32;
+        totals
+    }
+
+    /// Whether outflow crossed the threshold and no alert fired in the current window
+    ///
+    /// Marks the alert as fired when returning true.
+    pub fn check_large_movement(&mut self, threshold_usd: f64, now_hour: u64) -> bool {
+        if self.totals().outflow_usd <= threshold_usd {
+            return false;
+        }
+        if let Some(last) = self.last_alert_hour {
+            if last + WINDOW_HOURS > now_hour {
+                return false;
+            }
+        }
+        self.last_alert_hour = Some(now_hour);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(ts: u64, chain: &str, is_sender: bool, usd: f64) -> WindowEvent<'_> {
+        WindowEvent {
+            block_timestamp: ts,
+            chain_id: chain,
+            is_sender,
+            is_internal: false,
+            value_usd: Some(usd),
+        }
+    }
+
+    #[test]
+    fn test_totals_merge_chains() {
+        let mut window = EntityWindow::default();
+        assert!(window.record(&event(3_600, "ethereum_mainnet", true, 400_000.0)));
+        assert!(window.record(&event(7_200, "polygon_mainnet", true, 700_000.0)));
+        assert!(window.record(&event(7_300, "ethereum_mainnet", false, 50_000.0)));
+
+        let totals = window.totals();
+        assert_eq!(totals.outflow_usd, 1_100_000.0);
+        assert_eq!(totals.inflow_usd, 50_000.0);
+        assert_eq!(totals.tx_count, 3);
+        assert_eq!(totals.chain_count, 2);
+        assert_eq!(window.buckets.len(), 2);
+    }
+
+    #[test]
+    fn test_window_prunes_old_buckets_and_ignores_late_events() {
+        let mut window = EntityWindow::default();
+        window.record(&event(0, "ethereum_mainnet", true, 10.0));
+        window.record(&event(24 * 3_600, "ethereum_mainnet", true, 20.0));
+
+        assert_eq!(window.buckets.len(), 1);
+        assert_eq!(window.totals().outflow_usd, 20.0);
+
+        assert!(!window.record(&event(10, "ethereum_mainnet", true, 5.0)));
+        assert_eq!(window.totals().outflow_usd, 20.0);
+    }
+
+    #[test]
+    fn test_internal_moves_excluded_from_flows() {
+        let mut window = EntityWindow::default();
+        let mut sent = event(3_600, "ethereum_mainnet", true, 1_000.0);
+        sent.is_internal = true;
+        let mut received = event(3_600, "ethereum_mainnet", false, 1_000.0);
+        received.is_internal = true;
+
+        window.record(&sent);
+        window.record(&received);
+
+        let totals = window.totals();
+        assert_eq!(totals.outflow_usd, 0.0);
+        assert_eq!(totals.inflow_usd, 0.0);
+        assert_eq!(totals.tx_count, 1);
+    }
+
+    #[test]
+    fn test_large_movement_fires_once_per_window() {
+        let mut window = EntityWindow::default();
+        window.record(&event(3_600, "ethereum_mainnet", true, 600_000.0));
+        assert!(!window.check_large_movement(1_000_000.0, 1));
+
+        window.record(&event(7_200, "arbitrum_one", true, 600_000.0));
+        assert!(window.check_large_movement(1_000_000.0, 2));
+        assert!(!window.check_large_movement(1_000_000.0, 3));
+        assert!(window.check_large_movement(1_000_000.0, 26));
+    }
+}
@@ -0,0 +1,238 @@
+//! Deployment export webhooks
+//!
+//! External services register interest in new deployments by writing a
+//! subscription list to Redis under `deployment_webhooks:subscriptions`. Each
+//! processed deployment is matched against every enabled subscription and, on a
+//! match, handed to the webhook notification provider on
+//! `notifications.send.immediate.webhook`. Endpoint URL, auth and retries come
+//! from the subscriber's `webhook:config:{user_id}` entry, as for alert webhooks.
+
+use crate::{BytecodePattern, ContractType, ProcessedContractCreation};
+use serde::{Deserialize, Serialize};
+
+/// Redis key holding the JSON array of subscriptions
+pub const SUBSCRIPTIONS_KEY: &str = "deployment_webhooks:subscriptions";
+
+/// Subject consumed by the webhook notification provider
+pub const WEBHOOK_SUBJECT: &str = "notifications.send.immediate.webhook";
+
+/// A registered deployment feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentSubscription {
+    pub subscription_id: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub filter: DeploymentFilter,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Deployment filters; empty lists and missing bounds match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeploymentFilter {
+    /// Chain ids ("ethereum_mainnet") or bare networks ("ethereum")
+    #[serde(default)]
+    pub chains: Vec<String>,
+    /// ContractType names, e.g. "ERC20Token", "ProxyContract", "Unknown"
+    #[serde(default)]
+    pub contract_types: Vec<String>,
+    /// Bytecode hashes; identical bytecode shares a template id
+    #[serde(default)]
+    pub template_ids: Vec<String>,
+    #[serde(default)]
+    pub min_risk_score: Option<u8>,
+    #[serde(default)]
+    pub max_risk_score: Option<u8>,
+}
+
+/// Attributes of a deployment that filters are evaluated against
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeploymentFeatures {
+    pub chain_id: String,
+    pub contract_type: String,
+    pub template_id: String,
+    pub risk_score: u8,
+}
+
+impl DeploymentFeatures {
+    pub fn from_deployment(deployment: &ProcessedContractCreation) -> Self {
+        let contract_type = deployment
+            .contract_type
+            .as_ref()
+            .unwrap_or(&ContractType::Unknown);
+
+        Self {
+            chain_id: format!("{}_{}", deployment.network, deployment.subnet).to_lowercase(),
+            contract_type: format!("{:?}", contract_type),
+            template_id: deployment.bytecode_hash.clone(),
+            risk_score: risk_score(deployment),
+        }
+    }
+}
+
+impl DeploymentFilter {
+    pub fn matches(&self, features: &DeploymentFeatures) -> bool {
+        let chain_matches = self.chains.is_empty()
+            || self.chains.iter().any(|chain| {
+                let chain = chain.to_lowercase();
+                features.chain_id == chain || features.chain_id.starts_with(&format!("{}_", chain))
+            });
+        let type_matches = self.contract_types.is_empty()
+            || self
+                .contract_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&features.contract_type));
+        let template_matches = self.template_ids.is_empty()
+            || self
+                .template_ids
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&features.template_id));
+        let risk_matches = self
+            .min_risk_score
+            .is_none_or(|min| features.risk_score >= min)
+            && self
+                .max_risk_score
+                .is_none_or(|max| features.risk_score <= max);
+
+        chain_matches && type_matches && template_matches && risk_matches
+    }
+}
+
+/// Heuristic 0-100 deployment risk score from bytecode analysis
+///
+/// Upgradeable proxies can swap logic after review, unclassified bytecode has
+/// no known interface, and first-time deployers have no history to go on.
+pub fn risk_score(deployment: &ProcessedContractCreation) -> u8 {
+    let mut score: u8 = 0;
+
+    if deployment.is_proxy {
+        score += 35;
+    }
+    if matches!(deployment.contract_type, None | Some(ContractType::Unknown)) {
+        score += 25;
+    }
+    if deployment.creator_deployment_count <= 1 {
+        score += 20;
+    }
+    if deployment
+        .detected_patterns
+        .iter()
+        .any(|p| matches!(p, BytecodePattern::MinimalProxy))
+        && deployment.implementation_address.is_none()
+    {
+        score += 20;
+    }
+
+    score.min(100)
+}
+
+/// Subscriptions whose filters match the deployment
+pub fn matching_subscriptions<'a>(
+    subscriptions: &'a [DeploymentSubscription],
+    features: &DeploymentFeatures,
+) -> Vec<&'a DeploymentSubscription> {
+    subscriptions
+        .iter()
+        .filter(|sub| sub.enabled && sub.filter.matches(features))
+        .collect()
+}
+
+/// Webhook provider request carrying the processed deployment record
+pub fn build_webhook_request(
+    subscription: &DeploymentSubscription,
+    deployment: &ProcessedContractCreation,
+    features: &DeploymentFeatures,
+    timestamp: i64,
+) -> serde_json::Value {
+    serde_json::json!({
+        "notification_id": format!(
+            "{}:{}",
+            subscription.subscription_id, deployment.transaction_hash
+        ),
+        "user_id": subscription.user_id,
+        "alert_id": subscription.subscription_id,
+        "alert_name": "contract_deployment",
+        "priority": "normal",
+        "payload": {
+            "event": "contract.deployed",
+            "subscription_id": subscription.subscription_id,
+            "chain_id": features.chain_id,
+            "contract_type": features.contract_type,
+            "template_id": features.template_id,
+            "risk_score": features.risk_score,
+            "deployment": deployment,
+        },
+        "timestamp": timestamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(risk_score: u8) -> DeploymentFeatures {
+        DeploymentFeatures {
+            chain_id: "ethereum_mainnet".to_string(),
+            contract_type: "ERC20Token".to_string(),
+            template_id: "abc123".to_string(),
+            risk_score,
+        }
+    }
+
+    fn subscription(filter: DeploymentFilter) -> DeploymentSubscription {
+        DeploymentSubscription {
+            subscription_id: "sub-1".to_string(),
+            user_id: "user-1".to_string(),
+            filter,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_chain_type_template_and_risk() {
+        let filter = DeploymentFilter {
+            chains: vec!["ethereum".to_string()],
+            contract_types: vec!["erc20token".to_string()],
+            template_ids: vec!["ABC123".to_string()],
+            min_risk_score: Some(20),
+            max_risk_score: Some(60),
+        };
+
+        assert!(filter.matches(&features(40)));
+        assert!(!filter.matches(&features(10)));
+        assert!(!filter.matches(&features(80)));
+
+        let mut other_chain = features(40);
+        other_chain.chain_id = "polygon_mainnet".to_string();
+        assert!(!filter.matches(&other_chain));
+        assert!(DeploymentFilter::default().matches(&other_chain));
+    }
+
+    #[test]
+    fn test_matching_subscriptions_skips_disabled() {
+        let mut disabled = subscription(DeploymentFilter::default());
+        disabled.subscription_id = "sub-2".to_string();
+        disabled.enabled = false;
+        let subscriptions = vec![subscription(DeploymentFilter::default()), disabled];
+
+        let matched = matching_subscriptions(&subscriptions, &features(0));
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].subscription_id, "sub-1");
+    }
+
+    #[test]
+    fn test_subscription_defaults_from_json() {
+        let subscriptions: Vec<DeploymentSubscription> = serde_json::from_str(
+            r#"[{"subscription_id":"sub-1","user_id":"user-1","filter":{"chains":["base_mainnet"]}}]"#,
+        )
+        .expect("subscriptions parse");
+
+        assert!(subscriptions[0].enabled);
+        assert_eq!(subscriptions[0].filter.chains, vec!["base_mainnet"]);
+        assert!(subscriptions[0].filter.max_risk_score.is_none());
+    }
+}
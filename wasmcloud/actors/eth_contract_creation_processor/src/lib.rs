@@ -17,17 +17,32 @@
 //!   - `alerts.evaluate.{chain}` - Alert evaluation system
 //!   - `contracts.registry.{chain}` - Contract registry updates
 //!   - `ducklake.transactions.{network}.{subnet}.write` - Historical data persistence
+//!   - `notifications.send.immediate.webhook` - Deployment feeds for matching webhook subscriptions
+//!
+//! ## ABI Seeding
+//! Solidity bytecode carries the IPFS hash of the contract's metadata JSON. When
+//! present, the metadata is fetched through the HTTP client provider and its ABI
+//! is written to `abi:{network}:{contract_address}` for the abi-decoder actor,
+//! followed by a `cache.invalidate.abi` event for in-memory ABI caches.
 
 use chrono::{TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+mod export_webhooks;
+mod metadata;
+
 // Generate WIT bindings for the processor world
 wit_bindgen::generate!({ generate_all });
 
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use wasmcloud::messaging::{consumer, types};
 
+subject_acl::component!("eth-contract-creation-processor");
+
+/// Public gateway used when `abi_metadata:ipfs_gateway` is not set in Redis
+const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
 /// Raw contract creation transaction in standard Ethereum format
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawContractCreation {
@@ -59,6 +74,23 @@ pub struct RawContractCreation {
     pub r: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
+
+    // Receipt-based fee split from eth_process_transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
+}
+
+/// Receipt-based split of the fee in wei (EIP-1559 burn/tip, refunded gas)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeAccounting {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_burned: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_tip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_refunded: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net_fee: Option<String>,
 }
 
 /// Processed contract deployment with enrichment and analysis
@@ -113,6 +145,9 @@ pub struct ProcessedContractCreation {
     pub protocol: Option<String>,     // "ERC20" | "ERC721" | "Proxy" | "Uniswap_V2" | etc.
     pub category: String,             // Always "infrastructure"
     pub decoded: serde_json::Value,   // Deployment details JSON
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
 }
 
 /// Minimal DuckLake transaction record aligned to transactions schema.
@@ -174,6 +209,14 @@ pub struct DuckLakeTransactionRecord {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_burned: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_tip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_refunded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_fee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub processor_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub correlation_id: Option<String>,
@@ -231,7 +274,20 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages containing contract deployment transactions
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
-        let subject = msg.subject.as_str();
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+
+        let Some(subject) = subject_registry::unprefixed(&msg.subject) else {
+            return Ok(());
+        };
         let (network, subnet, vm_type) = if subject.starts_with("contract-creations.")
             && subject.ends_with(".raw")
         {
@@ -327,7 +383,7 @@ impl Component {
         let correlation_id = format!(
             "{}-{}",
             raw_creation.hash,
-            chrono::Utc::now().timestamp_millis()
+            replay_clock::now().timestamp_millis()
         );
 
         // Create decoded deployment details
@@ -351,7 +407,7 @@ impl Component {
             .block_timestamp
             .as_ref()
             .map(|ts| Self::parse_hex_u64(ts))
-            .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+            .unwrap_or_else(|| replay_clock::now().timestamp() as u64);
 
         // Create processed deployment
         let processed_deployment = ProcessedContractCreation {
@@ -379,7 +435,7 @@ impl Component {
             implementation_address,
             creator_deployment_count: 1, // Would be fetched from Redis in production
             is_factory: false,           // Would be determined from deployment count
-            processed_at: chrono::Utc::now().to_rfc3339(),
+            processed_at: replay_clock::now().to_rfc3339(),
             processor_id: "eth-contract-creation-processor-actor".to_string(),
             correlation_id,
             // Standardized enrichment fields
@@ -390,14 +446,200 @@ impl Component {
             protocol,
             category: "infrastructure".to_string(),
             decoded,
+            fee_accounting: raw_creation.fee_accounting.clone(),
         };
 
         // Publish to all destinations
         Self::publish_processed_deployment(&processed_deployment, &network, &subnet)?;
 
+        // Best effort: a missing ABI only means calls to this contract stay undecoded
+        if let Err(e) =
+            Self::seed_abi_from_metadata(&network, &contract_address, &raw_creation.input)
+        {
+            eprintln!(
+                "[ETH-CONTRACT-CREATION] ⚠️ ABI not seeded for {}: {}",
+                contract_address, e
+            );
+        }
+        if let Err(e) = Self::publish_deployment_webhooks(&processed_deployment) {
+            eprintln!(
+                "[ETH-CONTRACT-CREATION] ⚠️ Deployment webhooks skipped for {}: {}",
+                contract_address, e
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Hand the deployment to the webhook provider for every matching subscription
+    fn publish_deployment_webhooks(deployment: &ProcessedContractCreation) -> Result<(), String> {
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        let Some(bytes) = bucket
+            .get(export_webhooks::SUBSCRIPTIONS_KEY)
+            .map_err(|e| format!("Failed to load webhook subscriptions: {:?}", e))?
+        else {
+            return Ok(());
+        };
+        let subscriptions: Vec<export_webhooks::DeploymentSubscription> =
+            serde_json::from_slice(&bytes)
+                .map_err(|e| format!("Invalid webhook subscriptions: {}", e))?;
+
+        let features = export_webhooks::DeploymentFeatures::from_deployment(deployment);
+        let timestamp = replay_clock::now().timestamp();
+        for subscription in export_webhooks::matching_subscriptions(&subscriptions, &features) {
+            let request = export_webhooks::build_webhook_request(
+                subscription,
+                deployment,
+                &features,
+                timestamp,
+            );
+            Self::publish_message(
+                export_webhooks::WEBHOOK_SUBJECT,
+                request.to_string().as_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Seed the abi-decoder cache from the metadata JSON referenced by the bytecode
+    ///
+    /// Writes `abi:{network}:{address}` in the abi-decoder's AbiInfo format. An
+    /// existing entry (e.g. a verified ABI uploaded manually) is never overwritten.
+    fn seed_abi_from_metadata(
+        network: &str,
+        contract_address: &str,
+        bytecode: &str,
+    ) -> Result<(), String> {
+        let Some(embedded) = metadata::extract_metadata(bytecode) else {
+            return Ok(());
+        };
+        let Some(cid) = embedded.ipfs_cid else {
+            // Swarm gateways are gone; bzzr hashes cannot be resolved
+            return Ok(());
+        };
+
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        let cache_key = retention_policy::ABI_CACHE.key(&format!(
+            "{}:{}",
+            network,
+            contract_address.to_lowercase()
+        ));
+        if bucket
+            .exists(&cache_key)
+            .map_err(|e| format!("Failed to check ABI cache: {:?}", e))?
+        {
+            return Ok(());
+        }
+
+        let gateway = bucket
+            .get("abi_metadata:ipfs_gateway")
+            .ok()
+            .flatten()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .unwrap_or_else(|| DEFAULT_IPFS_GATEWAY.to_string());
+        let metadata_json = Self::http_get(&format!("{}/{}", gateway.trim_end_matches('/'), cid))?;
+        let abi_json = metadata::abi_from_metadata_json(&metadata_json)
+            .ok_or_else(|| format!("Metadata {} has no output.abi", cid))?;
+
+        let abi_info = serde_json::json!({
+            "address": contract_address.to_lowercase(),
+            "network": network,
+            "abi_json": abi_json,
+            "source": "ipfs_metadata",
+            "verified": false,
+            "cached_at": replay_clock::now().to_rfc3339(),
+        });
+        bucket
+            .set(&cache_key, abi_info.to_string().as_bytes())
+            .map_err(|e| format!("Failed to cache ABI: {:?}", e))?;
+        Self::publish_abi_invalidation(&bucket, network, contract_address)?;
+
+        eprintln!(
+            "[ETH-CONTRACT-CREATION] ✅ Seeded ABI for {} from IPFS metadata {}",
+            contract_address, cid
+        );
         Ok(())
     }
 
+    /// Tell in-memory ABI caches (abi-decoder provider) to drop any stale entry
+    fn publish_abi_invalidation(
+        bucket: &wasi::keyvalue::store::Bucket,
+        network: &str,
+        contract_address: &str,
+    ) -> Result<(), String> {
+        let kind = cache_invalidation::InvalidationKind::Abi;
+        let version = wasi::keyvalue::atomics::increment(bucket, &kind.version_key(), 1)
+            .map_err(|e| format!("Failed to bump ABI registry version: {:?}", e))?;
+
+        let event = cache_invalidation::InvalidationEvent::new(
+            kind,
+            vec![format!("{}:{}", network, contract_address.to_lowercase())],
+            version,
+            "eth-contract-creation-processor-actor",
+            &replay_clock::now().to_rfc3339(),
+        );
+        let payload = serde_json::to_vec(&event)
+            .map_err(|e| format!("Failed to serialize invalidation: {}", e))?;
+        Self::publish_message(&kind.subject(), &payload)
+    }
+
+    /// GET a URL through the HTTP client provider and return the body as text
+    fn http_get(url: &str) -> Result<String, String> {
+        let (scheme, rest) = match url.split_once("://") {
+            Some(("https", rest)) => (wasi::http::types::Scheme::Https, rest),
+            Some(("http", rest)) => (wasi::http::types::Scheme::Http, rest),
+            _ => return Err(format!("Unsupported URL: {}", url)),
+        };
+        let (authority, path) = match rest.split_once('/') {
+            Some((authority, path)) => (authority.to_string(), format!("/{}", path)),
+            None => (rest.to_string(), "/".to_string()),
+        };
+
+        let request = wasi::http::types::OutgoingRequest::new(wasi::http::types::Fields::new());
+        request
+            .set_method(&wasi::http::types::Method::Get)
+            .map_err(|_| "Failed to set request method".to_string())?;
+        request
+            .set_scheme(Some(&scheme))
+            .map_err(|_| "Failed to set request scheme".to_string())?;
+        request
+            .set_authority(Some(&authority))
+            .map_err(|_| "Failed to set request authority".to_string())?;
+        request
+            .set_path_with_query(Some(&path))
+            .map_err(|_| "Failed to set request path".to_string())?;
+
+        let future_response = wasi::http::outgoing_handler::handle(request, None)
+            .map_err(|e| format!("Failed to send HTTP request: {:?}", e))?;
+        wasi::io::poll::poll(&[&future_response.subscribe()]);
+
+        let response = future_response
+            .get()
+            .ok_or_else(|| "Failed to get response from future".to_string())?
+            .map_err(|e| format!("HTTP request failed: {:?}", e))?
+            .map_err(|e| format!("HTTP request failed: {:?}", e))?;
+        let status = response.status();
+        if !(200..300).contains(&status) {
+            return Err(format!("HTTP error: status {}", status));
+        }
+
+        let body = response
+            .consume()
+            .map_err(|_| "Failed to consume response body".to_string())?;
+        let stream = body
+            .stream()
+            .map_err(|_| "Failed to get response stream".to_string())?;
+        let mut bytes = Vec::new();
+        while let Ok(chunk) = stream.blocking_read(8192) {
+            bytes.extend_from_slice(&chunk);
+        }
+
+        String::from_utf8(bytes).map_err(|e| format!("Invalid UTF-8 in response: {}", e))
+    }
+
     /// Calculate contract address using CREATE formula
     /// address = keccak256(rlp([sender, nonce]))[12:]
     /// Simplified implementation - would use proper RLP encoding in production
@@ -664,8 +906,16 @@ impl Component {
 
     /// Helper to publish a message to a subject
     fn publish_message(subject: &str, payload: &[u8]) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
         let msg = types::BrokerMessage {
-            subject: subject.to_string(),
+            subject: subject_registry::prefixed(&feature_flags::dry_run::route(subject)),
             body: payload.to_vec(),
             reply_to: None,
         };
@@ -685,6 +935,10 @@ impl Component {
             .single()
             .map(|dt| dt.format("%Y-%m-%d").to_string())
             .unwrap_or_else(|| "1970-01-01".to_string());
+        let fees = processed_deployment
+            .fee_accounting
+            .clone()
+            .unwrap_or_default();
 
         DuckLakeTransactionRecord {
             chain_id: format!(
@@ -727,6 +981,10 @@ impl Component {
             v: None,
             r: None,
             s: None,
+            fee_burned: fees.fee_burned,
+            fee_tip: fees.fee_tip,
+            fee_refunded: fees.fee_refunded,
+            net_fee: fees.net_fee,
             processor_id: Some(processed_deployment.processor_id.clone()),
             correlation_id: Some(processed_deployment.correlation_id.clone()),
         }
@@ -833,6 +1091,7 @@ mod tests {
             s: Some(
                 "0xfedcba0987654321fedcba0987654321fedcba0987654321fedcba0987654321".to_string(),
             ),
+            fee_accounting: None,
         }
     }
 
@@ -1048,6 +1307,12 @@ mod tests {
             protocol: None,
             category: "infrastructure".to_string(),
             decoded: serde_json::json!({}),
+            fee_accounting: Some(FeeAccounting {
+                fee_burned: Some("45000000000000000".to_string()),
+                fee_tip: Some("5000000000000000".to_string()),
+                fee_refunded: Some("10000000000000000".to_string()),
+                net_fee: Some("50000000000000000".to_string()),
+            }),
         };
 
         let record = Component::build_ducklake_transaction_record(&processed);
@@ -1056,6 +1321,9 @@ mod tests {
         assert_eq!(record.transaction_type, "contract_deployment");
         assert_eq!(record.transaction_subtype, Some("create".to_string()));
         assert_eq!(record.transaction_fee, Some("0".to_string()));
+        assert_eq!(record.fee_burned.as_deref(), Some("45000000000000000"));
+        assert_eq!(record.fee_refunded.as_deref(), Some("10000000000000000"));
+        assert_eq!(record.net_fee.as_deref(), Some("50000000000000000"));
     }
 
     #[test]
@@ -1095,6 +1363,7 @@ mod tests {
             protocol: None,
             category: "infrastructure".to_string(),
             decoded: serde_json::json!({}),
+            fee_accounting: None,
         };
 
         let records = Component::build_address_transaction_records(&processed);
@@ -0,0 +1,200 @@
+//! Solidity bytecode metadata extraction
+//!
+//! solc appends a CBOR map to the runtime bytecode holding the hash of the
+//! contract's metadata JSON (`ipfs` or `bzzr0`/`bzzr1`) and the compiler
+//! version (`solc`). The runtime code is embedded in the creation input, and
+//! constructor arguments may follow it, so the map is located by scanning
+//! for its header rather than trusting the trailing length word.
+//!
+//! The metadata JSON published to IPFS contains the full ABI under
+//! `output.abi`, which lets new contracts be decoded without a manual upload.
+
+use serde::{Deserialize, Serialize};
+
+/// Hashes and compiler version embedded in the bytecode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BytecodeMetadata {
+    /// CIDv0 (base58 `Qm...`) of the metadata JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipfs_cid: Option<String>,
+    /// Swarm hash (hex) of the metadata JSON, for older compilers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swarm_hash: Option<String>,
+    /// Compiler version, e.g. "0.8.24"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solc_version: Option<String>,
+}
+
+/// CBOR value types solc emits in the metadata map
+enum CborValue {
+    Bytes(Vec<u8>),
+    Text(String),
+    Bool,
+}
+
+/// Find and decode the metadata map in creation or runtime bytecode
+pub fn extract_metadata(bytecode_hex: &str) -> Option<BytecodeMetadata> {
+    let bytes = hex::decode(bytecode_hex.trim_start_matches("0x")).ok()?;
+
+    // Latest match wins: factories embed child bytecode (with its own metadata) earlier on
+    (0..bytes.len())
+        .rev()
+        .filter(|&i| is_map_header(bytes[i]) && starts_with_metadata_key(&bytes[i + 1..]))
+        .find_map(|i| parse_metadata_map(&bytes[i..]))
+}
+
+/// Extract the ABI array from a solc metadata JSON document
+pub fn abi_from_metadata_json(metadata_json: &str) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata_json).ok()?;
+    let abi = metadata.get("output")?.get("abi")?;
+    if !abi.is_array() {
+        return None;
+    }
+    serde_json::to_string(abi).ok()
+}
+
+fn is_map_header(byte: u8) -> bool {
+    (0xa1..=0xa5).contains(&byte)
+}
+
+fn starts_with_metadata_key(bytes: &[u8]) -> bool {
+    [&b"\x64ipfs"[..], &b"\x65bzzr0"[..], &b"\x65bzzr1"[..]]
+        .iter()
+        .any(|key| bytes.starts_with(key))
+}
+
+fn parse_metadata_map(bytes: &[u8]) -> Option<BytecodeMetadata> {
+    let entries = (bytes[0] & 0x1f) as usize;
+    let mut offset = 1;
+    let mut metadata = BytecodeMetadata::default();
+
+    for _ in 0..entries {
+        let key = match read_value(bytes, &mut offset)? {
+            CborValue::Text(key) => key,
+            _ => return None,
+        };
+        match (key.as_str(), read_value(bytes, &mut offset)?) {
+            ("ipfs", CborValue::Bytes(hash)) => metadata.ipfs_cid = Some(base58_encode(&hash)),
+            ("bzzr0" | "bzzr1", CborValue::Bytes(hash)) => {
+                metadata.swarm_hash = Some(hex::encode(hash))
+            }
+            // Release builds store [major, minor, patch]; pre-releases store a string
+            ("solc", CborValue::Bytes(version)) if version.len() == 3 => {
+                metadata.solc_version =
+                    Some(format!("{}.{}.{}", version[0], version[1], version[2]))
+            }
+            ("solc", CborValue::Text(version)) => metadata.solc_version = Some(version),
+            _ => {}
+        }
+    }
+
+    if metadata.ipfs_cid.is_none() && metadata.swarm_hash.is_none() {
+        return None;
+    }
+    Some(metadata)
+}
+
+fn read_value(bytes: &[u8], offset: &mut usize) -> Option<CborValue> {
+    let header = *bytes.get(*offset)?;
+    *offset += 1;
+
+    if matches!(header, 0xf4 | 0xf5) {
+        return Some(CborValue::Bool);
+    }
+
+    let major = header >> 5;
+    let len = match header & 0x1f {
+        n @ 0..=23 => n as usize,
+        24 => {
+            let len = *bytes.get(*offset)? as usize;
+            *offset += 1;
+            len
+        }
+        25 => {
+            let len = u16::from_be_bytes([*bytes.get(*offset)?, *bytes.get(*offset + 1)?]);
+            *offset += 2;
+            len as usize
+        }
+        _ => return None,
+    };
+
+    let data = bytes.get(*offset..*offset + len)?.to_vec();
+    *offset += len;
+
+    match major {
+        2 => Some(CborValue::Bytes(data)),
+        3 => String::from_utf8(data).ok().map(CborValue::Text),
+        _ => None,
+    }
+}
+
+/// Bitcoin-alphabet base58, as used by IPFS CIDv0
+fn base58_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let leading_zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    let mut encoded = "1".repeat(leading_zeros);
+    encoded.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize] as char));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a2 64 "ipfs" 58 22 <34-byte multihash> 64 "solc" 43 <0.8.24> 0033
+    const IPFS_TRAILER: &str = "a264697066735822122043e2f8a5b0fc8d8b3e208cb2bff1e7e8ee7a4da8c6e0b57a4c8bc0c347912e1f64736f6c63430008180033";
+
+    #[test]
+    fn test_extract_ipfs_metadata_with_constructor_args() {
+        let bytecode = format!(
+            "0x6080604052348015600f57600080fd5b50{}{}",
+            IPFS_TRAILER, "000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045"
+        );
+
+        let metadata = extract_metadata(&bytecode).expect("metadata present");
+        assert_eq!(
+            metadata.ipfs_cid.as_deref(),
+            Some("QmSuem2LwycD1cMKKk82H8BzYevdW6SZtwwxLvxca3pSht")
+        );
+        assert_eq!(metadata.solc_version.as_deref(), Some("0.8.24"));
+        assert_eq!(metadata.swarm_hash, None);
+    }
+
+    #[test]
+    fn test_extract_swarm_metadata_and_missing_metadata() {
+        let swarm = format!("0x6080a165627a7a72305820{}0029", "aa".repeat(32));
+        let metadata = extract_metadata(&swarm).expect("swarm metadata present");
+        assert_eq!(metadata.swarm_hash, Some("aa".repeat(32)));
+        assert_eq!(metadata.ipfs_cid, None);
+
+        assert_eq!(
+            extract_metadata("0x6080604052348015600f57600080fd5b50"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_abi_from_metadata_json() {
+        let json = r#"{"compiler":{"version":"0.8.24"},"output":{"abi":[{"type":"function","name":"transfer","inputs":[],"outputs":[]}],"devdoc":{}}}"#;
+
+        let abi = abi_from_metadata_json(json).expect("abi present");
+        assert!(abi.starts_with("[{"));
+        assert!(abi.contains("\"transfer\""));
+        assert_eq!(abi_from_metadata_json(r#"{"output":{}}"#), None);
+    }
+}
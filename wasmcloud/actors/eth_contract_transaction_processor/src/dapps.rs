@@ -0,0 +1,158 @@
+//! dApp classification for contract calls
+//!
+//! Wallets reach dApps through a small set of router / front-end contracts, so
+//! the called address is a good signal for which dApp a wallet used (including
+//! WalletConnect sessions, which only surface on-chain as calls to these
+//! contracts). Knowledge-pack entries loaded into Redis under
+//! `dapp:contract:{network}:{address}` take precedence over the built-in table.
+
+use serde::{Deserialize, Serialize};
+
+/// Well-known router / front-end contracts: (network, lowercase address, dApp)
+///
+/// Balancer, Permit2 and Seaport use the same address on every EVM network and
+/// are listed under `*`.
+#[rustfmt::skip]
+const BUILTIN_DAPPS: &[(&str, &str, &str)] = &[
+    ("ethereum", "0x7a250d5630b4cf539739df2c5dacb4c659f2488d", "Uniswap"),
+    ("ethereum", "0xe592427a0aece92de3edee1f18e0157c05861564", "Uniswap"),
+    ("ethereum", "0x68b3465833fb72a70ecdf485e0e4c7bd8665fc45", "Uniswap"),
+    ("ethereum", "0x3fc91a3afd70395cd496c647d5a6cc9d4b2b7fad", "Uniswap"),
+    ("ethereum", "0xd9e1ce17f2641f24ae83637ab66a2cca9c378b9f", "SushiSwap"),
+    ("ethereum", "0x1111111254eeb25477b68fb85ed929f73a960582", "1inch"),
+    ("ethereum", "0xdef1c0ded9bec7f1a1670819833240f027b25eff", "0x"),
+    ("ethereum", "0x87870bca3f3fd6335c3f4ce8392d69350b4fa4e2", "Aave"),
+    ("ethereum", "0x7d2768de32b0b80b7a3454c06bdac94a69ddc7a9", "Aave"),
+    ("ethereum", "0xc3d688b66703497daa19211eedff47f25384cdc3", "Compound"),
+    ("ethereum", "0xae7ab96520de3a18e5e111b5eaab095312d7fe84", "Lido"),
+    ("polygon", "0xa5e0829caced8ffdd4de3c43696c57f7d7a678ff", "QuickSwap"),
+    ("*", "0xba12222222228d8ba445958a75a0704d566bf2c8", "Balancer"),
+    ("*", "0x000000000022d473030f116ddee9f6b43ac78ba3", "Uniswap"),
+    ("*", "0x00000000000000adc04c56bf30ac9d3c0aaf14dc", "OpenSea"),
+];
+
+/// Where a classification came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DappSource {
+    KnowledgePack,
+    Builtin,
+}
+
+/// dApp a contract call was attributed to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DappMatch {
+    pub dapp_name: String,
+    pub source: DappSource,
+}
+
+/// Redis key of a knowledge-pack entry for `address` on `network`
+pub fn registry_key(network: &str, address: &str) -> String {
+    retention_policy::DAPP_CONTRACT.key(&format!(
+        "{}:{}",
+        network.to_lowercase(),
+        address.to_lowercase()
+    ))
+}
+
+/// Redis counter of calls from `address` into `dapp_name` on `chain_id`
+pub fn usage_counter_key(chain_id: &str, address: &str, dapp_name: &str) -> String {
+    retention_policy::DAPP_USAGE_COUNTER.key(&format!(
+        "{}:{}:{}",
+        chain_id,
+        address.to_lowercase(),
+        slug(dapp_name)
+    ))
+}
+
+/// Classify a call to `contract_address`; `registry` resolves knowledge-pack keys
+pub fn classify(
+    network: &str,
+    contract_address: &str,
+    registry: impl Fn(&str) -> Option<String>,
+) -> Option<DappMatch> {
+    if let Some(name) = registry(&registry_key(network, contract_address))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+    {
+        return Some(DappMatch {
+            dapp_name: name,
+            source: DappSource::KnowledgePack,
+        });
+    }
+
+    let network = network.to_lowercase();
+    let address = contract_address.to_lowercase();
+    BUILTIN_DAPPS
+        .iter()
+        .find(|(net, addr, _)| (*net == "*" || *net == network) && *addr == address)
+        .map(|(_, _, name)| DappMatch {
+            dapp_name: name.to_string(),
+            source: DappSource::Builtin,
+        })
+}
+
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_builtin_and_wildcard_networks() {
+        let uniswap = classify(
+            "ethereum",
+            "0x7A250D5630B4CF539739DF2C5DACB4C659F2488D",
+            |_| None,
+        )
+        .unwrap();
+        assert_eq!(uniswap.dapp_name, "Uniswap");
+        assert_eq!(uniswap.source, DappSource::Builtin);
+
+        let balancer = classify(
+            "arbitrum",
+            "0xba12222222228d8ba445958a75a0704d566bf2c8",
+            |_| None,
+        );
+        assert_eq!(balancer.unwrap().dapp_name, "Balancer");
+
+        // Network-specific entries do not leak to other networks
+        assert!(classify(
+            "polygon",
+            "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+            |_| None
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_knowledge_pack_overrides_builtin() {
+        let registry = |key: &str| {
+            (key == "dapp:contract:ethereum:0x7a250d5630b4cf539739df2c5dacb4c659f2488d")
+                .then(|| "Uniswap V2".to_string())
+        };
+
+        let matched = classify(
+            "Ethereum",
+            "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+            registry,
+        )
+        .unwrap();
+        assert_eq!(matched.dapp_name, "Uniswap V2");
+        assert_eq!(matched.source, DappSource::KnowledgePack);
+        assert_eq!(
+            usage_counter_key("ethereum_mainnet", "0xABC", "Uniswap V2"),
+            "dapp_usage:ethereum_mainnet:0xabc:uniswap_v2"
+        );
+    }
+}
@@ -19,6 +19,17 @@
 //!   - `abi.decode.request` - ABI decode requests
 //!   - `ducklake.contract_calls.{network}.{subnet}.write` - Contract call analytics
 //!   - `ducklake.transactions.{network}.{subnet}.write` - Unified transaction history
+//!   - `ducklake.dapp_usage.{network}.{subnet}.write` - Per-address dApp usage
+//!
+//! ## dApp Classification
+//! Calls to known router / front-end contracts get a `dapp_name` (see [`dapps`]).
+//! Each classified call also bumps the caller's lifetime counter for that dApp
+//! (`dapp_usage:{chain_id}:{address}:{dapp}`) and writes a `dapp_usage` row, so
+//! users can see and alert on which dApps a watched wallet interacts with.
+//! Classification is behind the `dapp_classification` feature flag (on unless
+//! configured otherwise), rolled out per caller address.
+
+mod dapps;
 
 use chrono::{TimeZone, Utc};
 use serde::{Deserialize, Serialize};
@@ -30,6 +41,12 @@ wit_bindgen::generate!({ generate_all });
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use wasmcloud::messaging::{consumer, types};
 
+subject_acl::component!("eth-contract-transaction-processor");
+
+/// dApp attribution and usage counters; rolled out per caller address
+const DAPP_CLASSIFICATION: feature_flags::FlagSpec =
+    feature_flags::FlagSpec::new("dapp_classification", true);
+
 /// Raw contract transaction in standard Ethereum format with receipt data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawContractTransaction {
@@ -67,6 +84,42 @@ pub struct RawContractTransaction {
     // Block timestamp (not in standard transaction, but commonly provided)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub block_timestamp: Option<String>,
+
+    // Receipt gas price and ZK rollup batch data from chain plugins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
+}
+
+/// L1 batch/sequence metadata for ZK rollups (zkSync Era, Polygon zkEVM)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollupContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_tx_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_sequence_tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_verify_tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_tx_kind: Option<String>,
+}
+
+/// Receipt-based split of the fee in wei (EIP-1559 burn/tip, refunded gas)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeAccounting {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_burned: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_tip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_refunded: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net_fee: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,6 +173,7 @@ pub struct AbiDecodedParameter {
 
 /// Processed contract transaction with enrichment and analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProcessedContractTransaction {
     // Original transaction data
     pub network: String,
@@ -169,11 +223,13 @@ pub struct ProcessedContractTransaction {
     pub transaction_subtype: String,  // "swap" | "stake" | "borrow" | "transfer" | etc.
     pub protocol: Option<String>,     // "Uniswap_V2" | "Aave" | "ERC20" | etc.
     pub category: String,             // "defi" | "nft" | "governance" | "token" | etc.
+    pub dapp_name: Option<String>,    // "Uniswap" | "OpenSea" | etc. (router/front-end contract)
     pub decoded: serde_json::Value,   // Function call details JSON
 }
 
 /// Minimal DuckLake contract_calls record aligned to schema requirements.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct DuckLakeContractCallRecord {
     pub chain_id: String,
     pub block_date: String,
@@ -210,6 +266,30 @@ pub struct DuckLakeContractCallRecord {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub revert_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dapp_name: Option<String>,
+}
+
+/// DuckLake dapp_usage record: one classified contract call by `address`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeDappUsageRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub address: String,
+    pub dapp_name: String,
+    pub dapp_source: dapps::DappSource,
+    pub network: String,
+    pub subnet: String,
+    pub contract_address: String,
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub transaction_subtype: String,
+    pub category: String,
+    pub value: String,
+    pub success: bool,
+    /// Calls by `address` into this dApp so far, including this one
+    pub interaction_count: u64,
 }
 
 /// Minimal DuckLake transaction record aligned to unified transactions schema.
@@ -289,6 +369,24 @@ pub struct DuckLakeTransactionRecord {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_tx_index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_sequence_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_verify_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l2_tx_kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_burned: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_tip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_refunded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_fee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub processor_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub correlation_id: Option<String>,
@@ -315,20 +413,25 @@ pub struct DuckLakeAddressTransactionRecord {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum FunctionCategory {
-    Transfer,   // Maps to transaction_subtype: "transfer"
-    Approval,   // Maps to transaction_subtype: "approve"
-    Swap,       // Maps to transaction_subtype: "swap"
-    Stake,      // Maps to transaction_subtype: "stake"
-    Unstake,    // Maps to transaction_subtype: "unstake"
-    Borrow,     // Maps to transaction_subtype: "borrow"
-    Repay,      // Maps to transaction_subtype: "repay"
-    Liquidate,  // Maps to transaction_subtype: "liquidate"
-    Governance, // Maps to transaction_subtype: "governance"
-    Unknown,    // Maps to transaction_subtype: "unknown"
+    Transfer,        // Maps to transaction_subtype: "transfer"
+    Approval,        // Maps to transaction_subtype: "approve"
+    Swap,            // Maps to transaction_subtype: "swap"
+    Stake,           // Maps to transaction_subtype: "stake"
+    Unstake,         // Maps to transaction_subtype: "unstake"
+    Borrow,          // Maps to transaction_subtype: "borrow"
+    Repay,           // Maps to transaction_subtype: "repay"
+    Liquidate,       // Maps to transaction_subtype: "liquidate"
+    Governance,      // Maps to transaction_subtype: "governance"
+    PerpOpen,        // Maps to transaction_subtype: "perp_open"
+    PerpClose,       // Maps to transaction_subtype: "perp_close"
+    PerpLiquidation, // Maps to transaction_subtype: "perp_liquidation"
+    Unknown,         // Maps to transaction_subtype: "unknown"
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum TransactionStatus {
     Success,
     Failed,
@@ -337,6 +440,7 @@ pub enum TransactionStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum DecodingStatus {
     Success,
     Pending,
@@ -346,6 +450,7 @@ pub enum DecodingStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EventLog {
     pub event_signature: String,
     pub event_name: Option<String>,
@@ -355,6 +460,7 @@ pub struct EventLog {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct DecodedParameter {
     pub name: String,
     pub param_type: String,
@@ -370,20 +476,33 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages containing contract transactions or decoded responses
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+
+        let Some(subject) = subject_registry::unprefixed(&msg.subject) else {
+            return Ok(());
+        };
+
         // Handle decoded transaction responses (abi-decoder actor)
-        if Self::is_contracts_decoded_subject(&msg.subject)
-            || msg.subject == "transactions.decoded.evm"
-        {
+        if Self::is_contracts_decoded_subject(subject) || subject == "transactions.decoded.evm" {
             return Self::handle_decoded_response(msg);
         }
 
         // Handle raw contract transactions
-        if !msg.subject.starts_with("contract-transactions.") || !msg.subject.ends_with(".raw") {
+        if !subject.starts_with("contract-transactions.") || !subject.ends_with(".raw") {
             return Ok(());
         }
 
         // Extract network context from subject: contract-transactions.{network}.{subnet}.{vm_type}.raw
-        let (network, subnet, vm_type) = Self::parse_subject_context(&msg.subject)?;
+        let (network, subnet, vm_type) = Self::parse_subject_context(subject)?;
 
         // Parse the contract transaction from the message
         let raw_transaction: RawContractTransaction = serde_json::from_slice(&msg.body)
@@ -490,7 +609,7 @@ impl Component {
             .block_timestamp
             .as_ref()
             .map(|ts| Self::parse_hex_u64(ts))
-            .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+            .unwrap_or_else(|| replay_clock::now().timestamp() as u64);
 
         // Extract function selector (first 4 bytes / 8 hex chars of input)
         let function_selector = Self::extract_function_selector(&raw_tx.input);
@@ -512,8 +631,12 @@ impl Component {
         // Process event logs
         let events = Self::process_event_logs(&raw_tx.logs);
 
-        // Calculate transaction fee
-        let transaction_fee_wei = Self::calculate_transaction_fee(gas_used, &raw_tx.gas_price);
+        // Calculate transaction fee (receipt effective gas price when available)
+        let fee_gas_price = raw_tx
+            .effective_gas_price
+            .as_deref()
+            .unwrap_or(&raw_tx.gas_price);
+        let transaction_fee_wei = Self::calculate_transaction_fee(gas_used, fee_gas_price);
 
         // Determine enrichment fields
         let (transaction_currency, transaction_value) =
@@ -522,6 +645,11 @@ impl Component {
         let transaction_subtype = Self::category_to_subtype(&function_category);
         let protocol = Self::detect_protocol(&function_selector, &raw_tx.to);
         let category = Self::determine_category(&function_category, &protocol);
+        let dapp = if Self::flag_enabled(DAPP_CLASSIFICATION, &network, &subnet, &raw_tx.from) {
+            dapps::classify(&network, &raw_tx.to, Self::get_from_redis)
+        } else {
+            None
+        };
 
         // Create decoded JSON
         let decoded = Self::create_decoded_json(
@@ -536,7 +664,7 @@ impl Component {
         );
 
         // Generate correlation ID
-        let correlation_id = format!("{}-{}", raw_tx.hash, chrono::Utc::now().timestamp_millis());
+        let correlation_id = format!("{}-{}", raw_tx.hash, replay_clock::now().timestamp_millis());
 
         // Create processed transaction
         let processed_tx = ProcessedContractTransaction {
@@ -559,12 +687,17 @@ impl Component {
             gas_price: raw_tx.gas_price.clone(),
             transaction_fee_wei,
             decoded_params: None, // Would be populated when decoder responds
-            decoding_status: DecodingStatus::NotRequested,
+            // Unknown functions stay Pending until the decoder writes its outcome back
+            decoding_status: if is_popular {
+                DecodingStatus::NotRequested
+            } else {
+                DecodingStatus::Pending
+            },
             events,
             event_count: raw_tx.logs.len() as u32,
             is_popular_function: is_popular,
             interaction_frequency: 0, // Would be fetched from Redis in production
-            processed_at: chrono::Utc::now().to_rfc3339(),
+            processed_at: replay_clock::now().to_rfc3339(),
             processor_id: "eth-contract-transaction-processor-actor".to_string(),
             correlation_id,
             transaction_type: "contract_call".to_string(),
@@ -573,11 +706,15 @@ impl Component {
             transaction_subtype,
             protocol,
             category,
+            dapp_name: dapp.as_ref().map(|dapp| dapp.dapp_name.clone()),
             decoded,
         };
 
         // Publish to all destinations
         Self::publish_processed_transaction(&processed_tx, &raw_tx, &network, &subnet)?;
+        if let Some(dapp) = &dapp {
+            Self::publish_dapp_usage(&processed_tx, dapp)?;
+        }
 
         // Request ABI decoding if needed (for unknown functions or important contracts)
         if !is_popular {
@@ -628,6 +765,18 @@ impl Component {
             "0xda95691a" => FunctionCategory::Governance, // propose
             "0x15373e3d" => FunctionCategory::Governance, // vote
 
+            // Perpetuals (GMX v1 routers, Perp v2 clearing house)
+            "0xf2ae372f" => FunctionCategory::PerpOpen, // createIncreasePosition
+            "0x5b88e8c6" => FunctionCategory::PerpOpen, // createIncreasePositionETH
+            "0xb7ddc992" => FunctionCategory::PerpOpen, // increasePosition
+            "0xb6b1b6c3" => FunctionCategory::PerpOpen, // openPosition
+            "0x7be7d141" => FunctionCategory::PerpClose, // createDecreasePosition
+            "0x90205d8c" => FunctionCategory::PerpClose, // decreasePosition
+            "0x00aa9a89" => FunctionCategory::PerpClose, // closePosition
+            "0xde2ea948" => FunctionCategory::PerpLiquidation, // liquidatePosition
+            "0x65d461bd" => FunctionCategory::PerpLiquidation, // liquidate(address,address,int256)
+            "0x86b9d81f" => FunctionCategory::PerpLiquidation, // liquidate(address,address)
+
             _ => FunctionCategory::Unknown,
         }
     }
@@ -661,6 +810,27 @@ impl Component {
                 "borrow(address,uint256,uint256,uint16,address)",
             ),
             ("0x573ade81", "repay(address,uint256,uint256,address)"),
+            // Perpetuals
+            (
+                "0xf2ae372f",
+                "createIncreasePosition(address[],address,uint256,uint256,uint256,bool,uint256,uint256,bytes32,address)",
+            ),
+            (
+                "0x7be7d141",
+                "createDecreasePosition(address[],address,uint256,uint256,bool,address,uint256,uint256,uint256,bool,address)",
+            ),
+            (
+                "0xde2ea948",
+                "liquidatePosition(address,address,address,bool,address)",
+            ),
+            (
+                "0xb6b1b6c3",
+                "openPosition((address,bool,bool,uint256,uint256,uint256,uint160,bytes32))",
+            ),
+            (
+                "0x00aa9a89",
+                "closePosition((address,uint160,uint256,uint256,bytes32))",
+            ),
         ]
         .iter()
         .cloned()
@@ -779,6 +949,9 @@ impl Component {
             FunctionCategory::Repay => "repay".to_string(),
             FunctionCategory::Liquidate => "liquidate".to_string(),
             FunctionCategory::Governance => "governance".to_string(),
+            FunctionCategory::PerpOpen => "perp_open".to_string(),
+            FunctionCategory::PerpClose => "perp_close".to_string(),
+            FunctionCategory::PerpLiquidation => "perp_liquidation".to_string(),
             FunctionCategory::Unknown => "unknown".to_string(),
         }
     }
@@ -796,6 +969,15 @@ impl Component {
             // ERC20
             "0xa9059cbb" | "0x095ea7b3" | "0x23b872dd" => Some("ERC20".to_string()),
 
+            // GMX v1 position routers
+            "0xf2ae372f" | "0x5b88e8c6" | "0xb7ddc992" | "0x7be7d141" | "0x90205d8c"
+            | "0xde2ea948" => Some("GMX".to_string()),
+
+            // Perpetual Protocol v2 clearing house
+            "0xb6b1b6c3" | "0x00aa9a89" | "0x65d461bd" | "0x86b9d81f" => {
+                Some("Perpetual_Protocol".to_string())
+            }
+
             _ => None,
         }
     }
@@ -815,7 +997,10 @@ impl Component {
             | FunctionCategory::Unstake
             | FunctionCategory::Borrow
             | FunctionCategory::Repay
-            | FunctionCategory::Liquidate => "defi".to_string(),
+            | FunctionCategory::Liquidate
+            | FunctionCategory::PerpOpen
+            | FunctionCategory::PerpClose
+            | FunctionCategory::PerpLiquidation => "defi".to_string(),
             FunctionCategory::Governance => "governance".to_string(),
             FunctionCategory::Unknown => "unknown".to_string(),
         }
@@ -913,6 +1098,9 @@ impl Component {
     }
 
     /// Request ABI decoding for a transaction
+    ///
+    /// The decode-queue provider persists the request and retries it until the
+    /// decoder answers.
     fn request_abi_decode(
         raw_tx: &RawContractTransaction,
         network: &str,
@@ -922,9 +1110,10 @@ impl Component {
             "transaction_hash": raw_tx.hash,
             "network": network,
             "subnet": subnet,
-            "contract_address": raw_tx.to,
+            "to_address": raw_tx.to,
             "function_selector": Self::extract_function_selector(&raw_tx.input),
             "input_data": raw_tx.input,
+            "request_id": format!("contract-call-{}", raw_tx.hash),
         });
 
         let payload = serde_json::to_vec(&decode_request)
@@ -980,10 +1169,71 @@ impl Component {
         Ok(())
     }
 
+    /// Count the call against the caller's dApp usage and write the usage row
+    fn publish_dapp_usage(
+        processed_tx: &ProcessedContractTransaction,
+        dapp: &dapps::DappMatch,
+    ) -> Result<(), String> {
+        let chain_id = format!("{}_{}", processed_tx.network, processed_tx.subnet);
+        let counter_key =
+            dapps::usage_counter_key(&chain_id, &processed_tx.caller_address, &dapp.dapp_name);
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        let interaction_count = wasi::keyvalue::atomics::increment(&bucket, &counter_key, 1)
+            .map_err(|e| format!("Failed to increment {}: {:?}", counter_key, e))?;
+
+        let record = Self::build_dapp_usage_record(processed_tx, dapp, interaction_count);
+        let payload = serde_json::to_vec(&record)
+            .map_err(|e| format!("Failed to serialize dapp usage: {}", e))?;
+        let subject = format!(
+            "ducklake.dapp_usage.{}.{}.write",
+            processed_tx.network, processed_tx.subnet
+        );
+        Self::publish_message(&subject, &payload)
+    }
+
+    /// Evaluate a feature flag for a message and count the outcome
+    fn flag_enabled(
+        flag: feature_flags::FlagSpec,
+        network: &str,
+        subnet: &str,
+        unit: &str,
+    ) -> bool {
+        let chain_id = format!("{}_{}", network, subnet);
+        let context = feature_flags::FlagContext {
+            chain_id: Some(&chain_id),
+            tenant_id: None,
+            unit,
+        };
+        let evaluation = flag.evaluate(&context, |key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+        // Counting is best-effort; a failed increment must not change processing
+        if let Ok(bucket) = wasi::keyvalue::store::open("default") {
+            let _ = wasi::keyvalue::atomics::increment(&bucket, &evaluation.counter_key(), 1);
+        }
+        evaluation.enabled
+    }
+
+    /// Get a UTF-8 value from Redis; lookup failures count as a miss
+    fn get_from_redis(key: &str) -> Option<String> {
+        let bucket = wasi::keyvalue::store::open("default").ok()?;
+        let bytes = bucket.get(key).ok()??;
+        String::from_utf8(bytes).ok()
+    }
+
     /// Helper to publish a message to a subject
     fn publish_message(subject: &str, payload: &[u8]) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
         let msg = types::BrokerMessage {
-            subject: subject.to_string(),
+            subject: subject_registry::prefixed(&feature_flags::dry_run::route(subject)),
             body: payload.to_vec(),
             reply_to: None,
         };
@@ -1057,6 +1307,39 @@ impl Component {
             call_depth: None,
             success,
             revert_reason,
+            dapp_name: processed_tx.dapp_name.clone(),
+        }
+    }
+
+    fn build_dapp_usage_record(
+        processed_tx: &ProcessedContractTransaction,
+        dapp: &dapps::DappMatch,
+        interaction_count: u64,
+    ) -> DuckLakeDappUsageRecord {
+        let block_date = Utc
+            .timestamp_opt(processed_tx.block_timestamp as i64, 0)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "1970-01-01".to_string());
+        let (success, _) = Self::status_fields(&processed_tx.status, &processed_tx.revert_reason);
+
+        DuckLakeDappUsageRecord {
+            chain_id: format!("{}_{}", processed_tx.network, processed_tx.subnet),
+            block_date,
+            address: processed_tx.caller_address.to_lowercase(),
+            dapp_name: dapp.dapp_name.clone(),
+            dapp_source: dapp.source,
+            network: processed_tx.network.clone(),
+            subnet: processed_tx.subnet.clone(),
+            contract_address: processed_tx.contract_address.to_lowercase(),
+            transaction_hash: processed_tx.transaction_hash.clone(),
+            block_number: processed_tx.block_number,
+            block_timestamp: processed_tx.block_timestamp,
+            transaction_subtype: processed_tx.transaction_subtype.clone(),
+            category: processed_tx.category.clone(),
+            value: Self::normalize_quantity_string(&processed_tx.call_value_wei),
+            success,
+            interaction_count,
         }
     }
 
@@ -1076,6 +1359,13 @@ impl Component {
         let v = raw_tx.v.as_ref().map(|value| Self::parse_hex_u64(value));
 
         let gas_price = Self::normalize_quantity_string(&processed_tx.gas_price);
+        let effective_gas_price = raw_tx
+            .effective_gas_price
+            .as_deref()
+            .map(Self::normalize_quantity_string)
+            .unwrap_or_else(|| gas_price.clone());
+        let rollup = raw_tx.rollup.clone().unwrap_or_default();
+        let fees = raw_tx.fee_accounting.clone().unwrap_or_default();
         let value = Self::normalize_quantity_string(&processed_tx.call_value_wei);
         let transaction_fee = Self::normalize_quantity_string(&processed_tx.transaction_fee_wei);
 
@@ -1112,12 +1402,12 @@ impl Component {
             value: Some(value),
             gas_limit: Some(gas_limit),
             gas_used: Some(processed_tx.gas_used),
-            gas_price: Some(gas_price.clone()),
+            gas_price: Some(gas_price),
             max_fee_per_gas: None,
             max_priority_fee_per_gas: None,
             status: Self::transaction_status_string(&processed_tx.status),
             transaction_fee: Some(transaction_fee),
-            effective_gas_price: Some(gas_price),
+            effective_gas_price: Some(effective_gas_price),
             input_data,
             method_signature: Some(processed_tx.function_selector.clone()),
             transaction_type: processed_tx.transaction_type.clone(),
@@ -1140,6 +1430,15 @@ impl Component {
             v,
             r: raw_tx.r.clone(),
             s: raw_tx.s.clone(),
+            l1_batch_number: rollup.l1_batch_number,
+            l1_batch_tx_index: rollup.l1_batch_tx_index,
+            l1_sequence_tx_hash: rollup.l1_sequence_tx_hash,
+            l1_verify_tx_hash: rollup.l1_verify_tx_hash,
+            l2_tx_kind: rollup.l2_tx_kind,
+            fee_burned: fees.fee_burned,
+            fee_tip: fees.fee_tip,
+            fee_refunded: fees.fee_refunded,
+            net_fee: fees.net_fee,
             processor_id: Some(processed_tx.processor_id.clone()),
             correlation_id: Some(processed_tx.correlation_id.clone()),
         }
@@ -1285,6 +1584,9 @@ mod tests {
                 }
             ],
             block_timestamp: Some("0x65a4c888".to_string()), // 1705320600
+            effective_gas_price: None,
+            rollup: None,
+            fee_accounting: None,
         }
     }
 
@@ -1442,6 +1744,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_perp_selectors() {
+        for (selector, subtype, protocol) in [
+            ("0xf2ae372f", "perp_open", "GMX"),
+            ("0x7be7d141", "perp_close", "GMX"),
+            ("0xde2ea948", "perp_liquidation", "GMX"),
+            ("0xb6b1b6c3", "perp_open", "Perpetual_Protocol"),
+            ("0x86b9d81f", "perp_liquidation", "Perpetual_Protocol"),
+        ] {
+            let category = Component::categorize_function(selector);
+            assert_eq!(Component::category_to_subtype(&category), subtype);
+            assert_eq!(
+                Component::detect_protocol(selector, "0xcontract").as_deref(),
+                Some(protocol)
+            );
+            assert_eq!(Component::determine_category(&category, &None), "defi");
+        }
+    }
+
     #[test]
     fn test_detect_protocol() {
         assert_eq!(
@@ -1683,6 +2004,7 @@ mod tests {
             transaction_subtype: "transfer".to_string(),
             protocol: Some("ERC20".to_string()),
             category: "token".to_string(),
+            dapp_name: None,
             decoded: serde_json::json!({}),
         };
 
@@ -1693,6 +2015,19 @@ mod tests {
         assert!(record.success);
         assert_eq!(record.value, Some("0".to_string()));
         assert_eq!(record.transaction_hash, "0xabc");
+        assert_eq!(record.dapp_name, None);
+
+        let dapp = dapps::DappMatch {
+            dapp_name: "Uniswap".to_string(),
+            source: dapps::DappSource::Builtin,
+        };
+        let usage = Component::build_dapp_usage_record(&processed_tx, &dapp, 3);
+        assert_eq!(usage.chain_id, "ethereum_mainnet");
+        assert_eq!(usage.block_date, "2023-11-14");
+        assert_eq!(usage.address, "0xcaller");
+        assert_eq!(usage.dapp_name, "Uniswap");
+        assert_eq!(usage.interaction_count, 3);
+        assert!(usage.success);
     }
 
     #[test]
@@ -1731,6 +2066,7 @@ mod tests {
             transaction_subtype: "transfer".to_string(),
             protocol: Some("ERC20".to_string()),
             category: "token".to_string(),
+            dapp_name: None,
             decoded: serde_json::json!({}),
         };
 
@@ -1755,6 +2091,14 @@ mod tests {
             gas_used: "0x5208".to_string(),
             logs: vec![],
             block_timestamp: Some(format!("0x{:x}", processed_tx.block_timestamp)),
+            effective_gas_price: None,
+            rollup: None,
+            fee_accounting: Some(FeeAccounting {
+                fee_burned: Some("300000000000000".to_string()),
+                fee_tip: Some("120000000000000".to_string()),
+                fee_refunded: Some("0".to_string()),
+                net_fee: Some("420000000000000".to_string()),
+            }),
         };
 
         let record = Component::build_ducklake_transaction_record(&processed_tx, &raw_tx);
@@ -1765,6 +2109,9 @@ mod tests {
         assert_eq!(record.status, "SUCCESS");
         assert_eq!(record.method_signature, Some("0xa9059cbb".to_string()));
         assert!(record.amount_native.unwrap_or(1.0).abs() < 1e-9);
+        assert_eq!(record.fee_burned.as_deref(), Some("300000000000000"));
+        assert_eq!(record.fee_tip.as_deref(), Some("120000000000000"));
+        assert_eq!(record.net_fee.as_deref(), Some("420000000000000"));
     }
 
     #[test]
@@ -1803,6 +2150,7 @@ mod tests {
             transaction_subtype: "transfer".to_string(),
             protocol: Some("ERC20".to_string()),
             category: "token".to_string(),
+            dapp_name: None,
             decoded: serde_json::json!({}),
         };
 
@@ -1827,6 +2175,9 @@ mod tests {
             gas_used: "0x5208".to_string(),
             logs: vec![],
             block_timestamp: Some(format!("0x{:x}", processed_tx.block_timestamp)),
+            effective_gas_price: None,
+            rollup: None,
+            fee_accounting: None,
         };
 
         let records = Component::build_address_transaction_records(&processed_tx, &raw_tx);
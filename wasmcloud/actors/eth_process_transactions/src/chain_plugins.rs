@@ -0,0 +1,334 @@
+//! Chain plugins for EVM chains whose semantics differ from Ethereum L1
+//!
+//! A plugin can:
+//! - filter out system transactions that aren't user activity
+//! - override categorization (e.g. zkSync deploys via the ContractDeployer system contract)
+//! - compute the actual fee from receipt data when gas is refunded
+//! - label the rollup-native transaction kind for the DuckLake extension columns
+//!
+//! Chains without a plugin still get receipt-based fees and the EIP-1559
+//! burn/tip breakdown via `receipt_fee` and `fee_accounting`.
+
+use crate::{FeeAccounting, RawTransaction, TransactionType};
+
+/// Fee settled for a transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeComputation {
+    pub gas_used: u64,
+    pub effective_gas_price: u128,
+    pub fee_wei: u128,
+    /// True when no receipt was available and the fee is an estimate from gas_limit
+    pub estimated: bool,
+}
+
+/// Chain-specific processing hooks
+pub trait ChainPlugin {
+    /// Plugin name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Whether the transaction is chain bookkeeping rather than user activity
+    fn is_system_transaction(&self, raw_tx: &RawTransaction) -> bool;
+
+    /// Override the default to/input based categorization
+    fn categorize(&self, _raw_tx: &RawTransaction) -> Option<TransactionType> {
+        None
+    }
+
+    /// Rollup-native transaction kind (`l2_tx_kind` column)
+    fn tx_kind(&self, raw_tx: &RawTransaction) -> &'static str;
+
+    /// Settled fee, preferring receipt data over the gas_limit estimate
+    fn compute_fee(&self, raw_tx: &RawTransaction) -> FeeComputation {
+        receipt_fee(raw_tx)
+    }
+}
+
+/// zkSync Era: native account abstraction, L1 priority transactions and system contracts
+pub struct ZkSyncEra;
+
+/// Bootloader formal address; transactions "from" it are batch bookkeeping
+const ZKSYNC_BOOTLOADER: &str = "0x0000000000000000000000000000000000008001";
+
+/// ContractDeployer system contract; all deployments are calls to it
+const ZKSYNC_CONTRACT_DEPLOYER: &str = "0x0000000000000000000000000000000000008006";
+
+/// zkSync EIP-712 transaction type (account abstraction / paymasters)
+const ZKSYNC_EIP712_TX_TYPE: u8 = 0x71;
+
+/// zkSync protocol upgrade transaction type
+const ZKSYNC_UPGRADE_TX_TYPE: u8 = 0xfe;
+
+/// zkSync L1 -> L2 priority transaction type
+const ZKSYNC_PRIORITY_TX_TYPE: u8 = 0xff;
+
+impl ChainPlugin for ZkSyncEra {
+    fn name(&self) -> &'static str {
+        "zksync-era"
+    }
+
+    fn is_system_transaction(&self, raw_tx: &RawTransaction) -> bool {
+        raw_tx.from_address.eq_ignore_ascii_case(ZKSYNC_BOOTLOADER)
+            || raw_tx.transaction_type == Some(ZKSYNC_UPGRADE_TX_TYPE)
+    }
+
+    fn categorize(&self, raw_tx: &RawTransaction) -> Option<TransactionType> {
+        let to = raw_tx.to_address.as_deref()?;
+        if to.eq_ignore_ascii_case(ZKSYNC_CONTRACT_DEPLOYER) {
+            Some(TransactionType::ContractCreation)
+        } else {
+            None
+        }
+    }
+
+    fn tx_kind(&self, raw_tx: &RawTransaction) -> &'static str {
+        match raw_tx.transaction_type {
+            Some(ZKSYNC_EIP712_TX_TYPE) => "eip712",
+            Some(ZKSYNC_PRIORITY_TX_TYPE) => "l1_priority",
+            Some(ZKSYNC_UPGRADE_TX_TYPE) => "upgrade",
+            _ => "standard",
+        }
+    }
+}
+
+/// Polygon zkEVM: batch-sequenced blocks with a sequencer-injected system sender
+pub struct PolygonZkEvm;
+
+/// Sender used for sequencer bookkeeping transactions (global exit root updates)
+const ZKEVM_SYSTEM_SENDER: &str = "0x0000000000000000000000000000000000000000";
+
+impl ChainPlugin for PolygonZkEvm {
+    fn name(&self) -> &'static str {
+        "polygon-zkevm"
+    }
+
+    fn is_system_transaction(&self, raw_tx: &RawTransaction) -> bool {
+        raw_tx
+            .from_address
+            .eq_ignore_ascii_case(ZKEVM_SYSTEM_SENDER)
+    }
+
+    fn tx_kind(&self, _raw_tx: &RawTransaction) -> &'static str {
+        "standard"
+    }
+}
+
+/// Resolve the plugin for a transaction's chain, if any
+pub fn plugin_for(raw_tx: &RawTransaction) -> Option<&'static dyn ChainPlugin> {
+    let network = raw_tx.network.to_lowercase().replace('_', "-");
+    let chain_id = parse_quantity(&raw_tx.chain_id);
+
+    match (network.as_str(), chain_id) {
+        ("zksync" | "zksync-era" | "era", _) | (_, 324 | 300) => Some(&ZkSyncEra),
+        ("polygon-zkevm" | "zkevm", _) | (_, 1101 | 2442) => Some(&PolygonZkEvm),
+        _ => None,
+    }
+}
+
+/// gas_used * effective_gas_price from the receipt, else gas_limit * gas_price
+pub fn receipt_fee(raw_tx: &RawTransaction) -> FeeComputation {
+    match &raw_tx.receipt {
+        Some(receipt) => {
+            let gas_used = parse_quantity(&receipt.gas_used) as u64;
+            let effective_gas_price = receipt
+                .effective_gas_price
+                .as_deref()
+                .map(parse_quantity)
+                .unwrap_or_else(|| parse_quantity(&raw_tx.gas_price));
+            FeeComputation {
+                gas_used,
+                effective_gas_price,
+                fee_wei: effective_gas_price * gas_used as u128,
+                estimated: false,
+            }
+        }
+        None => {
+            let gas_price = parse_quantity(&raw_tx.gas_price);
+            FeeComputation {
+                gas_used: raw_tx.gas_limit,
+                effective_gas_price: gas_price,
+                fee_wei: gas_price * raw_tx.gas_limit as u128,
+                estimated: true,
+            }
+        }
+    }
+}
+
+/// Split a receipt-settled fee into base fee burned, priority tip and refund
+///
+/// Returns None for gas_limit estimates, which have nothing to split. Chains
+/// without a base fee (pre-London, some L2s) report the whole fee as tip.
+pub fn fee_accounting(raw_tx: &RawTransaction, fee: &FeeComputation) -> Option<FeeAccounting> {
+    if fee.estimated {
+        return None;
+    }
+
+    let gas_used = fee.gas_used as u128;
+    let base_fee = raw_tx
+        .base_fee_per_gas
+        .as_deref()
+        .map(parse_quantity)
+        .unwrap_or(0)
+        .min(fee.effective_gas_price);
+    let fee_burned = base_fee * gas_used;
+    let fee_tip = (fee.effective_gas_price - base_fee) * gas_used;
+    let fee_refunded =
+        raw_tx.gas_limit.saturating_sub(fee.gas_used) as u128 * fee.effective_gas_price;
+
+    Some(FeeAccounting {
+        fee_burned: fee_burned.to_string(),
+        fee_tip: fee_tip.to_string(),
+        fee_refunded: fee_refunded.to_string(),
+        net_fee: fee.fee_wei.to_string(),
+    })
+}
+
+fn parse_quantity(value: &str) -> u128 {
+    let trimmed = value.trim();
+    match trimmed.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).unwrap_or(0),
+        None => trimmed.parse::<u128>().unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReceiptData;
+
+    fn raw_tx(network: &str, chain_id: &str) -> RawTransaction {
+        RawTransaction {
+            network: network.to_string(),
+            subnet: "mainnet".to_string(),
+            vm_type: "evm".to_string(),
+            transaction_hash: "0xabc".to_string(),
+            block_number: 1,
+            block_hash: "0xblock".to_string(),
+            block_timestamp: 1_700_000_000,
+            transaction_index: 0,
+            from_address: "0x1111111111111111111111111111111111111111".to_string(),
+            to_address: Some("0x2222222222222222222222222222222222222222".to_string()),
+            value: "0x0".to_string(),
+            gas_limit: 1_000_000,
+            gas_price: "0x2faf080".to_string(), // 50_000_000
+            input_data: "0x".to_string(),
+            nonce: 0,
+            chain_id: chain_id.to_string(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            transaction_type: None,
+            base_fee_per_gas: None,
+            v: None,
+            r: None,
+            s: None,
+            receipt: None,
+            rollup: None,
+            processed_at: "2024-01-01T00:00:00Z".to_string(),
+            processor_id: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plugin_resolution() {
+        assert_eq!(
+            plugin_for(&raw_tx("zksync", "0x0")).unwrap().name(),
+            "zksync-era"
+        );
+        assert_eq!(
+            plugin_for(&raw_tx("unknown", "0x44d")).unwrap().name(),
+            "polygon-zkevm"
+        );
+        assert!(plugin_for(&raw_tx("ethereum", "0x1")).is_none());
+    }
+
+    #[test]
+    fn test_zksync_system_and_kind() {
+        let mut tx = raw_tx("zksync", "0x144");
+        assert!(!ZkSyncEra.is_system_transaction(&tx));
+        assert_eq!(ZkSyncEra.tx_kind(&tx), "standard");
+
+        tx.transaction_type = Some(0x71);
+        assert_eq!(ZkSyncEra.tx_kind(&tx), "eip712");
+
+        tx.transaction_type = Some(0xff);
+        assert_eq!(ZkSyncEra.tx_kind(&tx), "l1_priority");
+
+        tx.from_address = ZKSYNC_BOOTLOADER.to_string();
+        assert!(ZkSyncEra.is_system_transaction(&tx));
+    }
+
+    #[test]
+    fn test_zksync_deployments_categorized_as_creation() {
+        let mut tx = raw_tx("zksync", "0x144");
+        tx.to_address = Some(ZKSYNC_CONTRACT_DEPLOYER.to_string());
+        tx.input_data = "0x9c4d535b".to_string();
+        assert!(matches!(
+            ZkSyncEra.categorize(&tx),
+            Some(TransactionType::ContractCreation)
+        ));
+    }
+
+    #[test]
+    fn test_fee_uses_receipt_after_refund() {
+        let mut tx = raw_tx("zksync", "0x144");
+        let estimate = ZkSyncEra.compute_fee(&tx);
+        assert!(estimate.estimated);
+        assert_eq!(estimate.fee_wei, 50_000_000u128 * 1_000_000);
+
+        tx.receipt = Some(ReceiptData {
+            gas_used: "0x1d4c0".to_string(), // 120_000 after refund
+            effective_gas_price: Some("0x2625a00".to_string()), // 40_000_000
+            status: "0x1".to_string(),
+        });
+        let fee = ZkSyncEra.compute_fee(&tx);
+        assert!(!fee.estimated);
+        assert_eq!(fee.gas_used, 120_000);
+        assert_eq!(fee.fee_wei, 40_000_000u128 * 120_000);
+    }
+
+    #[test]
+    fn test_fee_accounting_splits_burn_tip_and_refund() {
+        let mut tx = raw_tx("ethereum", "0x1");
+        tx.gas_limit = 100_000;
+        tx.base_fee_per_gas = Some("0x6fc23ac00".to_string()); // 30 gwei
+        tx.receipt = Some(ReceiptData {
+            gas_used: "0xc350".to_string(),                       // 50_000
+            effective_gas_price: Some("0x7dea31200".to_string()), // 33.8 gwei
+            status: "0x1".to_string(),
+        });
+
+        let fee = receipt_fee(&tx);
+        let accounting = fee_accounting(&tx, &fee).expect("receipt-based accounting");
+        let burned: u128 = 30_000_000_000 * 50_000;
+        let tip: u128 = 3_800_000_000 * 50_000;
+        assert_eq!(accounting.fee_burned, burned.to_string());
+        assert_eq!(accounting.fee_tip, tip.to_string());
+        assert_eq!(accounting.net_fee, (burned + tip).to_string());
+        assert_eq!(
+            accounting.fee_refunded,
+            (33_800_000_000u128 * 50_000).to_string()
+        );
+    }
+
+    #[test]
+    fn test_fee_accounting_requires_receipt_and_handles_legacy() {
+        let mut tx = raw_tx("ethereum", "0x1");
+        assert!(fee_accounting(&tx, &receipt_fee(&tx)).is_none());
+
+        tx.receipt = Some(ReceiptData {
+            gas_used: "0x5208".to_string(),
+            effective_gas_price: None,
+            status: "0x1".to_string(),
+        });
+        let accounting = fee_accounting(&tx, &receipt_fee(&tx)).unwrap();
+        assert_eq!(accounting.fee_burned, "0");
+        assert_eq!(accounting.fee_tip, accounting.net_fee);
+    }
+
+    #[test]
+    fn test_zkevm_system_sender() {
+        let mut tx = raw_tx("polygon-zkevm", "0x44d");
+        assert!(!PolygonZkEvm.is_system_transaction(&tx));
+        tx.from_address = ZKEVM_SYSTEM_SENDER.to_string();
+        assert!(PolygonZkEvm.is_system_transaction(&tx));
+    }
+}
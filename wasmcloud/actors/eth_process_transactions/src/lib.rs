@@ -26,6 +26,8 @@ wit_bindgen::generate!({ generate_all });
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use wasmcloud::messaging::{consumer, types};
 
+mod chain_plugins;
+
 /// Raw transaction from eth_raw_transactions actor
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawTransaction {
@@ -53,6 +55,8 @@ pub struct RawTransaction {
     pub max_fee_per_gas: Option<String>,
     pub max_priority_fee_per_gas: Option<String>,
     pub transaction_type: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub v: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -60,11 +64,64 @@ pub struct RawTransaction {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
 
+    // Receipt and rollup data (ZK rollups, chains with receipt fetching enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<ReceiptData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
+
     // Processing metadata
     pub processed_at: String,
     pub processor_id: String,
 }
 
+/// Receipt data attached by eth_raw_transactions when block receipts are fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptData {
+    pub gas_used: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    pub status: String,
+}
+
+/// L1 batch/sequence metadata for ZK rollups, forwarded to the DuckLake extension columns
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollupContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_tx_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_sequence_tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_verify_tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_tx_kind: Option<String>,
+}
+
+/// Receipt-based fee breakdown in wei (decimal strings)
+///
+/// `net_fee` is what the sender actually paid (`fee_burned + fee_tip`);
+/// `fee_refunded` is the prepaid `gas_limit * price` returned for unused gas,
+/// which already includes gas refunded for SSTORE clears.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeAccounting {
+    pub fee_burned: String,
+    pub fee_tip: String,
+    pub fee_refunded: String,
+    pub net_fee: String,
+}
+
+/// Settled execution data from receipts and chain plugins, carried on categorized payloads
+#[derive(Debug, Clone, Default)]
+struct Settlement {
+    gas_used: Option<String>,
+    effective_gas_price: Option<String>,
+    status: Option<String>,
+    fee_accounting: Option<FeeAccounting>,
+    rollup: Option<RollupContext>,
+}
+
 /// Raw transfer transaction in standard Ethereum format
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawTransferTransaction {
@@ -88,6 +145,16 @@ pub struct RawTransferTransaction {
     pub r: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
 }
 
 /// Raw contract creation transaction in standard Ethereum format
@@ -116,6 +183,16 @@ pub struct RawContractCreation {
     pub r: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
 }
 
 /// Raw contract transaction in standard Ethereum format with receipt data
@@ -146,6 +223,12 @@ pub struct RawContractTransaction {
     pub logs: Vec<RawEventLog>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub block_timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,6 +305,14 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages containing raw transactions
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+
         eprintln!("[ETH-PROCESS] ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
         eprintln!(
             "[ETH-PROCESS] 📨 Received message on subject: {}",
@@ -229,7 +320,7 @@ impl MessageHandler for Component {
         );
 
         // Only process transactions.raw.evm messages
-        if msg.subject != "transactions.raw.evm" {
+        if subject_registry::unprefixed(&msg.subject) != Some("transactions.raw.evm") {
             eprintln!("[ETH-PROCESS] ⏭️  Skipping - not a raw EVM transaction message");
             return Ok(());
         }
@@ -267,8 +358,24 @@ impl MessageHandler for Component {
 impl Component {
     /// Process a raw transaction and publish it to appropriate subjects
     fn process_and_publish_transaction(raw_tx: RawTransaction) -> Result<(), String> {
+        let plugin = chain_plugins::plugin_for(&raw_tx);
+
+        // Drop chain bookkeeping (e.g. zkSync bootloader) before categorization
+        if let Some(plugin) = plugin {
+            if plugin.is_system_transaction(&raw_tx) {
+                eprintln!(
+                    "[ETH-PROCESS] ⏭️  Skipping {} system transaction {}",
+                    plugin.name(),
+                    raw_tx.transaction_hash
+                );
+                return Ok(());
+            }
+        }
+
         // Determine transaction type
-        let transaction_category = Self::detect_transaction_type(&raw_tx);
+        let transaction_category = plugin
+            .and_then(|p| p.categorize(&raw_tx))
+            .unwrap_or_else(|| Self::detect_transaction_type(&raw_tx));
 
         // Analyze gas price
         let gas_analysis = Self::analyze_gas_price(&raw_tx.gas_price);
@@ -309,7 +416,7 @@ impl Component {
             method_signature,
             gas_analysis,
             details,
-            processed_at: chrono::Utc::now().to_rfc3339(),
+            processed_at: replay_clock::now().to_rfc3339(),
             processor_id: "eth-process-transactions-actor".to_string(),
         };
 
@@ -563,7 +670,7 @@ impl Component {
 
         for target in subjects {
             let msg = types::BrokerMessage {
-                subject: target.clone(),
+                subject: subject_registry::prefixed(&target),
                 body: payload.clone(),
                 reply_to: None,
             };
@@ -584,11 +691,36 @@ impl Component {
         Ok(())
     }
 
+    /// Receipt-settled gas/fee data, plus rollup metadata from the chain plugin
+    fn settlement(raw_tx: &RawTransaction) -> Settlement {
+        let plugin = chain_plugins::plugin_for(raw_tx);
+        let fee = match plugin {
+            Some(plugin) => plugin.compute_fee(raw_tx),
+            None => chain_plugins::receipt_fee(raw_tx),
+        };
+
+        let rollup = plugin.map(|plugin| {
+            let mut rollup = raw_tx.rollup.clone().unwrap_or_default();
+            rollup.l2_tx_kind = Some(plugin.tx_kind(raw_tx).to_string());
+            rollup
+        });
+
+        Settlement {
+            gas_used: (!fee.estimated).then(|| Self::to_hex_u64(fee.gas_used)),
+            effective_gas_price: (!fee.estimated)
+                .then(|| format!("0x{:x}", fee.effective_gas_price)),
+            status: raw_tx.receipt.as_ref().map(|r| r.status.clone()),
+            fee_accounting: chain_plugins::fee_accounting(raw_tx, &fee),
+            rollup,
+        }
+    }
+
     fn build_raw_transfer(raw_tx: &RawTransaction) -> Result<RawTransferTransaction, String> {
         let to_address = raw_tx
             .to_address
             .clone()
             .ok_or_else(|| "Transfer transaction missing to_address".to_string())?;
+        let settlement = Self::settlement(raw_tx);
 
         Ok(RawTransferTransaction {
             hash: raw_tx.transaction_hash.clone(),
@@ -607,10 +739,16 @@ impl Component {
             v: raw_tx.v.clone(),
             r: raw_tx.r.clone(),
             s: raw_tx.s.clone(),
+            gas_used: settlement.gas_used,
+            effective_gas_price: settlement.effective_gas_price,
+            status: settlement.status,
+            fee_accounting: settlement.fee_accounting,
+            rollup: settlement.rollup,
         })
     }
 
     fn build_raw_contract_creation(raw_tx: &RawTransaction) -> Result<RawContractCreation, String> {
+        let settlement = Self::settlement(raw_tx);
         Ok(RawContractCreation {
             hash: raw_tx.transaction_hash.clone(),
             from: raw_tx.from_address.clone(),
@@ -629,6 +767,11 @@ impl Component {
             v: raw_tx.v.clone(),
             r: raw_tx.r.clone(),
             s: raw_tx.s.clone(),
+            gas_used: settlement.gas_used,
+            effective_gas_price: settlement.effective_gas_price,
+            status: settlement.status,
+            fee_accounting: settlement.fee_accounting,
+            rollup: settlement.rollup,
         })
     }
 
@@ -639,6 +782,7 @@ impl Component {
             .to_address
             .clone()
             .ok_or_else(|| "Contract transaction missing to_address".to_string())?;
+        let settlement = Self::settlement(raw_tx);
 
         Ok(RawContractTransaction {
             hash: raw_tx.transaction_hash.clone(),
@@ -656,11 +800,14 @@ impl Component {
             v: raw_tx.v.clone(),
             r: raw_tx.r.clone(),
             s: raw_tx.s.clone(),
-            status: "0x1".to_string(),
+            status: settlement.status.unwrap_or_else(|| "0x1".to_string()),
             revert_reason: None,
-            gas_used: "0x0".to_string(),
+            gas_used: settlement.gas_used.unwrap_or_else(|| "0x0".to_string()),
             logs: Vec::new(),
             block_timestamp: Some(Self::to_hex_u64(raw_tx.block_timestamp)),
+            effective_gas_price: settlement.effective_gas_price,
+            fee_accounting: settlement.fee_accounting,
+            rollup: settlement.rollup,
         })
     }
 
@@ -745,6 +892,9 @@ mod tests {
                 v: Some("0x1b".to_string()),
                 r: Some("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string()),
                 s: Some("0xfedcba0987654321fedcba0987654321fedcba0987654321fedcba0987654321".to_string()),
+                base_fee_per_gas: None,
+                receipt: None,
+                rollup: None,
                 processed_at: "2024-01-15T10:30:00Z".to_string(),
                 processor_id: "test".to_string(),
             },
@@ -771,6 +921,9 @@ mod tests {
                 v: Some("0x1b".to_string()),
                 r: Some("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string()),
                 s: Some("0xfedcba0987654321fedcba0987654321fedcba0987654321fedcba0987654321".to_string()),
+                base_fee_per_gas: None,
+                receipt: None,
+                rollup: None,
                 processed_at: "2024-01-15T10:30:01Z".to_string(),
                 processor_id: "test".to_string(),
             },
@@ -797,6 +950,9 @@ mod tests {
                 v: Some("0x1b".to_string()),
                 r: Some("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string()),
                 s: Some("0xfedcba0987654321fedcba0987654321fedcba0987654321fedcba0987654321".to_string()),
+                base_fee_per_gas: None,
+                receipt: None,
+                rollup: None,
                 processed_at: "2024-01-15T10:30:02Z".to_string(),
                 processor_id: "test".to_string(),
             },
@@ -944,6 +1100,66 @@ mod tests {
         assert_eq!(payload.value, "0xde0b6b3a7640000");
     }
 
+    #[test]
+    fn test_build_raw_transfer_carries_rollup_settlement() {
+        let mut raw_tx = create_test_raw_transaction("transfer");
+        assert!(Component::build_raw_transfer(&raw_tx)
+            .unwrap()
+            .rollup
+            .is_none());
+
+        raw_tx.network = "zksync".to_string();
+        raw_tx.chain_id = "0x144".to_string();
+        raw_tx.transaction_type = Some(0x71);
+        raw_tx.receipt = Some(ReceiptData {
+            gas_used: "0x3a98".to_string(),
+            effective_gas_price: Some("0x17d7840".to_string()),
+            status: "0x1".to_string(),
+        });
+        raw_tx.rollup = Some(RollupContext {
+            l1_batch_number: Some(500_000),
+            l1_batch_tx_index: Some(3),
+            ..Default::default()
+        });
+
+        let payload = Component::build_raw_transfer(&raw_tx).expect("build transfer");
+        assert_eq!(payload.gas_used.as_deref(), Some("0x3a98"));
+        assert_eq!(payload.effective_gas_price.as_deref(), Some("0x17d7840"));
+        assert_eq!(payload.status.as_deref(), Some("0x1"));
+
+        let rollup = payload.rollup.expect("rollup context");
+        assert_eq!(rollup.l1_batch_number, Some(500_000));
+        assert_eq!(rollup.l2_tx_kind.as_deref(), Some("eip712"));
+    }
+
+    #[test]
+    fn test_build_raw_transfer_carries_fee_accounting_without_plugin() {
+        let mut raw_tx = create_test_raw_transaction("transfer");
+        assert!(Component::build_raw_transfer(&raw_tx)
+            .unwrap()
+            .fee_accounting
+            .is_none());
+
+        raw_tx.base_fee_per_gas = Some("0x3b9aca00".to_string()); // 1 gwei
+        raw_tx.receipt = Some(ReceiptData {
+            gas_used: "0x5208".to_string(),
+            effective_gas_price: Some("0x77359400".to_string()), // 2 gwei
+            status: "0x1".to_string(),
+        });
+
+        let payload = Component::build_raw_transfer(&raw_tx).expect("build transfer");
+        assert_eq!(payload.gas_used.as_deref(), Some("0x5208"));
+        assert!(payload.rollup.is_none());
+
+        let accounting = payload.fee_accounting.expect("fee accounting");
+        assert_eq!(
+            accounting.fee_burned,
+            (1_000_000_000u128 * 21_000).to_string()
+        );
+        assert_eq!(accounting.fee_tip, (1_000_000_000u128 * 21_000).to_string());
+        assert_eq!(accounting.net_fee, (2_000_000_000u128 * 21_000).to_string());
+    }
+
     #[test]
     fn test_resolve_chain_id_hex_fallback() {
         let mut raw_tx = create_test_raw_transaction("transfer");
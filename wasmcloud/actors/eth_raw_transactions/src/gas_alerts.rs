@@ -0,0 +1,129 @@
+//! Built-in gas fee alerts
+//!
+//! Summarizes each block's base fee and median priority fee, advances the
+//! per-chain `GasFeeDetectorV1` kept in Redis and publishes any rule that
+//! fires on `alerts.system.gas.{network}.{subnet}.{rule}`.
+
+use alert_runtime_common::{
+    effective_priority_fee, median, AlertGasFeeV1, BlockGasSampleV1, GasAlertConfigV1,
+    GasFeeDetectorV1, PartitionV1,
+};
+
+use crate::wasmcloud::messaging::{consumer, types};
+use crate::BlockHeader;
+
+const SOURCE: &str = "eth_raw_transactions";
+
+/// Build the fee sample for a block; `None` before EIP-1559
+pub fn block_sample(
+    block_number: u64,
+    base_fee_per_gas: Option<&str>,
+    transactions: &[serde_json::Value],
+) -> Option<BlockGasSampleV1> {
+    let base_fee = parse_hex_u128(base_fee_per_gas?)?;
+    let field = |tx: &serde_json::Value, name: &str| {
+        tx.get(name)
+            .and_then(|v| v.as_str())
+            .and_then(parse_hex_u128)
+    };
+    let mut tips: Vec<u128> = transactions
+        .iter()
+        .filter_map(|tx| {
+            effective_priority_fee(
+                base_fee,
+                field(tx, "gasPrice"),
+                field(tx, "maxFeePerGas"),
+                field(tx, "maxPriorityFeePerGas"),
+            )
+        })
+        .collect();
+
+    Some(BlockGasSampleV1 {
+        block_number,
+        base_fee_per_gas: Some(base_fee),
+        median_priority_fee_per_gas: median(&mut tips),
+    })
+}
+
+/// Run the gas rules for one block. Failures are logged and never block
+/// transaction publishing.
+pub fn process_block(block_header: &BlockHeader, chain_id: &str, sample: &BlockGasSampleV1) {
+    if let Err(e) = try_process_block(block_header, chain_id, sample) {
+        eprintln!("[ETH-RAW] ⚠️  Gas alert evaluation failed: {}", e);
+    }
+}
+
+fn try_process_block(
+    block_header: &BlockHeader,
+    chain_id: &str,
+    sample: &BlockGasSampleV1,
+) -> Result<(), String> {
+    let bucket = crate::wasi::keyvalue::store::open("default")
+        .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+    let chain = format!("{}:{}", block_header.network, block_header.subnet);
+
+    let config = bucket
+        .get(&retention_policy::GAS_ALERT_CONFIG.key(&chain))
+        .map_err(|e| format!("Failed to get gas alert config: {:?}", e))?
+        .and_then(|bytes| serde_json::from_slice::<GasAlertConfigV1>(&bytes).ok())
+        .unwrap_or_default();
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let state_key = retention_policy::GAS_ALERT_STATE.key(&chain);
+    let mut detector = bucket
+        .get(&state_key)
+        .map_err(|e| format!("Failed to get gas alert state: {:?}", e))?
+        .and_then(|bytes| serde_json::from_slice::<GasFeeDetectorV1>(&bytes).ok())
+        .unwrap_or_default();
+
+    let signals = detector.observe(&config, sample);
+    let state = serde_json::to_vec(&detector)
+        .map_err(|e| format!("Failed to serialize gas alert state: {}", e))?;
+    bucket
+        .set(&state_key, &state)
+        .map_err(|e| format!("Failed to store gas alert state: {:?}", e))?;
+
+    let partition = PartitionV1 {
+        network: block_header.network.clone(),
+        subnet: block_header.subnet.clone(),
+        chain_id: parse_chain_id(chain_id),
+    };
+    for signal in &signals {
+        let alert = AlertGasFeeV1::new(
+            partition.clone(),
+            &config,
+            sample,
+            signal,
+            replay_clock::now(),
+            SOURCE,
+        );
+        let body = serde_json::to_vec(&alert)
+            .map_err(|e| format!("Failed to serialize gas alert: {}", e))?;
+        let subject = alert.subject();
+        consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(&feature_flags::dry_run::route(&subject)),
+            body,
+            reply_to: None,
+        })
+        .map_err(|e| format!("Failed to publish gas alert: {:?}", e))?;
+        eprintln!(
+            "[ETH-RAW] ⛽ Gas alert {} at block #{}",
+            subject, sample.block_number
+        );
+    }
+    Ok(())
+}
+
+/// Node configs store the chain id as decimal or hex
+fn parse_chain_id(chain_id: &str) -> i64 {
+    match chain_id.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).unwrap_or(0),
+        None => chain_id.parse().unwrap_or(0),
+    }
+}
+
+fn parse_hex_u128(value: &str) -> Option<u128> {
+    u128::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
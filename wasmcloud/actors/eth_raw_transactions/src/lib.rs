@@ -3,8 +3,13 @@
 //! WasmCloud actor that processes Ethereum newheads and fetches raw transactions from RPC nodes.
 //! This actor receives blockchain newheads via NATS, fetches transaction details via HTTP RPC,
 //! and publishes processed transactions back to NATS for downstream processing.
+//!
+//! Each chain's progress is tracked by a block checkpoint (`shared/block-checkpoint`)
+//! that only advances once every transaction of a block has been published, so
+//! redelivered heads are skipped and skipped ranges are recorded for backfill.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // Generate WIT bindings for the processor world
 wit_bindgen::generate!({ generate_all });
@@ -12,8 +17,14 @@ wit_bindgen::generate!({ generate_all });
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use wasmcloud::messaging::{consumer, types};
 
+mod gas_alerts;
+mod receipts;
+mod rollup;
 mod simplified_lib;
 
+use receipts::ReceiptData;
+use rollup::{BatchDetails, RollupContext, RollupKind};
+
 /// Block header from newheads provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockHeader {
@@ -48,6 +59,9 @@ pub struct NetworkConfig {
     pub is_primary: bool,
     #[serde(default)]
     pub priority: u32,
+    /// Fetch block receipts for exact gas/fee accounting (always on for ZK rollups)
+    #[serde(default)]
+    pub fetch_receipts: bool,
 }
 
 /// Raw transaction data
@@ -72,12 +86,21 @@ pub struct RawTransaction {
     pub max_fee_per_gas: Option<String>,
     pub max_priority_fee_per_gas: Option<String>,
     pub transaction_type: Option<u8>,
+    /// Block base fee (EIP-1559 chains), used to split burned fee from tip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub v: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub r: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
+    /// Receipt data, fetched for ZK rollups and chains with `fetch_receipts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<ReceiptData>,
+    /// L1 batch/sequence metadata for ZK rollups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
     pub processed_at: String,
     pub processor_id: String,
 }
@@ -88,64 +111,9 @@ pub struct Component;
 // Export Component for WasmCloud
 export!(Component);
 
-/// Get current UTC timestamp as ISO 8601 string using WASI clock interface
+/// Current UTC timestamp as ISO 8601, frozen in deterministic replays
 fn get_current_timestamp() -> String {
-    let now = wasi::clocks::wall_clock::now();
-    // Convert WASI datetime (seconds + nanoseconds) to ISO 8601 format
-    let total_seconds = now.seconds;
-
-    let days_since_epoch = total_seconds / 86400;
-    let time_of_day = total_seconds % 86400;
-
-    let hours = time_of_day / 3600;
-    let minutes = (time_of_day % 3600) / 60;
-    let seconds = time_of_day % 60;
-
-    let (year, month, day) = days_to_ymd(days_since_epoch as i64);
-
-    format!(
-        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
-        year, month, day, hours, minutes, seconds
-    )
-}
-
-/// Convert days since Unix epoch to year-month-day
-fn days_to_ymd(days: i64) -> (i32, u32, u32) {
-    let mut remaining_days = days;
-    let mut year = 1970i32;
-
-    loop {
-        let days_in_year = if is_leap_year(year) { 366 } else { 365 };
-        if remaining_days < days_in_year {
-            break;
-        }
-        remaining_days -= days_in_year;
-        year += 1;
-    }
-
-    let month_days: [i64; 12] = if is_leap_year(year) {
-        [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    } else {
-        [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    };
-
-    let mut month = 1u32;
-    for days_in_month in month_days.iter() {
-        if remaining_days < *days_in_month {
-            break;
-        }
-        remaining_days -= *days_in_month;
-        month += 1;
-    }
-
-    let day = remaining_days as u32 + 1;
-
-    (year, month, day)
-}
-
-/// Check if a year is a leap year
-fn is_leap_year(year: i32) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
+    replay_clock::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
 }
 
 impl MessageHandler for Component {
@@ -155,10 +123,21 @@ impl MessageHandler for Component {
         eprintln!("[ETH-RAW] 📨 Received message on subject: {}", msg.subject);
 
         // Only process newheads messages for EVM chains
-        if !msg.subject.starts_with("newheads.") || !msg.subject.ends_with(".evm") {
+        let subject = subject_registry::unprefixed(&msg.subject).unwrap_or_default();
+        if !subject.starts_with("newheads.") || !subject.ends_with(".evm") {
             eprintln!("[ETH-RAW] ⏭️  Skipping - not an EVM newheads message");
             return Ok(());
         }
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
 
         // Parse the block header from the message
         let block_header: BlockHeader = serde_json::from_slice(&msg.body).map_err(|e| {
@@ -211,6 +190,38 @@ impl Component {
             ));
         }
 
+        // Skip blocks that were already handed off; a redelivered head must
+        // not publish its transactions twice
+        let checkpoint = Self::load_checkpoint(&block_header);
+        match block_checkpoint::handoff(
+            checkpoint.as_ref(),
+            block_header.block_number,
+            &block_header.block_hash,
+        ) {
+            block_checkpoint::Handoff::AlreadyProcessed => {
+                eprintln!(
+                    "[ETH-RAW] ⏭️  Block #{} already handed off, skipping",
+                    block_header.block_number
+                );
+                return Ok(());
+            }
+            block_checkpoint::Handoff::Gap(gap) => {
+                eprintln!(
+                    "[ETH-RAW] ⚠️  Gap detected: blocks #{}-#{} ({} blocks) pending backfill",
+                    gap.from,
+                    gap.to,
+                    gap.len()
+                );
+            }
+            block_checkpoint::Handoff::Reorg => {
+                eprintln!(
+                    "[ETH-RAW] 🔀 Reorg at block #{}, reprocessing",
+                    block_header.block_number
+                );
+            }
+            block_checkpoint::Handoff::Process => {}
+        }
+
         let rpc_url = &config.rpc_url;
         eprintln!("[ETH-RAW] 🌐 Fetching block from RPC: {}...", rpc_url);
 
@@ -229,10 +240,39 @@ impl Component {
             tx_count, block_header.block_number
         );
 
+        let base_fee_per_gas = block_data
+            .get("baseFeePerGas")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        if let Some(sample) = gas_alerts::block_sample(
+            block_header.block_number,
+            base_fee_per_gas.as_deref(),
+            transactions,
+        ) {
+            gas_alerts::process_block(&block_header, &config.chain_id, &sample);
+        }
+
+        let rollup_kind = RollupKind::detect(&block_header.network, &config.chain_id);
+        let receipts = if rollup_kind.is_some() || config.fetch_receipts {
+            Self::fetch_block_receipts(rpc_url, block_header.block_number)
+        } else {
+            HashMap::new()
+        };
+        let rollup_batches = rollup_kind
+            .map(|kind| Self::fetch_rollup_batches(rpc_url, kind, &block_header, transactions));
+
         // Process and publish each transaction
-        let mut published_count = 0;
+        let mut published_count: u32 = 0;
         for (index, tx_data) in transactions.iter().enumerate() {
-            let transaction = Self::parse_transaction(tx_data, &block_header, index as u32)?;
+            let mut transaction = Self::parse_transaction(tx_data, &block_header, index as u32)?;
+            transaction.base_fee_per_gas = base_fee_per_gas.clone();
+            transaction.receipt = receipts
+                .get(&transaction.transaction_hash.to_lowercase())
+                .cloned();
+            if let (Some(kind), Some(batches)) = (rollup_kind, &rollup_batches) {
+                Self::attach_rollup_data(&mut transaction, tx_data, kind, batches);
+            }
             Self::publish_transaction(transaction)?;
             published_count += 1;
         }
@@ -241,6 +281,20 @@ impl Component {
             "[ETH-RAW] ✅ Published {} raw transactions to transactions.raw.evm",
             published_count
         );
+
+        // Every transaction is out; only now may the checkpoint move
+        let checkpoint = block_checkpoint::advance(
+            checkpoint,
+            &block_checkpoint::HandedOffBlock {
+                network: &block_header.network,
+                subnet: &block_header.subnet,
+                block_number: block_header.block_number,
+                block_hash: &block_header.block_hash,
+                tx_count: published_count,
+            },
+            &get_current_timestamp(),
+        );
+        Self::save_checkpoint(&checkpoint)?;
         eprintln!("[ETH-RAW] ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
         Ok(())
@@ -271,6 +325,36 @@ impl Component {
         Ok(config)
     }
 
+    /// Load the chain's processing checkpoint; `None` before the first block
+    fn load_checkpoint(block_header: &BlockHeader) -> Option<block_checkpoint::BlockCheckpointV1> {
+        let key = block_checkpoint::checkpoint_key(&block_header.network, &block_header.subnet);
+        let bytes = wasi::keyvalue::store::open("default")
+            .ok()?
+            .get(&key)
+            .ok()??;
+        match serde_json::from_slice(&bytes) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(e) => {
+                eprintln!(
+                    "[ETH-RAW] ⚠️  Ignoring unreadable checkpoint {}: {}",
+                    key, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Persist the checkpoint after a block was fully handed off
+    fn save_checkpoint(checkpoint: &block_checkpoint::BlockCheckpointV1) -> Result<(), String> {
+        let key = block_checkpoint::checkpoint_key(&checkpoint.network, &checkpoint.subnet);
+        let bytes = serde_json::to_vec(checkpoint)
+            .map_err(|e| format!("Failed to serialize checkpoint: {}", e))?;
+        wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?
+            .set(&key, &bytes)
+            .map_err(|e| format!("Failed to save checkpoint {}: {:?}", key, e))
+    }
+
     /// Parse URL into components for WASI HTTP request
     fn parse_url(url: &str) -> Result<(wasi::http::types::Scheme, String, String), String> {
         // Parse scheme
@@ -300,9 +384,124 @@ impl Component {
         block_hash: &str,
     ) -> Result<serde_json::Value, String> {
         eprintln!("[ETH-RAW] fetch_block_with_transactions called");
-        eprintln!("[ETH-RAW]   rpc_url: {}", rpc_url);
         eprintln!("[ETH-RAW]   block_hash: {}", block_hash);
 
+        // true = include full transaction objects
+        Self::rpc_call(
+            rpc_url,
+            "eth_getBlockByHash",
+            serde_json::json!([block_hash, true]),
+        )
+    }
+
+    /// Fetch all receipts of a block, indexed by transaction hash.
+    ///
+    /// Best effort: a node without `eth_getBlockReceipts` still yields the block's
+    /// transactions, just with fees estimated from the gas limit downstream.
+    fn fetch_block_receipts(rpc_url: &str, block_number: u64) -> HashMap<String, ReceiptData> {
+        match Self::rpc_call(
+            rpc_url,
+            "eth_getBlockReceipts",
+            serde_json::json!([format!("0x{:x}", block_number)]),
+        ) {
+            Ok(result) => receipts::index_receipts(&result),
+            Err(e) => {
+                eprintln!("[ETH-RAW] ⚠️  Failed to fetch block receipts: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Fetch L1 batch details for a ZK rollup block.
+    ///
+    /// Best effort: a node without batch endpoints still yields the block's
+    /// transactions, just without batch enrichment.
+    fn fetch_rollup_batches(
+        rpc_url: &str,
+        kind: RollupKind,
+        block_header: &BlockHeader,
+        transactions: &[serde_json::Value],
+    ) -> HashMap<u64, BatchDetails> {
+        let block_number_hex = format!("0x{:x}", block_header.block_number);
+
+        // zkSync reports the batch on each transaction; zkEVM maps blocks to batches
+        let batch_numbers: Vec<u64> = match kind {
+            RollupKind::ZkSyncEra => transactions
+                .iter()
+                .filter_map(|tx| rollup::zksync_tx_batch(tx).0)
+                .collect(),
+            RollupKind::PolygonZkEvm => Self::rpc_call(
+                rpc_url,
+                "zkevm_batchNumberByBlockNumber",
+                serde_json::json!([block_number_hex]),
+            )
+            .ok()
+            .and_then(|result| rollup::parse_batch_number(&result))
+            .into_iter()
+            .collect(),
+        };
+
+        let mut batches = HashMap::new();
+        for batch_number in batch_numbers {
+            if batches.contains_key(&batch_number) {
+                continue;
+            }
+            let params = match kind {
+                RollupKind::ZkSyncEra => serde_json::json!([batch_number]),
+                RollupKind::PolygonZkEvm => {
+                    serde_json::json!([format!("0x{:x}", batch_number), false])
+                }
+            };
+            match Self::rpc_call(rpc_url, kind.batch_details_method(), params) {
+                Ok(details) => {
+                    batches.insert(batch_number, rollup::parse_batch_details(kind, &details));
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[ETH-RAW] ⚠️  Failed to fetch batch {} details: {}",
+                        batch_number, e
+                    );
+                    batches.insert(batch_number, BatchDetails::default());
+                }
+            }
+        }
+
+        batches
+    }
+
+    /// Attach batch metadata to a parsed rollup transaction
+    fn attach_rollup_data(
+        transaction: &mut RawTransaction,
+        tx_data: &serde_json::Value,
+        kind: RollupKind,
+        batches: &HashMap<u64, BatchDetails>,
+    ) {
+        let (batch_number, batch_tx_index) = match kind {
+            RollupKind::ZkSyncEra => rollup::zksync_tx_batch(tx_data),
+            // zkEVM blocks map to a single batch
+            RollupKind::PolygonZkEvm => (batches.keys().next().copied(), None),
+        };
+        let details = batch_number
+            .and_then(|n| batches.get(&n))
+            .cloned()
+            .unwrap_or_default();
+
+        transaction.rollup = Some(RollupContext {
+            l1_batch_number: batch_number,
+            l1_batch_tx_index: batch_tx_index,
+            l1_sequence_tx_hash: details.sequence_tx_hash,
+            l1_verify_tx_hash: details.verify_tx_hash,
+        });
+    }
+
+    /// Send a JSON-RPC request and return its `result`
+    fn rpc_call(
+        rpc_url: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        eprintln!("[ETH-RAW] rpc_call {} -> {}", method, rpc_url);
+
         // Parse the RPC URL
         eprintln!("[ETH-RAW] Parsing URL...");
         let (scheme, authority, path) = match Self::parse_url(rpc_url) {
@@ -322,8 +521,8 @@ impl Component {
         // Construct JSON-RPC request
         let rpc_request = serde_json::json!({
             "jsonrpc": "2.0",
-            "method": "eth_getBlockByHash",
-            "params": [block_hash, true],  // true = include full transaction objects
+            "method": method,
+            "params": params,
             "id": 1
         });
         eprintln!("[ETH-RAW] JSON-RPC request: {}", rpc_request);
@@ -608,6 +807,7 @@ impl Component {
                 .get("type")
                 .and_then(|v| v.as_str())
                 .and_then(|s| Self::parse_hex_u8(s)),
+            base_fee_per_gas: None,
             v: tx_data
                 .get("v")
                 .and_then(|v| v.as_str())
@@ -620,6 +820,8 @@ impl Component {
                 .get("s")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
+            receipt: None,
+            rollup: None,
             processed_at: get_current_timestamp(),
             processor_id: "eth-raw-transactions-actor".to_string(),
         })
@@ -632,7 +834,7 @@ impl Component {
 
         // Publish to transactions.raw.evm topic
         let msg = types::BrokerMessage {
-            subject: "transactions.raw.evm".to_string(),
+            subject: subject_registry::prefixed("transactions.raw.evm"),
             body: tx_payload,
             reply_to: None,
         };
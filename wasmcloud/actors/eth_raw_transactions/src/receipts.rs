@@ -0,0 +1,78 @@
+//! Transaction receipts from `eth_getBlockReceipts`
+//!
+//! Receipts carry the post-execution gas accounting the transaction objects
+//! lack: gas used after refunds (unused gas and SSTORE clears), the effective
+//! gas price actually charged, and the execution status. Fetched for every ZK
+//! rollup block and for other chains that enable `fetch_receipts`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Execution results from `eth_getBlockReceipts`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptData {
+    /// Gas used after refunds (hex)
+    pub gas_used: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    /// "0x1" success, "0x0" reverted
+    pub status: String,
+}
+
+/// Index `eth_getBlockReceipts` results by transaction hash
+pub fn index_receipts(receipts: &serde_json::Value) -> HashMap<String, ReceiptData> {
+    receipts
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|receipt| {
+                    let hash = receipt.get("transactionHash")?.as_str()?.to_lowercase();
+                    let data = ReceiptData {
+                        gas_used: receipt.get("gasUsed")?.as_str()?.to_string(),
+                        effective_gas_price: hex_field(receipt, "effectiveGasPrice"),
+                        status: hex_field(receipt, "status").unwrap_or_else(|| "0x1".to_string()),
+                    };
+                    Some((hash, data))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn hex_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_index_receipts() {
+        let receipts = json!([
+            {"transactionHash": "0xABC", "gasUsed": "0x5208", "effectiveGasPrice": "0x17d7840", "status": "0x1"},
+            {"transactionHash": "0xdef", "gasUsed": "0x7530", "status": "0x0"},
+            {"gasUsed": "0x1"}
+        ]);
+
+        let indexed = index_receipts(&receipts);
+        assert_eq!(indexed.len(), 2);
+        assert_eq!(indexed["0xabc"].gas_used, "0x5208");
+        assert_eq!(
+            indexed["0xabc"].effective_gas_price.as_deref(),
+            Some("0x17d7840")
+        );
+        assert_eq!(indexed["0xdef"].status, "0x0");
+        assert_eq!(indexed["0xdef"].effective_gas_price, None);
+    }
+
+    #[test]
+    fn test_index_receipts_non_array() {
+        assert!(index_receipts(&json!(null)).is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! ZK rollup context (zkSync Era, Polygon zkEVM)
+//!
+//! Both rollups settle L2 blocks to L1 in batches and refund unused gas, so the
+//! block's transaction objects alone are not enough to compute fees or locate a
+//! transaction's batch. For these chains the actor always fetches block
+//! receipts (see `receipts`) and batch details; this module holds the pure
+//! parsing side of the batch lookups.
+
+use serde::{Deserialize, Serialize};
+
+/// ZK rollups with chain-specific ingestion semantics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollupKind {
+    ZkSyncEra,
+    PolygonZkEvm,
+}
+
+impl RollupKind {
+    /// Detect the rollup from the network name or numeric chain id
+    pub fn detect(network: &str, chain_id: &str) -> Option<Self> {
+        match network.to_lowercase().replace('_', "-").as_str() {
+            "zksync" | "zksync-era" | "era" => return Some(Self::ZkSyncEra),
+            "polygon-zkevm" | "zkevm" => return Some(Self::PolygonZkEvm),
+            _ => {}
+        }
+
+        let numeric = match chain_id.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => chain_id.parse::<u64>().ok(),
+        };
+        match numeric {
+            Some(324) | Some(300) => Some(Self::ZkSyncEra),
+            Some(1101) | Some(2442) => Some(Self::PolygonZkEvm),
+            _ => None,
+        }
+    }
+
+    /// JSON-RPC method returning L1 batch details
+    pub fn batch_details_method(&self) -> &'static str {
+        match self {
+            Self::ZkSyncEra => "zks_getL1BatchDetails",
+            Self::PolygonZkEvm => "zkevm_getBatchByNumber",
+        }
+    }
+}
+
+/// Batch/sequence metadata attached to each rollup transaction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RollupContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_tx_index: Option<u32>,
+    /// zkSync `commitTxHash` / zkEVM `sendSequencesTxHash`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_sequence_tx_hash: Option<String>,
+    /// zkSync `proveTxHash` / zkEVM `verifyBatchTxHash`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_verify_tx_hash: Option<String>,
+}
+
+/// L1 batch details shared by all transactions of a batch
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchDetails {
+    pub sequence_tx_hash: Option<String>,
+    pub verify_tx_hash: Option<String>,
+}
+
+/// Batch number and position reported on zkSync transaction objects
+pub fn zksync_tx_batch(tx_data: &serde_json::Value) -> (Option<u64>, Option<u32>) {
+    let batch = hex_field(tx_data, "l1BatchNumber").and_then(|v| parse_hex(&v));
+    let index = hex_field(tx_data, "l1BatchTxIndex")
+        .and_then(|v| parse_hex(&v))
+        .map(|v| v as u32);
+    (batch, index)
+}
+
+/// Parse the result of the rollup's batch details method
+pub fn parse_batch_details(kind: RollupKind, details: &serde_json::Value) -> BatchDetails {
+    let (sequence_key, verify_key) = match kind {
+        RollupKind::ZkSyncEra => ("commitTxHash", "proveTxHash"),
+        RollupKind::PolygonZkEvm => ("sendSequencesTxHash", "verifyBatchTxHash"),
+    };
+
+    BatchDetails {
+        sequence_tx_hash: non_zero_hash(details, sequence_key),
+        verify_tx_hash: non_zero_hash(details, verify_key),
+    }
+}
+
+/// Parse a hex or decimal JSON number (zkEVM returns batch numbers as hex strings)
+pub fn parse_batch_number(value: &serde_json::Value) -> Option<u64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => parse_hex(s),
+        _ => None,
+    }
+}
+
+fn hex_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+fn parse_hex(value: &str) -> Option<u64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse::<u64>().ok(),
+    }
+}
+
+/// Pending batches report null or an all-zero hash until the L1 tx lands
+fn non_zero_hash(value: &serde_json::Value, key: &str) -> Option<String> {
+    hex_field(value, key).filter(|hash| hash.trim_start_matches("0x").chars().any(|c| c != '0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_rollup() {
+        assert_eq!(
+            RollupKind::detect("zksync", "0x0"),
+            Some(RollupKind::ZkSyncEra)
+        );
+        assert_eq!(
+            RollupKind::detect("polygon_zkevm", "0x0"),
+            Some(RollupKind::PolygonZkEvm)
+        );
+        assert_eq!(
+            RollupKind::detect("unknown", "0x144"),
+            Some(RollupKind::ZkSyncEra)
+        );
+        assert_eq!(
+            RollupKind::detect("unknown", "1101"),
+            Some(RollupKind::PolygonZkEvm)
+        );
+        assert_eq!(RollupKind::detect("ethereum", "0x1"), None);
+    }
+
+    #[test]
+    fn test_zksync_tx_batch() {
+        let tx = json!({"l1BatchNumber": "0x7a120", "l1BatchTxIndex": "0x3"});
+        assert_eq!(zksync_tx_batch(&tx), (Some(500_000), Some(3)));
+        assert_eq!(
+            zksync_tx_batch(&json!({"l1BatchNumber": null})),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn test_parse_batch_details() {
+        let zksync = json!({
+            "commitTxHash": "0x1111111111111111111111111111111111111111111111111111111111111111",
+            "proveTxHash": null
+        });
+        let details = parse_batch_details(RollupKind::ZkSyncEra, &zksync);
+        assert!(details.sequence_tx_hash.is_some());
+        assert_eq!(details.verify_tx_hash, None);
+
+        let zkevm = json!({
+            "sendSequencesTxHash": "0x2222222222222222222222222222222222222222222222222222222222222222",
+            "verifyBatchTxHash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+        });
+        let details = parse_batch_details(RollupKind::PolygonZkEvm, &zkevm);
+        assert!(details.sequence_tx_hash.is_some());
+        assert_eq!(details.verify_tx_hash, None);
+    }
+
+    #[test]
+    fn test_parse_batch_number() {
+        assert_eq!(parse_batch_number(&json!("0x1f")), Some(31));
+        assert_eq!(parse_batch_number(&json!(42)), Some(42));
+        assert_eq!(parse_batch_number(&json!(null)), None);
+    }
+}
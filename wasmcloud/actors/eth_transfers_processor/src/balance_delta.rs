@@ -0,0 +1,254 @@
+//! Delta-encoded balance stream for dashboard websockets
+//!
+//! Every native transfer becomes one [`BalanceDeltaV1`] per touched address on
+//! `balances.delta.{network}.{subnet}`: the signed change in wei, the new
+//! balance when it is known, and a per-(address, token) version that grows by
+//! one with each delta. A client that sees a version gap resyncs from the next
+//! [`BalanceSnapshotV1`] on the same subject, which carries the latest balance
+//! and version of every stream that changed since the previous snapshot.
+//! Snapshots are cut every `SNAPSHOT_INTERVAL_SECS` of block time, so replays
+//! emit the same stream as live ingestion.
+
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the per-chain delta subjects
+pub const BALANCE_DELTA_SUBJECT_PREFIX: &str = "balances.delta";
+
+/// Token id used for the chain's native currency
+pub const NATIVE_TOKEN: &str = "native";
+
+/// Block seconds between snapshots
+pub const SNAPSHOT_INTERVAL_SECS: u64 = 30;
+
+/// Entries per snapshot message; larger snapshots are split
+pub const MAX_SNAPSHOT_ENTRIES: usize = 500;
+
+pub fn subject(network: &str, subnet: &str) -> String {
+    format!(
+        "{}.{}.{}",
+        BALANCE_DELTA_SUBJECT_PREFIX,
+        network.to_lowercase(),
+        subnet.to_lowercase()
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStreamKindV1 {
+    Delta,
+    Snapshot,
+}
+
+/// One balance change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct BalanceDeltaV1 {
+    pub schema_version: String,
+    pub kind: BalanceStreamKindV1,
+    pub network: String,
+    pub subnet: String,
+    pub address: String,
+    /// Token contract address, or `native`
+    pub token: String,
+    /// Signed decimal wei
+    pub delta: String,
+    /// Decimal wei after the change; absent until the balance is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<String>,
+    pub version: u64,
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+}
+
+/// Latest state of one stream inside a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct BalanceSnapshotEntryV1 {
+    pub address: String,
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<String>,
+    pub version: u64,
+}
+
+/// Resync point for the streams that changed since the previous snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct BalanceSnapshotV1 {
+    pub schema_version: String,
+    pub kind: BalanceStreamKindV1,
+    pub network: String,
+    pub subnet: String,
+    pub entries: Vec<BalanceSnapshotEntryV1>,
+    /// Block timestamp the snapshot was cut at
+    pub block_timestamp: u64,
+}
+
+pub fn balance_delta_schema_version_v1() -> String {
+    "balance_delta_v1".to_string()
+}
+
+pub fn balance_snapshot_schema_version_v1() -> String {
+    "balance_snapshot_v1".to_string()
+}
+
+/// Per-(address, token) stream state, stored under
+/// `balance_stream:{network}:{subnet}:{address}:{token}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceStreamV1 {
+    pub version: u64,
+    /// Decimal wei; `None` while the starting balance is unknown
+    pub balance: Option<String>,
+}
+
+impl BalanceStreamV1 {
+    /// Start a stream from a known balance (e.g. the `balance:*` cache)
+    pub fn seeded(balance: Option<String>) -> Self {
+        Self {
+            version: 0,
+            balance,
+        }
+    }
+
+    /// Apply a delta and return the new version. A balance that would go
+    /// negative means the seed was stale, so it is dropped until the next
+    /// state rebuild.
+    pub fn apply(&mut self, delta: i128) -> u64 {
+        self.version += 1;
+        self.balance = self
+            .balance
+            .as_deref()
+            .and_then(|balance| balance.parse::<i128>().ok())
+            .and_then(|balance| balance.checked_add(delta))
+            .filter(|balance| *balance >= 0)
+            .map(|balance| balance.to_string());
+        self.version
+    }
+}
+
+/// Streams touched since the last snapshot of a chain, stored under
+/// `balance_snapshot:{network}:{subnet}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotWindowV1 {
+    pub last_snapshot_at: u64,
+    /// `(address, token)` pairs, in first-touched order
+    pub touched: Vec<(String, String)>,
+}
+
+impl SnapshotWindowV1 {
+    pub fn touch(&mut self, address: &str, token: &str) {
+        if !self.touched.iter().any(|(a, t)| a == address && t == token) {
+            self.touched.push((address.to_string(), token.to_string()));
+        }
+    }
+
+    /// Take the touched streams when a snapshot is due at `block_timestamp`
+    pub fn take_due(&mut self, block_timestamp: u64) -> Option<Vec<(String, String)>> {
+        if self.last_snapshot_at == 0 {
+            self.last_snapshot_at = block_timestamp;
+        }
+        if self.touched.is_empty()
+            || block_timestamp < self.last_snapshot_at + SNAPSHOT_INTERVAL_SECS
+        {
+            return None;
+        }
+        self.last_snapshot_at = block_timestamp;
+        Some(std::mem::take(&mut self.touched))
+    }
+}
+
+/// Signed native balance changes of one transfer, per lowercase address
+///
+/// The sender pays the fee even when the transaction failed; the value only
+/// moves on success. A self-transfer nets to the fee alone, and zero changes
+/// are omitted.
+pub fn native_deltas(
+    from: &str,
+    to: &str,
+    amount_wei: u128,
+    fee_wei: u128,
+    succeeded: bool,
+) -> Vec<(String, i128)> {
+    let amount = if succeeded { amount_wei as i128 } else { 0 };
+    let from = from.to_lowercase();
+    let to = to.to_lowercase();
+
+    let mut deltas: Vec<(String, i128)> = Vec::with_capacity(2);
+    let mut add = |address: &str, delta: i128| {
+        if address.is_empty() {
+            return;
+        }
+        match deltas.iter_mut().find(|(a, _)| a == address) {
+            Some((_, total)) => *total += delta,
+            None => deltas.push((address.to_string(), delta)),
+        }
+    };
+    add(&from, -(amount + fee_wei as i128));
+    add(&to, amount);
+
+    deltas.retain(|(_, delta)| *delta != 0);
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_deltas() {
+        assert_eq!(
+            native_deltas("0xA", "0xB", 100, 5, true),
+            vec![("0xa".to_string(), -105), ("0xb".to_string(), 100)]
+        );
+        // Failed: only the fee leaves the sender
+        assert_eq!(
+            native_deltas("0xA", "0xB", 100, 5, false),
+            vec![("0xa".to_string(), -5)]
+        );
+        assert_eq!(
+            native_deltas("0xA", "0xa", 100, 5, true),
+            vec![("0xa".to_string(), -5)]
+        );
+        assert!(native_deltas("0xA", "0xB", 0, 0, true).is_empty());
+    }
+
+    #[test]
+    fn test_stream_versions_and_balance() {
+        let mut stream = BalanceStreamV1::seeded(Some("1000".to_string()));
+        assert_eq!(stream.apply(-105), 1);
+        assert_eq!(stream.balance.as_deref(), Some("895"));
+        assert_eq!(stream.apply(5), 2);
+        assert_eq!(stream.balance.as_deref(), Some("900"));
+
+        // A stale seed that would go negative is dropped, versions keep going
+        assert_eq!(stream.apply(-1000), 3);
+        assert_eq!(stream.balance, None);
+        assert_eq!(stream.apply(10), 4);
+        assert_eq!(stream.balance, None);
+    }
+
+    #[test]
+    fn test_snapshot_window_cuts_on_block_time() {
+        let mut window = SnapshotWindowV1::default();
+        window.touch("0xa", NATIVE_TOKEN);
+        assert!(window.take_due(1_000).is_none());
+
+        window.touch("0xb", NATIVE_TOKEN);
+        window.touch("0xa", NATIVE_TOKEN);
+        assert!(window.take_due(1_029).is_none());
+
+        let due = window.take_due(1_030).unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(window.last_snapshot_at, 1_030);
+        assert!(window.touched.is_empty());
+        assert!(window.take_due(1_100).is_none());
+        assert_eq!(
+            subject("Ethereum", "Mainnet"),
+            "balances.delta.ethereum.mainnet"
+        );
+    }
+}
@@ -20,11 +20,35 @@
 //!   - `transfers.processed.evm` - Processed transfers with enrichment
 //!   - `alerts.schedule.event_driven` - Alert schedule requests (Stage 1)
 //!   - `balances.updated.{chain}` - Balance change notifications
+//!   - `balances.delta.{network}.{subnet}` - Versioned per-address balance deltas and
+//!     periodic snapshots for websocket fan-out
 //!   - `ducklake.transactions.{chain}.{subnet}.write` - DuckLake persistence (Schema Redesign)
+//!   - `alerts.sweep_detected` - Sweep / consolidation into one destination
+//!
+//! ## Sweep Detection
+//! Transfers with value are tracked per destination under
+//! `sweep:window:{chain_id}:{destination}` (1 day TTL, applied by the redis-janitor
+//! per `retention_policy::SWEEP_WINDOW`). Thresholds are read from `sweep:config`.
+//! When a transfer completes a sweep, its `sweep_id` is set and the alert lists
+//! every involved transaction hash under the same id. Window updates are
+//! read-modify-write, so sweep detection assumes a single replica.
+//!
+//! ## Internal Exchange Ops
+//! Transfers between the cold and hot wallets of one labeled entity (entity links
+//! `entity:address:{chain_id}:{address}` with a `wallet_role`) are tagged with
+//! `transfer_category_context: "internal_exchange_ops"`. The tag is carried on
+//! the schedule event so alert triggers can exclude it via
+//! `exclude_category_contexts`.
+
+pub mod balance_delta;
+mod sweep;
+mod wallets;
 
 use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use sweep::{DetectedSweep, SweepConfig, SweepKind, SweepLeg, SweepWindow};
 use time::format_description::well_known::Rfc3339;
+use wallets::EntityLink;
 
 // Generate WIT bindings for the processor world
 wit_bindgen::generate!({ generate_all });
@@ -36,15 +60,7 @@ use alert_runtime_common::{
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use wasmcloud::messaging::{consumer, types};
 
-// Thread-local counter for generating unique correlation IDs
-use std::sync::atomic::{AtomicU64, Ordering};
-static COUNTER: AtomicU64 = AtomicU64::new(0);
-
-/// Get a unique monotonic timestamp value (not wall clock, but unique per call)
-/// This is used for correlation IDs and uniqueness, not for actual timing
-fn get_unique_counter() -> u64 {
-    COUNTER.fetch_add(1, Ordering::SeqCst)
-}
+subject_acl::component!("eth-transfers-processor");
 
 fn datetime_from_unix_secs(seconds: u64) -> DateTime<Utc> {
     let rendered = rfc3339_from_unix_secs(seconds);
@@ -87,11 +103,52 @@ pub struct RawTransferTransaction {
     pub r: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
+
+    // Receipt-settled data from chain plugins (ZK rollups refund unused gas)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupContext>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_accounting: Option<FeeAccounting>,
+}
+
+/// L1 batch/sequence metadata for ZK rollups (zkSync Era, Polygon zkEVM)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollupContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_tx_index: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_sequence_tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_verify_tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_tx_kind: Option<String>,
+}
+
+/// Receipt-based split of the fee in wei (EIP-1559 burn/tip, refunded gas)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeAccounting {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_burned: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_tip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_refunded: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net_fee: Option<String>,
 }
 
 /// Processed transfer with enrichment and balance context
 /// Updated for unified transactions schema (DuckLake Schema Redesign)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProcessedTransfer {
     // ═══════════════════════════════════════════════════════════════════════════
     // NETWORK IDENTIFICATION (Schema Redesign)
@@ -128,6 +185,8 @@ pub struct ProcessedTransfer {
     pub transfer_category: TransferCategory, // Micro/Small/Medium/Large/Whale
     pub sender_type: AddressType,            // EOA/Contract/Unknown
     pub recipient_type: AddressType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_category_context: Option<String>, // "internal_exchange_ops" for cold/hot moves
 
     // Balance context (populated from Redis in production)
     pub sender_balance_before: Option<String>,
@@ -158,6 +217,12 @@ pub struct ProcessedTransfer {
     pub decoding_time_ms: Option<i32>,         // Time taken to decode
     pub decoded_summary: Option<String>,       // Human-readable: "transfer 1.5 ETH to 0x742e..."
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    // PATTERN TAGS
+    // ═══════════════════════════════════════════════════════════════════════════
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sweep_id: Option<String>, // Set when this transfer completed a sweep
+
     // Legacy decoded field (JSON) - kept for backwards compatibility
     pub decoded: serde_json::Value,
 
@@ -247,6 +312,24 @@ pub struct DuckLakeTransactionRecord {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub s: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_tx_index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_sequence_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_verify_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l2_tx_kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_burned: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_tip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_refunded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_fee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub processor_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub correlation_id: Option<String>,
@@ -272,7 +355,29 @@ pub struct DuckLakeAddressTransactionRecord {
     pub transaction_subtype: Option<String>,
 }
 
+/// Payload of `alerts.sweep_detected`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepDetectedEvent {
+    pub sweep_id: String,
+    pub kind: SweepKind,
+    pub network: String,
+    pub subnet: String,
+    pub chain_id: String,
+    pub destination_address: String,
+    pub source_addresses: Vec<String>,
+    /// Every transfer in the sweep, oldest first
+    pub transaction_hashes: Vec<String>,
+    pub transfer_count: u32,
+    pub total_value_wei: String,
+    pub total_value_native: f64,
+    pub total_value: String, // "12.500000 ETH"
+    pub first_block_timestamp: u64,
+    pub last_block_timestamp: u64,
+    pub detected_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum TransferCategory {
     Micro,  // < 0.01 ETH
     Small,  // 0.01 - 1 ETH
@@ -282,6 +387,7 @@ pub enum TransferCategory {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum AddressType {
     ExternallyOwnedAccount, // EOA
     Contract,
@@ -297,13 +403,19 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages containing transfer transactions
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| Self::get_json(&retention_policy::REPLAY_CONFIG.key("")));
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+
         // Check if subject matches transfer pattern
-        if !msg.subject.starts_with("transfer-transactions.") || !msg.subject.ends_with(".raw") {
+        let subject = subject_registry::unprefixed(&msg.subject).unwrap_or_default();
+        if !subject.starts_with("transfer-transactions.") || !subject.ends_with(".raw") {
             return Ok(());
         }
 
         // Extract network context from subject: transfer-transactions.{network}.{subnet}.{vm_type}.raw
-        let (network, subnet, vm_type) = Self::parse_subject_context(&msg.subject)?;
+        let (network, subnet, vm_type) = Self::parse_subject_context(subject)?;
 
         // Parse the transfer transaction from the message
         let raw_transfer: RawTransferTransaction = serde_json::from_slice(&msg.body)
@@ -342,6 +454,8 @@ impl Component {
         let chain_id_numeric = Self::parse_hex_u128(&raw_transfer.chain_id) as i64;
         let canonical_network = Self::canonical_network_name(&network, chain_id_numeric);
         let normalized_subnet = subnet.to_lowercase();
+        // Sweep alerts do not name their chain; dry run follows the transfer's
+        feature_flags::dry_run::set_chain(&canonical_network, &normalized_subnet);
 
         // Parse block number
         let block_number = Self::parse_hex_u64(&raw_transfer.block_number);
@@ -352,9 +466,18 @@ impl Component {
         // Parse gas limit
         let gas_limit = Self::parse_hex_u64(&raw_transfer.gas);
 
-        // Calculate transaction fee (estimate using gas limit since gas_used not in raw tx)
-        let transaction_fee_wei =
-            Self::calculate_transaction_fee(gas_limit, &raw_transfer.gas_price);
+        // Calculate transaction fee from receipt data when the chain plugin provided it,
+        // otherwise estimate using gas limit since gas_used is not in the raw tx
+        let gas_used = raw_transfer
+            .gas_used
+            .as_deref()
+            .map(Self::parse_hex_u64)
+            .unwrap_or(gas_limit);
+        let fee_gas_price = raw_transfer
+            .effective_gas_price
+            .as_deref()
+            .unwrap_or(&raw_transfer.gas_price);
+        let transaction_fee_wei = Self::calculate_transaction_fee(gas_used, fee_gas_price);
         let transaction_fee_eth = Self::wei_to_eth(&transaction_fee_wei);
 
         // Categorize transfer size
@@ -365,7 +488,7 @@ impl Component {
         let recipient_type = Self::determine_address_type(&raw_transfer.to);
 
         // Generate correlation ID
-        let correlation_id = format!("{}-{}", raw_transfer.hash, get_unique_counter());
+        let correlation_id = format!("{}-{}", raw_transfer.hash, replay_clock::next_counter());
 
         // Create decoded transfer details
         let decoded = Self::create_decoded_transfer_details(
@@ -388,11 +511,18 @@ impl Component {
             .block_timestamp
             .as_ref()
             .map(|ts| Self::parse_hex_u64(ts))
-            .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+            .unwrap_or_else(|| replay_clock::now().timestamp() as u64);
 
         // Construct chain_id for partitioning (Schema Redesign)
         let chain_id = format!("{}_{}", canonical_network, normalized_subnet);
 
+        // Cold/hot moves between one labeled entity's wallets are internal ops
+        let transfer_category_context = wallets::transfer_category_context(
+            Self::lookup_entity(&chain_id, &raw_transfer.from).as_ref(),
+            Self::lookup_entity(&chain_id, &raw_transfer.to).as_ref(),
+        )
+        .map(str::to_string);
+
         // Create human-readable decoded summary for native transfers
         let to_short = if raw_transfer.to.len() > 10 {
             format!(
@@ -409,7 +539,7 @@ impl Component {
         );
 
         // Create processed transfer with unified schema fields
-        let processed_transfer = ProcessedTransfer {
+        let mut processed_transfer = ProcessedTransfer {
             // Network identification (Schema Redesign)
             network: canonical_network.clone(),
             subnet: normalized_subnet.clone(),
@@ -427,7 +557,7 @@ impl Component {
             amount_wei: raw_transfer.value.clone(),
             amount_native: amount_eth, // Renamed from amount_eth
             amount_usd: None,          // Would be calculated from price oracle in production
-            gas_used: gas_limit,       // Use gas limit as estimate (actual gas_used in receipt)
+            gas_used,                  // Gas limit as estimate unless receipt data was attached
             gas_price: raw_transfer.gas_price.clone(),
             transaction_fee_wei,
             transaction_fee_native: transaction_fee_eth, // Renamed from transaction_fee_eth
@@ -437,6 +567,7 @@ impl Component {
             transfer_category,
             sender_type,
             recipient_type,
+            transfer_category_context,
 
             // Balance context (populated from Redis in production)
             sender_balance_before: None,
@@ -462,6 +593,7 @@ impl Component {
             abi_source: None,
             decoding_time_ms: Some(0), // No decoding time for native transfers
             decoded_summary: Some(decoded_summary),
+            sweep_id: None,
 
             // Legacy decoded field for backwards compatibility
             decoded,
@@ -472,6 +604,16 @@ impl Component {
             correlation_id,
         };
 
+        // Track the destination's sweep window; a completed sweep tags this transfer
+        let sweep_event = Self::track_sweep(
+            &processed_transfer,
+            &raw_transfer.nonce,
+            &transaction_currency,
+        );
+        if let Some(event) = &sweep_event {
+            processed_transfer.sweep_id = Some(event.sweep_id.clone());
+        }
+
         // Publish to all destinations
         Self::publish_processed_transfer(
             &processed_transfer,
@@ -482,9 +624,196 @@ impl Component {
             &normalized_subnet,
         )?;
 
+        Self::publish_balance_deltas(&processed_transfer, &raw_transfer);
+
+        if let Some(event) = sweep_event {
+            let sweep_payload = serde_json::to_vec(&event)
+                .map_err(|e| format!("Failed to serialize sweep event: {}", e))?;
+            Self::publish_message("alerts.sweep_detected", &sweep_payload)?;
+            eprintln!(
+                "[ETH-TRANSFERS] 🚨 Sweep {} into {}: {} transfers, {}",
+                event.sweep_id, event.destination_address, event.transfer_count, event.total_value
+            );
+        }
+
         Ok(())
     }
 
+    /// Record a transfer in its destination's sweep window
+    ///
+    /// Window state is best effort: Redis failures are logged and never block
+    /// transfer processing.
+    fn track_sweep(
+        transfer: &ProcessedTransfer,
+        nonce: &str,
+        currency: &str,
+    ) -> Option<SweepDetectedEvent> {
+        let amount_wei = Self::normalize_quantity_string(&transfer.amount_wei);
+        if amount_wei == "0" || transfer.to_address.is_empty() {
+            return None;
+        }
+
+        let destination = transfer.to_address.to_lowercase();
+        let window_key =
+            retention_policy::SWEEP_WINDOW.key(&format!("{}:{}", transfer.chain_id, destination));
+        let config: SweepConfig =
+            Self::get_json(&retention_policy::SWEEP_CONFIG.key("")).unwrap_or_default();
+        let mut window: SweepWindow = Self::get_json(&window_key).unwrap_or_default();
+
+        let detected = window.record(
+            SweepLeg {
+                transaction_hash: transfer.transaction_hash.clone(),
+                from_address: transfer.from_address.to_lowercase(),
+                nonce: Self::parse_hex_u64(nonce),
+                amount_wei,
+                block_timestamp: transfer.block_timestamp,
+            },
+            &config,
+        );
+        if let Err(e) = Self::set_json(&window_key, &window) {
+            eprintln!("[ETH-TRANSFERS] ⚠️ Failed to persist sweep window: {}", e);
+        }
+
+        detected.map(|sweep| Self::build_sweep_event(transfer, &destination, &sweep, currency))
+    }
+
+    /// Publish the transfer's balance deltas, then a snapshot when one is due
+    ///
+    /// Stream state is best effort like sweep windows: failures are logged and
+    /// never block transfer processing.
+    fn publish_balance_deltas(transfer: &ProcessedTransfer, raw_transfer: &RawTransferTransaction) {
+        let deltas = balance_delta::native_deltas(
+            &transfer.from_address,
+            &transfer.to_address,
+            Self::parse_hex_u128(&raw_transfer.value),
+            Self::parse_hex_u128(&transfer.transaction_fee_wei),
+            raw_transfer.status.as_deref() != Some("0x0"),
+        );
+        if deltas.is_empty() {
+            return;
+        }
+
+        let chain = format!("{}:{}", transfer.network, transfer.subnet);
+        let subject = balance_delta::subject(&transfer.network, &transfer.subnet);
+        let window_key = retention_policy::BALANCE_SNAPSHOT_WINDOW.key(&chain);
+        let mut window: balance_delta::SnapshotWindowV1 =
+            Self::get_json(&window_key).unwrap_or_default();
+        let token = balance_delta::NATIVE_TOKEN;
+
+        for (address, delta) in deltas {
+            let stream_key =
+                retention_policy::BALANCE_STREAM.key(&format!("{}:{}:{}", chain, address, token));
+            let mut stream: balance_delta::BalanceStreamV1 = Self::get_json(&stream_key)
+                .unwrap_or_else(|| {
+                    let cached: Option<serde_json::Value> = Self::get_json(
+                        &retention_policy::BALANCE_LATEST
+                            .key(&format!("{}:{}:{}", chain, address, token)),
+                    );
+                    balance_delta::BalanceStreamV1::seeded(
+                        cached.and_then(|v| v.get("balance")?.as_str().map(str::to_string)),
+                    )
+                });
+            let version = stream.apply(delta);
+            if let Err(e) = Self::set_json(&stream_key, &stream) {
+                eprintln!("[ETH-TRANSFERS] ⚠️ Failed to persist balance stream: {}", e);
+            }
+            window.touch(&address, token);
+
+            let message = balance_delta::BalanceDeltaV1 {
+                schema_version: balance_delta::balance_delta_schema_version_v1(),
+                kind: balance_delta::BalanceStreamKindV1::Delta,
+                network: transfer.network.clone(),
+                subnet: transfer.subnet.clone(),
+                address,
+                token: token.to_string(),
+                delta: delta.to_string(),
+                balance: stream.balance,
+                version,
+                transaction_hash: transfer.transaction_hash.clone(),
+                block_number: transfer.block_number,
+                block_timestamp: transfer.block_timestamp,
+            };
+            if let Err(e) = serde_json::to_vec(&message)
+                .map_err(|e| e.to_string())
+                .and_then(|payload| Self::publish_message(&subject, &payload))
+            {
+                eprintln!("[ETH-TRANSFERS] ⚠️ Failed to publish balance delta: {}", e);
+            }
+        }
+
+        if let Some(touched) = window.take_due(transfer.block_timestamp) {
+            let entries: Vec<balance_delta::BalanceSnapshotEntryV1> = touched
+                .into_iter()
+                .map(|(address, token)| {
+                    let stream: balance_delta::BalanceStreamV1 = Self::get_json(
+                        &retention_policy::BALANCE_STREAM
+                            .key(&format!("{}:{}:{}", chain, address, token)),
+                    )
+                    .unwrap_or_default();
+                    balance_delta::BalanceSnapshotEntryV1 {
+                        address,
+                        token,
+                        balance: stream.balance,
+                        version: stream.version,
+                    }
+                })
+                .collect();
+            for chunk in entries.chunks(balance_delta::MAX_SNAPSHOT_ENTRIES) {
+                let snapshot = balance_delta::BalanceSnapshotV1 {
+                    schema_version: balance_delta::balance_snapshot_schema_version_v1(),
+                    kind: balance_delta::BalanceStreamKindV1::Snapshot,
+                    network: transfer.network.clone(),
+                    subnet: transfer.subnet.clone(),
+                    entries: chunk.to_vec(),
+                    block_timestamp: transfer.block_timestamp,
+                };
+                if let Err(e) = serde_json::to_vec(&snapshot)
+                    .map_err(|e| e.to_string())
+                    .and_then(|payload| Self::publish_message(&subject, &payload))
+                {
+                    eprintln!(
+                        "[ETH-TRANSFERS] ⚠️ Failed to publish balance snapshot: {}",
+                        e
+                    );
+                }
+            }
+        }
+        if let Err(e) = Self::set_json(&window_key, &window) {
+            eprintln!(
+                "[ETH-TRANSFERS] ⚠️ Failed to persist snapshot window: {}",
+                e
+            );
+        }
+    }
+
+    fn build_sweep_event(
+        transfer: &ProcessedTransfer,
+        destination: &str,
+        sweep: &DetectedSweep,
+        currency: &str,
+    ) -> SweepDetectedEvent {
+        let total_wei = sweep.total_value_wei();
+        let total_native = total_wei as f64 / 1_000_000_000_000_000_000.0;
+
+        SweepDetectedEvent {
+            sweep_id: format!("sweep-{}-{}", transfer.chain_id, transfer.transaction_hash),
+            kind: sweep.kind,
+            network: transfer.network.clone(),
+            subnet: transfer.subnet.clone(),
+            chain_id: transfer.chain_id.clone(),
+            destination_address: destination.to_string(),
+            source_addresses: sweep.source_addresses(),
+            transaction_hashes: sweep.transaction_hashes(),
+            transfer_count: sweep.legs.len() as u32,
+            total_value_wei: total_wei.to_string(),
+            total_value_native: total_native,
+            total_value: format!("{:.6} {}", total_native, currency),
+            first_block_timestamp: sweep.first_block_timestamp(),
+            last_block_timestamp: sweep.last_block_timestamp(),
+            detected_at: rfc3339_from_unix_secs(transfer.block_timestamp),
+        }
+    }
+
     /// Convert Wei (as hex string) to ETH (as f64)
     fn wei_to_eth(wei_hex: &str) -> f64 {
         let wei_value = Self::parse_hex_u128(wei_hex);
@@ -720,6 +1049,7 @@ impl Component {
                     method_selector,
                     value_wei: transfer.amount_wei.clone(),
                     value_native: transfer.amount_native,
+                    transfer_category_context: transfer.transfer_category_context.clone(),
                     block_number: transfer.block_number as i64,
                     block_timestamp: event_time,
                 }),
@@ -775,6 +1105,17 @@ impl Component {
             .map(|value| Self::parse_hex_u64(value));
 
         let gas_price = Self::normalize_quantity_string(&raw_transfer.gas_price);
+        let effective_gas_price = raw_transfer
+            .effective_gas_price
+            .as_deref()
+            .map(Self::normalize_quantity_string)
+            .unwrap_or_else(|| gas_price.clone());
+        let status = match raw_transfer.status.as_deref() {
+            Some("0x0") => "FAILED",
+            _ => "SUCCESS",
+        };
+        let rollup = raw_transfer.rollup.clone().unwrap_or_default();
+        let fees = raw_transfer.fee_accounting.clone().unwrap_or_default();
         let value = Self::normalize_quantity_string(&raw_transfer.value);
         let transaction_fee =
             Self::normalize_quantity_string(&processed_transfer.transaction_fee_wei);
@@ -804,13 +1145,13 @@ impl Component {
             to_address: Self::optional_string(&processed_transfer.to_address),
             value: Some(value),
             gas_limit: Some(gas_limit),
-            gas_used: Some(gas_limit),
-            gas_price: Some(gas_price.clone()),
+            gas_used: Some(processed_transfer.gas_used),
+            gas_price: Some(gas_price),
             max_fee_per_gas: None,
             max_priority_fee_per_gas: None,
-            status: "SUCCESS".to_string(),
+            status: status.to_string(),
             transaction_fee: Some(transaction_fee),
-            effective_gas_price: Some(gas_price),
+            effective_gas_price: Some(effective_gas_price),
             input_data,
             method_signature: Self::extract_method_selector(input),
             transaction_type: processed_transfer.transaction_type.clone(),
@@ -833,6 +1174,15 @@ impl Component {
             v,
             r: raw_transfer.r.clone(),
             s: raw_transfer.s.clone(),
+            l1_batch_number: rollup.l1_batch_number,
+            l1_batch_tx_index: rollup.l1_batch_tx_index,
+            l1_sequence_tx_hash: rollup.l1_sequence_tx_hash,
+            l1_verify_tx_hash: rollup.l1_verify_tx_hash,
+            l2_tx_kind: rollup.l2_tx_kind,
+            fee_burned: fees.fee_burned,
+            fee_tip: fees.fee_tip,
+            fee_refunded: fees.fee_refunded,
+            net_fee: fees.net_fee,
             processor_id: Some(processed_transfer.processor_id.clone()),
             correlation_id: Some(processed_transfer.correlation_id.clone()),
         }
@@ -885,10 +1235,41 @@ impl Component {
         ]
     }
 
+    /// Entity link for an address; chain-specific links take precedence
+    fn lookup_entity(chain_id: &str, address: &str) -> Option<EntityLink> {
+        let address = address.to_lowercase();
+        Self::get_json(&retention_policy::ENTITY_LINK.key(&format!("{}:{}", chain_id, address)))
+            .or_else(|| Self::get_json(&retention_policy::ENTITY_LINK.key(&address)))
+    }
+
+    fn get_json<T: serde::de::DeserializeOwned>(key: &str) -> Option<T> {
+        let bucket = wasi::keyvalue::store::open("default").ok()?;
+        let bytes = bucket.get(key).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn set_json<T: Serialize>(key: &str, value: &T) -> Result<(), String> {
+        let payload =
+            serde_json::to_vec(value).map_err(|e| format!("Failed to serialize {}: {}", key, e))?;
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        bucket
+            .set(key, &payload)
+            .map_err(|e| format!("Failed to set key {}: {:?}", key, e))
+    }
+
     /// Helper to publish a message to a subject
     fn publish_message(subject: &str, payload: &[u8]) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
         let msg = types::BrokerMessage {
-            subject: subject.to_string(),
+            subject: subject_registry::prefixed(&feature_flags::dry_run::route(subject)),
             body: payload.to_vec(),
             reply_to: None,
         };
@@ -928,6 +1309,11 @@ mod tests {
             s: Some(
                 "0xfedcba0987654321fedcba0987654321fedcba0987654321fedcba0987654321".to_string(),
             ),
+            gas_used: None,
+            effective_gas_price: None,
+            status: None,
+            rollup: None,
+            fee_accounting: None,
         }
     }
 
@@ -958,6 +1344,7 @@ mod tests {
             transfer_category: TransferCategory::Medium,
             sender_type: AddressType::ExternallyOwnedAccount,
             recipient_type: AddressType::ExternallyOwnedAccount,
+            transfer_category_context: None,
             sender_balance_before: None,
             sender_balance_after: None,
             recipient_balance_before: None,
@@ -976,6 +1363,7 @@ mod tests {
             abi_source: None,
             decoding_time_ms: Some(0),
             decoded_summary: None,
+            sweep_id: None,
             decoded: serde_json::json!({}),
             processed_at: "2024-01-01T00:00:00Z".to_string(),
             processor_id: "test".to_string(),
@@ -1133,6 +1521,63 @@ mod tests {
         assert!(json.get("transaction_fee_native").is_none());
     }
 
+    #[test]
+    fn test_build_ducklake_transaction_record_maps_rollup_settlement() {
+        let mut raw_transfer = create_test_transfer();
+        raw_transfer.effective_gas_price = Some("0x3b9aca00".to_string());
+        raw_transfer.status = Some("0x0".to_string());
+        raw_transfer.rollup = Some(RollupContext {
+            l1_batch_number: Some(500_000),
+            l1_batch_tx_index: Some(3),
+            l1_sequence_tx_hash: Some("0xcommit".to_string()),
+            l1_verify_tx_hash: None,
+            l2_tx_kind: Some("eip712".to_string()),
+        });
+        let processed_transfer = create_test_processed_transfer();
+
+        let record = Component::build_ducklake_transaction_record(
+            &processed_transfer,
+            &raw_transfer,
+            &raw_transfer.input,
+        );
+
+        assert_eq!(record.status, "FAILED");
+        assert_eq!(record.effective_gas_price.as_deref(), Some("1000000000"));
+        assert_eq!(record.l1_batch_number, Some(500_000));
+        assert_eq!(record.l1_batch_tx_index, Some(3));
+        assert_eq!(record.l1_sequence_tx_hash.as_deref(), Some("0xcommit"));
+        assert_eq!(record.l2_tx_kind.as_deref(), Some("eip712"));
+
+        let json = serde_json::to_value(&record).expect("ducklake record should serialize");
+        assert!(json.get("l1_verify_tx_hash").is_none());
+    }
+
+    #[test]
+    fn test_build_ducklake_transaction_record_maps_fee_accounting() {
+        let mut raw_transfer = create_test_transfer();
+        raw_transfer.fee_accounting = Some(FeeAccounting {
+            fee_burned: Some("315000000000000".to_string()),
+            fee_tip: Some("105000000000000".to_string()),
+            fee_refunded: Some("0".to_string()),
+            net_fee: Some("420000000000000".to_string()),
+        });
+        let processed_transfer = create_test_processed_transfer();
+
+        let record = Component::build_ducklake_transaction_record(
+            &processed_transfer,
+            &raw_transfer,
+            &raw_transfer.input,
+        );
+
+        assert_eq!(record.fee_burned.as_deref(), Some("315000000000000"));
+        assert_eq!(record.fee_tip.as_deref(), Some("105000000000000"));
+        assert_eq!(record.fee_refunded.as_deref(), Some("0"));
+        assert_eq!(record.net_fee.as_deref(), Some("420000000000000"));
+
+        let json = serde_json::to_value(&record).expect("ducklake record should serialize");
+        assert_eq!(json["net_fee"], "420000000000000");
+    }
+
     #[test]
     fn test_build_address_transaction_records() {
         let raw_transfer = create_test_transfer();
@@ -1326,7 +1771,8 @@ mod tests {
 
     #[test]
     fn test_build_schedule_event() {
-        let transfer = create_test_processed_transfer();
+        let mut transfer = create_test_processed_transfer();
+        transfer.transfer_category_context = Some("internal_exchange_ops".to_string());
         let chain = Component::get_network_currency("ethereum");
         let candidate_keys = Component::build_candidate_target_keys(
             &chain,
@@ -1362,6 +1808,56 @@ mod tests {
             transfer.block_timestamp as i64
         );
         assert_eq!(event.event.evm_tx.as_ref().unwrap().method_selector, None);
+        assert_eq!(
+            event
+                .event
+                .evm_tx
+                .as_ref()
+                .unwrap()
+                .transfer_category_context
+                .as_deref(),
+            Some("internal_exchange_ops")
+        );
+    }
+
+    #[test]
+    fn test_build_sweep_event_summarizes_legs() {
+        let transfer = create_test_processed_transfer();
+        let mut window = SweepWindow::default();
+        let config = SweepConfig {
+            min_sequential: 2,
+            ..SweepConfig::default()
+        };
+        let leg = |hash: &str, nonce: u64, ts: u64| SweepLeg {
+            transaction_hash: hash.to_string(),
+            from_address: transfer.from_address.to_lowercase(),
+            nonce,
+            amount_wei: "1500000000000000000".to_string(),
+            block_timestamp: ts,
+        };
+        window.record(leg("0xaa", 41, 1698999900), &config);
+        let sweep = window
+            .record(leg(&transfer.transaction_hash, 42, 1699000000), &config)
+            .unwrap();
+
+        let event = Component::build_sweep_event(
+            &transfer,
+            &transfer.to_address.to_lowercase(),
+            &sweep,
+            "ETH",
+        );
+
+        assert_eq!(event.kind, SweepKind::SequentialDrain);
+        assert_eq!(
+            event.sweep_id,
+            format!("sweep-ethereum_mainnet-{}", transfer.transaction_hash)
+        );
+        assert_eq!(event.transfer_count, 2);
+        assert_eq!(event.transaction_hashes[0], "0xaa");
+        assert_eq!(event.total_value_wei, "3000000000000000000");
+        assert_eq!(event.total_value, "3.000000 ETH");
+        assert_eq!(event.first_block_timestamp, 1698999900);
+        assert_eq!(event.detected_at, "2023-11-03T08:26:40Z");
     }
 
     #[test]
@@ -0,0 +1,259 @@
+//! Sweep / consolidation pattern recognition
+//!
+//! A sweep moves funds from one or more addresses into a single destination
+//! within minutes: exchanges consolidating deposit addresses (many sources, one
+//! destination) and drainers emptying a compromised wallet (sequential nonces
+//! from one source). Transfers are tracked per destination in a short window
+//! persisted as JSON in Redis. The window slides with block timestamps, not
+//! wall clock, so replays detect the same sweeps as live ingestion.
+
+use serde::{Deserialize, Serialize};
+
+/// Detection thresholds, stored as JSON under `sweep:config`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepConfig {
+    /// Legs older than this (relative to the newest leg) leave the window
+    pub window_secs: u64,
+    /// Distinct source addresses that make a consolidation
+    pub min_sources: usize,
+    /// Consecutive-nonce transfers from one source that make a drain
+    pub min_sequential: usize,
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 600,
+            min_sources: 5,
+            min_sequential: 3,
+        }
+    }
+}
+
+/// One transfer into the tracked destination
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepLeg {
+    pub transaction_hash: String,
+    pub from_address: String,
+    pub nonce: u64,
+    /// Decimal wei
+    pub amount_wei: String,
+    pub block_timestamp: u64,
+}
+
+/// Persisted window state for one destination
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SweepWindow {
+    pub legs: Vec<SweepLeg>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepKind {
+    /// Many source addresses into one destination
+    Consolidation,
+    /// Consecutive nonces from one source into one destination
+    SequentialDrain,
+}
+
+/// Legs that formed a sweep; they are removed from the window once reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedSweep {
+    pub kind: SweepKind,
+    pub legs: Vec<SweepLeg>,
+}
+
+impl DetectedSweep {
+    pub fn source_addresses(&self) -> Vec<String> {
+        let mut sources: Vec<String> = self.legs.iter().map(|l| l.from_address.clone()).collect();
+        sources.sort();
+        sources.dedup();
+        sources
+    }
+
+    pub fn transaction_hashes(&self) -> Vec<String> {
+        self.legs
+            .iter()
+            .map(|l| l.transaction_hash.clone())
+            .collect()
+    }
+
+    /// Sum of leg amounts in wei
+    pub fn total_value_wei(&self) -> u128 {
+        self.legs
+            .iter()
+            .map(|l| l.amount_wei.parse::<u128>().unwrap_or(0))
+            .fold(0u128, |acc, v| acc.saturating_add(v))
+    }
+
+    pub fn first_block_timestamp(&self) -> u64 {
+        self.legs
+            .iter()
+            .map(|l| l.block_timestamp)
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub fn last_block_timestamp(&self) -> u64 {
+        self.legs
+            .iter()
+            .map(|l| l.block_timestamp)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl SweepWindow {
+    /// Add a leg, prune the window and return a sweep if the leg completed one
+    ///
+    /// Legs older than the window and already-seen transactions are ignored.
+    pub fn record(&mut self, leg: SweepLeg, config: &SweepConfig) -> Option<DetectedSweep> {
+        if self
+            .legs
+            .iter()
+            .any(|l| l.transaction_hash == leg.transaction_hash)
+        {
+            return None;
+        }
+
+        let newest = self
+            .legs
+            .iter()
+            .map(|l| l.block_timestamp)
+            .max()
+            .unwrap_or(leg.block_timestamp)
+            .max(leg.block_timestamp);
+        if leg.block_timestamp + config.window_secs < newest {
+            return None;
+        }
+        self.legs
+            .retain(|l| l.block_timestamp + config.window_secs >= newest);
+
+        let source = leg.from_address.to_lowercase();
+        let nonce = leg.nonce;
+        self.legs.push(leg);
+        self.legs.sort_by_key(|l| l.block_timestamp);
+
+        let mut sources: Vec<String> = self
+            .legs
+            .iter()
+            .map(|l| l.from_address.to_lowercase())
+            .collect();
+        sources.sort();
+        sources.dedup();
+        if sources.len() >= config.min_sources.max(2) {
+            return Some(DetectedSweep {
+                kind: SweepKind::Consolidation,
+                legs: std::mem::take(&mut self.legs),
+            });
+        }
+
+        let run = self.nonce_run(&source, nonce);
+        if run.len() >= config.min_sequential.max(2) {
+            let legs: Vec<SweepLeg> = self
+                .legs
+                .iter()
+                .filter(|l| l.from_address.to_lowercase() == source && run.contains(&l.nonce))
+                .cloned()
+                .collect();
+            self.legs
+                .retain(|l| l.from_address.to_lowercase() != source || !run.contains(&l.nonce));
+            return Some(DetectedSweep {
+                kind: SweepKind::SequentialDrain,
+                legs,
+            });
+        }
+
+        None
+    }
+
+    /// Consecutive nonces from `source` around `nonce`
+    fn nonce_run(&self, source: &str, nonce: u64) -> Vec<u64> {
+        let nonces: Vec<u64> = self
+            .legs
+            .iter()
+            .filter(|l| l.from_address.to_lowercase() == source)
+            .map(|l| l.nonce)
+            .collect();
+
+        let mut low = nonce;
+        while low > 0 && nonces.contains(&(low - 1)) {
+            low -= 1;
+        }
+        let mut high = nonce;
+        while nonces.contains(&(high + 1)) {
+            high += 1;
+        }
+        (low..=high).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(hash: &str, from: &str, nonce: u64, wei: u128, ts: u64) -> SweepLeg {
+        SweepLeg {
+            transaction_hash: hash.to_string(),
+            from_address: from.to_string(),
+            nonce,
+            amount_wei: wei.to_string(),
+            block_timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn test_sequential_nonces_from_one_source_form_a_drain() {
+        let config = SweepConfig::default();
+        let mut window = SweepWindow::default();
+
+        assert!(window
+            .record(leg("0x1", "0xA", 7, 100, 1_000), &config)
+            .is_none());
+        assert!(window
+            .record(leg("0x1", "0xA", 7, 100, 1_000), &config)
+            .is_none());
+        assert!(window
+            .record(leg("0x2", "0xa", 9, 50, 1_030), &config)
+            .is_none());
+        let sweep = window
+            .record(leg("0x3", "0xA", 8, 25, 1_020), &config)
+            .unwrap();
+
+        assert_eq!(sweep.kind, SweepKind::SequentialDrain);
+        assert_eq!(sweep.transaction_hashes(), vec!["0x1", "0x3", "0x2"]);
+        assert_eq!(sweep.total_value_wei(), 175);
+        assert_eq!(sweep.first_block_timestamp(), 1_000);
+        assert_eq!(sweep.last_block_timestamp(), 1_030);
+        assert!(window.legs.is_empty());
+    }
+
+    #[test]
+    fn test_many_sources_form_a_consolidation_within_the_window() {
+        let config = SweepConfig {
+            min_sources: 3,
+            ..SweepConfig::default()
+        };
+        let mut window = SweepWindow::default();
+
+        window.record(leg("0x1", "0xa", 1, 10, 0), &config);
+        // The first leg falls out of the window before the consolidation completes
+        window.record(leg("0x2", "0xb", 1, 20, 700), &config);
+        assert!(window
+            .record(leg("0x3", "0xc", 4, 30, 750), &config)
+            .is_none());
+        // Late legs are ignored
+        assert!(window
+            .record(leg("0x4", "0xd", 1, 40, 10), &config)
+            .is_none());
+
+        let sweep = window
+            .record(leg("0x5", "0xd", 2, 40, 760), &config)
+            .unwrap();
+        assert_eq!(sweep.kind, SweepKind::Consolidation);
+        assert_eq!(sweep.source_addresses(), vec!["0xb", "0xc", "0xd"]);
+        assert_eq!(sweep.total_value_wei(), 90);
+        assert!(window.legs.is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+//! Cold/hot wallet classification for labeled entities
+//!
+//! The clustering/labels system links addresses to entities under
+//! `entity:address:{chain_id}:{address}` (or `entity:address:{address}` for all
+//! chains) and may mark a wallet as the entity's cold or hot wallet. A transfer
+//! between two such wallets of the same entity is treasury housekeeping rather
+//! than a genuine inflow or outflow, and is tagged so alert rules can skip it.
+
+use alert_runtime_common::INTERNAL_EXCHANGE_OPS;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletRole {
+    Cold,
+    Hot,
+}
+
+/// Address entity link as written by the clustering/labels system
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityLink {
+    pub entity_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_role: Option<WalletRole>,
+}
+
+/// `transfer_category_context` for a transfer between the two linked addresses
+pub fn transfer_category_context(
+    from: Option<&EntityLink>,
+    to: Option<&EntityLink>,
+) -> Option<&'static str> {
+    let (from, to) = (from?, to?);
+    let internal =
+        from.entity_id == to.entity_id && from.wallet_role.is_some() && to.wallet_role.is_some();
+    internal.then_some(INTERNAL_EXCHANGE_OPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(entity_id: &str, role: Option<WalletRole>) -> EntityLink {
+        EntityLink {
+            entity_id: entity_id.to_string(),
+            label: None,
+            wallet_role: role,
+        }
+    }
+
+    #[test]
+    fn test_cold_hot_moves_within_an_entity_are_internal_ops() {
+        let cold = link("binance", Some(WalletRole::Cold));
+        let hot = link("binance", Some(WalletRole::Hot));
+
+        assert_eq!(
+            transfer_category_context(Some(&cold), Some(&hot)),
+            Some("internal_exchange_ops")
+        );
+        assert_eq!(
+            transfer_category_context(Some(&hot), Some(&cold)),
+            Some("internal_exchange_ops")
+        );
+
+        // Deposits, other entities and unlabeled counterparties stay genuine flows
+        let deposit = link("binance", None);
+        let other = link("kraken", Some(WalletRole::Hot));
+        assert_eq!(transfer_category_context(Some(&deposit), Some(&hot)), None);
+        assert_eq!(transfer_category_context(Some(&other), Some(&cold)), None);
+        assert_eq!(transfer_category_context(None, Some(&cold)), None);
+
+        let parsed: EntityLink =
+            serde_json::from_str(r#"{"entity_id":"binance","wallet_role":"cold"}"#).unwrap();
+        assert_eq!(parsed, cold);
+    }
+}
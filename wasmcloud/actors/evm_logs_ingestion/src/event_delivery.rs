@@ -0,0 +1,171 @@
+//! Decoded event subscriptions (`shared/event-subscriptions`)
+//!
+//! Serves the `events.subscriptions.*` registry requests and, per block,
+//! decodes logs matching a registered `(contract, event)` and delivers them to
+//! the subscription's subject or webhook within its per-minute rate limit.
+
+use std::collections::HashMap;
+
+use event_subscriptions::{
+    decoded_event_schema_version_v1, DecodedEventV1, RateWindowV1, SubscriptionIndex,
+    SubscriptionStore,
+};
+
+use crate::{Component, RpcLog};
+
+/// wasi:keyvalue access for the subscription registry
+pub struct KvStore;
+
+impl KvStore {
+    fn bucket() -> Result<crate::wasi::keyvalue::store::Bucket, String> {
+        crate::wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))
+    }
+}
+
+impl SubscriptionStore for KvStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Self::bucket()?
+            .get(key)
+            .map_err(|e| format!("Failed to get {}: {:?}", key, e))
+    }
+
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        Self::bucket()?
+            .set(key, value)
+            .map_err(|e| format!("Failed to set {}: {:?}", key, e))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        Self::bucket()?
+            .delete(key)
+            .map_err(|e| format!("Failed to delete {}: {:?}", key, e))
+    }
+}
+
+/// Answer an `events.subscriptions.*` request on its reply subject
+pub fn handle_registry_request(
+    subject: &str,
+    body: &[u8],
+    reply_to: Option<String>,
+) -> Result<(), String> {
+    let response =
+        event_subscriptions::handle_request(&KvStore, subject, body, replay_clock::now());
+    if let Some(error) = &response.error {
+        eprintln!("[EVM-LOGS] ⚠️  {} rejected: {}", subject, error);
+    }
+    let Some(reply_to) = reply_to else {
+        return Ok(());
+    };
+    let payload = serde_json::to_vec(&response)
+        .map_err(|e| format!("Failed to serialize subscription response: {}", e))?;
+    crate::consumer::publish(&crate::types::BrokerMessage {
+        subject: reply_to,
+        body: payload,
+        reply_to: None,
+    })
+    .map_err(|e| format!("Failed to send subscription response: {:?}", e))
+}
+
+/// Per-block delivery state
+pub struct EventDeliveries {
+    network: String,
+    subnet: String,
+    index: SubscriptionIndex,
+    windows: HashMap<String, RateWindowV1>,
+    pub delivered: u64,
+    pub throttled: u64,
+    pub failures: u64,
+}
+
+impl EventDeliveries {
+    pub fn load(network: &str, subnet: &str) -> Self {
+        let subscriptions = event_subscriptions::load_chain(&KvStore, network, subnet)
+            .unwrap_or_else(|err| {
+                eprintln!("[EVM-LOGS] ⚠️  Failed to load event subscriptions: {}", err);
+                Vec::new()
+            });
+        Self {
+            network: network.to_lowercase(),
+            subnet: subnet.to_lowercase(),
+            index: SubscriptionIndex::new(subscriptions),
+            windows: HashMap::new(),
+            delivered: 0,
+            throttled: 0,
+            failures: 0,
+        }
+    }
+
+    pub fn on_log(&mut self, log: &RpcLog, block_number: i64, block_timestamp: i64) {
+        if self.index.is_empty() {
+            return;
+        }
+        let address = Component::normalize_hex(&log.address);
+        let topics: Vec<String> = log
+            .topics
+            .iter()
+            .map(|t| Component::normalize_hex(t))
+            .collect();
+        let topic_refs: Vec<&str> = topics.iter().map(String::as_str).collect();
+        let now = replay_clock::now().timestamp();
+
+        for (subscription, params) in self.index.matches(&address, &topic_refs, &log.data) {
+            let window = self
+                .windows
+                .entry(subscription.subscription_id.clone())
+                .or_insert_with(|| load_window(&subscription.subscription_id));
+            if !window.admit(now, subscription.rate_limit_per_minute) {
+                self.throttled += 1;
+                continue;
+            }
+
+            let event = DecodedEventV1 {
+                schema_version: decoded_event_schema_version_v1(),
+                subscription_id: subscription.subscription_id.clone(),
+                network: self.network.clone(),
+                subnet: self.subnet.clone(),
+                contract_address: address.clone(),
+                event_name: subscription.event_name.clone(),
+                event_signature: subscription.event_signature.clone(),
+                transaction_hash: Component::normalize_hex(&log.transaction_hash),
+                log_index: Component::parse_hex_i64(&log.log_index),
+                block_number,
+                block_timestamp,
+                params,
+            };
+            let payload = event.payload(subscription).to_string();
+            match Component::publish_message(&subscription.delivery_subject(), payload.as_bytes()) {
+                Ok(()) => self.delivered += 1,
+                Err(err) => {
+                    self.failures += 1;
+                    eprintln!(
+                        "[EVM-LOGS] ❌ Failed to deliver event to {}: {}",
+                        subscription.subscription_id, err
+                    );
+                }
+            }
+        }
+    }
+
+    /// Persist rate limit windows touched in this block
+    pub fn finish(&self) {
+        for (subscription_id, window) in &self.windows {
+            let key = retention_policy::EVENT_SUBSCRIPTION_RATE.key(subscription_id);
+            let Ok(bytes) = serde_json::to_vec(window) else {
+                continue;
+            };
+            if let Err(err) = KvStore.set(&key, &bytes) {
+                eprintln!("[EVM-LOGS] ⚠️  {}", err);
+            }
+        }
+    }
+}
+
+fn load_window(subscription_id: &str) -> RateWindowV1 {
+    KvStore
+        .get(&retention_policy::EVENT_SUBSCRIPTION_RATE.key(subscription_id))
+        .ok()
+        .flatten()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
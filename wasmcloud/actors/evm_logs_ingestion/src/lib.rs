@@ -8,6 +8,20 @@
 //! - Publishes to:
 //!   - `ducklake.logs.{network}.{subnet}.write`
 //!   - `alerts.schedule.event_driven`
+//!   - `alerts.upgrade_risk` - watched proxy upgrades whose simulated behavior
+//!     diverged (see [`upgrade_sim`])
+//!   - `events.decoded.{subscription_id}` - logs decoded for registered event
+//!     subscriptions (see [`event_delivery`])
+//!   - `ducklake.token_transfers.{network}.{subnet}.write` - ERC-721 transfers
+//!     of watched collections with tokenURI metadata (see [`nft_metadata`])
+//!   - `ducklake.perp_events.{network}.{subnet}.write` - decoded perpetuals
+//!     position events (see [`perp_events`])
+//! - Serves: `events.subscriptions.{register,delete,list}`
+
+mod event_delivery;
+mod nft_metadata;
+mod perp_events;
+mod upgrade_sim;
 
 use serde::{Deserialize, Serialize};
 
@@ -15,15 +29,24 @@ use serde::{Deserialize, Serialize};
 wit_bindgen::generate!({ generate_all });
 
 use alert_runtime_common::{
-    alert_schedule_event_driven_schema_version_v1, AlertScheduleEventDrivenV1, EvmLogV1,
-    PartitionV1, ScheduleEventV1, TxKindV1, VmKindV1,
+    alert_schedule_event_driven_schema_version_v1, alert_upgrade_risk_schema_version_v1,
+    AlertScheduleEventDrivenV1, AlertUpgradeRiskV1, EvmLogV1, PartitionV1, ScheduleEventV1,
+    TxKindV1, VmKindV1, ALERT_UPGRADE_RISK_SUBJECT,
 };
 use chrono::{TimeZone, Utc};
 use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use wasmcloud::messaging::{consumer, types};
 
+subject_acl::component!("evm-logs-ingestion");
+
 const MAX_LOGS_PER_BLOCK: usize = 50_000;
 const RPC_RETRY_ATTEMPTS: usize = 3;
+/// Upgrades simulated per block; each costs two simulations plus a storage read
+const MAX_UPGRADE_SIMULATIONS_PER_BLOCK: usize = 5;
+
+// Alert scheduler event indexes; a proxy is watched when either key exists
+const EVENT_IDX_TARGET_INSTANCES_PREFIX: &str = "alerts:event_idx:target_instances:";
+const EVENT_IDX_TARGET_GROUPS_PREFIX: &str = "alerts:event_idx:target_groups:";
 
 /// Block header from newheads provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +97,14 @@ struct RpcLog {
     pub block_number: Option<String>,
 }
 
+/// `Upgraded(address)` emitted by a proxy in the current block
+#[derive(Debug, Clone)]
+struct ProxyUpgrade {
+    proxy: String,
+    new_implementation: String,
+    transaction_hash: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct DuckLakeLogRecord {
     pub chain_id: String,
@@ -130,10 +161,26 @@ export!(Component);
 
 impl MessageHandler for Component {
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+
         eprintln!("[EVM-LOGS] ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
         eprintln!("[EVM-LOGS] 📨 Received message on subject: {}", msg.subject);
 
-        if !msg.subject.starts_with("newheads.") || !msg.subject.ends_with(".evm") {
+        let subject = subject_registry::unprefixed(&msg.subject).unwrap_or_default();
+        if subject.starts_with("events.subscriptions.") {
+            return event_delivery::handle_registry_request(subject, &msg.body, msg.reply_to);
+        }
+
+        if !subject.starts_with("newheads.") || !subject.ends_with(".evm") {
             eprintln!("[EVM-LOGS] ⏭️  Skipping - not an EVM newheads message");
             return Ok(());
         }
@@ -189,6 +236,9 @@ impl Component {
             logs
         };
 
+        // Schedule events do not name their chain; dry run follows the block's
+        feature_flags::dry_run::set_chain(&block_header.network, &block_header.subnet);
+
         let chain_prefix = Self::network_prefix(&block_header.network);
         let chain_id_numeric = Self::chain_id_numeric(&block_header, chain_prefix);
         if chain_id_numeric == 0 {
@@ -207,6 +257,12 @@ impl Component {
         let mut persisted_count = 0u64;
         let mut ducklake_failures = 0u64;
         let mut schedule_failures = 0u64;
+        let mut upgrades = Vec::new();
+        let mut deliveries =
+            event_delivery::EventDeliveries::load(&block_header.network, &block_header.subnet);
+        let mut nfts =
+            nft_metadata::NftEnrichment::load(&block_header.network, &block_header.subnet);
+        let mut perps = perp_events::PerpEvents::new(&block_header.network, &block_header.subnet);
 
         for log in capped_logs.iter() {
             let normalized_address = Self::normalize_hex(&log.address);
@@ -223,6 +279,16 @@ impl Component {
             let topic2 = topics.get(2).cloned();
             let topic3 = topics.get(3).cloned();
 
+            if let Some(new_implementation) =
+                upgrade_sim::upgraded_implementation(topic0.as_deref(), topic1.as_deref())
+            {
+                upgrades.push(ProxyUpgrade {
+                    proxy: normalized_address.clone(),
+                    new_implementation,
+                    transaction_hash: normalized_tx_hash.clone(),
+                });
+            }
+
             let log_index = Self::parse_hex_i64(&log.log_index) as i32;
             let block_number = block_header.block_number as i64;
 
@@ -264,7 +330,11 @@ impl Component {
                 persisted_count += 1;
             }
 
-            let candidate_target_keys = Self::build_candidate_target_keys(
+            deliveries.on_log(log, block_number, block_header.timestamp as i64);
+            let nft = nfts.on_log(&config.rpc_url, &record);
+            let perp = perps.on_log(&record);
+
+            let mut candidate_target_keys = Self::build_candidate_target_keys(
                 chain_prefix,
                 &block_header.subnet,
                 &normalized_address,
@@ -272,6 +342,14 @@ impl Component {
                 &topic2,
                 &topic3,
             );
+            // GMX does not index the trader; key the event on its account too
+            if let Some(perp) = &perp {
+                let account_key =
+                    format!("{}:{}:{}", chain_prefix, &block_header.subnet, perp.account);
+                if !candidate_target_keys.contains(&account_key) {
+                    candidate_target_keys.push(account_key);
+                }
+            }
 
             if candidate_target_keys.is_empty() {
                 continue;
@@ -286,6 +364,8 @@ impl Component {
                 topic2,
                 topic3,
                 data: normalized_data,
+                nft,
+                perp,
                 block_number,
                 block_timestamp: block_dt,
             };
@@ -318,10 +398,52 @@ impl Component {
             }
         }
 
+        deliveries.finish();
+
+        for upgrade in upgrades.iter().take(MAX_UPGRADE_SIMULATIONS_PER_BLOCK) {
+            let partition = PartitionV1 {
+                network: chain_prefix.to_string(),
+                subnet: block_header.subnet.clone(),
+                chain_id: chain_id_numeric,
+            };
+            if let Err(err) =
+                Self::check_proxy_upgrade(&config.rpc_url, &block_header, partition, upgrade)
+            {
+                eprintln!(
+                    "[EVM-LOGS] ⚠️  Upgrade simulation failed for {}: {}",
+                    upgrade.proxy, err
+                );
+            }
+        }
+        if upgrades.len() > MAX_UPGRADE_SIMULATIONS_PER_BLOCK {
+            eprintln!(
+                "[EVM-LOGS] ⚠️  Skipped simulating {} proxy upgrades",
+                upgrades.len() - MAX_UPGRADE_SIMULATIONS_PER_BLOCK
+            );
+        }
+
         eprintln!(
             "[EVM-LOGS] ✅ Published {} schedule events, persisted {} logs",
             schedule_count, persisted_count
         );
+        if deliveries.delivered > 0 || deliveries.throttled > 0 || deliveries.failures > 0 {
+            eprintln!(
+                "[EVM-LOGS] 📬 Event subscriptions: {} delivered, {} throttled, {} failed",
+                deliveries.delivered, deliveries.throttled, deliveries.failures
+            );
+        }
+        if nfts.enriched > 0 || nfts.failures > 0 {
+            eprintln!(
+                "[EVM-LOGS] 🖼️  NFT transfers: {} enriched, {} failures",
+                nfts.enriched, nfts.failures
+            );
+        }
+        if perps.decoded > 0 || perps.failures > 0 {
+            eprintln!(
+                "[EVM-LOGS] 📈 Perp events: {} decoded, {} failures",
+                perps.decoded, perps.failures
+            );
+        }
         if ducklake_failures > 0 || schedule_failures > 0 {
             eprintln!(
                 "[EVM-LOGS] ⚠️  DuckLake failures: {}, schedule failures: {}",
@@ -333,6 +455,141 @@ impl Component {
         Ok(())
     }
 
+    /// Record the new implementation and, for watched proxies, diff canonical
+    /// calls between the old and new implementation
+    fn check_proxy_upgrade(
+        rpc_url: &str,
+        block_header: &BlockHeader,
+        partition: PartitionV1,
+        upgrade: &ProxyUpgrade,
+    ) -> Result<(), String> {
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+        let registry_key = retention_policy::PROXY_IMPLEMENTATION.key(&format!(
+            "{}:{}",
+            block_header.network.to_lowercase(),
+            upgrade.proxy
+        ));
+        bucket
+            .set(&registry_key, upgrade.new_implementation.as_bytes())
+            .map_err(|e| format!("Failed to set {}: {:?}", registry_key, e))?;
+
+        let target_key = format!(
+            "{}:{}:{}",
+            partition.network, partition.subnet, upgrade.proxy
+        );
+        let watched = [
+            EVENT_IDX_TARGET_INSTANCES_PREFIX,
+            EVENT_IDX_TARGET_GROUPS_PREFIX,
+        ]
+        .iter()
+        .any(|prefix| {
+            bucket
+                .exists(&format!("{}{}", prefix, target_key))
+                .unwrap_or(false)
+        });
+        if !watched {
+            return Ok(());
+        }
+
+        // The upgrade block already runs the new implementation; read the old
+        // one from the slot as of the parent block
+        let parent_tag = format!("0x{:x}", block_header.block_number.saturating_sub(1));
+        let slot = Self::rpc_result(
+            rpc_url,
+            "eth_getStorageAt",
+            serde_json::json!([upgrade.proxy, upgrade_sim::IMPLEMENTATION_SLOT, parent_tag]),
+        )?;
+        let Some(old_implementation) = slot.as_str().and_then(upgrade_sim::slot_address) else {
+            eprintln!(
+                "[EVM-LOGS] ℹ️  Proxy {} had no implementation before upgrade",
+                upgrade.proxy
+            );
+            return Ok(());
+        };
+        if old_implementation == upgrade.new_implementation {
+            return Ok(());
+        }
+
+        let block_tag = format!("0x{:x}", block_header.block_number);
+        let calls = upgrade_sim::canonical_calls();
+        let old = Self::simulate_calls(
+            rpc_url,
+            &upgrade.proxy,
+            &old_implementation,
+            &calls,
+            &block_tag,
+        )?;
+        let new = Self::simulate_calls(
+            rpc_url,
+            &upgrade.proxy,
+            &upgrade.new_implementation,
+            &calls,
+            &block_tag,
+        )?;
+        let divergences = upgrade_sim::diff_outcomes(&calls, &old, &new);
+        if divergences.is_empty() {
+            eprintln!(
+                "[EVM-LOGS] ✅ Upgrade of {} to {} kept canonical behavior",
+                upgrade.proxy, upgrade.new_implementation
+            );
+            return Ok(());
+        }
+
+        eprintln!(
+            "[EVM-LOGS] 🚨 Upgrade of {} diverged on {} calls",
+            upgrade.proxy,
+            divergences.len()
+        );
+        let event = AlertUpgradeRiskV1 {
+            schema_version: alert_upgrade_risk_schema_version_v1(),
+            partition,
+            proxy_address: upgrade.proxy.clone(),
+            old_implementation: Some(old_implementation),
+            new_implementation: upgrade.new_implementation.clone(),
+            transaction_hash: upgrade.transaction_hash.clone(),
+            block_number: block_header.block_number as i64,
+            candidate_target_keys: vec![target_key],
+            divergences,
+            detected_at: replay_clock::now(),
+            source: "evm_logs_ingestion".to_string(),
+        };
+        let payload = serde_json::to_vec(&event)
+            .map_err(|e| format!("Failed to serialize upgrade risk event: {}", e))?;
+        Self::publish_message(ALERT_UPGRADE_RISK_SUBJECT, &payload)
+    }
+
+    /// Run the canonical calls against `implementation`, preferring
+    /// `eth_simulateV1` and falling back to per-call `eth_call`
+    fn simulate_calls(
+        rpc_url: &str,
+        proxy: &str,
+        implementation: &str,
+        calls: &[upgrade_sim::CanonicalCall],
+        block_tag: &str,
+    ) -> Result<Vec<upgrade_sim::CallOutcome>, String> {
+        let params = upgrade_sim::simulate_params(proxy, implementation, calls, block_tag);
+        let response = Self::rpc_post(rpc_url, "eth_simulateV1", params)?;
+        match response.get("error") {
+            None => {
+                let result = response.get("result").ok_or("No simulation result")?;
+                return upgrade_sim::parse_simulation(result);
+            }
+            Some(error) if !upgrade_sim::is_unsupported_method(error) => {
+                return Err(format!("RPC error: {}", error));
+            }
+            Some(_) => {}
+        }
+
+        calls
+            .iter()
+            .map(|call| {
+                let params = upgrade_sim::eth_call_params(proxy, implementation, call, block_tag);
+                upgrade_sim::outcome_from_eth_call(&Self::rpc_post(rpc_url, "eth_call", params)?)
+            })
+            .collect()
+    }
+
     fn publish_ducklake_log(
         record: &DuckLakeLogRecord,
         network: &str,
@@ -345,8 +602,16 @@ impl Component {
     }
 
     fn publish_message(subject: &str, payload: &[u8]) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
         let msg = types::BrokerMessage {
-            subject: subject.to_string(),
+            subject: subject_registry::prefixed(&feature_flags::dry_run::route(subject)),
             body: payload.to_vec(),
             reply_to: None,
         };
@@ -524,39 +789,104 @@ impl Component {
     }
 
     fn fetch_block_logs(rpc_url: &str, block_number: u64) -> Result<Vec<RpcLog>, String> {
-        let (scheme, authority, path) = Self::parse_url(rpc_url)?;
         let block_hex = format!("0x{:x}", block_number);
+        let params = serde_json::json!([{
+            "fromBlock": block_hex,
+            "toBlock": block_hex
+        }]);
+        let rpc_response = Self::rpc_post(rpc_url, "eth_getLogs", params)?;
+
+        if let Some(error) = rpc_response.get("error") {
+            return Err(format!("RPC error: {}", error));
+        }
+
+        let result = rpc_response
+            .get("result")
+            .and_then(|v| v.as_array())
+            .ok_or("No result logs array in response")?;
+
+        let mut logs = Vec::with_capacity(result.len());
+        for log_value in result {
+            let log: RpcLog = serde_json::from_value(log_value.clone())
+                .map_err(|e| format!("Failed to parse log entry: {}", e))?;
+            logs.push(log);
+        }
+
+        Ok(logs)
+    }
+
+    /// JSON-RPC `result`, with RPC errors mapped to `Err`
+    fn rpc_result(
+        rpc_url: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let mut response = Self::rpc_post(rpc_url, method, params)?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("RPC error: {}", error));
+        }
+        response
+            .get_mut("result")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| format!("No result in {} response", method))
+    }
 
+    /// POST a JSON-RPC request and return the full response envelope
+    fn rpc_post(
+        rpc_url: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
         let rpc_request = serde_json::json!({
             "jsonrpc": "2.0",
-            "method": "eth_getLogs",
-            "params": [{
-                "fromBlock": block_hex,
-                "toBlock": block_hex
-            }],
+            "method": method,
+            "params": params,
             "id": 1
         });
 
         let request_body = serde_json::to_vec(&rpc_request)
             .map_err(|e| format!("Failed to serialize RPC request: {}", e))?;
+        let response_bytes = Self::http_send(rpc_url, Some(&request_body))?;
+
+        serde_json::from_slice(&response_bytes)
+            .map_err(|e| format!("Failed to parse RPC response: {}", e))
+    }
+
+    /// GET a URL and return the body as text
+    fn http_get(url: &str) -> Result<String, String> {
+        let bytes = Self::http_send(url, None)?;
+        String::from_utf8(bytes).map_err(|e| format!("Response from {} is not UTF-8: {}", url, e))
+    }
+
+    /// POST a JSON body (or GET when `json_body` is `None`) and return the
+    /// response body of a 2xx response
+    fn http_send(url: &str, json_body: Option<&[u8]>) -> Result<Vec<u8>, String> {
+        let (scheme, authority, path) = Self::parse_url(url)?;
 
         let headers = wasi::http::types::Fields::new();
-        headers
-            .set(
-                &"content-type".to_string(),
-                &vec!["application/json".as_bytes().to_vec()],
-            )
-            .map_err(|e| format!("Failed to set content-type header: {:?}", e))?;
-        headers
-            .set(
-                &"content-length".to_string(),
-                &vec![request_body.len().to_string().as_bytes().to_vec()],
-            )
-            .map_err(|e| format!("Failed to set content-length header: {:?}", e))?;
+        if let Some(request_body) = json_body {
+            headers
+                .set(
+                    &"content-type".to_string(),
+                    &vec!["application/json".as_bytes().to_vec()],
+                )
+                .map_err(|e| format!("Failed to set content-type header: {:?}", e))?;
+            headers
+                .set(
+                    &"content-length".to_string(),
+                    &vec![request_body.len().to_string().as_bytes().to_vec()],
+                )
+                .map_err(|e| format!("Failed to set content-length header: {:?}", e))?;
+        }
         let request = wasi::http::types::OutgoingRequest::new(headers);
 
+        let method = if json_body.is_some() {
+            wasi::http::types::Method::Post
+        } else {
+            wasi::http::types::Method::Get
+        };
         request
-            .set_method(&wasi::http::types::Method::Post)
+            .set_method(&method)
             .map_err(|e| format!("Failed to set method: {:?}", e))?;
         request
             .set_scheme(Some(&scheme))
@@ -571,12 +901,12 @@ impl Component {
         let body = request
             .body()
             .map_err(|_| "Failed to get request body".to_string())?;
-        {
+        if let Some(request_body) = json_body {
             let output_stream = body
                 .write()
                 .map_err(|_| "Failed to get body output stream".to_string())?;
             output_stream
-                .blocking_write_and_flush(&request_body)
+                .blocking_write_and_flush(request_body)
                 .map_err(|e| format!("Failed to write request body: {:?}", e))?;
         }
         wasi::http::types::OutgoingBody::finish(body, None)
@@ -619,26 +949,7 @@ impl Component {
             }
         }
 
-        let rpc_response: serde_json::Value = serde_json::from_slice(&response_bytes)
-            .map_err(|e| format!("Failed to parse RPC response: {}", e))?;
-
-        if let Some(error) = rpc_response.get("error") {
-            return Err(format!("RPC error: {}", error));
-        }
-
-        let result = rpc_response
-            .get("result")
-            .and_then(|v| v.as_array())
-            .ok_or("No result logs array in response")?;
-
-        let mut logs = Vec::with_capacity(result.len());
-        for log_value in result {
-            let log: RpcLog = serde_json::from_value(log_value.clone())
-                .map_err(|e| format!("Failed to parse log entry: {}", e))?;
-            logs.push(log);
-        }
-
-        Ok(logs)
+        Ok(response_bytes)
     }
 
     fn parse_hex_i64(hex_str: &str) -> i64 {
@@ -0,0 +1,587 @@
+//! ERC-721 tokenURI enrichment for watched collections
+//!
+//! Collections are configured per chain under `nft_enrichment:{network}:{subnet}`.
+//! For a `Transfer(address,address,uint256)` log with an indexed token id
+//! (four topics, unlike ERC-20) from a watched collection, the token's
+//! `tokenURI` is read with `eth_call`, `ipfs://` and `ar://` URIs are rewritten
+//! to HTTP gateways, and the metadata JSON is fetched through the HTTP client
+//! provider. Name and image are cached under
+//! `nft_metadata:{network}:{subnet}:{collection}:{token_id}`.
+//!
+//! The resulting [`NftMetadataV1`] is attached to the schedule event's log (so
+//! notifications can use `{{nft.display_name}}`) and written with the transfer
+//! to `ducklake.token_transfers.{network}.{subnet}.write`. At most
+//! `MAX_RESOLUTIONS_PER_BLOCK` uncached tokens are resolved per block; the rest
+//! fall back to the collection label and token id.
+
+use alert_runtime_common::NftMetadataV1;
+use serde::{Deserialize, Serialize};
+
+use crate::{Component, DuckLakeLogRecord};
+
+/// `Transfer(address,address,uint256)`, shared by ERC-20 and ERC-721
+pub const TRANSFER_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// `tokenURI(uint256)`
+const TOKEN_URI_SELECTOR: &str = "0xc87b56dd";
+/// `name()`
+const NAME_SELECTOR: &str = "0x06fdde03";
+
+/// Public gateways used when the chain config does not set `ipfs_gateway`
+const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+const ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// Uncached tokens resolved per block; each costs up to two calls and a fetch
+const MAX_RESOLUTIONS_PER_BLOCK: usize = 10;
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Per-chain enrichment settings, written by the alert API
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NftEnrichmentConfigV1 {
+    pub collections: Vec<WatchedCollectionV1>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipfs_gateway: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedCollectionV1 {
+    pub address: String,
+    /// Short collection name for display (`Bored Ape`); falls back to `name()`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl NftEnrichmentConfigV1 {
+    pub fn collection(&self, address: &str) -> Option<&WatchedCollectionV1> {
+        self.collections
+            .iter()
+            .find(|collection| collection.address.eq_ignore_ascii_case(address))
+    }
+
+    pub fn ipfs_gateway(&self) -> &str {
+        self.ipfs_gateway
+            .as_deref()
+            .filter(|gateway| !gateway.trim().is_empty())
+            .unwrap_or(DEFAULT_IPFS_GATEWAY)
+    }
+}
+
+/// Decoded ERC-721 `Transfer` log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc721Transfer {
+    pub from: String,
+    pub to: String,
+    /// Decimal token id
+    pub token_id: String,
+    /// The token id as a 32-byte hex word without `0x`
+    pub token_word: String,
+}
+
+impl Erc721Transfer {
+    pub fn is_burn(&self) -> bool {
+        self.to == ZERO_ADDRESS
+    }
+}
+
+/// ERC-721 transfer from the log's topics; ERC-20 transfers (three topics) are ignored
+pub fn erc721_transfer(
+    topic0: Option<&str>,
+    topic1: Option<&str>,
+    topic2: Option<&str>,
+    topic3: Option<&str>,
+) -> Option<Erc721Transfer> {
+    if !topic0?.eq_ignore_ascii_case(TRANSFER_TOPIC) {
+        return None;
+    }
+    let token_word = topic_word(topic3?)?;
+    Some(Erc721Transfer {
+        from: format!("0x{}", &topic_word(topic1?)?[24..]),
+        to: format!("0x{}", &topic_word(topic2?)?[24..]),
+        token_id: word_to_decimal(&token_word)?,
+        token_word,
+    })
+}
+
+fn topic_word(topic: &str) -> Option<String> {
+    let word = topic.trim().trim_start_matches("0x").to_lowercase();
+    (word.len() == 64 && word.chars().all(|c| c.is_ascii_hexdigit())).then_some(word)
+}
+
+/// Decimal string of a big-endian hex word (uint256)
+pub(crate) fn word_to_decimal(word: &str) -> Option<String> {
+    // Little-endian decimal digits
+    let mut digits: Vec<u8> = vec![0];
+    for c in word.chars() {
+        let mut carry = c.to_digit(16)?;
+        for digit in digits.iter_mut() {
+            let value = *digit as u32 * 16 + carry;
+            *digit = (value % 10) as u8;
+            carry = value / 10;
+        }
+        while carry > 0 {
+            digits.push((carry % 10) as u8);
+            carry /= 10;
+        }
+    }
+    Some(digits.iter().rev().map(|d| char::from(b'0' + d)).collect())
+}
+
+/// ABI-decode a `string` return value
+pub fn decode_abi_string(hex: &str) -> Option<String> {
+    let bytes = hex_decode(hex.trim().trim_start_matches("0x"))?;
+    let offset = word_usize(bytes.get(0..32)?)?;
+    let len = word_usize(bytes.get(offset..offset.checked_add(32)?)?)?;
+    let start = offset + 32;
+    let data = bytes.get(start..start.checked_add(len)?)?;
+    String::from_utf8(data.to_vec()).ok()
+}
+
+fn word_usize(word: &[u8]) -> Option<usize> {
+    if word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&word[24..32]);
+    usize::try_from(u64::from_be_bytes(value)).ok()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Where a token's metadata JSON lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataSource {
+    Url(String),
+    /// `data:application/json` URIs carry the JSON on chain
+    Inline(String),
+}
+
+/// Resolve a `tokenURI` to a fetchable URL or inline JSON
+///
+/// `{id}` is substituted with the hex token id (ERC-1155 style, also used by
+/// some ERC-721 collections).
+pub fn metadata_source(token_uri: &str, token_word: &str, gateway: &str) -> Option<MetadataSource> {
+    let uri = token_uri.trim().replace("{id}", token_word);
+    if let Some(data) = uri.strip_prefix("data:application/json;base64,") {
+        let json = String::from_utf8(base64_decode(data)?).ok()?;
+        return Some(MetadataSource::Inline(json));
+    }
+    if let Some(json) = uri
+        .strip_prefix("data:application/json;utf8,")
+        .or_else(|| uri.strip_prefix("data:application/json,"))
+    {
+        return Some(MetadataSource::Inline(json.to_string()));
+    }
+    gateway_url(&uri, gateway).map(MetadataSource::Url)
+}
+
+/// HTTP URL for an `ipfs://`, `ar://` or `http(s)://` URI
+fn gateway_url(uri: &str, gateway: &str) -> Option<String> {
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        return Some(format!("{}/{}", gateway.trim_end_matches('/'), path));
+    }
+    if let Some(path) = uri.strip_prefix("ar://") {
+        return Some(format!("{}{}", ARWEAVE_GATEWAY, path));
+    }
+    (uri.starts_with("https://") || uri.starts_with("http://")).then(|| uri.to_string())
+}
+
+fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b'\n' | b'\r' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// `name` and gateway `image` from a metadata JSON document
+///
+/// On-chain `data:` images are dropped; they can be many kilobytes of SVG.
+pub fn parse_metadata(json: &str, gateway: &str) -> (Option<String>, Option<String>) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return (None, None);
+    };
+    let text = |field: &str| {
+        value
+            .get(field)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+    let name = text("name").map(str::to_string);
+    let image = text("image")
+        .or_else(|| text("image_url"))
+        .and_then(|image| gateway_url(image, gateway));
+    (name, image)
+}
+
+/// Metadata `name`, else `{collection} #{token_id}`
+pub fn display_name(name: Option<&str>, collection_name: Option<&str>, token_id: &str) -> String {
+    match (name, collection_name) {
+        (Some(name), _) => name.to_string(),
+        (None, Some(collection)) => format!("{} #{}", collection, token_id),
+        (None, None) => format!("#{}", token_id),
+    }
+}
+
+/// DuckLake token_transfers record for an ERC-721 transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeNftTransferRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub transaction_hash: String,
+    pub log_index: i32,
+    pub token_address: String,
+    pub token_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_name: Option<String>,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+    pub token_id: String,
+    pub nft_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nft_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nft_token_uri: Option<String>,
+}
+
+impl DuckLakeNftTransferRecord {
+    pub fn new(log: &DuckLakeLogRecord, transfer: &Erc721Transfer, nft: &NftMetadataV1) -> Self {
+        Self {
+            chain_id: log.chain_id.clone(),
+            block_date: log.block_date.clone(),
+            block_number: log.block_number,
+            block_timestamp: log.block_timestamp,
+            transaction_hash: log.transaction_hash.clone(),
+            log_index: log.log_index,
+            token_address: log.address.clone(),
+            token_type: "ERC721".to_string(),
+            token_name: nft.collection_name.clone(),
+            from_address: transfer.from.clone(),
+            to_address: transfer.to.clone(),
+            amount: "1".to_string(),
+            token_id: transfer.token_id.clone(),
+            nft_name: nft.display_name.clone(),
+            nft_image: nft.image.clone(),
+            nft_token_uri: nft.token_uri.clone(),
+        }
+    }
+}
+
+/// Per-block enrichment state
+pub struct NftEnrichment {
+    network: String,
+    subnet: String,
+    config: NftEnrichmentConfigV1,
+    resolutions: usize,
+    pub enriched: u64,
+    pub failures: u64,
+}
+
+impl NftEnrichment {
+    pub fn load(network: &str, subnet: &str) -> Self {
+        let network = network.to_lowercase();
+        let subnet = subnet.to_lowercase();
+        let config = crate::wasi::keyvalue::store::open("default")
+            .ok()
+            .and_then(|bucket| {
+                bucket
+                    .get(
+                        &retention_policy::NFT_ENRICHMENT_CONFIG
+                            .key(&format!("{}:{}", network, subnet)),
+                    )
+                    .ok()
+                    .flatten()
+            })
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            network,
+            subnet,
+            config,
+            resolutions: 0,
+            enriched: 0,
+            failures: 0,
+        }
+    }
+
+    /// Enrich and persist a transfer of a watched collection
+    pub fn on_log(&mut self, rpc_url: &str, log: &DuckLakeLogRecord) -> Option<NftMetadataV1> {
+        let collection = self.config.collection(&log.address)?.clone();
+        let transfer = erc721_transfer(
+            log.topic0.as_deref(),
+            log.topic1.as_deref(),
+            log.topic2.as_deref(),
+            log.topic3.as_deref(),
+        )?;
+
+        let cache_key = retention_policy::NFT_METADATA_CACHE.key(&format!(
+            "{}:{}:{}:{}",
+            self.network, self.subnet, log.address, transfer.token_id
+        ));
+        let nft = match self.cached(&cache_key) {
+            Some(nft) => nft,
+            None if self.resolutions < MAX_RESOLUTIONS_PER_BLOCK => {
+                self.resolutions += 1;
+                match self.resolve(rpc_url, &collection, log, &transfer) {
+                    Ok(nft) => {
+                        self.store(&cache_key, &nft);
+                        nft
+                    }
+                    Err(err) => {
+                        self.failures += 1;
+                        eprintln!(
+                            "[EVM-LOGS] ⚠️  tokenURI resolution failed for {} #{}: {}",
+                            log.address, transfer.token_id, err
+                        );
+                        Self::fallback(&collection, log, &transfer)
+                    }
+                }
+            }
+            None => Self::fallback(&collection, log, &transfer),
+        };
+
+        let record = DuckLakeNftTransferRecord::new(log, &transfer, &nft);
+        let subject = format!(
+            "ducklake.token_transfers.{}.{}.write",
+            self.network, self.subnet
+        );
+        let published = serde_json::to_vec(&record)
+            .map_err(|e| format!("Failed to serialize NFT transfer: {}", e))
+            .and_then(|payload| Component::publish_message(&subject, &payload));
+        if let Err(err) = published {
+            self.failures += 1;
+            eprintln!("[EVM-LOGS] ❌ Failed to persist NFT transfer: {}", err);
+        }
+
+        self.enriched += 1;
+        Some(nft)
+    }
+
+    fn resolve(
+        &self,
+        rpc_url: &str,
+        collection: &WatchedCollectionV1,
+        log: &DuckLakeLogRecord,
+        transfer: &Erc721Transfer,
+    ) -> Result<NftMetadataV1, String> {
+        // A burned token has no URI in its own block
+        let block = if transfer.is_burn() {
+            log.block_number - 1
+        } else {
+            log.block_number
+        };
+        let block_tag = format!("0x{:x}", block.max(0));
+
+        let token_uri = Self::call_string(
+            rpc_url,
+            &log.address,
+            &format!("{}{}", TOKEN_URI_SELECTOR, transfer.token_word),
+            &block_tag,
+        )?;
+        let collection_name = match &collection.label {
+            Some(label) => Some(label.clone()),
+            None => Self::call_string(rpc_url, &log.address, NAME_SELECTOR, &block_tag).ok(),
+        };
+
+        let gateway = self.config.ipfs_gateway();
+        let json = match metadata_source(&token_uri, &transfer.token_word, gateway) {
+            Some(MetadataSource::Url(url)) => Component::http_get(&url)?,
+            Some(MetadataSource::Inline(json)) => json,
+            None => return Err(format!("Unsupported tokenURI {}", token_uri)),
+        };
+        let (name, image) = parse_metadata(&json, gateway);
+
+        Ok(NftMetadataV1 {
+            collection_address: log.address.clone(),
+            token_id: transfer.token_id.clone(),
+            display_name: display_name(
+                name.as_deref(),
+                collection_name.as_deref(),
+                &transfer.token_id,
+            ),
+            collection_name,
+            name,
+            image,
+            token_uri: Some(token_uri),
+        })
+    }
+
+    fn fallback(
+        collection: &WatchedCollectionV1,
+        log: &DuckLakeLogRecord,
+        transfer: &Erc721Transfer,
+    ) -> NftMetadataV1 {
+        NftMetadataV1 {
+            collection_address: log.address.clone(),
+            token_id: transfer.token_id.clone(),
+            display_name: display_name(None, collection.label.as_deref(), &transfer.token_id),
+            collection_name: collection.label.clone(),
+            name: None,
+            image: None,
+            token_uri: None,
+        }
+    }
+
+    fn call_string(rpc_url: &str, to: &str, data: &str, block_tag: &str) -> Result<String, String> {
+        let result = Component::rpc_result(
+            rpc_url,
+            "eth_call",
+            serde_json::json!([{ "to": to, "data": data }, block_tag]),
+        )?;
+        result
+            .as_str()
+            .and_then(decode_abi_string)
+            .ok_or_else(|| format!("{} returned no string for {}", to, data))
+    }
+
+    fn cached(&self, key: &str) -> Option<NftMetadataV1> {
+        let bucket = crate::wasi::keyvalue::store::open("default").ok()?;
+        let bytes = bucket.get(key).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn store(&self, key: &str, nft: &NftMetadataV1) {
+        let stored = serde_json::to_vec(nft)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                crate::wasi::keyvalue::store::open("default")
+                    .and_then(|bucket| bucket.set(key, &bytes))
+                    .map_err(|e| format!("{:?}", e))
+            });
+        if let Err(err) = stored {
+            eprintln!(
+                "[EVM-LOGS] ⚠️  Failed to cache NFT metadata {}: {}",
+                key, err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(hex: &str) -> String {
+        format!("0x{:0>64}", hex)
+    }
+
+    #[test]
+    fn test_erc721_transfer_from_topics() {
+        let from = word("bc4ca0eda7647a8ab7c2061c2e118a18a936f13d");
+        let to = word("0000000000000000000000000000000000000001");
+        let token = word("4d2");
+        let transfer =
+            erc721_transfer(Some(TRANSFER_TOPIC), Some(&from), Some(&to), Some(&token)).unwrap();
+        assert_eq!(transfer.from, "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d");
+        assert_eq!(transfer.token_id, "1234");
+        assert!(!transfer.is_burn());
+
+        // ERC-20 Transfer carries the amount in data, not a fourth topic
+        assert!(erc721_transfer(Some(TRANSFER_TOPIC), Some(&from), Some(&to), None).is_none());
+
+        let max = "f".repeat(64);
+        let transfer = erc721_transfer(
+            Some(TRANSFER_TOPIC),
+            Some(&from),
+            Some(&word("0")),
+            Some(&max),
+        )
+        .unwrap();
+        assert!(transfer.is_burn());
+        assert_eq!(
+            transfer.token_id,
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+    }
+
+    #[test]
+    fn test_decode_abi_string() {
+        let encoded = format!(
+            "0x{}{}{:0<64}",
+            word("20").trim_start_matches("0x"),
+            word("7").trim_start_matches("0x"),
+            "697066733a2f2f"
+        );
+        assert_eq!(decode_abi_string(&encoded).as_deref(), Some("ipfs://"));
+        assert!(decode_abi_string("0x").is_none());
+    }
+
+    #[test]
+    fn test_metadata_source_and_display_name() {
+        let id = format!("{:0>64}", "4d2");
+        assert_eq!(
+            metadata_source("ipfs://ipfs/QmeSjS/1234", &id, "https://cf-ipfs.com/ipfs/"),
+            Some(MetadataSource::Url(
+                "https://cf-ipfs.com/ipfs/QmeSjS/1234".to_string()
+            ))
+        );
+        assert_eq!(
+            metadata_source("ar://abc", &id, DEFAULT_IPFS_GATEWAY),
+            Some(MetadataSource::Url("https://arweave.net/abc".to_string()))
+        );
+        assert_eq!(
+            metadata_source("https://api.example/{id}.json", &id, DEFAULT_IPFS_GATEWAY),
+            Some(MetadataSource::Url(format!(
+                "https://api.example/{}.json",
+                id
+            )))
+        );
+        // {"name":"Loot #1"}
+        assert_eq!(
+            metadata_source(
+                "data:application/json;base64,eyJuYW1lIjoiTG9vdCAjMSJ9",
+                &id,
+                DEFAULT_IPFS_GATEWAY
+            ),
+            Some(MetadataSource::Inline(r#"{"name":"Loot #1"}"#.to_string()))
+        );
+        assert!(metadata_source("bzz://abc", &id, DEFAULT_IPFS_GATEWAY).is_none());
+
+        let (name, image) = parse_metadata(
+            r#"{"image": "ipfs://QmImage", "attributes": []}"#,
+            DEFAULT_IPFS_GATEWAY,
+        );
+        assert_eq!(name, None);
+        assert_eq!(image.as_deref(), Some("https://ipfs.io/ipfs/QmImage"));
+        assert_eq!(
+            display_name(name.as_deref(), Some("Bored Ape"), "1234"),
+            "Bored Ape #1234"
+        );
+        assert_eq!(
+            display_name(Some("Azuki #9"), Some("Azuki"), "9"),
+            "Azuki #9"
+        );
+    }
+}
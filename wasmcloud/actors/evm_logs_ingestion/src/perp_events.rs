@@ -0,0 +1,638 @@
+//! Perpetuals position events (GMX v1, Perpetual Protocol v2)
+//!
+//! Logs are matched by topic0 alone, so forks of either protocol deployed
+//! under other addresses are decoded too:
+//! - GMX v1 `Vault`: `IncreasePosition` (`perp_open`), `DecreasePosition`
+//!   (`perp_close`) and `LiquidatePosition` (`perp_liquidation`). The account
+//!   is not indexed and is read from the data; USD amounts use 1e30 precision.
+//! - Perp v2 `ClearingHouse` (dYdX-style virtual AMM): `PositionChanged`,
+//!   `PositionClosed`, `PositionLiquidated` and `FundingPaymentSettled`
+//!   (`perp_funding`), with the trader and base token indexed and 1e18
+//!   precision. A `PositionChanged` that realizes PnL or leaves no open
+//!   notional reduces the position (`perp_close`); otherwise it is a
+//!   `perp_open`.
+//!
+//! Each decoded event is written to `ducklake.perp_events.{network}.{subnet}.write`
+//! and attached to the log's schedule event as `evm_log.perp`, with the account
+//! added to the candidate target keys so alerts on watched wallets see it.
+
+use alert_runtime_common::PerpEventV1;
+use serde::{Deserialize, Serialize};
+
+use crate::nft_metadata::word_to_decimal;
+use crate::{Component, DuckLakeLogRecord};
+
+/// `IncreasePosition(bytes32,address,address,address,uint256,uint256,bool,uint256,uint256)`
+pub const GMX_INCREASE_POSITION_TOPIC: &str =
+    "0x2fe68525253654c21998f35787a8d0f361905ef647c854092430ab65f2f15022";
+/// `DecreasePosition(bytes32,address,address,address,uint256,uint256,bool,uint256,uint256)`
+pub const GMX_DECREASE_POSITION_TOPIC: &str =
+    "0x93d75d64d1f84fc6f430a64fc578bdd4c1e090e90ea2d51773e626d19de56d30";
+/// `LiquidatePosition(bytes32,address,address,address,bool,uint256,uint256,uint256,int256,uint256)`
+pub const GMX_LIQUIDATE_POSITION_TOPIC: &str =
+    "0x2e1f85a64a2f22cf2f0c42584e7c919ed4abe8d53675cff0f62bf1e95a1c676f";
+/// `PositionChanged(address,address,int256,int256,uint256,int256,int256,uint256)`
+pub const PERP_V2_POSITION_CHANGED_TOPIC: &str =
+    "0x968bc4f738eae0486dc6736c4b427dbafa4acfdf6eaf223337791ddeb3a56247";
+/// `PositionClosed(address,address,int256,int256,int256,int256,uint256)`
+pub const PERP_V2_POSITION_CLOSED_TOPIC: &str =
+    "0x90195cdfd4796e4ab1175fced26945fa837ef5989e372fcd306856d8ad0ff23b";
+/// `PositionLiquidated(address,address,uint256,uint256,uint256,address)`
+pub const PERP_V2_POSITION_LIQUIDATED_TOPIC: &str =
+    "0xd9aced30440caca81570436bc942f816cfd95a3f08f700a2aeb6334c7cb5b497";
+/// `FundingPaymentSettled(address,address,int256)`
+pub const PERP_V2_FUNDING_PAYMENT_SETTLED_TOPIC: &str =
+    "0x733330d4aad1a878654bf888817b79bc6478013399be29fa3b8845c81305249e";
+
+pub const PERP_OPEN: &str = "perp_open";
+pub const PERP_CLOSE: &str = "perp_close";
+pub const PERP_LIQUIDATION: &str = "perp_liquidation";
+pub const PERP_FUNDING: &str = "perp_funding";
+
+const GMX_V1: &str = "gmx_v1";
+const PERP_V2: &str = "perp_v2";
+
+/// GMX v1 USD precision
+const GMX_DECIMALS: usize = 30;
+/// Perp v2 quote (USD) precision
+const PERP_V2_DECIMALS: usize = 18;
+/// Fractional digits kept; matches the table's DECIMAL(38, 18)
+const MAX_FRACTION_DIGITS: usize = 18;
+
+/// Decode a perpetuals position event; `None` for any other log
+pub fn decode_perp_event(
+    topic0: Option<&str>,
+    topic1: Option<&str>,
+    topic2: Option<&str>,
+    data: &str,
+) -> Option<PerpEventV1> {
+    let topic0 = topic0?.to_lowercase();
+    let words = data_words(data)?;
+    match topic0.as_str() {
+        GMX_INCREASE_POSITION_TOPIC => gmx_position(&words, "IncreasePosition", PERP_OPEN),
+        GMX_DECREASE_POSITION_TOPIC => gmx_position(&words, "DecreasePosition", PERP_CLOSE),
+        GMX_LIQUIDATE_POSITION_TOPIC => gmx_liquidation(&words),
+        PERP_V2_POSITION_CHANGED_TOPIC => perp_v2_position_changed(topic1?, topic2?, &words),
+        PERP_V2_POSITION_CLOSED_TOPIC => perp_v2_position_closed(topic1?, topic2?, &words),
+        PERP_V2_POSITION_LIQUIDATED_TOPIC => perp_v2_position_liquidated(topic1?, topic2?, &words),
+        PERP_V2_FUNDING_PAYMENT_SETTLED_TOPIC => perp_v2_funding_payment(topic1?, topic2?, &words),
+        _ => None,
+    }
+}
+
+fn event(
+    protocol: &str,
+    event_name: &str,
+    event_type: &str,
+    account: String,
+    market: String,
+) -> PerpEventV1 {
+    PerpEventV1 {
+        protocol: protocol.to_string(),
+        event_type: event_type.to_string(),
+        event_name: event_name.to_string(),
+        account,
+        market,
+        collateral_token: None,
+        is_long: None,
+        size_delta_usd: None,
+        collateral_delta_usd: None,
+        price_usd: None,
+        fee_usd: None,
+        realized_pnl_usd: None,
+        funding_payment_usd: None,
+        liquidator: None,
+        position_key: None,
+    }
+}
+
+/// `(key, account, collateralToken, indexToken, collateralDelta, sizeDelta, isLong, price, fee)`
+fn gmx_position(words: &[String], event_name: &str, event_type: &str) -> Option<PerpEventV1> {
+    if words.len() < 9 {
+        return None;
+    }
+    let mut perp = event(
+        GMX_V1,
+        event_name,
+        event_type,
+        word_address(&words[1]),
+        word_address(&words[3]),
+    );
+    perp.collateral_token = Some(word_address(&words[2]));
+    perp.collateral_delta_usd = Some(uint_amount(&words[4], GMX_DECIMALS)?);
+    perp.size_delta_usd = Some(uint_amount(&words[5], GMX_DECIMALS)?);
+    perp.is_long = Some(word_bool(&words[6]));
+    perp.price_usd = Some(uint_amount(&words[7], GMX_DECIMALS)?);
+    perp.fee_usd = Some(uint_amount(&words[8], GMX_DECIMALS)?);
+    Some(with_position_key(perp, &words[0]))
+}
+
+/// `(key, account, collateralToken, indexToken, isLong, size, collateral, reserveAmount, realisedPnl, markPrice)`
+fn gmx_liquidation(words: &[String]) -> Option<PerpEventV1> {
+    if words.len() < 10 {
+        return None;
+    }
+    let mut perp = event(
+        GMX_V1,
+        "LiquidatePosition",
+        PERP_LIQUIDATION,
+        word_address(&words[1]),
+        word_address(&words[3]),
+    );
+    perp.collateral_token = Some(word_address(&words[2]));
+    perp.is_long = Some(word_bool(&words[4]));
+    perp.size_delta_usd = Some(uint_amount(&words[5], GMX_DECIMALS)?);
+    perp.collateral_delta_usd = Some(uint_amount(&words[6], GMX_DECIMALS)?);
+    perp.realized_pnl_usd = Some(int_amount(&words[8], GMX_DECIMALS)?);
+    perp.price_usd = Some(uint_amount(&words[9], GMX_DECIMALS)?);
+    Some(with_position_key(perp, &words[0]))
+}
+
+fn with_position_key(mut perp: PerpEventV1, key: &str) -> PerpEventV1 {
+    perp.position_key = Some(format!("0x{}", key));
+    perp
+}
+
+/// `(exchangedPositionSize, exchangedPositionNotional, fee, openNotional, realizedPnl, sqrtPriceAfterX96)`
+fn perp_v2_position_changed(
+    trader: &str,
+    base_token: &str,
+    words: &[String],
+) -> Option<PerpEventV1> {
+    if words.len() < 6 {
+        return None;
+    }
+    let (size_negative, _) = signed_word(&words[0])?;
+    let (open_negative, open_notional) = signed_word(&words[3])?;
+    let (_, realized_pnl) = signed_word(&words[4])?;
+    let reduces = open_notional == "0" || realized_pnl != "0";
+
+    let mut perp = event(
+        PERP_V2,
+        "PositionChanged",
+        if reduces { PERP_CLOSE } else { PERP_OPEN },
+        topic_address(trader)?,
+        topic_address(base_token)?,
+    );
+    // A long position carries negative open notional (quote owed); a fully
+    // closed position's side is the opposite of the closing trade
+    perp.is_long = Some(if open_notional == "0" {
+        size_negative
+    } else {
+        open_negative
+    });
+    perp.size_delta_usd = Some(scale(&signed_word(&words[1])?.1, PERP_V2_DECIMALS));
+    perp.fee_usd = Some(uint_amount(&words[2], PERP_V2_DECIMALS)?);
+    perp.realized_pnl_usd = Some(int_amount(&words[4], PERP_V2_DECIMALS)?);
+    Some(perp)
+}
+
+/// `(closedPositionSize, closedPositionNotional, openNotional, realizedPnl, closedPrice)`
+fn perp_v2_position_closed(
+    trader: &str,
+    base_token: &str,
+    words: &[String],
+) -> Option<PerpEventV1> {
+    if words.len() < 5 {
+        return None;
+    }
+    let mut perp = event(
+        PERP_V2,
+        "PositionClosed",
+        PERP_CLOSE,
+        topic_address(trader)?,
+        topic_address(base_token)?,
+    );
+    perp.size_delta_usd = Some(scale(&signed_word(&words[1])?.1, PERP_V2_DECIMALS));
+    perp.realized_pnl_usd = Some(int_amount(&words[3], PERP_V2_DECIMALS)?);
+    perp.price_usd = Some(uint_amount(&words[4], PERP_V2_DECIMALS)?);
+    Some(perp)
+}
+
+/// `(positionNotional, positionSize, liquidationFee, liquidator)`
+fn perp_v2_position_liquidated(
+    trader: &str,
+    base_token: &str,
+    words: &[String],
+) -> Option<PerpEventV1> {
+    if words.len() < 4 {
+        return None;
+    }
+    let mut perp = event(
+        PERP_V2,
+        "PositionLiquidated",
+        PERP_LIQUIDATION,
+        topic_address(trader)?,
+        topic_address(base_token)?,
+    );
+    perp.size_delta_usd = Some(uint_amount(&words[0], PERP_V2_DECIMALS)?);
+    perp.fee_usd = Some(uint_amount(&words[2], PERP_V2_DECIMALS)?);
+    perp.liquidator = Some(word_address(&words[3]));
+    Some(perp)
+}
+
+/// `(fundingPayment)`; positive when the trader pays, stored negated so paid
+/// funding is negative like a loss
+fn perp_v2_funding_payment(
+    trader: &str,
+    base_token: &str,
+    words: &[String],
+) -> Option<PerpEventV1> {
+    let (negative, magnitude) = signed_word(words.first()?)?;
+    let mut perp = event(
+        PERP_V2,
+        "FundingPaymentSettled",
+        PERP_FUNDING,
+        topic_address(trader)?,
+        topic_address(base_token)?,
+    );
+    perp.funding_payment_usd = Some(signed_amount(!negative, &magnitude, PERP_V2_DECIMALS));
+    Some(perp)
+}
+
+/// ABI data split into 32-byte hex words (without `0x`)
+fn data_words(data: &str) -> Option<Vec<String>> {
+    let hex = data.trim().trim_start_matches("0x").to_lowercase();
+    if hex.len() % 64 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(
+        (0..hex.len())
+            .step_by(64)
+            .map(|i| hex[i..i + 64].to_string())
+            .collect(),
+    )
+}
+
+fn word_address(word: &str) -> String {
+    format!("0x{}", &word[24..])
+}
+
+fn topic_address(topic: &str) -> Option<String> {
+    let word = topic.trim().trim_start_matches("0x").to_lowercase();
+    (word.len() == 64).then(|| word_address(&word))
+}
+
+fn word_bool(word: &str) -> bool {
+    word.chars().any(|c| c != '0')
+}
+
+/// Sign and decimal magnitude of a two's-complement int256 word
+fn signed_word(word: &str) -> Option<(bool, String)> {
+    let negative = word.chars().next()?.to_digit(16)? >= 8;
+    if !negative {
+        return Some((false, word_to_decimal(word)?));
+    }
+    let inverted: String = word
+        .chars()
+        .map(|c| c.to_digit(16).and_then(|d| char::from_digit(15 - d, 16)))
+        .collect::<Option<_>>()?;
+    Some((true, add_one(&word_to_decimal(&inverted)?)))
+}
+
+fn add_one(decimal: &str) -> String {
+    let mut digits: Vec<u8> = decimal.bytes().map(|b| b - b'0').collect();
+    for digit in digits.iter_mut().rev() {
+        if *digit == 9 {
+            *digit = 0;
+        } else {
+            *digit += 1;
+            return digits.iter().map(|d| char::from(b'0' + d)).collect();
+        }
+    }
+    std::iter::once('1')
+        .chain(digits.iter().map(|d| char::from(b'0' + d)))
+        .collect()
+}
+
+fn uint_amount(word: &str, decimals: usize) -> Option<String> {
+    Some(scale(&word_to_decimal(word)?, decimals))
+}
+
+fn int_amount(word: &str, decimals: usize) -> Option<String> {
+    let (negative, magnitude) = signed_word(word)?;
+    Some(signed_amount(negative, &magnitude, decimals))
+}
+
+fn signed_amount(negative: bool, magnitude: &str, decimals: usize) -> String {
+    let scaled = scale(magnitude, decimals);
+    if negative && scaled != "0" {
+        format!("-{}", scaled)
+    } else {
+        scaled
+    }
+}
+
+/// Decimal string of `integer / 10^decimals`, truncated to 18 fractional digits
+pub fn scale(integer: &str, decimals: usize) -> String {
+    let integer = integer.trim_start_matches('0');
+    let padded = format!("{:0>width$}", integer, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction[..fraction.len().min(MAX_FRACTION_DIGITS)].trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// DuckLake perp_events record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakePerpEventRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub transaction_hash: String,
+    pub log_index: i32,
+    pub protocol: String,
+    pub contract_address: String,
+    pub event_type: String,
+    pub event_name: String,
+    pub account: String,
+    pub market: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collateral_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_long: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_delta_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collateral_delta_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realized_pnl_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub funding_payment_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidator: Option<String>,
+    pub ingested_at: String,
+}
+
+impl DuckLakePerpEventRecord {
+    pub fn new(log: &DuckLakeLogRecord, perp: &PerpEventV1) -> Self {
+        Self {
+            chain_id: log.chain_id.clone(),
+            block_date: log.block_date.clone(),
+            block_number: log.block_number,
+            block_timestamp: log.block_timestamp,
+            transaction_hash: log.transaction_hash.clone(),
+            log_index: log.log_index,
+            protocol: perp.protocol.clone(),
+            contract_address: log.address.clone(),
+            event_type: perp.event_type.clone(),
+            event_name: perp.event_name.clone(),
+            account: perp.account.clone(),
+            market: perp.market.clone(),
+            collateral_token: perp.collateral_token.clone(),
+            is_long: perp.is_long,
+            position_key: perp.position_key.clone(),
+            size_delta_usd: perp.size_delta_usd.clone(),
+            collateral_delta_usd: perp.collateral_delta_usd.clone(),
+            price_usd: perp.price_usd.clone(),
+            fee_usd: perp.fee_usd.clone(),
+            realized_pnl_usd: perp.realized_pnl_usd.clone(),
+            funding_payment_usd: perp.funding_payment_usd.clone(),
+            liquidator: perp.liquidator.clone(),
+            ingested_at: log.ingested_at.clone(),
+        }
+    }
+}
+
+/// Per-block decoding state
+pub struct PerpEvents {
+    subject: String,
+    pub decoded: u64,
+    pub failures: u64,
+}
+
+impl PerpEvents {
+    pub fn new(network: &str, subnet: &str) -> Self {
+        Self {
+            subject: format!(
+                "ducklake.perp_events.{}.{}.write",
+                network.to_lowercase(),
+                subnet.to_lowercase()
+            ),
+            decoded: 0,
+            failures: 0,
+        }
+    }
+
+    /// Decode and persist a perpetuals event
+    pub fn on_log(&mut self, log: &DuckLakeLogRecord) -> Option<PerpEventV1> {
+        let perp = decode_perp_event(
+            log.topic0.as_deref(),
+            log.topic1.as_deref(),
+            log.topic2.as_deref(),
+            log.data.as_deref().unwrap_or_default(),
+        )?;
+
+        let record = DuckLakePerpEventRecord::new(log, &perp);
+        let published = serde_json::to_vec(&record)
+            .map_err(|e| format!("Failed to serialize perp event: {}", e))
+            .and_then(|payload| Component::publish_message(&self.subject, &payload));
+        if let Err(err) = published {
+            self.failures += 1;
+            eprintln!("[EVM-LOGS] ❌ Failed to persist perp event: {}", err);
+        }
+
+        self.decoded += 1;
+        Some(perp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "000000000000000000000000bc4ca0eda7647a8ab7c2061c2e118a18a936f13d";
+    const WETH: &str = "00000000000000000000000082af49447d8a07e3bd95bd0d56f35241523fbab1";
+    const USDC: &str = "000000000000000000000000ff970a61a04b1ca14834a43f5de4533ebddb5cc8";
+
+    fn uint(value: u128) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn int(value: i128) -> String {
+        let fill = if value < 0 { "f" } else { "0" };
+        format!("{}{:032x}", fill.repeat(32), value)
+    }
+
+    fn usd30(dollars: u128) -> String {
+        decimal_word(&format!("{}{}", dollars, "0".repeat(30)))
+    }
+
+    /// Big-endian hex word of a decimal string (values beyond u128)
+    fn decimal_word(decimal: &str) -> String {
+        let mut bytes = [0u8; 32];
+        for digit in decimal.bytes().map(|b| (b - b'0') as u32) {
+            let mut carry = digit;
+            for byte in bytes.iter_mut().rev() {
+                let value = *byte as u32 * 10 + carry;
+                *byte = (value & 0xff) as u8;
+                carry = value >> 8;
+            }
+        }
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn data(words: &[String]) -> String {
+        format!("0x{}", words.concat())
+    }
+
+    #[test]
+    fn test_scale_and_signed_words() {
+        assert_eq!(scale("1500000000000000000", 18), "1.5");
+        assert_eq!(scale("0", 18), "0");
+        assert_eq!(scale("1", 30), "0");
+        assert_eq!(scale("123", 2), "1.23");
+        assert_eq!(signed_word(&int(-42)), Some((true, "42".to_string())));
+        assert_eq!(signed_word(&int(7)), Some((false, "7".to_string())));
+        assert_eq!(
+            int_amount(&int(-2_500_000_000_000_000_000), 18).unwrap(),
+            "-2.5"
+        );
+    }
+
+    #[test]
+    fn test_decode_gmx_increase_and_liquidation() {
+        let key = "ab".repeat(32);
+        let increase = data(&[
+            key.clone(),
+            ACCOUNT.to_string(),
+            USDC.to_string(),
+            WETH.to_string(),
+            usd30(1_000),
+            usd30(10_000),
+            uint(1),
+            usd30(1_800),
+            usd30(10),
+        ]);
+        let perp =
+            decode_perp_event(Some(GMX_INCREASE_POSITION_TOPIC), None, None, &increase).unwrap();
+        assert_eq!(perp.protocol, "gmx_v1");
+        assert_eq!(perp.event_type, PERP_OPEN);
+        assert_eq!(perp.account, "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d");
+        assert_eq!(perp.market, "0x82af49447d8a07e3bd95bd0d56f35241523fbab1");
+        assert_eq!(perp.size_delta_usd.as_deref(), Some("10000"));
+        assert_eq!(perp.collateral_delta_usd.as_deref(), Some("1000"));
+        assert_eq!(perp.is_long, Some(true));
+        assert_eq!(perp.position_key, Some(format!("0x{}", key)));
+
+        let liquidation = data(&[
+            key,
+            ACCOUNT.to_string(),
+            USDC.to_string(),
+            WETH.to_string(),
+            uint(0),
+            usd30(10_000),
+            usd30(90),
+            uint(0),
+            int(-910),
+            usd30(1_650),
+        ]);
+        let perp = decode_perp_event(Some(GMX_LIQUIDATE_POSITION_TOPIC), None, None, &liquidation)
+            .unwrap();
+        assert_eq!(perp.event_type, PERP_LIQUIDATION);
+        assert_eq!(perp.is_long, Some(false));
+        assert_eq!(perp.price_usd.as_deref(), Some("1650"));
+        // Raw -910 at 1e30 precision truncates to zero
+        assert_eq!(perp.realized_pnl_usd.as_deref(), Some("0"));
+
+        // Truncated data is not a GMX event
+        assert!(decode_perp_event(Some(GMX_INCREASE_POSITION_TOPIC), None, None, "0x").is_none());
+    }
+
+    #[test]
+    fn test_decode_perp_v2_events() {
+        let trader = format!("0x{}", ACCOUNT);
+        let base = format!("0x{}", WETH);
+        let e18 = 1_000_000_000_000_000_000i128;
+
+        // Buy 2 base for 3,600 quote: long open
+        let opened = data(&[
+            int(2 * e18),
+            int(-3_600 * e18),
+            uint(36 * e18 as u128 / 10),
+            int(-3_600 * e18),
+            int(0),
+            uint(0),
+        ]);
+        let perp = decode_perp_event(
+            Some(PERP_V2_POSITION_CHANGED_TOPIC),
+            Some(&trader),
+            Some(&base),
+            &opened,
+        )
+        .unwrap();
+        assert_eq!(perp.event_type, PERP_OPEN);
+        assert_eq!(perp.is_long, Some(true));
+        assert_eq!(perp.size_delta_usd.as_deref(), Some("3600"));
+        assert_eq!(perp.fee_usd.as_deref(), Some("3.6"));
+
+        // Sell it all back at a loss: closes the long
+        let closed = data(&[
+            int(-2 * e18),
+            int(3_500 * e18),
+            uint(0),
+            int(0),
+            int(-100 * e18),
+            uint(0),
+        ]);
+        let perp = decode_perp_event(
+            Some(PERP_V2_POSITION_CHANGED_TOPIC),
+            Some(&trader),
+            Some(&base),
+            &closed,
+        )
+        .unwrap();
+        assert_eq!(perp.event_type, PERP_CLOSE);
+        assert_eq!(perp.is_long, Some(true));
+        assert_eq!(perp.realized_pnl_usd.as_deref(), Some("-100"));
+
+        let funding = data(&[int(5 * e18)]);
+        let perp = decode_perp_event(
+            Some(PERP_V2_FUNDING_PAYMENT_SETTLED_TOPIC),
+            Some(&trader),
+            Some(&base),
+            &funding,
+        )
+        .unwrap();
+        assert_eq!(perp.event_type, PERP_FUNDING);
+        assert_eq!(perp.funding_payment_usd.as_deref(), Some("-5"));
+
+        let liquidated = data(&[
+            uint(3_500 * e18 as u128),
+            uint(2 * e18 as u128),
+            uint(35 * e18 as u128),
+            ACCOUNT.to_string(),
+        ]);
+        let perp = decode_perp_event(
+            Some(PERP_V2_POSITION_LIQUIDATED_TOPIC),
+            Some(&trader),
+            Some(&base),
+            &liquidated,
+        )
+        .unwrap();
+        assert_eq!(perp.event_type, PERP_LIQUIDATION);
+        assert_eq!(
+            perp.liquidator.as_deref(),
+            Some("0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d")
+        );
+
+        // Perp v2 events need the indexed trader
+        assert!(decode_perp_event(
+            Some(PERP_V2_FUNDING_PAYMENT_SETTLED_TOPIC),
+            None,
+            None,
+            &funding
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,399 @@
+//! Upgrade-risk simulation for watched EIP-1967 proxies.
+//!
+//! When a proxy emits `Upgraded(address)`, a fixed set of canonical ERC-20
+//! calls is simulated at the upgrade block twice: once with the proxy's
+//! implementation slot overridden to the old implementation and once with the
+//! new one. Storage is identical in both runs, so a benign upgrade returns the
+//! same data and emits the same events; any difference is reported as a
+//! [`CallDivergenceV1`].
+//!
+//! Simulation uses `eth_simulateV1` so emitted events can be compared. Nodes
+//! without it fall back to `eth_call` with a state override, which compares
+//! status and return data only.
+
+use alert_runtime_common::{CallDivergenceV1, DivergenceKindV1};
+use serde_json::{json, Value};
+
+/// `Upgraded(address indexed implementation)`
+pub const UPGRADED_TOPIC: &str =
+    "0xbc7cd75a20ee27fd9adebab32041f755214dbc6bffa89cc0225b39da2e5c2d3b";
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+pub const IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// Holder used as `from` / balance probe; burn addresses hold balances on most tokens
+const PROBE_HOLDER: &str = "0x000000000000000000000000000000000000dead";
+const PROBE_RECIPIENT: &str = "0x0000000000000000000000000000000000000001";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalCall {
+    pub label: &'static str,
+    pub data: String,
+}
+
+pub fn canonical_calls() -> Vec<CanonicalCall> {
+    let probe = address_word(PROBE_HOLDER);
+    let recipient = address_word(PROBE_RECIPIENT);
+    let zero = "0".repeat(64);
+    vec![
+        CanonicalCall {
+            label: "name()",
+            data: "0x06fdde03".to_string(),
+        },
+        CanonicalCall {
+            label: "symbol()",
+            data: "0x95d89b41".to_string(),
+        },
+        CanonicalCall {
+            label: "decimals()",
+            data: "0x313ce567".to_string(),
+        },
+        CanonicalCall {
+            label: "totalSupply()",
+            data: "0x18160ddd".to_string(),
+        },
+        CanonicalCall {
+            label: "balanceOf(probe)",
+            data: format!("0x70a08231{}", probe),
+        },
+        CanonicalCall {
+            label: "transfer(probe, 0)",
+            data: format!("0xa9059cbb{}{}", recipient, zero),
+        },
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmittedLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallOutcome {
+    pub success: bool,
+    pub return_data: String,
+    /// `None` when the simulation method cannot report events
+    pub logs: Option<Vec<EmittedLog>>,
+}
+
+/// New implementation announced by an `Upgraded` log
+pub fn upgraded_implementation(topic0: Option<&str>, topic1: Option<&str>) -> Option<String> {
+    if topic0? != UPGRADED_TOPIC {
+        return None;
+    }
+    slot_address(topic1?)
+}
+
+/// Address stored in a 32-byte word, or `None` for the zero address
+pub fn slot_address(word: &str) -> Option<String> {
+    let hex = word.trim().trim_start_matches("0x").to_lowercase();
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let address = &hex[24..];
+    if address.chars().all(|c| c == '0') {
+        return None;
+    }
+    Some(format!("0x{}", address))
+}
+
+fn address_word(address: &str) -> String {
+    format!(
+        "{:0>64}",
+        address.trim().trim_start_matches("0x").to_lowercase()
+    )
+}
+
+fn implementation_override(proxy: &str, implementation: &str) -> Value {
+    json!({
+        proxy: {
+            "stateDiff": {
+                IMPLEMENTATION_SLOT: format!("0x{}", address_word(implementation)),
+            }
+        }
+    })
+}
+
+/// `eth_simulateV1` params running every canonical call against `implementation`
+pub fn simulate_params(
+    proxy: &str,
+    implementation: &str,
+    calls: &[CanonicalCall],
+    block_tag: &str,
+) -> Value {
+    let calls: Vec<Value> = calls
+        .iter()
+        .map(|call| json!({"from": PROBE_HOLDER, "to": proxy, "data": call.data}))
+        .collect();
+    json!([
+        {
+            "blockStateCalls": [{
+                "stateOverrides": implementation_override(proxy, implementation),
+                "calls": calls,
+            }],
+            "validation": false,
+        },
+        block_tag
+    ])
+}
+
+/// `eth_call` params for one canonical call against `implementation`
+pub fn eth_call_params(
+    proxy: &str,
+    implementation: &str,
+    call: &CanonicalCall,
+    block_tag: &str,
+) -> Value {
+    json!([
+        {"from": PROBE_HOLDER, "to": proxy, "data": call.data},
+        block_tag,
+        implementation_override(proxy, implementation),
+    ])
+}
+
+/// Per-call outcomes from an `eth_simulateV1` result
+pub fn parse_simulation(result: &Value) -> Result<Vec<CallOutcome>, String> {
+    let calls = result
+        .get(0)
+        .and_then(|block| block.get("calls"))
+        .and_then(|calls| calls.as_array())
+        .ok_or("simulation result has no calls")?;
+
+    Ok(calls
+        .iter()
+        .map(|call| {
+            let logs = call
+                .get("logs")
+                .and_then(|logs| logs.as_array())
+                .map(|logs| logs.iter().map(emitted_log).collect());
+            CallOutcome {
+                success: call.get("status").and_then(|s| s.as_str()) == Some("0x1"),
+                return_data: string_field(call, "returnData"),
+                logs,
+            }
+        })
+        .collect())
+}
+
+/// Outcome of a full `eth_call` JSON-RPC response; reverts arrive as errors
+pub fn outcome_from_eth_call(response: &Value) -> Result<CallOutcome, String> {
+    if let Some(result) = response.get("result").and_then(|r| r.as_str()) {
+        return Ok(CallOutcome {
+            success: true,
+            return_data: result.to_lowercase(),
+            logs: None,
+        });
+    }
+
+    let error = response.get("error").ok_or("eth_call returned no result")?;
+    let message = string_field(error, "message");
+    // Code 3 is the standard revert error; older nodes only say so in the message
+    let reverted =
+        error.get("code").and_then(|c| c.as_i64()) == Some(3) || message.contains("revert");
+    if !reverted {
+        return Err(format!("eth_call failed: {}", error));
+    }
+    Ok(CallOutcome {
+        success: false,
+        return_data: string_field(error, "data"),
+        logs: None,
+    })
+}
+
+/// True when the node rejected the method itself rather than the call
+pub fn is_unsupported_method(error: &Value) -> bool {
+    matches!(
+        error.get("code").and_then(|c| c.as_i64()),
+        Some(-32601) | Some(-32602)
+    )
+}
+
+fn emitted_log(log: &Value) -> EmittedLog {
+    EmittedLog {
+        address: string_field(log, "address"),
+        topics: log
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .map(|topics| {
+                topics
+                    .iter()
+                    .filter_map(|t| t.as_str())
+                    .map(str::to_lowercase)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        data: string_field(log, "data"),
+    }
+}
+
+fn string_field(value: &Value, field: &str) -> String {
+    value
+        .get(field)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+fn describe_logs(logs: &[EmittedLog]) -> String {
+    let topics: Vec<&str> = logs
+        .iter()
+        .map(|log| {
+            log.topics
+                .first()
+                .map(String::as_str)
+                .unwrap_or("anonymous")
+        })
+        .collect();
+    format!("{} logs [{}]", logs.len(), topics.join(", "))
+}
+
+/// Differences between the old and new implementation, call by call
+pub fn diff_outcomes(
+    calls: &[CanonicalCall],
+    old: &[CallOutcome],
+    new: &[CallOutcome],
+) -> Vec<CallDivergenceV1> {
+    let mut divergences = Vec::new();
+    for ((call, old), new) in calls.iter().zip(old).zip(new) {
+        let divergence = |kind, old: String, new: String| CallDivergenceV1 {
+            call: call.label.to_string(),
+            kind,
+            old,
+            new,
+        };
+        let status =
+            |outcome: &CallOutcome| if outcome.success { "success" } else { "revert" }.to_string();
+
+        if old.success != new.success {
+            divergences.push(divergence(
+                DivergenceKindV1::Status,
+                status(old),
+                status(new),
+            ));
+            continue;
+        }
+        if !old.success {
+            continue;
+        }
+        if old.return_data != new.return_data {
+            divergences.push(divergence(
+                DivergenceKindV1::ReturnData,
+                old.return_data.clone(),
+                new.return_data.clone(),
+            ));
+        }
+        if let (Some(old_logs), Some(new_logs)) = (&old.logs, &new.logs) {
+            if old_logs != new_logs {
+                divergences.push(divergence(
+                    DivergenceKindV1::Events,
+                    describe_logs(old_logs),
+                    describe_logs(new_logs),
+                ));
+            }
+        }
+    }
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: &str = "0x00000000000000000000000000000000000000aa";
+    const TRANSFER: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn simulated(status: &str, return_data: &str, topics: &[&str]) -> Value {
+        json!({
+            "status": status,
+            "returnData": return_data,
+            "logs": topics.iter().map(|t| json!({"address": PROXY, "topics": [t], "data": "0x"})).collect::<Vec<_>>(),
+        })
+    }
+
+    #[test]
+    fn test_upgraded_implementation_and_slot_address() {
+        let topic1 = "0x000000000000000000000000AbCd00000000000000000000000000000000beef";
+        assert_eq!(
+            upgraded_implementation(Some(UPGRADED_TOPIC), Some(topic1)).as_deref(),
+            Some("0xabcd00000000000000000000000000000000beef")
+        );
+        assert!(upgraded_implementation(Some(TRANSFER), Some(topic1)).is_none());
+        assert!(slot_address(&format!("0x{}", "0".repeat(64))).is_none());
+        assert!(slot_address("0x1234").is_none());
+    }
+
+    #[test]
+    fn test_simulate_params_override_implementation_slot() {
+        let calls = canonical_calls();
+        let params = simulate_params(PROXY, "0xbeef", &calls, "0x10");
+        let block = &params[0]["blockStateCalls"][0];
+        assert_eq!(
+            block["stateOverrides"][PROXY]["stateDiff"][IMPLEMENTATION_SLOT],
+            format!("0x{:0>64}", "beef")
+        );
+        assert_eq!(block["calls"].as_array().unwrap().len(), calls.len());
+        assert_eq!(params[1], "0x10");
+        assert_eq!(calls[5].data.len(), 2 + 8 + 128);
+    }
+
+    #[test]
+    fn test_diff_outcomes_reports_status_data_and_event_changes() {
+        let calls = &canonical_calls()[3..6];
+        let old = parse_simulation(&json!([{"calls": [
+            simulated("0x1", "0x64", &[]),
+            simulated("0x1", "0x05", &[]),
+            simulated("0x1", "0x01", &[TRANSFER]),
+        ]}]))
+        .unwrap();
+
+        assert!(diff_outcomes(calls, &old, &old).is_empty());
+
+        let new = parse_simulation(&json!([{"calls": [
+            simulated("0x1", "0x64", &[]),
+            simulated("0x1", "0x00", &[]),
+            simulated("0x0", "0x", &[]),
+        ]}]))
+        .unwrap();
+        let divergences = diff_outcomes(calls, &old, &new);
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(divergences[0].call, "balanceOf(probe)");
+        assert_eq!(divergences[0].kind, DivergenceKindV1::ReturnData);
+        assert_eq!(divergences[1].kind, DivergenceKindV1::Status);
+        assert_eq!(divergences[1].new, "revert");
+
+        let quiet = parse_simulation(&json!([{"calls": [
+            simulated("0x1", "0x64", &[]),
+            simulated("0x1", "0x05", &[]),
+            simulated("0x1", "0x01", &[]),
+        ]}]))
+        .unwrap();
+        let divergences = diff_outcomes(calls, &old, &quiet);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].kind, DivergenceKindV1::Events);
+    }
+
+    #[test]
+    fn test_outcome_from_eth_call() {
+        let ok = outcome_from_eth_call(&json!({"result": "0x0A"})).unwrap();
+        assert!(ok.success);
+        assert_eq!(ok.return_data, "0x0a");
+        assert!(ok.logs.is_none());
+
+        let revert = outcome_from_eth_call(
+            &json!({"error": {"code": 3, "message": "execution reverted", "data": "0x08c379a0"}}),
+        )
+        .unwrap();
+        assert!(!revert.success);
+
+        assert!(
+            outcome_from_eth_call(&json!({"error": {"code": -32000, "message": "timeout"}}))
+                .is_err()
+        );
+        assert!(is_unsupported_method(
+            &json!({"code": -32601, "message": "method not found"})
+        ));
+    }
+}
@@ -117,10 +117,11 @@ export!(Component);
 impl MessageConsumer for Component {
     /// Handle incoming NATS messages containing health check requests
     fn handle_message(subject: String, payload: Vec<u8>) -> Result<(), String> {
-        // Only process health check messages
-        if !subject.starts_with("health.") {
-            return Ok(()); // Ignore non-health messages
-        }
+        // Only process health check messages from this environment
+        let subject = match subject_registry::unprefixed(&subject) {
+            Some(subject) if subject.starts_with("health.") => subject,
+            _ => return Ok(()), // Ignore non-health messages
+        };
 
         eprintln!(
             "Health check actor received message on subject: {}",
@@ -154,7 +155,10 @@ impl MessageConsumer for Component {
         // Use the messaging handler to publish response
         use ekko::messaging::handler::publish;
 
-        match publish(&response_subject, &response_json) {
+        match publish(
+            &subject_registry::prefixed(&response_subject),
+            &response_json,
+        ) {
             Ok(_) => {
                 eprintln!("Health check response sent to: {}", response_subject);
                 Ok(())
@@ -5,6 +5,9 @@
 //! - renders notification templates per matched target
 //! - enforces dedupe/cooldown (wasi:keyvalue/atomics + store)
 //! - publishes channel delivery requests (v1: webhook)
+//!
+//! Batches sealed with per-tenant payload encryption are opened transparently
+//! using the `alert-payload-master-key` secret (wasmcloud:secrets).
 
 mod runtime;
 
@@ -22,6 +25,9 @@ use wasmcloud::messaging::types as nats_types;
 #[cfg(target_arch = "wasm32")]
 use wasi::keyvalue::{atomics, store};
 
+#[cfg(target_arch = "wasm32")]
+subject_acl::component!("notification-router");
+
 #[cfg(target_arch = "wasm32")]
 struct Component;
 
@@ -61,8 +67,16 @@ impl RuntimeIO for WasmRuntime {
     }
 
     fn nats_publish(&self, subject: &str, body: Vec<u8>) -> Result<(), RouterError> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(RouterError::store(violation.to_string()));
+        }
         let msg = nats_types::BrokerMessage {
-            subject: subject.to_string(),
+            subject: subject_registry::prefixed(subject),
             body,
             reply_to: None,
         };
@@ -71,6 +85,25 @@ impl RuntimeIO for WasmRuntime {
         Ok(())
     }
 
+    fn secret_get(&self, name: &str) -> Result<Option<Vec<u8>>, RouterError> {
+        use wasmcloud::secrets::store::{SecretValue, SecretsError};
+
+        let secret = match wasmcloud::secrets::store::get(name) {
+            Ok(secret) => secret,
+            Err(SecretsError::NotFound) => return Ok(None),
+            Err(e) => {
+                return Err(RouterError::encryption(format!(
+                    "secret {} unavailable: {:?}",
+                    name, e
+                )))
+            }
+        };
+        Ok(Some(match wasmcloud::secrets::reveal::reveal(&secret) {
+            SecretValue::String(s) => s.into_bytes(),
+            SecretValue::Bytes(b) => b,
+        }))
+    }
+
     fn now_unix_secs(&self) -> i64 {
         chrono::Utc::now().timestamp()
     }
@@ -80,6 +113,13 @@ impl RuntimeIO for WasmRuntime {
 impl MessageHandler for Component {
     fn handle_message(msg: nats_types::BrokerMessage) -> std::result::Result<(), String> {
         let io = WasmRuntime;
-        runtime::handle_nats_message(&io, &msg.subject, &msg.body).map_err(|e| e.to_string())
+        let subject = subject_registry::unprefixed(&msg.subject).ok_or_else(|| {
+            format!(
+                "Subject {} is outside environment prefix {}",
+                msg.subject,
+                subject_registry::active_prefix()
+            )
+        })?;
+        runtime::handle_nats_message(&io, subject, &msg.body).map_err(|e| e.to_string())
     }
 }
@@ -1,8 +1,12 @@
 use alert_runtime_common::{
-    alert_triggered_batch_schema_version_v1, ActionV1, AlertTriggeredBatchV1,
-    NotificationTemplateV1,
+    alert_escalation_due_schema_version_v1, alert_schedule_escalation_schema_version_v1,
+    alert_triggered_batch_schema_version_v1, ActionV1, AlertEscalationDueV1,
+    AlertScheduleEscalationV1, AlertTriggeredBatchV1, EscalationPolicyV1, NotificationTemplateV1,
+    ALERT_SCHEDULE_ESCALATION_SUBJECT,
 };
 use chrono::{TimeZone, Utc};
+use payload_encryption::{EncryptedPayloadV1, TenantKeyring, MASTER_KEY_SECRET};
+use retention_policy::{ALERTS_ACK, ALERTS_COOLDOWN, ALERTS_DEDUPE, ALERTS_ESCALATION};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -41,6 +45,13 @@ impl RouterError {
             message,
         }
     }
+
+    pub fn encryption(message: String) -> Self {
+        Self {
+            code: "encryption_error",
+            message,
+        }
+    }
 }
 
 impl std::fmt::Display for RouterError {
@@ -57,6 +68,7 @@ pub trait RuntimeIO {
     fn kv_exists(&self, key: &str) -> Result<bool, RouterError>;
     fn kv_incr(&self, key: &str, delta: u64) -> Result<u64, RouterError>;
     fn nats_publish(&self, subject: &str, body: Vec<u8>) -> Result<(), RouterError>;
+    fn secret_get(&self, name: &str) -> Result<Option<Vec<u8>>, RouterError>;
     fn now_unix_secs(&self) -> i64;
 }
 
@@ -74,6 +86,36 @@ struct InstanceSnapshotV1 {
     variable_values: Value,
     notification_template: NotificationTemplateV1,
     action: ActionV1,
+    #[serde(default)]
+    escalation_policy: Option<EscalationPolicyV1>,
+}
+
+/// A delivered notification waiting for acknowledgement
+/// (`alerts:escalation:{notification_id}`).
+///
+/// Keeps the triggering message as received, so sealed batches stay sealed at
+/// rest; it is opened and re-rendered when a step fires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingEscalationV1 {
+    notification_id: String,
+    instance_id: String,
+    user_id: String,
+    batch_body: Value,
+    /// Index of the escalated match in the batch
+    match_index: usize,
+    sent_at: i64,
+    /// Policy as of the original delivery; later rule edits do not affect it
+    policy: EscalationPolicyV1,
+    /// First step not yet sent; redelivered due messages for earlier steps are ignored
+    #[serde(default)]
+    next_step: usize,
+}
+
+struct RenderedNotification {
+    render_context: Value,
+    alert_name: String,
+    title: String,
+    message: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -153,22 +195,67 @@ pub fn handle_nats_message(
     subject: &str,
     body: &[u8],
 ) -> Result<(), RouterError> {
+    if subject.starts_with("alerts.escalation.due.") {
+        let due: AlertEscalationDueV1 = serde_json::from_slice(body)
+            .map_err(|e| RouterError::json(format!("invalid escalation due: {e}")))?;
+        if due.schema_version != alert_escalation_due_schema_version_v1() {
+            return Ok(());
+        }
+        return handle_escalation_due(io, due);
+    }
     if !subject.starts_with("alerts.triggered.") {
         return Ok(());
     }
 
+    let Some(batch) = parse_triggered_batch(io, body)? else {
+        return Ok(());
+    };
+
+    route_triggered_batch(io, batch, body)
+}
+
+/// Open (if sealed) and parse a triggered batch; `None` for other schema versions.
+fn parse_triggered_batch(
+    io: &dyn RuntimeIO,
+    body: &[u8],
+) -> Result<Option<AlertTriggeredBatchV1>, RouterError> {
+    let opened = open_sealed_batch(io, body)?;
+    let body = opened.as_deref().unwrap_or(body);
+
     let batch: AlertTriggeredBatchV1 = serde_json::from_slice(body)
         .map_err(|e| RouterError::json(format!("invalid triggered batch: {e}")))?;
     if batch.schema_version != alert_triggered_batch_schema_version_v1() {
-        return Ok(());
+        return Ok(None);
     }
+    Ok(Some(batch))
+}
+
+/// Decrypt a batch sealed for its tenant; `None` for plaintext batches.
+fn open_sealed_batch(io: &dyn RuntimeIO, body: &[u8]) -> Result<Option<Vec<u8>>, RouterError> {
+    let Some(envelope) = EncryptedPayloadV1::detect(body) else {
+        return Ok(None);
+    };
 
-    route_triggered_batch(io, batch)
+    let Some(master_key) = io.secret_get(MASTER_KEY_SECRET)? else {
+        return Err(RouterError::encryption(format!(
+            "sealed batch for tenant {} but secret {} is not provisioned",
+            envelope.tenant_id, MASTER_KEY_SECRET
+        )));
+    };
+    let keyring = TenantKeyring::new(master_key)
+        .map_err(|e| RouterError::encryption(format!("{}: {e}", MASTER_KEY_SECRET)))?;
+    keyring.open(&envelope).map(Some).map_err(|e| {
+        RouterError::encryption(format!(
+            "sealed batch for tenant {} (key {}): {e}",
+            envelope.tenant_id, envelope.key_id
+        ))
+    })
 }
 
 fn route_triggered_batch(
     io: &dyn RuntimeIO,
     batch: AlertTriggeredBatchV1,
+    raw_body: &[u8],
 ) -> Result<(), RouterError> {
     let instance = load_instance_snapshot(io, &batch.instance_id)?;
     if instance.instance_id != batch.instance_id {
@@ -183,36 +270,14 @@ fn route_triggered_batch(
 
     let recipients = load_recipients(io, &batch.instance_id, &instance.user_id)?;
 
-    for m in batch.matches.iter() {
+    for (match_index, m) in batch.matches.iter().enumerate() {
         let target = parse_target_key(&m.target_key)?;
-
-        let render_context = build_render_context(&batch, &instance, &target, &m.match_context)?;
-
-        let title_rendered =
-            render_template(&instance.notification_template.title, &render_context)?;
-        let body_rendered = render_template(&instance.notification_template.body, &render_context)?;
-        let alert_name_raw = if instance.alert_name.trim().is_empty() {
-            if title_rendered.trim().is_empty() {
-                "Alert triggered".to_string()
-            } else {
-                title_rendered.clone()
-            }
-        } else {
-            instance.alert_name.clone()
-        };
-        let title_raw = if title_rendered.trim().is_empty() {
-            alert_name_raw.clone()
-        } else {
-            title_rendered
-        };
-        let message_raw = if body_rendered.trim().is_empty() {
-            alert_name_raw.clone()
-        } else {
-            body_rendered
-        };
-        let title = truncate_hex_addresses_in_text(&title_raw);
-        let message = truncate_hex_addresses_in_text(&message_raw);
-        let alert_name = truncate_hex_addresses_in_text(&alert_name_raw);
+        let RenderedNotification {
+            render_context,
+            alert_name,
+            title,
+            message,
+        } = render_notification(&batch, &instance, &target, &m.match_context)?;
 
         for user_id in recipients.iter() {
             let dedupe_key =
@@ -280,12 +345,186 @@ fn route_triggered_batch(
                 alert_name.clone(),
                 message.clone(),
             )?;
+
+            if let Some(policy) = instance
+                .escalation_policy
+                .as_ref()
+                .filter(|p| !p.is_empty())
+            {
+                let batch_body = serde_json::from_slice(raw_body)
+                    .map_err(|e| RouterError::json(format!("triggered batch: {e}")))?;
+                let pending = PendingEscalationV1 {
+                    notification_id: notification_id.clone(),
+                    instance_id: batch.instance_id.clone(),
+                    user_id: user_id.clone(),
+                    batch_body,
+                    match_index,
+                    sent_at: io.now_unix_secs(),
+                    policy: policy.clone(),
+                    next_step: 0,
+                };
+                arm_escalation_step(io, &pending)?;
+            }
         }
     }
 
     Ok(())
 }
 
+fn render_notification(
+    batch: &AlertTriggeredBatchV1,
+    instance: &InstanceSnapshotV1,
+    target: &ParsedTargetKey,
+    match_context: &Value,
+) -> Result<RenderedNotification, RouterError> {
+    let render_context = build_render_context(batch, instance, target, match_context)?;
+
+    let title_rendered = render_template(&instance.notification_template.title, &render_context)?;
+    let body_rendered = render_template(&instance.notification_template.body, &render_context)?;
+    let alert_name_raw = if instance.alert_name.trim().is_empty() {
+        if title_rendered.trim().is_empty() {
+            "Alert triggered".to_string()
+        } else {
+            title_rendered.clone()
+        }
+    } else {
+        instance.alert_name.clone()
+    };
+    let title_raw = if title_rendered.trim().is_empty() {
+        alert_name_raw.clone()
+    } else {
+        title_rendered
+    };
+    let message_raw = if body_rendered.trim().is_empty() {
+        alert_name_raw.clone()
+    } else {
+        body_rendered
+    };
+
+    Ok(RenderedNotification {
+        render_context,
+        alert_name: truncate_hex_addresses_in_text(&alert_name_raw),
+        title: truncate_hex_addresses_in_text(&title_raw),
+        message: truncate_hex_addresses_in_text(&message_raw),
+    })
+}
+
+/// Persist the pending escalation and ask the scheduler for its next timer.
+fn arm_escalation_step(
+    io: &dyn RuntimeIO,
+    pending: &PendingEscalationV1,
+) -> Result<(), RouterError> {
+    let bytes = serde_json::to_vec(pending)
+        .map_err(|e| RouterError::json(format!("pending escalation: {e}")))?;
+    io.kv_set(&ALERTS_ESCALATION.key(&pending.notification_id), bytes)?;
+
+    let sent_at = Utc
+        .timestamp_opt(pending.sent_at, 0)
+        .single()
+        .ok_or_else(|| RouterError::schema(format!("invalid sent_at {}", pending.sent_at)))?;
+    let Some(due_at) = pending.policy.due_at(pending.next_step, sent_at) else {
+        return Ok(());
+    };
+
+    let req = AlertScheduleEscalationV1 {
+        schema_version: alert_schedule_escalation_schema_version_v1(),
+        notification_id: pending.notification_id.clone(),
+        instance_id: pending.instance_id.clone(),
+        user_id: pending.user_id.clone(),
+        step: pending.next_step,
+        due_at,
+        requested_at: Utc
+            .timestamp_opt(io.now_unix_secs(), 0)
+            .single()
+            .unwrap_or(sent_at),
+        source: "notification_router".to_string(),
+    };
+    let bytes = serde_json::to_vec(&req)
+        .map_err(|e| RouterError::json(format!("escalation request: {e}")))?;
+    io.nats_publish(ALERT_SCHEDULE_ESCALATION_SUBJECT, bytes)
+}
+
+/// Re-send an unacknowledged notification to the channels of the due step.
+fn handle_escalation_due(io: &dyn RuntimeIO, due: AlertEscalationDueV1) -> Result<(), RouterError> {
+    let Some(raw) = io.kv_get(&ALERTS_ESCALATION.key(&due.notification_id))? else {
+        return Ok(());
+    };
+    let mut pending: PendingEscalationV1 = serde_json::from_slice(&raw)
+        .map_err(|e| RouterError::json(format!("pending escalation: {e}")))?;
+    if due.step < pending.next_step {
+        return Ok(());
+    }
+    if io.kv_exists(&ALERTS_ACK.key(&due.notification_id))? {
+        return Ok(());
+    }
+    let Some(step) = pending.policy.steps.get(due.step).cloned() else {
+        return Ok(());
+    };
+
+    // Disabling or deleting the rule stops its escalations
+    let instance = match load_instance_snapshot(io, &pending.instance_id) {
+        Ok(instance) if instance.enabled => instance,
+        _ => return Ok(()),
+    };
+    let batch_body = serde_json::to_vec(&pending.batch_body)
+        .map_err(|e| RouterError::json(format!("pending escalation batch: {e}")))?;
+    let Some(batch) = parse_triggered_batch(io, &batch_body)? else {
+        return Ok(());
+    };
+    let Some(m) = batch.matches.get(pending.match_index) else {
+        return Ok(());
+    };
+    let target = parse_target_key(&m.target_key)?;
+    let rendered = render_notification(&batch, &instance, &target, &m.match_context)?;
+    let title = format!("[Escalated] {}", rendered.title);
+
+    for channel in step.channels.iter() {
+        match channel.as_str() {
+            "webhook" => publish_webhook(
+                io,
+                &instance,
+                &batch,
+                &pending.user_id,
+                &target.key,
+                &pending.notification_id,
+                rendered.alert_name.clone(),
+                title.clone(),
+                rendered.message.clone(),
+                m.match_context.clone(),
+            )?,
+            "websocket" => publish_websocket(
+                io,
+                &instance,
+                &batch,
+                &pending.user_id,
+                &pending.notification_id,
+                title.clone(),
+                rendered.message.clone(),
+                &rendered.render_context,
+            )?,
+            "telegram" => publish_telegram(
+                io,
+                &instance,
+                &batch,
+                &pending.user_id,
+                &target,
+                &pending.notification_id,
+                rendered.alert_name.clone(),
+                format!("{}\n{}", title, rendered.message),
+            )?,
+            other => {
+                return Err(RouterError::schema(format!(
+                    "unsupported escalation channel '{}'",
+                    other
+                )))
+            }
+        }
+    }
+
+    pending.next_step = due.step + 1;
+    arm_escalation_step(io, &pending)
+}
+
 fn load_instance_snapshot(
     io: &dyn RuntimeIO,
     instance_id: &str,
@@ -394,6 +633,10 @@ fn build_render_context(
         let tx_json =
             serde_json::to_value(tx).map_err(|e| RouterError::json(format!("tx json: {e}")))?;
         root.insert("tx".to_string(), tx_json.clone());
+        if let Some(nft) = tx_json.get("nft") {
+            // `{{nft.display_name}}` / `{{nft.image}}` for NFT transfer alerts
+            root.insert("nft".to_string(), nft.clone());
+        }
         if let Value::Object(map) = tx_json {
             trigger_map = map;
         }
@@ -528,7 +771,7 @@ fn value_to_string(v: &Value) -> String {
 }
 
 fn check_dedupe(io: &dyn RuntimeIO, user_id: &str, dedupe_key: &str) -> Result<bool, RouterError> {
-    let key = format!("alerts:dedupe:{}:{}", user_id, dedupe_key);
+    let key = ALERTS_DEDUPE.key(&format!("{}:{}", user_id, dedupe_key));
     let count = io.kv_incr(&key, 1)?;
     Ok(count == 1)
 }
@@ -540,7 +783,7 @@ fn check_cooldown(
     cooldown_secs: i64,
     now: i64,
 ) -> Result<bool, RouterError> {
-    let key = format!("alerts:cooldown:{}:{}", user_id, cooldown_key);
+    let key = ALERTS_COOLDOWN.key(&format!("{}:{}", user_id, cooldown_key));
     if let Some(raw) = io.kv_get(&key)? {
         if let Ok(s) = String::from_utf8(raw) {
             if let Ok(last) = s.parse::<i64>() {
@@ -905,6 +1148,7 @@ mod tests {
                 method_selector: None,
                 value_wei: None,
                 value_native: None,
+                transfer_category_context: None,
                 log_index: None,
                 log_address: None,
                 topic0: None,
@@ -912,6 +1156,8 @@ mod tests {
                 topic2: None,
                 topic3: None,
                 data: None,
+                nft: None,
+                perp: None,
                 block_number: 1,
                 block_timestamp: Utc.timestamp_opt(0, 0).unwrap(),
             }),
@@ -960,6 +1206,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_context_exposes_nft_metadata() {
+        let batch = AlertTriggeredBatchV1 {
+            schema_version: alert_triggered_batch_schema_version_v1(),
+            job_id: "job1".to_string(),
+            run_id: "run1".to_string(),
+            instance_id: "inst1".to_string(),
+            partition: alert_runtime_common::PartitionV1 {
+                network: "ETH".to_string(),
+                subnet: "mainnet".to_string(),
+                chain_id: 1,
+            },
+            schedule: None,
+            tx: Some(alert_runtime_common::EvaluationTxV1 {
+                kind: alert_runtime_common::TxKindV1::Log,
+                hash: "0xhash".to_string(),
+                from: None,
+                to: None,
+                method_selector: None,
+                value_wei: None,
+                value_native: None,
+                transfer_category_context: None,
+                log_index: Some(3),
+                log_address: Some("0xbc4c".to_string()),
+                topic0: None,
+                topic1: None,
+                topic2: None,
+                topic3: None,
+                data: None,
+                nft: Some(alert_runtime_common::NftMetadataV1 {
+                    collection_address: "0xbc4c".to_string(),
+                    token_id: "1234".to_string(),
+                    display_name: "Bored Ape #1234".to_string(),
+                    collection_name: Some("Bored Ape".to_string()),
+                    name: None,
+                    image: Some("https://ipfs.io/ipfs/QmImage".to_string()),
+                    token_uri: None,
+                }),
+                perp: None,
+                block_number: 1,
+                block_timestamp: Utc.timestamp_opt(0, 0).unwrap(),
+            }),
+            matches: vec![],
+        };
+        let instance: InstanceSnapshotV1 = serde_json::from_value(serde_json::json!({
+            "instance_id": "inst1",
+            "alert_name": "NFT Alert",
+            "user_id": "u1",
+            "enabled": true,
+            "priority": "normal",
+            "variable_values": {},
+            "notification_template": { "title": "t", "body": "b" },
+            "action": {
+                "notification_policy": "per_matched_target",
+                "cooldown_secs": 0,
+                "cooldown_key_template": "x",
+                "dedupe_key_template": "y"
+            }
+        }))
+        .unwrap();
+
+        let target = parse_target_key("ETH:mainnet:0x222").unwrap();
+        let ctx = build_render_context(&batch, &instance, &target, &serde_json::json!({})).unwrap();
+        let out = render_template("{{nft.display_name}} moved ({{tx.nft.image}})", &ctx).unwrap();
+        assert_eq!(out, "Bored Ape #1234 moved (https://ipfs.io/ipfs/QmImage)");
+    }
+
     #[test]
     fn truncates_hex_addresses_only() {
         let addr = "0x1234567890abcdef1234567890abcdef12345678";
@@ -1041,6 +1354,7 @@ mod tests {
         kv: Mutex<HashMap<String, Vec<u8>>>,
         incr: Mutex<HashMap<String, u64>>,
         published: Mutex<Vec<(String, Vec<u8>)>>,
+        secrets: HashMap<String, Vec<u8>>,
         now: i64,
     }
 
@@ -1050,6 +1364,7 @@ mod tests {
                 kv: Mutex::new(HashMap::new()),
                 incr: Mutex::new(HashMap::new()),
                 published: Mutex::new(Vec::new()),
+                secrets: HashMap::new(),
                 now,
             }
         }
@@ -1098,6 +1413,10 @@ mod tests {
             Ok(())
         }
 
+        fn secret_get(&self, name: &str) -> Result<Option<Vec<u8>>, RouterError> {
+            Ok(self.secrets.get(name).cloned())
+        }
+
         fn now_unix_secs(&self) -> i64 {
             self.now
         }
@@ -1276,6 +1595,71 @@ mod tests {
         assert_eq!(websocket["message"], "Alert Name");
     }
 
+    #[test]
+    fn opens_sealed_batches_transparently() {
+        let mut io = MockRuntime::new(1_000);
+        io.secrets
+            .insert(MASTER_KEY_SECRET.to_string(), vec![4u8; 32]);
+        let keyring = TenantKeyring::new(vec![4u8; 32]).unwrap();
+
+        io.put_json(
+            "alerts:instance:inst1",
+            serde_json::json!({
+                "instance_id": "inst1",
+                "alert_name": "Sealed Alert",
+                "user_id": "u1",
+                "enabled": true,
+                "priority": "normal",
+                "variable_values": {},
+                "notification_template": { "title": "T", "body": "B" },
+                "action": {
+                    "notification_policy": "per_matched_target",
+                    "cooldown_secs": 0,
+                    "cooldown_key_template": "x",
+                    "dedupe_key_template": "{{run_id}}:{{target.key}}"
+                }
+            }),
+        );
+
+        let batch = AlertTriggeredBatchV1 {
+            schema_version: alert_triggered_batch_schema_version_v1(),
+            job_id: "job1".to_string(),
+            run_id: "run1".to_string(),
+            instance_id: "inst1".to_string(),
+            partition: alert_runtime_common::PartitionV1 {
+                network: "ETH".to_string(),
+                subnet: "mainnet".to_string(),
+                chain_id: 1,
+            },
+            schedule: None,
+            tx: None,
+            matches: vec![alert_runtime_common::AlertTriggeredMatchV1 {
+                target_key: "ETH:mainnet:0xabc".to_string(),
+                match_context: serde_json::json!({}),
+            }],
+        };
+
+        let public_key = keyring.public_key("u1", "k1");
+        let envelope = payload_encryption::seal(
+            &serde_json::to_vec(&batch).unwrap(),
+            "u1",
+            "k1",
+            &public_key,
+        )
+        .unwrap();
+        let sealed = serde_json::to_vec(&envelope).unwrap();
+        handle_nats_message(&io, "alerts.triggered.inst1", &sealed).unwrap();
+
+        let published = io.published();
+        assert!(published.iter().any(|(subject, v)| subject
+            == "notifications.send.immediate.webhook"
+            && v["alert_name"] == "Sealed Alert"));
+
+        let unkeyed = MockRuntime::new(1_000);
+        let err = handle_nats_message(&unkeyed, "alerts.triggered.inst1", &sealed).unwrap_err();
+        assert_eq!(err.code, "encryption_error");
+    }
+
     #[test]
     fn cooldown_suppresses_across_runs() {
         let io = MockRuntime::new(1_000);
@@ -1329,4 +1713,102 @@ mod tests {
         handle_nats_message(&io, "alerts.triggered.ETH.mainnet", &bytes2).unwrap();
         assert_eq!(io.published().len(), 4);
     }
+
+    #[test]
+    fn escalates_until_acknowledged() {
+        let io = MockRuntime::new(1_000);
+
+        io.put_json(
+            "alerts:instance:inst1",
+            serde_json::json!({
+                "instance_id": "inst1",
+                "alert_name": "Large Transfer",
+                "user_id": "u1",
+                "enabled": true,
+                "priority": "high",
+                "variable_values": {},
+                "notification_template": { "title": "T {{target.short}}", "body": "B" },
+                "action": {
+                    "notification_policy": "per_matched_target",
+                    "cooldown_secs": 0,
+                    "cooldown_key_template": "x",
+                    "dedupe_key_template": "{{run_id}}:{{target.key}}"
+                },
+                "escalation_policy": {
+                    "steps": [
+                        { "after_secs": 600, "channels": ["telegram"] },
+                        { "after_secs": 1800, "channels": ["webhook"] }
+                    ]
+                }
+            }),
+        );
+
+        let batch = AlertTriggeredBatchV1 {
+            schema_version: alert_triggered_batch_schema_version_v1(),
+            job_id: "job1".to_string(),
+            run_id: "run1".to_string(),
+            instance_id: "inst1".to_string(),
+            partition: alert_runtime_common::PartitionV1 {
+                network: "ETH".to_string(),
+                subnet: "mainnet".to_string(),
+                chain_id: 1,
+            },
+            schedule: None,
+            tx: None,
+            matches: vec![alert_runtime_common::AlertTriggeredMatchV1 {
+                target_key: "ETH:mainnet:0xabc".to_string(),
+                match_context: serde_json::json!({}),
+            }],
+        };
+        let bytes = serde_json::to_vec(&batch).unwrap();
+        handle_nats_message(&io, "alerts.triggered.ETH.mainnet", &bytes).unwrap();
+
+        let scheduled: Vec<serde_json::Value> = io
+            .published()
+            .into_iter()
+            .filter(|(subject, _)| subject == ALERT_SCHEDULE_ESCALATION_SUBJECT)
+            .map(|(_, v)| v)
+            .collect();
+        assert_eq!(scheduled.len(), 1);
+        assert_eq!(scheduled[0]["step"], 0);
+        assert_eq!(scheduled[0]["due_at"], "1970-01-01T00:26:40Z");
+        let notification_id = scheduled[0]["notification_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let due = |step: usize| {
+            serde_json::to_vec(&AlertEscalationDueV1 {
+                schema_version: alert_escalation_due_schema_version_v1(),
+                notification_id: notification_id.clone(),
+                instance_id: "inst1".to_string(),
+                user_id: "u1".to_string(),
+                step,
+                due_at: Utc.timestamp_opt(1_600, 0).unwrap(),
+                fired_at: Utc.timestamp_opt(1_600, 0).unwrap(),
+            })
+            .unwrap()
+        };
+
+        let before = io.published().len();
+        handle_nats_message(&io, "alerts.escalation.due.inst1", &due(0)).unwrap();
+        let fired = io.published()[before..].to_vec();
+        assert_eq!(fired.len(), 2);
+        assert_eq!(fired[0].0, "notifications.send.immediate.telegram");
+        assert_eq!(fired[0].1["notification_id"], notification_id.as_str());
+        assert!(fired[0].1["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("[Escalated] T 0xabc"));
+        assert_eq!(fired[1].0, ALERT_SCHEDULE_ESCALATION_SUBJECT);
+        assert_eq!(fired[1].1["step"], 1);
+
+        // A redelivered step is not sent twice
+        handle_nats_message(&io, "alerts.escalation.due.inst1", &due(0)).unwrap();
+        assert_eq!(io.published().len(), before + 2);
+
+        io.put_json(&ALERTS_ACK.key(&notification_id), serde_json::json!("u1"));
+        handle_nats_message(&io, "alerts.escalation.due.inst1", &due(1)).unwrap();
+        assert_eq!(io.published().len(), before + 2);
+    }
 }
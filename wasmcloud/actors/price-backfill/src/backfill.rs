@@ -0,0 +1,900 @@
+//! Price backfill plan and runner for `admin.prices.backfill`
+//!
+//! Imported or backfilled transactions reach the lake without `amount_usd` /
+//! `fee_usd`. A backfill finds the time range of those rows, makes sure
+//! `price_history` covers it (fetching the native asset's daily or hourly
+//! series once and upserting it), then pages through the rows by
+//! `(block_number, transaction_hash)` and upserts their USD values by
+//! `(chain_id, transaction_hash)`. Only one backfill may run per chain at a
+//! time, and pages are read with a pause between queries.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+
+use arrow::array::Array;
+use arrow::ipc::reader::StreamReader;
+use arrow::util::display::array_value_to_string;
+use chrono::{DateTime, Utc};
+use ducklake_common::types::{QueryRequest, SqlParam};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const BACKFILL_SUBJECT: &str = "admin.prices.backfill";
+pub const PROGRESS_SUBJECT: &str = "admin.prices.backfill.progress";
+
+pub const DEFAULT_PAGE_SIZE: u32 = 1_000;
+pub const MAX_PAGE_SIZE: u32 = 5_000;
+pub const DEFAULT_PAUSE_MS: u64 = 200;
+pub const MIN_PAUSE_MS: u64 = 50;
+pub const QUERY_TIMEOUT_MS: u32 = 30_000;
+
+/// Price series rows per `price_history` upsert message
+pub const PRICE_CHUNK_SIZE: usize = 500;
+
+pub const DEFAULT_PRICE_API_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// `price_history.asset` of the chain's native currency
+pub const NATIVE_ASSET: &str = "native";
+
+/// Transaction subtypes whose `value` is not the native currency
+const TOKEN_SUBTYPES: [&str; 6] = ["erc20", "erc721", "erc1155", "trc10", "trc20", "spl"];
+
+/// One DuckLake result row; NULL columns are absent
+pub type Row = HashMap<String, String>;
+
+/// Decode a DuckLake Arrow IPC response into rows of display strings
+pub fn decode_rows(bytes: &[u8]) -> Result<Vec<Row>, String> {
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)
+        .map_err(|e| format!("failed to decode arrow stream: {}", e))?;
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(|e| format!("arrow decode error: {}", e))?;
+        let schema = batch.schema();
+        for index in 0..batch.num_rows() {
+            let mut row = Row::new();
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                if column.is_null(index) {
+                    continue;
+                }
+                let value = array_value_to_string(column, index)
+                    .map_err(|e| format!("arrow value error: {}", e))?;
+                row.insert(field.name().clone(), value);
+            }
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+pub trait BackfillIO {
+    fn kv_exists(&self, key: &str) -> Result<bool, String>;
+    fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String>;
+    fn kv_delete(&self, key: &str) -> Result<(), String>;
+    /// Run a DuckLake query and return its rows
+    fn query(&self, subject: &str, request: &QueryRequest) -> Result<Vec<Row>, String>;
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String>;
+    /// GET `url` and return the response body
+    fn http_get(&self, url: &str) -> Result<Vec<u8>, String>;
+    fn sleep_ms(&self, ms: u64);
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Price bucket width
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceIntervalV1 {
+    #[default]
+    Daily,
+    Hourly,
+}
+
+impl PriceIntervalV1 {
+    /// `price_history.interval` value
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Daily => "1d",
+            Self::Hourly => "1h",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Self::Daily => 86_400,
+            Self::Hourly => 3_600,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`
+    pub fn bucket(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.seconds())
+    }
+}
+
+/// `admin.prices.backfill` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceBackfillRequestV1 {
+    pub network: String,
+    pub subnet: String,
+    #[serde(default)]
+    pub interval: PriceIntervalV1,
+    /// Only backfill transactions at or after this unix time
+    #[serde(default)]
+    pub from: Option<i64>,
+    /// Only backfill transactions before this unix time
+    #[serde(default)]
+    pub to: Option<i64>,
+    /// CoinGecko coin id of the native currency; defaults from `network`
+    #[serde(default)]
+    pub coin_id: Option<String>,
+    /// CoinGecko-compatible API base URL
+    #[serde(default)]
+    pub price_api_url: Option<String>,
+    #[serde(default)]
+    pub page_size: Option<u32>,
+    /// Pause between pages
+    #[serde(default)]
+    pub pause_ms: Option<u64>,
+    /// Count rows that would be updated without writing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl PriceBackfillRequestV1 {
+    /// DuckLake `chain_id` partition value, e.g. `ethereum_mainnet`
+    pub fn chain_key(&self) -> String {
+        format!("{}_{}", self.network, self.subnet)
+    }
+
+    fn page_size(&self) -> u32 {
+        self.page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    fn pause_ms(&self) -> u64 {
+        self.pause_ms.unwrap_or(DEFAULT_PAUSE_MS).max(MIN_PAUSE_MS)
+    }
+
+    /// `[from, to)` in microseconds, as bound to the range filters
+    fn range_us(&self) -> (i64, i64) {
+        let from = self.from.unwrap_or(0).max(0);
+        let to = self.to.unwrap_or(i64::MAX / 1_000_000);
+        (from * 1_000_000, to.saturating_mul(1_000_000))
+    }
+
+    fn coin_id(&self) -> Option<String> {
+        if let Some(coin_id) = &self.coin_id {
+            return Some(coin_id.clone());
+        }
+        let coin_id = match self.network.to_lowercase().as_str() {
+            "ethereum" | "arbitrum" | "optimism" | "base" | "zksync" | "linea" | "scroll" => {
+                "ethereum"
+            }
+            "polygon" => "polygon-ecosystem-token",
+            "bsc" => "binancecoin",
+            "avalanche" => "avalanche-2",
+            "bitcoin" => "bitcoin",
+            "solana" => "solana",
+            "tron" => "tron",
+            _ => return None,
+        };
+        Some(coin_id.to_string())
+    }
+
+    /// Decimals of the chain's native asset base unit
+    fn native_decimals(&self) -> i32 {
+        match self.network.to_lowercase().as_str() {
+            "bitcoin" => 8,
+            "solana" => 9,
+            "tron" => 6,
+            _ => 18,
+        }
+    }
+
+    fn subject(&self, table: &str, action: &str) -> String {
+        format!(
+            "ducklake.{}.{}.{}.{}",
+            table, self.network, self.subnet, action
+        )
+    }
+}
+
+/// Progress of a backfill, published on [`PROGRESS_SUBJECT`] after every page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceBackfillProgressV1 {
+    pub schema_version: String,
+    pub backfill_id: String,
+    pub pages: u32,
+    pub rows: u64,
+    pub rows_updated: u64,
+    /// Rows whose price bucket is missing from the series
+    pub rows_unpriced: u64,
+    pub done: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Reply to an `admin.prices.backfill` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceBackfillResultV1 {
+    pub schema_version: String,
+    pub backfill_id: String,
+    pub success: bool,
+    pub dry_run: bool,
+    pub interval: PriceIntervalV1,
+    /// Transactions missing a USD value when the backfill started
+    pub rows_missing: u64,
+    /// Buckets already stored in `price_history`
+    pub prices_stored: u64,
+    /// Buckets fetched from the price API and upserted into `price_history`
+    pub prices_fetched: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<PriceBackfillProgressV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn handle_backfill_message(io: &dyn BackfillIO, body: &[u8]) -> PriceBackfillResultV1 {
+    match serde_json::from_slice::<PriceBackfillRequestV1>(body) {
+        Ok(request) => run_backfill(io, &request),
+        Err(e) => PriceBackfillResultV1 {
+            schema_version: result_schema_version(),
+            backfill_id: String::new(),
+            success: false,
+            dry_run: false,
+            interval: PriceIntervalV1::default(),
+            rows_missing: 0,
+            prices_stored: 0,
+            prices_fetched: 0,
+            progress: None,
+            error: Some(format!("Invalid backfill request: {}", e)),
+        },
+    }
+}
+
+pub fn run_backfill(
+    io: &dyn BackfillIO,
+    request: &PriceBackfillRequestV1,
+) -> PriceBackfillResultV1 {
+    let chain_key = request.chain_key();
+    let backfill_id = format!("{}-{}", chain_key, io.now().timestamp_millis());
+    let mut result = PriceBackfillResultV1 {
+        schema_version: result_schema_version(),
+        backfill_id: backfill_id.clone(),
+        success: false,
+        dry_run: request.dry_run,
+        interval: request.interval,
+        rows_missing: 0,
+        prices_stored: 0,
+        prices_fetched: 0,
+        progress: None,
+        error: None,
+    };
+
+    let lock_key = retention_policy::PRICE_BACKFILL_LOCK
+        .key(&format!("{}:{}", request.network, request.subnet));
+    match io.kv_exists(&lock_key) {
+        Ok(false) => {}
+        Ok(true) => {
+            result.error = Some(format!("Price backfill already running for {}", chain_key));
+            return result;
+        }
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    }
+    if let Err(e) = io.kv_set(&lock_key, backfill_id.as_bytes()) {
+        result.error = Some(e);
+        return result;
+    }
+
+    if let Err(e) = backfill(io, request, &backfill_id, &mut result) {
+        result.error = Some(e);
+    }
+
+    if let Err(e) = io.kv_delete(&lock_key) {
+        result.error.get_or_insert(e);
+    }
+    result.success = result.error.is_none();
+    result
+}
+
+fn backfill(
+    io: &dyn BackfillIO,
+    request: &PriceBackfillRequestV1,
+    backfill_id: &str,
+    result: &mut PriceBackfillResultV1,
+) -> Result<(), String> {
+    let (from_us, to_us) = request.range_us();
+    let transactions = request.subject("transactions", "query");
+
+    let range = QueryRequest::new(
+        "SELECT CAST(COUNT(*) AS BIGINT) AS missing, \
+         CAST(epoch(MIN(block_timestamp)) AS BIGINT) AS first_ts, \
+         CAST(epoch(MAX(block_timestamp)) AS BIGINT) AS last_ts \
+         FROM transactions \
+         WHERE chain_id = ? AND (amount_usd IS NULL OR fee_usd IS NULL) \
+         AND block_timestamp >= make_timestamp(?) AND block_timestamp < make_timestamp(?)",
+    )
+    .with_timeout(QUERY_TIMEOUT_MS / 1000)
+    .with_parameters(vec![
+        SqlParam::String(request.chain_key()),
+        SqlParam::Int64(from_us),
+        SqlParam::Int64(to_us),
+    ]);
+    let range = io.query(&transactions, &range)?;
+    let row = range.first();
+    let field = |name: &str| row.and_then(|row| row.get(name)?.parse::<i64>().ok());
+    result.rows_missing = field("missing").unwrap_or(0).max(0) as u64;
+    let (Some(first_ts), Some(last_ts)) = (field("first_ts"), field("last_ts")) else {
+        return Ok(());
+    };
+    if result.rows_missing == 0 {
+        return Ok(());
+    }
+
+    let first_bucket = request.interval.bucket(first_ts);
+    let last_bucket = request.interval.bucket(last_ts);
+    let mut prices = load_prices(io, request, first_bucket, last_bucket)?;
+    result.prices_stored = prices.len() as u64;
+
+    let expected = (last_bucket - first_bucket) / request.interval.seconds() + 1;
+    if (prices.len() as i64) < expected {
+        let fetched = fetch_prices(io, request, first_bucket, last_bucket)?;
+        let new_prices: BTreeMap<i64, f64> = fetched
+            .into_iter()
+            .filter(|(bucket, _)| !prices.contains_key(bucket))
+            .collect();
+        if !request.dry_run {
+            store_prices(io, request, &new_prices)?;
+        }
+        result.prices_fetched = new_prices.len() as u64;
+        prices.extend(new_prices);
+    }
+
+    result.progress = Some(update_transactions(io, request, backfill_id, &prices));
+    match result.progress.as_ref().and_then(|p| p.error.clone()) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Stored buckets of the native series in `[first_bucket, last_bucket]`
+fn load_prices(
+    io: &dyn BackfillIO,
+    request: &PriceBackfillRequestV1,
+    first_bucket: i64,
+    last_bucket: i64,
+) -> Result<BTreeMap<i64, f64>, String> {
+    let query = QueryRequest::new(
+        "SELECT CAST(epoch(price_timestamp) AS BIGINT) AS price_timestamp, price_usd \
+         FROM price_history \
+         WHERE chain_id = ? AND asset = ? AND \"interval\" = ? \
+         AND price_timestamp >= make_timestamp(?) AND price_timestamp <= make_timestamp(?) \
+         ORDER BY price_timestamp",
+    )
+    .with_timeout(QUERY_TIMEOUT_MS / 1000)
+    .with_parameters(vec![
+        SqlParam::String(request.chain_key()),
+        SqlParam::String(NATIVE_ASSET.to_string()),
+        SqlParam::String(request.interval.label().to_string()),
+        SqlParam::Int64(first_bucket * 1_000_000),
+        SqlParam::Int64(last_bucket * 1_000_000),
+    ]);
+    let rows = io.query(&request.subject("price_history", "query"), &query)?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let bucket = row.get("price_timestamp")?.parse::<i64>().ok()?;
+            let price = row.get("price_usd")?.parse::<f64>().ok()?;
+            Some((bucket, price))
+        })
+        .collect())
+}
+
+/// Fetch the native series from the price API, bucketed to the interval
+fn fetch_prices(
+    io: &dyn BackfillIO,
+    request: &PriceBackfillRequestV1,
+    first_bucket: i64,
+    last_bucket: i64,
+) -> Result<BTreeMap<i64, f64>, String> {
+    let coin_id = request.coin_id().ok_or_else(|| {
+        format!(
+            "No price source for {}; set coin_id in the request",
+            request.network
+        )
+    })?;
+    let url = format!(
+        "{}/coins/{}/market_chart/range?vs_currency=usd&from={}&to={}",
+        request
+            .price_api_url
+            .as_deref()
+            .unwrap_or(DEFAULT_PRICE_API_URL)
+            .trim_end_matches('/'),
+        coin_id,
+        first_bucket,
+        last_bucket + request.interval.seconds()
+    );
+    let body = io.http_get(&url)?;
+    parse_market_chart(&body, request.interval)
+}
+
+/// First price of each bucket from a `market_chart` response
+/// (`{"prices": [[unix_ms, price], ...]}`)
+pub fn parse_market_chart(
+    body: &[u8],
+    interval: PriceIntervalV1,
+) -> Result<BTreeMap<i64, f64>, String> {
+    let value: Value =
+        serde_json::from_slice(body).map_err(|e| format!("invalid price response: {}", e))?;
+    let points = value
+        .get("prices")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "price response has no prices".to_string())?;
+
+    let mut buckets = BTreeMap::new();
+    for point in points {
+        let (Some(ms), Some(price)) = (
+            point.get(0).and_then(Value::as_f64),
+            point.get(1).and_then(Value::as_f64),
+        ) else {
+            continue;
+        };
+        if price > 0.0 {
+            buckets
+                .entry(interval.bucket(ms as i64 / 1_000))
+                .or_insert(price);
+        }
+    }
+    Ok(buckets)
+}
+
+fn store_prices(
+    io: &dyn BackfillIO,
+    request: &PriceBackfillRequestV1,
+    prices: &BTreeMap<i64, f64>,
+) -> Result<(), String> {
+    let subject = request.subject("price_history", "upsert");
+    let source = format!("coingecko:{}", request.coin_id().unwrap_or_default());
+    let ingested_at = io.now().format("%Y-%m-%d %H:%M:%S%.6f").to_string();
+    let records: Vec<Value> = prices
+        .iter()
+        .filter_map(|(bucket, price)| {
+            let timestamp = DateTime::from_timestamp(*bucket, 0)?;
+            Some(json!({
+                "chain_id": request.chain_key(),
+                "price_date": timestamp.format("%Y-%m-%d").to_string(),
+                "asset": NATIVE_ASSET,
+                "interval": request.interval.label(),
+                "price_timestamp": timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "price_usd": price,
+                "source": source,
+                "ingested_at": ingested_at,
+            }))
+        })
+        .collect();
+    for chunk in records.chunks(PRICE_CHUNK_SIZE) {
+        let body = serde_json::to_vec(chunk)
+            .map_err(|e| format!("failed to serialize price records: {}", e))?;
+        io.publish(&subject, body)?;
+    }
+    Ok(())
+}
+
+fn update_transactions(
+    io: &dyn BackfillIO,
+    request: &PriceBackfillRequestV1,
+    backfill_id: &str,
+    prices: &BTreeMap<i64, f64>,
+) -> PriceBackfillProgressV1 {
+    let mut progress = PriceBackfillProgressV1 {
+        schema_version: progress_schema_version(),
+        backfill_id: backfill_id.to_string(),
+        pages: 0,
+        rows: 0,
+        rows_updated: 0,
+        rows_unpriced: 0,
+        done: false,
+        error: None,
+        updated_at: io.now(),
+    };
+
+    let query_subject = request.subject("transactions", "query");
+    let upsert_subject = request.subject("transactions", "upsert");
+    let (from_us, to_us) = request.range_us();
+    let page_size = request.page_size();
+    // Keyset cursor: rows are upserted asynchronously, so OFFSET would skip
+    // rows as earlier pages drop out of the filter
+    let mut cursor: (i64, String) = (-1, String::new());
+
+    loop {
+        if progress.pages > 0 {
+            io.sleep_ms(request.pause_ms());
+        }
+
+        let query = QueryRequest::new(
+            "SELECT transaction_hash, CAST(block_number AS BIGINT) AS block_number, \
+             CAST(epoch(block_timestamp) AS BIGINT) AS block_timestamp, transaction_subtype, \
+             amount_native, CAST(value AS VARCHAR) AS value, \
+             CAST(transaction_fee AS VARCHAR) AS transaction_fee, amount_usd, fee_usd \
+             FROM transactions \
+             WHERE chain_id = ? AND (amount_usd IS NULL OR fee_usd IS NULL) \
+             AND block_timestamp >= make_timestamp(?) AND block_timestamp < make_timestamp(?) \
+             AND (block_number > ? OR (block_number = ? AND transaction_hash > ?)) \
+             ORDER BY block_number, transaction_hash LIMIT ?",
+        )
+        .with_timeout(QUERY_TIMEOUT_MS / 1000)
+        .with_parameters(vec![
+            SqlParam::String(request.chain_key()),
+            SqlParam::Int64(from_us),
+            SqlParam::Int64(to_us),
+            SqlParam::Int64(cursor.0),
+            SqlParam::Int64(cursor.0),
+            SqlParam::String(cursor.1.clone()),
+            SqlParam::Int64(page_size as i64),
+        ]);
+        let rows = match io.query(&query_subject, &query) {
+            Ok(rows) => rows,
+            Err(e) => {
+                progress.error = Some(e);
+                break;
+            }
+        };
+
+        progress.pages += 1;
+        progress.rows += rows.len() as u64;
+        let mut updates = Vec::new();
+        for row in &rows {
+            let Some(bucket) = row
+                .get("block_timestamp")
+                .and_then(|ts| ts.parse::<i64>().ok())
+                .map(|ts| request.interval.bucket(ts))
+            else {
+                continue;
+            };
+            let Some(price) = prices.get(&bucket) else {
+                progress.rows_unpriced += 1;
+                continue;
+            };
+            if let Some(update) = usd_update(request, row, *price) {
+                updates.push(update);
+            }
+        }
+        if let Some(last) = rows.last() {
+            cursor = (
+                last.get("block_number")
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(cursor.0),
+                last.get("transaction_hash").cloned().unwrap_or_default(),
+            );
+        }
+
+        if !updates.is_empty() && !request.dry_run {
+            let published = serde_json::to_vec(&updates)
+                .map_err(|e| format!("failed to serialize USD updates: {}", e))
+                .and_then(|body| io.publish(&upsert_subject, body));
+            if let Err(e) = published {
+                progress.error = Some(e);
+                break;
+            }
+        }
+        progress.rows_updated += updates.len() as u64;
+
+        progress.done = rows.len() < page_size as usize;
+        progress.updated_at = io.now();
+        report(io, &progress);
+        if progress.done {
+            return progress;
+        }
+    }
+
+    progress.updated_at = io.now();
+    report(io, &progress);
+    progress
+}
+
+/// Upsert record filling the row's missing USD values at `price_usd`;
+/// `None` when nothing can be filled. Token transfers only get `fee_usd`,
+/// since their `value` is not the native currency.
+pub fn usd_update(request: &PriceBackfillRequestV1, row: &Row, price_usd: f64) -> Option<Value> {
+    let scale = 10f64.powi(request.native_decimals());
+    let parse = |name: &str| row.get(name).and_then(|v| v.trim().parse::<f64>().ok());
+    let is_token = row
+        .get("transaction_subtype")
+        .is_some_and(|subtype| TOKEN_SUBTYPES.contains(&subtype.to_lowercase().as_str()));
+
+    let mut update = serde_json::Map::new();
+    if !row.contains_key("amount_usd") && !is_token {
+        let amount = parse("amount_native").or_else(|| parse("value").map(|wei| wei / scale));
+        if let Some(amount) = amount {
+            update.insert("amount_usd".to_string(), json!(amount * price_usd));
+        }
+    }
+    if !row.contains_key("fee_usd") {
+        if let Some(fee) = parse("transaction_fee") {
+            update.insert("fee_usd".to_string(), json!(fee / scale * price_usd));
+        }
+    }
+    if update.is_empty() {
+        return None;
+    }
+
+    update.insert("chain_id".to_string(), json!(request.chain_key()));
+    update.insert(
+        "transaction_hash".to_string(),
+        json!(row.get("transaction_hash")?),
+    );
+    Some(Value::Object(update))
+}
+
+fn report(io: &dyn BackfillIO, progress: &PriceBackfillProgressV1) {
+    if let Ok(body) = serde_json::to_vec(progress) {
+        // Progress is best-effort; the final result is always replied
+        let _ = io.publish(PROGRESS_SUBJECT, body);
+    }
+}
+
+pub fn progress_schema_version() -> String {
+    "price_backfill_progress_v1".to_string()
+}
+
+pub fn result_schema_version() -> String {
+    "price_backfill_result_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeIO {
+        kv: RefCell<HashMap<String, Vec<u8>>>,
+        transactions: Vec<Row>,
+        stored_prices: Vec<Row>,
+        price_response: Vec<u8>,
+        http_calls: RefCell<Vec<String>>,
+        published: RefCell<Vec<(String, Value)>>,
+    }
+
+    impl BackfillIO for FakeIO {
+        fn kv_exists(&self, key: &str) -> Result<bool, String> {
+            Ok(self.kv.borrow().contains_key(key))
+        }
+
+        fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+            self.kv.borrow_mut().insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+
+        fn kv_delete(&self, key: &str) -> Result<(), String> {
+            self.kv.borrow_mut().remove(key);
+            Ok(())
+        }
+
+        fn query(&self, subject: &str, request: &QueryRequest) -> Result<Vec<Row>, String> {
+            let params = request.parameters.clone().unwrap_or_default();
+            if subject.starts_with("ducklake.price_history.") {
+                return Ok(self.stored_prices.clone());
+            }
+            if request.query.contains("COUNT(*)") {
+                let timestamps = self
+                    .transactions
+                    .iter()
+                    .map(|r| r["block_timestamp"].as_str());
+                let missing = self.transactions.len().to_string();
+                return Ok(vec![row(&[
+                    ("missing", missing.as_str()),
+                    ("first_ts", timestamps.clone().min().unwrap_or_default()),
+                    ("last_ts", timestamps.max().unwrap_or_default()),
+                ])]);
+            }
+            let (SqlParam::Int64(block), SqlParam::String(hash), SqlParam::Int64(limit)) =
+                (&params[3], &params[5], &params[6])
+            else {
+                return Err("bad paging params".to_string());
+            };
+            Ok(self
+                .transactions
+                .iter()
+                .filter(|r| {
+                    let n: i64 = r["block_number"].parse().unwrap();
+                    n > *block || (n == *block && r["transaction_hash"] > *hash)
+                })
+                .take(*limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+            self.published
+                .borrow_mut()
+                .push((subject.to_string(), serde_json::from_slice(&body).unwrap()));
+            Ok(())
+        }
+
+        fn http_get(&self, url: &str) -> Result<Vec<u8>, String> {
+            self.http_calls.borrow_mut().push(url.to_string());
+            Ok(self.price_response.clone())
+        }
+
+        fn sleep_ms(&self, _ms: u64) {}
+
+        fn now(&self) -> DateTime<Utc> {
+            DateTime::from_timestamp(1_704_067_200, 0).unwrap()
+        }
+    }
+
+    fn row(pairs: &[(&str, &str)]) -> Row {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn request() -> PriceBackfillRequestV1 {
+        serde_json::from_value(json!({
+            "network": "ethereum",
+            "subnet": "mainnet",
+            "page_size": 2,
+        }))
+        .unwrap()
+    }
+
+    // 2024-01-01 and 2024-01-02 00:00 UTC
+    const DAY_1: i64 = 1_704_067_200;
+    const DAY_2: i64 = DAY_1 + 86_400;
+
+    fn transactions() -> Vec<Row> {
+        let day_1 = (DAY_1 + 60).to_string();
+        let day_2 = (DAY_2 + 3_600).to_string();
+        vec![
+            row(&[
+                ("transaction_hash", "0xa"),
+                ("block_number", "100"),
+                ("block_timestamp", day_1.as_str()),
+                ("amount_native", "1.5"),
+                ("transaction_fee", "2000000000000000"),
+            ]),
+            row(&[
+                ("transaction_hash", "0xb"),
+                ("block_number", "100"),
+                ("block_timestamp", day_1.as_str()),
+                ("transaction_subtype", "erc20"),
+                ("value", "5000000"),
+                ("transaction_fee", "1000000000000000"),
+                ("amount_usd", "5.0"),
+            ]),
+            row(&[
+                ("transaction_hash", "0xc"),
+                ("block_number", "200"),
+                ("block_timestamp", day_2.as_str()),
+                ("value", "1000000000000000000"),
+                ("fee_usd", "0.5"),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_backfill_fetches_series_once_and_upserts_usd() {
+        let io = FakeIO {
+            transactions: transactions(),
+            price_response: json!({"prices": [
+                [DAY_1 * 1_000, 2_000.0],
+                [(DAY_1 + 3_600) * 1_000, 2_100.0],
+                [DAY_2 * 1_000, 2_500.0],
+            ]})
+            .to_string()
+            .into_bytes(),
+            ..Default::default()
+        };
+
+        let result = run_backfill(&io, &request());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.rows_missing, 3);
+        assert_eq!(result.prices_fetched, 2);
+        assert_eq!(
+            *io.http_calls.borrow(),
+            vec![format!(
+                "{}/coins/ethereum/market_chart/range?vs_currency=usd&from={}&to={}",
+                DEFAULT_PRICE_API_URL,
+                DAY_1,
+                DAY_2 + 86_400
+            )]
+        );
+
+        let published = io.published.borrow();
+        let (subject, prices) = &published[0];
+        assert_eq!(subject, "ducklake.price_history.ethereum.mainnet.upsert");
+        assert_eq!(prices[0]["price_timestamp"], "2024-01-01 00:00:00");
+        assert_eq!(prices[0]["price_usd"], 2_000.0);
+        assert_eq!(prices[1]["interval"], "1d");
+
+        let updates: Vec<&Value> = published
+            .iter()
+            .filter(|(s, _)| s == "ducklake.transactions.ethereum.mainnet.upsert")
+            .flat_map(|(_, body)| body.as_array().unwrap())
+            .collect();
+        assert_eq!(
+            updates,
+            vec![
+                &json!({"chain_id": "ethereum_mainnet", "transaction_hash": "0xa",
+                        "amount_usd": 3_000.0, "fee_usd": 4.0}),
+                // Token transfer: only the fee is native
+                &json!({"chain_id": "ethereum_mainnet", "transaction_hash": "0xb",
+                        "fee_usd": 2.0}),
+                &json!({"chain_id": "ethereum_mainnet", "transaction_hash": "0xc",
+                        "amount_usd": 2_500.0}),
+            ]
+        );
+        let progress = result.progress.unwrap();
+        assert_eq!((progress.pages, progress.rows_updated), (2, 3));
+        assert!(!io
+            .kv
+            .borrow()
+            .contains_key("price_backfill:lock:ethereum:mainnet"));
+    }
+
+    #[test]
+    fn test_stored_series_is_reused_and_missing_buckets_counted() {
+        let (day_1, day_2) = (DAY_1.to_string(), DAY_2.to_string());
+        let io = FakeIO {
+            transactions: transactions(),
+            stored_prices: vec![
+                row(&[("price_timestamp", day_1.as_str()), ("price_usd", "2000")]),
+                row(&[("price_timestamp", day_2.as_str()), ("price_usd", "2500")]),
+            ],
+            ..Default::default()
+        };
+        let result = run_backfill(&io, &request());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!((result.prices_stored, result.prices_fetched), (2, 0));
+        assert!(io.http_calls.borrow().is_empty());
+
+        // Hourly buckets the stored daily series does not cover stay unpriced
+        let mut hourly = request();
+        hourly.interval = PriceIntervalV1::Hourly;
+        hourly.dry_run = true;
+        let io = FakeIO {
+            transactions: transactions(),
+            price_response: json!({"prices": [[DAY_1 * 1_000, 2_000.0]]})
+                .to_string()
+                .into_bytes(),
+            ..Default::default()
+        };
+        let result = run_backfill(&io, &hourly);
+        assert!(result.success, "{:?}", result.error);
+        let progress = result.progress.unwrap();
+        assert_eq!((progress.rows_updated, progress.rows_unpriced), (2, 1));
+        // Dry runs only report progress
+        assert!(io
+            .published
+            .borrow()
+            .iter()
+            .all(|(subject, _)| subject == PROGRESS_SUBJECT));
+    }
+
+    #[test]
+    fn test_backfill_rejected_while_locked() {
+        let io = FakeIO::default();
+        io.kv.borrow_mut().insert(
+            "price_backfill:lock:ethereum:mainnet".to_string(),
+            b"x".to_vec(),
+        );
+        let result = run_backfill(&io, &request());
+        assert!(!result.success);
+        assert!(io.published.borrow().is_empty());
+
+        let mut unknown = request();
+        unknown.network = "somechain".to_string();
+        let io = FakeIO {
+            transactions: transactions(),
+            ..Default::default()
+        };
+        let result = run_backfill(&io, &unknown);
+        assert!(result.error.unwrap().contains("set coin_id"));
+    }
+}
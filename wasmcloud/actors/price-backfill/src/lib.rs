@@ -0,0 +1,223 @@
+//! Price Backfill Actor
+//!
+//! Handles `admin.prices.backfill` requests by filling `amount_usd` and
+//! `fee_usd` on transactions that were imported or backfilled without them:
+//! - the native asset's daily or hourly USD series is fetched once from a
+//!   CoinGecko-compatible API and stored in DuckLake `price_history`
+//! - transactions missing a USD value are read in pages and priced at the
+//!   bucket containing their block time
+//! - the values are written back in place through the lake writer's upsert
+//!   path (`ducklake.transactions.{network}.{subnet}.upsert`)
+//!
+//! Progress is published on `admin.prices.backfill.progress`, and the final
+//! `PriceBackfillResultV1` is sent to the request's reply subject.
+
+mod backfill;
+
+pub use backfill::{
+    decode_rows, handle_backfill_message, parse_market_chart, run_backfill, usd_update, BackfillIO,
+    PriceBackfillProgressV1, PriceBackfillRequestV1, PriceBackfillResultV1, PriceIntervalV1, Row,
+    BACKFILL_SUBJECT, PROGRESS_SUBJECT,
+};
+
+#[cfg(target_arch = "wasm32")]
+wit_bindgen::generate!({ generate_all });
+
+#[cfg(target_arch = "wasm32")]
+use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+
+#[cfg(target_arch = "wasm32")]
+use wasmcloud::messaging::types as nats_types;
+
+#[cfg(target_arch = "wasm32")]
+use wasi::keyvalue::store;
+
+#[cfg(target_arch = "wasm32")]
+subject_acl::component!("price-backfill");
+
+#[cfg(target_arch = "wasm32")]
+struct Component;
+
+#[cfg(target_arch = "wasm32")]
+export!(Component);
+
+#[cfg(target_arch = "wasm32")]
+struct WasmRuntime;
+
+#[cfg(target_arch = "wasm32")]
+impl WasmRuntime {
+    fn bucket(&self) -> Result<store::Bucket, String> {
+        store::open("default").map_err(|e| format!("failed to open keyvalue bucket: {:?}", e))
+    }
+
+    fn parse_url(url: &str) -> Result<(wasi::http::types::Scheme, String, String), String> {
+        let (scheme_str, rest) = url
+            .split_once("://")
+            .ok_or_else(|| format!("invalid URL format: {}", url))?;
+
+        let scheme = match scheme_str {
+            "http" => wasi::http::types::Scheme::Http,
+            "https" => wasi::http::types::Scheme::Https,
+            _ => return Err(format!("unsupported scheme: {}", scheme_str)),
+        };
+
+        let (authority, path) = match rest.split_once('/') {
+            Some((authority, path)) => (authority.to_string(), format!("/{}", path)),
+            None => (rest.to_string(), "/".to_string()),
+        };
+
+        Ok((scheme, authority, path))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl BackfillIO for WasmRuntime {
+    fn kv_exists(&self, key: &str) -> Result<bool, String> {
+        self.bucket()?
+            .exists(key)
+            .map_err(|e| format!("keyvalue exists failed: {:?}", e))
+    }
+
+    fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        self.bucket()?
+            .set(key, value)
+            .map_err(|e| format!("keyvalue set failed: {:?}", e))
+    }
+
+    fn kv_delete(&self, key: &str) -> Result<(), String> {
+        self.bucket()?
+            .delete(key)
+            .map_err(|e| format!("keyvalue delete failed: {:?}", e))
+    }
+
+    fn query(
+        &self,
+        subject: &str,
+        request: &ducklake_common::types::QueryRequest,
+    ) -> Result<Vec<Row>, String> {
+        let body =
+            serde_json::to_vec(request).map_err(|e| format!("failed to serialize query: {}", e))?;
+        let resp = wasmcloud::messaging::consumer::request(
+            &subject_registry::prefixed(subject),
+            &body,
+            backfill::QUERY_TIMEOUT_MS,
+        )
+        .map_err(|e| format!("ducklake query failed: {:?}", e))?;
+        decode_rows(&resp.body)
+    }
+
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
+        wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+            subject: subject_registry::prefixed(subject),
+            body,
+            reply_to: None,
+        })
+        .map_err(|e| format!("nats publish failed: {:?}", e))
+    }
+
+    fn http_get(&self, url: &str) -> Result<Vec<u8>, String> {
+        let (scheme, authority, path) = Self::parse_url(url)?;
+
+        let headers = wasi::http::types::Fields::new();
+        headers
+            .set(
+                &"accept".to_string(),
+                &vec!["application/json".as_bytes().to_vec()],
+            )
+            .map_err(|e| format!("failed to set accept header: {:?}", e))?;
+        let request = wasi::http::types::OutgoingRequest::new(headers);
+        request
+            .set_method(&wasi::http::types::Method::Get)
+            .map_err(|e| format!("failed to set method: {:?}", e))?;
+        request
+            .set_scheme(Some(&scheme))
+            .map_err(|e| format!("failed to set scheme: {:?}", e))?;
+        request
+            .set_authority(Some(&authority))
+            .map_err(|e| format!("failed to set authority: {:?}", e))?;
+        request
+            .set_path_with_query(Some(&path))
+            .map_err(|e| format!("failed to set path: {:?}", e))?;
+
+        let body = request
+            .body()
+            .map_err(|_| "failed to get request body".to_string())?;
+        wasi::http::types::OutgoingBody::finish(body, None)
+            .map_err(|_| "failed to finish request body".to_string())?;
+
+        let future_response = wasi::http::outgoing_handler::handle(request, None)
+            .map_err(|e| format!("price API request failed: {:?}", e))?;
+        let pollable = future_response.subscribe();
+        wasi::io::poll::poll(&[&pollable]);
+
+        let response = future_response
+            .get()
+            .ok_or_else(|| "price API response not ready".to_string())?
+            .map_err(|e| format!("price API request failed (outer): {:?}", e))?
+            .map_err(|e| format!("price API request failed (inner): {:?}", e))?;
+
+        let status = response.status();
+        if !(200..300).contains(&status) {
+            return Err(format!("price API returned status {}", status));
+        }
+
+        let response_body = response
+            .consume()
+            .map_err(|_| "failed to consume price API response".to_string())?;
+        let stream = response_body
+            .stream()
+            .map_err(|_| "failed to get price API response stream".to_string())?;
+        let mut bytes = Vec::new();
+        loop {
+            match stream.blocking_read(64 * 1024) {
+                Ok(chunk) if chunk.is_empty() => break,
+                Ok(chunk) => bytes.extend_from_slice(&chunk),
+                Err(wasi::io::streams::StreamError::Closed) => break,
+                Err(e) => return Err(format!("failed to read price API response: {:?}", e)),
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn sleep_ms(&self, ms: u64) {
+        wasi::clocks::monotonic_clock::subscribe_duration(ms * 1_000_000).block();
+    }
+
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl MessageHandler for Component {
+    fn handle_message(msg: nats_types::BrokerMessage) -> Result<(), String> {
+        if subject_registry::unprefixed(&msg.subject) != Some(BACKFILL_SUBJECT) {
+            return Ok(());
+        }
+
+        let io = WasmRuntime;
+        let result = handle_backfill_message(&io, &msg.body);
+        if let Some(error) = &result.error {
+            eprintln!("[PRICE-BACKFILL] ❌ {}: {}", result.backfill_id, error);
+        }
+        if let Some(reply_to) = msg.reply_to {
+            let body = serde_json::to_vec(&result)
+                .map_err(|e| format!("failed to serialize backfill result: {}", e))?;
+            wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: reply_to,
+                body,
+                reply_to: None,
+            })
+            .map_err(|e| format!("failed to send backfill reply: {:?}", e))?;
+        }
+        Ok(())
+    }
+}
@@ -102,9 +102,18 @@ export!(Component);
 impl MessageHandler for Component {
     /// Handle incoming NATS messages containing blockchain newheads
     fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+
         // Only process newheads messages for SVM chains
-        if !msg.subject.starts_with("newheads.") || !msg.subject.ends_with(".svm") {
-            return Ok(());
+        match subject_registry::unprefixed(&msg.subject) {
+            Some(subject) if subject.starts_with("newheads.") && subject.ends_with(".svm") => {}
+            _ => return Ok(()),
         }
 
         // Parse the block header from the message
@@ -354,7 +363,7 @@ impl Component {
                 .get("meta")
                 .map(|m| Self::parse_meta(m))
                 .transpose()?,
-            processed_at: chrono::Utc::now().to_rfc3339(),
+            processed_at: replay_clock::now().to_rfc3339(),
             processor_id: "svm-raw-transactions-actor".to_string(),
         })
     }
@@ -479,7 +488,7 @@ impl Component {
 
         // Publish to transactions.raw.svm topic
         let msg = types::BrokerMessage {
-            subject: "transactions.raw.svm".to_string(),
+            subject: subject_registry::prefixed("transactions.raw.svm"),
             body: tx_payload,
             reply_to: None,
         };
@@ -0,0 +1,136 @@
+//! State Rebuild Actor
+//!
+//! Handles `admin.state.rebuild` requests by reconstructing Redis state from
+//! DuckLake tables after a Redis loss or flush:
+//! - dApp interaction counters (`dapp_usage:*`) from `dapp_usage`
+//! - the Tron token registry (`tokens:tron:*`) from `token_transfers`
+//! - latest balances (`balance:*`) from `wallet_balances`
+//! - last activity per address (`activity:last:*`) from `address_transactions`
+//!
+//! Tables are read in pages with a pause between queries, progress is published
+//! on `admin.state.rebuild.progress`, and the final `StateRebuildResultV1` is
+//! sent to the request's reply subject.
+
+mod rebuild;
+
+pub use rebuild::{
+    decode_rows, handle_rebuild_message, run_rebuild, RebuildIO, RebuildTargetV1, Row,
+    StateRebuildProgressV1, StateRebuildRequestV1, StateRebuildResultV1, PROGRESS_SUBJECT,
+    REBUILD_SUBJECT,
+};
+
+#[cfg(target_arch = "wasm32")]
+wit_bindgen::generate!({ generate_all });
+
+#[cfg(target_arch = "wasm32")]
+use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+
+#[cfg(target_arch = "wasm32")]
+use wasmcloud::messaging::types as nats_types;
+
+#[cfg(target_arch = "wasm32")]
+use wasi::keyvalue::store;
+
+#[cfg(target_arch = "wasm32")]
+subject_acl::component!("state-rebuild");
+
+#[cfg(target_arch = "wasm32")]
+struct Component;
+
+#[cfg(target_arch = "wasm32")]
+export!(Component);
+
+#[cfg(target_arch = "wasm32")]
+struct WasmRuntime;
+
+#[cfg(target_arch = "wasm32")]
+impl WasmRuntime {
+    fn bucket(&self) -> Result<store::Bucket, String> {
+        store::open("default").map_err(|e| format!("failed to open keyvalue bucket: {:?}", e))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl RebuildIO for WasmRuntime {
+    fn kv_exists(&self, key: &str) -> Result<bool, String> {
+        self.bucket()?
+            .exists(key)
+            .map_err(|e| format!("keyvalue exists failed: {:?}", e))
+    }
+
+    fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        self.bucket()?
+            .set(key, value)
+            .map_err(|e| format!("keyvalue set failed: {:?}", e))
+    }
+
+    fn kv_delete(&self, key: &str) -> Result<(), String> {
+        self.bucket()?
+            .delete(key)
+            .map_err(|e| format!("keyvalue delete failed: {:?}", e))
+    }
+
+    fn query(
+        &self,
+        subject: &str,
+        request: &ducklake_common::types::QueryRequest,
+    ) -> Result<Vec<Row>, String> {
+        let body =
+            serde_json::to_vec(request).map_err(|e| format!("failed to serialize query: {}", e))?;
+        let resp = wasmcloud::messaging::consumer::request(
+            &subject_registry::prefixed(subject),
+            &body,
+            rebuild::QUERY_TIMEOUT_MS,
+        )
+        .map_err(|e| format!("ducklake query failed: {:?}", e))?;
+        decode_rows(&resp.body)
+    }
+
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
+        wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+            subject: subject_registry::prefixed(subject),
+            body,
+            reply_to: None,
+        })
+        .map_err(|e| format!("nats publish failed: {:?}", e))
+    }
+
+    fn sleep_ms(&self, ms: u64) {
+        wasi::clocks::monotonic_clock::subscribe_duration(ms * 1_000_000).block();
+    }
+
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl MessageHandler for Component {
+    fn handle_message(msg: nats_types::BrokerMessage) -> Result<(), String> {
+        if subject_registry::unprefixed(&msg.subject) != Some(REBUILD_SUBJECT) {
+            return Ok(());
+        }
+
+        let io = WasmRuntime;
+        let result = handle_rebuild_message(&io, &msg.body);
+        if let Some(reply_to) = msg.reply_to {
+            let body = serde_json::to_vec(&result)
+                .map_err(|e| format!("failed to serialize rebuild result: {}", e))?;
+            wasmcloud::messaging::consumer::publish(&nats_types::BrokerMessage {
+                subject: reply_to,
+                body,
+                reply_to: None,
+            })
+            .map_err(|e| format!("failed to send rebuild reply: {:?}", e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,667 @@
+//! Rebuild plan and runner for `admin.state.rebuild`
+//!
+//! Each [`RebuildTargetV1`] is one family of Redis keys together with the
+//! DuckLake aggregate that reproduces it. Targets are read page by page
+//! (`LIMIT`/`OFFSET`) with a pause between pages, and only one rebuild may run
+//! per chain at a time, so a recovery cannot stampede the lake.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use arrow::array::Array;
+use arrow::ipc::reader::StreamReader;
+use arrow::util::display::array_value_to_string;
+use chrono::{DateTime, Utc};
+use ducklake_common::types::{QueryRequest, SqlParam};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub const REBUILD_SUBJECT: &str = "admin.state.rebuild";
+pub const PROGRESS_SUBJECT: &str = "admin.state.rebuild.progress";
+
+pub const DEFAULT_PAGE_SIZE: u32 = 1_000;
+pub const MAX_PAGE_SIZE: u32 = 5_000;
+pub const DEFAULT_PAUSE_MS: u64 = 200;
+pub const MIN_PAUSE_MS: u64 = 50;
+pub const QUERY_TIMEOUT_MS: u32 = 30_000;
+
+/// One DuckLake result row; NULL columns are absent
+pub type Row = HashMap<String, String>;
+
+/// Decode a DuckLake Arrow IPC response into rows of display strings
+pub fn decode_rows(bytes: &[u8]) -> Result<Vec<Row>, String> {
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)
+        .map_err(|e| format!("failed to decode arrow stream: {}", e))?;
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(|e| format!("arrow decode error: {}", e))?;
+        let schema = batch.schema();
+        for index in 0..batch.num_rows() {
+            let mut row = Row::new();
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                if column.is_null(index) {
+                    continue;
+                }
+                let value = array_value_to_string(column, index)
+                    .map_err(|e| format!("arrow value error: {}", e))?;
+                row.insert(field.name().clone(), value);
+            }
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+pub trait RebuildIO {
+    fn kv_exists(&self, key: &str) -> Result<bool, String>;
+    fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String>;
+    fn kv_delete(&self, key: &str) -> Result<(), String>;
+    /// Run a DuckLake query and return its rows
+    fn query(&self, subject: &str, request: &QueryRequest) -> Result<Vec<Row>, String>;
+    fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String>;
+    fn sleep_ms(&self, ms: u64);
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebuildTargetV1 {
+    /// `dapp_usage:{chain_id}:{address}:{dapp}` from `dapp_usage`
+    InteractionCounters,
+    /// `tokens:tron:{subnet}:{address}` from `token_transfers` (Tron only)
+    TokenRegistry,
+    /// `balance:{network}:{subnet}:{wallet}:{token}` from `wallet_balances`
+    Balances,
+    /// `activity:last:{network}:{subnet}:{address}` from `address_transactions`
+    LastActivity,
+}
+
+impl RebuildTargetV1 {
+    pub const ALL: [Self; 4] = [
+        Self::InteractionCounters,
+        Self::TokenRegistry,
+        Self::Balances,
+        Self::LastActivity,
+    ];
+
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::InteractionCounters => "dapp_usage",
+            Self::TokenRegistry => "token_transfers",
+            Self::Balances => "wallet_balances",
+            Self::LastActivity => "address_transactions",
+        }
+    }
+
+    fn sql(&self) -> &'static str {
+        match self {
+            Self::InteractionCounters => {
+                "SELECT address, dapp_name, \
+                 CAST(MAX(interaction_count) AS BIGINT) AS interaction_count \
+                 FROM dapp_usage WHERE chain_id = ? \
+                 GROUP BY address, dapp_name ORDER BY address, dapp_name \
+                 LIMIT ? OFFSET ?"
+            }
+            Self::TokenRegistry => {
+                "SELECT token_address, \
+                 arg_max(token_symbol, block_number) AS token_symbol, \
+                 arg_max(token_name, block_number) AS token_name, \
+                 CAST(arg_max(token_decimals, block_number) AS BIGINT) AS token_decimals \
+                 FROM token_transfers \
+                 WHERE chain_id = ? AND token_symbol IS NOT NULL AND token_decimals IS NOT NULL \
+                 GROUP BY token_address ORDER BY token_address \
+                 LIMIT ? OFFSET ?"
+            }
+            Self::Balances => {
+                "SELECT wallet_address, token_address, \
+                 arg_max(CAST(balance AS VARCHAR), snapshot_timestamp) AS balance, \
+                 arg_max(token_symbol, snapshot_timestamp) AS token_symbol, \
+                 CAST(arg_max(token_decimals, snapshot_timestamp) AS BIGINT) AS token_decimals, \
+                 CAST(epoch(MAX(snapshot_timestamp)) AS BIGINT) AS snapshot_timestamp \
+                 FROM wallet_balances WHERE chain_id = ? \
+                 GROUP BY wallet_address, token_address ORDER BY wallet_address, token_address \
+                 LIMIT ? OFFSET ?"
+            }
+            Self::LastActivity => {
+                "SELECT address, CAST(MAX(block_number) AS BIGINT) AS block_number, \
+                 CAST(epoch(MAX(block_timestamp)) AS BIGINT) AS block_timestamp, \
+                 arg_max(transaction_hash, block_number) AS transaction_hash \
+                 FROM address_transactions WHERE chain_id = ? \
+                 GROUP BY address ORDER BY address \
+                 LIMIT ? OFFSET ?"
+            }
+        }
+    }
+
+    /// Chain filter bound to the query's first parameter; `None` when the
+    /// target does not apply to the chain
+    fn chain_param(&self, request: &StateRebuildRequestV1) -> Option<SqlParam> {
+        match self {
+            Self::TokenRegistry if request.network != "tron" => None,
+            // wallet_balances is keyed by the numeric chain id
+            Self::Balances => request.chain_id.map(SqlParam::Int64),
+            _ => Some(SqlParam::String(request.chain_key())),
+        }
+    }
+
+    /// Redis key and value reproduced from one row
+    pub fn entry(&self, request: &StateRebuildRequestV1, row: &Row) -> Option<(String, Vec<u8>)> {
+        let network = &request.network;
+        let subnet = &request.subnet;
+        match self {
+            Self::InteractionCounters => {
+                let count: u64 = row.get("interaction_count")?.parse().ok()?;
+                let key = retention_policy::DAPP_USAGE_COUNTER.key(&format!(
+                    "{}:{}:{}",
+                    request.chain_key(),
+                    row.get("address")?.to_lowercase(),
+                    slug(row.get("dapp_name")?)
+                ));
+                Some((key, count.to_string().into_bytes()))
+            }
+            Self::TokenRegistry => {
+                let decimals: u32 = row.get("token_decimals")?.parse().ok()?;
+                let key = retention_policy::TRON_TOKEN_METADATA.key(&format!(
+                    "{}:{}",
+                    subnet,
+                    row.get("token_address")?
+                ));
+                let value = json!({
+                    "symbol": row.get("token_symbol")?,
+                    "name": row.get("token_name"),
+                    "decimals": decimals,
+                });
+                Some((key, value.to_string().into_bytes()))
+            }
+            Self::Balances => {
+                let key = retention_policy::BALANCE_LATEST.key(&format!(
+                    "{}:{}:{}:{}",
+                    network,
+                    subnet,
+                    row.get("wallet_address")?.to_lowercase(),
+                    row.get("token_address")?.to_lowercase()
+                ));
+                let value = json!({
+                    "balance": row.get("balance")?,
+                    "token_symbol": row.get("token_symbol"),
+                    "token_decimals": row.get("token_decimals").and_then(|v| v.parse::<u32>().ok()),
+                    "snapshot_timestamp": row.get("snapshot_timestamp")?.parse::<i64>().ok()?,
+                });
+                Some((key, value.to_string().into_bytes()))
+            }
+            Self::LastActivity => {
+                let key = retention_policy::LAST_ACTIVITY.key(&format!(
+                    "{}:{}:{}",
+                    network,
+                    subnet,
+                    row.get("address")?.to_lowercase()
+                ));
+                let value = json!({
+                    "block_number": row.get("block_number")?.parse::<i64>().ok()?,
+                    "block_timestamp": row.get("block_timestamp")?.parse::<i64>().ok()?,
+                    "transaction_hash": row.get("transaction_hash"),
+                });
+                Some((key, value.to_string().into_bytes()))
+            }
+        }
+    }
+}
+
+/// Same slug as the dApp usage counters written by eth_contract_transaction_processor
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// `admin.state.rebuild` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateRebuildRequestV1 {
+    pub network: String,
+    pub subnet: String,
+    /// Numeric chain id; required for the `balances` target
+    #[serde(default)]
+    pub chain_id: Option<i64>,
+    /// Targets to rebuild; empty for all
+    #[serde(default)]
+    pub targets: Vec<RebuildTargetV1>,
+    #[serde(default)]
+    pub page_size: Option<u32>,
+    /// Pause between pages
+    #[serde(default)]
+    pub pause_ms: Option<u64>,
+    /// Count rows and keys without writing to Redis
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl StateRebuildRequestV1 {
+    /// DuckLake `chain_id` partition value, e.g. `ethereum_mainnet`
+    pub fn chain_key(&self) -> String {
+        format!("{}_{}", self.network, self.subnet)
+    }
+
+    fn page_size(&self) -> u32 {
+        self.page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    fn pause_ms(&self) -> u64 {
+        self.pause_ms.unwrap_or(DEFAULT_PAUSE_MS).max(MIN_PAUSE_MS)
+    }
+
+    fn targets(&self) -> Vec<RebuildTargetV1> {
+        if self.targets.is_empty() {
+            RebuildTargetV1::ALL.to_vec()
+        } else {
+            self.targets.clone()
+        }
+    }
+}
+
+/// Progress of one target, published on [`PROGRESS_SUBJECT`] after every page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateRebuildProgressV1 {
+    pub schema_version: String,
+    pub rebuild_id: String,
+    pub target: RebuildTargetV1,
+    pub pages: u32,
+    pub rows: u64,
+    pub keys_written: u64,
+    pub done: bool,
+    /// Set when the target was skipped for this chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Reply to an `admin.state.rebuild` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateRebuildResultV1 {
+    pub schema_version: String,
+    pub rebuild_id: String,
+    pub success: bool,
+    pub dry_run: bool,
+    pub targets: Vec<StateRebuildProgressV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn handle_rebuild_message(io: &dyn RebuildIO, body: &[u8]) -> StateRebuildResultV1 {
+    match serde_json::from_slice::<StateRebuildRequestV1>(body) {
+        Ok(request) => run_rebuild(io, &request),
+        Err(e) => StateRebuildResultV1 {
+            schema_version: result_schema_version(),
+            rebuild_id: String::new(),
+            success: false,
+            dry_run: false,
+            targets: Vec::new(),
+            error: Some(format!("Invalid rebuild request: {}", e)),
+        },
+    }
+}
+
+pub fn run_rebuild(io: &dyn RebuildIO, request: &StateRebuildRequestV1) -> StateRebuildResultV1 {
+    let chain_key = request.chain_key();
+    let rebuild_id = format!("{}-{}", chain_key, io.now().timestamp_millis());
+    let mut result = StateRebuildResultV1 {
+        schema_version: result_schema_version(),
+        rebuild_id: rebuild_id.clone(),
+        success: false,
+        dry_run: request.dry_run,
+        targets: Vec::new(),
+        error: None,
+    };
+
+    let lock_key = retention_policy::STATE_REBUILD_LOCK
+        .key(&format!("{}:{}", request.network, request.subnet));
+    match io.kv_exists(&lock_key) {
+        Ok(false) => {}
+        Ok(true) => {
+            result.error = Some(format!("Rebuild already running for {}", chain_key));
+            return result;
+        }
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    }
+    if let Err(e) = io.kv_set(&lock_key, rebuild_id.as_bytes()) {
+        result.error = Some(e);
+        return result;
+    }
+
+    for target in request.targets() {
+        let progress = rebuild_target(io, request, &rebuild_id, target);
+        if let Some(e) = &progress.error {
+            result.error = Some(format!("{:?}: {}", target, e));
+        }
+        result.targets.push(progress);
+        if result.error.is_some() {
+            break;
+        }
+    }
+
+    if let Err(e) = io.kv_delete(&lock_key) {
+        result.error.get_or_insert(e);
+    }
+    result.success = result.error.is_none();
+    result
+}
+
+fn rebuild_target(
+    io: &dyn RebuildIO,
+    request: &StateRebuildRequestV1,
+    rebuild_id: &str,
+    target: RebuildTargetV1,
+) -> StateRebuildProgressV1 {
+    let mut progress = StateRebuildProgressV1 {
+        schema_version: progress_schema_version(),
+        rebuild_id: rebuild_id.to_string(),
+        target,
+        pages: 0,
+        rows: 0,
+        keys_written: 0,
+        done: false,
+        skipped: None,
+        error: None,
+        updated_at: io.now(),
+    };
+
+    let Some(chain_param) = target.chain_param(request) else {
+        progress.done = true;
+        progress.skipped = Some(format!("not applicable to {}", request.chain_key()));
+        report(io, &progress);
+        return progress;
+    };
+
+    let subject = format!(
+        "ducklake.{}.{}.{}.query",
+        target.table(),
+        request.network,
+        request.subnet
+    );
+    let page_size = request.page_size();
+
+    loop {
+        if progress.pages > 0 {
+            io.sleep_ms(request.pause_ms());
+        }
+
+        let query = QueryRequest::new(target.sql())
+            .with_timeout(QUERY_TIMEOUT_MS / 1000)
+            .with_parameters(vec![
+                chain_param.clone(),
+                SqlParam::Int64(page_size as i64),
+                SqlParam::Int64(progress.rows as i64),
+            ]);
+        let rows = match io.query(&subject, &query) {
+            Ok(rows) => rows,
+            Err(e) => {
+                progress.error = Some(e);
+                break;
+            }
+        };
+
+        progress.pages += 1;
+        progress.rows += rows.len() as u64;
+        for row in &rows {
+            let Some((key, value)) = target.entry(request, row) else {
+                continue;
+            };
+            if !request.dry_run {
+                if let Err(e) = io.kv_set(&key, &value) {
+                    progress.error = Some(e);
+                    break;
+                }
+            }
+            progress.keys_written += 1;
+        }
+        if progress.error.is_some() {
+            break;
+        }
+
+        progress.done = rows.len() < page_size as usize;
+        progress.updated_at = io.now();
+        report(io, &progress);
+        if progress.done {
+            return progress;
+        }
+    }
+
+    progress.updated_at = io.now();
+    report(io, &progress);
+    progress
+}
+
+fn report(io: &dyn RebuildIO, progress: &StateRebuildProgressV1) {
+    if let Ok(body) = serde_json::to_vec(progress) {
+        // Progress is best-effort; the final result is always replied
+        let _ = io.publish(PROGRESS_SUBJECT, body);
+    }
+}
+
+pub fn progress_schema_version() -> String {
+    "state_rebuild_progress_v1".to_string()
+}
+
+pub fn result_schema_version() -> String {
+    "state_rebuild_result_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeIO {
+        kv: RefCell<HashMap<String, Vec<u8>>>,
+        tables: HashMap<&'static str, Vec<Row>>,
+        queries: RefCell<Vec<(String, Vec<SqlParam>)>>,
+        progress: RefCell<Vec<StateRebuildProgressV1>>,
+        sleeps: RefCell<Vec<u64>>,
+    }
+
+    impl RebuildIO for FakeIO {
+        fn kv_exists(&self, key: &str) -> Result<bool, String> {
+            Ok(self.kv.borrow().contains_key(key))
+        }
+
+        fn kv_set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+            self.kv.borrow_mut().insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+
+        fn kv_delete(&self, key: &str) -> Result<(), String> {
+            self.kv.borrow_mut().remove(key);
+            Ok(())
+        }
+
+        fn query(&self, subject: &str, request: &QueryRequest) -> Result<Vec<Row>, String> {
+            let params = request.parameters.clone().unwrap_or_default();
+            self.queries
+                .borrow_mut()
+                .push((subject.to_string(), params.clone()));
+            let table = subject.split('.').nth(1).unwrap();
+            let (SqlParam::Int64(limit), SqlParam::Int64(offset)) = (&params[1], &params[2]) else {
+                return Err("bad paging params".to_string());
+            };
+            Ok(self
+                .tables
+                .get(table)
+                .map(|rows| {
+                    rows.iter()
+                        .skip(*offset as usize)
+                        .take(*limit as usize)
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+
+        fn publish(&self, subject: &str, body: Vec<u8>) -> Result<(), String> {
+            assert_eq!(subject, PROGRESS_SUBJECT);
+            self.progress
+                .borrow_mut()
+                .push(serde_json::from_slice(&body).unwrap());
+            Ok(())
+        }
+
+        fn sleep_ms(&self, ms: u64) {
+            self.sleeps.borrow_mut().push(ms);
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            DateTime::from_timestamp(1_704_067_200, 0).unwrap()
+        }
+    }
+
+    fn row(pairs: &[(&str, &str)]) -> Row {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn request(network: &str, targets: Vec<RebuildTargetV1>) -> StateRebuildRequestV1 {
+        StateRebuildRequestV1 {
+            network: network.to_string(),
+            subnet: "mainnet".to_string(),
+            chain_id: None,
+            targets,
+            page_size: Some(2),
+            pause_ms: Some(0),
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_interaction_counters_paged_and_rate_limited() {
+        let mut io = FakeIO::default();
+        io.tables.insert(
+            "dapp_usage",
+            vec![
+                row(&[
+                    ("address", "0xABC"),
+                    ("dapp_name", "Uniswap V2"),
+                    ("interaction_count", "7"),
+                ]),
+                row(&[
+                    ("address", "0xabc"),
+                    ("dapp_name", "Aave"),
+                    ("interaction_count", "2"),
+                ]),
+                row(&[
+                    ("address", "0xdef"),
+                    ("dapp_name", "Aave"),
+                    ("interaction_count", "1"),
+                ]),
+            ],
+        );
+
+        let result = run_rebuild(
+            &io,
+            &request("ethereum", vec![RebuildTargetV1::InteractionCounters]),
+        );
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            io.kv
+                .borrow()
+                .get("dapp_usage:ethereum_mainnet:0xabc:uniswap_v2"),
+            Some(&b"7".to_vec())
+        );
+        assert_eq!(result.targets[0].keys_written, 3);
+        assert_eq!(result.targets[0].pages, 2);
+
+        // Pause is clamped to the minimum between pages
+        assert_eq!(*io.sleeps.borrow(), vec![MIN_PAUSE_MS]);
+        let queries = io.queries.borrow();
+        assert_eq!(queries[0].0, "ducklake.dapp_usage.ethereum.mainnet.query");
+        assert!(matches!(queries[1].1[2], SqlParam::Int64(2)));
+
+        let progress = io.progress.borrow();
+        assert_eq!(progress.len(), 2);
+        assert!(!progress[0].done && progress[1].done);
+        // Lock released
+        assert!(!io
+            .kv
+            .borrow()
+            .contains_key("state_rebuild:lock:ethereum:mainnet"));
+    }
+
+    #[test]
+    fn test_rebuild_rejected_while_locked_and_targets_skipped() {
+        let io = FakeIO::default();
+        io.kv.borrow_mut().insert(
+            "state_rebuild:lock:ethereum:mainnet".to_string(),
+            b"x".to_vec(),
+        );
+        let result = run_rebuild(&io, &request("ethereum", Vec::new()));
+        assert!(!result.success);
+        assert!(io.queries.borrow().is_empty());
+
+        io.kv.borrow_mut().clear();
+        let result = run_rebuild(&io, &request("ethereum", Vec::new()));
+        assert!(result.success);
+        let skipped: Vec<RebuildTargetV1> = result
+            .targets
+            .iter()
+            .filter(|t| t.skipped.is_some())
+            .map(|t| t.target)
+            .collect();
+        // Token registry is Tron-only; balances need a numeric chain id
+        assert_eq!(
+            skipped,
+            vec![RebuildTargetV1::TokenRegistry, RebuildTargetV1::Balances]
+        );
+    }
+
+    #[test]
+    fn test_entries_match_reader_formats() {
+        let tron = request("tron", Vec::new());
+        let (key, value) = RebuildTargetV1::TokenRegistry
+            .entry(
+                &tron,
+                &row(&[
+                    ("token_address", "TXYZ"),
+                    ("token_symbol", "USDT"),
+                    ("token_decimals", "6"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(key, "tokens:tron:mainnet:TXYZ");
+        let value: serde_json::Value = serde_json::from_slice(&value).unwrap();
+        assert_eq!(
+            value,
+            json!({"symbol": "USDT", "name": null, "decimals": 6})
+        );
+
+        let eth = request("ethereum", Vec::new());
+        let (key, _) = RebuildTargetV1::LastActivity
+            .entry(
+                &eth,
+                &row(&[
+                    ("address", "0xAA"),
+                    ("block_number", "10"),
+                    ("block_timestamp", "1700000000"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(key, "activity:last:ethereum:mainnet:0xaa");
+
+        // Rows missing required columns are skipped
+        assert!(RebuildTargetV1::InteractionCounters
+            .entry(&eth, &row(&[("address", "0x1")]))
+            .is_none());
+    }
+}
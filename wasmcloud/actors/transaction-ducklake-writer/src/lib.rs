@@ -123,6 +123,8 @@ use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
 use subject_registry::blockchain;
 use wasmcloud::messaging::{consumer, types};
 
+subject_acl::component!("transaction-ducklake-writer");
+
 /// Processed transaction from processing actors
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessedTransaction {
@@ -374,8 +376,13 @@ impl MessageHandler for Component {
         eprintln!("[DuckLake Writer] Subject: {}", msg.subject);
         eprintln!("[DuckLake Writer] Payload size: {} bytes", msg.body.len());
 
-        let subject = &msg.subject;
+        let Some(subject) = subject_registry::unprefixed(&msg.subject) else {
+            return Ok(());
+        };
         let payload = &msg.body;
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
 
         eprintln!(
             "[DuckLake Writer] Processing message on subject: {}",
@@ -500,9 +507,19 @@ fn publish_to_ducklake(
         payload.len()
     );
 
+    if let Err(violation) = subject_acl::authorize(ACTOR_ID, &subject) {
+        eprintln!("[DuckLake Writer] Rejected by subject ACL: {}", violation);
+        let _ = consumer::publish(&types::BrokerMessage {
+            subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+            body: violation.to_json(),
+            reply_to: None,
+        });
+        return Err(violation.to_string());
+    }
+
     // Use the messaging consumer to publish to NATS
     match consumer::publish(&types::BrokerMessage {
-        subject: subject.clone(),
+        subject: subject_registry::prefixed(&feature_flags::dry_run::route(&subject)),
         body: payload,
         reply_to: None,
     }) {
@@ -154,7 +154,7 @@ impl Component {
             transaction_count: original_count,
             filtered_count,
             transactions: processed_json,
-            processed_at: chrono::Utc::now().to_rfc3339(),
+            processed_at: replay_clock::now().to_rfc3339(),
         })
     }
 
@@ -178,7 +178,7 @@ impl Component {
                     transaction_count: 0,
                     filtered_count: 0,
                     transactions: "[]".to_string(),
-                    processed_at: chrono::Utc::now().to_rfc3339(),
+                    processed_at: replay_clock::now().to_rfc3339(),
                 })
             }
             "solana" | "sol" => {
@@ -190,7 +190,7 @@ impl Component {
                     transaction_count: 0,
                     filtered_count: 0,
                     transactions: "[]".to_string(),
-                    processed_at: chrono::Utc::now().to_rfc3339(),
+                    processed_at: replay_clock::now().to_rfc3339(),
                 })
             }
             _ => Err(format!("Unsupported network: {}", network)),
@@ -221,7 +221,10 @@ impl Component {
         // Use the messaging handler to publish processed transactions
         use ekko::messaging::handler::publish;
 
-        match publish(&processed_subject, &output_json) {
+        match publish(
+            &subject_registry::prefixed(&processed_subject),
+            &output_json,
+        ) {
             Ok(_) => {
                 eprintln!(
                     "Published {} processed {}.{} transactions to: {}",
@@ -263,7 +266,10 @@ impl Component {
         // Use the messaging handler to publish notification
         use ekko::messaging::handler::publish;
 
-        match publish(notification_subject, &notification_json) {
+        match publish(
+            &subject_registry::prefixed(notification_subject),
+            &notification_json,
+        ) {
             Ok(_) => {
                 eprintln!(
                     "Sent notification for {} processed transactions",
@@ -282,17 +288,28 @@ impl Component {
 impl MessageConsumer for Component {
     /// Handle incoming NATS messages containing raw transaction data
     fn handle_message(subject: String, payload: Vec<u8>) -> Result<(), String> {
-        // Only process blockchain transaction messages
+        // Only process blockchain transaction messages from this environment
         // Subject pattern: blockchain.{network}.{subnet}.transactions.*
-        if !subject.starts_with("blockchain.") || !subject.contains(".transactions.") {
-            return Ok(()); // Ignore non-blockchain-transaction messages
-        }
+        let subject = match subject_registry::unprefixed(&subject) {
+            Some(subject)
+                if subject.starts_with("blockchain.") && subject.contains(".transactions.") =>
+            {
+                subject
+            }
+            _ => return Ok(()), // Ignore non-blockchain-transaction messages
+        };
 
         eprintln!(
             "Transaction processor received message on subject: {}",
             subject
         );
 
+        replay_clock::begin_message(|| {
+            let config =
+                ekko::keyvalue::store::get(&retention_policy::REPLAY_CONFIG.key("")).ok()??;
+            serde_json::from_str(&config).ok()
+        });
+
         // Try to parse as structured message first
         let message = match RawTransactionMessage::from_json(&payload) {
             Ok(msg) => msg,
@@ -0,0 +1,216 @@
+//! Tron address format conversion
+//!
+//! Tron accounts are 21 bytes: a `0x41` network prefix followed by the same
+//! 20-byte account hash used on the EVM. The full-node HTTP API returns them as
+//! hex (`41...`) while wallets and explorers show base58check (`T...`).
+//! Contract logs and ABI-encoded calldata drop the prefix entirely and carry the
+//! 20-byte hash left-padded to a 32-byte word.
+
+use sha2::{Digest, Sha256};
+
+/// Network prefix byte for Tron mainnet/testnet addresses
+pub const ADDRESS_PREFIX: u8 = 0x41;
+
+/// Length of a prefixed Tron address in bytes
+const ADDRESS_LEN: usize = 21;
+
+/// Length of the base58check checksum in bytes
+const CHECKSUM_LEN: usize = 4;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Convert a hex address (`41...`, `0x41...` or a bare 20-byte `0x...` hash) to base58check
+pub fn hex_to_base58(hex_address: &str) -> Result<String, String> {
+    let bytes = decode_hex_address(hex_address)?;
+    let checksum = checksum(&bytes);
+
+    let mut payload = bytes;
+    payload.extend_from_slice(&checksum);
+    Ok(base58_encode(&payload))
+}
+
+/// Convert a base58check address (`T...`) to lowercase hex with the `41` prefix
+pub fn base58_to_hex(address: &str) -> Result<String, String> {
+    let decoded = base58_decode(address)?;
+    if decoded.len() != ADDRESS_LEN + CHECKSUM_LEN {
+        return Err(format!(
+            "Invalid Tron address length: expected {} bytes, got {}",
+            ADDRESS_LEN + CHECKSUM_LEN,
+            decoded.len()
+        ));
+    }
+
+    let (payload, expected) = decoded.split_at(ADDRESS_LEN);
+    if checksum(payload) != expected {
+        return Err(format!(
+            "Invalid base58check checksum for address {}",
+            address
+        ));
+    }
+    if payload[0] != ADDRESS_PREFIX {
+        return Err(format!(
+            "Invalid Tron address prefix: expected 0x41, got 0x{:02x}",
+            payload[0]
+        ));
+    }
+
+    Ok(hex::encode(payload))
+}
+
+/// Convert a 32-byte ABI word (log topic / calldata argument) into a base58check address
+pub fn abi_word_to_base58(word: &str) -> Result<String, String> {
+    let cleaned = word.trim_start_matches("0x");
+    if cleaned.len() < 40 {
+        return Err(format!("ABI word too short for an address: {}", word));
+    }
+    hex_to_base58(&cleaned[cleaned.len() - 40..])
+}
+
+/// Normalize any supported address representation to base58check
+///
+/// Empty input is passed through so optional addresses (e.g. contract
+/// creations without a recipient) don't need special-casing by callers.
+pub fn normalize(address: &str) -> Result<String, String> {
+    let trimmed = address.trim();
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    if trimmed.starts_with('T') && trimmed.len() == 34 {
+        // Validate checksum, then return the canonical input
+        base58_to_hex(trimmed)?;
+        return Ok(trimmed.to_string());
+    }
+    hex_to_base58(trimmed)
+}
+
+fn decode_hex_address(hex_address: &str) -> Result<Vec<u8>, String> {
+    let cleaned = hex_address.trim().trim_start_matches("0x");
+    let bytes =
+        hex::decode(cleaned).map_err(|e| format!("Invalid hex address {}: {}", hex_address, e))?;
+
+    match bytes.len() {
+        ADDRESS_LEN if bytes[0] == ADDRESS_PREFIX => Ok(bytes),
+        20 => {
+            let mut prefixed = Vec::with_capacity(ADDRESS_LEN);
+            prefixed.push(ADDRESS_PREFIX);
+            prefixed.extend_from_slice(&bytes);
+            Ok(prefixed)
+        }
+        _ => Err(format!(
+            "Invalid Tron hex address {}: expected 20 or 21 bytes",
+            hex_address
+        )),
+    }
+}
+
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let first = Sha256::digest(payload);
+    let second = Sha256::digest(first);
+    let mut out = [0u8; CHECKSUM_LEN];
+    out.copy_from_slice(&second[..CHECKSUM_LEN]);
+    out
+}
+
+fn base58_encode(input: &[u8]) -> String {
+    let zeros = input.iter().take_while(|b| **b == 0).count();
+
+    // Little-endian base58 digits
+    let mut digits: Vec<u8> = Vec::with_capacity(input.len() * 138 / 100 + 1);
+    for byte in &input[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(std::iter::repeat('1').take(zeros));
+    out.extend(digits.iter().rev().map(|d| ALPHABET[*d as usize] as char));
+    out
+}
+
+fn base58_decode(input: &str) -> Result<Vec<u8>, String> {
+    let zeros = input.bytes().take_while(|b| *b == b'1').count();
+
+    // Little-endian base256 bytes
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+    for ch in input.bytes().skip(zeros) {
+        let value = ALPHABET
+            .iter()
+            .position(|a| *a == ch)
+            .ok_or_else(|| format!("Invalid base58 character '{}'", ch as char))?;
+
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes.iter().rev());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // USDT (TRC-20) contract on Tron mainnet
+    const USDT_BASE58: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+    const USDT_HEX: &str = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+
+    #[test]
+    fn test_hex_to_base58() {
+        assert_eq!(hex_to_base58(USDT_HEX).unwrap(), USDT_BASE58);
+        assert_eq!(
+            hex_to_base58("0xa614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap(),
+            USDT_BASE58
+        );
+    }
+
+    #[test]
+    fn test_base58_to_hex_roundtrip() {
+        assert_eq!(base58_to_hex(USDT_BASE58).unwrap(), USDT_HEX);
+
+        let zero = hex_to_base58(&format!("41{}", "00".repeat(20))).unwrap();
+        assert_eq!(zero, "T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb");
+        assert_eq!(
+            base58_to_hex(&zero).unwrap(),
+            format!("41{}", "00".repeat(20))
+        );
+    }
+
+    #[test]
+    fn test_base58_to_hex_rejects_bad_checksum() {
+        let mut tampered = USDT_BASE58.to_string();
+        tampered.replace_range(33..34, "u");
+        assert!(base58_to_hex(&tampered).is_err());
+        assert!(base58_to_hex("T0invalid").is_err());
+    }
+
+    #[test]
+    fn test_abi_word_to_base58() {
+        let word = "0x000000000000000000000000a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+        assert_eq!(abi_word_to_base58(word).unwrap(), USDT_BASE58);
+        assert!(abi_word_to_base58("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_normalize_accepts_both_formats() {
+        assert_eq!(normalize(USDT_HEX).unwrap(), USDT_BASE58);
+        assert_eq!(normalize(USDT_BASE58).unwrap(), USDT_BASE58);
+        assert_eq!(normalize("").unwrap(), "");
+    }
+}
@@ -0,0 +1,204 @@
+//! Energy/bandwidth fee modeling
+//!
+//! Tron has no gas price. Execution is metered in *energy* and transaction size
+//! in *bandwidth*; both are covered by staked/free resources first and only the
+//! shortfall is paid by burning TRX (in sun, 1 TRX = 1_000_000 sun).
+//!
+//! The unified transactions schema is EVM-shaped, so the model maps onto it as:
+//! - `gas_limit`           → `fee_limit` (max sun the caller allows to burn)
+//! - `gas_used`            → total energy consumed (caller + contract origin)
+//! - `gas_price`           → network energy price in sun
+//! - `effective_gas_price` → sun actually paid per burned energy unit
+//! - `transaction_fee`     → total sun burned (energy + bandwidth + fixed fees)
+
+use serde::{Deserialize, Serialize};
+
+/// Default energy price in sun when none is configured for the network
+pub const DEFAULT_ENERGY_PRICE_SUN: u64 = 420;
+
+/// Default bandwidth price in sun per byte when none is configured
+pub const DEFAULT_BANDWIDTH_PRICE_SUN: u64 = 1_000;
+
+/// Sun per TRX
+pub const SUN_PER_TRX: f64 = 1_000_000.0;
+
+/// Resource receipt from `gettransactioninfobyblocknum`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceReceipt {
+    /// Energy paid from the caller's staked energy
+    #[serde(default)]
+    pub energy_usage: u64,
+    /// Sun burned for energy the caller didn't have staked
+    #[serde(default)]
+    pub energy_fee: u64,
+    /// Energy paid by the contract deployer (origin energy limit sharing)
+    #[serde(default)]
+    pub origin_energy_usage: u64,
+    /// Total energy consumed by execution
+    #[serde(default)]
+    pub energy_usage_total: u64,
+    /// Bandwidth paid from staked/free bandwidth (bytes)
+    #[serde(default)]
+    pub net_usage: u64,
+    /// Sun burned for bandwidth (bytes * bandwidth price)
+    #[serde(default)]
+    pub net_fee: u64,
+    /// Execution result (SUCCESS, REVERT, OUT_OF_ENERGY, ...)
+    #[serde(default)]
+    pub result: Option<String>,
+}
+
+/// Network resource pricing used when the receipt alone can't determine prices
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ResourcePrices {
+    pub energy_price_sun: u64,
+    pub bandwidth_price_sun: u64,
+}
+
+impl Default for ResourcePrices {
+    fn default() -> Self {
+        Self {
+            energy_price_sun: DEFAULT_ENERGY_PRICE_SUN,
+            bandwidth_price_sun: DEFAULT_BANDWIDTH_PRICE_SUN,
+        }
+    }
+}
+
+/// Fee breakdown for a single Tron transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeBreakdown {
+    /// Total sun burned (the `fee` field of transaction info)
+    pub total_fee_sun: u64,
+    pub energy_used: u64,
+    /// Energy that had to be paid for by burning TRX
+    pub energy_burned: u64,
+    pub energy_fee_sun: u64,
+    /// Bandwidth consumed in bytes (staked + burned)
+    pub bandwidth_used: u64,
+    pub bandwidth_fee_sun: u64,
+    /// Fixed fees not attributable to energy or bandwidth (account activation, memo, multisig)
+    pub other_fee_sun: u64,
+    pub energy_price_sun: u64,
+    /// Sun paid per burned energy unit; falls back to `energy_price_sun`
+    pub effective_energy_price_sun: u64,
+}
+
+impl FeeBreakdown {
+    /// Total fee in TRX
+    pub fn total_fee_trx(&self) -> f64 {
+        self.total_fee_sun as f64 / SUN_PER_TRX
+    }
+}
+
+/// Model the fees for a transaction from its receipt and the `fee` field
+pub fn model_fees(
+    receipt: &ResourceReceipt,
+    total_fee_sun: u64,
+    prices: ResourcePrices,
+) -> FeeBreakdown {
+    let energy_used = receipt
+        .energy_usage_total
+        .max(receipt.energy_usage + receipt.origin_energy_usage);
+
+    let energy_burned = if prices.energy_price_sun > 0 {
+        receipt.energy_fee / prices.energy_price_sun
+    } else {
+        0
+    };
+
+    let effective_energy_price_sun = if energy_burned > 0 {
+        receipt.energy_fee / energy_burned
+    } else {
+        prices.energy_price_sun
+    };
+
+    let bandwidth_burned = if prices.bandwidth_price_sun > 0 {
+        receipt.net_fee / prices.bandwidth_price_sun
+    } else {
+        0
+    };
+
+    // Older receipts omit `fee`; reconstruct it from its components
+    let component_fees = receipt.energy_fee + receipt.net_fee;
+    let total_fee_sun = total_fee_sun.max(component_fees);
+
+    FeeBreakdown {
+        total_fee_sun,
+        energy_used,
+        energy_burned,
+        energy_fee_sun: receipt.energy_fee,
+        bandwidth_used: receipt.net_usage + bandwidth_burned,
+        bandwidth_fee_sun: receipt.net_fee,
+        other_fee_sun: total_fee_sun - component_fees,
+        energy_price_sun: prices.energy_price_sun,
+        effective_energy_price_sun,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trx_transfer_with_free_bandwidth() {
+        let receipt = ResourceReceipt {
+            net_usage: 268,
+            ..Default::default()
+        };
+        let fees = model_fees(&receipt, 0, ResourcePrices::default());
+
+        assert_eq!(fees.total_fee_sun, 0);
+        assert_eq!(fees.energy_used, 0);
+        assert_eq!(fees.bandwidth_used, 268);
+        assert_eq!(fees.effective_energy_price_sun, DEFAULT_ENERGY_PRICE_SUN);
+    }
+
+    #[test]
+    fn test_trc20_transfer_burning_energy_and_bandwidth() {
+        // USDT transfer to a fresh address with no staked resources
+        let receipt = ResourceReceipt {
+            energy_fee: 27_255_900,
+            energy_usage_total: 64_895,
+            net_fee: 345_000,
+            result: Some("SUCCESS".to_string()),
+            ..Default::default()
+        };
+        let fees = model_fees(&receipt, 27_600_900, ResourcePrices::default());
+
+        assert_eq!(fees.energy_used, 64_895);
+        assert_eq!(fees.energy_burned, 64_895);
+        assert_eq!(fees.effective_energy_price_sun, 420);
+        assert_eq!(fees.bandwidth_used, 345);
+        assert_eq!(fees.other_fee_sun, 0);
+        assert!((fees.total_fee_trx() - 27.6009).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fixed_fees_and_missing_total() {
+        let receipt = ResourceReceipt {
+            net_fee: 100_000,
+            ..Default::default()
+        };
+
+        // Account activation adds a fixed 1 TRX on top of bandwidth
+        let fees = model_fees(&receipt, 1_100_000, ResourcePrices::default());
+        assert_eq!(fees.other_fee_sun, 1_000_000);
+
+        // No `fee` field: total reconstructed from components
+        let fees = model_fees(&receipt, 0, ResourcePrices::default());
+        assert_eq!(fees.total_fee_sun, 100_000);
+        assert_eq!(fees.other_fee_sun, 0);
+    }
+
+    #[test]
+    fn test_energy_split_with_origin_sharing() {
+        let receipt = ResourceReceipt {
+            energy_usage: 10_000,
+            origin_energy_usage: 5_000,
+            ..Default::default()
+        };
+        let fees = model_fees(&receipt, 0, ResourcePrices::default());
+        assert_eq!(fees.energy_used, 15_000);
+        assert_eq!(fees.energy_burned, 0);
+    }
+}
@@ -0,0 +1,1057 @@
+//! # TVM Raw Transactions Actor
+//!
+//! WasmCloud actor that processes Tron newheads and fetches raw transactions from full nodes.
+//! Tron exposes a REST-style HTTP API (`/wallet/*`) instead of JSON-RPC, so this actor
+//! adapts the block and transaction-info payloads into the unified transaction format.
+//!
+//! ## Processing
+//! - Addresses are converted from the API's hex format (`41...`) to base58check (`T...`)
+//! - TRC-20 transfers are extracted from logs (or calldata) and enriched with token metadata
+//! - Energy/bandwidth consumption is modeled into the unified schema's fee fields
+//!
+//! ## Subscription Pattern
+//! - Subscribes to: `newheads.{network}.{subnet}.tvm`
+//! - Publishes to:
+//!   - `transactions.raw.tvm` - Raw Tron transactions
+//!   - `ducklake.transactions.{network}.{subnet}.write` - Unified transaction records
+//!   - `ducklake.token_transfers.{network}.{subnet}.write` - TRC-20 transfers
+//!   - `ducklake.address_transactions.{network}.{subnet}.write` - Address index rows
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Generate WIT bindings for the processor world
+wit_bindgen::generate!({ generate_all });
+
+use exports::wasmcloud::messaging::handler::Guest as MessageHandler;
+use wasmcloud::messaging::{consumer, types};
+
+subject_acl::component!("tron-raw-transactions");
+
+pub mod address;
+pub mod fees;
+pub mod trc20;
+
+use fees::{FeeBreakdown, ResourcePrices, ResourceReceipt, SUN_PER_TRX};
+use trc20::{TokenMetadata, Trc20Transfer, TronLog};
+
+/// Block header from newheads provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub network: String,     // e.g., "tron"
+    pub subnet: String,      // e.g., "mainnet", "nile", "shasta"
+    pub vm_type: String,     // "tvm"
+    pub chain_id: String,    // e.g., "tron-mainnet"
+    pub chain_name: String,  // e.g., "Tron Mainnet"
+    pub block_number: u64,   // Block height
+    pub block_hash: String,  // blockID
+    pub parent_hash: String, // Parent blockID
+    pub timestamp: u64,
+    pub transaction_count: Option<u32>,
+    pub received_at: String, // ISO timestamp
+    pub provider_id: String,
+}
+
+/// Network configuration from Redis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Full-node HTTP API base URLs (e.g. `https://api.trongrid.io`)
+    pub rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub ws_urls: Vec<String>,
+    /// Optional TronGrid API key sent as `TRON-PRO-API-KEY`
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Energy price override in sun (chain parameter `getEnergyFee`)
+    #[serde(default)]
+    pub energy_price_sun: Option<u64>,
+    /// Bandwidth price override in sun per byte (chain parameter `getTransactionFee`)
+    #[serde(default)]
+    pub bandwidth_price_sun: Option<u64>,
+    pub enabled: bool,
+}
+
+impl NetworkConfig {
+    /// Resource prices for fee modeling, falling back to current network defaults
+    pub fn resource_prices(&self) -> ResourcePrices {
+        let defaults = ResourcePrices::default();
+        ResourcePrices {
+            energy_price_sun: self.energy_price_sun.unwrap_or(defaults.energy_price_sun),
+            bandwidth_price_sun: self
+                .bandwidth_price_sun
+                .unwrap_or(defaults.bandwidth_price_sun),
+        }
+    }
+}
+
+/// Raw TVM transaction data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawTransaction {
+    pub network: String,
+    pub subnet: String,
+    pub vm_type: String,
+    pub transaction_hash: String, // txID
+    pub block_number: u64,
+    pub block_hash: String,
+    pub block_timestamp: u64, // Unix seconds (API reports milliseconds)
+    pub transaction_index: u32,
+
+    // TVM-specific transaction data
+    pub contract_type: String, // TransferContract, TriggerSmartContract, ...
+    pub from_address: String,  // base58check
+    pub to_address: Option<String>,
+    pub value: String, // Sun (TRX transfers / call_value) or asset units (TRC-10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_name: Option<String>, // TRC-10 token id
+    pub input_data: String,
+    pub fee_limit: u64,
+    pub status: String, // contractRet: SUCCESS, REVERT, OUT_OF_ENERGY, ...
+    pub signatures: Vec<String>,
+
+    // Processing metadata
+    pub processed_at: String,
+    pub processor_id: String,
+}
+
+/// Transaction info from `gettransactioninfobyblocknum`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default)]
+    pub receipt: ResourceReceipt,
+    #[serde(default)]
+    pub log: Vec<TronLog>,
+    #[serde(default)]
+    pub contract_address: Option<String>,
+}
+
+/// DuckLake transaction record aligned to the unified transactions schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeTransactionRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub network: String,
+    pub subnet: String,
+    pub vm_type: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub transaction_hash: String,
+    pub transaction_index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_fee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method_signature: Option<String>,
+    pub transaction_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_subtype: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_native: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processor_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+/// DuckLake token_transfers record for TRC-20 transfers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeTokenTransferRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub transaction_hash: String,
+    pub log_index: u32,
+    pub token_address: String,
+    pub token_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_decimals: Option<u32>,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+}
+
+/// DuckLake address_transactions record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeAddressTransactionRecord {
+    pub chain_id: String,
+    pub block_date: String,
+    pub address: String,
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub is_sender: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterparty_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_subtype: Option<String>,
+}
+
+/// Main TVM Raw Transactions Actor
+pub struct Component;
+
+// Export Component for WasmCloud
+export!(Component);
+
+impl MessageHandler for Component {
+    /// Handle incoming NATS messages containing blockchain newheads
+    fn handle_message(msg: types::BrokerMessage) -> Result<(), String> {
+        replay_clock::begin_message(|| {
+            let bucket = wasi::keyvalue::store::open("default").ok()?;
+            let bytes = bucket
+                .get(&retention_policy::REPLAY_CONFIG.key(""))
+                .ok()??;
+            serde_json::from_slice(&bytes).ok()
+        });
+        feature_flags::dry_run::begin_message(|key| {
+            wasi::keyvalue::store::open("default").ok()?.get(key).ok()?
+        });
+
+        // Only process newheads messages for TVM chains
+        let subject = subject_registry::unprefixed(&msg.subject).unwrap_or_default();
+        if !subject.starts_with("newheads.") || !subject.ends_with(".tvm") {
+            return Ok(());
+        }
+
+        // Parse the block header from the message
+        let block_header: BlockHeader = serde_json::from_slice(&msg.body)
+            .map_err(|e| format!("Failed to parse block header: {}", e))?;
+
+        // Process the block header and fetch transactions
+        Self::process_block_header(block_header)?;
+
+        Ok(())
+    }
+}
+
+impl Component {
+    /// Process a block header by fetching its transactions and publishing them
+    fn process_block_header(block_header: BlockHeader) -> Result<(), String> {
+        // Get network configuration from Redis
+        let config = Self::get_network_config(&block_header)?;
+
+        if !config.enabled {
+            return Err(format!(
+                "Network {}:{}:{} is disabled",
+                block_header.network, block_header.subnet, block_header.vm_type
+            ));
+        }
+
+        // Get the first available full-node URL
+        let base_url = config
+            .rpc_urls
+            .first()
+            .ok_or_else(|| "No RPC URLs configured for network".to_string())?;
+
+        let block_data = Self::call_wallet_api(
+            base_url,
+            config.api_key.as_deref(),
+            "getblockbynum",
+            block_header.block_number,
+        )?;
+        let info_data = Self::call_wallet_api(
+            base_url,
+            config.api_key.as_deref(),
+            "gettransactioninfobyblocknum",
+            block_header.block_number,
+        )?;
+
+        let infos = Self::index_transaction_infos(&info_data);
+        let prices = config.resource_prices();
+
+        if let Some(transactions) = block_data.get("transactions").and_then(|v| v.as_array()) {
+            for (index, tx_data) in transactions.iter().enumerate() {
+                let raw_tx = Self::parse_transaction(tx_data, &block_header, index as u32)?;
+                let info = infos
+                    .get(&raw_tx.transaction_hash)
+                    .cloned()
+                    .unwrap_or_default();
+
+                Self::publish_transaction(&raw_tx, &info, prices)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get network configuration from Redis key-value store
+    fn get_network_config(block_header: &BlockHeader) -> Result<NetworkConfig, String> {
+        let redis_key = format!(
+            "nodes:{}:{}:{}",
+            block_header.network, block_header.subnet, block_header.vm_type
+        );
+
+        let bucket = wasi::keyvalue::store::open("default")
+            .map_err(|e| format!("Failed to open keyvalue bucket: {:?}", e))?;
+
+        let config_bytes = bucket
+            .get(&redis_key)
+            .map_err(|e| format!("Failed to get key from store: {:?}", e))?
+            .ok_or_else(|| format!("No configuration found for key: {}", redis_key))?;
+
+        let config: NetworkConfig = serde_json::from_slice(&config_bytes)
+            .map_err(|e| format!("Failed to parse network config: {}", e))?;
+
+        Ok(config)
+    }
+
+    /// Resolve TRC-20 metadata: built-in table first, then `tokens:tron:{subnet}:{address}`
+    fn lookup_token_metadata(subnet: &str, token_address: &str) -> Option<TokenMetadata> {
+        if let Some(known) = trc20::known_token(subnet, token_address) {
+            return Some(known);
+        }
+
+        let bucket = wasi::keyvalue::store::open("default").ok()?;
+        let key =
+            retention_policy::TRON_TOKEN_METADATA.key(&format!("{}:{}", subnet, token_address));
+        let bytes = bucket.get(&key).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Parse URL into components for WASI HTTP request
+    fn parse_url(url: &str) -> Result<(wasi::http::types::Scheme, String, String), String> {
+        let (scheme_str, rest) = url
+            .split_once("://")
+            .ok_or_else(|| format!("Invalid URL format: {}", url))?;
+
+        let scheme = match scheme_str {
+            "http" => wasi::http::types::Scheme::Http,
+            "https" => wasi::http::types::Scheme::Https,
+            _ => return Err(format!("Unsupported scheme: {}", scheme_str)),
+        };
+
+        let (authority, path) = if let Some((auth, p)) = rest.split_once('/') {
+            (auth.to_string(), format!("/{}", p))
+        } else {
+            (rest.to_string(), "/".to_string())
+        };
+
+        Ok((scheme, authority, path))
+    }
+
+    /// Send a JSON POST request using WASI HTTP and return raw response bytes
+    fn post_json(url: &str, api_key: Option<&str>, body: &str) -> Result<Vec<u8>, String> {
+        let (scheme, authority, path) = Self::parse_url(url)?;
+
+        let headers = wasi::http::types::Fields::new();
+        headers
+            .set(
+                &"content-type".to_string(),
+                &vec![b"application/json".to_vec()],
+            )
+            .map_err(|e| format!("Failed to set content-type header: {:?}", e))?;
+        headers
+            .set(
+                &"content-length".to_string(),
+                &vec![body.len().to_string().into_bytes()],
+            )
+            .map_err(|e| format!("Failed to set content-length header: {:?}", e))?;
+        if let Some(key) = api_key {
+            headers
+                .set(
+                    &"tron-pro-api-key".to_string(),
+                    &vec![key.as_bytes().to_vec()],
+                )
+                .map_err(|e| format!("Failed to set api key header: {:?}", e))?;
+        }
+
+        let request = wasi::http::types::OutgoingRequest::new(headers);
+        request
+            .set_method(&wasi::http::types::Method::Post)
+            .map_err(|_| "Failed to set request method".to_string())?;
+        request
+            .set_scheme(Some(&scheme))
+            .map_err(|_| "Failed to set request scheme".to_string())?;
+        request
+            .set_authority(Some(&authority))
+            .map_err(|_| "Failed to set request authority".to_string())?;
+        request
+            .set_path_with_query(Some(&path))
+            .map_err(|_| "Failed to set request path".to_string())?;
+
+        let request_body = request
+            .body()
+            .map_err(|_| "Failed to get request body".to_string())?;
+        {
+            let output_stream = request_body
+                .write()
+                .map_err(|_| "Failed to get body output stream".to_string())?;
+            output_stream
+                .blocking_write_and_flush(body.as_bytes())
+                .map_err(|e| format!("Failed to write request body: {:?}", e))?;
+        }
+        wasi::http::types::OutgoingBody::finish(request_body, None)
+            .map_err(|_| "Failed to finish request body".to_string())?;
+
+        let future_response = wasi::http::outgoing_handler::handle(request, None)
+            .map_err(|e| format!("Failed to send HTTP request: {:?}", e))?;
+        let pollable = future_response.subscribe();
+        wasi::io::poll::poll(&[&pollable]);
+
+        let outer_result = future_response
+            .get()
+            .ok_or_else(|| "Failed to get response from future".to_string())?;
+        let inner_result =
+            outer_result.map_err(|e| format!("HTTP request failed (outer): {:?}", e))?;
+        let incoming_response =
+            inner_result.map_err(|e| format!("HTTP request failed (inner): {:?}", e))?;
+
+        let status = incoming_response.status();
+        if !(200..300).contains(&status) {
+            return Err(format!("HTTP error: status {}", status));
+        }
+
+        let response_body = incoming_response
+            .consume()
+            .map_err(|_| "Failed to consume response body".to_string())?;
+        let input_stream = response_body
+            .stream()
+            .map_err(|_| "Failed to get response stream".to_string())?;
+
+        let mut response_bytes = Vec::new();
+        loop {
+            match input_stream.blocking_read(8192) {
+                Ok(chunk) => {
+                    if chunk.is_empty() {
+                        break;
+                    }
+                    response_bytes.extend_from_slice(&chunk);
+                }
+                Err(_) => break,
+            }
+        }
+
+        Ok(response_bytes)
+    }
+
+    /// Call a `/wallet/{method}` endpoint with a block number
+    fn call_wallet_api(
+        base_url: &str,
+        api_key: Option<&str>,
+        method: &str,
+        block_number: u64,
+    ) -> Result<serde_json::Value, String> {
+        let url = format!("{}/wallet/{}", base_url.trim_end_matches('/'), method);
+        let request_body = serde_json::json!({ "num": block_number }).to_string();
+
+        let response_bytes = Self::post_json(&url, api_key, &request_body)?;
+        let response: serde_json::Value = serde_json::from_slice(&response_bytes)
+            .map_err(|e| format!("Failed to parse {} response: {}", method, e))?;
+
+        // The wallet API reports failures as {"Error": "..."} with HTTP 200
+        if let Some(error) = response.get("Error") {
+            return Err(format!("Tron API error from {}: {}", method, error));
+        }
+
+        Ok(response)
+    }
+
+    /// Index transaction info entries by transaction id
+    fn index_transaction_infos(info_data: &serde_json::Value) -> HashMap<String, TransactionInfo> {
+        info_data
+            .as_array()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| {
+                        serde_json::from_value::<TransactionInfo>(entry.clone()).ok()
+                    })
+                    .map(|info| (info.id.clone(), info))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Parse a raw transaction from the Tron block response into our format
+    fn parse_transaction(
+        tx_data: &serde_json::Value,
+        block_header: &BlockHeader,
+        index: u32,
+    ) -> Result<RawTransaction, String> {
+        let transaction_hash = tx_data
+            .get("txID")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing transaction txID")?;
+
+        let raw_data = tx_data
+            .get("raw_data")
+            .ok_or("Missing transaction raw_data")?;
+
+        // Tron transactions carry exactly one contract in practice
+        let contract = raw_data
+            .get("contract")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first())
+            .ok_or("Missing transaction contract")?;
+
+        let contract_type = contract
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        let value = contract
+            .get("parameter")
+            .and_then(|p| p.get("value"))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        let hex_field = |name: &str| value.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        let u64_field = |name: &str| value.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+
+        let from_address = address::normalize(hex_field("owner_address"))?;
+        let (to_address, amount, asset_name, input_data) = match contract_type.as_str() {
+            "TransferContract" => (
+                Some(address::normalize(hex_field("to_address"))?),
+                u64_field("amount"),
+                None,
+                String::new(),
+            ),
+            "TransferAssetContract" => (
+                Some(address::normalize(hex_field("to_address"))?),
+                u64_field("amount"),
+                Some(Self::decode_asset_name(hex_field("asset_name"))),
+                String::new(),
+            ),
+            "TriggerSmartContract" => (
+                Some(address::normalize(hex_field("contract_address"))?),
+                u64_field("call_value"),
+                None,
+                hex_field("data").to_string(),
+            ),
+            _ => (None, 0, None, String::new()),
+        };
+
+        let status = tx_data
+            .get("ret")
+            .and_then(|r| r.as_array())
+            .and_then(|r| r.first())
+            .and_then(|r| r.get("contractRet"))
+            .and_then(|r| r.as_str())
+            .unwrap_or("SUCCESS")
+            .to_string();
+
+        let block_timestamp_ms = raw_data
+            .get("timestamp")
+            .and_then(|t| t.as_u64())
+            .unwrap_or(block_header.timestamp);
+
+        Ok(RawTransaction {
+            network: block_header.network.clone(),
+            subnet: block_header.subnet.clone(),
+            vm_type: block_header.vm_type.clone(),
+            transaction_hash: transaction_hash.to_string(),
+            block_number: block_header.block_number,
+            block_hash: block_header.block_hash.clone(),
+            block_timestamp: Self::normalize_timestamp_secs(block_timestamp_ms),
+            transaction_index: index,
+            contract_type,
+            from_address,
+            to_address,
+            value: amount.to_string(),
+            asset_name,
+            input_data,
+            fee_limit: raw_data
+                .get("fee_limit")
+                .and_then(|f| f.as_u64())
+                .unwrap_or(0),
+            status,
+            signatures: tx_data
+                .get("signature")
+                .and_then(|s| s.as_array())
+                .unwrap_or(&vec![])
+                .iter()
+                .filter_map(|s| s.as_str())
+                .map(|s| s.to_string())
+                .collect(),
+            processed_at: replay_clock::now().to_rfc3339(),
+            processor_id: "tvm-raw-transactions-actor".to_string(),
+        })
+    }
+
+    /// TRC-10 asset names are hex-encoded token ids (e.g. "31303030303031" = "1000001")
+    fn decode_asset_name(hex_name: &str) -> String {
+        hex::decode(hex_name)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .unwrap_or_else(|| hex_name.to_string())
+    }
+
+    /// The API reports milliseconds; newheads may already be in seconds
+    fn normalize_timestamp_secs(timestamp: u64) -> u64 {
+        if timestamp > 10_000_000_000 {
+            timestamp / 1000
+        } else {
+            timestamp
+        }
+    }
+
+    /// Map a contract type into the unified (transaction_type, transaction_subtype) pair
+    fn classify(raw_tx: &RawTransaction, has_trc20: bool) -> (String, Option<String>) {
+        match raw_tx.contract_type.as_str() {
+            "TransferContract" => ("TRANSFER".to_string(), Some("native".to_string())),
+            "TransferAssetContract" => ("TRANSFER".to_string(), Some("trc10".to_string())),
+            "TriggerSmartContract" if has_trc20 => {
+                ("CONTRACT_CALL".to_string(), Some("trc20".to_string()))
+            }
+            "TriggerSmartContract" => ("CONTRACT_CALL".to_string(), None),
+            "CreateSmartContract" => ("CONTRACT_CREATE".to_string(), None),
+            // Staking, voting, account management and other system contracts
+            other => ("SYSTEM".to_string(), Some(other.to_string())),
+        }
+    }
+
+    /// Extract TRC-20 transfers, preferring logs over calldata
+    fn extract_trc20_transfers(
+        raw_tx: &RawTransaction,
+        info: &TransactionInfo,
+    ) -> Vec<Trc20Transfer> {
+        let from_logs = trc20::decode_transfer_logs(&info.log);
+        if !from_logs.is_empty() || raw_tx.contract_type != "TriggerSmartContract" {
+            return from_logs;
+        }
+
+        // No logs (e.g. pruned info or failed execution): fall back to calldata
+        let (Ok(owner), Some(Ok(contract))) = (
+            address::base58_to_hex(&raw_tx.from_address),
+            raw_tx.to_address.as_deref().map(address::base58_to_hex),
+        ) else {
+            return Vec::new();
+        };
+
+        trc20::decode_transfer_call(&owner, &contract, &raw_tx.input_data)
+            .filter(|_| raw_tx.status == "SUCCESS")
+            .into_iter()
+            .collect()
+    }
+
+    fn block_date(block_timestamp: u64) -> String {
+        Utc.timestamp_opt(block_timestamp as i64, 0)
+            .single()
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "1970-01-01".to_string())
+    }
+
+    fn chain_id(raw_tx: &RawTransaction) -> String {
+        format!(
+            "{}_{}",
+            raw_tx.network.to_lowercase(),
+            raw_tx.subnet.to_lowercase()
+        )
+    }
+
+    /// Build the unified transaction record with energy/bandwidth mapped to fee fields
+    fn build_ducklake_transaction_record(
+        raw_tx: &RawTransaction,
+        fees: &FeeBreakdown,
+        has_trc20: bool,
+    ) -> DuckLakeTransactionRecord {
+        let (transaction_type, transaction_subtype) = Self::classify(raw_tx, has_trc20);
+
+        let input_data = if raw_tx.input_data.is_empty() {
+            None
+        } else {
+            Some(format!("0x{}", raw_tx.input_data.trim_start_matches("0x")))
+        };
+        let method_signature = input_data
+            .as_ref()
+            .filter(|data| data.len() >= 10)
+            .map(|data| data[..10].to_string());
+
+        // TRC-10 amounts are in asset units, not sun
+        let amount_native = if raw_tx.asset_name.is_none() {
+            raw_tx
+                .value
+                .parse::<u64>()
+                .ok()
+                .map(|v| v as f64 / SUN_PER_TRX)
+        } else {
+            None
+        };
+
+        DuckLakeTransactionRecord {
+            chain_id: Self::chain_id(raw_tx),
+            block_date: Self::block_date(raw_tx.block_timestamp),
+            network: raw_tx.network.clone(),
+            subnet: raw_tx.subnet.clone(),
+            vm_type: raw_tx.vm_type.clone(),
+            block_number: raw_tx.block_number,
+            block_timestamp: raw_tx.block_timestamp,
+            transaction_hash: raw_tx.transaction_hash.clone(),
+            transaction_index: raw_tx.transaction_index,
+            from_address: Some(raw_tx.from_address.clone()).filter(|a| !a.is_empty()),
+            to_address: raw_tx.to_address.clone(),
+            value: Some(raw_tx.value.clone()),
+            gas_limit: Some(raw_tx.fee_limit),
+            gas_used: Some(fees.energy_used),
+            gas_price: Some(fees.energy_price_sun.to_string()),
+            status: if raw_tx.status == "SUCCESS" {
+                "SUCCESS".to_string()
+            } else {
+                "FAILED".to_string()
+            },
+            transaction_fee: Some(fees.total_fee_sun.to_string()),
+            effective_gas_price: Some(fees.effective_energy_price_sun.to_string()),
+            input_data,
+            method_signature,
+            transaction_type,
+            transaction_subtype,
+            amount_native,
+            processor_id: Some(raw_tx.processor_id.clone()),
+            correlation_id: Some(raw_tx.transaction_hash.clone()),
+        }
+    }
+
+    fn build_token_transfer_records(
+        raw_tx: &RawTransaction,
+        transfers: &[Trc20Transfer],
+    ) -> Vec<DuckLakeTokenTransferRecord> {
+        transfers
+            .iter()
+            .enumerate()
+            .map(|(position, transfer)| {
+                let metadata = Self::lookup_token_metadata(&raw_tx.subnet, &transfer.token_address);
+                Self::build_token_transfer_record(raw_tx, transfer, position as u32, metadata)
+            })
+            .collect()
+    }
+
+    fn build_token_transfer_record(
+        raw_tx: &RawTransaction,
+        transfer: &Trc20Transfer,
+        position: u32,
+        metadata: Option<TokenMetadata>,
+    ) -> DuckLakeTokenTransferRecord {
+        // Without decimals the raw amount is stored as-is
+        let amount = match &metadata {
+            Some(meta) => trc20::format_units(&transfer.amount, meta.decimals),
+            None => transfer.amount.clone(),
+        };
+
+        DuckLakeTokenTransferRecord {
+            chain_id: Self::chain_id(raw_tx),
+            block_date: Self::block_date(raw_tx.block_timestamp),
+            block_number: raw_tx.block_number,
+            block_timestamp: raw_tx.block_timestamp,
+            transaction_hash: raw_tx.transaction_hash.clone(),
+            log_index: transfer.log_index.unwrap_or(position),
+            token_address: transfer.token_address.clone(),
+            token_type: "TRC20".to_string(),
+            token_symbol: metadata.as_ref().map(|m| m.symbol.clone()),
+            token_name: metadata.as_ref().and_then(|m| m.name.clone()),
+            token_decimals: metadata.as_ref().map(|m| m.decimals),
+            from_address: transfer.from_address.clone(),
+            to_address: transfer.to_address.clone(),
+            amount,
+        }
+    }
+
+    fn build_address_transaction_records(
+        record: &DuckLakeTransactionRecord,
+    ) -> Vec<DuckLakeAddressTransactionRecord> {
+        let (Some(from), Some(to)) = (&record.from_address, &record.to_address) else {
+            return Vec::new();
+        };
+
+        [(from, to, true), (to, from, false)]
+            .into_iter()
+            .map(
+                |(address, counterparty, is_sender)| DuckLakeAddressTransactionRecord {
+                    chain_id: record.chain_id.clone(),
+                    block_date: record.block_date.clone(),
+                    address: address.clone(),
+                    transaction_hash: record.transaction_hash.clone(),
+                    block_number: record.block_number,
+                    block_timestamp: record.block_timestamp,
+                    is_sender,
+                    counterparty_address: Some(counterparty.clone()),
+                    value: record.value.clone(),
+                    transaction_type: Some(record.transaction_type.clone()),
+                    transaction_subtype: record.transaction_subtype.clone(),
+                },
+            )
+            .collect()
+    }
+
+    /// Publish the raw transaction and its DuckLake records to NATS
+    fn publish_transaction(
+        raw_tx: &RawTransaction,
+        info: &TransactionInfo,
+        prices: ResourcePrices,
+    ) -> Result<(), String> {
+        let tx_payload = serde_json::to_vec(raw_tx)
+            .map_err(|e| format!("Failed to serialize transaction: {}", e))?;
+        Self::publish_message("transactions.raw.tvm", tx_payload)?;
+
+        let fees = fees::model_fees(&info.receipt, info.fee, prices);
+        let transfers = Self::extract_trc20_transfers(raw_tx, info);
+
+        let record = Self::build_ducklake_transaction_record(raw_tx, &fees, !transfers.is_empty());
+        let payload = serde_json::to_vec(&record)
+            .map_err(|e| format!("Failed to serialize ducklake transaction: {}", e))?;
+        Self::publish_message(
+            &format!(
+                "ducklake.transactions.{}.{}.write",
+                raw_tx.network, raw_tx.subnet
+            ),
+            payload,
+        )?;
+
+        let transfer_subject = format!(
+            "ducklake.token_transfers.{}.{}.write",
+            raw_tx.network, raw_tx.subnet
+        );
+        for transfer in Self::build_token_transfer_records(raw_tx, &transfers) {
+            let payload = serde_json::to_vec(&transfer)
+                .map_err(|e| format!("Failed to serialize token transfer: {}", e))?;
+            Self::publish_message(&transfer_subject, payload)?;
+        }
+
+        let address_subject = format!(
+            "ducklake.address_transactions.{}.{}.write",
+            raw_tx.network, raw_tx.subnet
+        );
+        for address_record in Self::build_address_transaction_records(&record) {
+            let payload = serde_json::to_vec(&address_record)
+                .map_err(|e| format!("Failed to serialize address transaction: {}", e))?;
+            Self::publish_message(&address_subject, payload)?;
+        }
+
+        Ok(())
+    }
+
+    fn publish_message(subject: &str, body: Vec<u8>) -> Result<(), String> {
+        if let Err(violation) = subject_acl::authorize(ACTOR_ID, subject) {
+            let _ = consumer::publish(&types::BrokerMessage {
+                subject: subject_registry::prefixed(subject_acl::ACL_VIOLATIONS_SUBJECT),
+                body: violation.to_json(),
+                reply_to: None,
+            });
+            return Err(violation.to_string());
+        }
+        let msg = types::BrokerMessage {
+            subject: subject_registry::prefixed(&feature_flags::dry_run::route(subject)),
+            body,
+            reply_to: None,
+        };
+
+        consumer::publish(&msg).map_err(|e| format!("Failed to publish to {}: {:?}", subject, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_header() -> BlockHeader {
+        BlockHeader {
+            network: "tron".to_string(),
+            subnet: "mainnet".to_string(),
+            vm_type: "tvm".to_string(),
+            chain_id: "tron-mainnet".to_string(),
+            chain_name: "Tron Mainnet".to_string(),
+            block_number: 62_000_000,
+            block_hash: "0000000003b20b80a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718"
+                .to_string(),
+            parent_hash: "0000000003b20b7f".to_string(),
+            timestamp: 1_716_000_000_000,
+            transaction_count: Some(2),
+            received_at: "2024-05-18T02:40:00Z".to_string(),
+            provider_id: "test".to_string(),
+        }
+    }
+
+    fn trx_transfer_json() -> serde_json::Value {
+        serde_json::json!({
+            "txID": "a1b2c3",
+            "ret": [{"contractRet": "SUCCESS"}],
+            "signature": ["deadbeef"],
+            "raw_data": {
+                "contract": [{
+                    "type": "TransferContract",
+                    "parameter": {"value": {
+                        "owner_address": "41e552f6487585c2b58bc2c9bb4492bc1f17132cd0",
+                        "to_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                        "amount": 2_500_000
+                    }}
+                }],
+                "timestamp": 1_716_000_000_000u64
+            }
+        })
+    }
+
+    fn trc20_call_json() -> serde_json::Value {
+        serde_json::json!({
+            "txID": "d4e5f6",
+            "ret": [{"contractRet": "SUCCESS"}],
+            "raw_data": {
+                "contract": [{
+                    "type": "TriggerSmartContract",
+                    "parameter": {"value": {
+                        "owner_address": "41e552f6487585c2b58bc2c9bb4492bc1f17132cd0",
+                        "contract_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                        "data": format!(
+                            "a9059cbb000000000000000000000000a614f803b6fd780986a42c78ec9c7f77e6ded13c{:0>64}",
+                            "5f5e100"
+                        )
+                    }}
+                }],
+                "fee_limit": 100_000_000,
+                "timestamp": 1_716_000_000_000u64
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_trx_transfer() {
+        let tx = Component::parse_transaction(&trx_transfer_json(), &test_header(), 0).unwrap();
+
+        assert_eq!(tx.contract_type, "TransferContract");
+        assert!(tx.from_address.starts_with('T'));
+        assert_eq!(
+            tx.to_address.as_deref(),
+            Some("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t")
+        );
+        assert_eq!(tx.value, "2500000");
+        assert_eq!(tx.block_timestamp, 1_716_000_000);
+        assert_eq!(tx.signatures, vec!["deadbeef".to_string()]);
+    }
+
+    #[test]
+    fn test_trx_transfer_record_maps_bandwidth_fee() {
+        let tx = Component::parse_transaction(&trx_transfer_json(), &test_header(), 0).unwrap();
+        let receipt = ResourceReceipt {
+            net_fee: 268_000,
+            ..Default::default()
+        };
+        let fees = fees::model_fees(&receipt, 268_000, ResourcePrices::default());
+        let record = Component::build_ducklake_transaction_record(&tx, &fees, false);
+
+        assert_eq!(record.chain_id, "tron_mainnet");
+        assert_eq!(record.vm_type, "tvm");
+        assert_eq!(record.transaction_type, "TRANSFER");
+        assert_eq!(record.transaction_subtype.as_deref(), Some("native"));
+        assert_eq!(record.transaction_fee.as_deref(), Some("268000"));
+        assert_eq!(record.gas_used, Some(0));
+        assert_eq!(record.amount_native, Some(2.5));
+        assert_eq!(record.block_date, "2024-05-18");
+        assert_eq!(record.input_data, None);
+    }
+
+    #[test]
+    fn test_trc20_call_falls_back_to_calldata() {
+        let tx = Component::parse_transaction(&trc20_call_json(), &test_header(), 1).unwrap();
+        let transfers = Component::extract_trc20_transfers(&tx, &TransactionInfo::default());
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].amount, "100000000");
+        assert_eq!(
+            transfers[0].token_address,
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+        );
+
+        let fees = fees::model_fees(
+            &ResourceReceipt {
+                energy_fee: 27_255_900,
+                energy_usage_total: 64_895,
+                net_fee: 345_000,
+                ..Default::default()
+            },
+            27_600_900,
+            ResourcePrices::default(),
+        );
+        let record = Component::build_ducklake_transaction_record(&tx, &fees, true);
+        assert_eq!(record.transaction_type, "CONTRACT_CALL");
+        assert_eq!(record.transaction_subtype.as_deref(), Some("trc20"));
+        assert_eq!(record.gas_limit, Some(100_000_000));
+        assert_eq!(record.gas_used, Some(64_895));
+        assert_eq!(record.gas_price.as_deref(), Some("420"));
+        assert_eq!(record.method_signature.as_deref(), Some("0xa9059cbb"));
+    }
+
+    #[test]
+    fn test_token_transfer_record_formats_with_metadata() {
+        let tx = Component::parse_transaction(&trc20_call_json(), &test_header(), 1).unwrap();
+        let transfers = Component::extract_trc20_transfers(&tx, &TransactionInfo::default());
+        let metadata = trc20::known_token("mainnet", &transfers[0].token_address);
+
+        let record = Component::build_token_transfer_record(&tx, &transfers[0], 0, metadata);
+        assert_eq!(record.token_type, "TRC20");
+        assert_eq!(record.token_symbol.as_deref(), Some("USDT"));
+        assert_eq!(record.amount, "100.000000");
+
+        let raw = Component::build_token_transfer_record(&tx, &transfers[0], 0, None);
+        assert_eq!(raw.amount, "100000000");
+        assert_eq!(raw.token_decimals, None);
+    }
+
+    #[test]
+    fn test_system_contracts_classified() {
+        let mut tx = Component::parse_transaction(&trx_transfer_json(), &test_header(), 0).unwrap();
+        tx.contract_type = "FreezeBalanceV2Contract".to_string();
+        let (tx_type, subtype) = Component::classify(&tx, false);
+        assert_eq!(tx_type, "SYSTEM");
+        assert_eq!(subtype.as_deref(), Some("FreezeBalanceV2Contract"));
+    }
+
+    #[test]
+    fn test_address_transaction_records() {
+        let tx = Component::parse_transaction(&trx_transfer_json(), &test_header(), 0).unwrap();
+        let fees = fees::model_fees(&ResourceReceipt::default(), 0, ResourcePrices::default());
+        let record = Component::build_ducklake_transaction_record(&tx, &fees, false);
+
+        let rows = Component::build_address_transaction_records(&record);
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].is_sender);
+        assert_eq!(rows[1].address, "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+    }
+
+    #[test]
+    fn test_decode_asset_name() {
+        assert_eq!(Component::decode_asset_name("31303030303031"), "1000001");
+    }
+
+    #[test]
+    fn test_network_config_resource_prices() {
+        let config: NetworkConfig = serde_json::from_value(serde_json::json!({
+            "rpc_urls": ["https://api.trongrid.io"],
+            "energy_price_sun": 210,
+            "enabled": true
+        }))
+        .unwrap();
+
+        let prices = config.resource_prices();
+        assert_eq!(prices.energy_price_sun, 210);
+        assert_eq!(
+            prices.bandwidth_price_sun,
+            fees::DEFAULT_BANDWIDTH_PRICE_SUN
+        );
+    }
+}
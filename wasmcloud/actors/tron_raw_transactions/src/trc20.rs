@@ -0,0 +1,274 @@
+//! TRC-20 transfer extraction and enrichment
+//!
+//! TRC-20 is ABI-compatible with ERC-20, so transfers are recovered either from
+//! `Transfer(address,address,uint256)` logs in the transaction info or, when logs
+//! are unavailable, from `transfer`/`transferFrom` calldata on a
+//! `TriggerSmartContract`. Addresses are emitted in base58check.
+
+use serde::{Deserialize, Serialize};
+
+use crate::address;
+
+/// `transfer(address,uint256)` selector
+pub const TRANSFER_SELECTOR: &str = "a9059cbb";
+
+/// `transferFrom(address,address,uint256)` selector
+pub const TRANSFER_FROM_SELECTOR: &str = "23b872dd";
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const TRANSFER_EVENT_TOPIC: &str =
+    "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// Static metadata for a TRC-20 token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub decimals: u32,
+}
+
+/// A decoded TRC-20 transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trc20Transfer {
+    pub token_address: String,
+    pub from_address: String,
+    pub to_address: String,
+    /// Raw amount in the token's smallest unit (decimal string)
+    pub amount: String,
+    /// Position of the log within the transaction (None when decoded from calldata)
+    pub log_index: Option<u32>,
+}
+
+impl Trc20Transfer {
+    /// Human-readable amount using the token's decimals
+    pub fn formatted_amount(&self, decimals: u32) -> f64 {
+        let raw = self.amount.parse::<f64>().unwrap_or(0.0);
+        raw / 10f64.powi(decimals as i32)
+    }
+}
+
+/// A single log entry from `gettransactioninfobyblocknum`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TronLog {
+    /// Emitting contract as a 20-byte hex hash (no `41` prefix)
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub data: String,
+}
+
+/// Built-in metadata for well-known mainnet tokens; anything else is looked up
+/// from the `tokens:tron:{subnet}:{address}` key in Redis by the actor.
+pub fn known_token(subnet: &str, token_address: &str) -> Option<TokenMetadata> {
+    let (symbol, name, decimals) = match (subnet, token_address) {
+        ("mainnet", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t") => ("USDT", "Tether USD", 6),
+        ("mainnet", "TEkxiTehnzSmSe2XqrBj4w32RUN966rdz8") => ("USDC", "USD Coin", 6),
+        ("mainnet", "TNUC9Qb1rRpS5CbWLmNMxXBjyFoydXjWFR") => ("WTRX", "Wrapped TRX", 6),
+        _ => return None,
+    };
+    Some(TokenMetadata {
+        symbol: symbol.to_string(),
+        name: Some(name.to_string()),
+        decimals,
+    })
+}
+
+/// Decode TRC-20 transfers from transaction logs
+pub fn decode_transfer_logs(logs: &[TronLog]) -> Vec<Trc20Transfer> {
+    logs.iter()
+        .enumerate()
+        .filter_map(|(index, log)| {
+            if log.topics.len() != 3
+                || log.topics[0].trim_start_matches("0x").to_lowercase() != TRANSFER_EVENT_TOPIC
+            {
+                // 4 topics means ERC-721 style (indexed tokenId); not a TRC-20 transfer
+                return None;
+            }
+
+            Some(Trc20Transfer {
+                token_address: address::hex_to_base58(&log.address).ok()?,
+                from_address: address::abi_word_to_base58(&log.topics[1]).ok()?,
+                to_address: address::abi_word_to_base58(&log.topics[2]).ok()?,
+                amount: hex_word_to_decimal(&log.data)?,
+                log_index: Some(index as u32),
+            })
+        })
+        .collect()
+}
+
+/// Decode a TRC-20 transfer from `TriggerSmartContract` calldata
+///
+/// `owner_address` and `contract_address` are in the API's hex format.
+pub fn decode_transfer_call(
+    owner_address: &str,
+    contract_address: &str,
+    data: &str,
+) -> Option<Trc20Transfer> {
+    let data = data.trim_start_matches("0x").to_lowercase();
+    if data.len() < 8 {
+        return None;
+    }
+    let (selector, args) = data.split_at(8);
+    let word = |i: usize| args.get(i * 64..(i + 1) * 64);
+
+    let (from, to, amount) = match selector {
+        TRANSFER_SELECTOR => (
+            address::hex_to_base58(owner_address).ok()?,
+            address::abi_word_to_base58(word(0)?).ok()?,
+            hex_word_to_decimal(word(1)?)?,
+        ),
+        TRANSFER_FROM_SELECTOR => (
+            address::abi_word_to_base58(word(0)?).ok()?,
+            address::abi_word_to_base58(word(1)?).ok()?,
+            hex_word_to_decimal(word(2)?)?,
+        ),
+        _ => return None,
+    };
+
+    Some(Trc20Transfer {
+        token_address: address::hex_to_base58(contract_address).ok()?,
+        from_address: from,
+        to_address: to,
+        amount,
+        log_index: None,
+    })
+}
+
+/// Format a raw decimal amount with the token's decimals without going through f64
+pub fn format_units(raw_amount: &str, decimals: u32) -> String {
+    let digits = raw_amount.trim_start_matches('0');
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return if digits.is_empty() {
+            "0".to_string()
+        } else {
+            digits.to_string()
+        };
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    format!("{}.{}", whole, fraction)
+}
+
+/// Convert a big-endian hex word (up to 256 bits) into a decimal string
+pub fn hex_word_to_decimal(word: &str) -> Option<String> {
+    let cleaned = word.trim_start_matches("0x");
+    if cleaned.is_empty() {
+        return Some("0".to_string());
+    }
+    let bytes = hex::decode(if cleaned.len() % 2 == 1 {
+        format!("0{}", cleaned)
+    } else {
+        cleaned.to_string()
+    })
+    .ok()?;
+
+    // Repeated division by 10 over the big-endian byte buffer
+    let mut value: Vec<u8> = bytes.into_iter().skip_while(|b| *b == 0).collect();
+    if value.is_empty() {
+        return Some("0".to_string());
+    }
+
+    let mut digits = Vec::new();
+    while !value.is_empty() {
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let acc = (remainder << 8) | *byte as u32;
+            *byte = (acc / 10) as u8;
+            remainder = acc % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        let leading = value.iter().take_while(|b| **b == 0).count();
+        value.drain(..leading);
+    }
+
+    digits.reverse();
+    String::from_utf8(digits).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDT_HEX: &str = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+    const OWNER_HEX: &str = "41e552f6487585c2b58bc2c9bb4492bc1f17132cd0";
+    const RECIPIENT_WORD: &str = "000000000000000000000000a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+
+    #[test]
+    fn test_hex_word_to_decimal() {
+        assert_eq!(hex_word_to_decimal("0x0").as_deref(), Some("0"));
+        assert_eq!(hex_word_to_decimal("0x").as_deref(), Some("0"));
+        assert_eq!(hex_word_to_decimal("0xf4240").as_deref(), Some("1000000"));
+        assert_eq!(
+            hex_word_to_decimal(&"f".repeat(64)).as_deref(),
+            Some("115792089237316195423570985008687907853269984665640564039457584007913129639935")
+        );
+        assert!(hex_word_to_decimal("0xzz").is_none());
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units("100000000", 6), "100.000000");
+        assert_eq!(format_units("1", 6), "0.000001");
+        assert_eq!(format_units("0", 6), "0.000000");
+        assert_eq!(format_units("42", 0), "42");
+    }
+
+    #[test]
+    fn test_decode_transfer_call() {
+        let data = format!("a9059cbb{}{:0>64}", RECIPIENT_WORD, "5f5e100");
+        let transfer = decode_transfer_call(OWNER_HEX, USDT_HEX, &data).expect("transfer");
+
+        assert_eq!(transfer.token_address, "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert_eq!(transfer.to_address, "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert_eq!(transfer.amount, "100000000");
+        assert_eq!(transfer.log_index, None);
+        assert!((transfer.formatted_amount(6) - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decode_transfer_call_ignores_other_selectors() {
+        let data = format!("095ea7b3{}{:0>64}", RECIPIENT_WORD, "1");
+        assert!(decode_transfer_call(OWNER_HEX, USDT_HEX, &data).is_none());
+        assert!(decode_transfer_call(OWNER_HEX, USDT_HEX, "a9059cbb").is_none());
+    }
+
+    #[test]
+    fn test_decode_transfer_logs() {
+        let logs = vec![
+            TronLog {
+                address: "a614f803b6fd780986a42c78ec9c7f77e6ded13c".to_string(),
+                topics: vec![
+                    TRANSFER_EVENT_TOPIC.to_string(),
+                    format!("{:0>64}", "e552f6487585c2b58bc2c9bb4492bc1f17132cd0"),
+                    RECIPIENT_WORD.to_string(),
+                ],
+                data: format!("{:0>64}", "f4240"),
+            },
+            TronLog {
+                address: "a614f803b6fd780986a42c78ec9c7f77e6ded13c".to_string(),
+                topics: vec![
+                    "8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925".to_string(),
+                ],
+                data: String::new(),
+            },
+        ];
+
+        let transfers = decode_transfer_logs(&logs);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].amount, "1000000");
+        assert_eq!(transfers[0].log_index, Some(0));
+        assert!(transfers[0].from_address.starts_with('T'));
+    }
+
+    #[test]
+    fn test_known_token() {
+        let usdt = known_token("mainnet", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t").unwrap();
+        assert_eq!(usdt.symbol, "USDT");
+        assert_eq!(usdt.decimals, 6);
+        assert!(known_token("nile", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t").is_none());
+    }
+}
@@ -17,6 +17,9 @@ ACTORS=(
     "abi-decoder"
     "alerts-processor"
     "btc_raw_transactions"
+    "chain-onboarding"
+    "consistency-checker"
+    "entity_activity_aggregator"
     "evm_logs_ingestion"
     "eth_contract_creation_processor"
     "eth_contract_transaction_processor"
@@ -25,9 +28,12 @@ ACTORS=(
     "eth_transfers_processor"
     "health-check"
     "notification-router"
+    "price-backfill"
     "sol_raw_transactions"
+    "state-rebuild"
     "transaction-ducklake-writer"
     "transaction-processor"
+    "tron_raw_transactions"
 )
 
 # Build all actors
@@ -36,6 +42,9 @@ cargo build --release --target wasm32-wasip1 \
     -p abi-decoder \
     -p alerts-processor \
     -p btc_raw_transactions \
+    -p chain-onboarding \
+    -p consistency-checker \
+    -p entity_activity_aggregator \
     -p evm_logs_ingestion \
     -p eth_contract_creation_processor \
     -p eth_contract_transaction_processor \
@@ -44,9 +53,12 @@ cargo build --release --target wasm32-wasip1 \
     -p eth_transfers_processor \
     -p health-check \
     -p notification-router \
+    -p price-backfill \
     -p sol_raw_transactions \
+    -p state-rebuild \
     -p transaction-ducklake-writer \
-    -p transaction-processor
+    -p transaction-processor \
+    -p tron_raw_transactions
 
 echo "Copying WASM binaries to actor directories..."
 
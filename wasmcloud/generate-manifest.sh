@@ -34,6 +34,13 @@ RESEND_API_KEY="${RESEND_API_KEY:-}"
 TELEGRAM_WEBHOOK_PORT="${TELEGRAM_WEBHOOK_PORT:-8081}"
 NEWHEADS_ENABLED="${NEWHEADS_ENABLED:-false}"
 
+# Environment subject prefix (e.g. "staging.") for sharing one NATS cluster.
+# Actors must be built with the same EKKO_SUBJECT_PREFIX.
+SUBJECT_PREFIX="${SUBJECT_PREFIX:-${EKKO_SUBJECT_PREFIX:-}}"
+if [ -n "$SUBJECT_PREFIX" ] && [ "${SUBJECT_PREFIX%.}" = "$SUBJECT_PREFIX" ]; then
+    SUBJECT_PREFIX="${SUBJECT_PREFIX}."
+fi
+
 # S3/MinIO configuration
 # For development, MinIO runs on host via docker-compose (ekko-minio-dev)
 # From inside K8s, use host.docker.internal to reach host services
@@ -86,6 +93,7 @@ echo "  NATS URL:          ${NATS_URL}"
 echo "  Resend API Key:    ${RESEND_API_KEY:+set}"
 echo "  Telegram Port:     ${TELEGRAM_WEBHOOK_PORT}"
 echo "  Newheads Enabled:  ${NEWHEADS_ENABLED}"
+echo "  Subject Prefix:    ${SUBJECT_PREFIX:-(none)}"
 echo "  S3 Endpoint:       ${S3_ENDPOINT}"
 echo "  S3 Bucket:         ${S3_BUCKET}"
 echo "  S3 Access Key:     ${S3_ACCESS_KEY_ID}"
@@ -124,6 +132,7 @@ if command -v envsubst &> /dev/null; then
     export RESEND_API_KEY
     export TELEGRAM_WEBHOOK_PORT
     export NEWHEADS_ENABLED
+    export SUBJECT_PREFIX
     export S3_ENDPOINT
     export S3_BUCKET
     export S3_ACCESS_KEY_ID
@@ -153,6 +162,7 @@ else
         -e "s|\${REDIS_URL}|${REDIS_URL}|g" \
         -e "s|\${NATS_URL}|${NATS_URL}|g" \
         -e "s|\${NEWHEADS_ENABLED}|${NEWHEADS_ENABLED}|g" \
+        -e "s|\${SUBJECT_PREFIX}|${SUBJECT_PREFIX}|g" \
         -e "s|\${S3_ENDPOINT}|${S3_ENDPOINT}|g" \
         -e "s|\${S3_BUCKET}|${S3_BUCKET}|g" \
         -e "s|\${S3_ACCESS_KEY_ID}|${S3_ACCESS_KEY_ID}|g" \
@@ -4,7 +4,7 @@
 //! This binary is deployed to wasmCloud and handles actor invocations.
 
 use anyhow::{Context, Result};
-use tracing::info;
+use tracing::{error, info};
 use wasmcloud_provider_sdk::{load_host_data, run_provider};
 
 use abi_decoder_provider::AbiDecoderProvider;
@@ -28,11 +28,37 @@ async fn main() -> Result<()> {
     info!("Provider ID: {}", host_data.provider_key);
     info!("Lattice RPC URL: {}", host_data.lattice_rpc_url);
 
+    let subject_prefix = subject_registry::SubjectPrefix::from_properties(&host_data.config)
+        .and_then(subject_registry::install_prefix)
+        .map_err(anyhow::Error::msg)?;
+    info!("Subject prefix: {}", subject_prefix);
+
     // Create provider instance
     let provider = AbiDecoderProvider::from_host_data(host_data.clone())
         .await
         .context("Failed to create ABI Decoder provider")?;
 
+    // Keep the hot cache in step with ABI registry changes
+    let invalidator = provider.cache_invalidator();
+    let nats_url =
+        std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+    let invalidation_nats_url = nats_url.clone();
+    tokio::spawn(async move {
+        if let Err(e) = invalidator.run(&invalidation_nats_url).await {
+            error!("ABI cache invalidation listener stopped: {}", e);
+        }
+    });
+
+    // Seed selector signatures and answer abi.selector.lookup
+    let selectors = provider
+        .selector_directory()
+        .context("Failed to create selector directory")?;
+    tokio::spawn(async move {
+        if let Err(e) = selectors.serve(&nats_url).await {
+            error!("Selector lookup service stopped: {}", e);
+        }
+    });
+
     info!("🎯 Provider ready - waiting for actor invocations");
 
     // Run provider (blocks until shutdown signal)
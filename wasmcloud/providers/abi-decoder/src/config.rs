@@ -23,6 +23,9 @@ pub struct AbiDecoderConfig {
     /// DuckLake configuration for ABI storage
     pub ducklake: DuckLakeConfig,
 
+    /// Selector signature directory
+    pub selectors: SelectorConfig,
+
     /// Provider instance configuration
     pub instance_id: String,
 
@@ -101,6 +104,33 @@ pub struct DuckLakeConfig {
     pub base_path: String,
 }
 
+/// Selector signature directory configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectorConfig {
+    /// JSON array of text signatures seeded into Redis at startup
+    pub seed_file: Option<String>,
+    /// Ask openchain and 4byte.directory for selectors missing from Redis
+    pub remote_lookup: bool,
+    /// openchain signature lookup URL
+    pub openchain_url: String,
+    /// 4byte.directory signatures URL
+    pub fourbyte_url: String,
+    /// Request timeout in seconds
+    pub timeout_secs: u64,
+}
+
+impl Default for SelectorConfig {
+    fn default() -> Self {
+        Self {
+            seed_file: None,
+            remote_lookup: true,
+            openchain_url: "https://api.openchain.xyz/signature-database/v1/lookup".to_string(),
+            fourbyte_url: "https://www.4byte.directory/api/v1/signatures/".to_string(),
+            timeout_secs: 10,
+        }
+    }
+}
+
 impl AbiDecoderConfig {
     /// Load configuration from environment variables
     pub fn from_env() -> Result<Self> {
@@ -210,6 +240,23 @@ impl AbiDecoderConfig {
                 .unwrap_or_else(|_| "s3://ekko-ducklake/abis".to_string()),
         };
 
+        let selector_defaults = SelectorConfig::default();
+        let selectors = SelectorConfig {
+            seed_file: env::var("ABI_DECODER_SELECTOR_SEED_FILE").ok(),
+            remote_lookup: env::var("ABI_DECODER_SELECTOR_REMOTE_LOOKUP")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            openchain_url: env::var("ABI_DECODER_OPENCHAIN_URL")
+                .unwrap_or(selector_defaults.openchain_url),
+            fourbyte_url: env::var("ABI_DECODER_FOURBYTE_URL")
+                .unwrap_or(selector_defaults.fourbyte_url),
+            timeout_secs: env::var("ABI_DECODER_SELECTOR_TIMEOUT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+        };
+
         let instance_id = env::var("ABI_DECODER_INSTANCE_ID")
             .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
 
@@ -224,6 +271,7 @@ impl AbiDecoderConfig {
             api_sources,
             rate_limiting,
             ducklake,
+            selectors,
             instance_id,
             enable_metrics,
         })
@@ -308,6 +356,7 @@ impl Default for AbiDecoderConfig {
                 s3_secret_access_key: "minioadmin".to_string(),
                 base_path: "s3://ekko-ducklake/abis".to_string(),
             },
+            selectors: SelectorConfig::default(),
             instance_id: uuid::Uuid::new_v4().to_string(),
             enable_metrics: false,
         }
@@ -0,0 +1,158 @@
+//! Hot cache invalidation from ABI registry changes
+//!
+//! Listens on `cache.invalidate.abi` and evicts the listed `{network}:{contract}`
+//! entries from the in-memory LRU. Redis entries are rewritten by the registry
+//! itself, so only the hot cache can go stale. When the version tracker detects
+//! a missed event the whole hot cache is flushed and the tracker resynced to the
+//! registry's snapshot version in Redis.
+
+use crate::decoder::AbiDecoder;
+use crate::types::AbiInfo;
+
+use anyhow::{Context, Result};
+use cache_invalidation::{InvalidationEvent, InvalidationKind, Observation, VersionTracker};
+use futures::StreamExt;
+use lru::LruCache;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Applies ABI registry invalidations to the decoder's hot cache
+pub struct AbiCacheInvalidator {
+    hot_cache: Arc<RwLock<LruCache<String, AbiInfo>>>,
+    redis: ConnectionManager,
+    tracker: VersionTracker,
+}
+
+impl AbiCacheInvalidator {
+    pub fn new(decoder: &AbiDecoder) -> Self {
+        Self {
+            hot_cache: decoder.hot_cache.clone(),
+            redis: decoder.redis.clone(),
+            tracker: VersionTracker::new(),
+        }
+    }
+
+    /// Subscribe to ABI invalidations and apply them until the subscription ends
+    pub async fn run(mut self, nats_url: &str) -> Result<()> {
+        let client = async_nats::connect(nats_url)
+            .await
+            .context("Failed to connect to NATS for cache invalidation")?;
+        let subject = subject_registry::prefixed(&InvalidationKind::Abi.subject());
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(anyhow::Error::msg)?;
+        let mut subscriber = client
+            .subscribe(subject.clone())
+            .await
+            .context("Failed to subscribe to ABI invalidations")?;
+
+        // Entries cached before subscribing may already be stale
+        self.resync().await?;
+        info!("Listening for ABI cache invalidations on {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            match serde_json::from_slice::<InvalidationEvent>(&message.payload) {
+                Ok(event) => {
+                    if let Err(e) = self.handle_event(&event).await {
+                        warn!("Failed to apply ABI invalidation: {}", e);
+                    }
+                }
+                Err(e) => warn!("Ignoring malformed ABI invalidation: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply one invalidation event
+    pub async fn handle_event(&mut self, event: &InvalidationEvent) -> Result<()> {
+        if event.kind != InvalidationKind::Abi {
+            return Ok(());
+        }
+
+        match self.tracker.observe(event) {
+            Observation::Apply if !event.is_flush() => {
+                let evicted = evict_keys(&self.hot_cache, &event.keys).await;
+                info!(
+                    "Evicted {} hot cache entries for ABI registry v{}",
+                    evicted, event.version
+                );
+            }
+            Observation::Apply => {
+                self.hot_cache.write().await.clear();
+            }
+            Observation::Resync { missed } => {
+                warn!(
+                    "Missed {} ABI invalidations before v{}, resyncing",
+                    missed, event.version
+                );
+                self.resync().await?;
+            }
+            Observation::Stale => {}
+        }
+
+        Ok(())
+    }
+
+    /// Flush the hot cache and adopt the registry's current snapshot version
+    pub async fn resync(&mut self) -> Result<()> {
+        self.hot_cache.write().await.clear();
+
+        let kind = InvalidationKind::Abi;
+        let version: Option<u64> = self
+            .redis
+            .get(kind.version_key())
+            .await
+            .context("Failed to read ABI registry version")?;
+        self.tracker.sync_to(kind, version.unwrap_or(0));
+
+        Ok(())
+    }
+}
+
+/// Remove cache entries matching the keys, ignoring address case
+pub async fn evict_keys(cache: &RwLock<LruCache<String, AbiInfo>>, keys: &[String]) -> usize {
+    let mut cache = cache.write().await;
+    let stale: Vec<String> = cache
+        .iter()
+        .map(|(key, _)| key)
+        .filter(|key| keys.iter().any(|k| k.eq_ignore_ascii_case(key)))
+        .cloned()
+        .collect();
+
+    for key in &stale {
+        cache.pop(key);
+    }
+    stale.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    #[tokio::test]
+    async fn test_evict_keys_ignores_address_case() {
+        let cache = RwLock::new(LruCache::new(NonZeroUsize::new(10).unwrap()));
+        {
+            let mut cache = cache.write().await;
+            for key in ["ethereum:0xAbC", "ethereum:0xdef", "polygon:0xabc"] {
+                cache.put(
+                    key.to_string(),
+                    AbiInfo::new(key.to_string(), "[]".to_string(), "test".to_string(), false),
+                );
+            }
+        }
+
+        let evicted = evict_keys(&cache, &["ethereum:0xabc".to_string()]).await;
+
+        assert_eq!(evicted, 1);
+        let cache = cache.read().await;
+        assert!(!cache.contains("ethereum:0xAbC"));
+        assert!(cache.contains("ethereum:0xdef"));
+        assert!(cache.contains("polygon:0xabc"));
+    }
+}
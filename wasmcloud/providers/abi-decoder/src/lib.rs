@@ -2,13 +2,20 @@
 //!
 //! High-performance EVM ABI decoding using Alloy library.
 //! Provides multi-level caching and external API integration for ABI discovery.
+//! The in-memory hot cache is kept fresh by `cache.invalidate.abi` events.
+//! Selector signatures for contracts without an ABI are served on
+//! `abi.selector.lookup` (see [`selectors`]).
 
 pub mod config;
 pub mod decoder;
+pub mod invalidation;
+pub mod selectors;
 pub mod types;
 
 pub use config::AbiDecoderConfig;
 pub use decoder::AbiDecoder;
+pub use invalidation::AbiCacheInvalidator;
+pub use selectors::SelectorDirectory;
 pub use types::{
     AbiInfo, CacheStats, DecodedFunction, DecodedParameter, DecoderError, DecodingResult,
     DecodingStatus, TransactionInput,
@@ -193,6 +200,16 @@ impl AbiDecoderProvider {
         Ok(self.decoder.get_cache_stats().await)
     }
 
+    /// Invalidation listener sharing this provider's hot cache
+    pub fn cache_invalidator(&self) -> AbiCacheInvalidator {
+        AbiCacheInvalidator::new(&self.decoder)
+    }
+
+    /// Selector directory sharing this provider's Redis connection
+    pub fn selector_directory(&self) -> Result<SelectorDirectory> {
+        SelectorDirectory::new(self.config.selectors.clone(), self.decoder.redis.clone())
+    }
+
     /// Get configuration
     pub fn get_config(&self) -> &AbiDecoderConfig {
         &self.config
@@ -0,0 +1,373 @@
+//! Selector → signature directory
+//!
+//! Contracts without a cached ABI can still be decoded partially when the
+//! 4-byte selector of the call is known: the text signature gives the function
+//! name and parameter types. Signatures are kept in Redis under
+//! `abi_signature:{selector}`, where the abi-decoder actor reads them, and come
+//! from:
+//! - a seed file of text signatures (`ABI_DECODER_SELECTOR_SEED_FILE`, a JSON
+//!   array such as `["transfer(address,uint256)", ...]`), written at startup
+//! - openchain, then 4byte.directory, asked on a Redis miss; answers are
+//!   written back so each selector is fetched once
+//!
+//! Lookups are served on `abi.selector.lookup`: the request is
+//! `{"selectors": ["0xa9059cbb"]}` and the reply maps every selector to its
+//! candidate signatures, best first (empty when unknown). Colliding selectors
+//! have several candidates; openchain's filtered (spam) entries are dropped
+//! and 4byte.directory's oldest entry is taken first. Only the best candidate
+//! is stored.
+
+use crate::config::SelectorConfig;
+
+use alloy_primitives::keccak256;
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Request/reply subject of selector lookups
+pub const SELECTOR_LOOKUP_SUBJECT: &str = "abi.selector.lookup";
+
+/// Selectors to resolve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectorLookupRequest {
+    pub selectors: Vec<String>,
+}
+
+/// Candidate signatures per selector, best first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SelectorLookupResponse {
+    pub signatures: HashMap<String, Vec<String>>,
+}
+
+/// Resolves selectors from Redis and the public signature databases
+pub struct SelectorDirectory {
+    config: SelectorConfig,
+    redis: ConnectionManager,
+    http: reqwest::Client,
+}
+
+impl SelectorDirectory {
+    pub fn new(config: SelectorConfig, redis: ConnectionManager) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("Failed to build selector lookup HTTP client")?;
+        Ok(Self {
+            config,
+            redis,
+            http,
+        })
+    }
+
+    /// Write the seed file's signatures to Redis; returns how many were written
+    pub async fn seed(&self) -> Result<usize> {
+        let Some(path) = &self.config.seed_file else {
+            return Ok(0);
+        };
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read selector seed file {}", path))?;
+        let signatures: Vec<String> = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid selector seed file {}", path))?;
+
+        let entries = seed_entries(&signatures);
+        let mut redis = self.redis.clone();
+        for (selector, signature) in &entries {
+            // Seeding never replaces a signature already chosen
+            let _: bool = redis
+                .set_nx(signature_key(selector), signature)
+                .await
+                .context("Failed to seed selector signature")?;
+        }
+        info!("Seeded {} selector signatures from {}", entries.len(), path);
+        Ok(entries.len())
+    }
+
+    /// Candidate signatures of every requested selector
+    pub async fn lookup(&self, selectors: &[String]) -> SelectorLookupResponse {
+        let mut response = SelectorLookupResponse::default();
+        let mut missing = Vec::new();
+        let mut redis = self.redis.clone();
+
+        for selector in selectors {
+            let Some(selector) = normalize_selector(selector) else {
+                continue;
+            };
+            let stored: Option<String> = redis
+                .get(signature_key(&selector))
+                .await
+                .unwrap_or_default();
+            match stored {
+                Some(signature) => {
+                    response.signatures.insert(selector, vec![signature]);
+                }
+                None => missing.push(selector),
+            }
+        }
+
+        if !missing.is_empty() && self.config.remote_lookup {
+            let found = self.fetch_remote(&missing).await;
+            for (selector, candidates) in &found {
+                if let Some(best) = candidates.first() {
+                    let _: redis::RedisResult<()> = redis.set(signature_key(selector), best).await;
+                }
+            }
+            response.signatures.extend(found);
+        }
+        for selector in missing {
+            response.signatures.entry(selector).or_default();
+        }
+        response
+    }
+
+    /// Ask openchain for every selector, then 4byte.directory for the rest
+    async fn fetch_remote(&self, selectors: &[String]) -> HashMap<String, Vec<String>> {
+        let mut found = match self.fetch_openchain(selectors).await {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("openchain selector lookup failed: {}", e);
+                HashMap::new()
+            }
+        };
+
+        for selector in selectors {
+            if found.get(selector).is_some_and(|c| !c.is_empty()) {
+                continue;
+            }
+            match self.fetch_fourbyte(selector).await {
+                Ok(candidates) if !candidates.is_empty() => {
+                    found.insert(selector.clone(), candidates);
+                }
+                Ok(_) => debug!("No signature known for {}", selector),
+                Err(e) => warn!("4byte.directory lookup of {} failed: {}", selector, e),
+            }
+        }
+        found
+    }
+
+    async fn fetch_openchain(&self, selectors: &[String]) -> Result<HashMap<String, Vec<String>>> {
+        let answer: Value = self
+            .http
+            .get(&self.config.openchain_url)
+            .query(&[("function", selectors.join(",")), ("filter", "true".into())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(parse_openchain(&answer))
+    }
+
+    async fn fetch_fourbyte(&self, selector: &str) -> Result<Vec<String>> {
+        let answer: Value = self
+            .http
+            .get(&self.config.fourbyte_url)
+            .query(&[("hex_signature", selector)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(parse_fourbyte(&answer))
+    }
+
+    /// Answer `abi.selector.lookup` requests until the subscription ends
+    pub async fn serve(self, nats_url: &str) -> Result<()> {
+        let client = async_nats::connect(nats_url)
+            .await
+            .context("Failed to connect to NATS for selector lookups")?;
+        let subject = subject_registry::prefixed(SELECTOR_LOOKUP_SUBJECT);
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(anyhow::Error::msg)?;
+        let mut subscriber = client
+            .subscribe(subject.clone())
+            .await
+            .context("Failed to subscribe to selector lookups")?;
+
+        if let Err(e) = self.seed().await {
+            warn!("Selector seeding failed: {}", e);
+        }
+        info!("Serving selector lookups on {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply else {
+                continue;
+            };
+            let request = match serde_json::from_slice::<SelectorLookupRequest>(&message.payload) {
+                Ok(request) => request,
+                Err(e) => {
+                    warn!("Ignoring malformed selector lookup: {}", e);
+                    continue;
+                }
+            };
+            let response = self.lookup(&request.selectors).await;
+            match serde_json::to_vec(&response) {
+                Ok(payload) => {
+                    if let Err(e) = client.publish(reply, payload.into()).await {
+                        warn!("Failed to reply to selector lookup: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to serialize selector lookup reply: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Redis key the actor reads a selector's signature from
+fn signature_key(selector: &str) -> String {
+    retention_policy::ABI_SIGNATURE.key(selector)
+}
+
+/// `0x`-prefixed lowercase selector, or `None` if not 4 bytes of hex
+pub fn normalize_selector(selector: &str) -> Option<String> {
+    let digits = selector
+        .strip_prefix("0x")
+        .or_else(|| selector.strip_prefix("0X"))
+        .unwrap_or(selector);
+    (digits.len() == 8 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("0x{}", digits.to_lowercase()))
+}
+
+/// Selector of a text signature such as `transfer(address,uint256)`
+pub fn selector_of(signature: &str) -> String {
+    let hash = keccak256(signature.as_bytes());
+    format!("0x{}", hex::encode(&hash[..4]))
+}
+
+/// `(selector, signature)` of every well-formed seed signature, whitespace removed
+pub fn seed_entries(signatures: &[String]) -> Vec<(String, String)> {
+    signatures
+        .iter()
+        .map(|signature| signature.split_whitespace().collect::<String>())
+        .filter(|signature| signature.contains('(') && signature.ends_with(')'))
+        .map(|signature| (selector_of(&signature), signature))
+        .collect()
+}
+
+/// Function candidates of an openchain `signature-database/v1/lookup` answer
+pub fn parse_openchain(answer: &Value) -> HashMap<String, Vec<String>> {
+    let Some(functions) = answer
+        .pointer("/result/function")
+        .and_then(Value::as_object)
+    else {
+        return HashMap::new();
+    };
+    functions
+        .iter()
+        .filter_map(|(selector, candidates)| {
+            let candidates = candidates
+                .as_array()?
+                .iter()
+                .filter(|candidate| candidate["filtered"] != Value::Bool(true))
+                .filter_map(|candidate| candidate["name"].as_str().map(str::to_string))
+                .collect();
+            Some((normalize_selector(selector)?, candidates))
+        })
+        .collect()
+}
+
+/// Candidates of a 4byte.directory `signatures` answer, oldest first
+pub fn parse_fourbyte(answer: &Value) -> Vec<String> {
+    let mut results: Vec<(u64, String)> = answer["results"]
+        .as_array()
+        .map(|results| {
+            results
+                .iter()
+                .filter_map(|result| {
+                    Some((
+                        result["id"].as_u64().unwrap_or(u64::MAX),
+                        result["text_signature"].as_str()?.to_string(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    results.sort_by_key(|(id, _)| *id);
+    results
+        .into_iter()
+        .map(|(_, signature)| signature)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_seed_entries_compute_selectors() {
+        let entries = seed_entries(&[
+            "transfer(address, uint256)".to_string(),
+            "approve(address,uint256)".to_string(),
+            "not a signature".to_string(),
+        ]);
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "0xa9059cbb".to_string(),
+                    "transfer(address,uint256)".to_string()
+                ),
+                (
+                    "0x095ea7b3".to_string(),
+                    "approve(address,uint256)".to_string()
+                ),
+            ]
+        );
+
+        assert_eq!(
+            normalize_selector("A9059CBB"),
+            Some("0xa9059cbb".to_string())
+        );
+        assert_eq!(normalize_selector("0xa9059c"), None);
+        assert_eq!(normalize_selector("0xzz059cbb"), None);
+    }
+
+    #[test]
+    fn test_remote_answers_best_candidate_first() {
+        let openchain = json!({
+            "ok": true,
+            "result": {
+                "event": {},
+                "function": {
+                    "0xa9059cbb": [
+                        {"name": "many_msg_babbage(bytes1)", "filtered": true},
+                        {"name": "transfer(address,uint256)", "filtered": false}
+                    ],
+                    "0xdeadbeef": null
+                }
+            }
+        });
+        let found = parse_openchain(&openchain);
+        assert_eq!(
+            found["0xa9059cbb"],
+            vec!["transfer(address,uint256)".to_string()]
+        );
+        assert!(!found.contains_key("0xdeadbeef"));
+
+        let fourbyte = json!({
+            "count": 2,
+            "results": [
+                {"id": 313067, "text_signature": "func_2093253501(bytes)", "hex_signature": "0x095ea7b3"},
+                {"id": 149, "text_signature": "approve(address,uint256)", "hex_signature": "0x095ea7b3"}
+            ]
+        });
+        assert_eq!(
+            parse_fourbyte(&fourbyte),
+            vec![
+                "approve(address,uint256)".to_string(),
+                "func_2093253501(bytes)".to_string()
+            ]
+        );
+        assert!(parse_fourbyte(&json!({"detail": "Not found."})).is_empty());
+    }
+}
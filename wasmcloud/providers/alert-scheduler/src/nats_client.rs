@@ -1,5 +1,7 @@
 use crate::{AlertSchedulerError, Result};
-use alert_runtime_common::{job_create_subject, AlertEvaluationJobV1, JobPriorityV1};
+use alert_runtime_common::{
+    job_create_subject, AlertEvaluationJobV1, JobPriorityV1, ALERT_ACK_SUBJECT,
+};
 use async_nats::jetstream;
 use async_nats::Client;
 use async_trait::async_trait;
@@ -15,6 +17,9 @@ pub trait AlertSchedulerPublisher: Send + Sync {
         payload: Vec<u8>,
     ) -> Result<()>;
 
+    /// Plain (non-JetStream) publish for subjects consumed by actors
+    async fn publish_core_bytes(&self, subject: &str, payload: Vec<u8>) -> Result<()>;
+
     async fn publish_evaluation_job(
         &self,
         job: &AlertEvaluationJobV1,
@@ -38,6 +43,16 @@ impl NatsClient {
             AlertSchedulerError::Configuration(format!("Failed to connect to NATS: {}", e))
         })?;
 
+        let prefix = subject_registry::active_prefix();
+        prefix
+            .check_subjects(&[
+                prefix.apply("alerts.jobs.create.>"),
+                prefix.apply("alerts.schedule.>"),
+                prefix.apply(ALERT_ACK_SUBJECT),
+            ])
+            .map_err(AlertSchedulerError::Configuration)?;
+        let jobs_stream_name = prefix.stream_name(&jobs_stream_name);
+
         let jetstream = jetstream::new(client.clone());
         Self::ensure_jobs_stream(&jetstream, &jobs_stream_name).await?;
 
@@ -52,7 +67,7 @@ impl NatsClient {
         let stream_config = jetstream::stream::Config {
             name: stream_name.to_string(),
             description: Some("Alert evaluation job queue".to_string()),
-            subjects: vec!["alerts.jobs.create.>".to_string()],
+            subjects: vec![subject_registry::prefixed("alerts.jobs.create.>")],
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             storage: jetstream::stream::StorageType::File,
             max_messages: 1_000_000,
@@ -85,11 +100,12 @@ impl NatsClient {
     }
 
     async fn ensure_schedule_requests_stream(js: &jetstream::Context) -> Result<()> {
-        let stream_name = "ALERT_SCHEDULE_REQUESTS";
+        let stream_name = schedule_requests_stream_name();
+        let stream_name = stream_name.as_str();
         let stream_config = jetstream::stream::Config {
             name: stream_name.to_string(),
             description: Some("Alert schedule requests".to_string()),
-            subjects: vec!["alerts.schedule.>".to_string()],
+            subjects: vec![subject_registry::prefixed("alerts.schedule.>")],
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             storage: jetstream::stream::StorageType::File,
             max_messages: 500_000,
@@ -126,7 +142,7 @@ impl NatsClient {
 
         let stream = self
             .jetstream
-            .get_stream("ALERT_SCHEDULE_REQUESTS")
+            .get_stream(schedule_requests_stream_name())
             .await
             .map_err(|e| {
                 AlertSchedulerError::NatsConnection(format!("Failed to get stream: {}", e))
@@ -140,7 +156,7 @@ impl NatsClient {
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             ack_wait: std::time::Duration::from_secs(30),
             max_deliver: 5,
-            filter_subject: "alerts.schedule.>".to_string(),
+            filter_subject: subject_registry::prefixed("alerts.schedule.>"),
             replay_policy: jetstream::consumer::ReplayPolicy::Instant,
             ..Default::default()
         };
@@ -175,7 +191,7 @@ impl NatsClient {
         headers.insert("Nats-Msg-Id", msg_id);
 
         self.jetstream
-            .publish_with_headers(subject.to_string(), headers, bytes.into())
+            .publish_with_headers(subject_registry::prefixed(subject), headers, bytes.into())
             .await
             .map_err(|e| {
                 AlertSchedulerError::NatsPublish(format!(
@@ -199,7 +215,7 @@ impl NatsClient {
         headers.insert("Nats-Msg-Id", msg_id);
 
         self.jetstream
-            .publish_with_headers(subject.to_string(), headers, payload.into())
+            .publish_with_headers(subject_registry::prefixed(subject), headers, payload.into())
             .await
             .map_err(|e| {
                 AlertSchedulerError::NatsPublish(format!(
@@ -213,6 +229,29 @@ impl NatsClient {
         Ok(())
     }
 
+    pub async fn publish_core_bytes(&self, subject: &str, payload: Vec<u8>) -> Result<()> {
+        self.client
+            .publish(subject_registry::prefixed(subject), payload.into())
+            .await
+            .map_err(|e| {
+                AlertSchedulerError::NatsPublish(format!("Failed to publish {}: {}", subject, e))
+            })
+    }
+
+    /// Core NATS subscription for notification acknowledgements (`alerts.ack`)
+    pub async fn subscribe_to_acks(&self) -> Result<async_nats::Subscriber> {
+        info!("Setting up subscription for {}", ALERT_ACK_SUBJECT);
+        self.client
+            .subscribe(subject_registry::prefixed(ALERT_ACK_SUBJECT))
+            .await
+            .map_err(|e| {
+                AlertSchedulerError::NatsConnection(format!(
+                    "Failed to subscribe to {}: {}",
+                    ALERT_ACK_SUBJECT, e
+                ))
+            })
+    }
+
     pub async fn publish_evaluation_job(
         &self,
         job: &AlertEvaluationJobV1,
@@ -240,7 +279,7 @@ impl NatsClient {
         headers.insert("run-id", job.evaluation_context.run.run_id.as_str());
 
         self.jetstream
-            .publish_with_headers(subject, headers, bytes.into())
+            .publish_with_headers(subject_registry::prefixed(&subject), headers, bytes.into())
             .await
             .map_err(|e| {
                 AlertSchedulerError::NatsPublish(format!(
@@ -260,6 +299,11 @@ impl NatsClient {
     }
 }
 
+/// Schedule request stream, scoped to the environment subject prefix
+fn schedule_requests_stream_name() -> String {
+    subject_registry::active_prefix().stream_name("ALERT_SCHEDULE_REQUESTS")
+}
+
 async fn create_or_get_consumer<T, CreateErr, GetErr, CreateFut, GetFut, CreateFn, GetFn>(
     create: CreateFn,
     get: GetFn,
@@ -350,6 +394,10 @@ impl AlertSchedulerPublisher for NatsClient {
         NatsClient::publish_schedule_request_bytes(self, subject, msg_id, payload).await
     }
 
+    async fn publish_core_bytes(&self, subject: &str, payload: Vec<u8>) -> Result<()> {
+        NatsClient::publish_core_bytes(self, subject, payload).await
+    }
+
     async fn publish_evaluation_job(
         &self,
         job: &AlertEvaluationJobV1,
@@ -2,6 +2,7 @@ use crate::{
     AlertSchedulerConfig, AlertSchedulerError, NatsClient, RedisManager, Result, RuntimeStore,
     ScheduleRequestHandler, ScheduleScanner,
 };
+use alert_runtime_common::ALERT_SCHEDULE_ESCALATION_SUBJECT;
 use async_trait::async_trait;
 use futures::StreamExt;
 use std::sync::Arc;
@@ -19,10 +20,16 @@ pub struct AlertSchedulerProvider {
     store: Arc<RuntimeStore>,
     scanner_task: Arc<RwLock<Option<JoinHandle<()>>>>,
     schedule_request_task: Arc<RwLock<Option<JoinHandle<()>>>>,
+    ack_task: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 impl AlertSchedulerProvider {
     pub async fn from_host_data(host_data: wasmcloud_provider_sdk::HostData) -> Result<Self> {
+        let subject_prefix = subject_registry::SubjectPrefix::from_properties(&host_data.config)
+            .and_then(subject_registry::install_prefix)
+            .map_err(AlertSchedulerError::Configuration)?;
+        info!("Subject prefix: {}", subject_prefix);
+
         let config = if !host_data.config.is_empty() {
             AlertSchedulerConfig::from_properties(&host_data.config)
                 .map_err(|e| AlertSchedulerError::Configuration(format!("Config error: {}", e)))?
@@ -48,6 +55,7 @@ impl AlertSchedulerProvider {
             store: store.clone(),
             scanner_task: Arc::new(RwLock::new(None)),
             schedule_request_task: Arc::new(RwLock::new(None)),
+            ack_task: Arc::new(RwLock::new(None)),
         };
 
         provider
@@ -56,6 +64,9 @@ impl AlertSchedulerProvider {
         provider
             .start_schedule_scanner(config.clone(), store.clone(), nats.clone())
             .await?;
+        provider
+            .start_ack_consumer(config.clone(), store.clone(), nats.clone())
+            .await?;
 
         Ok(provider)
     }
@@ -95,7 +106,9 @@ impl AlertSchedulerProvider {
                     }
                 };
 
-                let subject = msg.subject.as_str().to_string();
+                let subject = subject_registry::unprefixed(msg.subject.as_str())
+                    .unwrap_or_default()
+                    .to_string();
                 let payload = msg.payload.clone();
 
                 let result = if subject == "alerts.schedule.periodic" {
@@ -119,6 +132,13 @@ impl AlertSchedulerProvider {
                         Ok(req) => handler.handle_event_driven(req).await.map(|_| ()),
                         Err(e) => Err(AlertSchedulerError::Serialization(e)),
                     }
+                } else if subject == ALERT_SCHEDULE_ESCALATION_SUBJECT {
+                    match serde_json::from_slice::<alert_runtime_common::AlertScheduleEscalationV1>(
+                        &payload,
+                    ) {
+                        Ok(req) => handler.handle_escalation(req).await.map(|_| ()),
+                        Err(e) => Err(AlertSchedulerError::Serialization(e)),
+                    }
                 } else {
                     Ok(())
                 };
@@ -145,6 +165,44 @@ impl AlertSchedulerProvider {
         *self.schedule_request_task.write().await = Some(handle);
         Ok(())
     }
+
+    async fn start_ack_consumer(
+        &self,
+        config: AlertSchedulerConfig,
+        store: Arc<RuntimeStore>,
+        nats: Arc<NatsClient>,
+    ) -> Result<()> {
+        let handler = ScheduleRequestHandler::new(config, store, nats.clone());
+        let mut subscriber = nats.subscribe_to_acks().await?;
+
+        let handle = tokio::spawn(async move {
+            while let Some(msg) = subscriber.next().await {
+                let acknowledged = match serde_json::from_slice::<alert_runtime_common::AlertAckV1>(
+                    &msg.payload,
+                ) {
+                    Ok(ack) => handler.handle_ack(ack).await.map(|_| true),
+                    Err(e) => Err(AlertSchedulerError::Serialization(e)),
+                };
+                let acknowledged = match acknowledged {
+                    Ok(acknowledged) => acknowledged,
+                    Err(e) => {
+                        warn!("Failed to handle ack message: {}", e);
+                        false
+                    }
+                };
+
+                if let Some(reply) = msg.reply {
+                    let body = serde_json::json!({ "acknowledged": acknowledged }).to_string();
+                    if let Err(e) = nats.publish_core_bytes(reply.as_str(), body.into()).await {
+                        warn!("Failed to reply to ack message: {}", e);
+                    }
+                }
+            }
+        });
+
+        *self.ack_task.write().await = Some(handle);
+        Ok(())
+    }
 }
 
 // Provider trait implementation for wasmCloud SDK v0.16 (default impls)
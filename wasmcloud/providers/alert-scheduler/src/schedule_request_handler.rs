@@ -2,11 +2,11 @@ use crate::nats_client::AlertSchedulerPublisher;
 use crate::runtime_store::RuntimeStoreOps;
 use crate::{AlertSchedulerConfig, AlertSchedulerError, Result};
 use alert_runtime_common::{
-    alert_evaluation_job_schema_version_v1, evaluation_context_schema_version_v1,
-    AlertEvaluationJobV1, AlertExecutableV1, AlertScheduleEventDrivenV1, AlertScheduleOneTimeV1,
-    AlertSchedulePeriodicV1, AlertTemplateV1, EvaluationContextInstanceV1, EvaluationContextRunV1,
-    EvaluationContextV1, EvaluationTxV1, JobMetaV1, JobPriorityV1, PartitionV1, ScheduleV1,
-    TargetModeV1, TargetsV1, TriggerTypeV1, TxKindV1,
+    alert_evaluation_job_schema_version_v1, evaluation_context_schema_version_v1, AlertAckV1,
+    AlertEvaluationJobV1, AlertExecutableV1, AlertScheduleEscalationV1, AlertScheduleEventDrivenV1,
+    AlertScheduleOneTimeV1, AlertSchedulePeriodicV1, AlertTemplateV1, EvaluationContextInstanceV1,
+    EvaluationContextRunV1, EvaluationContextV1, EvaluationTxV1, JobMetaV1, JobPriorityV1,
+    PartitionV1, ScheduleV1, TargetModeV1, TargetsV1, TriggerTypeV1, TxKindV1,
 };
 use chrono::{DateTime, Utc};
 use serde_json::Value;
@@ -25,6 +25,12 @@ const GROUP_PARTITIONS_PREFIX: &str = "alerts:targets:group_partitions:";
 const SCHEDULE_DEDUPE_PREFIX: &str = "alerts:schedule:dedupe:";
 const ONE_TIME_FIRED_PREFIX: &str = "alerts:one_time:fired:";
 
+/// Pending escalation timers; members are serialized `AlertScheduleEscalationV1`
+pub(crate) const ESCALATION_ZSET: &str = "alerts:schedule:escalation";
+/// Acknowledged notifications (`alerts:ack:{notification_id}`)
+pub(crate) const ACK_PREFIX: &str = "alerts:ack:";
+const ACK_TTL_SECS: usize = 60 * 60 * 24 * 7;
+
 pub struct ScheduleRequestHandler {
     config: AlertSchedulerConfig,
     store: Arc<dyn RuntimeStoreOps>,
@@ -213,6 +219,40 @@ impl ScheduleRequestHandler {
         Ok(jobs_published)
     }
 
+    /// Arm the timer for one escalation step; returns false when already acknowledged.
+    pub async fn handle_escalation(&self, req: AlertScheduleEscalationV1) -> Result<bool> {
+        let ack_key = format!("{}{}", ACK_PREFIX, req.notification_id);
+        if self.store.exists(&ack_key).await? {
+            debug!(
+                "notification {} already acknowledged; not arming step {}",
+                req.notification_id, req.step
+            );
+            return Ok(false);
+        }
+
+        let member = serde_json::to_string(&req)?;
+        self.store
+            .zadd_nx(ESCALATION_ZSET, &member, req.due_at.timestamp())
+            .await?;
+        Ok(true)
+    }
+
+    /// Record an acknowledgement; pending timers for the notification are dropped when due.
+    pub async fn handle_ack(&self, ack: AlertAckV1) -> Result<bool> {
+        let ack_key = format!("{}{}", ACK_PREFIX, ack.notification_id);
+        let first = self
+            .store
+            .set_nx_ex(&ack_key, &ack.user_id, ACK_TTL_SECS)
+            .await?;
+        if first {
+            info!(
+                "notification {} acknowledged by {} via {}",
+                ack.notification_id, ack.user_id, ack.source
+            );
+        }
+        Ok(first)
+    }
+
     async fn create_scheduled_jobs(
         &self,
         instance_id: String,
@@ -595,12 +635,25 @@ fn instance_spec_ref(instance: &crate::runtime_store::InstanceSnapshot) -> Resul
     )))
 }
 
+/// Whether the transaction's category context is one the trigger opted out of
+fn excluded_by_category_context(exclude: &[String], req: &AlertScheduleEventDrivenV1) -> bool {
+    let context = req
+        .event
+        .evm_tx
+        .as_ref()
+        .and_then(|tx| tx.transfer_category_context.as_deref());
+    context.is_some_and(|context| exclude.iter().any(|c| c == context))
+}
+
 fn trigger_prunes_template(template: &AlertTemplateV1, req: &AlertScheduleEventDrivenV1) -> bool {
     if let Some(chain_id) = template.trigger.chain_id {
         if chain_id != req.partition.chain_id {
             return false;
         }
     }
+    if excluded_by_category_context(&template.trigger.exclude_category_contexts, req) {
+        return false;
+    }
 
     match req.event.kind {
         TxKindV1::Tx => {
@@ -659,6 +712,9 @@ fn trigger_prunes_executable(
     if !evm.chain_ids.is_empty() && !evm.chain_ids.iter().any(|c| *c == req.partition.chain_id) {
         return false;
     }
+    if excluded_by_category_context(&evm.exclude_category_contexts, req) {
+        return false;
+    }
 
     match req.event.kind {
         TxKindV1::Tx => {
@@ -737,6 +793,7 @@ fn schedule_event_to_eval_tx(req: &AlertScheduleEventDrivenV1) -> Result<Evaluat
                     .or_else(|| tx.input.get(0..10).map(|s| s.to_string())),
                 value_wei: Some(tx.value_wei.clone()),
                 value_native: Some(tx.value_native),
+                transfer_category_context: tx.transfer_category_context.clone(),
                 log_index: None,
                 log_address: None,
                 topic0: None,
@@ -744,6 +801,8 @@ fn schedule_event_to_eval_tx(req: &AlertScheduleEventDrivenV1) -> Result<Evaluat
                 topic2: None,
                 topic3: None,
                 data: None,
+                nft: None,
+                perp: None,
                 block_number: tx.block_number,
                 block_timestamp: tx.block_timestamp,
             })
@@ -760,6 +819,7 @@ fn schedule_event_to_eval_tx(req: &AlertScheduleEventDrivenV1) -> Result<Evaluat
                 method_selector: None,
                 value_wei: None,
                 value_native: None,
+                transfer_category_context: None,
                 log_index: Some(log.log_index),
                 log_address: Some(log.address.clone()),
                 topic0: Some(log.topic0.clone()),
@@ -767,6 +827,8 @@ fn schedule_event_to_eval_tx(req: &AlertScheduleEventDrivenV1) -> Result<Evaluat
                 topic2: log.topic2.clone(),
                 topic3: log.topic3.clone(),
                 data: Some(log.data.clone()),
+                nft: log.nft.clone(),
+                perp: log.perp.clone(),
                 block_number: log.block_number,
                 block_timestamp: log.block_timestamp,
             })
@@ -978,6 +1040,10 @@ mod tests {
             Ok(())
         }
 
+        async fn publish_core_bytes(&self, _subject: &str, _payload: Vec<u8>) -> Result<()> {
+            Ok(())
+        }
+
         async fn publish_evaluation_job(
             &self,
             job: &AlertEvaluationJobV1,
@@ -1027,6 +1093,7 @@ mod tests {
                     required: method_required,
                 },
                 event: None,
+                exclude_category_contexts: vec![],
             },
             datasources: vec![],
             enrichments: vec![],
@@ -1295,6 +1362,7 @@ mod tests {
                     method_selector: Some("0x12345678".to_string()),
                     value_wei: "0".to_string(),
                     value_native: 0.0,
+                    transfer_category_context: None,
                     block_number: 1,
                     block_timestamp: requested_at,
                 }),
@@ -1329,4 +1397,56 @@ mod tests {
             vec!["ETH:mainnet:0xabc".to_string()]
         );
     }
+
+    #[test]
+    fn trigger_pruning_skips_excluded_category_contexts() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 15, 12, 0, 0).unwrap();
+        let mut req = AlertScheduleEventDrivenV1 {
+            schema_version: alert_schedule_event_driven_schema_version_v1(),
+            vm: VmKindV1::Evm,
+            partition: PartitionV1 {
+                network: "ETH".to_string(),
+                subnet: "mainnet".to_string(),
+                chain_id: 1,
+            },
+            candidate_target_keys: vec!["ETH:mainnet:0xabc".to_string()],
+            event: alert_runtime_common::ScheduleEventV1 {
+                kind: TxKindV1::Tx,
+                evm_tx: Some(alert_runtime_common::EvmTxV1 {
+                    hash: "0xaaa".to_string(),
+                    from: "0x111".to_string(),
+                    to: Some("0x222".to_string()),
+                    input: "0x".to_string(),
+                    method_selector: None,
+                    value_wei: "1".to_string(),
+                    value_native: 0.0,
+                    transfer_category_context: Some(
+                        alert_runtime_common::INTERNAL_EXCHANGE_OPS.to_string(),
+                    ),
+                    block_number: 1,
+                    block_timestamp: at,
+                }),
+                evm_log: None,
+            },
+            requested_at: at,
+            source: "test".to_string(),
+        };
+
+        let mut template = minimal_template_v1(1, false, vec![]);
+        assert!(trigger_prunes_template(&template, &req));
+
+        template.trigger.exclude_category_contexts =
+            vec![alert_runtime_common::INTERNAL_EXCHANGE_OPS.to_string()];
+        assert!(!trigger_prunes_template(&template, &req));
+        assert_eq!(
+            schedule_event_to_eval_tx(&req)
+                .unwrap()
+                .transfer_category_context
+                .as_deref(),
+            Some("internal_exchange_ops")
+        );
+
+        req.event.evm_tx.as_mut().unwrap().transfer_category_context = None;
+        assert!(trigger_prunes_template(&template, &req));
+    }
 }
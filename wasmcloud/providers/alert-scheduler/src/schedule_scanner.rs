@@ -1,9 +1,11 @@
 use crate::nats_client::AlertSchedulerPublisher;
 use crate::runtime_store::RuntimeStoreOps;
+use crate::schedule_request_handler::{ACK_PREFIX, ESCALATION_ZSET};
 use crate::{AlertSchedulerConfig, AlertSchedulerError, Result};
 use alert_runtime_common::{
-    alert_schedule_one_time_schema_version_v1, alert_schedule_periodic_schema_version_v1,
-    AlertScheduleOneTimeV1, AlertSchedulePeriodicV1,
+    alert_escalation_due_schema_version_v1, alert_schedule_one_time_schema_version_v1,
+    alert_schedule_periodic_schema_version_v1, escalation_due_subject, AlertEscalationDueV1,
+    AlertScheduleEscalationV1, AlertScheduleOneTimeV1, AlertSchedulePeriodicV1,
 };
 use chrono::{DateTime, TimeZone, Utc};
 use cron::Schedule;
@@ -53,6 +55,9 @@ impl ScheduleScanner {
             if let Err(err) = self.publish_due_periodic().await {
                 warn!("periodic scan failed: {}", err);
             }
+            if let Err(err) = self.publish_due_escalations().await {
+                warn!("escalation scan failed: {}", err);
+            }
         }
     }
 
@@ -292,6 +297,59 @@ impl ScheduleScanner {
 
         Ok(())
     }
+
+    /// Fire due escalation timers whose notification is still unacknowledged.
+    async fn publish_due_escalations(&self) -> Result<()> {
+        let now = Utc::now();
+        let due = self
+            .store
+            .zrangebyscore_withscores(
+                ESCALATION_ZSET,
+                now.timestamp(),
+                self.config.schedule_due_batch_size as usize,
+            )
+            .await?;
+
+        for (member, _) in due {
+            let req: AlertScheduleEscalationV1 = match serde_json::from_str(&member) {
+                Ok(req) => req,
+                Err(e) => {
+                    warn!("dropping malformed escalation timer: {}", e);
+                    let _ = self.store.zrem(ESCALATION_ZSET, &member).await;
+                    continue;
+                }
+            };
+
+            let ack_key = format!("{}{}", ACK_PREFIX, req.notification_id);
+            if self.store.exists(&ack_key).await? {
+                debug!(
+                    "notification {} acknowledged; dropping escalation step {}",
+                    req.notification_id, req.step
+                );
+                let _ = self.store.zrem(ESCALATION_ZSET, &member).await;
+                continue;
+            }
+
+            let msg = AlertEscalationDueV1 {
+                schema_version: alert_escalation_due_schema_version_v1(),
+                notification_id: req.notification_id.clone(),
+                instance_id: req.instance_id.clone(),
+                user_id: req.user_id.clone(),
+                step: req.step,
+                due_at: req.due_at,
+                fired_at: now,
+            };
+            let bytes = serde_json::to_vec(&msg)?;
+            self.nats
+                .publish_core_bytes(&escalation_due_subject(&req.instance_id), bytes)
+                .await?;
+
+            // Publish first, then remove; the router ignores steps it has already sent.
+            let _ = self.store.zrem(ESCALATION_ZSET, &member).await;
+        }
+
+        Ok(())
+    }
 }
 
 fn schedule_request_id(
@@ -539,6 +597,14 @@ mod tests {
             Ok(())
         }
 
+        async fn publish_core_bytes(&self, subject: &str, payload: Vec<u8>) -> Result<()> {
+            self.published
+                .lock()
+                .await
+                .push((subject.to_string(), String::new(), payload));
+            Ok(())
+        }
+
         async fn publish_evaluation_job(
             &self,
             _job: &AlertEvaluationJobV1,
@@ -651,4 +717,56 @@ mod tests {
         let score = store.zscore(ONE_TIME_ZSET, &instance_id).await.unwrap();
         assert!(score.is_none());
     }
+
+    #[tokio::test]
+    async fn escalation_fires_only_while_unacknowledged() {
+        use crate::ScheduleRequestHandler;
+        use alert_runtime_common::{
+            alert_ack_schema_version_v1, alert_schedule_escalation_schema_version_v1, AlertAckV1,
+        };
+
+        let store = Arc::new(TestStore::default());
+        let publisher = Arc::new(TestPublisher::default());
+        let handler =
+            ScheduleRequestHandler::new(default_config(), store.clone(), publisher.clone());
+        let scanner = ScheduleScanner::new(default_config(), store.clone(), publisher.clone());
+
+        let timer = |notification_id: &str| AlertScheduleEscalationV1 {
+            schema_version: alert_schedule_escalation_schema_version_v1(),
+            notification_id: notification_id.to_string(),
+            instance_id: "inst-3".to_string(),
+            user_id: "u1".to_string(),
+            step: 0,
+            due_at: Utc::now() - chrono::Duration::seconds(1),
+            requested_at: Utc::now(),
+            source: "notification_router".to_string(),
+        };
+        assert!(handler.handle_escalation(timer("n-open")).await.unwrap());
+        assert!(handler.handle_escalation(timer("n-acked")).await.unwrap());
+
+        let ack = AlertAckV1 {
+            schema_version: alert_ack_schema_version_v1(),
+            notification_id: "n-acked".to_string(),
+            user_id: "u1".to_string(),
+            acked_at: Utc::now(),
+            source: "api".to_string(),
+        };
+        assert!(handler.handle_ack(ack.clone()).await.unwrap());
+        assert!(!handler.handle_ack(ack).await.unwrap());
+        // Arming a step after the ack is a no-op
+        assert!(!handler.handle_escalation(timer("n-acked")).await.unwrap());
+
+        scanner.publish_due_escalations().await.unwrap();
+
+        let published = publisher.published.lock().await;
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, "alerts.escalation.due.inst-3");
+        let due: AlertEscalationDueV1 = serde_json::from_slice(&published[0].2).unwrap();
+        assert_eq!(due.notification_id, "n-open");
+        assert!(store
+            .zrangebyscore_withscores(ESCALATION_ZSET, i64::MAX, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,60 @@
+//! Decode Queue Provider binary entry point
+//!
+//! Runs as a wasmCloud capability provider, persisting `abi.decode.request`
+//! messages in a Redis stream and dispatching them to the abi-decoder actor.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tracing::{error, info};
+use wasmcloud_provider_sdk::{load_host_data, run_provider};
+
+use decode_queue_provider::{DecodeQueueConfig, DecodeQueueProvider};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("decode_queue_provider=info".parse()?),
+        )
+        .init();
+
+    info!("📥 Starting Decode Queue Provider for wasmCloud");
+
+    let host_data = load_host_data().context("Failed to load wasmCloud host data")?;
+
+    info!("Provider ID: {}", host_data.provider_key);
+    info!("Config entries: {}", host_data.config.len());
+
+    let subject_prefix = subject_registry::SubjectPrefix::from_properties(&host_data.config)
+        .and_then(subject_registry::install_prefix)
+        .map_err(anyhow::Error::msg)?;
+    info!("Subject prefix: {}", subject_prefix);
+
+    let config = if host_data.config.is_empty() {
+        DecodeQueueConfig::from_env()
+    } else {
+        DecodeQueueConfig::from_properties(&host_data.config)
+    };
+
+    info!("Consumer: {}", config.consumer);
+    info!("Max attempts: {}", config.max_attempts);
+
+    let provider = DecodeQueueProvider::with_config(config);
+    let runtime_provider = provider.clone();
+    let provider = Arc::new(provider);
+
+    tokio::spawn(async move {
+        if let Err(e) = provider.start().await {
+            error!("provider error: {e:?}");
+        }
+    });
+
+    let handler = run_provider(runtime_provider, "decode-queue-provider")
+        .await
+        .context("Provider runtime error")?;
+    handler.await;
+
+    info!("Decode Queue Provider shutdown complete");
+    Ok(())
+}
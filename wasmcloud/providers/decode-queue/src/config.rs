@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+/// Decode queue settings from host config properties or environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeQueueConfig {
+    pub redis_url: String,
+    pub nats_url: String,
+    /// Consumer name within the `decode-queue` group; unique per replica
+    pub consumer: String,
+    /// Entries read from the stream per round trip
+    pub batch_size: usize,
+    /// Approximate stream length cap (`XADD MAXLEN ~`)
+    pub max_stream_len: usize,
+    /// Deliveries before an entry is moved to the dead-letter stream
+    pub max_attempts: u64,
+    /// Idle time before the first redelivery, doubled per attempt
+    pub retry_base_secs: u64,
+    pub retry_max_secs: u64,
+    /// How long the decoder has to answer one dispatch
+    pub dispatch_timeout_ms: u64,
+    /// Seconds between DuckLake reconciliation rounds
+    pub reconcile_interval_secs: u64,
+    /// Rows `Pending` for longer than this are requested again
+    pub reconcile_threshold_secs: u64,
+    /// Rows requested again per chain per round
+    pub reconcile_batch: u64,
+    /// `(network, subnet)` pairs to reconcile
+    pub chains: Vec<(String, String)>,
+}
+
+impl DecodeQueueConfig {
+    const DEFAULT_REDIS_URL: &'static str = "redis://localhost:6379";
+    const DEFAULT_NATS_URL: &'static str = "nats://localhost:4222";
+    const DEFAULT_CONSUMER: &'static str = "decode-queue-1";
+    const DEFAULT_BATCH_SIZE: usize = 50;
+    const DEFAULT_MAX_STREAM_LEN: usize = 100_000;
+    const DEFAULT_MAX_ATTEMPTS: u64 = 8;
+    const DEFAULT_RETRY_BASE_SECS: u64 = 15;
+    const DEFAULT_RETRY_MAX_SECS: u64 = 1800;
+    const DEFAULT_DISPATCH_TIMEOUT_MS: u64 = 10_000;
+    const DEFAULT_RECONCILE_INTERVAL_SECS: u64 = 600;
+    const DEFAULT_RECONCILE_THRESHOLD_SECS: u64 = 3600;
+    const DEFAULT_RECONCILE_BATCH: u64 = 500;
+    const DEFAULT_CHAINS: &'static str = "ethereum.mainnet";
+
+    pub fn from_env() -> Self {
+        let vars: HashMap<String, String> = std::env::vars().collect();
+        Self::from_properties(&vars)
+    }
+
+    pub fn from_properties(props: &HashMap<String, String>) -> Self {
+        let get = |key: &str| {
+            props
+                .get(key)
+                .or_else(|| props.get(&key.to_uppercase()))
+                .cloned()
+        };
+        let number =
+            |key: &str, default: u64| get(key).and_then(|v| v.parse().ok()).unwrap_or(default);
+
+        Self {
+            redis_url: get("redis_url").unwrap_or_else(|| Self::DEFAULT_REDIS_URL.to_string()),
+            nats_url: get("nats_url").unwrap_or_else(|| Self::DEFAULT_NATS_URL.to_string()),
+            consumer: get("decode_queue_consumer")
+                .or_else(|| get("hostname"))
+                .unwrap_or_else(|| Self::DEFAULT_CONSUMER.to_string()),
+            batch_size: number("decode_queue_batch_size", Self::DEFAULT_BATCH_SIZE as u64) as usize,
+            max_stream_len: number(
+                "decode_queue_max_stream_len",
+                Self::DEFAULT_MAX_STREAM_LEN as u64,
+            ) as usize,
+            max_attempts: number("decode_queue_max_attempts", Self::DEFAULT_MAX_ATTEMPTS).max(1),
+            retry_base_secs: number(
+                "decode_queue_retry_base_secs",
+                Self::DEFAULT_RETRY_BASE_SECS,
+            ),
+            retry_max_secs: number("decode_queue_retry_max_secs", Self::DEFAULT_RETRY_MAX_SECS),
+            dispatch_timeout_ms: number(
+                "decode_queue_dispatch_timeout_ms",
+                Self::DEFAULT_DISPATCH_TIMEOUT_MS,
+            ),
+            reconcile_interval_secs: number(
+                "decode_queue_reconcile_interval_secs",
+                Self::DEFAULT_RECONCILE_INTERVAL_SECS,
+            ),
+            reconcile_threshold_secs: number(
+                "decode_queue_reconcile_threshold_secs",
+                Self::DEFAULT_RECONCILE_THRESHOLD_SECS,
+            ),
+            reconcile_batch: number(
+                "decode_queue_reconcile_batch",
+                Self::DEFAULT_RECONCILE_BATCH,
+            ),
+            chains: parse_chains(
+                &get("decode_queue_chains").unwrap_or_else(|| Self::DEFAULT_CHAINS.to_string()),
+            ),
+        }
+    }
+}
+
+/// Parse `ethereum.mainnet,polygon.mainnet` into `(network, subnet)` pairs
+fn parse_chains(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|chain| chain.trim().split_once('.'))
+        .filter(|(network, subnet)| !network.is_empty() && !subnet.is_empty())
+        .map(|(network, subnet)| (network.to_lowercase(), subnet.to_lowercase()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_properties_defaults_and_overrides() {
+        let defaults = DecodeQueueConfig::from_properties(&HashMap::new());
+        assert_eq!(defaults.max_attempts, 8);
+        assert_eq!(
+            defaults.chains,
+            vec![("ethereum".to_string(), "mainnet".to_string())]
+        );
+
+        let props = HashMap::from([
+            ("redis_url".to_string(), "redis://redis:6379".to_string()),
+            ("DECODE_QUEUE_MAX_ATTEMPTS".to_string(), "3".to_string()),
+            (
+                "decode_queue_chains".to_string(),
+                "Ethereum.Mainnet, polygon.mainnet,bogus".to_string(),
+            ),
+        ]);
+        let config = DecodeQueueConfig::from_properties(&props);
+        assert_eq!(config.redis_url, "redis://redis:6379");
+        assert_eq!(config.max_attempts, 3);
+        assert_eq!(
+            config.chains,
+            vec![
+                ("ethereum".to_string(), "mainnet".to_string()),
+                ("polygon".to_string(), "mainnet".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! Decode Queue Provider
+//!
+//! Makes ABI decode requests durable. Processors publish `abi.decode.request`
+//! fire-and-forget, so a decode sent while the decoder is down used to be lost.
+//! This provider:
+//! - appends every request to the `decode_queue:stream` Redis stream
+//! - hands entries to the abi-decoder actor on `abi.decode.dispatch` from a
+//!   consumer group and acknowledges them once the decoder replies
+//! - redelivers unanswered entries with exponential backoff and moves them to
+//!   `decode_queue:dead` after `max_attempts`
+//! - periodically re-requests DuckLake rows stuck in `decoding_status = 'Pending'`
+
+pub mod config;
+pub mod provider;
+pub mod queue;
+pub mod reconcile;
+
+pub use config::DecodeQueueConfig;
+pub use provider::DecodeQueueProvider;
+pub use queue::{DecodeQueue, DispatchOutcome, PendingDecodeV1};
+pub use reconcile::Reconciler;
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use tracing::{error, info, instrument};
+use wasmcloud_provider_sdk::Provider;
+
+use crate::config::DecodeQueueConfig;
+use crate::queue::DecodeQueue;
+use crate::reconcile::Reconciler;
+
+/// Decode Queue Provider
+///
+/// - Subscribes: `abi.decode.request`
+/// - Requests: `abi.decode.dispatch` (abi-decoder actor), `ducklake.transactions.*.*.query`
+#[derive(Clone)]
+pub struct DecodeQueueProvider {
+    config: DecodeQueueConfig,
+}
+
+impl DecodeQueueProvider {
+    #[instrument]
+    pub fn with_config(config: DecodeQueueConfig) -> Self {
+        Self { config }
+    }
+
+    #[instrument]
+    pub fn from_properties(props: &HashMap<String, String>) -> Self {
+        Self::with_config(DecodeQueueConfig::from_properties(props))
+    }
+
+    /// Run intake, worker and reconciliation until one of them stops
+    #[instrument(skip(self))]
+    pub async fn start(self: Arc<Self>) -> Result<()> {
+        info!("Starting Decode Queue Provider");
+
+        let nats = async_nats::connect(&self.config.nats_url)
+            .await
+            .context("failed to connect to NATS")?;
+        let worker = DecodeQueue::connect(self.config.clone(), nats.clone()).await?;
+        let intake = DecodeQueue::connect(self.config.clone(), nats.clone()).await?;
+        let reconciler = Reconciler::connect(self.config.clone(), nats).await?;
+
+        let reconcile = tokio::spawn(async move {
+            if let Err(e) = reconciler.run().await {
+                error!("Decode reconciliation stopped: {e:?}");
+            }
+        });
+        let result = tokio::select! {
+            result = intake.run_intake() => result,
+            result = worker.run_worker() => result,
+        };
+        reconcile.abort();
+        result
+    }
+}
+
+impl Default for DecodeQueueProvider {
+    fn default() -> Self {
+        Self::with_config(DecodeQueueConfig::from_env())
+    }
+}
+
+impl Provider for DecodeQueueProvider {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_traits() {
+        fn assert_provider<T: Provider + Clone>() {}
+        assert_provider::<DecodeQueueProvider>();
+    }
+}
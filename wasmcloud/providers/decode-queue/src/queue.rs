@@ -0,0 +1,446 @@
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use redis::aio::ConnectionManager;
+use redis::streams::{
+    StreamClaimReply, StreamId, StreamMaxlen, StreamPendingCountReply, StreamPendingId,
+    StreamReadOptions, StreamReadReply,
+};
+use redis::AsyncCommands;
+use retention_policy::{DECODE_QUEUE_DEAD_LETTERS, DECODE_QUEUE_STREAM};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::config::DecodeQueueConfig;
+
+/// Decode requests published by processors
+pub const REQUEST_SUBJECT: &str = "abi.decode.request";
+
+/// Queued requests handed to the abi-decoder actor, which replies with its result
+pub const DISPATCH_SUBJECT: &str = "abi.decode.dispatch";
+
+/// Consumer group on the queue stream (and NATS queue group for intake)
+pub const CONSUMER_GROUP: &str = "decode-queue";
+
+/// Fields of a decode request the queue needs; the payload is forwarded as-is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingDecodeV1 {
+    pub transaction_hash: String,
+    pub network: String,
+    pub subnet: String,
+    #[serde(default)]
+    pub to_address: String,
+    #[serde(default)]
+    pub input_data: String,
+    #[serde(default)]
+    pub request_id: String,
+}
+
+/// What the decoder's reply means for the queue entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum DispatchOutcome {
+    /// Final result; acknowledge and drop the entry
+    Done,
+    /// Leave the entry pending for a later redelivery
+    Retry(String),
+}
+
+impl DispatchOutcome {
+    /// Classify a `DecodeResult` reply; rate limits and errors are retried
+    pub fn from_reply(body: &[u8]) -> Self {
+        let Ok(reply) = serde_json::from_slice::<serde_json::Value>(body) else {
+            return Self::Retry("unparseable decoder reply".to_string());
+        };
+        if let Some(error) = reply.get("error") {
+            return Self::Retry(format!("decoder error: {}", error));
+        }
+        match reply.pointer("/status/type").and_then(|t| t.as_str()) {
+            Some("RateLimited") => Self::Retry("decoder rate limited".to_string()),
+            Some(_) => Self::Done,
+            None => Self::Retry("decoder reply without status".to_string()),
+        }
+    }
+}
+
+/// Next step for an entry that is still pending
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingAction {
+    Wait,
+    Redeliver,
+    DeadLetter,
+}
+
+/// Backoff before redelivering an entry delivered `deliveries` times
+pub fn retry_delay(deliveries: u64, base: Duration, max: Duration) -> Duration {
+    let exponent = deliveries.saturating_sub(1).min(20) as u32;
+    base.saturating_mul(2u32.saturating_pow(exponent)).min(max)
+}
+
+/// XPENDING range start for the page after entry `id` (exclusive)
+pub fn pending_page_after(id: &str) -> String {
+    format!("({}", id)
+}
+
+pub fn pending_action(
+    deliveries: u64,
+    idle: Duration,
+    config: &DecodeQueueConfig,
+) -> PendingAction {
+    let delay = retry_delay(
+        deliveries,
+        Duration::from_secs(config.retry_base_secs),
+        Duration::from_secs(config.retry_max_secs),
+    );
+    if idle < delay {
+        PendingAction::Wait
+    } else if deliveries >= config.max_attempts {
+        PendingAction::DeadLetter
+    } else {
+        PendingAction::Redeliver
+    }
+}
+
+/// Redis-stream-backed queue of decode requests
+///
+/// - intake: `abi.decode.request` -> `XADD decode_queue:stream`
+/// - worker: `XREADGROUP` -> request on `abi.decode.dispatch` -> `XACK` on a
+///   final reply; unanswered entries stay pending and are reclaimed with
+///   exponential backoff, then moved to `decode_queue:dead`
+pub struct DecodeQueue {
+    config: DecodeQueueConfig,
+    redis: ConnectionManager,
+    nats: async_nats::Client,
+}
+
+impl DecodeQueue {
+    /// Open a queue handle with its own Redis connection, so a blocking
+    /// `XREADGROUP` never holds up intake or reconciliation
+    pub async fn connect(config: DecodeQueueConfig, nats: async_nats::Client) -> Result<Self> {
+        let client = redis::Client::open(config.redis_url.as_str())
+            .context("invalid Redis URL for decode queue")?;
+        let redis = ConnectionManager::new(client)
+            .await
+            .context("failed to connect to Redis")?;
+
+        Ok(Self {
+            config,
+            redis,
+            nats,
+        })
+    }
+
+    fn stream_key() -> String {
+        DECODE_QUEUE_STREAM.key("")
+    }
+
+    /// Create the consumer group (and stream) if they do not exist yet
+    pub async fn ensure_group(&mut self) -> Result<()> {
+        let created: redis::RedisResult<()> = self
+            .redis
+            .xgroup_create_mkstream(Self::stream_key(), CONSUMER_GROUP, "0")
+            .await;
+        match created {
+            Ok(()) => info!(
+                "Created consumer group {} on {}",
+                CONSUMER_GROUP,
+                Self::stream_key()
+            ),
+            Err(e) if e.code() == Some("BUSYGROUP") => {}
+            Err(e) => return Err(e).context("failed to create decode queue consumer group"),
+        }
+        Ok(())
+    }
+
+    /// Append a decode request; requests without a transaction are dropped
+    pub async fn enqueue(&mut self, payload: &[u8]) -> Result<Option<String>> {
+        let request = match serde_json::from_slice::<PendingDecodeV1>(payload) {
+            Ok(request) if !request.transaction_hash.is_empty() => request,
+            Ok(_) => {
+                warn!("Dropping decode request without transaction_hash");
+                return Ok(None);
+            }
+            Err(e) => {
+                warn!("Dropping malformed decode request: {}", e);
+                return Ok(None);
+            }
+        };
+        let payload = String::from_utf8_lossy(payload).to_string();
+
+        let id: String = self
+            .redis
+            .xadd_maxlen(
+                Self::stream_key(),
+                StreamMaxlen::Approx(self.config.max_stream_len),
+                "*",
+                &[
+                    ("transaction_hash", request.transaction_hash.as_str()),
+                    ("payload", payload.as_str()),
+                ],
+            )
+            .await
+            .context("XADD to decode queue failed")?;
+        Ok(Some(id))
+    }
+
+    /// Persist every `abi.decode.request` until the subscription ends
+    pub async fn run_intake(mut self) -> Result<()> {
+        let subject = subject_registry::prefixed(REQUEST_SUBJECT);
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(anyhow::Error::msg)?;
+        let mut subscriber = self
+            .nats
+            .queue_subscribe(subject.clone(), CONSUMER_GROUP.to_string())
+            .await
+            .context("failed to subscribe to decode requests")?;
+        info!("Queueing decode requests from {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            match self.enqueue(&message.payload).await {
+                Ok(Some(id)) => debug!("Queued decode request {}", id),
+                Ok(None) => {}
+                Err(e) => error!("Failed to queue decode request: {e:?}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read new entries and retry due ones until the process stops
+    pub async fn run_worker(mut self) -> Result<()> {
+        self.ensure_group().await?;
+        info!(
+            "Decode queue worker {} dispatching to {} (max {} attempts)",
+            self.config.consumer, DISPATCH_SUBJECT, self.config.max_attempts
+        );
+
+        loop {
+            if let Err(e) = self.read_new().await {
+                error!("Failed to read decode queue: {e:?}");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            if let Err(e) = self.retry_pending().await {
+                error!("Failed to retry pending decodes: {e:?}");
+            }
+        }
+    }
+
+    async fn read_new(&mut self) -> Result<()> {
+        let options = StreamReadOptions::default()
+            .group(CONSUMER_GROUP, &self.config.consumer)
+            .count(self.config.batch_size)
+            .block(1000);
+        let reply: StreamReadReply = self
+            .redis
+            .xread_options(&[Self::stream_key()], &[">"], &options)
+            .await
+            .context("XREADGROUP failed")?;
+
+        for entry in reply.keys.into_iter().flat_map(|key| key.ids) {
+            self.dispatch(&entry).await?;
+        }
+        Ok(())
+    }
+
+    /// Reclaim pending entries whose backoff has elapsed, paging through the
+    /// whole pending list so entries still in backoff at its head do not hide
+    /// the ones behind them
+    async fn retry_pending(&mut self) -> Result<()> {
+        let mut start = "-".to_string();
+        loop {
+            let pending: StreamPendingCountReply = self
+                .redis
+                .xpending_count(
+                    Self::stream_key(),
+                    CONSUMER_GROUP,
+                    &start,
+                    "+",
+                    self.config.batch_size,
+                )
+                .await
+                .context("XPENDING failed")?;
+
+            let Some(last) = pending.ids.last() else {
+                return Ok(());
+            };
+            let full_page = pending.ids.len() >= self.config.batch_size;
+            start = pending_page_after(&last.id);
+            self.retry_entries(pending.ids).await?;
+            if !full_page {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn retry_entries(&mut self, entries: Vec<StreamPendingId>) -> Result<()> {
+        for entry in entries {
+            let deliveries = entry.times_delivered as u64;
+            let idle = Duration::from_millis(entry.last_delivered_ms as u64);
+            let action = pending_action(deliveries, idle, &self.config);
+            if action == PendingAction::Wait {
+                continue;
+            }
+
+            // Claiming with the observed idle time skips entries another
+            // replica redelivered in the meantime
+            let claimed: StreamClaimReply = self
+                .redis
+                .xclaim(
+                    Self::stream_key(),
+                    CONSUMER_GROUP,
+                    &self.config.consumer,
+                    entry.last_delivered_ms,
+                    &[&entry.id],
+                )
+                .await
+                .context("XCLAIM failed")?;
+
+            for claimed in claimed.ids {
+                match action {
+                    PendingAction::DeadLetter => self.dead_letter(&claimed, deliveries).await?,
+                    _ => self.dispatch(&claimed).await?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hand one entry to the decoder and acknowledge it on a final reply
+    async fn dispatch(&mut self, entry: &StreamId) -> Result<()> {
+        let Some(payload) = entry.get::<String>("payload") else {
+            warn!("Decode queue entry {} has no payload, dropping", entry.id);
+            return self.ack(&entry.id).await;
+        };
+
+        let request = self.nats.request(
+            subject_registry::prefixed(DISPATCH_SUBJECT),
+            payload.into_bytes().into(),
+        );
+        let outcome = match tokio::time::timeout(
+            Duration::from_millis(self.config.dispatch_timeout_ms),
+            request,
+        )
+        .await
+        {
+            Ok(Ok(reply)) => DispatchOutcome::from_reply(&reply.payload),
+            Ok(Err(e)) => DispatchOutcome::Retry(format!("dispatch failed: {}", e)),
+            Err(_) => DispatchOutcome::Retry("decoder did not answer in time".to_string()),
+        };
+
+        match outcome {
+            DispatchOutcome::Done => self.ack(&entry.id).await,
+            DispatchOutcome::Retry(reason) => {
+                debug!("Decode {} stays pending: {}", entry.id, reason);
+                Ok(())
+            }
+        }
+    }
+
+    async fn dead_letter(&mut self, entry: &StreamId, deliveries: u64) -> Result<()> {
+        let payload = entry.get::<String>("payload").unwrap_or_default();
+        let transaction_hash = entry.get::<String>("transaction_hash").unwrap_or_default();
+        let attempts = deliveries.to_string();
+
+        let _: String = self
+            .redis
+            .xadd_maxlen(
+                DECODE_QUEUE_DEAD_LETTERS.key(""),
+                StreamMaxlen::Approx(self.config.max_stream_len),
+                "*",
+                &[
+                    ("transaction_hash", transaction_hash.as_str()),
+                    ("payload", payload.as_str()),
+                    ("attempts", attempts.as_str()),
+                ],
+            )
+            .await
+            .context("XADD to decode dead letters failed")?;
+        warn!(
+            "Decode of {} gave up after {} attempts, moved to {}",
+            transaction_hash,
+            deliveries,
+            DECODE_QUEUE_DEAD_LETTERS.key("")
+        );
+
+        self.ack(&entry.id).await
+    }
+
+    async fn ack(&mut self, id: &str) -> Result<()> {
+        let _: i64 = self
+            .redis
+            .xack(Self::stream_key(), CONSUMER_GROUP, &[id])
+            .await
+            .context("XACK failed")?;
+        let _: i64 = self
+            .redis
+            .xdel(Self::stream_key(), &[id])
+            .await
+            .context("XDEL failed")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_retry_delay_doubles_and_caps() {
+        let base = Duration::from_secs(15);
+        let max = Duration::from_secs(100);
+        assert_eq!(retry_delay(1, base, max), Duration::from_secs(15));
+        assert_eq!(retry_delay(2, base, max), Duration::from_secs(30));
+        assert_eq!(retry_delay(3, base, max), Duration::from_secs(60));
+        assert_eq!(retry_delay(4, base, max), max);
+        assert_eq!(retry_delay(u64::MAX, base, max), max);
+    }
+
+    #[test]
+    fn test_pending_page_after_excludes_last_id() {
+        assert_eq!(pending_page_after("1700000000000-3"), "(1700000000000-3");
+    }
+
+    #[test]
+    fn test_pending_action() {
+        let config = DecodeQueueConfig::from_properties(&HashMap::from([(
+            "decode_queue_max_attempts".to_string(),
+            "3".to_string(),
+        )]));
+        assert_eq!(
+            pending_action(1, Duration::from_secs(5), &config),
+            PendingAction::Wait
+        );
+        assert_eq!(
+            pending_action(1, Duration::from_secs(15), &config),
+            PendingAction::Redeliver
+        );
+        assert_eq!(
+            pending_action(3, Duration::from_secs(30), &config),
+            PendingAction::Wait
+        );
+        assert_eq!(
+            pending_action(3, Duration::from_secs(60), &config),
+            PendingAction::DeadLetter
+        );
+    }
+
+    #[test]
+    fn test_dispatch_outcome_from_reply() {
+        let done = br#"{"status": {"type": "AbiNotFound", "details": {"message": "none"}}}"#;
+        assert_eq!(DispatchOutcome::from_reply(done), DispatchOutcome::Done);
+
+        let limited = br#"{"status": {"type": "RateLimited", "details": {"message": "slow"}}}"#;
+        assert!(matches!(
+            DispatchOutcome::from_reply(limited),
+            DispatchOutcome::Retry(_)
+        ));
+        assert!(matches!(
+            DispatchOutcome::from_reply(br#"{"error": "boom"}"#),
+            DispatchOutcome::Retry(_)
+        ));
+        assert!(matches!(
+            DispatchOutcome::from_reply(b"not json"),
+            DispatchOutcome::Retry(_)
+        ));
+    }
+}
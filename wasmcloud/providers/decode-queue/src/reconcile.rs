@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use ducklake_common::types::QueryRequest;
+use ekko_client::{EkkoClient, Row};
+use redis::aio::ConnectionManager;
+use retention_policy::DECODE_QUEUE_REQUEUED;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::config::DecodeQueueConfig;
+use crate::queue::{DecodeQueue, PendingDecodeV1};
+
+/// DuckLake-side limit on the stuck-row query
+const QUERY_TIMEOUT_SECS: u32 = 30;
+
+/// Re-requests decodes for lake rows stuck in `decoding_status = 'Pending'`
+///
+/// Requests lost before they reached the queue (or dead-lettered and then
+/// fixed) are found from DuckLake every `reconcile_interval_secs`. Each
+/// transaction is requested at most once per `reconcile_threshold_secs`
+/// (`decode_queue:requeued:{chain_id}:{hash}`), so a decode still in flight
+/// is not queued twice.
+pub struct Reconciler {
+    config: DecodeQueueConfig,
+    queue: DecodeQueue,
+    redis: ConnectionManager,
+    client: EkkoClient,
+}
+
+impl Reconciler {
+    pub async fn connect(config: DecodeQueueConfig, nats: async_nats::Client) -> Result<Self> {
+        let queue = DecodeQueue::connect(config.clone(), nats.clone()).await?;
+        let client = redis::Client::open(config.redis_url.as_str())
+            .context("invalid Redis URL for decode reconciliation")?;
+        let redis = ConnectionManager::new(client)
+            .await
+            .context("failed to connect to Redis")?;
+
+        Ok(Self {
+            config,
+            queue,
+            redis,
+            client: EkkoClient::new(nats)
+                .with_timeout(Duration::from_secs(u64::from(QUERY_TIMEOUT_SECS) + 5)),
+        })
+    }
+
+    pub async fn run(mut self) -> Result<()> {
+        let mut ticker =
+            tokio::time::interval(Duration::from_secs(self.config.reconcile_interval_secs));
+        info!(
+            "Reconciling Pending decodes older than {}s on {} chain(s) every {}s",
+            self.config.reconcile_threshold_secs,
+            self.config.chains.len(),
+            self.config.reconcile_interval_secs
+        );
+
+        loop {
+            ticker.tick().await;
+            for (network, subnet) in self.config.chains.clone() {
+                match self.reconcile_chain(&network, &subnet).await {
+                    Ok(0) => {}
+                    Ok(requeued) => info!(
+                        "Requested {} stuck decodes again on {}.{}",
+                        requeued, network, subnet
+                    ),
+                    Err(e) => error!("Decode reconciliation failed for {network}.{subnet}: {e:?}"),
+                }
+            }
+        }
+    }
+
+    /// Queue the chain's stuck rows again and return how many were queued
+    pub async fn reconcile_chain(&mut self, network: &str, subnet: &str) -> Result<u64> {
+        let chain_id = format!("{}_{}", network, subnet);
+        let request = QueryRequest::new(stuck_decodes_sql(
+            &chain_id,
+            self.config.reconcile_threshold_secs,
+            self.config.reconcile_batch,
+        ))
+        .with_timeout(QUERY_TIMEOUT_SECS);
+        let rows = self
+            .client
+            .query_rows("transactions", network, subnet, &request)
+            .await
+            .context("DuckLake query failed")?;
+
+        let mut requeued = 0;
+        for row in rows {
+            let Some(request) = decode_request_from_row(&row) else {
+                continue;
+            };
+            let marker =
+                DECODE_QUEUE_REQUEUED.key(&format!("{}:{}", chain_id, request.transaction_hash));
+            let first: bool = redis::cmd("SET")
+                .arg(&marker)
+                .arg(1)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.config.reconcile_threshold_secs.max(1))
+                .query_async::<_, Option<String>>(&mut self.redis)
+                .await
+                .context("failed to set requeue marker")?
+                .is_some();
+            if !first {
+                continue;
+            }
+
+            match self.queue.enqueue(&serde_json::to_vec(&request)?).await {
+                Ok(Some(_)) => requeued += 1,
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Failed to requeue decode of {}: {e:?}",
+                    request.transaction_hash
+                ),
+            }
+        }
+        Ok(requeued)
+    }
+}
+
+/// Rows of one chain still `Pending` `threshold_secs` after ingestion
+pub fn stuck_decodes_sql(chain_id: &str, threshold_secs: u64, limit: u64) -> String {
+    format!(
+        "SELECT network, subnet, transaction_hash, to_address, input_data \
+         FROM transactions \
+         WHERE chain_id = '{}' AND decoding_status = 'Pending' \
+         AND ingested_at < now() - INTERVAL '{} seconds' \
+         ORDER BY ingested_at LIMIT {}",
+        chain_id.replace('\'', "''"),
+        threshold_secs,
+        limit
+    )
+}
+
+/// Decode request for a stuck row; rows without call data cannot be decoded
+pub fn decode_request_from_row(row: &Row) -> Option<PendingDecodeV1> {
+    let field = |name: &str| row.get(name).cloned().unwrap_or_default();
+    let request = PendingDecodeV1 {
+        transaction_hash: field("transaction_hash"),
+        network: field("network"),
+        subnet: field("subnet"),
+        to_address: field("to_address"),
+        input_data: field("input_data"),
+        request_id: format!("reconcile-{}", field("transaction_hash")),
+    };
+    if request.transaction_hash.is_empty()
+        || request.network.is_empty()
+        || request.input_data.len() < 10
+    {
+        return None;
+    }
+    Some(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stuck_decodes_sql() {
+        let sql = stuck_decodes_sql("ethereum_main'net", 3600, 500);
+        assert!(sql.contains("chain_id = 'ethereum_main''net'"));
+        assert!(sql.contains("decoding_status = 'Pending'"));
+        assert!(sql.contains("INTERVAL '3600 seconds'"));
+        assert!(sql.ends_with("LIMIT 500"));
+    }
+
+    #[test]
+    fn test_decode_request_from_row() {
+        let mut row = Row::from([
+            ("network".to_string(), "ethereum".to_string()),
+            ("subnet".to_string(), "mainnet".to_string()),
+            ("transaction_hash".to_string(), "0xabc".to_string()),
+            ("to_address".to_string(), "0xto".to_string()),
+            ("input_data".to_string(), "0xa9059cbb0000".to_string()),
+        ]);
+        let request = decode_request_from_row(&row).unwrap();
+        assert_eq!(request.request_id, "reconcile-0xabc");
+        assert_eq!(request.to_address, "0xto");
+
+        row.insert("input_data".to_string(), "0x".to_string());
+        assert!(decode_request_from_row(&row).is_none());
+    }
+}
@@ -0,0 +1,338 @@
+//! Historical gas fee estimator
+//!
+//! Answers `gas.estimate.request` with recommended `maxFeePerGas` /
+//! `maxPriorityFeePerGas` for a set of target confirmation times, plus the
+//! cheapest recurring UTC hour-of-week windows, so bots and the dashboard can
+//! schedule non-urgent transactions cheaply.
+//!
+//! Inputs come from DuckLake:
+//! - `blocks.base_fee_per_gas` for the recent base fee, block time and the
+//!   hour-of-week history
+//! - `transactions.fee_tip / gas_used` for the tips recently paid per gas
+//!
+//! Urgent targets add headroom for base fee growth (at most 12.5% per block);
+//! relaxed targets bid at a lower percentile of recent base fees and wait for
+//! the fee to come down to them.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ducklake_common::{config::DuckLakeConfig, connection::create_readonly_connection};
+use tracing::{debug, instrument};
+
+pub use ducklake_common::types::{
+    CheapWindow, FeeEstimate, GasEstimateRequest, GasEstimateResponse, GAS_ESTIMATE_SUBJECT,
+};
+
+/// Recent blocks sampled for the current base fee and block time
+const RECENT_BLOCKS: usize = 200;
+/// Blocks an hour-of-week window needs before it is recommended
+const MIN_WINDOW_SAMPLES: u64 = 10;
+/// Tip used when no tip history exists (1 gwei)
+const DEFAULT_PRIORITY_FEE_WEI: u64 = 1_000_000_000;
+const DEFAULT_BLOCK_TIME_SECS: f64 = 12.0;
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Fee history loaded from DuckLake for one chain
+#[derive(Debug, Clone, Default)]
+pub struct FeeHistory {
+    /// Recent blocks, newest first: (unix timestamp, base fee)
+    pub recent_blocks: Vec<(i64, u64)>,
+    /// Recent tip per gas at p25, p50, p75, p90
+    pub tip_percentiles: Option<[u64; 4]>,
+    /// (ISO day of week 1-7, UTC hour, median base fee, blocks)
+    pub hour_of_week: Vec<(u32, u32, u64, u64)>,
+}
+
+/// Tip percentile index for a target: next block p90, then p75, p50, p25
+fn urgency(target_blocks: u64) -> usize {
+    match target_blocks {
+        0..=1 => 3,
+        2..=3 => 2,
+        4..=10 => 1,
+        _ => 0,
+    }
+}
+
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Build the response from loaded history
+pub fn estimate(request: &GasEstimateRequest, history: &FeeHistory) -> GasEstimateResponse {
+    let Some(&(_, base_fee)) = history.recent_blocks.first() else {
+        return GasEstimateResponse::error(
+            &request.chain_id,
+            format!("no base fee history for {}", request.chain_id),
+        );
+    };
+
+    let block_time = match (history.recent_blocks.first(), history.recent_blocks.last()) {
+        (Some((newest, _)), Some((oldest, _))) if history.recent_blocks.len() > 1 => {
+            let span = (newest - oldest) as f64 / (history.recent_blocks.len() - 1) as f64;
+            if span > 0.0 {
+                span
+            } else {
+                DEFAULT_BLOCK_TIME_SECS
+            }
+        }
+        _ => DEFAULT_BLOCK_TIME_SECS,
+    };
+
+    let mut recent_base: Vec<u64> = history.recent_blocks.iter().map(|(_, fee)| *fee).collect();
+    recent_base.sort_unstable();
+    let tips = history
+        .tip_percentiles
+        .unwrap_or([DEFAULT_PRIORITY_FEE_WEI; 4]);
+
+    let mut estimates: Vec<FeeEstimate> = request
+        .target_seconds
+        .iter()
+        .map(|&target_seconds| {
+            let target_blocks = ((target_seconds as f64 / block_time).ceil() as u64).max(1);
+            let priority = tips[urgency(target_blocks)];
+            let base_ceiling = match target_blocks {
+                0..=3 => {
+                    let growth = 1.125f64.powi(target_blocks as i32);
+                    (base_fee as f64 * growth).ceil() as u64
+                }
+                4..=10 => base_fee.max(percentile(&recent_base, 0.5)),
+                _ => percentile(&recent_base, 0.25),
+            };
+            FeeEstimate {
+                target_seconds,
+                target_blocks,
+                max_fee_per_gas: base_ceiling.saturating_add(priority),
+                max_priority_fee_per_gas: priority,
+            }
+        })
+        .collect();
+    estimates.sort_by_key(|e| e.target_seconds);
+
+    let mut windows: Vec<&(u32, u32, u64, u64)> = history
+        .hour_of_week
+        .iter()
+        .filter(|(dow, hour, _, samples)| {
+            (1..=7).contains(dow) && *hour < 24 && *samples >= MIN_WINDOW_SAMPLES
+        })
+        .collect();
+    windows.sort_by_key(|(dow, hour, median, _)| (*median, *dow, *hour));
+    let cheapest_windows = windows
+        .into_iter()
+        .take(request.window_count)
+        .map(|&(dow, hour, median, samples)| {
+            let day = WEEKDAYS[(dow - 1) as usize];
+            CheapWindow {
+                label: format!("{} {:02}:00 UTC", day, hour),
+                day_of_week: day.to_string(),
+                hour_utc: hour,
+                median_base_fee_per_gas: median,
+                samples,
+            }
+        })
+        .collect();
+
+    GasEstimateResponse {
+        success: true,
+        chain_id: request.chain_id.clone(),
+        base_fee_per_gas: Some(base_fee),
+        block_time_secs: Some(block_time),
+        estimates,
+        cheapest_windows,
+        error: None,
+    }
+}
+
+/// Loads fee history from DuckLake and answers estimate requests
+pub struct GasEstimator {
+    config: DuckLakeConfig,
+}
+
+impl GasEstimator {
+    pub fn new(config: DuckLakeConfig) -> Self {
+        Self { config }
+    }
+
+    #[instrument(skip(self, request), fields(chain_id = %request.chain_id))]
+    pub async fn handle(&self, request: &GasEstimateRequest) -> GasEstimateResponse {
+        let config = self.config.clone();
+        let chain_id = request.chain_id.clone();
+        let lookback_days = request.lookback_days.max(1);
+        let task =
+            tokio::task::spawn_blocking(move || load_history(&config, &chain_id, lookback_days));
+
+        let history = match tokio::time::timeout(Duration::from_secs(60), task).await {
+            Ok(Ok(Ok(history))) => history,
+            Ok(Ok(Err(e))) => return GasEstimateResponse::error(&request.chain_id, e.to_string()),
+            Ok(Err(e)) => return GasEstimateResponse::error(&request.chain_id, e.to_string()),
+            Err(_) => return GasEstimateResponse::error(&request.chain_id, "estimate timed out"),
+        };
+        debug!(
+            "Loaded {} recent blocks and {} hour-of-week buckets",
+            history.recent_blocks.len(),
+            history.hour_of_week.len()
+        );
+
+        estimate(request, &history)
+    }
+}
+
+fn load_history(config: &DuckLakeConfig, chain_id: &str, lookback_days: u32) -> Result<FeeHistory> {
+    let conn = create_readonly_connection(config)
+        .context("Failed to create DuckLake read-only connection")?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT CAST(epoch(block_timestamp) AS BIGINT), CAST(base_fee_per_gas AS BIGINT) \
+             FROM blocks \
+             WHERE chain_id = ? AND block_date >= current_date - 1 AND base_fee_per_gas IS NOT NULL \
+             ORDER BY block_number DESC LIMIT {}",
+            RECENT_BLOCKS
+        ))
+        .context("Failed to prepare recent base fee query")?;
+    let recent_blocks = stmt
+        .query_map([chain_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?.max(0) as u64))
+        })
+        .context("Recent base fee query failed")?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let recent_count = recent_blocks.len() as i64;
+    let mut stmt = conn
+        .prepare(
+            "SELECT \
+                quantile_cont(tip, 0.25), quantile_cont(tip, 0.5), \
+                quantile_cont(tip, 0.75), quantile_cont(tip, 0.9) \
+             FROM ( \
+                SELECT CAST(fee_tip AS DOUBLE) / gas_used AS tip \
+                FROM transactions \
+                WHERE chain_id = ? AND block_date >= current_date - 1 \
+                  AND block_number > (SELECT MAX(block_number) FROM blocks WHERE chain_id = ?) - ? \
+                  AND fee_tip IS NOT NULL AND gas_used > 0 \
+             )",
+        )
+        .context("Failed to prepare tip query")?;
+    let tip_percentiles = stmt
+        .query_row(duckdb::params![chain_id, chain_id, recent_count], |row| {
+            let mut tips = [0u64; 4];
+            for (i, tip) in tips.iter_mut().enumerate() {
+                match row.get::<_, Option<f64>>(i)? {
+                    Some(value) => *tip = value.max(0.0).ceil() as u64,
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(tips))
+        })
+        .context("Tip query failed")?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT \
+                CAST(isodow(block_timestamp) AS INTEGER), CAST(hour(block_timestamp) AS INTEGER), \
+                CAST(median(base_fee_per_gas) AS BIGINT), COUNT(*) \
+             FROM blocks \
+             WHERE chain_id = ? AND block_date >= current_date - CAST(? AS INTEGER) \
+               AND base_fee_per_gas IS NOT NULL \
+             GROUP BY 1, 2",
+        )
+        .context("Failed to prepare hour-of-week query")?;
+    let hour_of_week = stmt
+        .query_map(duckdb::params![chain_id, lookback_days], |row| {
+            Ok((
+                row.get::<_, i32>(0)? as u32,
+                row.get::<_, i32>(1)? as u32,
+                row.get::<_, i64>(2)?.max(0) as u64,
+                row.get::<_, i64>(3)?.max(0) as u64,
+            ))
+        })
+        .context("Hour-of-week query failed")?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(FeeHistory {
+        recent_blocks,
+        tip_percentiles,
+        hour_of_week,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn request() -> GasEstimateRequest {
+        serde_json::from_str(r#"{"chain_id": "ethereum_mainnet"}"#).unwrap()
+    }
+
+    fn history() -> FeeHistory {
+        FeeHistory {
+            // 12s blocks, base fee rising from 10 to 20 gwei
+            recent_blocks: (0..11)
+                .map(|i| (1_700_000_120 - i * 12, (20 - i as u64) * GWEI))
+                .collect(),
+            tip_percentiles: Some([GWEI / 10, GWEI / 2, GWEI, 2 * GWEI]),
+            hour_of_week: vec![
+                (7, 4, 5 * GWEI, 300),
+                (6, 3, 6 * GWEI, 300),
+                (1, 14, 30 * GWEI, 300),
+                (3, 2, GWEI, 2),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_estimates_scale_with_urgency() {
+        let response = estimate(&request(), &history());
+        assert!(response.success);
+        assert_eq!(response.base_fee_per_gas, Some(20 * GWEI));
+        assert_eq!(response.block_time_secs, Some(12.0));
+
+        let targets: Vec<u64> = response.estimates.iter().map(|e| e.target_blocks).collect();
+        assert_eq!(targets, vec![2, 5, 25, 300]);
+
+        let next = &response.estimates[0];
+        assert_eq!(next.max_priority_fee_per_gas, GWEI);
+        // 20 gwei * 1.125^2 + tip
+        assert_eq!(next.max_fee_per_gas, 25_312_500_000 + GWEI);
+
+        let hour = &response.estimates[3];
+        assert_eq!(hour.max_priority_fee_per_gas, GWEI / 10);
+        assert_eq!(hour.max_fee_per_gas, 13 * GWEI + GWEI / 10);
+        assert!(response
+            .estimates
+            .windows(2)
+            .all(|w| w[0].max_fee_per_gas >= w[1].max_fee_per_gas));
+    }
+
+    #[test]
+    fn test_cheapest_windows_skip_sparse_buckets() {
+        let response = estimate(&request(), &history());
+        let labels: Vec<&str> = response
+            .cheapest_windows
+            .iter()
+            .map(|w| w.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["Sunday 04:00 UTC", "Saturday 03:00 UTC", "Monday 14:00 UTC"]
+        );
+
+        let empty = estimate(&request(), &FeeHistory::default());
+        assert!(!empty.success);
+        assert!(empty.error.unwrap().contains("ethereum_mainnet"));
+    }
+}
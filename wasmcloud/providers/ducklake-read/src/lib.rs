@@ -7,17 +7,21 @@
 //! - Executes SQL queries against shared DuckLake instance
 //! - Returns query results as JSON
 //! - Provides schema discovery via `ducklake.schema.list` and `ducklake.schema.get`
+//! - Answers historical gas fee estimates on `gas.estimate.request`
+//! - Serves wire-format JSON Schemas on `admin.schemas.get`
 //!
 //! Configuration via environment variables:
 //! - NATS_URL: NATS server URL
 //! - DUCKLAKE_POSTGRES_*: PostgreSQL metadata catalog settings
 //! - DUCKLAKE_S3_*: S3/MinIO storage settings
 
+pub mod gas_estimator;
 pub mod nats_listener;
 pub mod provider;
 pub mod reader;
 pub mod schema_handler;
 
+pub use gas_estimator::{GasEstimateRequest, GasEstimateResponse, GasEstimator};
 pub use nats_listener::NatsQueryListener;
 pub use provider::DuckLakeReadProvider;
 pub use reader::DuckLakeReader;
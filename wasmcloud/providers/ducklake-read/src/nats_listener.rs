@@ -4,6 +4,10 @@
 //! - `ducklake.*.*.*.query` - SQL query execution
 //! - `ducklake.schema.list` - List all table schemas
 //! - `ducklake.schema.get` - Get specific table schema
+//! - `gas.estimate.request` - Historical gas fee estimates
+//! - `admin.schemas.get` - JSON Schemas of public message types
+//!
+//! each under the environment subject prefix.
 
 use anyhow::{Context, Result};
 use ducklake_common::subject_parser::SubjectInfo;
@@ -12,7 +16,11 @@ use futures::StreamExt;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info, instrument, warn};
+use wire_schemas::{WireSchemaGetRequest, WireSchemaGetResponse, ADMIN_SCHEMAS_GET_SUBJECT};
 
+use crate::gas_estimator::{
+    GasEstimateRequest, GasEstimateResponse, GasEstimator, GAS_ESTIMATE_SUBJECT,
+};
 use crate::reader::DuckLakeReader;
 use crate::schema_handler::SchemaHandler;
 
@@ -27,6 +35,10 @@ pub struct NatsQueryListenerConfig {
     pub schema_list_subject: String,
     /// Subject for schema get requests
     pub schema_get_subject: String,
+    /// Subject for gas fee estimate requests
+    pub gas_estimate_subject: String,
+    /// Subject for wire-format JSON Schema requests
+    pub wire_schemas_subject: String,
 }
 
 impl NatsQueryListenerConfig {
@@ -46,11 +58,19 @@ impl NatsQueryListenerConfig {
         let schema_get_subject = std::env::var("DUCKLAKE_SCHEMA_GET_SUBJECT")
             .unwrap_or_else(|_| "ducklake.schema.get".to_string());
 
+        let gas_estimate_subject = std::env::var("DUCKLAKE_GAS_ESTIMATE_SUBJECT")
+            .unwrap_or_else(|_| GAS_ESTIMATE_SUBJECT.to_string());
+
+        let wire_schemas_subject = std::env::var("WIRE_SCHEMAS_SUBJECT")
+            .unwrap_or_else(|_| ADMIN_SCHEMAS_GET_SUBJECT.to_string());
+
         Self {
             nats_url,
             query_subject_pattern,
             schema_list_subject,
             schema_get_subject,
+            gas_estimate_subject,
+            wire_schemas_subject,
         }
     }
 
@@ -83,11 +103,25 @@ impl NatsQueryListenerConfig {
             .cloned()
             .unwrap_or_else(|| "ducklake.schema.get".to_string());
 
+        let gas_estimate_subject = props
+            .get("ducklake_gas_estimate_subject")
+            .or_else(|| props.get("DUCKLAKE_GAS_ESTIMATE_SUBJECT"))
+            .cloned()
+            .unwrap_or_else(|| GAS_ESTIMATE_SUBJECT.to_string());
+
+        let wire_schemas_subject = props
+            .get("wire_schemas_subject")
+            .or_else(|| props.get("WIRE_SCHEMAS_SUBJECT"))
+            .cloned()
+            .unwrap_or_else(|| ADMIN_SCHEMAS_GET_SUBJECT.to_string());
+
         Self {
             nats_url,
             query_subject_pattern,
             schema_list_subject,
             schema_get_subject,
+            gas_estimate_subject,
+            wire_schemas_subject,
         }
     }
 }
@@ -97,15 +131,18 @@ pub struct NatsQueryListener {
     config: NatsQueryListenerConfig,
     reader: Arc<DuckLakeReader>,
     schema_handler: SchemaHandler,
+    gas_estimator: GasEstimator,
 }
 
 impl NatsQueryListener {
     /// Create a new NATS query listener
     pub fn new(config: NatsQueryListenerConfig, reader: Arc<DuckLakeReader>) -> Self {
+        let gas_estimator = GasEstimator::new(reader.config().clone());
         Self {
             config,
             reader,
             schema_handler: SchemaHandler::new(),
+            gas_estimator,
         }
     }
 
@@ -120,46 +157,70 @@ impl NatsQueryListener {
 
         info!("Connected to NATS successfully");
 
+        let query_subject = subject_registry::prefixed(&self.config.query_subject_pattern);
+        let schema_list_subject = subject_registry::prefixed(&self.config.schema_list_subject);
+        let schema_get_subject = subject_registry::prefixed(&self.config.schema_get_subject);
+        let gas_estimate_subject = subject_registry::prefixed(&self.config.gas_estimate_subject);
+        let wire_schemas_subject = subject_registry::prefixed(&self.config.wire_schemas_subject);
+        subject_registry::active_prefix()
+            .check_subjects(&[
+                &query_subject,
+                &schema_list_subject,
+                &schema_get_subject,
+                &gas_estimate_subject,
+                &wire_schemas_subject,
+            ])
+            .map_err(anyhow::Error::msg)?;
+
         // Subscribe to query pattern
-        info!(
-            "Subscribing to query pattern: {}",
-            self.config.query_subject_pattern
-        );
+        info!("Subscribing to query pattern: {}", query_subject);
         let mut query_subscriber = client
-            .subscribe(self.config.query_subject_pattern.clone())
+            .subscribe(query_subject.clone())
             .await
             .context("Failed to subscribe to query subject pattern")?;
 
         // Subscribe to schema list subject
-        info!(
-            "Subscribing to schema list: {}",
-            self.config.schema_list_subject
-        );
+        info!("Subscribing to schema list: {}", schema_list_subject);
         let mut schema_list_subscriber = client
-            .subscribe(self.config.schema_list_subject.clone())
+            .subscribe(schema_list_subject.clone())
             .await
             .context("Failed to subscribe to schema list subject")?;
 
         // Subscribe to schema get subject
-        info!(
-            "Subscribing to schema get: {}",
-            self.config.schema_get_subject
-        );
+        info!("Subscribing to schema get: {}", schema_get_subject);
         let mut schema_get_subscriber = client
-            .subscribe(self.config.schema_get_subject.clone())
+            .subscribe(schema_get_subject.clone())
             .await
             .context("Failed to subscribe to schema get subject")?;
 
+        // Subscribe to gas estimate subject
+        info!("Subscribing to gas estimates: {}", gas_estimate_subject);
+        let mut gas_estimate_subscriber = client
+            .subscribe(gas_estimate_subject.clone())
+            .await
+            .context("Failed to subscribe to gas estimate subject")?;
+
+        // Subscribe to wire schema subject
+        info!("Subscribing to wire schemas: {}", wire_schemas_subject);
+        let mut wire_schemas_subscriber = client
+            .subscribe(wire_schemas_subject.clone())
+            .await
+            .context("Failed to subscribe to wire schemas subject")?;
+
         info!("DuckLake Query & Schema Listener is ready");
-        info!("  Query: {}", self.config.query_subject_pattern);
-        info!("  Schema List: {}", self.config.schema_list_subject);
-        info!("  Schema Get: {}", self.config.schema_get_subject);
+        info!("  Query: {}", query_subject);
+        info!("  Schema List: {}", schema_list_subject);
+        info!("  Schema Get: {}", schema_get_subject);
+        info!("  Gas Estimate: {}", gas_estimate_subject);
+        info!("  Wire Schemas: {}", wire_schemas_subject);
 
         // Process messages from all subscriptions using tokio::select!
         loop {
             tokio::select! {
                 Some(message) = query_subscriber.next() => {
-                    let subject = message.subject.to_string();
+                    let subject = subject_registry::unprefixed(message.subject.as_str())
+                        .unwrap_or_default()
+                        .to_string();
                     let reply_to = message.reply.clone();
                     let payload = message.payload.to_vec();
 
@@ -211,6 +272,34 @@ impl NatsQueryListener {
                     }
                 }
 
+                Some(message) = gas_estimate_subscriber.next() => {
+                    let reply_to = message.reply.clone();
+                    let payload = message.payload.to_vec();
+
+                    let response = self.process_gas_estimate(&payload).await;
+                    if let Some(reply_subject) = reply_to {
+                        let response_bytes = serde_json::to_vec(&response)
+                            .unwrap_or_else(|e| format!(r#"{{"error": "{}"}}"#, e).into_bytes());
+                        if let Err(e) = client.publish(reply_subject, response_bytes.into()).await {
+                            error!("Failed to send gas estimate response: {}", e);
+                        }
+                    }
+                }
+
+                Some(message) = wire_schemas_subscriber.next() => {
+                    let reply_to = message.reply.clone();
+                    let payload = message.payload.to_vec();
+
+                    let response = self.process_wire_schemas(&payload);
+                    if let Some(reply_subject) = reply_to {
+                        let response_bytes = serde_json::to_vec(&response)
+                            .unwrap_or_else(|e| format!(r#"{{"error": "{}"}}"#, e).into_bytes());
+                        if let Err(e) = client.publish(reply_subject, response_bytes.into()).await {
+                            error!("Failed to send wire schemas response: {}", e);
+                        }
+                    }
+                }
+
                 else => {
                     warn!("All NATS subscriptions ended");
                     break;
@@ -265,6 +354,43 @@ impl NatsQueryListener {
         self.schema_handler.handle_get(&request)
     }
 
+    /// Process gas estimate request
+    #[instrument(skip(self, payload))]
+    async fn process_gas_estimate(&self, payload: &[u8]) -> GasEstimateResponse {
+        info!("Processing gas estimate request");
+
+        let request: GasEstimateRequest = match serde_json::from_slice(payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse gas estimate request: {}", e);
+                return GasEstimateResponse::error("", format!("Invalid request: {}", e));
+            }
+        };
+
+        self.gas_estimator.handle(&request).await
+    }
+
+    /// Process wire schema request
+    #[instrument(skip(self, payload))]
+    fn process_wire_schemas(&self, payload: &[u8]) -> WireSchemaGetResponse {
+        info!("Processing wire schema request");
+
+        // Parse request (empty payload = list all schemas)
+        let request: WireSchemaGetRequest = if payload.is_empty() {
+            WireSchemaGetRequest::default()
+        } else {
+            match serde_json::from_slice(payload) {
+                Ok(req) => req,
+                Err(e) => {
+                    error!("Failed to parse wire schema request: {}", e);
+                    return WireSchemaGetResponse::error(format!("Invalid request: {}", e));
+                }
+            }
+        };
+
+        wire_schemas::handle_get(&request)
+    }
+
     /// Process a query request
     #[instrument(skip(self, payload), fields(subject = %subject))]
     async fn process_query(&self, subject: &str, payload: &[u8]) -> Result<Vec<u8>> {
@@ -303,6 +429,8 @@ mod tests {
             query_subject_pattern: "ducklake.transactions.*.*.query".to_string(),
             schema_list_subject: "ducklake.schema.list".to_string(),
             schema_get_subject: "ducklake.schema.get".to_string(),
+            gas_estimate_subject: "gas.estimate.request".to_string(),
+            wire_schemas_subject: "admin.schemas.get".to_string(),
         };
         assert_eq!(config.nats_url, "nats://test:4222");
         assert_eq!(
@@ -26,6 +26,11 @@ impl DuckLakeReadProvider {
     pub fn with_config(config: HashMap<String, String>) -> Result<Self> {
         info!("Creating DuckLake Read Provider with config from HostData");
 
+        let subject_prefix = subject_registry::SubjectPrefix::from_properties(&config)
+            .and_then(subject_registry::install_prefix)
+            .map_err(anyhow::Error::msg)?;
+        info!("Subject prefix: {}", subject_prefix);
+
         let ducklake_config = if !config.is_empty() {
             DuckLakeConfig::from_properties(&config)?
         } else {
@@ -25,6 +25,11 @@ impl DuckLakeReader {
         Self { config }
     }
 
+    /// DuckLake configuration the reader connects with
+    pub fn config(&self) -> &DuckLakeConfig {
+        &self.config
+    }
+
     /// Execute a parameterized SQL query and return Arrow IPC stream bytes.
     #[instrument(skip(self, request), fields(query_len = request.query.len()))]
     pub async fn execute_query_ipc(&self, request: &QueryRequest) -> Result<Vec<u8>> {
@@ -15,7 +15,7 @@ use tokio::time::interval;
 use tracing::{debug, info, instrument, warn};
 use uuid::Uuid;
 
-/// Buffer key combining table and chain_id
+/// Buffer key combining table, chain_id and write mode
 pub type BufferKey = String;
 
 /// Micro-batch configuration with configurable triggers
@@ -146,6 +146,18 @@ pub enum FlushTrigger {
     Shutdown,
 }
 
+/// How a batch is applied to its table
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteMode {
+    /// Append every record (`...write`)
+    #[default]
+    Append,
+    /// Update rows matching the table's upsert key in place, inserting the
+    /// rest (`...upsert`)
+    Upsert,
+}
+
 /// A buffered record ready for writing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BufferedRecord {
@@ -155,6 +167,9 @@ pub struct BufferedRecord {
     pub chain_id: String,
     /// Target table name
     pub table: String,
+    /// Append or upsert
+    #[serde(default)]
+    pub mode: WriteMode,
     /// Block timestamp for partitioning
     pub block_timestamp: i64,
     /// Record size in bytes
@@ -169,6 +184,8 @@ pub struct ReadyBatch {
     pub batch_id: Uuid,
     pub table: String,
     pub chain_id: String,
+    #[serde(default)]
+    pub mode: WriteMode,
     pub records: Vec<BufferedRecord>,
     pub flush_reason: FlushTrigger,
     pub total_size_bytes: usize,
@@ -180,16 +197,18 @@ pub struct ReadyBatch {
 struct PartitionBuffer {
     table: String,
     chain_id: String,
+    mode: WriteMode,
     records: Vec<BufferedRecord>,
     buffer_size_bytes: usize,
     last_flush: Instant,
 }
 
 impl PartitionBuffer {
-    fn new(table: String, chain_id: String) -> Self {
+    fn new(table: String, chain_id: String, mode: WriteMode) -> Self {
         Self {
             table,
             chain_id,
+            mode,
             records: Vec::new(),
             buffer_size_bytes: 0,
             last_flush: Instant::now(),
@@ -232,6 +251,7 @@ impl PartitionBuffer {
             batch_id: Uuid::new_v4(),
             table: self.table.clone(),
             chain_id: self.chain_id.clone(),
+            mode: self.mode,
             records,
             flush_reason,
             total_size_bytes,
@@ -257,22 +277,27 @@ impl MicroBatchBuffer {
         }
     }
 
-    /// Create buffer key from table and chain_id
-    fn buffer_key(table: &str, chain_id: &str) -> BufferKey {
-        format!("{}:{}", table, chain_id)
+    /// Create buffer key from table and chain_id; upserts never share a
+    /// batch with appends to the same partition
+    fn buffer_key(table: &str, chain_id: &str, mode: WriteMode) -> BufferKey {
+        match mode {
+            WriteMode::Append => format!("{}:{}", table, chain_id),
+            WriteMode::Upsert => format!("{}:{}:upsert", table, chain_id),
+        }
     }
 
     /// Add a record to the buffer
     #[instrument(skip(self, record), fields(table = %record.table, chain_id = %record.chain_id))]
     pub async fn add_record(&self, record: BufferedRecord) -> Result<(), DuckLakeError> {
-        let key = Self::buffer_key(&record.table, &record.chain_id);
+        let key = Self::buffer_key(&record.table, &record.chain_id, record.mode);
         let table = record.table.clone();
         let chain_id = record.chain_id.clone();
+        let mode = record.mode;
 
         // Get or create buffer for this partition
         let mut buffer = self.buffers.entry(key.clone()).or_insert_with(|| {
             debug!("Creating new buffer for {}:{}", table, chain_id);
-            PartitionBuffer::new(table.clone(), chain_id.clone())
+            PartitionBuffer::new(table.clone(), chain_id.clone(), mode)
         });
 
         // Add record to buffer
@@ -424,6 +449,7 @@ mod tests {
             data: r#"{"test": 1}"#.to_string(),
             chain_id: "ethereum_mainnet".to_string(),
             table: "transactions".to_string(),
+            mode: WriteMode::Append,
             block_timestamp: 1640995200,
             size_bytes: 20,
             buffered_at: Utc::now(),
@@ -438,6 +464,7 @@ mod tests {
             data: r#"{"test": 2}"#.to_string(),
             chain_id: "ethereum_mainnet".to_string(),
             table: "transactions".to_string(),
+            mode: WriteMode::Append,
             block_timestamp: 1640995200,
             size_bytes: 20,
             buffered_at: Utc::now(),
@@ -451,4 +478,33 @@ mod tests {
         assert_eq!(batch.records.len(), 2);
         assert_eq!(batch.flush_reason, FlushTrigger::CountThreshold);
     }
+
+    #[tokio::test]
+    async fn test_upserts_batch_separately_from_appends() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let config = MicroBatchConfig {
+            count_threshold: 2,
+            ..Default::default()
+        };
+        let buffer = MicroBatchBuffer::new(config, tx);
+
+        let record = |mode: WriteMode| BufferedRecord {
+            data: r#"{"transaction_hash": "0xabc", "amount_usd": 1.5}"#.to_string(),
+            chain_id: "ethereum_mainnet".to_string(),
+            table: "transactions".to_string(),
+            mode,
+            block_timestamp: 1640995200,
+            size_bytes: 48,
+            buffered_at: Utc::now(),
+        };
+        buffer.add_record(record(WriteMode::Append)).await.unwrap();
+        buffer.add_record(record(WriteMode::Upsert)).await.unwrap();
+        assert!(rx.try_recv().is_err());
+        assert_eq!(buffer.get_stats().partition_count, 2);
+
+        buffer.add_record(record(WriteMode::Upsert)).await.unwrap();
+        let batch = rx.recv().await.unwrap();
+        assert_eq!(batch.mode, WriteMode::Upsert);
+        assert_eq!(batch.records.len(), 2);
+    }
 }
@@ -1,16 +1,23 @@
 //! NATS listener for DuckLake write operations
 //!
-//! Subscribes to `ducklake.*.*.*.write` and forwards records to the buffer.
+//! Subscribes to `ducklake.*.*.*.write` and `ducklake.*.*.*.upsert` and
+//! forwards records to the buffer. Records failing the inline data-quality
+//! checks are routed to the `quarantine` table with their violation reasons.
+//! Upserts carry partial rows keyed by the table's upsert key, so they skip
+//! the quality checks. Both patterns are subscribed under the environment
+//! subject prefix.
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use ducklake_common::quality::{check_record, quarantine_record};
+use ducklake_common::schemas::QUARANTINE_TABLE;
 use ducklake_common::subject_parser::SubjectInfo;
 use futures::StreamExt;
 use serde_json::Value;
 use std::sync::Arc;
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::buffer::{BufferedRecord, MicroBatchBuffer};
+use crate::buffer::{BufferedRecord, MicroBatchBuffer, WriteMode};
 
 /// NATS listener configuration
 #[derive(Debug, Clone)]
@@ -19,6 +26,9 @@ pub struct NatsListenerConfig {
     pub nats_url: String,
     /// Subject pattern to subscribe to
     pub subject_pattern: String,
+    /// Subject pattern for upserts; skipped when `subject_pattern` already
+    /// covers upsert subjects (e.g. `ducklake.>`)
+    pub upsert_subject_pattern: String,
 }
 
 impl NatsListenerConfig {
@@ -30,10 +40,13 @@ impl NatsListenerConfig {
         // Subscribe to all write operations by default
         let subject_pattern = std::env::var("DUCKLAKE_WRITE_SUBJECT")
             .unwrap_or_else(|_| "ducklake.*.*.*.write".to_string());
+        let upsert_subject_pattern = std::env::var("DUCKLAKE_UPSERT_SUBJECT")
+            .unwrap_or_else(|_| "ducklake.*.*.*.upsert".to_string());
 
         Self {
             nats_url,
             subject_pattern,
+            upsert_subject_pattern,
         }
     }
 
@@ -54,9 +67,16 @@ impl NatsListenerConfig {
             .cloned()
             .unwrap_or_else(|| "ducklake.*.*.*.write".to_string());
 
+        let upsert_subject_pattern = props
+            .get("ducklake_upsert_subject")
+            .or_else(|| props.get("DUCKLAKE_UPSERT_SUBJECT"))
+            .cloned()
+            .unwrap_or_else(|| "ducklake.*.*.*.upsert".to_string());
+
         Self {
             nats_url,
             subject_pattern,
+            upsert_subject_pattern,
         }
     }
 }
@@ -83,19 +103,43 @@ impl NatsWriteListener {
             .context("Failed to connect to NATS")?;
 
         info!("Connected to NATS successfully");
-        info!("Subscribing to pattern: {}", self.config.subject_pattern);
 
-        let mut subscriber = client
-            .subscribe(self.config.subject_pattern.clone())
+        let write_subject = subject_registry::prefixed(&self.config.subject_pattern);
+        let upsert_subject = subject_registry::prefixed(&self.config.upsert_subject_pattern);
+        subject_registry::active_prefix()
+            .check_subjects(&[&write_subject, &upsert_subject])
+            .map_err(anyhow::Error::msg)?;
+        info!("Subscribing to pattern: {}", write_subject);
+
+        let write_subscriber = client
+            .subscribe(write_subject.clone())
             .await
             .context("Failed to subscribe to write subject pattern")?;
+        info!("Successfully subscribed to {}", write_subject);
+
+        // A second overlapping subscription would deliver every upsert twice
+        let mut subscriber = if subject_acl::subject_matches(
+            &self.config.subject_pattern,
+            "ducklake.transactions.ethereum.mainnet.upsert",
+        ) {
+            write_subscriber.boxed()
+        } else {
+            let upsert_subscriber = client
+                .subscribe(upsert_subject.clone())
+                .await
+                .context("Failed to subscribe to upsert subject pattern")?;
+            info!("Successfully subscribed to {}", upsert_subject);
+            futures::stream::select(write_subscriber, upsert_subscriber).boxed()
+        };
 
-        info!("Successfully subscribed to {}", self.config.subject_pattern);
         info!("DuckLake Write Listener is ready");
 
         // Process messages
         while let Some(message) = subscriber.next().await {
-            let subject = message.subject.as_str();
+            let Some(subject) = subject_registry::unprefixed(message.subject.as_str()) else {
+                warn!("Ignoring subject outside environment: {}", message.subject);
+                continue;
+            };
 
             if let Err(e) = self.process_message(subject, &message.payload).await {
                 error!("Failed to process message on {}: {}", subject, e);
@@ -125,17 +169,21 @@ impl NatsWriteListener {
             }
         };
 
-        if subject_info.action != "write" {
-            debug!(
-                "Ignoring DuckLake message with non-write action (action={}): {}",
-                subject_info.action, subject
-            );
-            return Ok(());
-        }
+        let mode = match subject_info.action.as_str() {
+            "write" => WriteMode::Append,
+            "upsert" => WriteMode::Upsert,
+            _ => {
+                debug!(
+                    "Ignoring DuckLake message with non-write action (action={}): {}",
+                    subject_info.action, subject
+                );
+                return Ok(());
+            }
+        };
 
         debug!(
-            "Processing write for table={} chain_id={}",
-            subject_info.table, subject_info.chain_id
+            "Processing {} for table={} chain_id={}",
+            subject_info.action, subject_info.table, subject_info.chain_id
         );
 
         // Deserialize JSON record(s)
@@ -158,7 +206,30 @@ impl NatsWriteListener {
         );
 
         // Process each record
-        for record_value in records {
+        for mut record_value in records {
+            let mut table = subject_info.table.clone();
+            let violations = match mode {
+                WriteMode::Append => check_record(&table, &record_value, Utc::now().timestamp()),
+                WriteMode::Upsert => Vec::new(),
+            };
+            if !violations.is_empty() {
+                let reasons: Vec<&str> = violations.iter().map(|v| v.reason.as_str()).collect();
+                warn!(
+                    "Quarantining record for {}:{}: {}",
+                    table,
+                    subject_info.chain_id,
+                    reasons.join("; ")
+                );
+                record_value = quarantine_record(
+                    &table,
+                    &subject_info.chain_id,
+                    &record_value,
+                    &violations,
+                    Utc::now(),
+                );
+                table = QUARANTINE_TABLE.to_string();
+            }
+
             // Extract block_timestamp for partitioning
             let block_timestamp = record_value
                 .get("block_timestamp")
@@ -173,7 +244,8 @@ impl NatsWriteListener {
             let buffered_record = BufferedRecord {
                 data,
                 chain_id: subject_info.chain_id.clone(),
-                table: subject_info.table.clone(),
+                table,
+                mode,
                 block_timestamp,
                 size_bytes,
                 buffered_at: Utc::now(),
@@ -220,6 +292,7 @@ mod tests {
         let config = NatsListenerConfig::from_env();
         assert_eq!(config.nats_url, "nats://localhost:4222");
         assert_eq!(config.subject_pattern, "ducklake.*.*.*.write");
+        assert_eq!(config.upsert_subject_pattern, "ducklake.*.*.*.upsert");
     }
 
     #[test]
@@ -237,5 +310,6 @@ mod tests {
             config.subject_pattern,
             "ducklake.address_transactions.*.*.write"
         );
+        assert_eq!(config.upsert_subject_pattern, "ducklake.*.*.*.upsert");
     }
 }
@@ -30,6 +30,11 @@ impl DuckLakeWriteProvider {
         info!("Creating DuckLake Write Provider with config from HostData");
         info!("Config keys: {:?}", config.keys().collect::<Vec<_>>());
 
+        let subject_prefix = subject_registry::SubjectPrefix::from_properties(&config)
+            .and_then(subject_registry::install_prefix)
+            .map_err(anyhow::Error::msg)?;
+        info!("Subject prefix: {}", subject_prefix);
+
         // Load DuckLake config - try properties first, fall back to env vars
         let ducklake_config = if !config.is_empty() {
             info!("Using configuration from wasmCloud HostData");
@@ -13,6 +13,15 @@
 //! ## Partitioning Strategy
 //!
 //! Supports both function-based and shard-based partitioning depending on table schema.
+//!
+//! ## Upserts
+//!
+//! Batches from `ducklake.{table}.{chain}.{subnet}.upsert` are staged in a
+//! temp table and applied by the table's upsert key
+//! (`get_upsert_key_columns`): matching rows get the batch's non-null values,
+//! and rows with a new key are inserted when the batch carries every NOT NULL
+//! column. Upserts never clear a value, so partial rows such as
+//! `{chain_id, transaction_hash, amount_usd}` only fill what they carry.
 
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Utc};
@@ -23,12 +32,12 @@ use ducklake_common::{
     migrations::ddl::generate_create_table_ddl,
     partitioner::Partitioner,
     schemas::{
-        get_partition_columns_for_table, get_schema_for_table, CONTRACT_CALLS_TABLE,
-        NOTIFICATION_CONTENT_TABLE, TRANSACTIONS_TABLE,
+        get_partition_columns_for_table, get_schema_for_table, get_upsert_key_columns,
+        CONTRACT_CALLS_TABLE, NOTIFICATION_CONTENT_TABLE, TRANSACTIONS_TABLE,
     },
 };
 use serde_json::{Map, Number, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::sync::Arc;
@@ -36,7 +45,7 @@ use std::time::Instant;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::buffer::ReadyBatch;
+use crate::buffer::{BufferedRecord, ReadyBatch, WriteMode};
 
 fn ensure_contract_calls_block_timestamp(map: &mut Map<String, Value>) -> Result<()> {
     if map.contains_key("block_timestamp") {
@@ -172,14 +181,136 @@ fn select_expr_for_column(column: &str) -> String {
     match column {
         "block_timestamp" | "started_at" | "completed_at" | "first_delivery_at"
         | "all_delivered_at" => format!("make_timestamp(\"{}\"::BIGINT) AS \"{}\"", column, column),
-        "created_at" | "ingested_at" => {
+        "created_at" | "ingested_at" | "occurred_at" | "quarantined_at" | "price_timestamp" => {
             format!("\"{}\"::TIMESTAMP AS \"{}\"", column, column)
         }
-        "notification_date" | "delivery_date" => format!("\"{}\"::DATE AS \"{}\"", column, column),
+        "notification_date" | "delivery_date" | "audit_date" | "quarantine_date" | "price_date" => {
+            format!("\"{}\"::DATE AS \"{}\"", column, column)
+        }
         _ => format!("\"{}\"", column),
     }
 }
 
+/// Indices of the records an upsert batch applies: the last record per key,
+/// skipping records that lack a key column
+fn latest_upsert_indices(records: &[BufferedRecord], key_columns: &[String]) -> HashSet<usize> {
+    let mut latest: HashMap<String, usize> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let Ok(Value::Object(map)) = serde_json::from_str::<Value>(&record.data) else {
+            continue;
+        };
+        let key: Option<Vec<String>> = key_columns
+            .iter()
+            .map(|column| match map.get(column) {
+                None | Some(Value::Null) => None,
+                Some(value) => Some(value.to_string()),
+            })
+            .collect();
+        match key {
+            Some(key) => {
+                latest.insert(key.join("\u{1f}"), index);
+            }
+            None => warn!(
+                "Skipping {} upsert record without key columns {:?}",
+                record.table, key_columns
+            ),
+        }
+    }
+    latest.into_values().collect()
+}
+
+/// UPDATE (and optionally INSERT) statements applying `staging` to `table`
+fn build_upsert_sql(
+    table: &str,
+    staging: &str,
+    key_columns: &[String],
+    column_names: &[String],
+    insert_missing: bool,
+) -> Vec<String> {
+    let key_match = |target: &str| {
+        key_columns
+            .iter()
+            .map(|k| format!("{}.\"{}\" = src.\"{}\"", target, k, k))
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    };
+
+    let mut statements = Vec::new();
+    let assignments = column_names
+        .iter()
+        .filter(|c| !key_columns.contains(c))
+        .map(|c| {
+            format!(
+                "\"{}\" = COALESCE(src.\"{}\", \"{}\".\"{}\")",
+                c, c, table, c
+            )
+        })
+        .collect::<Vec<_>>();
+    if !assignments.is_empty() {
+        statements.push(format!(
+            "UPDATE \"{}\" SET {} FROM \"{}\" AS src WHERE {}",
+            table,
+            assignments.join(", "),
+            staging,
+            key_match(&format!("\"{}\"", table))
+        ));
+    }
+    if insert_missing {
+        let columns_str = column_names
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        statements.push(format!(
+            "INSERT INTO \"{}\" ({}) SELECT {} FROM \"{}\" AS src WHERE NOT EXISTS (SELECT 1 FROM \"{}\" AS dst WHERE {})",
+            table,
+            columns_str,
+            columns_str,
+            staging,
+            table,
+            key_match("dst")
+        ));
+    }
+    statements
+}
+
+/// Stage `source_sql` in a temp table and run the upsert statements in one
+/// transaction
+fn run_upsert(
+    conn: &Connection,
+    staging: &str,
+    source_sql: &str,
+    statements: &[String],
+) -> Result<()> {
+    conn.execute_batch("BEGIN TRANSACTION")
+        .context("Failed to begin upsert transaction")?;
+    let result = conn
+        .execute(
+            &format!("CREATE TEMP TABLE \"{}\" AS {}", staging, source_sql),
+            [],
+        )
+        .context("Failed to stage upsert batch")
+        .and_then(|_| {
+            statements.iter().try_for_each(|sql| {
+                conn.execute(sql, []).map(|_| ()).with_context(|| {
+                    format!(
+                        "Upsert statement failed: {}...",
+                        sql.chars().take(500).collect::<String>()
+                    )
+                })
+            })
+        });
+    match result {
+        Ok(()) => conn
+            .execute_batch(&format!("DROP TABLE \"{}\"; COMMIT", staging))
+            .context("Failed to commit upsert"),
+        Err(e) => {
+            let _ = conn.execute_batch("ROLLBACK");
+            Err(e)
+        }
+    }
+}
+
 fn retain_schema_columns(map: &mut Map<String, Value>, allowed: &HashSet<String>) -> usize {
     let mut dropped = 0usize;
     map.retain(|key, _| {
@@ -289,6 +420,26 @@ impl DuckLakeWriter {
         let schema = get_schema_for_table(&batch.table)
             .ok_or_else(|| anyhow::anyhow!("Unknown table: {}", batch.table))?;
 
+        let upsert_keys = match batch.mode {
+            WriteMode::Append => None,
+            WriteMode::Upsert => {
+                Some(get_upsert_key_columns(&batch.table).ok_or_else(|| {
+                    anyhow::anyhow!("Table {} does not accept upserts", batch.table)
+                })?)
+            }
+        };
+        let upsert_indices = upsert_keys
+            .as_ref()
+            .map(|keys| latest_upsert_indices(&batch.records, keys));
+        if upsert_indices.as_ref().is_some_and(HashSet::is_empty) {
+            warn!(
+                "No keyed records in upsert batch {}, skipping",
+                batch.batch_id
+            );
+            return Ok(());
+        }
+        let append = batch.mode == WriteMode::Append;
+
         // Create connection for this batch write
         let conn = create_ducklake_connection(&self.config)
             .context("Failed to create DuckLake connection")?;
@@ -340,26 +491,31 @@ impl DuckLakeWriter {
                 .with_context(|| format!("Failed to create NDJSON temp file: {}", temp_path))?;
             let mut writer = BufWriter::new(file);
 
-            for record in &batch.records {
+            for (index, record) in batch.records.iter().enumerate() {
+                if matches!(&upsert_indices, Some(indices) if !indices.contains(&index)) {
+                    continue;
+                }
+
                 // Parse JSON and enrich with required fields
                 let mut json_value: Value = serde_json::from_str(&record.data)
                     .with_context(|| format!("Failed to parse JSON record: {}", &record.data))?;
 
                 if let Value::Object(ref mut map) = json_value {
-                    if batch.table == CONTRACT_CALLS_TABLE {
+                    // Upserts carry partial rows; only appends get required fields
+                    if append && batch.table == CONTRACT_CALLS_TABLE {
                         ensure_contract_calls_block_timestamp(map).with_context(|| {
                             format!("Failed to ensure block_timestamp for {}", batch.table)
                         })?;
                     }
 
-                    if batch.table == NOTIFICATION_CONTENT_TABLE {
+                    if append && batch.table == NOTIFICATION_CONTENT_TABLE {
                         ensure_notification_content_partition_fields(map, &partitioner)
                             .with_context(|| {
                                 format!("Failed to ensure partition fields for {}", batch.table)
                             })?;
                     }
 
-                    if is_transactions_table(&batch.table) {
+                    if append && is_transactions_table(&batch.table) {
                         ensure_transaction_required_fields(map, &partitioner, &allowed_columns)
                             .with_context(|| {
                                 format!("Failed to ensure required fields for {}", batch.table)
@@ -367,7 +523,7 @@ impl DuckLakeWriter {
                     }
 
                     // Add ingested_at if not present (required NOT NULL field)
-                    if !map.contains_key("ingested_at") {
+                    if append && !map.contains_key("ingested_at") {
                         map.insert(
                             "ingested_at".to_string(),
                             Value::String(ingestion_timestamp.clone()),
@@ -435,11 +591,47 @@ impl DuckLakeWriter {
 
         // Build SELECT expressions with proper type casting for TIMESTAMP columns
         let select_exprs = build_select_exprs(&column_names);
+        let source_sql = format!(
+            "SELECT {} FROM read_json('{}', format = 'newline_delimited', auto_detect = true, ignore_errors = true)",
+            select_exprs, temp_path
+        );
+
+        if let Some(keys) = &upsert_keys {
+            let insert_missing = schema
+                .fields()
+                .iter()
+                .filter(|field| !field.is_nullable())
+                .all(|field| column_names.contains(field.name()));
+            let staging = format!("upsert_{}", batch.batch_id.simple());
+            let statements =
+                build_upsert_sql(&batch.table, &staging, keys, &column_names, insert_missing);
+            let result = run_upsert(&conn, &staging, &source_sql, &statements);
+            let _ = fs::remove_file(&temp_path);
+
+            match &result {
+                Ok(()) => info!(
+                    "Successfully upserted batch {} into {} ({} records in {:?}, inserts {})",
+                    batch.batch_id,
+                    batch.table,
+                    upsert_indices.as_ref().map_or(0, HashSet::len),
+                    start_time.elapsed(),
+                    if insert_missing { "enabled" } else { "skipped" }
+                ),
+                Err(e) => error!("Batch UPSERT failed with error: {:#}", e),
+            }
+            return result.with_context(|| {
+                format!(
+                    "Failed to upsert {} records into {}",
+                    batch.records.len(),
+                    batch.table
+                )
+            });
+        }
 
         // Execute single batch INSERT using read_json for NDJSON format
         let insert_sql = format!(
-            "INSERT INTO \"{}\" ({}) SELECT {} FROM read_json('{}', format = 'newline_delimited', auto_detect = true, ignore_errors = true)",
-            batch.table, columns_str, select_exprs, temp_path
+            "INSERT INTO \"{}\" ({}) {}",
+            batch.table, columns_str, source_sql
         );
 
         debug!(
@@ -693,6 +885,74 @@ mod tests {
             select_expr_for_column("delivery_date"),
             "\"delivery_date\"::DATE AS \"delivery_date\""
         );
+        assert_eq!(
+            select_expr_for_column("occurred_at"),
+            "\"occurred_at\"::TIMESTAMP AS \"occurred_at\""
+        );
+        assert_eq!(
+            select_expr_for_column("audit_date"),
+            "\"audit_date\"::DATE AS \"audit_date\""
+        );
+        assert_eq!(
+            select_expr_for_column("quarantine_date"),
+            "\"quarantine_date\"::DATE AS \"quarantine_date\""
+        );
+        assert_eq!(
+            select_expr_for_column("price_date"),
+            "\"price_date\"::DATE AS \"price_date\""
+        );
         assert_eq!(select_expr_for_column("other"), "\"other\"");
     }
+
+    #[test]
+    fn test_latest_upsert_indices_keeps_last_record_per_key() {
+        let record = |data: &str| BufferedRecord {
+            data: data.to_string(),
+            chain_id: "ethereum_mainnet".to_string(),
+            table: TRANSACTIONS_TABLE.to_string(),
+            mode: WriteMode::Upsert,
+            block_timestamp: 0,
+            size_bytes: data.len(),
+            buffered_at: Utc::now(),
+        };
+        let records = vec![
+            record(r#"{"chain_id":"ethereum_mainnet","transaction_hash":"0xa","amount_usd":1.0}"#),
+            record(r#"{"chain_id":"ethereum_mainnet","transaction_hash":"0xb","amount_usd":2.0}"#),
+            record(r#"{"chain_id":"ethereum_mainnet","transaction_hash":"0xa","amount_usd":3.0}"#),
+            record(r#"{"chain_id":"ethereum_mainnet","amount_usd":4.0}"#),
+        ];
+        let keys = vec!["chain_id".to_string(), "transaction_hash".to_string()];
+
+        let indices = latest_upsert_indices(&records, &keys);
+        assert_eq!(indices, HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn test_build_upsert_sql() {
+        let keys = vec!["chain_id".to_string(), "transaction_hash".to_string()];
+        let columns = vec![
+            "chain_id".to_string(),
+            "transaction_hash".to_string(),
+            "amount_usd".to_string(),
+        ];
+
+        let statements = build_upsert_sql("transactions", "stage", &keys, &columns, false);
+        assert_eq!(
+            statements,
+            vec![
+                "UPDATE \"transactions\" SET \"amount_usd\" = COALESCE(src.\"amount_usd\", \"transactions\".\"amount_usd\") FROM \"stage\" AS src WHERE \"transactions\".\"chain_id\" = src.\"chain_id\" AND \"transactions\".\"transaction_hash\" = src.\"transaction_hash\"".to_string()
+            ]
+        );
+
+        // Full rows also insert keys that are missing
+        let statements = build_upsert_sql("transactions", "stage", &keys, &columns, true);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[1].starts_with(
+            "INSERT INTO \"transactions\" (\"chain_id\", \"transaction_hash\", \"amount_usd\")"
+        ));
+        assert!(statements[1].ends_with("WHERE dst.\"chain_id\" = src.\"chain_id\" AND dst.\"transaction_hash\" = src.\"transaction_hash\")"));
+
+        // Key-only batches have nothing to update
+        assert!(build_upsert_sql("transactions", "stage", &keys, &keys, false).is_empty());
+    }
 }
@@ -0,0 +1,169 @@
+//! Archive-node routing
+//!
+//! Full nodes prune state older than a few minutes of blocks, so a call for
+//! the balance, code or storage at an old block fails on them ("missing trie
+//! node") while archive nodes, usually on pricier plans, answer it. Endpoints
+//! are tagged `full` (the default) or `archive`:
+//! - a call naming an explicit block more than `history_blocks` behind the
+//!   pool's median head goes to archive endpoints only
+//! - every other call prefers full endpoints and falls back to archive ones
+//!   when no full endpoint is healthy
+//!
+//! Pools without archive endpoints route as before. Calls by block hash, and
+//! calls made before any head is known, count as latest-state calls.
+
+use crate::cost::endpoint_host;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Position of the block parameter per method that reads state at that block
+/// (blocks, receipts and logs stay on full nodes)
+const BLOCK_PARAMS: &[(&str, usize)] = &[
+    ("eth_getBalance", 1),
+    ("eth_getCode", 1),
+    ("eth_getTransactionCount", 1),
+    ("eth_getStorageAt", 2),
+    ("eth_getProof", 2),
+    ("eth_call", 1),
+    ("eth_estimateGas", 1),
+    ("debug_traceBlockByNumber", 0),
+    ("debug_traceCall", 1),
+    ("trace_block", 0),
+    ("trace_call", 2),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    #[default]
+    Full,
+    Archive,
+}
+
+/// Node tags and history depth (`HTTP_RPC_ARCHIVE_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Node kind per endpoint URL or host; a full URL wins over its host
+    pub endpoints: HashMap<String, NodeKind>,
+    /// Blocks behind the head a full node still serves state for
+    pub history_blocks: u64,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: HashMap::new(),
+            history_blocks: 128,
+        }
+    }
+}
+
+impl ArchiveConfig {
+    /// Kind of node behind `endpoint`
+    pub fn kind_of(&self, endpoint: &str) -> NodeKind {
+        self.endpoints
+            .get(endpoint)
+            .or_else(|| self.endpoints.get(&endpoint_host(endpoint)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether the call reads state older than full nodes keep; `head` is the
+    /// pool's current block height, if known
+    pub fn is_historical(&self, method: &str, params: &[Value], head: Option<u64>) -> bool {
+        requested_block(method, params).is_some_and(|block| self.is_old_block(block, head))
+    }
+
+    /// Whether `block` is older than full nodes keep state for
+    pub fn is_old_block(&self, block: u64, head: Option<u64>) -> bool {
+        head.is_some_and(|head| head.saturating_sub(block) > self.history_blocks)
+    }
+}
+
+/// Position of `method`'s block parameter, for methods reading state at a block
+pub fn block_param_position(method: &str) -> Option<usize> {
+    BLOCK_PARAMS
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map(|(_, position)| *position)
+}
+
+/// Explicit block number a call reads at; `None` for block tags, block hashes
+/// and methods without a block parameter
+pub fn requested_block(method: &str, params: &[Value]) -> Option<u64> {
+    let block = match params.get(block_param_position(method)?)? {
+        // EIP-1898 block parameter
+        Value::Object(fields) => fields.get("blockNumber")?,
+        other => other,
+    };
+    let hex = block.as_str()?.strip_prefix("0x")?;
+    // Block hashes are 32 bytes; numbers never get near that
+    if hex.len() > 16 {
+        return None;
+    }
+    u64::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_requested_block() {
+        assert_eq!(
+            requested_block("eth_getBalance", &[json!("0xabc"), json!("0x10")]),
+            Some(16)
+        );
+        assert_eq!(
+            requested_block(
+                "eth_getStorageAt",
+                &[json!("0xabc"), json!("0x0"), json!("0x64")]
+            ),
+            Some(100)
+        );
+        assert_eq!(
+            requested_block(
+                "eth_call",
+                &[json!({"to": "0xabc"}), json!({"blockNumber": "0x20"})]
+            ),
+            Some(32)
+        );
+        assert_eq!(
+            requested_block("eth_getBalance", &[json!("0xabc"), json!("latest")]),
+            None
+        );
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(
+            requested_block("debug_traceBlockByNumber", &[json!(hash)]),
+            None
+        );
+        assert_eq!(requested_block("eth_getBalance", &[json!("0xabc")]), None);
+        assert_eq!(
+            requested_block("eth_getBlockByNumber", &[json!("0x1"), json!(false)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_historical_calls_and_node_kinds() {
+        let config: ArchiveConfig = serde_json::from_str(
+            r#"{"endpoints": {"archive.example.org": "archive"}, "history_blocks": 100}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.kind_of("https://archive.example.org/key"),
+            NodeKind::Archive
+        );
+        assert_eq!(config.kind_of("https://full.example.org"), NodeKind::Full);
+
+        let at = |block: u64| vec![json!("0xabc"), json!(format!("0x{:x}", block))];
+        assert!(config.is_historical("eth_getBalance", &at(899), Some(1_000)));
+        assert!(!config.is_historical("eth_getBalance", &at(900), Some(1_000)));
+        // Blocks ahead of a lagging median are not historical
+        assert!(!config.is_historical("eth_getBalance", &at(1_010), Some(1_000)));
+        assert!(!config.is_historical("eth_getBalance", &at(1), None));
+    }
+}
@@ -0,0 +1,555 @@
+//! Per-endpoint authentication
+//!
+//! Private RPC plans authenticate with a header instead of a key embedded in
+//! the URL. Each endpoint (by host, or by full URL for several keys on one host)
+//! can carry one of:
+//! - `header`: any header, e.g. `{"type": "header", "name": "x-api-key",
+//!   "value": {"env": "ALCHEMY_KEY"}}`
+//! - `bearer`: `Authorization: Bearer <token>`
+//! - `basic`: `Authorization: Basic <username:password>`
+//! - `hmac`: for gateways that want signed requests, an HMAC-SHA256 of
+//!   `<unix seconds>.<body>` in hex under `header` (`x-signature`), with the
+//!   timestamp under `timestamp_header` (`x-timestamp`) and an optional
+//!   `key_id` under `x-key-id`
+//! - `jwt`: `Authorization: Bearer <jwt>`, a fresh HS256 token per request
+//!   with `iat`, `exp` (`ttl_secs` on), the optional `iss`/`aud`, and the
+//!   hex SHA-256 of the body as `body_sha256`
+//!
+//! Secrets are given as `{"value": "..."}`, `{"env": "VAR"}` or
+//! `{"redis": "key"}` and re-read every `refresh_secs`, so a key rotated in the
+//! environment or in Redis is picked up without a restart. Until a secret
+//! resolves, calls go out without it. Secrets never appear in `Debug` output
+//! or status.
+
+use crate::cost::endpoint_host;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use parking_lot::RwLock;
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+const DEFAULT_REFRESH_SECS: u64 = 300;
+
+/// Header carrying the key id of `hmac` signatures
+const KEY_ID_HEADER: &str = "x-key-id";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn default_signature_header() -> String {
+    "x-signature".to_string()
+}
+
+fn default_timestamp_header() -> String {
+    "x-timestamp".to_string()
+}
+
+fn default_jwt_ttl() -> u64 {
+    60
+}
+
+/// Where a secret is read from
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretSource {
+    /// Inline secret
+    Value(String),
+    /// Environment variable holding the secret
+    Env(String),
+    /// Redis key holding the secret
+    Redis(String),
+}
+
+impl fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(_) => write!(f, "Value(<redacted>)"),
+            Self::Env(var) => f.debug_tuple("Env").field(var).finish(),
+            Self::Redis(key) => f.debug_tuple("Redis").field(key).finish(),
+        }
+    }
+}
+
+impl SecretSource {
+    /// Redis key to fetch, if the secret lives in Redis
+    pub fn redis_key(&self) -> Option<&str> {
+        match self {
+            Self::Redis(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Current secret; `redis` holds the secrets fetched from Redis
+    fn resolve(&self, redis: &HashMap<String, String>) -> Result<String, String> {
+        let secret = match self {
+            Self::Value(value) => Some(value.clone()),
+            Self::Env(var) => std::env::var(var).ok(),
+            Self::Redis(key) => redis.get(key).cloned(),
+        };
+        match secret.map(|s| s.trim().to_string()) {
+            Some(secret) if !secret.is_empty() => Ok(secret),
+            _ => Err(match self {
+                Self::Value(_) => "empty inline secret".to_string(),
+                Self::Env(var) => format!("environment variable {} is not set", var),
+                Self::Redis(key) => format!("Redis key {} is not set", key),
+            }),
+        }
+    }
+}
+
+/// Authentication for one endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EndpointAuth {
+    Header {
+        name: String,
+        value: SecretSource,
+    },
+    Bearer {
+        token: SecretSource,
+    },
+    Basic {
+        username: String,
+        password: SecretSource,
+    },
+    Hmac {
+        secret: SecretSource,
+        #[serde(default = "default_signature_header")]
+        header: String,
+        #[serde(default = "default_timestamp_header")]
+        timestamp_header: String,
+        #[serde(default)]
+        key_id: Option<String>,
+    },
+    Jwt {
+        secret: SecretSource,
+        #[serde(default)]
+        issuer: Option<String>,
+        #[serde(default)]
+        audience: Option<String>,
+        #[serde(default = "default_jwt_ttl")]
+        ttl_secs: u64,
+    },
+}
+
+impl EndpointAuth {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Header { .. } => "header",
+            Self::Bearer { .. } => "bearer",
+            Self::Basic { .. } => "basic",
+            Self::Hmac { .. } => "hmac",
+            Self::Jwt { .. } => "jwt",
+        }
+    }
+
+    fn secret(&self) -> &SecretSource {
+        match self {
+            Self::Header { value, .. } => value,
+            Self::Bearer { token } => token,
+            Self::Basic { password, .. } => password,
+            Self::Hmac { secret, .. } | Self::Jwt { secret, .. } => secret,
+        }
+    }
+
+    fn resolve(&self, redis: &HashMap<String, String>) -> Result<Resolved, String> {
+        let secret = self.secret().resolve(redis)?;
+        Ok(match self {
+            Self::Header { name, .. } => Resolved::Header(name.clone(), secret),
+            Self::Bearer { .. } => Resolved::Bearer(secret),
+            Self::Basic { username, .. } => Resolved::Basic(username.clone(), secret),
+            Self::Hmac {
+                header,
+                timestamp_header,
+                key_id,
+                ..
+            } => Resolved::Hmac {
+                header: header.clone(),
+                timestamp_header: timestamp_header.clone(),
+                key_id: key_id.clone(),
+                secret,
+            },
+            Self::Jwt {
+                issuer,
+                audience,
+                ttl_secs,
+                ..
+            } => Resolved::Jwt {
+                issuer: issuer.clone(),
+                audience: audience.clone(),
+                ttl_secs: *ttl_secs,
+                secret,
+            },
+        })
+    }
+}
+
+/// Endpoint authentication (`HTTP_RPC_AUTH_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Auth per endpoint URL or host; a full URL wins over its host
+    pub endpoints: HashMap<String, EndpointAuth>,
+    /// Seconds between re-reads of env and Redis secrets
+    pub refresh_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: HashMap::new(),
+            refresh_secs: DEFAULT_REFRESH_SECS,
+        }
+    }
+}
+
+impl AuthConfig {
+    /// Auth that applies to `endpoint`, if any
+    pub fn auth_for(&self, endpoint: &str) -> Option<EndpointAuth> {
+        self.endpoints
+            .get(endpoint)
+            .or_else(|| self.endpoints.get(&endpoint_host(endpoint)))
+            .cloned()
+    }
+}
+
+/// Credentials snapshot for one endpoint (no secrets)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthStatus {
+    pub endpoint: String,
+    /// `header`, `bearer`, `basic`, `hmac` or `jwt`
+    pub kind: String,
+    pub resolved: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum Resolved {
+    Header(String, String),
+    Bearer(String),
+    Basic(String, String),
+    Hmac {
+        header: String,
+        timestamp_header: String,
+        key_id: Option<String>,
+        secret: String,
+    },
+    Jwt {
+        issuer: Option<String>,
+        audience: Option<String>,
+        ttl_secs: u64,
+        secret: String,
+    },
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hmac_sha256(secret: &str, message: &[u8]) -> Vec<u8> {
+    // HMAC takes keys of any length
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC key");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// HS256 token over `body`, issued at `now` (unix seconds)
+fn jwt(
+    issuer: Option<&str>,
+    audience: Option<&str>,
+    ttl_secs: u64,
+    secret: &str,
+    body: &[u8],
+    now: u64,
+) -> String {
+    let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+    let mut claims = json!({
+        "iat": now,
+        "exp": now + ttl_secs,
+        "body_sha256": to_hex(&Sha256::digest(body)),
+    });
+    if let Some(issuer) = issuer {
+        claims["iss"] = json!(issuer);
+    }
+    if let Some(audience) = audience {
+        claims["aud"] = json!(audience);
+    }
+    let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let signing_input = format!("{}.{}", header, claims);
+    let signature = URL_SAFE_NO_PAD.encode(hmac_sha256(secret, signing_input.as_bytes()));
+    format!("{}.{}", signing_input, signature)
+}
+
+impl Resolved {
+    /// Signature headers for `body` sent at `now` (unix seconds); empty for
+    /// auth that does not sign
+    fn signed_headers(&self, body: &[u8], now: u64) -> Vec<(String, String)> {
+        match self {
+            Self::Hmac {
+                header,
+                timestamp_header,
+                key_id,
+                secret,
+            } => {
+                let timestamp = now.to_string();
+                let mut message = format!("{}.", timestamp).into_bytes();
+                message.extend_from_slice(body);
+                let mut headers = vec![
+                    (header.clone(), to_hex(&hmac_sha256(secret, &message))),
+                    (timestamp_header.clone(), timestamp),
+                ];
+                if let Some(key_id) = key_id {
+                    headers.push((KEY_ID_HEADER.to_string(), key_id.clone()));
+                }
+                headers
+            }
+            Self::Jwt {
+                issuer,
+                audience,
+                ttl_secs,
+                secret,
+            } => {
+                let token = jwt(
+                    issuer.as_deref(),
+                    audience.as_deref(),
+                    *ttl_secs,
+                    secret,
+                    body,
+                    now,
+                );
+                vec![("authorization".to_string(), format!("Bearer {}", token))]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CredentialState {
+    resolved: Option<Resolved>,
+    last_error: Option<String>,
+}
+
+/// Resolved credentials for one endpoint
+pub struct Credentials {
+    endpoint: String,
+    auth: Option<EndpointAuth>,
+    state: RwLock<CredentialState>,
+}
+
+impl Credentials {
+    /// Credentials for `endpoint`; env and inline secrets resolve right away
+    pub fn new(endpoint: &str, config: &AuthConfig) -> Self {
+        let credentials = Self {
+            endpoint: endpoint_host(endpoint),
+            auth: config.auth_for(endpoint),
+            state: RwLock::new(CredentialState::default()),
+        };
+        if credentials.redis_key().is_none() {
+            credentials.refresh(&HashMap::new());
+        }
+        credentials
+    }
+
+    /// Redis key the secret is read from, if any
+    pub fn redis_key(&self) -> Option<&str> {
+        self.auth.as_ref()?.secret().redis_key()
+    }
+
+    /// Re-read the secret; `redis` holds the values fetched for `redis_key`
+    ///
+    /// A failed read keeps the last good secret.
+    pub fn refresh(&self, redis: &HashMap<String, String>) {
+        let Some(auth) = &self.auth else {
+            return;
+        };
+        let mut state = self.state.write();
+        match auth.resolve(redis) {
+            Ok(resolved) => {
+                if state.resolved.as_ref().is_some_and(|old| *old != resolved) {
+                    info!("Rotated {} credentials for {}", auth.kind(), self.endpoint);
+                }
+                state.resolved = Some(resolved);
+                state.last_error = None;
+            }
+            Err(error) => {
+                if state.last_error.as_ref() != Some(&error) {
+                    warn!("Credentials for {} unavailable: {}", self.endpoint, error);
+                }
+                state.last_error = Some(error);
+            }
+        }
+    }
+
+    /// Add the endpoint's auth to an outgoing request carrying `body`; signing
+    /// auth signs the exact bytes sent
+    pub fn apply(&self, request: RequestBuilder, body: &[u8]) -> RequestBuilder {
+        match &self.state.read().resolved {
+            Some(signed @ (Resolved::Hmac { .. } | Resolved::Jwt { .. })) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs());
+                signed
+                    .signed_headers(body, now)
+                    .into_iter()
+                    .fold(request, |request, (name, value)| {
+                        request.header(name, value)
+                    })
+            }
+            Some(Resolved::Header(name, value)) => request.header(name.as_str(), value.as_str()),
+            Some(Resolved::Bearer(token)) => request.bearer_auth(token),
+            Some(Resolved::Basic(username, password)) => {
+                request.basic_auth(username, Some(password))
+            }
+            None => request,
+        }
+    }
+
+    /// `None` for endpoints without auth
+    pub fn status(&self) -> Option<AuthStatus> {
+        let auth = self.auth.as_ref()?;
+        let state = self.state.read();
+        Some(AuthStatus {
+            endpoint: self.endpoint.clone(),
+            kind: auth.kind().to_string(),
+            resolved: state.resolved.is_some(),
+            last_error: state.last_error.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> AuthConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_auth_for_url_then_host() {
+        let config = config(
+            r#"{"endpoints": {
+                "eth-mainnet.g.alchemy.com": {"type": "header", "name": "x-api-key", "value": {"value": "k1"}},
+                "https://eth-mainnet.g.alchemy.com/v2/team-b": {"type": "bearer", "token": {"env": "TEAM_B_TOKEN"}}
+            }}"#,
+        );
+        assert_eq!(config.refresh_secs, 300);
+        assert!(matches!(
+            config.auth_for("https://eth-mainnet.g.alchemy.com/v2"),
+            Some(EndpointAuth::Header { .. })
+        ));
+        assert!(matches!(
+            config.auth_for("https://eth-mainnet.g.alchemy.com/v2/team-b"),
+            Some(EndpointAuth::Bearer { .. })
+        ));
+        assert_eq!(config.auth_for("https://rpc.example.org"), None);
+    }
+
+    #[test]
+    fn test_redis_secret_rotates_and_survives_failed_reads() {
+        let config = config(
+            r#"{"endpoints": {"rpc.example.org": {"type": "basic", "username": "ekko", "password": {"redis": "secrets:rpc"}}}}"#,
+        );
+        let credentials = Credentials::new("https://rpc.example.org", &config);
+        assert_eq!(credentials.redis_key(), Some("secrets:rpc"));
+        assert!(!credentials.status().unwrap().resolved);
+
+        let mut redis = HashMap::new();
+        redis.insert("secrets:rpc".to_string(), "first".to_string());
+        credentials.refresh(&redis);
+        assert!(credentials.status().unwrap().resolved);
+
+        redis.insert("secrets:rpc".to_string(), "second".to_string());
+        credentials.refresh(&redis);
+        assert!(matches!(
+            &credentials.state.read().resolved,
+            Some(Resolved::Basic(user, password)) if user == "ekko" && password == "second"
+        ));
+
+        // Losing the key keeps the last good secret
+        credentials.refresh(&HashMap::new());
+        let status = credentials.status().unwrap();
+        assert!(status.resolved);
+        assert!(status.last_error.unwrap().contains("secrets:rpc"));
+    }
+
+    #[test]
+    fn test_hmac_signs_timestamp_and_body() {
+        let config = config(
+            r#"{"endpoints": {"gateway.internal": {"type": "hmac", "secret": {"value": "key"}, "key_id": "ekko-1"}}}"#,
+        );
+        let credentials = Credentials::new("https://gateway.internal/rpc", &config);
+        assert_eq!(credentials.status().unwrap().kind, "hmac");
+        let state = credentials.state.read();
+        let resolved = state.resolved.as_ref().unwrap();
+
+        let body = br#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
+        let headers = resolved.signed_headers(body, 1_700_000_000);
+        let mut message = b"1700000000.".to_vec();
+        message.extend_from_slice(body);
+        assert_eq!(
+            headers,
+            vec![
+                (
+                    "x-signature".to_string(),
+                    to_hex(&hmac_sha256("key", &message))
+                ),
+                ("x-timestamp".to_string(), "1700000000".to_string()),
+                ("x-key-id".to_string(), "ekko-1".to_string()),
+            ]
+        );
+        // Any other body or time signs differently
+        assert_ne!(resolved.signed_headers(b"{}", 1_700_000_000), headers);
+        assert_ne!(resolved.signed_headers(body, 1_700_000_001), headers);
+    }
+
+    #[test]
+    fn test_jwt_claims_and_signature() {
+        let config = config(
+            r#"{"endpoints": {"gateway.internal": {"type": "jwt", "secret": {"value": "key"}, "issuer": "ekko", "audience": "rpc-gateway"}}}"#,
+        );
+        let credentials = Credentials::new("https://gateway.internal", &config);
+        let state = credentials.state.read();
+        let headers = state
+            .resolved
+            .as_ref()
+            .unwrap()
+            .signed_headers(b"{}", 1_700_000_000);
+        assert_eq!(headers[0].0, "authorization");
+        let token = headers[0].1.strip_prefix("Bearer ").unwrap();
+
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let claims: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1]).unwrap()).unwrap();
+        assert_eq!(claims["iss"], "ekko");
+        assert_eq!(claims["aud"], "rpc-gateway");
+        assert_eq!(claims["exp"], 1_700_000_060);
+        assert_eq!(claims["body_sha256"], to_hex(&Sha256::digest(b"{}")));
+        let signature = hmac_sha256("key", format!("{}.{}", parts[0], parts[1]).as_bytes());
+        assert_eq!(URL_SAFE_NO_PAD.decode(parts[2]).unwrap(), signature);
+    }
+
+    #[test]
+    fn test_inline_secret_is_redacted() {
+        let source = SecretSource::Value("super-secret".to_string());
+        assert!(!format!("{:?}", source).contains("super-secret"));
+
+        let config = config(
+            r#"{"endpoints": {"rpc.example.org": {"type": "bearer", "token": {"value": "super-secret"}}}}"#,
+        );
+        let credentials = Credentials::new("https://rpc.example.org", &config);
+        assert!(credentials.status().unwrap().resolved);
+        assert!(Credentials::new("https://other.example.org", &config)
+            .status()
+            .is_none());
+    }
+}
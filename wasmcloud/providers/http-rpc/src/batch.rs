@@ -0,0 +1,230 @@
+//! JSON-RPC batch requests
+//!
+//! Fetching a block with its receipts, or a range of blocks, costs one HTTP
+//! round trip per call. A batch packs the calls into one JSON array payload;
+//! the endpoint answers with an array in any order, which is matched back to
+//! the calls by id.
+//!
+//! Vendors cap the batch size (and some reject large batches outright), so
+//! every endpoint has a limit: the built-in value for its vendor, or an
+//! override keyed by host in [`BatchConfig::endpoints`]. Batches larger than
+//! the chosen endpoint's limit are sent as several payloads.
+
+use crate::cost::{detect_vendor, endpoint_host};
+use crate::endpoint_pool::{RpcError, RpcRequest, RpcResponse};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Built-in batch size limits per vendor (see `cost::detect_vendor`)
+const BUILTIN_LIMITS: &[(&str, usize)] = &[
+    ("alchemy", 1000),
+    ("infura", 1000),
+    ("quicknode", 100),
+    ("flat", 50),
+];
+
+/// JSON-RPC error code for a call the endpoint left out of its batch answer
+pub const MISSING_RESPONSE_CODE: i32 = -32603;
+
+/// Batch size limits (`HTTP_RPC_BATCH_CONFIG`, JSON)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchConfig {
+    /// Limit for endpoints with no override; the vendor's built-in limit when unset
+    pub default_max_batch_size: Option<usize>,
+    /// Per-endpoint limits keyed by endpoint host
+    pub endpoints: HashMap<String, usize>,
+}
+
+impl BatchConfig {
+    /// Most calls `endpoint` accepts in one payload (at least 1)
+    pub fn max_batch_size(&self, endpoint: &str) -> usize {
+        let host = endpoint_host(endpoint);
+        let limit = self
+            .endpoints
+            .get(&host)
+            .copied()
+            .or(self.default_max_batch_size)
+            .unwrap_or_else(|| {
+                let vendor = detect_vendor(&host);
+                BUILTIN_LIMITS
+                    .iter()
+                    .find(|(name, _)| *name == vendor)
+                    .map_or(1, |(_, limit)| *limit)
+            });
+        limit.max(1)
+    }
+}
+
+/// Calls to send together; answered in the same order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RpcBatchRequest {
+    pub requests: Vec<RpcRequest>,
+}
+
+impl RpcBatchRequest {
+    pub fn new(requests: Vec<RpcRequest>) -> Self {
+        Self { requests }
+    }
+
+    /// Add a call
+    pub fn push(mut self, method: &str, params: Vec<serde_json::Value>) -> Self {
+        self.requests.push(RpcRequest::new(method, params));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+/// One response per request, in request order, carrying the caller's ids.
+/// Per-call JSON-RPC errors stay in their response's `error`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RpcBatchResponse {
+    pub responses: Vec<RpcResponse>,
+}
+
+impl RpcBatchResponse {
+    /// Results in request order; fails on the first call that errored
+    pub fn into_results(self) -> Result<Vec<serde_json::Value>> {
+        self.responses
+            .into_iter()
+            .map(|response| match response.error {
+                Some(error) => Err(anyhow!("RPC error {}: {}", error.code, error.message)),
+                None => Ok(response.result.unwrap_or(serde_json::Value::Null)),
+            })
+            .collect()
+    }
+}
+
+/// Match a batch answer back to `ids` (the ids sent, in order)
+///
+/// Fails when the body is not an array, which is how endpoints without batch
+/// support answer. Calls missing from the array get a `MISSING_RESPONSE_CODE`
+/// error.
+pub fn split_responses(ids: &[u64], body: serde_json::Value) -> Result<Vec<RpcResponse>> {
+    let serde_json::Value::Array(items) = body else {
+        let detail = body
+            .get("error")
+            .map(|error| error.to_string())
+            .unwrap_or_else(|| "not an array".to_string());
+        return Err(anyhow!("Batch request rejected: {}", detail));
+    };
+
+    let mut by_id: HashMap<u64, RpcResponse> = HashMap::with_capacity(items.len());
+    for item in items {
+        match serde_json::from_value::<RpcResponse>(item) {
+            Ok(response) => {
+                by_id.insert(response.id, response);
+            }
+            Err(e) => return Err(anyhow!("Failed to parse batch response item: {}", e)),
+        }
+    }
+
+    Ok(ids
+        .iter()
+        .map(|id| {
+            by_id.remove(id).unwrap_or_else(|| RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: None,
+                error: Some(RpcError {
+                    code: MISSING_RESPONSE_CODE,
+                    message: "missing from batch response".to_string(),
+                    data: None,
+                }),
+                id: *id,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_max_batch_size_per_endpoint() {
+        let mut config = BatchConfig::default();
+        assert_eq!(
+            config.max_batch_size("https://eth-mainnet.g.alchemy.com/v2/key"),
+            1000
+        );
+        assert_eq!(config.max_batch_size("https://rpc.example.org"), 50);
+
+        config.endpoints.insert("rpc.example.org".to_string(), 10);
+        config.default_max_batch_size = Some(200);
+        assert_eq!(config.max_batch_size("https://rpc.example.org"), 10);
+        assert_eq!(
+            config.max_batch_size("https://mainnet.infura.io/v3/key"),
+            200
+        );
+
+        config.endpoints.insert("rpc.example.org".to_string(), 0);
+        assert_eq!(config.max_batch_size("https://rpc.example.org"), 1);
+    }
+
+    #[test]
+    fn test_split_responses_by_id() {
+        let body = json!([
+            {"jsonrpc": "2.0", "id": 2, "result": "0x2"},
+            {"jsonrpc": "2.0", "id": 0, "result": "0x0"},
+            {"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "header not found"}}
+        ]);
+        let responses = split_responses(&[0, 1, 2, 3], body).unwrap();
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0].result, Some(json!("0x0")));
+        assert_eq!(responses[1].error.as_ref().unwrap().code, -32000);
+        assert_eq!(responses[2].result, Some(json!("0x2")));
+        assert_eq!(
+            responses[3].error.as_ref().unwrap().code,
+            MISSING_RESPONSE_CODE
+        );
+    }
+
+    #[test]
+    fn test_split_responses_rejects_non_batch_answers() {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {"code": -32600, "message": "batch requests not supported"}
+        });
+        let error = split_responses(&[0], body).unwrap_err();
+        assert!(error.to_string().contains("not supported"));
+    }
+
+    #[test]
+    fn test_batch_response_results() {
+        let batch = RpcBatchRequest::default()
+            .push("eth_blockNumber", vec![])
+            .push("eth_chainId", vec![]);
+        assert_eq!(batch.len(), 2);
+
+        let ok = RpcBatchResponse {
+            responses: split_responses(
+                &[0, 1],
+                json!([
+                    {"jsonrpc": "2.0", "id": 0, "result": "0x10"},
+                    {"jsonrpc": "2.0", "id": 1, "result": "0x1"}
+                ]),
+            )
+            .unwrap(),
+        };
+        assert_eq!(
+            ok.into_results().unwrap(),
+            vec![json!("0x10"), json!("0x1")]
+        );
+
+        let failed = RpcBatchResponse {
+            responses: split_responses(&[0], json!([])).unwrap(),
+        };
+        assert!(failed.into_results().is_err());
+    }
+}
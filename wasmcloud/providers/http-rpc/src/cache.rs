@@ -1,22 +1,257 @@
-//! Redis-backed caching for RPC responses
+//! Redis-backed (or NATS KV, or in-memory) caching for RPC responses
 //!
 //! Provides caching capabilities to reduce RPC endpoint load and improve response times.
-//! Supports configurable TTLs per cache key pattern.
+//! Supports configurable TTLs per cache key pattern, and per JSON-RPC method through
+//! [`CacheConfig::method_ttls`] (e.g. `{"eth_chainId": "forever", "eth_gasPrice": 5,
+//! "eth_getLogs": 30}`), which take precedence over the block/tx/default TTLs.
+//!
+//! Keys are built from the call's params in canonical form: object keys
+//! sorted, hex strings and block tags lowercased, and block numbers as minimal
+//! hex quantities, so `"0xA1"` and `"0xa1"`, or `"0x0f"`, `"0xf"` and `15` as a
+//! block, share one entry.
+//!
+//! Answers that depend on the chain head (`eth_blockNumber`, or any call with a
+//! `"latest"`/`"pending"` block tag) are also tracked per network, so a new
+//! block can drop them all at once ([`RpcCache::invalidate_latest`]).
+//!
+//! A bounded in-process LRU tier sits in front of Redis: reads check it first
+//! and Redis hits are copied into it, so hot calls such as `eth_chainId` skip
+//! the Redis round trip. Its entries live for at most `memory_ttl` seconds (and
+//! never longer than their Redis TTL); `memory_capacity: 0` turns it off.
+//!
+//! Entries written together, e.g. every call about one new block, would also
+//! expire together and send their callers upstream at once. Each TTL is
+//! shortened by a random share of up to `ttl_jitter`, so they age out over a
+//! spread instead. When an entry is missing, one caller takes a short-lived
+//! refresh lock in the backend and asks upstream; the others poll the cache
+//! for its answer for up to `refresh_wait_ms` and only then ask themselves.
+//!
+//! Redis is a single instance, a cluster or a sentinel-managed master set
+//! ([`CacheConfig::redis`]), with optional TLS and AUTH. Deployments without
+//! Redis can put a NATS JetStream KV bucket or a plain in-process map behind
+//! the tier instead ([`CacheConfig::backend`]).
 
+use crate::archive::block_param_position;
+use crate::cache_backend::{CacheBackend, CacheBackendConfig};
+use crate::redis_topology::RedisTopologyConfig;
 use anyhow::{anyhow, Result};
-use redis::{AsyncCommands, Client as RedisClient};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
 
+/// Methods whose answer never changes for a network, and mempool snapshots,
+/// which are stale as soon as they are read
+const BUILTIN_METHOD_TTLS: &[(&str, CacheTtl)] = &[
+    ("eth_chainId", CacheTtl::Forever),
+    ("net_version", CacheTtl::Forever),
+    ("txpool_content", CacheTtl::Never),
+];
+
+/// Block tags whose answer moves with the chain head
+const HEAD_TAGS: &[&str] = &["latest", "pending"];
+
+/// Every block tag, lowercased in keys
+const BLOCK_TAGS: &[&str] = &["latest", "pending", "earliest", "safe", "finalized"];
+
+/// Methods whose first param is a block number, besides the state reads of
+/// [`block_param_position`]
+const BLOCK_FIRST_METHODS: &[&str] = &[
+    "eth_getBlockByNumber",
+    "eth_getBlockReceipts",
+    "eth_getBlockTransactionCountByNumber",
+    "eth_getTransactionByBlockNumberAndIndex",
+    "eth_getUncleByBlockNumberAndIndex",
+    "eth_getUncleCountByBlockNumber",
+];
+
+/// Object fields holding a block number or tag (log filters, EIP-1898)
+const BLOCK_FIELDS: &[&str] = &["fromBlock", "toBlock", "blockNumber"];
+
+/// Suffix of the key holding a call's refresh lock
+const REFRESH_SUFFIX: &str = ":refresh";
+
+/// How often a caller waiting on another's refresh checks the cache
+const REFRESH_POLL: Duration = Duration::from_millis(25);
+
+/// Suffix of the key holding a call's negative-cache entry
+const NEGATIVE_SUFFIX: &str = ":negative";
+
+/// Key of the negative-cache entry for the call cached under `key`
+pub fn negative_key(key: &str) -> String {
+    format!("{}{}", key, NEGATIVE_SUFFIX)
+}
+
+/// Suffix of the key holding a call's stale-while-revalidate copy
+const STALE_SUFFIX: &str = ":stale";
+
+/// Key of the stale copy of the call cached under `key`
+pub fn stale_key(key: &str) -> String {
+    format!("{}{}", key, STALE_SUFFIX)
+}
+
+/// Whether a cached answer to this call goes stale on the next block
+pub fn is_head_dependent(method: &str, params: &[Value]) -> bool {
+    let is_head_tag = |value: &Value| {
+        value
+            .as_str()
+            .is_some_and(|tag| HEAD_TAGS.iter().any(|head| tag.eq_ignore_ascii_case(head)))
+    };
+    method == "eth_blockNumber"
+        || params.iter().any(|param| match param {
+            Value::Object(fields) => fields.values().any(is_head_tag),
+            other => is_head_tag(other),
+        })
+}
+
+/// `params` of a `method` call in the canonical form keys are built from
+pub fn canonical_params(method: &str, params: &[Value]) -> Vec<Value> {
+    let block_position =
+        block_param_position(method).or_else(|| BLOCK_FIRST_METHODS.contains(&method).then_some(0));
+    params
+        .iter()
+        .enumerate()
+        .map(|(position, param)| {
+            let param = canonical_value(param);
+            if Some(position) == block_position {
+                canonical_block(param)
+            } else {
+                param
+            }
+        })
+        .collect()
+}
+
+fn canonical_value(value: &Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(canonical_string(text)),
+        Value::Array(items) => Value::Array(items.iter().map(canonical_value).collect()),
+        Value::Object(fields) => {
+            // Inserted in order, so the order holds with `preserve_order` too
+            let mut sorted: Vec<(&String, &Value)> = fields.iter().collect();
+            sorted.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                sorted
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = canonical_value(value);
+                        let value = if BLOCK_FIELDS.contains(&key.as_str()) {
+                            canonical_block(value)
+                        } else {
+                            value
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            )
+        }
+        other => other.clone(),
+    }
+}
+
+/// Hex strings and block tags lowercased; anything else (base58 keys,
+/// free text) as it is
+fn canonical_string(text: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let hex = lower
+        .strip_prefix("0x")
+        .is_some_and(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()));
+    if hex || BLOCK_TAGS.contains(&lower.as_str()) {
+        lower
+    } else {
+        text.to_string()
+    }
+}
+
+/// Block numbers as minimal hex quantities; tags and hashes are kept
+fn canonical_block(value: Value) -> Value {
+    match value {
+        Value::Number(number) => match number.as_u64() {
+            Some(block) => Value::String(format!("{:#x}", block)),
+            None => Value::Number(number),
+        },
+        Value::String(text) => match text.strip_prefix("0x") {
+            // Block hashes are 32 bytes; numbers never get near that
+            Some(digits) if !digits.is_empty() && digits.len() <= 16 => {
+                let digits = digits.trim_start_matches('0');
+                Value::String(format!(
+                    "0x{}",
+                    if digits.is_empty() { "0" } else { digits }
+                ))
+            }
+            _ => Value::String(text),
+        },
+        other => other,
+    }
+}
+
+/// Cache lifetime for one method: seconds, `"forever"` or `"never"` (`0` also
+/// disables caching)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTtl {
+    Secs(u64),
+    Forever,
+    Never,
+}
+
+impl Serialize for CacheTtl {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Secs(secs) => serializer.serialize_u64(*secs),
+            Self::Forever => serializer.serialize_str("forever"),
+            Self::Never => serializer.serialize_str("never"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CacheTtl {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Secs(u64),
+            Word(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Secs(0) => Ok(Self::Never),
+            Raw::Secs(secs) => Ok(Self::Secs(secs)),
+            Raw::Word(word) if word == "forever" => Ok(Self::Forever),
+            Raw::Word(word) if word == "never" => Ok(Self::Never),
+            Raw::Word(word) => Err(D::Error::custom(format!(
+                "invalid cache TTL '{}': expected seconds, \"forever\" or \"never\"",
+                word
+            ))),
+        }
+    }
+}
+
+/// Built-in per-method TTLs with `overrides` applied on top
+pub fn method_ttls_with(overrides: HashMap<String, CacheTtl>) -> HashMap<String, CacheTtl> {
+    let mut ttls: HashMap<String, CacheTtl> = BUILTIN_METHOD_TTLS
+        .iter()
+        .map(|(method, ttl)| (method.to_string(), *ttl))
+        .collect();
+    ttls.extend(overrides);
+    ttls
+}
+
 /// Cache configuration
 #[derive(Debug, Clone)]
 pub struct CacheConfig {
     /// Redis connection URL
     pub redis_url: String,
 
+    /// Single, cluster or sentinel mode, TLS and AUTH
+    pub redis: RedisTopologyConfig,
+
+    /// Store behind the in-process tier
+    pub backend: CacheBackendConfig,
+
     /// Default TTL for cache entries (seconds)
     pub default_ttl: u64,
 
@@ -26,6 +261,25 @@ pub struct CacheConfig {
     /// TTL for transaction data (seconds) - very long because txs are immutable
     pub tx_ttl: u64,
 
+    /// TTL per JSON-RPC method, overriding the TTLs above
+    pub method_ttls: HashMap<String, CacheTtl>,
+
+    /// Entries kept in the in-process tier (0 disables it)
+    pub memory_capacity: usize,
+
+    /// Longest an entry stays in the in-process tier (seconds)
+    pub memory_ttl: u64,
+
+    /// Largest share of a TTL taken off at random (0 keeps TTLs exact)
+    pub ttl_jitter: f64,
+
+    /// Lifetime of a key's refresh lock (milliseconds, 0 disables locking)
+    pub refresh_lock_ms: u64,
+
+    /// Longest a caller waits for another's refresh before asking upstream
+    /// itself (milliseconds)
+    pub refresh_wait_ms: u64,
+
     /// Enable caching (can be disabled for testing)
     pub enabled: bool,
 }
@@ -35,95 +289,271 @@ impl Default for CacheConfig {
         //TODO: these should be environment variables with defaults
         Self {
             redis_url: "redis://redis.ekko.svc.cluster.local:6379".to_string(),
+            redis: RedisTopologyConfig::default(),
+            backend: CacheBackendConfig::default(),
             default_ttl: 60, // 1 minute
             block_ttl: 300,  // 5 minutes (blocks finalize)
             tx_ttl: 3600,    // 1 hour (txs are immutable)
+            method_ttls: method_ttls_with(HashMap::new()),
+            memory_capacity: 10_000,
+            memory_ttl: 30,
+            ttl_jitter: 0.1,
+            refresh_lock_ms: 5_000,
+            refresh_wait_ms: 2_000,
             enabled: true,
         }
     }
 }
 
-/// RPC response cache using Redis
+struct MemoryEntry {
+    value: Value,
+    expires_at: Instant,
+    /// Position in `MemoryTier::order`
+    tick: u64,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    /// Recency order: oldest tick first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+/// Bounded in-process LRU with per-entry expiry
+struct MemoryTier {
+    state: parking_lot::Mutex<MemoryState>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl MemoryTier {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            state: parking_lot::Mutex::new(MemoryState::default()),
+            capacity,
+            ttl,
+        }
+    }
+
+    fn get(&self, key: &str, now: Instant) -> Option<Value> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let mut state = self.state.lock();
+        let (expired, old_tick) = match state.entries.get(key) {
+            Some(entry) => (entry.expires_at <= now, entry.tick),
+            None => return None,
+        };
+        state.order.remove(&old_tick);
+        if expired {
+            state.entries.remove(key);
+            return None;
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.order.insert(tick, key.to_string());
+        let entry = state.entries.get_mut(key)?;
+        entry.tick = tick;
+        Some(entry.value.clone())
+    }
+
+    /// Insert, expiring after `ttl` capped at the tier's own TTL
+    fn insert(&self, key: &str, value: &Value, ttl: Option<Duration>, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        let ttl = ttl.map_or(self.ttl, |ttl| ttl.min(self.ttl));
+        let mut state = self.state.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = MemoryEntry {
+            value: value.clone(),
+            expires_at: now + ttl,
+            tick,
+        };
+        if let Some(previous) = state.entries.insert(key.to_string(), entry) {
+            state.order.remove(&previous.tick);
+        }
+        state.order.insert(tick, key.to_string());
+
+        while state.entries.len() > self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+    }
+
+    /// Drop entries whose key matches `doomed`; returns how many were dropped
+    fn remove_where(&self, doomed: impl Fn(&str) -> bool) -> usize {
+        let mut state = self.state.lock();
+        let keys: Vec<String> = state
+            .entries
+            .keys()
+            .filter(|key| doomed(key))
+            .cloned()
+            .collect();
+        for key in &keys {
+            if let Some(entry) = state.entries.remove(key) {
+                state.order.remove(&entry.tick);
+            }
+        }
+        keys.len()
+    }
+
+    fn clear(&self) {
+        *self.state.lock() = MemoryState::default();
+    }
+
+    fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+}
+
+/// Whether a `make_key` key of `network` holds a head-dependent answer
+fn is_head_dependent_key(network: &str, key: &str) -> bool {
+    let Some(rest) = key
+        .strip_prefix(network)
+        .and_then(|rest| rest.strip_prefix(':'))
+    else {
+        return false;
+    };
+    let Some((method, params)) = rest.split_once(':') else {
+        return false;
+    };
+    // Stale copies are meant to outlive the head they were read at
+    if params.ends_with(STALE_SUFFIX) {
+        return false;
+    }
+    let params = params.strip_suffix(NEGATIVE_SUFFIX).unwrap_or(params);
+    let params: Vec<Value> = serde_json::from_str(params).unwrap_or_default();
+    is_head_dependent(method, &params)
+}
+
+/// RPC response cache: in-process LRU tier in front of a shared backend
 pub struct RpcCache {
-    /// Redis client
-    client: Arc<RwLock<Option<RedisClient>>>,
+    /// Shared backend (Redis by default)
+    backend: Arc<RwLock<Option<Box<dyn CacheBackend>>>>,
+
+    /// In-process tier checked before the backend
+    memory: MemoryTier,
 
     /// Cache configuration
     config: CacheConfig,
 
     /// Cache key prefix
     key_prefix: String,
+
+    /// splitmix64 state for TTL jitter
+    rng: AtomicU64,
 }
 
 impl RpcCache {
     /// Create a new RPC cache
     pub fn new(config: CacheConfig) -> Self {
         Self {
-            client: Arc::new(RwLock::new(None)),
+            backend: Arc::new(RwLock::new(None)),
+            memory: MemoryTier::new(
+                config.memory_capacity,
+                Duration::from_secs(config.memory_ttl),
+            ),
             config,
             key_prefix: "rpc:cache:".to_string(),
+            rng: AtomicU64::new(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+            ),
         }
     }
 
-    /// Connect to Redis
+    /// Create a cache on an already open backend
+    pub fn with_backend(config: CacheConfig, backend: Box<dyn CacheBackend>) -> Self {
+        Self {
+            backend: Arc::new(RwLock::new(Some(backend))),
+            ..Self::new(config)
+        }
+    }
+
+    /// Open the configured backend
     pub async fn connect(&self) -> Result<()> {
         if !self.config.enabled {
-            debug!("Cache is disabled, skipping Redis connection");
+            debug!("Cache is disabled, skipping backend connection");
+            return Ok(());
+        }
+        if self.backend.read().await.is_some() {
             return Ok(());
         }
 
-        match RedisClient::open(self.config.redis_url.as_str()) {
-            Ok(client) => {
-                *self.client.write().await = Some(client);
-                debug!("Connected to Redis at {}", self.config.redis_url);
+        match self
+            .config
+            .backend
+            .open(&self.config.redis_url, &self.config.redis)
+            .await
+        {
+            Ok(backend) => {
+                match backend.name() {
+                    "redis" => debug!(
+                        "Connected to Redis at {}",
+                        self.config.redis.describe(&self.config.redis_url)
+                    ),
+                    name => debug!("Opened {} cache backend", name),
+                }
+                *self.backend.write().await = Some(backend);
                 Ok(())
             }
             Err(e) => {
-                warn!("Failed to connect to Redis: {}. Cache will be disabled.", e);
+                warn!(
+                    "Failed to open {:?} cache backend: {}. Cache will be disabled.",
+                    self.config.backend.kind, e
+                );
                 // Don't fail - just disable caching
                 Ok(())
             }
         }
     }
 
-    /// Get a cached RPC response
+    /// Get a cached RPC response, from the in-process tier when present
     pub async fn get(&self, key: &str) -> Result<Option<Value>> {
         if !self.config.enabled {
             return Ok(None);
         }
 
-        let client_lock = self.client.read().await;
-        if client_lock.is_none() {
-            return Ok(None);
+        if let Some(value) = self.memory.get(key, Instant::now()) {
+            debug!("Memory cache HIT for key: {}", key);
+            return Ok(Some(value));
         }
 
-        let client = client_lock.as_ref().unwrap();
+        let backend = self.backend.read().await;
+        let Some(backend) = backend.as_ref() else {
+            return Ok(None);
+        };
         let full_key = format!("{}{}", self.key_prefix, key);
 
-        match client.get_async_connection().await {
-            Ok(mut conn) => match conn.get::<_, Option<String>>(&full_key).await {
-                Ok(Some(cached_str)) => {
-                    debug!("Cache HIT for key: {}", key);
-                    match serde_json::from_str(&cached_str) {
-                        Ok(value) => Ok(Some(value)),
-                        Err(e) => {
-                            warn!("Failed to deserialize cached value: {}", e);
-                            Ok(None)
-                        }
+        match backend.get(&full_key).await {
+            Ok(Some(cached_str)) => {
+                debug!("Cache HIT for key: {}", key);
+                match serde_json::from_str(&cached_str) {
+                    Ok(value) => {
+                        self.memory.insert(key, &value, None, Instant::now());
+                        Ok(Some(value))
+                    }
+                    Err(e) => {
+                        warn!("Failed to deserialize cached value: {}", e);
+                        Ok(None)
                     }
                 }
-                Ok(None) => {
-                    debug!("Cache MISS for key: {}", key);
-                    Ok(None)
-                }
-                Err(e) => {
-                    warn!("Redis GET error: {}. Treating as cache miss.", e);
-                    Ok(None)
-                }
-            },
+            }
+            Ok(None) => {
+                debug!("Cache MISS for key: {}", key);
+                Ok(None)
+            }
             Err(e) => {
                 warn!(
-                    "Failed to get Redis connection: {}. Treating as cache miss.",
+                    "{} GET error: {}. Treating as cache miss.",
+                    backend.name(),
                     e
                 );
                 Ok(None)
@@ -131,47 +561,220 @@ impl RpcCache {
         }
     }
 
-    /// Set a cached RPC response with TTL
+    /// Write to the backend, expiring after `ttl` (`None`: no expiry)
+    async fn write(&self, key: &str, value: &Value, ttl: Option<Duration>) -> Result<()> {
+        let backend = self.backend.read().await;
+        let Some(backend) = backend.as_ref() else {
+            return Ok(());
+        };
+        let full_key = format!("{}{}", self.key_prefix, key);
+
+        let value_str = serde_json::to_string(value)
+            .map_err(|e| anyhow!("Failed to serialize value: {}", e))?;
+
+        match backend.set(&full_key, value_str, ttl).await {
+            Ok(()) => {
+                match ttl {
+                    Some(ttl) => {
+                        debug!("Cached value for key: {} (TTL: {}s)", key, ttl.as_secs())
+                    }
+                    None => debug!("Cached value for key: {} (no expiry)", key),
+                }
+                Ok(())
+            }
+            Err(e) => {
+                warn!("{} SET error: {}. Cache write failed.", backend.name(), e);
+                // Don't fail - just log the error
+                Ok(())
+            }
+        }
+    }
+
+    /// Uniform random number in `[0, 1)`
+    fn next_unit(&self) -> f64 {
+        let mut z = self
+            .rng
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// `ttl` shortened by a random share of up to `ttl_jitter`, in whole
+    /// seconds as Redis keeps them; never below one second
+    fn jittered(&self, ttl: Duration) -> Duration {
+        let jitter = self.config.ttl_jitter.clamp(0.0, 1.0);
+        let secs = ttl.as_secs();
+        let spread = (secs as f64 * jitter) as u64;
+        if spread == 0 {
+            return ttl;
+        }
+        let cut = (self.next_unit() * (spread + 1) as f64) as u64;
+        Duration::from_secs(secs.saturating_sub(cut.min(spread)).max(1))
+    }
+
+    /// Set a cached RPC response with TTL, jittered
     pub async fn set(&self, key: &str, value: &Value, ttl: Duration) -> Result<()> {
         if !self.config.enabled {
             return Ok(());
         }
+        let ttl = self.jittered(ttl);
+        self.memory.insert(key, value, Some(ttl), Instant::now());
+        self.write(key, value, Some(ttl)).await
+    }
 
-        let client_lock = self.client.read().await;
-        if client_lock.is_none() {
+    /// Set a cached RPC response without expiry
+    pub async fn set_persistent(&self, key: &str, value: &Value) -> Result<()> {
+        if !self.config.enabled {
             return Ok(());
         }
+        self.memory.insert(key, value, None, Instant::now());
+        self.write(key, value, None).await
+    }
+
+    /// Backend key of the refresh lock of the call cached under `key`
+    fn refresh_key(&self, key: &str) -> String {
+        format!("{}{}{}", self.key_prefix, key, REFRESH_SUFFIX)
+    }
+
+    /// Take the refresh lock of `key`; `false` when another caller holds it.
+    /// Locking off, or a backend that cannot answer, lets every caller through.
+    pub async fn lock_refresh(&self, key: &str) -> bool {
+        if !self.config.enabled || self.config.refresh_lock_ms == 0 {
+            return true;
+        }
+        let backend = self.backend.read().await;
+        let Some(backend) = backend.as_ref() else {
+            return true;
+        };
+        let ttl = Duration::from_millis(self.config.refresh_lock_ms);
+        match backend
+            .set_if_absent(&self.refresh_key(key), "1".to_string(), ttl)
+            .await
+        {
+            Ok(taken) => taken,
+            Err(e) => {
+                warn!("{} refresh lock error: {}", backend.name(), e);
+                true
+            }
+        }
+    }
+
+    /// Release the refresh lock of `key` taken with [`Self::lock_refresh`]
+    pub async fn release_refresh(&self, key: &str) {
+        if !self.config.enabled || self.config.refresh_lock_ms == 0 {
+            return;
+        }
+        let backend = self.backend.read().await;
+        if let Some(backend) = backend.as_ref() {
+            if let Err(e) = backend.delete(&self.refresh_key(key)).await {
+                debug!("{} refresh unlock error: {}", backend.name(), e);
+            }
+        }
+    }
 
-        let client = client_lock.as_ref().unwrap();
+    /// Wait for the caller holding the refresh lock of `key` to cache its
+    /// answer; `None` once the lock is gone without one, or after
+    /// `refresh_wait_ms`
+    pub async fn wait_for_refresh(&self, key: &str) -> Result<Option<Value>> {
+        let deadline = Instant::now() + Duration::from_millis(self.config.refresh_wait_ms);
+        while Instant::now() < deadline {
+            tokio::time::sleep(REFRESH_POLL).await;
+            if let Some(value) = self.get(key).await? {
+                return Ok(Some(value));
+            }
+            let backend = self.backend.read().await;
+            let Some(backend) = backend.as_ref() else {
+                return Ok(None);
+            };
+            // The holder failed, or cached nothing
+            if !matches!(backend.get(&self.refresh_key(key)).await, Ok(Some(_))) {
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Backend set holding a network's head-dependent cache keys
+    fn latest_set_key(&self, network: &str) -> String {
+        format!("{}latest:{}", self.key_prefix, network)
+    }
+
+    /// Record `key` as head-dependent for `network`
+    pub async fn track_latest(&self, network: &str, key: &str) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let backend = self.backend.read().await;
+        let Some(backend) = backend.as_ref() else {
+            return Ok(());
+        };
         let full_key = format!("{}{}", self.key_prefix, key);
 
-        let value_str = serde_json::to_string(value)
-            .map_err(|e| anyhow!("Failed to serialize value: {}", e))?;
+        if let Err(e) = backend
+            .add_to_set(&self.latest_set_key(network), &full_key)
+            .await
+        {
+            warn!(
+                "{} set update error: {}. Head tracking failed.",
+                backend.name(),
+                e
+            );
+        }
+        Ok(())
+    }
 
-        match client.get_async_connection().await {
-            Ok(mut conn) => {
-                match conn
-                    .set_ex::<_, _, ()>(&full_key, value_str, ttl.as_secs())
-                    .await
-                {
-                    Ok(_) => {
-                        debug!("Cached value for key: {} (TTL: {}s)", key, ttl.as_secs());
-                        Ok(())
-                    }
-                    Err(e) => {
-                        warn!("Redis SET error: {}. Cache write failed.", e);
-                        // Don't fail - just log the error
-                        Ok(())
-                    }
-                }
+    /// Drop every head-dependent entry of `network`; returns how many were dropped
+    pub async fn invalidate_latest(&self, network: &str) -> Result<usize> {
+        if !self.config.enabled {
+            return Ok(0);
+        }
+        let dropped_in_memory = self
+            .memory
+            .remove_where(|key| is_head_dependent_key(network, key));
+
+        let backend = self.backend.read().await;
+        let Some(backend) = backend.as_ref() else {
+            return Ok(dropped_in_memory);
+        };
+
+        match backend.drain_set(&self.latest_set_key(network)).await {
+            Ok(keys) => {
+                debug!(
+                    "Invalidated {} head-dependent entries for {}",
+                    keys.len(),
+                    network
+                );
+                Ok(keys.len().max(dropped_in_memory))
             }
             Err(e) => {
-                warn!("Failed to get Redis connection: {}. Cache write failed.", e);
-                Ok(())
+                warn!(
+                    "Failed to drop head-dependent keys from {}: {}",
+                    backend.name(),
+                    e
+                );
+                Ok(dropped_in_memory)
             }
         }
     }
 
+    /// Configured TTL for `method`, if any
+    pub fn method_ttl(&self, method: &str) -> Option<CacheTtl> {
+        self.config.method_ttls.get(method).copied()
+    }
+
+    /// Set with a per-method TTL; `Never` skips the write
+    pub async fn set_with_ttl(&self, key: &str, value: &Value, ttl: CacheTtl) -> Result<()> {
+        match ttl {
+            CacheTtl::Secs(secs) => self.set(key, value, Duration::from_secs(secs)).await,
+            CacheTtl::Forever => self.set_persistent(key, value).await,
+            CacheTtl::Never => Ok(()),
+        }
+    }
+
     /// Set with default TTL
     pub async fn set_default(&self, key: &str, value: &Value) -> Result<()> {
         self.set(key, value, Duration::from_secs(self.config.default_ttl))
@@ -192,8 +795,9 @@ impl RpcCache {
 
     /// Generate cache key for an RPC request
     pub fn make_key(&self, network: &str, method: &str, params: &[Value]) -> String {
-        // Create deterministic key from method and params
-        let params_str = serde_json::to_string(params).unwrap_or_default();
+        // Create deterministic key from method and canonical params
+        let params_str =
+            serde_json::to_string(&canonical_params(method, params)).unwrap_or_default();
         format!("{}:{}:{}", network, method, params_str)
     }
 
@@ -202,58 +806,45 @@ impl RpcCache {
         if !self.config.enabled {
             return Ok(());
         }
+        self.memory.clear();
 
-        let client_lock = self.client.read().await;
-        if let Some(client) = client_lock.as_ref() {
-            match client.get_async_connection().await {
-                Ok(mut conn) => {
-                    let pattern = format!("{}*", self.key_prefix);
-                    match redis::cmd("KEYS")
-                        .arg(&pattern)
-                        .query_async::<_, Vec<String>>(&mut conn)
-                        .await
-                    {
-                        Ok(keys) => {
-                            if !keys.is_empty() {
-                                match conn.del::<_, ()>(keys).await {
-                                    Ok(_) => {
-                                        debug!("Cleared all cache entries");
-                                        Ok(())
-                                    }
-                                    Err(e) => {
-                                        warn!("Failed to delete cache keys: {}", e);
-                                        Ok(())
-                                    }
-                                }
-                            } else {
-                                Ok(())
-                            }
-                        }
-                        Err(e) => {
-                            warn!("Failed to list cache keys: {}", e);
-                            Ok(())
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to get Redis connection for clear: {}", e);
-                    Ok(())
-                }
+        let backend = self.backend.read().await;
+        if let Some(backend) = backend.as_ref() {
+            match backend.clear_prefix(&self.key_prefix).await {
+                Ok(()) => debug!("Cleared all cache entries"),
+                Err(e) => warn!("Failed to clear {} cache entries: {}", backend.name(), e),
             }
-        } else {
-            Ok(())
         }
+        Ok(())
+    }
+
+    /// Entries currently held in the in-process tier
+    pub fn memory_len(&self) -> usize {
+        self.memory.len()
     }
 
     /// Check if caching is enabled and connected
     pub async fn is_available(&self) -> bool {
-        self.config.enabled && self.client.read().await.is_some()
+        self.config.enabled && self.backend.read().await.is_some()
+    }
+
+    /// Whether the shared backend answers; `None` when caching is off
+    pub async fn ping(&self) -> Option<bool> {
+        if !self.config.enabled {
+            return None;
+        }
+        match self.backend.read().await.as_ref() {
+            Some(backend) => Some(backend.ping().await.is_ok()),
+            None => Some(false),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache_backend::MemoryBackend;
+    use serde_json::json;
 
     #[test]
     fn test_cache_config_default() {
@@ -282,6 +873,294 @@ mod tests {
         assert_ne!(key1, key2);
     }
 
+    #[test]
+    fn test_equivalent_params_share_a_key() {
+        let cache = RpcCache::new(CacheConfig::default());
+        let key = |method: &str, params: Value| {
+            cache.make_key("ethereum", method, params.as_array().unwrap())
+        };
+
+        assert_eq!(
+            key("eth_getBalance", json!(["0xA1b2C3", "Latest"])),
+            key("eth_getBalance", json!(["0xa1b2c3", "latest"]))
+        );
+        assert_eq!(
+            key("eth_getBlockByNumber", json!(["0x000F", false])),
+            key("eth_getBlockByNumber", json!([15, false]))
+        );
+        assert_eq!(
+            key(
+                "eth_getLogs",
+                json!([{"toBlock": "0x0a", "address": "0xABC", "fromBlock": "0x1"}])
+            ),
+            key(
+                "eth_getLogs",
+                json!([{"address": "0xabc", "fromBlock": "0x01", "toBlock": "0xa"}])
+            )
+        );
+        // Block hashes keep their leading zeros
+        let hash = format!("0x00{}", "ab".repeat(31));
+        assert!(key("eth_getBlockByHash", json!([hash.clone(), false])).contains(&hash));
+
+        // Case-sensitive values are left alone
+        assert_ne!(
+            key(
+                "getBalance",
+                json!(["Vote111111111111111111111111111111111111111"])
+            ),
+            key(
+                "getBalance",
+                json!(["vote111111111111111111111111111111111111111"])
+            )
+        );
+        assert!(is_head_dependent("eth_call", &[json!({}), json!("LATEST")]));
+    }
+
+    #[test]
+    fn test_method_ttls_parse_and_override_builtins() {
+        let overrides: HashMap<String, CacheTtl> = serde_json::from_str(
+            r#"{"eth_gasPrice": 5, "eth_getLogs": 30, "eth_blockNumber": 0, "net_version": "never"}"#,
+        )
+        .unwrap();
+        let mut config = CacheConfig::default();
+        config.method_ttls = method_ttls_with(overrides);
+        let cache = RpcCache::new(config);
+
+        assert_eq!(cache.method_ttl("eth_chainId"), Some(CacheTtl::Forever));
+        assert_eq!(cache.method_ttl("eth_gasPrice"), Some(CacheTtl::Secs(5)));
+        assert_eq!(cache.method_ttl("eth_getLogs"), Some(CacheTtl::Secs(30)));
+        assert_eq!(cache.method_ttl("eth_blockNumber"), Some(CacheTtl::Never));
+        assert_eq!(cache.method_ttl("net_version"), Some(CacheTtl::Never));
+        assert_eq!(cache.method_ttl("txpool_content"), Some(CacheTtl::Never));
+        assert_eq!(cache.method_ttl("eth_call"), None);
+
+        assert_eq!(
+            serde_json::to_value(CacheTtl::Forever).unwrap(),
+            serde_json::json!("forever")
+        );
+        assert!(serde_json::from_str::<CacheTtl>(r#""always""#).is_err());
+    }
+
+    #[test]
+    fn test_head_dependent_calls() {
+        let address = Value::String("0xd8da6bf26964af9d7eed9e10e49db9cda2de5ae7".to_string());
+        let latest = Value::String("latest".to_string());
+
+        assert!(is_head_dependent("eth_blockNumber", &[]));
+        assert!(is_head_dependent(
+            "eth_getBalance",
+            &[address.clone(), latest.clone()]
+        ));
+        assert!(is_head_dependent(
+            "eth_call",
+            &[serde_json::json!({"to": address}), latest]
+        ));
+        assert!(is_head_dependent(
+            "eth_getLogs",
+            &[serde_json::json!({"fromBlock": "0x10", "toBlock": "latest"})]
+        ));
+        assert!(!is_head_dependent(
+            "eth_getBalance",
+            &[address, Value::String("0x10".to_string())]
+        ));
+        assert!(!is_head_dependent("eth_chainId", &[]));
+
+        // Negative entries follow their call
+        let key = r#"ethereum:eth_getCode:["0xabc","latest"]"#;
+        assert!(is_head_dependent_key("ethereum", &negative_key(key)));
+        let key = r#"ethereum:eth_getTransactionReceipt:["0xdef"]"#;
+        assert!(!is_head_dependent_key("ethereum", &negative_key(key)));
+        let key = r#"ethereum:eth_call:[{"to":"0xabc"},"latest"]"#;
+        assert!(is_head_dependent_key("ethereum", key));
+        assert!(!is_head_dependent_key("ethereum", &stale_key(key)));
+    }
+
+    #[test]
+    fn test_memory_tier_evicts_least_recently_used() {
+        let tier = MemoryTier::new(2, Duration::from_secs(30));
+        let now = Instant::now();
+        tier.insert("a", &Value::from(1), None, now);
+        tier.insert("b", &Value::from(2), None, now);
+
+        // Touching "a" makes "b" the eviction candidate
+        assert_eq!(tier.get("a", now), Some(Value::from(1)));
+        tier.insert("c", &Value::from(3), None, now);
+        assert_eq!(tier.get("b", now), None);
+        assert_eq!(tier.get("a", now), Some(Value::from(1)));
+        assert_eq!(tier.get("c", now), Some(Value::from(3)));
+        assert_eq!(tier.len(), 2);
+
+        // Re-inserting a key replaces it without growing the tier
+        tier.insert("c", &Value::from(4), None, now);
+        assert_eq!(tier.get("c", now), Some(Value::from(4)));
+        assert_eq!(tier.len(), 2);
+
+        let disabled = MemoryTier::new(0, Duration::from_secs(30));
+        disabled.insert("a", &Value::from(1), None, now);
+        assert_eq!(disabled.get("a", now), None);
+    }
+
+    #[test]
+    fn test_memory_tier_expiry_is_capped() {
+        let tier = MemoryTier::new(10, Duration::from_secs(30));
+        let now = Instant::now();
+        tier.insert("short", &Value::from(1), Some(Duration::from_secs(2)), now);
+        tier.insert(
+            "long",
+            &Value::from(2),
+            Some(Duration::from_secs(3600)),
+            now,
+        );
+
+        let later = now + Duration::from_secs(5);
+        assert_eq!(tier.get("short", later), None);
+        assert_eq!(tier.get("long", later), Some(Value::from(2)));
+        assert_eq!(tier.get("long", now + Duration::from_secs(31)), None);
+        assert_eq!(tier.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_tier_serves_without_redis() {
+        let cache = RpcCache::new(CacheConfig::default());
+        let head_key = cache.make_key("ethereum", "eth_blockNumber", &[]);
+        let chain_key = cache.make_key("ethereum", "eth_chainId", &[]);
+        let balance_key = cache.make_key(
+            "ethereum",
+            "eth_getBalance",
+            &[Value::from("0xabc"), Value::from("latest")],
+        );
+
+        cache
+            .set_default(&head_key, &Value::from("0x10"))
+            .await
+            .unwrap();
+        cache
+            .set_persistent(&chain_key, &Value::from("0x1"))
+            .await
+            .unwrap();
+        cache
+            .set_default(&balance_key, &Value::from("0x0"))
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.get(&chain_key).await.unwrap(),
+            Some(Value::from("0x1"))
+        );
+        assert_eq!(cache.memory_len(), 3);
+
+        // A new head drops the head-dependent entries only
+        assert_eq!(cache.invalidate_latest("ethereum").await.unwrap(), 2);
+        assert_eq!(cache.get(&head_key).await.unwrap(), None);
+        assert_eq!(cache.get(&balance_key).await.unwrap(), None);
+        assert_eq!(
+            cache.get(&chain_key).await.unwrap(),
+            Some(Value::from("0x1"))
+        );
+        assert_eq!(cache.invalidate_latest("avalanche").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_behind_disabled_tier() {
+        let config = CacheConfig {
+            memory_capacity: 0,
+            ..Default::default()
+        };
+        let cache = RpcCache::with_backend(config, Box::new(MemoryBackend::new(100)));
+        assert!(cache.is_available().await);
+        cache.connect().await.unwrap();
+
+        let head_key = cache.make_key("ethereum", "eth_blockNumber", &[]);
+        let chain_key = cache.make_key("ethereum", "eth_chainId", &[]);
+        cache
+            .set_default(&head_key, &Value::from("0x10"))
+            .await
+            .unwrap();
+        cache.track_latest("ethereum", &head_key).await.unwrap();
+        cache
+            .set_persistent(&chain_key, &Value::from("0x1"))
+            .await
+            .unwrap();
+        assert_eq!(cache.memory_len(), 0);
+        assert_eq!(
+            cache.get(&head_key).await.unwrap(),
+            Some(Value::from("0x10"))
+        );
+
+        assert_eq!(cache.invalidate_latest("ethereum").await.unwrap(), 1);
+        assert_eq!(cache.get(&head_key).await.unwrap(), None);
+        assert_eq!(
+            cache.get(&chain_key).await.unwrap(),
+            Some(Value::from("0x1"))
+        );
+
+        cache.clear_all().await.unwrap();
+        assert_eq!(cache.get(&chain_key).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_ttl_jitter_spreads_expiry() {
+        let cache = RpcCache::new(CacheConfig {
+            ttl_jitter: 0.2,
+            ..Default::default()
+        });
+        let ttls: std::collections::HashSet<u64> = (0..200)
+            .map(|_| cache.jittered(Duration::from_secs(300)).as_secs())
+            .collect();
+        assert!(ttls.len() > 10);
+        assert!(ttls.iter().all(|ttl| (240..=300).contains(ttl)));
+
+        // Too short to spread, and never cut to nothing
+        assert_eq!(
+            cache.jittered(Duration::from_secs(2)),
+            Duration::from_secs(2)
+        );
+        let exact = RpcCache::new(CacheConfig {
+            ttl_jitter: 0.0,
+            ..Default::default()
+        });
+        assert_eq!(
+            exact.jittered(Duration::from_secs(300)),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_one_caller_refreshes_a_missing_entry() {
+        let cache = Arc::new(RpcCache::with_backend(
+            CacheConfig {
+                refresh_wait_ms: 1_000,
+                ..Default::default()
+            },
+            Box::new(MemoryBackend::new(100)),
+        ));
+        let key = cache.make_key("ethereum", "eth_getBlockByNumber", &[json!("0x10")]);
+        assert!(cache.lock_refresh(&key).await);
+        assert!(!cache.lock_refresh(&key).await);
+
+        // A waiter gets the holder's answer
+        let waiter = {
+            let cache = cache.clone();
+            let key = key.clone();
+            tokio::spawn(async move { cache.wait_for_refresh(&key).await.unwrap() })
+        };
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        cache
+            .set_block(&key, &json!({"number": "0x10"}))
+            .await
+            .unwrap();
+        cache.release_refresh(&key).await;
+        assert_eq!(waiter.await.unwrap(), Some(json!({"number": "0x10"})));
+
+        // A holder that gives up frees the waiters at once
+        let other = cache.make_key("ethereum", "eth_getBalance", &[json!("0xabc")]);
+        assert!(cache.lock_refresh(&other).await);
+        cache.release_refresh(&other).await;
+        let started = Instant::now();
+        assert_eq!(cache.wait_for_refresh(&other).await.unwrap(), None);
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert!(cache.lock_refresh(&other).await);
+    }
+
     #[tokio::test]
     async fn test_cache_disabled() {
         let mut config = CacheConfig::default();
@@ -298,5 +1177,8 @@ mod tests {
             .set_default("test-key", &Value::String("test".to_string()))
             .await;
         assert!(set_result.is_ok());
+
+        assert!(cache.track_latest("ethereum", "test-key").await.is_ok());
+        assert_eq!(cache.invalidate_latest("ethereum").await.unwrap(), 0);
     }
 }
@@ -0,0 +1,597 @@
+//! Storage behind the RPC response cache
+//!
+//! [`RpcCache`](crate::cache::RpcCache) keeps its shared tier in a
+//! [`CacheBackend`], picked by `kind`:
+//! - `redis` (default): the cache's Redis, in any of its topologies
+//! - `nats_kv`: a JetStream key-value bucket on the provider's NATS, for
+//!   deployments that run NATS but no Redis; every entry also ages out after
+//!   `max_age_secs`, the bucket's own limit
+//! - `memory`: a map in the provider process holding up to `capacity`
+//!   entries, shared by nothing but needing nothing, e.g. for tests
+//!
+//! Backends store strings; key prefixes, TTL choice and (de)serialization stay
+//! in the cache.
+
+use crate::redis_topology::{RedisConnector, RedisTopologyConfig};
+use anyhow::{anyhow, Result};
+use async_nats::jetstream::kv;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Attempts at a compare-and-set update of a KV set entry
+const KV_UPDATE_ATTEMPTS: usize = 5;
+
+/// Which store backs the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackendKind {
+    Redis,
+    NatsKv,
+    Memory,
+}
+
+/// Cache backend settings (`HTTP_RPC_CACHE_BACKEND_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheBackendConfig {
+    pub kind: CacheBackendKind,
+    /// NATS server of `nats_kv`; defaults to the provider's `nats_url`
+    pub nats_url: Option<String>,
+    /// JetStream KV bucket of `nats_kv`
+    pub bucket: String,
+    /// Longest any `nats_kv` entry is kept (seconds)
+    pub max_age_secs: u64,
+    /// Entries held by `memory`
+    pub capacity: usize,
+}
+
+impl Default for CacheBackendConfig {
+    fn default() -> Self {
+        Self {
+            kind: CacheBackendKind::Redis,
+            nats_url: None,
+            bucket: "rpc_cache".to_string(),
+            max_age_secs: 86_400,
+            capacity: 100_000,
+        }
+    }
+}
+
+impl CacheBackendConfig {
+    /// Open the configured backend; `redis_url`/`redis` are used by `redis`
+    pub async fn open(
+        &self,
+        redis_url: &str,
+        redis: &RedisTopologyConfig,
+    ) -> Result<Box<dyn CacheBackend>> {
+        match self.kind {
+            CacheBackendKind::Redis => Ok(Box::new(RedisBackend {
+                client: redis.connector(redis_url)?,
+            })),
+            CacheBackendKind::NatsKv => {
+                let nats_url = self
+                    .nats_url
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("nats_kv cache backend needs a NATS URL"))?;
+                Ok(Box::new(
+                    NatsKvBackend::open(nats_url, &self.bucket, self.max_age_secs).await?,
+                ))
+            }
+            CacheBackendKind::Memory => Ok(Box::new(MemoryBackend::new(self.capacity))),
+        }
+    }
+}
+
+/// Shared store of cache entries and the sets tracking head-dependent keys
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Backend name, for logs
+    fn name(&self) -> &'static str;
+
+    async fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Store `value`, expiring after `ttl` (`None`: no expiry)
+    async fn set(&self, key: &str, value: String, ttl: Option<Duration>) -> Result<()>;
+
+    /// Store `value` for `ttl` unless `key` holds a live entry; returns
+    /// whether it was stored
+    async fn set_if_absent(&self, key: &str, value: String, ttl: Duration) -> Result<bool>;
+
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// Add `member` to the set at `set`
+    async fn add_to_set(&self, set: &str, member: &str) -> Result<()>;
+
+    /// Delete the set at `set` and every key in it; returns those keys
+    async fn drain_set(&self, set: &str) -> Result<Vec<String>>;
+
+    /// Delete every entry whose key starts with `prefix`
+    async fn clear_prefix(&self, prefix: &str) -> Result<()>;
+
+    /// Check the backend answers
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Redis, single instance, cluster or sentinel
+pub struct RedisBackend {
+    client: RedisConnector,
+}
+
+#[async_trait]
+impl CacheBackend for RedisBackend {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        Ok(conn.get::<_, Option<String>>(key).await?)
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Option<Duration>) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        match ttl {
+            Some(ttl) => conn.set_ex::<_, _, ()>(key, value, ttl.as_secs()).await?,
+            None => conn.set::<_, _, ()>(key, value).await?,
+        }
+        Ok(())
+    }
+
+    async fn set_if_absent(&self, key: &str, value: String, ttl: Duration) -> Result<bool> {
+        let mut conn = self.client.get_async_connection().await?;
+        let stored: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut conn)
+            .await?;
+        Ok(stored.is_some())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        conn.del::<_, ()>(key).await?;
+        Ok(())
+    }
+
+    async fn add_to_set(&self, set: &str, member: &str) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        conn.sadd::<_, _, ()>(set, member).await?;
+        Ok(())
+    }
+
+    async fn drain_set(&self, set: &str) -> Result<Vec<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let keys: Vec<String> = conn.smembers(set).await?;
+        let mut doomed = keys.clone();
+        doomed.push(set.to_string());
+        conn.del_keys(doomed).await?;
+        Ok(keys)
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        let keys: Vec<String> = redis::cmd("KEYS")
+            .arg(format!("{}*", prefix))
+            .query_async(&mut conn)
+            .await?;
+        if !keys.is_empty() {
+            conn.del_keys(keys).await?;
+        }
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct MemoryBackendState {
+    values: HashMap<String, (String, Option<Instant>)>,
+    sets: HashMap<String, HashSet<String>>,
+}
+
+/// In-process map; entries past `capacity` are not stored
+pub struct MemoryBackend {
+    state: parking_lot::Mutex<MemoryBackendState>,
+    capacity: usize,
+}
+
+impl MemoryBackend {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: parking_lot::Mutex::new(MemoryBackendState::default()),
+            capacity,
+        }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let mut state = self.state.lock();
+        let expired = match state.values.get(key) {
+            Some((_, expires_at)) => expires_at.is_some_and(|at| at <= Instant::now()),
+            None => return Ok(None),
+        };
+        if expired {
+            state.values.remove(key);
+            return Ok(None);
+        }
+        Ok(state.values.get(key).map(|(value, _)| value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Option<Duration>) -> Result<()> {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        if state.values.len() >= self.capacity && !state.values.contains_key(key) {
+            state
+                .values
+                .retain(|_, (_, expires_at)| expires_at.is_none_or(|at| at > now));
+            if state.values.len() >= self.capacity {
+                return Ok(());
+            }
+        }
+        state
+            .values
+            .insert(key.to_string(), (value, ttl.map(|ttl| now + ttl)));
+        Ok(())
+    }
+
+    async fn set_if_absent(&self, key: &str, value: String, ttl: Duration) -> Result<bool> {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        let live = state
+            .values
+            .get(key)
+            .is_some_and(|(_, expires_at)| expires_at.is_none_or(|at| at > now));
+        if live {
+            return Ok(false);
+        }
+        state
+            .values
+            .insert(key.to_string(), (value, Some(now + ttl)));
+        Ok(true)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.state.lock().values.remove(key);
+        Ok(())
+    }
+
+    async fn add_to_set(&self, set: &str, member: &str) -> Result<()> {
+        self.state
+            .lock()
+            .sets
+            .entry(set.to_string())
+            .or_default()
+            .insert(member.to_string());
+        Ok(())
+    }
+
+    async fn drain_set(&self, set: &str) -> Result<Vec<String>> {
+        let mut state = self.state.lock();
+        let keys: Vec<String> = state
+            .sets
+            .remove(set)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        for key in &keys {
+            state.values.remove(key);
+        }
+        Ok(keys)
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        state.values.retain(|key, _| !key.starts_with(prefix));
+        state.sets.retain(|key, _| !key.starts_with(prefix));
+        Ok(())
+    }
+}
+
+/// Value stored in the KV bucket; `key` tells hash collisions apart
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct KvEntry {
+    key: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    value: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    members: Vec<String>,
+    /// Unix milliseconds after which the entry is stale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<u64>,
+}
+
+impl KvEntry {
+    fn parse(bytes: &[u8], key: &str) -> Option<Self> {
+        let entry: Self = serde_json::from_slice(bytes).ok()?;
+        let fresh = entry.expires_at.is_none_or(|at| at > unix_millis());
+        (entry.key == key && fresh).then_some(entry)
+    }
+
+    fn to_bytes(&self) -> Result<Bytes> {
+        Ok(Bytes::from(serde_json::to_vec(self)?))
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// KV key of a cache key: KV keys allow few characters and cache keys hold
+/// whole request params, so they are hashed (64-bit FNV-1a)
+fn kv_key(key: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("c.{:016x}", hash)
+}
+
+/// NATS JetStream key-value bucket
+pub struct NatsKvBackend {
+    store: kv::Store,
+}
+
+impl NatsKvBackend {
+    /// Bind to `bucket`, creating it when missing
+    pub async fn open(nats_url: &str, bucket: &str, max_age_secs: u64) -> Result<Self> {
+        let client = async_nats::connect(nats_url).await?;
+        let jetstream = async_nats::jetstream::new(client);
+        let store = match jetstream.get_key_value(bucket).await {
+            Ok(store) => store,
+            Err(_) => {
+                jetstream
+                    .create_key_value(kv::Config {
+                        bucket: bucket.to_string(),
+                        history: 1,
+                        max_age: Duration::from_secs(max_age_secs),
+                        ..Default::default()
+                    })
+                    .await?
+            }
+        };
+        Ok(Self { store })
+    }
+
+    async fn entry(&self, key: &str) -> Result<Option<KvEntry>> {
+        Ok(self
+            .store
+            .get(kv_key(key))
+            .await?
+            .and_then(|bytes| KvEntry::parse(&bytes, key)))
+    }
+}
+
+#[async_trait]
+impl CacheBackend for NatsKvBackend {
+    fn name(&self) -> &'static str {
+        "nats_kv"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.entry(key).await?.map(|entry| entry.value))
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Option<Duration>) -> Result<()> {
+        let entry = KvEntry {
+            key: key.to_string(),
+            value,
+            expires_at: ttl.map(|ttl| unix_millis() + ttl.as_millis() as u64),
+            ..Default::default()
+        };
+        self.store.put(kv_key(key), entry.to_bytes()?).await?;
+        Ok(())
+    }
+
+    async fn set_if_absent(&self, key: &str, value: String, ttl: Duration) -> Result<bool> {
+        let current = self.store.entry(kv_key(key)).await?;
+        if current
+            .as_ref()
+            .is_some_and(|current| KvEntry::parse(&current.value, key).is_some())
+        {
+            return Ok(false);
+        }
+        let entry = KvEntry {
+            key: key.to_string(),
+            value,
+            expires_at: Some(unix_millis() + ttl.as_millis() as u64),
+            ..Default::default()
+        };
+        // Revision-checked, so of two callers racing for it one wins
+        let stored = match current {
+            Some(current) => self
+                .store
+                .update(kv_key(key), entry.to_bytes()?, current.revision)
+                .await
+                .is_ok(),
+            None => self
+                .store
+                .create(kv_key(key), entry.to_bytes()?)
+                .await
+                .is_ok(),
+        };
+        Ok(stored)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.store.delete(kv_key(key)).await?;
+        Ok(())
+    }
+
+    async fn add_to_set(&self, set: &str, member: &str) -> Result<()> {
+        // Compare-and-set, as several pools may track the same network
+        for _ in 0..KV_UPDATE_ATTEMPTS {
+            let current = self.store.entry(kv_key(set)).await?;
+            let mut entry = current
+                .as_ref()
+                .and_then(|current| KvEntry::parse(&current.value, set))
+                .unwrap_or_else(|| KvEntry {
+                    key: set.to_string(),
+                    ..Default::default()
+                });
+            if entry.members.iter().any(|known| known == member) {
+                return Ok(());
+            }
+            entry.members.push(member.to_string());
+
+            let written = match current {
+                Some(current) => self
+                    .store
+                    .update(kv_key(set), entry.to_bytes()?, current.revision)
+                    .await
+                    .is_ok(),
+                None => self
+                    .store
+                    .create(kv_key(set), entry.to_bytes()?)
+                    .await
+                    .is_ok(),
+            };
+            if written {
+                return Ok(());
+            }
+        }
+        Err(anyhow!("Concurrent updates of {} kept conflicting", set))
+    }
+
+    async fn drain_set(&self, set: &str) -> Result<Vec<String>> {
+        let Some(entry) = self.entry(set).await? else {
+            return Ok(Vec::new());
+        };
+        for key in &entry.members {
+            self.store.delete(kv_key(key)).await?;
+        }
+        self.store.delete(kv_key(set)).await?;
+        Ok(entry.members)
+    }
+
+    async fn clear_prefix(&self, prefix: &str) -> Result<()> {
+        let mut keys = self.store.keys().await?;
+        let mut doomed = Vec::new();
+        while let Some(key) = keys.next().await {
+            let key = key?;
+            let Some(bytes) = self.store.get(&key).await? else {
+                continue;
+            };
+            let cache_key = serde_json::from_slice::<KvEntry>(&bytes)
+                .map(|entry| entry.key)
+                .unwrap_or_default();
+            if cache_key.starts_with(prefix) {
+                doomed.push(key);
+            }
+        }
+        for key in doomed {
+            self.store.delete(key).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_backend_sets_and_capacity() {
+        let backend = MemoryBackend::new(2);
+        backend
+            .set("rpc:cache:a", "1".to_string(), None)
+            .await
+            .unwrap();
+        backend
+            .set("rpc:cache:b", "2".to_string(), Some(Duration::ZERO))
+            .await
+            .unwrap();
+        assert_eq!(backend.get("rpc:cache:b").await.unwrap(), None);
+
+        // Full but for an expired entry, which makes room
+        backend
+            .set("rpc:cache:b", "2".to_string(), Some(Duration::ZERO))
+            .await
+            .unwrap();
+        backend
+            .set("rpc:cache:c", "3".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            backend.get("rpc:cache:c").await.unwrap(),
+            Some("3".to_string())
+        );
+        backend
+            .set("rpc:cache:d", "4".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(backend.get("rpc:cache:d").await.unwrap(), None);
+
+        backend
+            .add_to_set("rpc:cache:latest:ethereum", "rpc:cache:c")
+            .await
+            .unwrap();
+        assert_eq!(
+            backend
+                .drain_set("rpc:cache:latest:ethereum")
+                .await
+                .unwrap(),
+            vec!["rpc:cache:c".to_string()]
+        );
+        assert_eq!(backend.get("rpc:cache:c").await.unwrap(), None);
+        assert!(backend
+            .drain_set("rpc:cache:latest:ethereum")
+            .await
+            .unwrap()
+            .is_empty());
+
+        backend.clear_prefix("rpc:cache:").await.unwrap();
+        assert_eq!(backend.get("rpc:cache:a").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_kv_entries_checked_against_their_key() {
+        let key = r#"rpc:cache:ethereum:eth_call:[{"to":"0xabc"},"latest"]"#;
+        let kv = kv_key(key);
+        assert_eq!(kv, kv_key(key));
+        assert_ne!(kv, kv_key("rpc:cache:ethereum:eth_chainId:[]"));
+        assert!(kv.chars().all(|c| c == '.' || c.is_ascii_hexdigit()));
+
+        let entry = KvEntry {
+            key: key.to_string(),
+            value: "\"0x1\"".to_string(),
+            ..Default::default()
+        };
+        let bytes = entry.to_bytes().unwrap();
+        assert_eq!(KvEntry::parse(&bytes, key), Some(entry));
+        // A colliding key reads as a miss
+        assert_eq!(KvEntry::parse(&bytes, "rpc:cache:other"), None);
+
+        let stale = KvEntry {
+            key: key.to_string(),
+            expires_at: Some(unix_millis() - 1),
+            ..Default::default()
+        };
+        assert_eq!(KvEntry::parse(&stale.to_bytes().unwrap(), key), None);
+    }
+}
@@ -0,0 +1,509 @@
+//! Synthetic canary probes per endpoint
+//!
+//! Live traffic only exercises the endpoints it happens to hit, so a quiet
+//! chain can sit on a stale or broken endpoint for hours. The canary loop
+//! probes every endpoint of a network on a fixed interval with:
+//! - `eth_blockNumber`, checked against the highest head seen in the round
+//! - `eth_getBlockByNumber` of a fixed old block, checked against its known hash
+//! - `eth_call` to a known contract, checked against its known return data
+//!
+//! Solana networks (`chain: solana`) are probed with `getSlot` as the head,
+//! `getHealth`, which a node lagging its cluster answers with an error, and
+//! `getBlock` of a fixed finalized slot checked against its `blockhash`. Their
+//! heads are slots, so they get a wider `max_head_lag` of their own.
+//!
+//! Each endpoint keeps a sliding window of probe outcomes and latencies that
+//! yields a 0-100 health score. Wrong answers count as failures alongside
+//! errors, and every round's verdict is fed to the endpoint's circuit breaker.
+
+use crate::cost::endpoint_host;
+use crate::endpoint_pool::RpcRequest;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Canary configuration (`HTTP_RPC_CANARY_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanaryConfig {
+    pub enabled: bool,
+    /// Seconds between probe rounds
+    pub interval_secs: u64,
+    /// Blocks an endpoint's head may trail the round's highest head
+    pub max_head_lag: u64,
+    /// Probe rounds kept per endpoint for scoring
+    pub window: usize,
+    /// Average probe latency above which the score is scaled down
+    pub slow_latency_ms: u64,
+    /// Probes per network; networks not listed are not probed
+    pub networks: HashMap<String, NetworkCanary>,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            max_head_lag: 5,
+            window: 20,
+            slow_latency_ms: 1500,
+            networks: ["ethereum", "avalanche"]
+                .into_iter()
+                .map(|network| (network.to_string(), NetworkCanary::default()))
+                .chain([("solana".to_string(), NetworkCanary::solana())])
+                .collect(),
+        }
+    }
+}
+
+/// JSON-RPC dialect a network's probes are asked in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Chain {
+    #[default]
+    Evm,
+    Solana,
+}
+
+/// Probes for one network; the head probe always runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkCanary {
+    pub chain: Chain,
+    /// Heads this network's endpoints may trail by, over the global one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_head_lag: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_block: Option<ReferenceBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call: Option<CanaryCall>,
+}
+
+/// A finalized block (a slot on Solana) whose hash every honest endpoint
+/// returns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceBlock {
+    pub number: u64,
+    pub hash: String,
+}
+
+/// A read-only call with a fixed result, e.g. `decimals()` on a token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryCall {
+    pub to: String,
+    pub data: String,
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeKind {
+    Head,
+    ReferenceBlock,
+    Call,
+    /// Solana `getHealth`
+    Health,
+}
+
+/// One probe's raw answer from one endpoint
+#[derive(Debug, Clone)]
+pub struct ProbeAnswer {
+    pub kind: ProbeKind,
+    pub latency: Duration,
+    pub result: Result<Value, String>,
+}
+
+impl ProbeAnswer {
+    /// Block number reported by a head probe
+    pub fn head(&self) -> Option<u64> {
+        if self.kind != ProbeKind::Head {
+            return None;
+        }
+        let result = self.result.as_ref().ok()?;
+        // `getSlot` answers a plain number
+        if let Some(slot) = result.as_u64() {
+            return Some(slot);
+        }
+        u64::from_str_radix(result.as_str()?.trim_start_matches("0x"), 16).ok()
+    }
+}
+
+impl NetworkCanary {
+    /// Probes of a Solana network, a minute of slots of head lag allowed
+    pub fn solana() -> Self {
+        Self {
+            chain: Chain::Solana,
+            max_head_lag: Some(150),
+            ..Default::default()
+        }
+    }
+
+    pub fn requests(&self) -> Vec<(ProbeKind, RpcRequest)> {
+        if self.chain == Chain::Solana {
+            return self.solana_requests();
+        }
+        let mut requests = vec![(ProbeKind::Head, RpcRequest::new("eth_blockNumber", vec![]))];
+        if let Some(block) = &self.reference_block {
+            requests.push((
+                ProbeKind::ReferenceBlock,
+                RpcRequest::new(
+                    "eth_getBlockByNumber",
+                    vec![json!(format!("0x{:x}", block.number)), json!(false)],
+                ),
+            ));
+        }
+        if let Some(call) = &self.call {
+            requests.push((
+                ProbeKind::Call,
+                RpcRequest::new(
+                    "eth_call",
+                    vec![json!({"to": call.to, "data": call.data}), json!("latest")],
+                ),
+            ));
+        }
+        requests
+    }
+
+    fn solana_requests(&self) -> Vec<(ProbeKind, RpcRequest)> {
+        let mut requests = vec![
+            (
+                ProbeKind::Head,
+                RpcRequest::new("getSlot", vec![json!({"commitment": "confirmed"})]),
+            ),
+            (ProbeKind::Health, RpcRequest::new("getHealth", vec![])),
+        ];
+        if let Some(block) = &self.reference_block {
+            requests.push((
+                ProbeKind::ReferenceBlock,
+                RpcRequest::new(
+                    "getBlock",
+                    vec![
+                        json!(block.number),
+                        json!({
+                            "transactionDetails": "none",
+                            "rewards": false,
+                            "maxSupportedTransactionVersion": 0,
+                        }),
+                    ],
+                ),
+            ));
+        }
+        requests
+    }
+
+    /// Whether `answer` is correct, given the highest head seen in the round
+    pub fn check(
+        &self,
+        answer: &ProbeAnswer,
+        round_head: Option<u64>,
+        max_head_lag: u64,
+    ) -> Result<(), String> {
+        let value = answer.result.as_ref().map_err(Clone::clone)?;
+        match answer.kind {
+            ProbeKind::Head => {
+                let head = answer
+                    .head()
+                    .ok_or_else(|| format!("unparseable block number {}", value))?;
+                match round_head {
+                    Some(best) if best.saturating_sub(head) > max_head_lag => Err(format!(
+                        "head {} trails {} by {} blocks",
+                        head,
+                        best,
+                        best - head
+                    )),
+                    _ => Ok(()),
+                }
+            }
+            ProbeKind::ReferenceBlock => {
+                let expected = self
+                    .reference_block
+                    .as_ref()
+                    .map(|block| block.hash.as_str())
+                    .unwrap_or_default();
+                // Solana hashes are base58, where case matters
+                let (field, matches): (_, fn(&str, &str) -> bool) = match self.chain {
+                    Chain::Evm => ("hash", |a, b| a.eq_ignore_ascii_case(b)),
+                    Chain::Solana => ("blockhash", |a, b| a == b),
+                };
+                let hash = value.get(field).and_then(Value::as_str).unwrap_or("");
+                if matches(hash, expected) {
+                    Ok(())
+                } else {
+                    Err(format!("reference block hash {} != {}", hash, expected))
+                }
+            }
+            ProbeKind::Call => {
+                let expected = self
+                    .call
+                    .as_ref()
+                    .map(|call| call.expected.as_str())
+                    .unwrap_or_default();
+                let data = value.as_str().unwrap_or("");
+                if data.eq_ignore_ascii_case(expected) {
+                    Ok(())
+                } else {
+                    Err(format!("eth_call returned {}", data))
+                }
+            }
+            ProbeKind::Health => match value.as_str() {
+                Some("ok") => Ok(()),
+                _ => Err(format!("getHealth returned {}", value)),
+            },
+        }
+    }
+}
+
+/// Probe snapshot for one endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryStatus {
+    pub endpoint: String,
+    /// 0-100; correct rounds scaled down for slow answers
+    pub score: f64,
+    pub rounds: usize,
+    pub failed_rounds: usize,
+    pub avg_latency_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct CanaryState {
+    /// Per round: (all probes correct, mean probe latency)
+    rounds: VecDeque<(bool, Duration)>,
+    head: Option<u64>,
+    last_error: Option<String>,
+}
+
+/// Sliding probe history for one endpoint
+pub struct CanaryTracker {
+    endpoint: String,
+    state: Mutex<CanaryState>,
+}
+
+impl CanaryTracker {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint_host(endpoint),
+            state: Mutex::new(CanaryState::default()),
+        }
+    }
+
+    /// Record one round; returns the first probe error, if any
+    pub fn record_round(
+        &self,
+        canary: &NetworkCanary,
+        answers: &[ProbeAnswer],
+        round_head: Option<u64>,
+        config: &CanaryConfig,
+    ) -> Option<String> {
+        let error = answers.iter().find_map(|answer| {
+            let max_head_lag = canary.max_head_lag.unwrap_or(config.max_head_lag);
+            canary.check(answer, round_head, max_head_lag).err()
+        });
+        let latency = answers
+            .iter()
+            .map(|answer| answer.latency)
+            .sum::<Duration>()
+            .checked_div(answers.len().max(1) as u32)
+            .unwrap_or_default();
+
+        let mut state = self.state.lock();
+        state.rounds.push_back((error.is_none(), latency));
+        while state.rounds.len() > config.window.max(1) {
+            state.rounds.pop_front();
+        }
+        if let Some(head) = answers.iter().find_map(ProbeAnswer::head) {
+            state.head = Some(head);
+        }
+        if let Some(error) = &error {
+            state.last_error = Some(error.clone());
+        }
+        error
+    }
+
+    pub fn status(&self, config: &CanaryConfig) -> CanaryStatus {
+        let state = self.state.lock();
+        let rounds = state.rounds.len();
+        let failed_rounds = state.rounds.iter().filter(|(ok, _)| !ok).count();
+
+        let (score, avg_latency_ms) = if rounds == 0 {
+            (100.0, 0.0)
+        } else {
+            let correct = (rounds - failed_rounds) as f64 / rounds as f64;
+            let avg_latency_ms = state
+                .rounds
+                .iter()
+                .map(|(_, latency)| latency.as_secs_f64() * 1000.0)
+                .sum::<f64>()
+                / rounds as f64;
+            let slow = config.slow_latency_ms as f64;
+            let speed = if avg_latency_ms > slow && avg_latency_ms > 0.0 {
+                slow / avg_latency_ms
+            } else {
+                1.0
+            };
+            (correct * speed * 100.0, avg_latency_ms)
+        };
+
+        CanaryStatus {
+            endpoint: self.endpoint.clone(),
+            score,
+            rounds,
+            failed_rounds,
+            avg_latency_ms,
+            head: state.head,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canary() -> NetworkCanary {
+        NetworkCanary {
+            reference_block: Some(ReferenceBlock {
+                number: 1_000_000,
+                hash: "0xABC".to_string(),
+            }),
+            call: Some(CanaryCall {
+                to: "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".to_string(),
+                data: "0x313ce567".to_string(),
+                expected: format!("0x{:064x}", 18),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn answer(kind: ProbeKind, ms: u64, result: Result<Value, String>) -> ProbeAnswer {
+        ProbeAnswer {
+            kind,
+            latency: Duration::from_millis(ms),
+            result,
+        }
+    }
+
+    #[test]
+    fn test_probes_check_correctness_not_just_liveness() {
+        let canary = canary();
+        let requests = canary.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].1.params[0], json!("0xf4240"));
+
+        let head = answer(ProbeKind::Head, 10, Ok(json!("0x64")));
+        assert_eq!(head.head(), Some(100));
+        assert!(canary.check(&head, Some(105), 5).is_ok());
+        assert!(canary.check(&head, Some(106), 5).is_err());
+
+        let block = answer(ProbeKind::ReferenceBlock, 10, Ok(json!({"hash": "0xabc"})));
+        assert!(canary.check(&block, None, 5).is_ok());
+        let forked = answer(ProbeKind::ReferenceBlock, 10, Ok(json!({"hash": "0xdef"})));
+        assert!(canary.check(&forked, None, 5).is_err());
+
+        let call = answer(ProbeKind::Call, 10, Ok(json!(format!("0x{:064x}", 18))));
+        assert!(canary.check(&call, None, 5).is_ok());
+        let empty = answer(ProbeKind::Call, 10, Ok(json!("0x")));
+        assert!(canary.check(&empty, None, 5).is_err());
+        let failed = answer(ProbeKind::Call, 10, Err("timeout".to_string()));
+        assert_eq!(canary.check(&failed, None, 5).unwrap_err(), "timeout");
+    }
+
+    #[test]
+    fn test_solana_probes() {
+        let canary = NetworkCanary {
+            reference_block: Some(ReferenceBlock {
+                number: 250_000_000,
+                hash: "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d".to_string(),
+            }),
+            ..NetworkCanary::solana()
+        };
+        let methods: Vec<String> = canary
+            .requests()
+            .into_iter()
+            .map(|(_, request)| request.method)
+            .collect();
+        assert_eq!(methods, ["getSlot", "getHealth", "getBlock"]);
+        assert_eq!(
+            CanaryConfig::default().networks["solana"].chain,
+            Chain::Solana
+        );
+
+        let head = answer(ProbeKind::Head, 10, Ok(json!(250_000_100u64)));
+        assert_eq!(head.head(), Some(250_000_100));
+        let behind = answer(
+            ProbeKind::Health,
+            10,
+            Err("RPC error -32005: Node is behind by 42 slots".to_string()),
+        );
+        assert!(canary.check(&behind, None, 150).is_err());
+        let healthy = answer(ProbeKind::Health, 10, Ok(json!("ok")));
+        assert!(canary.check(&healthy, None, 150).is_ok());
+
+        let block = answer(
+            ProbeKind::ReferenceBlock,
+            10,
+            Ok(json!({"blockhash": "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d"})),
+        );
+        assert!(canary.check(&block, None, 150).is_ok());
+        let recased = answer(
+            ProbeKind::ReferenceBlock,
+            10,
+            Ok(json!({"blockhash": "5EYKT4USFV8P8NJDTREPY1VZQKQZKVDPKUC147DW2N9D"})),
+        );
+        assert!(canary.check(&recased, None, 150).is_err());
+
+        // Slots come fast; 100 behind is within the Solana allowance
+        let tracker = CanaryTracker::new("https://api.mainnet-beta.solana.com");
+        let config = CanaryConfig::default();
+        let lagging = [answer(ProbeKind::Head, 10, Ok(json!(250_000_000u64)))];
+        assert!(tracker
+            .record_round(&canary, &lagging, Some(250_000_100), &config)
+            .is_none());
+    }
+
+    #[test]
+    fn test_tracker_scores_window_of_rounds() {
+        let canary = NetworkCanary::default();
+        let config = CanaryConfig {
+            window: 4,
+            slow_latency_ms: 100,
+            ..Default::default()
+        };
+        let tracker = CanaryTracker::new("https://rpc.example.org/key");
+        assert_eq!(tracker.status(&config).score, 100.0);
+
+        let ok = [answer(ProbeKind::Head, 50, Ok(json!("0x10")))];
+        let lagging = [answer(ProbeKind::Head, 50, Ok(json!("0x1")))];
+        for _ in 0..3 {
+            assert!(tracker
+                .record_round(&canary, &ok, Some(16), &config)
+                .is_none());
+        }
+        assert!(tracker
+            .record_round(&canary, &lagging, Some(16), &config)
+            .is_some());
+
+        let status = tracker.status(&config);
+        assert_eq!(status.endpoint, "rpc.example.org");
+        assert_eq!(status.rounds, 4);
+        assert_eq!(status.failed_rounds, 1);
+        assert_eq!(status.score, 75.0);
+        assert_eq!(status.head, Some(1));
+        assert!(status.last_error.unwrap().contains("trails"));
+
+        // Slow answers halve the score; old rounds fall out of the window
+        let slow = [answer(ProbeKind::Head, 200, Ok(json!("0x10")))];
+        for _ in 0..4 {
+            tracker.record_round(&canary, &slow, Some(16), &config);
+        }
+        let status = tracker.status(&config);
+        assert_eq!(status.failed_rounds, 0);
+        assert_eq!(status.avg_latency_ms, 200.0);
+        assert_eq!(status.score, 50.0);
+    }
+}
@@ -0,0 +1,224 @@
+//! Chain id verification per endpoint
+//!
+//! A mistyped URL (a Sepolia endpoint in the mainnet list, a Fuji node behind
+//! the C-Chain name) answers every call with plausible data from the wrong
+//! chain. Every endpoint's `eth_chainId` is checked against the network's
+//! expected chain id when the pool is registered and on every check interval:
+//! - a mismatch quarantines the endpoint: no live traffic reaches it until a
+//!   later check reports the right chain id
+//! - an error leaves the verdict as it was; the circuit breaker deals with
+//!   unreachable endpoints
+//!
+//! Networks without an expected chain id (non-EVM ones like bitcoin and
+//! solana) are not checked.
+
+use crate::cost::endpoint_host;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Built-in chain ids per network name
+const BUILTIN_CHAIN_IDS: &[(&str, u64)] = &[
+    ("ethereum", 1),
+    ("ethereum-sepolia", 11_155_111),
+    ("ethereum-holesky", 17_000),
+    ("avalanche", 43_114),
+    ("avalanche-fuji", 43_113),
+    ("polygon", 137),
+    ("arbitrum", 42_161),
+    ("optimism", 10),
+    ("base", 8_453),
+    ("bsc", 56),
+];
+
+/// Chain id checks (`HTTP_RPC_CHAIN_ID_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainIdConfig {
+    pub enabled: bool,
+    /// Seconds between checks after the one at registration
+    pub interval_secs: u64,
+    /// Expected chain id per network, on top of the built-in table
+    pub networks: HashMap<String, u64>,
+}
+
+impl Default for ChainIdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 300,
+            networks: HashMap::new(),
+        }
+    }
+}
+
+impl ChainIdConfig {
+    /// Chain id every endpoint of `network` must report, if known
+    pub fn expected(&self, network: &str) -> Option<u64> {
+        if !self.enabled {
+            return None;
+        }
+        self.networks.get(network).copied().or_else(|| {
+            BUILTIN_CHAIN_IDS
+                .iter()
+                .find(|(name, _)| *name == network)
+                .map(|(_, chain_id)| *chain_id)
+        })
+    }
+}
+
+/// Chain id from an `eth_chainId` result (hex quantity)
+pub fn parse_chain_id(value: &Value) -> Option<u64> {
+    let hex = value.as_str()?;
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+/// Verification snapshot for one endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainIdStatus {
+    pub endpoint: String,
+    pub expected: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reported: Option<u64>,
+    pub quarantined: bool,
+    /// Error of the latest check, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    reported: Option<u64>,
+    quarantined: bool,
+    last_error: Option<String>,
+}
+
+/// Quarantine flag for one endpoint, driven by its chain id checks
+pub struct ChainIdGuard {
+    endpoint: String,
+    network: String,
+    expected: Option<u64>,
+    state: Mutex<GuardState>,
+}
+
+impl ChainIdGuard {
+    pub fn new(endpoint: &str, network: &str, expected: Option<u64>) -> Self {
+        Self {
+            endpoint: endpoint_host(endpoint),
+            network: network.to_string(),
+            expected,
+            state: Mutex::new(GuardState::default()),
+        }
+    }
+
+    /// Chain id to verify against; `None` when the endpoint is not checked
+    pub fn expected(&self) -> Option<u64> {
+        self.expected
+    }
+
+    pub fn is_quarantined(&self) -> bool {
+        self.state.lock().quarantined
+    }
+
+    /// Apply one `eth_chainId` answer; returns whether the endpoint is quarantined
+    pub fn record(&self, answer: Result<Value, String>) -> bool {
+        let Some(expected) = self.expected else {
+            return false;
+        };
+        let mut state = self.state.lock();
+        let reported = match answer {
+            Ok(value) => parse_chain_id(&value),
+            Err(error) => {
+                state.last_error = Some(error);
+                return state.quarantined;
+            }
+        };
+        let Some(reported) = reported else {
+            state.last_error = Some("unparseable eth_chainId result".to_string());
+            return state.quarantined;
+        };
+
+        let mismatch = reported != expected;
+        if mismatch && !state.quarantined {
+            warn!(
+                "Quarantining {} for {}: chain id {} (expected {})",
+                self.endpoint, self.network, reported, expected
+            );
+        } else if !mismatch && state.quarantined {
+            info!(
+                "Releasing {} for {} from quarantine: chain id {} matches",
+                self.endpoint, self.network, reported
+            );
+        }
+        state.reported = Some(reported);
+        state.quarantined = mismatch;
+        state.last_error = None;
+        mismatch
+    }
+
+    /// `None` for endpoints that are not checked
+    pub fn status(&self) -> Option<ChainIdStatus> {
+        let expected = self.expected?;
+        let state = self.state.lock();
+        Some(ChainIdStatus {
+            endpoint: self.endpoint.clone(),
+            expected,
+            reported: state.reported,
+            quarantined: state.quarantined,
+            last_error: state.last_error.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_expected_chain_ids() {
+        let mut config = ChainIdConfig::default();
+        assert_eq!(config.expected("ethereum"), Some(1));
+        assert_eq!(config.expected("avalanche-fuji"), Some(43_113));
+        assert_eq!(config.expected("solana"), None);
+
+        config.networks.insert("ethereum".to_string(), 5);
+        config.networks.insert("mychain".to_string(), 777);
+        assert_eq!(config.expected("ethereum"), Some(5));
+        assert_eq!(config.expected("mychain"), Some(777));
+
+        config.enabled = false;
+        assert_eq!(config.expected("ethereum"), None);
+    }
+
+    #[test]
+    fn test_mismatch_quarantines_until_chain_id_matches() {
+        let guard = ChainIdGuard::new("https://rpc.sepolia.org", "ethereum", Some(1));
+        assert!(!guard.is_quarantined());
+
+        assert!(guard.record(Ok(json!("0xaa36a7"))));
+        let status = guard.status().unwrap();
+        assert_eq!(status.reported, Some(11_155_111));
+        assert!(status.quarantined);
+
+        // Errors keep the verdict
+        assert!(guard.record(Err("connection refused".to_string())));
+        assert!(guard.record(Ok(json!(null))));
+        assert!(guard.status().unwrap().last_error.is_some());
+
+        assert!(!guard.record(Ok(json!("0x1"))));
+        let status = guard.status().unwrap();
+        assert!(!status.quarantined);
+        assert_eq!(status.last_error, None);
+    }
+
+    #[test]
+    fn test_unchecked_endpoint_is_never_quarantined() {
+        let guard = ChainIdGuard::new("https://api.mainnet-beta.solana.com", "solana", None);
+        assert!(!guard.record(Ok(json!("0x1"))));
+        assert!(!guard.is_quarantined());
+        assert_eq!(guard.status(), None);
+    }
+}
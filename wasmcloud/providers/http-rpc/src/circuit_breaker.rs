@@ -6,18 +6,43 @@
 //! - Open: Too many failures, reject requests immediately
 //! - HalfOpen: Testing if endpoint has recovered
 //!
+//! Half-open admits at most `half_open_max_probes` probe requests at a time and
+//! closes after `success_threshold` consecutive probe successes. A failed probe
+//! reopens the circuit for twice as long as the last time (up to
+//! `max_timeout`), and every open period is jittered so endpoints that failed
+//! together are not all probed at the same instant.
+//!
+//! [`MethodBreakers`] keeps one more breaker per JSON-RPC method of an
+//! endpoint, so an endpoint that keeps timing out on `eth_getLogs` stops
+//! getting `eth_getLogs` while `eth_call` still reaches it. Only failures that
+//! span several methods count against the endpoint as a whole.
+//!
+//! Every state change is sent as a [`CircuitTransition`] to the channel given
+//! to [`CircuitBreaker::with_transitions`]; the provider publishes them on
+//! [`TRANSITIONS_SUBJECT`].
+//!
 //! Based on the classic pattern from Michael Nygard's "Release It!"
 
+use crate::cost::endpoint_host;
 use anyhow::{anyhow, Result};
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
 
+/// NATS subject circuit state transitions are published on
+pub const TRANSITIONS_SUBJECT: &str = "metrics.http_rpc.circuit";
+
 /// Circuit breaker states
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CircuitState {
     /// Circuit is closed - requests flow normally
+    #[default]
     Closed,
     /// Circuit is open - reject requests immediately to protect endpoint
     Open,
@@ -39,6 +64,21 @@ pub struct CircuitBreakerConfig {
 
     /// Rolling window duration for failure counting
     pub window_duration: Duration,
+
+    /// Probe requests admitted at once in half-open state
+    pub half_open_max_probes: u32,
+
+    /// How long an unanswered probe holds its slot
+    pub probe_timeout: Duration,
+
+    /// Longest open period after repeated failed probes
+    pub max_timeout: Duration,
+
+    /// Spread (0.0-1.0) applied to every open period, e.g. 0.2 for ±20%
+    pub jitter: f64,
+
+    /// Track failures per (endpoint, method) as well as per endpoint
+    pub per_method: bool,
 }
 
 impl Default for CircuitBreakerConfig {
@@ -48,10 +88,34 @@ impl Default for CircuitBreakerConfig {
             success_threshold: 2,
             timeout: Duration::from_secs(30),
             window_duration: Duration::from_secs(60),
+            half_open_max_probes: 1,
+            probe_timeout: Duration::from_secs(10),
+            max_timeout: Duration::from_secs(300),
+            jitter: 0.2,
+            per_method: true,
         }
     }
 }
 
+/// One state change of one endpoint's circuit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitTransition {
+    pub network: String,
+    /// Endpoint host
+    pub endpoint: String,
+    /// JSON-RPC method, for per-method circuits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub reason: String,
+    /// How long the circuit stays open, for transitions to open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_for_ms: Option<u64>,
+    /// Unix milliseconds
+    pub at_ms: u64,
+}
+
 /// Circuit breaker state tracking
 #[derive(Debug)]
 struct CircuitBreakerState {
@@ -69,6 +133,15 @@ struct CircuitBreakerState {
 
     /// Window start time for failure counting
     window_start: Instant,
+
+    /// Jittered length of the current open period
+    open_for: Duration,
+
+    /// Failed probes since the circuit last closed
+    reopen_count: u32,
+
+    /// Admission times of unanswered half-open probes
+    probes: VecDeque<Instant>,
 }
 
 impl Default for CircuitBreakerState {
@@ -80,6 +153,9 @@ impl Default for CircuitBreakerState {
             success_count: 0,
             last_transition: now,
             window_start: now,
+            open_for: Duration::ZERO,
+            reopen_count: 0,
+            probes: VecDeque::new(),
         }
     }
 }
@@ -94,18 +170,52 @@ pub struct CircuitBreaker {
 
     /// Internal state
     state: Arc<RwLock<CircuitBreakerState>>,
+
+    /// splitmix64 state for open-period jitter
+    jitter_seed: AtomicU64,
+
+    /// Network name and channel state transitions are sent to
+    transitions: Option<(String, broadcast::Sender<CircuitTransition>)>,
+
+    /// JSON-RPC method this breaker guards; the whole endpoint when unset
+    method: Option<String>,
 }
 
 impl CircuitBreaker {
     /// Create a new circuit breaker for an endpoint
     pub fn new(endpoint: String, config: CircuitBreakerConfig) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+            ^ endpoint
+                .bytes()
+                .fold(0u64, |hash, byte| hash.rotate_left(5) ^ u64::from(byte));
         Self {
             endpoint,
             config,
             state: Arc::new(RwLock::new(CircuitBreakerState::default())),
+            jitter_seed: AtomicU64::new(seed),
+            transitions: None,
+            method: None,
         }
     }
 
+    /// Guard only calls of `method` to the endpoint
+    pub fn for_method(mut self, method: &str) -> Self {
+        self.method = Some(method.to_string());
+        self
+    }
+
+    /// Send every state transition of this `network` endpoint to `sender`
+    pub fn with_transitions(
+        mut self,
+        network: &str,
+        sender: broadcast::Sender<CircuitTransition>,
+    ) -> Self {
+        self.transitions = Some((network.to_string(), sender));
+        self
+    }
+
     /// Check if a request can proceed
     pub fn can_execute(&self) -> Result<()> {
         let mut state = self.state.write();
@@ -120,17 +230,15 @@ impl CircuitBreaker {
                 Ok(())
             }
             CircuitState::Open => {
-                // Check if timeout has elapsed
-                if state.last_transition.elapsed() > self.config.timeout {
+                // Check if the (jittered) open period has elapsed
+                if state.last_transition.elapsed() > state.open_for {
                     // Transition to half-open for testing
-                    state.state = CircuitState::HalfOpen;
-                    state.success_count = 0;
-                    state.last_transition = Instant::now();
+                    self.transition(&mut state, CircuitState::HalfOpen, "open period elapsed");
                     info!(
                         "Circuit breaker for {} transitioning to HALF_OPEN",
                         self.endpoint
                     );
-                    Ok(())
+                    self.admit_probe(&mut state)
                 } else {
                     Err(anyhow!(
                         "Circuit breaker is OPEN for endpoint: {}",
@@ -138,13 +246,111 @@ impl CircuitBreaker {
                     ))
                 }
             }
+            CircuitState::HalfOpen => self.admit_probe(&mut state),
+        }
+    }
+
+    /// Whether `can_execute` would admit a request, without taking a probe slot
+    pub fn would_execute(&self) -> bool {
+        let state = self.state.read();
+        match state.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => state.last_transition.elapsed() > state.open_for,
             CircuitState::HalfOpen => {
-                // Allow limited testing traffic
-                Ok(())
+                let timeout = self.config.probe_timeout;
+                let pending = state
+                    .probes
+                    .iter()
+                    .filter(|started| started.elapsed() <= timeout)
+                    .count();
+                pending < self.config.half_open_max_probes.max(1) as usize
             }
         }
     }
 
+    /// Take a half-open probe slot; unanswered probes give theirs back after
+    /// `probe_timeout`
+    fn admit_probe(&self, state: &mut CircuitBreakerState) -> Result<()> {
+        let timeout = self.config.probe_timeout;
+        while state
+            .probes
+            .front()
+            .is_some_and(|started| started.elapsed() > timeout)
+        {
+            state.probes.pop_front();
+        }
+        if state.probes.len() >= self.config.half_open_max_probes.max(1) as usize {
+            return Err(anyhow!(
+                "Circuit breaker is HALF_OPEN with all probe slots taken for endpoint: {}",
+                self.endpoint
+            ));
+        }
+        state.probes.push_back(Instant::now());
+        Ok(())
+    }
+
+    /// Open period after `reopen_count` failed probes: the timeout doubled per
+    /// failed probe, capped, then jittered
+    fn open_period(&self, reopen_count: u32) -> Duration {
+        let base = self
+            .config
+            .timeout
+            .saturating_mul(2u32.saturating_pow(reopen_count.min(16)))
+            .min(self.config.max_timeout.max(self.config.timeout));
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        base.mul_f64(1.0 + jitter * (2.0 * self.next_unit() - 1.0))
+    }
+
+    /// Uniform value in [0, 1)
+    fn next_unit(&self) -> f64 {
+        let mut z = self
+            .jitter_seed
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Move to `to`, resetting the counters that belong to the old state, and
+    /// announce the change
+    fn transition(&self, state: &mut CircuitBreakerState, to: CircuitState, reason: &str) {
+        let from = state.state;
+        let now = Instant::now();
+        state.state = to;
+        state.success_count = 0;
+        state.last_transition = now;
+        state.probes.clear();
+        match to {
+            CircuitState::Open => state.open_for = self.open_period(state.reopen_count),
+            CircuitState::Closed => {
+                state.failure_count = 0;
+                state.reopen_count = 0;
+                state.window_start = now;
+            }
+            CircuitState::HalfOpen => {}
+        }
+
+        if let Some((network, sender)) = &self.transitions {
+            let at_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64);
+            // No receiver just means nobody is publishing transitions
+            let _ = sender.send(CircuitTransition {
+                network: network.clone(),
+                endpoint: endpoint_host(&self.endpoint),
+                method: self.method.clone(),
+                from,
+                to,
+                reason: reason.to_string(),
+                open_for_ms: (to == CircuitState::Open)
+                    .then_some(state.open_for.as_millis() as u64),
+                at_ms,
+            });
+        }
+    }
+
     /// Record a successful request
     pub fn record_success(&self) {
         let mut state = self.state.write();
@@ -155,15 +361,12 @@ impl CircuitBreaker {
                 state.failure_count = 0;
             }
             CircuitState::HalfOpen => {
+                state.probes.pop_front();
                 state.success_count += 1;
 
-                // Transition to closed if enough successes
+                // Transition to closed after enough consecutive probe successes
                 if state.success_count >= self.config.success_threshold {
-                    state.state = CircuitState::Closed;
-                    state.failure_count = 0;
-                    state.success_count = 0;
-                    state.last_transition = Instant::now();
-                    state.window_start = Instant::now();
+                    self.transition(&mut state, CircuitState::Closed, "probes succeeded");
                     info!(
                         "Circuit breaker for {} transitioning to CLOSED (recovered)",
                         self.endpoint
@@ -190,8 +393,7 @@ impl CircuitBreaker {
 
                 // Transition to open if threshold exceeded
                 if state.failure_count >= self.config.failure_threshold {
-                    state.state = CircuitState::Open;
-                    state.last_transition = Instant::now();
+                    self.transition(&mut state, CircuitState::Open, "failure threshold reached");
                     warn!(
                         "Circuit breaker for {} transitioning to OPEN ({} failures)",
                         self.endpoint, state.failure_count
@@ -199,14 +401,13 @@ impl CircuitBreaker {
                 }
             }
             CircuitState::HalfOpen => {
-                // Single failure in half-open immediately opens circuit
-                state.state = CircuitState::Open;
+                // Single failure in half-open immediately reopens circuit, for longer
                 state.failure_count = self.config.failure_threshold; // Ensure we stay open
-                state.success_count = 0;
-                state.last_transition = Instant::now();
+                state.reopen_count = state.reopen_count.saturating_add(1);
+                self.transition(&mut state, CircuitState::Open, "half-open probe failed");
                 warn!(
-                    "Circuit breaker for {} transitioning to OPEN (half-open test failed)",
-                    self.endpoint
+                    "Circuit breaker for {} transitioning to OPEN (half-open test failed, open for {:?})",
+                    self.endpoint, state.open_for
                 );
             }
             CircuitState::Open => {
@@ -247,6 +448,110 @@ impl CircuitBreaker {
     }
 }
 
+/// Circuit breakers per JSON-RPC method of one endpoint, created on a
+/// method's first call
+pub struct MethodBreakers {
+    endpoint: String,
+    config: CircuitBreakerConfig,
+    transitions: Option<(String, broadcast::Sender<CircuitTransition>)>,
+    breakers: RwLock<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl MethodBreakers {
+    pub fn new(endpoint: String, config: CircuitBreakerConfig) -> Self {
+        Self {
+            endpoint,
+            config,
+            transitions: None,
+            breakers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Send every state transition of this `network` endpoint's method
+    /// circuits to `sender`
+    pub fn with_transitions(
+        mut self,
+        network: &str,
+        sender: broadcast::Sender<CircuitTransition>,
+    ) -> Self {
+        self.transitions = Some((network.to_string(), sender));
+        self
+    }
+
+    /// Whether `can_execute` would admit a call of `method`
+    pub fn would_execute(&self, method: &str) -> bool {
+        self.breakers
+            .read()
+            .get(method)
+            .is_none_or(|breaker| breaker.would_execute())
+    }
+
+    /// Check if a call of `method` can proceed
+    pub fn can_execute(&self, method: &str) -> Result<()> {
+        match self.breakers.read().get(method) {
+            Some(breaker) => breaker.can_execute(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self, method: &str) {
+        if let Some(breaker) = self.breakers.read().get(method) {
+            breaker.record_success();
+        }
+    }
+
+    /// Record a failed call of `method`; returns whether the endpoint as a
+    /// whole should count it, i.e. whether other methods are failing too
+    ///
+    /// With per-method tracking off every failure counts.
+    pub fn record_failure(&self, method: &str) -> bool {
+        if !self.config.per_method {
+            return true;
+        }
+        self.breaker(method).record_failure();
+        let failing = self
+            .breakers
+            .read()
+            .values()
+            .filter(|breaker| {
+                breaker.failure_count() > 0 || breaker.state() != CircuitState::Closed
+            })
+            .count();
+        failing > 1
+    }
+
+    /// Methods whose circuit is not closed, sorted
+    pub fn open_methods(&self) -> Vec<String> {
+        let mut methods: Vec<String> = self
+            .breakers
+            .read()
+            .iter()
+            .filter(|(_, breaker)| breaker.state() != CircuitState::Closed)
+            .map(|(method, _)| method.clone())
+            .collect();
+        methods.sort();
+        methods
+    }
+
+    fn breaker(&self, method: &str) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self.breakers.read().get(method) {
+            return breaker.clone();
+        }
+        self.breakers
+            .write()
+            .entry(method.to_string())
+            .or_insert_with(|| {
+                let breaker = CircuitBreaker::new(self.endpoint.clone(), self.config.clone())
+                    .for_method(method);
+                Arc::new(match &self.transitions {
+                    Some((network, sender)) => breaker.with_transitions(network, sender.clone()),
+                    None => breaker,
+                })
+            })
+            .clone()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -357,6 +662,127 @@ mod tests {
         assert_eq!(cb.state(), CircuitState::Open);
     }
 
+    #[test]
+    fn test_half_open_limits_probes() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 2,
+            timeout: Duration::ZERO,
+            jitter: 0.0,
+            half_open_max_probes: 1,
+            ..Default::default()
+        };
+        let cb = CircuitBreaker::new("http://example.com".to_string(), config);
+        cb.record_failure();
+        std::thread::sleep(Duration::from_millis(2));
+
+        // The first check moves to half-open and takes the only probe slot
+        assert!(cb.would_execute());
+        assert!(cb.can_execute().is_ok());
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        assert!(!cb.would_execute());
+        assert!(cb.can_execute().is_err());
+
+        // A success frees the slot but the circuit needs a second one to close
+        cb.record_success();
+        assert_eq!(cb.state(), CircuitState::HalfOpen);
+        assert!(cb.can_execute().is_ok());
+        cb.record_success();
+        assert_eq!(cb.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_failed_probes_back_off_with_jitter() {
+        let config = CircuitBreakerConfig {
+            timeout: Duration::from_secs(10),
+            max_timeout: Duration::from_secs(60),
+            jitter: 0.2,
+            ..Default::default()
+        };
+        let cb = CircuitBreaker::new("http://example.com".to_string(), config);
+
+        for (failed_probes, base) in [(0, 10.0), (1, 20.0), (2, 40.0), (3, 60.0), (9, 60.0)] {
+            let period = cb.open_period(failed_probes).as_secs_f64();
+            assert!(
+                period >= base * 0.8 && period <= base * 1.2,
+                "{} failed probes: {}s",
+                failed_probes,
+                period
+            );
+        }
+
+        let periods: Vec<Duration> = (0..8).map(|_| cb.open_period(0)).collect();
+        assert!(periods.iter().any(|p| *p != periods[0]));
+    }
+
+    #[test]
+    fn test_transitions_are_announced() {
+        let (sender, mut receiver) = broadcast::channel(16);
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 1,
+            timeout: Duration::ZERO,
+            ..Default::default()
+        };
+        let cb = CircuitBreaker::new("https://rpc.example.org/key".to_string(), config)
+            .with_transitions("ethereum", sender);
+
+        cb.record_failure();
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(cb.can_execute().is_ok());
+        cb.record_failure();
+        let transitions: Vec<CircuitTransition> =
+            std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+
+        let steps: Vec<(CircuitState, CircuitState)> =
+            transitions.iter().map(|t| (t.from, t.to)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Open),
+            ]
+        );
+        assert_eq!(transitions[0].network, "ethereum");
+        assert_eq!(transitions[0].endpoint, "rpc.example.org");
+        assert_eq!(transitions[2].reason, "half-open probe failed");
+        assert!(transitions[2].open_for_ms.is_some());
+    }
+
+    #[test]
+    fn test_method_circuit_isolates_one_method() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            ..Default::default()
+        };
+        let methods = MethodBreakers::new("http://example.com".to_string(), config);
+
+        assert!(!methods.record_failure("eth_getLogs"));
+        assert!(!methods.record_failure("eth_getLogs"));
+        assert!(!methods.would_execute("eth_getLogs"));
+        assert!(methods.can_execute("eth_getLogs").is_err());
+        assert!(methods.would_execute("eth_call"));
+        assert!(methods.can_execute("eth_call").is_ok());
+        assert_eq!(methods.open_methods(), vec!["eth_getLogs".to_string()]);
+
+        // A second failing method means the endpoint itself is in trouble
+        assert!(methods.record_failure("eth_call"));
+    }
+
+    #[test]
+    fn test_method_circuit_disabled() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            per_method: false,
+            ..Default::default()
+        };
+        let methods = MethodBreakers::new("http://example.com".to_string(), config);
+        assert!(methods.record_failure("eth_getLogs"));
+        assert!(methods.would_execute("eth_getLogs"));
+        assert!(methods.open_methods().is_empty());
+    }
+
     #[test]
     fn test_reset() {
         let cb = CircuitBreaker::new(
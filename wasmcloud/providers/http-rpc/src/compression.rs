@@ -0,0 +1,94 @@
+//! Response compression
+//!
+//! `eth_getLogs` and `debug_traceTransaction` answers can run to megabytes of
+//! JSON that compresses several times over. With compression on, pool requests
+//! advertise `Accept-Encoding: gzip, br` and answers are decompressed as they
+//! stream in, before they are validated and parsed.
+//!
+//! Some endpoints mishandle it (double-encoded or truncated bodies behind a
+//! CDN), so it can be turned off per endpoint URL or host; those endpoints are
+//! sent requests from a client that advertises no encodings.
+
+use crate::connection::EndpointConnection;
+use crate::cost::endpoint_host;
+use crate::egress::EndpointEgress;
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const USER_AGENT: &str = "WasmCloud-HTTP-RPC/1.0";
+
+/// Compression settings (`HTTP_RPC_COMPRESSION_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Negotiate gzip and brotli with endpoints not listed in `endpoints`
+    pub enabled: bool,
+    /// On or off per endpoint URL or host; a full URL wins over its host
+    pub endpoints: HashMap<String, bool>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            endpoints: HashMap::new(),
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Whether answers from `endpoint` are requested compressed
+    pub fn enabled_for(&self, endpoint: &str) -> bool {
+        self.endpoints
+            .get(endpoint)
+            .or_else(|| self.endpoints.get(&endpoint_host(endpoint)))
+            .copied()
+            .unwrap_or(self.enabled)
+    }
+}
+
+/// HTTP client for pool requests, negotiating compression when `compress`,
+/// routed through `egress` when set and pooling connections by `connection`
+pub fn http_client(
+    timeout: Duration,
+    compress: bool,
+    egress: Option<(&str, &EndpointEgress)>,
+    connection: &EndpointConnection,
+) -> reqwest::Result<HttpClient> {
+    let builder = connection.apply(
+        HttpClient::builder()
+            .timeout(timeout)
+            .user_agent(USER_AGENT)
+            .gzip(compress)
+            .brotli(compress),
+    );
+    match egress {
+        Some((endpoint, egress)) => egress.apply(endpoint, builder)?.build(),
+        None => builder.build(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_per_endpoint() {
+        let config: CompressionConfig = serde_json::from_str(
+            r#"{"endpoints": {"rpc.example.org": false, "https://rpc.example.org/gz": true}}"#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert!(config.enabled_for("https://eth-mainnet.g.alchemy.com/v2/key"));
+        assert!(!config.enabled_for("https://rpc.example.org/key"));
+        assert!(config.enabled_for("https://rpc.example.org/gz"));
+
+        let config = CompressionConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(!config.enabled_for("https://eth-mainnet.g.alchemy.com/v2/key"));
+    }
+}
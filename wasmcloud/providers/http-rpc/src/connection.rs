@@ -0,0 +1,227 @@
+//! Concurrency limits and connection pooling per endpoint
+//!
+//! Some vendors throttle on concurrent connections or requests in flight rather
+//! than on requests per second. Every endpoint (by URL or host; a full URL wins
+//! over its host) can be given:
+//! - `max_in_flight`: requests sent to it at once; an endpoint at its limit is
+//!   skipped while another can take the call, as rate-limited ones are
+//! - `keep_alive`: `false` opens a fresh connection per request
+//! - `max_idle_connections`: idle connections kept open for reuse
+//! - `idle_timeout_secs`: how long an idle connection is kept
+//! - `tcp_keepalive_secs`: TCP keepalive probe interval
+//! - `http2`: `never` (HTTP/1.1 only, the default), `negotiate` (HTTP/2 when
+//!   the TLS handshake offers it) or `prior_knowledge` (HTTP/2 from the first
+//!   byte, requests multiplexed on one connection)
+//!
+//! Fields an endpoint leaves unset keep the `default` settings; unset there,
+//! the HTTP client's own defaults apply. Endpoints with pooling settings of
+//! their own get their own HTTP client.
+
+use crate::cost::endpoint_host;
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// How often a pool with every endpoint at its in-flight limit looks again
+const IN_FLIGHT_POLL: Duration = Duration::from_millis(10);
+
+/// HTTP version spoken to an endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Http2Mode {
+    #[default]
+    Never,
+    Negotiate,
+    PriorKnowledge,
+}
+
+/// Connection settings (`HTTP_RPC_CONNECTION_CONFIG`, JSON)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionConfig {
+    /// Settings of every endpoint
+    pub default: EndpointConnection,
+    /// Overrides per endpoint URL or host
+    pub endpoints: HashMap<String, EndpointConnection>,
+}
+
+/// Limits and pooling of one endpoint; `None` keeps the default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointConnection {
+    pub max_in_flight: Option<usize>,
+    pub keep_alive: Option<bool>,
+    pub max_idle_connections: Option<usize>,
+    pub idle_timeout_secs: Option<u64>,
+    pub tcp_keepalive_secs: Option<u64>,
+    pub http2: Option<Http2Mode>,
+}
+
+impl ConnectionConfig {
+    /// Settings `endpoint` is reached with
+    pub fn for_endpoint(&self, endpoint: &str) -> EndpointConnection {
+        let Some(own) = self
+            .endpoints
+            .get(endpoint)
+            .or_else(|| self.endpoints.get(&endpoint_host(endpoint)))
+        else {
+            return self.default.clone();
+        };
+        EndpointConnection {
+            max_in_flight: own.max_in_flight.or(self.default.max_in_flight),
+            keep_alive: own.keep_alive.or(self.default.keep_alive),
+            max_idle_connections: own
+                .max_idle_connections
+                .or(self.default.max_idle_connections),
+            idle_timeout_secs: own.idle_timeout_secs.or(self.default.idle_timeout_secs),
+            tcp_keepalive_secs: own.tcp_keepalive_secs.or(self.default.tcp_keepalive_secs),
+            http2: own.http2.or(self.default.http2),
+        }
+    }
+
+    /// Whether `endpoint` needs an HTTP client of its own
+    pub fn has_own_client(&self, endpoint: &str) -> bool {
+        self.for_endpoint(endpoint).client_settings() != self.default.client_settings()
+    }
+}
+
+impl EndpointConnection {
+    /// The settings that shape the HTTP client (all but `max_in_flight`)
+    fn client_settings(&self) -> Self {
+        Self {
+            max_in_flight: None,
+            ..self.clone()
+        }
+    }
+
+    /// `builder` pooling connections by these settings
+    pub fn apply(&self, builder: ClientBuilder) -> ClientBuilder {
+        let mut builder = builder;
+        if self.keep_alive == Some(false) {
+            builder = builder.pool_max_idle_per_host(0);
+        } else if let Some(idle) = self.max_idle_connections {
+            builder = builder.pool_max_idle_per_host(idle);
+        }
+        if let Some(secs) = self.idle_timeout_secs {
+            builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.tcp_keepalive_secs {
+            builder = builder.tcp_keepalive(Duration::from_secs(secs));
+        }
+        match self.http2.unwrap_or_default() {
+            Http2Mode::Never => builder.http1_only(),
+            Http2Mode::Negotiate => builder,
+            Http2Mode::PriorKnowledge => builder.http2_prior_knowledge(),
+        }
+    }
+}
+
+/// Requests in flight to one endpoint, bounded by `max_in_flight`
+pub struct InFlightLimit {
+    slots: Option<Arc<Semaphore>>,
+    max: Option<usize>,
+}
+
+impl InFlightLimit {
+    pub fn new(settings: &EndpointConnection) -> Self {
+        Self {
+            slots: settings
+                .max_in_flight
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            max: settings.max_in_flight.map(|max| max.max(1)),
+        }
+    }
+
+    /// A request could be sent without waiting
+    pub fn has_capacity(&self) -> bool {
+        self.slots
+            .as_ref()
+            .is_none_or(|slots| slots.available_permits() > 0)
+    }
+
+    /// How long to wait before looking for a free slot again
+    pub fn wait_time(&self) -> Duration {
+        if self.has_capacity() {
+            Duration::ZERO
+        } else {
+            IN_FLIGHT_POLL
+        }
+    }
+
+    /// Hold a slot for one request; `None` when unlimited
+    pub async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let slots = self.slots.clone()?;
+        slots.acquire_owned().await.ok()
+    }
+
+    /// Requests in flight, when limited
+    pub fn in_flight(&self) -> Option<usize> {
+        let slots = self.slots.as_ref()?;
+        Some(self.max? - slots.available_permits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_overrides_keep_unset_defaults() {
+        let config: ConnectionConfig = serde_json::from_str(
+            r#"{"default": {"max_idle_connections": 16, "tcp_keepalive_secs": 30},
+                "endpoints": {
+                    "gateway.internal": {"max_in_flight": 4, "http2": "prior_knowledge"},
+                    "https://gateway.internal/archive": {"keep_alive": false}
+                }}"#,
+        )
+        .unwrap();
+
+        let gateway = config.for_endpoint("http://gateway.internal:8545");
+        assert_eq!(gateway.max_in_flight, Some(4));
+        assert_eq!(gateway.http2, Some(Http2Mode::PriorKnowledge));
+        assert_eq!(gateway.max_idle_connections, Some(16));
+        assert_eq!(gateway.tcp_keepalive_secs, Some(30));
+
+        let archive = config.for_endpoint("https://gateway.internal/archive");
+        assert_eq!(archive.keep_alive, Some(false));
+        assert_eq!(archive.max_in_flight, None);
+
+        assert_eq!(
+            config.for_endpoint("https://rpc.example.org"),
+            config.default
+        );
+        assert!(config.has_own_client("http://gateway.internal:8545"));
+        assert!(!config.has_own_client("https://rpc.example.org"));
+
+        // A concurrency limit alone shares the default client
+        let limited: ConnectionConfig =
+            serde_json::from_str(r#"{"endpoints": {"rpc.example.org": {"max_in_flight": 2}}}"#)
+                .unwrap();
+        assert!(!limited.has_own_client("https://rpc.example.org"));
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_slots() {
+        let unlimited = InFlightLimit::new(&EndpointConnection::default());
+        assert!(unlimited.acquire().await.is_none());
+        assert!(unlimited.has_capacity());
+        assert_eq!(unlimited.in_flight(), None);
+
+        let limit = InFlightLimit::new(&EndpointConnection {
+            max_in_flight: Some(2),
+            ..Default::default()
+        });
+        let first = limit.acquire().await;
+        let _second = limit.acquire().await;
+        assert!(!limit.has_capacity());
+        assert_eq!(limit.in_flight(), Some(2));
+        assert_eq!(limit.wait_time(), IN_FLIGHT_POLL);
+
+        drop(first);
+        assert!(limit.has_capacity());
+        assert_eq!(limit.in_flight(), Some(1));
+    }
+}
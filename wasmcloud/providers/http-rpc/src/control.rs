@@ -0,0 +1,259 @@
+//! Runtime endpoint management
+//!
+//! A network's endpoints can be changed without restarting the provider by
+//! publishing `{"endpoint": "https://..."}` to `rpc.endpoints.{network}.{action}`:
+//! - `add`: add the endpoint; registers the network when it has no pool yet
+//! - `remove`: drop the endpoint; a network keeps at least one endpoint
+//! - `pause`: keep the endpoint configured but send it no live traffic
+//! - `resume`: send a paused endpoint live traffic again
+//!
+//! `add` and `remove` rebuild the network's pool, which starts its circuit
+//! breakers and scores afresh; `pause` and `resume` apply to the running pool.
+//! Requests with a reply subject get the resulting [`EndpointSet`] back, or
+//! `{"error": "..."}`.
+//!
+//! Every change is saved in the Redis hash [`ENDPOINTS_KEY`] (one field per
+//! network) and restored at startup, over the endpoints from the environment.
+
+use crate::redis_topology::{RedisConnector, RedisTopologyConfig};
+use anyhow::{anyhow, Result};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+/// Subject pattern control commands are received on (canonical form)
+pub const CONTROL_SUBJECTS: &str = "rpc.endpoints.*.*";
+
+/// Redis hash holding the endpoint set of every changed network
+pub const ENDPOINTS_KEY: &str = "http_rpc:endpoints";
+
+/// Change requested by a control subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointAction {
+    Add,
+    Remove,
+    Pause,
+    Resume,
+}
+
+impl EndpointAction {
+    fn parse(token: &str) -> Option<Self> {
+        match token {
+            "add" => Some(Self::Add),
+            "remove" => Some(Self::Remove),
+            "pause" => Some(Self::Pause),
+            "resume" => Some(Self::Resume),
+            _ => None,
+        }
+    }
+}
+
+/// Network and action of a canonical `rpc.endpoints.{network}.{action}` subject
+pub fn parse_subject(subject: &str) -> Option<(String, EndpointAction)> {
+    let tokens: Vec<&str> = subject.split('.').collect();
+    match tokens.as_slice() {
+        ["rpc", "endpoints", network, action] if !network.is_empty() => {
+            Some((network.to_string(), EndpointAction::parse(action)?))
+        }
+        _ => None,
+    }
+}
+
+/// Payload of a control message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointCommand {
+    pub endpoint: String,
+}
+
+/// Endpoints of one network, as persisted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointSet {
+    pub endpoints: Vec<String>,
+    /// Configured endpoints that get no live traffic
+    #[serde(default)]
+    pub paused: Vec<String>,
+}
+
+impl EndpointSet {
+    /// Apply `action` to `endpoint`; returns whether the endpoint list changed
+    /// (and the pool must be rebuilt) rather than only its paused flags
+    pub fn apply(&mut self, action: EndpointAction, endpoint: &str) -> Result<bool> {
+        let endpoint = endpoint.trim();
+        if endpoint.is_empty() {
+            return Err(anyhow!("Endpoint must not be empty"));
+        }
+        let configured = self.endpoints.iter().any(|e| e == endpoint);
+        if action != EndpointAction::Add && !configured {
+            return Err(anyhow!("Endpoint {} is not configured", endpoint));
+        }
+
+        match action {
+            EndpointAction::Add => {
+                if configured {
+                    return Ok(false);
+                }
+                self.endpoints.push(endpoint.to_string());
+                Ok(true)
+            }
+            EndpointAction::Remove => {
+                if self.endpoints.len() == 1 {
+                    return Err(anyhow!("Cannot remove the last endpoint {}", endpoint));
+                }
+                self.endpoints.retain(|e| e != endpoint);
+                self.paused.retain(|e| e != endpoint);
+                Ok(true)
+            }
+            EndpointAction::Pause => {
+                if self.paused.iter().any(|e| e == endpoint) {
+                    return Ok(false);
+                }
+                if self.paused.len() + 1 >= self.endpoints.len() {
+                    return Err(anyhow!(
+                        "Cannot pause the last active endpoint {}",
+                        endpoint
+                    ));
+                }
+                self.paused.push(endpoint.to_string());
+                Ok(false)
+            }
+            EndpointAction::Resume => {
+                self.paused.retain(|e| e != endpoint);
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// Endpoint sets persisted in Redis
+pub struct EndpointStore {
+    client: Option<RedisConnector>,
+}
+
+impl EndpointStore {
+    /// Store backed by the cache's Redis, reached through `topology`; changes
+    /// are kept in memory only when the URL or topology is invalid
+    pub fn new(redis_url: &str, topology: &RedisTopologyConfig) -> Self {
+        let client = match topology.connector(redis_url) {
+            Ok(client) => Some(client),
+            Err(e) => {
+                warn!(
+                    "Invalid Redis config for endpoint sets: {}. Changes will not persist.",
+                    e
+                );
+                None
+            }
+        };
+        Self { client }
+    }
+
+    /// Every persisted endpoint set, keyed by network
+    pub async fn load(&self) -> Result<HashMap<String, EndpointSet>> {
+        let Some(client) = &self.client else {
+            return Ok(HashMap::new());
+        };
+        let mut conn = client.get_async_connection().await?;
+        let raw: HashMap<String, String> = conn.hgetall(ENDPOINTS_KEY).await?;
+
+        let mut sets = HashMap::new();
+        for (network, json) in raw {
+            match serde_json::from_str::<EndpointSet>(&json) {
+                Ok(set) if !set.endpoints.is_empty() => {
+                    sets.insert(network, set);
+                }
+                Ok(_) => debug!("Ignoring empty endpoint set for {}", network),
+                Err(e) => warn!("Ignoring malformed endpoint set for {}: {}", network, e),
+            }
+        }
+        Ok(sets)
+    }
+
+    pub async fn save(&self, network: &str, set: &EndpointSet) -> Result<()> {
+        let Some(client) = &self.client else {
+            return Ok(());
+        };
+        let mut conn = client.get_async_connection().await?;
+        conn.hset::<_, _, _, ()>(ENDPOINTS_KEY, network, serde_json::to_string(set)?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(endpoints: &[&str]) -> EndpointSet {
+        EndpointSet {
+            endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+            paused: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_subject() {
+        assert_eq!(
+            parse_subject("rpc.endpoints.ethereum.add"),
+            Some(("ethereum".to_string(), EndpointAction::Add))
+        );
+        assert_eq!(
+            parse_subject("rpc.endpoints.avalanche-fuji.pause"),
+            Some(("avalanche-fuji".to_string(), EndpointAction::Pause))
+        );
+        assert_eq!(parse_subject("rpc.endpoints.ethereum.drop"), None);
+        assert_eq!(parse_subject("rpc.endpoints.add"), None);
+        assert_eq!(parse_subject("staging.rpc.endpoints.ethereum.add"), None);
+    }
+
+    #[test]
+    fn test_add_and_remove_change_the_endpoint_list() {
+        let mut endpoints = set(&["https://a.example.org"]);
+        assert!(endpoints
+            .apply(EndpointAction::Add, " https://b.example.org ")
+            .unwrap());
+        assert!(!endpoints
+            .apply(EndpointAction::Add, "https://b.example.org")
+            .unwrap());
+        assert_eq!(endpoints.endpoints.len(), 2);
+
+        assert!(endpoints
+            .apply(EndpointAction::Remove, "https://a.example.org")
+            .unwrap());
+        assert_eq!(endpoints.endpoints, vec!["https://b.example.org"]);
+
+        // The last endpoint stays, and unknown endpoints are rejected
+        assert!(endpoints
+            .apply(EndpointAction::Remove, "https://b.example.org")
+            .is_err());
+        assert!(endpoints
+            .apply(EndpointAction::Remove, "https://c.example.org")
+            .is_err());
+    }
+
+    #[test]
+    fn test_pause_keeps_one_endpoint_active() {
+        let mut endpoints = set(&["https://a.example.org", "https://b.example.org"]);
+        assert!(!endpoints
+            .apply(EndpointAction::Pause, "https://a.example.org")
+            .unwrap());
+        assert_eq!(endpoints.paused, vec!["https://a.example.org"]);
+        assert!(endpoints
+            .apply(EndpointAction::Pause, "https://b.example.org")
+            .is_err());
+
+        endpoints
+            .apply(EndpointAction::Resume, "https://a.example.org")
+            .unwrap();
+        assert!(endpoints.paused.is_empty());
+
+        // Removing a paused endpoint clears its flag
+        endpoints
+            .apply(EndpointAction::Pause, "https://b.example.org")
+            .unwrap();
+        endpoints
+            .apply(EndpointAction::Remove, "https://b.example.org")
+            .unwrap();
+        assert!(endpoints.paused.is_empty());
+    }
+}
@@ -0,0 +1,540 @@
+//! Per-endpoint request cost accounting
+//!
+//! RPC vendors bill per compute unit (Alchemy CUs, Infura/QuickNode credits),
+//! not per request, and the weight depends on the method: an `eth_getLogs` can
+//! cost as much as a dozen `eth_blockNumber` calls. Each endpoint gets a
+//! [`CostMeter`] that prices every upstream attempt with its vendor's weight
+//! table and tracks spend against an optional budget per period.
+//!
+//! The endpoint pool consults the meters when picking an endpoint: once an
+//! endpoint's spend reaches `shift_threshold` of its budget, traffic shifts to
+//! the cheapest healthy endpoint that still has headroom, and a
+//! [`BudgetWarning`] goes out once per period for the endpoint.
+//!
+//! Spend is also added to a Redis hash per UTC day ([`usage_key`], one
+//! `{network}:{host}` field per endpoint) every `usage_flush_secs`. A pool
+//! starts from the day's persisted spend, so a restart does not hand an
+//! endpoint a fresh daily budget.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Subject budget warnings are published on (canonical form)
+pub const BUDGET_SUBJECT: &str = "metrics.http_rpc.budget";
+
+/// Redis hash holding the spend of every endpoint on `day` (`YYYY-MM-DD`, UTC)
+pub fn usage_key(day: &str) -> String {
+    retention_policy::HTTP_RPC_COST_USAGE.key(day)
+}
+
+/// Field of an endpoint in the daily usage hash
+pub fn usage_field(network: &str, host: &str) -> String {
+    format!("{}:{}", network, host)
+}
+
+/// Vendor name, default weight and per-method weights
+type VendorWeights = (&'static str, u64, &'static [(&'static str, u64)]);
+
+/// Built-in weight tables
+///
+/// Values follow the vendors' published pricing; override them through
+/// [`CostConfig::vendors`] when a plan differs.
+const BUILTIN_VENDORS: &[VendorWeights] = &[
+    (
+        "alchemy",
+        26,
+        &[
+            ("eth_blockNumber", 10),
+            ("eth_chainId", 0),
+            ("eth_gasPrice", 19),
+            ("eth_getBalance", 19),
+            ("eth_getCode", 19),
+            ("eth_call", 26),
+            ("eth_getBlockByNumber", 16),
+            ("eth_getBlockByHash", 16),
+            ("eth_getTransactionByHash", 17),
+            ("eth_getTransactionReceipt", 15),
+            ("eth_getLogs", 75),
+            ("eth_estimateGas", 87),
+            ("eth_sendRawTransaction", 250),
+            ("debug_traceTransaction", 309),
+        ],
+    ),
+    (
+        "infura",
+        80,
+        &[
+            ("eth_getLogs", 255),
+            ("eth_estimateGas", 300),
+            ("eth_sendRawTransaction", 720),
+            ("debug_traceTransaction", 1000),
+        ],
+    ),
+    (
+        "quicknode",
+        20,
+        &[("debug_traceTransaction", 40), ("trace_block", 40)],
+    ),
+    ("flat", 1, &[]),
+];
+
+const DEFAULT_BUDGET_PERIOD_SECS: u64 = 24 * 3600;
+const DEFAULT_SHIFT_THRESHOLD: f64 = 0.9;
+const DEFAULT_USAGE_FLUSH_SECS: u64 = 60;
+
+/// Method weights for one vendor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VendorCostModel {
+    pub default_weight: u64,
+    pub method_weights: HashMap<String, u64>,
+}
+
+impl VendorCostModel {
+    fn builtin(vendor: &str) -> Option<Self> {
+        BUILTIN_VENDORS
+            .iter()
+            .find(|(name, _, _)| *name == vendor)
+            .map(|(_, default_weight, methods)| Self {
+                default_weight: *default_weight,
+                method_weights: methods
+                    .iter()
+                    .map(|(method, weight)| (method.to_string(), *weight))
+                    .collect(),
+            })
+    }
+
+    pub fn weight(&self, method: &str) -> u64 {
+        self.method_weights
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+}
+
+/// Cost settings for one endpoint, keyed by host in [`CostConfig::endpoints`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointCostConfig {
+    /// Vendor weight table to use; detected from the host when unset
+    pub vendor: Option<String>,
+    /// Overrides on top of the vendor table
+    pub method_weights: HashMap<String, u64>,
+    /// Units the endpoint may spend per budget period; unlimited when unset
+    pub budget_units: Option<u64>,
+    pub budget_period_secs: u64,
+}
+
+impl Default for EndpointCostConfig {
+    fn default() -> Self {
+        Self {
+            vendor: None,
+            method_weights: HashMap::new(),
+            budget_units: None,
+            budget_period_secs: DEFAULT_BUDGET_PERIOD_SECS,
+        }
+    }
+}
+
+/// Provider-wide cost accounting configuration (`HTTP_RPC_COST_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostConfig {
+    /// Vendor tables; replace the built-in table of the same name
+    pub vendors: HashMap<String, VendorCostModel>,
+    /// Per-endpoint settings keyed by endpoint host
+    pub endpoints: HashMap<String, EndpointCostConfig>,
+    /// Budget utilization (0.0-1.0) at which traffic shifts away from an endpoint
+    pub shift_threshold: f64,
+    /// Seconds between writes of spend to the daily usage hashes
+    pub usage_flush_secs: u64,
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        Self {
+            vendors: HashMap::new(),
+            endpoints: HashMap::new(),
+            shift_threshold: DEFAULT_SHIFT_THRESHOLD,
+            usage_flush_secs: DEFAULT_USAGE_FLUSH_SECS,
+        }
+    }
+}
+
+/// Host of an endpoint URL; also keeps API keys in the path out of metrics
+pub fn endpoint_host(endpoint: &str) -> String {
+    reqwest::Url::parse(endpoint)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| endpoint.to_string())
+}
+
+/// Vendor whose billing model applies to `host`
+pub fn detect_vendor(host: &str) -> &'static str {
+    let host = host.to_lowercase();
+    if host.contains("alchemy") {
+        "alchemy"
+    } else if host.contains("infura.io") {
+        "infura"
+    } else if host.contains("quiknode.pro") || host.contains("quicknode") {
+        "quicknode"
+    } else {
+        "flat"
+    }
+}
+
+/// Spend snapshot for one endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointCostStatus {
+    pub endpoint: String,
+    pub vendor: String,
+    pub total_units: u64,
+    pub total_requests: u64,
+    pub period_units: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_units: Option<u64>,
+    /// Fraction of the period budget spent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_utilization: Option<f64>,
+    /// Average spend over the current period
+    pub units_per_minute: f64,
+    pub near_budget: bool,
+}
+
+/// Sent once per budget period when an endpoint's spend reaches the shift
+/// threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetWarning {
+    pub network: String,
+    pub endpoint: String,
+    pub period_units: u64,
+    pub budget_units: u64,
+    pub budget_utilization: f64,
+    /// Unix time of the warning in milliseconds
+    pub at_ms: u64,
+}
+
+/// Outcome of charging one upstream attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Charge {
+    pub cost: u64,
+    /// Whether this attempt took the endpoint past the shift threshold
+    pub crossed_threshold: bool,
+}
+
+#[derive(Debug)]
+struct MeterState {
+    total_units: u64,
+    total_requests: u64,
+    period_start: Instant,
+    period_units: u64,
+    /// Whether the current period's warning went out
+    warned: bool,
+    /// Units not yet written to the daily usage hash
+    unflushed_units: u64,
+}
+
+/// Cost counters for one endpoint
+pub struct CostMeter {
+    endpoint: String,
+    vendor: String,
+    model: VendorCostModel,
+    budget_units: Option<u64>,
+    budget_period: Duration,
+    shift_threshold: f64,
+    state: Mutex<MeterState>,
+}
+
+impl CostMeter {
+    pub fn new(endpoint: &str, config: &CostConfig) -> Self {
+        Self::new_at(endpoint, config, Instant::now())
+    }
+
+    /// Meter whose first budget period starts at `start`
+    fn new_at(endpoint: &str, config: &CostConfig, start: Instant) -> Self {
+        let host = endpoint_host(endpoint);
+        let endpoint_config = config.endpoints.get(&host).cloned().unwrap_or_default();
+        let vendor = endpoint_config
+            .vendor
+            .clone()
+            .unwrap_or_else(|| detect_vendor(&host).to_string());
+
+        let mut model = config
+            .vendors
+            .get(&vendor)
+            .cloned()
+            .or_else(|| VendorCostModel::builtin(&vendor))
+            .unwrap_or_else(|| VendorCostModel::builtin("flat").unwrap_or_default());
+        model.method_weights.extend(endpoint_config.method_weights);
+
+        Self {
+            endpoint: host,
+            vendor,
+            model,
+            budget_units: endpoint_config.budget_units,
+            budget_period: Duration::from_secs(endpoint_config.budget_period_secs.max(1)),
+            shift_threshold: config.shift_threshold,
+            state: Mutex::new(MeterState {
+                total_units: 0,
+                total_requests: 0,
+                period_start: start,
+                period_units: 0,
+                warned: false,
+                unflushed_units: 0,
+            }),
+        }
+    }
+
+    /// Endpoint host
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Units one call of `method` costs on this endpoint
+    pub fn cost_of(&self, method: &str) -> u64 {
+        self.model.weight(method)
+    }
+
+    /// Charge one upstream attempt
+    pub fn record(&self, method: &str) -> Charge {
+        self.record_at(method, Instant::now())
+    }
+
+    /// Carry over today's persisted spend (e.g. from before a restart); only
+    /// applies to daily budgets, the period the usage hashes are kept for
+    pub fn restore_period_units(&self, units: u64) {
+        if self.budget_period != Duration::from_secs(DEFAULT_BUDGET_PERIOD_SECS) {
+            return;
+        }
+        let mut state = self.state.lock();
+        state.period_units = state.period_units.max(units);
+        state.warned = self.over_threshold(state.period_units);
+    }
+
+    /// Units charged since the last call, for the daily usage hash
+    pub fn take_unflushed(&self) -> u64 {
+        std::mem::take(&mut self.state.lock().unflushed_units)
+    }
+
+    /// Give back units whose write to the daily usage hash failed
+    pub fn return_unflushed(&self, units: u64) {
+        let mut state = self.state.lock();
+        state.unflushed_units = state.unflushed_units.saturating_add(units);
+    }
+
+    /// Warning for `network` about the current period's spend
+    pub fn budget_warning(&self, network: &str) -> Option<BudgetWarning> {
+        let status = self.status();
+        Some(BudgetWarning {
+            network: network.to_string(),
+            endpoint: status.endpoint,
+            period_units: status.period_units,
+            budget_units: status.budget_units?,
+            budget_utilization: status.budget_utilization?,
+            at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+        })
+    }
+
+    /// Whether spend reached the shift threshold of the budget
+    pub fn near_budget(&self) -> bool {
+        self.near_budget_at(Instant::now())
+    }
+
+    pub fn status(&self) -> EndpointCostStatus {
+        self.status_at(Instant::now())
+    }
+
+    fn record_at(&self, method: &str, now: Instant) -> Charge {
+        let cost = self.cost_of(method);
+        let mut state = self.state.lock();
+        self.roll_period(&mut state, now);
+        state.total_units = state.total_units.saturating_add(cost);
+        state.total_requests += 1;
+        state.period_units = state.period_units.saturating_add(cost);
+        state.unflushed_units = state.unflushed_units.saturating_add(cost);
+
+        let crossed_threshold = !state.warned && self.over_threshold(state.period_units);
+        if crossed_threshold {
+            state.warned = true;
+        }
+        Charge {
+            cost,
+            crossed_threshold,
+        }
+    }
+
+    fn near_budget_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock();
+        self.roll_period(&mut state, now);
+        self.over_threshold(state.period_units)
+    }
+
+    fn over_threshold(&self, period_units: u64) -> bool {
+        self.utilization(period_units)
+            .is_some_and(|utilization| utilization >= self.shift_threshold)
+    }
+
+    fn status_at(&self, now: Instant) -> EndpointCostStatus {
+        let mut state = self.state.lock();
+        self.roll_period(&mut state, now);
+        let elapsed_minutes = now
+            .saturating_duration_since(state.period_start)
+            .as_secs_f64()
+            / 60.0;
+        let budget_utilization = self.utilization(state.period_units);
+
+        EndpointCostStatus {
+            endpoint: self.endpoint.clone(),
+            vendor: self.vendor.clone(),
+            total_units: state.total_units,
+            total_requests: state.total_requests,
+            period_units: state.period_units,
+            budget_units: self.budget_units,
+            budget_utilization,
+            units_per_minute: if elapsed_minutes > 0.0 {
+                state.period_units as f64 / elapsed_minutes
+            } else {
+                0.0
+            },
+            near_budget: self.over_threshold(state.period_units),
+        }
+    }
+
+    fn utilization(&self, period_units: u64) -> Option<f64> {
+        self.budget_units.map(|budget| {
+            if budget == 0 {
+                1.0
+            } else {
+                period_units as f64 / budget as f64
+            }
+        })
+    }
+
+    /// Start a new budget period once the current one has elapsed
+    fn roll_period(&self, state: &mut MeterState, now: Instant) {
+        if now.saturating_duration_since(state.period_start) >= self.budget_period {
+            state.period_start = now;
+            state.period_units = 0;
+            state.warned = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_weights_and_overrides() {
+        let mut config = CostConfig::default();
+        config.endpoints.insert(
+            "eth-mainnet.g.alchemy.com".to_string(),
+            EndpointCostConfig {
+                method_weights: HashMap::from([("eth_getLogs".to_string(), 60)]),
+                ..Default::default()
+            },
+        );
+
+        let alchemy = CostMeter::new("https://eth-mainnet.g.alchemy.com/v2/secret", &config);
+        assert_eq!(alchemy.cost_of("eth_blockNumber"), 10);
+        assert_eq!(alchemy.cost_of("eth_getLogs"), 60);
+        assert_eq!(alchemy.cost_of("eth_unknownMethod"), 26);
+        assert_eq!(alchemy.status().endpoint, "eth-mainnet.g.alchemy.com");
+
+        let public = CostMeter::new("https://cloudflare-eth.com", &config);
+        assert_eq!(public.status().vendor, "flat");
+        assert_eq!(public.cost_of("eth_getLogs"), 1);
+        assert_eq!(detect_vendor("abc.quiknode.pro"), "quicknode");
+    }
+
+    #[test]
+    fn test_budget_threshold_and_period_rollover() {
+        let mut config = CostConfig::default();
+        config.endpoints.insert(
+            "mainnet.infura.io".to_string(),
+            EndpointCostConfig {
+                budget_units: Some(1_000),
+                budget_period_secs: 60,
+                ..Default::default()
+            },
+        );
+        let start = Instant::now();
+        let meter = CostMeter::new_at("https://mainnet.infura.io/v3/key", &config, start);
+
+        for _ in 0..10 {
+            meter.record_at("eth_call", start);
+        }
+        assert!(!meter.near_budget_at(start));
+        meter.record_at("eth_getLogs", start);
+        assert!(meter.near_budget_at(start));
+
+        let status = meter.status_at(start + Duration::from_secs(30));
+        assert_eq!(status.period_units, 1_055);
+        assert_eq!(status.total_requests, 11);
+        assert_eq!(status.units_per_minute, 2_110.0);
+        assert!(status.near_budget);
+
+        // A new period resets period spend but keeps lifetime totals
+        let status = meter.status_at(start + Duration::from_secs(61));
+        assert_eq!(status.period_units, 0);
+        assert_eq!(status.total_units, 1_055);
+        assert!(!status.near_budget);
+    }
+
+    #[test]
+    fn test_warning_once_per_period_and_restored_spend() {
+        let mut config = CostConfig::default();
+        config.endpoints.insert(
+            "mainnet.infura.io".to_string(),
+            EndpointCostConfig {
+                budget_units: Some(170),
+                budget_period_secs: 60,
+                ..Default::default()
+            },
+        );
+        let start = Instant::now();
+        let meter = CostMeter::new_at("https://mainnet.infura.io/v3/key", &config, start);
+
+        assert!(!meter.record_at("eth_call", start).crossed_threshold);
+        assert!(meter.record_at("eth_call", start).crossed_threshold);
+        assert!(!meter.record_at("eth_call", start).crossed_threshold);
+        assert_eq!(meter.take_unflushed(), 240);
+        assert_eq!(meter.take_unflushed(), 0);
+
+        let warning = meter.budget_warning("ethereum").unwrap();
+        assert_eq!(warning.endpoint, "mainnet.infura.io");
+        assert_eq!(warning.period_units, 240);
+        assert_eq!(warning.budget_units, 170);
+
+        // A new period warns again
+        let later = start + Duration::from_secs(61);
+        assert!(!meter.record_at("eth_call", later).crossed_threshold);
+        assert!(meter.record_at("eth_call", later).crossed_threshold);
+
+        // Only daily budgets carry over persisted spend
+        meter.restore_period_units(1_000);
+        assert_eq!(meter.status_at(later).period_units, 160);
+
+        // Spend restored past the threshold does not warn a second time
+        config.endpoints.insert(
+            "mainnet.infura.io".to_string(),
+            EndpointCostConfig {
+                budget_units: Some(170),
+                ..Default::default()
+            },
+        );
+        let restarted = CostMeter::new("https://mainnet.infura.io/v3/key", &config);
+        restarted.restore_period_units(190);
+        assert!(restarted.near_budget());
+        assert!(!restarted.record("eth_call").crossed_threshold);
+        assert_eq!(restarted.status().period_units, 270);
+
+        let unbudgeted = CostMeter::new("https://rpc.example.org", &config);
+        unbudgeted.record("eth_call");
+        assert_eq!(unbudgeted.budget_warning("ethereum"), None);
+    }
+}
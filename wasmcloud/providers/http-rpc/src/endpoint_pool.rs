@@ -1,24 +1,104 @@
 //! Endpoint pool with multi-endpoint rotation and failover
 //!
 //! Manages multiple RPC endpoints with:
-//! - Round-robin load balancing
-//! - Circuit breaker per endpoint
+//! - Round-robin, lowest-latency or health-weighted endpoint selection
+//! - Circuit breaker per endpoint, probing recovery with a few jittered requests
+//! - Circuit breaker per (endpoint, method), so one failing method does not take
+//!   the endpoint out for every other method
 //! - Automatic failover to healthy endpoints
 //! - Redis caching for responses
+//! - Cost accounting per endpoint, shifting traffic away from endpoints near budget
+//!   and warning once per budget period; daily spend persisted in Redis
+//! - Weighted A/B split between a primary and a trial arm, with automatic rollback
+//! - Synthetic canary probes scoring every endpoint independently of live traffic
+//! - JSON-RPC batches split to each endpoint's max batch size
+//! - Head-dependent cache entries dropped on every new block
+//! - One caller per missing cache entry asks upstream; the rest wait for its answer
+//! - Token-bucket rate limit per endpoint; limited endpoints are skipped
+//! - Solana networks cached by commitment level, with slot-based head lag
+//! - Requests in flight capped per endpoint, with keep-alive, HTTP/2 and
+//!   connection pool size set per endpoint
+//! - Endpoints that name a retry time when rate limiting are held that long
+//! - Optional hedging of slow calls to a second endpoint
+//! - Endpoints reporting the wrong `eth_chainId` quarantined
+//! - Endpoints lagging the pool's median block height left out until they catch up
+//! - Endpoints paused at runtime get no live traffic
+//! - API key header, bearer or basic auth per endpoint, re-read for key rotation,
+//!   or HMAC/JWT signing of every request for authenticated gateways
+//! - Calls for old blocks routed to archive endpoints, the rest to full nodes first
+//! - HTTP 200 answers that are not valid JSON-RPC responses counted as failures
+//! - gzip/brotli answers negotiated and decompressed, except where turned off
+//! - Outbound HTTP/SOCKS5 proxies and pinned host addresses per endpoint
+//! - Answers read in chunks and abandoned past their method's size limit
+//! - Not-found and reverted answers cached briefly so repeats stay local
+//! - Traces in whichever flavor (geth or parity) each endpoint supports
+//! - Block receipts over `eth_getBlockReceipts` where an endpoint has it, else
+//!   one batch of `eth_getTransactionReceipt` calls
+//! - Rate-limited public fallback endpoints, used only while every primary
+//!   circuit is open
+//! - Optional stale-while-revalidate reads within a per-method staleness budget
+//! - Optional weighted fair queuing of realtime, backfill and bulk calls
+//! - Optional recording of upstream answers, and replay of them without upstream
+//! - Identical concurrent calls coalesced into one upstream call
 //!
 //! This provides resilient RPC access even when individual endpoints fail.
 
-use crate::cache::{CacheConfig, RpcCache};
-use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use crate::archive::{requested_block, ArchiveConfig, NodeKind};
+use crate::auth::{AuthConfig, AuthStatus, Credentials};
+use crate::batch::{split_responses, BatchConfig, RpcBatchRequest, RpcBatchResponse};
+use crate::cache::{is_head_dependent, negative_key, stale_key, CacheConfig, RpcCache};
+use crate::canary::{CanaryConfig, CanaryStatus, CanaryTracker, ProbeAnswer};
+use crate::chain_id::{ChainIdGuard, ChainIdStatus};
+use crate::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitState, CircuitTransition, MethodBreakers,
+};
+use crate::compression::{http_client, CompressionConfig};
+use crate::connection::{ConnectionConfig, InFlightLimit};
+use crate::cost::{
+    endpoint_host, usage_field, usage_key, BudgetWarning, CostConfig, CostMeter, EndpointCostStatus,
+};
+use crate::egress::EgressConfig;
+use crate::fallback::FallbackConfig;
+use crate::head_lag::{block_height, HeadLagConfig, HeadLagTracker};
+use crate::hedge::{HedgeConfig, LatencyWindow};
+use crate::logs::{is_log_range_error, is_range_error, RangeError};
+use crate::negative_cache::{NegativeAnswer, NegativeCacheConfig};
+use crate::priority::{Priority, PriorityConfig, PriorityQueue, QueueDepth};
+use crate::rate_limit::{
+    is_retry_after_error, retry_after_header, retry_after_hint, retry_after_in_body,
+    RateLimitConfig, RateLimitStatus, RateLimiter, RetryAfter,
+};
+use crate::receipts::{
+    batch_receipts, block_receipts_request, block_request, into_receipts, receipt_requests,
+    ReceiptSupport, BLOCK_RECEIPTS,
+};
+use crate::recording::Recorder;
+use crate::redis_topology::RedisConnection;
+use crate::selection::{
+    unmeasured_latency_ms, EndpointScore, EndpointStats, SelectionConfig, SelectionStrategy,
+    Selector,
+};
+use crate::singleflight::{Flight, SingleFlight};
+use crate::size_limit::{is_size_limit_error, read_body, SizeLimitConfig};
+use crate::solana::SolanaConfig;
+use crate::split::{Arm, SplitConfig, SplitStatus, TrafficSplit};
+use crate::swr::{Revalidation, SwrConfig};
+use crate::trace::{is_unsupported_method, Trace, TraceFlavor, TraceSupport, TraceTarget};
+use crate::validation::{is_rate_limit_error, InvalidResponse, ValidationConfig};
 use anyhow::{anyhow, Result};
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{broadcast, OwnedSemaphorePermit};
 use tracing::{debug, info, warn};
 
+/// Largest 429 body read for a retry hint
+const RATE_LIMIT_BODY_LIMIT: u64 = 64 * 1024;
+
 /// RPC request structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RpcRequest {
@@ -26,6 +106,9 @@ pub struct RpcRequest {
     pub method: String,
     pub params: Vec<Value>,
     pub id: u64,
+    /// Queue class of the call; never sent upstream
+    #[serde(default, skip_serializing)]
+    pub priority: Priority,
 }
 
 impl RpcRequest {
@@ -35,8 +118,15 @@ impl RpcRequest {
             method: method.to_string(),
             params,
             id: 1,
+            priority: Priority::default(),
         }
     }
+
+    /// The same call queued as `priority`
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 /// RPC response structure
@@ -73,6 +163,78 @@ pub struct EndpointPoolConfig {
 
     /// Cache configuration
     pub cache: CacheConfig,
+
+    /// Cost weights and budgets
+    pub cost: CostConfig,
+
+    /// A/B split between vendors (none routes across all endpoints)
+    pub split: Option<SplitConfig>,
+
+    /// Batch size limits per endpoint
+    pub batch: BatchConfig,
+
+    /// Requests per second and burst per endpoint
+    pub rate_limit: RateLimitConfig,
+
+    /// Hedging of slow calls (off by default)
+    pub hedge: HedgeConfig,
+
+    /// How the next endpoint is picked
+    pub selection: SelectionConfig,
+
+    /// Chain id every endpoint must report; unchecked when unset
+    pub expected_chain_id: Option<u64>,
+
+    /// Block-height lag at which an endpoint is degraded
+    pub head_lag: HeadLagConfig,
+
+    /// Channel circuit breaker state transitions are sent to
+    pub transitions: Option<broadcast::Sender<CircuitTransition>>,
+
+    /// Credentials per endpoint
+    pub auth: AuthConfig,
+
+    /// Channel budget warnings are sent to
+    pub budget_warnings: Option<broadcast::Sender<BudgetWarning>>,
+
+    /// Full or archive tag per endpoint
+    pub archive: ArchiveConfig,
+
+    /// JSON-RPC envelope and result type checks
+    pub validation: ValidationConfig,
+
+    /// gzip/brotli negotiation per endpoint
+    pub compression: CompressionConfig,
+
+    /// Largest request and answer per method
+    pub size_limit: SizeLimitConfig,
+
+    /// Short-lived caching of not-found and deterministic error answers
+    pub negative_cache: NegativeCacheConfig,
+
+    /// Endpoints used only while every endpoint above has an open circuit
+    pub fallback: FallbackConfig,
+
+    /// Proxy and resolver overrides per endpoint
+    pub egress: EgressConfig,
+
+    /// In-flight limits, keep-alive, HTTP/2 and connection pooling per endpoint
+    pub connection: ConnectionConfig,
+
+    /// Staleness budgets of stale-while-revalidate reads
+    pub swr: SwrConfig,
+
+    /// Upstream concurrency and weights of the priority queues
+    pub priority: PriorityConfig,
+
+    /// Channel calls answered stale are sent to for a background refresh
+    pub revalidations: Option<broadcast::Sender<Revalidation>>,
+
+    /// Recorder of upstream answers, or source of replayed ones
+    pub recorder: Option<Arc<Recorder>>,
+
+    /// Which networks speak Solana JSON-RPC, and their commitment-aware TTLs
+    pub solana: SolanaConfig,
 }
 
 impl Default for EndpointPoolConfig {
@@ -83,24 +245,111 @@ impl Default for EndpointPoolConfig {
             max_retries: 3,
             circuit_breaker: CircuitBreakerConfig::default(),
             cache: CacheConfig::default(),
+            cost: CostConfig::default(),
+            split: None,
+            batch: BatchConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            hedge: HedgeConfig::default(),
+            selection: SelectionConfig::default(),
+            expected_chain_id: None,
+            head_lag: HeadLagConfig::default(),
+            transitions: None,
+            auth: AuthConfig::default(),
+            budget_warnings: None,
+            archive: ArchiveConfig::default(),
+            validation: ValidationConfig::default(),
+            compression: CompressionConfig::default(),
+            size_limit: SizeLimitConfig::default(),
+            negative_cache: NegativeCacheConfig::default(),
+            fallback: FallbackConfig::default(),
+            egress: EgressConfig::default(),
+            connection: ConnectionConfig::default(),
+            swr: SwrConfig::default(),
+            priority: PriorityConfig::default(),
+            revalidations: None,
+            recorder: None,
+            solana: SolanaConfig::default(),
         }
     }
 }
 
 /// Endpoint pool for load balancing and failover
 pub struct EndpointPool {
-    /// HTTP client
-    client: HttpClient,
+    /// HTTP client per endpoint, negotiating compression unless it is turned
+    /// off, routed through its proxy or resolver overrides and pooling
+    /// connections by its settings (same order as circuit breakers)
+    clients: Vec<HttpClient>,
+
+    /// Requests in flight per endpoint (same order as circuit breakers)
+    in_flight: Vec<InFlightLimit>,
 
     /// Circuit breakers per endpoint
     circuit_breakers: Vec<Arc<CircuitBreaker>>,
 
+    /// Endpoints before this index are primaries, the rest fallbacks
+    primary_count: usize,
+
+    /// Per-method circuit breakers per endpoint (same order as circuit breakers)
+    method_breakers: Vec<MethodBreakers>,
+
+    /// Cost meters per endpoint (same order as circuit breakers)
+    cost_meters: Vec<CostMeter>,
+
+    /// Canary probe history per endpoint (same order as circuit breakers)
+    canaries: Vec<CanaryTracker>,
+
+    /// Token buckets per endpoint (same order as circuit breakers)
+    rate_limiters: Vec<RateLimiter>,
+
+    /// Latency and success EWMAs per endpoint (same order as circuit breakers)
+    endpoint_stats: Vec<EndpointStats>,
+
+    /// Selection strategy state
+    selector: Selector,
+
+    /// Chain id quarantine per endpoint (same order as circuit breakers)
+    chain_id_guards: Vec<ChainIdGuard>,
+
+    /// Latest block height per endpoint
+    head_lag: HeadLagTracker,
+
+    /// Pause flag per endpoint (same order as circuit breakers)
+    paused: Vec<AtomicBool>,
+
+    /// Auth per endpoint (same order as circuit breakers)
+    credentials: Vec<Credentials>,
+
+    /// Archive tag per endpoint (same order as circuit breakers)
+    archive: Vec<bool>,
+
+    /// Trace flavors per endpoint (same order as circuit breakers)
+    trace_support: Vec<TraceSupport>,
+
+    /// `eth_getBlockReceipts` support per endpoint (same order as circuit
+    /// breakers)
+    receipt_support: Vec<ReceiptSupport>,
+
+    /// Recent successful call latencies, for the hedge delay
+    latencies: LatencyWindow,
+
+    /// Cache keys of stale answers being refreshed
+    revalidating: parking_lot::Mutex<HashSet<String>>,
+
+    /// Upstream calls waiting per priority class
+    queue: PriorityQueue,
+
     /// Round-robin counter
     counter: AtomicUsize,
 
+    /// Active A/B split; swapped live by `set_split`
+    split: parking_lot::RwLock<Option<Arc<TrafficSplit>>>,
+
     /// RPC cache
     cache: Arc<RpcCache>,
 
+    /// Upstream calls in flight, shared by identical concurrent requests
+    single_flight: SingleFlight<Result<RpcResponse, String>>,
+
     /// Configuration
     config: EndpointPoolConfig,
 
@@ -110,36 +359,170 @@ pub struct EndpointPool {
 
 impl EndpointPool {
     /// Create a new endpoint pool
-    pub fn new(network: String, config: EndpointPoolConfig) -> Result<Self> {
+    pub fn new(network: String, mut config: EndpointPoolConfig) -> Result<Self> {
         if config.endpoints.is_empty() {
             return Err(anyhow!("At least one endpoint must be configured"));
         }
 
-        let client = HttpClient::builder()
-            .timeout(config.request_timeout)
-            .user_agent("WasmCloud-HTTP-RPC/1.0")
-            .build()
+        // Fallback endpoints go after the primaries
+        let primary_count = config.endpoints.len();
+        let fallbacks = config.fallback.endpoints_for(&network, &config.endpoints);
+        config.endpoints.extend(fallbacks);
+
+        // Endpoints with their own proxy, resolver or pooling settings get
+        // their own client
+        let shared = &config.connection.default;
+        let client = http_client(config.request_timeout, true, None, shared)
             .map_err(|e| anyhow!("Failed to build HTTP client: {}", e))?;
+        let plain_client = http_client(config.request_timeout, false, None, shared)
+            .map_err(|e| anyhow!("Failed to build HTTP client: {}", e))?;
+        let clients = config
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let compress = config.compression.enabled_for(endpoint);
+                let egress = config.egress.for_endpoint(endpoint);
+                if egress.is_none() && !config.connection.has_own_client(endpoint) {
+                    return Ok(if compress {
+                        client.clone()
+                    } else {
+                        plain_client.clone()
+                    });
+                }
+                http_client(
+                    config.request_timeout,
+                    compress,
+                    egress.as_ref().map(|egress| (endpoint.as_str(), egress)),
+                    &config.connection.for_endpoint(endpoint),
+                )
+                .map_err(|e| anyhow!("Failed to build HTTP client for {}: {}", endpoint, e))
+            })
+            .collect::<Result<Vec<HttpClient>>>()?;
+        let in_flight = config
+            .endpoints
+            .iter()
+            .map(|endpoint| InFlightLimit::new(&config.connection.for_endpoint(endpoint)))
+            .collect();
 
         // Create circuit breakers for each endpoint
         let circuit_breakers: Vec<Arc<CircuitBreaker>> = config
             .endpoints
             .iter()
             .map(|endpoint| {
-                Arc::new(CircuitBreaker::new(
-                    endpoint.clone(),
-                    config.circuit_breaker.clone(),
-                ))
+                let breaker = CircuitBreaker::new(endpoint.clone(), config.circuit_breaker.clone());
+                Arc::new(match &config.transitions {
+                    Some(sender) => breaker.with_transitions(&network, sender.clone()),
+                    None => breaker,
+                })
+            })
+            .collect();
+
+        let method_breakers: Vec<MethodBreakers> = config
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let breakers =
+                    MethodBreakers::new(endpoint.clone(), config.circuit_breaker.clone());
+                match &config.transitions {
+                    Some(sender) => breakers.with_transitions(&network, sender.clone()),
+                    None => breakers,
+                }
             })
             .collect();
 
+        let cost_meters: Vec<CostMeter> = config
+            .endpoints
+            .iter()
+            .map(|endpoint| CostMeter::new(endpoint, &config.cost))
+            .collect();
+
+        let canaries: Vec<CanaryTracker> = config
+            .endpoints
+            .iter()
+            .map(|e| CanaryTracker::new(e))
+            .collect();
+
+        let fallback_limits = config.fallback.rate_limits(&config.rate_limit);
+        let rate_limiters: Vec<RateLimiter> = config
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                if index < primary_count {
+                    RateLimiter::new(endpoint, &config.rate_limit)
+                } else {
+                    RateLimiter::new(endpoint, &fallback_limits)
+                }
+            })
+            .collect();
+
+        let endpoint_stats: Vec<EndpointStats> = config
+            .endpoints
+            .iter()
+            .map(|endpoint| EndpointStats::new(endpoint, &config.selection))
+            .collect();
+
+        let chain_id_guards: Vec<ChainIdGuard> = config
+            .endpoints
+            .iter()
+            .map(|endpoint| ChainIdGuard::new(endpoint, &network, config.expected_chain_id))
+            .collect();
+
+        let split = match &config.split {
+            Some(split) => Some(Arc::new(TrafficSplit::new(
+                &config.endpoints,
+                split.clone(),
+            )?)),
+            None => None,
+        };
+
         let cache = Arc::new(RpcCache::new(config.cache.clone()));
 
         Ok(Self {
-            client,
+            clients,
+            in_flight,
             circuit_breakers,
+            primary_count,
+            method_breakers,
+            cost_meters,
+            canaries,
+            rate_limiters,
+            endpoint_stats,
+            selector: Selector::new(&config.selection),
+            chain_id_guards,
+            head_lag: HeadLagTracker::new(config.endpoints.len(), config.head_lag.clone()),
+            paused: config
+                .endpoints
+                .iter()
+                .map(|_| AtomicBool::new(false))
+                .collect(),
+            credentials: config
+                .endpoints
+                .iter()
+                .map(|endpoint| Credentials::new(endpoint, &config.auth))
+                .collect(),
+            archive: config
+                .endpoints
+                .iter()
+                .map(|endpoint| config.archive.kind_of(endpoint) == NodeKind::Archive)
+                .collect(),
+            trace_support: config
+                .endpoints
+                .iter()
+                .map(|_| TraceSupport::default())
+                .collect(),
+            receipt_support: config
+                .endpoints
+                .iter()
+                .map(|_| ReceiptSupport::default())
+                .collect(),
+            latencies: LatencyWindow::new(config.hedge.window),
+            revalidating: parking_lot::Mutex::new(HashSet::new()),
+            queue: PriorityQueue::new(&config.priority),
             counter: AtomicUsize::new(0),
+            split: parking_lot::RwLock::new(split),
             cache,
+            single_flight: SingleFlight::new(),
             config,
             network,
         })
@@ -148,6 +531,13 @@ impl EndpointPool {
     /// Initialize the pool (connect cache, etc.)
     pub async fn init(&self) -> Result<()> {
         self.cache.connect().await?;
+        self.refresh_credentials().await;
+        if let Err(e) = self.restore_usage().await {
+            warn!(
+                "Failed to restore today's spend for {}: {}",
+                self.network, e
+            );
+        }
         info!(
             "Endpoint pool initialized for {} with {} endpoints",
             self.network,
@@ -156,25 +546,402 @@ impl EndpointPool {
         Ok(())
     }
 
-    /// Get next healthy endpoint (round-robin with circuit breaker check)
-    fn get_next_endpoint(&self) -> Option<(usize, Arc<CircuitBreaker>)> {
+    /// Re-read every endpoint's secret from the environment and Redis
+    ///
+    /// When Redis can't be reached, Redis-held secrets keep their last value.
+    pub async fn refresh_credentials(&self) {
+        let keys: Vec<&str> = self
+            .credentials
+            .iter()
+            .filter_map(Credentials::redis_key)
+            .collect();
+        let mut secrets = HashMap::new();
+        let mut redis_ok = true;
+        if !keys.is_empty() {
+            match self.fetch_secrets(&keys).await {
+                Ok(fetched) => secrets = fetched,
+                Err(e) => {
+                    warn!("Failed to read endpoint secrets from Redis: {}", e);
+                    redis_ok = false;
+                }
+            }
+        }
+        for credentials in &self.credentials {
+            if redis_ok || credentials.redis_key().is_none() {
+                credentials.refresh(&secrets);
+            }
+        }
+    }
+
+    /// Connection to the cache's Redis, through its configured topology
+    async fn redis_connection(&self) -> Result<RedisConnection> {
+        let cache = &self.config.cache;
+        Ok(cache
+            .redis
+            .connector(&cache.redis_url)?
+            .get_async_connection()
+            .await?)
+    }
+
+    async fn fetch_secrets(&self, keys: &[&str]) -> Result<HashMap<String, String>> {
+        let mut conn = self.redis_connection().await?;
+        let mut secrets = HashMap::new();
+        for key in keys {
+            let value: Option<String> = redis::AsyncCommands::get(&mut conn, *key).await?;
+            if let Some(value) = value {
+                secrets.insert(key.to_string(), value);
+            }
+        }
+        Ok(secrets)
+    }
+
+    /// Seed the cost meters with today's spend from the daily usage hash
+    async fn restore_usage(&self) -> Result<()> {
+        let mut conn = self.redis_connection().await?;
+        let key = usage_key(&chrono::Utc::now().format("%Y-%m-%d").to_string());
+        for meter in &self.cost_meters {
+            let field = usage_field(&self.network, meter.endpoint());
+            let units: Option<u64> = redis::AsyncCommands::hget(&mut conn, &key, field).await?;
+            if let Some(units) = units {
+                meter.restore_period_units(units);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the spend since the last flush to today's usage hash
+    ///
+    /// Units that fail to write are kept for the next flush.
+    pub async fn flush_usage(&self) -> Result<()> {
+        let spend: Vec<u64> = self
+            .cost_meters
+            .iter()
+            .map(CostMeter::take_unflushed)
+            .collect();
+        if spend.iter().all(|units| *units == 0) {
+            return Ok(());
+        }
+
+        let result = self.write_usage(&spend).await;
+        if result.is_err() {
+            for (meter, units) in self.cost_meters.iter().zip(spend) {
+                meter.return_unflushed(units);
+            }
+        }
+        result
+    }
+
+    async fn write_usage(&self, spend: &[u64]) -> Result<()> {
+        let mut conn = self.redis_connection().await?;
+        let key = usage_key(&chrono::Utc::now().format("%Y-%m-%d").to_string());
+
+        let mut pipe = redis::pipe();
+        for (meter, units) in self.cost_meters.iter().zip(spend) {
+            if *units > 0 {
+                pipe.hincr(&key, usage_field(&self.network, meter.endpoint()), *units)
+                    .ignore();
+            }
+        }
+        if let Some(ttl) = retention_policy::HTTP_RPC_COST_USAGE.ttl_secs {
+            pipe.expire(&key, ttl as i64).ignore();
+        }
+        pipe.query_async::<_, ()>(&mut conn).await?;
+        Ok(())
+    }
+
+    /// Charge one upstream attempt of `method` to endpoint `index`, warning
+    /// when it takes the endpoint near its budget
+    fn charge(&self, index: usize, method: &str) {
+        let meter = &self.cost_meters[index];
+        if !meter.record(method).crossed_threshold {
+            return;
+        }
+        let Some(warning) = meter.budget_warning(&self.network) else {
+            return;
+        };
+        warn!(
+            "{} for {} at {:.0}% of its budget ({} of {} units)",
+            warning.endpoint,
+            self.network,
+            warning.budget_utilization * 100.0,
+            warning.period_units,
+            warning.budget_units
+        );
+        if let Some(sender) = &self.config.budget_warnings {
+            // No receivers is fine; the warning is also logged
+            let _ = sender.send(warning);
+        }
+    }
+
+    /// Configured endpoint URLs
+    pub fn endpoints(&self) -> &[String] {
+        &self.config.endpoints[..self.primary_count]
+    }
+
+    /// Fallback endpoints, behind the primaries
+    pub fn fallback_endpoints(&self) -> &[String] {
+        &self.config.endpoints[self.primary_count..]
+    }
+
+    /// Whether fallback endpoints may take calls: every primary circuit is
+    /// open and cannot take a probe
+    fn fallback_active(&self) -> bool {
+        self.circuit_breakers[..self.primary_count]
+            .iter()
+            .all(|breaker| !breaker.would_execute())
+    }
+
+    /// Stop (or resume) live traffic to `endpoint`; returns whether it is
+    /// configured
+    pub fn set_paused(&self, endpoint: &str, paused: bool) -> bool {
+        match self.config.endpoints.iter().position(|e| e == endpoint) {
+            Some(index) => {
+                self.paused[index].store(paused, Ordering::Relaxed);
+                info!(
+                    "{} {} for {}",
+                    if paused { "Paused" } else { "Resumed" },
+                    self.cost_meters[index].endpoint(),
+                    self.network
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// URLs of the paused endpoints
+    pub fn paused_endpoints(&self) -> Vec<String> {
+        self.config
+            .endpoints
+            .iter()
+            .zip(&self.paused)
+            .filter(|(_, paused)| paused.load(Ordering::Relaxed))
+            .map(|(endpoint, _)| endpoint.clone())
+            .collect()
+    }
+
+    /// Replace the A/B split without rebuilding the pool; resets arm metrics
+    /// and re-arms a rolled-back split
+    pub fn set_split(&self, split: Option<SplitConfig>) -> Result<()> {
+        let split = match split {
+            Some(split) => Some(Arc::new(TrafficSplit::new(&self.config.endpoints, split)?)),
+            None => None,
+        };
+        *self.split.write() = split;
+        Ok(())
+    }
+
+    /// Whether a call must go to an archive endpoint: it reads an old block
+    /// and the pool has archive endpoints
+    fn needs_archive(&self, method: &str, params: &[Value]) -> bool {
+        requested_block(method, params).is_some_and(|block| self.needs_archive_at(block))
+    }
+
+    /// Whether reading state at `block` takes an archive endpoint
+    fn needs_archive_at(&self, block: u64) -> bool {
+        self.archive.iter().any(|archive| *archive)
+            && self
+                .config
+                .archive
+                .is_old_block(block, self.head_lag.median())
+    }
+
+    /// Get the endpoint for `method`: round-robin within `arm`, unless the pick
+    /// is near its budget
+    ///
+    /// An endpoint near its budget hands the call to the cheapest closed-circuit
+    /// endpoint of the same arm with headroom, and keeps it when there is none.
+    /// When the arm has no healthy endpoint the whole pool is used. `archive`
+    /// calls only go to archive endpoints; the rest prefer full nodes.
+    fn get_next_endpoint(
+        &self,
+        method: &str,
+        split: Option<&TrafficSplit>,
+        arm: Arm,
+        archive: bool,
+    ) -> Option<(usize, Arc<CircuitBreaker>)> {
+        let in_arm = |i: usize| split.is_none_or(|split| split.arm_of(i) == arm);
+        let in_tier = |i: usize| self.archive[i] == archive;
+        let (index, circuit_breaker) = self
+            .next_healthy_endpoint(Some(method), |i| in_arm(i) && in_tier(i))
+            .or_else(|| self.next_healthy_endpoint(Some(method), in_tier))
+            .or_else(|| {
+                // Full-node calls fall back to archive endpoints
+                (!archive)
+                    .then(|| self.next_healthy_endpoint(Some(method), |_| true))
+                    .flatten()
+            })?;
+        if !self.cost_meters[index].near_budget() {
+            return Some((index, circuit_breaker));
+        }
+
+        let cheaper = (0..self.circuit_breakers.len())
+            .filter(|i| *i != index && *i < self.primary_count && in_arm(*i))
+            .filter(|i| !archive || self.archive[*i])
+            .filter(|i| self.circuit_breakers[*i].state() == CircuitState::Closed)
+            .filter(|i| self.in_rotation(*i))
+            .filter(|i| self.method_breakers[*i].would_execute(method))
+            .filter(|i| !self.cost_meters[*i].near_budget())
+            .filter(|i| self.rate_limiters[*i].has_capacity())
+            .filter(|i| self.in_flight[*i].has_capacity())
+            .min_by_key(|i| self.cost_meters[*i].cost_of(method));
+
+        match cheaper {
+            Some(i) => {
+                debug!(
+                    "{} near its budget, shifting {} to {}",
+                    self.cost_meters[index].endpoint(),
+                    method,
+                    self.cost_meters[i].endpoint()
+                );
+                Some((i, self.circuit_breakers[i].clone()))
+            }
+            None => Some((index, circuit_breaker)),
+        }
+    }
+
+    /// Whether the endpoint may be picked at all: not paused, not
+    /// quarantined for answering for another chain and not lagging the head.
+    /// Every selection path checks this before capacity and circuit state.
+    fn in_rotation(&self, index: usize) -> bool {
+        !self.paused[index].load(Ordering::Relaxed)
+            && !self.chain_id_guards[index].is_quarantined()
+            && !self.head_lag.is_degraded(index)
+    }
+
+    /// Get next healthy endpoint accepted by `eligible` (rate limit and
+    /// circuit breaker check, and the circuit of `method` when given),
+    /// round-robin or by the selection strategy
+    fn next_healthy_endpoint(
+        &self,
+        method: Option<&str>,
+        eligible: impl Fn(usize) -> bool,
+    ) -> Option<(usize, Arc<CircuitBreaker>)> {
         let total_endpoints = self.circuit_breakers.len();
+        let fallback_active = self.fallback_active();
+        let available = |index: usize| {
+            eligible(index)
+                && (index < self.primary_count || fallback_active)
+                && self.in_rotation(index)
+                && self.rate_limiters[index].has_capacity()
+                && self.in_flight[index].has_capacity()
+                && method.is_none_or(|method| self.method_breakers[index].would_execute(method))
+        };
+        // Takes a half-open probe slot, so only for the endpoint being returned
+        let admit = |index: usize| {
+            self.circuit_breakers[index].can_execute().is_ok()
+                && method
+                    .is_none_or(|method| self.method_breakers[index].can_execute(method).is_ok())
+        };
+
+        if self.selector.strategy() != SelectionStrategy::RoundRobin {
+            // Only the picked endpoint may take a half-open probe slot
+            let mut candidates: Vec<usize> = (0..total_endpoints)
+                .filter(|i| available(*i) && self.circuit_breakers[*i].would_execute())
+                .collect();
+            while let Some(index) = self.selector.pick(&candidates, &self.endpoint_stats) {
+                if admit(index) {
+                    return Some((index, self.circuit_breakers[index].clone()));
+                }
+                candidates.retain(|i| *i != index);
+            }
+            return None;
+        }
 
         // Try all endpoints starting from round-robin position
+        let start = self.counter.fetch_add(1, Ordering::Relaxed);
         for i in 0..total_endpoints {
-            let index = (self.counter.fetch_add(1, Ordering::Relaxed) + i) % total_endpoints;
-            let cb = &self.circuit_breakers[index];
-
-            if cb.can_execute().is_ok() {
-                return Some((index, cb.clone()));
+            let index = (start + i) % total_endpoints;
+            if available(index) && admit(index) {
+                return Some((index, self.circuit_breakers[index].clone()));
             }
         }
 
         None
     }
 
+    /// Pick an endpoint as `get_next_endpoint` does, waiting for a rate limit
+    /// token when every healthy endpoint is limited
+    ///
+    /// Waits at most `max_wait_ms` in total; the caller spends the tokens.
+    async fn wait_for_endpoint(
+        &self,
+        method: &str,
+        split: Option<&TrafficSplit>,
+        arm: Arm,
+        archive: bool,
+    ) -> Result<(usize, Arc<CircuitBreaker>)> {
+        let deadline = Instant::now() + Duration::from_millis(self.config.rate_limit.max_wait_ms);
+        loop {
+            if let Some(picked) = self.get_next_endpoint(method, split, arm, archive) {
+                return Ok(picked);
+            }
+
+            // Only endpoints that could take the call once refilled count
+            let fallback_active = self.fallback_active();
+            let wait = (0..self.circuit_breakers.len())
+                .filter(|i| *i < self.primary_count || fallback_active)
+                .filter(|i| !archive || self.archive[*i])
+                .filter(|i| self.circuit_breakers[*i].would_execute())
+                .filter(|i| self.method_breakers[*i].would_execute(method))
+                .filter(|i| self.in_rotation(*i))
+                .map(|i| {
+                    self.rate_limiters[i]
+                        .wait_time()
+                        .max(self.in_flight[i].wait_time())
+                })
+                .min();
+            let Some(wait) = wait else {
+                warn!("No healthy endpoints available for {}", self.network);
+                return Err(anyhow!(
+                    "All endpoints are unhealthy (circuit breakers open)"
+                ));
+            };
+            if Instant::now() + wait > deadline {
+                warn!(
+                    "All healthy endpoints for {} are rate limited",
+                    self.network
+                );
+                return Err(anyhow!("All healthy endpoints are rate limited"));
+            }
+            debug!(
+                "All healthy endpoints for {} are rate limited, waiting {:?}",
+                self.network, wait
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
     /// Call RPC with failover across endpoints
+    ///
+    /// Identical concurrent calls share one upstream call and one cache write;
+    /// each caller gets the answer under its own request id. The caller that
+    /// made the call keeps its typed error, the others get its message.
     pub async fn call_with_failover(&self, request: &RpcRequest) -> Result<RpcResponse> {
+        if let Some(replayed) = self.replayed(request) {
+            return replayed;
+        }
+
+        let key = self
+            .cache
+            .make_key(&self.network, &request.method, &request.params);
+        let answer = self
+            .single_flight
+            .run(&key, || self.call_uncoalesced(request), shared_answer)
+            .await;
+        match answer {
+            Flight::Led(answer) => answer,
+            Flight::Joined(answer) => answer
+                .map(|response| RpcResponse {
+                    id: request.id,
+                    ..response
+                })
+                .map_err(|e| anyhow!(e)),
+        }
+    }
+
+    /// Cache lookup, then failover across endpoints
+    async fn call_uncoalesced(&self, request: &RpcRequest) -> Result<RpcResponse> {
         // Check cache first
         let cache_key = self
             .cache
@@ -189,24 +956,87 @@ impl EndpointPool {
                 id: request.id,
             });
         }
+        if let Some(stale_value) = self.stale_answer(&cache_key, request).await? {
+            debug!("Stale cache hit for {}/{}", self.network, request.method);
+            return Ok(RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: Some(stale_value),
+                error: None,
+                id: request.id,
+            });
+        }
+        let negative_key = negative_key(&cache_key);
+        if let Some(answer) = self.negative_answer(&negative_key).await? {
+            debug!("Negative cache hit for {}/{}", self.network, request.method);
+            return match answer {
+                NegativeAnswer::Result(result) => Ok(RpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    result,
+                    error: None,
+                    id: request.id,
+                }),
+                NegativeAnswer::Error(message) => Err(anyhow!(message)),
+            };
+        }
+
+        // One caller refreshes a missing entry; the rest wait for its answer
+        let locked = self.cache.lock_refresh(&cache_key).await;
+        if !locked {
+            if let Some(value) = self.cache.wait_for_refresh(&cache_key).await? {
+                debug!(
+                    "Refreshed cache hit for {}/{}",
+                    self.network, request.method
+                );
+                return Ok(RpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    result: Some(value),
+                    error: None,
+                    id: request.id,
+                });
+            }
+        }
+        let response = self
+            .fetch_with_failover(request, &cache_key, &negative_key)
+            .await;
+        if locked {
+            self.cache.release_refresh(&cache_key).await;
+        }
+        response
+    }
 
+    /// Send `request` upstream with failover and cache the answer under
+    /// `cache_key` (or `negative_key` for not-found and deterministic errors)
+    async fn fetch_with_failover(
+        &self,
+        request: &RpcRequest,
+        cache_key: &str,
+        negative_key: &str,
+    ) -> Result<RpcResponse> {
+        let _permit = self.queue.acquire(request.priority).await;
         let mut last_error = None;
         let mut attempts = 0;
 
+        // The arm is picked once per request; failover retries stay in it
+        let split = self.split.read().clone();
+        let arm = split
+            .as_deref()
+            .map_or(Arm::Primary, TrafficSplit::choose_arm);
+        let archive = self.needs_archive(&request.method, &request.params);
+        if archive {
+            debug!(
+                "{} reads an old block, routing to archive endpoints",
+                request.method
+            );
+        }
+
         // Try with failover
         while attempts < self.config.max_retries {
             attempts += 1;
 
-            // Get next healthy endpoint
-            let (endpoint_idx, circuit_breaker) = match self.get_next_endpoint() {
-                Some(ep) => ep,
-                None => {
-                    warn!("No healthy endpoints available for {}", self.network);
-                    return Err(anyhow!(
-                        "All endpoints are unhealthy (circuit breakers open)"
-                    ));
-                }
-            };
+            // Get next healthy endpoint with rate limit headroom
+            let (endpoint_idx, _) = self
+                .wait_for_endpoint(&request.method, split.as_deref(), arm, archive)
+                .await?;
 
             let endpoint = &self.config.endpoints[endpoint_idx];
 
@@ -215,76 +1045,944 @@ impl EndpointPool {
                 attempts, self.config.max_retries, request.method, endpoint
             );
 
-            match self.make_request(endpoint, request).await {
-                Ok(response) => {
-                    // Record success
-                    circuit_breaker.record_success();
+            // Vendors bill and count every upstream attempt, failed ones included
+            self.charge(endpoint_idx, &request.method);
+            self.rate_limiters[endpoint_idx].acquire(1);
+
+            let mut answer = None;
+            let outcomes = self
+                .send_with_hedge(endpoint_idx, request, split.as_deref(), arm)
+                .await;
+            for (index, result, elapsed) in outcomes {
+                if let Some(split) = &split {
+                    split.record(index, result.is_ok(), elapsed);
+                }
+                self.endpoint_stats[index].record(result.is_ok(), Some(elapsed));
+
+                match result {
+                    Ok(response) => {
+                        // Record success
+                        self.circuit_breakers[index].record_success();
+                        self.method_breakers[index].record_success(&request.method);
+                        self.latencies.record(elapsed);
+                        if request.method == "eth_blockNumber" {
+                            if let Some(height) = response.result.as_ref().and_then(block_height) {
+                                self.head_lag.record(index, height);
+                            }
+                        }
+                        answer = Some(response);
+                    }
+                    Err(e) => {
+                        // Every endpoint would send the same oversized answer
+                        if is_size_limit_error(&e) {
+                            return Err(e);
+                        }
+
+                        // Record failure; the endpoint only counts it when it is
+                        // not confined to this method. An oversized log query
+                        // says nothing about the endpoint's health.
+                        // An endpoint that said when to come back is held for
+                        // that long instead.
+                        if !is_log_range_error(&request.method, &e)
+                            && !is_retry_after_error(&e)
+                            && self.method_breakers[index].record_failure(&request.method)
+                        {
+                            self.circuit_breakers[index].record_failure();
+                        }
+
+                        warn!(
+                            "RPC call to {} failed (attempt {}/{}): {}",
+                            self.config.endpoints[index], attempts, self.config.max_retries, e
+                        );
+                        last_error = Some(e);
+                    }
+                }
+            }
 
-                    // Cache successful response if it has a result
-                    if let Some(ref result) = response.result {
-                        self.cache_response(&cache_key, &request.method, result)
+            if let Some(response) = answer {
+                // Not-found answers are cached briefly, the rest as usual
+                let negative = &self.config.negative_cache;
+                if negative.is_negative_result(&request.method, response.result.as_ref()) {
+                    let answer = NegativeAnswer::Result(response.result.clone());
+                    self.cache_negative(negative_key, request, &answer).await?;
+                } else if let Some(ref result) = response.result {
+                    self.cache_response(cache_key, request, result).await?;
+                    if let Some(budget) = self.config.swr.budget(&request.method) {
+                        self.cache
+                            .set(&stale_key(cache_key), result, budget)
                             .await?;
                     }
-
-                    return Ok(response);
                 }
-                Err(e) => {
-                    // Record failure
-                    circuit_breaker.record_failure();
 
-                    warn!(
-                        "RPC call to {} failed (attempt {}/{}): {}",
-                        endpoint, attempts, self.config.max_retries, e
-                    );
-                    last_error = Some(e);
+                return Ok(response);
+            }
 
-                    // Small delay before retry
-                    if attempts < self.config.max_retries {
-                        tokio::time::sleep(Duration::from_millis(100 * attempts as u64)).await;
-                    }
-                }
+            // Small delay before retry; a held endpoint is simply skipped
+            let held = last_error.as_ref().is_some_and(is_retry_after_error);
+            if attempts < self.config.max_retries && !held {
+                tokio::time::sleep(Duration::from_millis(100 * attempts as u64)).await;
             }
         }
 
-        Err(last_error.unwrap_or_else(|| anyhow!("All RPC attempts failed")))
+        let error = last_error.unwrap_or_else(|| anyhow!("All RPC attempts failed"));
+        if self
+            .config
+            .negative_cache
+            .is_negative_error(&error.to_string())
+        {
+            let answer = NegativeAnswer::Error(error.to_string());
+            self.cache_negative(negative_key, request, &answer).await?;
+        }
+        Err(error)
     }
 
-    /// Make a single RPC request to an endpoint
-    async fn make_request(&self, endpoint: &str, request: &RpcRequest) -> Result<RpcResponse> {
-        let response = self
-            .client
-            .post(endpoint)
-            .header("Content-Type", "application/json")
-            .json(request)
-            .send()
-            .await
-            .map_err(|e| anyhow!("HTTP request failed: {}", e))?;
+    /// Stale copy of the call cached under `key`, within its method's
+    /// staleness budget; a background refresh is requested when one is served
+    async fn stale_answer(&self, key: &str, request: &RpcRequest) -> Result<Option<Value>> {
+        if self.config.swr.budget(&request.method).is_none() {
+            return Ok(None);
+        }
+        let Some(value) = self.cache.get(&stale_key(key)).await? else {
+            return Ok(None);
+        };
 
-        if !response.status().is_success() {
-            return Err(anyhow!("HTTP error: status {}", response.status()));
+        if self.revalidating.lock().insert(key.to_string()) {
+            let sent = self.config.revalidations.as_ref().is_some_and(|sender| {
+                sender
+                    .send(Revalidation {
+                        network: self.network.clone(),
+                        request: request.clone(),
+                    })
+                    .is_ok()
+            });
+            if !sent {
+                // Nothing refreshes it; a later stale read asks again
+                self.revalidating.lock().remove(key);
+            }
         }
+        Ok(Some(value))
+    }
 
-        let rpc_response: RpcResponse = response
-            .json()
-            .await
-            .map_err(|e| anyhow!("Failed to parse RPC response: {}", e))?;
+    /// Refresh a call answered stale, bypassing the cache
+    pub async fn revalidate(&self, request: &RpcRequest) {
+        let cache_key = self
+            .cache
+            .make_key(&self.network, &request.method, &request.params);
+        let outcome = self
+            .fetch_with_failover(request, &cache_key, &negative_key(&cache_key))
+            .await;
+        self.revalidating.lock().remove(&cache_key);
+        if let Err(e) = outcome {
+            debug!(
+                "Refreshing stale {} for {} failed: {}",
+                request.method, self.network, e
+            );
+        }
+    }
 
-        if let Some(error) = &rpc_response.error {
-            return Err(anyhow!("RPC error {}: {}", error.code, error.message));
+    /// Cached failing answer under `key`, if negative caching is on
+    async fn negative_answer(&self, key: &str) -> Result<Option<NegativeAnswer>> {
+        if !self.config.negative_cache.enabled {
+            return Ok(None);
         }
+        Ok(self
+            .cache
+            .get(key)
+            .await?
+            .as_ref()
+            .and_then(NegativeAnswer::from_value))
+    }
 
-        Ok(rpc_response)
+    /// Cache a failing answer for the negative-cache TTL
+    async fn cache_negative(
+        &self,
+        key: &str,
+        request: &RpcRequest,
+        answer: &NegativeAnswer,
+    ) -> Result<()> {
+        if is_head_dependent(&request.method, &request.params) {
+            self.cache.track_latest(&self.network, key).await?;
+        }
+        let ttl = Duration::from_secs(self.config.negative_cache.ttl_secs);
+        self.cache.set(key, &answer.to_value(), ttl).await
     }
 
-    /// Cache response with appropriate TTL based on method and network
-    async fn cache_response(&self, key: &str, method: &str, value: &Value) -> Result<()> {
-        // Avalanche has 2s blocks vs Ethereum's 12s - adjust TTLs accordingly
-        if self.network == "avalanche" || self.network == "avalanche-fuji" {
-            // Avalanche-specific caching strategy (2-second block times)
-            let ttl = if method.contains("blockNumber") {
-                Duration::from_secs(2) // Cache for 1 block
-            } else if method.contains("getBalance") || method.contains("call") {
-                Duration::from_secs(10) // Cache for 5 blocks
+    /// Send `request` to `primary`, hedging to a second endpoint when it has not
+    /// answered within the hedge delay
+    ///
+    /// Returns the finished attempts as (endpoint, result, latency). The first
+    /// success wins and the slower request is dropped, which cancels it; when one
+    /// attempt fails the other is awaited.
+    async fn send_with_hedge(
+        &self,
+        primary: usize,
+        request: &RpcRequest,
+        split: Option<&TrafficSplit>,
+        arm: Arm,
+    ) -> Vec<(usize, Result<RpcResponse>, Duration)> {
+        let started = Instant::now();
+        let first = self.make_request(&self.config.endpoints[primary], request);
+        let Some(delay) = self.config.hedge.delay(&request.method, &self.latencies) else {
+            return vec![(primary, first.await, started.elapsed())];
+        };
+        tokio::pin!(first);
+
+        tokio::select! {
+            result = &mut first => return vec![(primary, result, started.elapsed())],
+            _ = tokio::time::sleep(delay) => {}
+        }
+        let archive = self.needs_archive(&request.method, &request.params);
+        let Some(hedge) = self.hedge_endpoint(primary, &request.method, split, arm, archive) else {
+            return vec![(primary, first.await, started.elapsed())];
+        };
+
+        debug!(
+            "{} on {} unanswered after {:?}, hedging to {}",
+            request.method,
+            self.cost_meters[primary].endpoint(),
+            delay,
+            self.cost_meters[hedge].endpoint()
+        );
+        self.charge(hedge, &request.method);
+        self.rate_limiters[hedge].acquire(1);
+        let hedge_started = Instant::now();
+        let second = self.make_request(&self.config.endpoints[hedge], request);
+        tokio::pin!(second);
+
+        tokio::select! {
+            result = &mut first => {
+                let outcome = (primary, result, started.elapsed());
+                if outcome.1.is_ok() {
+                    return vec![outcome];
+                }
+                vec![outcome, (hedge, second.await, hedge_started.elapsed())]
+            }
+            result = &mut second => {
+                let outcome = (hedge, result, hedge_started.elapsed());
+                if outcome.1.is_ok() {
+                    return vec![outcome];
+                }
+                vec![outcome, (primary, first.await, started.elapsed())]
+            }
+        }
+    }
+
+    /// Endpoint a consistent session reading at `block` (the head when unset)
+    /// is pinned to, picked as for a single call
+    pub(crate) async fn session_endpoint(&self, block: Option<u64>) -> Result<usize> {
+        let split = self.split.read().clone();
+        let arm = split
+            .as_deref()
+            .map_or(Arm::Primary, TrafficSplit::choose_arm);
+        let archive = block.is_some_and(|block| self.needs_archive_at(block));
+        let (index, _) = self
+            .wait_for_endpoint("eth_blockNumber", split.as_deref(), arm, archive)
+            .await?;
+        Ok(index)
+    }
+
+    /// Send `request` to endpoint `index` only, bypassing the cache and failover
+    ///
+    /// The call is charged, rate limited and scored like any other.
+    pub(crate) async fn call_pinned(
+        &self,
+        index: usize,
+        request: &RpcRequest,
+    ) -> Result<RpcResponse> {
+        if let Some(replayed) = self.replayed(request) {
+            return replayed;
+        }
+        self.charge(index, &request.method);
+        self.rate_limiters[index].acquire(1);
+        let started = Instant::now();
+        let result = self
+            .make_request(&self.config.endpoints[index], request)
+            .await;
+        self.endpoint_stats[index].record(result.is_ok(), Some(started.elapsed()));
+        match &result {
+            Ok(_) => {
+                self.circuit_breakers[index].record_success();
+                self.method_breakers[index].record_success(&request.method);
+            }
+            Err(e) => {
+                let message = e.to_string();
+                if !is_log_range_error(&request.method, e)
+                    && !is_size_limit_error(e)
+                    && !is_unsupported_method(&message)
+                    && !is_retry_after_error(e)
+                    && self.method_breakers[index].record_failure(&request.method)
+                {
+                    self.circuit_breakers[index].record_failure();
+                }
+            }
+        }
+        result
+    }
+
+    /// Traces of `target` from the first healthy endpoint with a trace API
+    ///
+    /// Each endpoint is asked in the flavor it has answered in before, else
+    /// geth's then parity's; a flavor it lacks is remembered and not asked
+    /// again. Endpoints with neither are skipped, and a failed call moves on to
+    /// the next endpoint.
+    pub async fn trace(&self, target: &TraceTarget) -> Result<Vec<Trace>> {
+        let method = target.request(TraceFlavor::Geth).method;
+        let _permit = self.queue.acquire(Priority::Realtime).await;
+        let mut tried = Vec::new();
+        let mut last_error = None;
+
+        while tried.len() < self.config.max_retries as usize {
+            let Some((index, _)) =
+                self.next_healthy_endpoint(Some(&method), |i| !tried.contains(&i))
+            else {
+                break;
+            };
+            tried.push(index);
+
+            for flavor in self.trace_support[index].candidates() {
+                match self.call_pinned(index, &target.request(flavor)).await {
+                    Ok(response) => {
+                        self.trace_support[index].record(flavor, true);
+                        return target.normalize(flavor, response.result.unwrap_or(Value::Null));
+                    }
+                    Err(e) if is_unsupported_method(&e.to_string()) => {
+                        debug!(
+                            "{} has no {:?} trace API: {}",
+                            self.config.endpoints[index], flavor, e
+                        );
+                        self.trace_support[index].record(flavor, false);
+                        last_error = Some(e);
+                    }
+                    Err(e) => {
+                        warn!("Trace failed on {}: {}", self.config.endpoints[index], e);
+                        last_error = Some(e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| anyhow!("No endpoint with a trace API for {}", self.network)))
+    }
+
+    /// Every receipt of `block` (a number, tag or hash), in transaction order
+    ///
+    /// Healthy endpoints not known to lack `eth_getBlockReceipts` are asked it
+    /// in turn; when none answers it, the block's transaction hashes are
+    /// fetched and their receipts asked in one batch.
+    pub async fn block_receipts(&self, block: &str) -> Result<Vec<Value>> {
+        let request = block_receipts_request(block);
+        {
+            let _permit = self.queue.acquire(Priority::Realtime).await;
+            let mut tried = Vec::new();
+            while tried.len() < self.config.max_retries as usize {
+                let Some((index, _)) = self.next_healthy_endpoint(Some(BLOCK_RECEIPTS), |i| {
+                    !tried.contains(&i) && self.receipt_support[i].may_support()
+                }) else {
+                    break;
+                };
+                tried.push(index);
+
+                let endpoint = &self.config.endpoints[index];
+                match self.call_pinned(index, &request).await {
+                    Ok(response) => {
+                        self.receipt_support[index].record(true);
+                        return into_receipts(block, response.result);
+                    }
+                    Err(e) if is_unsupported_method(&e.to_string()) => {
+                        debug!("{} has no {}: {}", endpoint, BLOCK_RECEIPTS, e);
+                        self.receipt_support[index].record(false);
+                    }
+                    Err(e) => warn!("{} failed on {}: {}", BLOCK_RECEIPTS, endpoint, e),
+                }
+            }
+        }
+
+        debug!(
+            "Fetching receipts of block {} per transaction on {}",
+            block, self.network
+        );
+        let answer = self
+            .call_with_failover(&block_request(block, false))
+            .await?;
+        let requests = receipt_requests(block, answer.result)?;
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+        let batch = self
+            .execute_batch(RpcBatchRequest {
+                requests: requests.clone(),
+            })
+            .await?;
+        batch_receipts(&requests, batch)
+    }
+
+    /// Healthy endpoint other than `primary` for a hedge of `method`,
+    /// preferring `arm`; only archive endpoints for `archive` calls
+    fn hedge_endpoint(
+        &self,
+        primary: usize,
+        method: &str,
+        split: Option<&TrafficSplit>,
+        arm: Arm,
+        archive: bool,
+    ) -> Option<usize> {
+        let in_arm = |i: usize| split.is_none_or(|split| split.arm_of(i) == arm);
+        let allowed = |i: usize| i != primary && (!archive || self.archive[i]);
+        self.next_healthy_endpoint(Some(method), |i| allowed(i) && in_arm(i))
+            .or_else(|| self.next_healthy_endpoint(Some(method), allowed))
+            .map(|(index, _)| index)
+    }
+
+    /// Send a batch of calls with failover, answering in request order
+    ///
+    /// Cached calls are answered from the cache; the rest go out as payloads no
+    /// larger than the chosen endpoint's max batch size. A payload that fails
+    /// (transport error, or the endpoint rejects batches) is retried on the next
+    /// endpoint. Per-call RPC errors are returned in their response, not retried.
+    pub async fn execute_batch(&self, batch: RpcBatchRequest) -> Result<RpcBatchResponse> {
+        let mut responses: Vec<Option<RpcResponse>> = vec![None; batch.len()];
+        let mut pending: Vec<(usize, String)> = Vec::new();
+        for (index, request) in batch.requests.iter().enumerate() {
+            if let Some(replayed) = self.replayed_in_batch(request) {
+                responses[index] = Some(replayed);
+                continue;
+            }
+            let cache_key = self
+                .cache
+                .make_key(&self.network, &request.method, &request.params);
+            match self.cache.get(&cache_key).await? {
+                Some(cached_value) => {
+                    responses[index] = Some(RpcResponse {
+                        jsonrpc: "2.0".to_string(),
+                        result: Some(cached_value),
+                        error: None,
+                        id: request.id,
+                    });
+                }
+                None => pending.push((index, cache_key)),
+            }
+        }
+        debug!(
+            "Batch of {} for {}: {} cached, {} to send",
+            batch.len(),
+            self.network,
+            batch.len() - pending.len(),
+            pending.len()
+        );
+
+        // The payloads share one slot, at the most urgent call's priority
+        let priority = pending
+            .iter()
+            .map(|(index, _)| batch.requests[*index].priority)
+            .min();
+        let _permit = match priority {
+            Some(priority) => Some(self.queue.acquire(priority).await),
+            None => None,
+        };
+
+        let split = self.split.read().clone();
+        let arm = split
+            .as_deref()
+            .map_or(Arm::Primary, TrafficSplit::choose_arm);
+        let mut attempts = 0;
+
+        while !pending.is_empty() {
+            let method = &batch.requests[pending[0].0].method;
+            // One payload goes to one endpoint, so one old-block call sends it to
+            // an archive endpoint
+            let archive = pending.iter().any(|(index, _)| {
+                let request = &batch.requests[*index];
+                self.needs_archive(&request.method, &request.params)
+            });
+            let (endpoint_idx, circuit_breaker) = self
+                .wait_for_endpoint(method, split.as_deref(), arm, archive)
+                .await?;
+            let endpoint = &self.config.endpoints[endpoint_idx];
+            let size = self
+                .config
+                .batch
+                .max_batch_size(endpoint)
+                .min(pending.len());
+
+            // Wire ids are positions in the payload, so callers' ids may repeat
+            let payload: Vec<RpcRequest> = pending[..size]
+                .iter()
+                .enumerate()
+                .map(|(wire_id, (index, _))| RpcRequest {
+                    id: wire_id as u64,
+                    ..batch.requests[*index].clone()
+                })
+                .collect();
+            for request in &payload {
+                self.charge(endpoint_idx, &request.method);
+            }
+            self.rate_limiters[endpoint_idx].acquire(payload.len());
+
+            let started = Instant::now();
+            let result = self.make_batch_request(endpoint, &payload).await;
+            if let Some(split) = &split {
+                split.record(endpoint_idx, result.is_ok(), started.elapsed());
+            }
+            // A payload's duration says little about single-call latency
+            self.endpoint_stats[endpoint_idx].record(result.is_ok(), None);
+
+            match result {
+                Ok(payload_responses) => {
+                    circuit_breaker.record_success();
+                    for ((index, cache_key), mut response) in
+                        pending.drain(..size).zip(payload_responses)
+                    {
+                        let request = &batch.requests[index];
+                        if response.error.is_none() {
+                            if let Some(ref result) = response.result {
+                                self.cache_response(&cache_key, request, result).await?;
+                            }
+                        }
+                        response.id = request.id;
+                        responses[index] = Some(response);
+                    }
+                    attempts = 0;
+                }
+                Err(e) if is_size_limit_error(&e) => return Err(e),
+                Err(e) => {
+                    if !is_retry_after_error(&e) {
+                        circuit_breaker.record_failure();
+                    }
+                    attempts += 1;
+
+                    warn!(
+                        "Batch of {} to {} failed (attempt {}/{}): {}",
+                        size, endpoint, attempts, self.config.max_retries, e
+                    );
+                    if attempts >= self.config.max_retries {
+                        return Err(e);
+                    }
+                    tokio::time::sleep(Duration::from_millis(100 * attempts as u64)).await;
+                }
+            }
+        }
+
+        Ok(RpcBatchResponse {
+            responses: responses.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Apply a new head: drop the network's head-dependent cache entries and
+    /// re-seed `eth_blockNumber`; returns how many entries were dropped
+    pub async fn on_new_head(&self, block_number: u64) -> Result<usize> {
+        let dropped = self.cache.invalidate_latest(&self.network).await?;
+
+        let request = RpcRequest::new("eth_blockNumber", vec![]);
+        let key = self
+            .cache
+            .make_key(&self.network, &request.method, &request.params);
+        let head = Value::String(format!("{:#x}", block_number));
+        self.cache_response(&key, &request, &head).await?;
+
+        debug!(
+            "Head #{} on {}: dropped {} cached entries",
+            block_number, self.network, dropped
+        );
+        Ok(dropped)
+    }
+
+    /// Check every endpoint's `eth_chainId` against the expected chain id,
+    /// quarantining mismatches; returns how many endpoints are quarantined
+    pub async fn verify_chain_ids(&self) -> usize {
+        let Some(expected) = self.config.expected_chain_id else {
+            return 0;
+        };
+        if self.replaying() {
+            return 0;
+        }
+        let request = RpcRequest::new("eth_chainId", vec![]);
+        let answers =
+            futures_util::future::join_all(self.config.endpoints.iter().map(|endpoint| async {
+                self.make_request(endpoint, &request)
+                    .await
+                    .map(|response| response.result.unwrap_or(Value::Null))
+                    .map_err(|e| e.to_string())
+            }))
+            .await;
+
+        let mut quarantined = 0;
+        for (index, answer) in answers.into_iter().enumerate() {
+            self.charge(index, &request.method);
+            self.rate_limiters[index].acquire(1);
+            if self.chain_id_guards[index].record(answer) {
+                quarantined += 1;
+            }
+        }
+        if quarantined > 0 {
+            warn!(
+                "{}/{} endpoints for {} quarantined (expected chain id {})",
+                quarantined,
+                self.config.endpoints.len(),
+                self.network,
+                expected
+            );
+        }
+        quarantined
+    }
+
+    /// Run one canary round against every endpoint, bypassing the cache
+    ///
+    /// Probes go out even while a circuit is open, so a broken endpoint's score
+    /// keeps updating; the verdict only reaches a breaker that admits requests.
+    pub async fn run_canary(&self, config: &CanaryConfig) {
+        let Some(canary) = config.networks.get(&self.network) else {
+            return;
+        };
+        if self.replaying() {
+            return;
+        }
+        let requests = canary.requests();
+
+        let mut answers = Vec::with_capacity(self.config.endpoints.len());
+        for (index, endpoint) in self.config.endpoints.iter().enumerate() {
+            let mut endpoint_answers = Vec::with_capacity(requests.len());
+            for (kind, request) in &requests {
+                self.charge(index, &request.method);
+                self.rate_limiters[index].acquire(1);
+                let started = Instant::now();
+                let result = self
+                    .make_request(endpoint, request)
+                    .await
+                    .map(|response| response.result.unwrap_or(Value::Null))
+                    .map_err(|e| e.to_string());
+                let latency = started.elapsed();
+                self.endpoint_stats[index].record(result.is_ok(), Some(latency));
+                endpoint_answers.push(ProbeAnswer {
+                    kind: *kind,
+                    latency,
+                    result,
+                });
+            }
+            answers.push(endpoint_answers);
+        }
+
+        self.apply_canary_round(config, &answers);
+    }
+
+    /// Score a round of canary answers (one list per endpoint) and feed the
+    /// verdicts to the circuit breakers
+    fn apply_canary_round(&self, config: &CanaryConfig, answers: &[Vec<ProbeAnswer>]) {
+        let Some(canary) = config.networks.get(&self.network) else {
+            return;
+        };
+        let round_head = answers.iter().flatten().filter_map(ProbeAnswer::head).max();
+
+        for (index, endpoint_answers) in answers.iter().enumerate() {
+            if let Some(height) = endpoint_answers.iter().find_map(ProbeAnswer::head) {
+                self.head_lag.record(index, height);
+            }
+            let error =
+                self.canaries[index].record_round(canary, endpoint_answers, round_head, config);
+            let circuit_breaker = &self.circuit_breakers[index];
+            if circuit_breaker.can_execute().is_err() {
+                continue;
+            }
+            match error {
+                None => circuit_breaker.record_success(),
+                Some(error) => {
+                    warn!(
+                        "Canary probe failed for {} on {}: {}",
+                        self.network,
+                        self.cost_meters[index].endpoint(),
+                        error
+                    );
+                    circuit_breaker.record_failure();
+                }
+            }
+        }
+    }
+
+    /// Make a single RPC request to an endpoint
+    async fn make_request(&self, endpoint: &str, request: &RpcRequest) -> Result<RpcResponse> {
+        let limits = &self.config.size_limit;
+        let payload = serde_json::to_vec(request)?;
+        limits.check_request(&request.method, payload.len())?;
+        let _slot = self.in_flight_slot(endpoint).await;
+        let started = Instant::now();
+        let response = self
+            .authorize(endpoint, self.client_for(endpoint).post(endpoint), &payload)
+            .header("Content-Type", "application/json")
+            .body(payload)
+            .send()
+            .await
+            .map_err(|e| anyhow!("HTTP request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(self.http_error(endpoint, response).await);
+        }
+
+        let limit = limits.response_limit(&request.method);
+        let body = read_body(response, &request.method, limit).await?;
+        let rpc_response = self
+            .config
+            .validation
+            .parse_response(&request.method, request.id, &body)
+            .map_err(|invalid| self.invalid_response(endpoint, invalid))?;
+        if let Some(recorder) = self.recorder() {
+            recorder
+                .record(
+                    &self.network,
+                    endpoint,
+                    request,
+                    &rpc_response,
+                    started.elapsed(),
+                )
+                .await;
+        }
+
+        if let Some(error) = &rpc_response.error {
+            let message = format!("RPC error {}: {}", error.code, error.message);
+            if is_rate_limit_error(&error.message) {
+                let retry_after = retry_after_hint(&error.message, error.data.as_ref());
+                if let Some(wait) = self.record_rate_limit(endpoint, retry_after) {
+                    return Err(RetryAfter { message, wait }.into());
+                }
+            }
+            if request.method == "eth_getLogs" && is_range_error(&error.message) {
+                return Err(RangeError { message }.into());
+            }
+            return Err(anyhow!(message));
+        }
+
+        Ok(rpc_response)
+    }
+
+    /// Send one batch payload to an endpoint, matching answers back by wire id
+    async fn make_batch_request(
+        &self,
+        endpoint: &str,
+        payload: &[RpcRequest],
+    ) -> Result<Vec<RpcResponse>> {
+        let limits = &self.config.size_limit;
+        let what = format!("batch of {}", payload.len());
+        let body = serde_json::to_vec(payload)?;
+        limits.check_request(&what, body.len())?;
+        let _slot = self.in_flight_slot(endpoint).await;
+        let started = Instant::now();
+        let response = self
+            .authorize(endpoint, self.client_for(endpoint).post(endpoint), &body)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("HTTP request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(self.http_error(endpoint, response).await);
+        }
+
+        let limit = limits.batch_limit(payload.iter().map(|request| request.method.as_str()));
+        let bytes = read_body(response, &what, limit).await?;
+        let validation = &self.config.validation;
+        let body: Value = match serde_json::from_slice(&bytes) {
+            Ok(body) => body,
+            Err(e) if !validation.enabled => {
+                return Err(anyhow!("Failed to parse batch response: {}", e))
+            }
+            Err(_) => {
+                let invalid = validation.invalid_body(&String::from_utf8_lossy(&bytes));
+                return Err(self.invalid_response(endpoint, invalid));
+            }
+        };
+        validation
+            .check_batch(payload, &body)
+            .map_err(|invalid| self.invalid_response(endpoint, invalid))?;
+
+        let ids: Vec<u64> = payload.iter().map(|request| request.id).collect();
+        let responses = split_responses(&ids, body)?;
+        if let Some(recorder) = self.recorder() {
+            let elapsed = started.elapsed();
+            for (request, response) in payload.iter().zip(&responses) {
+                recorder
+                    .record(&self.network, endpoint, request, response, elapsed)
+                    .await;
+            }
+        }
+        Ok(responses)
+    }
+
+    /// Whether calls are answered from recordings
+    fn replaying(&self) -> bool {
+        self.config
+            .recorder
+            .as_ref()
+            .is_some_and(|recorder| recorder.replaying())
+    }
+
+    /// Recorder upstream answers are kept by, if any
+    fn recorder(&self) -> Option<&Recorder> {
+        self.config
+            .recorder
+            .as_deref()
+            .filter(|recorder| recorder.recording())
+    }
+
+    /// Recorded answer to `request` while replaying, an RPC error as `Err`;
+    /// `None` sends it upstream
+    fn replayed(&self, request: &RpcRequest) -> Option<Result<RpcResponse>> {
+        let recorder = self.config.recorder.as_ref()?;
+        if !recorder.replaying() {
+            return None;
+        }
+        match recorder.replay(&self.network, request) {
+            Ok(Some(response)) => Some(match &response.error {
+                Some(error) => Err(anyhow!("RPC error {}: {}", error.code, error.message)),
+                None => Ok(response),
+            }),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Recorded answer to a batched `request` while replaying; a call that was
+    /// not recorded gets an error answer of its own
+    fn replayed_in_batch(&self, request: &RpcRequest) -> Option<RpcResponse> {
+        let recorder = self.config.recorder.as_ref()?;
+        if !recorder.replaying() {
+            return None;
+        }
+        match recorder.replay(&self.network, request) {
+            Ok(response) => response,
+            Err(e) => Some(RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: None,
+                error: Some(RpcError {
+                    code: -32000,
+                    message: e.to_string(),
+                    data: None,
+                }),
+                id: request.id,
+            }),
+        }
+    }
+
+    /// Error for an answer that failed validation
+    fn invalid_response(&self, endpoint: &str, invalid: InvalidResponse) -> anyhow::Error {
+        if invalid.rate_limited {
+            self.record_rate_limit(endpoint, None);
+        }
+        anyhow!(
+            "Invalid RPC response from {}: {}",
+            endpoint_host(endpoint),
+            invalid
+        )
+    }
+
+    /// Client for `endpoint`
+    fn client_for(&self, endpoint: &str) -> &HttpClient {
+        let index = self
+            .config
+            .endpoints
+            .iter()
+            .position(|e| e == endpoint)
+            .unwrap_or(0);
+        &self.clients[index]
+    }
+
+    /// Hold one of `endpoint`'s in-flight slots, waiting for one to free up
+    async fn in_flight_slot(&self, endpoint: &str) -> Option<OwnedSemaphorePermit> {
+        let index = self.config.endpoints.iter().position(|e| e == endpoint)?;
+        self.in_flight[index].acquire().await
+    }
+
+    /// Add `endpoint`'s credentials to a request sending `body`
+    fn authorize(
+        &self,
+        endpoint: &str,
+        request: reqwest::RequestBuilder,
+        body: &[u8],
+    ) -> reqwest::RequestBuilder {
+        match self.config.endpoints.iter().position(|e| e == endpoint) {
+            Some(index) => self.credentials[index].apply(request, body),
+            None => request,
+        }
+    }
+
+    /// Error for a non-2xx answer; a 429 Too Many Requests empties the
+    /// endpoint's bucket and holds it for as long as the answer asks
+    async fn http_error(&self, endpoint: &str, response: reqwest::Response) -> anyhow::Error {
+        let status = response.status();
+        let message = format!("HTTP error: status {}", status);
+        if status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return anyhow!(message);
+        }
+
+        let retry_after = match retry_after_header(response.headers(), SystemTime::now()) {
+            Some(wait) => Some(wait),
+            None => read_body(response, "rate limit answer", RATE_LIMIT_BODY_LIMIT)
+                .await
+                .ok()
+                .and_then(|body| serde_json::from_slice::<Value>(&body).ok())
+                .and_then(|body| retry_after_in_body(&body)),
+        };
+        match self.record_rate_limit(endpoint, retry_after) {
+            Some(wait) => RetryAfter { message, wait }.into(),
+            None => anyhow!(message),
+        }
+    }
+
+    /// Empty an endpoint's bucket after it rate limited a call, and hold it
+    /// for `retry_after` when it said; returns the hold applied
+    fn record_rate_limit(&self, endpoint: &str, retry_after: Option<Duration>) -> Option<Duration> {
+        let index = self.config.endpoints.iter().position(|e| e == endpoint)?;
+        self.rate_limiters[index].record_rejection();
+        let hold = self.config.rate_limit.hold_for(retry_after);
+        match hold {
+            Some(wait) => {
+                warn!(
+                    "{} rate limited {} requests, holding it for {:?}",
+                    endpoint_host(endpoint),
+                    self.network,
+                    wait
+                );
+                self.rate_limiters[index].hold(wait);
+            }
+            None => warn!(
+                "{} rate limited {} requests",
+                endpoint_host(endpoint),
+                self.network
+            ),
+        }
+        hold
+    }
+
+    /// Cache response with appropriate TTL based on method and network
+    ///
+    /// Head-dependent answers are also tracked so the next block drops them.
+    async fn cache_response(&self, key: &str, request: &RpcRequest, value: &Value) -> Result<()> {
+        let method = request.method.as_str();
+        if is_head_dependent(method, &request.params) {
+            self.cache.track_latest(&self.network, key).await?;
+        }
+
+        // Operator-configured per-method TTLs win over the built-in strategy
+        if let Some(ttl) = self.cache.method_ttl(method) {
+            return self.cache.set_with_ttl(key, value, ttl).await;
+        }
+
+        // Solana answers live as long as their commitment level allows
+        let solana = &self.config.solana;
+        if solana.is_solana(&self.network) {
+            return match solana.cache_ttl(method, &request.params) {
+                Some(ttl) => self.cache.set_with_ttl(key, value, ttl).await,
+                None => self.cache.set_default(key, value).await,
+            };
+        }
+
+        // Avalanche has 2s blocks vs Ethereum's 12s - adjust TTLs accordingly
+        if self.network == "avalanche" || self.network == "avalanche-fuji" {
+            // Avalanche-specific caching strategy (2-second block times)
+            let ttl = if method.contains("blockNumber") {
+                Duration::from_secs(2) // Cache for 1 block
+            } else if method.contains("getBalance") || method.contains("call") {
+                Duration::from_secs(10) // Cache for 5 blocks
             } else if method.contains("getBlockBy") || method.contains("getBlock") {
                 Duration::from_secs(60) // Finalized blocks - 30 blocks
             } else if method.contains("getTransaction") || method.contains("Transaction") {
@@ -328,15 +2026,81 @@ impl EndpointPool {
             }
         }
 
+        let endpoints: Vec<EndpointHealth> = (0..self.circuit_breakers.len())
+            .map(|index| EndpointHealth {
+                endpoint: self.cost_meters[index].endpoint().to_string(),
+                circuit: self.circuit_breakers[index].state(),
+                latest_block: self.head_lag.height(index),
+                lag_blocks: self.head_lag.lag(index),
+                degraded: self.head_lag.is_degraded(index),
+                open_methods: self.method_breakers[index].open_methods(),
+                paused: self.paused[index].load(Ordering::Relaxed),
+                archive: self.archive[index],
+                trace_flavor: self.trace_support[index].flavor(),
+                block_receipts: self.receipt_support[index].known(),
+                in_flight: self.in_flight[index].in_flight(),
+                fallback: index >= self.primary_count,
+            })
+            .collect();
+
         PoolHealthStatus {
             network: self.network.clone(),
             total_endpoints: self.circuit_breakers.len(),
             healthy_endpoints: healthy,
             unhealthy_endpoints: unhealthy,
             half_open_endpoints: half_open,
+            degraded_endpoints: endpoints.iter().filter(|e| e.degraded).count(),
+            median_block: self.head_lag.median(),
+            queue: self.queue.depth(),
+            endpoints,
         }
     }
 
+    /// Spend per endpoint
+    pub fn cost_status(&self) -> Vec<EndpointCostStatus> {
+        self.cost_meters.iter().map(CostMeter::status).collect()
+    }
+
+    /// Credential state of the endpoints with auth
+    pub fn auth_status(&self) -> Vec<AuthStatus> {
+        self.credentials
+            .iter()
+            .filter_map(Credentials::status)
+            .collect()
+    }
+
+    /// Token bucket state per endpoint
+    pub fn rate_limit_status(&self) -> Vec<RateLimitStatus> {
+        self.rate_limiters.iter().map(RateLimiter::status).collect()
+    }
+
+    /// Latency and health scores per endpoint, as the selection strategy sees them
+    pub fn selection_status(&self) -> Vec<EndpointScore> {
+        let unmeasured_ms = unmeasured_latency_ms(&self.endpoint_stats);
+        self.endpoint_stats
+            .iter()
+            .map(|stats| stats.status(unmeasured_ms))
+            .collect()
+    }
+
+    /// Chain id verdict per checked endpoint
+    pub fn chain_id_status(&self) -> Vec<ChainIdStatus> {
+        self.chain_id_guards
+            .iter()
+            .filter_map(ChainIdGuard::status)
+            .collect()
+    }
+
+    /// Canary score per endpoint
+    pub fn canary_status(&self, config: &CanaryConfig) -> Vec<CanaryStatus> {
+        self.canaries.iter().map(|c| c.status(config)).collect()
+    }
+
+    /// Per-arm metrics of the active split
+    pub fn split_status(&self) -> Option<SplitStatus> {
+        self.split.read().as_ref().map(|split| split.status())
+    }
+
     /// Get reference to cache (for testing)
     pub fn cache(&self) -> &Arc<RpcCache> {
         &self.cache
@@ -344,13 +2108,56 @@ impl EndpointPool {
 }
 
 /// Pool health status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PoolHealthStatus {
     pub network: String,
     pub total_endpoints: usize,
     pub healthy_endpoints: usize,
     pub unhealthy_endpoints: usize,
     pub half_open_endpoints: usize,
+    /// Endpoints lagging the median block height, left out of selection
+    #[serde(default)]
+    pub degraded_endpoints: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_block: Option<u64>,
+    /// Upstream calls in flight and waiting per priority class
+    #[serde(default)]
+    pub queue: QueueDepth,
+    #[serde(default)]
+    pub endpoints: Vec<EndpointHealth>,
+}
+
+/// Block height of one endpoint against the pool median
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub endpoint: String,
+    #[serde(default)]
+    pub circuit: CircuitState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_block: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lag_blocks: Option<u64>,
+    pub degraded: bool,
+    /// Methods whose per-method circuit is open or half-open
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub open_methods: Vec<String>,
+    #[serde(default)]
+    pub paused: bool,
+    /// Tagged as an archive node
+    #[serde(default)]
+    pub archive: bool,
+    /// Trace API the endpoint has answered in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_flavor: Option<TraceFlavor>,
+    /// Whether the endpoint has `eth_getBlockReceipts`, once known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_receipts: Option<bool>,
+    /// Requests in flight, for endpoints with a `max_in_flight` limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_flight: Option<usize>,
+    /// In the fallback tier, serving only while every primary circuit is open
+    #[serde(default)]
+    pub fallback: bool,
 }
 
 impl PoolHealthStatus {
@@ -367,9 +2174,19 @@ impl PoolHealthStatus {
     }
 }
 
+/// Copy of a call's answer handed to identical calls waiting on it
+fn shared_answer(answer: &Result<RpcResponse>) -> std::result::Result<RpcResponse, String> {
+    match answer {
+        Ok(response) => Ok(response.clone()),
+        Err(e) => Err(format!("{:#}", e)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::canary::ProbeKind;
+    use crate::egress::EndpointEgress;
 
     #[test]
     fn test_pool_config_default() {
@@ -414,31 +2231,1487 @@ mod tests {
         assert!(result.is_err());
     }
 
-    #[test]
-    fn test_health_status_percentage() {
-        let status = PoolHealthStatus {
-            network: "ethereum".to_string(),
-            total_endpoints: 4,
-            healthy_endpoints: 3,
-            unhealthy_endpoints: 1,
-            half_open_endpoints: 0,
+    #[tokio::test]
+    async fn test_near_budget_endpoint_shifts_to_cheapest_with_headroom() {
+        let mut cost = CostConfig::default();
+        cost.endpoints.insert(
+            "eth-mainnet.g.alchemy.com".to_string(),
+            crate::cost::EndpointCostConfig {
+                budget_units: Some(20),
+                ..Default::default()
+            },
+        );
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "https://eth-mainnet.g.alchemy.com/v2/key".to_string(),
+                "https://mainnet.infura.io/v3/key".to_string(),
+                "https://rpc.example.org".to_string(),
+            ],
+            cost,
+            ..Default::default()
         };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
 
-        assert_eq!(status.health_percentage(), 75.0);
-        assert!(status.is_healthy());
+        pool.cost_meters[0].record("eth_blockNumber");
+        pool.cost_meters[0].record("eth_blockNumber");
+
+        for _ in 0..6 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_getLogs", None, Arm::Primary, false)
+                .unwrap();
+            assert_ne!(index, 0);
+        }
+
+        let costs = pool.cost_status();
+        assert_eq!(costs[0].endpoint, "eth-mainnet.g.alchemy.com");
+        assert_eq!(costs[0].period_units, 20);
+        assert!(costs[0].near_budget);
     }
 
-    #[test]
-    fn test_health_status_all_unhealthy() {
-        let status = PoolHealthStatus {
-            network: "ethereum".to_string(),
+    /// Endpoint 0 near its budget, endpoint 2 the cheapest for `eth_getLogs`
+    fn budget_shift_pool(expected_chain_id: Option<u64>) -> EndpointPool {
+        let mut cost = CostConfig::default();
+        cost.endpoints.insert(
+            "eth-mainnet.g.alchemy.com".to_string(),
+            crate::cost::EndpointCostConfig {
+                budget_units: Some(20),
+                ..Default::default()
+            },
+        );
+        cost.endpoints.insert(
+            "rpc.example.org".to_string(),
+            crate::cost::EndpointCostConfig {
+                method_weights: HashMap::from([("eth_getLogs".to_string(), 1)]),
+                ..Default::default()
+            },
+        );
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "https://eth-mainnet.g.alchemy.com/v2/key".to_string(),
+                "https://mainnet.infura.io/v3/key".to_string(),
+                "https://rpc.example.org".to_string(),
+            ],
+            cost,
+            expected_chain_id,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        pool.cost_meters[0].record("eth_blockNumber");
+        pool.cost_meters[0].record("eth_blockNumber");
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_budget_shift_skips_quarantined_endpoint() {
+        let pool = budget_shift_pool(Some(1));
+        pool.chain_id_guards[2].record(Ok(serde_json::json!("0xaa36a7")));
+        assert!(pool.chain_id_guards[2].is_quarantined());
+
+        for _ in 0..6 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_getLogs", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_shift_skips_lagging_endpoint() {
+        let pool = budget_shift_pool(None);
+        pool.head_lag.record(0, 20_000_000);
+        pool.head_lag.record(1, 20_000_001);
+        pool.head_lag.record(2, 19_999_000);
+        assert!(pool.head_lag.is_degraded(2));
+
+        for _ in 0..6 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_getLogs", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_warning_sent_once_per_period() {
+        let mut cost = CostConfig::default();
+        cost.endpoints.insert(
+            "eth-mainnet.g.alchemy.com".to_string(),
+            crate::cost::EndpointCostConfig {
+                budget_units: Some(20),
+                ..Default::default()
+            },
+        );
+        let (sender, mut warnings) = broadcast::channel(8);
+        let config = EndpointPoolConfig {
+            endpoints: vec!["https://eth-mainnet.g.alchemy.com/v2/key".to_string()],
+            cost,
+            budget_warnings: Some(sender),
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        pool.charge(0, "eth_blockNumber");
+        assert!(warnings.try_recv().is_err());
+        pool.charge(0, "eth_blockNumber");
+        pool.charge(0, "eth_blockNumber");
+
+        let warning = warnings.try_recv().unwrap();
+        assert_eq!(warning.network, "ethereum");
+        assert_eq!(warning.endpoint, "eth-mainnet.g.alchemy.com");
+        assert_eq!(warning.period_units, 20);
+        assert!(warnings.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_endpoint_is_skipped() {
+        let mut rate_limit = RateLimitConfig {
+            max_wait_ms: 0,
+            ..Default::default()
+        };
+        rate_limit.endpoints.insert(
+            "eth-mainnet.g.alchemy.com".to_string(),
+            crate::rate_limit::EndpointRateLimit {
+                requests_per_sec: 0.001,
+                burst: Some(1),
+            },
+        );
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "https://eth-mainnet.g.alchemy.com/v2/key".to_string(),
+                "https://rpc.example.org".to_string(),
+            ],
+            rate_limit,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        pool.rate_limiters[0].acquire(1);
+        for _ in 0..4 {
+            let (index, _) = pool
+                .wait_for_endpoint("eth_call", None, Arm::Primary, false)
+                .await
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+        assert!(pool.rate_limit_status()[0].limited);
+
+        // With the unlimited endpoint down, the limited one can't take the call
+        for _ in 0..5 {
+            pool.circuit_breakers[1].record_failure();
+        }
+        let Err(error) = pool
+            .wait_for_endpoint("eth_call", None, Arm::Primary, false)
+            .await
+        else {
+            panic!("the limited endpoint took the call");
+        };
+        assert!(error.to_string().contains("rate limited"));
+    }
+
+    #[tokio::test]
+    async fn test_failing_method_skips_endpoint_for_that_method_only() {
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://endpoint1.com".to_string(),
+                "http://endpoint2.com".to_string(),
+            ],
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        for _ in 0..5 {
+            if pool.method_breakers[0].record_failure("eth_getLogs") {
+                pool.circuit_breakers[0].record_failure();
+            }
+        }
+        assert_eq!(pool.circuit_breakers[0].state(), CircuitState::Closed);
+
+        for _ in 0..4 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_getLogs", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+        let call_endpoints: Vec<usize> = (0..2)
+            .map(|_| {
+                pool.get_next_endpoint("eth_call", None, Arm::Primary, false)
+                    .unwrap()
+                    .0
+            })
+            .collect();
+        assert!(call_endpoints.contains(&0));
+        assert_eq!(
+            pool.health_status().endpoints[0].open_methods,
+            vec!["eth_getLogs".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paused_endpoint_gets_no_traffic() {
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://endpoint1.com".to_string(),
+                "http://endpoint2.com".to_string(),
+            ],
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        assert!(pool.set_paused("http://endpoint1.com", true));
+        assert!(!pool.set_paused("http://unknown.com", true));
+        for _ in 0..4 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_call", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+        assert_eq!(pool.paused_endpoints(), vec!["http://endpoint1.com"]);
+        assert!(pool.health_status().endpoints[0].paused);
+
+        pool.set_paused("http://endpoint1.com", false);
+        assert!(pool.paused_endpoints().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_old_block_calls_go_to_archive_endpoints() {
+        let mut archive = ArchiveConfig::default();
+        archive
+            .endpoints
+            .insert("archive.example.org".to_string(), NodeKind::Archive);
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://full.example.org".to_string(),
+                "http://archive.example.org".to_string(),
+            ],
+            archive,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        pool.head_lag.record(0, 10_000);
+        pool.head_lag.record(1, 10_000);
+
+        let old = [Value::from("0xabc"), Value::from("0x10")];
+        let latest = [Value::from("0xabc"), Value::from("latest")];
+        assert!(pool.needs_archive("eth_getBalance", &old));
+        assert!(!pool.needs_archive("eth_getBalance", &latest));
+        for _ in 0..4 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_getBalance", None, Arm::Primary, true)
+                .unwrap();
+            assert_eq!(index, 1);
+            let (index, _) = pool
+                .get_next_endpoint("eth_getBalance", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 0);
+        }
+        assert!(pool.health_status().endpoints[1].archive);
+
+        // Latest-state calls fall back to archive nodes, old blocks never go
+        // to full nodes
+        pool.set_paused("http://full.example.org", true);
+        let (index, _) = pool
+            .get_next_endpoint("eth_getBalance", None, Arm::Primary, false)
+            .unwrap();
+        assert_eq!(index, 1);
+        pool.set_paused("http://full.example.org", false);
+        pool.set_paused("http://archive.example.org", true);
+        assert!(pool
+            .get_next_endpoint("eth_getBalance", None, Arm::Primary, true)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_at_old_block_opens_on_archive_endpoint() {
+        let mut archive = ArchiveConfig::default();
+        archive
+            .endpoints
+            .insert("archive.example.org".to_string(), NodeKind::Archive);
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://full.example.org".to_string(),
+                "http://archive.example.org".to_string(),
+            ],
+            archive,
+            ..Default::default()
+        };
+        let pool = Arc::new(EndpointPool::new("ethereum".to_string(), config).unwrap());
+        pool.head_lag.record(0, 10_000);
+        pool.head_lag.record(1, 10_000);
+
+        let session = crate::session::ConsistentSession::open(pool.clone(), Some(16))
+            .await
+            .unwrap();
+        assert_eq!(session.endpoint(), "archive.example.org");
+        assert_eq!(session.block(), 16);
+
+        let session = crate::session::ConsistentSession::open(pool, Some(9_990))
+            .await
+            .unwrap();
+        assert_eq!(session.endpoint(), "full.example.org");
+    }
+
+    #[tokio::test]
+    async fn test_lowest_latency_strategy_picks_fastest_endpoint() {
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://slow.com".to_string(),
+                "http://fast.com".to_string(),
+                "http://down.com".to_string(),
+            ],
+            selection: SelectionConfig {
+                strategy: SelectionStrategy::LowestLatency,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        pool.endpoint_stats[0].record(true, Some(Duration::from_millis(300)));
+        pool.endpoint_stats[1].record(true, Some(Duration::from_millis(40)));
+        pool.endpoint_stats[2].record(true, Some(Duration::from_millis(5)));
+        for _ in 0..5 {
+            pool.circuit_breakers[2].record_failure();
+        }
+
+        for _ in 0..4 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_call", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+
+        let scores = pool.selection_status();
+        assert_eq!(scores[1].endpoint, "fast.com");
+        assert!((scores[1].latency_ewma_ms.unwrap() - 40.0).abs() < 1e-9);
+        assert!(scores[1].health_score > scores[0].health_score);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_chain_id_quarantines_endpoint() {
+        let mainnet = delayed_server(Duration::ZERO, "0x1").await;
+        let sepolia = delayed_server(Duration::ZERO, "0xaa36a7").await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![sepolia, mainnet],
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            expected_chain_id: Some(1),
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        assert_eq!(pool.verify_chain_ids().await, 1);
+        for _ in 0..4 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_call", None, Arm::Primary, false)
+                .unwrap();
+            assert_eq!(index, 1);
+        }
+
+        let status = pool.chain_id_status();
+        assert!(status[0].quarantined);
+        assert_eq!(status[0].reported, Some(11_155_111));
+        assert!(!status[1].quarantined);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_endpoint_excluded_from_selection() {
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://rpc1.com".to_string(),
+                "http://rpc2.com".to_string(),
+                "http://stale.com".to_string(),
+            ],
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        pool.head_lag.record(0, 20_000_000);
+        pool.head_lag.record(1, 20_000_001);
+        pool.head_lag.record(2, 19_999_000);
+
+        for _ in 0..6 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_call", None, Arm::Primary, false)
+                .unwrap();
+            assert_ne!(index, 2);
+        }
+
+        let health = pool.health_status();
+        assert_eq!(health.degraded_endpoints, 1);
+        assert_eq!(health.median_block, Some(20_000_000));
+        assert_eq!(health.endpoints[2].endpoint, "stale.com");
+        assert_eq!(health.endpoints[2].lag_blocks, Some(1_000));
+        assert!(health.endpoints[2].degraded);
+
+        pool.head_lag.record(2, 20_000_001);
+        assert_eq!(pool.health_status().degraded_endpoints, 0);
+    }
+
+    #[tokio::test]
+    async fn test_split_routes_arms_and_updates_live() {
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://primary1.com".to_string(),
+                "http://primary2.com".to_string(),
+                "http://trial.com".to_string(),
+            ],
+            split: Some(SplitConfig {
+                trial_endpoints: vec!["trial.com".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let split = pool.split.read().clone().unwrap();
+        for _ in 0..4 {
+            let (index, _) = pool
+                .get_next_endpoint("eth_call", Some(&split), Arm::Primary, false)
+                .unwrap();
+            assert_ne!(index, 2);
+            let (index, _) = pool
+                .get_next_endpoint("eth_call", Some(&split), Arm::Trial, false)
+                .unwrap();
+            assert_eq!(index, 2);
+        }
+        assert_eq!(pool.split_status().unwrap().trial_percent, 10);
+
+        let unknown = SplitConfig {
+            trial_endpoints: vec!["other.com".to_string()],
+            ..Default::default()
+        };
+        assert!(pool.set_split(Some(unknown)).is_err());
+        pool.set_split(None).unwrap();
+        assert!(pool.split_status().is_none());
+    }
+
+    #[test]
+    fn test_canary_wrong_answers_open_circuit() {
+        let config = EndpointPoolConfig {
+            endpoints: vec![
+                "http://good.com".to_string(),
+                "http://forked.com".to_string(),
+            ],
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        let mut canary = CanaryConfig::default();
+        canary.networks.insert(
+            "ethereum".to_string(),
+            crate::canary::NetworkCanary {
+                reference_block: Some(crate::canary::ReferenceBlock {
+                    number: 1,
+                    hash: "0xaa".to_string(),
+                }),
+                ..Default::default()
+            },
+        );
+
+        let answer = |kind, result: Value| ProbeAnswer {
+            kind,
+            latency: Duration::from_millis(20),
+            result: Ok(result),
+        };
+        let round = vec![
+            vec![
+                answer(ProbeKind::Head, serde_json::json!("0x100")),
+                answer(
+                    ProbeKind::ReferenceBlock,
+                    serde_json::json!({"hash": "0xaa"}),
+                ),
+            ],
+            vec![
+                answer(ProbeKind::Head, serde_json::json!("0x100")),
+                answer(
+                    ProbeKind::ReferenceBlock,
+                    serde_json::json!({"hash": "0xbb"}),
+                ),
+            ],
+        ];
+        for _ in 0..5 {
+            pool.apply_canary_round(&canary, &round);
+        }
+
+        assert_eq!(pool.circuit_breakers[0].state(), CircuitState::Closed);
+        assert_eq!(pool.circuit_breakers[1].state(), CircuitState::Open);
+        let status = pool.canary_status(&canary);
+        assert_eq!(status[0].score, 100.0);
+        assert_eq!(status[1].score, 0.0);
+        assert_eq!(status[1].head, Some(256));
+
+        // Unconfigured networks are not probed
+        let quiet = CanaryConfig {
+            networks: Default::default(),
+            ..Default::default()
+        };
+        pool.apply_canary_round(&quiet, &round);
+        assert_eq!(pool.canary_status(&canary)[0].rounds, 5);
+    }
+
+    /// Read one HTTP request from `socket` and return its body
+    async fn read_request_body(socket: &mut tokio::net::TcpStream) -> String {
+        read_request(socket).await.1
+    }
+
+    /// Read one HTTP request from `socket` and return its head and body
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> (String, String) {
+        use tokio::io::AsyncReadExt;
+
+        let mut buffer = Vec::new();
+        loop {
+            let mut chunk = [0u8; 4096];
+            let read = socket.read(&mut chunk).await.unwrap();
+            buffer.extend_from_slice(&chunk[..read]);
+            let text = String::from_utf8_lossy(&buffer).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    return (head.to_string(), body.to_string());
+                }
+            }
+        }
+    }
+
+    fn http_ok(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+
+    /// Serve `payloads` HTTP requests, answering each JSON-RPC batch with
+    /// `result = {method, block: params[0]}`; returns the URL and the batch
+    /// sizes seen
+    async fn batch_server(payloads: usize) -> (String, tokio::task::JoinHandle<Vec<usize>>) {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut sizes = Vec::new();
+            for _ in 0..payloads {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let body = read_request_body(&mut socket).await;
+                let requests: Vec<RpcRequest> = serde_json::from_str(&body).unwrap();
+                sizes.push(requests.len());
+                let answers: Vec<Value> = requests
+                    .iter()
+                    .rev()
+                    .map(|request| {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": request.id,
+                            "result": {"method": request.method, "block": request.params[0]},
+                        })
+                    })
+                    .collect();
+                let answer = serde_json::to_string(&answers).unwrap();
+                socket.write_all(http_ok(&answer).as_bytes()).await.unwrap();
+            }
+            sizes
+        });
+        (url, handle)
+    }
+
+    /// Server answering every call with `result` after `delay`
+    async fn delayed_server(delay: Duration, result: &'static str) -> String {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let body = read_request_body(&mut socket).await;
+                    let request: RpcRequest = serde_json::from_str(&body).unwrap();
+                    tokio::time::sleep(delay).await;
+                    let answer = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": request.id,
+                        "result": result,
+                    })
+                    .to_string();
+                    // The pool may have cancelled the call already
+                    let _ = socket.write_all(http_ok(&answer).as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    /// Server with only the parity trace API, answering `calls` calls and
+    /// returning the methods asked
+    async fn parity_trace_server(calls: usize) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut methods = Vec::new();
+            for _ in 0..calls {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let body = read_request_body(&mut socket).await;
+                let request: RpcRequest = serde_json::from_str(&body).unwrap();
+                let answer = if request.method.starts_with("trace_") {
+                    serde_json::json!({"jsonrpc": "2.0", "id": request.id, "result": [
+                        {"type": "call", "action": {"callType": "call", "from": "0xa", "to": "0xb"},
+                         "result": {"gasUsed": "0x5208"}, "traceAddress": [],
+                         "transactionHash": request.params[0]}
+                    ]})
+                } else {
+                    serde_json::json!({"jsonrpc": "2.0", "id": request.id, "error": {
+                        "code": -32601,
+                        "message": format!("the method {} does not exist/is not available", request.method),
+                    }})
+                };
+                socket
+                    .write_all(http_ok(&answer.to_string()).as_bytes())
+                    .await
+                    .unwrap();
+                methods.push(request.method);
+            }
+            methods
+        });
+        (url, handle)
+    }
+
+    /// Server answering every call with HTTP 200 and `body`, whatever it is
+    async fn body_server(body: &'static str) -> String {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_request_body(&mut socket).await;
+                let _ = socket.write_all(http_ok(body).as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_invalid_200_answers_fail_over_and_count_as_failures() {
+        let html = body_server("<html><h1>429 Too Many Requests</h1></html>").await;
+        let wrong_type = body_server(r#"{"jsonrpc": "2.0", "id": 1, "result": "sixteen"}"#).await;
+        let good = delayed_server(Duration::ZERO, "0x10").await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![html, wrong_type, good],
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            circuit_breaker: CircuitBreakerConfig {
+                per_method: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let response = pool
+            .call_with_failover(&RpcRequest::new("eth_blockNumber", vec![]))
+            .await
+            .unwrap();
+        assert_eq!(response.result, Some(serde_json::json!("0x10")));
+        assert_eq!(pool.circuit_breakers[0].failure_count(), 1);
+        assert_eq!(pool.circuit_breakers[1].failure_count(), 1);
+        assert_eq!(pool.circuit_breakers[2].failure_count(), 0);
+
+        // Only the rate-limit page empties the endpoint's bucket
+        let limits = pool.rate_limit_status();
+        assert_eq!(limits[0].rejections, 1);
+        assert_eq!(limits[1].rejections, 0);
+    }
+
+    /// Server answering every call with the request's `Accept-Encoding` header
+    async fn accept_encoding_server() -> String {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (head, body) = read_request(&mut socket).await;
+                let request: RpcRequest = serde_json::from_str(&body).unwrap();
+                let encodings = head.lines().find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("accept-encoding")
+                        .then(|| value.trim().to_string())
+                });
+                let answer = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.id,
+                    "result": encodings.unwrap_or_default(),
+                })
+                .to_string();
+                let _ = socket.write_all(http_ok(&answer).as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_oversized_answer_aborted_without_failover() {
+        let large = body_server(
+            r#"{"jsonrpc": "2.0", "id": 1, "result": "a trace far larger than this method may answer"}"#,
+        )
+        .await;
+        let small = delayed_server(Duration::ZERO, "0x10").await;
+        let mut size_limit = SizeLimitConfig::default();
+        size_limit
+            .methods
+            .insert("debug_traceTransaction".to_string(), 32);
+        let config = EndpointPoolConfig {
+            endpoints: vec![large, small],
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            size_limit,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let request = RpcRequest::new("debug_traceTransaction", vec![]);
+        let error = pool.call_with_failover(&request).await.unwrap_err();
+        assert!(is_size_limit_error(&error));
+        assert_eq!(pool.circuit_breakers[0].failure_count(), 0);
+        assert_eq!(pool.cost_status()[1].total_requests, 0);
+
+        // Other methods keep the default limit
+        let request = RpcRequest::new("web3_clientVersion", vec![]);
+        assert!(pool.call_with_failover(&request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_not_found_and_reverted_answers_cached_briefly() {
+        let missing = body_server(r#"{"jsonrpc": "2.0", "id": 1, "result": null}"#).await;
+        let reverted = body_server(
+            r#"{"jsonrpc": "2.0", "id": 1, "error": {"code": 3, "message": "execution reverted"}}"#,
+        )
+        .await;
+        let pool = |endpoint: String| {
+            let config = EndpointPoolConfig {
+                endpoints: vec![endpoint],
+                ..Default::default()
+            };
+            EndpointPool::new("ethereum".to_string(), config).unwrap()
+        };
+
+        let receipts = pool(missing);
+        let request = RpcRequest::new("eth_getTransactionReceipt", vec![serde_json::json!("0xab")]);
+        for _ in 0..2 {
+            let response = receipts.call_with_failover(&request).await.unwrap();
+            assert_eq!(response.result, None);
+        }
+        assert_eq!(receipts.cost_status()[0].total_requests, 1);
+
+        // Errors are cached once every attempt failed with them
+        let calls = pool(reverted);
+        let request = RpcRequest::new("eth_call", vec![serde_json::json!({"to": "0xabc"})]);
+        for _ in 0..2 {
+            let error = calls.call_with_failover(&request).await.unwrap_err();
+            assert!(error.to_string().contains("execution reverted"));
+        }
+        assert_eq!(calls.cost_status()[0].total_requests, 3);
+    }
+
+    #[tokio::test]
+    async fn test_trace_flavor_detected_once_per_endpoint() {
+        let (url, methods) = parity_trace_server(3).await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![url],
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let target = TraceTarget::Transaction("0xt1".to_string());
+        for _ in 0..2 {
+            let traces = pool.trace(&target).await.unwrap();
+            assert_eq!(traces.len(), 1);
+            assert_eq!(traces[0].gas_used.as_deref(), Some("0x5208"));
+            assert_eq!(traces[0].transaction_hash.as_deref(), Some("0xt1"));
+        }
+        assert_eq!(
+            methods.await.unwrap(),
+            vec![
+                "debug_traceTransaction",
+                "trace_transaction",
+                "trace_transaction"
+            ]
+        );
+
+        // Lacking a method is not an endpoint failure
+        assert_eq!(pool.circuit_breakers[0].failure_count(), 0);
+        let status = pool.health_status();
+        assert_eq!(status.endpoints[0].trace_flavor, Some(TraceFlavor::Parity));
+        assert!(status.endpoints[0].open_methods.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_endpoints_only_serve_while_primaries_are_open() {
+        let primary = body_server(r#"{"jsonrpc": "2.0", "id": 1, "result": "primary"}"#).await;
+        let fallback = body_server(r#"{"jsonrpc": "2.0", "id": 1, "result": "fallback"}"#).await;
+        let pool = |primary: String| {
+            let mut config = EndpointPoolConfig {
+                endpoints: vec![primary],
+                circuit_breaker: CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    per_method: false,
+                    ..Default::default()
+                },
+                cache: CacheConfig {
+                    enabled: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            config
+                .fallback
+                .networks
+                .insert("ethereum".to_string(), vec![fallback.clone()]);
+            EndpointPool::new("ethereum".to_string(), config).unwrap()
+        };
+        let request = RpcRequest::new("web3_clientVersion", vec![]);
+
+        let healthy = pool(primary);
+        assert_eq!(healthy.endpoints().len(), 1);
+        assert_eq!(healthy.fallback_endpoints(), &[fallback.clone()]);
+        for _ in 0..3 {
+            let response = healthy.call_with_failover(&request).await.unwrap();
+            assert_eq!(response.result, Some(serde_json::json!("primary")));
+        }
+
+        // The only primary fails once, opening its circuit
+        let outage = pool("http://127.0.0.1:1".to_string());
+        let response = outage.call_with_failover(&request).await.unwrap();
+        assert_eq!(response.result, Some(serde_json::json!("fallback")));
+        let status = outage.health_status();
+        assert!(!status.endpoints[0].fallback);
+        assert!(status.endpoints[1].fallback);
+        assert_eq!(
+            outage.rate_limit_status()[1].requests_per_sec,
+            Some(FallbackConfig::default().rate_limit.requests_per_sec)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compression_negotiated_unless_turned_off() {
+        let compressed = accept_encoding_server().await;
+        let plain = accept_encoding_server().await;
+        let mut compression = CompressionConfig::default();
+        compression.endpoints.insert(plain.clone(), false);
+        let config = EndpointPoolConfig {
+            endpoints: vec![compressed, plain],
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            compression,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        // Round-robin answers from the compressed endpoint, then the plain one
+        let request = RpcRequest::new("web3_clientVersion", vec![]);
+        let encodings = pool.call_with_failover(&request).await.unwrap().result;
+        let encodings = encodings.unwrap().as_str().unwrap().to_string();
+        assert!(encodings.contains("gzip"));
+        assert!(encodings.contains("br"));
+        let encodings = pool.call_with_failover(&request).await.unwrap().result;
+        assert_eq!(encodings, Some(serde_json::json!("")));
+    }
+
+    #[tokio::test]
+    async fn test_slow_call_is_hedged_to_second_endpoint() {
+        let slow = delayed_server(Duration::from_secs(2), "0x5105").await;
+        let fast = delayed_server(Duration::ZERO, "0xfa57").await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![slow, fast],
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            hedge: HedgeConfig {
+                enabled: true,
+                min_samples: 1,
+                min_delay_ms: 20,
+                max_delay_ms: 50,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        pool.latencies.record(Duration::from_millis(10));
+
+        // Round-robin sends the first call to the slow endpoint
+        let started = Instant::now();
+        let response = pool
+            .call_with_failover(&RpcRequest::new("eth_call", vec![]))
+            .await
+            .unwrap();
+        assert_eq!(response.result, Some(serde_json::json!("0xfa57")));
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let costs = pool.cost_status();
+        assert_eq!(costs[0].total_requests, 1);
+        assert_eq!(costs[1].total_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_splits_by_max_batch_size() {
+        let (url, server) = batch_server(2).await;
+        let mut batch_config = BatchConfig::default();
+        batch_config.endpoints.insert("127.0.0.1".to_string(), 2);
+        let config = EndpointPoolConfig {
+            endpoints: vec![url],
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            batch: batch_config,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let batch = RpcBatchRequest::default()
+            .push("eth_getBlockByNumber", vec![serde_json::json!("0x1")])
+            .push("trace_block", vec![serde_json::json!("0x1")])
+            .push("eth_getBlockByNumber", vec![serde_json::json!("0x2")]);
+        let response = pool.execute_batch(batch).await.unwrap();
+
+        // Every call keeps its caller id (1) and its position
+        assert!(response.responses.iter().all(|r| r.id == 1));
+        assert_eq!(
+            response.into_results().unwrap(),
+            vec![
+                serde_json::json!({"method": "eth_getBlockByNumber", "block": "0x1"}),
+                serde_json::json!({"method": "trace_block", "block": "0x1"}),
+                serde_json::json!({"method": "eth_getBlockByNumber", "block": "0x2"}),
+            ]
+        );
+        assert_eq!(server.await.unwrap(), vec![2, 1]);
+        assert_eq!(pool.cost_status()[0].period_units, 3);
+
+        let empty = pool
+            .execute_batch(RpcBatchRequest::default())
+            .await
+            .unwrap();
+        assert!(empty.responses.is_empty());
+    }
+
+    #[test]
+    fn test_health_status_percentage() {
+        let status = PoolHealthStatus {
+            network: "ethereum".to_string(),
+            total_endpoints: 4,
+            healthy_endpoints: 3,
+            unhealthy_endpoints: 1,
+            half_open_endpoints: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(status.health_percentage(), 75.0);
+        assert!(status.is_healthy());
+    }
+
+    #[test]
+    fn test_health_status_all_unhealthy() {
+        let status = PoolHealthStatus {
+            network: "ethereum".to_string(),
             total_endpoints: 2,
             healthy_endpoints: 0,
             unhealthy_endpoints: 2,
             half_open_endpoints: 0,
+            ..Default::default()
         };
 
         assert_eq!(status.health_percentage(), 0.0);
         assert!(!status.is_healthy());
     }
+
+    #[tokio::test]
+    async fn test_stale_answers_served_while_revalidating() {
+        use tokio::io::AsyncWriteExt;
+
+        // Answers the number of calls it has served
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut served = 0u64;
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let body = read_request_body(&mut socket).await;
+                let request: RpcRequest = serde_json::from_str(&body).unwrap();
+                served += 1;
+                let answer = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.id,
+                    "result": format!("{:#x}", served),
+                })
+                .to_string();
+                let _ = socket.write_all(http_ok(&answer).as_bytes()).await;
+            }
+        });
+
+        let (sender, mut revalidations) = broadcast::channel(16);
+        let mut swr = SwrConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        swr.methods.insert("eth_getBalance".to_string(), 60);
+        let config = EndpointPoolConfig {
+            endpoints: vec![url],
+            swr,
+            revalidations: Some(sender),
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+        let request = RpcRequest::new(
+            "eth_getBalance",
+            vec![Value::from("0xabc"), Value::from("latest")],
+        );
+
+        let first = pool.call_with_failover(&request).await.unwrap();
+        assert_eq!(first.result, Some(Value::from("0x1")));
+
+        // The next head drops the fresh answer; the stale copy answers at once
+        pool.on_new_head(0x11).await.unwrap();
+        for _ in 0..2 {
+            let stale = pool.call_with_failover(&request).await.unwrap();
+            assert_eq!(stale.result, Some(Value::from("0x1")));
+        }
+        // One refresh asked, however many stale reads
+        let revalidation = revalidations.try_recv().unwrap();
+        assert_eq!(revalidation.network, "ethereum");
+        assert!(revalidations.try_recv().is_err());
+
+        pool.revalidate(&revalidation.request).await;
+        let fresh = pool.call_with_failover(&request).await.unwrap();
+        assert_eq!(fresh.result, Some(Value::from("0x2")));
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_reached_through_proxy_and_pinned_addresses() {
+        let upstream = delayed_server(Duration::ZERO, "0x10").await;
+        let port = upstream.rsplit(':').next().unwrap().to_string();
+        // Neither name resolves: one goes through the proxy, one to its pin
+        let proxied = "http://rpc.behind-proxy.invalid/v2/key".to_string();
+        let pinned = format!("http://rpc.pinned.invalid:{}", port);
+
+        let mut egress = EgressConfig::default();
+        egress.endpoints.insert(
+            "rpc.behind-proxy.invalid".to_string(),
+            EndpointEgress {
+                proxy: Some(upstream),
+                ..Default::default()
+            },
+        );
+        egress.endpoints.insert(
+            "rpc.pinned.invalid".to_string(),
+            EndpointEgress {
+                resolve: vec!["127.0.0.1".parse().unwrap()],
+                ..Default::default()
+            },
+        );
+        let config = EndpointPoolConfig {
+            endpoints: vec![proxied, pinned],
+            max_retries: 1,
+            egress,
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let request = RpcRequest::new("eth_blockNumber", vec![]);
+        for _ in 0..2 {
+            let response = pool.call_with_failover(&request).await.unwrap();
+            assert_eq!(response.result, Some(Value::from("0x10")));
+        }
+        assert_eq!(pool.circuit_breakers[0].failure_count(), 0);
+        assert_eq!(pool.circuit_breakers[1].failure_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_realtime_calls_jump_queued_backfill() {
+        let url = delayed_server(Duration::from_millis(100), "0x10").await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![url],
+            priority: PriorityConfig {
+                max_in_flight: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = Arc::new(EndpointPool::new("ethereum".to_string(), config).unwrap());
+        let call = |address: &str, priority: Priority| {
+            let pool = pool.clone();
+            let request = RpcRequest::new(
+                "eth_getBalance",
+                vec![Value::from(address), Value::from("latest")],
+            )
+            .with_priority(priority);
+            tokio::spawn(async move {
+                pool.call_with_failover(&request).await.unwrap();
+                Instant::now()
+            })
+        };
+        let queued = |pool: &EndpointPool| {
+            let queue = pool.health_status().queue;
+            queue.in_flight + queue.realtime + queue.backfill + queue.bulk
+        };
+
+        let first = call("0xa", Priority::Backfill);
+        while queued(&pool) < 1 {
+            tokio::task::yield_now().await;
+        }
+        let backfill = call("0xb", Priority::Backfill);
+        while queued(&pool) < 2 {
+            tokio::task::yield_now().await;
+        }
+        let realtime = call("0xc", Priority::Realtime);
+        while queued(&pool) < 3 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(
+            pool.health_status().queue,
+            QueueDepth {
+                in_flight: 1,
+                realtime: 1,
+                backfill: 1,
+                bulk: 0,
+            }
+        );
+
+        first.await.unwrap();
+        let (backfill, realtime) = (backfill.await.unwrap(), realtime.await.unwrap());
+        assert!(realtime < backfill);
+        assert_eq!(pool.health_status().queue, QueueDepth::default());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_holds_endpoint_without_tripping_its_circuit() {
+        use tokio::io::AsyncWriteExt;
+
+        // Answers 429 with `extra` headers and `body`, counting the calls
+        async fn throttling_server(
+            extra: &'static str,
+            body: &'static str,
+        ) -> (String, Arc<AtomicUsize>) {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let calls = Arc::new(AtomicUsize::new(0));
+            let served = calls.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (mut socket, _) = listener.accept().await.unwrap();
+                    read_request_body(&mut socket).await;
+                    served.fetch_add(1, Ordering::SeqCst);
+                    let answer = format!(
+                        "HTTP/1.1 429 Too Many Requests\r\n{}content-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        extra,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(answer.as_bytes()).await;
+                }
+            });
+            (url, calls)
+        }
+
+        let (header, header_calls) = throttling_server("retry-after: 30\r\n", "").await;
+        let (infura, infura_calls) = throttling_server(
+            "",
+            r#"{"jsonrpc": "2.0", "id": 1, "error": {"code": -32005, "message": "daily request count exceeded, request rate limited", "data": {"rate": {"backoff_seconds": 20}}}}"#,
+        )
+        .await;
+        let good = delayed_server(Duration::ZERO, "0x10").await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![header, infura, good],
+            max_retries: 3,
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let started = Instant::now();
+        let response = pool
+            .call_with_failover(&RpcRequest::new("eth_blockNumber", vec![]))
+            .await
+            .unwrap();
+        assert_eq!(response.result, Some(serde_json::json!("0x10")));
+        // Failed over without the retry delay
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        let limits = pool.rate_limit_status();
+        assert!(limits[0].limited && limits[1].limited);
+        assert!(limits[0].retry_in_ms.unwrap() > 25_000);
+        assert!(limits[1].retry_in_ms.unwrap() > 15_000);
+        assert_eq!(pool.circuit_breakers[0].failure_count(), 0);
+        assert_eq!(pool.circuit_breakers[1].failure_count(), 0);
+
+        // Held endpoints are left alone until their time is up
+        for _ in 0..3 {
+            pool.call_with_failover(&RpcRequest::new("eth_blockNumber", vec![]))
+                .await
+                .unwrap();
+        }
+        assert_eq!(header_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(infura_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_calls_replayed_without_upstream() {
+        use crate::recording::{RecordingConfig, RecordingMode};
+
+        let dir = std::env::temp_dir().join(format!("http-rpc-replay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = CacheConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let recorder = |mode| {
+            Some(Arc::new(Recorder::new(
+                RecordingConfig {
+                    mode,
+                    dir: dir.clone(),
+                    ..Default::default()
+                },
+                None,
+            )))
+        };
+
+        let upstream = delayed_server(Duration::ZERO, "0x5a17").await;
+        let recording = EndpointPool::new(
+            "ethereum".to_string(),
+            EndpointPoolConfig {
+                endpoints: vec![upstream],
+                cache: cache.clone(),
+                recorder: recorder(RecordingMode::Record),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let request = RpcRequest::new(
+            "eth_getBalance",
+            vec![serde_json::json!("0xAB"), serde_json::json!(16)],
+        );
+        recording.call_with_failover(&request).await.unwrap();
+
+        // Nothing listens here; every answer comes from the recording
+        let replaying = EndpointPool::new(
+            "ethereum".to_string(),
+            EndpointPoolConfig {
+                endpoints: vec!["http://127.0.0.1:1".to_string()],
+                cache,
+                recorder: recorder(RecordingMode::Replay),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let equivalent = RpcRequest::new(
+            "eth_getBalance",
+            vec![serde_json::json!("0xab"), serde_json::json!("0x10")],
+        );
+        let response = replaying.call_with_failover(&equivalent).await.unwrap();
+        assert_eq!(response.result, Some(serde_json::json!("0x5a17")));
+
+        let missing = RpcRequest::new("eth_blockNumber", vec![]);
+        let error = replaying.call_with_failover(&missing).await.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No recording of eth_blockNumber"));
+
+        let batch = replaying
+            .execute_batch(RpcBatchRequest {
+                requests: vec![equivalent, missing],
+            })
+            .await
+            .unwrap();
+        assert_eq!(batch.responses[0].result, Some(serde_json::json!("0x5a17")));
+        assert!(batch.responses[1].error.is_some());
+        assert_eq!(replaying.cost_status()[0].total_requests, 0);
+    }
+
+    /// Upstream of one block with two transactions, with or without
+    /// `eth_getBlockReceipts`; the methods it was asked are logged
+    async fn receipts_server(supported: bool) -> (String, Arc<parking_lot::Mutex<Vec<String>>>) {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let methods = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let log = methods.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let body = read_request_body(&mut socket).await;
+                let answer = |request: &RpcRequest| {
+                    log.lock().push(request.method.clone());
+                    match request.method.as_str() {
+                        BLOCK_RECEIPTS if !supported => serde_json::json!({
+                            "jsonrpc": "2.0", "id": request.id,
+                            "error": {"code": -32601, "message": "the method eth_getBlockReceipts does not exist/is not available"},
+                        }),
+                        BLOCK_RECEIPTS => serde_json::json!({
+                            "jsonrpc": "2.0", "id": request.id,
+                            "result": [{"transactionHash": "0x01"}, {"transactionHash": "0x02"}],
+                        }),
+                        "eth_getBlockByNumber" => serde_json::json!({
+                            "jsonrpc": "2.0", "id": request.id,
+                            "result": {"number": "0x10", "transactions": ["0x01", "0x02"]},
+                        }),
+                        _ => serde_json::json!({
+                            "jsonrpc": "2.0", "id": request.id,
+                            "result": {"transactionHash": request.params[0]},
+                        }),
+                    }
+                };
+                let reply = match serde_json::from_str::<Vec<RpcRequest>>(&body) {
+                    Ok(batch) => Value::Array(batch.iter().map(answer).collect()),
+                    Err(_) => answer(&serde_json::from_str(&body).unwrap()),
+                };
+                socket
+                    .write_all(http_ok(&reply.to_string()).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+        (url, methods)
+    }
+
+    #[tokio::test]
+    async fn test_block_receipts_fall_back_to_batched_receipts() {
+        let pool_for = |url: String| {
+            let config = EndpointPoolConfig {
+                endpoints: vec![url],
+                cache: CacheConfig {
+                    enabled: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            EndpointPool::new("ethereum".to_string(), config).unwrap()
+        };
+        let hashes = |receipts: Vec<Value>| -> Vec<Value> {
+            receipts
+                .into_iter()
+                .map(|receipt| receipt["transactionHash"].clone())
+                .collect()
+        };
+        let expected = vec![serde_json::json!("0x01"), serde_json::json!("0x02")];
+
+        let (url, methods) = receipts_server(false).await;
+        let pool = pool_for(url);
+        assert_eq!(hashes(pool.block_receipts("0x10").await.unwrap()), expected);
+        assert_eq!(
+            methods.lock().drain(..).collect::<Vec<_>>(),
+            [
+                BLOCK_RECEIPTS,
+                "eth_getBlockByNumber",
+                "eth_getTransactionReceipt",
+                "eth_getTransactionReceipt"
+            ]
+        );
+        // Not asked again, and the endpoint's circuit is untouched
+        pool.block_receipts("0x10").await.unwrap();
+        assert!(!methods.lock().contains(&BLOCK_RECEIPTS.to_string()));
+        let health = pool.health_status();
+        assert_eq!(health.healthy_endpoints, 1);
+        assert_eq!(health.endpoints[0].block_receipts, Some(false));
+
+        let (url, methods) = receipts_server(true).await;
+        let pool = pool_for(url);
+        assert_eq!(hashes(pool.block_receipts("0x10").await.unwrap()), expected);
+        assert_eq!(*methods.lock(), [BLOCK_RECEIPTS]);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_at_in_flight_limit_is_skipped() {
+        let limited = delayed_server(Duration::from_millis(200), "0xa").await;
+        let open = delayed_server(Duration::from_millis(200), "0xb").await;
+        let connection: ConnectionConfig = serde_json::from_value(serde_json::json!({
+            "endpoints": {limited.clone(): {"max_in_flight": 1}}
+        }))
+        .unwrap();
+        let config = EndpointPoolConfig {
+            endpoints: vec![limited, open],
+            connection,
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("ethereum".to_string(), config).unwrap();
+
+        let calls = ["0x1", "0x2", "0x3"].map(|address| {
+            RpcRequest::new(
+                "eth_getBalance",
+                vec![Value::from(address), Value::from("latest")],
+            )
+        });
+        let answers =
+            futures_util::future::join_all(calls.iter().map(|call| pool.call_with_failover(call)))
+                .await;
+        let from_limited = answers
+            .iter()
+            .filter(|answer| answer.as_ref().unwrap().result == Some(Value::from("0xa")))
+            .count();
+        // Round-robin would have sent the third call back to the limited one
+        assert_eq!(from_limited, 1);
+        assert_eq!(pool.health_status().endpoints[0].in_flight, Some(0));
+        assert_eq!(pool.health_status().endpoints[1].in_flight, None);
+    }
+
+    #[tokio::test]
+    async fn test_solana_answers_cached_by_commitment() {
+        let url = delayed_server(Duration::ZERO, "answer").await;
+        let config = EndpointPoolConfig {
+            endpoints: vec![url],
+            ..Default::default()
+        };
+        let pool = EndpointPool::new("solana".to_string(), config).unwrap();
+        let cached = |request: &RpcRequest| {
+            let key = pool
+                .cache
+                .make_key("solana", &request.method, &request.params);
+            let cache = pool.cache.clone();
+            async move { cache.get(&key).await.unwrap() }
+        };
+
+        let finalized =
+            crate::solana::get_block_request(250_000_000, crate::solana::Commitment::Finalized)
+                .unwrap();
+        let processed = RpcRequest::new(
+            "getSlot",
+            vec![serde_json::json!({"commitment": "processed"})],
+        );
+        for request in [&finalized, &processed] {
+            pool.call_with_failover(request).await.unwrap();
+        }
+        assert_eq!(cached(&finalized).await, Some(Value::from("answer")));
+        // A processed slot may be forked away
+        assert_eq!(cached(&processed).await, None);
+    }
 }
@@ -0,0 +1,325 @@
+//! Typed answers of common `eth_*` methods
+//!
+//! Nodes answer quantities as `0x` hex strings, and every actor reading a block
+//! or receipt used to parse them itself. The structs here parse them once:
+//! quantities become integers (`u128` for wei amounts, which overflow `u64`
+//! past 18.4 ETH), hashes, addresses and data stay hex strings. Quantities are
+//! written back as hex, so a struct serializes in the node's own shape and can
+//! be passed on as JSON-RPC results; decimal numbers are accepted on the way
+//! in too. Fields a node may leave out (pre-London fees, pending blocks) are
+//! `Option`s, and fields not listed are ignored.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Integer a node answers as a hex quantity
+pub trait Quantity: Sized + Copy + std::fmt::LowerHex {
+    fn from_hex(digits: &str) -> Option<Self>;
+    fn from_u64(value: u64) -> Option<Self>;
+}
+
+macro_rules! quantity {
+    ($($int:ty),*) => {$(
+        impl Quantity for $int {
+            fn from_hex(digits: &str) -> Option<Self> {
+                <$int>::from_str_radix(digits, 16).ok()
+            }
+
+            fn from_u64(value: u64) -> Option<Self> {
+                <$int>::try_from(value).ok()
+            }
+        }
+    )*};
+}
+
+quantity!(u8, u64, u128);
+
+/// `value` as a hex quantity (`0x1b`, `0x` for zero) or a decimal string
+pub fn parse_quantity<T: Quantity>(value: &str) -> Option<T> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some("") => T::from_u64(0),
+        Some(digits) => T::from_hex(digits),
+        None => value.parse::<u64>().ok().and_then(T::from_u64),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawQuantity {
+    Text(String),
+    Number(u64),
+}
+
+fn quantity_from<'de, T: Quantity, D: Deserializer<'de>>(raw: RawQuantity) -> Result<T, D::Error> {
+    let parsed = match &raw {
+        RawQuantity::Text(text) => parse_quantity(text),
+        RawQuantity::Number(number) => T::from_u64(*number),
+    };
+    parsed.ok_or_else(|| {
+        let shown = match raw {
+            RawQuantity::Text(text) => text,
+            RawQuantity::Number(number) => number.to_string(),
+        };
+        serde::de::Error::custom(format!("invalid quantity: {}", shown))
+    })
+}
+
+/// Serde adapter for quantity fields
+pub mod quantity {
+    use super::*;
+
+    pub fn serialize<T: Quantity, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{:x}", value))
+    }
+
+    pub fn deserialize<'de, T: Quantity, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        quantity_from::<T, D>(RawQuantity::deserialize(deserializer)?)
+    }
+}
+
+/// Serde adapter for optional quantity fields; `null` and absent are `None`
+pub mod option_quantity {
+    use super::*;
+
+    pub fn serialize<T: Quantity, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => quantity::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T: Quantity, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error> {
+        Option::<RawQuantity>::deserialize(deserializer)?
+            .map(quantity_from::<T, D>)
+            .transpose()
+    }
+}
+
+/// Block with full transaction bodies (`eth_getBlockByNumber(block, true)`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    /// `None` for a pending block
+    #[serde(default, with = "option_quantity")]
+    pub number: Option<u64>,
+    #[serde(default)]
+    pub hash: Option<String>,
+    pub parent_hash: String,
+    #[serde(with = "quantity")]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub miner: Option<String>,
+    #[serde(with = "quantity")]
+    pub gas_used: u64,
+    #[serde(with = "quantity")]
+    pub gas_limit: u64,
+    #[serde(default, with = "option_quantity")]
+    pub base_fee_per_gas: Option<u128>,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+}
+
+/// Transaction body (`eth_getTransactionByHash`, or within a [`Block`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub hash: String,
+    pub from: String,
+    /// `None` for contract creations
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(with = "quantity")]
+    pub value: u128,
+    #[serde(default)]
+    pub input: String,
+    #[serde(with = "quantity")]
+    pub nonce: u64,
+    #[serde(with = "quantity")]
+    pub gas: u64,
+    #[serde(default, with = "option_quantity")]
+    pub gas_price: Option<u128>,
+    #[serde(default, with = "option_quantity")]
+    pub max_fee_per_gas: Option<u128>,
+    #[serde(default, with = "option_quantity")]
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// `None` while pending
+    #[serde(default, with = "option_quantity")]
+    pub block_number: Option<u64>,
+    #[serde(default)]
+    pub block_hash: Option<String>,
+    #[serde(default, with = "option_quantity")]
+    pub transaction_index: Option<u64>,
+    /// EIP-2718 type; `None` for legacy nodes that omit it
+    #[serde(rename = "type", default, with = "option_quantity")]
+    pub tx_type: Option<u8>,
+    #[serde(default, with = "option_quantity")]
+    pub chain_id: Option<u64>,
+}
+
+/// Receipt of a mined transaction (`eth_getTransactionReceipt`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    pub transaction_hash: String,
+    #[serde(with = "quantity")]
+    pub transaction_index: u64,
+    pub block_hash: String,
+    #[serde(with = "quantity")]
+    pub block_number: u64,
+    pub from: String,
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Address of the contract a creation deployed
+    #[serde(default)]
+    pub contract_address: Option<String>,
+    #[serde(with = "quantity")]
+    pub gas_used: u64,
+    #[serde(with = "quantity")]
+    pub cumulative_gas_used: u64,
+    #[serde(default, with = "option_quantity")]
+    pub effective_gas_price: Option<u128>,
+    /// 1 for success, 0 for a revert; `None` on pre-Byzantium receipts
+    #[serde(default, with = "option_quantity")]
+    pub status: Option<u8>,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    #[serde(rename = "type", default, with = "option_quantity")]
+    pub tx_type: Option<u8>,
+}
+
+impl TransactionReceipt {
+    /// Whether the transaction succeeded; `None` when the receipt predates
+    /// status codes
+    pub fn succeeded(&self) -> Option<bool> {
+        self.status.map(|status| status == 1)
+    }
+}
+
+/// Event log (`eth_getLogs`, or within a [`TransactionReceipt`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub address: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub data: String,
+    /// Position fields are `None` for pending logs
+    #[serde(default, with = "option_quantity")]
+    pub block_number: Option<u64>,
+    #[serde(default)]
+    pub block_hash: Option<String>,
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    #[serde(default, with = "option_quantity")]
+    pub transaction_index: Option<u64>,
+    #[serde(default, with = "option_quantity")]
+    pub log_index: Option<u64>,
+    /// Dropped by a reorg
+    #[serde(default)]
+    pub removed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_quantities_parsed_once() {
+        assert_eq!(parse_quantity::<u64>("0x1b4"), Some(436));
+        assert_eq!(parse_quantity::<u64>("0x"), Some(0));
+        assert_eq!(parse_quantity::<u64>("436"), Some(436));
+        assert_eq!(parse_quantity::<u8>("0x100"), None);
+        assert_eq!(parse_quantity::<u64>("0xzz"), None);
+
+        let block: Block = serde_json::from_value(json!({
+            "number": "0x10d4f",
+            "hash": "0xabc",
+            "parentHash": "0xabb",
+            "timestamp": "0x5e0be0ff",
+            "miner": "0xminer",
+            "gasUsed": "0x5208",
+            "gasLimit": "0x1c9c380",
+            "baseFeePerGas": "0x7",
+            "uncles": [],
+            "transactions": [{
+                "hash": "0x01",
+                "from": "0xfrom",
+                "to": null,
+                "value": "0x3635c9adc5dea00000",
+                "input": "0x60806040",
+                "nonce": "0x0",
+                "gas": "0x5208",
+                "maxFeePerGas": "0x3b9aca00",
+                "maxPriorityFeePerGas": "0x1",
+                "blockNumber": "0x10d4f",
+                "transactionIndex": "0x0",
+                "type": "0x2",
+                "chainId": "0x1"
+            }]
+        }))
+        .unwrap();
+        assert_eq!(block.number, Some(68943));
+        assert_eq!(block.base_fee_per_gas, Some(7));
+        let transaction = &block.transactions[0];
+        // 1000 ETH does not fit a u64 of wei
+        assert_eq!(transaction.value, 1_000_000_000_000_000_000_000);
+        assert_eq!(transaction.to, None);
+        assert_eq!(transaction.gas_price, None);
+        assert_eq!(transaction.tx_type, Some(2));
+
+        // Written back in the node's shape
+        let written = serde_json::to_value(&block).unwrap();
+        assert_eq!(written["transactions"][0]["value"], "0x3635c9adc5dea00000");
+        assert_eq!(serde_json::from_value::<Block>(written).unwrap(), block);
+
+        assert!(serde_json::from_value::<Log>(json!({
+            "address": "0xa", "blockNumber": "0xnope"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_receipt_with_logs() {
+        let receipt: TransactionReceipt = serde_json::from_value(json!({
+            "transactionHash": "0x01",
+            "transactionIndex": "0x3",
+            "blockHash": "0xabc",
+            "blockNumber": "0x10",
+            "from": "0xfrom",
+            "to": "0xto",
+            "contractAddress": null,
+            "gasUsed": "0xb411",
+            "cumulativeGasUsed": "0x1f2b3",
+            "effectiveGasPrice": "0x4a817c800",
+            "status": "0x0",
+            "logs": [{
+                "address": "0xtoken",
+                "topics": ["0xddf2"],
+                "data": "0x",
+                "blockNumber": "0x10",
+                "logIndex": "0x5",
+                "removed": false
+            }]
+        }))
+        .unwrap();
+        assert_eq!(receipt.transaction_index, 3);
+        assert_eq!(receipt.effective_gas_price, Some(20_000_000_000));
+        assert_eq!(receipt.succeeded(), Some(false));
+        assert_eq!(receipt.logs[0].log_index, Some(5));
+        assert_eq!(receipt.logs[0].transaction_hash, None);
+    }
+}
@@ -0,0 +1,110 @@
+//! Public fallback endpoints
+//!
+//! When every paid endpoint of a network is down (a vendor outage, a revoked
+//! key, an exhausted plan) the pipeline would halt. A network can list
+//! fallback endpoints, typically public RPCs, which sit behind the primaries:
+//! - they get no traffic while any primary endpoint's circuit can take a call
+//! - once every primary circuit is open they serve calls like any endpoint,
+//!   until a primary recovers (its circuit half-opens and takes probes)
+//! - their token bucket is `rate_limit` rather than the provider default, as
+//!   public endpoints throttle hard; host entries in the rate limit config
+//!   still win
+//!
+//! Fallback endpoints are not part of the network's managed endpoint set, so
+//! they are never persisted or removed over `rpc.endpoints.*`.
+
+use crate::rate_limit::{EndpointRateLimit, RateLimitConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Fallback tier settings (`HTTP_RPC_FALLBACK_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FallbackConfig {
+    /// Fallback endpoint URLs per network
+    pub networks: HashMap<String, Vec<String>>,
+    /// Token bucket of each fallback endpoint
+    pub rate_limit: EndpointRateLimit,
+}
+
+impl Default for FallbackConfig {
+    fn default() -> Self {
+        Self {
+            networks: HashMap::new(),
+            rate_limit: EndpointRateLimit {
+                requests_per_sec: 5.0,
+                burst: Some(10),
+            },
+        }
+    }
+}
+
+impl FallbackConfig {
+    /// Fallback endpoints of `network` not already among `primaries`
+    pub fn endpoints_for(&self, network: &str, primaries: &[String]) -> Vec<String> {
+        let mut endpoints: Vec<String> = Vec::new();
+        for endpoint in self.networks.get(network).into_iter().flatten() {
+            let endpoint = endpoint.trim();
+            if !endpoint.is_empty()
+                && !primaries.iter().any(|primary| primary == endpoint)
+                && !endpoints.iter().any(|known| known == endpoint)
+            {
+                endpoints.push(endpoint.to_string());
+            }
+        }
+        endpoints
+    }
+
+    /// Rate limits for fallback endpoints, on top of the pool's
+    pub fn rate_limits(&self, pool: &RateLimitConfig) -> RateLimitConfig {
+        RateLimitConfig {
+            default_limit: Some(self.rate_limit),
+            ..pool.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_endpoints_and_limits() {
+        let config: FallbackConfig = serde_json::from_str(
+            r#"{"networks": {"ethereum": [
+                "https://ethereum-rpc.publicnode.com",
+                "https://eth.example.org",
+                "https://ethereum-rpc.publicnode.com"
+            ]}}"#,
+        )
+        .unwrap();
+        let primaries = vec!["https://eth.example.org".to_string()];
+        assert_eq!(
+            config.endpoints_for("ethereum", &primaries),
+            vec!["https://ethereum-rpc.publicnode.com".to_string()]
+        );
+        assert!(config.endpoints_for("polygon", &primaries).is_empty());
+
+        let mut pool = RateLimitConfig::default();
+        pool.endpoints.insert(
+            "ethereum-rpc.publicnode.com".to_string(),
+            EndpointRateLimit {
+                requests_per_sec: 2.0,
+                burst: None,
+            },
+        );
+        let limits = config.rate_limits(&pool);
+        assert_eq!(
+            limits
+                .limit_for("https://ethereum-rpc.publicnode.com")
+                .map(|limit| limit.requests_per_sec),
+            Some(2.0)
+        );
+        assert_eq!(
+            limits
+                .limit_for("https://cloudflare-eth.com")
+                .map(|limit| limit.requests_per_sec),
+            Some(5.0)
+        );
+    }
+}
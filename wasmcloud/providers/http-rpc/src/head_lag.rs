@@ -0,0 +1,186 @@
+//! Block-height lag per endpoint
+//!
+//! An endpoint whose node fell out of sync keeps answering, just with old
+//! data. Every endpoint's latest block height is tracked from canary head
+//! probes and live `eth_blockNumber` answers; an endpoint more than
+//! `max_lag_blocks` behind the median of the pool's heights is degraded and
+//! left out of endpoint selection until it catches up.
+//!
+//! Heights older than `stale_after_secs` count neither toward the median nor
+//! against their endpoint, so an excluded endpoint gets traffic again (and a
+//! fresh height) when no probe has seen it for a while.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Lag settings (`HTTP_RPC_HEAD_LAG_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeadLagConfig {
+    pub enabled: bool,
+    /// Blocks an endpoint may trail the pool median before it is degraded
+    pub max_lag_blocks: u64,
+    /// Age after which a height no longer counts
+    pub stale_after_secs: u64,
+}
+
+impl Default for HeadLagConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_lag_blocks: 10,
+            stale_after_secs: 300,
+        }
+    }
+}
+
+/// Block number of an `eth_blockNumber` result (hex quantity)
+pub fn block_height(value: &Value) -> Option<u64> {
+    let hex = value.as_str()?;
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+/// Latest block heights per endpoint (same order as the pool's endpoints)
+pub struct HeadLagTracker {
+    config: HeadLagConfig,
+    heights: Mutex<Vec<Option<(u64, Instant)>>>,
+}
+
+impl HeadLagTracker {
+    pub fn new(endpoints: usize, config: HeadLagConfig) -> Self {
+        Self {
+            config,
+            heights: Mutex::new(vec![None; endpoints]),
+        }
+    }
+
+    /// Record a height reported by endpoint `index`
+    pub fn record(&self, index: usize, height: u64) {
+        self.record_at(index, height, Instant::now())
+    }
+
+    /// Latest fresh height of endpoint `index`
+    pub fn height(&self, index: usize) -> Option<u64> {
+        self.height_at(index, Instant::now())
+    }
+
+    /// Median of the fresh heights (upper median for an even count)
+    pub fn median(&self) -> Option<u64> {
+        self.median_at(Instant::now())
+    }
+
+    /// Blocks endpoint `index` trails the median by
+    pub fn lag(&self, index: usize) -> Option<u64> {
+        self.lag_at(index, Instant::now())
+    }
+
+    /// Whether endpoint `index` trails the median by more than `max_lag_blocks`
+    pub fn is_degraded(&self, index: usize) -> bool {
+        self.is_degraded_at(index, Instant::now())
+    }
+
+    fn record_at(&self, index: usize, height: u64, now: Instant) {
+        if let Some(slot) = self.heights.lock().get_mut(index) {
+            *slot = Some((height, now));
+        }
+    }
+
+    fn fresh(&self, sample: Option<(u64, Instant)>, now: Instant) -> Option<u64> {
+        let (height, seen) = sample?;
+        let max_age = Duration::from_secs(self.config.stale_after_secs);
+        (now.saturating_duration_since(seen) <= max_age).then_some(height)
+    }
+
+    fn height_at(&self, index: usize, now: Instant) -> Option<u64> {
+        let sample = self.heights.lock().get(index).copied().flatten();
+        self.fresh(sample, now)
+    }
+
+    fn median_at(&self, now: Instant) -> Option<u64> {
+        let mut heights: Vec<u64> = self
+            .heights
+            .lock()
+            .iter()
+            .filter_map(|sample| self.fresh(*sample, now))
+            .collect();
+        if heights.is_empty() {
+            return None;
+        }
+        heights.sort_unstable();
+        Some(heights[heights.len() / 2])
+    }
+
+    fn lag_at(&self, index: usize, now: Instant) -> Option<u64> {
+        let height = self.height_at(index, now)?;
+        Some(self.median_at(now)?.saturating_sub(height))
+    }
+
+    fn is_degraded_at(&self, index: usize, now: Instant) -> bool {
+        self.config.enabled
+            && self
+                .lag_at(index, now)
+                .is_some_and(|lag| lag > self.config.max_lag_blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lagging_endpoint_degraded_until_caught_up() {
+        let tracker = HeadLagTracker::new(3, HeadLagConfig::default());
+        let now = Instant::now();
+        tracker.record_at(0, 1_000, now);
+        tracker.record_at(1, 1_002, now);
+        tracker.record_at(2, 950, now);
+
+        assert_eq!(tracker.median_at(now), Some(1_000));
+        assert_eq!(tracker.lag_at(1, now), Some(0));
+        assert_eq!(tracker.lag_at(2, now), Some(50));
+        assert!(tracker.is_degraded_at(2, now));
+        assert!(!tracker.is_degraded_at(0, now));
+
+        tracker.record_at(2, 995, now);
+        assert!(!tracker.is_degraded_at(2, now));
+    }
+
+    #[test]
+    fn test_two_endpoints_use_upper_median() {
+        let tracker = HeadLagTracker::new(2, HeadLagConfig::default());
+        let now = Instant::now();
+        tracker.record_at(0, 500, now);
+        tracker.record_at(1, 480, now);
+        assert!(tracker.is_degraded_at(1, now));
+        assert!(!tracker.is_degraded_at(0, now));
+    }
+
+    #[test]
+    fn test_stale_heights_do_not_count() {
+        let tracker = HeadLagTracker::new(3, HeadLagConfig::default());
+        let now = Instant::now();
+        tracker.record_at(2, 100, now);
+        let later = now + Duration::from_secs(301);
+        tracker.record_at(0, 1_000, later);
+        tracker.record_at(1, 1_001, later);
+
+        assert_eq!(tracker.height_at(2, later), None);
+        assert!(!tracker.is_degraded_at(2, later));
+        assert_eq!(tracker.median_at(later), Some(1_001));
+
+        let disabled = HeadLagTracker::new(
+            2,
+            HeadLagConfig {
+                enabled: false,
+                ..Default::default()
+            },
+        );
+        disabled.record_at(0, 1_000, now);
+        disabled.record_at(1, 1, now);
+        assert!(!disabled.is_degraded_at(1, now));
+        assert_eq!(block_height(&json!("0x3e8")), Some(1_000));
+    }
+}
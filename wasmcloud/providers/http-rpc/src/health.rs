@@ -0,0 +1,328 @@
+//! Liveness and readiness probes over HTTP
+//!
+//! The provider binary answers Kubernetes probes on `listen`:
+//! - `GET /healthz`: 200 for as long as the provider's runtime answers. Pools
+//!   with every circuit open are reported but do not fail the probe; a restart
+//!   does not bring a vendor back and would only drop the breaker history.
+//! - `GET /readyz`: 200 once at least one network is registered and every
+//!   network not in `optional_networks` is ready, else 503. A network is ready
+//!   with `min_healthy_endpoints` endpoints whose circuit is closed and which
+//!   are not paused, and, with `require_cache`, a cache backend that answers.
+//!
+//! Both answer a JSON report of every network: endpoint counts, each
+//! endpoint's circuit state and whether the cache backend answered a ping.
+
+use crate::circuit_breaker::CircuitState;
+use crate::endpoint_pool::EndpointPool;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Longest a cache ping may take before the backend counts as down
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Largest probe request read
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Probe server settings (`HTTP_RPC_HEALTH_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub enabled: bool,
+    /// Address the probe server listens on
+    pub listen: String,
+    /// Serving endpoints a network needs to be ready
+    pub min_healthy_endpoints: usize,
+    /// Networks that are reported but never hold back readiness
+    pub optional_networks: Vec<String>,
+    /// Hold back readiness while a network's cache backend is unreachable
+    pub require_cache: bool,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            listen: "0.0.0.0:8089".to_string(),
+            min_healthy_endpoints: 1,
+            optional_networks: Vec::new(),
+            require_cache: false,
+        }
+    }
+}
+
+/// Circuit state of one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointProbe {
+    pub endpoint: String,
+    pub circuit: CircuitState,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Readiness of one network's pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolReadiness {
+    pub network: String,
+    pub ready: bool,
+    /// Closed circuit and not paused
+    pub healthy_endpoints: usize,
+    pub total_endpoints: usize,
+    pub open_endpoints: usize,
+    pub half_open_endpoints: usize,
+    /// Whether the cache backend answered; `None` when caching is off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+    #[serde(default)]
+    pub optional: bool,
+    pub endpoints: Vec<EndpointProbe>,
+}
+
+/// Answer to a probe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub ready: bool,
+    pub networks: Vec<PoolReadiness>,
+}
+
+/// Readiness of `pool` under `config`
+pub async fn pool_readiness(pool: &EndpointPool, config: &HealthConfig) -> PoolReadiness {
+    let health = pool.health_status();
+    let endpoints: Vec<EndpointProbe> = health
+        .endpoints
+        .iter()
+        .map(|endpoint| EndpointProbe {
+            endpoint: crate::cost::endpoint_host(&endpoint.endpoint),
+            circuit: endpoint.circuit,
+            paused: endpoint.paused,
+        })
+        .collect();
+    let healthy = endpoints
+        .iter()
+        .filter(|endpoint| endpoint.circuit == CircuitState::Closed && !endpoint.paused)
+        .count();
+    let cache = tokio::time::timeout(PING_TIMEOUT, pool.cache().ping())
+        .await
+        .unwrap_or(Some(false));
+
+    let optional = config.optional_networks.contains(&health.network);
+    let cache_ok = !config.require_cache || cache != Some(false);
+    PoolReadiness {
+        ready: healthy >= config.min_healthy_endpoints.max(1) && cache_ok,
+        network: health.network,
+        healthy_endpoints: healthy,
+        total_endpoints: health.total_endpoints,
+        open_endpoints: health.unhealthy_endpoints,
+        half_open_endpoints: health.half_open_endpoints,
+        cache,
+        optional,
+        endpoints,
+    }
+}
+
+/// Report of every registered pool, sorted by network
+pub async fn report(
+    pools: &RwLock<HashMap<String, Arc<EndpointPool>>>,
+    config: &HealthConfig,
+) -> HealthReport {
+    let targets: Vec<Arc<EndpointPool>> = pools.read().await.values().cloned().collect();
+    let mut networks =
+        futures_util::future::join_all(targets.iter().map(|pool| pool_readiness(pool, config)))
+            .await;
+    networks.sort_by(|a, b| a.network.cmp(&b.network));
+    let ready = networks.iter().any(|pool| !pool.optional)
+        && networks.iter().all(|pool| pool.ready || pool.optional);
+    HealthReport { ready, networks }
+}
+
+/// Bind the probe server's listener
+pub async fn bind(config: &HealthConfig) -> Result<TcpListener> {
+    TcpListener::bind(&config.listen)
+        .await
+        .map_err(|e| anyhow!("Failed to bind health server on {}: {}", config.listen, e))
+}
+
+/// Answer probes on `listener` until the task is aborted
+pub async fn serve(
+    listener: TcpListener,
+    pools: Arc<RwLock<HashMap<String, Arc<EndpointPool>>>>,
+    config: HealthConfig,
+) {
+    let config = Arc::new(config);
+    loop {
+        let (socket, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Health server accept failed: {}", e);
+                continue;
+            }
+        };
+        let pools = pools.clone();
+        let config = config.clone();
+        tokio::spawn(async move {
+            if let Err(e) = answer(socket, &pools, &config).await {
+                debug!("Health probe failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn answer(
+    mut socket: TcpStream,
+    pools: &RwLock<HashMap<String, Arc<EndpointPool>>>,
+    config: &HealthConfig,
+) -> Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = socket.read(&mut buf).await?;
+        if n == 0 || request.len() + n > MAX_REQUEST_BYTES {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    // Query strings are ignored
+    let path = parts
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default();
+
+    let (status, body) = match (method, path) {
+        ("GET", "/healthz") => (
+            "200 OK",
+            serde_json::to_string(&report(pools, config).await)?,
+        ),
+        ("GET", "/readyz") => {
+            let report = report(pools, config).await;
+            let status = if report.ready {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            (status, serde_json::to_string(&report)?)
+        }
+        ("GET", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#.to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+    use crate::endpoint_pool::EndpointPoolConfig;
+
+    fn pool(network: &str, endpoints: &[&str]) -> Arc<EndpointPool> {
+        let config = EndpointPoolConfig {
+            endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+            cache: CacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Arc::new(EndpointPool::new(network.to_string(), config).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_paused_and_optional_networks_in_readiness() {
+        let pools = RwLock::new(HashMap::new());
+        let config = HealthConfig {
+            min_healthy_endpoints: 2,
+            optional_networks: vec!["solana".to_string()],
+            ..Default::default()
+        };
+        // Nothing registered yet: not ready
+        assert!(!report(&pools, &config).await.ready);
+
+        let ethereum = pool(
+            "ethereum",
+            &["http://a.example:8545", "http://b.example:8545"],
+        );
+        pools
+            .write()
+            .await
+            .insert("ethereum".to_string(), ethereum.clone());
+        let solana = pool("solana", &["http://c.example:8899"]);
+        pools.write().await.insert("solana".to_string(), solana);
+
+        // Solana has one endpoint of the two needed, but is optional
+        let report_now = report(&pools, &config).await;
+        assert!(report_now.ready);
+        assert_eq!(report_now.networks[0].network, "ethereum");
+        assert_eq!(report_now.networks[0].healthy_endpoints, 2);
+        assert_eq!(report_now.networks[0].cache, None);
+        assert!(!report_now.networks[1].ready);
+        assert_eq!(report_now.networks[1].endpoints[0].endpoint, "c.example");
+
+        assert!(ethereum.set_paused("http://b.example:8545", true));
+        let report_now = report(&pools, &config).await;
+        assert!(!report_now.ready);
+        assert_eq!(report_now.networks[0].healthy_endpoints, 1);
+        assert!(report_now.networks[0].endpoints[1].paused);
+    }
+
+    #[tokio::test]
+    async fn test_probe_status_codes() {
+        let pools = Arc::new(RwLock::new(HashMap::new()));
+        let listener = bind(&HealthConfig {
+            listen: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(listener, pools.clone(), HealthConfig::default()));
+
+        let client = reqwest::Client::new();
+        let status = |path: &'static str| {
+            let request = client.get(format!("{}{}", url, path));
+            async move { request.send().await.unwrap().status().as_u16() }
+        };
+        assert_eq!(status("/healthz").await, 200);
+        assert_eq!(status("/readyz").await, 503);
+        assert_eq!(status("/metrics").await, 404);
+
+        pools.write().await.insert(
+            "ethereum".to_string(),
+            pool("ethereum", &["http://a.example:8545"]),
+        );
+        assert_eq!(status("/readyz?verbose").await, 200);
+        let report: HealthReport = client
+            .get(format!("{}/healthz", url))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            report.networks[0].endpoints[0].circuit,
+            CircuitState::Closed
+        );
+        server.abort();
+    }
+}
@@ -0,0 +1,167 @@
+//! Hedged requests
+//!
+//! A slow endpoint answer dominates tail latency even when the pool has a fast
+//! healthy endpoint idle. With hedging enabled, a call that has not been
+//! answered after the hedge delay is also sent to a second endpoint; the first
+//! success wins and the other request is cancelled.
+//!
+//! The delay is a percentile (p95 by default) of the pool's recent successful
+//! latencies, clamped to `min_delay_ms..=max_delay_ms`, so only the slowest
+//! calls pay for a second request. Until `min_samples` latencies are known no
+//! call is hedged. Write methods listed in `skip_methods` are never hedged.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Hedging settings (`HTTP_RPC_HEDGE_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgeConfig {
+    pub enabled: bool,
+    /// Latency percentile (0.0-1.0) after which a call is hedged
+    pub percentile: f64,
+    pub min_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Recent latencies the percentile is taken over
+    pub window: usize,
+    /// Latencies needed before the first hedge
+    pub min_samples: usize,
+    /// Methods never sent twice
+    pub skip_methods: Vec<String>,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            percentile: 0.95,
+            min_delay_ms: 50,
+            max_delay_ms: 2_000,
+            window: 200,
+            min_samples: 20,
+            skip_methods: vec![
+                "eth_sendRawTransaction".to_string(),
+                "eth_sendTransaction".to_string(),
+            ],
+        }
+    }
+}
+
+impl HedgeConfig {
+    /// Delay before hedging a call of `method`; `None` when it is not hedged
+    pub fn delay(&self, method: &str, latencies: &LatencyWindow) -> Option<Duration> {
+        if !self.enabled || self.skip_methods.iter().any(|m| m == method) {
+            return None;
+        }
+        if latencies.len() < self.min_samples.max(1) {
+            return None;
+        }
+        let delay = latencies.percentile(self.percentile)?;
+        let min = Duration::from_millis(self.min_delay_ms);
+        let max = Duration::from_millis(self.max_delay_ms.max(self.min_delay_ms));
+        Some(delay.clamp(min, max))
+    }
+}
+
+/// Rolling window of successful call latencies
+pub struct LatencyWindow {
+    samples: Mutex<VecDeque<Duration>>,
+    capacity: usize,
+}
+
+impl LatencyWindow {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn record(&self, latency: Duration) {
+        let mut samples = self.samples.lock();
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Latency at percentile `p` (nearest rank)
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        let mut sorted: Vec<Duration> = self.samples.lock().iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+        let rank = (p.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(millis: impl IntoIterator<Item = u64>) -> LatencyWindow {
+        let window = LatencyWindow::new(100);
+        for ms in millis {
+            window.record(Duration::from_millis(ms));
+        }
+        window
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let latencies = window(1..=100);
+        assert_eq!(latencies.percentile(0.95), Some(Duration::from_millis(95)));
+        assert_eq!(latencies.percentile(0.5), Some(Duration::from_millis(50)));
+        assert_eq!(latencies.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(latencies.percentile(1.0), Some(Duration::from_millis(100)));
+        assert_eq!(LatencyWindow::new(10).percentile(0.95), None);
+
+        // The window keeps the most recent samples only
+        latencies.record(Duration::from_millis(500));
+        assert_eq!(latencies.len(), 100);
+        assert_eq!(latencies.percentile(0.0), Some(Duration::from_millis(2)));
+        assert_eq!(latencies.percentile(1.0), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_delay_clamped_and_gated() {
+        let config = HedgeConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let latencies = window(1..=100);
+        assert_eq!(
+            config.delay("eth_call", &latencies),
+            Some(Duration::from_millis(95))
+        );
+        assert_eq!(config.delay("eth_sendRawTransaction", &latencies), None);
+
+        let fast = window(std::iter::repeat_n(5, 30));
+        assert_eq!(
+            config.delay("eth_call", &fast),
+            Some(Duration::from_millis(50))
+        );
+
+        let slow = window(std::iter::repeat_n(10_000, 30));
+        assert_eq!(
+            config.delay("eth_call", &slow),
+            Some(Duration::from_millis(2_000))
+        );
+
+        assert_eq!(config.delay("eth_call", &window(1..=5)), None);
+        assert_eq!(HedgeConfig::default().delay("eth_call", &latencies), None);
+    }
+}
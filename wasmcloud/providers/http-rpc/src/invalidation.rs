@@ -0,0 +1,79 @@
+//! Block-aware cache invalidation
+//!
+//! Head-dependent answers (`eth_blockNumber`, calls against `"latest"`) are
+//! cached with the usual TTLs, which outlive a block on fast chains. On every
+//! new head the network's endpoint pool drops those entries and re-seeds
+//! `eth_blockNumber` with the new height, so a cached read is never more than
+//! one block behind.
+//!
+//! Heads come from the network's WebSocket `newHeads` subscription when one is
+//! registered (`HttpRpcProvider::start_invalidation`); other feeds, such as the
+//! `newheads.*` NATS subjects, call `HttpRpcProvider::on_new_head`. Both go
+//! through a [`HeadTracker`], so a head seen on several feeds, or a late
+//! duplicate, invalidates once.
+
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Highest head applied per network
+#[derive(Debug, Default)]
+pub struct HeadTracker {
+    heads: Mutex<HashMap<String, u64>>,
+}
+
+impl HeadTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a head; true if it is above the highest one seen for the network
+    pub fn observe(&self, network: &str, block_number: u64) -> bool {
+        let mut heads = self.heads.lock();
+        match heads.get(network) {
+            Some(head) if *head >= block_number => false,
+            _ => {
+                heads.insert(network.to_string(), block_number);
+                true
+            }
+        }
+    }
+
+    pub fn head(&self, network: &str) -> Option<u64> {
+        self.heads.lock().get(network).copied()
+    }
+}
+
+/// Block number of a `newHeads` notification
+pub fn head_number(header: &Value) -> Option<u64> {
+    let number = header.get("number")?.as_str()?;
+    u64::from_str_radix(number.trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_head_tracker_applies_each_head_once() {
+        let tracker = HeadTracker::new();
+        assert!(tracker.observe("ethereum", 100));
+        assert!(!tracker.observe("ethereum", 100));
+        assert!(!tracker.observe("ethereum", 99));
+        assert!(tracker.observe("ethereum", 101));
+        assert!(tracker.observe("avalanche", 5));
+        assert_eq!(tracker.head("ethereum"), Some(101));
+        assert_eq!(tracker.head("polygon"), None);
+    }
+
+    #[test]
+    fn test_head_number() {
+        assert_eq!(
+            head_number(&json!({"number": "0x12a05f2", "hash": "0xabc"})),
+            Some(19_531_250)
+        );
+        assert_eq!(head_number(&json!({"hash": "0xabc"})), None);
+        assert_eq!(head_number(&json!({"number": "0xzz"})), None);
+    }
+}
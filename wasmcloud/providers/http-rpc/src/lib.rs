@@ -4,30 +4,183 @@
 //! specifically designed for blockchain RPC calls without direct dependencies.
 //!
 //! Features:
-//! - Multi-endpoint rotation with failover
-//! - Redis-backed response caching
-//! - Circuit breaker pattern per endpoint
+//! - Multi-endpoint rotation with failover: round-robin, lowest-latency EWMA or
+//!   health-weighted random selection
+//! - Response caching behind an in-process LRU tier, in Redis (a single
+//!   instance, a cluster or a sentinel-managed master, with TLS and AUTH), a
+//!   NATS JetStream KV bucket or process memory
+//! - Circuit breaker pattern per endpoint, with a probe-limited half-open state,
+//!   jittered backoff after failed probes and state transitions published on
+//!   `metrics.http_rpc.circuit`
+//! - Identical concurrent calls coalesced into one upstream call
 //! - Automatic retry with exponential backoff
+//! - A fallback tier of rate-limited public endpoints per network, used only
+//!   while every primary endpoint's circuit is open
+//! - Per-method cost accounting against vendor billing models, with budget caps,
+//!   daily spend in Redis and budget warnings published on `metrics.http_rpc.budget`
+//! - Config-driven A/B routing between vendors with automatic rollback
+//! - Per-network overrides of timeouts, retries, breaker thresholds and cache
+//!   TTLs, from env, a JSON/YAML file, config values or link definitions
+//! - Periodic canary probes per endpoint, so quiet chains still detect endpoint rot
+//! - `eth_chainId` checks at registration and on an interval, quarantining
+//!   endpoints that serve the wrong chain
+//! - Per-endpoint block heights in pool health; endpoints lagging the median
+//!   are skipped until they catch up
+//! - JSON-RPC batching within each endpoint's max batch size
+//! - Token-bucket rate limits per endpoint, rotating past limited endpoints
+//!   before paid plans answer 429, and holding an endpoint for as long as its
+//!   429 or rate-limit error asks (`Retry-After`, vendor backoff hints)
+//! - Optional request hedging: calls slower than the pool's recent p95 are also
+//!   sent to a second endpoint and the first answer wins
+//! - Block-aware invalidation of cached `latest` reads on every new head
+//! - Jittered cache TTLs and a per-key refresh lock, so entries cached
+//!   together neither expire together nor send every caller upstream at once
+//! - Negative caching: not-found results and reverted or not-verified errors
+//!   served from the cache for a short TTL instead of re-asked upstream
+//! - Optional stale-while-revalidate: answers within a per-method staleness
+//!   budget served from the cache at once and refreshed in the background
+//! - Optional priority queues: realtime, backfill and bulk calls share a pool's
+//!   upstream concurrency by weight, so backfills cannot starve live traffic
+//! - Record mode keeping every upstream answer in JSON-lines files or the
+//!   `rpc_recordings` DuckLake table, and a replay mode serving them back
+//!   offline for deterministic integration tests
+//! - Multicall3 aggregation of plain `eth_call` lists into `aggregate3` calls,
+//!   falling back to individual calls where it is missing or fails
+//! - Consistent sessions pinning a sequence of calls to one endpoint and block,
+//!   so a block, its receipts and its traces come from the same view
+//! - Transaction and block traces over `debug_*` or `trace_*`, whichever each
+//!   endpoint supports, normalized into one flat call list
+//! - Typed blocks, receipts and logs with hex quantities parsed once in the
+//!   provider rather than in every actor
+//! - Block receipts in one round trip over `eth_getBlockReceipts`, falling back
+//!   to a batch of `eth_getTransactionReceipt` calls on endpoints without it
+//! - `eth_getLogs` over large ranges split into block-range chunks on range or
+//!   result-limit errors, with the chunks' logs stitched back in order
+//! - Calls for blocks older than full nodes keep routed to archive-tagged
+//!   endpoints; latest-state calls go to full nodes first
+//! - HTTP 200 answers that are not JSON-RPC (rate-limit pages, wrong result
+//!   types) rejected and counted against the endpoint's circuit breaker
+//! - gzip/brotli response compression negotiated and decompressed as answers
+//!   stream in, with a per-endpoint switch for endpoints that mishandle it
+//! - Request and per-method response size limits; oversized answers are
+//!   abandoned mid-stream instead of buffered
+//! - Solana JSON-RPC (`getBlock`, `getTransaction`, `getSignaturesForAddress`)
+//!   on the same pools, cached by commitment level and probed with `getSlot`
+//!   and `getHealth`
+//! - Outbound HTTP or SOCKS5 proxies and pinned host addresses per endpoint,
+//!   for deployments egressing through corporate proxies or allow-listed IPs
+//! - Requests in flight, keep-alive, HTTP/2 and connection pool size per
+//!   endpoint, for vendors that throttle on concurrent connections
+//! - API key header, bearer or basic auth per endpoint, or HMAC/JWT signing of
+//!   each request for private gateways, with secrets from env or Redis re-read
+//!   for key rotation
+//! - Endpoints added, removed, paused and resumed at runtime over
+//!   `rpc.endpoints.{network}.{action}`, persisted in Redis across restarts
+//! - `/healthz` and `/readyz` probes reporting each network's serving
+//!   endpoints, circuit states and cache connectivity, ready only while every
+//!   required network can serve
+//! - JSON-RPC calls and batches over NATS request/reply on
+//!   `rpc.request.{network}.{subnet}`, for components outside wasmCloud
+//! - WebSocket `eth_subscribe` streams (new heads, logs, pending transactions)
+//!   with reconnect and re-subscribe on failover, so actors need not poll
+//! - Pending transactions from `newPendingTransactions` or `txpool_content`
+//!   published with full bodies on `mempool.{network}.{subnet}.pending`
 
 use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 use wasmcloud_provider_sdk::Provider;
 
 // New modules for enhanced functionality
+pub mod archive;
+pub mod auth;
+pub mod batch;
 pub mod cache;
+pub mod cache_backend;
+pub mod canary;
+pub mod chain_id;
 pub mod circuit_breaker;
+pub mod compression;
+pub mod connection;
+pub mod control;
+pub mod cost;
+pub mod egress;
 pub mod endpoint_pool;
-
-use cache::CacheConfig;
-use circuit_breaker::CircuitBreakerConfig;
+pub mod eth_types;
+pub mod fallback;
+pub mod head_lag;
+pub mod health;
+pub mod hedge;
+pub mod invalidation;
+pub mod logs;
+pub mod mempool;
+pub mod multicall;
+pub mod nats_rpc;
+pub mod negative_cache;
+pub mod network_overlay;
+pub mod priority;
+pub mod rate_limit;
+pub mod receipts;
+pub mod recording;
+pub mod redis_topology;
+pub mod selection;
+pub mod session;
+pub mod singleflight;
+pub mod size_limit;
+pub mod solana;
+pub mod split;
+pub mod swr;
+pub mod trace;
+pub mod validation;
+pub mod ws;
+
+use archive::ArchiveConfig;
+use auth::{AuthConfig, AuthStatus};
+use batch::{BatchConfig, RpcBatchRequest, RpcBatchResponse};
+use cache::{CacheConfig, CacheTtl};
+use cache_backend::CacheBackendConfig;
+use canary::{CanaryConfig, CanaryStatus};
+use chain_id::{ChainIdConfig, ChainIdStatus};
+use circuit_breaker::{CircuitBreakerConfig, CircuitTransition, TRANSITIONS_SUBJECT};
+use compression::CompressionConfig;
+use connection::ConnectionConfig;
+use control::{EndpointAction, EndpointCommand, EndpointSet, EndpointStore, CONTROL_SUBJECTS};
+use cost::{BudgetWarning, CostConfig, EndpointCostStatus, BUDGET_SUBJECT};
+use egress::EgressConfig;
 use endpoint_pool::{EndpointPool, EndpointPoolConfig, PoolHealthStatus, RpcRequest};
+use eth_types::{Block, Log, TransactionReceipt};
+use fallback::FallbackConfig;
+use head_lag::HeadLagConfig;
+use health::{HealthConfig, HealthReport};
+use hedge::HedgeConfig;
+use invalidation::HeadTracker;
+use logs::LogsConfig;
+use mempool::MempoolConfig;
+use multicall::MulticallConfig;
+use nats_rpc::{NatsRpcConfig, REQUEST_SUBJECTS};
+use negative_cache::NegativeCacheConfig;
+use network_overlay::NetworkOverlays;
+use priority::PriorityConfig;
+use rate_limit::{RateLimitConfig, RateLimitStatus};
+use recording::{Recorder, RecordingConfig, RecordingMode};
+use redis_topology::RedisTopologyConfig;
+use selection::{EndpointScore, SelectionConfig};
+use session::ConsistentSession;
+use size_limit::SizeLimitConfig;
+use solana::{Commitment, SolanaConfig};
+use split::{SplitConfig, SplitStatus};
+use swr::{Revalidation, SwrConfig};
+use trace::{Trace, TraceTarget};
+use validation::ValidationConfig;
+use ws::{SubscriptionKind, WsConfig, WsNotification, WsPool, WsPoolStatus};
 
 /// HTTP RPC Provider
 ///
@@ -37,8 +190,62 @@ pub struct HttpRpcProvider {
     /// Endpoint pools per network (ethereum, polygon, etc.)
     endpoint_pools: Arc<RwLock<HashMap<String, Arc<EndpointPool>>>>,
 
+    /// WebSocket subscription pools per network
+    ws_pools: Arc<RwLock<HashMap<String, Arc<WsPool>>>>,
+
     /// Configuration
     config: Arc<RwLock<ProviderConfig>>,
+
+    /// Background canary probe loop
+    canary_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Background chain id verification loop
+    chain_id_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Circuit breaker state transitions of every pool
+    transitions: broadcast::Sender<CircuitTransition>,
+
+    /// Background loop publishing transitions to NATS
+    transition_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Budget warnings of every pool
+    budget_warnings: broadcast::Sender<BudgetWarning>,
+
+    /// Background loop publishing budget warnings to NATS
+    budget_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Background loop writing spend to Redis
+    usage_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Background loop applying `rpc.endpoints.*` control messages
+    control_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Background loop answering `rpc.request.*` JSON-RPC requests
+    request_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Background loop re-reading endpoint secrets
+    auth_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Calls every pool answered stale
+    revalidations: broadcast::Sender<Revalidation>,
+
+    /// Background loop refreshing stale answers
+    revalidation_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Server answering `/healthz` and `/readyz` probes
+    health_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+
+    /// Highest head applied per network, shared by every head feed
+    head_tracker: Arc<HeadTracker>,
+
+    /// Recorder or replay source shared by every pool; none when off
+    recorder: Option<Arc<Recorder>>,
+
+    /// Per-network loops feeding WebSocket heads to cache invalidation
+    invalidation_tasks: parking_lot::Mutex<Vec<JoinHandle<()>>>,
+
+    /// Per-network pending-transaction feeds
+    mempool_tasks: parking_lot::Mutex<Vec<JoinHandle<()>>>,
 }
 
 /// Provider configuration
@@ -53,125 +260,1606 @@ pub struct ProviderConfig {
     pub circuit_breaker_failure_threshold: u32,
     pub circuit_breaker_success_threshold: u32,
     pub circuit_breaker_timeout_seconds: u64,
+    /// Half-open probes in flight at once, longest open period after failed
+    /// probes, and the spread (0.0-1.0) applied to open periods
+    #[serde(default = "default_half_open_max_probes")]
+    pub circuit_breaker_half_open_max_probes: u32,
+    #[serde(default = "default_max_timeout_seconds")]
+    pub circuit_breaker_max_timeout_seconds: u64,
+    #[serde(default = "default_jitter")]
+    pub circuit_breaker_jitter: f64,
+
+    // NATS server circuit transitions are published to; not published when unset
+    #[serde(default)]
+    pub nats_url: Option<String>,
 
     // Cache settings
     pub cache_enabled: bool,
     pub cache_redis_url: String,
+    /// Cluster or sentinel mode, TLS and AUTH for the cache's Redis
+    #[serde(default)]
+    pub cache_redis: RedisTopologyConfig,
+    /// Store behind the in-process tier: Redis, NATS KV or memory
+    #[serde(default)]
+    pub cache_backend: CacheBackendConfig,
     pub cache_default_ttl: u64,
     pub cache_block_ttl: u64,
     pub cache_tx_ttl: u64,
+    /// Per-method TTL overrides on top of the built-ins (seconds, "forever" or "never")
+    #[serde(default)]
+    pub cache_method_ttls: HashMap<String, CacheTtl>,
+    /// In-process LRU tier size (0 disables it) and entry lifetime
+    #[serde(default = "default_memory_capacity")]
+    pub cache_memory_capacity: usize,
+    #[serde(default = "default_memory_ttl")]
+    pub cache_memory_ttl: u64,
+    /// Largest random share taken off cache TTLs
+    #[serde(default = "default_ttl_jitter")]
+    pub cache_ttl_jitter: f64,
+    /// Refresh lock lifetime (0 disables it) and how long others wait on it
+    /// (milliseconds)
+    #[serde(default = "default_refresh_lock_ms")]
+    pub cache_refresh_lock_ms: u64,
+    #[serde(default = "default_refresh_wait_ms")]
+    pub cache_refresh_wait_ms: u64,
+
+    // Cost accounting (vendor weights, per-endpoint budgets)
+    #[serde(default)]
+    pub cost: CostConfig,
+
+    // A/B vendor splits, keyed by network
+    #[serde(default)]
+    pub splits: HashMap<String, SplitConfig>,
+
+    // Synthetic canary probes, per network
+    #[serde(default)]
+    pub canary: CanaryConfig,
+
+    // Batch size limits per endpoint host
+    #[serde(default)]
+    pub batch: BatchConfig,
+
+    // WebSocket reconnect and buffering settings
+    #[serde(default)]
+    pub ws: WsConfig,
+
+    // Requests per second and burst per endpoint host
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    // Hedging of slow calls to a second endpoint
+    #[serde(default)]
+    pub hedge: HedgeConfig,
+
+    // Endpoint selection strategy and EWMA weight
+    #[serde(default)]
+    pub selection: SelectionConfig,
+
+    // Expected chain id per network and check interval
+    #[serde(default)]
+    pub chain_id: ChainIdConfig,
+
+    // Block-height lag at which an endpoint is degraded
+    #[serde(default)]
+    pub head_lag: HeadLagConfig,
+
+    // Header, bearer, basic auth or request signing per endpoint host or URL
+    #[serde(default)]
+    pub auth: AuthConfig,
+
+    // eth_getLogs range splitting
+    #[serde(default)]
+    pub logs: LogsConfig,
+
+    // Full or archive tag per endpoint host or URL, and full-node history depth
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+
+    // JSON-RPC envelope and result type checks, extra provider error strings
+    #[serde(default)]
+    pub validation: ValidationConfig,
+
+    // gzip/brotli negotiation, on or off per endpoint host or URL
+    #[serde(default)]
+    pub compression: CompressionConfig,
+
+    // Largest request body, and largest answer per method
+    #[serde(default)]
+    pub size_limit: SizeLimitConfig,
+
+    // Short-lived caching of not-found and deterministic error answers
+    #[serde(default)]
+    pub negative_cache: NegativeCacheConfig,
+
+    // Multicall3 address per network and calls per aggregate3
+    #[serde(default)]
+    pub multicall: MulticallConfig,
+
+    // Networks whose pending transactions are published, subscribed or polled
+    #[serde(default)]
+    pub mempool: MempoolConfig,
+
+    // JSON-RPC over `rpc.request.{network}.{subnet}`, and pool overrides
+    #[serde(default)]
+    pub nats_rpc: NatsRpcConfig,
+
+    // Public endpoints per network behind the primaries, and their rate limit
+    #[serde(default)]
+    pub fallback: FallbackConfig,
+
+    // Stale-while-revalidate staleness budgets per method
+    #[serde(default)]
+    pub swr: SwrConfig,
+
+    // Outbound proxies and resolver overrides per endpoint
+    #[serde(default)]
+    pub egress: EgressConfig,
+
+    // In-flight limits and connection pooling per endpoint
+    #[serde(default)]
+    pub connection: ConnectionConfig,
+
+    // Upstream concurrency per pool, shared by priority class
+    #[serde(default)]
+    pub priority: PriorityConfig,
+
+    // Recording of upstream answers, or replay of recorded ones
+    #[serde(default)]
+    pub recording: RecordingConfig,
+
+    // Liveness and readiness probe server
+    #[serde(default)]
+    pub health: HealthConfig,
+
+    // Solana networks and their commitment-aware caching
+    #[serde(default)]
+    pub solana: SolanaConfig,
+
+    // Per-network overrides of the settings above, keyed by network
+    #[serde(default)]
+    pub networks: NetworkOverlays,
+}
+
+fn default_half_open_max_probes() -> u32 {
+    CircuitBreakerConfig::default().half_open_max_probes
+}
+
+fn default_max_timeout_seconds() -> u64 {
+    CircuitBreakerConfig::default().max_timeout.as_secs()
+}
+
+fn default_jitter() -> f64 {
+    CircuitBreakerConfig::default().jitter
+}
+
+fn default_memory_capacity() -> usize {
+    CacheConfig::default().memory_capacity
+}
+
+fn default_memory_ttl() -> u64 {
+    CacheConfig::default().memory_ttl
+}
+
+fn default_ttl_jitter() -> f64 {
+    CacheConfig::default().ttl_jitter
+}
+
+fn default_refresh_lock_ms() -> u64 {
+    CacheConfig::default().refresh_lock_ms
+}
+
+fn default_refresh_wait_ms() -> u64 {
+    CacheConfig::default().refresh_wait_ms
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 30,
+            max_retries: 3,
+            retry_delay_ms: 1000,
+            user_agent: "WasmCloud-HTTP-RPC-Provider/1.0.0".to_string(),
+
+            // Circuit breaker defaults
+            circuit_breaker_failure_threshold: 5,
+            circuit_breaker_success_threshold: 2,
+            circuit_breaker_timeout_seconds: 30,
+            circuit_breaker_half_open_max_probes: default_half_open_max_probes(),
+            circuit_breaker_max_timeout_seconds: default_max_timeout_seconds(),
+            circuit_breaker_jitter: default_jitter(),
+
+            nats_url: None,
+
+            // Cache defaults
+            cache_enabled: true,
+            cache_redis_url: "redis://redis.ekko.svc.cluster.local:6379".to_string(),
+            cache_redis: RedisTopologyConfig::default(),
+            cache_backend: CacheBackendConfig::default(),
+            cache_default_ttl: 60,
+            cache_block_ttl: 300,
+            cache_tx_ttl: 3600,
+            cache_method_ttls: HashMap::new(),
+            cache_memory_capacity: default_memory_capacity(),
+            cache_memory_ttl: default_memory_ttl(),
+            cache_ttl_jitter: default_ttl_jitter(),
+            cache_refresh_lock_ms: default_refresh_lock_ms(),
+            cache_refresh_wait_ms: default_refresh_wait_ms(),
+
+            cost: CostConfig::default(),
+            splits: HashMap::new(),
+            canary: CanaryConfig::default(),
+            batch: BatchConfig::default(),
+            ws: WsConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            hedge: HedgeConfig::default(),
+            selection: SelectionConfig::default(),
+            chain_id: ChainIdConfig::default(),
+            head_lag: HeadLagConfig::default(),
+            auth: AuthConfig::default(),
+            logs: LogsConfig::default(),
+            archive: ArchiveConfig::default(),
+            validation: ValidationConfig::default(),
+            compression: CompressionConfig::default(),
+            size_limit: SizeLimitConfig::default(),
+            negative_cache: NegativeCacheConfig::default(),
+            multicall: MulticallConfig::default(),
+            mempool: MempoolConfig::default(),
+            nats_rpc: NatsRpcConfig::default(),
+            fallback: FallbackConfig::default(),
+            swr: SwrConfig::default(),
+            egress: EgressConfig::default(),
+            connection: ConnectionConfig::default(),
+            priority: PriorityConfig::default(),
+            recording: RecordingConfig::default(),
+            health: HealthConfig::default(),
+            solana: SolanaConfig::default(),
+            networks: NetworkOverlays::new(),
+        }
+    }
+}
+
+impl ProviderConfig {
+    /// Load configuration from environment variables with fallback to defaults
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            timeout_seconds: std::env::var("HTTP_RPC_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.timeout_seconds),
+            max_retries: std::env::var("HTTP_RPC_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.max_retries),
+            retry_delay_ms: std::env::var("HTTP_RPC_RETRY_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.retry_delay_ms),
+            user_agent: std::env::var("HTTP_RPC_USER_AGENT").unwrap_or(default.user_agent),
+
+            circuit_breaker_failure_threshold: std::env::var("HTTP_RPC_CB_FAILURE_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.circuit_breaker_failure_threshold),
+            circuit_breaker_success_threshold: std::env::var("HTTP_RPC_CB_SUCCESS_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.circuit_breaker_success_threshold),
+            circuit_breaker_timeout_seconds: std::env::var("HTTP_RPC_CB_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.circuit_breaker_timeout_seconds),
+            circuit_breaker_half_open_max_probes: std::env::var("HTTP_RPC_CB_HALF_OPEN_MAX_PROBES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.circuit_breaker_half_open_max_probes),
+            circuit_breaker_max_timeout_seconds: std::env::var("HTTP_RPC_CB_MAX_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.circuit_breaker_max_timeout_seconds),
+            circuit_breaker_jitter: std::env::var("HTTP_RPC_CB_JITTER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.circuit_breaker_jitter),
+
+            nats_url: std::env::var("NATS_URL").ok().or(default.nats_url),
+
+            cache_enabled: std::env::var("HTTP_RPC_CACHE_ENABLED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_enabled),
+            cache_redis_url: std::env::var("HTTP_RPC_CACHE_REDIS_URL")
+                .unwrap_or(default.cache_redis_url),
+            cache_redis: std::env::var("HTTP_RPC_CACHE_REDIS_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.cache_redis),
+            cache_backend: std::env::var("HTTP_RPC_CACHE_BACKEND_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.cache_backend),
+            cache_default_ttl: std::env::var("HTTP_RPC_CACHE_DEFAULT_TTL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_default_ttl),
+            cache_block_ttl: std::env::var("HTTP_RPC_CACHE_BLOCK_TTL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_block_ttl),
+            cache_tx_ttl: std::env::var("HTTP_RPC_CACHE_TX_TTL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_tx_ttl),
+            cache_method_ttls: std::env::var("HTTP_RPC_CACHE_METHOD_TTLS")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.cache_method_ttls),
+            cache_memory_capacity: std::env::var("HTTP_RPC_CACHE_MEMORY_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_memory_capacity),
+            cache_memory_ttl: std::env::var("HTTP_RPC_CACHE_MEMORY_TTL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_memory_ttl),
+            cache_ttl_jitter: std::env::var("HTTP_RPC_CACHE_TTL_JITTER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_ttl_jitter),
+            cache_refresh_lock_ms: std::env::var("HTTP_RPC_CACHE_REFRESH_LOCK_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_refresh_lock_ms),
+            cache_refresh_wait_ms: std::env::var("HTTP_RPC_CACHE_REFRESH_WAIT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.cache_refresh_wait_ms),
+
+            cost: std::env::var("HTTP_RPC_COST_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.cost),
+
+            splits: std::env::var("HTTP_RPC_SPLIT_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.splits),
+
+            canary: std::env::var("HTTP_RPC_CANARY_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.canary),
+
+            batch: std::env::var("HTTP_RPC_BATCH_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.batch),
+
+            ws: std::env::var("HTTP_RPC_WS_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.ws),
+
+            rate_limit: std::env::var("HTTP_RPC_RATE_LIMIT_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.rate_limit),
+
+            hedge: std::env::var("HTTP_RPC_HEDGE_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.hedge),
+
+            selection: std::env::var("HTTP_RPC_SELECTION_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.selection),
+
+            chain_id: std::env::var("HTTP_RPC_CHAIN_ID_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.chain_id),
+
+            head_lag: std::env::var("HTTP_RPC_HEAD_LAG_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.head_lag),
+
+            auth: std::env::var("HTTP_RPC_AUTH_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.auth),
+
+            logs: std::env::var("HTTP_RPC_LOGS_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.logs),
+
+            archive: std::env::var("HTTP_RPC_ARCHIVE_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.archive),
+
+            validation: std::env::var("HTTP_RPC_VALIDATION_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.validation),
+
+            compression: std::env::var("HTTP_RPC_COMPRESSION_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.compression),
+
+            size_limit: std::env::var("HTTP_RPC_SIZE_LIMIT_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.size_limit),
+
+            negative_cache: std::env::var("HTTP_RPC_NEGATIVE_CACHE_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.negative_cache),
+
+            multicall: std::env::var("HTTP_RPC_MULTICALL_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.multicall),
+
+            mempool: std::env::var("HTTP_RPC_MEMPOOL_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.mempool),
+
+            nats_rpc: std::env::var("HTTP_RPC_NATS_RPC_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.nats_rpc),
+
+            fallback: std::env::var("HTTP_RPC_FALLBACK_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.fallback),
+
+            swr: std::env::var("HTTP_RPC_SWR_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.swr),
+
+            egress: std::env::var("HTTP_RPC_EGRESS_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.egress),
+
+            connection: std::env::var("HTTP_RPC_CONNECTION_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.connection),
+
+            priority: std::env::var("HTTP_RPC_PRIORITY_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.priority),
+
+            recording: std::env::var("HTTP_RPC_RECORDING_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.recording),
+
+            health: std::env::var("HTTP_RPC_HEALTH_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.health),
+
+            solana: std::env::var("HTTP_RPC_SOLANA_CONFIG")
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or(default.solana),
+
+            networks: Self::network_overlays_from_env(),
+        }
+    }
+
+    /// `HTTP_RPC_NETWORKS_CONFIG` overlays, then `HTTP_RPC_NETWORKS_FILE`'s
+    fn network_overlays_from_env() -> NetworkOverlays {
+        let mut overlays: NetworkOverlays = std::env::var("HTTP_RPC_NETWORKS_CONFIG")
+            .ok()
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default();
+        if let Ok(path) = std::env::var("HTTP_RPC_NETWORKS_FILE") {
+            match network_overlay::load(std::path::Path::new(&path)) {
+                Ok(file) => overlays.extend(file),
+                Err(e) => warn!("Network overlays not loaded: {}", e),
+            }
+        }
+        overlays
+    }
+
+    /// This configuration with `network`'s overlay applied
+    pub fn for_network(&self, network: &str) -> ProviderConfig {
+        let mut config = self.clone();
+        let Some(overlay) = self.networks.get(network) else {
+            return config;
+        };
+        if let Some(timeout) = overlay.timeout_seconds {
+            config.timeout_seconds = timeout;
+        }
+        if let Some(retries) = overlay.max_retries {
+            config.max_retries = retries;
+        }
+        if let Some(threshold) = overlay.circuit_breaker_failure_threshold {
+            config.circuit_breaker_failure_threshold = threshold;
+        }
+        if let Some(threshold) = overlay.circuit_breaker_success_threshold {
+            config.circuit_breaker_success_threshold = threshold;
+        }
+        if let Some(timeout) = overlay.circuit_breaker_timeout_seconds {
+            config.circuit_breaker_timeout_seconds = timeout;
+        }
+        if let Some(timeout) = overlay.circuit_breaker_max_timeout_seconds {
+            config.circuit_breaker_max_timeout_seconds = timeout;
+        }
+        if let Some(ttl) = overlay.cache_default_ttl {
+            config.cache_default_ttl = ttl;
+        }
+        if let Some(ttl) = overlay.cache_block_ttl {
+            config.cache_block_ttl = ttl;
+        }
+        if let Some(ttl) = overlay.cache_tx_ttl {
+            config.cache_tx_ttl = ttl;
+        }
+        config.cache_method_ttls.extend(
+            overlay
+                .cache_method_ttls
+                .iter()
+                .map(|(method, ttl)| (method.clone(), *ttl)),
+        );
+        config
+    }
 }
 
-impl Default for ProviderConfig {
-    fn default() -> Self {
-        Self {
-            timeout_seconds: 30,
-            max_retries: 3,
-            retry_delay_ms: 1000,
-            user_agent: "WasmCloud-HTTP-RPC-Provider/1.0.0".to_string(),
+// RpcRequest, RpcResponse, and RpcError are now defined in endpoint_pool module
+
+impl HttpRpcProvider {
+    /// Create provider from wasmCloud host data (for WADM deployment)
+    ///
+    /// Loads configuration from environment variables and creates provider instance.
+    /// This is the entry point when deployed via wasmCloud/WADM.
+    pub fn from_host_data(_host_data: wasmcloud_provider_sdk::HostData) -> Result<Self> {
+        info!("🌟 Creating HTTP RPC Provider from wasmCloud host data");
+
+        // Load configuration from environment variables
+        let config = ProviderConfig::from_env();
+
+        Ok(Self::with_config(config))
+    }
+
+    /// Create a new HTTP RPC provider with custom configuration
+    pub fn with_config(config: ProviderConfig) -> Self {
+        let recorder = (config.recording.mode != RecordingMode::Off).then(|| {
+            Arc::new(Recorder::new(
+                config.recording.clone(),
+                config.nats_url.clone(),
+            ))
+        });
+        Self {
+            endpoint_pools: Arc::new(RwLock::new(HashMap::new())),
+            ws_pools: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(config)),
+            canary_task: parking_lot::Mutex::new(None),
+            chain_id_task: parking_lot::Mutex::new(None),
+            transitions: broadcast::channel(256).0,
+            transition_task: parking_lot::Mutex::new(None),
+            budget_warnings: broadcast::channel(256).0,
+            budget_task: parking_lot::Mutex::new(None),
+            usage_task: parking_lot::Mutex::new(None),
+            control_task: parking_lot::Mutex::new(None),
+            request_task: parking_lot::Mutex::new(None),
+            auth_task: parking_lot::Mutex::new(None),
+            revalidations: broadcast::channel(1024).0,
+            revalidation_task: parking_lot::Mutex::new(None),
+            health_task: parking_lot::Mutex::new(None),
+            head_tracker: Arc::new(HeadTracker::new()),
+            recorder,
+            invalidation_tasks: parking_lot::Mutex::new(Vec::new()),
+            mempool_tasks: parking_lot::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create a new HTTP RPC provider with default configuration
+    pub fn new() -> Self {
+        Self::with_config(ProviderConfig::default())
+    }
+
+    /// Register RPC endpoints for a network
+    pub async fn register_endpoints(&self, network: &str, endpoints: Vec<String>) -> Result<()> {
+        self.registry().register(network, endpoints).await?;
+        Ok(())
+    }
+
+    /// Handles for (re)building pools from background loops
+    fn registry(&self) -> PoolRegistry {
+        PoolRegistry {
+            config: self.config.clone(),
+            pools: self.endpoint_pools.clone(),
+            transitions: self.transitions.clone(),
+            budget_warnings: self.budget_warnings.clone(),
+            revalidations: self.revalidations.clone(),
+            recorder: self.recorder.clone(),
+        }
+    }
+
+    /// Merge per-network overlays into the configuration, rebuilding the
+    /// pools of the networks they cover
+    pub async fn apply_network_overlays(&self, overlays: NetworkOverlays) -> Result<()> {
+        if overlays.is_empty() {
+            return Ok(());
+        }
+        let networks: Vec<String> = overlays.keys().cloned().collect();
+        self.config.write().await.networks.extend(overlays);
+
+        let registry = self.registry();
+        for network in networks {
+            let Some(pool) = self.endpoint_pools.read().await.get(&network).cloned() else {
+                continue;
+            };
+            let paused = pool.paused_endpoints();
+            let rebuilt = registry
+                .register(&network, pool.endpoints().to_vec())
+                .await?;
+            for endpoint in &paused {
+                rebuilt.set_paused(endpoint, true);
+            }
+            info!("Rebuilt endpoint pool for {} with its overlay", network);
+        }
+        Ok(())
+    }
+
+    /// Add, remove, pause or resume one endpoint of a running network and
+    /// persist the resulting set
+    pub async fn apply_endpoint_command(
+        &self,
+        network: &str,
+        action: EndpointAction,
+        endpoint: &str,
+    ) -> Result<EndpointSet> {
+        self.registry().apply(network, action, endpoint).await
+    }
+
+    /// Re-register every network whose endpoints were changed at runtime
+    pub async fn restore_endpoints(&self) {
+        let registry = self.registry();
+        let sets = match registry.store().await.load().await {
+            Ok(sets) => sets,
+            Err(e) => {
+                warn!("Failed to load persisted endpoint sets: {}", e);
+                return;
+            }
+        };
+        for (network, set) in sets {
+            match registry.register(&network, set.endpoints.clone()).await {
+                Ok(pool) => {
+                    for endpoint in &set.paused {
+                        pool.set_paused(endpoint, true);
+                    }
+                    info!(
+                        "Restored {} endpoints ({} paused) for {}",
+                        set.endpoints.len(),
+                        set.paused.len(),
+                        network
+                    );
+                }
+                Err(e) => warn!("Failed to restore endpoints for {}: {}", network, e),
+            }
+        }
+    }
+
+    /// Start (or restart) applying endpoint control messages from NATS
+    pub async fn start_endpoint_control(&self) {
+        let nats_url = self.config.read().await.nats_url.clone();
+        if let Some(previous) = self.control_task.lock().take() {
+            previous.abort();
+        }
+        let Some(nats_url) = nats_url else {
+            info!("No NATS_URL, endpoint control subjects not subscribed");
+            return;
+        };
+
+        let registry = self.registry();
+        let subject = subject_registry::prefixed(CONTROL_SUBJECTS);
+        let task = tokio::spawn(async move {
+            let client = match async_nats::connect(&nats_url).await {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("Failed to connect to NATS for endpoint control: {}", e);
+                    return;
+                }
+            };
+            let mut subscriber = match client.subscribe(subject.clone()).await {
+                Ok(subscriber) => subscriber,
+                Err(e) => {
+                    warn!("Failed to subscribe to {}: {}", subject, e);
+                    return;
+                }
+            };
+            info!("Listening for endpoint changes on {}", subject);
+
+            while let Some(message) = subscriber.next().await {
+                let reply = match registry
+                    .handle_control(message.subject.as_str(), &message.payload)
+                    .await
+                {
+                    Ok(set) => serde_json::to_value(&set).unwrap_or_default(),
+                    Err(e) => {
+                        warn!("Endpoint change on {} failed: {}", message.subject, e);
+                        serde_json::json!({ "error": e.to_string() })
+                    }
+                };
+                if let Some(reply_to) = message.reply {
+                    let payload = serde_json::to_vec(&reply).unwrap_or_default();
+                    if let Err(e) = client.publish(reply_to, payload.into()).await {
+                        warn!("Failed to reply to endpoint change: {}", e);
+                    }
+                }
+            }
+        });
+        *self.control_task.lock() = Some(task);
+    }
+
+    /// Start (or restart) answering JSON-RPC requests from NATS
+    pub async fn start_request_handler(&self) {
+        let (config, nats_url) = {
+            let config = self.config.read().await;
+            (config.nats_rpc.clone(), config.nats_url.clone())
+        };
+        if let Some(previous) = self.request_task.lock().take() {
+            previous.abort();
+        }
+        if !config.enabled {
+            info!("NATS JSON-RPC requests disabled");
+            return;
+        }
+        let Some(nats_url) = nats_url else {
+            info!("No NATS_URL, JSON-RPC request subjects not subscribed");
+            return;
+        };
+
+        let pools = self.endpoint_pools.clone();
+        let provider_config = self.config.clone();
+        let subject = subject_registry::prefixed(REQUEST_SUBJECTS);
+        let task = tokio::spawn(async move {
+            let client = match async_nats::connect(&nats_url).await {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("Failed to connect to NATS for JSON-RPC requests: {}", e);
+                    return;
+                }
+            };
+            let mut subscriber = match client.subscribe(subject.clone()).await {
+                Ok(subscriber) => subscriber,
+                Err(e) => {
+                    warn!("Failed to subscribe to {}: {}", subject, e);
+                    return;
+                }
+            };
+            info!("Answering JSON-RPC requests on {}", subject);
+
+            while let Some(message) = subscriber.next().await {
+                let Some(reply_to) = message.reply.clone() else {
+                    debug!(
+                        "JSON-RPC request on {} without reply subject",
+                        message.subject
+                    );
+                    continue;
+                };
+                let Some((network, subnet)) =
+                    subject_registry::unprefixed(message.subject.as_str())
+                        .and_then(nats_rpc::parse_subject)
+                else {
+                    continue;
+                };
+                let network = config.pool_network(&network, &subnet);
+                let pool = pools.read().await.get(&network).cloned();
+                let logs = provider_config.read().await.logs.clone();
+                let client = client.clone();
+                // Calls run concurrently; a slow upstream holds only its own reply
+                tokio::spawn(async move {
+                    let reply = nats_rpc::answer(pool, &logs, &message.payload).await;
+                    let payload = serde_json::to_vec(&reply).unwrap_or_default();
+                    if let Err(e) = client.publish(reply_to, payload.into()).await {
+                        warn!("Failed to reply to JSON-RPC request: {}", e);
+                    }
+                });
+            }
+        });
+        *self.request_task.lock() = Some(task);
+    }
+
+    /// Register WebSocket endpoints for a network
+    ///
+    /// Replaces any existing pool for the network, which ends its subscriptions.
+    pub async fn register_ws_endpoints(&self, network: &str, endpoints: Vec<String>) -> Result<()> {
+        let config = self.config.read().await.ws.clone();
+        let pool = Arc::new(WsPool::new(network.to_string(), endpoints, config)?);
+
+        let mut pools = self.ws_pools.write().await;
+        pools.insert(network.to_string(), pool);
+
+        info!("Registered WebSocket pool for network: {}", network);
+        Ok(())
+    }
+
+    /// Get endpoint pool for a network
+    async fn get_pool(&self, network: &str) -> Result<Arc<EndpointPool>> {
+        let pools = self.endpoint_pools.read().await;
+
+        pools
+            .get(network)
+            .cloned()
+            .ok_or_else(|| anyhow!("No endpoint pool configured for network: {}", network))
+    }
+
+    /// Make a blockchain-specific RPC call with failover and caching
+    pub async fn blockchain_rpc(
+        &self,
+        network: &str,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<Value> {
+        if method == "eth_getLogs" && params.len() == 1 && self.config.read().await.logs.enabled {
+            let filter = params.into_iter().next().unwrap_or_default();
+            return self.get_raw_logs(network, filter).await.map(Value::Array);
+        }
+
+        let pool = self.get_pool(network).await?;
+        let request = RpcRequest::new(method, params);
+
+        debug!("Making RPC call to {}: {}", network, method);
+
+        let response = pool.call_with_failover(&request).await?;
+
+        response
+            .result
+            .ok_or_else(|| anyhow!("No result in RPC response"))
+    }
+
+    /// `eth_getLogs` for `filter`, split into block ranges the upstreams accept
+    ///
+    /// Ranges ending at `latest` (or left open) are resolved to the current
+    /// head first; `blockHash` filters are sent as they are.
+    pub async fn get_raw_logs(&self, network: &str, filter: Value) -> Result<Vec<Value>> {
+        let pool = self.get_pool(network).await?;
+        let config = self.config.read().await.logs.clone();
+
+        debug!("Fetching logs from {}", network);
+        logs::pool_logs(pool, &config, filter).await
+    }
+
+    /// [`Self::get_raw_logs`], parsed into typed logs
+    pub async fn get_logs(&self, network: &str, filter: Value) -> Result<Vec<Log>> {
+        self.get_raw_logs(network, filter)
+            .await?
+            .into_iter()
+            .map(|log| serde_json::from_value(log).map_err(|e| anyhow!("Invalid log: {}", e)))
+            .collect()
+    }
+
+    /// `block` (a number, tag or hash) with its full transactions, typed
+    pub async fn get_block_with_txs(&self, network: &str, block: &str) -> Result<Block> {
+        let pool = self.get_pool(network).await?;
+        let response = pool
+            .call_with_failover(&receipts::block_request(block, true))
+            .await?;
+        match response.result {
+            None | Some(Value::Null) => Err(anyhow!("Block {} not found on {}", block, network)),
+            Some(result) => serde_json::from_value(result)
+                .map_err(|e| anyhow!("Invalid block {}: {}", block, e)),
+        }
+    }
+
+    /// Solana block at `slot` with its transactions; `None` for a skipped
+    /// slot or one not yet at `commitment`
+    pub async fn solana_get_block(
+        &self,
+        network: &str,
+        slot: u64,
+        commitment: Commitment,
+    ) -> Result<Option<Value>> {
+        let pool = self.get_pool(network).await?;
+        let response = pool
+            .call_with_failover(&solana::get_block_request(slot, commitment)?)
+            .await?;
+        Ok(response.result.filter(|block| !block.is_null()))
+    }
+
+    /// Solana transaction `signature`; `None` while unknown at `commitment`
+    pub async fn solana_get_transaction(
+        &self,
+        network: &str,
+        signature: &str,
+        commitment: Commitment,
+    ) -> Result<Option<Value>> {
+        let pool = self.get_pool(network).await?;
+        let response = pool
+            .call_with_failover(&solana::get_transaction_request(signature, commitment)?)
+            .await?;
+        Ok(response.result.filter(|transaction| !transaction.is_null()))
+    }
+
+    /// Signatures of transactions touching `address`, newest first; `before`
+    /// pages back from a signature
+    pub async fn solana_get_signatures_for_address(
+        &self,
+        network: &str,
+        address: &str,
+        before: Option<&str>,
+        limit: Option<u32>,
+        commitment: Commitment,
+    ) -> Result<Vec<Value>> {
+        let pool = self.get_pool(network).await?;
+        let request = solana::signatures_request(address, before, limit, commitment);
+        match pool.call_with_failover(&request).await?.result {
+            Some(Value::Array(signatures)) => Ok(signatures),
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(other) => Err(anyhow!("Unexpected signatures for {}: {}", address, other)),
+        }
+    }
+
+    /// Typed receipt of `tx_hash`; `None` while it is pending or unknown
+    pub async fn get_transaction_receipt(
+        &self,
+        network: &str,
+        tx_hash: &str,
+    ) -> Result<Option<TransactionReceipt>> {
+        let pool = self.get_pool(network).await?;
+        let request = RpcRequest::new("eth_getTransactionReceipt", vec![Value::from(tx_hash)]);
+        let response = pool.call_with_failover(&request).await?;
+        match response.result {
+            None | Some(Value::Null) => Ok(None),
+            Some(result) => serde_json::from_value(result)
+                .map(Some)
+                .map_err(|e| anyhow!("Invalid receipt of {}: {}", tx_hash, e)),
+        }
+    }
+
+    /// Open a session pinning calls on `network` to one endpoint and one block
+    /// (`block`, or that endpoint's head when unset)
+    pub async fn consistent_session(
+        &self,
+        network: &str,
+        block: Option<u64>,
+    ) -> Result<ConsistentSession> {
+        let pool = self.get_pool(network).await?;
+        ConsistentSession::open(pool, block).await
+    }
+
+    /// Make `calls` in order within one consistent session; the first failing
+    /// call fails them all
+    pub async fn blockchain_consistent(
+        &self,
+        network: &str,
+        block: Option<u64>,
+        calls: Vec<RpcRequest>,
+    ) -> Result<Vec<Value>> {
+        let session = self.consistent_session(network, block).await?;
+        debug!(
+            "Making {} consistent calls to {} at block {} on {}",
+            calls.len(),
+            network,
+            session.block(),
+            session.endpoint()
+        );
+
+        let mut results = Vec::with_capacity(calls.len());
+        for call in calls {
+            results.push(session.call(&call.method, call.params).await?);
+        }
+        Ok(results)
+    }
+
+    /// Make a JSON-RPC batch call with failover and caching
+    ///
+    /// Responses come back in request order; per-call errors stay in each
+    /// response's `error`.
+    pub async fn blockchain_batch(
+        &self,
+        network: &str,
+        batch: RpcBatchRequest,
+    ) -> Result<RpcBatchResponse> {
+        let pool = self.get_pool(network).await?;
+
+        debug!(
+            "Making batch RPC call to {}: {} calls",
+            network,
+            batch.len()
+        );
+
+        pool.execute_batch(batch).await
+    }
+
+    /// Make a batch of `eth_call`s, aggregated through Multicall3 where the
+    /// network has it and the calls are plain reads
+    ///
+    /// Answers as [`Self::blockchain_batch`] does; each reverted call gets its
+    /// own error with the revert data.
+    pub async fn blockchain_multicall(
+        &self,
+        network: &str,
+        batch: RpcBatchRequest,
+    ) -> Result<RpcBatchResponse> {
+        let pool = self.get_pool(network).await?;
+        let config = self.config.read().await.multicall.clone();
+        let Some(address) = config.address(network) else {
+            return pool.execute_batch(batch).await;
+        };
+
+        debug!(
+            "Making multicall to {} via {}: {} calls",
+            network,
+            address,
+            batch.len()
+        );
+        multicall::aggregate(&pool, &address, batch, config.max_calls).await
+    }
+
+    /// Traces of a transaction, from `debug_traceTransaction` or
+    /// `trace_transaction` as the endpoint supports
+    pub async fn trace_transaction(&self, network: &str, tx_hash: &str) -> Result<Vec<Trace>> {
+        let pool = self.get_pool(network).await?;
+
+        debug!("Tracing transaction {} on {}", tx_hash, network);
+        pool.trace(&TraceTarget::Transaction(tx_hash.to_string()))
+            .await
+    }
+
+    /// Traces of every transaction in `block` (a number or tag), from
+    /// `debug_traceBlockByNumber` or `trace_block` as the endpoint supports
+    pub async fn trace_block(&self, network: &str, block: &str) -> Result<Vec<Trace>> {
+        let pool = self.get_pool(network).await?;
+
+        debug!("Tracing block {} on {}", block, network);
+        pool.trace(&TraceTarget::Block(block.to_string())).await
+    }
+
+    /// Every receipt of `block` (a number, tag or hash), in transaction order,
+    /// from `eth_getBlockReceipts` or per-transaction calls as the endpoint
+    /// supports
+    pub async fn block_receipts(&self, network: &str, block: &str) -> Result<Vec<Value>> {
+        let pool = self.get_pool(network).await?;
+
+        debug!("Fetching receipts of block {} on {}", block, network);
+        pool.block_receipts(block).await
+    }
+
+    /// Subscribe to pushed chain data; returns the local subscription id and
+    /// a receiver that survives reconnects
+    pub async fn subscribe(
+        &self,
+        network: &str,
+        kind: SubscriptionKind,
+    ) -> Result<(u64, tokio::sync::mpsc::Receiver<WsNotification>)> {
+        let pools = self.ws_pools.read().await;
+        let pool = pools
+            .get(network)
+            .ok_or_else(|| anyhow!("No WebSocket pool configured for network: {}", network))?;
+
+        debug!("Subscribing to {:?} on {}", kind, network);
+        Ok(pool.subscribe(kind))
+    }
+
+    /// End a subscription; false if it was not active
+    pub async fn unsubscribe(&self, network: &str, id: u64) -> Result<bool> {
+        let pools = self.ws_pools.read().await;
+        let pool = pools
+            .get(network)
+            .ok_or_else(|| anyhow!("No WebSocket pool configured for network: {}", network))?;
+
+        Ok(pool.unsubscribe(id))
+    }
+
+    /// Apply a new head from any feed (e.g. `newheads.*` on NATS); returns how
+    /// many cached entries were dropped, 0 if the head was already applied
+    pub async fn on_new_head(&self, network: &str, block_number: u64) -> Result<usize> {
+        if !self.head_tracker.observe(network, block_number) {
+            return Ok(0);
+        }
+        let pool = self.get_pool(network).await?;
+        pool.on_new_head(block_number).await
+    }
+
+    /// Start (or restart) cache invalidation from the `newHeads` stream of
+    /// every network with a WebSocket pool
+    pub async fn start_invalidation(&self) {
+        for task in self.invalidation_tasks.lock().drain(..) {
+            task.abort();
+        }
+
+        let mut tasks = Vec::new();
+        let ws_pools = self.ws_pools.read().await;
+        for (network, ws_pool) in ws_pools.iter() {
+            let (_, mut heads) = ws_pool.subscribe(SubscriptionKind::NewHeads);
+            let network = network.clone();
+            let pools = self.endpoint_pools.clone();
+            let tracker = self.head_tracker.clone();
+            info!("Cache invalidation following newHeads for {}", network);
+            tasks.push(tokio::spawn(async move {
+                while let Some(head) = heads.recv().await {
+                    let Some(block_number) = invalidation::head_number(&head.result) else {
+                        continue;
+                    };
+                    if !tracker.observe(&network, block_number) {
+                        continue;
+                    }
+                    let Some(pool) = pools.read().await.get(&network).cloned() else {
+                        continue;
+                    };
+                    if let Err(e) = pool.on_new_head(block_number).await {
+                        warn!("Cache invalidation failed for {}: {}", network, e);
+                    }
+                }
+            }));
+        }
+        drop(ws_pools);
+
+        *self.invalidation_tasks.lock() = tasks;
+    }
+
+    /// Start (or restart) publishing the pending transactions of every
+    /// network in the mempool config
+    pub async fn start_mempool(&self) {
+        for task in self.mempool_tasks.lock().drain(..) {
+            task.abort();
+        }
+
+        let (config, nats_url) = {
+            let config = self.config.read().await;
+            (config.mempool.clone(), config.nats_url.clone())
+        };
+        if !config.enabled || config.networks.is_empty() {
+            info!("Mempool ingestion disabled");
+            return;
+        }
+        let Some(nats_url) = nats_url else {
+            info!("No NATS_URL, pending transactions not published");
+            return;
+        };
+
+        let mut tasks = Vec::new();
+        let ws_pools = self.ws_pools.read().await;
+        for network in config.networks.keys() {
+            let network = network.clone();
+            let ws_pool = ws_pools.get(&network).cloned();
+            let pools = self.endpoint_pools.clone();
+            let config = config.clone();
+            let nats_url = nats_url.clone();
+            tasks.push(tokio::spawn(async move {
+                let client = match async_nats::connect(&nats_url).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        warn!("Failed to connect to NATS for pending transactions: {}", e);
+                        return;
+                    }
+                };
+                mempool::run(network, config, ws_pool, pools, client).await;
+            }));
+        }
+        drop(ws_pools);
+
+        *self.mempool_tasks.lock() = tasks;
+    }
+
+    /// Get WebSocket connection status for all networks
+    pub async fn get_all_ws_status(&self) -> Vec<WsPoolStatus> {
+        let pools = self.ws_pools.read().await;
+
+        pools.values().map(|pool| pool.status()).collect()
+    }
+
+    /// Get health status for a network's endpoint pool
+    pub async fn get_health_status(&self, network: &str) -> Result<PoolHealthStatus> {
+        let pool = self.get_pool(network).await?;
+        Ok(pool.health_status())
+    }
+
+    /// Get health status for all networks
+    pub async fn get_all_health_status(&self) -> Vec<PoolHealthStatus> {
+        let pools = self.endpoint_pools.read().await;
+
+        pools.values().map(|pool| pool.health_status()).collect()
+    }
+
+    /// Apply or clear a network's A/B split on the running pool
+    ///
+    /// The split is also kept in the provider config so re-registering the
+    /// network's endpoints keeps it.
+    pub async fn set_split(&self, network: &str, split: Option<SplitConfig>) -> Result<()> {
+        let pool = self.get_pool(network).await?;
+        pool.set_split(split.clone())?;
+
+        let mut config = self.config.write().await;
+        match split {
+            Some(split) => config.splits.insert(network.to_string(), split),
+            None => config.splits.remove(network),
+        };
+
+        info!("Updated traffic split for network: {}", network);
+        Ok(())
+    }
+
+    /// Get per-arm split metrics for networks with an active split
+    pub async fn get_all_split_status(&self) -> HashMap<String, SplitStatus> {
+        let pools = self.endpoint_pools.read().await;
+
+        pools
+            .iter()
+            .filter_map(|(network, pool)| Some((network.clone(), pool.split_status()?)))
+            .collect()
+    }
+
+    /// Start (or restart) the canary loop probing every registered pool
+    pub async fn start_canary(&self) {
+        let config = self.config.read().await.canary.clone();
+        if let Some(previous) = self.canary_task.lock().take() {
+            previous.abort();
+        }
+        if !config.enabled {
+            info!("Canary probes disabled");
+            return;
+        }
+
+        let pools = self.endpoint_pools.clone();
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let targets: Vec<Arc<EndpointPool>> =
+                    pools.read().await.values().cloned().collect();
+                for pool in targets {
+                    pool.run_canary(&config).await;
+                }
+            }
+        });
+        *self.canary_task.lock() = Some(task);
+
+        info!("Canary probes running every {}s", interval.as_secs());
+    }
+
+    /// Readiness of every registered pool, as `/readyz` reports it
+    pub async fn health_report(&self) -> HealthReport {
+        let config = self.config.read().await.health.clone();
+        health::report(&self.endpoint_pools, &config).await
+    }
+
+    /// Start (or restart) the server answering `/healthz` and `/readyz`
+    pub async fn start_health_server(&self) {
+        let config = self.config.read().await.health.clone();
+        if let Some(previous) = self.health_task.lock().take() {
+            previous.abort();
+        }
+        if !config.enabled {
+            info!("Health probes disabled");
+            return;
+        }
+
+        let listener = match health::bind(&config).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("{}", e);
+                return;
+            }
+        };
+        info!("Health probes answered on {}", config.listen);
+        let task = tokio::spawn(health::serve(listener, self.endpoint_pools.clone(), config));
+        *self.health_task.lock() = Some(task);
+    }
+
+    /// Start (or restart) the loop re-checking every pool's chain ids
+    pub async fn start_chain_id_checks(&self) {
+        let config = self.config.read().await.chain_id.clone();
+        if let Some(previous) = self.chain_id_task.lock().take() {
+            previous.abort();
+        }
+        if !config.enabled {
+            info!("Chain id checks disabled");
+            return;
+        }
+
+        let pools = self.endpoint_pools.clone();
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // Registration already ran the first check
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let targets: Vec<Arc<EndpointPool>> =
+                    pools.read().await.values().cloned().collect();
+                for pool in targets {
+                    pool.verify_chain_ids().await;
+                }
+            }
+        });
+        *self.chain_id_task.lock() = Some(task);
+
+        info!("Chain id checks running every {}s", interval.as_secs());
+    }
+
+    /// Start (or restart) the loop refreshing answers served stale
+    pub async fn start_revalidation(&self) {
+        let enabled = self.config.read().await.swr.enabled;
+        if let Some(previous) = self.revalidation_task.lock().take() {
+            previous.abort();
+        }
+        if !enabled {
+            info!("Stale-while-revalidate disabled");
+            return;
+        }
+
+        let pools = self.endpoint_pools.clone();
+        let mut revalidations = self.revalidations.subscribe();
+        let task = tokio::spawn(async move {
+            loop {
+                let revalidation = match revalidations.recv().await {
+                    Ok(revalidation) => revalidation,
+                    // Skipped refreshes are asked again by the next stale read
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Dropped {} stale answer refreshes", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Some(pool) = pools.read().await.get(&revalidation.network).cloned() else {
+                    continue;
+                };
+                tokio::spawn(async move {
+                    pool.revalidate(&revalidation.request).await;
+                });
+            }
+        });
+        *self.revalidation_task.lock() = Some(task);
+
+        info!("Stale answers refreshed in the background");
+    }
+
+    /// Receive the circuit breaker state transitions of every pool
+    pub fn subscribe_transitions(&self) -> broadcast::Receiver<CircuitTransition> {
+        self.transitions.subscribe()
+    }
+
+    /// Start (or restart) publishing circuit transitions on NATS
+    pub async fn start_transition_publisher(&self) {
+        let nats_url = self.config.read().await.nats_url.clone();
+        if let Some(previous) = self.transition_task.lock().take() {
+            previous.abort();
+        }
+        let Some(nats_url) = nats_url else {
+            info!("No NATS_URL, circuit transitions not published");
+            return;
+        };
+
+        let task = spawn_publisher(
+            nats_url,
+            self.transitions.subscribe(),
+            TRANSITIONS_SUBJECT,
+            "circuit transition",
+        );
+        *self.transition_task.lock() = Some(task);
+
+        info!("Publishing circuit transitions on {}", TRANSITIONS_SUBJECT);
+    }
+
+    /// Receive the budget warnings of every pool
+    pub fn subscribe_budget_warnings(&self) -> broadcast::Receiver<BudgetWarning> {
+        self.budget_warnings.subscribe()
+    }
+
+    /// Start (or restart) publishing budget warnings on NATS
+    pub async fn start_budget_publisher(&self) {
+        let nats_url = self.config.read().await.nats_url.clone();
+        if let Some(previous) = self.budget_task.lock().take() {
+            previous.abort();
+        }
+        let Some(nats_url) = nats_url else {
+            info!("No NATS_URL, budget warnings not published");
+            return;
+        };
+
+        let task = spawn_publisher(
+            nats_url,
+            self.budget_warnings.subscribe(),
+            BUDGET_SUBJECT,
+            "budget warning",
+        );
+        *self.budget_task.lock() = Some(task);
+
+        info!("Publishing budget warnings on {}", BUDGET_SUBJECT);
+    }
+
+    /// Start (or restart) the loop writing spend to the daily usage hashes
+    pub async fn start_usage_flush(&self) {
+        let interval = Duration::from_secs(self.config.read().await.cost.usage_flush_secs.max(1));
+        if let Some(previous) = self.usage_task.lock().take() {
+            previous.abort();
+        }
+
+        let pools = self.endpoint_pools.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                flush_usage(&pools).await;
+            }
+        });
+        *self.usage_task.lock() = Some(task);
+
+        info!("Daily RPC spend written every {}s", interval.as_secs());
+    }
+
+    /// Start (or restart) the loop re-reading endpoint secrets for key rotation
+    pub async fn start_auth_refresh(&self) {
+        let config = self.config.read().await.auth.clone();
+        if let Some(previous) = self.auth_task.lock().take() {
+            previous.abort();
+        }
+        if config.endpoints.is_empty() {
+            debug!("No endpoint auth configured");
+            return;
+        }
+
+        let pools = self.endpoint_pools.clone();
+        let interval = Duration::from_secs(config.refresh_secs.max(1));
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // Pool initialization already read the secrets
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let targets: Vec<Arc<EndpointPool>> =
+                    pools.read().await.values().cloned().collect();
+                for pool in targets {
+                    pool.refresh_credentials().await;
+                }
+            }
+        });
+        *self.auth_task.lock() = Some(task);
+
+        info!("Endpoint secrets re-read every {}s", interval.as_secs());
+    }
+
+    /// Get credential state of endpoints with auth, keyed by network
+    pub async fn get_all_auth_status(&self) -> HashMap<String, Vec<AuthStatus>> {
+        let pools = self.endpoint_pools.read().await;
 
-            // Circuit breaker defaults
-            circuit_breaker_failure_threshold: 5,
-            circuit_breaker_success_threshold: 2,
-            circuit_breaker_timeout_seconds: 30,
+        pools
+            .iter()
+            .map(|(network, pool)| (network.clone(), pool.auth_status()))
+            .filter(|(_, status)| !status.is_empty())
+            .collect()
+    }
 
-            // Cache defaults
-            cache_enabled: true,
-            cache_redis_url: "redis://redis.ekko.svc.cluster.local:6379".to_string(),
-            cache_default_ttl: 60,
-            cache_block_ttl: 300,
-            cache_tx_ttl: 3600,
-        }
+    /// Get per-endpoint chain id verdicts for all checked networks, keyed by network
+    pub async fn get_all_chain_id_status(&self) -> HashMap<String, Vec<ChainIdStatus>> {
+        let pools = self.endpoint_pools.read().await;
+
+        pools
+            .iter()
+            .map(|(network, pool)| (network.clone(), pool.chain_id_status()))
+            .filter(|(_, status)| !status.is_empty())
+            .collect()
     }
-}
 
-impl ProviderConfig {
-    /// Load configuration from environment variables with fallback to defaults
-    pub fn from_env() -> Self {
-        let default = Self::default();
+    /// Get per-endpoint canary scores for all networks, keyed by network
+    pub async fn get_all_canary_status(&self) -> HashMap<String, Vec<CanaryStatus>> {
+        let canary = self.config.read().await.canary.clone();
+        let pools = self.endpoint_pools.read().await;
 
-        Self {
-            timeout_seconds: std::env::var("HTTP_RPC_TIMEOUT_SECONDS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.timeout_seconds),
-            max_retries: std::env::var("HTTP_RPC_MAX_RETRIES")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.max_retries),
-            retry_delay_ms: std::env::var("HTTP_RPC_RETRY_DELAY_MS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.retry_delay_ms),
-            user_agent: std::env::var("HTTP_RPC_USER_AGENT").unwrap_or(default.user_agent),
+        pools
+            .iter()
+            .filter(|(network, _)| canary.networks.contains_key(*network))
+            .map(|(network, pool)| (network.clone(), pool.canary_status(&canary)))
+            .collect()
+    }
 
-            circuit_breaker_failure_threshold: std::env::var("HTTP_RPC_CB_FAILURE_THRESHOLD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.circuit_breaker_failure_threshold),
-            circuit_breaker_success_threshold: std::env::var("HTTP_RPC_CB_SUCCESS_THRESHOLD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.circuit_breaker_success_threshold),
-            circuit_breaker_timeout_seconds: std::env::var("HTTP_RPC_CB_TIMEOUT_SECONDS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.circuit_breaker_timeout_seconds),
+    /// Get per-endpoint spend for all networks, keyed by network
+    pub async fn get_all_cost_status(&self) -> HashMap<String, Vec<EndpointCostStatus>> {
+        let pools = self.endpoint_pools.read().await;
 
-            cache_enabled: std::env::var("HTTP_RPC_CACHE_ENABLED")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.cache_enabled),
-            cache_redis_url: std::env::var("HTTP_RPC_CACHE_REDIS_URL")
-                .unwrap_or(default.cache_redis_url),
-            cache_default_ttl: std::env::var("HTTP_RPC_CACHE_DEFAULT_TTL")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.cache_default_ttl),
-            cache_block_ttl: std::env::var("HTTP_RPC_CACHE_BLOCK_TTL")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.cache_block_ttl),
-            cache_tx_ttl: std::env::var("HTTP_RPC_CACHE_TX_TTL")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(default.cache_tx_ttl),
-        }
+        pools
+            .iter()
+            .map(|(network, pool)| (network.clone(), pool.cost_status()))
+            .collect()
     }
-}
 
-// RpcRequest, RpcResponse, and RpcError are now defined in endpoint_pool module
+    /// Get per-endpoint selection scores for all networks, keyed by network
+    pub async fn get_all_selection_status(&self) -> HashMap<String, Vec<EndpointScore>> {
+        let pools = self.endpoint_pools.read().await;
 
-impl HttpRpcProvider {
-    /// Create provider from wasmCloud host data (for WADM deployment)
-    ///
-    /// Loads configuration from environment variables and creates provider instance.
-    /// This is the entry point when deployed via wasmCloud/WADM.
-    pub fn from_host_data(_host_data: wasmcloud_provider_sdk::HostData) -> Result<Self> {
-        info!("🌟 Creating HTTP RPC Provider from wasmCloud host data");
+        pools
+            .iter()
+            .map(|(network, pool)| (network.clone(), pool.selection_status()))
+            .collect()
+    }
 
-        // Load configuration from environment variables
-        let config = ProviderConfig::from_env();
+    /// Get per-endpoint rate limit buckets for all networks, keyed by network
+    pub async fn get_all_rate_limit_status(&self) -> HashMap<String, Vec<RateLimitStatus>> {
+        let pools = self.endpoint_pools.read().await;
 
-        Ok(Self::with_config(config))
+        pools
+            .iter()
+            .map(|(network, pool)| (network.clone(), pool.rate_limit_status()))
+            .collect()
     }
+}
 
-    /// Create a new HTTP RPC provider with custom configuration
-    pub fn with_config(config: ProviderConfig) -> Self {
-        Self {
-            endpoint_pools: Arc::new(RwLock::new(HashMap::new())),
-            config: Arc::new(RwLock::new(config)),
+/// Publish every event received on `events` to `subject` (canonical form)
+fn spawn_publisher<T>(
+    nats_url: String,
+    mut events: broadcast::Receiver<T>,
+    subject: &'static str,
+    kind: &'static str,
+) -> JoinHandle<()>
+where
+    T: Clone + Serialize + Send + 'static,
+{
+    let subject = subject_registry::prefixed(subject);
+    tokio::spawn(async move {
+        let client = match async_nats::connect(&nats_url).await {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to connect to NATS for {}s: {}", kind, e);
+                return;
+            }
+        };
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Dropped {} {}s", missed, kind);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let payload = match serde_json::to_vec(&event) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("Failed to serialize {}: {}", kind, e);
+                    continue;
+                }
+            };
+            if let Err(e) = client.publish(subject.clone(), payload.into()).await {
+                warn!("Failed to publish {}: {}", kind, e);
+            }
         }
-    }
+    })
+}
 
-    /// Create a new HTTP RPC provider with default configuration
-    pub fn new() -> Self {
-        Self::with_config(ProviderConfig::default())
+/// Write the unflushed spend of every pool to the daily usage hashes
+async fn flush_usage(pools: &RwLock<HashMap<String, Arc<EndpointPool>>>) {
+    let targets: Vec<Arc<EndpointPool>> = pools.read().await.values().cloned().collect();
+    for pool in targets {
+        if let Err(e) = pool.flush_usage().await {
+            warn!("Failed to persist RPC spend: {}", e);
+        }
     }
+}
 
-    /// Register RPC endpoints for a network
-    pub async fn register_endpoints(&self, network: &str, endpoints: Vec<String>) -> Result<()> {
-        let config = self.config.read().await;
+/// Shared handles for building endpoint pools, cloned into background loops
+#[derive(Clone)]
+struct PoolRegistry {
+    config: Arc<RwLock<ProviderConfig>>,
+    pools: Arc<RwLock<HashMap<String, Arc<EndpointPool>>>>,
+    transitions: broadcast::Sender<CircuitTransition>,
+    budget_warnings: broadcast::Sender<BudgetWarning>,
+    revalidations: broadcast::Sender<Revalidation>,
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl PoolRegistry {
+    /// Build, initialize and store a network's pool, replacing any previous one
+    async fn register(&self, network: &str, endpoints: Vec<String>) -> Result<Arc<EndpointPool>> {
+        let config = self.config.read().await.for_network(network);
 
         // Create endpoint pool configuration
         let pool_config = EndpointPoolConfig {
@@ -183,14 +1871,57 @@ impl HttpRpcProvider {
                 success_threshold: config.circuit_breaker_success_threshold,
                 timeout: Duration::from_secs(config.circuit_breaker_timeout_seconds),
                 window_duration: Duration::from_secs(60),
+                half_open_max_probes: config.circuit_breaker_half_open_max_probes,
+                max_timeout: Duration::from_secs(config.circuit_breaker_max_timeout_seconds),
+                jitter: config.circuit_breaker_jitter,
+                ..Default::default()
             },
             cache: CacheConfig {
                 redis_url: config.cache_redis_url.clone(),
+                redis: config.cache_redis.clone(),
+                backend: CacheBackendConfig {
+                    nats_url: config
+                        .cache_backend
+                        .nats_url
+                        .clone()
+                        .or_else(|| config.nats_url.clone()),
+                    ..config.cache_backend.clone()
+                },
                 default_ttl: config.cache_default_ttl,
                 block_ttl: config.cache_block_ttl,
                 tx_ttl: config.cache_tx_ttl,
+                method_ttls: cache::method_ttls_with(config.cache_method_ttls.clone()),
+                memory_capacity: config.cache_memory_capacity,
+                memory_ttl: config.cache_memory_ttl,
+                ttl_jitter: config.cache_ttl_jitter,
+                refresh_lock_ms: config.cache_refresh_lock_ms,
+                refresh_wait_ms: config.cache_refresh_wait_ms,
                 enabled: config.cache_enabled,
             },
+            cost: config.cost.clone(),
+            split: config.splits.get(network).cloned(),
+            batch: config.batch.clone(),
+            rate_limit: config.rate_limit.clone(),
+            hedge: config.hedge.clone(),
+            selection: config.selection.clone(),
+            expected_chain_id: config.chain_id.expected(network),
+            head_lag: config.solana.head_lag(network, &config.head_lag),
+            transitions: Some(self.transitions.clone()),
+            auth: config.auth.clone(),
+            budget_warnings: Some(self.budget_warnings.clone()),
+            archive: config.archive.clone(),
+            validation: config.validation.clone(),
+            compression: config.compression.clone(),
+            size_limit: config.size_limit.clone(),
+            negative_cache: config.negative_cache.clone(),
+            fallback: config.fallback.clone(),
+            egress: config.egress.clone(),
+            connection: config.connection.clone(),
+            swr: config.swr.clone(),
+            priority: config.priority.clone(),
+            revalidations: Some(self.revalidations.clone()),
+            recorder: self.recorder.clone(),
+            solana: config.solana.clone(),
         };
 
         drop(config);
@@ -198,55 +1929,85 @@ impl HttpRpcProvider {
         // Create and initialize endpoint pool
         let pool = Arc::new(EndpointPool::new(network.to_string(), pool_config)?);
         pool.init().await?;
+        pool.verify_chain_ids().await;
 
         // Store in registry
-        let mut pools = self.endpoint_pools.write().await;
-        pools.insert(network.to_string(), pool);
+        let mut pools = self.pools.write().await;
+        pools.insert(network.to_string(), pool.clone());
 
         info!("Registered endpoint pool for network: {}", network);
-        Ok(())
+        Ok(pool)
     }
 
-    /// Get endpoint pool for a network
-    async fn get_pool(&self, network: &str) -> Result<Arc<EndpointPool>> {
-        let pools = self.endpoint_pools.read().await;
+    async fn store(&self) -> EndpointStore {
+        let config = self.config.read().await;
+        EndpointStore::new(&config.cache_redis_url, &config.cache_redis)
+    }
 
-        pools
-            .get(network)
-            .cloned()
-            .ok_or_else(|| anyhow!("No endpoint pool configured for network: {}", network))
+    /// Apply one control message received on `subject`
+    async fn handle_control(&self, subject: &str, payload: &[u8]) -> Result<EndpointSet> {
+        let (network, action) = subject_registry::unprefixed(subject)
+            .and_then(control::parse_subject)
+            .ok_or_else(|| anyhow!("Not an endpoint control subject: {}", subject))?;
+        let command: EndpointCommand = serde_json::from_slice(payload)
+            .map_err(|e| anyhow!("Invalid endpoint command: {}", e))?;
+        self.apply(&network, action, &command.endpoint).await
     }
 
-    /// Make a blockchain-specific RPC call with failover and caching
-    pub async fn blockchain_rpc(
+    async fn apply(
         &self,
         network: &str,
-        method: &str,
-        params: Vec<Value>,
-    ) -> Result<Value> {
-        let pool = self.get_pool(network).await?;
-        let request = RpcRequest::new(method, params);
-
-        debug!("Making RPC call to {}: {}", network, method);
-
-        let response = pool.call_with_failover(&request).await?;
-
-        response
-            .result
-            .ok_or_else(|| anyhow!("No result in RPC response"))
-    }
-
-    /// Get health status for a network's endpoint pool
-    pub async fn get_health_status(&self, network: &str) -> Result<PoolHealthStatus> {
-        let pool = self.get_pool(network).await?;
-        Ok(pool.health_status())
-    }
+        action: EndpointAction,
+        endpoint: &str,
+    ) -> Result<EndpointSet> {
+        let endpoint = endpoint.trim();
+        let current = self.pools.read().await.get(network).cloned();
+        let mut set = match &current {
+            Some(pool) => EndpointSet {
+                endpoints: pool.endpoints().to_vec(),
+                paused: pool.paused_endpoints(),
+            },
+            None if action == EndpointAction::Add => EndpointSet::default(),
+            None => {
+                return Err(anyhow!(
+                    "No endpoint pool configured for network: {}",
+                    network
+                ))
+            }
+        };
 
-    /// Get health status for all networks
-    pub async fn get_all_health_status(&self) -> Vec<PoolHealthStatus> {
-        let pools = self.endpoint_pools.read().await;
+        let rebuild = set.apply(action, endpoint)?;
+        let pool = match current {
+            Some(pool) if !rebuild => pool,
+            _ => self.register(network, set.endpoints.clone()).await?,
+        };
+        match action {
+            EndpointAction::Pause => {
+                pool.set_paused(endpoint, true);
+            }
+            EndpointAction::Resume => {
+                pool.set_paused(endpoint, false);
+            }
+            // A rebuilt pool starts with every endpoint active
+            EndpointAction::Add | EndpointAction::Remove => {
+                for paused in &set.paused {
+                    pool.set_paused(paused, true);
+                }
+            }
+        }
 
-        pools.values().map(|pool| pool.health_status()).collect()
+        if let Err(e) = self.store().await.save(network, &set).await {
+            warn!("Endpoint change for {} not persisted: {}", network, e);
+        }
+        info!(
+            "Endpoints for {} after {:?} {}: {} configured, {} paused",
+            network,
+            action,
+            endpoint,
+            set.endpoints.len(),
+            set.paused.len()
+        );
+        Ok(set)
     }
 }
 
@@ -255,13 +2016,15 @@ impl Provider for HttpRpcProvider {
     /// Initialize the provider
     fn init(
         &self,
-        _init_config: impl wasmcloud_provider_sdk::ProviderInitConfig,
+        init_config: impl wasmcloud_provider_sdk::ProviderInitConfig,
     ) -> impl std::future::Future<Output = Result<()>> + Send {
+        // Per-network overlays from the provider's config values
+        let overlays = network_overlay::from_values(init_config.get_config());
         async move {
             info!("Initializing HTTP RPC provider with enhanced failover and caching");
 
-            // Load configuration from environment variables
-            let mut config = ProviderConfig::default();
+            // Start from the loaded configuration (env or `with_config`)
+            let mut config = self.config.read().await.clone();
 
             // Override with environment variables if present
             if let Ok(timeout) = std::env::var("RPC_TIMEOUT_SECONDS") {
@@ -278,6 +2041,11 @@ impl Provider for HttpRpcProvider {
                 config.cache_enabled = enabled.parse().unwrap_or(true);
             }
 
+            match overlays {
+                Ok(overlays) => config.networks.extend(overlays),
+                Err(e) => warn!("Ignoring network overlays in provider config: {}", e),
+            }
+
             *self.config.write().await = config;
 
             // Register default network endpoints from environment
@@ -344,16 +2112,97 @@ impl Provider for HttpRpcProvider {
                 }
             }
 
+            // WebSocket endpoints for eth_subscribe streams
+            // Example: ETH_WS_ENDPOINTS=wss://endpoint1.com,wss://endpoint2.com
+            for (var, network) in [
+                ("ETH_WS_ENDPOINTS", "ethereum"),
+                ("AVALANCHE_WS_ENDPOINTS", "avalanche"),
+                ("AVALANCHE_FUJI_WS_ENDPOINTS", "avalanche-fuji"),
+            ] {
+                if let Ok(ws_endpoints) = std::env::var(var) {
+                    let endpoints: Vec<String> = ws_endpoints
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect();
+                    if !endpoints.is_empty() {
+                        self.register_ws_endpoints(network, endpoints).await?;
+                    }
+                }
+            }
+
+            self.restore_endpoints().await;
+
+            self.start_transition_publisher().await;
+            self.start_budget_publisher().await;
+            self.start_usage_flush().await;
+            self.start_endpoint_control().await;
+            self.start_request_handler().await;
+            self.start_canary().await;
+            self.start_chain_id_checks().await;
+            self.start_auth_refresh().await;
+            self.start_invalidation().await;
+            self.start_mempool().await;
+            self.start_revalidation().await;
+            self.start_health_server().await;
+
             info!("HTTP RPC provider initialized successfully");
             Ok(())
         }
     }
 
+    /// Apply the per-network overlays carried by a link to this provider
+    fn receive_link_config_as_target(
+        &self,
+        link_config: wasmcloud_provider_sdk::LinkConfig<'_>,
+    ) -> impl std::future::Future<Output = Result<()>> + Send {
+        let overlays = network_overlay::from_values(link_config.config);
+        async move { self.apply_network_overlays(overlays?).await }
+    }
+
     /// Shutdown the provider
     fn shutdown(&self) -> impl std::future::Future<Output = Result<()>> + Send {
         async move {
             info!("Shutting down HTTP RPC provider");
+            if let Some(task) = self.canary_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.chain_id_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.transition_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.budget_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.usage_task.lock().take() {
+                task.abort();
+            }
+            flush_usage(&self.endpoint_pools).await;
+            if let Some(task) = self.control_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.request_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.auth_task.lock().take() {
+                task.abort();
+            }
+            for task in self.invalidation_tasks.lock().drain(..) {
+                task.abort();
+            }
+            for task in self.mempool_tasks.lock().drain(..) {
+                task.abort();
+            }
+            if let Some(task) = self.revalidation_task.lock().take() {
+                task.abort();
+            }
+            if let Some(task) = self.health_task.lock().take() {
+                task.abort();
+            }
             self.endpoint_pools.write().await.clear();
+            self.ws_pools.write().await.clear();
             Ok(())
         }
     }
@@ -389,6 +2238,55 @@ impl HttpHandler {
         self.provider.blockchain_rpc(network, method, params).await
     }
 
+    /// Handle a batch of RPC calls from an actor
+    pub async fn handle_batch(
+        &self,
+        network: &str,
+        batch: RpcBatchRequest,
+    ) -> Result<RpcBatchResponse> {
+        self.provider.blockchain_batch(network, batch).await
+    }
+
+    /// Handle a list of `eth_call`s to aggregate through Multicall3
+    pub async fn handle_multicall(
+        &self,
+        network: &str,
+        batch: RpcBatchRequest,
+    ) -> Result<RpcBatchResponse> {
+        self.provider.blockchain_multicall(network, batch).await
+    }
+
+    /// Handle a trace request for a transaction
+    pub async fn handle_trace_transaction(
+        &self,
+        network: &str,
+        tx_hash: &str,
+    ) -> Result<Vec<Trace>> {
+        self.provider.trace_transaction(network, tx_hash).await
+    }
+
+    /// Handle a trace request for a block
+    pub async fn handle_trace_block(&self, network: &str, block: &str) -> Result<Vec<Trace>> {
+        self.provider.trace_block(network, block).await
+    }
+
+    /// Handle a request for every receipt of a block
+    pub async fn handle_block_receipts(&self, network: &str, block: &str) -> Result<Vec<Value>> {
+        self.provider.block_receipts(network, block).await
+    }
+
+    /// Handle a sequence of calls that must see one endpoint at one block
+    pub async fn handle_consistent(
+        &self,
+        network: &str,
+        block: Option<u64>,
+        calls: Vec<RpcRequest>,
+    ) -> Result<Vec<Value>> {
+        self.provider
+            .blockchain_consistent(network, block, calls)
+            .await
+    }
+
     /// Get health status for a network
     pub async fn get_health(&self, network: &str) -> Result<PoolHealthStatus> {
         self.provider.get_health_status(network).await
@@ -399,6 +2297,65 @@ impl HttpHandler {
         self.provider.get_all_health_status().await
     }
 
+    /// Get spend-rate metrics for all networks
+    pub async fn get_all_costs(&self) -> HashMap<String, Vec<EndpointCostStatus>> {
+        self.provider.get_all_cost_status().await
+    }
+
+    /// Get endpoint latency and health scores for all networks
+    pub async fn get_all_endpoint_scores(&self) -> HashMap<String, Vec<EndpointScore>> {
+        self.provider.get_all_selection_status().await
+    }
+
+    /// Get rate limit bucket state for all networks
+    pub async fn get_all_rate_limits(&self) -> HashMap<String, Vec<RateLimitStatus>> {
+        self.provider.get_all_rate_limit_status().await
+    }
+
+    /// Get chain id verdicts for all checked networks
+    pub async fn get_all_chain_ids(&self) -> HashMap<String, Vec<ChainIdStatus>> {
+        self.provider.get_all_chain_id_status().await
+    }
+
+    /// Get canary probe scores for all probed networks
+    pub async fn get_all_canaries(&self) -> HashMap<String, Vec<CanaryStatus>> {
+        self.provider.get_all_canary_status().await
+    }
+
+    /// Apply or clear a network's A/B split
+    pub async fn set_split(&self, network: &str, split: Option<SplitConfig>) -> Result<()> {
+        self.provider.set_split(network, split).await
+    }
+
+    /// Subscribe to new heads, logs or pending transactions for a network
+    pub async fn subscribe(
+        &self,
+        network: &str,
+        kind: SubscriptionKind,
+    ) -> Result<(u64, tokio::sync::mpsc::Receiver<WsNotification>)> {
+        self.provider.subscribe(network, kind).await
+    }
+
+    /// End a subscription
+    pub async fn unsubscribe(&self, network: &str, id: u64) -> Result<bool> {
+        self.provider.unsubscribe(network, id).await
+    }
+
+    /// Apply a new head pushed by an actor or another feed
+    pub async fn on_new_head(&self, network: &str, block_number: u64) -> Result<usize> {
+        self.provider.on_new_head(network, block_number).await
+    }
+
+    /// Get WebSocket connection status for all networks
+    pub async fn get_all_ws_status(&self) -> Vec<WsPoolStatus> {
+        self.provider.get_all_ws_status().await
+    }
+
+    /// Get per-arm split metrics for all networks
+    pub async fn get_all_splits(&self) -> HashMap<String, SplitStatus> {
+        self.provider.get_all_split_status().await
+    }
+
     /// Handle raw HTTP POST request from actor (non-RPC)
     pub async fn handle_post(&self, url: &str, body: &str) -> Result<String> {
         let response = self
@@ -487,6 +2444,97 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_subscribe_requires_ws_pool() {
+        let provider = HttpRpcProvider::new();
+        assert!(provider
+            .subscribe("ethereum", SubscriptionKind::NewHeads)
+            .await
+            .is_err());
+
+        provider
+            .register_ws_endpoints("ethereum", vec!["ws://127.0.0.1:1".to_string()])
+            .await
+            .unwrap();
+        let (id, _receiver) = provider
+            .subscribe("ethereum", SubscriptionKind::NewHeads)
+            .await
+            .unwrap();
+        assert!(provider.unsubscribe("ethereum", id).await.unwrap());
+        assert_eq!(provider.get_all_ws_status().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_on_new_head_applies_each_head_once() {
+        let provider = HttpRpcProvider::new();
+        assert!(provider.on_new_head("ethereum", 100).await.is_err());
+
+        let mut config = ProviderConfig::default();
+        config.cache_enabled = false;
+        let provider = HttpRpcProvider::with_config(config);
+        provider
+            .register_endpoints("ethereum", vec!["http://localhost:8545".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(provider.on_new_head("ethereum", 100).await.unwrap(), 0);
+        assert_eq!(provider.on_new_head("ethereum", 99).await.unwrap(), 0);
+        assert_eq!(provider.head_tracker.head("ethereum"), Some(100));
+    }
+
+    #[test]
+    fn test_network_overlay_applies_to_its_network_only() {
+        let mut config = ProviderConfig::default();
+        config
+            .cache_method_ttls
+            .insert("eth_gasPrice".to_string(), CacheTtl::Secs(5));
+        config.networks = network_overlay::parse(
+            r#"{"solana": {"timeout_seconds": 5, "max_retries": 6,
+                "cache_method_ttls": {"getSlot": "never"}}}"#,
+        )
+        .unwrap();
+
+        let solana = config.for_network("solana");
+        assert_eq!(solana.timeout_seconds, 5);
+        assert_eq!(solana.max_retries, 6);
+        assert_eq!(
+            solana.circuit_breaker_failure_threshold,
+            config.circuit_breaker_failure_threshold
+        );
+        assert_eq!(
+            solana.cache_method_ttls.get("getSlot"),
+            Some(&CacheTtl::Never)
+        );
+        assert_eq!(
+            solana.cache_method_ttls.get("eth_gasPrice"),
+            Some(&CacheTtl::Secs(5))
+        );
+
+        let ethereum = config.for_network("ethereum");
+        assert_eq!(ethereum.timeout_seconds, config.timeout_seconds);
+        assert!(ethereum.cache_method_ttls.get("getSlot").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_network_overlays_rebuild_running_pools() {
+        let mut config = ProviderConfig::default();
+        config.cache_enabled = false;
+        let provider = HttpRpcProvider::with_config(config);
+        provider
+            .register_endpoints("solana", vec!["http://localhost:8899".to_string()])
+            .await
+            .unwrap();
+        let before = provider.get_pool("solana").await.unwrap();
+        assert_eq!(before.cache().method_ttl("getSlot"), None);
+
+        let overlays =
+            network_overlay::parse(r#"{"solana": {"cache_method_ttls": {"getSlot": "never"}}}"#)
+                .unwrap();
+        provider.apply_network_overlays(overlays).await.unwrap();
+        let after = provider.get_pool("solana").await.unwrap();
+        assert!(!Arc::ptr_eq(&before, &after));
+        assert_eq!(after.cache().method_ttl("getSlot"), Some(CacheTtl::Never));
+    }
+
     #[tokio::test]
     async fn test_health_status_empty() {
         let provider = HttpRpcProvider::new();
@@ -0,0 +1,404 @@
+//! `eth_getLogs` range splitting
+//!
+//! Vendors cap log queries by block range (Alchemy, QuickNode, most public
+//! nodes) or by result count (Infura's 10,000 logs). A query over a range
+//! that is too large is answered with an error instead of logs, so the
+//! provider splits it:
+//! - with `max_block_range` set, the range is sent in chunks of that size
+//! - a chunk answered with a range or result-limit error is split in two,
+//!   or at the range the error suggests (Alchemy names one), and retried
+//! - the chunks' logs are concatenated in block order
+//!
+//! Queries by `blockHash` and ranges bounded by `safe`/`finalized` go out as
+//! they are. `max_requests` bounds the upstream calls a single query may take.
+
+use crate::endpoint_pool::{EndpointPool, RpcRequest};
+use crate::head_lag::block_height;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Error messages upstreams answer an oversized log query with (lowercase)
+const RANGE_ERRORS: &[&str] = &[
+    "query returned more than",
+    "log response size exceeded",
+    "block range",
+    "range is too large",
+    "range too large",
+    "exceed maximum",
+    "too many results",
+    "response size should not",
+];
+
+/// Log query settings (`HTTP_RPC_LOGS_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogsConfig {
+    /// Split `eth_getLogs` calls on range errors
+    pub enabled: bool,
+    /// Largest range sent upstream in one call; the whole range when unset
+    pub max_block_range: Option<u64>,
+    /// Most upstream calls one query may take
+    pub max_requests: usize,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_block_range: None,
+            max_requests: 256,
+        }
+    }
+}
+
+/// One end of a log filter's block range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockBound {
+    Number(u64),
+    /// `latest`, `pending` or left out: the chain head
+    Head,
+    /// A tag the range can't be split on (`safe`, `finalized`)
+    Unsplittable,
+}
+
+/// Bound given by a filter's `fromBlock` or `toBlock`
+pub fn block_bound(value: Option<&Value>) -> BlockBound {
+    match value.and_then(Value::as_str) {
+        None | Some("latest") | Some("pending") => BlockBound::Head,
+        Some("earliest") => BlockBound::Number(0),
+        Some(hex) => u64::from_str_radix(hex.trim_start_matches("0x"), 16)
+            .map_or(BlockBound::Unsplittable, BlockBound::Number),
+    }
+}
+
+/// Bounds of a filter whose range can be split; `None` for `blockHash`
+/// filters and ranges bounded by `safe`/`finalized`
+pub fn splittable_range(filter: &Value) -> Option<(BlockBound, BlockBound)> {
+    if filter.get("blockHash").is_some() {
+        return None;
+    }
+    let from = block_bound(filter.get("fromBlock"));
+    let to = block_bound(filter.get("toBlock"));
+    (from != BlockBound::Unsplittable && to != BlockBound::Unsplittable).then_some((from, to))
+}
+
+/// Logs of an `eth_getLogs` result
+pub fn into_logs(result: Value) -> Result<Vec<Value>> {
+    match result {
+        Value::Array(logs) => Ok(logs),
+        other => Err(anyhow!("Unexpected eth_getLogs result: {}", other)),
+    }
+}
+
+/// Whether an upstream error means the log query covered too much
+pub fn is_range_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    RANGE_ERRORS.iter().any(|pattern| message.contains(pattern))
+}
+
+/// Upstream refusal of an `eth_getLogs` query that covered too much
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeError {
+    pub message: String,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/// Whether `error` is a [`RangeError`] answering an `eth_getLogs` call, which
+/// says nothing about the endpoint's health
+pub fn is_log_range_error(method: &str, error: &anyhow::Error) -> bool {
+    method == "eth_getLogs" && error.is::<RangeError>()
+}
+
+/// Block range an error message suggests, e.g. Alchemy's
+/// "this block range should work: [0x10, 0x7df]"
+fn suggested_range(message: &str) -> Option<(u64, u64)> {
+    let start = message.rfind('[')?;
+    let end = start + message[start..].find(']')?;
+    let mut blocks = message[start + 1..end].split(',').map(|block| {
+        let block = block.trim();
+        let hex = block.strip_prefix("0x")?;
+        u64::from_str_radix(hex, 16).ok()
+    });
+    Some((blocks.next()??, blocks.next()??))
+}
+
+/// Fetch the logs of `filter` over `from..=to`, one `call` per chunk
+///
+/// `call` sends one `eth_getLogs` filter upstream and returns its result.
+pub async fn fetch_logs<F, Fut>(
+    filter: &Value,
+    from: u64,
+    to: u64,
+    config: &LogsConfig,
+    mut call: F,
+) -> Result<Vec<Value>>
+where
+    F: FnMut(Value) -> Fut,
+    Fut: Future<Output = Result<Value>>,
+{
+    let chunk = config.max_block_range.unwrap_or(u64::MAX).max(1);
+    // Stack of ranges still to fetch; the earliest is on top
+    let mut pending = Vec::new();
+    let mut start = from;
+    loop {
+        let end = start.saturating_add(chunk - 1).min(to);
+        pending.push((start, end));
+        if end >= to {
+            break;
+        }
+        start = end + 1;
+    }
+    pending.reverse();
+
+    let mut logs = Vec::new();
+    let mut requests = 0;
+    while let Some((start, end)) = pending.pop() {
+        requests += 1;
+        if requests > config.max_requests {
+            return Err(anyhow!(
+                "eth_getLogs over blocks {}-{} needs more than {} requests",
+                from,
+                to,
+                config.max_requests
+            ));
+        }
+
+        let mut range = filter.clone();
+        range["fromBlock"] = Value::String(format!("0x{:x}", start));
+        range["toBlock"] = Value::String(format!("0x{:x}", end));
+        let error = match call(range).await {
+            Ok(result) => {
+                logs.extend(into_logs(result)?);
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        let message = error.to_string();
+        if start == end || !is_range_error(&message) {
+            return Err(error);
+        }
+        let split = suggested_range(&message)
+            .filter(|(suggested, last)| *suggested == start && *last < end)
+            .map_or(start + (end - start) / 2, |(_, last)| last);
+        debug!(
+            "Splitting eth_getLogs over blocks {}-{} at {}: {}",
+            start, end, split, message
+        );
+        pending.push((split + 1, end));
+        pending.push((start, split));
+    }
+    Ok(logs)
+}
+
+/// `eth_getLogs` for `filter` through `pool`, split into block ranges the
+/// upstreams accept
+///
+/// Ranges ending at `latest` (or left open) are resolved to the current head
+/// first; `blockHash` filters are sent as they are.
+pub async fn pool_logs(
+    pool: Arc<EndpointPool>,
+    config: &LogsConfig,
+    filter: Value,
+) -> Result<Vec<Value>> {
+    let call = |filter: Value| {
+        let pool = pool.clone();
+        async move {
+            let request = RpcRequest::new("eth_getLogs", vec![filter]);
+            let response = pool.call_with_failover(&request).await?;
+            response
+                .result
+                .ok_or_else(|| anyhow!("No result in RPC response"))
+        }
+    };
+
+    let Some((from, to)) = splittable_range(&filter) else {
+        return into_logs(call(filter).await?);
+    };
+    let head = if from == BlockBound::Head || to == BlockBound::Head {
+        pool.call_with_failover(&RpcRequest::new("eth_blockNumber", vec![]))
+            .await?
+            .result
+            .as_ref()
+            .and_then(block_height)
+    } else {
+        None
+    };
+    let block = |bound| match bound {
+        BlockBound::Number(block) => Some(block),
+        _ => head,
+    };
+    let (Some(from), Some(to)) = (block(from), block(to)) else {
+        return Err(anyhow!("No block number for the log range"));
+    };
+    if from > to {
+        // Nothing to split; the upstream answers as without splitting
+        return into_logs(call(filter).await?);
+    }
+
+    fetch_logs(&filter, from, to, config, call).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    /// Upstream with one log per block that rejects ranges over `limit` blocks
+    fn upstream(filter: &Value, limit: u64, message: &str) -> Result<Value> {
+        let from = match block_bound(filter.get("fromBlock")) {
+            BlockBound::Number(block) => block,
+            other => panic!("unsplit bound {:?}", other),
+        };
+        let to = match block_bound(filter.get("toBlock")) {
+            BlockBound::Number(block) => block,
+            other => panic!("unsplit bound {:?}", other),
+        };
+        if to - from + 1 > limit {
+            return Err(anyhow!("RPC error -32005: {}", message));
+        }
+        Ok(Value::Array(
+            (from..=to)
+                .map(|block| json!({"blockNumber": block}))
+                .collect(),
+        ))
+    }
+
+    fn blocks(logs: &[Value]) -> Vec<u64> {
+        logs.iter()
+            .map(|log| log["blockNumber"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_bounds_and_range_errors() {
+        assert_eq!(block_bound(None), BlockBound::Head);
+        assert_eq!(block_bound(Some(&json!("earliest"))), BlockBound::Number(0));
+        assert_eq!(block_bound(Some(&json!("0x10"))), BlockBound::Number(16));
+        assert_eq!(
+            block_bound(Some(&json!("finalized"))),
+            BlockBound::Unsplittable
+        );
+        assert_eq!(
+            splittable_range(&json!({"fromBlock": "0x1"})),
+            Some((BlockBound::Number(1), BlockBound::Head))
+        );
+        assert_eq!(splittable_range(&json!({"blockHash": "0xabc"})), None);
+        assert_eq!(splittable_range(&json!({"toBlock": "safe"})), None);
+
+        assert!(is_range_error(
+            "RPC error -32005: query returned more than 10000 results"
+        ));
+        assert!(is_range_error(
+            "RPC error -32600: eth_getLogs is limited to a 10,000 block range"
+        ));
+        assert!(!is_range_error("RPC error -32000: execution reverted"));
+        let refused = anyhow::Error::new(RangeError {
+            message: "RPC error -32005: query returned more than 10000 results".to_string(),
+        });
+        assert!(is_log_range_error("eth_getLogs", &refused));
+        assert!(!is_log_range_error("eth_call", &refused));
+        assert!(!is_log_range_error(
+            "eth_getLogs",
+            &anyhow!("RPC error -32000: exceed maximum block range")
+        ));
+        assert_eq!(
+            suggested_range(
+                "Log response size exceeded. this block range should work: [0x10, 0x7df]"
+            ),
+            Some((0x10, 0x7df))
+        );
+        assert_eq!(
+            suggested_range("query returned more than 10000 results"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_range_split_and_stitched_in_order() {
+        let filter = json!({"address": "0xabc", "topics": []});
+        let calls = RefCell::new(0);
+        let logs = fetch_logs(&filter, 100, 399, &LogsConfig::default(), |range| {
+            *calls.borrow_mut() += 1;
+            assert_eq!(range["address"], "0xabc");
+            let result = upstream(&range, 64, "query returned more than 10000 results");
+            async move { result }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(blocks(&logs), (100..=399).collect::<Vec<_>>());
+        // 300 blocks halve down to chunks of at most 64
+        assert!(*calls.borrow() > 5);
+
+        let config = LogsConfig {
+            max_block_range: Some(100),
+            ..Default::default()
+        };
+        let calls = RefCell::new(0);
+        let logs = fetch_logs(&filter, 0, 249, &config, |range| {
+            *calls.borrow_mut() += 1;
+            let result = upstream(&range, 100, "block range too large");
+            async move { result }
+        })
+        .await
+        .unwrap();
+        assert_eq!(logs.len(), 250);
+        assert_eq!(*calls.borrow(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_suggested_range_and_request_cap() {
+        let filter = json!({});
+        let calls = RefCell::new(0);
+        let logs = fetch_logs(&filter, 0, 999, &LogsConfig::default(), |range| {
+            *calls.borrow_mut() += 1;
+            let start = range["fromBlock"].as_str().unwrap().to_string();
+            let suggestion = format!(
+                "Log response size exceeded. this block range should work: [{}, 0x{:x}]",
+                start,
+                u64::from_str_radix(start.trim_start_matches("0x"), 16).unwrap() + 499
+            );
+            let result = upstream(&range, 500, &suggestion);
+            async move { result }
+        })
+        .await
+        .unwrap();
+        assert_eq!(logs.len(), 1_000);
+        assert_eq!(*calls.borrow(), 3);
+
+        // Errors that are not about the range, and single blocks, are returned
+        let result = fetch_logs(&filter, 0, 9, &LogsConfig::default(), |_| async {
+            Err(anyhow!("RPC error -32000: header not found"))
+        })
+        .await;
+        assert!(result.unwrap_err().to_string().contains("header not found"));
+
+        let config = LogsConfig {
+            max_requests: 4,
+            ..Default::default()
+        };
+        let result = fetch_logs(&filter, 0, 999, &config, |range| {
+            let result = upstream(&range, 1, "query returned more than 10000 results");
+            async move { result }
+        })
+        .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("more than 4 requests"));
+    }
+}
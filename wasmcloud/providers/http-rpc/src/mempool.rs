@@ -0,0 +1,403 @@
+//! Pending-transaction ingestion
+//!
+//! Alerts on a transfer or a contract call can fire before it is mined if the
+//! provider watches the mempool. For every network in the mempool config, one
+//! background feed collects pending transactions and publishes each full body
+//! once on `mempool.{network}.{subnet}.pending`:
+//! - `subscribe`: `eth_subscribe("newPendingTransactions")` on the network's
+//!   WebSocket pool, fetching each announced hash with
+//!   `eth_getTransactionByHash` through the endpoint pool
+//! - `poll`: `txpool_content` every `poll_interval_ms`, whose pending section
+//!   already holds full bodies
+//! - `auto` (the default): subscribe when the network has a WebSocket pool,
+//!   poll otherwise
+//!
+//! Hashes already published are remembered (up to `max_seen`), so a
+//! transaction lingering in the pool, or announced again after a reconnect, is
+//! published once. Transactions mined or dropped before their body is fetched
+//! are skipped.
+
+use crate::endpoint_pool::{EndpointPool, RpcRequest};
+use crate::ws::{SubscriptionKind, WsPool};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{RwLock, Semaphore};
+use tracing::{debug, info, warn};
+
+/// Pending-transaction ingestion settings (`HTTP_RPC_MEMPOOL_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MempoolConfig {
+    pub enabled: bool,
+    /// Networks (pool names) to ingest, and how
+    pub networks: HashMap<String, MempoolNetwork>,
+    /// Milliseconds between `txpool_content` polls
+    pub poll_interval_ms: u64,
+    /// Transaction bodies fetched at once in `subscribe` mode
+    pub fetch_concurrency: usize,
+    /// Published hashes remembered to skip repeats
+    pub max_seen: usize,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            networks: HashMap::new(),
+            poll_interval_ms: 2000,
+            fetch_concurrency: 16,
+            max_seen: 50_000,
+        }
+    }
+}
+
+/// How one network's pending transactions are collected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolMode {
+    #[default]
+    Auto,
+    Subscribe,
+    Poll,
+}
+
+/// Ingestion of one network
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MempoolNetwork {
+    pub mode: MempoolMode,
+    /// Network and subnet tokens of the subject; by default the pool name
+    /// split at its first `-` (`avalanche-fuji`), with `mainnet` when it has
+    /// none (`ethereum`)
+    pub network: Option<String>,
+    pub subnet: Option<String>,
+}
+
+impl MempoolNetwork {
+    /// Network and subnet tokens for the pool named `pool_network`
+    pub fn subject_parts(&self, pool_network: &str) -> (String, String) {
+        let (network, subnet) = pool_network
+            .split_once('-')
+            .unwrap_or((pool_network, "mainnet"));
+        (
+            self.network.clone().unwrap_or_else(|| network.to_string()),
+            self.subnet.clone().unwrap_or_else(|| subnet.to_string()),
+        )
+    }
+}
+
+/// Where a pending transaction was seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingSource {
+    Subscription,
+    Txpool,
+}
+
+/// Message published for each pending transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub network: String,
+    pub subnet: String,
+    pub hash: String,
+    /// Full transaction body, as `eth_getTransactionByHash` answers it
+    pub transaction: Value,
+    pub source: PendingSource,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Recently published hashes, oldest forgotten first
+#[derive(Debug)]
+pub struct SeenHashes {
+    order: VecDeque<String>,
+    hashes: HashSet<String>,
+    capacity: usize,
+}
+
+impl SeenHashes {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            order: VecDeque::new(),
+            hashes: HashSet::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Remember `hash`; false if it was already seen
+    pub fn insert(&mut self, hash: &str) -> bool {
+        let hash = hash.to_lowercase();
+        if self.hashes.contains(&hash) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        self.order.push_back(hash.clone());
+        self.hashes.insert(hash);
+        true
+    }
+}
+
+/// Pending transaction bodies in a `txpool_content` answer
+///
+/// The answer maps sender to nonce to body; queued transactions (nonce gaps)
+/// cannot be mined yet and are left out.
+pub fn pending_from_txpool(content: &Value) -> Vec<Value> {
+    let Some(senders) = content.get("pending").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    senders
+        .values()
+        .filter_map(Value::as_object)
+        .flat_map(|nonces| nonces.values().cloned())
+        .filter(|transaction| transaction.get("hash").is_some_and(Value::is_string))
+        .collect()
+}
+
+/// Publishes one network's pending transactions
+#[derive(Clone)]
+struct Publisher {
+    client: async_nats::Client,
+    subject: String,
+    network: String,
+    subnet: String,
+}
+
+impl Publisher {
+    async fn publish(&self, hash: String, transaction: Value, source: PendingSource) {
+        let pending = PendingTransaction {
+            network: self.network.clone(),
+            subnet: self.subnet.clone(),
+            hash,
+            transaction,
+            source,
+            received_at: Utc::now(),
+        };
+        let payload = match serde_json::to_vec(&pending) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize pending transaction: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self
+            .client
+            .publish(self.subject.clone(), payload.into())
+            .await
+        {
+            warn!("Failed to publish pending transaction: {}", e);
+        }
+    }
+}
+
+/// Collect and publish `pool_network`'s pending transactions until aborted
+pub async fn run(
+    pool_network: String,
+    config: MempoolConfig,
+    ws_pool: Option<Arc<WsPool>>,
+    pools: Arc<RwLock<HashMap<String, Arc<EndpointPool>>>>,
+    client: async_nats::Client,
+) {
+    let source = config
+        .networks
+        .get(&pool_network)
+        .cloned()
+        .unwrap_or_default();
+    let (network, subnet) = source.subject_parts(&pool_network);
+    let publisher = Publisher {
+        client,
+        subject: subject_registry::prefixed(&subject_registry::mempool::pending(&network, &subnet)),
+        network,
+        subnet,
+    };
+    let seen = SeenHashes::new(config.max_seen);
+
+    match (source.mode, ws_pool) {
+        (MempoolMode::Poll, _) | (MempoolMode::Auto, None) => {
+            info!(
+                "Polling txpool of {} for {}",
+                pool_network, publisher.subject
+            );
+            poll(&pool_network, &config, &pools, &publisher, seen).await;
+        }
+        (_, Some(ws_pool)) => {
+            info!(
+                "Subscribing to pending transactions of {} for {}",
+                pool_network, publisher.subject
+            );
+            subscribe(&pool_network, &config, &ws_pool, &pools, &publisher, seen).await;
+        }
+        (MempoolMode::Subscribe, None) => {
+            warn!(
+                "No WebSocket pool for {}, pending transactions not ingested",
+                pool_network
+            );
+        }
+    }
+}
+
+/// Pool currently registered for `network`
+async fn current_pool(
+    pools: &RwLock<HashMap<String, Arc<EndpointPool>>>,
+    network: &str,
+) -> Option<Arc<EndpointPool>> {
+    pools.read().await.get(network).cloned()
+}
+
+async fn poll(
+    network: &str,
+    config: &MempoolConfig,
+    pools: &RwLock<HashMap<String, Arc<EndpointPool>>>,
+    publisher: &Publisher,
+    mut seen: SeenHashes,
+) {
+    let request = RpcRequest::new("txpool_content", vec![]);
+    let mut ticker = tokio::time::interval(Duration::from_millis(config.poll_interval_ms.max(100)));
+    loop {
+        ticker.tick().await;
+        let Some(pool) = current_pool(pools, network).await else {
+            continue;
+        };
+        let content = match pool.call_with_failover(&request).await {
+            Ok(response) => response.result.unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to read txpool of {}: {}", network, e);
+                continue;
+            }
+        };
+        for transaction in pending_from_txpool(&content) {
+            let hash = transaction["hash"].as_str().unwrap_or_default().to_string();
+            if seen.insert(&hash) {
+                publisher
+                    .publish(hash, transaction, PendingSource::Txpool)
+                    .await;
+            }
+        }
+    }
+}
+
+async fn subscribe(
+    network: &str,
+    config: &MempoolConfig,
+    ws_pool: &WsPool,
+    pools: &RwLock<HashMap<String, Arc<EndpointPool>>>,
+    publisher: &Publisher,
+    mut seen: SeenHashes,
+) {
+    let (_, mut announced) =
+        ws_pool.subscribe(SubscriptionKind::PendingTransactions { full: false });
+    let fetches = Arc::new(Semaphore::new(config.fetch_concurrency.max(1)));
+
+    while let Some(notification) = announced.recv().await {
+        // Some nodes push full bodies even when asked for hashes
+        let (hash, body) = match notification.result {
+            Value::String(hash) => (hash, None),
+            body => match body.get("hash").and_then(Value::as_str) {
+                Some(hash) => (hash.to_string(), Some(body.clone())),
+                None => continue,
+            },
+        };
+        if !seen.insert(&hash) {
+            continue;
+        }
+        if let Some(body) = body {
+            publisher
+                .publish(hash, body, PendingSource::Subscription)
+                .await;
+            continue;
+        }
+
+        let Some(pool) = current_pool(pools, network).await else {
+            continue;
+        };
+        let Ok(permit) = fetches.clone().acquire_owned().await else {
+            break;
+        };
+        let publisher = publisher.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            let request = RpcRequest::new("eth_getTransactionByHash", vec![json!(hash)]);
+            match pool.call_with_failover(&request).await {
+                Ok(response) => match response.result {
+                    Some(body) if !body.is_null() => {
+                        publisher
+                            .publish(hash, body, PendingSource::Subscription)
+                            .await;
+                    }
+                    _ => debug!("Pending transaction {} gone before fetch", hash),
+                },
+                Err(e) => debug!("Failed to fetch pending transaction {}: {}", hash, e),
+            }
+        });
+    }
+    warn!("Pending transaction subscription for {} ended", network);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_parts_from_pool_name() {
+        let default = MempoolNetwork::default();
+        assert_eq!(
+            default.subject_parts("ethereum"),
+            ("ethereum".to_string(), "mainnet".to_string())
+        );
+        assert_eq!(
+            default.subject_parts("avalanche-fuji"),
+            ("avalanche".to_string(), "fuji".to_string())
+        );
+
+        let config: MempoolConfig = serde_json::from_str(
+            r#"{"enabled": true, "networks": {"sepolia": {"mode": "poll", "network": "ethereum", "subnet": "sepolia"}}}"#,
+        )
+        .unwrap();
+        let sepolia = &config.networks["sepolia"];
+        assert_eq!(sepolia.mode, MempoolMode::Poll);
+        assert_eq!(
+            sepolia.subject_parts("sepolia"),
+            ("ethereum".to_string(), "sepolia".to_string())
+        );
+        assert_eq!(config.poll_interval_ms, 2000);
+    }
+
+    #[test]
+    fn test_seen_hashes_bounded() {
+        let mut seen = SeenHashes::new(2);
+        assert!(seen.insert("0xAA"));
+        assert!(!seen.insert("0xaa"));
+        assert!(seen.insert("0xbb"));
+        assert!(seen.insert("0xcc"));
+        // The oldest hash was forgotten
+        assert!(seen.insert("0xaa"));
+        assert!(!seen.insert("0xcc"));
+    }
+
+    #[test]
+    fn test_pending_from_txpool() {
+        let content = json!({
+            "pending": {
+                "0xsender1": {
+                    "7": {"hash": "0x01", "nonce": "0x7"},
+                    "8": {"hash": "0x02", "nonce": "0x8"}
+                },
+                "0xsender2": {"1": {"hash": "0x03", "nonce": "0x1"}}
+            },
+            "queued": {"0xsender3": {"9": {"hash": "0x04", "nonce": "0x9"}}}
+        });
+        let mut hashes: Vec<String> = pending_from_txpool(&content)
+            .iter()
+            .map(|transaction| transaction["hash"].as_str().unwrap().to_string())
+            .collect();
+        hashes.sort();
+        assert_eq!(hashes, vec!["0x01", "0x02", "0x03"]);
+        assert!(pending_from_txpool(&Value::Null).is_empty());
+    }
+}
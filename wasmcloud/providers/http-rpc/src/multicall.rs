@@ -0,0 +1,437 @@
+//! Multicall3 aggregation
+//!
+//! Reading many contract values (balances across tokens, a pool's reserves,
+//! several getters) is one `eth_call` each. On networks where Multicall3 is
+//! deployed, [`aggregate`] packs them into `aggregate3` calls, one per block
+//! tag and at most `max_calls` each, and decodes every call's answer back out:
+//! - a call that succeeded answers its return data, as `eth_call` would
+//! - a call that reverted answers a `REVERTED_CODE` error carrying the revert
+//!   data, without failing the others
+//!
+//! Only plain reads are aggregated: `to` and `data` with an optional block tag.
+//! Calls with `from`, `value`, gas settings or state overrides would behave
+//! differently through the contract, so they go out on their own in one
+//! batch, as do the calls of an `aggregate3` that fails as a whole (Multicall3
+//! missing, gas cap reached).
+
+use crate::batch::{RpcBatchRequest, RpcBatchResponse};
+use crate::endpoint_pool::{EndpointPool, RpcError, RpcRequest, RpcResponse};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Multicall3's address on every chain it was deployed to with its
+/// deterministic deployment
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// `aggregate3((address,bool,bytes)[])`
+const AGGREGATE3_SELECTOR: [u8; 4] = [0x82, 0xad, 0x56, 0xcb];
+
+/// Error code of a reverted call, as nodes answer `eth_call`
+pub const REVERTED_CODE: i32 = 3;
+
+/// Networks Multicall3 is deployed on at [`MULTICALL3_ADDRESS`]
+const BUILTIN_NETWORKS: &[&str] = &[
+    "ethereum",
+    "ethereum-sepolia",
+    "ethereum-holesky",
+    "avalanche",
+    "avalanche-fuji",
+    "polygon",
+    "arbitrum",
+    "optimism",
+    "base",
+    "bsc",
+];
+
+/// Multicall3 settings (`HTTP_RPC_MULTICALL_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MulticallConfig {
+    pub enabled: bool,
+    /// Multicall3 address per network, on top of the built-in networks
+    pub networks: HashMap<String, String>,
+    /// Most calls packed into one `aggregate3`
+    pub max_calls: usize,
+}
+
+impl Default for MulticallConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            networks: HashMap::new(),
+            max_calls: 100,
+        }
+    }
+}
+
+impl MulticallConfig {
+    /// Multicall3 address on `network`; `None` when calls go out one by one
+    pub fn address(&self, network: &str) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        self.networks.get(network).cloned().or_else(|| {
+            BUILTIN_NETWORKS
+                .contains(&network)
+                .then(|| MULTICALL3_ADDRESS.to_string())
+        })
+    }
+}
+
+/// One call of an `aggregate3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call3 {
+    pub target: [u8; 20],
+    pub call_data: Vec<u8>,
+}
+
+/// The call an `eth_call` request makes and its block tag, if it is a plain
+/// read that can go through Multicall3
+pub fn aggregatable(request: &RpcRequest) -> Option<(Call3, String)> {
+    if request.method != "eth_call" || request.params.len() > 2 {
+        return None;
+    }
+    let call = request.params.first()?.as_object()?;
+    if call
+        .keys()
+        .any(|key| !matches!(key.as_str(), "to" | "data" | "input"))
+    {
+        return None;
+    }
+    let target: [u8; 20] = decode_hex(call.get("to")?.as_str()?)?.try_into().ok()?;
+    let call_data = match call.get("input").or_else(|| call.get("data")) {
+        Some(data) => decode_hex(data.as_str()?)?,
+        None => Vec::new(),
+    };
+    let block = match request.params.get(1) {
+        Some(block) => block.as_str()?.to_string(),
+        None => "latest".to_string(),
+    };
+    Some((Call3 { target, call_data }, block))
+}
+
+/// Calls of a batch grouped into `aggregate3` calls
+#[derive(Debug, Default, PartialEq)]
+pub struct Plan {
+    /// Block tag and request indexes of each `aggregate3`
+    pub groups: Vec<(String, Vec<usize>)>,
+    /// Requests sent on their own
+    pub individual: Vec<usize>,
+}
+
+/// Group the aggregatable calls of `requests` by block tag, `max_calls` at most
+/// per group; groups of one call are sent on their own
+pub fn plan(requests: &[RpcRequest], max_calls: usize) -> Plan {
+    let mut by_block: Vec<(String, Vec<usize>)> = Vec::new();
+    let mut individual = Vec::new();
+    for (index, request) in requests.iter().enumerate() {
+        let Some((_, block)) = aggregatable(request) else {
+            individual.push(index);
+            continue;
+        };
+        match by_block.iter_mut().find(|(tag, _)| *tag == block) {
+            Some((_, indexes)) => indexes.push(index),
+            None => by_block.push((block, vec![index])),
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (block, indexes) in by_block {
+        for chunk in indexes.chunks(max_calls.max(1)) {
+            if chunk.len() == 1 {
+                individual.push(chunk[0]);
+            } else {
+                groups.push((block.clone(), chunk.to_vec()));
+            }
+        }
+    }
+    individual.sort_unstable();
+    Plan { groups, individual }
+}
+
+/// Calldata of `aggregate3(calls)` with every call allowed to fail
+pub fn encode_aggregate3(calls: &[Call3]) -> Vec<u8> {
+    let tuples: Vec<Vec<u8>> = calls
+        .iter()
+        .map(|call| {
+            let mut tuple = Vec::with_capacity(128 + call.call_data.len());
+            tuple.extend_from_slice(&[0u8; 12]);
+            tuple.extend_from_slice(&call.target);
+            tuple.extend_from_slice(&word(1));
+            tuple.extend_from_slice(&word(0x60));
+            tuple.extend_from_slice(&word(call.call_data.len()));
+            tuple.extend_from_slice(&call.call_data);
+            tuple.resize(tuple.len() + padding(call.call_data.len()), 0);
+            tuple
+        })
+        .collect();
+
+    let mut data = AGGREGATE3_SELECTOR.to_vec();
+    data.extend_from_slice(&word(0x20));
+    data.extend_from_slice(&word(calls.len()));
+    let mut offset = 32 * calls.len();
+    for tuple in &tuples {
+        data.extend_from_slice(&word(offset));
+        offset += tuple.len();
+    }
+    for tuple in tuples {
+        data.extend_from_slice(&tuple);
+    }
+    data
+}
+
+/// `(success, returnData)` per call of an `aggregate3` answer; `None` when the
+/// answer is malformed or holds another number of calls than `count`
+pub fn decode_aggregate3(data: &[u8], count: usize) -> Option<Vec<(bool, Vec<u8>)>> {
+    let read = |at: usize| -> Option<usize> {
+        let word = data.get(at..at.checked_add(32)?)?;
+        if word[..24].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+    };
+
+    let array = read(0)?;
+    if read(array)? != count {
+        return None;
+    }
+    let base = array.checked_add(32)?;
+    (0..count)
+        .map(|i| {
+            let tuple = base.checked_add(read(base.checked_add(32 * i)?)?)?;
+            let success = read(tuple)? != 0;
+            let bytes = tuple.checked_add(read(tuple.checked_add(32)?)?)?;
+            let len = read(bytes)?;
+            let start = bytes.checked_add(32)?;
+            let return_data = data.get(start..start.checked_add(len)?)?.to_vec();
+            Some((success, return_data))
+        })
+        .collect()
+}
+
+/// Answer `batch` on `pool`, aggregating plain `eth_call`s through the
+/// Multicall3 contract at `address`
+///
+/// Responses come back in request order with the callers' ids, as from
+/// [`EndpointPool::execute_batch`].
+pub async fn aggregate(
+    pool: &EndpointPool,
+    address: &str,
+    batch: RpcBatchRequest,
+    max_calls: usize,
+) -> Result<RpcBatchResponse> {
+    let Plan {
+        groups,
+        mut individual,
+    } = plan(&batch.requests, max_calls);
+    let mut responses: Vec<Option<RpcResponse>> = vec![None; batch.len()];
+
+    for (block, indexes) in groups {
+        let calls: Vec<Call3> = indexes
+            .iter()
+            .filter_map(|index| aggregatable(&batch.requests[*index]))
+            .map(|(call, _)| call)
+            .collect();
+        let priority = indexes
+            .iter()
+            .map(|index| batch.requests[*index].priority)
+            .min()
+            .unwrap_or_default();
+        let request = RpcRequest::new(
+            "eth_call",
+            vec![
+                json!({"to": address, "data": encode_hex(&encode_aggregate3(&calls))}),
+                Value::String(block),
+            ],
+        )
+        .with_priority(priority);
+        let decoded = match pool.call_with_failover(&request).await {
+            Ok(response) => response
+                .result
+                .as_ref()
+                .and_then(Value::as_str)
+                .and_then(decode_hex)
+                .and_then(|data| decode_aggregate3(&data, calls.len())),
+            Err(e) => {
+                debug!("aggregate3 of {} calls failed: {}", calls.len(), e);
+                None
+            }
+        };
+        let Some(decoded) = decoded else {
+            individual.extend(indexes);
+            continue;
+        };
+
+        for (index, (success, return_data)) in indexes.into_iter().zip(decoded) {
+            let data = encode_hex(&return_data);
+            let (result, error) = if success {
+                (Some(Value::String(data)), None)
+            } else {
+                let error = RpcError {
+                    code: REVERTED_CODE,
+                    message: "execution reverted".to_string(),
+                    data: Some(Value::String(data)),
+                };
+                (None, Some(error))
+            };
+            responses[index] = Some(RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result,
+                error,
+                id: batch.requests[index].id,
+            });
+        }
+    }
+
+    if !individual.is_empty() {
+        individual.sort_unstable();
+        let requests = individual
+            .iter()
+            .map(|index| batch.requests[*index].clone())
+            .collect();
+        let answered = pool.execute_batch(RpcBatchRequest::new(requests)).await?;
+        for (index, response) in individual.into_iter().zip(answered.responses) {
+            responses[index] = Some(response);
+        }
+    }
+
+    Ok(RpcBatchResponse {
+        responses: responses.into_iter().flatten().collect(),
+    })
+}
+
+/// ABI word holding `value`
+fn word(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// Zero bytes padding `len` bytes to a whole number of words
+fn padding(len: usize) -> usize {
+    (32 - len % 32) % 32
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x")?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 + bytes.len() * 2);
+    hex.push_str("0x");
+    for byte in bytes {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(to: &str, data: &str) -> RpcRequest {
+        RpcRequest::new("eth_call", vec![json!({"to": to, "data": data})])
+    }
+
+    /// `aggregate3` return data for `results`
+    fn encode_results(results: &[(bool, &[u8])]) -> Vec<u8> {
+        let tuples: Vec<Vec<u8>> = results
+            .iter()
+            .map(|(success, data)| {
+                let mut tuple = word(*success as usize).to_vec();
+                tuple.extend_from_slice(&word(0x40));
+                tuple.extend_from_slice(&word(data.len()));
+                tuple.extend_from_slice(data);
+                tuple.resize(tuple.len() + padding(data.len()), 0);
+                tuple
+            })
+            .collect();
+        let mut out = word(0x20).to_vec();
+        out.extend_from_slice(&word(results.len()));
+        let mut offset = 32 * results.len();
+        for tuple in &tuples {
+            out.extend_from_slice(&word(offset));
+            offset += tuple.len();
+        }
+        for tuple in tuples {
+            out.extend_from_slice(&tuple);
+        }
+        out
+    }
+
+    #[test]
+    fn test_encode_aggregate3() {
+        let (call, block) = aggregatable(&call(
+            "0x0000000000000000000000000000000000000001",
+            "0xabcdef",
+        ))
+        .unwrap();
+        assert_eq!(block, "latest");
+
+        let data = encode_aggregate3(&[call]);
+        assert_eq!(data[..4], AGGREGATE3_SELECTOR);
+        let words: Vec<&[u8]> = data[4..].chunks(32).collect();
+        assert_eq!(words.len(), 8);
+        assert_eq!(words[0], word(0x20)); // array offset
+        assert_eq!(words[1], word(1)); // length
+        assert_eq!(words[2], word(0x20)); // first tuple, after the offsets
+        assert_eq!(words[3], word(1)); // target
+        assert_eq!(words[4], word(1)); // allowFailure
+        assert_eq!(words[5], word(0x60)); // callData offset in the tuple
+        assert_eq!(words[6], word(3)); // callData length
+        assert_eq!(words[7][..4], [0xab, 0xcd, 0xef, 0]);
+    }
+
+    #[test]
+    fn test_decode_aggregate3() {
+        let data = encode_results(&[(true, &[0x2a; 32]), (false, &[0x08, 0xc3, 0x79, 0xa0])]);
+        assert_eq!(
+            decode_aggregate3(&data, 2).unwrap(),
+            vec![
+                (true, vec![0x2a; 32]),
+                (false, vec![0x08, 0xc3, 0x79, 0xa0])
+            ]
+        );
+        assert_eq!(decode_aggregate3(&data, 3), None);
+        assert_eq!(decode_aggregate3(&data[..100], 2), None);
+        assert_eq!(decode_aggregate3(&[0xff; 64], 1), None);
+    }
+
+    #[test]
+    fn test_plan_groups_plain_reads_by_block() {
+        let token = "0x0000000000000000000000000000000000000002";
+        let requests = vec![
+            call(token, "0x01"),
+            RpcRequest::new(
+                "eth_call",
+                vec![json!({"to": token, "data": "0x02"}), json!("0x10")],
+            ),
+            call(token, "0x03"),
+            RpcRequest::new(
+                "eth_call",
+                vec![json!({"to": token, "data": "0x04", "from": token})],
+            ),
+            RpcRequest::new("eth_blockNumber", vec![]),
+            call(token, "0x05"),
+        ];
+        let plan = plan(&requests, 2);
+        assert_eq!(plan.groups, vec![("latest".to_string(), vec![0, 2])]);
+        // The lone call at 0x10, the call with `from`, other methods and the
+        // chunk left over go out on their own
+        assert_eq!(plan.individual, vec![1, 3, 4, 5]);
+
+        let config = MulticallConfig::default();
+        assert_eq!(config.address("base"), Some(MULTICALL3_ADDRESS.to_string()));
+        assert_eq!(config.address("devnet"), None);
+    }
+}
@@ -0,0 +1,324 @@
+//! JSON-RPC over NATS request/reply
+//!
+//! Components outside wasmCloud (the Django API, scripts, other services) can
+//! use the provider's pools without a link by sending a JSON-RPC payload as a
+//! NATS request to `rpc.request.{network}.{subnet}`:
+//! - a single call object is answered with one JSON-RPC response object
+//! - an array is sent as a batch and answered with an array in the same order
+//! - the caller's ids are kept, whatever their type
+//! - a call may name its queue `priority` (`realtime`, the default,
+//!   `backfill` or `bulk`)
+//!
+//! The subject's network and subnet pick the pool: `ethereum.mainnet` is the
+//! `ethereum` pool and `avalanche.fuji` the `avalanche-fuji` pool, unless
+//! `pools` names another. Single calls go through the same path as link calls
+//! (cache, failover, `eth_getLogs` range splitting). Upstream errors keep their
+//! JSON-RPC code; provider failures (no pool, every endpoint down) answer
+//! `-32603`.
+
+use crate::batch::RpcBatchRequest;
+use crate::endpoint_pool::{EndpointPool, RpcRequest};
+use crate::logs::{self, LogsConfig};
+use crate::priority::Priority;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Subject pattern JSON-RPC requests are received on (canonical form)
+pub const REQUEST_SUBJECTS: &str = "rpc.request.*.*";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// NATS request/reply settings (`HTTP_RPC_NATS_RPC_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NatsRpcConfig {
+    pub enabled: bool,
+    /// Pool per `{network}.{subnet}`, over the default naming
+    pub pools: HashMap<String, String>,
+}
+
+impl Default for NatsRpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pools: HashMap::new(),
+        }
+    }
+}
+
+impl NatsRpcConfig {
+    /// Pool answering requests for `network`/`subnet`
+    pub fn pool_network(&self, network: &str, subnet: &str) -> String {
+        if let Some(pool) = self.pools.get(&format!("{}.{}", network, subnet)) {
+            return pool.clone();
+        }
+        if subnet == "mainnet" {
+            network.to_string()
+        } else {
+            format!("{}-{}", network, subnet)
+        }
+    }
+}
+
+/// Network and subnet of a canonical `rpc.request.{network}.{subnet}` subject
+pub fn parse_subject(subject: &str) -> Option<(String, String)> {
+    let tokens: Vec<&str> = subject.split('.').collect();
+    match tokens.as_slice() {
+        ["rpc", "request", network, subnet] if !network.is_empty() && !subnet.is_empty() => {
+            Some((network.to_string(), subnet.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// JSON-RPC error response
+pub fn error_reply(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+}
+
+/// JSON-RPC response for the outcome of one call
+fn reply(id: Value, outcome: Result<Value>) -> Value {
+    match outcome {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => {
+            let (code, message) = error_parts(&e.to_string());
+            error_reply(id, code, &message)
+        }
+    }
+}
+
+/// Code and message of a call error; upstream errors (`RPC error {code}:
+/// {message}`) keep theirs
+fn error_parts(message: &str) -> (i64, String) {
+    let upstream = message.strip_prefix("RPC error ").and_then(|rest| {
+        let (code, message) = rest.split_once(": ")?;
+        Some((code.parse().ok()?, message.to_string()))
+    });
+    upstream.unwrap_or_else(|| (INTERNAL_ERROR, message.to_string()))
+}
+
+/// Caller's id and the request for one call object, or the error reply
+fn parse_call(call: Value) -> std::result::Result<(Value, RpcRequest), Value> {
+    let id = call.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = call.get("method").and_then(Value::as_str) else {
+        return Err(error_reply(
+            id,
+            INVALID_REQUEST,
+            "Invalid request: no method",
+        ));
+    };
+    let params = match call.get("params") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(params)) => params.clone(),
+        Some(_) => {
+            return Err(error_reply(
+                id,
+                INVALID_PARAMS,
+                "Invalid params: expected an array",
+            ))
+        }
+    };
+    let priority = match call.get("priority") {
+        None | Some(Value::Null) => Priority::default(),
+        Some(priority) => match serde_json::from_value::<Priority>(priority.clone()) {
+            Ok(priority) => priority,
+            Err(_) => {
+                return Err(error_reply(
+                    id,
+                    INVALID_REQUEST,
+                    "Invalid request: priority must be realtime, backfill or bulk",
+                ))
+            }
+        },
+    };
+    Ok((id, RpcRequest::new(method, params).with_priority(priority)))
+}
+
+/// Answer a request payload with `pool`; `None` when the subject's network
+/// has no pool
+pub async fn answer(
+    pool: Option<Arc<EndpointPool>>,
+    logs_config: &LogsConfig,
+    payload: &[u8],
+) -> Value {
+    let payload: Value = match serde_json::from_slice(payload) {
+        Ok(payload) => payload,
+        Err(e) => return error_reply(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e)),
+    };
+    let Some(pool) = pool else {
+        return error_reply(
+            payload.get("id").cloned().unwrap_or(Value::Null),
+            INTERNAL_ERROR,
+            "No endpoint pool configured for this network",
+        );
+    };
+
+    match payload {
+        Value::Array(calls) if calls.is_empty() => {
+            error_reply(Value::Null, INVALID_REQUEST, "Invalid request: empty batch")
+        }
+        Value::Array(calls) => Value::Array(answer_batch(&pool, calls).await),
+        call => answer_call(pool, logs_config, call).await,
+    }
+}
+
+async fn answer_call(pool: Arc<EndpointPool>, logs_config: &LogsConfig, call: Value) -> Value {
+    let (id, request) = match parse_call(call) {
+        Ok(call) => call,
+        Err(reply) => return reply,
+    };
+
+    let outcome =
+        if request.method == "eth_getLogs" && request.params.len() == 1 && logs_config.enabled {
+            let filter = request.params.into_iter().next().unwrap_or_default();
+            logs::pool_logs(pool, logs_config, filter)
+                .await
+                .map(Value::Array)
+        } else {
+            pool.call_with_failover(&request)
+                .await
+                .map(|response| response.result.unwrap_or(Value::Null))
+        };
+    reply(id, outcome)
+}
+
+/// Replies to a batch in call order, invalid calls answered in place
+async fn answer_batch(pool: &EndpointPool, calls: Vec<Value>) -> Vec<Value> {
+    let mut replies: Vec<Option<Value>> = vec![None; calls.len()];
+    let mut sent = Vec::new();
+    let mut requests = Vec::new();
+    for (position, call) in calls.into_iter().enumerate() {
+        match parse_call(call) {
+            Ok((id, mut request)) => {
+                // Caller ids need not be numbers; positions stand in upstream
+                request.id = position as u64;
+                sent.push((position, id));
+                requests.push(request);
+            }
+            Err(reply) => replies[position] = Some(reply),
+        }
+    }
+
+    if !requests.is_empty() {
+        match pool.execute_batch(RpcBatchRequest::new(requests)).await {
+            Ok(batch) => {
+                for ((position, id), response) in sent.into_iter().zip(batch.responses) {
+                    replies[position] = Some(match response.error {
+                        Some(error) => error_reply(id, error.code as i64, &error.message),
+                        None => reply(id, Ok(response.result.unwrap_or(Value::Null))),
+                    });
+                }
+            }
+            Err(e) => {
+                let (code, message) = error_parts(&e.to_string());
+                for (position, id) in sent {
+                    replies[position] = Some(error_reply(id, code, &message));
+                }
+            }
+        }
+    }
+    replies.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint_pool::EndpointPoolConfig;
+
+    #[test]
+    fn test_subjects_map_to_pools() {
+        assert_eq!(
+            parse_subject("rpc.request.ethereum.mainnet"),
+            Some(("ethereum".to_string(), "mainnet".to_string()))
+        );
+        assert_eq!(parse_subject("rpc.request.ethereum"), None);
+        assert_eq!(parse_subject("rpc.endpoints.ethereum.add"), None);
+
+        let mut config = NatsRpcConfig::default();
+        assert_eq!(config.pool_network("ethereum", "mainnet"), "ethereum");
+        assert_eq!(config.pool_network("avalanche", "fuji"), "avalanche-fuji");
+        config
+            .pools
+            .insert("ethereum.sepolia".to_string(), "sepolia".to_string());
+        assert_eq!(config.pool_network("ethereum", "sepolia"), "sepolia");
+    }
+
+    #[test]
+    fn test_upstream_error_codes_kept() {
+        assert_eq!(
+            error_parts("RPC error 3: execution reverted: paused"),
+            (3, "execution reverted: paused".to_string())
+        );
+        assert_eq!(
+            error_parts("All endpoints are unhealthy (circuit breakers open)"),
+            (
+                INTERNAL_ERROR,
+                "All endpoints are unhealthy (circuit breakers open)".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_call_priority_parsed() {
+        let (_, request) = parse_call(json!({"id": 1, "method": "eth_chainId"})).unwrap();
+        assert_eq!(request.priority, Priority::Realtime);
+        let (_, request) =
+            parse_call(json!({"id": 1, "method": "eth_getLogs", "priority": "backfill"})).unwrap();
+        assert_eq!(request.priority, Priority::Backfill);
+
+        let reply = parse_call(json!({"id": 2, "method": "eth_chainId", "priority": "urgent"}))
+            .unwrap_err();
+        assert_eq!(reply["id"], 2);
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_payloads_answered_without_upstream_calls() {
+        let config = LogsConfig::default();
+        let reply = answer(None, &config, b"{not json").await;
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+
+        let reply = answer(None, &config, br#"{"id": "a", "method": "eth_chainId"}"#).await;
+        assert_eq!(reply["id"], "a");
+        assert_eq!(reply["error"]["code"], INTERNAL_ERROR);
+
+        // Unreachable endpoint: only calls that parse would reach it
+        let pool_config = EndpointPoolConfig {
+            endpoints: vec!["http://127.0.0.1:1".to_string()],
+            max_retries: 1,
+            ..Default::default()
+        };
+        let pool = Arc::new(EndpointPool::new("ethereum".to_string(), pool_config).unwrap());
+        let reply = answer(
+            Some(pool.clone()),
+            &config,
+            br#"{"id": 7, "method": "eth_getBalance", "params": {"address": "0xabc"}}"#,
+        )
+        .await;
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+
+        let reply = answer(Some(pool.clone()), &config, b"[]").await;
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+
+        let reply = answer(Some(pool), &config, br#"[{"id": "x"}, {"id": "y"}]"#).await;
+        let ids: Vec<&str> = reply
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|reply| reply["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["x", "y"]);
+        assert_eq!(reply[0]["error"]["code"], INVALID_REQUEST);
+    }
+}
@@ -0,0 +1,156 @@
+//! Negative caching
+//!
+//! Actors retry lookups that keep failing the same way: a receipt for a
+//! transaction that is not mined (or never will be), the code of an address
+//! with none, a call that reverts. Each repeat costs upstream requests, and
+//! with failover several. These answers are cached too, but only for
+//! `ttl_secs`, since most of them change eventually:
+//! - `null` results of lookup methods (transaction, receipt, block not found),
+//!   Solana's `getBlock` and `getTransaction` included
+//! - `0x` from `eth_getCode` (no contract at the address)
+//! - errors matching a known not-found, reverted or not-verified message,
+//!   once every attempt has failed with it
+//!
+//! Entries live next to the positive ones (see [`crate::cache::negative_key`])
+//! and, for `latest` reads, are dropped on the next block like them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Methods whose `null` result means "not found"
+const LOOKUP_METHODS: &[&str] = &[
+    "eth_getTransactionByHash",
+    "eth_getTransactionReceipt",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getTransactionByBlockHashAndIndex",
+    "eth_getTransactionByBlockNumberAndIndex",
+    "getBlock",
+    "getTransaction",
+];
+
+/// Error messages that repeat as long as the chain stays as it is (lowercase)
+const NEGATIVE_ERRORS: &[&str] = &[
+    "transaction not found",
+    "block not found",
+    "unknown block",
+    "no contract code",
+    "contract has no code",
+    "execution reverted",
+    "not verified",
+    "was skipped",
+];
+
+/// Negative caching settings (`HTTP_RPC_NEGATIVE_CACHE_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NegativeCacheConfig {
+    pub enabled: bool,
+    /// Seconds a failing answer is served from the cache
+    pub ttl_secs: u64,
+    /// Extra error messages to cache (any case)
+    pub error_patterns: Vec<String>,
+}
+
+impl Default for NegativeCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 15,
+            error_patterns: Vec::new(),
+        }
+    }
+}
+
+impl NegativeCacheConfig {
+    /// Whether a `method` answer of `result` (`None` for `null`) is a
+    /// not-found answer
+    pub fn is_negative_result(&self, method: &str, result: Option<&Value>) -> bool {
+        self.enabled
+            && match result {
+                None => LOOKUP_METHODS.contains(&method),
+                Some(result) => method == "eth_getCode" && result.as_str() == Some("0x"),
+            }
+    }
+
+    /// Whether a failed call's error repeats on every retry
+    pub fn is_negative_error(&self, message: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let message = message.to_lowercase();
+        NEGATIVE_ERRORS
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(self.error_patterns.iter().map(|p| p.to_lowercase()))
+            .any(|pattern| message.contains(&pattern))
+    }
+}
+
+/// A cached failing answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum NegativeAnswer {
+    /// The call answered `result` (`None` for `null`)
+    Result(Option<Value>),
+    /// The call failed with this error message
+    Error(String),
+}
+
+impl NegativeAnswer {
+    /// Form stored in the cache
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Result(result) => json!({ "result": result }),
+            Self::Error(message) => json!({ "error": message }),
+        }
+    }
+
+    /// Answer stored as `value`; `None` for entries of another shape
+    pub fn from_value(value: &Value) -> Option<Self> {
+        if let Some(message) = value.get("error") {
+            return Some(Self::Error(message.as_str()?.to_string()));
+        }
+        let result = value.get("result")?;
+        Some(Self::Result((!result.is_null()).then(|| result.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_results_and_errors() {
+        let config = NegativeCacheConfig {
+            error_patterns: vec!["Nonce Too Low".to_string()],
+            ..Default::default()
+        };
+        assert!(config.is_negative_result("eth_getTransactionReceipt", None));
+        assert!(config.is_negative_result("eth_getCode", Some(&json!("0x"))));
+        assert!(!config.is_negative_result("eth_getCode", Some(&json!("0x6080"))));
+        assert!(!config.is_negative_result("eth_call", None));
+
+        assert!(config.is_negative_error("RPC error 3: execution reverted: ERC20: paused"));
+        assert!(config.is_negative_error("RPC error -32000: nonce too low"));
+        assert!(!config.is_negative_error("HTTP error: status 503"));
+
+        let disabled = NegativeCacheConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(!disabled.is_negative_result("eth_getTransactionReceipt", None));
+        assert!(!disabled.is_negative_error("execution reverted"));
+    }
+
+    #[test]
+    fn test_answers_round_trip() {
+        for answer in [
+            NegativeAnswer::Result(None),
+            NegativeAnswer::Result(Some(json!("0x"))),
+            NegativeAnswer::Error("RPC error 3: execution reverted".to_string()),
+        ] {
+            assert_eq!(NegativeAnswer::from_value(&answer.to_value()), Some(answer));
+        }
+        assert_eq!(NegativeAnswer::from_value(&json!("0x")), None);
+    }
+}
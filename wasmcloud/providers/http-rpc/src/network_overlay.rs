@@ -0,0 +1,131 @@
+//! Per-network overrides of the provider configuration
+//!
+//! [`ProviderConfig`](crate::ProviderConfig) is shared by every network, but a
+//! Solana endpoint answering in 400ms and an Ethereum archive node taking 8s
+//! for a trace want different timeouts, retries, breaker thresholds and cache
+//! TTLs. An overlay sets any of those for one network; unset fields keep the
+//! global value, and `cache_method_ttls` entries are merged over the global
+//! ones. Overlays are read from, later sources winning per network:
+//! - `HTTP_RPC_NETWORKS_CONFIG`: JSON
+//! - `HTTP_RPC_NETWORKS_FILE`: a `.json`, `.yaml` or `.yml` file
+//! - the `networks` (inline JSON or YAML) and `networks_file` keys of the
+//!   provider's config values and of link definitions targeting it
+//!
+//! e.g. `{"solana": {"timeout_seconds": 5, "max_retries": 5,
+//! "cache_default_ttl": 1}}`.
+
+use crate::cache::CacheTtl;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Overrides of one network; `None` keeps the global setting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkOverlay {
+    pub timeout_seconds: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub circuit_breaker_failure_threshold: Option<u32>,
+    pub circuit_breaker_success_threshold: Option<u32>,
+    pub circuit_breaker_timeout_seconds: Option<u64>,
+    pub circuit_breaker_max_timeout_seconds: Option<u64>,
+    pub cache_default_ttl: Option<u64>,
+    pub cache_block_ttl: Option<u64>,
+    pub cache_tx_ttl: Option<u64>,
+    /// Merged over the global per-method TTLs
+    pub cache_method_ttls: HashMap<String, CacheTtl>,
+}
+
+/// Overlays keyed by network name
+pub type NetworkOverlays = HashMap<String, NetworkOverlay>;
+
+/// Overlays from inline JSON or YAML
+pub fn parse(text: &str) -> Result<NetworkOverlays> {
+    match serde_json::from_str(text) {
+        Ok(overlays) => Ok(overlays),
+        // YAML is a superset of JSON; its error is the more useful one
+        Err(_) => {
+            serde_yaml::from_str(text).map_err(|e| anyhow!("Invalid network overlays: {}", e))
+        }
+    }
+}
+
+/// Overlays from a `.json`, `.yaml` or `.yml` file
+pub fn load(path: &Path) -> Result<NetworkOverlays> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("json") => serde_json::from_str(&text)
+            .map_err(|e| anyhow!("Invalid network overlays in {}: {}", path.display(), e)),
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&text)
+            .map_err(|e| anyhow!("Invalid network overlays in {}: {}", path.display(), e)),
+        _ => parse(&text),
+    }
+}
+
+/// Overlays named by config values: `networks_file`, then `networks`
+pub fn from_values(values: &HashMap<String, String>) -> Result<NetworkOverlays> {
+    let mut overlays = NetworkOverlays::new();
+    if let Some(path) = values.get("networks_file") {
+        overlays.extend(load(Path::new(path.trim()))?);
+    }
+    if let Some(text) = values.get("networks") {
+        overlays.extend(parse(text)?);
+    }
+    Ok(overlays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlays_parse_from_json_and_yaml() {
+        let json = parse(
+            r#"{"solana": {"timeout_seconds": 5, "cache_method_ttls": {"getSlot": "never"}}}"#,
+        )
+        .unwrap();
+        let yaml =
+            parse("solana:\n  timeout_seconds: 5\n  cache_method_ttls:\n    getSlot: never\n")
+                .unwrap();
+        assert_eq!(json, yaml);
+        assert_eq!(json["solana"].timeout_seconds, Some(5));
+        assert_eq!(json["solana"].max_retries, None);
+        assert_eq!(
+            json["solana"].cache_method_ttls.get("getSlot"),
+            Some(&CacheTtl::Never)
+        );
+
+        // Misspelt settings are reported rather than ignored
+        assert!(parse(r#"{"solana": {"timeout_secs": 5}}"#).is_err());
+    }
+
+    #[test]
+    fn test_values_inline_wins_over_file() {
+        let path =
+            std::env::temp_dir().join(format!("http-rpc-networks-{}.yaml", std::process::id()));
+        std::fs::write(
+            &path,
+            "ethereum:\n  max_retries: 5\nsolana:\n  max_retries: 2\n",
+        )
+        .unwrap();
+
+        let mut values = HashMap::new();
+        values.insert("networks_file".to_string(), path.display().to_string());
+        values.insert(
+            "networks".to_string(),
+            r#"{"solana": {"max_retries": 8}}"#.to_string(),
+        );
+        let overlays = from_values(&values).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(overlays["ethereum"].max_retries, Some(5));
+        assert_eq!(overlays["solana"].max_retries, Some(8));
+        assert!(from_values(&HashMap::new()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+//! Request priority classes
+//!
+//! The live alert pipeline and historical backfills share a pool's endpoints,
+//! and a backfill can issue thousands of calls in a burst. Every call carries a
+//! [`Priority`]: `realtime` (the default), `backfill` or `bulk`. With
+//! `max_in_flight` set, a pool sends at most that many calls upstream at a
+//! time; the rest wait in one queue per class. A freed slot goes to the waiting
+//! class that has been served least relative to its weight (stride
+//! scheduling), so with the default weights realtime calls get 16 slots for
+//! every 4 backfill and 1 bulk call while all three are waiting, and no class
+//! is starved outright.
+//!
+//! Cache hits never queue. A batch queues once, at its most urgent call's
+//! priority; traces queue as realtime.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+/// Pass advance of a class with weight 1
+const STRIDE: u64 = 1 << 20;
+
+/// Scheduling class of a call
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Live pipeline calls
+    #[default]
+    Realtime,
+    /// Historical backfills
+    Backfill,
+    /// Bulk exports and anything else that can wait
+    Bulk,
+}
+
+impl Priority {
+    fn index(self) -> usize {
+        match self {
+            Self::Realtime => 0,
+            Self::Backfill => 1,
+            Self::Bulk => 2,
+        }
+    }
+}
+
+/// Share of freed slots per class while several are waiting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriorityWeights {
+    pub realtime: u32,
+    pub backfill: u32,
+    pub bulk: u32,
+}
+
+impl Default for PriorityWeights {
+    fn default() -> Self {
+        Self {
+            realtime: 16,
+            backfill: 4,
+            bulk: 1,
+        }
+    }
+}
+
+/// Priority queuing settings (`HTTP_RPC_PRIORITY_CONFIG`, JSON)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriorityConfig {
+    /// Upstream calls in flight per pool; 0 sends every call at once
+    pub max_in_flight: usize,
+    pub weights: PriorityWeights,
+}
+
+/// Calls in flight and waiting per class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueDepth {
+    #[serde(default)]
+    pub in_flight: usize,
+    #[serde(default)]
+    pub realtime: usize,
+    #[serde(default)]
+    pub backfill: usize,
+    #[serde(default)]
+    pub bulk: usize,
+}
+
+struct QueueState {
+    in_flight: usize,
+    waiting: [VecDeque<oneshot::Sender<PriorityPermit>>; 3],
+    /// Virtual time per class; the lowest waiting one is served next
+    pass: [u64; 3],
+    /// Pass of the last class served; a class that starts waiting resumes
+    /// from here rather than with credit saved up while idle
+    clock: u64,
+}
+
+struct QueueInner {
+    max_in_flight: usize,
+    strides: [u64; 3],
+    state: Mutex<QueueState>,
+}
+
+/// Weighted fair queue in front of a pool's upstream calls
+pub struct PriorityQueue {
+    inner: Arc<QueueInner>,
+}
+
+/// Slot held for one upstream call; freed on drop
+pub struct PriorityPermit {
+    inner: Option<Arc<QueueInner>>,
+}
+
+impl PriorityQueue {
+    pub fn new(config: &PriorityConfig) -> Self {
+        let stride = |weight: u32| STRIDE / u64::from(weight.max(1));
+        Self {
+            inner: Arc::new(QueueInner {
+                max_in_flight: config.max_in_flight,
+                strides: [
+                    stride(config.weights.realtime),
+                    stride(config.weights.backfill),
+                    stride(config.weights.bulk),
+                ],
+                state: Mutex::new(QueueState {
+                    in_flight: 0,
+                    waiting: Default::default(),
+                    pass: [0; 3],
+                    clock: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Wait for a slot for a call of `priority`
+    pub async fn acquire(&self, priority: Priority) -> PriorityPermit {
+        if self.inner.max_in_flight == 0 {
+            return PriorityPermit { inner: None };
+        }
+        let receiver = {
+            let mut state = self.inner.state.lock();
+            let class = priority.index();
+            if state.waiting[class].is_empty() {
+                state.pass[class] = state.pass[class].max(state.clock);
+            }
+            let queued = state.waiting.iter().any(|waiting| !waiting.is_empty());
+            if !queued && state.in_flight < self.inner.max_in_flight {
+                state.in_flight += 1;
+                self.inner.charge(&mut state, class);
+                return PriorityPermit {
+                    inner: Some(self.inner.clone()),
+                };
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting[class].push_back(sender);
+            receiver
+        };
+        // A permit dropped unreceived (the caller gave up) frees its slot
+        receiver.await.unwrap_or(PriorityPermit { inner: None })
+    }
+
+    /// Calls in flight and waiting
+    pub fn depth(&self) -> QueueDepth {
+        let state = self.inner.state.lock();
+        QueueDepth {
+            in_flight: state.in_flight,
+            realtime: state.waiting[0].len(),
+            backfill: state.waiting[1].len(),
+            bulk: state.waiting[2].len(),
+        }
+    }
+}
+
+impl QueueInner {
+    fn charge(&self, state: &mut QueueState, class: usize) {
+        state.clock = state.pass[class];
+        state.pass[class] += self.strides[class];
+    }
+
+    /// Hand a freed slot to the next waiting call, or give it back
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock();
+        loop {
+            let next = (0..3)
+                .filter(|class| !state.waiting[*class].is_empty())
+                .min_by_key(|class| state.pass[*class]);
+            let Some(class) = next else {
+                state.in_flight -= 1;
+                return;
+            };
+            let Some(sender) = state.waiting[class].pop_front() else {
+                continue;
+            };
+            let permit = PriorityPermit {
+                inner: Some(self.clone()),
+            };
+            match sender.send(permit) {
+                Ok(()) => {
+                    self.charge(&mut state, class);
+                    return;
+                }
+                // The waiter is gone; the slot stays held for the next one
+                Err(mut permit) => permit.inner = None,
+            }
+        }
+    }
+}
+
+impl Drop for PriorityPermit {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            inner.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_for_depth(queue: &PriorityQueue, queued: usize) {
+        loop {
+            let depth = queue.depth();
+            if depth.realtime + depth.backfill + depth.bulk >= queued {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slots_shared_by_weight() {
+        let queue = Arc::new(PriorityQueue::new(&PriorityConfig {
+            max_in_flight: 1,
+            weights: PriorityWeights {
+                realtime: 4,
+                backfill: 2,
+                bulk: 1,
+            },
+        }));
+        let held = queue.acquire(Priority::Bulk).await;
+
+        let served = Arc::new(Mutex::new(String::new()));
+        let mut tasks = Vec::new();
+        for (priority, label, count) in [(Priority::Bulk, 'B', 4), (Priority::Realtime, 'R', 8)] {
+            for _ in 0..count {
+                let queue = queue.clone();
+                let served = served.clone();
+                tasks.push(tokio::spawn(async move {
+                    let _permit = queue.acquire(priority).await;
+                    served.lock().push(label);
+                }));
+            }
+            wait_for_depth(&queue, tasks.len()).await;
+        }
+        assert_eq!(
+            queue.depth(),
+            QueueDepth {
+                in_flight: 1,
+                realtime: 8,
+                backfill: 0,
+                bulk: 4,
+            }
+        );
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        // Realtime gets four slots per bulk one, but bulk is not starved
+        assert_eq!(served.lock().as_str(), "RRRRRBRRRBBB");
+        assert_eq!(queue.depth(), QueueDepth::default());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_waiter_frees_its_slot() {
+        let queue = PriorityQueue::new(&PriorityConfig {
+            max_in_flight: 1,
+            ..Default::default()
+        });
+        let held = queue.acquire(Priority::Realtime).await;
+        let abandoned = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            queue.acquire(Priority::Backfill),
+        )
+        .await;
+        assert!(abandoned.is_err());
+
+        drop(held);
+        let _permit = queue.acquire(Priority::Bulk).await;
+        assert_eq!(queue.depth().in_flight, 1);
+
+        // No limit: nothing is counted or queued
+        let unlimited = PriorityQueue::new(&PriorityConfig::default());
+        let _permits = [
+            unlimited.acquire(Priority::Bulk).await,
+            unlimited.acquire(Priority::Bulk).await,
+        ];
+        assert_eq!(unlimited.depth(), QueueDepth::default());
+    }
+}
@@ -0,0 +1,525 @@
+//! Per-endpoint request rate limits
+//!
+//! Paid RPC plans cap requests per second, and Alchemy or Infura answer with
+//! HTTP 429 past the cap. Each endpoint gets a token bucket: tokens refill at
+//! `requests_per_sec` up to `burst`, and every upstream call spends one (a
+//! batch spends one per call). The endpoint pool skips endpoints whose bucket
+//! is empty and rotates to the next healthy one; when every healthy endpoint is
+//! limited it waits for the first token, up to `max_wait_ms`.
+//!
+//! A 429 from the endpoint empties its bucket, so the pool backs off even when
+//! the configured limit is higher than the plan's. Endpoints without a limit
+//! are never throttled.
+//!
+//! When the rate-limit answer says how long to wait (a `Retry-After` header in
+//! seconds or as an HTTP date, Infura's `data.rate.backoff_seconds`, a
+//! `retry_after` data field, or "try again in 5 seconds" in the message), the
+//! endpoint is held for exactly that long, up to `max_retry_after_secs`,
+//! whether it has a limit or not. Such an answer is not counted against the
+//! endpoint's circuit breaker and the call fails over without the retry
+//! delay: hammering a vendor that asked for a pause, or opening its circuit
+//! for ever longer, is what escalates a throttle into a ban.
+
+use crate::cost::endpoint_host;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_MAX_WAIT_MS: u64 = 2_000;
+const DEFAULT_MAX_RETRY_AFTER_SECS: u64 = 600;
+
+/// Message phrases followed by the wait (lowercase)
+const RETRY_AFTER_PHRASES: &[&str] = &["retry after", "retry in", "try again in", "backoff"];
+
+/// Token bucket settings for one endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EndpointRateLimit {
+    /// Sustained requests per second
+    pub requests_per_sec: f64,
+    /// Requests allowed back to back; `requests_per_sec` (at least 1) when unset
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+impl EndpointRateLimit {
+    fn capacity(&self) -> f64 {
+        self.burst
+            .map(f64::from)
+            .unwrap_or(self.requests_per_sec.ceil())
+            .max(1.0)
+    }
+}
+
+/// Provider-wide rate limits (`HTTP_RPC_RATE_LIMIT_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Limit for endpoints with no override; unlimited when unset
+    pub default_limit: Option<EndpointRateLimit>,
+    /// Per-endpoint limits keyed by endpoint host
+    pub endpoints: HashMap<String, EndpointRateLimit>,
+    /// Longest a call waits for a token when every healthy endpoint is limited
+    pub max_wait_ms: u64,
+    /// Longest an endpoint is held on a vendor's say-so; 0 ignores retry hints
+    pub max_retry_after_secs: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            default_limit: None,
+            endpoints: HashMap::new(),
+            max_wait_ms: DEFAULT_MAX_WAIT_MS,
+            max_retry_after_secs: DEFAULT_MAX_RETRY_AFTER_SECS,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Limit that applies to `endpoint`, if any
+    pub fn limit_for(&self, endpoint: &str) -> Option<EndpointRateLimit> {
+        self.endpoints
+            .get(&endpoint_host(endpoint))
+            .copied()
+            .or(self.default_limit)
+            .filter(|limit| limit.requests_per_sec > 0.0)
+    }
+
+    /// How long to hold an endpoint that asked for `retry_after`
+    pub fn hold_for(&self, retry_after: Option<Duration>) -> Option<Duration> {
+        retry_after
+            .filter(|wait| !wait.is_zero() && self.max_retry_after_secs > 0)
+            .map(|wait| wait.min(Duration::from_secs(self.max_retry_after_secs)))
+    }
+}
+
+/// Wait asked by a `Retry-After` header, in seconds or as an HTTP date
+pub fn retry_after_header(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return (secs.is_finite() && secs > 0.0).then(|| Duration::from_secs_f64(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    SystemTime::from(at).duration_since(now).ok()
+}
+
+/// Wait asked by a JSON-RPC rate-limit error, from its data or message
+pub fn retry_after_hint(message: &str, data: Option<&Value>) -> Option<Duration> {
+    data.and_then(retry_after_in_data)
+        .or_else(|| retry_after_in_message(message))
+}
+
+/// Wait asked by a rate-limit response body (a JSON-RPC error or a batch of them)
+pub fn retry_after_in_body(body: &Value) -> Option<Duration> {
+    let answers = match body {
+        Value::Array(answers) => answers.iter().collect(),
+        answer => vec![answer],
+    };
+    answers.into_iter().find_map(|answer| {
+        let error = answer.get("error")?;
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .or_else(|| error.as_str())
+            .unwrap_or_default();
+        retry_after_hint(message, error.get("data"))
+    })
+}
+
+fn retry_after_in_data(data: &Value) -> Option<Duration> {
+    let secs = |value: &Value| {
+        value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|text| text.trim().parse().ok()))
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+    };
+    let seconds = ["backoff_seconds", "retry_after", "retryAfter"];
+    let millis = ["retry_after_ms", "retryAfterMs"];
+    let found = |data: &Value| {
+        seconds
+            .iter()
+            .find_map(|key| data.get(*key).and_then(secs))
+            .or_else(|| {
+                millis
+                    .iter()
+                    .find_map(|key| data.get(*key).and_then(secs))
+                    .map(|ms| ms / 1000.0)
+            })
+    };
+    // Infura nests its hint under `rate`
+    found(data)
+        .or_else(|| data.get("rate").and_then(found))
+        .map(Duration::from_secs_f64)
+}
+
+fn retry_after_in_message(message: &str) -> Option<Duration> {
+    let message = message.to_lowercase();
+    RETRY_AFTER_PHRASES.iter().find_map(|phrase| {
+        let rest = message[message.find(phrase)? + phrase.len()..]
+            .trim_start_matches(|c: char| c == ':' || c == '=' || c.is_whitespace());
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..end].parse().ok()?;
+        let unit = rest[end..].trim_start();
+        let secs = if unit.starts_with("ms") || unit.starts_with("milli") {
+            amount / 1000.0
+        } else if unit.starts_with("min") || unit.starts_with("m ") || unit == "m" {
+            amount * 60.0
+        } else {
+            amount
+        };
+        (secs > 0.0).then(|| Duration::from_secs_f64(secs))
+    })
+}
+
+/// Upstream error from an endpoint that said when to retry, and is held for
+/// `wait`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryAfter {
+    pub message: String,
+    pub wait: Duration,
+}
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (retry after {:?})", self.message, self.wait)
+    }
+}
+
+impl std::error::Error for RetryAfter {}
+
+/// Whether an upstream error said when to retry (and the endpoint is held)
+pub fn is_retry_after_error(error: &anyhow::Error) -> bool {
+    error.is::<RetryAfter>()
+}
+
+/// Bucket snapshot for one endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_sec: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<f64>,
+    /// Tokens left; negative while a large batch is being paid back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_tokens: Option<f64>,
+    pub limited: bool,
+    /// Rate-limit answers seen (HTTP 429, or a rate-limit body or error)
+    pub rejections: u64,
+    /// Time left on a hold the endpoint asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_in_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+    rejections: u64,
+    /// No calls before this, as the endpoint asked
+    held_until: Option<Instant>,
+}
+
+/// Token bucket for one endpoint
+pub struct RateLimiter {
+    endpoint: String,
+    limit: Option<EndpointRateLimit>,
+    state: Mutex<BucketState>,
+}
+
+impl RateLimiter {
+    pub fn new(endpoint: &str, config: &RateLimitConfig) -> Self {
+        let limit = config.limit_for(endpoint);
+        Self {
+            endpoint: endpoint_host(endpoint),
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: limit.map_or(0.0, |limit| limit.capacity()),
+                updated: Instant::now(),
+                rejections: 0,
+                held_until: None,
+            }),
+        }
+    }
+
+    /// Whether a call may go out now
+    pub fn has_capacity(&self) -> bool {
+        self.wait_time_at(Instant::now()).is_zero()
+    }
+
+    /// Spend one token per call; the bucket may go negative for a large batch
+    pub fn acquire(&self, calls: usize) {
+        self.acquire_at(calls, Instant::now())
+    }
+
+    /// Time until a call may go out (zero when it may go now)
+    pub fn wait_time(&self) -> Duration {
+        self.wait_time_at(Instant::now())
+    }
+
+    /// Empty the bucket after the endpoint answered 429
+    pub fn record_rejection(&self) {
+        self.record_rejection_at(Instant::now())
+    }
+
+    /// Send nothing for `wait`, as the endpoint asked
+    pub fn hold(&self, wait: Duration) {
+        self.hold_at(wait, Instant::now())
+    }
+
+    pub fn status(&self) -> RateLimitStatus {
+        self.status_at(Instant::now())
+    }
+
+    fn refill(&self, state: &mut BucketState, now: Instant) {
+        let Some(limit) = self.limit else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * limit.requests_per_sec).min(limit.capacity());
+        state.updated = now;
+    }
+
+    fn acquire_at(&self, calls: usize, now: Instant) {
+        if self.limit.is_none() {
+            return;
+        }
+        let mut state = self.state.lock();
+        self.refill(&mut state, now);
+        state.tokens -= calls as f64;
+    }
+
+    fn wait_time_at(&self, now: Instant) -> Duration {
+        let mut state = self.state.lock();
+        let held = held_for(&state, now);
+        let Some(limit) = self.limit else {
+            return held;
+        };
+        self.refill(&mut state, now);
+        let bucket = if state.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - state.tokens) / limit.requests_per_sec)
+        };
+        bucket.max(held)
+    }
+
+    fn hold_at(&self, wait: Duration, now: Instant) {
+        let mut state = self.state.lock();
+        let until = now + wait;
+        state.held_until = Some(state.held_until.map_or(until, |held| held.max(until)));
+    }
+
+    fn record_rejection_at(&self, now: Instant) {
+        let mut state = self.state.lock();
+        self.refill(&mut state, now);
+        state.tokens = state.tokens.min(0.0);
+        state.rejections += 1;
+    }
+
+    fn status_at(&self, now: Instant) -> RateLimitStatus {
+        let mut state = self.state.lock();
+        self.refill(&mut state, now);
+        let held = held_for(&state, now);
+        RateLimitStatus {
+            endpoint: self.endpoint.clone(),
+            requests_per_sec: self.limit.map(|limit| limit.requests_per_sec),
+            burst: self.limit.map(|limit| limit.capacity()),
+            available_tokens: self.limit.map(|_| state.tokens),
+            limited: (self.limit.is_some() && state.tokens < 1.0) || !held.is_zero(),
+            rejections: state.rejections,
+            retry_in_ms: (!held.is_zero()).then_some(held.as_millis() as u64),
+        }
+    }
+}
+
+/// Time left on the bucket's hold
+fn held_for(state: &BucketState, now: Instant) -> Duration {
+    state
+        .held_until
+        .map_or(Duration::ZERO, |until| until.saturating_duration_since(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_sec: f64, burst: Option<u32>) -> RateLimiter {
+        let config = RateLimitConfig {
+            default_limit: Some(EndpointRateLimit {
+                requests_per_sec,
+                burst,
+            }),
+            ..Default::default()
+        };
+        RateLimiter::new("https://eth-mainnet.g.alchemy.com/v2/key", &config)
+    }
+
+    #[test]
+    fn test_limit_for_endpoint() {
+        let mut config = RateLimitConfig::default();
+        assert_eq!(config.limit_for("https://rpc.example.org"), None);
+
+        let limit = EndpointRateLimit {
+            requests_per_sec: 25.0,
+            burst: Some(50),
+        };
+        config
+            .endpoints
+            .insert("rpc.example.org".to_string(), limit);
+        assert_eq!(config.limit_for("https://rpc.example.org/key"), Some(limit));
+        assert_eq!(config.limit_for("https://mainnet.infura.io/v3/key"), None);
+
+        config.endpoints.insert(
+            "rpc.example.org".to_string(),
+            EndpointRateLimit {
+                requests_per_sec: 0.0,
+                burst: None,
+            },
+        );
+        assert_eq!(config.limit_for("https://rpc.example.org"), None);
+    }
+
+    #[test]
+    fn test_bucket_spends_burst_then_refills() {
+        let limiter = limiter(10.0, Some(3));
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.wait_time_at(now).is_zero());
+            limiter.acquire_at(1, now);
+        }
+        assert_eq!(limiter.wait_time_at(now), Duration::from_millis(100));
+        assert!(limiter.status_at(now).limited);
+
+        let later = now + Duration::from_millis(100);
+        assert!(limiter.wait_time_at(later).is_zero());
+
+        // Refill stops at the burst size
+        let status = limiter.status_at(now + Duration::from_secs(60));
+        assert_eq!(status.available_tokens, Some(3.0));
+        assert!(!status.limited);
+    }
+
+    #[test]
+    fn test_batch_and_rejection_drain_the_bucket() {
+        let limiter = limiter(10.0, None);
+        let now = Instant::now();
+
+        // A batch of 30 leaves a debt of 20 tokens: 2.1 seconds to the next call
+        limiter.acquire_at(30, now);
+        assert_eq!(limiter.wait_time_at(now), Duration::from_millis(2100));
+
+        let later = now + Duration::from_secs(10);
+        assert!(limiter.wait_time_at(later).is_zero());
+        limiter.record_rejection_at(later);
+        assert_eq!(limiter.wait_time_at(later), Duration::from_millis(100));
+        assert_eq!(limiter.status_at(later).rejections, 1);
+    }
+
+    #[test]
+    fn test_retry_after_from_headers_and_errors() {
+        let now = SystemTime::now();
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after_header(&headers, now), None);
+        headers.insert(RETRY_AFTER, "30".parse().unwrap());
+        assert_eq!(
+            retry_after_header(&headers, now),
+            Some(Duration::from_secs(30))
+        );
+        let at = chrono::DateTime::<chrono::Utc>::from(now + Duration::from_secs(90));
+        headers.insert(RETRY_AFTER, at.to_rfc2822().parse().unwrap());
+        let wait = retry_after_header(&headers, now).unwrap();
+        assert!(wait > Duration::from_secs(88) && wait <= Duration::from_secs(90));
+
+        // Infura
+        let infura = serde_json::json!({"jsonrpc": "2.0", "id": 1, "error": {
+            "code": -32005,
+            "message": "daily request count exceeded, request rate limited",
+            "data": {"rate": {"allowed_rps": 1, "backoff_seconds": 30, "current_rps": 1.1}}
+        }});
+        assert_eq!(retry_after_in_body(&infura), Some(Duration::from_secs(30)));
+        assert_eq!(
+            retry_after_hint(
+                "rate limited",
+                Some(&serde_json::json!({"retry_after_ms": 1500}))
+            ),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            retry_after_hint("Too many requests, please try again in 5 seconds", None),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            retry_after_hint("request limit reached, retry after: 250ms", None),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            retry_after_hint("Rate limit exceeded. Retry in 2 minutes", None),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after_hint("Your app has exceeded its compute units", None),
+            None
+        );
+
+        let config = RateLimitConfig::default();
+        assert_eq!(
+            config.hold_for(Some(Duration::from_secs(86_400))),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(config.hold_for(Some(Duration::ZERO)), None);
+        let error = anyhow::Error::new(RetryAfter {
+            message: "HTTP error: status 429".to_string(),
+            wait: Duration::from_secs(30),
+        });
+        assert!(is_retry_after_error(&error));
+        assert_eq!(
+            error.to_string(),
+            "HTTP error: status 429 (retry after 30s)"
+        );
+        // An upstream body echoing the note is not a hold
+        assert!(!is_retry_after_error(&anyhow::anyhow!(
+            "RPC error 429: too many requests (retry after 30s)"
+        )));
+    }
+
+    #[test]
+    fn test_hold_delays_even_unlimited_endpoints() {
+        let unlimited = RateLimiter::new("https://rpc.example.org", &RateLimitConfig::default());
+        let now = Instant::now();
+        unlimited.hold_at(Duration::from_secs(30), now);
+        // A shorter hint does not cut the hold short
+        unlimited.hold_at(Duration::from_secs(5), now);
+        assert_eq!(unlimited.wait_time_at(now), Duration::from_secs(30));
+        let status = unlimited.status_at(now + Duration::from_secs(10));
+        assert!(status.limited);
+        assert_eq!(status.retry_in_ms, Some(20_000));
+
+        let later = now + Duration::from_secs(30);
+        assert!(unlimited.wait_time_at(later).is_zero());
+        assert_eq!(unlimited.status_at(later).retry_in_ms, None);
+
+        // The longer of the hold and the bucket's refill wins
+        let limited = limiter(10.0, Some(1));
+        limited.acquire_at(1, now);
+        assert_eq!(limited.wait_time_at(now), Duration::from_millis(100));
+        limited.hold_at(Duration::from_secs(2), now);
+        assert_eq!(limited.wait_time_at(now), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_unlimited_endpoint_never_waits() {
+        let limiter = RateLimiter::new("https://rpc.example.org", &RateLimitConfig::default());
+        limiter.acquire(1_000);
+        assert!(limiter.has_capacity());
+        let status = limiter.status();
+        assert_eq!(status.available_tokens, None);
+        assert!(!status.limited);
+    }
+}
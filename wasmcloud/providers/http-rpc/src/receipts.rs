@@ -0,0 +1,178 @@
+//! Every receipt of a block
+//!
+//! Modern nodes answer `eth_getBlockReceipts` with a block's receipts in one
+//! call. Older nodes and some vendors lack it; for those the block is fetched
+//! with its transaction hashes and the receipts are asked in one JSON-RPC batch
+//! of `eth_getTransactionReceipt` calls, split to the endpoint's max batch
+//! size. Whether an endpoint has `eth_getBlockReceipts` is found out on first
+//! use, as trace flavors are, and an endpoint lacking it is not asked again.
+//! Either way receipts come back in transaction order.
+
+use crate::batch::RpcBatchResponse;
+use crate::endpoint_pool::RpcRequest;
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+
+/// Method answering a block's receipts in one call
+pub const BLOCK_RECEIPTS: &str = "eth_getBlockReceipts";
+
+/// Whether one endpoint has `eth_getBlockReceipts`
+#[derive(Debug, Default)]
+pub struct ReceiptSupport {
+    /// Supported, lacking, or not yet known
+    known: Mutex<Option<bool>>,
+}
+
+impl ReceiptSupport {
+    /// Not known to lack it
+    pub fn may_support(&self) -> bool {
+        *self.known.lock() != Some(false)
+    }
+
+    pub fn record(&self, supported: bool) {
+        *self.known.lock() = Some(supported);
+    }
+
+    pub fn known(&self) -> Option<bool> {
+        *self.known.lock()
+    }
+}
+
+/// Whether `block` is a block hash rather than a number or tag
+fn is_block_hash(block: &str) -> bool {
+    block.len() == 66 && block.starts_with("0x")
+}
+
+/// `eth_getBlockReceipts` for `block` (a number, tag or hash)
+pub fn block_receipts_request(block: &str) -> RpcRequest {
+    RpcRequest::new(BLOCK_RECEIPTS, vec![json!(block)])
+}
+
+/// `block` (a number, tag or hash) itself, with full transaction bodies or
+/// their hashes only
+pub fn block_request(block: &str, full_transactions: bool) -> RpcRequest {
+    let method = if is_block_hash(block) {
+        "eth_getBlockByHash"
+    } else {
+        "eth_getBlockByNumber"
+    };
+    RpcRequest::new(method, vec![json!(block), json!(full_transactions)])
+}
+
+/// One `eth_getTransactionReceipt` per transaction of `block` (an
+/// `eth_getBlock*` answer)
+pub fn receipt_requests(block: &str, answer: Option<Value>) -> Result<Vec<RpcRequest>> {
+    let answer = answer
+        .filter(|answer| !answer.is_null())
+        .ok_or_else(|| anyhow!("Block {} not found", block))?;
+    let transactions = answer
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Block {} answered without transactions", block))?;
+    transactions
+        .iter()
+        .map(|transaction| {
+            // Full bodies are accepted too
+            let hash = transaction
+                .as_str()
+                .or_else(|| transaction.get("hash").and_then(Value::as_str))
+                .ok_or_else(|| anyhow!("Unexpected transaction in block {}", block))?;
+            Ok(RpcRequest::new(
+                "eth_getTransactionReceipt",
+                vec![json!(hash)],
+            ))
+        })
+        .collect()
+}
+
+/// Receipts of an `eth_getBlockReceipts` answer
+pub fn into_receipts(block: &str, result: Option<Value>) -> Result<Vec<Value>> {
+    match result {
+        Some(Value::Array(receipts)) => Ok(receipts),
+        None | Some(Value::Null) => Err(anyhow!("Block {} not found", block)),
+        Some(other) => Err(anyhow!("Unexpected block receipts: {}", other)),
+    }
+}
+
+/// Receipts of a batch of `eth_getTransactionReceipt` answers; the first
+/// failed or missing receipt fails them all
+pub fn batch_receipts(requests: &[RpcRequest], batch: RpcBatchResponse) -> Result<Vec<Value>> {
+    if batch.responses.len() != requests.len() {
+        return Err(anyhow!(
+            "{} receipts answered for {} transactions",
+            batch.responses.len(),
+            requests.len()
+        ));
+    }
+    requests
+        .iter()
+        .zip(batch.responses)
+        .map(|(request, response)| {
+            if let Some(error) = response.error {
+                return Err(anyhow!("RPC error {}: {}", error.code, error.message));
+            }
+            response
+                .result
+                .filter(|receipt| !receipt.is_null())
+                .ok_or_else(|| anyhow!("No receipt for {}", request.params[0]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint_pool::{RpcError, RpcResponse};
+
+    #[test]
+    fn test_fallback_requests_per_transaction() {
+        assert_eq!(block_request("0x10", false).method, "eth_getBlockByNumber");
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(block_request(&hash, false).method, "eth_getBlockByHash");
+
+        let requests = receipt_requests(
+            "0x10",
+            Some(json!({"transactions": ["0x01", {"hash": "0x02"}]})),
+        )
+        .unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, "eth_getTransactionReceipt");
+        assert_eq!(requests[1].params, vec![json!("0x02")]);
+
+        assert!(receipt_requests("0x10", Some(Value::Null)).is_err());
+        assert!(into_receipts("0x10", Some(Value::Null)).is_err());
+        assert_eq!(
+            into_receipts("0x10", Some(json!([{"status": "0x1"}]))).unwrap(),
+            vec![json!({"status": "0x1"})]
+        );
+    }
+
+    #[test]
+    fn test_missing_receipt_fails_the_block() {
+        let requests =
+            receipt_requests("0x10", Some(json!({"transactions": ["0x01", "0x02"]}))).unwrap();
+        let answer = |result: Option<Value>, error: Option<RpcError>| RpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result,
+            error,
+            id: 1,
+        };
+        let complete = RpcBatchResponse {
+            responses: vec![
+                answer(Some(json!({"transactionHash": "0x01"})), None),
+                answer(Some(json!({"transactionHash": "0x02"})), None),
+            ],
+        };
+        assert_eq!(batch_receipts(&requests, complete).unwrap().len(), 2);
+
+        let pending = RpcBatchResponse {
+            responses: vec![
+                answer(Some(json!({"transactionHash": "0x01"})), None),
+                answer(Some(Value::Null), None),
+            ],
+        };
+        let error = batch_receipts(&requests, pending).unwrap_err();
+        assert!(error.to_string().contains("0x02"));
+    }
+}
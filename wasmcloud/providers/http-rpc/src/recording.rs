@@ -0,0 +1,492 @@
+//! Record and replay of upstream calls
+//!
+//! Integration tests and offline pipeline work want the same answers on every
+//! run without a vendor account. In `record` mode every upstream answer is
+//! kept with its call:
+//! - sink `file`: one JSON line per call appended to `{dir}/{network}.jsonl`
+//! - sink `ducklake`: one row of the `rpc_recordings` table, published to
+//!   `ducklake.rpc_recordings.{network}.default.write`
+//!
+//! In `replay` mode the pool answers from the `*.jsonl` files in `dir` and never
+//! goes upstream; a call that was not recorded fails, unless `passthrough` sends
+//! it upstream and appends its answer. Lines may also be rows exported from
+//! `rpc_recordings` (JSON text columns). Calls match on network, method and
+//! canonical params, as cache keys do; the last line recorded for a call wins.
+
+use crate::cache::canonical_params;
+use crate::cost::endpoint_host;
+use crate::endpoint_pool::{RpcError, RpcRequest, RpcResponse};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+/// Subject recordings are published to, with the network filled in
+const DUCKLAKE_SUBJECT: &str = "ducklake.rpc_recordings.{network}.default.write";
+
+/// What the pool does with upstream calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingMode {
+    #[default]
+    Off,
+    Record,
+    Replay,
+}
+
+/// Where recorded calls go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingSink {
+    #[default]
+    File,
+    Ducklake,
+}
+
+/// Record/replay settings (`HTTP_RPC_RECORDING_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+    pub mode: RecordingMode,
+    pub sink: RecordingSink,
+    /// Directory recordings are written to and replayed from
+    pub dir: PathBuf,
+    /// Send calls missing from a replay upstream and record them
+    pub passthrough: bool,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            mode: RecordingMode::Off,
+            sink: RecordingSink::File,
+            dir: PathBuf::from("rpc-recordings"),
+            passthrough: false,
+        }
+    }
+}
+
+/// One recorded call, as written to a recording file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub network: String,
+    pub method: String,
+    pub params: Vec<Value>,
+    #[serde(default)]
+    pub result: Option<Value>,
+    #[serde(default)]
+    pub error: Option<RpcError>,
+    /// Host of the endpoint that answered
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Row of the `rpc_recordings` DuckLake table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeRpcRecordingRecord {
+    // Partition columns
+    pub chain_id: String,
+    pub recorded_date: String,
+    // Call
+    pub method: String,
+    pub params: String,
+    // Answer
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub endpoint: Option<String>,
+    pub latency_ms: Option<i64>,
+    // Processing metadata
+    pub recorded_at: i64,
+}
+
+impl From<&RecordedCall> for DuckLakeRpcRecordingRecord {
+    fn from(call: &RecordedCall) -> Self {
+        let text = |value: &Value| value.to_string();
+        Self {
+            chain_id: call.network.clone(),
+            recorded_date: call.recorded_at.format("%Y-%m-%d").to_string(),
+            method: call.method.clone(),
+            params: text(&Value::Array(canonical_params(&call.method, &call.params))),
+            result: call.result.as_ref().map(text),
+            error: call
+                .error
+                .as_ref()
+                .and_then(|error| serde_json::to_string(error).ok()),
+            endpoint: call.endpoint.clone(),
+            latency_ms: call.latency_ms.map(|ms| ms as i64),
+            recorded_at: call.recorded_at.timestamp_micros(),
+        }
+    }
+}
+
+impl TryFrom<DuckLakeRpcRecordingRecord> for RecordedCall {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DuckLakeRpcRecordingRecord) -> Result<Self> {
+        let params = serde_json::from_str(&row.params)?;
+        let result = row
+            .result
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()?;
+        let error = row.error.as_deref().map(serde_json::from_str).transpose()?;
+        Ok(Self {
+            network: row.chain_id,
+            method: row.method,
+            params,
+            result,
+            error,
+            endpoint: row.endpoint,
+            latency_ms: row.latency_ms.map(|ms| ms.max(0) as u64),
+            recorded_at: DateTime::from_timestamp_micros(row.recorded_at).unwrap_or_default(),
+        })
+    }
+}
+
+/// A recording line in either form
+fn parse_line(line: &str) -> Result<RecordedCall> {
+    let value: Value = serde_json::from_str(line)?;
+    if value.get("chain_id").is_some() {
+        serde_json::from_value::<DuckLakeRpcRecordingRecord>(value)?.try_into()
+    } else {
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Replay lookup key of a call
+fn call_key(network: &str, method: &str, params: &[Value]) -> String {
+    let params = serde_json::to_string(&canonical_params(method, params)).unwrap_or_default();
+    format!("{}:{}:{}", network, method, params)
+}
+
+/// Records upstream answers and serves them back
+#[derive(Debug)]
+pub struct Recorder {
+    config: RecordingConfig,
+    nats_url: Option<String>,
+    /// Recorded answers by call key
+    calls: parking_lot::RwLock<HashMap<String, RecordedCall>>,
+    /// Open recording files by network
+    files: tokio::sync::Mutex<HashMap<String, tokio::fs::File>>,
+    /// Connection for the DuckLake sink, opened on first use
+    nats: tokio::sync::OnceCell<Option<async_nats::Client>>,
+}
+
+impl Recorder {
+    /// Recorder for `config`; in replay mode every recording in its dir is
+    /// loaded, skipping lines that do not parse
+    pub fn new(config: RecordingConfig, nats_url: Option<String>) -> Self {
+        let mut calls = HashMap::new();
+        if config.mode == RecordingMode::Replay {
+            match load_dir(&config.dir) {
+                Ok(loaded) => {
+                    info!(
+                        "Replaying {} recorded calls from {}",
+                        loaded.len(),
+                        config.dir.display()
+                    );
+                    calls = loaded;
+                }
+                Err(e) => warn!("No recordings to replay: {}", e),
+            }
+        }
+        Self {
+            config,
+            nats_url,
+            calls: parking_lot::RwLock::new(calls),
+            files: tokio::sync::Mutex::new(HashMap::new()),
+            nats: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// Whether calls are answered from recordings
+    pub fn replaying(&self) -> bool {
+        self.config.mode == RecordingMode::Replay
+    }
+
+    /// Whether upstream answers are recorded
+    pub fn recording(&self) -> bool {
+        match self.config.mode {
+            RecordingMode::Off => false,
+            RecordingMode::Record => true,
+            RecordingMode::Replay => self.config.passthrough,
+        }
+    }
+
+    /// Recorded answer to `request`; `Err` for a call that was not recorded
+    /// and may not go upstream, `Ok(None)` when it may
+    pub fn replay(&self, network: &str, request: &RpcRequest) -> Result<Option<RpcResponse>> {
+        let key = call_key(network, &request.method, &request.params);
+        if let Some(call) = self.calls.read().get(&key) {
+            return Ok(Some(RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: call.result.clone(),
+                error: call.error.clone(),
+                id: request.id,
+            }));
+        }
+        if self.config.passthrough {
+            Ok(None)
+        } else {
+            Err(anyhow!(
+                "No recording of {} for {}",
+                request.method,
+                network
+            ))
+        }
+    }
+
+    /// Keep `response` as the answer `endpoint` gave to `request`
+    pub async fn record(
+        &self,
+        network: &str,
+        endpoint: &str,
+        request: &RpcRequest,
+        response: &RpcResponse,
+        latency: Duration,
+    ) {
+        let call = RecordedCall {
+            network: network.to_string(),
+            method: request.method.clone(),
+            params: request.params.clone(),
+            result: response.result.clone(),
+            error: response.error.clone(),
+            endpoint: Some(endpoint_host(endpoint)),
+            latency_ms: Some(latency.as_millis() as u64),
+            recorded_at: Utc::now(),
+        };
+        let written = match self.config.sink {
+            RecordingSink::File => self.append(&call).await,
+            RecordingSink::Ducklake => self.publish(&call).await,
+        };
+        if let Err(e) = written {
+            warn!("Failed to record {} for {}: {}", call.method, network, e);
+        }
+        if self.replaying() {
+            let key = call_key(network, &call.method, &call.params);
+            self.calls.write().insert(key, call);
+        }
+    }
+
+    async fn append(&self, call: &RecordedCall) -> Result<()> {
+        let mut line = serde_json::to_vec(call)?;
+        line.push(b'\n');
+        let mut files = self.files.lock().await;
+        if !files.contains_key(&call.network) {
+            tokio::fs::create_dir_all(&self.config.dir).await?;
+            let path = self.config.dir.join(format!("{}.jsonl", call.network));
+            let file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+            files.insert(call.network.clone(), file);
+        }
+        let file = files
+            .get_mut(&call.network)
+            .expect("recording file opened above");
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn publish(&self, call: &RecordedCall) -> Result<()> {
+        let Some(nats_url) = &self.nats_url else {
+            return Err(anyhow!("No NATS URL configured"));
+        };
+        let nats = self
+            .nats
+            .get_or_init(|| async {
+                match async_nats::connect(nats_url).await {
+                    Ok(client) => Some(client),
+                    Err(e) => {
+                        warn!("Failed to connect to NATS for recordings: {}", e);
+                        None
+                    }
+                }
+            })
+            .await;
+        let Some(client) = nats else {
+            return Err(anyhow!("No NATS connection"));
+        };
+        let payload = serde_json::to_vec(&DuckLakeRpcRecordingRecord::from(call))?;
+        let subject = DUCKLAKE_SUBJECT.replace("{network}", &call.network);
+        client
+            .publish(subject_registry::prefixed(&subject), payload.into())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Recorded calls from every `*.jsonl` file in `dir`, by call key
+fn load_dir(dir: &Path) -> Result<HashMap<String, RecordedCall>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| anyhow!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "jsonl")
+        })
+        .collect();
+    paths.sort();
+
+    let mut calls = HashMap::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_line(line) {
+                Ok(call) => {
+                    let key = call_key(&call.network, &call.method, &call.params);
+                    calls.insert(key, call);
+                }
+                Err(e) => warn!(
+                    "Skipping recording {}:{}: {}",
+                    path.display(),
+                    number + 1,
+                    e
+                ),
+            }
+        }
+    }
+    Ok(calls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "http-rpc-recordings-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_both_line_forms_replay_by_canonical_params() {
+        let dir = temp_dir("forms");
+        let recorded = RecordedCall {
+            network: "ethereum".to_string(),
+            method: "eth_getBlockByNumber".to_string(),
+            params: vec![json!("0x10"), json!(false)],
+            result: Some(json!({"number": "0x10"})),
+            error: None,
+            endpoint: Some("eth.example.org".to_string()),
+            latency_ms: Some(42),
+            recorded_at: Utc::now(),
+        };
+        let mut reverted = recorded.clone();
+        reverted.method = "eth_call".to_string();
+        reverted.params = vec![json!({"to": "0xAB", "data": "0x"}), json!("latest")];
+        reverted.result = None;
+        reverted.error = Some(RpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        });
+        let row = serde_json::to_string(&DuckLakeRpcRecordingRecord::from(&reverted)).unwrap();
+        std::fs::write(
+            dir.join("ethereum.jsonl"),
+            format!(
+                "{}\nnot json\n{}\n",
+                serde_json::to_string(&recorded).unwrap(),
+                row
+            ),
+        )
+        .unwrap();
+
+        let recorder = Recorder::new(
+            RecordingConfig {
+                mode: RecordingMode::Replay,
+                dir: dir.clone(),
+                ..Default::default()
+            },
+            None,
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Block 16 written in decimal still matches
+        let block = recorder
+            .replay(
+                "ethereum",
+                &RpcRequest::new("eth_getBlockByNumber", vec![json!(16), json!(false)]),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.result, Some(json!({"number": "0x10"})));
+
+        let call = recorder
+            .replay(
+                "ethereum",
+                &RpcRequest::new(
+                    "eth_call",
+                    vec![json!({"data": "0x", "to": "0xab"}), json!("LATEST")],
+                ),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(call.error.unwrap().message, "execution reverted");
+
+        assert!(recorder
+            .replay("polygon", &RpcRequest::new("eth_blockNumber", vec![]))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recorded_calls_appended_per_network() {
+        let dir = temp_dir("append");
+        let recorder = Recorder::new(
+            RecordingConfig {
+                mode: RecordingMode::Record,
+                dir: dir.clone(),
+                ..Default::default()
+            },
+            None,
+        );
+        assert!(recorder.recording() && !recorder.replaying());
+        let request = RpcRequest::new("eth_blockNumber", vec![]);
+        for result in ["0x1", "0x2"] {
+            let response = RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: Some(json!(result)),
+                error: None,
+                id: 1,
+            };
+            recorder
+                .record(
+                    "ethereum",
+                    "https://eth.example.org/v2/key",
+                    &request,
+                    &response,
+                    Duration::from_millis(5),
+                )
+                .await;
+        }
+
+        let text = std::fs::read_to_string(dir.join("ethereum.jsonl")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let lines: Vec<RecordedCall> = text.lines().map(|l| parse_line(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].result, Some(json!("0x2")));
+        // API keys in the URL path are not written out
+        assert_eq!(lines[0].endpoint.as_deref(), Some("eth.example.org"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Endpoint selection strategies
+//!
+//! The pool picks among endpoints that pass the circuit breaker and rate limit
+//! checks with one of:
+//! - `round_robin` (default): rotate through them in order
+//! - `lowest_latency`: the endpoint with the lowest latency EWMA; endpoints
+//!   without samples go first so every endpoint gets measured
+//! - `weighted_random`: a random endpoint weighted by health score (success
+//!   rate EWMA per millisecond of latency EWMA), so slow or flaky endpoints get
+//!   a trickle of traffic instead of none
+//!
+//! Every call and canary probe feeds the per-endpoint [`EndpointStats`],
+//! whatever the strategy, so switching strategies starts from live scores.
+
+use crate::cost::endpoint_host;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_EWMA_ALPHA: f64 = 0.2;
+
+/// Lowest success rate used for weighting, so a failing endpoint is retried
+const MIN_SUCCESS_RATE: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    #[default]
+    RoundRobin,
+    LowestLatency,
+    WeightedRandom,
+}
+
+/// Selection settings (`HTTP_RPC_SELECTION_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionConfig {
+    pub strategy: SelectionStrategy,
+    /// Weight (0.0-1.0) of the newest sample in the latency and success EWMAs
+    pub ewma_alpha: f64,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            strategy: SelectionStrategy::default(),
+            ewma_alpha: DEFAULT_EWMA_ALPHA,
+        }
+    }
+}
+
+/// Score snapshot for one endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointScore {
+    pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ewma_ms: Option<f64>,
+    pub success_rate: f64,
+    pub health_score: f64,
+    pub samples: u64,
+}
+
+#[derive(Debug)]
+struct StatsState {
+    latency_ms: Option<f64>,
+    success_rate: f64,
+    samples: u64,
+}
+
+/// Latency and success EWMAs for one endpoint
+pub struct EndpointStats {
+    endpoint: String,
+    alpha: f64,
+    state: Mutex<StatsState>,
+}
+
+impl EndpointStats {
+    pub fn new(endpoint: &str, config: &SelectionConfig) -> Self {
+        Self {
+            endpoint: endpoint_host(endpoint),
+            alpha: config.ewma_alpha.clamp(0.01, 1.0),
+            state: Mutex::new(StatsState {
+                latency_ms: None,
+                success_rate: 1.0,
+                samples: 0,
+            }),
+        }
+    }
+
+    /// Record a call; `latency` is left out for calls whose duration says
+    /// nothing about single-call latency (batches)
+    pub fn record(&self, ok: bool, latency: Option<Duration>) {
+        let mut state = self.state.lock();
+        let success = if ok { 1.0 } else { 0.0 };
+        state.success_rate += self.alpha * (success - state.success_rate);
+        if let Some(latency) = latency {
+            let ms = latency.as_secs_f64() * 1000.0;
+            state.latency_ms = Some(match state.latency_ms {
+                Some(previous) => previous + self.alpha * (ms - previous),
+                None => ms,
+            });
+        }
+        state.samples += 1;
+    }
+
+    pub fn latency_ms(&self) -> Option<f64> {
+        self.state.lock().latency_ms
+    }
+
+    /// Success rate per millisecond of latency; `unmeasured_ms` stands in for
+    /// an endpoint with no latency sample yet
+    pub fn health_score(&self, unmeasured_ms: f64) -> f64 {
+        let state = self.state.lock();
+        let latency = state.latency_ms.unwrap_or(unmeasured_ms).max(1.0);
+        state.success_rate.max(MIN_SUCCESS_RATE) / latency
+    }
+
+    pub fn status(&self, unmeasured_ms: f64) -> EndpointScore {
+        let health_score = self.health_score(unmeasured_ms);
+        let state = self.state.lock();
+        EndpointScore {
+            endpoint: self.endpoint.clone(),
+            latency_ewma_ms: state.latency_ms,
+            success_rate: state.success_rate,
+            health_score,
+            samples: state.samples,
+        }
+    }
+}
+
+/// Latency assumed for unmeasured endpoints: the best measured one, so they
+/// compete on equal terms until their first sample
+pub fn unmeasured_latency_ms(stats: &[EndpointStats]) -> f64 {
+    stats
+        .iter()
+        .filter_map(EndpointStats::latency_ms)
+        .reduce(f64::min)
+        .unwrap_or(1.0)
+}
+
+/// Picks an endpoint for the latency and health strategies
+pub struct Selector {
+    strategy: SelectionStrategy,
+    /// splitmix64 state for weighted picks
+    rng: AtomicU64,
+}
+
+impl Selector {
+    pub fn new(config: &SelectionConfig) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::with_seed(config, seed)
+    }
+
+    pub fn with_seed(config: &SelectionConfig, seed: u64) -> Self {
+        Self {
+            strategy: config.strategy,
+            rng: AtomicU64::new(seed),
+        }
+    }
+
+    pub fn strategy(&self) -> SelectionStrategy {
+        self.strategy
+    }
+
+    /// Pick one of `candidates` (indexes into `stats`); `None` when there are
+    /// none. Round-robin is the pool's own rotation and picks the first.
+    pub fn pick(&self, candidates: &[usize], stats: &[EndpointStats]) -> Option<usize> {
+        match self.strategy {
+            SelectionStrategy::RoundRobin => candidates.first().copied(),
+            SelectionStrategy::LowestLatency => candidates.iter().copied().min_by(|a, b| {
+                let latency = |i: usize| stats[i].latency_ms().unwrap_or(f64::NEG_INFINITY);
+                latency(*a).total_cmp(&latency(*b))
+            }),
+            SelectionStrategy::WeightedRandom => {
+                let unmeasured_ms = unmeasured_latency_ms(stats);
+                let weights: Vec<f64> = candidates
+                    .iter()
+                    .map(|i| stats[*i].health_score(unmeasured_ms))
+                    .collect();
+                let mut target = self.next_unit() * weights.iter().sum::<f64>();
+                for (candidate, weight) in candidates.iter().zip(&weights) {
+                    if target < *weight {
+                        return Some(*candidate);
+                    }
+                    target -= weight;
+                }
+                candidates.last().copied()
+            }
+        }
+    }
+
+    /// Uniform value in [0, 1)
+    fn next_unit(&self) -> f64 {
+        let mut z = self
+            .rng
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(latencies_ms: &[Option<u64>]) -> Vec<EndpointStats> {
+        latencies_ms
+            .iter()
+            .enumerate()
+            .map(|(i, latency)| {
+                let stats = EndpointStats::new(
+                    &format!("https://rpc{}.example.org", i),
+                    &Default::default(),
+                );
+                if let Some(ms) = latency {
+                    stats.record(true, Some(Duration::from_millis(*ms)));
+                }
+                stats
+            })
+            .collect()
+    }
+
+    fn selector(strategy: SelectionStrategy) -> Selector {
+        let config = SelectionConfig {
+            strategy,
+            ..Default::default()
+        };
+        Selector::with_seed(&config, 7)
+    }
+
+    #[test]
+    fn test_ewma_tracks_latency_and_success() {
+        let stats = EndpointStats::new("https://rpc.example.org/key", &Default::default());
+        stats.record(true, Some(Duration::from_millis(100)));
+        stats.record(true, Some(Duration::from_millis(200)));
+        stats.record(false, None);
+
+        let score = stats.status(1.0);
+        assert_eq!(score.endpoint, "rpc.example.org");
+        assert_eq!(score.samples, 3);
+        assert!((score.latency_ewma_ms.unwrap() - 120.0).abs() < 1e-9);
+        assert!((score.success_rate - 0.8).abs() < 1e-9);
+        assert!((score.health_score - 0.8 / 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lowest_latency_measures_new_endpoints_first() {
+        let stats = stats(&[Some(80), Some(20), None]);
+        let selector = selector(SelectionStrategy::LowestLatency);
+        assert_eq!(selector.pick(&[0, 1, 2], &stats), Some(2));
+        assert_eq!(selector.pick(&[0, 1], &stats), Some(1));
+        assert_eq!(selector.pick(&[0], &stats), Some(0));
+        assert_eq!(selector.pick(&[], &stats), None);
+    }
+
+    #[test]
+    fn test_weighted_random_prefers_healthy_endpoints() {
+        let stats = stats(&[Some(10), Some(40)]);
+        for _ in 0..10 {
+            stats[1].record(false, None);
+        }
+        let selector = selector(SelectionStrategy::WeightedRandom);
+
+        let mut picks = [0usize; 2];
+        for _ in 0..2_000 {
+            picks[selector.pick(&[0, 1], &stats).unwrap()] += 1;
+        }
+        // Endpoint 1 is 4x slower and mostly failing, but never starved
+        assert!(picks[0] > picks[1] * 10, "{:?}", picks);
+        assert!(picks[1] > 0, "{:?}", picks);
+        assert_eq!(selector.pick(&[1], &stats), Some(1));
+    }
+}
@@ -0,0 +1,135 @@
+//! Block-consistent call sequences
+//!
+//! An actor reading a block, then its receipts, then its traces through
+//! separate calls can get each answer from a different endpoint, one of them a
+//! block behind or on the other side of a reorg. A [`ConsistentSession`] pins
+//! a sequence of calls to one endpoint and one block:
+//! - the endpoint is picked once, when the session opens, as for a single
+//!   call (an archive endpoint when the block is old)
+//! - the block is the one asked for, or that endpoint's head at opening
+//! - `latest` and `pending` tags in the params, top level or inside filter
+//!   objects, are replaced with the pinned block; state methods called
+//!   without their block parameter get it appended
+//!
+//! Session calls skip the cache and never fail over, since either would mix
+//! in another endpoint's view. Errors are returned as they are; the caller
+//! may open a fresh session.
+
+use crate::archive::block_param_position;
+use crate::cost::endpoint_host;
+use crate::endpoint_pool::{EndpointPool, RpcRequest};
+use crate::head_lag::block_height;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Block tags a session replaces with its pinned block
+const MOVING_TAGS: &[&str] = &["latest", "pending"];
+
+/// Calls pinned to one endpoint and one block
+pub struct ConsistentSession {
+    pool: Arc<EndpointPool>,
+    endpoint: usize,
+    host: String,
+    block: u64,
+}
+
+impl ConsistentSession {
+    /// Pin a session on `pool` to `block`, or to the chosen endpoint's head
+    pub async fn open(pool: Arc<EndpointPool>, block: Option<u64>) -> Result<Self> {
+        let endpoint = pool.session_endpoint(block).await?;
+        let host = endpoint_host(&pool.endpoints()[endpoint]);
+        let block = match block {
+            Some(block) => block,
+            None => pool
+                .call_pinned(endpoint, &RpcRequest::new("eth_blockNumber", vec![]))
+                .await?
+                .result
+                .as_ref()
+                .and_then(block_height)
+                .ok_or_else(|| anyhow!("No block number from {}", host))?,
+        };
+        debug!("Consistent session on {} at block {}", host, block);
+
+        Ok(Self {
+            pool,
+            endpoint,
+            host,
+            block,
+        })
+    }
+
+    /// Host of the pinned endpoint
+    pub fn endpoint(&self) -> &str {
+        &self.host
+    }
+
+    pub fn block(&self) -> u64 {
+        self.block
+    }
+
+    /// Make one call at the pinned block on the pinned endpoint
+    pub async fn call(&self, method: &str, params: Vec<Value>) -> Result<Value> {
+        let request = RpcRequest::new(method, pin_block(method, params, self.block));
+        let response = self.pool.call_pinned(self.endpoint, &request).await?;
+        response
+            .result
+            .ok_or_else(|| anyhow!("No result in RPC response"))
+    }
+}
+
+/// `params` of a `method` call read at `block` instead of the head
+pub fn pin_block(method: &str, mut params: Vec<Value>, block: u64) -> Vec<Value> {
+    let pinned = Value::String(format!("0x{:x}", block));
+    let pin = |value: &mut Value| {
+        if value.as_str().is_some_and(|tag| MOVING_TAGS.contains(&tag)) {
+            *value = pinned.clone();
+        }
+    };
+    for param in params.iter_mut() {
+        match param {
+            Value::Object(fields) => fields.values_mut().for_each(&pin),
+            other => pin(other),
+        }
+    }
+    if block_param_position(method) == Some(params.len()) {
+        params.push(pinned);
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pin_block_replaces_moving_tags() {
+        assert_eq!(
+            pin_block(
+                "eth_getBlockByNumber",
+                vec![json!("latest"), json!(true)],
+                255
+            ),
+            vec![json!("0xff"), json!(true)]
+        );
+        assert_eq!(
+            pin_block(
+                "eth_getLogs",
+                vec![json!({"fromBlock": "0x1", "toBlock": "latest"})],
+                16
+            ),
+            vec![json!({"fromBlock": "0x1", "toBlock": "0x10"})]
+        );
+        // Omitted block parameters default to latest, so they are pinned too
+        assert_eq!(
+            pin_block("eth_call", vec![json!({"to": "0xabc"})], 16),
+            vec![json!({"to": "0xabc"}), json!("0x10")]
+        );
+        assert_eq!(
+            pin_block("eth_getTransactionReceipt", vec![json!("0xdef")], 16),
+            vec![json!("0xdef")]
+        );
+    }
+}
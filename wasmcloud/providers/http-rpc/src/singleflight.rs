@@ -0,0 +1,159 @@
+//! Request coalescing for identical in-flight calls
+//!
+//! When many actors ask for the same method and params at once (the tip block,
+//! a popular `eth_call`), only the first caller goes upstream; the others wait
+//! for its answer. Calls are keyed by their cache key, so the shared answer is
+//! also written to the cache once. The caller that made the call keeps its
+//! answer as it is; waiters get a shared copy (see [`Flight`]). A caller
+//! dropped before answering releases its waiters, which then make the call
+//! themselves.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::broadcast;
+
+/// Calls in flight, keyed by request, each with the answer shared with its
+/// waiters
+pub struct SingleFlight<S> {
+    calls: Mutex<HashMap<String, broadcast::Sender<S>>>,
+}
+
+/// Answer to one caller of [`SingleFlight::run`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Flight<T, S> {
+    /// This caller made the call
+    Led(T),
+    /// An identical call in flight answered; its answer as shared
+    Joined(S),
+}
+
+impl<S: Clone> SingleFlight<S> {
+    pub fn new() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `call` for `key`, or wait for the answer of the identical call
+    /// already in flight; `share` makes the copy handed to waiters
+    pub async fn run<T, F, Fut>(&self, key: &str, call: F, share: fn(&T) -> S) -> Flight<T, S>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let waiting = {
+            let mut calls = self.calls.lock();
+            match calls.get(key) {
+                Some(leader) => Some(leader.subscribe()),
+                None => {
+                    calls.insert(key.to_string(), broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+        if let Some(mut answer) = waiting {
+            return match answer.recv().await {
+                Ok(shared) => Flight::Joined(shared),
+                // The leader was dropped before answering
+                Err(_) => Flight::Led(call().await),
+            };
+        }
+
+        let leader = Leader {
+            calls: &self.calls,
+            key: Some(key),
+        };
+        let value = call().await;
+        if let Some(waiters) = leader.finish() {
+            // No receiver left is fine: nobody was waiting
+            let _ = waiters.send(share(&value));
+        }
+        Flight::Led(value)
+    }
+
+    /// Number of distinct calls in flight
+    pub fn in_flight(&self) -> usize {
+        self.calls.lock().len()
+    }
+}
+
+impl<S: Clone> Default for SingleFlight<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Leader's claim on a key, released when it answers or is dropped
+struct Leader<'a, T> {
+    calls: &'a Mutex<HashMap<String, broadcast::Sender<T>>>,
+    key: Option<&'a str>,
+}
+
+impl<T> Leader<'_, T> {
+    fn finish(mut self) -> Option<broadcast::Sender<T>> {
+        let key = self.key.take()?;
+        self.calls.lock().remove(key)
+    }
+}
+
+impl<T> Drop for Leader<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.calls.lock().remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_identical_calls_share_one_upstream_call() {
+        let flight = SingleFlight::new();
+        let calls = AtomicUsize::new(0);
+        let calls = &calls;
+        let call = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            "0x10".to_string()
+        };
+
+        let (first, second, other) = tokio::join!(
+            flight.run("eth_blockNumber", call, |answer| answer.len()),
+            flight.run("eth_blockNumber", call, |answer| answer.len()),
+            flight.run("eth_chainId", call, |answer| answer.len()),
+        );
+        assert_eq!(first, Flight::Led("0x10".to_string()));
+        assert_eq!(second, Flight::Joined(4));
+        assert_eq!(other, Flight::Led("0x10".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(flight.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_leader_releases_waiters() {
+        let flight = SingleFlight::new();
+        let leader = tokio::time::timeout(
+            Duration::from_millis(10),
+            flight.run(
+                "eth_blockNumber",
+                std::future::pending::<String>,
+                String::clone,
+            ),
+        );
+        let waiter = flight.run(
+            "eth_blockNumber",
+            || async { "0x10".to_string() },
+            String::clone,
+        );
+
+        let (leader, waiter) = tokio::join!(leader, waiter);
+        assert!(leader.is_err());
+        assert_eq!(waiter, Flight::Led("0x10".to_string()));
+        assert_eq!(flight.in_flight(), 0);
+    }
+}
@@ -0,0 +1,199 @@
+//! Request and response size limits
+//!
+//! A `debug_traceTransaction` of a busy transaction, or `eth_getLogs` over a
+//! popular contract, can answer hundreds of megabytes; buffered whole, a few at
+//! once exhaust the provider's memory. Answers are read chunk by chunk and
+//! abandoned once they pass the limit for their method:
+//! - `max_response_bytes` applies to methods without their own limit in
+//!   `methods`; a batch may take the sum of its calls' limits
+//! - a `Content-Length` over the limit aborts before the body is read
+//! - `max_request_bytes` bounds the JSON sent upstream
+//!
+//! Limits count decompressed bytes. An oversized call is the call's fault, not
+//! the endpoint's: it fails without counting against circuit breakers and is
+//! not retried elsewhere, since every endpoint would send the same answer.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Size limits (`HTTP_RPC_SIZE_LIMIT_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeLimitConfig {
+    /// Largest request body sent upstream
+    pub max_request_bytes: u64,
+    /// Largest answer read for methods not in `methods`
+    pub max_response_bytes: u64,
+    /// Largest answer read per method
+    pub methods: HashMap<String, u64>,
+}
+
+impl Default for SizeLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_request_bytes: 4 * MIB,
+            max_response_bytes: 64 * MIB,
+            methods: HashMap::new(),
+        }
+    }
+}
+
+impl SizeLimitConfig {
+    /// Largest answer read for a `method` call
+    pub fn response_limit(&self, method: &str) -> u64 {
+        self.methods
+            .get(method)
+            .copied()
+            .unwrap_or(self.max_response_bytes)
+    }
+
+    /// Largest answer read for a batch of calls to `methods`
+    pub fn batch_limit<'a>(&self, methods: impl IntoIterator<Item = &'a str>) -> u64 {
+        methods.into_iter().fold(0, |limit: u64, method| {
+            limit.saturating_add(self.response_limit(method))
+        })
+    }
+
+    /// Fail when a request body of `bytes` is too large to send
+    pub fn check_request(&self, what: &str, bytes: usize) -> Result<()> {
+        if bytes as u64 > self.max_request_bytes {
+            return Err(SizeLimitExceeded {
+                what: format!("Request {}", what),
+                bytes: bytes as u64,
+                limit: self.max_request_bytes,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Request or answer over its size limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeLimitExceeded {
+    /// What was too large, e.g. "Response to eth_call"
+    pub what: String,
+    pub bytes: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for SizeLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} size limit exceeded: {} bytes over {}",
+            self.what, self.bytes, self.limit
+        )
+    }
+}
+
+impl std::error::Error for SizeLimitExceeded {}
+
+/// Whether an error is a size limit error rather than an endpoint failure
+pub fn is_size_limit_error(error: &anyhow::Error) -> bool {
+    error.is::<SizeLimitExceeded>()
+}
+
+fn response_too_large(what: &str, bytes: u64, limit: u64) -> anyhow::Error {
+    SizeLimitExceeded {
+        what: format!("Response to {}", what),
+        bytes,
+        limit,
+    }
+    .into()
+}
+
+/// Read the body of `response` to `what` (a method, or a batch), giving up as
+/// soon as it passes `limit` bytes
+pub async fn read_body(mut response: reqwest::Response, what: &str, limit: u64) -> Result<Vec<u8>> {
+    if let Some(length) = response.content_length().filter(|length| *length > limit) {
+        return Err(response_too_large(what, length, limit));
+    }
+
+    let mut body = BodyBuffer::new(limit, response.content_length());
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| anyhow!("Failed to read RPC response: {}", e))?
+    {
+        body.push(&chunk)
+            .map_err(|read| response_too_large(what, read, limit))?;
+    }
+    Ok(body.bytes)
+}
+
+/// Body read so far, bounded by a limit
+struct BodyBuffer {
+    bytes: Vec<u8>,
+    limit: u64,
+}
+
+impl BodyBuffer {
+    fn new(limit: u64, length: Option<u64>) -> Self {
+        // Pre-allocate for the announced length, never past the limit
+        let capacity = length.unwrap_or(0).min(limit).min(MIB);
+        Self {
+            bytes: Vec::with_capacity(capacity as usize),
+            limit,
+        }
+    }
+
+    /// Append a chunk; fails with the bytes read when that passes the limit
+    fn push(&mut self, chunk: &[u8]) -> std::result::Result<(), u64> {
+        let read = self.bytes.len() as u64 + chunk.len() as u64;
+        if read > self.limit {
+            return Err(read);
+        }
+        self.bytes.extend_from_slice(chunk);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_per_method_and_batch() {
+        let config: SizeLimitConfig = serde_json::from_str(
+            r#"{"max_response_bytes": 1000, "methods": {"debug_traceTransaction": 5000}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.max_request_bytes, 4 * MIB);
+        assert_eq!(config.response_limit("eth_call"), 1000);
+        assert_eq!(config.response_limit("debug_traceTransaction"), 5000);
+        assert_eq!(
+            config.batch_limit(["eth_call", "debug_traceTransaction"]),
+            6000
+        );
+
+        assert!(config.check_request("eth_call", 4 * MIB as usize).is_ok());
+        let error = config
+            .check_request("eth_call", 4 * MIB as usize + 1)
+            .unwrap_err();
+        assert!(is_size_limit_error(&error));
+        assert_eq!(
+            error.to_string(),
+            "Request eth_call size limit exceeded: 4194305 bytes over 4194304"
+        );
+        // An upstream message quoting the phrase is not one
+        assert!(!is_size_limit_error(&anyhow!("HTTP error: status 413")));
+        assert!(!is_size_limit_error(&anyhow!(
+            "RPC error -32000: size limit exceeded"
+        )));
+    }
+
+    #[test]
+    fn test_body_buffer_stops_at_limit() {
+        let mut body = BodyBuffer::new(10, Some(u64::MAX));
+        assert!(body.bytes.capacity() <= 10);
+        assert!(body.push(b"hello").is_ok());
+        assert!(body.push(b"world").is_ok());
+        assert_eq!(body.push(b"!"), Err(11));
+        assert_eq!(body.bytes, b"helloworld");
+    }
+}
@@ -0,0 +1,252 @@
+//! Solana JSON-RPC on the shared endpoint pools
+//!
+//! Solana networks (`networks`) go through the same pools, failover and cache
+//! as EVM ones; what differs is how long an answer may be cached. Solana
+//! answers are read at a commitment level, given in the call's config object
+//! and `finalized` when omitted:
+//! - `finalized`: rooted, never rolled back; blocks and transactions are
+//!   cached without expiry
+//! - `confirmed`: voted on by a supermajority, all but never rolled back;
+//!   cached for `confirmed_ttl_secs`
+//! - `processed`: the node's own latest view, which may be forked away; never
+//!   cached
+//!
+//! `getSignaturesForAddress` pages that end at a `before` signature are fixed
+//! once finalized; the newest page grows with every slot and is cached like
+//! slot reads (`getSlot`, `getLatestBlockhash`, ...) for `head_ttl_secs`.
+//! `null` blocks and transactions (not there yet) go to the negative cache.
+//!
+//! Slots come every ~400ms, so endpoints of Solana networks are held to
+//! `max_lag_slots` rather than the block lag EVM endpoints are held to, and
+//! the canary probes them with `getSlot` and `getHealth`.
+
+use crate::cache::CacheTtl;
+use crate::endpoint_pool::RpcRequest;
+use crate::head_lag::HeadLagConfig;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Methods whose answer moves with every slot
+const SLOT_METHODS: &[&str] = &[
+    "getSlot",
+    "getBlockHeight",
+    "getLatestBlockhash",
+    "getEpochInfo",
+    "getRecentPrioritizationFees",
+];
+
+/// Commitment level of a Solana read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Processed => "processed",
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        }
+    }
+}
+
+/// Solana settings (`HTTP_RPC_SOLANA_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SolanaConfig {
+    /// Networks speaking Solana JSON-RPC
+    pub networks: Vec<String>,
+    /// Seconds `confirmed` answers are cached
+    pub confirmed_ttl_secs: u64,
+    /// Seconds slot reads and the newest signatures are cached
+    pub head_ttl_secs: u64,
+    /// Slots an endpoint may trail the pool's median before it is left out
+    pub max_lag_slots: u64,
+}
+
+impl Default for SolanaConfig {
+    fn default() -> Self {
+        Self {
+            networks: ["solana", "solana-devnet", "solana-testnet"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            confirmed_ttl_secs: 30,
+            head_ttl_secs: 1,
+            max_lag_slots: 150,
+        }
+    }
+}
+
+impl SolanaConfig {
+    pub fn is_solana(&self, network: &str) -> bool {
+        self.networks.iter().any(|solana| solana == network)
+    }
+
+    /// Head lag settings of `network`: slots instead of blocks for Solana
+    pub fn head_lag(&self, network: &str, head_lag: &HeadLagConfig) -> HeadLagConfig {
+        if !self.is_solana(network) {
+            return head_lag.clone();
+        }
+        HeadLagConfig {
+            max_lag_blocks: self.max_lag_slots,
+            ..head_lag.clone()
+        }
+    }
+
+    /// How long a Solana `method` answer may be cached; `None` leaves it to
+    /// the default TTL
+    pub fn cache_ttl(&self, method: &str, params: &[Value]) -> Option<CacheTtl> {
+        let commitment = commitment(params);
+        if commitment == Commitment::Processed {
+            return Some(CacheTtl::Never);
+        }
+        let settled = match commitment {
+            Commitment::Finalized => CacheTtl::Forever,
+            _ => CacheTtl::Secs(self.confirmed_ttl_secs),
+        };
+        match method {
+            "getGenesisHash" => Some(CacheTtl::Forever),
+            "getBlock" | "getTransaction" | "getBlockTime" => Some(settled),
+            "getSignaturesForAddress" => {
+                let bounded = config_object(params).is_some_and(|config| {
+                    config.get("before").is_some_and(|before| !before.is_null())
+                });
+                Some(if bounded {
+                    settled
+                } else {
+                    CacheTtl::Secs(self.head_ttl_secs)
+                })
+            }
+            method if SLOT_METHODS.contains(&method) => Some(CacheTtl::Secs(self.head_ttl_secs)),
+            _ => None,
+        }
+    }
+}
+
+/// The trailing config object of a call, if any
+fn config_object(params: &[Value]) -> Option<&serde_json::Map<String, Value>> {
+    params.last().and_then(Value::as_object)
+}
+
+/// Commitment a call reads at; nodes default to `finalized`
+pub fn commitment(params: &[Value]) -> Commitment {
+    config_object(params)
+        .and_then(|config| config.get("commitment"))
+        .and_then(|commitment| serde_json::from_value(commitment.clone()).ok())
+        .unwrap_or(Commitment::Finalized)
+}
+
+/// `getBlock` of `slot` with its full transactions (versioned ones included)
+pub fn get_block_request(slot: u64, commitment: Commitment) -> Result<RpcRequest> {
+    if commitment == Commitment::Processed {
+        return Err(anyhow!("getBlock does not read at processed commitment"));
+    }
+    Ok(RpcRequest::new(
+        "getBlock",
+        vec![
+            json!(slot),
+            json!({
+                "encoding": "json",
+                "transactionDetails": "full",
+                "rewards": false,
+                "maxSupportedTransactionVersion": 0,
+                "commitment": commitment.as_str(),
+            }),
+        ],
+    ))
+}
+
+/// `getTransaction` of `signature`
+pub fn get_transaction_request(signature: &str, commitment: Commitment) -> Result<RpcRequest> {
+    if commitment == Commitment::Processed {
+        return Err(anyhow!(
+            "getTransaction does not read at processed commitment"
+        ));
+    }
+    Ok(RpcRequest::new(
+        "getTransaction",
+        vec![
+            json!(signature),
+            json!({
+                "encoding": "json",
+                "maxSupportedTransactionVersion": 0,
+                "commitment": commitment.as_str(),
+            }),
+        ],
+    ))
+}
+
+/// `getSignaturesForAddress` of `address`, newest first, up to `limit` older
+/// than `before` when given
+pub fn signatures_request(
+    address: &str,
+    before: Option<&str>,
+    limit: Option<u32>,
+    commitment: Commitment,
+) -> RpcRequest {
+    let mut config = json!({"commitment": commitment.as_str()});
+    if let Some(before) = before {
+        config["before"] = json!(before);
+    }
+    if let Some(limit) = limit {
+        config["limit"] = json!(limit);
+    }
+    RpcRequest::new("getSignaturesForAddress", vec![json!(address), config])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_aware_ttls() {
+        let config = SolanaConfig::default();
+        assert!(config.is_solana("solana-devnet"));
+        assert!(!config.is_solana("ethereum"));
+
+        let block =
+            |commitment: Commitment| get_block_request(250_000_000, commitment).unwrap().params;
+        assert_eq!(
+            config.cache_ttl("getBlock", &block(Commitment::Finalized)),
+            Some(CacheTtl::Forever)
+        );
+        assert_eq!(
+            config.cache_ttl("getBlock", &block(Commitment::Confirmed)),
+            Some(CacheTtl::Secs(30))
+        );
+        assert!(get_block_request(1, Commitment::Processed).is_err());
+        // No config object: the node's default, finalized
+        assert_eq!(
+            config.cache_ttl("getTransaction", &[json!("5sig")]),
+            Some(CacheTtl::Forever)
+        );
+        assert_eq!(
+            config.cache_ttl("getSlot", &[json!({"commitment": "processed"})]),
+            Some(CacheTtl::Never)
+        );
+        assert_eq!(config.cache_ttl("getSlot", &[]), Some(CacheTtl::Secs(1)));
+        assert_eq!(config.cache_ttl("getAccountInfo", &[json!("Acc")]), None);
+    }
+
+    #[test]
+    fn test_only_bounded_signature_pages_settle() {
+        let config = SolanaConfig::default();
+        let newest = signatures_request("Acc", None, Some(100), Commitment::Finalized);
+        assert_eq!(
+            config.cache_ttl(&newest.method, &newest.params),
+            Some(CacheTtl::Secs(1))
+        );
+        let older = signatures_request("Acc", Some("5sig"), Some(100), Commitment::Finalized);
+        assert_eq!(older.params[1]["before"], "5sig");
+        assert_eq!(
+            config.cache_ttl(&older.method, &older.params),
+            Some(CacheTtl::Forever)
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Weighted A/B traffic split between RPC vendors
+//!
+//! Operators trial a new vendor by putting its endpoints in a trial arm and
+//! routing a fixed share of requests to it (e.g. 90/10). Each arm keeps its own
+//! request, error and latency counters. If the trial arm's error rate stays
+//! above `rollback_error_rate` for a full `rollback_window_secs`, the split
+//! rolls back and all traffic returns to the primary arm until a new split
+//! config is applied.
+
+use crate::cost::endpoint_host;
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Split configuration for one network's pool (`HTTP_RPC_SPLIT_CONFIG`, JSON keyed by network)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitConfig {
+    /// Endpoints (URL or host) forming the trial arm; all others are primary
+    pub trial_endpoints: Vec<String>,
+    /// Percent of requests (0-100) routed to the trial arm
+    pub trial_percent: u8,
+    /// Trial error rate (0.0-1.0) that triggers rollback when sustained
+    pub rollback_error_rate: f64,
+    /// How long the error rate must stay above the threshold; also the
+    /// sliding window the rate is measured over
+    pub rollback_window_secs: u64,
+    /// Trial requests needed in the window before the rate counts
+    pub min_requests: usize,
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        Self {
+            trial_endpoints: Vec::new(),
+            trial_percent: 10,
+            rollback_error_rate: 0.2,
+            rollback_window_secs: 300,
+            min_requests: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arm {
+    Primary,
+    Trial,
+}
+
+/// Counters for one arm
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArmStatus {
+    pub endpoints: Vec<String>,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+}
+
+/// Split snapshot for one pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitStatus {
+    pub trial_percent: u8,
+    pub rolled_back: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_reason: Option<String>,
+    pub primary: ArmStatus,
+    pub trial: ArmStatus,
+}
+
+#[derive(Debug, Default)]
+struct ArmCounters {
+    requests: u64,
+    errors: u64,
+    latency_total: Duration,
+}
+
+impl ArmCounters {
+    fn status(&self, endpoints: Vec<String>) -> ArmStatus {
+        let (error_rate, avg_latency_ms) = if self.requests == 0 {
+            (0.0, 0.0)
+        } else {
+            (
+                self.errors as f64 / self.requests as f64,
+                self.latency_total.as_secs_f64() * 1000.0 / self.requests as f64,
+            )
+        };
+        ArmStatus {
+            endpoints,
+            requests: self.requests,
+            errors: self.errors,
+            error_rate,
+            avg_latency_ms,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SplitState {
+    primary: ArmCounters,
+    trial: ArmCounters,
+    /// Trial outcomes inside the rollback window: (time, success)
+    recent_trial: VecDeque<(Instant, bool)>,
+    /// When the windowed trial error rate first went above the threshold
+    breach_since: Option<Instant>,
+    rollback_reason: Option<String>,
+}
+
+/// Live split state for one pool; endpoint indices follow the pool's order
+pub struct TrafficSplit {
+    config: SplitConfig,
+    hosts: Vec<String>,
+    trial: Vec<bool>,
+    counter: AtomicU64,
+    state: Mutex<SplitState>,
+}
+
+impl TrafficSplit {
+    pub fn new(endpoints: &[String], config: SplitConfig) -> Result<Self> {
+        if config.trial_percent > 100 {
+            return Err(anyhow!(
+                "trial_percent must be 0-100, got {}",
+                config.trial_percent
+            ));
+        }
+
+        let hosts: Vec<String> = endpoints.iter().map(|e| endpoint_host(e)).collect();
+        for trial in &config.trial_endpoints {
+            if !endpoints.contains(trial) && !hosts.contains(trial) {
+                return Err(anyhow!("Trial endpoint {} is not in the pool", trial));
+            }
+        }
+
+        let trial: Vec<bool> = endpoints
+            .iter()
+            .zip(&hosts)
+            .map(|(url, host)| {
+                config.trial_endpoints.contains(url) || config.trial_endpoints.contains(host)
+            })
+            .collect();
+        if trial.iter().all(|is_trial| *is_trial) {
+            return Err(anyhow!("Split needs at least one primary endpoint"));
+        }
+
+        Ok(Self {
+            config,
+            hosts,
+            trial,
+            counter: AtomicU64::new(0),
+            state: Mutex::new(SplitState::default()),
+        })
+    }
+
+    pub fn arm_of(&self, endpoint_idx: usize) -> Arm {
+        if self.trial.get(endpoint_idx).copied().unwrap_or(false) {
+            Arm::Trial
+        } else {
+            Arm::Primary
+        }
+    }
+
+    pub fn is_rolled_back(&self) -> bool {
+        self.state.lock().rollback_reason.is_some()
+    }
+
+    /// Arm for the next request
+    pub fn choose_arm(&self) -> Arm {
+        if self.is_rolled_back() {
+            return Arm::Primary;
+        }
+        // Striding by 37 (coprime with 100) visits every slot of each block of
+        // 100 requests once, so the trial share is exact and interleaved
+        let slot = self
+            .counter
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_mul(37)
+            % 100;
+        if slot < u64::from(self.config.trial_percent) {
+            Arm::Trial
+        } else {
+            Arm::Primary
+        }
+    }
+
+    /// Record the outcome of one upstream attempt
+    pub fn record(&self, endpoint_idx: usize, success: bool, latency: Duration) {
+        self.record_at(endpoint_idx, success, latency, Instant::now());
+    }
+
+    pub fn status(&self) -> SplitStatus {
+        let state = self.state.lock();
+        let arm_hosts = |arm: Arm| -> Vec<String> {
+            self.hosts
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| self.arm_of(*i) == arm)
+                .map(|(_, host)| host.clone())
+                .collect()
+        };
+
+        SplitStatus {
+            trial_percent: self.config.trial_percent,
+            rolled_back: state.rollback_reason.is_some(),
+            rollback_reason: state.rollback_reason.clone(),
+            primary: state.primary.status(arm_hosts(Arm::Primary)),
+            trial: state.trial.status(arm_hosts(Arm::Trial)),
+        }
+    }
+
+    fn record_at(&self, endpoint_idx: usize, success: bool, latency: Duration, now: Instant) {
+        let arm = self.arm_of(endpoint_idx);
+        let mut state = self.state.lock();
+
+        let counters = match arm {
+            Arm::Primary => &mut state.primary,
+            Arm::Trial => &mut state.trial,
+        };
+        counters.requests += 1;
+        counters.errors += u64::from(!success);
+        counters.latency_total += latency;
+
+        if arm == Arm::Trial && state.rollback_reason.is_none() {
+            self.evaluate_rollback(&mut state, success, now);
+        }
+    }
+
+    fn evaluate_rollback(&self, state: &mut SplitState, success: bool, now: Instant) {
+        let window = Duration::from_secs(self.config.rollback_window_secs);
+        state.recent_trial.push_back((now, success));
+        while state
+            .recent_trial
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > window)
+        {
+            state.recent_trial.pop_front();
+        }
+
+        let samples = state.recent_trial.len();
+        let errors = state.recent_trial.iter().filter(|(_, ok)| !ok).count();
+        let error_rate = errors as f64 / samples as f64;
+        if samples < self.config.min_requests || error_rate <= self.config.rollback_error_rate {
+            state.breach_since = None;
+            return;
+        }
+
+        let since = *state.breach_since.get_or_insert(now);
+        if now.saturating_duration_since(since) >= window {
+            let reason = format!(
+                "trial error rate {:.1}% above {:.1}% for {}s",
+                error_rate * 100.0,
+                self.config.rollback_error_rate * 100.0,
+                self.config.rollback_window_secs
+            );
+            warn!("Rolling back traffic split: {}", reason);
+            state.rollback_reason = Some(reason);
+            state.recent_trial.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints() -> Vec<String> {
+        vec![
+            "https://eth-mainnet.g.alchemy.com/v2/key".to_string(),
+            "https://mainnet.infura.io/v3/key".to_string(),
+            "https://rpc.newvendor.io".to_string(),
+        ]
+    }
+
+    fn config() -> SplitConfig {
+        SplitConfig {
+            trial_endpoints: vec!["rpc.newvendor.io".to_string()],
+            rollback_window_secs: 60,
+            min_requests: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_weighted_split_is_exact_per_hundred_requests() {
+        let split = TrafficSplit::new(&endpoints(), config()).unwrap();
+        assert_eq!(split.arm_of(0), Arm::Primary);
+        assert_eq!(split.arm_of(2), Arm::Trial);
+
+        let trial = (0..100)
+            .filter(|_| split.choose_arm() == Arm::Trial)
+            .count();
+        assert_eq!(trial, 10);
+
+        let unknown = SplitConfig {
+            trial_endpoints: vec!["rpc.unknown.io".to_string()],
+            ..Default::default()
+        };
+        assert!(TrafficSplit::new(&endpoints(), unknown).is_err());
+        let all_trial = SplitConfig {
+            trial_endpoints: endpoints(),
+            ..Default::default()
+        };
+        assert!(TrafficSplit::new(&endpoints(), all_trial).is_err());
+    }
+
+    #[test]
+    fn test_rollback_after_sustained_trial_errors() {
+        let split = TrafficSplit::new(&endpoints(), config()).unwrap();
+        let start = Instant::now();
+        let latency = Duration::from_millis(40);
+
+        // Breach starts once min_requests samples are in (s=4)
+        for s in 0..64 {
+            split.record_at(2, false, latency, start + Duration::from_secs(s));
+        }
+        assert!(!split.is_rolled_back());
+        split.record_at(2, false, latency, start + Duration::from_secs(64));
+        assert!(split.is_rolled_back());
+        assert_eq!(split.choose_arm(), Arm::Primary);
+        split.record_at(0, true, latency, start + Duration::from_secs(65));
+
+        // Recovery in between resets the breach clock
+        let recovered = TrafficSplit::new(&endpoints(), config()).unwrap();
+        for s in 0..=30 {
+            recovered.record_at(2, false, latency, start + Duration::from_secs(s));
+        }
+        for _ in 0..200 {
+            recovered.record_at(2, true, latency, start + Duration::from_secs(31));
+        }
+        for s in 32..=64 {
+            recovered.record_at(2, false, latency, start + Duration::from_secs(s));
+        }
+        assert!(!recovered.is_rolled_back());
+
+        let status = split.status();
+        assert!(status.rolled_back);
+        assert_eq!(status.primary.requests, 1);
+        assert_eq!(status.trial.requests, 65);
+        assert_eq!(status.trial.errors, 65);
+        assert_eq!(status.trial.avg_latency_ms, 40.0);
+        assert_eq!(status.trial.endpoints, vec!["rpc.newvendor.io".to_string()]);
+    }
+}
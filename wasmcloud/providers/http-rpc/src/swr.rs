@@ -0,0 +1,82 @@
+//! Stale-while-revalidate reads
+//!
+//! Alert evaluation reads the same `latest` state over and over, and every new
+//! head drops those answers from the cache, so the first read after each block
+//! waits on an upstream round trip. With SWR on, every answer to a method with
+//! a staleness budget is also kept as a stale copy for that long. A call whose
+//! fresh entry is gone but whose stale copy is within budget is answered from
+//! the copy at once, and the call is sent upstream in the background to
+//! refresh both (one refresh per cache key at a time).
+//!
+//! The budget bounds the age of what is served: a `12` budget never answers
+//! with data fetched more than 12 seconds ago.
+
+use crate::endpoint_pool::RpcRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Stale-while-revalidate settings (`HTTP_RPC_SWR_CONFIG`, JSON)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwrConfig {
+    pub enabled: bool,
+    /// Staleness budget per method (seconds)
+    pub methods: HashMap<String, u64>,
+    /// Budget of methods not listed (seconds, 0 for none)
+    pub default_stale_secs: u64,
+}
+
+impl SwrConfig {
+    /// How stale an answer to `method` may be served; `None` when never
+    pub fn budget(&self, method: &str) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        let secs = self
+            .methods
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_stale_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+/// A call answered stale, to be sent upstream again
+#[derive(Debug, Clone)]
+pub struct Revalidation {
+    pub network: String,
+    pub request: RpcRequest,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budgets_per_method() {
+        let config: SwrConfig = serde_json::from_str(
+            r#"{"enabled": true, "methods": {"eth_call": 12, "eth_gasPrice": 0}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.budget("eth_call"), Some(Duration::from_secs(12)));
+        assert_eq!(config.budget("eth_gasPrice"), None);
+        assert_eq!(config.budget("eth_getBalance"), None);
+
+        let config = SwrConfig {
+            default_stale_secs: 4,
+            ..config
+        };
+        assert_eq!(
+            config.budget("eth_getBalance"),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(config.budget("eth_gasPrice"), None);
+
+        let disabled = SwrConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(disabled.budget("eth_call"), None);
+    }
+}
@@ -0,0 +1,416 @@
+//! Transaction and block traces
+//!
+//! Nodes expose traces in one of two flavors:
+//! - geth (and most vendors): `debug_traceTransaction` and
+//!   `debug_traceBlockByNumber` with the `callTracer`, answering a tree of
+//!   call frames
+//! - erigon, nethermind and parity-style nodes: `trace_transaction` and
+//!   `trace_block`, answering a flat list addressed by `traceAddress`
+//!
+//! Which one an endpoint speaks is found out on first use: its known flavor is
+//! asked first, and an endpoint answering "method not found" for a flavor is
+//! not asked it again. Either answer is normalized into flat [`Trace`]s in
+//! parity order (a call, then its subcalls), so actors read one shape.
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Messages meaning an endpoint lacks a method (lowercase)
+const UNSUPPORTED_ERRORS: &[&str] = &[
+    "rpc error -32601",
+    "method not found",
+    "does not exist",
+    "is not available",
+    "not supported",
+    "unsupported method",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceFlavor {
+    /// `debug_*` with the call tracer
+    Geth,
+    /// `trace_*`
+    Parity,
+}
+
+impl TraceFlavor {
+    const ALL: [Self; 2] = [Self::Geth, Self::Parity];
+
+    fn index(self) -> usize {
+        match self {
+            Self::Geth => 0,
+            Self::Parity => 1,
+        }
+    }
+}
+
+/// What to trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceTarget {
+    Transaction(String),
+    /// Block number or tag
+    Block(String),
+}
+
+impl TraceTarget {
+    /// Request for the target's traces in `flavor`
+    pub fn request(&self, flavor: TraceFlavor) -> crate::endpoint_pool::RpcRequest {
+        use crate::endpoint_pool::RpcRequest;
+
+        let tracer = json!({"tracer": "callTracer"});
+        match (self, flavor) {
+            (Self::Transaction(hash), TraceFlavor::Geth) => {
+                RpcRequest::new("debug_traceTransaction", vec![json!(hash), tracer])
+            }
+            (Self::Transaction(hash), TraceFlavor::Parity) => {
+                RpcRequest::new("trace_transaction", vec![json!(hash)])
+            }
+            (Self::Block(block), TraceFlavor::Geth) => {
+                RpcRequest::new("debug_traceBlockByNumber", vec![json!(block), tracer])
+            }
+            (Self::Block(block), TraceFlavor::Parity) => {
+                RpcRequest::new("trace_block", vec![json!(block)])
+            }
+        }
+    }
+
+    /// Flat traces of a `flavor` answer to [`Self::request`]
+    pub fn normalize(&self, flavor: TraceFlavor, result: Value) -> Result<Vec<Trace>> {
+        let mut traces = Vec::new();
+        match (self, flavor) {
+            (Self::Transaction(hash), TraceFlavor::Geth) => {
+                flatten_frame(&result, Some(hash.as_str()), &mut Vec::new(), &mut traces)?;
+            }
+            (Self::Block(_), TraceFlavor::Geth) => {
+                // One `{txHash, result}` per transaction; older geth leaves out
+                // the hash
+                for tx in as_array(&result)? {
+                    let hash = tx.get("txHash").and_then(Value::as_str);
+                    let frame = tx
+                        .get("result")
+                        .ok_or_else(|| anyhow!("Trace failed: {}", tx["error"]))?;
+                    flatten_frame(frame, hash, &mut Vec::new(), &mut traces)?;
+                }
+            }
+            (_, TraceFlavor::Parity) => {
+                for trace in as_array(&result)? {
+                    traces.push(parity_trace(trace)?);
+                }
+            }
+        }
+        Ok(traces)
+    }
+}
+
+/// Kind of a trace entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceKind {
+    Call,
+    Create,
+    SelfDestruct,
+    /// Block and uncle rewards (parity flavor only)
+    Reward,
+}
+
+/// One call frame, in either flavor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Trace {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<String>,
+    pub kind: Option<TraceKind>,
+    /// `call`, `staticcall`, `delegatecall`, `callcode`, `create`, `create2`,
+    /// or the reward type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Callee, created contract, refund address or reward recipient
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<String>,
+    /// Calldata, or init code for creates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    /// Return data, or deployed code for creates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Position in the call tree: `[]` for the top call, `[1, 0]` for the first
+    /// subcall of its second subcall
+    pub trace_address: Vec<usize>,
+}
+
+/// Whether an upstream error means the endpoint lacks the method
+pub fn is_unsupported_method(message: &str) -> bool {
+    let message = message.to_lowercase();
+    UNSUPPORTED_ERRORS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Trace flavors one endpoint is known to support or lack
+#[derive(Debug, Default)]
+pub struct TraceSupport {
+    /// Per flavor: supported, lacking, or not yet known
+    known: Mutex<[Option<bool>; 2]>,
+}
+
+impl TraceSupport {
+    /// Flavors to ask, known-supported first; empty when the endpoint has none
+    pub fn candidates(&self) -> Vec<TraceFlavor> {
+        let known = *self.known.lock();
+        let mut flavors: Vec<TraceFlavor> = TraceFlavor::ALL
+            .into_iter()
+            .filter(|flavor| known[flavor.index()] != Some(false))
+            .collect();
+        flavors.sort_by_key(|flavor| known[flavor.index()] != Some(true));
+        flavors
+    }
+
+    pub fn record(&self, flavor: TraceFlavor, supported: bool) {
+        self.known.lock()[flavor.index()] = Some(supported);
+    }
+
+    /// Flavor the endpoint answered traces in, if any yet
+    pub fn flavor(&self) -> Option<TraceFlavor> {
+        let known = *self.known.lock();
+        TraceFlavor::ALL
+            .into_iter()
+            .find(|flavor| known[flavor.index()] == Some(true))
+    }
+}
+
+fn as_array(result: &Value) -> Result<&Vec<Value>> {
+    result
+        .as_array()
+        .ok_or_else(|| anyhow!("Unexpected trace result: {}", result))
+}
+
+fn string(value: &Value, field: &str) -> Option<String> {
+    value.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Append the geth call frame at `address` and its subcalls to `traces`
+fn flatten_frame(
+    frame: &Value,
+    hash: Option<&str>,
+    address: &mut Vec<usize>,
+    traces: &mut Vec<Trace>,
+) -> Result<()> {
+    let frame_type = frame
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("Unexpected call frame: {}", frame))?
+        .to_lowercase();
+    let kind = match frame_type.as_str() {
+        "create" | "create2" => TraceKind::Create,
+        "selfdestruct" => TraceKind::SelfDestruct,
+        _ => TraceKind::Call,
+    };
+    traces.push(Trace {
+        transaction_hash: hash.map(str::to_string),
+        kind: Some(kind),
+        call_type: (kind != TraceKind::SelfDestruct).then_some(frame_type),
+        from: string(frame, "from"),
+        to: string(frame, "to"),
+        value: string(frame, "value"),
+        gas: string(frame, "gas"),
+        gas_used: string(frame, "gasUsed"),
+        input: string(frame, "input"),
+        output: string(frame, "output"),
+        error: string(frame, "error"),
+        trace_address: address.clone(),
+    });
+
+    let calls = frame.get("calls").and_then(Value::as_array);
+    for (position, call) in calls.into_iter().flatten().enumerate() {
+        address.push(position);
+        flatten_frame(call, hash, address, traces)?;
+        address.pop();
+    }
+    Ok(())
+}
+
+/// Normalize one parity trace entry
+fn parity_trace(trace: &Value) -> Result<Trace> {
+    let action = &trace["action"];
+    let result = &trace["result"];
+    let trace_address = trace
+        .get("traceAddress")
+        .and_then(Value::as_array)
+        .map(|address| {
+            address
+                .iter()
+                .filter_map(|position| position.as_u64().map(|p| p as usize))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut normalized = Trace {
+        transaction_hash: string(trace, "transactionHash"),
+        error: string(trace, "error"),
+        trace_address,
+        ..Default::default()
+    };
+
+    match trace.get("type").and_then(Value::as_str) {
+        Some("call") => {
+            normalized.kind = Some(TraceKind::Call);
+            normalized.call_type = string(action, "callType");
+            normalized.from = string(action, "from");
+            normalized.to = string(action, "to");
+            normalized.value = string(action, "value");
+            normalized.gas = string(action, "gas");
+            normalized.input = string(action, "input");
+            normalized.gas_used = string(result, "gasUsed");
+            normalized.output = string(result, "output");
+        }
+        Some("create") => {
+            normalized.kind = Some(TraceKind::Create);
+            normalized.call_type =
+                string(action, "creationMethod").or_else(|| Some("create".to_string()));
+            normalized.from = string(action, "from");
+            normalized.to = string(result, "address");
+            normalized.value = string(action, "value");
+            normalized.gas = string(action, "gas");
+            normalized.input = string(action, "init");
+            normalized.gas_used = string(result, "gasUsed");
+            normalized.output = string(result, "code");
+        }
+        Some("suicide") => {
+            normalized.kind = Some(TraceKind::SelfDestruct);
+            normalized.from = string(action, "address");
+            normalized.to = string(action, "refundAddress");
+            normalized.value = string(action, "balance");
+        }
+        Some("reward") => {
+            normalized.kind = Some(TraceKind::Reward);
+            normalized.call_type = string(action, "rewardType");
+            normalized.to = string(action, "author");
+            normalized.value = string(action, "value");
+        }
+        _ => return Err(anyhow!("Unexpected trace: {}", trace)),
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(traces: &[Trace]) -> Vec<(Option<TraceKind>, Option<&str>, Vec<usize>)> {
+        traces
+            .iter()
+            .map(|t| (t.kind, t.call_type.as_deref(), t.trace_address.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_flavors_normalize_to_the_same_traces() {
+        let tx = TraceTarget::Transaction("0xt1".to_string());
+        let geth = json!({
+            "type": "CALL", "from": "0xa", "to": "0xb", "value": "0x0", "gas": "0x5208",
+            "gasUsed": "0x5000", "input": "0x01", "output": "0x",
+            "calls": [
+                {"type": "DELEGATECALL", "from": "0xb", "to": "0xc", "gas": "0x100",
+                 "gasUsed": "0x10", "input": "0x02",
+                 "calls": [{"type": "STATICCALL", "from": "0xc", "to": "0xd", "input": "0x03"}]},
+                {"type": "CREATE2", "from": "0xb", "to": "0xe", "input": "0x6080",
+                 "error": "out of gas"}
+            ]
+        });
+        let parity = json!([
+            {"type": "call", "action": {"callType": "call", "from": "0xa", "to": "0xb",
+             "value": "0x0", "gas": "0x5208", "input": "0x01"},
+             "result": {"gasUsed": "0x5000", "output": "0x"}, "traceAddress": [],
+             "transactionHash": "0xt1"},
+            {"type": "call", "action": {"callType": "delegatecall", "from": "0xb", "to": "0xc",
+             "gas": "0x100", "input": "0x02"}, "result": {"gasUsed": "0x10"},
+             "traceAddress": [0], "transactionHash": "0xt1"},
+            {"type": "call", "action": {"callType": "staticcall", "from": "0xc", "to": "0xd",
+             "input": "0x03"}, "result": {}, "traceAddress": [0, 0], "transactionHash": "0xt1"},
+            {"type": "create", "action": {"creationMethod": "create2", "from": "0xb",
+             "init": "0x6080"}, "result": {"address": "0xe"}, "error": "out of gas",
+             "traceAddress": [1], "transactionHash": "0xt1"}
+        ]);
+
+        let from_geth = tx.normalize(TraceFlavor::Geth, geth).unwrap();
+        let from_parity = tx.normalize(TraceFlavor::Parity, parity).unwrap();
+        assert_eq!(
+            shape(&from_geth),
+            vec![
+                (Some(TraceKind::Call), Some("call"), vec![]),
+                (Some(TraceKind::Call), Some("delegatecall"), vec![0]),
+                (Some(TraceKind::Call), Some("staticcall"), vec![0, 0]),
+                (Some(TraceKind::Create), Some("create2"), vec![1]),
+            ]
+        );
+        assert_eq!(shape(&from_geth), shape(&from_parity));
+        assert_eq!(from_geth[3].to.as_deref(), Some("0xe"));
+        assert_eq!(from_geth[3].error, from_parity[3].error);
+        assert_eq!(from_geth[1].gas_used, from_parity[1].gas_used);
+        assert!(from_geth
+            .iter()
+            .all(|t| t.transaction_hash.as_deref() == Some("0xt1")));
+    }
+
+    #[test]
+    fn test_block_traces_and_rewards() {
+        let block = TraceTarget::Block("0x10".to_string());
+        assert_eq!(block.request(TraceFlavor::Parity).method, "trace_block");
+
+        let geth = json!([
+            {"txHash": "0xt1", "result": {"type": "CALL", "from": "0xa", "to": "0xb"}},
+            {"txHash": "0xt2", "result": {"type": "SELFDESTRUCT", "from": "0xb", "to": "0xa"}}
+        ]);
+        let traces = block.normalize(TraceFlavor::Geth, geth).unwrap();
+        assert_eq!(traces[1].kind, Some(TraceKind::SelfDestruct));
+        assert_eq!(traces[1].transaction_hash.as_deref(), Some("0xt2"));
+
+        let parity = json!([
+            {"type": "reward", "action": {"author": "0xminer", "rewardType": "block",
+             "value": "0x1bc16d674ec80000"}, "result": null, "traceAddress": [],
+             "transactionHash": null}
+        ]);
+        let traces = block.normalize(TraceFlavor::Parity, parity).unwrap();
+        assert_eq!(traces[0].kind, Some(TraceKind::Reward));
+        assert_eq!(traces[0].to.as_deref(), Some("0xminer"));
+        assert_eq!(traces[0].transaction_hash, None);
+
+        assert!(block
+            .normalize(TraceFlavor::Geth, json!([{"error": "execution timeout"}]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_support_learned_per_endpoint() {
+        let support = TraceSupport::default();
+        assert_eq!(
+            support.candidates(),
+            vec![TraceFlavor::Geth, TraceFlavor::Parity]
+        );
+        support.record(TraceFlavor::Geth, false);
+        support.record(TraceFlavor::Parity, true);
+        assert_eq!(support.candidates(), vec![TraceFlavor::Parity]);
+        assert_eq!(support.flavor(), Some(TraceFlavor::Parity));
+
+        support.record(TraceFlavor::Parity, false);
+        assert!(support.candidates().is_empty());
+
+        assert!(is_unsupported_method(
+            "RPC error -32601: the method debug_traceTransaction does not exist/is not available"
+        ));
+        assert!(!is_unsupported_method(
+            "RPC error -32000: transaction not found"
+        ));
+    }
+}
@@ -0,0 +1,430 @@
+//! JSON-RPC response validation
+//!
+//! Some upstreams answer HTTP 200 with something other than a JSON-RPC
+//! response: a rate-limit or maintenance HTML page, a gateway's bare
+//! `{"message": "..."}`, or a result of the wrong shape. Every answer is
+//! checked before it is used:
+//! - the body is a JSON-RPC 2.0 envelope carrying the request's id and exactly
+//!   one of `result` and `error`
+//! - the result has the type its method returns (a quantity for
+//!   `eth_blockNumber`, an array for `eth_getLogs`, ...)
+//! - bodies that are not an envelope are matched against known provider error
+//!   strings, so the failure names the cause
+//!
+//! An invalid answer fails the attempt like a transport error: it counts
+//! against the endpoint's circuit breaker and the call fails over. Answers that
+//! read as rate limiting, envelopes with such an error included, also empty the
+//! endpoint's rate-limit bucket.
+
+use crate::endpoint_pool::{RpcRequest, RpcResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Provider messages meaning the endpoint is rate limiting us (lowercase)
+const RATE_LIMIT_ERRORS: &[&str] = &[
+    "too many requests",
+    "rate limit",
+    "ratelimit",
+    "daily request count exceeded",
+    "exceeded its compute units",
+    "capacity exceeded",
+    "request limit reached",
+    "credits limit",
+];
+
+/// Provider messages meaning the endpoint is down or misrouted (lowercase)
+const OUTAGE_ERRORS: &[&str] = &[
+    "bad gateway",
+    "gateway timeout",
+    "service unavailable",
+    "service temporarily unavailable",
+    "internal server error",
+    "upstream connect error",
+    "no healthy upstream",
+    "under maintenance",
+    "cloudflare",
+];
+
+/// Characters of an invalid body quoted in its error
+const SNIPPET_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultType {
+    /// Hex number with at least one digit
+    Quantity,
+    /// Hex byte string, `0x` when empty
+    Data,
+    Array,
+    /// Object, or null when not found
+    Object,
+    Bool,
+    /// `false`, or an object while syncing
+    SyncStatus,
+    String,
+}
+
+/// Result type per method; methods not listed are not checked
+const RESULT_TYPES: &[(&str, ResultType)] = &[
+    ("eth_blockNumber", ResultType::Quantity),
+    ("eth_chainId", ResultType::Quantity),
+    ("eth_gasPrice", ResultType::Quantity),
+    ("eth_maxPriorityFeePerGas", ResultType::Quantity),
+    ("eth_getBalance", ResultType::Quantity),
+    ("eth_getTransactionCount", ResultType::Quantity),
+    ("eth_estimateGas", ResultType::Quantity),
+    ("eth_getBlockTransactionCountByNumber", ResultType::Quantity),
+    ("eth_getCode", ResultType::Data),
+    ("eth_call", ResultType::Data),
+    ("eth_getStorageAt", ResultType::Data),
+    ("eth_sendRawTransaction", ResultType::Data),
+    ("eth_getLogs", ResultType::Array),
+    ("eth_getBlockReceipts", ResultType::Array),
+    ("eth_getBlockByNumber", ResultType::Object),
+    ("eth_getBlockByHash", ResultType::Object),
+    ("eth_getTransactionByHash", ResultType::Object),
+    ("eth_getTransactionReceipt", ResultType::Object),
+    ("eth_feeHistory", ResultType::Object),
+    ("eth_syncing", ResultType::SyncStatus),
+    ("net_listening", ResultType::Bool),
+    ("net_version", ResultType::String),
+    ("web3_clientVersion", ResultType::String),
+];
+
+impl ResultType {
+    fn of(method: &str) -> Option<Self> {
+        RESULT_TYPES
+            .iter()
+            .find(|(name, _)| *name == method)
+            .map(|(_, kind)| *kind)
+    }
+
+    fn matches(self, result: &Value) -> bool {
+        let hex = || result.as_str().and_then(|s| s.strip_prefix("0x"));
+        let is_hex = |digits: &str| digits.bytes().all(|b| b.is_ascii_hexdigit());
+        match self {
+            Self::Quantity => hex().is_some_and(|d| !d.is_empty() && is_hex(d)),
+            Self::Data => hex().is_some_and(|d| d.len() % 2 == 0 && is_hex(d)),
+            Self::Array => result.is_array(),
+            Self::Object => result.is_object() || result.is_null(),
+            Self::Bool => result.is_boolean(),
+            Self::SyncStatus => result == &Value::Bool(false) || result.is_object(),
+            Self::String => result.is_string(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Quantity => "a hex quantity",
+            Self::Data => "hex data",
+            Self::Array => "an array",
+            Self::Object => "an object",
+            Self::Bool => "a boolean",
+            Self::SyncStatus => "false or a sync status",
+            Self::String => "a string",
+        }
+    }
+}
+
+/// Response validation settings (`HTTP_RPC_VALIDATION_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Check envelopes and result types; only parse responses when off
+    pub enabled: bool,
+    /// Extra provider error strings marking a body as an outage (any case)
+    pub error_patterns: Vec<String>,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            error_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Why an answer was not a usable JSON-RPC response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidResponse {
+    pub reason: String,
+    /// The answer reads as the endpoint rate limiting us
+    pub rate_limited: bool,
+}
+
+impl InvalidResponse {
+    fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+            rate_limited: false,
+        }
+    }
+}
+
+impl fmt::Display for InvalidResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+/// Whether an upstream error message means the endpoint is rate limiting us
+pub fn is_rate_limit_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    RATE_LIMIT_ERRORS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+impl ValidationConfig {
+    /// Response to the `method` call sent with `id`, from a raw HTTP body
+    pub fn parse_response(
+        &self,
+        method: &str,
+        id: u64,
+        body: &[u8],
+    ) -> Result<RpcResponse, InvalidResponse> {
+        let mut value: Value = serde_json::from_slice(body).map_err(|e| {
+            if self.enabled {
+                self.invalid_body(&String::from_utf8_lossy(body))
+            } else {
+                InvalidResponse::new(format!("Failed to parse RPC response: {}", e))
+            }
+        })?;
+        self.check_response(method, id, &value)?;
+        if let Some(answered) = value.get_mut("id").filter(|answered| answered.is_null()) {
+            *answered = Value::from(id);
+        }
+        serde_json::from_value(value)
+            .map_err(|e| InvalidResponse::new(format!("Failed to parse RPC response: {}", e)))
+    }
+
+    /// Body that is not JSON, or JSON that is not a response envelope
+    pub fn invalid_body(&self, body: &str) -> InvalidResponse {
+        let lower = body.to_lowercase();
+        let snippet = snippet(body);
+        if RATE_LIMIT_ERRORS.iter().any(|p| lower.contains(p)) {
+            return InvalidResponse {
+                reason: format!("rate limited: {}", snippet),
+                rate_limited: true,
+            };
+        }
+        let outage = OUTAGE_ERRORS
+            .iter()
+            .map(|p| p.to_string())
+            .chain(self.error_patterns.iter().map(|p| p.to_lowercase()))
+            .any(|p| lower.contains(&p));
+        InvalidResponse::new(if outage {
+            format!("provider error: {}", snippet)
+        } else {
+            format!("not a JSON-RPC response: {}", snippet)
+        })
+    }
+
+    /// Check one response envelope against the `method` call sent with `id`
+    pub fn check_response(
+        &self,
+        method: &str,
+        id: u64,
+        value: &Value,
+    ) -> Result<(), InvalidResponse> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let Some(fields) = value.as_object() else {
+            return Err(self.invalid_body(&value.to_string()));
+        };
+        let (result, error) = (fields.get("result"), fields.get("error"));
+        if result.is_none() && error.is_none() {
+            return Err(self.invalid_body(&value.to_string()));
+        }
+        if fields.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return Err(InvalidResponse::new("missing jsonrpc 2.0 version"));
+        }
+        if result.is_some() && error.is_some() {
+            return Err(InvalidResponse::new("both result and error set"));
+        }
+
+        // Errors for unparseable requests come back with a null id
+        match fields.get("id") {
+            Some(Value::Null) | None if error.is_some() => {}
+            Some(answered) if answered.as_u64() == Some(id) => {}
+            answered => {
+                return Err(InvalidResponse::new(format!(
+                    "answered id {} to request {}",
+                    answered.unwrap_or(&Value::Null),
+                    id
+                )))
+            }
+        }
+
+        if let Some(error) = error {
+            if !error.is_object() {
+                return Err(InvalidResponse::new(format!(
+                    "malformed error {}",
+                    snippet(&error.to_string())
+                )));
+            }
+            return Ok(());
+        }
+        match (result, ResultType::of(method)) {
+            (Some(result), Some(kind)) if !kind.matches(result) => {
+                Err(InvalidResponse::new(format!(
+                    "{} result is not {}: {}",
+                    method,
+                    kind.name(),
+                    snippet(&result.to_string())
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the items of a batch answer that belong to `payload`; items with
+    /// other ids are left to the batch split
+    pub fn check_batch(&self, payload: &[RpcRequest], body: &Value) -> Result<(), InvalidResponse> {
+        let Value::Array(items) = body else {
+            return Ok(());
+        };
+        for item in items {
+            let id = item.get("id").and_then(Value::as_u64);
+            if let Some(request) = payload.iter().find(|request| Some(request.id) == id) {
+                self.check_response(&request.method, request.id, item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// First characters of `body` on one line
+fn snippet(body: &str) -> String {
+    let line: String = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check(method: &str, value: Value) -> Result<(), InvalidResponse> {
+        ValidationConfig::default().check_response(method, 7, &value)
+    }
+
+    #[test]
+    fn test_envelope_and_result_types() {
+        assert!(check(
+            "eth_blockNumber",
+            json!({"jsonrpc": "2.0", "id": 7, "result": "0x10"})
+        )
+        .is_ok());
+        assert!(check(
+            "eth_getTransactionReceipt",
+            json!({"jsonrpc": "2.0", "id": 7, "result": null})
+        )
+        .is_ok());
+        assert!(check(
+            "eth_getCode",
+            json!({"jsonrpc": "2.0", "id": 7, "result": "0x"})
+        )
+        .is_ok());
+        assert!(check(
+            "eth_call",
+            json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": "parse error"}})
+        )
+        .is_ok());
+
+        let reason = |method: &str, value: Value| check(method, value).unwrap_err().reason;
+        assert!(reason(
+            "eth_blockNumber",
+            json!({"jsonrpc": "2.0", "id": 7, "result": "16"})
+        )
+        .contains("not a hex quantity"));
+        assert!(reason(
+            "eth_getLogs",
+            json!({"jsonrpc": "2.0", "id": 7, "result": {}})
+        )
+        .contains("not an array"));
+        assert!(reason(
+            "eth_call",
+            json!({"jsonrpc": "2.0", "id": 8, "result": "0x"})
+        )
+        .contains("answered id 8"));
+        assert!(reason("eth_call", json!({"id": 7, "result": "0x"})).contains("jsonrpc"));
+        assert!(reason(
+            "eth_call",
+            json!({"jsonrpc": "2.0", "id": 7, "result": "0x", "error": {}})
+        )
+        .contains("both"));
+    }
+
+    #[test]
+    fn test_garbage_bodies_name_their_cause() {
+        let config = ValidationConfig {
+            error_patterns: vec!["Account Suspended".to_string()],
+            ..Default::default()
+        };
+        let html = b"<html><body><h1>429 Too Many Requests</h1></body></html>";
+        let invalid = config.parse_response("eth_call", 1, html).unwrap_err();
+        assert!(invalid.rate_limited);
+        assert!(invalid.reason.starts_with("rate limited"));
+
+        let invalid = config
+            .parse_response(
+                "eth_call",
+                1,
+                br#"{"message": "You have exceeded your rate limit"}"#,
+            )
+            .unwrap_err();
+        assert!(invalid.rate_limited);
+
+        let invalid = config
+            .parse_response("eth_call", 1, b"<html>502 Bad Gateway</html>")
+            .unwrap_err();
+        assert!(!invalid.rate_limited);
+        assert!(invalid.reason.starts_with("provider error"));
+        let invalid = config
+            .parse_response("eth_call", 1, b"account suspended")
+            .unwrap_err();
+        assert!(invalid.reason.starts_with("provider error"));
+        let invalid = config.parse_response("eth_call", 1, b"").unwrap_err();
+        assert!(invalid.reason.starts_with("not a JSON-RPC response"));
+
+        assert!(is_rate_limit_error(
+            "daily request count exceeded, request rate limited"
+        ));
+        assert!(!is_rate_limit_error("execution reverted"));
+        assert_eq!(snippet(&"x".repeat(200)).len(), SNIPPET_CHARS + 3);
+    }
+
+    #[test]
+    fn test_batch_items_checked_by_their_method() {
+        let payload = vec![
+            RpcRequest {
+                id: 0,
+                ..RpcRequest::new("eth_blockNumber", vec![])
+            },
+            RpcRequest {
+                id: 1,
+                ..RpcRequest::new("eth_getLogs", vec![])
+            },
+        ];
+        let config = ValidationConfig::default();
+        let body = json!([
+            {"jsonrpc": "2.0", "id": 1, "result": []},
+            {"jsonrpc": "2.0", "id": 0, "result": "0x1"},
+        ]);
+        assert!(config.check_batch(&payload, &body).is_ok());
+
+        let body = json!([{"jsonrpc": "2.0", "id": 1, "result": "0x1"}]);
+        assert!(config.check_batch(&payload, &body).is_err());
+        let disabled = ValidationConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(disabled.check_batch(&payload, &body).is_ok());
+    }
+}
@@ -0,0 +1,586 @@
+//! WebSocket subscriptions per endpoint pool
+//!
+//! HTTP endpoints only answer requests, so an actor that wants new heads,
+//! logs or pending transactions has to poll. A [`WsPool`] keeps one WebSocket
+//! connection per network open against the network's `wss://` endpoints and
+//! multiplexes `eth_subscribe` streams over it:
+//! - every [`subscribe`](WsPool::subscribe) gets a local id and a channel that
+//!   stays valid across reconnects
+//! - when the connection drops, the pool fails over to the next endpoint with
+//!   exponential backoff and re-issues every active subscription there,
+//!   mapping the node's new subscription ids back to the local ones
+//! - a dropped receiver is unsubscribed on its next notification
+//!
+//! Notifications pushed while disconnected are lost; consumers that need
+//! every block backfill from their own checkpoint after a reconnect.
+
+use anyhow::{anyhow, Result};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+/// JSON-RPC id used for `eth_unsubscribe`; subscription ids start at 1
+const UNSUBSCRIBE_REQUEST_ID: u64 = 0;
+
+/// WebSocket configuration (`HTTP_RPC_WS_CONFIG`, JSON)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WsConfig {
+    /// First reconnect delay; doubles with every failed attempt
+    pub reconnect_initial_ms: u64,
+    pub reconnect_max_ms: u64,
+    /// Notifications buffered per subscription before new ones are dropped
+    pub channel_capacity: usize,
+    /// Seconds between keepalive pings
+    pub ping_interval_secs: u64,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_initial_ms: 500,
+            reconnect_max_ms: 30_000,
+            channel_capacity: 1024,
+            ping_interval_secs: 30,
+        }
+    }
+}
+
+impl WsConfig {
+    /// Delay before reconnect attempt `attempt` (0-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .reconnect_initial_ms
+            .saturating_mul(1u64 << attempt.min(20));
+        Duration::from_millis(delay.min(self.reconnect_max_ms))
+    }
+}
+
+/// Stream requested with `eth_subscribe`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SubscriptionKind {
+    NewHeads,
+    /// `filter` is the node's log filter object (`address`, `topics`)
+    Logs {
+        #[serde(default)]
+        filter: Value,
+    },
+    /// Transaction hashes, or full transactions when `full` is set
+    PendingTransactions {
+        #[serde(default)]
+        full: bool,
+    },
+}
+
+impl SubscriptionKind {
+    /// `eth_subscribe` params
+    pub fn params(&self) -> Vec<Value> {
+        match self {
+            Self::NewHeads => vec![json!("newHeads")],
+            Self::Logs { filter } if filter.is_null() => vec![json!("logs"), json!({})],
+            Self::Logs { filter } => vec![json!("logs"), filter.clone()],
+            Self::PendingTransactions { full: false } => vec![json!("newPendingTransactions")],
+            Self::PendingTransactions { full: true } => {
+                vec![json!("newPendingTransactions"), json!(true)]
+            }
+        }
+    }
+}
+
+/// One pushed item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsNotification {
+    pub network: String,
+    /// Local subscription id returned by `subscribe`
+    pub subscription: u64,
+    /// Endpoint that pushed the item
+    pub endpoint: String,
+    pub result: Value,
+}
+
+/// Connection state of a network's WebSocket pool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WsPoolStatus {
+    pub network: String,
+    pub connected_endpoint: Option<String>,
+    pub active_subscriptions: usize,
+    pub reconnects: u64,
+    /// Notifications dropped because a subscriber's channel was full
+    pub dropped_notifications: u64,
+    pub last_error: Option<String>,
+}
+
+struct Subscription {
+    kind: SubscriptionKind,
+    sender: mpsc::Sender<WsNotification>,
+}
+
+enum Command {
+    Subscribe(u64),
+    Unsubscribe(u64),
+}
+
+struct Shared {
+    network: String,
+    subscriptions: Mutex<HashMap<u64, Subscription>>,
+    status: Mutex<WsPoolStatus>,
+}
+
+/// WebSocket subscriptions for one network, with failover across endpoints
+pub struct WsPool {
+    shared: Arc<Shared>,
+    config: WsConfig,
+    next_id: AtomicU64,
+    commands: mpsc::UnboundedSender<Command>,
+    task: JoinHandle<()>,
+}
+
+impl WsPool {
+    /// Create the pool and start connecting; must be called inside a Tokio runtime
+    pub fn new(network: String, endpoints: Vec<String>, config: WsConfig) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!(
+                "At least one WebSocket endpoint must be configured"
+            ));
+        }
+
+        let shared = Arc::new(Shared {
+            status: Mutex::new(WsPoolStatus {
+                network: network.clone(),
+                ..Default::default()
+            }),
+            network,
+            subscriptions: Mutex::new(HashMap::new()),
+        });
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(shared.clone(), endpoints, config.clone(), receiver));
+
+        Ok(Self {
+            shared,
+            config,
+            next_id: AtomicU64::new(1),
+            commands,
+            task,
+        })
+    }
+
+    /// Start a subscription; the receiver survives reconnects and ends if the
+    /// node rejects the subscription
+    pub fn subscribe(&self, kind: SubscriptionKind) -> (u64, mpsc::Receiver<WsNotification>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel(self.config.channel_capacity.max(1));
+        self.shared
+            .subscriptions
+            .lock()
+            .insert(id, Subscription { kind, sender });
+        let _ = self.commands.send(Command::Subscribe(id));
+        (id, receiver)
+    }
+
+    /// Stop a subscription; false if it was not active
+    pub fn unsubscribe(&self, id: u64) -> bool {
+        let removed = self.shared.subscriptions.lock().remove(&id).is_some();
+        if removed {
+            let _ = self.commands.send(Command::Unsubscribe(id));
+        }
+        removed
+    }
+
+    pub fn status(&self) -> WsPoolStatus {
+        let mut status = self.shared.status.lock().clone();
+        status.active_subscriptions = self.shared.subscriptions.lock().len();
+        status
+    }
+}
+
+impl Drop for WsPool {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Connect, serve and fail over until the pool is dropped
+async fn run(
+    shared: Arc<Shared>,
+    endpoints: Vec<String>,
+    config: WsConfig,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut attempt: u32 = 0;
+    let mut index = 0usize;
+
+    loop {
+        let endpoint = &endpoints[index % endpoints.len()];
+        let outcome = match connect_async(endpoint.as_str()).await {
+            Ok((stream, _)) => {
+                info!("{} WebSocket connected to {}", shared.network, endpoint);
+                attempt = 0;
+                shared.status.lock().connected_endpoint = Some(endpoint.clone());
+                let outcome = serve(&shared, endpoint, &config, stream, &mut commands).await;
+                shared.status.lock().connected_endpoint = None;
+                outcome
+            }
+            Err(e) => Err(anyhow!("connect failed: {}", e)),
+        };
+
+        match outcome {
+            Ok(()) => return,
+            Err(e) => {
+                warn!("{} WebSocket {} failed: {}", shared.network, endpoint, e);
+                let mut status = shared.status.lock();
+                status.reconnects += 1;
+                status.last_error = Some(format!("{}: {}", endpoint, e));
+            }
+        }
+
+        // Fail over to the next endpoint
+        index += 1;
+        tokio::time::sleep(config.backoff(attempt)).await;
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+/// Serve one connection: re-subscribe everything, then route until it drops.
+/// Returns `Ok` only when the pool is gone.
+async fn serve(
+    shared: &Shared,
+    endpoint: &str,
+    config: &WsConfig,
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+) -> Result<()> {
+    let (mut sink, mut stream) = stream.split();
+    let mut router = Router::default();
+
+    let active: Vec<(u64, SubscriptionKind)> = shared
+        .subscriptions
+        .lock()
+        .iter()
+        .map(|(id, subscription)| (*id, subscription.kind.clone()))
+        .collect();
+    for (id, kind) in active {
+        if let Some(request) = router.subscribe(id, &kind) {
+            sink.send(Message::Text(request)).await?;
+        }
+    }
+
+    let mut ping = tokio::time::interval(Duration::from_secs(config.ping_interval_secs.max(1)));
+    ping.tick().await;
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                None => {
+                    let _ = sink.send(Message::Close(None)).await;
+                    return Ok(());
+                }
+                Some(Command::Subscribe(id)) => {
+                    let kind = shared
+                        .subscriptions
+                        .lock()
+                        .get(&id)
+                        .map(|subscription| subscription.kind.clone());
+                    if let Some(request) = kind.and_then(|kind| router.subscribe(id, &kind)) {
+                        sink.send(Message::Text(request)).await?;
+                    }
+                }
+                Some(Command::Unsubscribe(id)) => {
+                    if let Some(request) = router.unsubscribe(id) {
+                        sink.send(Message::Text(request)).await?;
+                    }
+                }
+            },
+            message = stream.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    if let Some(request) = deliver(shared, endpoint, &mut router, &text) {
+                        sink.send(Message::Text(request)).await?;
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    return Err(anyhow!("closed by server: {:?}", frame));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(anyhow!("read failed: {}", e)),
+                None => return Err(anyhow!("connection closed")),
+            },
+            _ = ping.tick() => {
+                sink.send(Message::Ping(Vec::new())).await?;
+            }
+        }
+    }
+}
+
+/// Hand a frame to its subscriber; returns an `eth_unsubscribe` to send when
+/// the subscriber is gone
+fn deliver(shared: &Shared, endpoint: &str, router: &mut Router, text: &str) -> Option<String> {
+    match router.route(text) {
+        Frame::Notification { local, result } => {
+            let mut subscriptions = shared.subscriptions.lock();
+            let Some(subscription) = subscriptions.get(&local) else {
+                drop(subscriptions);
+                return router.unsubscribe(local);
+            };
+            let notification = WsNotification {
+                network: shared.network.clone(),
+                subscription: local,
+                endpoint: endpoint.to_string(),
+                result,
+            };
+            match subscription.sender.try_send(notification) {
+                Ok(()) => None,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    drop(subscriptions);
+                    shared.status.lock().dropped_notifications += 1;
+                    None
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    subscriptions.remove(&local);
+                    drop(subscriptions);
+                    router.unsubscribe(local)
+                }
+            }
+        }
+        Frame::Subscribed { local } => {
+            debug!(
+                "{} subscription {} active on {}",
+                shared.network, local, endpoint
+            );
+            None
+        }
+        Frame::Rejected { local, message } => {
+            warn!(
+                "{} subscription {} rejected by {}: {}",
+                shared.network, local, endpoint, message
+            );
+            shared.subscriptions.lock().remove(&local);
+            None
+        }
+        Frame::Ignored => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Frame {
+    Subscribed { local: u64 },
+    Rejected { local: u64, message: String },
+    Notification { local: u64, result: Value },
+    Ignored,
+}
+
+/// Maps the node's subscription ids to local ids for one connection
+#[derive(Debug, Default)]
+struct Router {
+    pending: HashSet<u64>,
+    remote_to_local: HashMap<String, u64>,
+    local_to_remote: HashMap<u64, String>,
+}
+
+impl Router {
+    /// `eth_subscribe` request for `local`, unless already requested on this connection
+    fn subscribe(&mut self, local: u64, kind: &SubscriptionKind) -> Option<String> {
+        if self.pending.contains(&local) || self.local_to_remote.contains_key(&local) {
+            return None;
+        }
+        self.pending.insert(local);
+        Some(
+            json!({
+                "jsonrpc": "2.0",
+                "id": local,
+                "method": "eth_subscribe",
+                "params": kind.params(),
+            })
+            .to_string(),
+        )
+    }
+
+    /// `eth_unsubscribe` request for `local`, if the node confirmed it
+    fn unsubscribe(&mut self, local: u64) -> Option<String> {
+        self.pending.remove(&local);
+        let remote = self.local_to_remote.remove(&local)?;
+        self.remote_to_local.remove(&remote);
+        Some(
+            json!({
+                "jsonrpc": "2.0",
+                "id": UNSUBSCRIBE_REQUEST_ID,
+                "method": "eth_unsubscribe",
+                "params": [remote],
+            })
+            .to_string(),
+        )
+    }
+
+    fn route(&mut self, text: &str) -> Frame {
+        let Ok(frame) = serde_json::from_str::<Value>(text) else {
+            return Frame::Ignored;
+        };
+
+        if frame.get("method").and_then(Value::as_str) == Some("eth_subscription") {
+            let params = &frame["params"];
+            return match params["subscription"]
+                .as_str()
+                .and_then(|remote| self.remote_to_local.get(remote))
+            {
+                Some(local) => Frame::Notification {
+                    local: *local,
+                    result: params["result"].clone(),
+                },
+                None => Frame::Ignored,
+            };
+        }
+
+        let Some(local) = frame.get("id").and_then(Value::as_u64) else {
+            return Frame::Ignored;
+        };
+        if !self.pending.remove(&local) {
+            return Frame::Ignored;
+        }
+        match frame.get("result").and_then(Value::as_str) {
+            Some(remote) => {
+                self.remote_to_local.insert(remote.to_string(), local);
+                self.local_to_remote.insert(local, remote.to_string());
+                Frame::Subscribed { local }
+            }
+            None => Frame::Rejected {
+                local,
+                message: frame
+                    .get("error")
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "no subscription id".to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_params() {
+        assert_eq!(SubscriptionKind::NewHeads.params(), vec![json!("newHeads")]);
+        assert_eq!(
+            SubscriptionKind::Logs {
+                filter: Value::Null
+            }
+            .params(),
+            vec![json!("logs"), json!({})]
+        );
+        let filter = json!({"address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"});
+        assert_eq!(
+            SubscriptionKind::Logs {
+                filter: filter.clone()
+            }
+            .params(),
+            vec![json!("logs"), filter]
+        );
+        assert_eq!(
+            SubscriptionKind::PendingTransactions { full: true }.params(),
+            vec![json!("newPendingTransactions"), json!(true)]
+        );
+
+        let kind: SubscriptionKind =
+            serde_json::from_str(r#"{"kind":"pending_transactions"}"#).unwrap();
+        assert_eq!(kind, SubscriptionKind::PendingTransactions { full: false });
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let config = WsConfig::default();
+        assert_eq!(config.backoff(0), Duration::from_millis(500));
+        assert_eq!(config.backoff(3), Duration::from_millis(4000));
+        assert_eq!(config.backoff(10), Duration::from_millis(30_000));
+        assert_eq!(config.backoff(u32::MAX), Duration::from_millis(30_000));
+    }
+
+    #[test]
+    fn test_router_maps_notifications_to_local_ids() {
+        let mut router = Router::default();
+        let request = router.subscribe(7, &SubscriptionKind::NewHeads).unwrap();
+        let request: Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["id"], 7);
+        assert_eq!(request["method"], "eth_subscribe");
+
+        // A second request for the same id on this connection is suppressed
+        assert!(router.subscribe(7, &SubscriptionKind::NewHeads).is_none());
+
+        assert_eq!(
+            router.route(r#"{"jsonrpc":"2.0","id":7,"result":"0xabc"}"#),
+            Frame::Subscribed { local: 7 }
+        );
+        assert_eq!(
+            router.route(
+                r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"subscription":"0xabc","result":{"number":"0x10"}}}"#
+            ),
+            Frame::Notification {
+                local: 7,
+                result: json!({"number": "0x10"})
+            }
+        );
+        assert_eq!(
+            router.route(
+                r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"subscription":"0xdead","result":{}}}"#
+            ),
+            Frame::Ignored
+        );
+
+        let unsubscribe: Value = serde_json::from_str(&router.unsubscribe(7).unwrap()).unwrap();
+        assert_eq!(unsubscribe["method"], "eth_unsubscribe");
+        assert_eq!(unsubscribe["params"], json!(["0xabc"]));
+        assert!(router.unsubscribe(7).is_none());
+    }
+
+    #[test]
+    fn test_router_reports_rejected_subscriptions() {
+        let mut router = Router::default();
+        router.subscribe(3, &SubscriptionKind::PendingTransactions { full: true });
+
+        let frame = router.route(
+            r#"{"jsonrpc":"2.0","id":3,"error":{"code":-32601,"message":"notifications not supported"}}"#,
+        );
+        assert!(
+            matches!(frame, Frame::Rejected { local: 3, ref message } if message.contains("not supported"))
+        );
+
+        // Unsubscribe acks and unknown ids are ignored
+        assert_eq!(
+            router.route(r#"{"jsonrpc":"2.0","id":0,"result":true}"#),
+            Frame::Ignored
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pool_tracks_subscriptions() {
+        assert!(WsPool::new("ethereum".to_string(), vec![], WsConfig::default()).is_err());
+
+        let pool = WsPool::new(
+            "ethereum".to_string(),
+            vec!["ws://127.0.0.1:1".to_string()],
+            WsConfig::default(),
+        )
+        .unwrap();
+        let (heads, _heads_rx) = pool.subscribe(SubscriptionKind::NewHeads);
+        let (logs, _logs_rx) = pool.subscribe(SubscriptionKind::Logs {
+            filter: Value::Null,
+        });
+        assert_ne!(heads, logs);
+        assert_eq!(pool.status().active_subscriptions, 2);
+
+        assert!(pool.unsubscribe(heads));
+        assert!(!pool.unsubscribe(heads));
+        let status = pool.status();
+        assert_eq!(status.network, "ethereum");
+        assert_eq!(status.active_subscriptions, 1);
+        assert!(status.connected_endpoint.is_none());
+    }
+}
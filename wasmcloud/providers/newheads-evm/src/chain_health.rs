@@ -0,0 +1,310 @@
+//! # Chain Halt / RPC Outage Detection
+//!
+//! Connection tasks report every head and WebSocket state change here. A
+//! watchdog then probes each chain's HTTP RPC with `eth_blockNumber` every
+//! `check_interval_secs` and advances its `ChainHeadWatchV1`:
+//! - a head from either source keeps the chain healthy
+//! - a round with the WebSocket down and the probe failing counts as every
+//!   endpoint failing (`rpc_outage`)
+//! - no new head within the chain's stall window raises `head_stalled`
+//!
+//! Signals are published on `alerts.system.chain.{network}.{subnet}.{rule}`.
+//! While a chain is unhealthy its lag alarm pause
+//! (`chain:health:pause:{network}:{subnet}`) is refreshed every round; it is
+//! deleted on recovery and otherwise expires after `pause_ttl_secs`.
+//! Thresholds are read from `chain:health:config` each round.
+
+use alert_runtime_common::{
+    AlertChainHealthV1, ChainHeadWatchV1, ChainHealthConfigV1, ChainHealthRuleV1,
+    ChainHealthSignalV1, LagAlarmPauseV1,
+};
+use anyhow::{anyhow, Result};
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::traits::ChainConfig;
+use crate::PROVIDER_NAME;
+
+struct WatchedChain {
+    rpc_url: String,
+    ws_connected: bool,
+    watch: ChainHeadWatchV1,
+}
+
+/// Per-chain head tracker shared by all connection tasks
+pub struct ChainHealthMonitor {
+    nats_client: async_nats::Client,
+    redis_client: Option<redis::Client>,
+    http_client: reqwest::Client,
+    chains: Mutex<HashMap<String, WatchedChain>>,
+}
+
+impl ChainHealthMonitor {
+    pub fn new(nats_client: async_nats::Client, redis_url: &str) -> Arc<Self> {
+        let redis_client = match redis::Client::open(redis_url) {
+            Ok(client) => Some(client),
+            Err(e) => {
+                warn!(
+                    "[HEALTH] Invalid Redis URL, lag alarm pauses disabled: {}",
+                    e
+                );
+                None
+            }
+        };
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Arc::new(Self {
+            nats_client,
+            redis_client,
+            http_client,
+            chains: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Start watching `config` (if new) and record its WebSocket state
+    pub async fn record_connection(&self, config: &ChainConfig, connected: bool) {
+        let mut chains = self.chains.lock().await;
+        let chain = chains
+            .entry(config.chain_id.clone())
+            .or_insert_with(|| WatchedChain {
+                rpc_url: config.rpc_url.clone(),
+                ws_connected: false,
+                watch: ChainHeadWatchV1::new(
+                    &config.network,
+                    &config.subnet,
+                    &config.chain_id,
+                    chrono::Utc::now(),
+                ),
+            });
+        chain.rpc_url = config.rpc_url.clone();
+        chain.ws_connected = connected;
+    }
+
+    /// Record a head received over the WebSocket
+    pub async fn record_head(&self, config: &ChainConfig, block_number: u64) {
+        let (watch, signals) = {
+            let mut chains = self.chains.lock().await;
+            let Some(chain) = chains.get_mut(&config.chain_id) else {
+                return;
+            };
+            let signals = chain.watch.record_head(block_number, chrono::Utc::now());
+            (chain.watch.clone(), signals)
+        };
+        if !signals.is_empty() {
+            let config = self.load_config().await;
+            self.apply(&config, &watch, &signals).await;
+        }
+    }
+
+    /// Stop watching a disabled or removed chain
+    pub async fn forget(&self, chain_id: &str) {
+        self.chains.lock().await.remove(chain_id);
+    }
+
+    /// Spawn the watchdog loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            info!("[HEALTH] Chain health watchdog started");
+            loop {
+                let config = self.load_config().await;
+                if config.enabled {
+                    self.run_round(&config).await;
+                }
+                tokio::time::sleep(Duration::from_secs(config.check_interval_secs.max(1))).await;
+            }
+        })
+    }
+
+    async fn run_round(&self, config: &ChainHealthConfigV1) {
+        let targets: Vec<(String, String)> = {
+            let chains = self.chains.lock().await;
+            chains
+                .iter()
+                .map(|(chain_id, chain)| (chain_id.clone(), chain.rpc_url.clone()))
+                .collect()
+        };
+
+        for (chain_id, rpc_url) in targets {
+            let probe = self.probe_head(&rpc_url).await;
+            if let Err(e) = &probe {
+                debug!("[HEALTH] eth_blockNumber failed for {}: {}", chain_id, e);
+            }
+
+            let now = chrono::Utc::now();
+            let (watch, signals) = {
+                let mut chains = self.chains.lock().await;
+                let Some(chain) = chains.get_mut(&chain_id) else {
+                    continue;
+                };
+                let mut signals = Vec::new();
+                if let Ok(head) = &probe {
+                    signals.extend(chain.watch.record_head(*head, now));
+                }
+                let all_failed = !chain.ws_connected && probe.is_err();
+                signals.extend(chain.watch.record_round(config, all_failed));
+                signals.extend(chain.watch.check_stall(config, now));
+                (chain.watch.clone(), signals)
+            };
+
+            if !signals.is_empty() || watch.unhealthy() {
+                self.apply(config, &watch, &signals).await;
+            }
+        }
+    }
+
+    /// Publish the signals and refresh or clear the chain's lag alarm pause
+    async fn apply(
+        &self,
+        config: &ChainHealthConfigV1,
+        watch: &ChainHeadWatchV1,
+        signals: &[ChainHealthSignalV1],
+    ) {
+        let now = chrono::Utc::now();
+        for signal in signals {
+            let alert = AlertChainHealthV1::new(config, watch, signal, now, PROVIDER_NAME);
+            let subject = subject_registry::prefixed(&alert.subject());
+            let payload = match serde_json::to_vec(&alert) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    error!("[HEALTH] Failed to serialize chain health alert: {}", e);
+                    continue;
+                }
+            };
+            match self
+                .nats_client
+                .publish(subject.clone(), payload.into())
+                .await
+            {
+                Ok(()) => warn!("[HEALTH] {} (last head: {:?})", subject, watch.last_head),
+                Err(e) => error!("[HEALTH] Failed to publish {}: {}", subject, e),
+            }
+        }
+
+        let recovered = signals
+            .iter()
+            .any(|signal| signal.rule == ChainHealthRuleV1::Recovered);
+        let result = match LagAlarmPauseV1::for_watch(config, watch, now) {
+            Some(pause) => self.set_pause(watch, &pause, config.pause_ttl_secs).await,
+            None if recovered => self.clear_pause(watch).await,
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!(
+                "[HEALTH] Failed to update lag alarm pause for {}: {}",
+                watch.chain_id, e
+            );
+        }
+    }
+
+    async fn probe_head(&self, rpc_url: &str) -> Result<u64> {
+        if rpc_url.is_empty() {
+            return Err(anyhow!("no HTTP RPC endpoint configured"));
+        }
+        let response: serde_json::Value = self
+            .http_client
+            .post(rpc_url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_blockNumber",
+                "params": [],
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse_block_number(&response)
+    }
+
+    async fn load_config(&self) -> ChainHealthConfigV1 {
+        let Some(client) = &self.redis_client else {
+            return ChainHealthConfigV1::default();
+        };
+        let raw: Option<String> = match client.get_multiplexed_async_connection().await {
+            Ok(mut conn) => conn
+                .get(retention_policy::CHAIN_HEALTH_CONFIG.key(""))
+                .await
+                .unwrap_or_default(),
+            Err(e) => {
+                debug!(
+                    "[HEALTH] Redis unavailable, using default thresholds: {}",
+                    e
+                );
+                None
+            }
+        };
+        raw.and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    async fn set_pause(
+        &self,
+        watch: &ChainHeadWatchV1,
+        pause: &LagAlarmPauseV1,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        let client = self
+            .redis_client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Redis not configured"))?;
+        let mut conn = client.get_multiplexed_async_connection().await?;
+        conn.set_ex::<_, _, ()>(
+            pause_key(watch),
+            serde_json::to_string(pause)?,
+            ttl_secs.max(1),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn clear_pause(&self, watch: &ChainHeadWatchV1) -> Result<()> {
+        let client = self
+            .redis_client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Redis not configured"))?;
+        let mut conn = client.get_multiplexed_async_connection().await?;
+        conn.del::<_, ()>(pause_key(watch)).await?;
+        info!("[HEALTH] Lag alarms resumed for {}", watch.chain_id);
+        Ok(())
+    }
+}
+
+fn pause_key(watch: &ChainHeadWatchV1) -> String {
+    retention_policy::LAG_ALARM_PAUSE.key(&format!("{}:{}", watch.network, watch.subnet))
+}
+
+/// Head number from an `eth_blockNumber` JSON-RPC response
+fn parse_block_number(response: &serde_json::Value) -> Result<u64> {
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("RPC error: {}", error));
+    }
+    let result = response
+        .get("result")
+        .and_then(|result| result.as_str())
+        .ok_or_else(|| anyhow!("No result in RPC response"))?;
+    u64::from_str_radix(result.trim_start_matches("0x"), 16)
+        .map_err(|e| anyhow!("Invalid block number {}: {}", result, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_number() {
+        let ok = serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "0x12a05f2"});
+        assert_eq!(parse_block_number(&ok).unwrap(), 19_531_250);
+
+        let failed = serde_json::json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000}});
+        assert!(parse_block_number(&failed).is_err());
+        assert!(parse_block_number(&serde_json::json!({"result": "0xzz"})).is_err());
+    }
+}
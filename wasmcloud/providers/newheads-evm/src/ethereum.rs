@@ -321,7 +321,9 @@ impl BlockchainClient for EthereumClient {
                                         };
 
                                         // Get NATS subject for logging
-                                        let nats_subject = block_header.nats_subject();
+                                        let nats_subject = subject_registry::prefixed(
+                                            &block_header.nats_subject(),
+                                        );
 
                                         // Publish to NATS if client is available
                                         if let Some(ref nats) = nats_client {
@@ -24,9 +24,11 @@ use provider_status_common::{
     ProviderStatusTracker, ProviderType as StatusProviderType, StatusTracker, StatusTrackerConfig,
 };
 
+pub mod chain_health;
 pub mod config;
 pub mod django_integration;
 pub mod ethereum;
+pub mod slashing;
 pub mod traits; // Keep for backwards compatibility, not used
 
 use config::{load_provider_config, ProviderConfig};
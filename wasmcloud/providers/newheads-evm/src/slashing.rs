@@ -0,0 +1,450 @@
+//! # Beacon Chain Slashing Monitor
+//!
+//! Follows every beacon node listed in `slashing:config` (`SlashingConfigV1`).
+//! Each poll reads the node's head slot and fetches the blocks after the
+//! chain's cursor (`slashing:cursor:{network}:{subnet}`), at most
+//! `max_slots_per_round` of them; a node further behind skips ahead, and a
+//! chain seen for the first time starts at its head. For every validator
+//! slashed by a block's `proposer_slashings` or `attester_slashings`:
+//! - a row is written to `ducklake.slashing_events.{network}.{subnet}.write`
+//! - when the validator or its withdrawal address is in
+//!   `slashing:watch:{network}:{subnet}` (`SlashingWatchV1`), an
+//!   `AlertSlashingV1` is published on
+//!   `alerts.system.slashing.{network}.{subnet}.{kind}`
+
+use alert_runtime_common::{
+    AlertSlashingV1, SlashingConfigV1, SlashingEventV1, SlashingKindV1, SlashingMatchV1,
+    SlashingWatchV1,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::PROVIDER_NAME;
+
+/// Slashing monitor for all configured beacon nodes
+pub struct SlashingMonitor {
+    nats_client: async_nats::Client,
+    redis_client: Option<redis::Client>,
+    http_client: reqwest::Client,
+    /// Last processed slot by `{network}:{subnet}`
+    cursors: Mutex<HashMap<String, u64>>,
+}
+
+impl SlashingMonitor {
+    pub fn new(nats_client: async_nats::Client, redis_url: &str) -> Arc<Self> {
+        let redis_client = match redis::Client::open(redis_url) {
+            Ok(client) => Some(client),
+            Err(e) => {
+                warn!(
+                    "[SLASHING] Invalid Redis URL, no beacon nodes will be followed: {}",
+                    e
+                );
+                None
+            }
+        };
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Arc::new(Self {
+            nats_client,
+            redis_client,
+            http_client,
+            cursors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Spawn the polling loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            info!("[SLASHING] Beacon chain slashing monitor started");
+            loop {
+                let config = self.load_config().await;
+                if config.enabled {
+                    for (network, subnet, beacon_url) in config.chains() {
+                        if let Err(e) = self
+                            .poll_chain(&config, &network, &subnet, &beacon_url)
+                            .await
+                        {
+                            debug!("[SLASHING] Poll failed for {}:{}: {}", network, subnet, e);
+                        }
+                    }
+                }
+                tokio::time::sleep(Duration::from_secs(config.poll_interval_secs.max(1))).await;
+            }
+        })
+    }
+
+    async fn poll_chain(
+        &self,
+        config: &SlashingConfigV1,
+        network: &str,
+        subnet: &str,
+        beacon_url: &str,
+    ) -> Result<()> {
+        let chain = format!("{}:{}", network, subnet);
+        let head = self.head_slot(beacon_url).await?;
+        let Some(cursor) = self.cursor(&chain).await else {
+            info!("[SLASHING] Following {} from slot {}", chain, head);
+            return self.save_cursor(&chain, head).await;
+        };
+        if head <= cursor {
+            return Ok(());
+        }
+
+        let start = cursor_start(cursor, head, config.max_slots_per_round);
+        if start > cursor + 1 {
+            warn!(
+                "[SLASHING] {} is {} slots behind, skipping to slot {}",
+                chain,
+                head - cursor,
+                start
+            );
+        }
+
+        let watch = self.load_watch(&chain).await;
+        for slot in start..=head {
+            if let Some(block) = self.block(beacon_url, slot).await? {
+                let block_timestamp = block_timestamp(&block);
+                for (kind, validator_index) in slashed_validators(&block) {
+                    let (validator_pubkey, withdrawal_address) =
+                        match self.validator(beacon_url, validator_index).await {
+                            Ok(validator) => validator,
+                            Err(e) => {
+                                warn!(
+                                    "[SLASHING] Failed to look up validator {} on {}: {}",
+                                    validator_index, chain, e
+                                );
+                                (None, None)
+                            }
+                        };
+                    let event = SlashingEventV1 {
+                        network: network.to_string(),
+                        subnet: subnet.to_string(),
+                        slot,
+                        kind,
+                        validator_index,
+                        validator_pubkey,
+                        withdrawal_address,
+                        block_timestamp,
+                    };
+                    self.record(&event, watch.matched_by(&event)).await;
+                }
+            }
+            self.save_cursor(&chain, slot).await?;
+        }
+        Ok(())
+    }
+
+    /// Write the DuckLake row and, for watched validators, raise the alert
+    async fn record(&self, event: &SlashingEventV1, matched_by: Option<SlashingMatchV1>) {
+        let now = Utc::now();
+        let record = DuckLakeSlashingEventRecord::new(event, matched_by, now);
+        let subject = subject_registry::prefixed(&format!(
+            "ducklake.slashing_events.{}.{}.write",
+            event.network, event.subnet
+        ));
+        self.publish(&subject, &record).await;
+
+        let Some(matched_by) = matched_by else {
+            info!(
+                "[SLASHING] Validator {} slashed on {}:{} (not watched)",
+                event.validator_index, event.network, event.subnet
+            );
+            return;
+        };
+        let alert = AlertSlashingV1::new(event, matched_by, now, PROVIDER_NAME);
+        let subject = subject_registry::prefixed(&alert.subject());
+        self.publish(&subject, &alert).await;
+        warn!("[SLASHING] {}", alert.summary);
+    }
+
+    async fn publish<T: Serialize>(&self, subject: &str, message: &T) {
+        let payload = match serde_json::to_vec(message) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("[SLASHING] Failed to serialize {}: {}", subject, e);
+                return;
+            }
+        };
+        if let Err(e) = self
+            .nats_client
+            .publish(subject.to_string(), payload.into())
+            .await
+        {
+            error!("[SLASHING] Failed to publish {}: {}", subject, e);
+        }
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Option<serde_json::Value>> {
+        let response = self.http_client.get(url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    async fn head_slot(&self, beacon_url: &str) -> Result<u64> {
+        let response = self
+            .get_json(&format!("{}/eth/v1/beacon/headers/head", beacon_url))
+            .await?
+            .ok_or_else(|| anyhow!("beacon node has no head"))?;
+        parse_u64(&response["data"]["header"]["message"]["slot"])
+            .ok_or_else(|| anyhow!("No slot in head header response"))
+    }
+
+    /// Block at `slot`; `None` when the slot was missed
+    async fn block(&self, beacon_url: &str, slot: u64) -> Result<Option<serde_json::Value>> {
+        self.get_json(&format!("{}/eth/v2/beacon/blocks/{}", beacon_url, slot))
+            .await
+    }
+
+    /// Public key and withdrawal address of a validator
+    async fn validator(
+        &self,
+        beacon_url: &str,
+        validator_index: u64,
+    ) -> Result<(Option<String>, Option<String>)> {
+        let response = self
+            .get_json(&format!(
+                "{}/eth/v1/beacon/states/head/validators/{}",
+                beacon_url, validator_index
+            ))
+            .await?
+            .ok_or_else(|| anyhow!("unknown validator"))?;
+        let validator = &response["data"]["validator"];
+        Ok((
+            validator["pubkey"].as_str().map(str::to_lowercase),
+            validator["withdrawal_credentials"]
+                .as_str()
+                .and_then(withdrawal_address),
+        ))
+    }
+
+    async fn redis_get(&self, key: &str) -> Option<String> {
+        let client = self.redis_client.as_ref()?;
+        match client.get_multiplexed_async_connection().await {
+            Ok(mut conn) => conn.get(key).await.unwrap_or_default(),
+            Err(e) => {
+                debug!("[SLASHING] Redis unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn load_config(&self) -> SlashingConfigV1 {
+        self.redis_get(&retention_policy::SLASHING_CONFIG.key(""))
+            .await
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    async fn load_watch(&self, chain: &str) -> SlashingWatchV1 {
+        self.redis_get(&retention_policy::SLASHING_WATCH.key(chain))
+            .await
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    async fn cursor(&self, chain: &str) -> Option<u64> {
+        if let Some(slot) = self.cursors.lock().await.get(chain) {
+            return Some(*slot);
+        }
+        let slot = self
+            .redis_get(&retention_policy::SLASHING_CURSOR.key(chain))
+            .await?
+            .parse()
+            .ok()?;
+        self.cursors.lock().await.insert(chain.to_string(), slot);
+        Some(slot)
+    }
+
+    async fn save_cursor(&self, chain: &str, slot: u64) -> Result<()> {
+        self.cursors.lock().await.insert(chain.to_string(), slot);
+        let client = self
+            .redis_client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Redis not configured"))?;
+        let mut conn = client.get_multiplexed_async_connection().await?;
+        conn.set::<_, _, ()>(retention_policy::SLASHING_CURSOR.key(chain), slot)
+            .await?;
+        Ok(())
+    }
+}
+
+/// DuckLake slashing_events record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckLakeSlashingEventRecord {
+    pub chain_id: String,
+    pub slot_date: String,
+    pub slot: i64,
+    pub epoch: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<i64>,
+    pub slashing_type: String,
+    pub validator_index: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validator_pubkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawal_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watched_by: Option<String>,
+    pub ingested_at: i64,
+}
+
+impl DuckLakeSlashingEventRecord {
+    pub fn new(
+        event: &SlashingEventV1,
+        matched_by: Option<SlashingMatchV1>,
+        ingested_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            chain_id: format!("{}_{}", event.network, event.subnet),
+            slot_date: event
+                .block_timestamp
+                .unwrap_or(ingested_at)
+                .format("%Y-%m-%d")
+                .to_string(),
+            slot: event.slot as i64,
+            epoch: event.epoch() as i64,
+            block_timestamp: event.block_timestamp.map(|at| at.timestamp_micros()),
+            slashing_type: event.kind.as_str().to_string(),
+            validator_index: event.validator_index as i64,
+            validator_pubkey: event.validator_pubkey.clone(),
+            withdrawal_address: event.withdrawal_address.clone(),
+            watched_by: matched_by.map(|matched| match matched {
+                SlashingMatchV1::Validator => "validator".to_string(),
+                SlashingMatchV1::WithdrawalAddress => "withdrawal_address".to_string(),
+            }),
+            ingested_at: ingested_at.timestamp_micros(),
+        }
+    }
+}
+
+/// First slot to fetch after `cursor`, bounded to `max_slots` behind `head`
+fn cursor_start(cursor: u64, head: u64, max_slots: u64) -> u64 {
+    (cursor + 1).max(head.saturating_sub(max_slots.max(1)) + 1)
+}
+
+/// Beacon API numbers are decimal strings
+fn parse_u64(value: &serde_json::Value) -> Option<u64> {
+    value
+        .as_str()
+        .and_then(|raw| raw.parse().ok())
+        .or_else(|| value.as_u64())
+}
+
+/// Validators slashed by a `/eth/v2/beacon/blocks` response
+///
+/// A proposer slashing names its proposer; an attester slashing slashes the
+/// validators that signed both conflicting attestations.
+fn slashed_validators(block: &serde_json::Value) -> Vec<(SlashingKindV1, u64)> {
+    let body = &block["data"]["message"]["body"];
+    let mut slashed = BTreeSet::new();
+
+    for slashing in body["proposer_slashings"].as_array().into_iter().flatten() {
+        if let Some(index) = parse_u64(&slashing["signed_header_1"]["message"]["proposer_index"]) {
+            slashed.insert((SlashingKindV1::ProposerSlashing, index));
+        }
+    }
+
+    for slashing in body["attester_slashings"].as_array().into_iter().flatten() {
+        let indices = |attestation: &str| -> BTreeSet<u64> {
+            slashing[attestation]["attesting_indices"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(parse_u64)
+                .collect()
+        };
+        for index in indices("attestation_1").intersection(&indices("attestation_2")) {
+            slashed.insert((SlashingKindV1::AttesterSlashing, *index));
+        }
+    }
+
+    slashed.into_iter().collect()
+}
+
+/// Execution payload timestamp of a post-merge block
+fn block_timestamp(block: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let secs = parse_u64(&block["data"]["message"]["body"]["execution_payload"]["timestamp"])?;
+    DateTime::from_timestamp(secs as i64, 0)
+}
+
+/// Withdrawal address of `0x01` / `0x02` credentials; BLS (`0x00`)
+/// credentials have none
+fn withdrawal_address(credentials: &str) -> Option<String> {
+    let credentials = credentials.to_lowercase();
+    let hex = credentials.strip_prefix("0x")?;
+    if hex.len() != 64 || !(hex.starts_with("01") || hex.starts_with("02")) {
+        return None;
+    }
+    Some(format!("0x{}", &hex[24..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slashed_validators() {
+        let block = serde_json::json!({
+            "data": {"message": {"slot": "8000000", "body": {
+                "execution_payload": {"timestamp": "1700000000"},
+                "proposer_slashings": [
+                    {"signed_header_1": {"message": {"slot": "7999990", "proposer_index": "42"}}}
+                ],
+                "attester_slashings": [{
+                    "attestation_1": {"attesting_indices": ["7", "9", "11"]},
+                    "attestation_2": {"attesting_indices": ["9", "11", "13"]}
+                }]
+            }}}
+        });
+        assert_eq!(
+            slashed_validators(&block),
+            vec![
+                (SlashingKindV1::ProposerSlashing, 42),
+                (SlashingKindV1::AttesterSlashing, 9),
+                (SlashingKindV1::AttesterSlashing, 11),
+            ]
+        );
+        assert_eq!(
+            block_timestamp(&block).map(|at| at.timestamp()),
+            Some(1_700_000_000)
+        );
+        assert!(slashed_validators(&serde_json::json!({"data": {}})).is_empty());
+    }
+
+    #[test]
+    fn test_withdrawal_address() {
+        assert_eq!(
+            withdrawal_address(
+                "0x010000000000000000000000B9D7934878B5FB9610B3FE8A5E441E8FAD7E293F"
+            )
+            .as_deref(),
+            Some("0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f")
+        );
+        assert_eq!(
+            withdrawal_address(
+                "0x00f50428677c60f997aadeab24aabf7fceaef491c96a52b463ae91f95611cf71"
+            ),
+            None
+        );
+        assert_eq!(withdrawal_address("0x01"), None);
+    }
+
+    #[test]
+    fn test_cursor_start_skips_ahead() {
+        assert_eq!(cursor_start(100, 105, 32), 101);
+        assert_eq!(cursor_start(100, 500, 32), 469);
+        assert_eq!(cursor_start(0, 0, 0), 1);
+    }
+}
@@ -10,6 +10,24 @@
 //! Environment variables:
 //! - `NATS_URL` - NATS server URL (optional, uses lattice RPC URL by default)
 //! - `REDIS_URL` - Redis server URL (default: redis://localhost:6379)
+//!
+//! ## Chain health
+//!
+//! Every connection task feeds a shared `ChainHealthMonitor`, which raises
+//! chain halt / RPC outage alerts on `alerts.system.chain.*` and pauses
+//! downstream lag alarms while a chain is unhealthy.
+//!
+//! ## Slashing
+//!
+//! A `SlashingMonitor` follows the beacon nodes in `slashing:config`, records
+//! every slashed validator to `slashing_events` and raises
+//! `alerts.system.slashing.*` for watched validators and withdrawal addresses.
+//!
+//! ## Chain onboarding
+//!
+//! Besides Django updates on `blockchain:nodes:updates`, the provider follows
+//! `admin.chains.activated` from the chain-onboarding actor and starts
+//! streaming a newly approved chain from its `blockchain:nodes:*` entry.
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
 use async_trait::async_trait;
@@ -23,15 +41,20 @@ use tracing::{debug, error, info, warn};
 use wasmcloud_provider_sdk::{load_host_data, run_provider, HostData, Provider};
 
 // Use the newheads_evm_provider module imports
+use newheads_evm_provider::chain_health::ChainHealthMonitor;
 use newheads_evm_provider::config::load_chain_configs;
 use newheads_evm_provider::django_integration::{DjangoBlockchainNode, DjangoConfigManager};
 use newheads_evm_provider::ethereum::EthereumClient;
+use newheads_evm_provider::slashing::SlashingMonitor;
 use newheads_evm_provider::traits::{BlockchainClient, ChainConfig};
 use newheads_evm_provider::PROVIDER_NAME;
 
 /// The capability contract ID for the newheads provider
 const CAPABILITY_ID: &str = "wasmcloud:newheads";
 
+/// Published by the chain-onboarding actor once a new chain's config is written
+const CHAIN_ACTIVATED_SUBJECT: &str = "admin.chains.activated";
+
 /// wasmCloud Newheads Provider - Multi-chain newheads streaming
 ///
 /// Connects to multiple EVM blockchains and streams newheads to NATS.
@@ -46,6 +69,9 @@ pub struct NewheadsProvider {
     /// Active connection task handles
     connection_handles: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
 
+    /// Head tracking for chain halt / RPC outage alerts
+    chain_health: Arc<ChainHealthMonitor>,
+
     /// Host data from wasmCloud
     host_data: HostData,
 }
@@ -73,7 +99,11 @@ impl NewheadsProvider {
     ///
     /// Loads configuration from Django Redis keys with env fallback
     /// and connects to the configured blockchain.
-    pub async fn new(host_data: HostData, chain_configs: Vec<ChainConfig>) -> Result<Self> {
+    pub async fn new(
+        host_data: HostData,
+        chain_configs: Vec<ChainConfig>,
+        redis_url: &str,
+    ) -> Result<Self> {
         info!(
             "Initializing Newheads Provider for {} chains",
             chain_configs.len()
@@ -93,6 +123,10 @@ impl NewheadsProvider {
 
         info!("Connected to NATS at {}", nats_url);
 
+        let chain_health = ChainHealthMonitor::new(nats_client.clone(), redis_url);
+        chain_health.clone().spawn();
+        SlashingMonitor::new(nats_client.clone(), redis_url).spawn();
+
         let configs_map = chain_configs
             .into_iter()
             .map(|config| (config.chain_id.clone(), config))
@@ -102,6 +136,7 @@ impl NewheadsProvider {
             chain_configs: Arc::new(RwLock::new(configs_map)),
             nats_client,
             connection_handles: Arc::new(Mutex::new(HashMap::new())),
+            chain_health,
             host_data,
         };
 
@@ -156,9 +191,13 @@ impl NewheadsProvider {
         }
 
         let nats_client = self.nats_client.clone();
+        let chain_health = self.chain_health.clone();
         let chain_name = config.chain_name.clone();
         let chain_id = config.chain_id.clone();
-        let subject = config.nats_subjects.newheads_output.clone();
+        let subject = subject_registry::prefixed(&config.nats_subjects.newheads_output);
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(|e| anyhow!(e))?;
 
         info!(
             "[CONN] Spawning connection task for {} ({}) -> {}",
@@ -171,6 +210,7 @@ impl NewheadsProvider {
                 chain_name
             );
             let mut reconnect_count: u32 = 0;
+            chain_health.record_connection(&config, false).await;
 
             loop {
                 reconnect_count += 1;
@@ -179,7 +219,13 @@ impl NewheadsProvider {
                     reconnect_count, chain_name
                 );
 
-                match blockchain_connection_loop(config.clone(), nats_client.clone()).await {
+                match blockchain_connection_loop(
+                    config.clone(),
+                    nats_client.clone(),
+                    chain_health.clone(),
+                )
+                .await
+                {
                     Ok(_) => {
                         warn!(
                             "[TASK] Blockchain connection ended for {}, reconnecting in 5s...",
@@ -195,6 +241,7 @@ impl NewheadsProvider {
                         error!("[TASK] Reconnecting in 5s...");
                     }
                 }
+                chain_health.record_connection(&config, false).await;
 
                 tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
             }
@@ -210,6 +257,7 @@ impl NewheadsProvider {
             warn!("[CONN] Stopping connection for {}", chain_id);
             handle.abort();
         }
+        self.chain_health.forget(chain_id).await;
     }
 
     /// Stop the blockchain connection
@@ -251,6 +299,7 @@ async fn start_django_update_listener(
     chain_configs: Arc<RwLock<HashMap<String, ChainConfig>>>,
     connection_handles: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
     nats_client: async_nats::Client,
+    chain_health: Arc<ChainHealthMonitor>,
 ) {
     let manager = match DjangoConfigManager::new(&redis_url) {
         Ok(m) => m,
@@ -319,47 +368,128 @@ async fn start_django_update_listener(
             update.action, chain_id
         );
 
-        match manager.load_node(&chain_id).await {
-            Ok(config) => {
-                {
-                    let mut configs = chain_configs.write().await;
-                    configs.insert(chain_id.clone(), config.clone());
-                }
+        apply_node_update(
+            &manager,
+            &chain_id,
+            &chain_configs,
+            &connection_handles,
+            &nats_client,
+            &chain_health,
+        )
+        .await;
+    }
+}
 
-                if config.enabled && !config.ws_url.is_empty() {
-                    let mut handles = connection_handles.lock().await;
-                    if !handles.contains_key(&chain_id) {
-                        drop(handles);
-                        info!("[UPDATES] Enabling chain {}", chain_id);
-                        let _ = ensure_chain_connection_for_update(
-                            config,
-                            nats_client.clone(),
-                            connection_handles.clone(),
-                        )
-                        .await;
-                    } else {
-                        debug!("[UPDATES] Chain {} already active", chain_id);
-                    }
-                } else {
-                    info!("[UPDATES] Disabling chain {}", chain_id);
-                    let mut handles = connection_handles.lock().await;
-                    if let Some(handle) = handles.remove(&chain_id) {
-                        handle.abort();
-                    }
-                }
+/// Start, stop or drop `chain_id` to match its `blockchain:nodes:*` entry
+async fn apply_node_update(
+    manager: &DjangoConfigManager,
+    chain_id: &str,
+    chain_configs: &Arc<RwLock<HashMap<String, ChainConfig>>>,
+    connection_handles: &Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    nats_client: &async_nats::Client,
+    chain_health: &Arc<ChainHealthMonitor>,
+) {
+    match manager.load_node(chain_id).await {
+        Ok(config) => {
+            {
+                let mut configs = chain_configs.write().await;
+                configs.insert(chain_id.to_string(), config.clone());
             }
-            Err(_) => {
-                info!("[UPDATES] Removing chain {} (not found in Redis)", chain_id);
-                {
-                    let mut configs = chain_configs.write().await;
-                    configs.remove(&chain_id);
+
+            if config.enabled && !config.ws_url.is_empty() {
+                let mut handles = connection_handles.lock().await;
+                if !handles.contains_key(chain_id) {
+                    drop(handles);
+                    info!("[UPDATES] Enabling chain {}", chain_id);
+                    let _ = ensure_chain_connection_for_update(
+                        config,
+                        nats_client.clone(),
+                        connection_handles.clone(),
+                        chain_health.clone(),
+                    )
+                    .await;
+                } else {
+                    debug!("[UPDATES] Chain {} already active", chain_id);
                 }
+            } else {
+                info!("[UPDATES] Disabling chain {}", chain_id);
                 let mut handles = connection_handles.lock().await;
-                if let Some(handle) = handles.remove(&chain_id) {
+                if let Some(handle) = handles.remove(chain_id) {
                     handle.abort();
                 }
+                chain_health.forget(chain_id).await;
             }
         }
+        Err(_) => {
+            info!("[UPDATES] Removing chain {} (not found in Redis)", chain_id);
+            {
+                let mut configs = chain_configs.write().await;
+                configs.remove(chain_id);
+            }
+            let mut handles = connection_handles.lock().await;
+            if let Some(handle) = handles.remove(chain_id) {
+                handle.abort();
+            }
+            chain_health.forget(chain_id).await;
+        }
+    }
+}
+
+/// Activation event from the chain-onboarding actor
+#[derive(Debug, serde::Deserialize)]
+struct ChainActivatedMessage {
+    /// `blockchain:nodes:*` suffix
+    node_id: String,
+}
+
+/// Follow `admin.chains.activated`, so onboarded chains stream without a
+/// Django update
+async fn start_chain_activation_listener(
+    redis_url: String,
+    chain_configs: Arc<RwLock<HashMap<String, ChainConfig>>>,
+    connection_handles: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    nats_client: async_nats::Client,
+    chain_health: Arc<ChainHealthMonitor>,
+) {
+    let manager = match DjangoConfigManager::new(&redis_url) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("[ONBOARDING] Failed to init DjangoConfigManager: {}", e);
+            return;
+        }
+    };
+
+    let subject = subject_registry::prefixed(CHAIN_ACTIVATED_SUBJECT);
+    let mut subscriber = match nats_client.subscribe(subject.clone()).await {
+        Ok(subscriber) => subscriber,
+        Err(e) => {
+            error!("[ONBOARDING] Failed to subscribe to {}: {}", subject, e);
+            return;
+        }
+    };
+    info!(
+        "[ONBOARDING] Listening for chain activations on {}",
+        subject
+    );
+
+    while let Some(message) = subscriber.next().await {
+        let activated: ChainActivatedMessage = match serde_json::from_slice(&message.payload) {
+            Ok(activated) => activated,
+            Err(e) => {
+                warn!("[ONBOARDING] Failed to parse activation: {}", e);
+                continue;
+            }
+        };
+        info!("[ONBOARDING] Chain {} activated", activated.node_id);
+        apply_node_update(
+            &manager,
+            &activated.node_id,
+            &chain_configs,
+            &connection_handles,
+            &nats_client,
+            &chain_health,
+        )
+        .await;
     }
 }
 
@@ -367,6 +497,7 @@ async fn ensure_chain_connection_for_update(
     config: ChainConfig,
     nats_client: async_nats::Client,
     connection_handles: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    chain_health: Arc<ChainHealthMonitor>,
 ) -> Result<()> {
     if !config.enabled || config.ws_url.is_empty() {
         return Ok(());
@@ -374,7 +505,10 @@ async fn ensure_chain_connection_for_update(
 
     let chain_id = config.chain_id.clone();
     let chain_name = config.chain_name.clone();
-    let subject = config.nats_subjects.newheads_output.clone();
+    let subject = subject_registry::prefixed(&config.nats_subjects.newheads_output);
+    subject_registry::active_prefix()
+        .check_subjects(&[&subject])
+        .map_err(|e| anyhow!(e))?;
 
     let mut handles = connection_handles.lock().await;
     if handles.contains_key(&chain_id) {
@@ -392,6 +526,7 @@ async fn ensure_chain_connection_for_update(
             chain_name
         );
         let mut reconnect_count: u32 = 0;
+        chain_health.record_connection(&config, false).await;
 
         loop {
             reconnect_count += 1;
@@ -400,7 +535,13 @@ async fn ensure_chain_connection_for_update(
                 reconnect_count, chain_name
             );
 
-            match blockchain_connection_loop(config.clone(), nats_client.clone()).await {
+            match blockchain_connection_loop(
+                config.clone(),
+                nats_client.clone(),
+                chain_health.clone(),
+            )
+            .await
+            {
                 Ok(_) => {
                     warn!(
                         "[TASK] Blockchain connection ended for {}, reconnecting in 5s...",
@@ -416,6 +557,7 @@ async fn ensure_chain_connection_for_update(
                     error!("[TASK] Reconnecting in 5s...");
                 }
             }
+            chain_health.record_connection(&config, false).await;
 
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
         }
@@ -431,6 +573,7 @@ async fn ensure_chain_connection_for_update(
 async fn blockchain_connection_loop(
     config: ChainConfig,
     nats_client: async_nats::Client,
+    chain_health: Arc<ChainHealthMonitor>,
 ) -> Result<()> {
     info!("[WS-LOOP] ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     info!("[WS-LOOP] Starting blockchain connection loop");
@@ -471,6 +614,7 @@ async fn blockchain_connection_loop(
     };
 
     info!("[WS-LOOP] ✓ Connected to {} blockchain", config.chain_id);
+    chain_health.record_connection(&config, true).await;
     info!(
         "[WS-LOOP] ✓ Streaming newheads to NATS subject: {}",
         config.nats_subjects.newheads_output
@@ -482,7 +626,10 @@ async fn blockchain_connection_loop(
     // Process incoming block headers
     while let Some(block_header) = receiver.recv().await {
         block_count += 1;
-        let subject = &config.nats_subjects.newheads_output;
+        chain_health
+            .record_head(&config, block_header.block_number)
+            .await;
+        let subject = subject_registry::prefixed(&config.nats_subjects.newheads_output);
 
         debug!(
             "[WS-LOOP] Received block #{} (total: {})",
@@ -576,6 +723,10 @@ async fn main() -> Result<()> {
         "[HOST] Config keys: {:?}",
         host_data.config.keys().collect::<Vec<_>>()
     );
+    let subject_prefix = subject_registry::SubjectPrefix::from_properties(&host_data.config)
+        .and_then(subject_registry::install_prefix)
+        .map_err(|e| anyhow!(e))?;
+    info!("[HOST] Subject prefix: {}", subject_prefix);
     info!(
         "[HOST] Link definitions count: {}",
         host_data.link_definitions.len()
@@ -637,7 +788,7 @@ async fn main() -> Result<()> {
 
     // Create provider instance
     info!("[TRACE] Creating NewheadsProvider instance...");
-    let provider = match NewheadsProvider::new(host_data.clone(), chain_configs).await {
+    let provider = match NewheadsProvider::new(host_data.clone(), chain_configs, &redis_url).await {
         Ok(p) => {
             info!("[TRACE] ✓ NewheadsProvider created successfully");
             p
@@ -651,6 +802,7 @@ async fn main() -> Result<()> {
     let updates_chain_configs = provider.chain_configs.clone();
     let updates_handles = provider.connection_handles.clone();
     let updates_nats = provider.nats_client.clone();
+    let updates_health = provider.chain_health.clone();
     let updates_redis = redis_url.clone();
 
     tokio::spawn(async move {
@@ -659,6 +811,24 @@ async fn main() -> Result<()> {
             updates_chain_configs,
             updates_handles,
             updates_nats,
+            updates_health,
+        )
+        .await;
+    });
+
+    let activation_chain_configs = provider.chain_configs.clone();
+    let activation_handles = provider.connection_handles.clone();
+    let activation_nats = provider.nats_client.clone();
+    let activation_health = provider.chain_health.clone();
+    let activation_redis = redis_url.clone();
+
+    tokio::spawn(async move {
+        start_chain_activation_listener(
+            activation_redis,
+            activation_chain_configs,
+            activation_handles,
+            activation_nats,
+            activation_health,
         )
         .await;
     });
@@ -95,9 +95,14 @@ impl NatsEvalListener {
             .await
             .context("failed to connect to NATS")?;
 
-        info!("Subscribing to {}", self.config.subscribe_subject);
+        let subscribe_subject = subject_registry::prefixed(&self.config.subscribe_subject);
+        subject_registry::active_prefix()
+            .check_subjects(&[&subscribe_subject])
+            .map_err(anyhow::Error::msg)?;
+
+        info!("Subscribing to {}", subscribe_subject);
         let mut sub = client
-            .subscribe(self.config.subscribe_subject.clone())
+            .subscribe(subscribe_subject)
             .await
             .context("failed to subscribe")?;
 
@@ -169,7 +174,7 @@ async fn handle_message(
     };
 
     // Always publish to the canonical response subject for observability.
-    let pub_subject = format!("{publish_prefix}.{}", request_id);
+    let pub_subject = subject_registry::prefixed(&format!("{publish_prefix}.{}", request_id));
     client
         .publish(pub_subject, bytes.clone().into())
         .await
@@ -0,0 +1,60 @@
+//! Redis Janitor Provider binary entry point
+//!
+//! Runs as a wasmCloud capability provider, auditing Redis against the
+//! retention registry and publishing `metrics.redis.retention` reports.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tracing::{error, info};
+use wasmcloud_provider_sdk::{load_host_data, run_provider};
+
+use redis_janitor_provider::{JanitorConfig, RedisJanitorProvider};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("redis_janitor_provider=info".parse()?),
+        )
+        .init();
+
+    info!("🧹 Starting Redis Janitor Provider for wasmCloud");
+
+    let host_data = load_host_data().context("Failed to load wasmCloud host data")?;
+
+    info!("Provider ID: {}", host_data.provider_key);
+    info!("Config entries: {}", host_data.config.len());
+
+    let subject_prefix = subject_registry::SubjectPrefix::from_properties(&host_data.config)
+        .and_then(subject_registry::install_prefix)
+        .map_err(anyhow::Error::msg)?;
+    info!("Subject prefix: {}", subject_prefix);
+
+    let config = if host_data.config.is_empty() {
+        JanitorConfig::from_env()
+    } else {
+        JanitorConfig::from_properties(&host_data.config)
+    };
+
+    info!("Audit interval: {}s", config.interval_secs);
+    info!("Enforce TTL: {}", config.enforce_ttl);
+
+    let provider = RedisJanitorProvider::with_config(config);
+    let runtime_provider = provider.clone();
+    let provider = Arc::new(provider);
+
+    tokio::spawn(async move {
+        if let Err(e) = provider.start().await {
+            error!("provider error: {e:?}");
+        }
+    });
+
+    let handler = run_provider(runtime_provider, "redis-janitor-provider")
+        .await
+        .context("Provider runtime error")?;
+    handler.await;
+
+    info!("Redis Janitor Provider shutdown complete");
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+/// Janitor settings from host config properties or environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct JanitorConfig {
+    pub redis_url: String,
+    pub nats_url: String,
+    /// Seconds between keyspace audits
+    pub interval_secs: u64,
+    /// `SCAN COUNT` hint per round trip
+    pub scan_count: usize,
+    /// Keys per pattern measured with `MEMORY USAGE`
+    pub memory_samples_per_pattern: u64,
+    /// Apply registered TTLs to keys that have none; audit-only when false
+    pub enforce_ttl: bool,
+}
+
+impl JanitorConfig {
+    const DEFAULT_REDIS_URL: &'static str = "redis://localhost:6379";
+    const DEFAULT_NATS_URL: &'static str = "nats://localhost:4222";
+    const DEFAULT_INTERVAL_SECS: u64 = 300;
+    const DEFAULT_SCAN_COUNT: usize = 1000;
+    const DEFAULT_MEMORY_SAMPLES: u64 = 50;
+
+    pub fn from_env() -> Self {
+        let vars: HashMap<String, String> = std::env::vars().collect();
+        Self::from_properties(&vars)
+    }
+
+    pub fn from_properties(props: &HashMap<String, String>) -> Self {
+        let get = |key: &str| {
+            props
+                .get(key)
+                .or_else(|| props.get(&key.to_uppercase()))
+                .cloned()
+        };
+
+        Self {
+            redis_url: get("redis_url").unwrap_or_else(|| Self::DEFAULT_REDIS_URL.to_string()),
+            nats_url: get("nats_url").unwrap_or_else(|| Self::DEFAULT_NATS_URL.to_string()),
+            interval_secs: get("redis_janitor_interval_secs")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(Self::DEFAULT_INTERVAL_SECS),
+            scan_count: get("redis_janitor_scan_count")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(Self::DEFAULT_SCAN_COUNT),
+            memory_samples_per_pattern: get("redis_janitor_memory_samples")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(Self::DEFAULT_MEMORY_SAMPLES),
+            enforce_ttl: get("redis_janitor_enforce_ttl")
+                .map(|v| !matches!(v.to_lowercase().as_str(), "false" | "0" | "no"))
+                .unwrap_or(true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_properties_defaults_and_overrides() {
+        let defaults = JanitorConfig::from_properties(&HashMap::new());
+        assert_eq!(defaults.interval_secs, 300);
+        assert!(defaults.enforce_ttl);
+
+        let props = HashMap::from([
+            ("redis_url".to_string(), "redis://redis:6379".to_string()),
+            ("REDIS_JANITOR_INTERVAL_SECS".to_string(), "60".to_string()),
+            ("redis_janitor_enforce_ttl".to_string(), "false".to_string()),
+        ]);
+        let config = JanitorConfig::from_properties(&props);
+        assert_eq!(config.redis_url, "redis://redis:6379");
+        assert_eq!(config.interval_secs, 60);
+        assert!(!config.enforce_ttl);
+    }
+}
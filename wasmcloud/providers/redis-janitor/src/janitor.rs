@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use redis::aio::ConnectionManager;
+use retention_policy::{KeyTtl, RetentionAudit, RetentionReport, REGISTRY, REPORT_SUBJECT};
+use std::time::Duration;
+use tracing::{error, info, instrument, warn};
+
+use crate::config::JanitorConfig;
+
+/// Periodic keyspace audit and TTL enforcement
+pub struct RedisJanitor {
+    config: JanitorConfig,
+    redis: ConnectionManager,
+    nats: async_nats::Client,
+}
+
+impl RedisJanitor {
+    pub async fn connect(config: JanitorConfig) -> Result<Self> {
+        let client = redis::Client::open(config.redis_url.as_str())
+            .context("invalid Redis URL for janitor")?;
+        let redis = ConnectionManager::new(client)
+            .await
+            .context("failed to connect to Redis")?;
+        let nats = async_nats::connect(&config.nats_url)
+            .await
+            .context("failed to connect to NATS")?;
+
+        Ok(Self {
+            config,
+            redis,
+            nats,
+        })
+    }
+
+    /// Audit on every interval until the process stops
+    pub async fn run(mut self) -> Result<()> {
+        let mut ticker = tokio::time::interval(Duration::from_secs(self.config.interval_secs));
+        info!(
+            "Redis janitor auditing {} retention rules every {}s (enforce_ttl={})",
+            REGISTRY.len(),
+            self.config.interval_secs,
+            self.config.enforce_ttl
+        );
+
+        loop {
+            ticker.tick().await;
+            match self.audit_once().await {
+                Ok(report) => self.publish(&report).await,
+                Err(e) => error!("Redis retention audit failed: {e:?}"),
+            }
+        }
+    }
+
+    /// Scan the whole keyspace once and build the report
+    #[instrument(skip(self))]
+    pub async fn audit_once(&mut self) -> Result<RetentionReport> {
+        let mut audit = RetentionAudit::new(REGISTRY, self.config.memory_samples_per_pattern);
+        let mut cursor: u64 = 0;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("COUNT")
+                .arg(self.config.scan_count)
+                .query_async(&mut self.redis)
+                .await
+                .context("SCAN failed")?;
+            self.audit_keys(&mut audit, &keys).await?;
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(audit.report(&chrono::Utc::now().to_rfc3339()))
+    }
+
+    async fn audit_keys(&mut self, audit: &mut RetentionAudit<'_>, keys: &[String]) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("TTL").arg(key);
+        }
+        let ttls: Vec<i64> = pipe
+            .query_async(&mut self.redis)
+            .await
+            .context("TTL pipeline failed")?;
+
+        for (key, ttl) in keys.iter().zip(ttls) {
+            let ttl = KeyTtl::from_redis(ttl);
+            let (rule, sample) = audit.classify(key);
+
+            let memory = if sample && ttl != KeyTtl::Missing {
+                redis::cmd("MEMORY")
+                    .arg("USAGE")
+                    .arg(key)
+                    .arg("SAMPLES")
+                    .arg(0)
+                    .query_async::<_, Option<u64>>(&mut self.redis)
+                    .await
+                    .context("MEMORY USAGE failed")?
+            } else {
+                None
+            };
+            audit.observe(key, ttl, memory);
+
+            let Some(limit) = rule.and_then(|rule| rule.ttl_secs) else {
+                continue;
+            };
+            if self.config.enforce_ttl && ttl == KeyTtl::Persistent {
+                let applied: bool = redis::cmd("EXPIRE")
+                    .arg(key)
+                    .arg(limit)
+                    .query_async(&mut self.redis)
+                    .await
+                    .context("EXPIRE failed")?;
+                if applied {
+                    audit.ttl_applied(key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn publish(&self, report: &RetentionReport) {
+        for drift in &report.drift {
+            warn!(
+                "Redis retention drift: {}",
+                serde_json::to_string(drift).unwrap_or_default()
+            );
+        }
+        info!(
+            "Redis retention audit: {} keys, {} drift findings",
+            report.scanned_keys,
+            report.drift.len()
+        );
+
+        let payload = match serde_json::to_vec(report) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("failed to serialize retention report: {e}");
+                return;
+            }
+        };
+        if let Err(e) = self
+            .nats
+            .publish(subject_registry::prefixed(REPORT_SUBJECT), payload.into())
+            .await
+        {
+            error!("failed to publish retention report: {e}");
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Redis Janitor Provider
+//!
+//! Enforces the centralized retention registry (`retention-policy`) against the
+//! shared Redis instance. wasm actors write through wasi:keyvalue, which cannot
+//! set expiries or inspect memory, so this runs as a native provider:
+//! - scans the keyspace on an interval
+//! - applies the registered TTL to keys written without one
+//! - audits key counts and sampled memory against each pattern's budget
+//! - publishes a `RetentionReport` on `metrics.redis.retention`
+
+pub mod config;
+pub mod janitor;
+pub mod provider;
+
+pub use config::JanitorConfig;
+pub use janitor::RedisJanitor;
+pub use provider::RedisJanitorProvider;
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tracing::{info, instrument};
+use wasmcloud_provider_sdk::Provider;
+
+use crate::config::JanitorConfig;
+use crate::janitor::RedisJanitor;
+
+/// Redis Janitor Provider
+///
+/// - Scans: the shared Redis keyspace every `interval_secs`
+/// - Publishes: `metrics.redis.retention` (`RetentionReport`)
+#[derive(Clone)]
+pub struct RedisJanitorProvider {
+    config: JanitorConfig,
+}
+
+impl RedisJanitorProvider {
+    #[instrument]
+    pub fn with_config(config: JanitorConfig) -> Self {
+        Self { config }
+    }
+
+    #[instrument]
+    pub fn from_properties(props: &HashMap<String, String>) -> Self {
+        Self::with_config(JanitorConfig::from_properties(props))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn start(self: Arc<Self>) -> Result<()> {
+        info!("Starting Redis Janitor Provider");
+
+        let janitor = RedisJanitor::connect(self.config.clone()).await?;
+        janitor.run().await
+    }
+}
+
+impl Default for RedisJanitorProvider {
+    fn default() -> Self {
+        Self::with_config(JanitorConfig::from_env())
+    }
+}
+
+impl Provider for RedisJanitorProvider {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_traits() {
+        fn assert_provider<T: Provider + Clone>() {}
+        assert_provider::<RedisJanitorProvider>();
+    }
+}
@@ -35,13 +35,18 @@ impl NatsHandler {
 
     /// Start subscribing to notifications
     pub async fn start(&self) -> Result<()> {
+        let subject = subject_registry::prefixed("notifications.slack");
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(anyhow::Error::msg)?;
+
         let subscriber = self
             .nats_client
-            .subscribe("notifications.slack")
+            .subscribe(subject.clone())
             .await
-            .context("Failed to subscribe to notifications.slack")?;
+            .with_context(|| format!("Failed to subscribe to {}", subject))?;
 
-        info!("Subscribed to notifications.slack");
+        info!("Subscribed to {}", subject);
 
         self.process_notifications(subscriber).await
     }
@@ -154,7 +159,10 @@ impl NatsHandler {
             serde_json::to_vec(&delivery_event).context("Failed to serialize delivery event")?;
 
         self.nats_client
-            .publish(NOTIFICATION_DUCKLAKE_SUBJECT, ducklake_payload.into())
+            .publish(
+                subject_registry::prefixed(NOTIFICATION_DUCKLAKE_SUBJECT),
+                ducklake_payload.into(),
+            )
             .await
             .context("Failed to publish delivery event to DuckLake")?;
 
@@ -44,21 +44,19 @@ impl NatsHandler {
 
     /// Start listening for notifications on the NATS subject
     pub async fn start(&self) -> Result<()> {
-        info!(
-            "Subscribing to NATS subject: {}",
-            TELEGRAM_NOTIFICATION_SUBJECT
-        );
+        let subject = subject_registry::prefixed(TELEGRAM_NOTIFICATION_SUBJECT);
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(anyhow::Error::msg)?;
+        info!("Subscribing to NATS subject: {}", subject);
 
         let subscriber = self
             .nats_client
-            .subscribe(TELEGRAM_NOTIFICATION_SUBJECT)
+            .subscribe(subject.clone())
             .await
             .context("Failed to subscribe to NATS subject")?;
 
-        info!(
-            "Successfully subscribed to {}",
-            TELEGRAM_NOTIFICATION_SUBJECT
-        );
+        info!("Successfully subscribed to {}", subject);
 
         self.process_notifications(subscriber).await
     }
@@ -176,7 +174,10 @@ impl NatsHandler {
             serde_json::to_vec(&delivery_event).context("Failed to serialize delivery event")?;
 
         self.nats_client
-            .publish(NOTIFICATION_DUCKLAKE_SUBJECT, ducklake_payload.into())
+            .publish(
+                subject_registry::prefixed(NOTIFICATION_DUCKLAKE_SUBJECT),
+                ducklake_payload.into(),
+            )
             .await
             .context("Failed to publish delivery event to DuckLake")?;
 
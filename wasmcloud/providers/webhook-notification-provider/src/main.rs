@@ -31,6 +31,11 @@ async fn main() -> Result<()> {
     info!("Lattice RPC URL: {}", host_data.lattice_rpc_url);
     info!("Config entries: {}", host_data.config.len());
 
+    let subject_prefix = subject_registry::SubjectPrefix::from_properties(&host_data.config)
+        .and_then(subject_registry::install_prefix)
+        .map_err(anyhow::Error::msg)?;
+    info!("Subject prefix: {}", subject_prefix);
+
     // Load configuration from host data or environment
     let config = if !host_data.config.is_empty() {
         info!("Using configuration from wasmCloud HostData");
@@ -35,11 +35,15 @@ impl NatsHandler {
 
     /// Start listening for webhook notification requests
     pub async fn start(&self, subject: &str) -> Result<()> {
+        let subject = subject_registry::prefixed(subject);
+        subject_registry::active_prefix()
+            .check_subjects(&[&subject])
+            .map_err(anyhow::Error::msg)?;
         info!("Starting NATS handler for subject: {}", subject);
 
         let subscriber = self
             .nats_client
-            .subscribe(subject.to_string())
+            .subscribe(subject.clone())
             .await
             .context("Failed to subscribe to NATS subject")?;
 
@@ -161,7 +165,10 @@ impl NatsHandler {
         }
 
         // Publish status update to NATS
-        let status_subject = format!("notifications.status.webhook.{}", request.notification_id);
+        let status_subject = subject_registry::prefixed(&format!(
+            "notifications.status.webhook.{}",
+            request.notification_id
+        ));
         let status_payload =
             serde_json::to_vec(&delivery_status).context("Failed to serialize delivery status")?;
 
@@ -184,7 +191,10 @@ impl NatsHandler {
             serde_json::to_vec(&delivery_event).context("Failed to serialize delivery event")?;
 
         nats_client
-            .publish(NOTIFICATION_DUCKLAKE_SUBJECT, ducklake_payload.into())
+            .publish(
+                subject_registry::prefixed(NOTIFICATION_DUCKLAKE_SUBJECT),
+                ducklake_payload.into(),
+            )
             .await
             .context("Failed to publish delivery event to DuckLake")?;
 
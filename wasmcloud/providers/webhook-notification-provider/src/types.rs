@@ -37,6 +37,18 @@ pub struct WebhookConfig {
     pub timeout_seconds: u64,
     pub retry_config: RetryConfig,
     pub enabled: bool,
+    /// Seal payloads to the receiver's key before delivery
+    #[serde(default)]
+    pub encryption: Option<WebhookEncryption>,
+}
+
+/// Receiver key for message-level payload encryption
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEncryption {
+    /// Receiver's key identifier, echoed in the envelope for rotation
+    pub key_id: String,
+    /// Base64 X25519 public key held by the receiver
+    pub public_key: String,
 }
 
 /// HTTP methods for webhook
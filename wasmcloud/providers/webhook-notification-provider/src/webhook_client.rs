@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use hmac::{Hmac, Mac};
+use payload_encryption::TenantEncryptionPolicy;
 use rand::Rng;
 use reqwest::Client;
 use sha2::Sha256;
@@ -133,7 +134,7 @@ impl WebhookClient {
         let start = Instant::now();
 
         // Build request body
-        let body = serde_json::to_vec(&request).context("Failed to serialize webhook payload")?;
+        let body = build_body(config, request)?;
 
         // Build HTTP request
         let mut req_builder = match config.http_method {
@@ -257,3 +258,96 @@ struct WebhookResponse {
     body: Option<String>,
     duration_ms: u64,
 }
+
+/// Serialize the request, sealing `payload` when the receiver registered a key
+///
+/// Routing fields (ids, priority, timestamp) stay in the clear so receivers can
+/// dedupe and verify signatures before decrypting.
+fn build_body(config: &WebhookConfig, request: &WebhookNotificationRequest) -> Result<Vec<u8>> {
+    let Some(encryption) = &config.encryption else {
+        return serde_json::to_vec(request).context("Failed to serialize webhook payload");
+    };
+
+    let receiver = TenantEncryptionPolicy {
+        enabled: true,
+        key_id: encryption.key_id.clone(),
+        public_key: encryption.public_key.clone(),
+    };
+    let public_key = receiver
+        .public_key_bytes()
+        .map_err(|e| anyhow::anyhow!("Invalid webhook encryption key: {}", e))?;
+    let plaintext =
+        serde_json::to_vec(&request.payload).context("Failed to serialize webhook payload")?;
+    let envelope =
+        payload_encryption::seal(&plaintext, &request.user_id, &receiver.key_id, &public_key)
+            .map_err(|e| anyhow::anyhow!("Failed to encrypt webhook payload: {}", e))?;
+
+    let mut sealed = request.clone();
+    sealed.payload =
+        serde_json::to_value(envelope).context("Failed to serialize encrypted payload")?;
+    serde_json::to_vec(&sealed).context("Failed to serialize webhook payload")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AlertPriority;
+    use payload_encryption::{EncryptedPayloadV1, TenantKeyring};
+
+    fn request() -> WebhookNotificationRequest {
+        WebhookNotificationRequest {
+            notification_id: "n1".to_string(),
+            user_id: "u1".to_string(),
+            alert_id: "a1".to_string(),
+            alert_name: "Whale watch".to_string(),
+            priority: AlertPriority::High,
+            payload: serde_json::json!({ "wallet": "0xabc" }),
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    fn config(encryption: serde_json::Value) -> WebhookConfig {
+        serde_json::from_value(serde_json::json!({
+            "user_id": "u1",
+            "webhook_url": "https://example.com/hook",
+            "fallback_url": null,
+            "http_method": "POST",
+            "headers": {},
+            "auth_type": "none",
+            "hmac_secret": null,
+            "jwt_secret": null,
+            "timeout_seconds": 5,
+            "retry_config": RetryConfig::default(),
+            "enabled": true,
+            "encryption": encryption,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn build_body_seals_payload_for_registered_key() {
+        let receiver = TenantKeyring::new(vec![2u8; 32]).unwrap();
+        let policy = receiver.policy("u1", "rk1");
+        let sealed_config = config(serde_json::json!({
+            "key_id": policy.key_id,
+            "public_key": policy.public_key,
+        }));
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&build_body(&sealed_config, &request()).unwrap()).unwrap();
+        assert_eq!(body["notification_id"], "n1");
+        assert!(body["payload"].get("wallet").is_none());
+
+        let envelope = EncryptedPayloadV1::detect(body["payload"].to_string().as_bytes())
+            .expect("sealed payload");
+        let payload: serde_json::Value =
+            serde_json::from_slice(&receiver.open(&envelope).unwrap()).unwrap();
+        assert_eq!(payload["wallet"], "0xabc");
+
+        let plain: serde_json::Value = serde_json::from_slice(
+            &build_body(&config(serde_json::Value::Null), &request()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(plain["payload"]["wallet"], "0xabc");
+    }
+}
@@ -13,6 +13,11 @@ async fn main() -> Result<()> {
 
     tracing::info!("Starting WebSocket Notification Provider");
 
+    let subject_prefix = subject_registry::SubjectPrefix::from_env()
+        .and_then(subject_registry::install_prefix)
+        .map_err(anyhow::Error::msg)?;
+    tracing::info!("Subject prefix: {}", subject_prefix);
+
     // Load configuration from environment or use defaults
     let config = ProviderConfig::default();
 
@@ -79,27 +79,34 @@ impl NatsHandler {
 
     /// Subscribe to notification subjects and start processing
     pub async fn start(&self) -> Result<()> {
+        let subjects = [
+            LEGACY_NOTIFICATION_SUBJECT,
+            IMMEDIATE_NOTIFICATION_SUBJECT,
+            DIGEST_NOTIFICATION_SUBJECT,
+            DIRECT_NOTIFICATION_SUBJECT,
+            EVENT_SUBJECT,
+        ]
+        .map(subject_registry::prefixed);
+        subject_registry::active_prefix()
+            .check_subjects(&subjects)
+            .map_err(anyhow::Error::msg)?;
+        let [legacy, immediate, digest, direct, events] = subjects;
+
         // Subscribe to legacy and current notification subjects
-        let legacy_subscriber = self.client.subscribe(LEGACY_NOTIFICATION_SUBJECT).await?;
-        info!("Subscribed to {}", LEGACY_NOTIFICATION_SUBJECT);
+        let legacy_subscriber = self.client.subscribe(legacy.clone()).await?;
+        info!("Subscribed to {}", legacy);
 
-        let immediate_subscriber = self
-            .client
-            .subscribe(IMMEDIATE_NOTIFICATION_SUBJECT)
-            .await?;
-        info!("Subscribed to {}", IMMEDIATE_NOTIFICATION_SUBJECT);
+        let immediate_subscriber = self.client.subscribe(immediate.clone()).await?;
+        info!("Subscribed to {}", immediate);
 
-        let digest_subscriber = self.client.subscribe(DIGEST_NOTIFICATION_SUBJECT).await?;
-        info!("Subscribed to {}", DIGEST_NOTIFICATION_SUBJECT);
+        let digest_subscriber = self.client.subscribe(digest.clone()).await?;
+        info!("Subscribed to {}", digest);
 
-        let direct_subscriber = self.client.subscribe(DIRECT_NOTIFICATION_SUBJECT).await?;
-        info!("Subscribed to {}", DIRECT_NOTIFICATION_SUBJECT);
+        let direct_subscriber = self.client.subscribe(direct.clone()).await?;
+        info!("Subscribed to {}", direct);
 
-        let event_subscriber = self.client.subscribe(EVENT_SUBJECT).await?;
-        info!(
-            "Subscribed to {} (Phase 1: NLP progress events)",
-            EVENT_SUBJECT
-        );
+        let event_subscriber = self.client.subscribe(events.clone()).await?;
+        info!("Subscribed to {} (Phase 1: NLP progress events)", events);
 
         // Process both streams concurrently
         self.process_all_messages(
@@ -214,7 +221,7 @@ impl NatsHandler {
 
     /// Handle a single notification message
     pub async fn handle_notification(&self, message: Message) -> Result<()> {
-        let subject = message.subject.to_string();
+        let subject = subject_registry::unprefixed(message.subject.as_str()).unwrap_or_default();
         if subject.starts_with("notifications.send.") {
             let request: DeliveryRequest = serde_json::from_slice(&message.payload)?;
             let priority = request
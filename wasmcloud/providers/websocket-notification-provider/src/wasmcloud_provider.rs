@@ -31,6 +31,11 @@ impl WebSocketProvider {
             info!("Config: {} = {}", key, display_value);
         }
 
+        let subject_prefix = subject_registry::SubjectPrefix::from_properties(&wasmcloud_config)
+            .and_then(subject_registry::install_prefix)
+            .map_err(anyhow::Error::msg)?;
+        info!("Subject prefix: {}", subject_prefix);
+
         // Build config from wasmCloud config properties, with env var fallbacks
         let config = ProviderConfig {
             websocket_port: wasmcloud_config
@@ -75,6 +75,7 @@ POSTGRES_DATABASE="${DUCKLAKE_POSTGRES_DATABASE:-${POSTGRES_DATABASE:-${POSTGRES
 
 # DuckLake write subject (should be write-only; avoid subscribing to query/schema subjects)
 DUCKLAKE_WRITE_SUBJECT="${DUCKLAKE_WRITE_SUBJECT:-ducklake.*.*.*.write}"
+DUCKLAKE_UPSERT_SUBJECT="${DUCKLAKE_UPSERT_SUBJECT:-ducklake.*.*.*.upsert}"
 WADM_APP_NAME="${WADM_APP_NAME:-ekko-platform}"
 WADM_CONFIG_PREFIX="${WADM_APP_NAME//-/_}"
 USE_K8S_NATS="${USE_K8S_NATS:-auto}"
@@ -280,14 +281,15 @@ put_config transaction-processor-handler subscriptions="blockchain.>.>.transacti
 
 # Notification router handler
 log_info "Creating notification-router-handler config..."
-put_config notification-router-handler subscriptions="alerts.triggered.>, notifications.send.immediate.>" CLUSTER_URIS="${NATS_URL}" && \
+put_config notification-router-handler subscriptions="alerts.triggered.>, alerts.escalation.due.>, notifications.send.immediate.>" CLUSTER_URIS="${NATS_URL}" && \
     log_success "notification-router-handler" || log_error "notification-router-handler failed"
 
 # ABI decoder handler
 # blockchain.>.>.contracts.transactions catches all networks/subnets
 # blockchain.abi.decode.> catches decode requests for all networks/subnets
+# abi.decode.dispatch carries queued requests from the decode-queue provider
 log_info "Creating abi-decoder-handler config..."
-put_config abi-decoder-handler subscriptions="blockchain.>.>.contracts.transactions, blockchain.abi.decode.>" CLUSTER_URIS="${NATS_URL}" && \
+put_config abi-decoder-handler subscriptions="blockchain.>.>.contracts.transactions, blockchain.abi.decode.>, abi.decode.dispatch" CLUSTER_URIS="${NATS_URL}" && \
     log_success "abi-decoder-handler" || log_error "abi-decoder-handler failed"
 
 # Transaction DuckLake writer handler
@@ -448,6 +450,7 @@ put_config ducklake-write-config \
     ducklake_postgres_password="${POSTGRES_PASSWORD}" \
     ducklake_postgres_database="${POSTGRES_DATABASE}" \
     ducklake_write_subject="${DUCKLAKE_WRITE_SUBJECT}" \
+    ducklake_upsert_subject="${DUCKLAKE_UPSERT_SUBJECT}" \
     ducklake_warehouse_path="ekko/ducklake" && \
     log_success "ducklake-write-config" || log_error "ducklake-write-config failed"
 
@@ -469,6 +472,7 @@ put_config ducklake-read-config \
     ducklake_query_subject="ducklake.*.*.*.query" \
     ducklake_schema_list_subject="ducklake.schema.list" \
     ducklake_schema_get_subject="ducklake.schema.get" \
+    ducklake_gas_estimate_subject="gas.estimate.request" \
     ducklake_warehouse_path="ekko/ducklake" && \
     log_success "ducklake-read-config" || log_error "ducklake-read-config failed"
 
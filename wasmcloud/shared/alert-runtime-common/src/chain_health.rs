@@ -0,0 +1,454 @@
+//! Chain halt and RPC outage alerts.
+//!
+//! The newheads provider tracks every chain's head: [`ChainHeadWatchV1`]
+//! raises a stall when no new head arrived within `stall_multiplier` expected
+//! block times, and an RPC outage when every endpoint of the chain (WebSocket
+//! and HTTP RPC) failed for `outage_rounds` consecutive probe rounds. Signals
+//! are published on `alerts.system.chain.{network}.{subnet}.{rule}`.
+//!
+//! While either condition is raised a [`LagAlarmPauseV1`] is kept in Redis so
+//! downstream lag alarms stay quiet instead of firing for every consumer that
+//! falls behind because the chain itself stopped.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the per-chain, per-rule subjects
+pub const ALERT_SYSTEM_CHAIN_SUBJECT_PREFIX: &str = "alerts.system.chain";
+
+/// Block time assumed for networks missing from `block_time_secs`
+const DEFAULT_BLOCK_TIME_SECS: f64 = 12.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ChainHealthRuleV1 {
+    /// No new head for `stall_multiplier` x the expected block time
+    HeadStalled,
+    /// Every endpoint of the chain failed for `outage_rounds` probe rounds
+    RpcOutage,
+    /// A raised condition cleared (`cleared_rule` names it)
+    Recovered,
+}
+
+impl ChainHealthRuleV1 {
+    pub const ALL: [Self; 3] = [Self::HeadStalled, Self::RpcOutage, Self::Recovered];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HeadStalled => "head_stalled",
+            Self::RpcOutage => "rpc_outage",
+            Self::Recovered => "recovered",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::HeadStalled => "No new head within the expected block time window",
+            Self::RpcOutage => "Every RPC endpoint of the chain is failing",
+            Self::Recovered => "Chain heads and RPC endpoints recovered",
+        }
+    }
+
+    /// Subject the rule publishes on for one chain; use `*` to subscribe to
+    /// every chain or rule
+    pub fn subject(&self, network: &str, subnet: &str) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            ALERT_SYSTEM_CHAIN_SUBJECT_PREFIX,
+            network.to_lowercase(),
+            subnet.to_lowercase(),
+            self.as_str()
+        )
+    }
+}
+
+/// Detection thresholds; defaults apply when no config is stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainHealthConfigV1 {
+    pub enabled: bool,
+    /// Expected block times without a new head before a stall is raised
+    pub stall_multiplier: f64,
+    /// Floor on the stall window, so fast chains don't flap on short gaps
+    pub min_stall_secs: u64,
+    /// Consecutive all-endpoints-failing rounds before an outage is raised
+    pub outage_rounds: u32,
+    /// Seconds between probe rounds
+    pub check_interval_secs: u64,
+    /// Lifetime of the lag alarm pause; refreshed every round while raised
+    pub pause_ttl_secs: u64,
+    /// Expected seconds per block, by network
+    pub block_time_secs: HashMap<String, f64>,
+}
+
+impl Default for ChainHealthConfigV1 {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stall_multiplier: 10.0,
+            min_stall_secs: 60,
+            outage_rounds: 3,
+            check_interval_secs: 15,
+            pause_ttl_secs: 300,
+            block_time_secs: [
+                ("ethereum", 12.0),
+                ("avalanche", 2.0),
+                ("polygon", 2.0),
+                ("base", 2.0),
+                ("optimism", 2.0),
+                ("arbitrum", 0.25),
+                ("bsc", 3.0),
+            ]
+            .into_iter()
+            .map(|(network, secs)| (network.to_string(), secs))
+            .collect(),
+        }
+    }
+}
+
+impl ChainHealthConfigV1 {
+    /// Time without a new head after which `network` counts as stalled
+    pub fn stall_after(&self, network: &str) -> Duration {
+        let block_time = self
+            .block_time_secs
+            .get(&network.to_lowercase())
+            .copied()
+            .unwrap_or(DEFAULT_BLOCK_TIME_SECS);
+        let secs = (block_time * self.stall_multiplier).max(self.min_stall_secs as f64);
+        Duration::milliseconds((secs * 1000.0) as i64)
+    }
+}
+
+/// Per-chain head tracking state, kept in memory by the newheads provider
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainHeadWatchV1 {
+    pub network: String,
+    pub subnet: String,
+    /// Node config id, e.g. `ethereum-mainnet`
+    pub chain_id: String,
+    pub last_head: Option<u64>,
+    /// When the head last advanced (or when watching started)
+    pub last_head_at: Option<DateTime<Utc>>,
+    pub failed_rounds: u32,
+    pub stalled: bool,
+    pub outage: bool,
+}
+
+/// One rule firing for a chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainHealthSignalV1 {
+    pub rule: ChainHealthRuleV1,
+    /// Rule cleared by a [`ChainHealthRuleV1::Recovered`] signal
+    pub cleared_rule: Option<ChainHealthRuleV1>,
+}
+
+impl ChainHealthSignalV1 {
+    fn raised(rule: ChainHealthRuleV1) -> Self {
+        Self {
+            rule,
+            cleared_rule: None,
+        }
+    }
+
+    fn recovered(cleared: ChainHealthRuleV1) -> Self {
+        Self {
+            rule: ChainHealthRuleV1::Recovered,
+            cleared_rule: Some(cleared),
+        }
+    }
+}
+
+impl ChainHeadWatchV1 {
+    pub fn new(network: &str, subnet: &str, chain_id: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            network: network.to_lowercase(),
+            subnet: subnet.to_lowercase(),
+            chain_id: chain_id.to_string(),
+            last_head_at: Some(now),
+            ..Default::default()
+        }
+    }
+
+    /// Record a head from any endpoint. A head not above the last one still
+    /// proves an endpoint answers, so it clears an outage but not a stall.
+    pub fn record_head(
+        &mut self,
+        block_number: u64,
+        at: DateTime<Utc>,
+    ) -> Vec<ChainHealthSignalV1> {
+        let mut signals = Vec::new();
+        self.failed_rounds = 0;
+        if self.outage {
+            self.outage = false;
+            signals.push(ChainHealthSignalV1::recovered(ChainHealthRuleV1::RpcOutage));
+        }
+        if self.last_head.is_some_and(|last| block_number <= last) {
+            return signals;
+        }
+        self.last_head = Some(block_number);
+        self.last_head_at = Some(at);
+        if self.stalled {
+            self.stalled = false;
+            signals.push(ChainHealthSignalV1::recovered(
+                ChainHealthRuleV1::HeadStalled,
+            ));
+        }
+        signals
+    }
+
+    /// Record one probe round; `all_failed` when no endpoint answered
+    pub fn record_round(
+        &mut self,
+        config: &ChainHealthConfigV1,
+        all_failed: bool,
+    ) -> Vec<ChainHealthSignalV1> {
+        let mut signals = Vec::new();
+        if !all_failed {
+            self.failed_rounds = 0;
+            if self.outage {
+                self.outage = false;
+                signals.push(ChainHealthSignalV1::recovered(ChainHealthRuleV1::RpcOutage));
+            }
+            return signals;
+        }
+        self.failed_rounds += 1;
+        if config.enabled && !self.outage && self.failed_rounds >= config.outage_rounds.max(1) {
+            self.outage = true;
+            signals.push(ChainHealthSignalV1::raised(ChainHealthRuleV1::RpcOutage));
+        }
+        signals
+    }
+
+    /// Raise a stall once the head is older than the chain's stall window.
+    /// Suppressed during an RPC outage, which already explains missing heads.
+    pub fn check_stall(
+        &mut self,
+        config: &ChainHealthConfigV1,
+        now: DateTime<Utc>,
+    ) -> Vec<ChainHealthSignalV1> {
+        if !config.enabled || self.stalled || self.outage {
+            return Vec::new();
+        }
+        let Some(last_head_at) = self.last_head_at else {
+            self.last_head_at = Some(now);
+            return Vec::new();
+        };
+        if now - last_head_at < config.stall_after(&self.network) {
+            return Vec::new();
+        }
+        self.stalled = true;
+        vec![ChainHealthSignalV1::raised(ChainHealthRuleV1::HeadStalled)]
+    }
+
+    /// Downstream lag alarms should be paused
+    pub fn unhealthy(&self) -> bool {
+        self.stalled || self.outage
+    }
+}
+
+/// Chain health alert published on [`ChainHealthRuleV1::subject`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AlertChainHealthV1 {
+    pub schema_version: String,
+    pub network: String,
+    pub subnet: String,
+    /// Node config id, e.g. `ethereum-mainnet`
+    pub chain_id: String,
+    pub rule: ChainHealthRuleV1,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleared_rule: Option<ChainHealthRuleV1>,
+    pub last_head: Option<u64>,
+    pub last_head_at: Option<DateTime<Utc>>,
+    pub stall_after_secs: i64,
+    /// Lag alarms for the chain are paused while this is true
+    pub lag_alarms_paused: bool,
+    pub detected_at: DateTime<Utc>,
+    pub source: String,
+}
+
+impl AlertChainHealthV1 {
+    pub fn new(
+        config: &ChainHealthConfigV1,
+        watch: &ChainHeadWatchV1,
+        signal: &ChainHealthSignalV1,
+        detected_at: DateTime<Utc>,
+        source: &str,
+    ) -> Self {
+        Self {
+            schema_version: alert_chain_health_schema_version_v1(),
+            network: watch.network.clone(),
+            subnet: watch.subnet.clone(),
+            chain_id: watch.chain_id.clone(),
+            rule: signal.rule,
+            cleared_rule: signal.cleared_rule,
+            last_head: watch.last_head,
+            last_head_at: watch.last_head_at,
+            stall_after_secs: config.stall_after(&watch.network).num_seconds(),
+            lag_alarms_paused: watch.unhealthy(),
+            detected_at,
+            source: source.to_string(),
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        self.rule.subject(&self.network, &self.subnet)
+    }
+}
+
+pub fn alert_chain_health_schema_version_v1() -> String {
+    "alert_chain_health_v1".to_string()
+}
+
+/// Lag alarm pause for one chain, stored with a TTL under
+/// `chain:health:pause:{network}:{subnet}`
+///
+/// Lag alarms check for the key before firing; it is refreshed every probe
+/// round while the chain is unhealthy and deleted on recovery, so a crashed
+/// provider can't pause alarms past `expires_at`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LagAlarmPauseV1 {
+    pub network: String,
+    pub subnet: String,
+    pub reason: ChainHealthRuleV1,
+    pub paused_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl LagAlarmPauseV1 {
+    /// Pause for the chain's raised condition; `None` while healthy
+    pub fn for_watch(
+        config: &ChainHealthConfigV1,
+        watch: &ChainHeadWatchV1,
+        now: DateTime<Utc>,
+    ) -> Option<Self> {
+        let reason = if watch.outage {
+            ChainHealthRuleV1::RpcOutage
+        } else if watch.stalled {
+            ChainHealthRuleV1::HeadStalled
+        } else {
+            return None;
+        };
+        Some(Self {
+            network: watch.network.clone(),
+            subnet: watch.subnet.clone(),
+            reason,
+            paused_at: now,
+            expires_at: now + Duration::seconds(config.pause_ttl_secs as i64),
+        })
+    }
+
+    pub fn active(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(
+        signals: &[ChainHealthSignalV1],
+    ) -> Vec<(ChainHealthRuleV1, Option<ChainHealthRuleV1>)> {
+        signals.iter().map(|s| (s.rule, s.cleared_rule)).collect()
+    }
+
+    #[test]
+    fn test_stall_window_scales_with_block_time() {
+        let config = ChainHealthConfigV1::default();
+        assert_eq!(config.stall_after("Ethereum").num_seconds(), 120);
+        // 0.25s blocks hit the floor
+        assert_eq!(config.stall_after("arbitrum").num_seconds(), 60);
+        assert_eq!(config.stall_after("unknown").num_seconds(), 120);
+
+        let start = Utc::now();
+        let mut watch = ChainHeadWatchV1::new("Ethereum", "mainnet", "ethereum-mainnet", start);
+        assert!(watch.record_head(100, start).is_empty());
+        let early = start + Duration::seconds(119);
+        assert!(watch.check_stall(&config, early).is_empty());
+
+        let late = start + Duration::seconds(121);
+        assert_eq!(
+            rules(&watch.check_stall(&config, late)),
+            vec![(ChainHealthRuleV1::HeadStalled, None)]
+        );
+        // Raised once, not on every check
+        assert!(watch.check_stall(&config, late).is_empty());
+        assert!(watch.unhealthy());
+
+        // The same head again doesn't clear the stall; a new one does
+        assert!(watch.record_head(100, late).is_empty());
+        assert_eq!(
+            rules(&watch.record_head(101, late)),
+            vec![(
+                ChainHealthRuleV1::Recovered,
+                Some(ChainHealthRuleV1::HeadStalled)
+            )]
+        );
+        assert!(!watch.unhealthy());
+    }
+
+    #[test]
+    fn test_outage_suppresses_stall_and_pauses_lag_alarms() {
+        let config = ChainHealthConfigV1::default();
+        let start = Utc::now();
+        let mut watch = ChainHeadWatchV1::new("Ethereum", "Mainnet", "ethereum-mainnet", start);
+
+        assert!(watch.record_round(&config, true).is_empty());
+        assert!(watch.record_round(&config, true).is_empty());
+        assert_eq!(
+            rules(&watch.record_round(&config, true)),
+            vec![(ChainHealthRuleV1::RpcOutage, None)]
+        );
+        assert!(watch.record_round(&config, true).is_empty());
+
+        let late = start + Duration::seconds(600);
+        assert!(watch.check_stall(&config, late).is_empty());
+
+        let pause = LagAlarmPauseV1::for_watch(&config, &watch, late).unwrap();
+        assert_eq!(pause.reason, ChainHealthRuleV1::RpcOutage);
+        assert_eq!(pause.network, "ethereum");
+        assert!(pause.active(late + Duration::seconds(299)));
+        assert!(!pause.active(late + Duration::seconds(300)));
+
+        assert_eq!(
+            rules(&watch.record_round(&config, false)),
+            vec![(
+                ChainHealthRuleV1::Recovered,
+                Some(ChainHealthRuleV1::RpcOutage)
+            )]
+        );
+        assert!(LagAlarmPauseV1::for_watch(&config, &watch, late).is_none());
+    }
+
+    #[test]
+    fn test_alert_subject_and_payload() {
+        let config = ChainHealthConfigV1::default();
+        let now = Utc::now();
+        let mut watch = ChainHeadWatchV1::new("Ethereum", "Mainnet", "ethereum-mainnet", now);
+        watch.record_head(7, now);
+        watch.stalled = true;
+        let alert = AlertChainHealthV1::new(
+            &config,
+            &watch,
+            &ChainHealthSignalV1::raised(ChainHealthRuleV1::HeadStalled),
+            now,
+            "newheads-evm",
+        );
+        assert_eq!(
+            alert.subject(),
+            "alerts.system.chain.ethereum.mainnet.head_stalled"
+        );
+        assert!(alert.lag_alarms_paused);
+
+        let json = serde_json::to_value(&alert).unwrap();
+        assert_eq!(json["schema_version"], "alert_chain_health_v1");
+        assert_eq!(json["rule"], "head_stalled");
+        assert_eq!(json["last_head"], 7);
+        assert!(json.get("cleared_rule").is_none());
+    }
+}
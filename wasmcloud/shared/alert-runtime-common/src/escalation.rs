@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Subject for acknowledging a delivered notification (API / any client -> scheduler).
+pub const ALERT_ACK_SUBJECT: &str = "alerts.ack";
+
+/// Subject for arming an escalation timer (notification-router -> scheduler).
+///
+/// Lives under `alerts.schedule.>` so requests are persisted by the schedule
+/// requests stream and consumed by the scheduler's durable consumer.
+pub const ALERT_SCHEDULE_ESCALATION_SUBJECT: &str = "alerts.schedule.escalation";
+
+/// Subject for a fired escalation timer (scheduler -> notification-router).
+///
+/// Example: `alerts.escalation.due.inst-123`
+pub fn escalation_due_subject(instance_id: &str) -> String {
+    format!("alerts.escalation.due.{}", instance_id)
+}
+
+/// Per-rule escalation policy.
+///
+/// Each step re-sends the notification to `channels` when it is still
+/// unacknowledged `after_secs` after the original delivery, e.g.
+/// `{"steps": [{"after_secs": 600, "channels": ["telegram"]}]}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EscalationPolicyV1 {
+    #[serde(default)]
+    pub steps: Vec<EscalationStepV1>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EscalationStepV1 {
+    /// Seconds after the original delivery, not after the previous step
+    pub after_secs: i64,
+    pub channels: Vec<String>,
+}
+
+impl EscalationPolicyV1 {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// When step `step` fires for a notification delivered at `sent_at`
+    pub fn due_at(&self, step: usize, sent_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.steps
+            .get(step)
+            .map(|s| sent_at + Duration::seconds(s.after_secs.max(0)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertScheduleEscalationV1 {
+    pub schema_version: String,
+    pub notification_id: String,
+    pub instance_id: String,
+    pub user_id: String,
+    /// Index into the policy's `steps`
+    pub step: usize,
+    pub due_at: DateTime<Utc>,
+    pub requested_at: DateTime<Utc>,
+    pub source: String,
+}
+
+pub fn alert_schedule_escalation_schema_version_v1() -> String {
+    "alert_schedule_escalation_v1".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertEscalationDueV1 {
+    pub schema_version: String,
+    pub notification_id: String,
+    pub instance_id: String,
+    pub user_id: String,
+    pub step: usize,
+    pub due_at: DateTime<Utc>,
+    pub fired_at: DateTime<Utc>,
+}
+
+pub fn alert_escalation_due_schema_version_v1() -> String {
+    "alert_escalation_due_v1".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AlertAckV1 {
+    pub schema_version: String,
+    pub notification_id: String,
+    /// User who acknowledged the notification
+    pub user_id: String,
+    pub acked_at: DateTime<Utc>,
+    pub source: String,
+}
+
+pub fn alert_ack_schema_version_v1() -> String {
+    "alert_ack_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_due_at_is_relative_to_original_delivery() {
+        let policy: EscalationPolicyV1 = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"after_secs": 600, "channels": ["telegram"]},
+                {"after_secs": 1800, "channels": ["webhook", "telegram"]}
+            ]
+        }))
+        .unwrap();
+        let sent_at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+
+        assert_eq!(
+            policy.due_at(0, sent_at).unwrap().timestamp(),
+            1_700_000_600
+        );
+        assert_eq!(
+            policy.due_at(1, sent_at).unwrap().timestamp(),
+            1_700_001_800
+        );
+        assert!(policy.due_at(2, sent_at).is_none());
+        assert_eq!(escalation_due_subject("i1"), "alerts.escalation.due.i1");
+    }
+}
@@ -28,6 +28,7 @@ pub struct EvaluationContextInstanceV1 {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct PartitionV1 {
     pub network: String,
     pub subnet: String,
@@ -35,6 +36,7 @@ pub struct PartitionV1 {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ScheduleV1 {
     pub scheduled_for: DateTime<Utc>,
     pub data_lag_secs: i64,
@@ -57,6 +59,7 @@ pub struct TargetsV1 {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum TxKindV1 {
     Tx,
@@ -64,6 +67,7 @@ pub enum TxKindV1 {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EvaluationTxV1 {
     pub kind: TxKindV1,
     pub hash: String,
@@ -78,6 +82,8 @@ pub struct EvaluationTxV1 {
     pub value_wei: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value_native: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_category_context: Option<String>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub log_index: Option<i64>,
@@ -93,11 +99,79 @@ pub struct EvaluationTxV1 {
     pub topic3: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data: Option<String>,
+    /// Token metadata of an ERC-721 transfer log from a watched collection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nft: Option<NftMetadataV1>,
+    /// Decoded position event of a perpetuals protocol log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perp: Option<PerpEventV1>,
 
     pub block_number: i64,
     pub block_timestamp: DateTime<Utc>,
 }
 
+/// ERC-721 token metadata resolved from `tokenURI`
+///
+/// `display_name` is always set: the metadata `name`, else the collection
+/// label and token id (`Bored Ape #1234`), so templates can use
+/// `{{tx.nft.display_name}}` whether or not resolution succeeded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NftMetadataV1 {
+    pub collection_address: String,
+    /// Decimal token id
+    pub token_id: String,
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Gateway URL of the image (`ipfs://` already rewritten)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_uri: Option<String>,
+}
+
+/// Position event of an on-chain perpetuals protocol (GMX v1, Perp v2)
+///
+/// Amounts are decimal USD strings scaled by the protocol's precision;
+/// signed amounts are negative for losses and paid funding. Templates can
+/// use `{{tx.perp.event_type}}` (`perp_open`, `perp_close`,
+/// `perp_liquidation`, `perp_funding`) and `{{tx.perp.size_delta_usd}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct PerpEventV1 {
+    /// `gmx_v1` or `perp_v2`
+    pub protocol: String,
+    pub event_type: String,
+    pub event_name: String,
+    pub account: String,
+    /// Index token (GMX) or base token (Perp v2)
+    pub market: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collateral_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_long: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_delta_usd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collateral_delta_usd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_usd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_usd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realized_pnl_usd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub funding_payment_usd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liquidator: Option<String>,
+    /// Protocol position id (GMX `bytes32` key)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_key: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvaluationContextV1 {
     pub schema_version: String,
@@ -30,6 +30,8 @@ pub struct EvmTriggerPruningV1 {
     pub to: TriggerAddressFilterV1,
     pub method: TriggerMethodFilterV1,
     pub event: TriggerEventFilterV1,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_category_contexts: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
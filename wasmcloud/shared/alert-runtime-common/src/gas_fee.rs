@@ -0,0 +1,401 @@
+//! Built-in gas fee alert rules.
+//!
+//! These are system rules generated by the pipeline rather than by user
+//! templates: the raw transaction stage summarizes every EVM block's base fee
+//! and median priority fee, [`GasFeeDetectorV1`] tracks them per chain, and
+//! each signal is published on `alerts.system.gas.{network}.{subnet}.{rule}`
+//! so consumers subscribe per chain and per rule.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::PartitionV1;
+
+/// Prefix of the per-chain, per-rule subjects
+pub const ALERT_SYSTEM_GAS_SUBJECT_PREFIX: &str = "alerts.system.gas";
+
+const WEI_PER_GWEI: f64 = 1_000_000_000.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GasAlertRuleV1 {
+    /// Base fee above the threshold for `sustained_blocks` consecutive blocks
+    BaseFeeSustained,
+    /// Median priority fee jumped to `spike_multiplier` x its baseline
+    PriorityFeeSpike,
+    /// A raised condition stayed clear for `normal_blocks` blocks
+    FeeNormalized,
+}
+
+impl GasAlertRuleV1 {
+    pub const ALL: [Self; 3] = [
+        Self::BaseFeeSustained,
+        Self::PriorityFeeSpike,
+        Self::FeeNormalized,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BaseFeeSustained => "base_fee_sustained",
+            Self::PriorityFeeSpike => "priority_fee_spike",
+            Self::FeeNormalized => "fee_normalized",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::BaseFeeSustained => "Base fee stayed above the chain threshold",
+            Self::PriorityFeeSpike => {
+                "Sudden priority fee spike (e.g. NFT mint or liquidation cascade)"
+            }
+            Self::FeeNormalized => "Base fee or priority fee returned to normal",
+        }
+    }
+
+    /// Subject the rule publishes on for one chain; use `*` to subscribe to
+    /// every chain or rule
+    pub fn subject(&self, network: &str, subnet: &str) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            ALERT_SYSTEM_GAS_SUBJECT_PREFIX,
+            network.to_lowercase(),
+            subnet.to_lowercase(),
+            self.as_str()
+        )
+    }
+}
+
+/// Per-chain thresholds; defaults apply when no config is stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasAlertConfigV1 {
+    pub enabled: bool,
+    pub base_fee_threshold_gwei: f64,
+    pub sustained_blocks: u32,
+    pub spike_multiplier: f64,
+    /// Spikes below this median priority fee are ignored
+    pub min_spike_priority_fee_gwei: f64,
+    /// EWMA weight of each block in the priority fee baseline
+    pub baseline_alpha: f64,
+    pub normal_blocks: u32,
+}
+
+impl Default for GasAlertConfigV1 {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_fee_threshold_gwei: 50.0,
+            sustained_blocks: 5,
+            spike_multiplier: 3.0,
+            min_spike_priority_fee_gwei: 2.0,
+            baseline_alpha: 0.1,
+            normal_blocks: 5,
+        }
+    }
+}
+
+/// Fee summary of one block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockGasSampleV1 {
+    pub block_number: u64,
+    pub base_fee_per_gas: Option<u128>,
+    pub median_priority_fee_per_gas: Option<u128>,
+}
+
+/// Detector state carried between blocks (stored in Redis by the caller)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasFeeDetectorV1 {
+    pub last_block: u64,
+    pub base_fee_high_blocks: u32,
+    pub base_fee_alerting: bool,
+    pub base_fee_clear_blocks: u32,
+    /// Priority fee baseline in wei; frozen while a spike is raised
+    pub priority_fee_baseline: Option<f64>,
+    pub priority_fee_alerting: bool,
+    pub priority_fee_clear_blocks: u32,
+}
+
+/// One rule firing on a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasSignalV1 {
+    pub rule: GasAlertRuleV1,
+    /// Rule cleared by a [`GasAlertRuleV1::FeeNormalized`] signal
+    pub cleared_rule: Option<GasAlertRuleV1>,
+    pub baseline_priority_fee_per_gas: Option<u128>,
+}
+
+impl GasFeeDetectorV1 {
+    /// Advance the detector by one block; blocks at or below the last seen
+    /// block (re-deliveries, reorg replays) are ignored
+    pub fn observe(
+        &mut self,
+        config: &GasAlertConfigV1,
+        sample: &BlockGasSampleV1,
+    ) -> Vec<GasSignalV1> {
+        let mut signals = Vec::new();
+        if !config.enabled || (self.last_block != 0 && sample.block_number <= self.last_block) {
+            return signals;
+        }
+        self.last_block = sample.block_number;
+
+        if let Some(base_fee) = sample.base_fee_per_gas {
+            let high = base_fee as f64 >= config.base_fee_threshold_gwei * WEI_PER_GWEI;
+            if high {
+                self.base_fee_high_blocks += 1;
+                self.base_fee_clear_blocks = 0;
+                if !self.base_fee_alerting && self.base_fee_high_blocks >= config.sustained_blocks {
+                    self.base_fee_alerting = true;
+                    signals.push(GasSignalV1 {
+                        rule: GasAlertRuleV1::BaseFeeSustained,
+                        cleared_rule: None,
+                        baseline_priority_fee_per_gas: None,
+                    });
+                }
+            } else {
+                self.base_fee_high_blocks = 0;
+                if self.base_fee_alerting {
+                    self.base_fee_clear_blocks += 1;
+                    if self.base_fee_clear_blocks >= config.normal_blocks {
+                        self.base_fee_alerting = false;
+                        self.base_fee_clear_blocks = 0;
+                        signals.push(GasSignalV1 {
+                            rule: GasAlertRuleV1::FeeNormalized,
+                            cleared_rule: Some(GasAlertRuleV1::BaseFeeSustained),
+                            baseline_priority_fee_per_gas: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(tip) = sample.median_priority_fee_per_gas {
+            let tip = tip as f64;
+            let Some(baseline) = self.priority_fee_baseline else {
+                self.priority_fee_baseline = Some(tip);
+                return signals;
+            };
+            let spiking = tip >= baseline * config.spike_multiplier
+                && tip >= config.min_spike_priority_fee_gwei * WEI_PER_GWEI;
+            if spiking {
+                self.priority_fee_clear_blocks = 0;
+                if !self.priority_fee_alerting {
+                    self.priority_fee_alerting = true;
+                    signals.push(GasSignalV1 {
+                        rule: GasAlertRuleV1::PriorityFeeSpike,
+                        cleared_rule: None,
+                        baseline_priority_fee_per_gas: Some(baseline as u128),
+                    });
+                }
+            } else {
+                let alpha = config.baseline_alpha.clamp(0.0, 1.0);
+                self.priority_fee_baseline = Some(baseline + alpha * (tip - baseline));
+                if self.priority_fee_alerting {
+                    self.priority_fee_clear_blocks += 1;
+                    if self.priority_fee_clear_blocks >= config.normal_blocks {
+                        self.priority_fee_alerting = false;
+                        self.priority_fee_clear_blocks = 0;
+                        signals.push(GasSignalV1 {
+                            rule: GasAlertRuleV1::FeeNormalized,
+                            cleared_rule: Some(GasAlertRuleV1::PriorityFeeSpike),
+                            baseline_priority_fee_per_gas: Some(baseline as u128),
+                        });
+                    }
+                }
+            }
+        }
+
+        signals
+    }
+}
+
+/// Tip paid per gas: `min(max_priority_fee, max_fee - base_fee)` for EIP-1559
+/// transactions, `gas_price - base_fee` for legacy ones
+pub fn effective_priority_fee(
+    base_fee_per_gas: u128,
+    gas_price: Option<u128>,
+    max_fee_per_gas: Option<u128>,
+    max_priority_fee_per_gas: Option<u128>,
+) -> Option<u128> {
+    match (max_fee_per_gas, max_priority_fee_per_gas) {
+        (Some(max_fee), Some(max_tip)) => {
+            Some(max_tip.min(max_fee.saturating_sub(base_fee_per_gas)))
+        }
+        _ => gas_price.map(|price| price.saturating_sub(base_fee_per_gas)),
+    }
+}
+
+pub fn median(values: &mut [u128]) -> Option<u128> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(values[values.len() / 2])
+}
+
+/// Gas alert published on [`GasAlertRuleV1::subject`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AlertGasFeeV1 {
+    pub schema_version: String,
+    pub partition: PartitionV1,
+    pub rule: GasAlertRuleV1,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleared_rule: Option<GasAlertRuleV1>,
+    pub block_number: u64,
+    /// Wei per gas, as decimal strings
+    pub base_fee_per_gas: Option<String>,
+    pub median_priority_fee_per_gas: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_priority_fee_per_gas: Option<String>,
+    pub base_fee_threshold_gwei: f64,
+    pub detected_at: DateTime<Utc>,
+    pub source: String,
+}
+
+impl AlertGasFeeV1 {
+    pub fn new(
+        partition: PartitionV1,
+        config: &GasAlertConfigV1,
+        sample: &BlockGasSampleV1,
+        signal: &GasSignalV1,
+        detected_at: DateTime<Utc>,
+        source: &str,
+    ) -> Self {
+        Self {
+            schema_version: alert_gas_fee_schema_version_v1(),
+            partition,
+            rule: signal.rule,
+            cleared_rule: signal.cleared_rule,
+            block_number: sample.block_number,
+            base_fee_per_gas: sample.base_fee_per_gas.map(|v| v.to_string()),
+            median_priority_fee_per_gas: sample.median_priority_fee_per_gas.map(|v| v.to_string()),
+            baseline_priority_fee_per_gas: signal
+                .baseline_priority_fee_per_gas
+                .map(|v| v.to_string()),
+            base_fee_threshold_gwei: config.base_fee_threshold_gwei,
+            detected_at,
+            source: source.to_string(),
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        self.rule
+            .subject(&self.partition.network, &self.partition.subnet)
+    }
+}
+
+pub fn alert_gas_fee_schema_version_v1() -> String {
+    "alert_gas_fee_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn sample(block_number: u64, base_fee_gwei: u128, tip_gwei: u128) -> BlockGasSampleV1 {
+        BlockGasSampleV1 {
+            block_number,
+            base_fee_per_gas: Some(base_fee_gwei * GWEI),
+            median_priority_fee_per_gas: Some(tip_gwei * GWEI),
+        }
+    }
+
+    fn rules(signals: &[GasSignalV1]) -> Vec<(GasAlertRuleV1, Option<GasAlertRuleV1>)> {
+        signals.iter().map(|s| (s.rule, s.cleared_rule)).collect()
+    }
+
+    #[test]
+    fn test_base_fee_sustained_then_normalized() {
+        let config = GasAlertConfigV1 {
+            sustained_blocks: 3,
+            normal_blocks: 2,
+            ..Default::default()
+        };
+        let mut detector = GasFeeDetectorV1::default();
+        let fired: Vec<_> = (1..=4)
+            .flat_map(|n| detector.observe(&config, &sample(n, 80, 1)))
+            .collect();
+        // Fires once, on the third high block
+        assert_eq!(
+            rules(&fired),
+            vec![(GasAlertRuleV1::BaseFeeSustained, None)]
+        );
+        assert_eq!(detector.base_fee_high_blocks, 4);
+
+        // Replayed block is ignored
+        assert!(detector.observe(&config, &sample(4, 10, 1)).is_empty());
+        assert!(detector.observe(&config, &sample(5, 10, 1)).is_empty());
+        assert_eq!(
+            rules(&detector.observe(&config, &sample(6, 10, 1))),
+            vec![(
+                GasAlertRuleV1::FeeNormalized,
+                Some(GasAlertRuleV1::BaseFeeSustained)
+            )]
+        );
+        assert!(!detector.base_fee_alerting);
+    }
+
+    #[test]
+    fn test_priority_fee_spike_keeps_baseline_until_normal() {
+        let config = GasAlertConfigV1 {
+            normal_blocks: 1,
+            ..Default::default()
+        };
+        let mut detector = GasFeeDetectorV1::default();
+        assert!(detector.observe(&config, &sample(1, 10, 2)).is_empty());
+        assert!(detector.observe(&config, &sample(2, 10, 2)).is_empty());
+
+        let spike = detector.observe(&config, &sample(3, 10, 40));
+        assert_eq!(
+            rules(&spike),
+            vec![(GasAlertRuleV1::PriorityFeeSpike, None)]
+        );
+        assert_eq!(spike[0].baseline_priority_fee_per_gas, Some(2 * GWEI));
+        // Still spiking: no repeat and the baseline does not chase the spike
+        assert!(detector.observe(&config, &sample(4, 10, 50)).is_empty());
+        assert_eq!(detector.priority_fee_baseline, Some((2 * GWEI) as f64));
+
+        assert_eq!(
+            rules(&detector.observe(&config, &sample(5, 10, 2))),
+            vec![(
+                GasAlertRuleV1::FeeNormalized,
+                Some(GasAlertRuleV1::PriorityFeeSpike)
+            )]
+        );
+
+        // Tiny absolute tips never count as a spike
+        let mut quiet = GasFeeDetectorV1::default();
+        quiet.observe(&config, &sample(1, 10, 0));
+        assert!(quiet.observe(&config, &sample(2, 10, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_effective_priority_fee_and_subject() {
+        let base = 30 * GWEI;
+        assert_eq!(
+            effective_priority_fee(base, None, Some(35 * GWEI), Some(10 * GWEI)),
+            Some(5 * GWEI)
+        );
+        assert_eq!(
+            effective_priority_fee(base, Some(32 * GWEI), None, None),
+            Some(2 * GWEI)
+        );
+        assert_eq!(median(&mut [5, 1, 3]), Some(3));
+        assert_eq!(median(&mut []), None);
+
+        assert_eq!(
+            GasAlertRuleV1::PriorityFeeSpike.subject("ETH", "mainnet"),
+            "alerts.system.gas.eth.mainnet.priority_fee_spike"
+        );
+        let json = serde_json::to_value(GasAlertConfigV1::default()).unwrap();
+        let parsed: GasAlertConfigV1 =
+            serde_json::from_str(r#"{"base_fee_threshold_gwei": 20}"#).unwrap();
+        assert_eq!(json["sustained_blocks"], 5);
+        assert_eq!(parsed.base_fee_threshold_gwei, 20.0);
+        assert_eq!(parsed.spike_multiplier, 3.0);
+    }
+}
@@ -5,20 +5,30 @@
 //! - `docs/prd/schemas/SCHEMA-EvaluationContext.md`
 //! - `docs/prd/wasmcloud/PRD-NATS-Subjects-Alert-System.md`
 
+pub mod chain_health;
+pub mod escalation;
 pub mod evaluation_context;
 pub mod executable;
+pub mod gas_fee;
 pub mod jobs;
 pub mod keys;
 pub mod polars_eval;
 pub mod schedule;
+pub mod slashing;
 pub mod template;
 pub mod triggered;
+pub mod upgrade_risk;
 
+pub use chain_health::*;
+pub use escalation::*;
 pub use evaluation_context::*;
 pub use executable::*;
+pub use gas_fee::*;
 pub use jobs::*;
 pub use keys::*;
 pub use polars_eval::*;
 pub use schedule::*;
+pub use slashing::*;
 pub use template::*;
 pub use triggered::*;
+pub use upgrade_risk::*;
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::evaluation_context::{PartitionV1, TxKindV1};
+use crate::evaluation_context::{NftMetadataV1, PartitionV1, PerpEventV1, TxKindV1};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -22,10 +22,16 @@ pub struct EvmTxV1 {
     pub method_selector: Option<String>,
     pub value_wei: String,
     pub value_native: f64,
+    /// Processor classification of the transfer, e.g. [`INTERNAL_EXCHANGE_OPS`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_category_context: Option<String>,
     pub block_number: i64,
     pub block_timestamp: DateTime<Utc>,
 }
 
+/// `transfer_category_context` of moves between one entity's cold and hot wallets
+pub const INTERNAL_EXCHANGE_OPS: &str = "internal_exchange_ops";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvmLogV1 {
     pub transaction_hash: String,
@@ -39,6 +45,10 @@ pub struct EvmLogV1 {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub topic3: Option<String>,
     pub data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nft: Option<NftMetadataV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perp: Option<PerpEventV1>,
     pub block_number: i64,
     pub block_timestamp: DateTime<Utc>,
 }
@@ -0,0 +1,334 @@
+//! Validator slashing alerts.
+//!
+//! The newheads provider follows each configured beacon node and extracts the
+//! `proposer_slashings` and `attester_slashings` of every new block. Each
+//! slashed validator becomes a [`SlashingEventV1`], recorded to
+//! `ducklake.slashing_events.{network}.{subnet}.write`. When the validator or
+//! its withdrawal address is in the chain's [`SlashingWatchV1`], an
+//! [`AlertSlashingV1`] is also published on
+//! `alerts.system.slashing.{network}.{subnet}.{kind}`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the per-chain, per-kind subjects
+pub const ALERT_SYSTEM_SLASHING_SUBJECT_PREFIX: &str = "alerts.system.slashing";
+
+/// Beacon chain slots per epoch
+pub const SLOTS_PER_EPOCH: u64 = 32;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SlashingKindV1 {
+    /// Two signed block headers for the same slot
+    ProposerSlashing,
+    /// Two conflicting attestations (double vote or surround vote)
+    AttesterSlashing,
+}
+
+impl SlashingKindV1 {
+    pub const ALL: [Self; 2] = [Self::ProposerSlashing, Self::AttesterSlashing];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ProposerSlashing => "proposer_slashing",
+            Self::AttesterSlashing => "attester_slashing",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ProposerSlashing => "Validator proposed two different blocks for one slot",
+            Self::AttesterSlashing => "Validator signed two conflicting attestations",
+        }
+    }
+
+    /// Subject the kind publishes on for one chain; use `*` to subscribe to
+    /// every chain or kind
+    pub fn subject(&self, network: &str, subnet: &str) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            ALERT_SYSTEM_SLASHING_SUBJECT_PREFIX,
+            network.to_lowercase(),
+            subnet.to_lowercase(),
+            self.as_str()
+        )
+    }
+}
+
+/// Beacon nodes to follow; defaults apply when no config is stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlashingConfigV1 {
+    pub enabled: bool,
+    /// Seconds between polls of each beacon node
+    pub poll_interval_secs: u64,
+    /// Blocks fetched per chain and poll; a node further behind skips ahead
+    pub max_slots_per_round: u64,
+    /// Beacon API base URL by `{network}:{subnet}`
+    pub beacon_urls: HashMap<String, String>,
+}
+
+impl Default for SlashingConfigV1 {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 12,
+            max_slots_per_round: SLOTS_PER_EPOCH,
+            beacon_urls: HashMap::new(),
+        }
+    }
+}
+
+impl SlashingConfigV1 {
+    /// Followed chains as `(network, subnet, beacon_url)`
+    pub fn chains(&self) -> Vec<(String, String, String)> {
+        let mut chains: Vec<_> = self
+            .beacon_urls
+            .iter()
+            .filter_map(|(chain, url)| {
+                let (network, subnet) = chain.split_once(':')?;
+                Some((
+                    network.to_lowercase(),
+                    subnet.to_lowercase(),
+                    url.trim_end_matches('/').to_string(),
+                ))
+            })
+            .filter(|(_, _, url)| !url.is_empty())
+            .collect();
+        chains.sort();
+        chains
+    }
+}
+
+/// Why a slashed validator is watched
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SlashingMatchV1 {
+    Validator,
+    WithdrawalAddress,
+}
+
+/// Watched validators of one chain, stored under
+/// `slashing:watch:{network}:{subnet}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlashingWatchV1 {
+    /// Validator indices or `0x` BLS public keys
+    pub validators: Vec<String>,
+    /// Execution-layer withdrawal addresses
+    pub withdrawal_addresses: Vec<String>,
+}
+
+impl SlashingWatchV1 {
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty() && self.withdrawal_addresses.is_empty()
+    }
+
+    /// Match a slashed validator by index, public key or withdrawal address
+    pub fn matched_by(&self, event: &SlashingEventV1) -> Option<SlashingMatchV1> {
+        let index = event.validator_index.to_string();
+        let pubkey = event.validator_pubkey.as_deref().map(str::to_lowercase);
+        let by_validator = self.validators.iter().any(|validator| {
+            let validator = validator.trim().to_lowercase();
+            validator == index || pubkey.as_deref() == Some(validator.as_str())
+        });
+        if by_validator {
+            return Some(SlashingMatchV1::Validator);
+        }
+        let address = event.withdrawal_address.as_deref()?.to_lowercase();
+        self.withdrawal_addresses
+            .iter()
+            .any(|watched| watched.trim().to_lowercase() == address)
+            .then_some(SlashingMatchV1::WithdrawalAddress)
+    }
+}
+
+/// One validator slashed in one beacon block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlashingEventV1 {
+    pub network: String,
+    pub subnet: String,
+    /// Slot of the block that included the slashing
+    pub slot: u64,
+    pub kind: SlashingKindV1,
+    pub validator_index: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_pubkey: Option<String>,
+    /// From `0x01` / `0x02` withdrawal credentials; `None` for BLS credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawal_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<DateTime<Utc>>,
+}
+
+impl SlashingEventV1 {
+    pub fn epoch(&self) -> u64 {
+        self.slot / SLOTS_PER_EPOCH
+    }
+}
+
+/// Slashing alert published on [`SlashingKindV1::subject`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AlertSlashingV1 {
+    pub schema_version: String,
+    pub network: String,
+    pub subnet: String,
+    pub kind: SlashingKindV1,
+    /// Always `critical`: a slashed validator is force-exited and loses stake
+    pub severity: String,
+    pub summary: String,
+    pub validator_index: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_pubkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawal_address: Option<String>,
+    pub matched_by: SlashingMatchV1,
+    pub slot: u64,
+    pub epoch: u64,
+    pub block_timestamp: Option<DateTime<Utc>>,
+    pub detected_at: DateTime<Utc>,
+    pub source: String,
+}
+
+impl AlertSlashingV1 {
+    pub fn new(
+        event: &SlashingEventV1,
+        matched_by: SlashingMatchV1,
+        detected_at: DateTime<Utc>,
+        source: &str,
+    ) -> Self {
+        Self {
+            schema_version: alert_slashing_schema_version_v1(),
+            network: event.network.clone(),
+            subnet: event.subnet.clone(),
+            kind: event.kind,
+            severity: "critical".to_string(),
+            summary: format!(
+                "Validator {} slashed on {} {} at slot {}: {}",
+                event.validator_index,
+                event.network,
+                event.subnet,
+                event.slot,
+                event.kind.description()
+            ),
+            validator_index: event.validator_index,
+            validator_pubkey: event.validator_pubkey.clone(),
+            withdrawal_address: event.withdrawal_address.clone(),
+            matched_by,
+            slot: event.slot,
+            epoch: event.epoch(),
+            block_timestamp: event.block_timestamp,
+            detected_at,
+            source: source.to_string(),
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        self.kind.subject(&self.network, &self.subnet)
+    }
+}
+
+pub fn alert_slashing_schema_version_v1() -> String {
+    "alert_slashing_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> SlashingEventV1 {
+        SlashingEventV1 {
+            network: "ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            slot: 8_000_031,
+            kind: SlashingKindV1::AttesterSlashing,
+            validator_index: 123_456,
+            validator_pubkey: Some("0xA1B2".to_string()),
+            withdrawal_address: Some("0x00000000219ab540356cbb839cbe05303d7705fa".to_string()),
+            block_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_config_chains() {
+        let config: SlashingConfigV1 = serde_json::from_value(serde_json::json!({
+            "beacon_urls": {
+                "Ethereum:Mainnet": "http://beacon:5052/",
+                "ethereum:holesky": "",
+                "malformed": "http://other:5052"
+            }
+        }))
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.max_slots_per_round, 32);
+        assert_eq!(
+            config.chains(),
+            vec![(
+                "ethereum".to_string(),
+                "mainnet".to_string(),
+                "http://beacon:5052".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_watch_matches_index_pubkey_and_withdrawal_address() {
+        let event = event();
+        assert_eq!(SlashingWatchV1::default().matched_by(&event), None);
+
+        let by_index = SlashingWatchV1 {
+            validators: vec!["123456".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            by_index.matched_by(&event),
+            Some(SlashingMatchV1::Validator)
+        );
+
+        let by_pubkey = SlashingWatchV1 {
+            validators: vec!["0xa1b2".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            by_pubkey.matched_by(&event),
+            Some(SlashingMatchV1::Validator)
+        );
+
+        let by_address = SlashingWatchV1 {
+            validators: vec!["1".to_string()],
+            withdrawal_addresses: vec!["0x00000000219AB540356cBB839Cbe05303d7705Fa".to_string()],
+        };
+        assert_eq!(
+            by_address.matched_by(&event),
+            Some(SlashingMatchV1::WithdrawalAddress)
+        );
+    }
+
+    #[test]
+    fn test_alert_subject_and_payload() {
+        let event = event();
+        let alert = AlertSlashingV1::new(
+            &event,
+            SlashingMatchV1::Validator,
+            Utc::now(),
+            "newheads-evm",
+        );
+        assert_eq!(
+            alert.subject(),
+            "alerts.system.slashing.ethereum.mainnet.attester_slashing"
+        );
+        assert_eq!(alert.epoch, 250_000);
+
+        let json = serde_json::to_value(&alert).unwrap();
+        assert_eq!(json["schema_version"], "alert_slashing_v1");
+        assert_eq!(json["kind"], "attester_slashing");
+        assert_eq!(json["severity"], "critical");
+        assert_eq!(json["matched_by"], "validator");
+    }
+}
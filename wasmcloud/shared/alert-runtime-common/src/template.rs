@@ -77,6 +77,9 @@ pub struct TriggerV1 {
     pub method: TriggerMethodFilterV1,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub event: Option<TriggerEventFilterV1>,
+    /// Skip transactions whose `transfer_category_context` is listed here
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_category_contexts: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -4,12 +4,14 @@ use serde_json::Value;
 use crate::evaluation_context::{EvaluationTxV1, PartitionV1, ScheduleV1};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct AlertTriggeredMatchV1 {
     pub target_key: String,
     pub match_context: Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct AlertTriggeredBatchV1 {
     pub schema_version: String,
     pub job_id: String,
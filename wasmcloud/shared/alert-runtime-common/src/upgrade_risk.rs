@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::PartitionV1;
+
+/// Subject for proxy upgrades whose simulated behavior diverged (evm-logs-ingestion -> alerting).
+pub const ALERT_UPGRADE_RISK_SUBJECT: &str = "alerts.upgrade_risk";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DivergenceKindV1 {
+    /// Call succeeded on one implementation and reverted on the other
+    Status,
+    ReturnData,
+    /// Emitted events differ (count, emitter or topics)
+    Events,
+}
+
+/// One canonical call whose outcome differs between the implementations
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CallDivergenceV1 {
+    /// Call label, e.g. `balanceOf(probe)`
+    pub call: String,
+    pub kind: DivergenceKindV1,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AlertUpgradeRiskV1 {
+    pub schema_version: String,
+    pub partition: PartitionV1,
+    pub proxy_address: String,
+    /// `None` when the proxy had no implementation before the upgrade
+    pub old_implementation: Option<String>,
+    pub new_implementation: String,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    /// Target keys of the proxy, for routing to watching instances
+    pub candidate_target_keys: Vec<String>,
+    pub divergences: Vec<CallDivergenceV1>,
+    pub detected_at: DateTime<Utc>,
+    pub source: String,
+}
+
+pub fn alert_upgrade_risk_schema_version_v1() -> String {
+    "alert_upgrade_risk_v1".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_risk_roundtrip() {
+        let event = AlertUpgradeRiskV1 {
+            schema_version: alert_upgrade_risk_schema_version_v1(),
+            partition: PartitionV1 {
+                network: "ETH".to_string(),
+                subnet: "mainnet".to_string(),
+                chain_id: 1,
+            },
+            proxy_address: "0xproxy".to_string(),
+            old_implementation: Some("0xold".to_string()),
+            new_implementation: "0xnew".to_string(),
+            transaction_hash: "0xtx".to_string(),
+            block_number: 19_000_000,
+            candidate_target_keys: vec!["ETH:mainnet:0xproxy".to_string()],
+            divergences: vec![CallDivergenceV1 {
+                call: "transfer(probe, 0)".to_string(),
+                kind: DivergenceKindV1::Status,
+                old: "success".to_string(),
+                new: "revert".to_string(),
+            }],
+            detected_at: Utc::now(),
+            source: "evm_logs_ingestion".to_string(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["divergences"][0]["kind"], "status");
+        let parsed: AlertUpgradeRiskV1 = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.divergences, event.divergences);
+        assert_eq!(parsed.old_implementation.as_deref(), Some("0xold"));
+    }
+}
@@ -0,0 +1,359 @@
+//! Persistent per-chain block processing checkpoints.
+//!
+//! A checkpoint records the last block a chain's ingest actor fully handed
+//! off: every per-transaction message for the block was published before the
+//! checkpoint moved. It lives under `checkpoint:block:{network}-{subnet}`
+//! (`retention_policy::BLOCK_CHECKPOINT`, the same suffix as the Django node
+//! config) and is the single source of truth for ingest progress:
+//!
+//! - the ingest actor asks [`handoff`] before processing a head, so a
+//!   redelivered or replayed block that was already handed off is skipped
+//!   instead of being published twice, and calls [`advance`] only after the
+//!   last publish for the block succeeded
+//! - the gap detector is [`handoff`] itself: a head more than one block past
+//!   the checkpoint reports the skipped range, and [`advance`] keeps it in
+//!   `pending_gaps` until every block in it has been handed off
+//! - the backfill orchestrator takes its work from
+//!   [`BlockCheckpointV1::backfill_ranges`] and feeds the blocks through the
+//!   same handoff, which closes the gaps as they are processed
+//! - health APIs report `block_number`, [`BlockCheckpointV1::lag`] and the
+//!   pending gaps straight from the stored checkpoint
+//!
+//! A head at the checkpoint's height with a different hash is a reorg and is
+//! processed again; deeper reorgs are left to the reorg handling downstream.
+
+use serde::{Deserialize, Serialize};
+
+pub const SCHEMA_VERSION: &str = "block_checkpoint_v1";
+
+/// Most gap ranges kept per chain; older ranges are dropped and counted
+pub const MAX_PENDING_GAPS: usize = 64;
+
+/// Redis key of a chain's checkpoint
+pub fn checkpoint_key(network: &str, subnet: &str) -> String {
+    retention_policy::BLOCK_CHECKPOINT.key(&format!("{}-{}", network, subnet))
+}
+
+/// Inclusive range of block numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockRange {
+    pub from: u64,
+    pub to: u64,
+}
+
+impl BlockRange {
+    pub fn len(&self) -> u64 {
+        self.to - self.from + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to < self.from
+    }
+
+    pub fn contains(&self, block_number: u64) -> bool {
+        (self.from..=self.to).contains(&block_number)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockCheckpointV1 {
+    pub schema_version: String,
+    pub network: String,
+    pub subnet: String,
+    /// Highest block whose messages were all published
+    pub block_number: u64,
+    pub block_hash: String,
+    pub tx_count: u32,
+    /// Ranges below `block_number` that were skipped and not yet backfilled,
+    /// oldest first
+    #[serde(default)]
+    pub pending_gaps: Vec<BlockRange>,
+    /// Blocks in gap ranges dropped once `MAX_PENDING_GAPS` was exceeded
+    #[serde(default)]
+    pub dropped_gap_blocks: u64,
+    pub updated_at: String,
+}
+
+impl BlockCheckpointV1 {
+    /// Blocks between the checkpoint and the chain head
+    pub fn lag(&self, head_block: u64) -> u64 {
+        head_block.saturating_sub(self.block_number)
+    }
+
+    /// Blocks waiting for backfill
+    pub fn pending_gap_blocks(&self) -> u64 {
+        self.pending_gaps.iter().map(BlockRange::len).sum()
+    }
+
+    /// Pending gaps split into chunks of at most `max_blocks`, oldest first
+    pub fn backfill_ranges(&self, max_blocks: u64) -> Vec<BlockRange> {
+        let max_blocks = max_blocks.max(1);
+        let mut ranges = Vec::new();
+        for gap in &self.pending_gaps {
+            let mut from = gap.from;
+            while from <= gap.to {
+                let to = gap.to.min(from.saturating_add(max_blocks - 1));
+                ranges.push(BlockRange { from, to });
+                if to == u64::MAX {
+                    break;
+                }
+                from = to + 1;
+            }
+        }
+        ranges
+    }
+
+    fn record_gap(&mut self, gap: BlockRange) {
+        self.pending_gaps.push(gap);
+        while self.pending_gaps.len() > MAX_PENDING_GAPS {
+            let dropped = self.pending_gaps.remove(0);
+            self.dropped_gap_blocks += dropped.len();
+        }
+    }
+
+    /// Remove one backfilled block from the pending gaps; false if it was not pending
+    fn fill_gap(&mut self, block_number: u64) -> bool {
+        let Some(index) = self
+            .pending_gaps
+            .iter()
+            .position(|gap| gap.contains(block_number))
+        else {
+            return false;
+        };
+        let gap = self.pending_gaps.remove(index);
+        let mut rest = Vec::with_capacity(2);
+        if block_number > gap.from {
+            rest.push(BlockRange {
+                from: gap.from,
+                to: block_number - 1,
+            });
+        }
+        if block_number < gap.to {
+            rest.push(BlockRange {
+                from: block_number + 1,
+                to: gap.to,
+            });
+        }
+        for (offset, range) in rest.into_iter().enumerate() {
+            self.pending_gaps.insert(index + offset, range);
+        }
+        true
+    }
+}
+
+/// What to do with an incoming block, given the chain's checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handoff {
+    /// Next block, first block for the chain or a pending backfill block
+    Process,
+    /// The head jumped past the checkpoint; process it and backfill the range
+    Gap(BlockRange),
+    /// Same height as the checkpoint with a different hash
+    Reorg,
+    /// Already handed off; publishing it again would duplicate messages
+    AlreadyProcessed,
+}
+
+impl Handoff {
+    pub fn should_process(&self) -> bool {
+        !matches!(self, Self::AlreadyProcessed)
+    }
+}
+
+pub fn handoff(
+    checkpoint: Option<&BlockCheckpointV1>,
+    block_number: u64,
+    block_hash: &str,
+) -> Handoff {
+    let Some(checkpoint) = checkpoint else {
+        return Handoff::Process;
+    };
+    if block_number > checkpoint.block_number.saturating_add(1) {
+        return Handoff::Gap(BlockRange {
+            from: checkpoint.block_number + 1,
+            to: block_number - 1,
+        });
+    }
+    if block_number > checkpoint.block_number {
+        return Handoff::Process;
+    }
+    if block_number == checkpoint.block_number {
+        return if checkpoint.block_hash.eq_ignore_ascii_case(block_hash) {
+            Handoff::AlreadyProcessed
+        } else {
+            Handoff::Reorg
+        };
+    }
+    if checkpoint
+        .pending_gaps
+        .iter()
+        .any(|gap| gap.contains(block_number))
+    {
+        Handoff::Process
+    } else {
+        Handoff::AlreadyProcessed
+    }
+}
+
+/// Block whose messages have all been published
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandedOffBlock<'a> {
+    pub network: &'a str,
+    pub subnet: &'a str,
+    pub block_number: u64,
+    pub block_hash: &'a str,
+    pub tx_count: u32,
+}
+
+/// Checkpoint after `block` was handed off. Call only once every message for
+/// the block was published; a block above the checkpoint moves it forward
+/// (recording any skipped range), one below it closes its gap.
+pub fn advance(
+    checkpoint: Option<BlockCheckpointV1>,
+    block: &HandedOffBlock,
+    updated_at: &str,
+) -> BlockCheckpointV1 {
+    let mut checkpoint = match checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => {
+            return BlockCheckpointV1 {
+                schema_version: SCHEMA_VERSION.to_string(),
+                network: block.network.to_string(),
+                subnet: block.subnet.to_string(),
+                block_number: block.block_number,
+                block_hash: block.block_hash.to_string(),
+                tx_count: block.tx_count,
+                pending_gaps: Vec::new(),
+                dropped_gap_blocks: 0,
+                updated_at: updated_at.to_string(),
+            }
+        }
+    };
+
+    if let Handoff::Gap(gap) = handoff(Some(&checkpoint), block.block_number, block.block_hash) {
+        checkpoint.record_gap(gap);
+    }
+    if block.block_number >= checkpoint.block_number {
+        checkpoint.block_number = block.block_number;
+        checkpoint.block_hash = block.block_hash.to_string();
+        checkpoint.tx_count = block.tx_count;
+    } else {
+        checkpoint.fill_gap(block.block_number);
+    }
+    checkpoint.schema_version = SCHEMA_VERSION.to_string();
+    checkpoint.updated_at = updated_at.to_string();
+    checkpoint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(block_number: u64, block_hash: &str) -> HandedOffBlock<'_> {
+        HandedOffBlock {
+            network: "ethereum",
+            subnet: "mainnet",
+            block_number,
+            block_hash,
+            tx_count: 3,
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_key_matches_retention_rule() {
+        let key = checkpoint_key("ethereum", "mainnet");
+        assert_eq!(key, "checkpoint:block:ethereum-mainnet");
+        assert!(retention_policy::BLOCK_CHECKPOINT.matches(&key));
+    }
+
+    #[test]
+    fn test_handoff_skips_blocks_already_handed_off() {
+        assert_eq!(handoff(None, 100, "0xa"), Handoff::Process);
+
+        let checkpoint = advance(None, &block(100, "0xa"), "2026-01-01T00:00:00Z");
+        assert_eq!(handoff(Some(&checkpoint), 101, "0xb"), Handoff::Process);
+        assert_eq!(
+            handoff(Some(&checkpoint), 100, "0xA"),
+            Handoff::AlreadyProcessed
+        );
+        assert_eq!(handoff(Some(&checkpoint), 100, "0xc"), Handoff::Reorg);
+        assert_eq!(
+            handoff(Some(&checkpoint), 99, "0xd"),
+            Handoff::AlreadyProcessed
+        );
+        assert!(!Handoff::AlreadyProcessed.should_process());
+    }
+
+    #[test]
+    fn test_gap_is_recorded_and_closed_by_backfill() {
+        let checkpoint = advance(None, &block(100, "0xa"), "t0");
+        let gap = handoff(Some(&checkpoint), 105, "0xf");
+        assert_eq!(gap, Handoff::Gap(BlockRange { from: 101, to: 104 }));
+        assert!(gap.should_process());
+
+        let mut checkpoint = advance(Some(checkpoint), &block(105, "0xf"), "t1");
+        assert_eq!(checkpoint.block_number, 105);
+        assert_eq!(checkpoint.pending_gap_blocks(), 4);
+        assert_eq!(checkpoint.lag(110), 5);
+        assert_eq!(
+            checkpoint.backfill_ranges(3),
+            vec![
+                BlockRange { from: 101, to: 103 },
+                BlockRange { from: 104, to: 104 }
+            ]
+        );
+
+        assert_eq!(handoff(Some(&checkpoint), 102, "0x2"), Handoff::Process);
+        checkpoint = advance(Some(checkpoint), &block(102, "0x2"), "t2");
+        assert_eq!(checkpoint.block_number, 105);
+        assert_eq!(checkpoint.block_hash, "0xf");
+        assert_eq!(
+            checkpoint.pending_gaps,
+            vec![
+                BlockRange { from: 101, to: 101 },
+                BlockRange { from: 103, to: 104 }
+            ]
+        );
+        assert_eq!(
+            handoff(Some(&checkpoint), 102, "0x2"),
+            Handoff::AlreadyProcessed
+        );
+
+        for number in [101, 103, 104] {
+            checkpoint = advance(Some(checkpoint), &block(number, "0x0"), "t3");
+        }
+        assert!(checkpoint.pending_gaps.is_empty());
+        assert_eq!(checkpoint.updated_at, "t3");
+    }
+
+    #[test]
+    fn test_pending_gaps_are_capped() {
+        let mut checkpoint = advance(None, &block(0, "0x0"), "t0");
+        for i in 1..=(MAX_PENDING_GAPS as u64 + 2) {
+            checkpoint = advance(Some(checkpoint), &block(i * 3, "0x0"), "t");
+        }
+        assert_eq!(checkpoint.pending_gaps.len(), MAX_PENDING_GAPS);
+        assert_eq!(checkpoint.dropped_gap_blocks, 4);
+        assert_eq!(checkpoint.pending_gaps[0], BlockRange { from: 7, to: 8 });
+    }
+
+    #[test]
+    fn test_checkpoint_round_trips_without_optional_fields() {
+        let json = r#"{
+            "schema_version": "block_checkpoint_v1",
+            "network": "base",
+            "subnet": "mainnet",
+            "block_number": 42,
+            "block_hash": "0xabc",
+            "tx_count": 7,
+            "updated_at": "2026-01-01T00:00:00Z"
+        }"#;
+        let checkpoint: BlockCheckpointV1 = serde_json::from_str(json).unwrap();
+        assert!(checkpoint.pending_gaps.is_empty());
+        assert_eq!(checkpoint.dropped_gap_blocks, 0);
+
+        let encoded = serde_json::to_string(&checkpoint).unwrap();
+        let decoded: BlockCheckpointV1 = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, checkpoint);
+    }
+}
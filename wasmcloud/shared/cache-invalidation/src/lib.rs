@@ -0,0 +1,199 @@
+//! Shared cache invalidation contracts.
+//!
+//! Registries (token metadata, address labels, ABIs) publish an
+//! [`InvalidationEvent`] on `cache.invalidate.{kind}` whenever an entry changes.
+//! Components holding in-memory copies (LRU caches, bloom filters) evict the
+//! listed keys, or flush everything when the key list is empty.
+//!
+//! Every change bumps the registry's snapshot version, a Redis counter at
+//! `cache:registry_version:{kind}`. Events carry the new version, so a consumer
+//! that sees a gap knows it missed an invalidation (NATS is at-most-once) and
+//! must flush and resync to the current snapshot instead of trusting its cache.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Registry whose entries are cached downstream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidationKind {
+    TokenMetadata,
+    AddressLabel,
+    Abi,
+}
+
+impl InvalidationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvalidationKind::TokenMetadata => "token_metadata",
+            InvalidationKind::AddressLabel => "address_label",
+            InvalidationKind::Abi => "abi",
+        }
+    }
+
+    /// Subject the registry publishes invalidations on
+    pub fn subject(&self) -> String {
+        subject_registry::invalidate(self.as_str())
+    }
+
+    /// Redis counter holding the registry's current snapshot version
+    pub fn version_key(&self) -> String {
+        format!("cache:registry_version:{}", self.as_str())
+    }
+}
+
+/// Registry change notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidationEvent {
+    pub kind: InvalidationKind,
+    /// Cache keys to evict, in the consumer's `{network}:{id}` form; empty flushes all
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// Snapshot version after this change
+    pub version: u64,
+    pub source: String,
+    pub issued_at: String,
+}
+
+impl InvalidationEvent {
+    pub fn new(
+        kind: InvalidationKind,
+        keys: Vec<String>,
+        version: u64,
+        source: &str,
+        issued_at: &str,
+    ) -> Self {
+        Self {
+            kind,
+            keys,
+            version,
+            source: source.to_string(),
+            issued_at: issued_at.to_string(),
+        }
+    }
+
+    pub fn is_flush(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// What a consumer should do with an incoming event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Observation {
+    /// Next version in sequence: evict the event's keys
+    Apply,
+    /// Versions were skipped: flush the cache and resync to the snapshot
+    Resync { missed: u64 },
+    /// Already seen (redelivery or reordering): ignore
+    Stale,
+}
+
+/// Tracks the last snapshot version applied per registry
+#[derive(Debug, Clone, Default)]
+pub struct VersionTracker {
+    versions: HashMap<InvalidationKind, u64>,
+}
+
+impl VersionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Classify an event and advance the tracked version
+    ///
+    /// The first event for a kind always resyncs: the cache may have been
+    /// filled before the consumer subscribed.
+    pub fn observe(&mut self, event: &InvalidationEvent) -> Observation {
+        let Some(&current) = self.versions.get(&event.kind) else {
+            self.versions.insert(event.kind, event.version);
+            return Observation::Resync {
+                missed: event.version.saturating_sub(1),
+            };
+        };
+
+        if event.version <= current {
+            return Observation::Stale;
+        }
+        self.versions.insert(event.kind, event.version);
+
+        let missed = event.version - current - 1;
+        if missed == 0 {
+            Observation::Apply
+        } else {
+            Observation::Resync { missed }
+        }
+    }
+
+    /// Record the snapshot version loaded during a resync
+    pub fn sync_to(&mut self, kind: InvalidationKind, version: u64) {
+        let entry = self.versions.entry(kind).or_insert(version);
+        *entry = (*entry).max(version);
+    }
+
+    pub fn version(&self, kind: InvalidationKind) -> Option<u64> {
+        self.versions.get(&kind).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(version: u64) -> InvalidationEvent {
+        InvalidationEvent::new(
+            InvalidationKind::AddressLabel,
+            vec!["ethereum:0xabc".to_string()],
+            version,
+            "test",
+            "2024-01-01T00:00:00Z",
+        )
+    }
+
+    #[test]
+    fn test_kind_subject_and_version_key() {
+        assert_eq!(
+            InvalidationKind::TokenMetadata.subject(),
+            "cache.invalidate.token_metadata"
+        );
+        assert_eq!(
+            InvalidationKind::Abi.version_key(),
+            "cache:registry_version:abi"
+        );
+    }
+
+    #[test]
+    fn test_tracker_applies_in_sequence_and_detects_gaps() {
+        let mut tracker = VersionTracker::new();
+        tracker.sync_to(InvalidationKind::AddressLabel, 4);
+
+        assert_eq!(tracker.observe(&event(5)), Observation::Apply);
+        assert_eq!(tracker.observe(&event(5)), Observation::Stale);
+        assert_eq!(
+            tracker.observe(&event(8)),
+            Observation::Resync { missed: 2 }
+        );
+        assert_eq!(tracker.version(InvalidationKind::AddressLabel), Some(8));
+    }
+
+    #[test]
+    fn test_tracker_resyncs_on_first_event() {
+        let mut tracker = VersionTracker::new();
+        assert_eq!(
+            tracker.observe(&event(3)),
+            Observation::Resync { missed: 2 }
+        );
+        assert_eq!(tracker.observe(&event(4)), Observation::Apply);
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let json = serde_json::to_string(&event(1)).unwrap();
+        assert!(json.contains("\"kind\":\"address_label\""));
+
+        let parsed: InvalidationEvent = serde_json::from_str(
+            r#"{"kind":"token_metadata","version":2,"source":"api","issued_at":"2024-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert!(parsed.is_flush());
+    }
+}
@@ -5,6 +5,7 @@
 //! instance (PostgreSQL metadata catalog + S3/MinIO parquet storage).
 
 pub mod error;
+pub mod quality;
 pub mod schemas;
 pub mod subject_parser;
 pub mod types;
@@ -24,22 +25,34 @@ pub mod partitioner;
 
 // Re-export commonly used types
 pub use error::DuckLakeError;
+pub use quality::{check_record, quarantine_record, QualityViolation};
 pub use schemas::{
     address_index_schema,
     address_transactions_schema,
+    // Operational table schemas
+    admin_audit_schema,
     // Core table schemas
     blocks_schema,
     contract_calls_schema,
+    // Entity aggregation table schemas
+    dapp_usage_schema,
+    entity_activity_schema,
     get_all_table_names,
     get_partition_columns,
     get_partition_columns_for_table,
     get_schema_for_table,
+    get_upsert_key_columns,
     get_z_order_columns,
     logs_schema,
     lp_positions_schema,
     notification_deliveries_schema,
+    perp_events_schema,
+    price_history_schema,
     processed_transfers_schema,
     protocol_events_schema,
+    quarantine_schema,
+    rpc_recordings_schema,
+    slashing_events_schema,
     token_holdings_schema,
     token_ohlcv_schema,
     token_prices_schema,
@@ -51,13 +64,23 @@ pub use schemas::{
     yield_events_schema,
     ADDRESS_INDEX_TABLE,
     ADDRESS_TRANSACTIONS_TABLE,
+    // Operational table names
+    ADMIN_AUDIT_TABLE,
     // Core table names
     BLOCKS_TABLE,
     CONTRACT_CALLS_TABLE,
+    // Entity aggregation table names
+    DAPP_USAGE_TABLE,
+    ENTITY_ACTIVITY_TABLE,
     LOGS_TABLE,
     LP_POSITIONS_TABLE,
     NOTIFICATION_DELIVERIES_TABLE,
+    PERP_EVENTS_TABLE,
+    PRICE_HISTORY_TABLE,
     PROTOCOL_EVENTS_TABLE,
+    QUARANTINE_TABLE,
+    RPC_RECORDINGS_TABLE,
+    SLASHING_EVENTS_TABLE,
     TOKEN_HOLDINGS_TABLE,
     TOKEN_OHLCV_TABLE,
     TOKEN_PRICES_TABLE,
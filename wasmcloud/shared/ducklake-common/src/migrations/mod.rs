@@ -51,6 +51,17 @@ pub mod registry;
 pub mod runner;
 pub mod v002_add_defi_tables;
 pub mod v003_wallet_balances;
+pub mod v004_zk_rollup_fields;
+pub mod v005_entity_activity;
+pub mod v006_fee_accounting_fields;
+pub mod v007_dapp_usage;
+pub mod v008_admin_audit;
+pub mod v009_quarantine;
+pub mod v010_nft_metadata_fields;
+pub mod v011_price_history;
+pub mod v012_perp_events;
+pub mod v013_slashing_events;
+pub mod v014_rpc_recordings;
 
 // Re-export commonly used types
 pub use ddl::{
@@ -66,6 +77,17 @@ pub use registry::{compute_checksum, MigrationRegistry};
 pub use runner::{MigrationRunner, MigrationStatus};
 pub use v002_add_defi_tables::V002AddDefiTables;
 pub use v003_wallet_balances::V003AddWalletBalances;
+pub use v004_zk_rollup_fields::V004AddZkRollupFields;
+pub use v005_entity_activity::V005AddEntityActivity;
+pub use v006_fee_accounting_fields::V006AddFeeAccountingFields;
+pub use v007_dapp_usage::V007AddDappUsage;
+pub use v008_admin_audit::V008AddAdminAudit;
+pub use v009_quarantine::V009AddQuarantine;
+pub use v010_nft_metadata_fields::V010AddNftMetadataFields;
+pub use v011_price_history::V011AddPriceHistory;
+pub use v012_perp_events::V012AddPerpEvents;
+pub use v013_slashing_events::V013AddSlashingEvents;
+pub use v014_rpc_recordings::V014AddRpcRecordings;
 
 /// Get all defined migrations in order
 ///
@@ -99,8 +121,19 @@ pub fn get_all_migrations() -> Vec<Box<dyn Migration>> {
         Box::new(V001InitialTables),
         Box::new(V002AddDefiTables),
         Box::new(V003AddWalletBalances),
+        Box::new(V004AddZkRollupFields),
+        Box::new(V005AddEntityActivity),
+        Box::new(V006AddFeeAccountingFields),
+        Box::new(V007AddDappUsage),
+        Box::new(V008AddAdminAudit),
+        Box::new(V009AddQuarantine),
+        Box::new(V010AddNftMetadataFields),
+        Box::new(V011AddPriceHistory),
+        Box::new(V012AddPerpEvents),
+        Box::new(V013AddSlashingEvents),
+        Box::new(V014AddRpcRecordings),
         // Add future migrations here:
-        // Box::new(V004SomeMigration),
+        // Box::new(V015SomeMigration),
     ]
 }
 
@@ -0,0 +1,103 @@
+//! V004: Add ZK rollup extension columns to transactions
+//!
+//! zkSync Era and Polygon zkEVM settle L2 blocks to L1 in batches. These
+//! nullable columns capture the batch a transaction landed in, the L1
+//! transactions that sequenced and proved it, and the rollup-native
+//! transaction kind (e.g. zkSync EIP-712 account abstraction or L1 priority txs).
+//!
+//! All columns are NULL for non-rollup chains.
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{transactions_schema, TRANSACTIONS_TABLE};
+
+/// V004: Add ZK rollup fields to the transactions table
+pub struct V004AddZkRollupFields;
+
+impl Migration for V004AddZkRollupFields {
+    fn version(&self) -> MigrationVersion {
+        4
+    }
+
+    fn name(&self) -> &'static str {
+        "add_zk_rollup_fields_to_transactions"
+    }
+
+    fn up(&self) -> &'static str {
+        V004_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V004_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let transactions = transactions_schema();
+
+        Some(schemas_to_json(&[(
+            TRANSACTIONS_TABLE,
+            transactions.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+const V004_UP_SQL: &str = r#"
+-- V004: Add ZK rollup extension columns to transactions
+-- Populated by the EVM processors for zkSync Era and Polygon zkEVM
+ALTER TABLE "transactions" ADD COLUMN "l1_batch_number" BIGINT;
+ALTER TABLE "transactions" ADD COLUMN "l1_batch_tx_index" INTEGER;
+ALTER TABLE "transactions" ADD COLUMN "l1_sequence_tx_hash" VARCHAR;
+ALTER TABLE "transactions" ADD COLUMN "l1_verify_tx_hash" VARCHAR;
+ALTER TABLE "transactions" ADD COLUMN "l2_tx_kind" VARCHAR;
+"#;
+
+/// Static SQL for down migration (rollback)
+const V004_DOWN_SQL: &str = r#"
+-- V004: Drop ZK rollup extension columns
+ALTER TABLE "transactions" DROP COLUMN "l2_tx_kind";
+ALTER TABLE "transactions" DROP COLUMN "l1_verify_tx_hash";
+ALTER TABLE "transactions" DROP COLUMN "l1_sequence_tx_hash";
+ALTER TABLE "transactions" DROP COLUMN "l1_batch_tx_index";
+ALTER TABLE "transactions" DROP COLUMN "l1_batch_number";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [&str; 5] = [
+        "l1_batch_number",
+        "l1_batch_tx_index",
+        "l1_sequence_tx_hash",
+        "l1_verify_tx_hash",
+        "l2_tx_kind",
+    ];
+
+    #[test]
+    fn test_v004_migration_properties() {
+        let migration = V004AddZkRollupFields;
+
+        assert_eq!(migration.version(), 4);
+        assert_eq!(migration.name(), "add_zk_rollup_fields_to_transactions");
+        assert!(!migration.up().is_empty());
+        assert!(!migration.down().is_empty());
+    }
+
+    #[test]
+    fn test_v004_up_and_down_cover_all_columns() {
+        for column in COLUMNS {
+            assert!(V004_UP_SQL.contains(&format!("ADD COLUMN \"{}\"", column)));
+            assert!(V004_DOWN_SQL.contains(&format!("DROP COLUMN \"{}\"", column)));
+        }
+    }
+
+    #[test]
+    fn test_v004_columns_match_arrow_schema() {
+        let schema = transactions_schema();
+        for column in COLUMNS {
+            let field = schema.field_with_name(column).expect("column in schema");
+            assert!(field.is_nullable(), "{} must be nullable", column);
+        }
+    }
+}
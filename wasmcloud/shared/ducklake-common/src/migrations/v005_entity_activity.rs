@@ -0,0 +1,126 @@
+//! V005: Add entity_activity table for cross-chain entity rollups
+//!
+//! Creates the entity_activity table written by the entity_activity_aggregator
+//! actor. Rows are address_transactions events for addresses that the
+//! clustering/labels system links to an entity, each carrying the entity's
+//! rolling 24h cross-chain totals at the time of the event.
+//!
+//! Key features:
+//! - Function-based partitioning by chain_id and block_date (like address_transactions)
+//! - Z-ordered by entity_id, block_timestamp for per-entity timelines
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{entity_activity_schema, ENTITY_ACTIVITY_TABLE};
+
+/// V005: Add entity_activity table
+pub struct V005AddEntityActivity;
+
+impl Migration for V005AddEntityActivity {
+    fn version(&self) -> MigrationVersion {
+        5
+    }
+
+    fn name(&self) -> &'static str {
+        "add_entity_activity_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V005_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V005_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let entity_activity = entity_activity_schema();
+
+        Some(schemas_to_json(&[(
+            ENTITY_ACTIVITY_TABLE,
+            entity_activity.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the entity_activity table:
+/// - Partition by: chain_id, block_date
+/// - Z-order: entity_id, block_timestamp
+const V005_UP_SQL: &str = r#"
+-- V005: Add entity_activity table for cross-chain entity rollups
+-- Written by the entity_activity_aggregator actor
+
+-- ============================================================================
+-- entity_activity: Entity-linked address activity with rolling 24h totals
+-- ============================================================================
+-- One row per entity-linked address_transactions row. The window_* columns are
+-- the entity's totals across all chains over the 24h preceding the event.
+CREATE TABLE IF NOT EXISTS "entity_activity" (
+    "chain_id" VARCHAR NOT NULL,
+    "block_date" DATE NOT NULL,
+    "entity_id" VARCHAR NOT NULL,
+    "entity_label" VARCHAR,
+    "address" VARCHAR NOT NULL,
+    "network" VARCHAR NOT NULL,
+    "subnet" VARCHAR NOT NULL,
+    "transaction_hash" VARCHAR NOT NULL,
+    "block_number" BIGINT NOT NULL,
+    "block_timestamp" TIMESTAMP NOT NULL,
+    "is_sender" BOOLEAN NOT NULL,
+    "counterparty_address" VARCHAR,
+    "is_internal" BOOLEAN NOT NULL,
+    "value" DECIMAL(38, 18),
+    "value_usd" DOUBLE,
+    "transaction_type" VARCHAR,
+    "transaction_subtype" VARCHAR,
+    "window_inflow_usd_24h" DOUBLE NOT NULL,
+    "window_outflow_usd_24h" DOUBLE NOT NULL,
+    "window_tx_count_24h" BIGINT NOT NULL,
+    "window_chain_count_24h" INTEGER NOT NULL,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "entity_activity" SET PARTITIONED BY (chain_id, block_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V005_DOWN_SQL: &str = r#"
+-- V005: Drop entity_activity table
+DROP TABLE IF EXISTS "entity_activity";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v005_migration_properties() {
+        let migration = V005AddEntityActivity;
+
+        assert_eq!(migration.version(), 5);
+        assert_eq!(migration.name(), "add_entity_activity_table");
+        assert!(!migration.up().is_empty());
+        assert!(!migration.down().is_empty());
+    }
+
+    #[test]
+    fn test_v005_up_and_down() {
+        assert!(V005_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"entity_activity\""));
+        assert!(V005_UP_SQL
+            .contains("ALTER TABLE \"entity_activity\" SET PARTITIONED BY (chain_id, block_date)"));
+        assert!(V005_DOWN_SQL.contains("DROP TABLE IF EXISTS \"entity_activity\""));
+    }
+
+    #[test]
+    fn test_v005_columns_match_arrow_schema() {
+        let schema = entity_activity_schema();
+        for field in schema.fields() {
+            assert!(
+                V005_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! V006: Add receipt-based fee accounting columns to transactions
+//!
+//! `transaction_fee` alone cannot answer how much of a fee was burned versus
+//! paid to the block producer, or how much of the gas limit went unused. These
+//! nullable columns split the fee using the receipt's `gasUsed` and
+//! `effectiveGasPrice` together with the block's `baseFeePerGas`:
+//!
+//! - `fee_burned`: base fee × gas used (EIP-1559 burn)
+//! - `fee_tip`: (effective price − base fee) × gas used
+//! - `fee_refunded`: unused gas limit × effective price
+//! - `net_fee`: gas used × effective price
+//!
+//! All columns are NULL when the receipt was not fetched. `fee_burned` and
+//! `fee_tip` are NULL for pre-London blocks that carry no base fee.
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{transactions_schema, TRANSACTIONS_TABLE};
+
+/// V006: Add fee accounting fields to the transactions table
+pub struct V006AddFeeAccountingFields;
+
+impl Migration for V006AddFeeAccountingFields {
+    fn version(&self) -> MigrationVersion {
+        6
+    }
+
+    fn name(&self) -> &'static str {
+        "add_fee_accounting_fields_to_transactions"
+    }
+
+    fn up(&self) -> &'static str {
+        V006_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V006_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let transactions = transactions_schema();
+
+        Some(schemas_to_json(&[(
+            TRANSACTIONS_TABLE,
+            transactions.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+const V006_UP_SQL: &str = r#"
+-- V006: Add receipt-based fee accounting columns to transactions
+-- Populated by the EVM processors when receipts are available
+ALTER TABLE "transactions" ADD COLUMN "fee_burned" DECIMAL(38, 18);
+ALTER TABLE "transactions" ADD COLUMN "fee_tip" DECIMAL(38, 18);
+ALTER TABLE "transactions" ADD COLUMN "fee_refunded" DECIMAL(38, 18);
+ALTER TABLE "transactions" ADD COLUMN "net_fee" DECIMAL(38, 18);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V006_DOWN_SQL: &str = r#"
+-- V006: Drop fee accounting columns
+ALTER TABLE "transactions" DROP COLUMN "net_fee";
+ALTER TABLE "transactions" DROP COLUMN "fee_refunded";
+ALTER TABLE "transactions" DROP COLUMN "fee_tip";
+ALTER TABLE "transactions" DROP COLUMN "fee_burned";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [&str; 4] = ["fee_burned", "fee_tip", "fee_refunded", "net_fee"];
+
+    #[test]
+    fn test_v006_migration_properties() {
+        let migration = V006AddFeeAccountingFields;
+
+        assert_eq!(migration.version(), 6);
+        assert_eq!(
+            migration.name(),
+            "add_fee_accounting_fields_to_transactions"
+        );
+        assert!(!migration.up().is_empty());
+        assert!(!migration.down().is_empty());
+    }
+
+    #[test]
+    fn test_v006_up_and_down_cover_all_columns() {
+        for column in COLUMNS {
+            assert!(V006_UP_SQL.contains(&format!("ADD COLUMN \"{}\"", column)));
+            assert!(V006_DOWN_SQL.contains(&format!("DROP COLUMN \"{}\"", column)));
+        }
+    }
+
+    #[test]
+    fn test_v006_columns_match_arrow_schema() {
+        let schema = transactions_schema();
+        for column in COLUMNS {
+            let field = schema.field_with_name(column).expect("column in schema");
+            assert!(field.is_nullable(), "{} must be nullable", column);
+        }
+    }
+}
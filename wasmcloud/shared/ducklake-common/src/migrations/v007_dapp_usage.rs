@@ -0,0 +1,132 @@
+//! V007: Add dApp attribution to contract_calls and the dapp_usage table
+//!
+//! The contract transaction processor attributes calls to router / front-end
+//! contracts to a dApp (Uniswap, OpenSea, Aave, ...). The name is stored on the
+//! contract_calls row, and every attributed call also writes a dapp_usage row
+//! carrying the caller's lifetime interaction count for that dApp.
+//!
+//! Key features:
+//! - Nullable `dapp_name` on contract_calls (NULL for unattributed calls)
+//! - Function-based partitioning by chain_id and block_date for dapp_usage
+//! - Z-ordered by address, dapp_name, block_timestamp for per-wallet usage
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{
+    contract_calls_schema, dapp_usage_schema, CONTRACT_CALLS_TABLE, DAPP_USAGE_TABLE,
+};
+
+/// V007: Add dapp_name to contract_calls and create dapp_usage
+pub struct V007AddDappUsage;
+
+impl Migration for V007AddDappUsage {
+    fn version(&self) -> MigrationVersion {
+        7
+    }
+
+    fn name(&self) -> &'static str {
+        "add_dapp_usage_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V007_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V007_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let contract_calls = contract_calls_schema();
+        let dapp_usage = dapp_usage_schema();
+
+        Some(schemas_to_json(&[
+            (CONTRACT_CALLS_TABLE, contract_calls.as_ref()),
+            (DAPP_USAGE_TABLE, dapp_usage.as_ref()),
+        ]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the dapp_usage table:
+/// - Partition by: chain_id, block_date
+/// - Z-order: address, dapp_name, block_timestamp
+const V007_UP_SQL: &str = r#"
+-- V007: dApp attribution for contract calls
+-- Written by the eth_contract_transaction_processor actor
+ALTER TABLE "contract_calls" ADD COLUMN "dapp_name" VARCHAR;
+
+-- ============================================================================
+-- dapp_usage: Contract calls attributed to a dApp, per calling address
+-- ============================================================================
+-- interaction_count is the caller's lifetime call count into the dApp,
+-- including the call on this row.
+CREATE TABLE IF NOT EXISTS "dapp_usage" (
+    "chain_id" VARCHAR NOT NULL,
+    "block_date" DATE NOT NULL,
+    "address" VARCHAR NOT NULL,
+    "dapp_name" VARCHAR NOT NULL,
+    "dapp_source" VARCHAR NOT NULL,
+    "network" VARCHAR NOT NULL,
+    "subnet" VARCHAR NOT NULL,
+    "contract_address" VARCHAR NOT NULL,
+    "transaction_hash" VARCHAR NOT NULL,
+    "block_number" BIGINT NOT NULL,
+    "block_timestamp" TIMESTAMP NOT NULL,
+    "transaction_subtype" VARCHAR,
+    "category" VARCHAR,
+    "value" DECIMAL(38, 18),
+    "success" BOOLEAN NOT NULL,
+    "interaction_count" BIGINT NOT NULL,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "dapp_usage" SET PARTITIONED BY (chain_id, block_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V007_DOWN_SQL: &str = r#"
+-- V007: Drop dapp_usage table and contract_calls attribution
+DROP TABLE IF EXISTS "dapp_usage";
+ALTER TABLE "contract_calls" DROP COLUMN "dapp_name";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v007_migration_properties() {
+        let migration = V007AddDappUsage;
+
+        assert_eq!(migration.version(), 7);
+        assert_eq!(migration.name(), "add_dapp_usage_table");
+        assert!(!migration.up().is_empty());
+        assert!(!migration.down().is_empty());
+    }
+
+    #[test]
+    fn test_v007_up_and_down() {
+        assert!(V007_UP_SQL.contains("ALTER TABLE \"contract_calls\" ADD COLUMN \"dapp_name\""));
+        assert!(V007_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"dapp_usage\""));
+        assert!(V007_DOWN_SQL.contains("DROP TABLE IF EXISTS \"dapp_usage\""));
+        assert!(V007_DOWN_SQL.contains("DROP COLUMN \"dapp_name\""));
+    }
+
+    #[test]
+    fn test_v007_columns_match_arrow_schema() {
+        let schema = dapp_usage_schema();
+        for field in schema.fields() {
+            assert!(
+                V007_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+        let contract_calls = contract_calls_schema();
+        assert!(contract_calls
+            .field_with_name("dapp_name")
+            .expect("dapp_name in contract_calls")
+            .is_nullable());
+    }
+}
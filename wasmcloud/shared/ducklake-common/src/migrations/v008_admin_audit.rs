@@ -0,0 +1,107 @@
+//! V008: Add the admin_audit table
+//!
+//! The Django API appends one signed row per admin mutation of rules,
+//! watchlists, chains and config. Rows are never updated or deleted; the
+//! signature lets compliance review detect a row edited after the fact.
+//!
+//! Key features:
+//! - Partitioned by audit_date for date-range compliance exports
+//! - Z-ordered by resource_type, resource_id, occurred_at for per-resource history
+//! - before/after snapshots stored as JSON text
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{admin_audit_schema, ADMIN_AUDIT_TABLE};
+
+/// V008: Create admin_audit
+pub struct V008AddAdminAudit;
+
+impl Migration for V008AddAdminAudit {
+    fn version(&self) -> MigrationVersion {
+        8
+    }
+
+    fn name(&self) -> &'static str {
+        "add_admin_audit_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V008_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V008_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let admin_audit = admin_audit_schema();
+
+        Some(schemas_to_json(&[(
+            ADMIN_AUDIT_TABLE,
+            admin_audit.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the admin_audit table:
+/// - Partition by: audit_date
+/// - Z-order: resource_type, resource_id, occurred_at
+const V008_UP_SQL: &str = r#"
+-- V008: Admin audit trail
+-- Written by the Django API (ducklake.admin_audit.ekko.default.write)
+CREATE TABLE IF NOT EXISTS "admin_audit" (
+    "audit_date" DATE NOT NULL,
+    "audit_id" VARCHAR NOT NULL,
+    "occurred_at" TIMESTAMP NOT NULL,
+    "actor_id" VARCHAR,
+    "actor_email" VARCHAR,
+    "source_ip" VARCHAR,
+    "request_method" VARCHAR,
+    "request_path" VARCHAR,
+    "action" VARCHAR NOT NULL,
+    "resource_type" VARCHAR NOT NULL,
+    "resource_id" VARCHAR NOT NULL,
+    "changed_fields" VARCHAR,
+    "before_json" VARCHAR,
+    "after_json" VARCHAR,
+    "signature" VARCHAR NOT NULL,
+    "signature_key_id" VARCHAR NOT NULL,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "admin_audit" SET PARTITIONED BY (audit_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V008_DOWN_SQL: &str = r#"
+-- V008: Drop admin_audit table
+DROP TABLE IF EXISTS "admin_audit";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v008_migration_properties() {
+        let migration = V008AddAdminAudit;
+
+        assert_eq!(migration.version(), 8);
+        assert_eq!(migration.name(), "add_admin_audit_table");
+        assert!(V008_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"admin_audit\""));
+        assert!(V008_DOWN_SQL.contains("DROP TABLE IF EXISTS \"admin_audit\""));
+    }
+
+    #[test]
+    fn test_v008_columns_match_arrow_schema() {
+        let schema = admin_audit_schema();
+        for field in schema.fields() {
+            assert!(
+                V008_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
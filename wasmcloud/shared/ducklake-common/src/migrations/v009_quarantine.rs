@@ -0,0 +1,94 @@
+//! V009: Add the quarantine table
+//!
+//! ducklake-write runs inline data-quality checks on every record. Records
+//! that fail are written here with their violation reasons instead of to
+//! their source table, so bad rows never land in the lake unnoticed.
+//!
+//! Key features:
+//! - Partitioned by quarantine_date for retention and daily review
+//! - Z-ordered by source_table, chain_id, quarantined_at
+//! - Original record and violations stored as JSON text
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{quarantine_schema, QUARANTINE_TABLE};
+
+/// V009: Create quarantine
+pub struct V009AddQuarantine;
+
+impl Migration for V009AddQuarantine {
+    fn version(&self) -> MigrationVersion {
+        9
+    }
+
+    fn name(&self) -> &'static str {
+        "add_quarantine_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V009_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V009_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let quarantine = quarantine_schema();
+
+        Some(schemas_to_json(&[(QUARANTINE_TABLE, quarantine.as_ref())]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the quarantine table:
+/// - Partition by: quarantine_date
+/// - Z-order: source_table, chain_id, quarantined_at
+const V009_UP_SQL: &str = r#"
+-- V009: Data-quality quarantine
+-- Written by ducklake-write for records failing inline checks
+CREATE TABLE IF NOT EXISTS "quarantine" (
+    "quarantine_date" DATE NOT NULL,
+    "quarantined_at" TIMESTAMP NOT NULL,
+    "chain_id" VARCHAR NOT NULL,
+    "source_table" VARCHAR NOT NULL,
+    "violations" VARCHAR NOT NULL,
+    "record_json" VARCHAR NOT NULL,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "quarantine" SET PARTITIONED BY (quarantine_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V009_DOWN_SQL: &str = r#"
+-- V009: Drop quarantine table
+DROP TABLE IF EXISTS "quarantine";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v009_migration_properties() {
+        let migration = V009AddQuarantine;
+
+        assert_eq!(migration.version(), 9);
+        assert_eq!(migration.name(), "add_quarantine_table");
+        assert!(V009_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"quarantine\""));
+        assert!(V009_DOWN_SQL.contains("DROP TABLE IF EXISTS \"quarantine\""));
+    }
+
+    #[test]
+    fn test_v009_columns_match_arrow_schema() {
+        let schema = quarantine_schema();
+        for field in schema.fields() {
+            assert!(
+                V009_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
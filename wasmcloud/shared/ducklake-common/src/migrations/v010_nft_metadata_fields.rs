@@ -0,0 +1,96 @@
+//! V010: Add tokenURI metadata columns to token_transfers
+//!
+//! ERC-721 transfers of watched collections are enriched by evm-logs-ingestion
+//! with the token's resolved `tokenURI` metadata so history and notifications
+//! can show a token name and image instead of a bare token id:
+//!
+//! - `nft_name`: display name (metadata `name`, else collection label + id)
+//! - `nft_image`: gateway URL of the metadata `image`
+//! - `nft_token_uri`: the `tokenURI` the metadata was resolved from
+//!
+//! All columns are NULL for fungible transfers and for collections that are
+//! not watched.
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{token_transfers_schema, TOKEN_TRANSFERS_TABLE};
+
+/// V010: Add NFT metadata fields to the token_transfers table
+pub struct V010AddNftMetadataFields;
+
+impl Migration for V010AddNftMetadataFields {
+    fn version(&self) -> MigrationVersion {
+        10
+    }
+
+    fn name(&self) -> &'static str {
+        "add_nft_metadata_fields_to_token_transfers"
+    }
+
+    fn up(&self) -> &'static str {
+        V010_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V010_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let token_transfers = token_transfers_schema();
+
+        Some(schemas_to_json(&[(
+            TOKEN_TRANSFERS_TABLE,
+            token_transfers.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+const V010_UP_SQL: &str = r#"
+-- V010: Add tokenURI metadata columns to token_transfers
+-- Populated by evm-logs-ingestion for watched ERC-721 collections. The table is
+-- created by ducklake-write on first write, already with these columns, so the
+-- statements are no-ops on a fresh catalog.
+ALTER TABLE IF EXISTS "token_transfers" ADD COLUMN IF NOT EXISTS "nft_name" VARCHAR;
+ALTER TABLE IF EXISTS "token_transfers" ADD COLUMN IF NOT EXISTS "nft_image" VARCHAR;
+ALTER TABLE IF EXISTS "token_transfers" ADD COLUMN IF NOT EXISTS "nft_token_uri" VARCHAR;
+"#;
+
+/// Static SQL for down migration (rollback)
+const V010_DOWN_SQL: &str = r#"
+-- V010: Drop NFT metadata columns
+ALTER TABLE IF EXISTS "token_transfers" DROP COLUMN IF EXISTS "nft_token_uri";
+ALTER TABLE IF EXISTS "token_transfers" DROP COLUMN IF EXISTS "nft_image";
+ALTER TABLE IF EXISTS "token_transfers" DROP COLUMN IF EXISTS "nft_name";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [&str; 3] = ["nft_name", "nft_image", "nft_token_uri"];
+
+    #[test]
+    fn test_v010_migration_properties() {
+        let migration = V010AddNftMetadataFields;
+
+        assert_eq!(migration.version(), 10);
+        assert_eq!(
+            migration.name(),
+            "add_nft_metadata_fields_to_token_transfers"
+        );
+        assert!(!migration.up().is_empty());
+        assert!(!migration.down().is_empty());
+    }
+
+    #[test]
+    fn test_v010_columns_match_arrow_schema() {
+        let schema = token_transfers_schema();
+        for column in COLUMNS {
+            assert!(V010_UP_SQL.contains(&format!("ADD COLUMN IF NOT EXISTS \"{}\"", column)));
+            assert!(V010_DOWN_SQL.contains(&format!("DROP COLUMN IF EXISTS \"{}\"", column)));
+            let field = schema.field_with_name(column).expect("column in schema");
+            assert!(field.is_nullable(), "{} must be nullable", column);
+        }
+    }
+}
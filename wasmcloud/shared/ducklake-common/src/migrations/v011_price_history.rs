@@ -0,0 +1,99 @@
+//! V011: Add the price_history table
+//!
+//! The price-backfill job fetches a daily or hourly USD price series once per
+//! asset and stores it here, then joins it onto imported transactions that
+//! were written without `amount_usd` / `fee_usd`. Rows are keyed by
+//! (chain_id, asset, interval, price_timestamp) so re-fetching a range
+//! upserts instead of duplicating buckets.
+//!
+//! Key features:
+//! - Partitioned by chain_id, price_date
+//! - Z-ordered by asset, interval, price_timestamp for range lookups
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{price_history_schema, PRICE_HISTORY_TABLE};
+
+/// V011: Create price_history
+pub struct V011AddPriceHistory;
+
+impl Migration for V011AddPriceHistory {
+    fn version(&self) -> MigrationVersion {
+        11
+    }
+
+    fn name(&self) -> &'static str {
+        "add_price_history_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V011_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V011_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let price_history = price_history_schema();
+
+        Some(schemas_to_json(&[(
+            PRICE_HISTORY_TABLE,
+            price_history.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the price_history table:
+/// - Partition by: chain_id, price_date
+/// - Z-order: asset, interval, price_timestamp
+const V011_UP_SQL: &str = r#"
+-- V011: Historical price series for USD backfill
+-- Written by the price-backfill actor (ducklake.price_history.{chain}.{subnet}.upsert)
+CREATE TABLE IF NOT EXISTS "price_history" (
+    "chain_id" VARCHAR NOT NULL,
+    "price_date" DATE NOT NULL,
+    "asset" VARCHAR NOT NULL,
+    "interval" VARCHAR NOT NULL,
+    "price_timestamp" TIMESTAMP NOT NULL,
+    "price_usd" DOUBLE NOT NULL,
+    "source" VARCHAR NOT NULL,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "price_history" SET PARTITIONED BY (chain_id, price_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V011_DOWN_SQL: &str = r#"
+-- V011: Drop price_history table
+DROP TABLE IF EXISTS "price_history";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v011_migration_properties() {
+        let migration = V011AddPriceHistory;
+
+        assert_eq!(migration.version(), 11);
+        assert_eq!(migration.name(), "add_price_history_table");
+        assert!(V011_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"price_history\""));
+        assert!(V011_DOWN_SQL.contains("DROP TABLE IF EXISTS \"price_history\""));
+    }
+
+    #[test]
+    fn test_v011_columns_match_arrow_schema() {
+        let schema = price_history_schema();
+        for field in schema.fields() {
+            assert!(
+                V011_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
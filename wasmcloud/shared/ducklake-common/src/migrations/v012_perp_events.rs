@@ -0,0 +1,116 @@
+//! V012: Add the perp_events table
+//!
+//! evm-logs-ingestion decodes position events of on-chain perpetuals
+//! protocols (GMX v1 `IncreasePosition` / `DecreasePosition` /
+//! `LiquidatePosition`, Perpetual Protocol v2 `PositionChanged` /
+//! `PositionClosed` / `PositionLiquidated` / `FundingPaymentSettled`) and
+//! writes one row per event with the trader account, market and USD
+//! amounts, so a watched account's derivatives history can be queried
+//! without re-decoding logs.
+//!
+//! Key features:
+//! - Partitioned by chain_id, block_date
+//! - Z-ordered by account, market, block_timestamp for per-trader lookups
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{perp_events_schema, PERP_EVENTS_TABLE};
+
+/// V012: Create perp_events
+pub struct V012AddPerpEvents;
+
+impl Migration for V012AddPerpEvents {
+    fn version(&self) -> MigrationVersion {
+        12
+    }
+
+    fn name(&self) -> &'static str {
+        "add_perp_events_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V012_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V012_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let perp_events = perp_events_schema();
+
+        Some(schemas_to_json(&[(
+            PERP_EVENTS_TABLE,
+            perp_events.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the perp_events table:
+/// - Partition by: chain_id, block_date
+/// - Z-order: account, market, block_timestamp
+const V012_UP_SQL: &str = r#"
+-- V012: Perpetuals position events
+-- Written by evm-logs-ingestion (ducklake.perp_events.{chain}.{subnet}.write)
+CREATE TABLE IF NOT EXISTS "perp_events" (
+    "chain_id" VARCHAR NOT NULL,
+    "block_date" DATE NOT NULL,
+    "block_number" BIGINT NOT NULL,
+    "block_timestamp" TIMESTAMP NOT NULL,
+    "transaction_hash" VARCHAR NOT NULL,
+    "log_index" INTEGER NOT NULL,
+    "protocol" VARCHAR NOT NULL,
+    "contract_address" VARCHAR NOT NULL,
+    "event_type" VARCHAR NOT NULL,
+    "event_name" VARCHAR NOT NULL,
+    "account" VARCHAR NOT NULL,
+    "market" VARCHAR NOT NULL,
+    "collateral_token" VARCHAR,
+    "is_long" BOOLEAN,
+    "position_key" VARCHAR,
+    "size_delta_usd" DECIMAL(38, 18),
+    "collateral_delta_usd" DECIMAL(38, 18),
+    "price_usd" DECIMAL(38, 18),
+    "fee_usd" DECIMAL(38, 18),
+    "realized_pnl_usd" DECIMAL(38, 18),
+    "funding_payment_usd" DECIMAL(38, 18),
+    "liquidator" VARCHAR,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "perp_events" SET PARTITIONED BY (chain_id, block_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V012_DOWN_SQL: &str = r#"
+-- V012: Drop perp_events table
+DROP TABLE IF EXISTS "perp_events";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v012_migration_properties() {
+        let migration = V012AddPerpEvents;
+
+        assert_eq!(migration.version(), 12);
+        assert_eq!(migration.name(), "add_perp_events_table");
+        assert!(V012_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"perp_events\""));
+        assert!(V012_DOWN_SQL.contains("DROP TABLE IF EXISTS \"perp_events\""));
+    }
+
+    #[test]
+    fn test_v012_columns_match_arrow_schema() {
+        let schema = perp_events_schema();
+        for field in schema.fields() {
+            assert!(
+                V012_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! V013: Add the slashing_events table
+//!
+//! The newheads-evm provider follows beacon nodes and records every
+//! validator slashed by a `ProposerSlashing` or `AttesterSlashing`, with the
+//! validator's public key and withdrawal address, so the slashing history of
+//! a staking operator can be queried alongside its execution-layer activity.
+//!
+//! Key features:
+//! - Partitioned by chain_id, slot_date
+//! - Z-ordered by validator_index, withdrawal_address, slot for per-operator lookups
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{slashing_events_schema, SLASHING_EVENTS_TABLE};
+
+/// V013: Create slashing_events
+pub struct V013AddSlashingEvents;
+
+impl Migration for V013AddSlashingEvents {
+    fn version(&self) -> MigrationVersion {
+        13
+    }
+
+    fn name(&self) -> &'static str {
+        "add_slashing_events_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V013_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V013_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let slashing_events = slashing_events_schema();
+
+        Some(schemas_to_json(&[(
+            SLASHING_EVENTS_TABLE,
+            slashing_events.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the slashing_events table:
+/// - Partition by: chain_id, slot_date
+/// - Z-order: validator_index, withdrawal_address, slot
+const V013_UP_SQL: &str = r#"
+-- V013: Beacon chain slashings
+-- Written by newheads-evm (ducklake.slashing_events.{chain}.{subnet}.write)
+CREATE TABLE IF NOT EXISTS "slashing_events" (
+    "chain_id" VARCHAR NOT NULL,
+    "slot_date" DATE NOT NULL,
+    "slot" BIGINT NOT NULL,
+    "epoch" BIGINT NOT NULL,
+    "block_timestamp" TIMESTAMP,
+    "slashing_type" VARCHAR NOT NULL,
+    "validator_index" BIGINT NOT NULL,
+    "validator_pubkey" VARCHAR,
+    "withdrawal_address" VARCHAR,
+    "watched_by" VARCHAR,
+    "ingested_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "slashing_events" SET PARTITIONED BY (chain_id, slot_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V013_DOWN_SQL: &str = r#"
+-- V013: Drop slashing_events table
+DROP TABLE IF EXISTS "slashing_events";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v013_migration_properties() {
+        let migration = V013AddSlashingEvents;
+
+        assert_eq!(migration.version(), 13);
+        assert_eq!(migration.name(), "add_slashing_events_table");
+        assert!(V013_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"slashing_events\""));
+        assert!(V013_DOWN_SQL.contains("DROP TABLE IF EXISTS \"slashing_events\""));
+    }
+
+    #[test]
+    fn test_v013_columns_match_arrow_schema() {
+        let schema = slashing_events_schema();
+        for field in schema.fields() {
+            assert!(
+                V013_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
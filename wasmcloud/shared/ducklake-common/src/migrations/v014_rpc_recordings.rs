@@ -0,0 +1,99 @@
+//! V014: Add the rpc_recordings table
+//!
+//! The http-rpc provider can record every upstream JSON-RPC call it answers,
+//! with the answer, so pipelines can later be replayed against a fixed view
+//! of the chain. With the `ducklake` sink the recordings land here, one row
+//! per call.
+//!
+//! Key features:
+//! - Partitioned by chain_id, recorded_date
+//! - Z-ordered by method, recorded_at for per-method lookups
+
+use super::ddl::schemas_to_json;
+use super::definitions::{Migration, MigrationVersion};
+use crate::schemas::{rpc_recordings_schema, RPC_RECORDINGS_TABLE};
+
+/// V014: Create rpc_recordings
+pub struct V014AddRpcRecordings;
+
+impl Migration for V014AddRpcRecordings {
+    fn version(&self) -> MigrationVersion {
+        14
+    }
+
+    fn name(&self) -> &'static str {
+        "add_rpc_recordings_table"
+    }
+
+    fn up(&self) -> &'static str {
+        V014_UP_SQL
+    }
+
+    fn down(&self) -> &'static str {
+        V014_DOWN_SQL
+    }
+
+    fn schema_json(&self) -> Option<String> {
+        let rpc_recordings = rpc_recordings_schema();
+
+        Some(schemas_to_json(&[(
+            RPC_RECORDINGS_TABLE,
+            rpc_recordings.as_ref(),
+        )]))
+    }
+}
+
+/// Static SQL for up migration
+///
+/// Creates the rpc_recordings table:
+/// - Partition by: chain_id, recorded_date
+/// - Z-order: method, recorded_at
+const V014_UP_SQL: &str = r#"
+-- V014: Recorded upstream RPC calls
+-- Written by http-rpc (ducklake.rpc_recordings.{network}.default.write)
+CREATE TABLE IF NOT EXISTS "rpc_recordings" (
+    "chain_id" VARCHAR NOT NULL,
+    "recorded_date" DATE NOT NULL,
+    "method" VARCHAR NOT NULL,
+    "params" VARCHAR NOT NULL,
+    "result" VARCHAR,
+    "error" VARCHAR,
+    "endpoint" VARCHAR,
+    "latency_ms" BIGINT,
+    "recorded_at" TIMESTAMP NOT NULL
+);
+ALTER TABLE "rpc_recordings" SET PARTITIONED BY (chain_id, recorded_date);
+"#;
+
+/// Static SQL for down migration (rollback)
+const V014_DOWN_SQL: &str = r#"
+-- V014: Drop rpc_recordings table
+DROP TABLE IF EXISTS "rpc_recordings";
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v014_migration_properties() {
+        let migration = V014AddRpcRecordings;
+
+        assert_eq!(migration.version(), 14);
+        assert_eq!(migration.name(), "add_rpc_recordings_table");
+        assert!(V014_UP_SQL.contains("CREATE TABLE IF NOT EXISTS \"rpc_recordings\""));
+        assert!(V014_DOWN_SQL.contains("DROP TABLE IF EXISTS \"rpc_recordings\""));
+    }
+
+    #[test]
+    fn test_v014_columns_match_arrow_schema() {
+        let schema = rpc_recordings_schema();
+        for field in schema.fields() {
+            assert!(
+                V014_UP_SQL.contains(&format!("\"{}\"", field.name())),
+                "{} missing from up SQL",
+                field.name()
+            );
+        }
+    }
+}
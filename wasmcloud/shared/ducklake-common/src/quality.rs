@@ -0,0 +1,250 @@
+//! Inline data-quality checks for DuckLake writes
+//!
+//! Records are checked before they are buffered. A record that fails any check
+//! is not written to its table; it is wrapped by [`quarantine_record`] and
+//! written to the `quarantine` table with the violation reasons, so bad rows
+//! can be inspected and replayed instead of being silently persisted.
+//!
+//! Checks only apply to columns present in the record:
+//! - `block_timestamp` is unix seconds between Bitcoin genesis and one day ahead
+//! - `gas_used` does not exceed `gas_limit`
+//! - `transaction_hash` is non-empty and not all zeros
+//! - `block_date` matches the UTC date of `block_timestamp`
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::schemas::QUARANTINE_TABLE;
+
+/// Earliest accepted block timestamp (Bitcoin genesis, 2009-01-03)
+pub const MIN_BLOCK_TIMESTAMP: i64 = 1_231_006_505;
+
+/// Allowed clock skew for block timestamps ahead of the writer
+pub const MAX_FUTURE_SKEW_SECS: i64 = 86_400;
+
+/// Why a record failed a check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityViolation {
+    /// Check name: `timestamp_range`, `gas_used_le_gas_limit`,
+    /// `non_zero_tx_hash` or `block_date_matches_timestamp`
+    pub check: String,
+    pub column: String,
+    pub reason: String,
+}
+
+impl QualityViolation {
+    fn new(check: &str, column: &str, reason: String) -> Self {
+        Self {
+            check: check.to_string(),
+            column: column.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Run all checks against a record destined for `table`
+///
+/// `now` is the writer's current unix time, used for the upper timestamp bound.
+/// Records for the quarantine table itself are never checked.
+pub fn check_record(table: &str, record: &Value, now: i64) -> Vec<QualityViolation> {
+    let mut violations = Vec::new();
+    let Some(map) = record.as_object() else {
+        return violations;
+    };
+    if table == QUARANTINE_TABLE {
+        return violations;
+    }
+
+    let block_timestamp = map.get("block_timestamp").filter(|v| !v.is_null());
+    let timestamp = match block_timestamp.map(parse_timestamp) {
+        Some(Some(ts)) if !(MIN_BLOCK_TIMESTAMP..=now + MAX_FUTURE_SKEW_SECS).contains(&ts) => {
+            violations.push(QualityViolation::new(
+                "timestamp_range",
+                "block_timestamp",
+                format!("block_timestamp {} outside sane bounds", ts),
+            ));
+            None
+        }
+        Some(Some(ts)) => Some(ts),
+        Some(None) => {
+            violations.push(QualityViolation::new(
+                "timestamp_range",
+                "block_timestamp",
+                "block_timestamp is not a unix timestamp".to_string(),
+            ));
+            None
+        }
+        None => None,
+    };
+
+    if let (Some(used), Some(limit)) = (
+        map.get("gas_used").and_then(parse_u64),
+        map.get("gas_limit").and_then(parse_u64),
+    ) {
+        if used > limit {
+            violations.push(QualityViolation::new(
+                "gas_used_le_gas_limit",
+                "gas_used",
+                format!("gas_used {} exceeds gas_limit {}", used, limit),
+            ));
+        }
+    }
+
+    if let Some(hash) = map.get("transaction_hash") {
+        if !is_valid_tx_hash(hash) {
+            violations.push(QualityViolation::new(
+                "non_zero_tx_hash",
+                "transaction_hash",
+                format!("transaction_hash {} is empty or zero", hash),
+            ));
+        }
+    }
+
+    if let (Some(ts), Some(block_date)) =
+        (timestamp, map.get("block_date").and_then(|v| v.as_str()))
+    {
+        let expected = DateTime::from_timestamp(ts, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        if block_date != expected {
+            violations.push(QualityViolation::new(
+                "block_date_matches_timestamp",
+                "block_date",
+                format!(
+                    "block_date {} does not match block_timestamp date {}",
+                    block_date, expected
+                ),
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Wrap a failed record as a row for the quarantine table
+pub fn quarantine_record(
+    source_table: &str,
+    chain_id: &str,
+    record: &Value,
+    violations: &[QualityViolation],
+    quarantined_at: DateTime<Utc>,
+) -> Value {
+    json!({
+        "quarantine_date": quarantined_at.format("%Y-%m-%d").to_string(),
+        "quarantined_at": quarantined_at.to_rfc3339(),
+        "chain_id": chain_id,
+        "source_table": source_table,
+        "violations": serde_json::to_string(violations).unwrap_or_default(),
+        "record_json": record.to_string(),
+    })
+}
+
+fn parse_timestamp(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(num) => num.as_i64(),
+        Value::String(text) => text.parse::<i64>().ok().or_else(|| {
+            DateTime::parse_from_rfc3339(text)
+                .ok()
+                .map(|dt| dt.timestamp())
+        }),
+        _ => None,
+    }
+}
+
+fn parse_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(num) => num.as_u64(),
+        Value::String(text) => match text.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => text.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn is_valid_tx_hash(value: &Value) -> bool {
+    let Some(hash) = value.as_str() else {
+        return false;
+    };
+    let digits = hash.strip_prefix("0x").unwrap_or(hash);
+    !digits.is_empty() && digits.chars().any(|c| c != '0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_704_067_200; // 2024-01-01T00:00:00Z
+
+    fn checks(violations: &[QualityViolation]) -> Vec<&str> {
+        violations.iter().map(|v| v.check.as_str()).collect()
+    }
+
+    #[test]
+    fn test_valid_record_passes() {
+        let record = json!({
+            "block_timestamp": NOW - 60,
+            "block_date": "2023-12-31",
+            "gas_used": 21000,
+            "gas_limit": "21000",
+            "transaction_hash": "0xabc123",
+        });
+        assert!(check_record("transactions", &record, NOW).is_empty());
+        // Checks skip absent columns
+        assert!(check_record("logs", &json!({"address": "0x1"}), NOW).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_record_reports_every_violation() {
+        let record = json!({
+            "block_timestamp": NOW * 1000,
+            "block_date": "2024-01-01",
+            "gas_used": "0x5209",
+            "gas_limit": 21000,
+            "transaction_hash": "0x0000",
+        });
+        let violations = check_record("transactions", &record, NOW);
+        assert_eq!(
+            checks(&violations),
+            vec![
+                "timestamp_range",
+                "gas_used_le_gas_limit",
+                "non_zero_tx_hash"
+            ]
+        );
+
+        let record =
+            json!({"block_timestamp": NOW, "block_date": "2023-12-31", "transaction_hash": ""});
+        assert_eq!(
+            checks(&check_record("transactions", &record, NOW)),
+            vec!["non_zero_tx_hash", "block_date_matches_timestamp"]
+        );
+
+        // Quarantine rows are never re-checked
+        assert!(check_record(QUARANTINE_TABLE, &record, NOW).is_empty());
+    }
+
+    #[test]
+    fn test_quarantine_record_keeps_original_and_reasons() {
+        let record = json!({"transaction_hash": "0x0", "gas_used": 2, "gas_limit": 1});
+        let violations = check_record("transactions", &record, NOW);
+        let quarantined_at = DateTime::from_timestamp(NOW, 0).unwrap();
+
+        let row = quarantine_record(
+            "transactions",
+            "ethereum_mainnet",
+            &record,
+            &violations,
+            quarantined_at,
+        );
+        assert_eq!(row["quarantine_date"], "2024-01-01");
+        assert_eq!(row["source_table"], "transactions");
+
+        let reasons: Vec<QualityViolation> =
+            serde_json::from_str(row["violations"].as_str().unwrap()).unwrap();
+        assert_eq!(reasons, violations);
+        let original: Value = serde_json::from_str(row["record_json"].as_str().unwrap()).unwrap();
+        assert_eq!(original, record);
+    }
+}
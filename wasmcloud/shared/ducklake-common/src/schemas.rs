@@ -127,7 +127,7 @@ pub fn transactions_schema() -> Arc<Schema> {
         // Format: [{"name":"_to","param_type":"address","value":"0x...","raw_value":"0x..."}]
         Field::new("decoded_parameters", DataType::Utf8, true), // JSON array of DecodedParameter
         // Decoding metadata
-        Field::new("decoding_status", DataType::Utf8, true), // Success, AbiNotFound, InvalidInput, DecodingError, Timeout, NativeTransfer, ContractCreation
+        Field::new("decoding_status", DataType::Utf8, true), // Success, PartialDecoded, HeuristicDecoded, AbiNotFound, InvalidInput, DecodingError, Timeout, NativeTransfer, ContractCreation
         Field::new("abi_source", DataType::Utf8, true), // etherscan, sourcify, 4byte, manual, unknown
         Field::new("decoding_time_ms", DataType::Int32, true), // Time taken to decode in milliseconds
         // Human-readable summary (optional, for UI display)
@@ -143,6 +143,17 @@ pub fn transactions_schema() -> Arc<Schema> {
         // SVM (Solana) fields
         Field::new("recent_blockhash", DataType::Utf8, true),
         Field::new("compute_units_consumed", DataType::Int64, true),
+        // ZK rollup fields (zkSync Era, Polygon zkEVM)
+        Field::new("l1_batch_number", DataType::Int64, true),
+        Field::new("l1_batch_tx_index", DataType::Int32, true),
+        Field::new("l1_sequence_tx_hash", DataType::Utf8, true), // L1 tx that sequenced/committed the batch
+        Field::new("l1_verify_tx_hash", DataType::Utf8, true),   // L1 tx that proved the batch
+        Field::new("l2_tx_kind", DataType::Utf8, true), // eip712, l1_priority, upgrade, standard
+        // Receipt-based fee accounting (EIP-1559 burn/tip, refunded gas)
+        Field::new("fee_burned", DataType::Decimal128(38, 18), true),
+        Field::new("fee_tip", DataType::Decimal128(38, 18), true),
+        Field::new("fee_refunded", DataType::Decimal128(38, 18), true),
+        Field::new("net_fee", DataType::Decimal128(38, 18), true),
         // ═══════════════════════════════════════════════════════════════════════════
         // PROCESSING METADATA
         // ═══════════════════════════════════════════════════════════════════════════
@@ -363,6 +374,8 @@ pub fn contract_calls_schema() -> Arc<Schema> {
         // Call status
         Field::new("success", DataType::Boolean, false),
         Field::new("revert_reason", DataType::Utf8, true),
+        // dApp attribution (router/front-end contract)
+        Field::new("dapp_name", DataType::Utf8, true),
         // Processing metadata
         Field::new(
             "ingested_at",
@@ -773,6 +786,10 @@ pub fn token_transfers_schema() -> Arc<Schema> {
         Field::new("to_address", DataType::Utf8, false),
         Field::new("amount", DataType::Decimal128(38, 18), false),
         Field::new("token_id", DataType::Utf8, true), // For NFTs (ERC721/ERC1155)
+        // tokenURI metadata for watched ERC-721 collections
+        Field::new("nft_name", DataType::Utf8, true),
+        Field::new("nft_image", DataType::Utf8, true),
+        Field::new("nft_token_uri", DataType::Utf8, true),
         // ═══════════════════════════════════════════════════════════════════════════
         // VALUE ENRICHMENT
         // ═══════════════════════════════════════════════════════════════════════════
@@ -836,6 +853,120 @@ pub fn address_transactions_schema() -> Arc<Schema> {
     ]))
 }
 
+/// Create Arrow schema for the entity_activity table
+///
+/// Cross-chain activity for addresses linked to the same entity by the
+/// clustering/labels system. One row per entity-linked address_transactions
+/// row, carrying a snapshot of the entity's rolling 24h cross-chain totals at
+/// the time of the event, so "entity moved > $X in 24h on any chain" is a
+/// single-row filter.
+///
+/// Partitioning: chain_id → year(block_timestamp) → month → day
+/// Z-order: entity_id, block_timestamp
+pub fn entity_activity_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // ═══════════════════════════════════════════════════════════════════════════
+        // PARTITION COLUMNS (function-based)
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("chain_id", DataType::Utf8, false),
+        Field::new("block_date", DataType::Date32, false),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // ENTITY KEY
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("entity_id", DataType::Utf8, false),
+        Field::new("entity_label", DataType::Utf8, true),
+        Field::new("address", DataType::Utf8, false),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // TRANSACTION CONTEXT
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("network", DataType::Utf8, false),
+        Field::new("subnet", DataType::Utf8, false),
+        Field::new("transaction_hash", DataType::Utf8, false),
+        Field::new("block_number", DataType::Int64, false),
+        Field::new(
+            "block_timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        Field::new("is_sender", DataType::Boolean, false),
+        Field::new("counterparty_address", DataType::Utf8, true),
+        Field::new("is_internal", DataType::Boolean, false), // Counterparty belongs to the same entity
+        Field::new("value", DataType::Decimal128(38, 18), true),
+        Field::new("value_usd", DataType::Float64, true),
+        Field::new("transaction_type", DataType::Utf8, true),
+        Field::new("transaction_subtype", DataType::Utf8, true),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // ROLLING 24H ENTITY ROLLUP (all chains)
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("window_inflow_usd_24h", DataType::Float64, false),
+        Field::new("window_outflow_usd_24h", DataType::Float64, false),
+        Field::new("window_tx_count_24h", DataType::Int64, false),
+        Field::new("window_chain_count_24h", DataType::Int32, false),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // PROCESSING METADATA
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
+/// Create Arrow schema for the dapp_usage table
+///
+/// One row per contract call that the contract transaction processor attributed
+/// to a dApp. `interaction_count` is the caller's lifetime call count into that
+/// dApp including this call, so "first time this wallet used X" is a
+/// single-row filter and per-address usage is a GROUP BY away.
+///
+/// Partitioning: chain_id → year(block_timestamp) → month → day
+/// Z-order: address, dapp_name, block_timestamp
+pub fn dapp_usage_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // ═══════════════════════════════════════════════════════════════════════════
+        // PARTITION COLUMNS (function-based)
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("chain_id", DataType::Utf8, false),
+        Field::new("block_date", DataType::Date32, false),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // USAGE KEY
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("address", DataType::Utf8, false),
+        Field::new("dapp_name", DataType::Utf8, false),
+        Field::new("dapp_source", DataType::Utf8, false), // knowledge_pack, builtin
+        // ═══════════════════════════════════════════════════════════════════════════
+        // CALL CONTEXT
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("network", DataType::Utf8, false),
+        Field::new("subnet", DataType::Utf8, false),
+        Field::new("contract_address", DataType::Utf8, false),
+        Field::new("transaction_hash", DataType::Utf8, false),
+        Field::new("block_number", DataType::Int64, false),
+        Field::new(
+            "block_timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        Field::new("transaction_subtype", DataType::Utf8, true), // swap, transfer, etc.
+        Field::new("category", DataType::Utf8, true),            // defi, token, etc.
+        Field::new("value", DataType::Decimal128(38, 18), true),
+        Field::new("success", DataType::Boolean, false),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // USAGE STATS
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new("interaction_count", DataType::Int64, false),
+        // ═══════════════════════════════════════════════════════════════════════════
+        // PROCESSING METADATA
+        // ═══════════════════════════════════════════════════════════════════════════
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
 // =============================================================================
 // Operational Tables (Existing)
 // =============================================================================
@@ -983,6 +1114,237 @@ pub fn notification_content_schema() -> Arc<Schema> {
     ]))
 }
 
+/// Create Arrow schema for the admin_audit table
+///
+/// Append-only trail of admin mutations (rules, watchlists, chains, config),
+/// written by the Django API. Each row is HMAC-signed by the API over its
+/// identifying fields and before/after snapshots, so rows are never updated.
+///
+/// Partitioning: audit_date
+/// Z-order: resource_type, resource_id, occurred_at
+pub fn admin_audit_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // Partition column
+        Field::new("audit_date", DataType::Date32, false),
+        // Record identity
+        Field::new("audit_id", DataType::Utf8, false),
+        Field::new(
+            "occurred_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        // Who
+        Field::new("actor_id", DataType::Utf8, true), // NULL for system changes
+        Field::new("actor_email", DataType::Utf8, true),
+        Field::new("source_ip", DataType::Utf8, true),
+        Field::new("request_method", DataType::Utf8, true),
+        Field::new("request_path", DataType::Utf8, true),
+        // What
+        Field::new("action", DataType::Utf8, false), // create, update, delete
+        Field::new("resource_type", DataType::Utf8, false), // rule, watchlist, chain, ...
+        Field::new("resource_id", DataType::Utf8, false),
+        Field::new("changed_fields", DataType::Utf8, true), // JSON: Vec<String>
+        Field::new("before_json", DataType::Utf8, true),    // NULL on create
+        Field::new("after_json", DataType::Utf8, true),     // NULL on delete
+        // Integrity
+        Field::new("signature", DataType::Utf8, false), // hex HMAC-SHA256
+        Field::new("signature_key_id", DataType::Utf8, false),
+        // Processing metadata
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
+/// Create Arrow schema for the quarantine table
+///
+/// Records rejected by the inline data-quality checks (`crate::quality`),
+/// kept verbatim with the reasons they failed instead of being written to
+/// their source table.
+///
+/// Partitioning: quarantine_date
+/// Z-order: source_table, chain_id, quarantined_at
+pub fn quarantine_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // Partition column
+        Field::new("quarantine_date", DataType::Date32, false),
+        Field::new(
+            "quarantined_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        // Origin
+        Field::new("chain_id", DataType::Utf8, false),
+        Field::new("source_table", DataType::Utf8, false),
+        // Failure
+        Field::new("violations", DataType::Utf8, false), // JSON: Vec<QualityViolation>
+        Field::new("record_json", DataType::Utf8, false),
+        // Processing metadata
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
+/// Create Arrow schema for the price_history table
+///
+/// Historical USD price series (daily or hourly buckets) fetched once per
+/// asset by the price-backfill job and joined onto imported transactions
+/// that were written without `amount_usd` / `fee_usd`.
+///
+/// Partitioning: chain_id, price_date
+/// Z-order: asset, interval, price_timestamp
+pub fn price_history_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // Partition columns
+        Field::new("chain_id", DataType::Utf8, false),
+        Field::new("price_date", DataType::Date32, false),
+        // Series identity
+        Field::new("asset", DataType::Utf8, false), // "native" or token contract address
+        Field::new("interval", DataType::Utf8, false), // "1d", "1h"
+        Field::new(
+            "price_timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ), // Bucket start
+        // Price data
+        Field::new("price_usd", DataType::Float64, false),
+        Field::new("source", DataType::Utf8, false),
+        // Processing metadata
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
+/// Create Arrow schema for the perp_events table
+///
+/// Position lifecycle events of on-chain perpetuals protocols (GMX v1
+/// vaults, Perpetual Protocol v2 clearing houses), decoded from logs by
+/// evm-logs-ingestion. Sizes, collateral, prices, fees and PnL are USD
+/// amounts already scaled by the protocol's precision (1e30 for GMX, 1e18
+/// for Perp v2); signed values are negative for losses and paid funding.
+///
+/// Partitioning: chain_id, block_date
+/// Z-order: account, market, block_timestamp
+pub fn perp_events_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // Partition columns
+        Field::new("chain_id", DataType::Utf8, false),
+        Field::new("block_date", DataType::Date32, false),
+        // Primary identifiers
+        Field::new("block_number", DataType::Int64, false),
+        Field::new(
+            "block_timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+        Field::new("transaction_hash", DataType::Utf8, false),
+        Field::new("log_index", DataType::Int32, false),
+        // Protocol
+        Field::new("protocol", DataType::Utf8, false), // "gmx_v1", "perp_v2"
+        Field::new("contract_address", DataType::Utf8, false),
+        // Event classification
+        Field::new("event_type", DataType::Utf8, false), // perp_open, perp_close, perp_liquidation, perp_funding
+        Field::new("event_name", DataType::Utf8, false),
+        // Position
+        Field::new("account", DataType::Utf8, false),
+        Field::new("market", DataType::Utf8, false), // Index token (GMX) or base token (Perp v2)
+        Field::new("collateral_token", DataType::Utf8, true),
+        Field::new("is_long", DataType::Boolean, true),
+        Field::new("position_key", DataType::Utf8, true),
+        // Amounts (USD)
+        Field::new("size_delta_usd", DataType::Decimal128(38, 18), true),
+        Field::new("collateral_delta_usd", DataType::Decimal128(38, 18), true),
+        Field::new("price_usd", DataType::Decimal128(38, 18), true),
+        Field::new("fee_usd", DataType::Decimal128(38, 18), true),
+        Field::new("realized_pnl_usd", DataType::Decimal128(38, 18), true),
+        Field::new("funding_payment_usd", DataType::Decimal128(38, 18), true),
+        Field::new("liquidator", DataType::Utf8, true),
+        // Processing metadata
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
+/// Create Arrow schema for the slashing_events table
+///
+/// One row per validator slashed on a beacon chain, recorded by the
+/// newheads-evm provider from the `proposer_slashings` and
+/// `attester_slashings` of each beacon block. `watched_by` is set when the
+/// validator or its withdrawal address was watched (and an operator alert
+/// was raised).
+///
+/// Partitioning: chain_id, slot_date
+/// Z-order: validator_index, withdrawal_address, slot
+pub fn slashing_events_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // Partition columns
+        Field::new("chain_id", DataType::Utf8, false),
+        Field::new("slot_date", DataType::Date32, false),
+        // Primary identifiers
+        Field::new("slot", DataType::Int64, false),
+        Field::new("epoch", DataType::Int64, false),
+        Field::new(
+            "block_timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            true,
+        ),
+        // Slashing
+        Field::new("slashing_type", DataType::Utf8, false), // proposer_slashing, attester_slashing
+        Field::new("validator_index", DataType::Int64, false),
+        Field::new("validator_pubkey", DataType::Utf8, true),
+        Field::new("withdrawal_address", DataType::Utf8, true),
+        Field::new("watched_by", DataType::Utf8, true), // validator, withdrawal_address
+        // Processing metadata
+        Field::new(
+            "ingested_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
+/// Create Arrow schema for the rpc_recordings table
+///
+/// One row per upstream JSON-RPC call answered while the http-rpc provider
+/// runs in record mode with the `ducklake` sink. `params`, `result` and
+/// `error` hold the call's JSON text (params in canonical form), so a
+/// recording exported as JSON lines can be replayed by the provider.
+///
+/// Partitioning: chain_id, recorded_date
+/// Z-order: method, recorded_at
+pub fn rpc_recordings_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        // Partition columns
+        Field::new("chain_id", DataType::Utf8, false), // provider pool network
+        Field::new("recorded_date", DataType::Date32, false),
+        // Call
+        Field::new("method", DataType::Utf8, false),
+        Field::new("params", DataType::Utf8, false),
+        // Answer
+        Field::new("result", DataType::Utf8, true),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("endpoint", DataType::Utf8, true), // host only
+        Field::new("latency_ms", DataType::Int64, true),
+        // Processing metadata
+        Field::new(
+            "recorded_at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]))
+}
+
 /// Table names as defined in the PRD
 pub const BLOCKS_TABLE: &str = "blocks";
 pub const TRANSACTIONS_TABLE: &str = "transactions";
@@ -992,6 +1354,12 @@ pub const PROTOCOL_EVENTS_TABLE: &str = "protocol_events";
 pub const CONTRACT_CALLS_TABLE: &str = "contract_calls";
 pub const NOTIFICATION_DELIVERIES_TABLE: &str = "notification_deliveries";
 pub const NOTIFICATION_CONTENT_TABLE: &str = "notification_content";
+pub const ADMIN_AUDIT_TABLE: &str = "admin_audit";
+pub const QUARANTINE_TABLE: &str = "quarantine";
+pub const PRICE_HISTORY_TABLE: &str = "price_history";
+pub const PERP_EVENTS_TABLE: &str = "perp_events";
+pub const SLASHING_EVENTS_TABLE: &str = "slashing_events";
+pub const RPC_RECORDINGS_TABLE: &str = "rpc_recordings";
 
 // ═══════════════════════════════════════════════════════════════════════════
 // DEPRECATED: VM-specific transaction tables (Schema Redesign)
@@ -1050,6 +1418,10 @@ pub const WALLET_BALANCES_TABLE: &str = "wallet_balances";
 pub const TOKEN_TRANSFERS_TABLE: &str = "token_transfers";
 pub const ADDRESS_TRANSACTIONS_TABLE: &str = "address_transactions";
 
+// Entity Aggregation Tables
+pub const ENTITY_ACTIVITY_TABLE: &str = "entity_activity";
+pub const DAPP_USAGE_TABLE: &str = "dapp_usage";
+
 /// Get schema for a table by name
 ///
 /// Supports both current and deprecated table names for backward compatibility.
@@ -1072,6 +1444,12 @@ pub fn get_schema_for_table(table_name: &str) -> Option<Arc<Schema>> {
         CONTRACT_CALLS_TABLE => Some(contract_calls_schema()),
         NOTIFICATION_DELIVERIES_TABLE => Some(notification_deliveries_schema()),
         NOTIFICATION_CONTENT_TABLE => Some(notification_content_schema()),
+        ADMIN_AUDIT_TABLE => Some(admin_audit_schema()),
+        QUARANTINE_TABLE => Some(quarantine_schema()),
+        PRICE_HISTORY_TABLE => Some(price_history_schema()),
+        PERP_EVENTS_TABLE => Some(perp_events_schema()),
+        SLASHING_EVENTS_TABLE => Some(slashing_events_schema()),
+        RPC_RECORDINGS_TABLE => Some(rpc_recordings_schema()),
         // DeFi Analytics Tables
         // DEPRECATED: processed_transfers uses its own schema but is deprecated
         PROCESSED_TRANSFERS_TABLE => Some(processed_transfers_schema()),
@@ -1086,6 +1464,9 @@ pub fn get_schema_for_table(table_name: &str) -> Option<Arc<Schema>> {
         // NEW: Unified Schema Tables (Schema Redesign)
         TOKEN_TRANSFERS_TABLE => Some(token_transfers_schema()),
         ADDRESS_TRANSACTIONS_TABLE => Some(address_transactions_schema()),
+        // Entity Aggregation Tables
+        ENTITY_ACTIVITY_TABLE => Some(entity_activity_schema()),
+        DAPP_USAGE_TABLE => Some(dapp_usage_schema()),
         _ => None,
     }
 }
@@ -1103,6 +1484,12 @@ pub fn get_all_table_names() -> Vec<&'static str> {
         CONTRACT_CALLS_TABLE,
         NOTIFICATION_DELIVERIES_TABLE,
         NOTIFICATION_CONTENT_TABLE,
+        ADMIN_AUDIT_TABLE,
+        QUARANTINE_TABLE,
+        PRICE_HISTORY_TABLE,
+        PERP_EVENTS_TABLE,
+        SLASHING_EVENTS_TABLE,
+        RPC_RECORDINGS_TABLE,
         // DEPRECATED: VM-specific transaction tables (kept for backward compatibility)
         TRANSACTIONS_EVM_TABLE,
         TRANSACTIONS_SVM_TABLE,
@@ -1123,6 +1510,9 @@ pub fn get_all_table_names() -> Vec<&'static str> {
         // NEW: Unified Schema Tables (Schema Redesign)
         TOKEN_TRANSFERS_TABLE,
         ADDRESS_TRANSACTIONS_TABLE,
+        // Entity Aggregation Tables
+        ENTITY_ACTIVITY_TABLE,
+        DAPP_USAGE_TABLE,
     ]
 }
 
@@ -1158,6 +1548,13 @@ pub fn get_partition_columns_for_table(table_name: &str) -> Vec<String> {
             "user_id_prefix".to_string(),
             "shard".to_string(),
         ],
+        // Admin audit trail is queried by date range first
+        ADMIN_AUDIT_TABLE => vec!["audit_date".to_string()],
+        QUARANTINE_TABLE => vec!["quarantine_date".to_string()],
+        PRICE_HISTORY_TABLE => vec!["chain_id".to_string(), "price_date".to_string()],
+        PERP_EVENTS_TABLE => vec!["chain_id".to_string(), "block_date".to_string()],
+        SLASHING_EVENTS_TABLE => vec!["chain_id".to_string(), "slot_date".to_string()],
+        RPC_RECORDINGS_TABLE => vec!["chain_id".to_string(), "recorded_date".to_string()],
         // Address-prefix partitioned tables
         WALLET_ACTIVITY_TABLE | ADDRESS_INDEX_TABLE => vec![
             "chain_id".to_string(),
@@ -1181,7 +1578,9 @@ pub fn get_partition_columns_for_table(table_name: &str) -> Vec<String> {
         | LOGS_TABLE
         | CONTRACT_CALLS_TABLE
         | TOKEN_TRANSFERS_TABLE
-        | ADDRESS_TRANSACTIONS_TABLE => vec!["chain_id".to_string(), "block_date".to_string()],
+        | ADDRESS_TRANSACTIONS_TABLE
+        | ENTITY_ACTIVITY_TABLE
+        | DAPP_USAGE_TABLE => vec!["chain_id".to_string(), "block_date".to_string()],
         // Standard 3-level partitioning for tables that still need explicit sharding
         _ => get_partition_columns(),
     }
@@ -1242,6 +1641,32 @@ pub fn get_z_order_columns(table_name: &str) -> Vec<String> {
             "created_at".to_string(),
             "priority".to_string(),
         ],
+        ADMIN_AUDIT_TABLE => vec![
+            "resource_type".to_string(),
+            "resource_id".to_string(),
+            "occurred_at".to_string(),
+        ],
+        QUARANTINE_TABLE => vec![
+            "source_table".to_string(),
+            "chain_id".to_string(),
+            "quarantined_at".to_string(),
+        ],
+        PRICE_HISTORY_TABLE => vec![
+            "asset".to_string(),
+            "interval".to_string(),
+            "price_timestamp".to_string(),
+        ],
+        PERP_EVENTS_TABLE => vec![
+            "account".to_string(),
+            "market".to_string(),
+            "block_timestamp".to_string(),
+        ],
+        SLASHING_EVENTS_TABLE => vec![
+            "validator_index".to_string(),
+            "withdrawal_address".to_string(),
+            "slot".to_string(),
+        ],
+        RPC_RECORDINGS_TABLE => vec!["method".to_string(), "recorded_at".to_string()],
         // DeFi Analytics Tables
         PROCESSED_TRANSFERS_TABLE => vec![
             "from_address".to_string(),
@@ -1285,10 +1710,35 @@ pub fn get_z_order_columns(table_name: &str) -> Vec<String> {
             "block_number".to_string(),
         ],
         ADDRESS_TRANSACTIONS_TABLE => vec!["address".to_string(), "block_number".to_string()],
+        // Entity Aggregation Tables
+        ENTITY_ACTIVITY_TABLE => vec!["entity_id".to_string(), "block_timestamp".to_string()],
+        DAPP_USAGE_TABLE => vec![
+            "address".to_string(),
+            "dapp_name".to_string(),
+            "block_timestamp".to_string(),
+        ],
         _ => vec!["block_number".to_string()],
     }
 }
 
+/// Key columns identifying a row for `ducklake.{table}.{chain}.{subnet}.upsert`
+///
+/// Upserts update the non-key columns of existing rows in place and insert
+/// rows whose key is missing. Tables without a stable natural key return
+/// `None` and only accept appends.
+pub fn get_upsert_key_columns(table_name: &str) -> Option<Vec<String>> {
+    match table_name {
+        TRANSACTIONS_TABLE => Some(vec!["chain_id".to_string(), "transaction_hash".to_string()]),
+        PRICE_HISTORY_TABLE => Some(vec![
+            "chain_id".to_string(),
+            "asset".to_string(),
+            "interval".to_string(),
+            "price_timestamp".to_string(),
+        ]),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 #[allow(deprecated)]
 mod tests {
@@ -1317,6 +1767,8 @@ mod tests {
         // NEW: Unified Schema Tables (Schema Redesign)
         assert!(!token_transfers_schema().fields().is_empty());
         assert!(!address_transactions_schema().fields().is_empty());
+        // Entity Aggregation tables
+        assert!(!entity_activity_schema().fields().is_empty());
     }
 
     #[test]
@@ -1333,9 +1785,18 @@ mod tests {
         assert!(get_schema_for_table(ADDRESS_INDEX_TABLE).is_some());
         // Notification tables
         assert!(get_schema_for_table(NOTIFICATION_CONTENT_TABLE).is_some());
+        assert!(get_schema_for_table(ADMIN_AUDIT_TABLE).is_some());
+        assert!(get_schema_for_table(QUARANTINE_TABLE).is_some());
+        assert!(get_schema_for_table(PRICE_HISTORY_TABLE).is_some());
+        assert!(get_schema_for_table(PERP_EVENTS_TABLE).is_some());
+        assert!(get_schema_for_table(SLASHING_EVENTS_TABLE).is_some());
+        assert!(get_schema_for_table(RPC_RECORDINGS_TABLE).is_some());
         // NEW: Unified Schema Tables (Schema Redesign)
         assert!(get_schema_for_table(TOKEN_TRANSFERS_TABLE).is_some());
         assert!(get_schema_for_table(ADDRESS_TRANSACTIONS_TABLE).is_some());
+        // Entity Aggregation tables
+        assert!(get_schema_for_table(ENTITY_ACTIVITY_TABLE).is_some());
+        assert!(get_schema_for_table(DAPP_USAGE_TABLE).is_some());
         // Nonexistent
         assert!(get_schema_for_table("nonexistent").is_none());
     }
@@ -1372,12 +1833,18 @@ mod tests {
     #[test]
     fn test_all_table_names() {
         let all_tables = get_all_table_names();
-        assert_eq!(all_tables.len(), 22); // 9 core + 4 VM-specific + 1 decoded + 6 DeFi + 2 new unified
+        assert_eq!(all_tables.len(), 28); // 9 core + 4 VM-specific + 1 decoded + 6 DeFi + 2 new unified + 1 entity
                                           // Core tables
         assert!(all_tables.contains(&BLOCKS_TABLE));
         assert!(all_tables.contains(&TRANSACTIONS_TABLE));
         assert!(all_tables.contains(&NOTIFICATION_DELIVERIES_TABLE));
         assert!(all_tables.contains(&NOTIFICATION_CONTENT_TABLE));
+        assert!(all_tables.contains(&ADMIN_AUDIT_TABLE));
+        assert!(all_tables.contains(&QUARANTINE_TABLE));
+        assert!(all_tables.contains(&PRICE_HISTORY_TABLE));
+        assert!(all_tables.contains(&PERP_EVENTS_TABLE));
+        assert!(all_tables.contains(&SLASHING_EVENTS_TABLE));
+        assert!(all_tables.contains(&RPC_RECORDINGS_TABLE));
         // DeFi tables
         assert!(all_tables.contains(&WALLET_ACTIVITY_TABLE));
         assert!(all_tables.contains(&LP_POSITIONS_TABLE));
@@ -1388,6 +1855,20 @@ mod tests {
         // NEW: Unified Schema Tables (Schema Redesign)
         assert!(all_tables.contains(&TOKEN_TRANSFERS_TABLE));
         assert!(all_tables.contains(&ADDRESS_TRANSACTIONS_TABLE));
+        // Entity Aggregation tables
+        assert!(all_tables.contains(&ENTITY_ACTIVITY_TABLE));
+        assert!(all_tables.contains(&DAPP_USAGE_TABLE));
+    }
+
+    #[test]
+    fn test_upsert_key_columns_exist_in_schema() {
+        for table in [TRANSACTIONS_TABLE, PRICE_HISTORY_TABLE] {
+            let schema = get_schema_for_table(table).unwrap();
+            for key in get_upsert_key_columns(table).unwrap() {
+                assert!(schema.field_with_name(&key).is_ok(), "{}.{}", table, key);
+            }
+        }
+        assert!(get_upsert_key_columns(ADMIN_AUDIT_TABLE).is_none());
     }
 
     #[test]
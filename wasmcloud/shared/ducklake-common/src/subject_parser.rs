@@ -7,6 +7,7 @@
 //! - `ducklake.transactions.ethereum.mainnet.write`
 //! - `ducklake.blocks.polygon.mainnet.write`
 //! - `ducklake.logs.arbitrum.one.write`
+//! - `ducklake.transactions.ethereum.mainnet.upsert`
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -16,17 +17,27 @@ use crate::error::DuckLakeError;
 use crate::schemas::{
     // NEW: Unified Schema Tables (Schema Redesign)
     ADDRESS_TRANSACTIONS_TABLE,
+    // Operational tables
+    ADMIN_AUDIT_TABLE,
     // Core tables
     BLOCKS_TABLE,
     CONTRACT_CALLS_TABLE,
+    // dApp usage tables
+    DAPP_USAGE_TABLE,
     // DEPRECATED: Decoded transaction tables
     DECODED_TRANSACTIONS_EVM_TABLE,
+    // Entity aggregation tables
+    ENTITY_ACTIVITY_TABLE,
     LOGS_TABLE,
     NOTIFICATION_CONTENT_TABLE,
     NOTIFICATION_DELIVERIES_TABLE,
+    PERP_EVENTS_TABLE,
+    PRICE_HISTORY_TABLE,
     // DEPRECATED: Processed/enriched transaction tables
     PROCESSED_TRANSFERS_TABLE,
     PROTOCOL_EVENTS_TABLE,
+    RPC_RECORDINGS_TABLE,
+    SLASHING_EVENTS_TABLE,
     TOKEN_PRICES_TABLE,
     TOKEN_TRANSFERS_TABLE,
     // DEPRECATED: VM-specific transaction tables (kept for backward compatibility)
@@ -92,7 +103,7 @@ impl SubjectInfo {
         // Validate action
         if !Self::is_valid_action(&action) {
             return Err(SubjectParseError::InvalidAction(format!(
-                "Unknown action: {}. Valid actions: write, query, compact",
+                "Unknown action: {}. Valid actions: write, upsert, query, compact",
                 action
             )));
         }
@@ -104,7 +115,7 @@ impl SubjectInfo {
         // We keep write/compact validation strict to prevent accidental writes to unknown tables.
         if action != "query" && !Self::is_valid_table(&table) {
             return Err(SubjectParseError::InvalidTable(format!(
-                "Unknown table: {}. Valid tables: blocks, transactions, transactions_evm, transactions_svm, transactions_btc, decoded_transactions_evm, logs, token_prices, protocol_events, contract_calls, notification_deliveries, notification_content, processed_transfers, token_transfers, address_transactions",
+                "Unknown table: {}. Valid tables: blocks, transactions, transactions_evm, transactions_svm, transactions_btc, decoded_transactions_evm, logs, token_prices, protocol_events, contract_calls, notification_deliveries, notification_content, processed_transfers, token_transfers, address_transactions, entity_activity, dapp_usage, admin_audit, price_history, perp_events, slashing_events, rpc_recordings",
                 table
             )));
         }
@@ -137,6 +148,11 @@ impl SubjectInfo {
                 | CONTRACT_CALLS_TABLE
                 | NOTIFICATION_DELIVERIES_TABLE
                 | NOTIFICATION_CONTENT_TABLE
+                | ADMIN_AUDIT_TABLE
+                | PRICE_HISTORY_TABLE
+                | PERP_EVENTS_TABLE
+                | SLASHING_EVENTS_TABLE
+                | RPC_RECORDINGS_TABLE
                 // DEPRECATED: VM-specific transaction tables (kept for backward compatibility)
                 | TRANSACTIONS_EVM_TABLE
                 | TRANSACTIONS_SVM_TABLE
@@ -148,12 +164,15 @@ impl SubjectInfo {
                 // NEW: Unified Schema Tables (Schema Redesign)
                 | TOKEN_TRANSFERS_TABLE
                 | ADDRESS_TRANSACTIONS_TABLE
+                // Entity aggregation tables
+                | ENTITY_ACTIVITY_TABLE
+                | DAPP_USAGE_TABLE
         )
     }
 
     /// Check if an action is valid
     pub fn is_valid_action(action: &str) -> bool {
-        matches!(action, "write" | "query" | "compact")
+        matches!(action, "write" | "upsert" | "query" | "compact")
     }
 
     /// Get the full NATS subject for this info
@@ -284,6 +303,11 @@ mod tests {
             "contract_calls",
             "notification_deliveries",
             "notification_content",
+            "admin_audit",
+            "price_history",
+            "perp_events",
+            "slashing_events",
+            "rpc_recordings",
             // VM-specific transaction tables
             "transactions_evm",
             "transactions_svm",
@@ -295,6 +319,9 @@ mod tests {
             // NEW: Unified Schema Tables (Schema Redesign)
             "token_transfers",
             "address_transactions",
+            // Entity aggregation tables
+            "entity_activity",
+            "dapp_usage",
         ];
 
         for table in tables {
@@ -306,7 +333,7 @@ mod tests {
 
     #[test]
     fn test_parse_all_actions() {
-        let actions = vec!["write", "query", "compact"];
+        let actions = vec!["write", "upsert", "query", "compact"];
 
         for action in actions {
             let subject = format!("ducklake.blocks.bitcoin.mainnet.{}", action);
@@ -520,6 +520,90 @@ impl SchemaMetadata {
     }
 }
 
+// ============================================================================
+// Gas Estimate Types (for gas.estimate.request)
+// ============================================================================
+
+/// Gas fee estimate request subject (answered by ducklake-read)
+pub const GAS_ESTIMATE_SUBJECT: &str = "gas.estimate.request";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasEstimateRequest {
+    /// DuckLake chain id, e.g. `ethereum_mainnet`
+    pub chain_id: String,
+    /// Target confirmation times in seconds
+    #[serde(default = "default_target_seconds")]
+    pub target_seconds: Vec<u64>,
+    /// Days of history for the cheapest windows
+    #[serde(default = "default_lookback_days")]
+    pub lookback_days: u32,
+    /// Number of cheapest windows to return
+    #[serde(default = "default_window_count")]
+    pub window_count: usize,
+}
+
+fn default_target_seconds() -> Vec<u64> {
+    vec![15, 60, 300, 3600]
+}
+
+fn default_lookback_days() -> u32 {
+    14
+}
+
+fn default_window_count() -> usize {
+    3
+}
+
+/// Recommended fees for one target confirmation time (wei per gas)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub target_seconds: u64,
+    pub target_blocks: u64,
+    pub max_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+}
+
+/// A recurring UTC hour of the week with low base fees
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheapWindow {
+    /// e.g. `Sunday 04:00 UTC`
+    pub label: String,
+    pub day_of_week: String,
+    pub hour_utc: u32,
+    pub median_base_fee_per_gas: u64,
+    pub samples: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasEstimateResponse {
+    pub success: bool,
+    pub chain_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_time_secs: Option<f64>,
+    #[serde(default)]
+    pub estimates: Vec<FeeEstimate>,
+    #[serde(default)]
+    pub cheapest_windows: Vec<CheapWindow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl GasEstimateResponse {
+    pub fn error(chain_id: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            chain_id: chain_id.into(),
+            base_fee_per_gas: None,
+            block_time_secs: None,
+            estimates: Vec::new(),
+            cheapest_windows: Vec::new(),
+            error: Some(error.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
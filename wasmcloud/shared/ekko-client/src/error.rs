@@ -0,0 +1,104 @@
+//! Error types for the request/reply client
+
+use std::time::Duration;
+
+use async_nats::{RequestError, RequestErrorKind};
+use thiserror::Error;
+
+/// Request/reply failures, tagged with the canonical subject
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// No reply within the request timeout
+    #[error("{subject}: no reply within {timeout:?}")]
+    Timeout { subject: String, timeout: Duration },
+
+    /// Nothing is subscribed to the subject (service down or prefix mismatch)
+    #[error("{subject}: no responders")]
+    NoResponders { subject: String },
+
+    /// NATS failed to send the request
+    #[error("{subject}: request failed: {message}")]
+    Transport { subject: String, message: String },
+
+    /// The request could not be serialized
+    #[error("{subject}: failed to encode request: {message}")]
+    Encode { subject: String, message: String },
+
+    /// The reply was not the expected type
+    #[error("{subject}: failed to decode reply: {message}")]
+    Decode { subject: String, message: String },
+
+    /// The service answered with an error
+    #[error("{subject}: {message}")]
+    Remote { subject: String, message: String },
+}
+
+impl ClientError {
+    pub fn subject(&self) -> &str {
+        match self {
+            Self::Timeout { subject, .. }
+            | Self::NoResponders { subject }
+            | Self::Transport { subject, .. }
+            | Self::Encode { subject, .. }
+            | Self::Decode { subject, .. }
+            | Self::Remote { subject, .. } => subject,
+        }
+    }
+
+    /// Whether retrying the same request may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Timeout { .. } | Self::NoResponders { .. } | Self::Transport { .. }
+        )
+    }
+
+    pub(crate) fn from_request(subject: &str, timeout: Duration, error: RequestError) -> Self {
+        let subject = subject.to_string();
+        match error.kind() {
+            RequestErrorKind::TimedOut => Self::Timeout { subject, timeout },
+            RequestErrorKind::NoResponders => Self::NoResponders { subject },
+            RequestErrorKind::Other => Self::Transport {
+                subject,
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+/// Result alias for client calls
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_error_mapping() {
+        let timeout = Duration::from_secs(5);
+        let timed_out = ClientError::from_request(
+            "admin.state.rebuild",
+            timeout,
+            RequestError::new(RequestErrorKind::TimedOut),
+        );
+        assert!(matches!(timed_out, ClientError::Timeout { .. }));
+        assert_eq!(timed_out.subject(), "admin.state.rebuild");
+        assert!(timed_out.is_retryable());
+
+        let no_responders = ClientError::from_request(
+            "gas.estimate.request",
+            timeout,
+            RequestError::new(RequestErrorKind::NoResponders),
+        );
+        assert_eq!(
+            no_responders.to_string(),
+            "gas.estimate.request: no responders"
+        );
+
+        let remote = ClientError::Remote {
+            subject: "ducklake.schema.get".to_string(),
+            message: "Table 'nope' not found".to_string(),
+        };
+        assert!(!remote.is_retryable());
+    }
+}
@@ -0,0 +1,469 @@
+//! Typed client for the internal NATS request/reply APIs.
+//!
+//! Wraps every request/reply subject served inside the platform so callers
+//! (gateway services, providers, integration tests) don't hand-roll subject
+//! strings and serde calls:
+//!
+//! | Method | Subject | Served by |
+//! |--------|---------|-----------|
+//! | [`EkkoClient::query`] | `ducklake.{table}.{network}.{subnet}.query` | ducklake-read |
+//! | [`EkkoClient::list_schemas`] / [`EkkoClient::get_schema`] | `ducklake.schema.list` / `.get` | ducklake-read |
+//! | [`EkkoClient::wire_schemas`] | `admin.schemas.get` | ducklake-read |
+//! | [`EkkoClient::estimate_gas`] | `gas.estimate.request` | ducklake-read |
+//! | [`EkkoClient::rebuild_state`] | `admin.state.rebuild` | state-rebuild |
+//! | [`EkkoClient::backfill_prices`] | `admin.prices.backfill` | price-backfill |
+//! | [`EkkoClient::check_consistency`] | `admin.consistency.check` | consistency-checker |
+//! | [`EkkoClient::register_subscription`] / `delete_` / `list_` | `events.subscriptions.*` | evm-logs-ingestion |
+//!
+//! Subjects are canonical; the client applies the environment prefix
+//! (`subject_registry::prefixed`) on the wire. Replies that report a failure
+//! (`success: false` or an `error` field) become [`ClientError::Remote`], so a
+//! returned value is always a successful reply.
+//!
+//! ```ignore
+//! let client = EkkoClient::connect("nats://localhost:4222").await?;
+//! let rows = client
+//!     .query_rows(
+//!         "transactions",
+//!         "ethereum",
+//!         "mainnet",
+//!         &QueryRequest::new("SELECT transaction_hash FROM transactions").with_limit(10),
+//!     )
+//!     .await?;
+//! let blocks = client.get_schema("blocks").await?.table;
+//! ```
+
+pub mod error;
+
+pub use error::{ClientError, Result};
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::time::Duration;
+
+use arrow::ipc::reader::StreamReader;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use consistency_checker::{ConsistencyCheckRequestV1, ConsistencyReportV1, CHECK_SUBJECT};
+use ducklake_common::types::{
+    GasEstimateRequest, GasEstimateResponse, QueryRequest, SchemaGetRequest, SchemaGetResponse,
+    SchemaListRequest, SchemaListResponse, GAS_ESTIMATE_SUBJECT,
+};
+use event_subscriptions::{
+    DeleteRequestV1, ListRequestV1, RegisterRequestV1, SubscriptionResponseV1, DELETE_SUBJECT,
+    LIST_SUBJECT, REGISTER_SUBJECT,
+};
+use price_backfill::{PriceBackfillRequestV1, PriceBackfillResultV1, BACKFILL_SUBJECT};
+use state_rebuild::{StateRebuildRequestV1, StateRebuildResultV1, REBUILD_SUBJECT};
+use wire_schemas::{WireSchemaGetRequest, WireSchemaGetResponse, ADMIN_SCHEMAS_GET_SUBJECT};
+
+/// Timeout for queries and lookups
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeout for admin jobs, which reply only when the whole job is done
+pub const DEFAULT_ADMIN_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// One DuckLake result row as display strings; NULL columns are omitted
+pub type Row = HashMap<String, String>;
+
+/// A reply that can report a failure in its body
+pub trait Reply {
+    /// The failure reported by the service, if any
+    fn remote_error(&self) -> Option<String>;
+}
+
+/// Failure message of a `success` / `error` reply
+fn failed(success: bool, error: &Option<String>) -> Option<String> {
+    match (success, error) {
+        (true, _) => None,
+        (false, Some(error)) => Some(error.clone()),
+        (false, None) => Some("request failed".to_string()),
+    }
+}
+
+impl Reply for SchemaListResponse {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+impl Reply for SchemaGetResponse {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+impl Reply for WireSchemaGetResponse {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+impl Reply for GasEstimateResponse {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+impl Reply for StateRebuildResultV1 {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+impl Reply for PriceBackfillResultV1 {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+impl Reply for ConsistencyReportV1 {
+    fn remote_error(&self) -> Option<String> {
+        self.error.clone()
+    }
+}
+
+impl Reply for SubscriptionResponseV1 {
+    fn remote_error(&self) -> Option<String> {
+        failed(self.success, &self.error)
+    }
+}
+
+/// Request/reply client over one NATS connection
+#[derive(Clone)]
+pub struct EkkoClient {
+    nats: async_nats::Client,
+    timeout: Duration,
+    admin_timeout: Duration,
+}
+
+impl EkkoClient {
+    pub fn new(nats: async_nats::Client) -> Self {
+        Self {
+            nats,
+            timeout: DEFAULT_TIMEOUT,
+            admin_timeout: DEFAULT_ADMIN_TIMEOUT,
+        }
+    }
+
+    /// Connect to NATS with the default timeouts
+    pub async fn connect(nats_url: &str) -> Result<Self> {
+        let nats = async_nats::connect(nats_url)
+            .await
+            .map_err(|e| ClientError::Transport {
+                subject: nats_url.to_string(),
+                message: e.to_string(),
+            })?;
+        Ok(Self::new(nats))
+    }
+
+    /// Set the timeout for queries and lookups
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the timeout for admin jobs
+    pub fn with_admin_timeout(mut self, timeout: Duration) -> Self {
+        self.admin_timeout = timeout;
+        self
+    }
+
+    pub fn nats(&self) -> &async_nats::Client {
+        &self.nats
+    }
+
+    /// Send a JSON request and wait for the raw reply
+    pub async fn request_bytes(
+        &self,
+        subject: &str,
+        body: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let request = async_nats::Request::new()
+            .payload(body.into())
+            .timeout(Some(timeout));
+        let reply = self
+            .nats
+            .send_request(subject_registry::prefixed(subject), request)
+            .await
+            .map_err(|e| ClientError::from_request(subject, timeout, e))?;
+        Ok(reply.payload.to_vec())
+    }
+
+    /// Send a typed request and decode a typed reply
+    pub async fn request<Req, Resp>(
+        &self,
+        subject: &str,
+        request: &Req,
+        timeout: Duration,
+    ) -> Result<Resp>
+    where
+        Req: Serialize,
+        Resp: DeserializeOwned + Reply,
+    {
+        let body = serde_json::to_vec(request).map_err(|e| ClientError::Encode {
+            subject: subject.to_string(),
+            message: e.to_string(),
+        })?;
+        let reply = self.request_bytes(subject, body, timeout).await?;
+        decode_reply(subject, &reply)
+    }
+
+    /// Run a SQL query against one chain partition of a table and return
+    /// the Arrow record batches
+    pub async fn query(
+        &self,
+        table: &str,
+        network: &str,
+        subnet: &str,
+        request: &QueryRequest,
+    ) -> Result<Vec<RecordBatch>> {
+        let subject = subject_registry::ducklake::chain_query(table, network, subnet);
+        let body = serde_json::to_vec(request).map_err(|e| ClientError::Encode {
+            subject: subject.clone(),
+            message: e.to_string(),
+        })?;
+        let reply = self.request_bytes(&subject, body, self.timeout).await?;
+        decode_batches(&subject, &reply)
+    }
+
+    /// [`EkkoClient::query`] with every value rendered as a string
+    pub async fn query_rows(
+        &self,
+        table: &str,
+        network: &str,
+        subnet: &str,
+        request: &QueryRequest,
+    ) -> Result<Vec<Row>> {
+        let subject = subject_registry::ducklake::chain_query(table, network, subnet);
+        let batches = self.query(table, network, subnet, request).await?;
+        batches_to_rows(&batches).map_err(|message| ClientError::Decode { subject, message })
+    }
+
+    pub async fn list_schemas(&self, request: &SchemaListRequest) -> Result<SchemaListResponse> {
+        self.request(
+            subject_registry::ducklake::schema_list(),
+            request,
+            self.timeout,
+        )
+        .await
+    }
+
+    pub async fn get_schema(&self, table_name: &str) -> Result<SchemaGetResponse> {
+        let request = SchemaGetRequest {
+            table_name: table_name.to_string(),
+        };
+        self.request(
+            subject_registry::ducklake::schema_get(),
+            &request,
+            self.timeout,
+        )
+        .await
+    }
+
+    /// Wire-format JSON Schemas; `None` lists every schema
+    pub async fn wire_schemas(&self, name: Option<&str>) -> Result<WireSchemaGetResponse> {
+        let request = WireSchemaGetRequest {
+            name: name.map(str::to_string),
+        };
+        self.request(ADMIN_SCHEMAS_GET_SUBJECT, &request, self.timeout)
+            .await
+    }
+
+    pub async fn estimate_gas(&self, request: &GasEstimateRequest) -> Result<GasEstimateResponse> {
+        self.request(GAS_ESTIMATE_SUBJECT, request, self.timeout)
+            .await
+    }
+
+    pub async fn rebuild_state(
+        &self,
+        request: &StateRebuildRequestV1,
+    ) -> Result<StateRebuildResultV1> {
+        self.request(REBUILD_SUBJECT, request, self.admin_timeout)
+            .await
+    }
+
+    pub async fn backfill_prices(
+        &self,
+        request: &PriceBackfillRequestV1,
+    ) -> Result<PriceBackfillResultV1> {
+        self.request(BACKFILL_SUBJECT, request, self.admin_timeout)
+            .await
+    }
+
+    pub async fn check_consistency(
+        &self,
+        request: &ConsistencyCheckRequestV1,
+    ) -> Result<ConsistencyReportV1> {
+        self.request(CHECK_SUBJECT, request, self.admin_timeout)
+            .await
+    }
+
+    pub async fn register_subscription(
+        &self,
+        request: &RegisterRequestV1,
+    ) -> Result<SubscriptionResponseV1> {
+        self.request(REGISTER_SUBJECT, request, self.timeout).await
+    }
+
+    pub async fn delete_subscription(
+        &self,
+        owner_id: &str,
+        subscription_id: &str,
+    ) -> Result<SubscriptionResponseV1> {
+        let request = DeleteRequestV1 {
+            owner_id: owner_id.to_string(),
+            subscription_id: subscription_id.to_string(),
+        };
+        self.request(DELETE_SUBJECT, &request, self.timeout).await
+    }
+
+    pub async fn list_subscriptions(&self, owner_id: &str) -> Result<SubscriptionResponseV1> {
+        let request = ListRequestV1 {
+            owner_id: owner_id.to_string(),
+        };
+        self.request(LIST_SUBJECT, &request, self.timeout).await
+    }
+}
+
+/// Decode a JSON reply, turning a reported failure into [`ClientError::Remote`]
+pub fn decode_reply<Resp>(subject: &str, reply: &[u8]) -> Result<Resp>
+where
+    Resp: DeserializeOwned + Reply,
+{
+    let response: Resp = serde_json::from_slice(reply).map_err(|e| ClientError::Decode {
+        subject: subject.to_string(),
+        message: e.to_string(),
+    })?;
+    match response.remote_error() {
+        Some(message) => Err(ClientError::Remote {
+            subject: subject.to_string(),
+            message,
+        }),
+        None => Ok(response),
+    }
+}
+
+/// Decode a DuckLake query reply: an Arrow IPC stream, or `{"error": ...}`
+/// when the query failed
+pub fn decode_batches(subject: &str, reply: &[u8]) -> Result<Vec<RecordBatch>> {
+    if reply.first() == Some(&b'{') {
+        let message = serde_json::from_slice::<serde_json::Value>(reply)
+            .ok()
+            .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(reply).into_owned());
+        return Err(ClientError::Remote {
+            subject: subject.to_string(),
+            message,
+        });
+    }
+    let decode_error = |e: arrow::error::ArrowError| ClientError::Decode {
+        subject: subject.to_string(),
+        message: e.to_string(),
+    };
+    StreamReader::try_new(Cursor::new(reply), None)
+        .map_err(decode_error)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(decode_error)
+}
+
+/// Render record batches as rows of display strings
+pub fn batches_to_rows(batches: &[RecordBatch]) -> std::result::Result<Vec<Row>, String> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        let schema = batch.schema();
+        for index in 0..batch.num_rows() {
+            let mut row = Row::new();
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                if column.is_null(index) {
+                    continue;
+                }
+                let value = array_value_to_string(column, index)
+                    .map_err(|e| format!("arrow value error: {}", e))?;
+                row.insert(field.name().clone(), value);
+            }
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::writer::StreamWriter;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_decode_reply_maps_reported_failures() {
+        let not_found = serde_json::to_vec(&SchemaGetResponse::not_found("nope")).unwrap();
+        match decode_reply::<SchemaGetResponse>("ducklake.schema.get", &not_found) {
+            Err(e @ ClientError::Remote { .. }) => {
+                assert_eq!(e.to_string(), "ducklake.schema.get: Table 'nope' not found")
+            }
+            other => panic!("unexpected {:?}", other.map(|r| r.success)),
+        }
+
+        let garbage = decode_reply::<GasEstimateResponse>("gas.estimate.request", b"not json");
+        assert!(matches!(garbage, Err(ClientError::Decode { .. })));
+
+        let report: ConsistencyReportV1 = serde_json::from_value(serde_json::json!({
+            "schema_version": "consistency_report_v1",
+            "check_id": "c1",
+            "network": "ethereum",
+            "subnet": "mainnet",
+            "sampled": 0,
+            "checks": [],
+            "examples": [],
+            "started_at": "2024-01-01T00:00:00Z",
+            "completed_at": "2024-01-01T00:00:01Z"
+        }))
+        .unwrap();
+        let body = serde_json::to_vec(&report).unwrap();
+        let decoded: ConsistencyReportV1 = decode_reply("admin.consistency.check", &body).unwrap();
+        assert_eq!(decoded.check_id, "c1");
+    }
+
+    #[test]
+    fn test_decode_query_reply() {
+        let subject = "ducklake.transactions.ethereum.mainnet.query";
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("transaction_hash", DataType::Utf8, false),
+            Field::new("block_number", DataType::Int64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["0xaa", "0xbb"])),
+                Arc::new(Int64Array::from(vec![Some(7), None])),
+            ],
+        )
+        .unwrap();
+        let mut ipc = Vec::new();
+        {
+            let mut writer = StreamWriter::try_new(&mut ipc, &schema).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let batches = decode_batches(subject, &ipc).unwrap();
+        let rows = batches_to_rows(&batches).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["block_number"], "7");
+        assert!(!rows[1].contains_key("block_number"));
+
+        let failed = decode_batches(subject, br#"{"error": "Catalog Error: no such table"}"#);
+        match failed {
+            Err(ClientError::Remote { message, .. }) => {
+                assert_eq!(message, "Catalog Error: no such table")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Event signature parsing and log decoding
+//!
+//! Supports the static ABI types (`address`, `bool`, `uintN`, `intN`,
+//! `bytesN`) plus `string` and `bytes`. Indexed dynamic parameters are only
+//! available as their topic hash. Arrays and tuples are rejected at
+//! registration.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tiny_keccak::{Hasher, Keccak};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventParamV1 {
+    pub name: String,
+    /// Canonical ABI type, e.g. `uint256`
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub indexed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedEvent {
+    pub name: String,
+    pub inputs: Vec<EventParamV1>,
+    /// `Name(type,type,...)`
+    pub canonical: String,
+    /// `keccak256(canonical)` as 0x-prefixed hex
+    pub topic0: String,
+}
+
+/// Parse `Transfer(address indexed from, address indexed to, uint256 value)`
+///
+/// Unnamed parameters are named `arg{index}`; `uint`/`int` are widened to
+/// their 256-bit canonical form.
+pub fn parse_event_signature(signature: &str) -> Result<ParsedEvent, String> {
+    let signature = signature.trim();
+    let open = signature
+        .find('(')
+        .ok_or_else(|| format!("Invalid event signature: {}", signature))?;
+    if !signature.ends_with(')') {
+        return Err(format!("Invalid event signature: {}", signature));
+    }
+    let name = signature[..open].trim().trim_start_matches("event ").trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid event name: {}", name));
+    }
+
+    let body = &signature[open + 1..signature.len() - 1];
+    let mut inputs = Vec::new();
+    for (index, param) in body.split(',').map(str::trim).enumerate() {
+        if param.is_empty() {
+            if body.trim().is_empty() {
+                break;
+            }
+            return Err(format!("Empty parameter in {}", signature));
+        }
+        let mut parts = param.split_whitespace();
+        let kind = canonical_type(parts.next().unwrap_or_default())?;
+        let mut indexed = false;
+        let mut param_name = None;
+        for part in parts {
+            if part == "indexed" && !indexed && param_name.is_none() {
+                indexed = true;
+            } else if param_name.is_none() {
+                param_name = Some(part.to_string());
+            } else {
+                return Err(format!("Invalid parameter: {}", param));
+            }
+        }
+        inputs.push(EventParamV1 {
+            name: param_name.unwrap_or_else(|| format!("arg{}", index)),
+            kind,
+            indexed,
+        });
+    }
+    if inputs.iter().filter(|p| p.indexed).count() > 3 {
+        return Err("At most 3 parameters can be indexed".to_string());
+    }
+
+    let canonical = format!(
+        "{}({})",
+        name,
+        inputs
+            .iter()
+            .map(|p| p.kind.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let topic0 = format!("0x{}", hex(&keccak256(canonical.as_bytes())));
+    Ok(ParsedEvent {
+        name: name.to_string(),
+        inputs,
+        canonical,
+        topic0,
+    })
+}
+
+fn canonical_type(kind: &str) -> Result<String, String> {
+    let kind = match kind {
+        "uint" => "uint256",
+        "int" => "int256",
+        other => other,
+    };
+    let bits_ok = |bits: &str| {
+        bits.parse::<u32>()
+            .is_ok_and(|n| n > 0 && n <= 256 && n % 8 == 0)
+    };
+    let bytes_ok = |len: &str| len.parse::<u32>().is_ok_and(|n| (1..=32).contains(&n));
+    let supported = match kind {
+        "address" | "bool" | "string" | "bytes" => true,
+        _ => {
+            if let Some(bits) = kind.strip_prefix("uint") {
+                bits_ok(bits)
+            } else if let Some(bits) = kind.strip_prefix("int") {
+                bits_ok(bits)
+            } else if let Some(len) = kind.strip_prefix("bytes") {
+                bytes_ok(len)
+            } else {
+                false
+            }
+        }
+    };
+    if supported {
+        Ok(kind.to_string())
+    } else {
+        Err(format!("Unsupported event parameter type: {}", kind))
+    }
+}
+
+/// Decode a log's topics and data into `{param name: value}`
+///
+/// Integers decode to decimal strings, addresses and byte strings to
+/// lowercase 0x-hex, `bool` to a JSON boolean.
+pub fn decode_log(
+    event: &ParsedEvent,
+    topics: &[&str],
+    data: &str,
+) -> Result<Map<String, Value>, String> {
+    if topics.first().map(|t| t.to_lowercase()) != Some(event.topic0.clone()) {
+        return Err("topic0 does not match the event signature".to_string());
+    }
+    let data = unhex(data)?;
+    let mut decoded = Map::new();
+    let mut topic_index = 1;
+    let mut head = 0;
+
+    for param in &event.inputs {
+        let value = if param.indexed {
+            let topic = topics
+                .get(topic_index)
+                .ok_or_else(|| format!("Missing topic for {}", param.name))?;
+            topic_index += 1;
+            let topic_word = word(&unhex(topic)?, 0)?;
+            if is_dynamic(&param.kind) {
+                // Only the hash of indexed strings and bytes is logged
+                Value::String(format!("0x{}", hex(&topic_word)))
+            } else {
+                decode_word(&param.kind, &topic_word)
+            }
+        } else {
+            let head_word = word(&data, head)?;
+            head += 32;
+            if is_dynamic(&param.kind) {
+                decode_dynamic(&param.kind, &data, &head_word)?
+            } else {
+                decode_word(&param.kind, &head_word)
+            }
+        };
+        decoded.insert(param.name.clone(), value);
+    }
+    Ok(decoded)
+}
+
+fn is_dynamic(kind: &str) -> bool {
+    kind == "string" || kind == "bytes"
+}
+
+fn decode_word(kind: &str, word: &[u8; 32]) -> Value {
+    match kind {
+        "address" => Value::String(format!("0x{}", hex(&word[12..]))),
+        "bool" => Value::Bool(word[31] != 0),
+        _ if kind.starts_with("uint") => Value::String(to_decimal(word)),
+        _ if kind.starts_with("int") => {
+            if word[0] & 0x80 == 0 {
+                Value::String(to_decimal(word))
+            } else {
+                // Two's complement magnitude
+                let mut magnitude = word.map(|b| !b);
+                for byte in magnitude.iter_mut().rev() {
+                    let (sum, carry) = byte.overflowing_add(1);
+                    *byte = sum;
+                    if !carry {
+                        break;
+                    }
+                }
+                Value::String(format!("-{}", to_decimal(&magnitude)))
+            }
+        }
+        _ => {
+            let len = kind
+                .strip_prefix("bytes")
+                .and_then(|n| n.parse::<usize>().ok())
+                .unwrap_or(32);
+            Value::String(format!("0x{}", hex(&word[..len.min(32)])))
+        }
+    }
+}
+
+fn decode_dynamic(kind: &str, data: &[u8], head_word: &[u8; 32]) -> Result<Value, String> {
+    let offset = word_to_usize(head_word)?;
+    let len = word_to_usize(&word(data, offset)?)?;
+    let start = offset + 32;
+    let bytes = data
+        .get(start..start.saturating_add(len))
+        .ok_or_else(|| "Dynamic value out of bounds".to_string())?;
+    Ok(match kind {
+        "string" => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+        _ => Value::String(format!("0x{}", hex(bytes))),
+    })
+}
+
+fn word(bytes: &[u8], offset: usize) -> Result<[u8; 32], String> {
+    bytes
+        .get(offset..offset + 32)
+        .and_then(|slice| slice.try_into().ok())
+        .ok_or_else(|| format!("Log data too short at offset {}", offset))
+}
+
+fn word_to_usize(word: &[u8; 32]) -> Result<usize, String> {
+    if word[..24].iter().any(|b| *b != 0) {
+        return Err("Offset out of range".to_string());
+    }
+    Ok(u64::from_be_bytes(word[24..].try_into().unwrap_or_default()) as usize)
+}
+
+/// Big-endian unsigned integer to decimal
+fn to_decimal(word: &[u8; 32]) -> String {
+    let mut digits = Vec::new();
+    let mut value = word.to_vec();
+    while value.iter().any(|b| *b != 0) {
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap_or_default()
+}
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut out = [0u8; 32];
+    hasher.update(bytes);
+    hasher.finalize(&mut out);
+    out
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(value: &str) -> Result<Vec<u8>, String> {
+    let digits = value.trim_start_matches("0x");
+    if digits.len() % 2 != 0 {
+        return Err(format!("Odd-length hex: {}", value));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| format!("Invalid hex: {}", value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER_TOPIC0: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn pad(hex_value: &str) -> String {
+        format!("{:0>64}", hex_value)
+    }
+
+    #[test]
+    fn test_parse_transfer_signature() {
+        let event =
+            parse_event_signature("Transfer(address indexed from, address indexed to, uint value)")
+                .unwrap();
+        assert_eq!(event.canonical, "Transfer(address,address,uint256)");
+        assert_eq!(event.topic0, TRANSFER_TOPIC0);
+        assert!(event.inputs[0].indexed && !event.inputs[2].indexed);
+
+        let unnamed = parse_event_signature("Ping(string,bytes32)").unwrap();
+        assert_eq!(unnamed.inputs[1].name, "arg1");
+        assert!(parse_event_signature("Bad(uint256[])").is_err());
+        assert!(parse_event_signature("Bad(uint7)").is_err());
+        assert!(parse_event_signature("NoParens").is_err());
+    }
+
+    #[test]
+    fn test_decode_transfer_log() {
+        let event = parse_event_signature(
+            "Transfer(address indexed from, address indexed to, uint256 value)",
+        )
+        .unwrap();
+        let from = format!("0x{}", pad("aa"));
+        let to = format!("0x{}", pad("BB"));
+        let data = format!("0x{}", pad("de0b6b3a7640000"));
+        let decoded = decode_log(&event, &[TRANSFER_TOPIC0, &from, &to], &data).unwrap();
+        assert_eq!(
+            decoded["from"],
+            "0x00000000000000000000000000000000000000aa"
+        );
+        assert_eq!(decoded["to"], "0x00000000000000000000000000000000000000bb");
+        assert_eq!(decoded["value"], "1000000000000000000");
+
+        assert!(decode_log(&event, &[&from], &data).is_err());
+    }
+
+    #[test]
+    fn test_decode_signed_bool_and_string() {
+        let event = parse_event_signature("Note(int256 delta, bool ok, string memo)").unwrap();
+        let minus_two = "f".repeat(63) + "e";
+        let memo = format!("{:0<64}", "6869");
+        let data = format!(
+            "0x{}{}{}{}{}",
+            minus_two,
+            pad("1"),
+            pad("60"),
+            pad("2"),
+            memo
+        );
+        let decoded = decode_log(&event, &[&event.topic0], &data).unwrap();
+        assert_eq!(decoded["delta"], "-2");
+        assert_eq!(decoded["ok"], true);
+        assert_eq!(decoded["memo"], "hi");
+    }
+}
@@ -0,0 +1,729 @@
+//! Per-contract decoded event subscriptions.
+//!
+//! External consumers register `(chain, contract address, event signature)`
+//! on [`REGISTER_SUBJECT`] and receive every matching log, decoded, as a
+//! [`DecodedEventV1`] while evm-logs-ingestion processes blocks. Delivery is
+//! either the subscription's dedicated subject
+//! (`events.decoded.{subscription_id}`) or the owner's configured webhook via
+//! the webhook notification provider. Each subscription can filter on decoded
+//! parameters and is rate limited per minute.
+//!
+//! Subscriptions live in Redis:
+//! - `events:sub:{id}` - the subscription
+//! - `events:sub:chain:{network}:{subnet}` - all subscriptions of a chain,
+//!   read once per block
+//! - `events:sub:owner:{owner_id}` - subscription ids of an owner
+//! - `events:sub:rate:{id}` - current rate limit window
+
+pub mod abi;
+
+pub use abi::{decode_log, parse_event_signature, EventParamV1, ParsedEvent};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+pub const REGISTER_SUBJECT: &str = "events.subscriptions.register";
+pub const DELETE_SUBJECT: &str = "events.subscriptions.delete";
+pub const LIST_SUBJECT: &str = "events.subscriptions.list";
+/// Prefix of the dedicated per-subscription delivery subjects
+pub const DECODED_SUBJECT_PREFIX: &str = "events.decoded";
+/// Webhook notification provider request subject
+pub const WEBHOOK_SUBJECT: &str = "notifications.send.immediate.webhook";
+
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 600;
+pub const MAX_RATE_LIMIT_PER_MINUTE: u32 = 6_000;
+pub const MAX_SUBSCRIPTIONS_PER_OWNER: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryV1 {
+    /// Publish on `events.decoded.{subscription_id}`
+    #[default]
+    Subject,
+    /// Send to the owner's webhook configuration
+    Webhook,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOpV1 {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// `value` is an array of accepted values
+    In,
+}
+
+/// Condition on one decoded parameter; all filters must match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventFilterV1 {
+    pub param: String,
+    pub op: FilterOpV1,
+    pub value: Value,
+}
+
+impl EventFilterV1 {
+    pub fn matches(&self, decoded: &Map<String, Value>) -> bool {
+        let Some(actual) = decoded.get(&self.param) else {
+            return false;
+        };
+        match self.op {
+            FilterOpV1::Eq => values_equal(actual, &self.value),
+            FilterOpV1::Ne => !values_equal(actual, &self.value),
+            FilterOpV1::In => self
+                .value
+                .as_array()
+                .is_some_and(|values| values.iter().any(|v| values_equal(actual, v))),
+            op => {
+                let ordering = compare_decimal(&scalar(actual), &scalar(&self.value));
+                match (op, ordering) {
+                    (_, None) => false,
+                    (FilterOpV1::Gt, Some(o)) => o == Ordering::Greater,
+                    (FilterOpV1::Gte, Some(o)) => o != Ordering::Less,
+                    (FilterOpV1::Lt, Some(o)) => o == Ordering::Less,
+                    (_, Some(o)) => o != Ordering::Greater,
+                }
+            }
+        }
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Strings compare case-insensitively so checksummed addresses match
+fn values_equal(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        _ => scalar(actual).eq_ignore_ascii_case(&scalar(expected)),
+    }
+}
+
+/// Compare decimal integer strings of any length
+fn compare_decimal(a: &str, b: &str) -> Option<Ordering> {
+    fn split(value: &str) -> Option<(bool, &str)> {
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let digits = digits.trim_start_matches('0');
+        Some((negative && !digits.is_empty(), digits))
+    }
+    let (a_negative, a_digits) = split(a)?;
+    let (b_negative, b_digits) = split(b)?;
+    let magnitude = a_digits
+        .len()
+        .cmp(&b_digits.len())
+        .then_with(|| a_digits.cmp(b_digits));
+    Some(match (a_negative, b_negative) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSubscriptionV1 {
+    pub schema_version: String,
+    pub subscription_id: String,
+    pub owner_id: String,
+    pub network: String,
+    pub subnet: String,
+    /// Lowercase 0x-prefixed address
+    pub contract_address: String,
+    pub event_name: String,
+    /// Canonical signature, e.g. `Transfer(address,address,uint256)`
+    pub event_signature: String,
+    pub topic0: String,
+    pub inputs: Vec<EventParamV1>,
+    #[serde(default)]
+    pub filters: Vec<EventFilterV1>,
+    #[serde(default)]
+    pub delivery: DeliveryV1,
+    pub rate_limit_per_minute: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EventSubscriptionV1 {
+    pub fn event(&self) -> ParsedEvent {
+        ParsedEvent {
+            name: self.event_name.clone(),
+            inputs: self.inputs.clone(),
+            canonical: self.event_signature.clone(),
+            topic0: self.topic0.clone(),
+        }
+    }
+
+    /// Subject decoded events are published on
+    pub fn delivery_subject(&self) -> String {
+        match self.delivery {
+            DeliveryV1::Subject => format!("{}.{}", DECODED_SUBJECT_PREFIX, self.subscription_id),
+            DeliveryV1::Webhook => WEBHOOK_SUBJECT.to_string(),
+        }
+    }
+
+    pub fn matches(&self, decoded: &Map<String, Value>) -> bool {
+        self.filters.iter().all(|filter| filter.matches(decoded))
+    }
+}
+
+/// `events.subscriptions.register` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterRequestV1 {
+    pub owner_id: String,
+    pub network: String,
+    pub subnet: String,
+    pub contract_address: String,
+    /// Human-readable signature, e.g.
+    /// `Transfer(address indexed from, address indexed to, uint256 value)`
+    pub event_signature: String,
+    #[serde(default)]
+    pub filters: Vec<EventFilterV1>,
+    #[serde(default)]
+    pub delivery: DeliveryV1,
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+/// `events.subscriptions.delete` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteRequestV1 {
+    pub owner_id: String,
+    pub subscription_id: String,
+}
+
+/// `events.subscriptions.list` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListRequestV1 {
+    pub owner_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionResponseV1 {
+    pub success: bool,
+    pub subscriptions: Vec<EventSubscriptionV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SubscriptionResponseV1 {
+    fn ok(subscriptions: Vec<EventSubscriptionV1>) -> Self {
+        Self {
+            success: true,
+            subscriptions,
+            error: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            subscriptions: Vec::new(),
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Decoded log delivered to a subscription
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedEventV1 {
+    pub schema_version: String,
+    pub subscription_id: String,
+    pub network: String,
+    pub subnet: String,
+    pub contract_address: String,
+    pub event_name: String,
+    pub event_signature: String,
+    pub transaction_hash: String,
+    pub log_index: i64,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub params: Map<String, Value>,
+}
+
+impl DecodedEventV1 {
+    /// Message for the subscription's delivery subject
+    pub fn payload(&self, subscription: &EventSubscriptionV1) -> Value {
+        match subscription.delivery {
+            DeliveryV1::Subject => json!(self),
+            // WebhookNotificationRequest of the webhook provider
+            DeliveryV1::Webhook => json!({
+                "notification_id": format!(
+                    "{}:{}:{}",
+                    self.subscription_id, self.transaction_hash, self.log_index
+                ),
+                "user_id": subscription.owner_id,
+                "alert_id": self.subscription_id,
+                "alert_name": self.event_name,
+                "priority": "normal",
+                "payload": self,
+                "timestamp": self.block_timestamp,
+            }),
+        }
+    }
+}
+
+pub fn event_subscription_schema_version_v1() -> String {
+    "event_subscription_v1".to_string()
+}
+
+pub fn decoded_event_schema_version_v1() -> String {
+    "decoded_event_v1".to_string()
+}
+
+/// Fixed one-minute rate limit window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateWindowV1 {
+    pub window_start: i64,
+    pub count: u32,
+}
+
+impl RateWindowV1 {
+    /// Count one delivery at `now` (unix seconds); `false` once the limit is
+    /// reached in the current minute
+    pub fn admit(&mut self, now: i64, limit: u32) -> bool {
+        let window_start = now - now.rem_euclid(60);
+        if window_start != self.window_start {
+            self.window_start = window_start;
+            self.count = 0;
+        }
+        if self.count >= limit {
+            return false;
+        }
+        self.count += 1;
+        true
+    }
+}
+
+/// Subscriptions of one chain keyed by `(contract address, topic0)`
+#[derive(Debug, Default)]
+pub struct SubscriptionIndex {
+    by_log: HashMap<(String, String), Vec<EventSubscriptionV1>>,
+}
+
+impl SubscriptionIndex {
+    pub fn new(subscriptions: Vec<EventSubscriptionV1>) -> Self {
+        let mut by_log: HashMap<(String, String), Vec<EventSubscriptionV1>> = HashMap::new();
+        for subscription in subscriptions {
+            by_log
+                .entry((
+                    subscription.contract_address.clone(),
+                    subscription.topic0.clone(),
+                ))
+                .or_default()
+                .push(subscription);
+        }
+        Self { by_log }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_log.is_empty()
+    }
+
+    /// Subscriptions whose event and filters match the log, with the decoded
+    /// parameters
+    pub fn matches(
+        &self,
+        address: &str,
+        topics: &[&str],
+        data: &str,
+    ) -> Vec<(&EventSubscriptionV1, Map<String, Value>)> {
+        let Some(topic0) = topics.first() else {
+            return Vec::new();
+        };
+        let key = (address.to_lowercase(), topic0.to_lowercase());
+        let Some(subscriptions) = self.by_log.get(&key) else {
+            return Vec::new();
+        };
+        subscriptions
+            .iter()
+            .filter_map(|subscription| {
+                let decoded = decode_log(&subscription.event(), topics, data).ok()?;
+                subscription
+                    .matches(&decoded)
+                    .then_some((subscription, decoded))
+            })
+            .collect()
+    }
+}
+
+/// Redis access for the subscription handlers
+pub trait SubscriptionStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), String>;
+    fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+fn chain_key(network: &str, subnet: &str) -> String {
+    retention_policy::EVENT_SUBSCRIPTION_CHAIN.key(&format!(
+        "{}:{}",
+        network.to_lowercase(),
+        subnet.to_lowercase()
+    ))
+}
+
+fn load<T: serde::de::DeserializeOwned + Default>(
+    store: &dyn SubscriptionStore,
+    key: &str,
+) -> Result<T, String> {
+    match store.get(key)? {
+        Some(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|e| format!("Failed to parse {}: {}", key, e))
+        }
+        None => Ok(T::default()),
+    }
+}
+
+fn save<T: Serialize>(store: &dyn SubscriptionStore, key: &str, value: &T) -> Result<(), String> {
+    let bytes =
+        serde_json::to_vec(value).map_err(|e| format!("Failed to serialize {}: {}", key, e))?;
+    store.set(key, &bytes)
+}
+
+/// All subscriptions of a chain
+pub fn load_chain(
+    store: &dyn SubscriptionStore,
+    network: &str,
+    subnet: &str,
+) -> Result<Vec<EventSubscriptionV1>, String> {
+    load(store, &chain_key(network, subnet))
+}
+
+/// Handle a request on one of the subscription subjects
+pub fn handle_request(
+    store: &dyn SubscriptionStore,
+    subject: &str,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> SubscriptionResponseV1 {
+    let result = match subject {
+        REGISTER_SUBJECT => serde_json::from_slice(body)
+            .map_err(|e| format!("Invalid register request: {}", e))
+            .and_then(|request| register(store, &request, now).map(|sub| vec![sub])),
+        DELETE_SUBJECT => serde_json::from_slice(body)
+            .map_err(|e| format!("Invalid delete request: {}", e))
+            .and_then(|request| delete(store, &request).map(|sub| vec![sub])),
+        LIST_SUBJECT => serde_json::from_slice(body)
+            .map_err(|e| format!("Invalid list request: {}", e))
+            .and_then(|request: ListRequestV1| list(store, &request.owner_id)),
+        other => Err(format!("Unknown subscription subject: {}", other)),
+    };
+    match result {
+        Ok(subscriptions) => SubscriptionResponseV1::ok(subscriptions),
+        Err(e) => SubscriptionResponseV1::error(e),
+    }
+}
+
+/// Register a subscription. Registering the same owner, chain, contract,
+/// event and filters again returns the existing subscription.
+pub fn register(
+    store: &dyn SubscriptionStore,
+    request: &RegisterRequestV1,
+    now: DateTime<Utc>,
+) -> Result<EventSubscriptionV1, String> {
+    if request.owner_id.is_empty() {
+        return Err("owner_id is required".to_string());
+    }
+    let address = request.contract_address.to_lowercase();
+    let digits = address.strip_prefix("0x").unwrap_or_default();
+    if digits.len() != 40 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid contract address: {}",
+            request.contract_address
+        ));
+    }
+    let event = parse_event_signature(&request.event_signature)?;
+    for filter in &request.filters {
+        let Some(param) = event.inputs.iter().find(|p| p.name == filter.param) else {
+            return Err(format!("Unknown filter parameter: {}", filter.param));
+        };
+        let numeric = param.kind.starts_with("uint") || param.kind.starts_with("int");
+        match filter.op {
+            FilterOpV1::In if !filter.value.is_array() => {
+                return Err(format!("Filter on {} needs an array value", filter.param));
+            }
+            FilterOpV1::Gt | FilterOpV1::Gte | FilterOpV1::Lt | FilterOpV1::Lte if !numeric => {
+                return Err(format!(
+                    "Filter on {} needs an integer parameter",
+                    filter.param
+                ));
+            }
+            _ => {}
+        }
+    }
+    let rate_limit_per_minute = request
+        .rate_limit_per_minute
+        .unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE)
+        .clamp(1, MAX_RATE_LIMIT_PER_MINUTE);
+
+    let network = request.network.to_lowercase();
+    let subnet = request.subnet.to_lowercase();
+    let filters_json = serde_json::to_string(&request.filters).unwrap_or_default();
+    let fingerprint = abi::keccak256(
+        format!(
+            "{}|{}|{}|{}|{}|{}",
+            request.owner_id, network, subnet, address, event.topic0, filters_json
+        )
+        .as_bytes(),
+    );
+    let subscription_id: String = fingerprint[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    let subscription_key = retention_policy::EVENT_SUBSCRIPTION.key(&subscription_id);
+    if let Some(bytes) = store.get(&subscription_key)? {
+        return serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Failed to parse subscription: {}", e));
+    }
+
+    let owner_key = retention_policy::EVENT_SUBSCRIPTION_OWNER.key(&request.owner_id);
+    let mut owner_ids: Vec<String> = load(store, &owner_key)?;
+    if owner_ids.len() >= MAX_SUBSCRIPTIONS_PER_OWNER {
+        return Err(format!(
+            "Owner {} already has {} subscriptions",
+            request.owner_id, MAX_SUBSCRIPTIONS_PER_OWNER
+        ));
+    }
+
+    let subscription = EventSubscriptionV1 {
+        schema_version: event_subscription_schema_version_v1(),
+        subscription_id: subscription_id.clone(),
+        owner_id: request.owner_id.clone(),
+        network: network.clone(),
+        subnet: subnet.clone(),
+        contract_address: address,
+        event_name: event.name,
+        event_signature: event.canonical,
+        topic0: event.topic0,
+        inputs: event.inputs,
+        filters: request.filters.clone(),
+        delivery: request.delivery,
+        rate_limit_per_minute,
+        created_at: now,
+    };
+
+    let mut chain: Vec<EventSubscriptionV1> = load(store, &chain_key(&network, &subnet))?;
+    chain.push(subscription.clone());
+    save(store, &chain_key(&network, &subnet), &chain)?;
+    owner_ids.push(subscription_id);
+    save(store, &owner_key, &owner_ids)?;
+    save(store, &subscription_key, &subscription)?;
+    Ok(subscription)
+}
+
+pub fn delete(
+    store: &dyn SubscriptionStore,
+    request: &DeleteRequestV1,
+) -> Result<EventSubscriptionV1, String> {
+    let subscription_key = retention_policy::EVENT_SUBSCRIPTION.key(&request.subscription_id);
+    let subscription: EventSubscriptionV1 = match store.get(&subscription_key)? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Failed to parse subscription: {}", e))?,
+        None => return Err(format!("Unknown subscription: {}", request.subscription_id)),
+    };
+    if subscription.owner_id != request.owner_id {
+        return Err(format!("Unknown subscription: {}", request.subscription_id));
+    }
+
+    let chain = chain_key(&subscription.network, &subscription.subnet);
+    let mut subscriptions: Vec<EventSubscriptionV1> = load(store, &chain)?;
+    subscriptions.retain(|s| s.subscription_id != subscription.subscription_id);
+    save(store, &chain, &subscriptions)?;
+
+    let owner_key = retention_policy::EVENT_SUBSCRIPTION_OWNER.key(&subscription.owner_id);
+    let mut owner_ids: Vec<String> = load(store, &owner_key)?;
+    owner_ids.retain(|id| *id != subscription.subscription_id);
+    save(store, &owner_key, &owner_ids)?;
+
+    store.delete(&subscription_key)?;
+    store.delete(&retention_policy::EVENT_SUBSCRIPTION_RATE.key(&subscription.subscription_id))?;
+    Ok(subscription)
+}
+
+pub fn list(
+    store: &dyn SubscriptionStore,
+    owner_id: &str,
+) -> Result<Vec<EventSubscriptionV1>, String> {
+    let owner_ids: Vec<String> = load(
+        store,
+        &retention_policy::EVENT_SUBSCRIPTION_OWNER.key(owner_id),
+    )?;
+    let mut subscriptions = Vec::new();
+    for id in owner_ids {
+        if let Some(bytes) = store.get(&retention_policy::EVENT_SUBSCRIPTION.key(&id))? {
+            subscriptions.push(
+                serde_json::from_slice(&bytes)
+                    .map_err(|e| format!("Failed to parse subscription: {}", e))?,
+            );
+        }
+    }
+    Ok(subscriptions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000cc";
+    const TRANSFER: &str = "Transfer(address indexed from, address indexed to, uint256 value)";
+
+    #[derive(Default)]
+    struct MemoryStore(RefCell<HashMap<String, Vec<u8>>>);
+
+    impl SubscriptionStore for MemoryStore {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+            self.0.borrow_mut().insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+
+        fn delete(&self, key: &str) -> Result<(), String> {
+            self.0.borrow_mut().remove(key);
+            Ok(())
+        }
+    }
+
+    fn register_request(filters: Vec<EventFilterV1>) -> Vec<u8> {
+        serde_json::to_vec(&RegisterRequestV1 {
+            owner_id: "user-1".to_string(),
+            network: "Ethereum".to_string(),
+            subnet: "mainnet".to_string(),
+            contract_address: TOKEN.to_uppercase().replace("0X", "0x"),
+            event_signature: TRANSFER.to_string(),
+            filters,
+            delivery: DeliveryV1::Subject,
+            rate_limit_per_minute: Some(2),
+        })
+        .unwrap()
+    }
+
+    fn word(hex_value: &str) -> String {
+        format!("0x{:0>64}", hex_value)
+    }
+
+    #[test]
+    fn test_register_list_delete_roundtrip() {
+        let store = MemoryStore::default();
+        let now = Utc::now();
+        let first = handle_request(&store, REGISTER_SUBJECT, &register_request(vec![]), now);
+        assert!(first.success, "{:?}", first.error);
+        let subscription = &first.subscriptions[0];
+        assert_eq!(subscription.contract_address, TOKEN);
+        assert_eq!(
+            subscription.event_signature,
+            "Transfer(address,address,uint256)"
+        );
+        assert_eq!(
+            subscription.delivery_subject(),
+            format!("events.decoded.{}", subscription.subscription_id)
+        );
+
+        // Idempotent registration
+        let again = handle_request(&store, REGISTER_SUBJECT, &register_request(vec![]), now);
+        assert_eq!(
+            again.subscriptions[0].subscription_id,
+            subscription.subscription_id
+        );
+        assert_eq!(load_chain(&store, "ethereum", "mainnet").unwrap().len(), 1);
+
+        let listed = handle_request(&store, LIST_SUBJECT, br#"{"owner_id": "user-1"}"#, now);
+        assert_eq!(listed.subscriptions.len(), 1);
+
+        let wrong_owner = serde_json::to_vec(&DeleteRequestV1 {
+            owner_id: "user-2".to_string(),
+            subscription_id: subscription.subscription_id.clone(),
+        })
+        .unwrap();
+        assert!(!handle_request(&store, DELETE_SUBJECT, &wrong_owner, now).success);
+        let delete_body = serde_json::to_vec(&DeleteRequestV1 {
+            owner_id: "user-1".to_string(),
+            subscription_id: subscription.subscription_id.clone(),
+        })
+        .unwrap();
+        assert!(handle_request(&store, DELETE_SUBJECT, &delete_body, now).success);
+        assert!(load_chain(&store, "ethereum", "mainnet")
+            .unwrap()
+            .is_empty());
+
+        let bad_filter = register_request(vec![EventFilterV1 {
+            param: "from".to_string(),
+            op: FilterOpV1::Gt,
+            value: json!("1"),
+        }]);
+        assert!(!handle_request(&store, REGISTER_SUBJECT, &bad_filter, now).success);
+    }
+
+    #[test]
+    fn test_index_decodes_and_filters_logs() {
+        let store = MemoryStore::default();
+        let filters = vec![EventFilterV1 {
+            param: "value".to_string(),
+            op: FilterOpV1::Gte,
+            value: json!("1000"),
+        }];
+        let body = register_request(filters);
+        handle_request(&store, REGISTER_SUBJECT, &body, Utc::now());
+        let index = SubscriptionIndex::new(load_chain(&store, "ethereum", "mainnet").unwrap());
+
+        let topic0 = parse_event_signature(TRANSFER).unwrap().topic0;
+        let (from, to) = (word("aa"), word("bb"));
+        let large = index.matches(TOKEN, &[&topic0, &from, &to], &word("3e8"));
+        assert_eq!(large.len(), 1);
+        assert_eq!(large[0].1["value"], "1000");
+        assert!(index
+            .matches(TOKEN, &[&topic0, &from, &to], &word("3e7"))
+            .is_empty());
+        // Other contracts and events are not indexed
+        assert!(index
+            .matches(&word("dd")[..42], &[&topic0, &from, &to], &word("3e8"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_filters_and_rate_window() {
+        let decoded: Map<String, Value> = serde_json::from_value(json!({
+            "to": "0xabc",
+            "value": "-5",
+            "ok": true,
+        }))
+        .unwrap();
+        let filter = |param: &str, op, value| EventFilterV1 {
+            param: param.to_string(),
+            op,
+            value,
+        };
+        assert!(filter("to", FilterOpV1::Eq, json!("0xABC")).matches(&decoded));
+        assert!(filter("to", FilterOpV1::In, json!(["0x1", "0xabc"])).matches(&decoded));
+        assert!(filter("value", FilterOpV1::Lt, json!("0")).matches(&decoded));
+        assert!(filter("value", FilterOpV1::Gt, json!(-10)).matches(&decoded));
+        assert!(filter("ok", FilterOpV1::Eq, json!(true)).matches(&decoded));
+        assert!(!filter("missing", FilterOpV1::Ne, json!("x")).matches(&decoded));
+
+        let mut window = RateWindowV1::default();
+        assert!(window.admit(120, 2));
+        assert!(window.admit(150, 2));
+        assert!(!window.admit(179, 2));
+        // Next minute resets the count
+        assert!(window.admit(180, 2));
+    }
+}
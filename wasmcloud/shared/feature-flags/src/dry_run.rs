@@ -0,0 +1,186 @@
+//! Pipeline-wide dry run.
+//!
+//! The config service stores one JSON [`DryRunConfigV1`] under `dry_run:config`
+//! (`retention_policy::DRY_RUN_CONFIG`), switching dry run on for every chain
+//! or for listed chains. Processors call [`begin_message`] when a message
+//! arrives and pass each subject they publish to through [`route`] after the
+//! ACL check: sink subjects (DuckLake writes, alerts) of a chain in dry run go
+//! to their `dryrun.*` mirror instead (see `subject_registry::dry_run`).
+//!
+//! The chain of a sink is read from its subject. Sinks that do not name one
+//! (`alerts.schedule.event_driven`) use the chain recorded with [`set_chain`]
+//! for the current message, and are diverted only under a global dry run when
+//! no chain was recorded.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// Dry-run switch written by the config service
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DryRunConfigV1 {
+    /// Dry run for every chain
+    #[serde(default)]
+    pub global: bool,
+    /// `chain_id` values (`ethereum_sepolia`) or bare networks (`base`) in dry run
+    #[serde(default)]
+    pub chains: Vec<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+impl DryRunConfigV1 {
+    /// Redis key holding the config
+    pub fn key() -> String {
+        retention_policy::DRY_RUN_CONFIG.key("")
+    }
+
+    /// Read the config; `load` reads a Redis key and misses on failure. A
+    /// missing or malformed config means live mode.
+    pub fn load(load: impl Fn(&str) -> Option<Vec<u8>>) -> Self {
+        load(&Self::key())
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.global || !self.chains.is_empty()
+    }
+
+    /// Whether output for `chain_id` is diverted; `None` only matches a
+    /// global dry run
+    pub fn applies_to(&self, chain_id: Option<&str>) -> bool {
+        self.global || chain_id.is_some_and(|chain_id| crate::chain_listed(&self.chains, chain_id))
+    }
+
+    /// Subject to publish on: the dry-run mirror for a sink of a chain in dry
+    /// run, the subject itself otherwise
+    pub fn route(&self, subject: &str, message_chain_id: Option<&str>) -> String {
+        if !self.is_active() || !subject_registry::is_dry_run_sink(subject) {
+            return subject.to_string();
+        }
+        let chain_id = subject_registry::sink_chain_id(subject);
+        if self.applies_to(chain_id.as_deref().or(message_chain_id)) {
+            subject_registry::dry_run(subject)
+        } else {
+            subject.to_string()
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    config: DryRunConfigV1,
+    chain_id: Option<String>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// Start handling a message: reload the config and forget the previous
+/// message's chain, so a change takes effect on the next message
+pub fn begin_message(load: impl Fn(&str) -> Option<Vec<u8>>) {
+    let config = DryRunConfigV1::load(load);
+    STATE.with(|state| {
+        *state.borrow_mut() = State {
+            config,
+            chain_id: None,
+        }
+    });
+}
+
+/// Record the chain of the current message for sinks that do not name one
+pub fn set_chain(network: &str, subnet: &str) {
+    let chain_id = format!("{}_{}", network, subnet).to_lowercase();
+    STATE.with(|state| state.borrow_mut().chain_id = Some(chain_id));
+}
+
+/// Whether any output of the current message may be diverted
+pub fn is_active() -> bool {
+    STATE.with(|state| state.borrow().config.is_active())
+}
+
+/// Subject to publish on for the current message
+pub fn route(subject: &str) -> String {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.config.route(subject, state.chain_id.as_deref())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(json: &str) -> impl Fn(&str) -> Option<Vec<u8>> + '_ {
+        move |key| (key == "dry_run:config").then(|| json.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_missing_or_malformed_config_is_live() {
+        for config in [
+            DryRunConfigV1::load(|_| None),
+            DryRunConfigV1::load(stored("{")),
+        ] {
+            assert!(!config.is_active());
+            assert_eq!(
+                config.route("ducklake.logs.ethereum.mainnet.write", None),
+                "ducklake.logs.ethereum.mainnet.write"
+            );
+        }
+    }
+
+    #[test]
+    fn test_per_chain_dry_run_diverts_only_that_chains_sinks() {
+        let config = DryRunConfigV1::load(stored(r#"{"chains": ["ethereum_sepolia", "base"]}"#));
+
+        assert_eq!(
+            config.route("ducklake.transactions.ethereum.sepolia.write", None),
+            "dryrun.ducklake.transactions.ethereum.sepolia.write"
+        );
+        assert_eq!(
+            config.route("alerts.evaluate.base.mainnet", None),
+            "dryrun.alerts.evaluate.base.mainnet"
+        );
+        assert_eq!(
+            config.route("ducklake.transactions.ethereum.mainnet.write", None),
+            "ducklake.transactions.ethereum.mainnet.write"
+        );
+        // Pipeline-internal subjects keep flowing
+        assert_eq!(
+            config.route("transactions.raw.evm", Some("ethereum_sepolia")),
+            "transactions.raw.evm"
+        );
+        // Chainless sinks follow the message's chain
+        assert_eq!(
+            config.route("alerts.schedule.event_driven", Some("base_mainnet")),
+            "dryrun.alerts.schedule.event_driven"
+        );
+        assert_eq!(
+            config.route("alerts.schedule.event_driven", None),
+            "alerts.schedule.event_driven"
+        );
+    }
+
+    #[test]
+    fn test_global_dry_run_and_message_state() {
+        begin_message(stored(r#"{"global": true}"#));
+        assert!(is_active());
+        assert_eq!(
+            route("alerts.schedule.event_driven"),
+            "dryrun.alerts.schedule.event_driven"
+        );
+
+        begin_message(stored(r#"{"chains": ["polygon"]}"#));
+        assert_eq!(route("alerts.sweep_detected"), "alerts.sweep_detected");
+        set_chain("Polygon", "mainnet");
+        assert_eq!(
+            route("alerts.sweep_detected"),
+            "dryrun.alerts.sweep_detected"
+        );
+
+        // The chain does not leak into the next message
+        begin_message(stored(r#"{"chains": ["polygon"]}"#));
+        assert_eq!(route("alerts.sweep_detected"), "alerts.sweep_detected");
+    }
+}
@@ -0,0 +1,312 @@
+//! Feature flags for gradual rollout of actor behaviour.
+//!
+//! The config service (alert API) stores one JSON [`FeatureFlagV1`] per flag
+//! under `feature_flag:{name}` (`retention_policy::FEATURE_FLAG`). Actors
+//! evaluate their flags while processing each message, so a change takes
+//! effect on the next message without a redeploy:
+//! - `enabled: false` is a kill switch
+//! - the first [`FlagRuleV1`] matching the message's chain and tenant sets the
+//!   rollout percentage; otherwise the flag's own `rollout_percent` applies
+//! - the rollout bucket is a stable hash of the flag name and the message's
+//!   rollout unit (wallet, tenant, ...), so a unit never flips back and forth
+//!   while the percentage is raised
+//!
+//! A missing or malformed flag evaluates to the [`FlagSpec`] default. Callers
+//! count every evaluation under [`Evaluation::counter_key`]
+//! (`feature_flag:evals:{name}:{chain_id}:{on|off}`), which makes the split of
+//! a rollout visible per chain.
+//!
+//! The [`dry_run`] switch uses the same chain targeting to divert a chain's
+//! DuckLake writes and alerts to mirrored subjects.
+
+use serde::{Deserialize, Serialize};
+
+pub mod dry_run;
+
+pub use dry_run::DryRunConfigV1;
+
+/// A flag an actor checks, with the value used when the flag is not configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagSpec {
+    pub name: &'static str,
+    pub default: bool,
+}
+
+impl FlagSpec {
+    pub const fn new(name: &'static str, default: bool) -> Self {
+        Self { name, default }
+    }
+
+    /// Redis key holding the flag's [`FeatureFlagV1`]
+    pub fn key(&self) -> String {
+        retention_policy::FEATURE_FLAG.key(self.name)
+    }
+
+    /// Evaluate the flag; `load` reads a Redis key and misses on failure
+    pub fn evaluate(
+        &self,
+        context: &FlagContext,
+        load: impl Fn(&str) -> Option<Vec<u8>>,
+    ) -> Evaluation {
+        let flag = load(&self.key())
+            .and_then(|bytes| serde_json::from_slice::<FeatureFlagV1>(&bytes).ok());
+        match flag {
+            Some(flag) => flag.evaluate(context),
+            None => Evaluation {
+                flag: self.name.to_string(),
+                chain_id: context.chain_id.map(str::to_string),
+                enabled: self.default,
+                reason: EvaluationReason::Missing,
+            },
+        }
+    }
+}
+
+/// Flag definition written by the config service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureFlagV1 {
+    pub name: String,
+    /// Kill switch; when false the flag is off everywhere
+    pub enabled: bool,
+    /// Share of rollout units (0-100) enabled when no rule matches
+    #[serde(default = "full_rollout")]
+    pub rollout_percent: u8,
+    /// Targeting rules, first match wins
+    #[serde(default)]
+    pub rules: Vec<FlagRuleV1>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Rollout for messages from the listed chains and tenants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagRuleV1 {
+    /// `chain_id` values (`ethereum_mainnet`) or bare networks (`ethereum`); empty for any
+    #[serde(default)]
+    pub chains: Vec<String>,
+    /// Tenant ids; empty for any, otherwise the message must carry a listed tenant
+    #[serde(default)]
+    pub tenants: Vec<String>,
+    pub rollout_percent: u8,
+}
+
+fn full_rollout() -> u8 {
+    100
+}
+
+/// What a message is evaluated against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlagContext<'a> {
+    /// `{network}_{subnet}`
+    pub chain_id: Option<&'a str>,
+    pub tenant_id: Option<&'a str>,
+    /// Value hashed into the rollout bucket, e.g. a wallet address
+    pub unit: &'a str,
+}
+
+/// Why an evaluation came out the way it did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluationReason {
+    /// Flag not configured (or unreadable); the spec default applied
+    Missing,
+    /// Kill switch off
+    Disabled,
+    /// A targeting rule matched
+    Rule,
+    /// No rule matched; the flag's rollout percentage applied
+    Rollout,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub flag: String,
+    pub chain_id: Option<String>,
+    pub enabled: bool,
+    pub reason: EvaluationReason,
+}
+
+impl Evaluation {
+    /// Redis counter of evaluations with this outcome for the flag and chain
+    pub fn counter_key(&self) -> String {
+        retention_policy::FEATURE_FLAG_EVALUATIONS.key(&format!(
+            "{}:{}:{}",
+            self.flag,
+            self.chain_id.as_deref().unwrap_or("all"),
+            if self.enabled { "on" } else { "off" }
+        ))
+    }
+}
+
+impl FeatureFlagV1 {
+    pub fn evaluate(&self, context: &FlagContext) -> Evaluation {
+        let (enabled, reason) = if !self.enabled {
+            (false, EvaluationReason::Disabled)
+        } else {
+            match self.rules.iter().find(|rule| rule.matches(context)) {
+                Some(rule) => (
+                    in_rollout(&self.name, context.unit, rule.rollout_percent),
+                    EvaluationReason::Rule,
+                ),
+                None => (
+                    in_rollout(&self.name, context.unit, self.rollout_percent),
+                    EvaluationReason::Rollout,
+                ),
+            }
+        };
+        Evaluation {
+            flag: self.name.clone(),
+            chain_id: context.chain_id.map(str::to_string),
+            enabled,
+            reason,
+        }
+    }
+}
+
+impl FlagRuleV1 {
+    fn matches(&self, context: &FlagContext) -> bool {
+        let chain_matches = self.chains.is_empty()
+            || context
+                .chain_id
+                .is_some_and(|chain_id| chain_listed(&self.chains, chain_id));
+        let tenant_matches = self.tenants.is_empty()
+            || context
+                .tenant_id
+                .is_some_and(|tenant| self.tenants.iter().any(|t| t == tenant));
+        chain_matches && tenant_matches
+    }
+}
+
+/// Whether `chain_id` is listed exactly or by its bare network
+pub(crate) fn chain_listed(chains: &[String], chain_id: &str) -> bool {
+    let chain_id = chain_id.to_lowercase();
+    chains.iter().any(|chain| {
+        let chain = chain.to_lowercase();
+        chain_id == chain || chain_id.starts_with(&format!("{}_", chain))
+    })
+}
+
+/// Whether `unit` falls in the first `percent` of the flag's 100 buckets
+pub fn in_rollout(flag: &str, unit: &str, percent: u8) -> bool {
+    if percent >= 100 {
+        return true;
+    }
+    bucket(flag, unit) < u64::from(percent)
+}
+
+/// Stable bucket 0-99 (FNV-1a over `{flag}:{unit}`)
+fn bucket(flag: &str, unit: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in flag
+        .bytes()
+        .chain(std::iter::once(b':'))
+        .chain(unit.to_lowercase().bytes())
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash % 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: FlagSpec = FlagSpec::new("new_enrichment", false);
+
+    fn context<'a>(
+        chain_id: &'a str,
+        tenant_id: Option<&'a str>,
+        unit: &'a str,
+    ) -> FlagContext<'a> {
+        FlagContext {
+            chain_id: Some(chain_id),
+            tenant_id,
+            unit,
+        }
+    }
+
+    fn stored(json: &str) -> impl Fn(&str) -> Option<Vec<u8>> + '_ {
+        move |key| (key == "feature_flag:new_enrichment").then(|| json.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_missing_or_malformed_flag_uses_default() {
+        let ctx = context("ethereum_mainnet", None, "0xabc");
+        let evaluation = SPEC.evaluate(&ctx, |_| None);
+        assert!(!evaluation.enabled);
+        assert_eq!(evaluation.reason, EvaluationReason::Missing);
+
+        let evaluation = FlagSpec::new("new_enrichment", true).evaluate(&ctx, stored("{"));
+        assert!(evaluation.enabled);
+        assert_eq!(evaluation.reason, EvaluationReason::Missing);
+    }
+
+    #[test]
+    fn test_kill_switch_and_default_rollout() {
+        let ctx = context("ethereum_mainnet", None, "0xabc");
+        let off = SPEC.evaluate(&ctx, stored(r#"{"name":"new_enrichment","enabled":false}"#));
+        assert!(!off.enabled);
+        assert_eq!(off.reason, EvaluationReason::Disabled);
+
+        let on = SPEC.evaluate(&ctx, stored(r#"{"name":"new_enrichment","enabled":true}"#));
+        assert!(on.enabled);
+        assert_eq!(on.reason, EvaluationReason::Rollout);
+        assert_eq!(
+            on.counter_key(),
+            "feature_flag:evals:new_enrichment:ethereum_mainnet:on"
+        );
+    }
+
+    #[test]
+    fn test_rules_target_chains_and_tenants() {
+        let json = r#"{
+            "name": "new_enrichment",
+            "enabled": true,
+            "rollout_percent": 0,
+            "rules": [
+                {"tenants": ["t1"], "rollout_percent": 100},
+                {"chains": ["polygon"], "rollout_percent": 100}
+            ]
+        }"#;
+
+        let tenant = SPEC.evaluate(
+            &context("ethereum_mainnet", Some("t1"), "0xabc"),
+            stored(json),
+        );
+        assert!(tenant.enabled);
+        assert_eq!(tenant.reason, EvaluationReason::Rule);
+
+        let network = SPEC.evaluate(&context("polygon_mainnet", None, "0xabc"), stored(json));
+        assert!(network.enabled);
+
+        let other = SPEC.evaluate(
+            &context("ethereum_mainnet", Some("t2"), "0xabc"),
+            stored(json),
+        );
+        assert!(!other.enabled);
+        assert_eq!(other.reason, EvaluationReason::Rollout);
+    }
+
+    #[test]
+    fn test_rollout_is_stable_and_proportional() {
+        let units: Vec<String> = (0..1000).map(|i| format!("0x{:040x}", i)).collect();
+        let enabled = units
+            .iter()
+            .filter(|unit| in_rollout("new_enrichment", unit, 25))
+            .count();
+        assert!((150..350).contains(&enabled), "enabled {}", enabled);
+
+        // Raising the percentage only adds units
+        for unit in &units {
+            if in_rollout("new_enrichment", unit, 25) {
+                assert!(in_rollout("new_enrichment", unit, 50));
+            }
+        }
+        assert_eq!(
+            in_rollout("new_enrichment", "0xABC", 40),
+            in_rollout("new_enrichment", "0xabc", 40)
+        );
+        assert!(!in_rollout("new_enrichment", "0xabc", 0));
+    }
+}
@@ -0,0 +1,404 @@
+//! Message-level encryption for sensitive alert payloads.
+//!
+//! Alert payloads can reveal address/wallet relationships, so tenants may opt
+//! in to having them sealed before they leave the evaluation pipeline. A sealed
+//! payload is an [`EncryptedPayloadV1`] envelope: an ephemeral X25519 key
+//! agreement with the recipient's public key, HKDF-SHA256 for the content key,
+//! and ChaCha20-Poly1305 over the original bytes with the tenant/key id bound
+//! as associated data.
+//!
+//! Two recipients exist:
+//! - `alerts.triggered.*` batches are sealed to the tenant's platform key. The
+//!   private half is never stored: [`TenantKeyring`] derives it from the
+//!   platform master key (secret `alert-payload-master-key`) so only holders of
+//!   that secret can open the batch. The alerts-processor derives the public
+//!   half from the same secret rather than trusting a key stored in Redis.
+//! - Webhook deliveries are sealed to a public key the tenant registers with
+//!   the webhook config; only the tenant's receiver can open them.
+//!
+//! Tenants opt in via a [`TenantEncryptionPolicy`] at `alerts:encryption:{tenant_id}`.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Envelope schema version
+pub const ENCRYPTED_PAYLOAD_SCHEMA_V1: &str = "encrypted_payload_v1";
+
+/// Key agreement, KDF and AEAD used by v1 envelopes
+pub const ALGORITHM_V1: &str = "x25519-hkdf-sha256-chacha20poly1305";
+
+/// Secret holding the platform master key tenant keys are derived from
+pub const MASTER_KEY_SECRET: &str = "alert-payload-master-key";
+
+/// Minimum master key length in bytes
+pub const MIN_MASTER_KEY_LEN: usize = 32;
+
+const CONTENT_KEY_INFO: &[u8] = b"ekko:payload-encryption:v1";
+
+/// Redis key holding a tenant's encryption policy
+pub fn policy_key(tenant_id: &str) -> String {
+    format!("alerts:encryption:{}", tenant_id)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    /// Key material is missing, malformed or too short
+    InvalidKey(String),
+    /// Envelope fields are not valid base64 or have the wrong length
+    Encoding(String),
+    /// Sealing or opening failed (wrong key, tampered ciphertext)
+    Crypto(String),
+    /// No randomness available for the ephemeral key or nonce
+    Random(String),
+}
+
+impl std::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionError::InvalidKey(msg) => write!(f, "invalid key: {}", msg),
+            EncryptionError::Encoding(msg) => write!(f, "invalid encoding: {}", msg),
+            EncryptionError::Crypto(msg) => write!(f, "crypto failure: {}", msg),
+            EncryptionError::Random(msg) => write!(f, "randomness unavailable: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+/// Source of named secrets (wasmCloud secrets store, environment, test fixtures)
+pub trait SecretSource {
+    fn get_secret(&self, name: &str) -> Result<Option<Vec<u8>>, String>;
+}
+
+/// Per-tenant opt-in, written by the API when a tenant enables encryption
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenantEncryptionPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Identifies the tenant key; bumping it rotates the key
+    pub key_id: String,
+    /// Base64 X25519 public key of the receiver. Webhook deliveries are sealed
+    /// to it; triggered batches use the derived platform key instead.
+    pub public_key: String,
+}
+
+impl TenantEncryptionPolicy {
+    pub fn public_key_bytes(&self) -> Result<[u8; 32], EncryptionError> {
+        decode_key(&self.public_key, "public_key")
+    }
+}
+
+/// Sealed payload as it travels over NATS or to a webhook receiver
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedPayloadV1 {
+    pub schema_version: String,
+    pub tenant_id: String,
+    pub key_id: String,
+    pub alg: String,
+    /// Base64 ephemeral X25519 public key
+    pub ephemeral_public_key: String,
+    /// Base64 96-bit nonce
+    pub nonce: String,
+    /// Base64 ciphertext with Poly1305 tag
+    pub ciphertext: String,
+}
+
+impl EncryptedPayloadV1 {
+    /// Parse `body` as an envelope, or `None` if it is a plaintext message
+    pub fn detect(body: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<Self>(body)
+            .ok()
+            .filter(|envelope| envelope.schema_version == ENCRYPTED_PAYLOAD_SCHEMA_V1)
+    }
+
+    fn associated_data(tenant_id: &str, key_id: &str) -> Vec<u8> {
+        format!("{}:{}:{}", ENCRYPTED_PAYLOAD_SCHEMA_V1, tenant_id, key_id).into_bytes()
+    }
+}
+
+/// Seal `plaintext` to `recipient_public_key` with fresh randomness
+pub fn seal(
+    plaintext: &[u8],
+    tenant_id: &str,
+    key_id: &str,
+    recipient_public_key: &[u8; 32],
+) -> Result<EncryptedPayloadV1, EncryptionError> {
+    let mut ephemeral_secret = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    getrandom::getrandom(&mut ephemeral_secret)
+        .and_then(|_| getrandom::getrandom(&mut nonce))
+        .map_err(|e| EncryptionError::Random(e.to_string()))?;
+
+    seal_with(
+        plaintext,
+        tenant_id,
+        key_id,
+        recipient_public_key,
+        ephemeral_secret,
+        nonce,
+    )
+}
+
+/// Seal with caller-supplied ephemeral key and nonce
+///
+/// Both must be fresh random values for every message; exposed for
+/// deterministic tests and callers with their own randomness source.
+pub fn seal_with(
+    plaintext: &[u8],
+    tenant_id: &str,
+    key_id: &str,
+    recipient_public_key: &[u8; 32],
+    ephemeral_secret: [u8; 32],
+    nonce: [u8; 12],
+) -> Result<EncryptedPayloadV1, EncryptionError> {
+    let ephemeral_secret = StaticSecret::from(ephemeral_secret);
+    let ephemeral_public = PublicKey::from(&ephemeral_secret);
+    let recipient_public = PublicKey::from(*recipient_public_key);
+
+    let cipher = content_cipher(&ephemeral_secret, &ephemeral_public, &recipient_public)?;
+    let aad = EncryptedPayloadV1::associated_data(tenant_id, key_id);
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| EncryptionError::Crypto("seal failed".to_string()))?;
+
+    Ok(EncryptedPayloadV1 {
+        schema_version: ENCRYPTED_PAYLOAD_SCHEMA_V1.to_string(),
+        tenant_id: tenant_id.to_string(),
+        key_id: key_id.to_string(),
+        alg: ALGORITHM_V1.to_string(),
+        ephemeral_public_key: STANDARD.encode(ephemeral_public.as_bytes()),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+/// Open an envelope with the recipient's X25519 secret key
+pub fn open(
+    envelope: &EncryptedPayloadV1,
+    recipient_secret_key: &[u8; 32],
+) -> Result<Vec<u8>, EncryptionError> {
+    if envelope.alg != ALGORITHM_V1 {
+        return Err(EncryptionError::Crypto(format!(
+            "unsupported algorithm '{}'",
+            envelope.alg
+        )));
+    }
+
+    let recipient_secret = StaticSecret::from(*recipient_secret_key);
+    let recipient_public = PublicKey::from(&recipient_secret);
+    let ephemeral_public = PublicKey::from(decode_key(
+        &envelope.ephemeral_public_key,
+        "ephemeral_public_key",
+    )?);
+    let nonce = STANDARD
+        .decode(&envelope.nonce)
+        .map_err(|e| EncryptionError::Encoding(format!("nonce: {}", e)))?;
+    if nonce.len() != 12 {
+        return Err(EncryptionError::Encoding(format!(
+            "nonce must be 12 bytes, got {}",
+            nonce.len()
+        )));
+    }
+    let ciphertext = STANDARD
+        .decode(&envelope.ciphertext)
+        .map_err(|e| EncryptionError::Encoding(format!("ciphertext: {}", e)))?;
+
+    // The ephemeral key plays the sender role, so both sides derive the same
+    // salt (ephemeral || recipient).
+    let shared = recipient_secret.diffie_hellman(&ephemeral_public);
+    if !shared.was_contributory() {
+        return Err(EncryptionError::InvalidKey(
+            "low-order ephemeral public key".to_string(),
+        ));
+    }
+    let cipher = cipher_from_shared(shared.as_bytes(), &ephemeral_public, &recipient_public)?;
+    let aad = EncryptedPayloadV1::associated_data(&envelope.tenant_id, &envelope.key_id);
+
+    cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| EncryptionError::Crypto("open failed (wrong key or tampered payload)".into()))
+}
+
+/// Derives per-tenant X25519 keys from the platform master key
+///
+/// Tenant private keys are never persisted; rotating `key_id` in the tenant's
+/// policy yields a fresh key pair while older envelopes stay openable.
+pub struct TenantKeyring {
+    master_key: Vec<u8>,
+}
+
+impl TenantKeyring {
+    pub fn new(master_key: Vec<u8>) -> Result<Self, EncryptionError> {
+        if master_key.len() < MIN_MASTER_KEY_LEN {
+            return Err(EncryptionError::InvalidKey(format!(
+                "master key must be at least {} bytes, got {}",
+                MIN_MASTER_KEY_LEN,
+                master_key.len()
+            )));
+        }
+        Ok(Self { master_key })
+    }
+
+    /// Load the master key from the secrets store; `None` if it is not provisioned
+    pub fn from_secrets(secrets: &dyn SecretSource) -> Result<Option<Self>, EncryptionError> {
+        let master_key = secrets
+            .get_secret(MASTER_KEY_SECRET)
+            .map_err(EncryptionError::InvalidKey)?;
+        master_key.map(Self::new).transpose()
+    }
+
+    pub fn secret_key(&self, tenant_id: &str, key_id: &str) -> [u8; 32] {
+        let info = format!("ekko:tenant-key:{}:{}", tenant_id, key_id);
+        let mut okm = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &self.master_key)
+            .expand(info.as_bytes(), &mut okm)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        okm
+    }
+
+    pub fn public_key(&self, tenant_id: &str, key_id: &str) -> [u8; 32] {
+        let secret = StaticSecret::from(self.secret_key(tenant_id, key_id));
+        PublicKey::from(&secret).to_bytes()
+    }
+
+    /// Enabled policy for provisioning a tenant's `alerts:encryption:*` entry
+    pub fn policy(&self, tenant_id: &str, key_id: &str) -> TenantEncryptionPolicy {
+        TenantEncryptionPolicy {
+            enabled: true,
+            key_id: key_id.to_string(),
+            public_key: STANDARD.encode(self.public_key(tenant_id, key_id)),
+        }
+    }
+
+    /// Open an envelope sealed to one of this keyring's tenant keys
+    pub fn open(&self, envelope: &EncryptedPayloadV1) -> Result<Vec<u8>, EncryptionError> {
+        open(
+            envelope,
+            &self.secret_key(&envelope.tenant_id, &envelope.key_id),
+        )
+    }
+}
+
+fn content_cipher(
+    ephemeral_secret: &StaticSecret,
+    ephemeral_public: &PublicKey,
+    recipient_public: &PublicKey,
+) -> Result<ChaCha20Poly1305, EncryptionError> {
+    let shared = ephemeral_secret.diffie_hellman(recipient_public);
+    if !shared.was_contributory() {
+        return Err(EncryptionError::InvalidKey(
+            "low-order recipient public key".to_string(),
+        ));
+    }
+    cipher_from_shared(shared.as_bytes(), ephemeral_public, recipient_public)
+}
+
+fn cipher_from_shared(
+    shared: &[u8; 32],
+    ephemeral_public: &PublicKey,
+    recipient_public: &PublicKey,
+) -> Result<ChaCha20Poly1305, EncryptionError> {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public.as_bytes());
+    salt[32..].copy_from_slice(recipient_public.as_bytes());
+
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt[..]), shared)
+        .expand(CONTENT_KEY_INFO, &mut key)
+        .map_err(|e| EncryptionError::Crypto(format!("content key derivation: {}", e)))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn decode_key(value: &str, field: &str) -> Result<[u8; 32], EncryptionError> {
+    let bytes = STANDARD
+        .decode(value.trim())
+        .map_err(|e| EncryptionError::Encoding(format!("{}: {}", field, e)))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        EncryptionError::InvalidKey(format!("{} must be 32 bytes, got {}", field, bytes.len()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyring() -> TenantKeyring {
+        TenantKeyring::new(vec![7u8; 32]).unwrap()
+    }
+
+    fn sealed(plaintext: &[u8]) -> EncryptedPayloadV1 {
+        let recipient = keyring().public_key("tenant-1", "k1");
+        seal_with(
+            plaintext, "tenant-1", "k1", &recipient, [9u8; 32], [3u8; 12],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_keyring_round_trip() {
+        let envelope = sealed(br#"{"instance_id":"abc"}"#);
+
+        assert_eq!(envelope.key_id, "k1");
+        assert!(!envelope.ciphertext.contains("instance_id"));
+        assert_eq!(
+            keyring().open(&envelope).unwrap(),
+            br#"{"instance_id":"abc"}"#
+        );
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_key_and_tampered_header() {
+        let envelope = sealed(b"secret");
+
+        let other = TenantKeyring::new(vec![8u8; 32]).unwrap();
+        assert!(matches!(
+            other.open(&envelope),
+            Err(EncryptionError::Crypto(_))
+        ));
+
+        let mut retargeted = envelope.clone();
+        retargeted.tenant_id = "tenant-2".to_string();
+        let tenant_1_key = keyring().secret_key("tenant-1", "k1");
+        assert!(matches!(
+            open(&retargeted, &tenant_1_key),
+            Err(EncryptionError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_and_policy() {
+        let envelope = sealed(b"payload");
+        let body = serde_json::to_vec(&envelope).unwrap();
+
+        assert_eq!(EncryptedPayloadV1::detect(&body), Some(envelope));
+        assert_eq!(
+            EncryptedPayloadV1::detect(br#"{"schema_version":"x"}"#),
+            None
+        );
+
+        let policy = keyring().policy("tenant-1", "k1");
+        assert!(policy.enabled);
+        assert_eq!(
+            policy.public_key_bytes().unwrap(),
+            keyring().public_key("tenant-1", "k1")
+        );
+        assert!(TenantKeyring::new(vec![1u8; 16]).is_err());
+    }
+}
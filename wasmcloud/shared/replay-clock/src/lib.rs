@@ -0,0 +1,156 @@
+//! Injectable clock and counters for deterministic replays.
+//!
+//! Processors stamp their output with wall-clock times (`processed_at`) and
+//! per-call counters (correlation IDs), so replaying the same input never
+//! yields the same bytes twice. In deterministic mode the clock is frozen at
+//! `frozen_at` and counters restart from `counter_seed` at the start of every
+//! message, so identical input produces byte-identical output that canary and
+//! integration harnesses can compare against golden files.
+//!
+//! Deterministic mode is switched on by a JSON [`ReplayConfig`] stored under
+//! `replay:config` (`retention_policy::REPLAY_CONFIG`). Processors call
+//! [`begin_message`] when a message arrives and take time and IDs from [`now`]
+//! and [`next_counter`] instead of `Utc::now()` or their own atomics. The
+//! config is loaded once per component instance, so toggling it takes effect
+//! on the next deploy.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Time returned by [`now`]; the Unix epoch when unset
+    #[serde(default)]
+    pub frozen_at: Option<DateTime<Utc>>,
+    /// First value returned by [`next_counter`] for every message
+    #[serde(default)]
+    pub counter_seed: u64,
+}
+
+#[derive(Default)]
+struct State {
+    config: Option<ReplayConfig>,
+    counter: u64,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// Start handling a message, loading the config on first use. In
+/// deterministic mode this resets the counter to the seed.
+pub fn begin_message(load: impl FnOnce() -> Option<ReplayConfig>) {
+    let loaded = STATE.with(|state| state.borrow().config.is_some());
+    let config = if loaded {
+        None
+    } else {
+        Some(load().unwrap_or_default())
+    };
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if let Some(config) = config {
+            state.config = Some(config);
+        }
+        if let Some(seed) = state
+            .config
+            .as_ref()
+            .filter(|config| config.enabled)
+            .map(|config| config.counter_seed)
+        {
+            state.counter = seed;
+        }
+    });
+}
+
+/// Replace the loaded config, e.g. from a test harness
+pub fn configure(config: ReplayConfig) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if config.enabled {
+            state.counter = config.counter_seed;
+        }
+        state.config = Some(config);
+    });
+}
+
+pub fn is_deterministic() -> bool {
+    STATE.with(|state| {
+        state
+            .borrow()
+            .config
+            .as_ref()
+            .is_some_and(|config| config.enabled)
+    })
+}
+
+/// Current time, or the frozen time in deterministic mode
+pub fn now() -> DateTime<Utc> {
+    let frozen = STATE.with(|state| {
+        state
+            .borrow()
+            .config
+            .as_ref()
+            .filter(|config| config.enabled)
+            .map(|config| config.frozen_at.unwrap_or(DateTime::UNIX_EPOCH))
+    });
+    frozen.unwrap_or_else(Utc::now)
+}
+
+/// Next counter value; unique per instance in live mode, seeded per message
+/// in deterministic mode
+pub fn next_counter() -> u64 {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let value = state.counter;
+        state.counter += 1;
+        value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_deterministic_mode_freezes_clock_and_reseeds_counters() {
+        let frozen = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let config: ReplayConfig = serde_json::from_str(
+            r#"{"enabled": true, "frozen_at": "2024-01-01T00:00:00Z", "counter_seed": 7}"#,
+        )
+        .unwrap();
+
+        begin_message(|| Some(config));
+        assert!(is_deterministic());
+        assert_eq!(now(), frozen);
+        assert_eq!(next_counter(), 7);
+        assert_eq!(next_counter(), 8);
+
+        // Second message: config is not reloaded, counter restarts at the seed
+        begin_message(|| panic!("config loaded twice"));
+        assert_eq!(next_counter(), 7);
+        assert_eq!(now(), frozen);
+    }
+
+    #[test]
+    fn test_live_mode_keeps_counting_across_messages() {
+        begin_message(|| None);
+        assert!(!is_deterministic());
+        let first = next_counter();
+        begin_message(|| None);
+        assert_eq!(next_counter(), first + 1);
+        assert!(now() > Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+
+        configure(ReplayConfig {
+            enabled: true,
+            frozen_at: None,
+            counter_seed: 0,
+        });
+        assert_eq!(now(), DateTime::UNIX_EPOCH);
+        assert_eq!(next_counter(), 0);
+    }
+}
@@ -0,0 +1,304 @@
+//! Keyspace audit against the retention registry
+//!
+//! The janitor feeds every scanned key into a [`RetentionAudit`] and turns the
+//! tally into a [`RetentionReport`]. Memory is measured on a sample of keys per
+//! pattern and extrapolated, since `MEMORY USAGE` on every key is too costly.
+
+use crate::{rule_for, RetentionRule};
+use serde::Serialize;
+
+/// Subject the janitor publishes reports on
+pub const REPORT_SUBJECT: &str = "metrics.redis.retention";
+
+/// Remaining TTL as reported by Redis `TTL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyTtl {
+    /// Key has no expiry (`-1`)
+    Persistent,
+    /// Seconds until expiry
+    Expires(u64),
+    /// Key vanished between SCAN and TTL (`-2`)
+    Missing,
+}
+
+impl KeyTtl {
+    pub fn from_redis(ttl: i64) -> Self {
+        match ttl {
+            -2 => KeyTtl::Missing,
+            t if t < 0 => KeyTtl::Persistent,
+            t => KeyTtl::Expires(t as u64),
+        }
+    }
+}
+
+/// Per-pattern tally
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PatternStats {
+    pub pattern: String,
+    pub owner: String,
+    pub key_count: u64,
+    /// Keys with no expiry under a rule that requires one
+    pub keys_without_ttl: u64,
+    /// Keys whose remaining TTL exceeds the rule's TTL
+    pub keys_over_ttl: u64,
+    /// TTLs set by the janitor this run
+    pub ttl_applied: u64,
+    pub sampled_keys: u64,
+    pub sampled_bytes: u64,
+    /// Few example keys, for unregistered patterns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+impl PatternStats {
+    fn new(pattern: &str, owner: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            owner: owner.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Total memory extrapolated from the sampled keys
+    pub fn estimated_bytes(&self) -> u64 {
+        if self.sampled_keys == 0 {
+            return 0;
+        }
+        let avg = self.sampled_bytes as f64 / self.sampled_keys as f64;
+        (avg * self.key_count as f64).round() as u64
+    }
+}
+
+/// Deviation between the keyspace and the registry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Drift {
+    MissingTtl {
+        pattern: String,
+        keys: u64,
+    },
+    TtlAbovePolicy {
+        pattern: String,
+        keys: u64,
+    },
+    KeyBudgetExceeded {
+        pattern: String,
+        keys: u64,
+        limit: u64,
+    },
+    MemoryBudgetExceeded {
+        pattern: String,
+        bytes: u64,
+        limit: u64,
+    },
+    UnregisteredKeys {
+        keys: u64,
+        examples: Vec<String>,
+    },
+}
+
+/// Operator metrics for one janitor run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetentionReport {
+    pub generated_at: String,
+    pub scanned_keys: u64,
+    pub patterns: Vec<PatternStats>,
+    pub unregistered: PatternStats,
+    pub drift: Vec<Drift>,
+}
+
+/// Running tally of a keyspace scan
+pub struct RetentionAudit<'a> {
+    rules: &'a [RetentionRule],
+    stats: Vec<PatternStats>,
+    unregistered: PatternStats,
+    scanned_keys: u64,
+    memory_samples_per_pattern: u64,
+    max_examples: usize,
+}
+
+impl<'a> RetentionAudit<'a> {
+    pub fn new(rules: &'a [RetentionRule], memory_samples_per_pattern: u64) -> Self {
+        Self {
+            rules,
+            stats: rules
+                .iter()
+                .map(|rule| PatternStats::new(rule.pattern, rule.owner))
+                .collect(),
+            unregistered: PatternStats::new("*", "unregistered"),
+            scanned_keys: 0,
+            memory_samples_per_pattern,
+            max_examples: 10,
+        }
+    }
+
+    /// Rule governing `key` and whether its memory should be sampled
+    pub fn classify(&self, key: &str) -> (Option<&'a RetentionRule>, bool) {
+        match rule_for(self.rules, key) {
+            Some((idx, rule)) => (
+                Some(rule),
+                self.stats[idx].sampled_keys < self.memory_samples_per_pattern,
+            ),
+            None => (
+                None,
+                self.unregistered.sampled_keys < self.memory_samples_per_pattern,
+            ),
+        }
+    }
+
+    /// Record one scanned key; `memory_bytes` is set for sampled keys
+    pub fn observe(&mut self, key: &str, ttl: KeyTtl, memory_bytes: Option<u64>) {
+        if ttl == KeyTtl::Missing {
+            return;
+        }
+        self.scanned_keys += 1;
+
+        let (stats, rule) = match rule_for(self.rules, key) {
+            Some((idx, rule)) => (&mut self.stats[idx], Some(rule)),
+            None => {
+                if self.unregistered.examples.len() < self.max_examples {
+                    self.unregistered.examples.push(key.to_string());
+                }
+                (&mut self.unregistered, None)
+            }
+        };
+
+        stats.key_count += 1;
+        if let Some(bytes) = memory_bytes {
+            stats.sampled_keys += 1;
+            stats.sampled_bytes += bytes;
+        }
+        if let Some(limit) = rule.and_then(|rule| rule.ttl_secs) {
+            match ttl {
+                KeyTtl::Persistent => stats.keys_without_ttl += 1,
+                KeyTtl::Expires(remaining) if remaining > limit => stats.keys_over_ttl += 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Record that the janitor set the rule's TTL on a key that had none
+    pub fn ttl_applied(&mut self, key: &str) {
+        if let Some((idx, _)) = rule_for(self.rules, key) {
+            self.stats[idx].ttl_applied += 1;
+        }
+    }
+
+    pub fn report(self, generated_at: &str) -> RetentionReport {
+        let mut drift = Vec::new();
+        for (rule, stats) in self.rules.iter().zip(&self.stats) {
+            let unresolved = stats.keys_without_ttl.saturating_sub(stats.ttl_applied);
+            if unresolved > 0 {
+                drift.push(Drift::MissingTtl {
+                    pattern: stats.pattern.clone(),
+                    keys: unresolved,
+                });
+            }
+            if stats.keys_over_ttl > 0 {
+                drift.push(Drift::TtlAbovePolicy {
+                    pattern: stats.pattern.clone(),
+                    keys: stats.keys_over_ttl,
+                });
+            }
+            if let Some(limit) = rule.max_keys.filter(|limit| stats.key_count > *limit) {
+                drift.push(Drift::KeyBudgetExceeded {
+                    pattern: stats.pattern.clone(),
+                    keys: stats.key_count,
+                    limit,
+                });
+            }
+            let bytes = stats.estimated_bytes();
+            if let Some(limit) = rule.max_bytes.filter(|limit| bytes > *limit) {
+                drift.push(Drift::MemoryBudgetExceeded {
+                    pattern: stats.pattern.clone(),
+                    bytes,
+                    limit,
+                });
+            }
+        }
+        if self.unregistered.key_count > 0 {
+            drift.push(Drift::UnregisteredKeys {
+                keys: self.unregistered.key_count,
+                examples: self.unregistered.examples.clone(),
+            });
+        }
+
+        RetentionReport {
+            generated_at: generated_at.to_string(),
+            scanned_keys: self.scanned_keys,
+            patterns: self.stats,
+            unregistered: self.unregistered,
+            drift,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &[RetentionRule] = &[
+        RetentionRule::new("dedupe:*", "router")
+            .ttl(100)
+            .max_keys(2),
+        RetentionRule::new("config:*", "api").max_bytes(10),
+    ];
+
+    #[test]
+    fn test_report_flags_ttl_budget_and_unregistered_drift() {
+        let mut audit = RetentionAudit::new(RULES, 1);
+        audit.observe("dedupe:a", KeyTtl::Persistent, Some(4));
+        audit.observe("dedupe:b", KeyTtl::Expires(500), None);
+        audit.observe("dedupe:c", KeyTtl::Expires(50), None);
+        audit.observe("config:a", KeyTtl::Persistent, Some(30));
+        audit.observe("stray:key", KeyTtl::Persistent, None);
+        audit.observe("dedupe:gone", KeyTtl::Missing, None);
+
+        let report = audit.report("2024-01-01T00:00:00Z");
+
+        assert_eq!(report.scanned_keys, 5);
+        assert_eq!(report.patterns[0].key_count, 3);
+        assert_eq!(
+            report.drift,
+            vec![
+                Drift::MissingTtl {
+                    pattern: "dedupe:*".to_string(),
+                    keys: 1
+                },
+                Drift::TtlAbovePolicy {
+                    pattern: "dedupe:*".to_string(),
+                    keys: 1
+                },
+                Drift::KeyBudgetExceeded {
+                    pattern: "dedupe:*".to_string(),
+                    keys: 3,
+                    limit: 2
+                },
+                Drift::MemoryBudgetExceeded {
+                    pattern: "config:*".to_string(),
+                    bytes: 30,
+                    limit: 10
+                },
+                Drift::UnregisteredKeys {
+                    keys: 1,
+                    examples: vec!["stray:key".to_string()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_applied_ttl_resolves_missing_ttl_drift() {
+        let mut audit = RetentionAudit::new(RULES, 0);
+        assert_eq!(audit.classify("dedupe:a"), (Some(&RULES[0]), false));
+
+        audit.observe("dedupe:a", KeyTtl::Persistent, None);
+        audit.ttl_applied("dedupe:a");
+
+        let report = audit.report("2024-01-01T00:00:00Z");
+        assert_eq!(report.patterns[0].ttl_applied, 1);
+        assert!(report.drift.is_empty());
+        assert_eq!(KeyTtl::from_redis(-1), KeyTtl::Persistent);
+        assert_eq!(KeyTtl::from_redis(-2), KeyTtl::Missing);
+    }
+}
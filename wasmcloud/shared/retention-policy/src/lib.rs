@@ -0,0 +1,402 @@
+//! Centralized retention policy for Redis state.
+//!
+//! Every component that writes Redis state registers its key patterns here as a
+//! [`RetentionRule`]: who owns the keys, how long they may live and how many
+//! keys / bytes the pattern is budgeted for. Writers build keys through their
+//! rule (`rule.key(..)`) so the pattern and the code cannot drift apart.
+//!
+//! wasi:keyvalue has no expiry, so actors cannot set TTLs themselves. The
+//! redis-janitor provider scans the keyspace, applies the rule's TTL to keys
+//! that have none and audits counts and memory against the budgets (see
+//! [`audit`]). Keys matching no rule are reported as unregistered.
+
+pub mod audit;
+
+pub use audit::*;
+
+use serde::Serialize;
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+const MIB: u64 = 1024 * 1024;
+
+/// Retention rule for one Redis key pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RetentionRule {
+    /// Glob pattern; `*` matches any run of characters
+    pub pattern: &'static str,
+    /// Component that writes the keys
+    pub owner: &'static str,
+    /// Maximum key lifetime; `None` for persistent configuration/registry data
+    pub ttl_secs: Option<u64>,
+    pub max_keys: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+impl RetentionRule {
+    const fn new(pattern: &'static str, owner: &'static str) -> Self {
+        Self {
+            pattern,
+            owner,
+            ttl_secs: None,
+            max_keys: None,
+            max_bytes: None,
+        }
+    }
+
+    const fn ttl(mut self, secs: u64) -> Self {
+        self.ttl_secs = Some(secs);
+        self
+    }
+
+    const fn max_keys(mut self, keys: u64) -> Self {
+        self.max_keys = Some(keys);
+        self
+    }
+
+    const fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Build a key by substituting `suffix` for the pattern's trailing `*`
+    pub fn key(&self, suffix: &str) -> String {
+        let prefix = self.pattern.strip_suffix('*').unwrap_or(self.pattern);
+        format!("{}{}", prefix, suffix)
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        glob_match(self.pattern, key)
+    }
+
+    /// Literal characters in the pattern; longer patterns are more specific
+    fn specificity(&self) -> usize {
+        self.pattern.chars().filter(|c| *c != '*').count()
+    }
+}
+
+// notification-router
+pub const ALERTS_DEDUPE: RetentionRule =
+    RetentionRule::new("alerts:dedupe:*", "notification-router").ttl(7 * DAY);
+pub const ALERTS_COOLDOWN: RetentionRule =
+    RetentionRule::new("alerts:cooldown:*", "notification-router").ttl(30 * DAY);
+pub const ALERTS_ESCALATION: RetentionRule =
+    RetentionRule::new("alerts:escalation:*", "notification-router").ttl(7 * DAY);
+
+// alert-scheduler - notification acknowledgements (written with SET EX)
+pub const ALERTS_ACK: RetentionRule =
+    RetentionRule::new("alerts:ack:*", "alert-scheduler").ttl(7 * DAY);
+
+// Alert API (Django) - instance snapshots, subscribers, catalog, encryption policies
+pub const ALERTS_INSTANCE: RetentionRule = RetentionRule::new("alerts:instance:*", "alert-api");
+pub const ALERTS_ENCRYPTION: RetentionRule = RetentionRule::new("alerts:encryption:*", "alert-api");
+pub const DATASOURCE_CATALOG: RetentionRule =
+    RetentionRule::new("datasource_catalog:*", "alert-api");
+
+// entity_activity_aggregator
+pub const ENTITY_ACTIVITY_WINDOW: RetentionRule =
+    RetentionRule::new("entity_activity:window:*", "entity-activity-aggregator")
+        .ttl(2 * DAY)
+        .max_keys(1_000_000);
+pub const ENTITY_ACTIVITY_CONFIG: RetentionRule =
+    RetentionRule::new("entity_activity:config", "entity-activity-aggregator");
+pub const ENTITY_LINK: RetentionRule = RetentionRule::new("entity:address:*", "labels-api");
+pub const NATIVE_PRICE: RetentionRule = RetentionRule::new("price:native:*", "price-feed").ttl(DAY);
+
+// eth_contract_transaction_processor - dApp knowledge-pack entries and usage counters
+pub const DAPP_CONTRACT: RetentionRule = RetentionRule::new("dapp:contract:*", "knowledge-packs");
+pub const DAPP_USAGE_COUNTER: RetentionRule =
+    RetentionRule::new("dapp_usage:*", "eth-contract-transaction-processor").max_keys(5_000_000);
+
+// eth_transfers_processor - sweep detection windows and balance delta streams
+pub const SWEEP_WINDOW: RetentionRule =
+    RetentionRule::new("sweep:window:*", "eth-transfers-processor").ttl(DAY);
+pub const SWEEP_CONFIG: RetentionRule =
+    RetentionRule::new("sweep:config", "eth-transfers-processor");
+pub const BALANCE_STREAM: RetentionRule =
+    RetentionRule::new("balance_stream:*", "eth-transfers-processor").max_keys(10_000_000);
+pub const BALANCE_SNAPSHOT_WINDOW: RetentionRule =
+    RetentionRule::new("balance_snapshot:*", "eth-transfers-processor");
+
+// tron_raw_transactions - TRC-20 metadata beyond the built-in table
+pub const TRON_TOKEN_METADATA: RetentionRule =
+    RetentionRule::new("tokens:tron:*", "token-registry");
+
+// state-rebuild - caches rebuilt from DuckLake and the per-chain rebuild lock
+pub const BALANCE_LATEST: RetentionRule =
+    RetentionRule::new("balance:*", "state-rebuild").max_keys(10_000_000);
+pub const LAST_ACTIVITY: RetentionRule =
+    RetentionRule::new("activity:last:*", "state-rebuild").max_keys(10_000_000);
+pub const STATE_REBUILD_LOCK: RetentionRule =
+    RetentionRule::new("state_rebuild:lock:*", "state-rebuild").ttl(HOUR);
+
+// price-backfill - per-chain USD backfill lock
+pub const PRICE_BACKFILL_LOCK: RetentionRule =
+    RetentionRule::new("price_backfill:lock:*", "price-backfill").ttl(HOUR);
+
+// eth_raw_transactions - per-chain gas alert thresholds and detector state
+pub const GAS_ALERT_CONFIG: RetentionRule = RetentionRule::new("gas:alerts:config:*", "alert-api");
+pub const GAS_ALERT_STATE: RetentionRule =
+    RetentionRule::new("gas:alerts:state:*", "eth-raw-transactions").ttl(DAY);
+
+// newheads-evm - chain halt / RPC outage thresholds and per-chain lag alarm pauses
+pub const CHAIN_HEALTH_CONFIG: RetentionRule =
+    RetentionRule::new("chain:health:config", "alert-api");
+pub const LAG_ALARM_PAUSE: RetentionRule =
+    RetentionRule::new("chain:health:pause:*", "newheads-evm").ttl(HOUR);
+
+// newheads-evm - beacon nodes, watched validators and per-chain slot cursors
+pub const SLASHING_CONFIG: RetentionRule = RetentionRule::new("slashing:config", "alert-api");
+pub const SLASHING_WATCH: RetentionRule = RetentionRule::new("slashing:watch:*", "alert-api");
+pub const SLASHING_CURSOR: RetentionRule = RetentionRule::new("slashing:cursor:*", "newheads-evm");
+
+// chain-onboarding - Django node configs it activates and staged onboarding plans
+pub const BLOCKCHAIN_NODE: RetentionRule = RetentionRule::new("blockchain:nodes:*", "alert-api");
+pub const CHAIN_ONBOARDING_PLAN: RetentionRule =
+    RetentionRule::new("chain_onboarding:plan:*", "chain-onboarding").ttl(30 * DAY);
+
+// eth_raw_transactions - last fully handed-off block per chain (shared/block-checkpoint)
+pub const BLOCK_CHECKPOINT: RetentionRule =
+    RetentionRule::new("checkpoint:block:*", "eth-raw-transactions");
+
+// evm_logs_ingestion - decoded event subscriptions (shared/event-subscriptions)
+pub const EVENT_SUBSCRIPTION: RetentionRule =
+    RetentionRule::new("events:sub:*", "evm-logs-ingestion").max_keys(100_000);
+pub const EVENT_SUBSCRIPTION_CHAIN: RetentionRule =
+    RetentionRule::new("events:sub:chain:*", "evm-logs-ingestion");
+pub const EVENT_SUBSCRIPTION_OWNER: RetentionRule =
+    RetentionRule::new("events:sub:owner:*", "evm-logs-ingestion");
+pub const EVENT_SUBSCRIPTION_RATE: RetentionRule =
+    RetentionRule::new("events:sub:rate:*", "evm-logs-ingestion").ttl(DAY);
+
+// evm_logs_ingestion - ERC-721 tokenURI enrichment for watched collections
+pub const NFT_ENRICHMENT_CONFIG: RetentionRule =
+    RetentionRule::new("nft_enrichment:*", "alert-api");
+pub const NFT_METADATA_CACHE: RetentionRule =
+    RetentionRule::new("nft_metadata:*", "evm-logs-ingestion")
+        .ttl(7 * DAY)
+        .max_keys(1_000_000);
+
+// Deterministic replay switch for processors (shared/replay-clock)
+pub const REPLAY_CONFIG: RetentionRule = RetentionRule::new("replay:config", "replay-harness");
+
+// Feature flags (shared/feature-flags) - definitions from the config service and
+// per-chain evaluation counters incremented by actors
+pub const FEATURE_FLAG: RetentionRule = RetentionRule::new("feature_flag:*", "alert-api");
+pub const FEATURE_FLAG_EVALUATIONS: RetentionRule =
+    RetentionRule::new("feature_flag:evals:*", "feature-flags")
+        .ttl(30 * DAY)
+        .max_keys(100_000);
+// Dry run (shared/feature-flags) - chains whose sinks are diverted to dryrun.*
+pub const DRY_RUN_CONFIG: RetentionRule = RetentionRule::new("dry_run:config", "alert-api");
+
+// ABI registry (abi-decoder provider/actor, eth_contract_creation_processor)
+pub const ABI_CACHE: RetentionRule = RetentionRule::new("abi:*", "abi-decoder")
+    .ttl(30 * DAY)
+    .max_bytes(512 * MIB);
+/// Written by evm-logs-ingestion on `Upgraded` logs, read by abi-decoder
+pub const PROXY_IMPLEMENTATION: RetentionRule =
+    RetentionRule::new("proxy:implementation:*", "evm-logs-ingestion");
+/// Proxy implementations the abi-decoder read from EIP-1967 slots or EIP-1167 code
+pub const PROXY_RESOLVED: RetentionRule =
+    RetentionRule::new("proxy:resolved:*", "abi-decoder").ttl(DAY);
+/// Selector-to-facet maps the abi-decoder read from EIP-2535 diamonds' `facets()`
+pub const DIAMOND_FACETS: RetentionRule =
+    RetentionRule::new("diamond:facets:*", "abi-decoder").ttl(DAY);
+pub const ABI_SIGNATURE: RetentionRule = RetentionRule::new("abi_signature:*", "abi-decoder");
+/// Recent decodes of identical calldata, reused by the abi-decoder for 10 minutes
+pub const DECODE_RESULT_CACHE: RetentionRule = RetentionRule::new("abi_decoded:*", "abi-decoder")
+    .ttl(HOUR)
+    .max_keys(1_000_000);
+pub const ABI_DECODER_BATCH_CONFIG: RetentionRule =
+    RetentionRule::new("abi_decoder:batch_config", "abi-decoder");
+pub const ABI_METADATA_CONFIG: RetentionRule =
+    RetentionRule::new("abi_metadata:*", "eth-contract-creation-processor");
+pub const DEPLOYMENT_WEBHOOKS: RetentionRule =
+    RetentionRule::new("deployment_webhooks:*", "eth-contract-creation-processor");
+pub const REGISTRY_VERSION: RetentionRule =
+    RetentionRule::new("cache:registry_version:*", "cache-invalidation");
+
+// decode-queue provider - durable ABI decode requests (Redis streams) and
+// reconciliation markers
+pub const DECODE_QUEUE_STREAM: RetentionRule =
+    RetentionRule::new("decode_queue:stream", "decode-queue").max_bytes(256 * MIB);
+pub const DECODE_QUEUE_DEAD_LETTERS: RetentionRule =
+    RetentionRule::new("decode_queue:dead", "decode-queue").max_bytes(64 * MIB);
+pub const DECODE_QUEUE_REQUEUED: RetentionRule =
+    RetentionRule::new("decode_queue:requeued:*", "decode-queue")
+        .ttl(DAY)
+        .max_keys(1_000_000);
+
+// http-rpc provider - daily spend per endpoint (`http_rpc:cost:{YYYY-MM-DD}` hashes)
+pub const HTTP_RPC_COST_USAGE: RetentionRule =
+    RetentionRule::new("http_rpc:cost:*", "http-rpc-provider").ttl(35 * DAY);
+
+// Notification providers
+pub const WEBHOOK_CONFIG: RetentionRule =
+    RetentionRule::new("webhook:config:*", "webhook-notification-provider");
+pub const WEBHOOK_STATUS: RetentionRule =
+    RetentionRule::new("webhook:status:*", "webhook-notification-provider").ttl(DAY);
+pub const WEBHOOK_STATS: RetentionRule =
+    RetentionRule::new("webhook:stats:*", "webhook-notification-provider").ttl(30 * DAY);
+pub const WEBHOOK_HEALTH: RetentionRule =
+    RetentionRule::new("webhook:health:*", "webhook-notification-provider").ttl(7 * DAY);
+pub const WEBSOCKET_SESSIONS: RetentionRule =
+    RetentionRule::new("websocket:*", "websocket-notification-provider").ttl(7 * DAY);
+pub const USER_NOTIFICATIONS: RetentionRule =
+    RetentionRule::new("user:notifications:*", "websocket-notification-provider")
+        .ttl(7 * DAY)
+        .max_bytes(256 * MIB);
+pub const SLACK_CONFIG: RetentionRule =
+    RetentionRule::new("slack:config:*", "slack-notification-provider");
+pub const SLACK_STATS: RetentionRule =
+    RetentionRule::new("slack:stats:*", "slack-notification-provider").ttl(30 * DAY);
+pub const TELEGRAM_CONFIG: RetentionRule =
+    RetentionRule::new("telegram:config:*", "telegram-notification-provider");
+pub const TELEGRAM_CHAT: RetentionRule =
+    RetentionRule::new("telegram:chat:*", "telegram-notification-provider");
+pub const TELEGRAM_STATS: RetentionRule =
+    RetentionRule::new("telegram:stats:*", "telegram-notification-provider").ttl(30 * DAY);
+
+/// Every registered rule
+pub const REGISTRY: &[RetentionRule] = &[
+    ALERTS_DEDUPE,
+    ALERTS_COOLDOWN,
+    ALERTS_ESCALATION,
+    ALERTS_ACK,
+    ALERTS_INSTANCE,
+    ALERTS_ENCRYPTION,
+    DATASOURCE_CATALOG,
+    ENTITY_ACTIVITY_WINDOW,
+    ENTITY_ACTIVITY_CONFIG,
+    ENTITY_LINK,
+    NATIVE_PRICE,
+    DAPP_CONTRACT,
+    DAPP_USAGE_COUNTER,
+    SWEEP_WINDOW,
+    SWEEP_CONFIG,
+    BALANCE_STREAM,
+    BALANCE_SNAPSHOT_WINDOW,
+    TRON_TOKEN_METADATA,
+    BALANCE_LATEST,
+    LAST_ACTIVITY,
+    STATE_REBUILD_LOCK,
+    PRICE_BACKFILL_LOCK,
+    GAS_ALERT_CONFIG,
+    GAS_ALERT_STATE,
+    CHAIN_HEALTH_CONFIG,
+    LAG_ALARM_PAUSE,
+    SLASHING_CONFIG,
+    SLASHING_WATCH,
+    SLASHING_CURSOR,
+    BLOCKCHAIN_NODE,
+    CHAIN_ONBOARDING_PLAN,
+    BLOCK_CHECKPOINT,
+    EVENT_SUBSCRIPTION,
+    EVENT_SUBSCRIPTION_CHAIN,
+    EVENT_SUBSCRIPTION_OWNER,
+    EVENT_SUBSCRIPTION_RATE,
+    NFT_ENRICHMENT_CONFIG,
+    NFT_METADATA_CACHE,
+    REPLAY_CONFIG,
+    FEATURE_FLAG,
+    FEATURE_FLAG_EVALUATIONS,
+    DRY_RUN_CONFIG,
+    ABI_CACHE,
+    PROXY_IMPLEMENTATION,
+    PROXY_RESOLVED,
+    DIAMOND_FACETS,
+    ABI_SIGNATURE,
+    DECODE_RESULT_CACHE,
+    ABI_DECODER_BATCH_CONFIG,
+    ABI_METADATA_CONFIG,
+    DEPLOYMENT_WEBHOOKS,
+    REGISTRY_VERSION,
+    DECODE_QUEUE_STREAM,
+    DECODE_QUEUE_DEAD_LETTERS,
+    DECODE_QUEUE_REQUEUED,
+    HTTP_RPC_COST_USAGE,
+    WEBHOOK_CONFIG,
+    WEBHOOK_STATUS,
+    WEBHOOK_STATS,
+    WEBHOOK_HEALTH,
+    WEBSOCKET_SESSIONS,
+    USER_NOTIFICATIONS,
+    SLACK_CONFIG,
+    SLACK_STATS,
+    TELEGRAM_CONFIG,
+    TELEGRAM_CHAT,
+    TELEGRAM_STATS,
+];
+
+/// Most specific rule matching `key`
+pub fn rule_for<'a>(rules: &'a [RetentionRule], key: &str) -> Option<(usize, &'a RetentionRule)> {
+    rules
+        .iter()
+        .enumerate()
+        .filter(|(_, rule)| rule.matches(key))
+        .max_by_key(|(_, rule)| rule.specificity())
+}
+
+/// Glob match supporting `*` only, as Redis `SCAN MATCH` does for these patterns
+pub fn glob_match(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = key.strip_prefix(first) else {
+        return false;
+    };
+
+    let tail: Vec<&str> = parts.collect();
+    let Some((last, middle)) = tail.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("alerts:dedupe:*", "alerts:dedupe:u1:run1"));
+        assert!(glob_match("a*b*c", "a-b-b-c"));
+        assert!(glob_match(
+            "entity_activity:config",
+            "entity_activity:config"
+        ));
+        assert!(!glob_match(
+            "entity_activity:config",
+            "entity_activity:config2"
+        ));
+        assert!(!glob_match("abi:*", "abi_metadata:ipfs_gateway"));
+        assert!(!glob_match("a*bc", "abc-c"));
+    }
+
+    #[test]
+    fn test_rule_for_prefers_specific_pattern() {
+        let (_, rule) = rule_for(REGISTRY, "webhook:stats:u1:success").unwrap();
+        assert_eq!(rule.pattern, "webhook:stats:*");
+
+        let (_, rule) = rule_for(REGISTRY, "alerts:instance:subscribers:i1").unwrap();
+        assert_eq!(rule.owner, "alert-api");
+        assert!(rule_for(REGISTRY, "unknown:key").is_none());
+    }
+
+    #[test]
+    fn test_rule_key_and_registry_patterns_unique() {
+        assert_eq!(ALERTS_DEDUPE.key("u1:x"), "alerts:dedupe:u1:x");
+        assert_eq!(ENTITY_ACTIVITY_CONFIG.key(""), "entity_activity:config");
+
+        let mut patterns: Vec<&str> = REGISTRY.iter().map(|rule| rule.pattern).collect();
+        patterns.sort_unstable();
+        patterns.dedup();
+        assert_eq!(patterns.len(), REGISTRY.len());
+    }
+}
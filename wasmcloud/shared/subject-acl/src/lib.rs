@@ -0,0 +1,255 @@
+//! Subject-level publish ACLs between actors.
+//!
+//! wasmCloud messaging carries no publisher identity, so any component linked
+//! to the NATS provider can publish to any subject. This crate holds the
+//! allowlist of publishing actors per subject family; every actor declares its
+//! component name with [`component!`], checks it against [`authorize`] before
+//! publishing and, when the
+//! subject is not allowed, drops the message and reports an [`AclViolation`]
+//! on [`ACL_VIOLATIONS_SUBJECT`] instead.
+//!
+//! Families are matched in registry order, so specific families come first and
+//! the trailing `ducklake` catch-alls deny writes to any table that has no
+//! registered writer. Subjects outside every family are not restricted.
+
+use serde::Serialize;
+use std::fmt;
+
+/// Subject violation reports are published to
+pub const ACL_VIOLATIONS_SUBJECT: &str = "acl.violations";
+
+/// Declare the calling crate's `ACTOR_ID`, the component name it passes to
+/// [`authorize`] before every publish
+#[macro_export]
+macro_rules! component {
+    ($name:literal) => {
+        /// Component name checked against the subject ACL before every publish
+        const ACTOR_ID: &str = $name;
+    };
+}
+
+const TX_WRITERS: &[&str] = &[
+    "eth-transfers-processor",
+    "eth-contract-creation-processor",
+    "eth-contract-transaction-processor",
+    "tron-raw-transactions",
+];
+
+/// Publish allowlist for one subject family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SubjectFamily {
+    /// NATS subject pattern; `*` matches one token, a trailing `>` the rest
+    pub pattern: &'static str,
+    /// Component names allowed to publish; empty denies everyone
+    pub publishers: &'static [&'static str],
+}
+
+impl SubjectFamily {
+    const fn new(pattern: &'static str, publishers: &'static [&'static str]) -> Self {
+        Self {
+            pattern,
+            publishers,
+        }
+    }
+
+    pub fn matches(&self, subject: &str) -> bool {
+        subject_matches(self.pattern, subject)
+    }
+
+    pub fn allows(&self, publisher: &str) -> bool {
+        self.publishers.contains(&publisher)
+    }
+}
+
+pub const REGISTRY: &[SubjectFamily] = &[
+    SubjectFamily::new("ducklake.transactions.*.*.write", TX_WRITERS),
+    SubjectFamily::new("ducklake.address_transactions.*.*.write", TX_WRITERS),
+    SubjectFamily::new(
+        "ducklake.contract_calls.*.*.write",
+        &["eth-contract-transaction-processor"],
+    ),
+    SubjectFamily::new(
+        "ducklake.dapp_usage.*.*.write",
+        &["eth-contract-transaction-processor"],
+    ),
+    SubjectFamily::new(
+        "ducklake.token_transfers.*.*.write",
+        &["tron-raw-transactions", "evm-logs-ingestion"],
+    ),
+    SubjectFamily::new("ducklake.logs.*.*.write", &["evm-logs-ingestion"]),
+    SubjectFamily::new("ducklake.perp_events.*.*.write", &["evm-logs-ingestion"]),
+    SubjectFamily::new(
+        "ducklake.entity_activity.*.*.write",
+        &["entity-activity-aggregator"],
+    ),
+    SubjectFamily::new(
+        "ducklake.notification_content.*.*.write",
+        &["notification-router"],
+    ),
+    SubjectFamily::new(
+        "ducklake.transactions_evm.*.*.write",
+        &["transaction-ducklake-writer"],
+    ),
+    SubjectFamily::new(
+        "ducklake.transactions_svm.*.*.write",
+        &["transaction-ducklake-writer"],
+    ),
+    SubjectFamily::new(
+        "ducklake.transactions_utxo.*.*.write",
+        &["transaction-ducklake-writer"],
+    ),
+    SubjectFamily::new(
+        "ducklake.decoded_transactions_evm.*.*.write",
+        &["transaction-ducklake-writer"],
+    ),
+    SubjectFamily::new("events.decoded.>", &["evm-logs-ingestion"]),
+    SubjectFamily::new("balances.delta.*.*", &["eth-transfers-processor"]),
+    SubjectFamily::new(
+        "ducklake.transactions.*.*.upsert",
+        &["price-backfill", "abi-decoder"],
+    ),
+    SubjectFamily::new("ducklake.price_history.*.*.upsert", &["price-backfill"]),
+    SubjectFamily::new("metrics.data_quality.*", &["consistency-checker"]),
+    SubjectFamily::new("admin.chains.activated", &["chain-onboarding"]),
+    // Catch-alls: no actor may write to an unregistered table
+    SubjectFamily::new("ducklake.*.*.*.write", &[]),
+    SubjectFamily::new("ducklake.*.*.*.upsert", &[]),
+    SubjectFamily::new("ducklake.*.write", &[]),
+];
+
+/// Rejected publish, reported on [`ACL_VIOLATIONS_SUBJECT`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AclViolation {
+    pub schema_version: &'static str,
+    pub publisher: String,
+    pub subject: String,
+    pub family: &'static str,
+}
+
+impl AclViolation {
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for AclViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is not allowed to publish to {} (family {})",
+            self.publisher, self.subject, self.family
+        )
+    }
+}
+
+/// First registered family matching `subject`
+pub fn family_for(subject: &str) -> Option<&'static SubjectFamily> {
+    REGISTRY.iter().find(|family| family.matches(subject))
+}
+
+/// Check that `publisher` may publish to `subject`
+pub fn authorize(publisher: &str, subject: &str) -> Result<(), AclViolation> {
+    match family_for(subject) {
+        Some(family) if !family.allows(publisher) => Err(AclViolation {
+            schema_version: "acl_violation_v1",
+            publisher: publisher.to_string(),
+            subject: subject.to_string(),
+            family: family.pattern,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// NATS subject matching: `*` matches exactly one token, `>` one or more
+/// trailing tokens
+pub fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut tokens = subject.split('.');
+    for part in pattern.split('.') {
+        if part == ">" {
+            return tokens.next().is_some();
+        }
+        match tokens.next() {
+            Some(token) if part == "*" || part == token => {}
+            _ => return false,
+        }
+    }
+    tokens.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_matches() {
+        assert!(subject_matches(
+            "ducklake.logs.*.*.write",
+            "ducklake.logs.ethereum.mainnet.write"
+        ));
+        assert!(!subject_matches(
+            "ducklake.logs.*.*.write",
+            "ducklake.logs.ethereum.write"
+        ));
+        assert!(!subject_matches(
+            "ducklake.transactions.*.*.write",
+            "ducklake.transactions_evm.ethereum.mainnet.write"
+        ));
+        assert!(subject_matches("ducklake.>", "ducklake.schema.list"));
+        assert!(!subject_matches("ducklake.>", "ducklake"));
+    }
+
+    #[test]
+    fn test_component_declares_actor_id() {
+        component!("eth-transfers-processor");
+        assert_eq!(ACTOR_ID, "eth-transfers-processor");
+        assert!(authorize(ACTOR_ID, "ducklake.transactions.ethereum.mainnet.write").is_ok());
+    }
+
+    #[test]
+    fn test_authorize() {
+        assert!(authorize(
+            "eth-transfers-processor",
+            "ducklake.transactions.ethereum.mainnet.write"
+        )
+        .is_ok());
+        assert!(authorize(
+            "abi-decoder",
+            "blockchain.ethereum.mainnet.contracts.decoded"
+        )
+        .is_ok());
+
+        let violation = authorize(
+            "abi-decoder",
+            "ducklake.transactions.ethereum.mainnet.write",
+        )
+        .unwrap_err();
+        assert_eq!(violation.family, "ducklake.transactions.*.*.write");
+
+        let violation = authorize(
+            "evm-logs-ingestion",
+            "ducklake.unknown_table.ethereum.mainnet.write",
+        )
+        .unwrap_err();
+        assert_eq!(violation.family, "ducklake.*.*.*.write");
+
+        let report: serde_json::Value = serde_json::from_slice(&violation.to_json()).unwrap();
+        assert_eq!(report["publisher"], "evm-logs-ingestion");
+        assert_eq!(report["schema_version"], "acl_violation_v1");
+    }
+
+    #[test]
+    fn test_registry_patterns_unique_and_catch_alls_last() {
+        let mut patterns: Vec<&str> = REGISTRY.iter().map(|family| family.pattern).collect();
+        patterns.sort_unstable();
+        patterns.dedup();
+        assert_eq!(patterns.len(), REGISTRY.len());
+
+        let first_catch_all = REGISTRY
+            .iter()
+            .position(|family| family.publishers.is_empty())
+            .unwrap();
+        assert!(REGISTRY[first_catch_all..]
+            .iter()
+            .all(|family| family.publishers.is_empty()));
+    }
+}
@@ -7,6 +7,9 @@
 //! alerts.jobs.retry.{job_id}                    # Failed job retry requests
 //! alerts.scheduler.scan.{trigger_type}          # Scheduler coordination
 //! alerts.triggered.{user_id}                    # Alert trigger events
+//! alerts.schedule.escalation                    # Escalation timer requests
+//! alerts.escalation.due.{alert_instance_id}     # Fired escalation timers
+//! alerts.ack                                    # Notification acknowledgements
 //! ```
 
 /// Job creation subject - from scheduler to job queue
@@ -44,6 +47,23 @@ pub fn triggered(user_id: &str) -> String {
     format!("alerts.triggered.{}", user_id)
 }
 
+/// Escalation timer request subject - from notification router to scheduler
+pub fn escalation_schedule() -> &'static str {
+    "alerts.schedule.escalation"
+}
+
+/// Escalation due subject - from scheduler back to notification router
+///
+/// Example: `alerts.escalation.due.uuid-12345`
+pub fn escalation_due(alert_instance_id: &str) -> String {
+    format!("alerts.escalation.due.{}", alert_instance_id)
+}
+
+/// Notification acknowledgement subject - stops pending escalations
+pub fn ack() -> &'static str {
+    "alerts.ack"
+}
+
 /// Subscription patterns for handlers
 
 /// Pattern for all job creation requests
@@ -61,6 +81,11 @@ pub fn pattern_triggered_all() -> &'static str {
     "alerts.triggered.>"
 }
 
+/// Pattern for all fired escalation timers
+pub fn pattern_escalation_due_all() -> &'static str {
+    "alerts.escalation.due.>"
+}
+
 /// Pattern for all alerts subjects (use with caution)
 pub fn pattern_alerts_all() -> &'static str {
     "alerts.>"
@@ -91,4 +116,14 @@ mod tests {
     fn test_triggered() {
         assert_eq!(triggered("user-uuid-123"), "alerts.triggered.user-uuid-123");
     }
+
+    #[test]
+    fn test_escalation_subjects() {
+        assert_eq!(
+            escalation_due("uuid-12345"),
+            "alerts.escalation.due.uuid-12345"
+        );
+        assert!(escalation_schedule().starts_with("alerts.schedule."));
+        assert_eq!(ack(), "alerts.ack");
+    }
 }
@@ -8,11 +8,13 @@
 //! blockchain.{network}.{subnet}.contracts.creation         # Contract deployment events
 //! blockchain.{network}.{subnet}.contracts.transactions     # Contract interaction events
 //! blockchain.{network}.{subnet}.contracts.decoded          # Decoded contract transactions
+//! blockchain.{network}.{subnet}.logs.decoded               # Decoded receipt logs
+//! blockchain.{network}.{subnet}.userops.decoded            # Decoded ERC-4337 UserOperations
 //! blockchain.abi.decode.{network}.{subnet}.{request|batch} # ABI decoding requests
 //! ```
 //!
-//! Networks: ethereum, polygon, arbitrum, avalanche, bitcoin, solana, cosmos
-//! Subnets: mainnet, sepolia, goerli, mumbai, amoy, devnet, testnet
+//! Networks: ethereum, polygon, arbitrum, avalanche, bitcoin, solana, tron, cosmos
+//! Subnets: mainnet, sepolia, goerli, mumbai, amoy, devnet, testnet, nile, shasta
 
 /// Raw transaction subject - from blockchain providers
 ///
@@ -108,6 +110,20 @@ pub fn contracts_decoded(network: &str, subnet: &str) -> String {
     format!("blockchain.{}.{}.contracts.decoded", network, subnet)
 }
 
+/// Decoded log subject - receipt logs with their ABI-decoded event
+///
+/// Example: `blockchain.ethereum.mainnet.logs.decoded`
+pub fn logs_decoded(network: &str, subnet: &str) -> String {
+    format!("blockchain.{}.{}.logs.decoded", network, subnet)
+}
+
+/// Decoded UserOperation subject - one message per ERC-4337 UserOperation of a bundle
+///
+/// Example: `blockchain.ethereum.mainnet.userops.decoded`
+pub fn user_ops_decoded(network: &str, subnet: &str) -> String {
+    format!("blockchain.{}.{}.userops.decoded", network, subnet)
+}
+
 /// ABI decode request subject for specific network/subnet
 ///
 /// Example: `blockchain.abi.decode.ethereum.mainnet.request`
@@ -197,6 +213,7 @@ pub mod networks {
     pub const AVALANCHE: &str = "avalanche";
     pub const BITCOIN: &str = "bitcoin";
     pub const SOLANA: &str = "solana";
+    pub const TRON: &str = "tron";
     pub const COSMOS: &str = "cosmos";
 }
 
@@ -208,6 +225,8 @@ pub mod subnets {
     pub const AMOY: &str = "amoy";
     pub const DEVNET: &str = "devnet";
     pub const TESTNET: &str = "testnet";
+    pub const NILE: &str = "nile";
+    pub const SHASTA: &str = "shasta";
 }
 
 #[cfg(test)]
@@ -270,6 +289,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_logs_decoded() {
+        assert_eq!(
+            logs_decoded("ethereum", "mainnet"),
+            "blockchain.ethereum.mainnet.logs.decoded"
+        );
+    }
+
+    #[test]
+    fn test_user_ops_decoded() {
+        assert_eq!(
+            user_ops_decoded("base", "mainnet"),
+            "blockchain.base.mainnet.userops.decoded"
+        );
+    }
+
     #[test]
     fn test_is_contracts_decoded_event() {
         assert!(is_contracts_decoded_event(
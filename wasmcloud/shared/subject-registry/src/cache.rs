@@ -0,0 +1,34 @@
+//! Cache Invalidation Subject Patterns
+//!
+//! Subject hierarchy for dropping stale enrichment held in memory:
+//! ```text
+//! cache.invalidate.{kind}                   # Registry changed (token_metadata, address_label, abi)
+//! ```
+
+/// Cache invalidation subject
+///
+/// Example: `cache.invalidate.token_metadata`
+pub fn invalidate(kind: &str) -> String {
+    format!("cache.invalidate.{}", kind)
+}
+
+// Subscription patterns
+
+/// Pattern for invalidations of every kind
+pub fn pattern_invalidate_all() -> &'static str {
+    "cache.invalidate.*"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalidate() {
+        assert_eq!(
+            invalidate("address_label"),
+            "cache.invalidate.address_label"
+        );
+        assert_eq!(pattern_invalidate_all(), "cache.invalidate.*");
+    }
+}
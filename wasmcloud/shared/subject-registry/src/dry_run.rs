@@ -0,0 +1,96 @@
+//! Dry-Run Subject Patterns
+//!
+//! In dry run, processors run their full enrichment but divert everything
+//! that would persist data or raise an alert to a mirrored subject:
+//! ```text
+//! ducklake.transactions.ethereum.mainnet.write         # live
+//! dryrun.ducklake.transactions.ethereum.mainnet.write  # dry run
+//! ```
+//!
+//! Nothing consumes `dryrun.>` by default, so operators can watch the output of
+//! a new config or chain onboarding against live traffic without it reaching
+//! DuckLake or the alert pipeline. Pipeline-internal subjects (raw and
+//! processed transactions, decode requests) are not mirrored, so downstream
+//! processors still see the traffic and make the same dry-run decision.
+
+/// Root of every mirrored subject
+pub const DRY_RUN_ROOT: &str = "dryrun";
+
+/// Roots of the subjects diverted in dry run
+pub const DRY_RUN_SINKS: &[&str] = &["ducklake", "alerts"];
+
+/// True if the subject persists data or raises an alert
+pub fn is_dry_run_sink(subject: &str) -> bool {
+    let root = subject.split('.').next().unwrap_or_default();
+    DRY_RUN_SINKS.contains(&root)
+}
+
+/// Mirrored subject for a live subject
+///
+/// Example: `dryrun.alerts.evaluate.ethereum.mainnet`
+pub fn dry_run(subject: &str) -> String {
+    format!("{}.{}", DRY_RUN_ROOT, subject)
+}
+
+/// Live subject for a mirrored one
+pub fn live(subject: &str) -> Option<&str> {
+    subject.strip_prefix(DRY_RUN_ROOT)?.strip_prefix('.')
+}
+
+/// `{network}_{subnet}` of a sink subject that names its chain
+///
+/// Covers `ducklake.{table}.{network}.{subnet}.write`,
+/// `alerts.evaluate.{network}.{subnet}` and
+/// `alerts.system.{kind}.{network}.{subnet}.{...}`; other sinks return `None`.
+pub fn sink_chain_id(subject: &str) -> Option<String> {
+    let parts: Vec<&str> = subject.split('.').collect();
+    let (network, subnet) = match parts.as_slice() {
+        ["ducklake", _, network, subnet, "write"] => (network, subnet),
+        ["alerts", "evaluate", network, subnet] => (network, subnet),
+        ["alerts", "system", _, network, subnet, ..] => (network, subnet),
+        _ => return None,
+    };
+    Some(format!("{}_{}", network, subnet))
+}
+
+/// Subscription pattern for all mirrored subjects
+pub fn pattern_dry_run_all() -> &'static str {
+    "dryrun.>"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_round_trip() {
+        let subject = "ducklake.logs.ethereum.mainnet.write";
+        assert!(is_dry_run_sink(subject));
+        assert!(is_dry_run_sink("alerts.schedule.event_driven"));
+        assert!(!is_dry_run_sink("transactions.raw.evm"));
+        assert!(!is_dry_run_sink("ducklake-ish.logs"));
+
+        let mirrored = dry_run(subject);
+        assert_eq!(mirrored, "dryrun.ducklake.logs.ethereum.mainnet.write");
+        assert_eq!(live(&mirrored), Some(subject));
+        assert_eq!(live(subject), None);
+    }
+
+    #[test]
+    fn test_sink_chain_id() {
+        assert_eq!(
+            sink_chain_id("ducklake.transactions.base.mainnet.write").as_deref(),
+            Some("base_mainnet")
+        );
+        assert_eq!(
+            sink_chain_id("alerts.evaluate.ethereum.sepolia").as_deref(),
+            Some("ethereum_sepolia")
+        );
+        assert_eq!(
+            sink_chain_id("alerts.system.gas.eth.mainnet.priority_fee_spike").as_deref(),
+            Some("eth_mainnet")
+        );
+        assert_eq!(sink_chain_id("alerts.schedule.event_driven"), None);
+        assert_eq!(sink_chain_id("ducklake.transactions.write"), None);
+    }
+}
@@ -4,6 +4,7 @@
 //! ```text
 //! ducklake.{table}.write                    # Write operations to tables
 //! ducklake.{table}.query                    # Query operations
+//! ducklake.{table}.{network}.{subnet}.query # Per-chain queries (ducklake-read)
 //! ducklake.schema.list                      # Schema list requests
 //! ducklake.schema.get                       # Schema get requests
 //! ```
@@ -22,6 +23,18 @@ pub fn query(table: &str) -> String {
     format!("ducklake.{}.query", table)
 }
 
+/// Per-chain query subject answered by ducklake-read
+///
+/// Example: `ducklake.transactions.ethereum.mainnet.query`
+pub fn chain_query(table: &str, network: &str, subnet: &str) -> String {
+    format!(
+        "ducklake.{}.{}.{}.query",
+        table,
+        network.to_lowercase(),
+        subnet.to_lowercase()
+    )
+}
+
 /// Schema list request subject
 pub fn schema_list() -> &'static str {
     "ducklake.schema.list"
@@ -71,5 +84,9 @@ mod tests {
     #[test]
     fn test_query() {
         assert_eq!(query("transactions"), "ducklake.transactions.query");
+        assert_eq!(
+            chain_query("transactions", "Ethereum", "mainnet"),
+            "ducklake.transactions.ethereum.mainnet.query"
+        );
     }
 }
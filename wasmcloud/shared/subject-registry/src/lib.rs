@@ -12,20 +12,34 @@
 //! alerts.jobs.{action}.{param}                # Alert job processing
 //! notifications.send.{mode}.{channel}         # Notification delivery
 //! ducklake.{table}.{operation}                # Data lake operations
+//! cache.invalidate.{kind}                     # Registry change invalidations
+//! mempool.{network}.{subnet}.pending          # Pending transactions
 //! system.{component}                          # System health/status
+//! dryrun.{subject}                            # Dry-run mirror of a sink subject
 //! ```
+//!
+//! All of the above are canonical subjects; on the wire they carry the
+//! environment prefix from [`prefix`] (e.g. `staging.system.health`).
 
 pub mod alerts;
 pub mod blockchain;
+pub mod cache;
+pub mod dry_run;
 pub mod ducklake;
+pub mod mempool;
 pub mod notifications;
+pub mod prefix;
 pub mod system;
 
 // Re-export all modules at crate root for convenience
 pub use alerts::*;
 pub use blockchain::*;
+pub use cache::*;
+pub use dry_run::*;
 pub use ducklake::*;
+pub use mempool::*;
 pub use notifications::*;
+pub use prefix::*;
 pub use system::*;
 
 /// Constants for supported blockchain chains
@@ -33,6 +47,7 @@ pub mod chains {
     pub const ETHEREUM: &str = "ethereum";
     pub const BITCOIN: &str = "bitcoin";
     pub const SOLANA: &str = "solana";
+    pub const TRON: &str = "tron";
     pub const COSMOS: &str = "cosmos";
 }
 
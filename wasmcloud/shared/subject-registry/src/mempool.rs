@@ -0,0 +1,34 @@
+//! Mempool Subject Patterns
+//!
+//! Subject hierarchy for transactions seen before they are mined:
+//! ```text
+//! mempool.{network}.{subnet}.pending        # Full pending transaction bodies
+//! ```
+
+/// Pending transaction subject
+///
+/// Example: `mempool.ethereum.mainnet.pending`
+pub fn pending(network: &str, subnet: &str) -> String {
+    format!("mempool.{}.{}.pending", network, subnet)
+}
+
+// Subscription patterns
+
+/// Pattern for pending transactions from any network/subnet
+pub fn pattern_pending_all() -> &'static str {
+    "mempool.*.*.pending"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending() {
+        assert_eq!(
+            pending("ethereum", "mainnet"),
+            "mempool.ethereum.mainnet.pending"
+        );
+        assert_eq!(pattern_pending_all(), "mempool.*.*.pending");
+    }
+}
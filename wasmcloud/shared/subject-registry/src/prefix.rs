@@ -0,0 +1,281 @@
+//! Environment Subject Prefix
+//!
+//! Lets several environments share one NATS cluster. Every subject on the wire
+//! carries the environment prefix, while the rest of this registry (and every
+//! subject parser downstream) keeps working on canonical subjects:
+//! ```text
+//! staging.blockchain.ethereum.mainnet.transactions.raw     # on the wire
+//!         blockchain.ethereum.mainnet.transactions.raw     # canonical
+//! ```
+//!
+//! Use [`prefixed`] for subjects being published or subscribed to, and
+//! [`unprefixed`] when a message arrives; ACL checks and routing only see the
+//! canonical form. Providers install the prefix at startup from their
+//! `subject_prefix` property (or `EKKO_SUBJECT_PREFIX`). Actors have no process
+//! environment, so theirs is fixed by `EKKO_SUBJECT_PREFIX` at build time.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Environment variable holding the prefix (read at runtime by providers and
+/// at build time by actors)
+pub const SUBJECT_PREFIX_ENV: &str = "EKKO_SUBJECT_PREFIX";
+
+/// Provider link/config property holding the prefix
+pub const SUBJECT_PREFIX_PROPERTY: &str = "subject_prefix";
+
+/// First token of every canonical subject
+pub const ROOTS: &[&str] = &[
+    "abi",
+    "acl",
+    "admin",
+    "alerts",
+    "balances",
+    "blockchain",
+    "cache",
+    "contract-creations",
+    "contract-transactions",
+    "contracts",
+    "control",
+    "dryrun",
+    "ducklake",
+    "entities",
+    "events",
+    "gas",
+    "health",
+    "mempool",
+    "metrics",
+    "newheads",
+    "notifications",
+    "rpc",
+    "status",
+    "system",
+    "transactions",
+    "transfer-transactions",
+    "ws",
+];
+
+static ACTIVE: OnceLock<SubjectPrefix> = OnceLock::new();
+
+/// Normalized environment prefix: empty, or dot-terminated tokens (`staging.`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubjectPrefix(String);
+
+impl SubjectPrefix {
+    /// Parse a configured prefix; the trailing dot is optional
+    ///
+    /// Example: `staging` and `staging.` both give `staging.`
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let trimmed = raw.trim().trim_end_matches('.');
+        if trimmed.is_empty() {
+            return Ok(Self::default());
+        }
+        for token in trimmed.split('.') {
+            let valid = !token.is_empty()
+                && token
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(format!(
+                    "invalid subject prefix {:?}: tokens must be non-empty [A-Za-z0-9_-]",
+                    raw
+                ));
+            }
+        }
+        if is_canonical(trimmed) {
+            return Err(format!(
+                "invalid subject prefix {:?}: starts with canonical subject root",
+                raw
+            ));
+        }
+        Ok(Self(format!("{}.", trimmed)))
+    }
+
+    /// Prefix baked in from `EKKO_SUBJECT_PREFIX` when the crate was built
+    pub fn build_time() -> Result<Self, String> {
+        Self::parse(option_env!("EKKO_SUBJECT_PREFIX").unwrap_or(""))
+    }
+
+    /// Prefix from `EKKO_SUBJECT_PREFIX`, falling back to the build-time value
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var(SUBJECT_PREFIX_ENV) {
+            Ok(raw) => Self::parse(&raw),
+            Err(_) => Self::build_time(),
+        }
+    }
+
+    /// Prefix from provider properties, falling back to [`SubjectPrefix::from_env`]
+    pub fn from_properties(props: &HashMap<String, String>) -> Result<Self, String> {
+        match props
+            .get(SUBJECT_PREFIX_PROPERTY)
+            .or_else(|| props.get(SUBJECT_PREFIX_ENV))
+        {
+            Some(raw) => Self::parse(raw),
+            None => Self::from_env(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Wire subject for a canonical subject; already-prefixed subjects are
+    /// returned unchanged
+    pub fn apply(&self, subject: &str) -> String {
+        if subject.starts_with(&self.0) {
+            subject.to_string()
+        } else {
+            format!("{}{}", self.0, subject)
+        }
+    }
+
+    /// Canonical subject for a wire subject, or `None` when the subject
+    /// belongs to another environment
+    pub fn strip<'a>(&self, subject: &'a str) -> Option<&'a str> {
+        subject
+            .strip_prefix(self.0.as_str())
+            .filter(|rest| is_canonical(rest))
+    }
+
+    /// JetStream stream name scoped to this environment (stream names are
+    /// account-wide, so two environments cannot share one)
+    ///
+    /// Example: `ALERT_SCHEDULE_REQUESTS` under `eu.staging.` gives
+    /// `EU_STAGING_ALERT_SCHEDULE_REQUESTS`
+    pub fn stream_name(&self, name: &str) -> String {
+        let scope = self.0.replace(['.', '-'], "_").to_ascii_uppercase();
+        if name.starts_with(&scope) {
+            name.to_string()
+        } else {
+            format!("{}{}", scope, name)
+        }
+    }
+
+    /// Startup check that every subscribe/publish subject belongs to this
+    /// environment
+    pub fn check_subjects<S: AsRef<str>>(&self, subjects: &[S]) -> Result<(), String> {
+        for subject in subjects {
+            let subject = subject.as_ref();
+            if self.strip(subject).is_none() {
+                return Err(format!(
+                    "subject {} does not match environment prefix {}",
+                    subject, self
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SubjectPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            write!(f, "(none)")
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+/// True if the subject (or pattern) starts with a canonical root
+pub fn is_canonical(subject: &str) -> bool {
+    let root = subject.split('.').next().unwrap_or_default();
+    ROOTS.contains(&root)
+}
+
+/// Install the process-wide prefix; call once at provider startup
+///
+/// Re-installing the same prefix is a no-op; a different one is an error.
+pub fn install_prefix(prefix: SubjectPrefix) -> Result<&'static SubjectPrefix, String> {
+    let active = ACTIVE.get_or_init(|| prefix.clone());
+    if *active != prefix {
+        return Err(format!(
+            "subject prefix {} requested but {} is already active",
+            prefix, active
+        ));
+    }
+    Ok(active)
+}
+
+/// Process-wide prefix; the build-time prefix unless one was installed
+///
+/// Panics if the build-time `EKKO_SUBJECT_PREFIX` is invalid.
+pub fn active_prefix() -> &'static SubjectPrefix {
+    ACTIVE.get_or_init(|| SubjectPrefix::build_time().unwrap_or_else(|e| panic!("{}", e)))
+}
+
+/// Wire subject under the active prefix
+///
+/// Example: `staging.ducklake.logs.ethereum.mainnet.write`
+pub fn prefixed(subject: &str) -> String {
+    active_prefix().apply(subject)
+}
+
+/// Canonical subject under the active prefix, `None` for other environments
+pub fn unprefixed(subject: &str) -> Option<&str> {
+    active_prefix().strip(subject)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(SubjectPrefix::parse("").unwrap().as_str(), "");
+        assert_eq!(
+            SubjectPrefix::parse("staging").unwrap().as_str(),
+            "staging."
+        );
+        assert_eq!(
+            SubjectPrefix::parse(" eu.staging. ").unwrap().as_str(),
+            "eu.staging."
+        );
+        assert!(SubjectPrefix::parse("stag*ing").is_err());
+        assert!(SubjectPrefix::parse("a..b").is_err());
+        assert!(SubjectPrefix::parse("ducklake").is_err());
+    }
+
+    #[test]
+    fn test_apply_and_strip() {
+        let staging = SubjectPrefix::parse("staging").unwrap();
+        let wire = staging.apply("ducklake.logs.ethereum.mainnet.write");
+        assert_eq!(wire, "staging.ducklake.logs.ethereum.mainnet.write");
+        assert_eq!(staging.apply(&wire), wire);
+        assert_eq!(
+            staging.strip(&wire),
+            Some("ducklake.logs.ethereum.mainnet.write")
+        );
+        assert_eq!(staging.strip("ducklake.logs.ethereum.mainnet.write"), None);
+        assert_eq!(staging.strip("staging.prod.ducklake.logs.write"), None);
+
+        let prod = SubjectPrefix::default();
+        assert_eq!(prod.apply("system.health"), "system.health");
+        assert_eq!(prod.strip("system.health"), Some("system.health"));
+        assert_eq!(prod.strip(&wire), None);
+
+        let eu = SubjectPrefix::parse("eu.staging-2").unwrap();
+        let stream = eu.stream_name("ALERT_SCHEDULE_REQUESTS");
+        assert_eq!(stream, "EU_STAGING_2_ALERT_SCHEDULE_REQUESTS");
+        assert_eq!(eu.stream_name(&stream), stream);
+        assert_eq!(prod.stream_name("ALERT_JOBS"), "ALERT_JOBS");
+    }
+
+    #[test]
+    fn test_check_subjects() {
+        let staging = SubjectPrefix::parse("staging").unwrap();
+        assert!(staging
+            .check_subjects(&["staging.ducklake.*.write", "staging.system.health"])
+            .is_ok());
+        let err = staging
+            .check_subjects(&["staging.ducklake.*.write", "ducklake.*.query"])
+            .unwrap_err();
+        assert!(err.contains("ducklake.*.query"));
+        assert!(SubjectPrefix::default().check_subjects(&[">"]).is_err());
+    }
+}
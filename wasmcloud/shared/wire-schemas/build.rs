@@ -0,0 +1,97 @@
+//! Generates a JSON Schema per public message type and a registry that
+//! `src/lib.rs` embeds. Schemas come from the same structs the actors
+//! serialize, so they cannot drift from the wire format.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use alert_runtime_common::{
+    AlertAckV1, AlertChainHealthV1, AlertGasFeeV1, AlertSlashingV1, AlertTriggeredBatchV1,
+    AlertUpgradeRiskV1, ALERT_ACK_SUBJECT, ALERT_UPGRADE_RISK_SUBJECT,
+};
+use eth_contract_transaction_processor::{
+    DuckLakeContractCallRecord, ProcessedContractTransaction,
+};
+use eth_transfers_processor::balance_delta::{BalanceDeltaV1, BalanceSnapshotV1};
+use eth_transfers_processor::ProcessedTransfer;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+fn main() {
+    let schemas: Vec<(&str, &str, RootSchema)> = vec![
+        (
+            "processed_transfer",
+            "transfers.processed.evm",
+            schema_for!(ProcessedTransfer),
+        ),
+        (
+            "processed_contract_transaction",
+            "contract-calls.processed.evm",
+            schema_for!(ProcessedContractTransaction),
+        ),
+        (
+            "contract_call_record",
+            "ducklake.contract_calls.{network}.{subnet}.write",
+            schema_for!(DuckLakeContractCallRecord),
+        ),
+        (
+            "alert_triggered_batch_v1",
+            "alerts.triggered.{user_id}",
+            schema_for!(AlertTriggeredBatchV1),
+        ),
+        (
+            "alert_upgrade_risk_v1",
+            ALERT_UPGRADE_RISK_SUBJECT,
+            schema_for!(AlertUpgradeRiskV1),
+        ),
+        ("alert_ack_v1", ALERT_ACK_SUBJECT, schema_for!(AlertAckV1)),
+        (
+            "alert_gas_fee_v1",
+            "alerts.system.gas.{network}.{subnet}.{rule}",
+            schema_for!(AlertGasFeeV1),
+        ),
+        (
+            "alert_chain_health_v1",
+            "alerts.system.chain.{network}.{subnet}.{rule}",
+            schema_for!(AlertChainHealthV1),
+        ),
+        (
+            "alert_slashing_v1",
+            "alerts.system.slashing.{network}.{subnet}.{kind}",
+            schema_for!(AlertSlashingV1),
+        ),
+        (
+            "balance_delta_v1",
+            "balances.delta.{network}.{subnet}",
+            schema_for!(BalanceDeltaV1),
+        ),
+        (
+            "balance_snapshot_v1",
+            "balances.delta.{network}.{subnet}",
+            schema_for!(BalanceSnapshotV1),
+        ),
+    ];
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let mut registry = String::from("&[\n");
+    for (name, subject, schema) in &schemas {
+        let json = serde_json::to_string_pretty(schema).expect("schema serializes");
+        let path = Path::new(&out_dir).join(format!("{}.json", name));
+        std::fs::write(&path, json).expect("write schema");
+        writeln!(
+            registry,
+            "    WireSchema {{ name: {:?}, subject: {:?}, schema: include_str!({:?}) }},",
+            name,
+            subject,
+            path.display().to_string()
+        )
+        .unwrap();
+    }
+    registry.push_str("]\n");
+    std::fs::write(Path::new(&out_dir).join("registry.rs"), registry).expect("write registry");
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../alert-runtime-common/src");
+    println!("cargo:rerun-if-changed=../../actors/eth_transfers_processor/src/lib.rs");
+    println!("cargo:rerun-if-changed=../../actors/eth_contract_transaction_processor/src/lib.rs");
+}
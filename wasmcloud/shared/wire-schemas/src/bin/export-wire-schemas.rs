@@ -0,0 +1,21 @@
+//! Write every registered wire schema to `<dir>/<name>.schema.json`
+//!
+//! Usage: `cargo run -p wire-schemas --bin export-wire-schemas -- <dir>`
+
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "wire-schemas".to_string()),
+    );
+    std::fs::create_dir_all(&dir)?;
+
+    for schema in wire_schemas::REGISTRY {
+        let path = dir.join(format!("{}.schema.json", schema.name));
+        std::fs::write(&path, schema.schema)?;
+        println!("{} ({}) -> {}", schema.name, schema.subject, path.display());
+    }
+    Ok(())
+}
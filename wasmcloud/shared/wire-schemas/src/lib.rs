@@ -0,0 +1,138 @@
+//! JSON Schemas for the public NATS message types.
+//!
+//! `build.rs` derives one JSON Schema (draft-07) per message type from the
+//! Rust structs the actors serialize (`ProcessedTransfer`,
+//! `ProcessedContractTransaction`, alert events, ...) and this crate embeds
+//! them in [`REGISTRY`]. Python and TypeScript consumers can fetch them over
+//! NATS on [`ADMIN_SCHEMAS_GET_SUBJECT`] (answered by ducklake-read) or dump
+//! them to disk with `cargo run -p wire-schemas --bin export-wire-schemas`
+//! for validation and codegen.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request/reply subject serving [`REGISTRY`]
+pub const ADMIN_SCHEMAS_GET_SUBJECT: &str = "admin.schemas.get";
+
+/// Generated schema for one message type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireSchema {
+    pub name: &'static str,
+    /// Subject (or subject pattern) the message is published on
+    pub subject: &'static str,
+    /// JSON Schema document
+    pub schema: &'static str,
+}
+
+impl WireSchema {
+    pub fn to_entry(&self) -> WireSchemaEntry {
+        WireSchemaEntry {
+            name: self.name.to_string(),
+            subject: self.subject.to_string(),
+            schema: serde_json::from_str(self.schema).unwrap_or(Value::Null),
+        }
+    }
+}
+
+pub const REGISTRY: &[WireSchema] = include!(concat!(env!("OUT_DIR"), "/registry.rs"));
+
+pub fn get(name: &str) -> Option<&'static WireSchema> {
+    REGISTRY.iter().find(|schema| schema.name == name)
+}
+
+/// `admin.schemas.get` request; omit `name` to list every schema
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WireSchemaGetRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireSchemaEntry {
+    pub name: String,
+    pub subject: String,
+    pub schema: Value,
+}
+
+/// `admin.schemas.get` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WireSchemaGetResponse {
+    pub success: bool,
+    pub schemas: Vec<WireSchemaEntry>,
+    pub error: Option<String>,
+}
+
+impl WireSchemaGetResponse {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            schemas: Vec::new(),
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Answer an `admin.schemas.get` request
+pub fn handle_get(request: &WireSchemaGetRequest) -> WireSchemaGetResponse {
+    let schemas = match &request.name {
+        Some(name) => match get(name) {
+            Some(schema) => vec![schema.to_entry()],
+            None => return WireSchemaGetResponse::error(format!("Unknown schema: {}", name)),
+        },
+        None => REGISTRY.iter().map(WireSchema::to_entry).collect(),
+    };
+    WireSchemaGetResponse {
+        success: true,
+        schemas,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_schemas_are_valid_json_objects() {
+        assert!(!REGISTRY.is_empty());
+        for schema in REGISTRY {
+            let entry = schema.to_entry();
+            assert!(
+                entry.schema.is_object(),
+                "{} is not a JSON object",
+                schema.name
+            );
+            assert!(entry.schema["$schema"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_processed_transfer_schema_matches_struct() {
+        let schema = get("processed_transfer").unwrap().to_entry().schema;
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&Value::from("transaction_hash")));
+        assert!(required.contains(&Value::from("transfer_category")));
+        // Optional fields are not required
+        assert!(!required.contains(&Value::from("sweep_id")));
+        assert!(schema["properties"]["block_number"].is_object());
+    }
+
+    #[test]
+    fn test_handle_get() {
+        let all = handle_get(&WireSchemaGetRequest::default());
+        assert!(all.success);
+        assert_eq!(all.schemas.len(), REGISTRY.len());
+
+        let one = handle_get(&WireSchemaGetRequest {
+            name: Some("alert_upgrade_risk_v1".to_string()),
+        });
+        assert_eq!(one.schemas.len(), 1);
+        assert_eq!(one.schemas[0].subject, "alerts.upgrade_risk");
+
+        let missing = handle_get(&WireSchemaGetRequest {
+            name: Some("nope".to_string()),
+        });
+        assert!(!missing.success);
+        assert!(missing.error.is_some());
+    }
+}